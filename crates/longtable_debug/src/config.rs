@@ -1,21 +1,44 @@
 //! Configuration for the observability system.
+//!
+//! [`ObservabilityConfig`] is the single switchboard for the debug
+//! subsystems: the `apply_to_*` methods push its settings into the tracer,
+//! timeline, and provenance tracker.
 
-use longtable_engine::provenance::ProvenanceVerbosity;
+use longtable_engine::provenance::{ProvenanceTracker, ProvenanceVerbosity};
+
+use crate::timeline::{Timeline, TimelineConfig};
+use crate::trace::{TraceOutput, Tracer};
 
 /// Configuration for the observability system.
 ///
-/// Controls tracing, debugging, and history retention.
-#[derive(Clone, Debug)]
+/// Controls tracing, debugging, profiling, and history retention.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct ObservabilityConfig {
     /// Whether observability is enabled (false = zero overhead).
     pub enabled: bool,
 
+    /// Whether provenance is recorded at all.
+    pub provenance: bool,
+
     /// Current verbosity level for provenance tracking.
     pub verbosity: ProvenanceVerbosity,
 
-    /// History ring buffer size (number of ticks to retain).
+    /// History ring buffer size (number of ticks to retain, 0 = no history).
     pub history_size: usize,
 
+    /// Capture a history snapshot every this many ticks.
+    pub keyframe_interval: u64,
+
+    /// Maximum records kept in the trace buffer.
+    pub trace_buffer_size: usize,
+
+    /// Maximum provenance history entries per (entity, component).
+    pub provenance_history: usize,
+
+    /// Whether to time ticks and report their duration.
+    pub profiling: bool,
+
     /// Default depth for why-queries.
     pub why_depth: usize,
 
@@ -30,8 +53,13 @@ impl Default for ObservabilityConfig {
     fn default() -> Self {
         Self {
             enabled: false,
+            provenance: true,
             verbosity: ProvenanceVerbosity::Minimal,
             history_size: 100,
+            keyframe_interval: 1,
+            trace_buffer_size: 10_000,
+            provenance_history: 100,
+            profiling: false,
             why_depth: 1,
             trace_to_stderr: true,
             json_output: false,
@@ -55,10 +83,8 @@ impl ObservabilityConfig {
         Self {
             enabled: true,
             verbosity: ProvenanceVerbosity::Standard,
-            history_size: 100,
             why_depth: 3,
-            trace_to_stderr: true,
-            json_output: false,
+            ..Self::default()
         }
     }
 
@@ -69,12 +95,40 @@ impl ObservabilityConfig {
             enabled: true,
             verbosity: ProvenanceVerbosity::Full,
             history_size: 200,
+            provenance_history: 1000,
+            profiling: true,
             why_depth: 10,
-            trace_to_stderr: true,
-            json_output: false,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a configuration for shipping games: no tracing, no
+    /// provenance, no history, and no profiling.
+    #[must_use]
+    pub fn play() -> Self {
+        Self {
+            enabled: false,
+            provenance: false,
+            history_size: 0,
+            trace_buffer_size: 0,
+            provenance_history: 0,
+            profiling: false,
+            ..Self::default()
         }
     }
 
+    /// Returns true if this configuration keeps everything [`Self::play`]
+    /// turns off switched off, whatever its display settings.
+    #[must_use]
+    pub fn is_play(&self) -> bool {
+        !self.enabled
+            && !self.provenance
+            && !self.profiling
+            && self.history_size == 0
+            && self.trace_buffer_size == 0
+            && self.provenance_history == 0
+    }
+
     /// Builder method to set enabled state.
     #[must_use]
     pub fn with_enabled(mut self, enabled: bool) -> Self {
@@ -82,6 +136,13 @@ impl ObservabilityConfig {
         self
     }
 
+    /// Builder method to enable/disable provenance recording.
+    #[must_use]
+    pub fn with_provenance(mut self, provenance: bool) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
     /// Builder method to set verbosity level.
     #[must_use]
     pub fn with_verbosity(mut self, verbosity: ProvenanceVerbosity) -> Self {
@@ -96,6 +157,34 @@ impl ObservabilityConfig {
         self
     }
 
+    /// Builder method to set the history keyframe interval.
+    #[must_use]
+    pub fn with_keyframe_interval(mut self, interval: u64) -> Self {
+        self.keyframe_interval = interval.max(1);
+        self
+    }
+
+    /// Builder method to set the trace buffer size.
+    #[must_use]
+    pub fn with_trace_buffer_size(mut self, size: usize) -> Self {
+        self.trace_buffer_size = size;
+        self
+    }
+
+    /// Builder method to set provenance history retention.
+    #[must_use]
+    pub fn with_provenance_history(mut self, max: usize) -> Self {
+        self.provenance_history = max;
+        self
+    }
+
+    /// Builder method to enable/disable the tick profiler.
+    #[must_use]
+    pub fn with_profiling(mut self, profiling: bool) -> Self {
+        self.profiling = profiling;
+        self
+    }
+
     /// Builder method to set default why depth.
     #[must_use]
     pub fn with_why_depth(mut self, depth: usize) -> Self {
@@ -116,6 +205,57 @@ impl ObservabilityConfig {
         self.json_output = json;
         self
     }
+
+    /// Returns the timeline configuration implied by these settings.
+    #[must_use]
+    pub fn timeline_config(&self) -> TimelineConfig {
+        TimelineConfig::new()
+            .with_history_size(self.history_size)
+            .with_keyframe_interval(self.keyframe_interval)
+    }
+
+    /// Applies the trace settings to a tracer.
+    ///
+    /// Tracing is on when observability is enabled; output goes to stderr
+    /// only if `trace_to_stderr` is set.
+    pub fn apply_to_tracer(&self, tracer: &mut Tracer) {
+        tracer.set_buffer_size(self.trace_buffer_size);
+        tracer.set_json_format(self.json_output);
+        if self.enabled {
+            tracer.enable();
+        } else {
+            tracer.disable();
+        }
+        tracer.set_output(if self.enabled && self.trace_to_stderr {
+            TraceOutput::Stderr
+        } else {
+            TraceOutput::None
+        });
+    }
+
+    /// Applies the history settings to a timeline, keeping its other options.
+    ///
+    /// A history size of zero disables the timeline so nothing is captured.
+    pub fn apply_to_timeline(&self, timeline: &mut Timeline) {
+        if self.history_size == 0 {
+            timeline.disable();
+        } else {
+            timeline.enable();
+        }
+        let config = timeline
+            .config()
+            .clone()
+            .with_history_size(self.history_size)
+            .with_keyframe_interval(self.keyframe_interval);
+        timeline.set_config(config);
+    }
+
+    /// Applies the verbosity and retention settings to a provenance tracker.
+    pub fn apply_to_provenance(&self, provenance: &mut ProvenanceTracker) {
+        provenance.set_enabled(self.provenance);
+        provenance.set_verbosity(self.verbosity);
+        provenance.set_max_history(self.provenance_history);
+    }
 }
 
 #[cfg(test)]
@@ -137,6 +277,56 @@ mod tests {
         assert_eq!(config.verbosity, ProvenanceVerbosity::Standard);
     }
 
+    #[test]
+    fn applies_to_subsystems() {
+        let config = ObservabilityConfig::enabled()
+            .with_verbosity(ProvenanceVerbosity::Standard)
+            .with_history_size(7)
+            .with_keyframe_interval(5)
+            .with_trace_buffer_size(50)
+            .with_trace_to_stderr(false);
+
+        let mut tracer = Tracer::disabled();
+        config.apply_to_tracer(&mut tracer);
+        assert!(tracer.is_enabled());
+        assert_eq!(tracer.stats().max_size, 50);
+
+        let mut timeline = Timeline::new();
+        config.apply_to_timeline(&mut timeline);
+        assert_eq!(timeline.config().history_size, 7);
+        assert_eq!(timeline.config().keyframe_interval, 5);
+        assert_eq!(timeline.current_branch().history().capacity(), 7);
+
+        let mut provenance = ProvenanceTracker::new();
+        config.apply_to_provenance(&mut provenance);
+        assert_eq!(provenance.verbosity(), ProvenanceVerbosity::Standard);
+    }
+
+    #[test]
+    fn play_config_turns_everything_off() {
+        let config = ObservabilityConfig::play();
+        assert!(config.is_play());
+        assert!(config.clone().with_json_output(true).is_play());
+        assert!(!config.clone().with_enabled(true).is_play());
+        assert!(!ObservabilityConfig::default().is_play());
+
+        let mut tracer = Tracer::disabled();
+        config.apply_to_tracer(&mut tracer);
+        assert!(!tracer.is_enabled());
+
+        let mut timeline = Timeline::new();
+        config.apply_to_timeline(&mut timeline);
+        assert!(!timeline.is_enabled());
+
+        let mut provenance = ProvenanceTracker::new();
+        config.apply_to_provenance(&mut provenance);
+        assert!(!provenance.is_enabled());
+
+        // Switching back re-enables recording
+        ObservabilityConfig::default().apply_to_provenance(&mut provenance);
+        assert!(provenance.is_enabled());
+    }
+
     #[test]
     fn builder_pattern() {
         let config = ObservabilityConfig::default()
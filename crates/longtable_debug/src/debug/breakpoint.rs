@@ -0,0 +1,424 @@
+//! Breakpoints for the debugger.
+//!
+//! Breakpoints can be set on rule activation, on a specific tick, or on
+//! component reads and writes (optionally scoped to a single entity).
+
+use longtable_foundation::{EntityId, KeywordId};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// =============================================================================
+// Breakpoint ID
+// =============================================================================
+
+/// Unique identifier for a breakpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BreakpointId(u64);
+
+impl BreakpointId {
+    /// Creates a new breakpoint ID.
+    #[must_use]
+    pub const fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    /// Returns the raw ID value.
+    #[must_use]
+    pub const fn raw(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for BreakpointId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+// =============================================================================
+// Breakpoint Kind
+// =============================================================================
+
+/// The kind of component access a breakpoint triggers on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessType {
+    /// Component is read.
+    Read,
+    /// Component is written.
+    Write,
+}
+
+impl fmt::Display for AccessType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Write => write!(f, "write"),
+        }
+    }
+}
+
+/// What a breakpoint triggers on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BreakpointKind {
+    /// Break when a rule is about to fire.
+    Rule(KeywordId),
+    /// Break at the start of a specific tick.
+    Tick(u64),
+    /// Break when a component is accessed.
+    Component {
+        /// Entity to watch (None = any entity).
+        entity: Option<EntityId>,
+        /// Component to watch.
+        component: KeywordId,
+        /// Read or write access.
+        access: AccessType,
+    },
+}
+
+// =============================================================================
+// Breakpoint
+// =============================================================================
+
+/// A single breakpoint.
+#[derive(Clone, Debug)]
+pub struct Breakpoint {
+    /// Unique identifier.
+    id: BreakpointId,
+    /// What this breakpoint triggers on.
+    kind: BreakpointKind,
+    /// Whether this breakpoint is active.
+    enabled: bool,
+    /// Number of times this breakpoint has been hit.
+    hit_count: u64,
+}
+
+impl Breakpoint {
+    /// Creates a new enabled breakpoint.
+    #[must_use]
+    pub const fn new(id: BreakpointId, kind: BreakpointKind) -> Self {
+        Self {
+            id,
+            kind,
+            enabled: true,
+            hit_count: 0,
+        }
+    }
+
+    /// Returns the breakpoint ID.
+    #[must_use]
+    pub const fn id(&self) -> BreakpointId {
+        self.id
+    }
+
+    /// Returns what this breakpoint triggers on.
+    #[must_use]
+    pub const fn kind(&self) -> &BreakpointKind {
+        &self.kind
+    }
+
+    /// Returns whether this breakpoint is enabled.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables this breakpoint.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns how many times this breakpoint has been hit.
+    #[must_use]
+    pub const fn hit_count(&self) -> u64 {
+        self.hit_count
+    }
+
+    /// Records a hit on this breakpoint.
+    pub fn record_hit(&mut self) {
+        self.hit_count += 1;
+    }
+
+    /// Returns a human-readable description of this breakpoint.
+    #[must_use]
+    pub fn description(&self) -> String {
+        match &self.kind {
+            BreakpointKind::Rule(rule) => format!("rule {rule:?}"),
+            BreakpointKind::Tick(tick) => format!("tick {tick}"),
+            BreakpointKind::Component {
+                entity,
+                component,
+                access,
+            } => match entity {
+                Some(e) => format!("{access} {component:?} on {e}"),
+                None => format!("{access} {component:?}"),
+            },
+        }
+    }
+
+    /// Returns true if this breakpoint matches a rule firing.
+    #[must_use]
+    pub fn matches_rule(&self, rule: KeywordId) -> bool {
+        matches!(self.kind, BreakpointKind::Rule(r) if r == rule)
+    }
+
+    /// Returns true if this breakpoint matches a tick.
+    #[must_use]
+    pub fn matches_tick(&self, tick: u64) -> bool {
+        matches!(self.kind, BreakpointKind::Tick(t) if t == tick)
+    }
+
+    /// Returns true if this breakpoint matches a component access.
+    #[must_use]
+    pub fn matches_component(
+        &self,
+        entity: EntityId,
+        component: KeywordId,
+        access: AccessType,
+    ) -> bool {
+        match &self.kind {
+            BreakpointKind::Component {
+                entity: bp_entity,
+                component: bp_component,
+                access: bp_access,
+            } => {
+                *bp_component == component
+                    && *bp_access == access
+                    && bp_entity.is_none_or(|e| e == entity)
+            }
+            _ => false,
+        }
+    }
+}
+
+// =============================================================================
+// Breakpoint Registry
+// =============================================================================
+
+/// Registry of all breakpoints in a debug session.
+#[derive(Clone, Debug)]
+pub struct BreakpointRegistry {
+    /// All breakpoints by ID (ordered for stable listing).
+    breakpoints: BTreeMap<BreakpointId, Breakpoint>,
+    /// Next breakpoint ID to assign.
+    next_id: u64,
+}
+
+impl BreakpointRegistry {
+    /// Creates an empty breakpoint registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            breakpoints: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Adds a breakpoint of the given kind and returns its ID.
+    pub fn add(&mut self, kind: BreakpointKind) -> BreakpointId {
+        let id = BreakpointId::new(self.next_id);
+        self.next_id += 1;
+        self.breakpoints.insert(id, Breakpoint::new(id, kind));
+        id
+    }
+
+    /// Adds a breakpoint on a rule.
+    pub fn add_rule(&mut self, rule: KeywordId) -> BreakpointId {
+        self.add(BreakpointKind::Rule(rule))
+    }
+
+    /// Adds a breakpoint on a tick.
+    pub fn add_tick(&mut self, tick: u64) -> BreakpointId {
+        self.add(BreakpointKind::Tick(tick))
+    }
+
+    /// Adds a breakpoint on component writes.
+    pub fn add_component_write(
+        &mut self,
+        entity: Option<EntityId>,
+        component: KeywordId,
+    ) -> BreakpointId {
+        self.add(BreakpointKind::Component {
+            entity,
+            component,
+            access: AccessType::Write,
+        })
+    }
+
+    /// Adds a breakpoint on component reads.
+    pub fn add_component_read(
+        &mut self,
+        entity: Option<EntityId>,
+        component: KeywordId,
+    ) -> BreakpointId {
+        self.add(BreakpointKind::Component {
+            entity,
+            component,
+            access: AccessType::Read,
+        })
+    }
+
+    /// Removes a breakpoint, returning it if it existed.
+    pub fn remove(&mut self, id: BreakpointId) -> Option<Breakpoint> {
+        self.breakpoints.remove(&id)
+    }
+
+    /// Gets a breakpoint by ID.
+    #[must_use]
+    pub fn get(&self, id: BreakpointId) -> Option<&Breakpoint> {
+        self.breakpoints.get(&id)
+    }
+
+    /// Gets a mutable reference to a breakpoint by ID.
+    pub fn get_mut(&mut self, id: BreakpointId) -> Option<&mut Breakpoint> {
+        self.breakpoints.get_mut(&id)
+    }
+
+    /// Enables or disables a breakpoint. Returns false if it doesn't exist.
+    pub fn set_enabled(&mut self, id: BreakpointId, enabled: bool) -> bool {
+        if let Some(bp) = self.breakpoints.get_mut(&id) {
+            bp.set_enabled(enabled);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes all breakpoints.
+    pub fn clear(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Returns the number of breakpoints.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.breakpoints.len()
+    }
+
+    /// Returns true if there are no breakpoints.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.breakpoints.is_empty()
+    }
+
+    /// Iterates over all breakpoints in ID order.
+    pub fn iter(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.breakpoints.values()
+    }
+
+    /// Returns enabled breakpoints matching a rule.
+    #[must_use]
+    pub fn get_rule_breakpoints(&self, rule: KeywordId) -> Vec<&Breakpoint> {
+        self.breakpoints
+            .values()
+            .filter(|bp| bp.is_enabled() && bp.matches_rule(rule))
+            .collect()
+    }
+
+    /// Returns enabled breakpoints matching a tick.
+    #[must_use]
+    pub fn get_tick_breakpoints(&self, tick: u64) -> Vec<&Breakpoint> {
+        self.breakpoints
+            .values()
+            .filter(|bp| bp.is_enabled() && bp.matches_tick(tick))
+            .collect()
+    }
+
+    /// Returns enabled breakpoints matching a component write.
+    #[must_use]
+    pub fn get_component_write_breakpoints(
+        &self,
+        entity: EntityId,
+        component: KeywordId,
+    ) -> Vec<&Breakpoint> {
+        self.component_breakpoints(entity, component, AccessType::Write)
+    }
+
+    /// Returns enabled breakpoints matching a component read.
+    #[must_use]
+    pub fn get_component_read_breakpoints(
+        &self,
+        entity: EntityId,
+        component: KeywordId,
+    ) -> Vec<&Breakpoint> {
+        self.component_breakpoints(entity, component, AccessType::Read)
+    }
+
+    fn component_breakpoints(
+        &self,
+        entity: EntityId,
+        component: KeywordId,
+        access: AccessType,
+    ) -> Vec<&Breakpoint> {
+        self.breakpoints
+            .values()
+            .filter(|bp| bp.is_enabled() && bp.matches_component(entity, component, access))
+            .collect()
+    }
+}
+
+impl Default for BreakpointRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::Interner;
+
+    #[test]
+    fn breakpoint_id_display() {
+        assert_eq!(format!("{}", BreakpointId::new(3)), "#3");
+    }
+
+    #[test]
+    fn add_and_remove() {
+        let mut interner = Interner::new();
+        let rule = interner.intern_keyword("rule");
+        let mut registry = BreakpointRegistry::new();
+
+        let id = registry.add_rule(rule);
+        assert_eq!(id.raw(), 1);
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.get_rule_breakpoints(rule).len(), 1);
+
+        assert!(registry.remove(id).is_some());
+        assert!(registry.is_empty());
+        assert!(registry.remove(id).is_none());
+    }
+
+    #[test]
+    fn disabled_breakpoints_do_not_match() {
+        let mut interner = Interner::new();
+        let rule = interner.intern_keyword("rule");
+        let mut registry = BreakpointRegistry::new();
+
+        let id = registry.add_rule(rule);
+        assert!(registry.set_enabled(id, false));
+        assert!(registry.get_rule_breakpoints(rule).is_empty());
+    }
+
+    #[test]
+    fn component_breakpoints_scope_by_entity() {
+        let mut interner = Interner::new();
+        let health = interner.intern_keyword("health");
+        let e1 = EntityId::new(1, 0);
+        let e2 = EntityId::new(2, 0);
+        let mut registry = BreakpointRegistry::new();
+
+        registry.add_component_write(Some(e1), health);
+        registry.add_component_read(None, health);
+
+        assert_eq!(
+            registry.get_component_write_breakpoints(e1, health).len(),
+            1
+        );
+        assert!(
+            registry
+                .get_component_write_breakpoints(e2, health)
+                .is_empty()
+        );
+        assert_eq!(registry.get_component_read_breakpoints(e2, health).len(), 1);
+    }
+}
@@ -0,0 +1,439 @@
+//! Interactive debugging for Longtable.
+//!
+//! Provides breakpoints, watch expressions, and stepping through
+//! tick execution one rule, phase, or tick at a time.
+//!
+//! # Example
+//!
+//! ```text
+//! (break :rule :apply-damage)          ;; Break when a rule fires
+//! (break :write :health)               ;; Break on any write to :health
+//! (watch (get ?player :health))        ;; Watch an expression
+//! (step-rule)                          ;; Step to the next rule
+//! (continue)                           ;; Resume execution
+//! ```
+
+pub mod breakpoint;
+pub mod watch;
+
+pub use breakpoint::{AccessType, Breakpoint, BreakpointId, BreakpointKind, BreakpointRegistry};
+pub use watch::{WatchExpression, WatchId, WatchRegistry};
+
+use longtable_engine::DebugPoint;
+use longtable_foundation::{EntityId, Interner, KeywordId};
+use std::fmt::Write as _;
+
+// =============================================================================
+// Debug State
+// =============================================================================
+
+/// Why execution is paused.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PauseReason {
+    /// The user explicitly asked to pause.
+    UserRequest,
+    /// A breakpoint was hit.
+    Breakpoint(BreakpointId),
+    /// A step (rule, phase, or tick) completed.
+    StepComplete,
+    /// A watched expression changed value since the previous tick.
+    WatchChanged(WatchId),
+}
+
+impl std::fmt::Display for PauseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UserRequest => write!(f, "user request"),
+            Self::Breakpoint(id) => write!(f, "breakpoint {id}"),
+            Self::StepComplete => write!(f, "step complete"),
+            Self::WatchChanged(id) => write!(f, "watch {id} changed"),
+        }
+    }
+}
+
+/// Execution state of a debug session.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub enum DebugState {
+    /// Running normally; only breakpoints will pause.
+    #[default]
+    Running,
+    /// Paused for the given reason.
+    Paused(PauseReason),
+    /// Running until the next rule fires.
+    StepRule,
+    /// Running until the next phase begins.
+    StepPhase,
+    /// Running until the next tick begins.
+    StepTick,
+}
+
+impl DebugState {
+    /// Returns a short name for this state.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Paused(_) => "paused",
+            Self::StepRule => "step-rule",
+            Self::StepPhase => "step-phase",
+            Self::StepTick => "step-tick",
+        }
+    }
+}
+
+// =============================================================================
+// Debug Session
+// =============================================================================
+
+/// State for an interactive debugging session.
+///
+/// Holds breakpoints and watches, tracks where execution currently is,
+/// and decides when execution should pause.
+#[derive(Clone, Debug, Default)]
+pub struct DebugSession {
+    state: DebugState,
+    breakpoints: BreakpointRegistry,
+    watches: WatchRegistry,
+    current_tick: u64,
+    current_phase: Option<String>,
+    current_rule: Option<String>,
+}
+
+impl DebugSession {
+    /// Creates a new debug session in the running state.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current execution state.
+    #[must_use]
+    pub fn state(&self) -> &DebugState {
+        &self.state
+    }
+
+    /// Returns true if execution is paused.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        matches!(self.state, DebugState::Paused(_))
+    }
+
+    /// Returns the reason execution is paused, if it is.
+    #[must_use]
+    pub fn pause_reason(&self) -> Option<&PauseReason> {
+        match &self.state {
+            DebugState::Paused(reason) => Some(reason),
+            _ => None,
+        }
+    }
+
+    /// Pauses execution.
+    pub fn pause(&mut self, reason: PauseReason) {
+        self.state = DebugState::Paused(reason);
+    }
+
+    /// Resumes normal execution.
+    pub fn resume(&mut self) {
+        self.state = DebugState::Running;
+    }
+
+    /// Runs until the next rule fires.
+    pub fn step_rule(&mut self) {
+        self.state = DebugState::StepRule;
+    }
+
+    /// Runs until the next phase begins.
+    pub fn step_phase(&mut self) {
+        self.state = DebugState::StepPhase;
+    }
+
+    /// Runs until the next tick begins.
+    pub fn step_tick(&mut self) {
+        self.state = DebugState::StepTick;
+    }
+
+    /// Returns the breakpoint registry.
+    #[must_use]
+    pub fn breakpoints(&self) -> &BreakpointRegistry {
+        &self.breakpoints
+    }
+
+    /// Returns a mutable reference to the breakpoint registry.
+    pub fn breakpoints_mut(&mut self) -> &mut BreakpointRegistry {
+        &mut self.breakpoints
+    }
+
+    /// Returns the watch registry.
+    #[must_use]
+    pub fn watches(&self) -> &WatchRegistry {
+        &self.watches
+    }
+
+    /// Returns a mutable reference to the watch registry.
+    pub fn watches_mut(&mut self) -> &mut WatchRegistry {
+        &mut self.watches
+    }
+
+    /// Returns the current tick.
+    #[must_use]
+    pub const fn current_tick(&self) -> u64 {
+        self.current_tick
+    }
+
+    /// Sets the current tick.
+    pub fn set_current_tick(&mut self, tick: u64) {
+        self.current_tick = tick;
+    }
+
+    /// Returns the current phase name.
+    #[must_use]
+    pub fn current_phase(&self) -> Option<&str> {
+        self.current_phase.as_deref()
+    }
+
+    /// Sets the current phase name.
+    pub fn set_current_phase(&mut self, phase: Option<String>) {
+        self.current_phase = phase;
+    }
+
+    /// Returns the current rule name.
+    #[must_use]
+    pub fn current_rule(&self) -> Option<&str> {
+        self.current_rule.as_deref()
+    }
+
+    /// Sets the current rule name.
+    pub fn set_current_rule(&mut self, rule: Option<String>) {
+        self.current_rule = rule;
+    }
+
+    /// Returns the first enabled breakpoint on the given rule, if any.
+    #[must_use]
+    pub fn should_break_on_rule(&self, rule: KeywordId) -> Option<BreakpointId> {
+        self.breakpoints
+            .get_rule_breakpoints(rule)
+            .first()
+            .map(|bp| bp.id())
+    }
+
+    /// Returns the first enabled breakpoint on the given tick, if any.
+    #[must_use]
+    pub fn should_break_on_tick(&self, tick: u64) -> Option<BreakpointId> {
+        self.breakpoints
+            .get_tick_breakpoints(tick)
+            .first()
+            .map(|bp| bp.id())
+    }
+
+    /// Returns the first enabled breakpoint on a component write, if any.
+    #[must_use]
+    pub fn should_break_on_component_write(
+        &self,
+        entity: EntityId,
+        component: KeywordId,
+    ) -> Option<BreakpointId> {
+        self.breakpoints
+            .get_component_write_breakpoints(entity, component)
+            .first()
+            .map(|bp| bp.id())
+    }
+
+    /// Returns the first enabled breakpoint on a component read, if any.
+    #[must_use]
+    pub fn should_break_on_component_read(
+        &self,
+        entity: EntityId,
+        component: KeywordId,
+    ) -> Option<BreakpointId> {
+        self.breakpoints
+            .get_component_read_breakpoints(entity, component)
+            .first()
+            .map(|bp| bp.id())
+    }
+
+    /// Notifies the session that a rule is about to fire.
+    ///
+    /// Returns true if execution should pause.
+    pub fn on_rule_enter(&mut self, rule: &str) -> bool {
+        self.current_rule = Some(rule.to_string());
+        self.complete_step_if(&DebugState::StepRule)
+    }
+
+    /// Notifies the session that a phase is beginning.
+    ///
+    /// Returns true if execution should pause.
+    pub fn on_phase_enter(&mut self, phase: &str) -> bool {
+        self.current_phase = Some(phase.to_string());
+        self.current_rule = None;
+        self.complete_step_if(&DebugState::StepPhase)
+    }
+
+    /// Notifies the session that a tick is beginning.
+    ///
+    /// Returns true if execution should pause.
+    pub fn on_tick_start(&mut self, tick: u64) -> bool {
+        self.current_tick = tick;
+        self.current_phase = None;
+        self.current_rule = None;
+        if let Some(id) = self.should_break_on_tick(tick) {
+            self.hit(id);
+            return true;
+        }
+        self.complete_step_if(&DebugState::StepTick)
+    }
+
+    /// Returns true if the session could pause a tick: it has breakpoints,
+    /// or is paused or stepping.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        !self.breakpoints.is_empty() || self.state != DebugState::Running
+    }
+
+    /// Notifies the session that a tick reached `point`, as reported by
+    /// [`TickExecutor::tick_with_debugger`](longtable_engine::TickExecutor::tick_with_debugger).
+    ///
+    /// Returns true if execution should pause.
+    pub fn on_debug_point(&mut self, point: DebugPoint<'_>, interner: &Interner) -> bool {
+        match point {
+            DebugPoint::TickStart(tick) => self.on_tick_start(tick),
+            DebugPoint::Phase(phase) => self.on_phase_enter(phase.name()),
+            DebugPoint::Rule(activation) => {
+                let name = interner.get_keyword(activation.rule_name).unwrap_or("?");
+                let stepped = self.on_rule_enter(name);
+                if let Some(id) = self.should_break_on_rule(activation.rule_name) {
+                    self.hit(id);
+                    return true;
+                }
+                stepped
+            }
+        }
+    }
+
+    /// Records a breakpoint hit and pauses execution.
+    pub fn hit(&mut self, id: BreakpointId) {
+        if let Some(bp) = self.breakpoints.get_mut(id) {
+            bp.record_hit();
+        }
+        self.pause(PauseReason::Breakpoint(id));
+    }
+
+    fn complete_step_if(&mut self, step: &DebugState) -> bool {
+        if self.state == *step {
+            self.pause(PauseReason::StepComplete);
+            true
+        } else {
+            self.is_paused()
+        }
+    }
+
+    /// Returns a human-readable summary of the session state.
+    #[must_use]
+    pub fn status_summary(&self) -> String {
+        let mut out = String::new();
+        match &self.state {
+            DebugState::Paused(reason) => {
+                let _ = writeln!(out, "State: paused ({reason})");
+            }
+            state => {
+                let _ = writeln!(out, "State: {}", state.name());
+            }
+        }
+        let _ = writeln!(out, "Tick: {}", self.current_tick);
+        if let Some(phase) = &self.current_phase {
+            let _ = writeln!(out, "Phase: {phase}");
+        }
+        if let Some(rule) = &self.current_rule {
+            let _ = writeln!(out, "Rule: {rule}");
+        }
+        let _ = writeln!(out, "Breakpoints: {}", self.breakpoints.len());
+        let _ = write!(out, "Watches: {}", self.watches.len());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::Interner;
+
+    #[test]
+    fn pause_and_resume() {
+        let mut session = DebugSession::new();
+        assert!(!session.is_paused());
+
+        session.pause(PauseReason::UserRequest);
+        assert!(session.is_paused());
+        assert_eq!(session.pause_reason(), Some(&PauseReason::UserRequest));
+
+        session.resume();
+        assert_eq!(session.state(), &DebugState::Running);
+    }
+
+    #[test]
+    fn step_rule_pauses_on_next_rule() {
+        let mut session = DebugSession::new();
+        assert!(!session.on_rule_enter("a"));
+
+        session.step_rule();
+        assert!(!session.on_phase_enter("activation"));
+        assert!(session.on_rule_enter("b"));
+        assert_eq!(session.pause_reason(), Some(&PauseReason::StepComplete));
+        assert_eq!(session.current_rule(), Some("b"));
+    }
+
+    #[test]
+    fn tick_breakpoint_pauses() {
+        let mut session = DebugSession::new();
+        let id = session.breakpoints_mut().add_tick(5);
+
+        assert!(!session.on_tick_start(4));
+        assert!(session.on_tick_start(5));
+        assert_eq!(session.pause_reason(), Some(&PauseReason::Breakpoint(id)));
+        assert_eq!(session.breakpoints().get(id).unwrap().hit_count(), 1);
+    }
+
+    #[test]
+    fn rule_breakpoint_lookup() {
+        let mut interner = Interner::new();
+        let rule = interner.intern_keyword("rule");
+        let mut session = DebugSession::new();
+        assert!(session.should_break_on_rule(rule).is_none());
+
+        let id = session.breakpoints_mut().add_rule(rule);
+        assert_eq!(session.should_break_on_rule(rule), Some(id));
+    }
+
+    #[test]
+    fn debug_points_pause_on_rule_breakpoints() {
+        use longtable_engine::{Activation, Bindings};
+
+        let mut interner = Interner::new();
+        let rule = interner.intern_keyword("heal");
+        let mut session = DebugSession::new();
+        assert!(!session.is_active());
+
+        let id = session.breakpoints_mut().add_rule(rule);
+        assert!(session.is_active());
+        assert!(!session.on_debug_point(DebugPoint::TickStart(1), &interner));
+        let activation = Activation {
+            rule_name: rule,
+            bindings: Bindings::new(),
+            salience: 0,
+            rank: 0,
+            specificity: 1,
+        };
+        assert!(session.on_debug_point(DebugPoint::Rule(&activation), &interner));
+        assert_eq!(session.pause_reason(), Some(&PauseReason::Breakpoint(id)));
+        assert_eq!(session.current_rule(), Some("heal"));
+    }
+
+    #[test]
+    fn status_summary_mentions_state() {
+        let mut session = DebugSession::new();
+        session.set_current_tick(42);
+        session.pause(PauseReason::UserRequest);
+        let summary = session.status_summary();
+        assert!(summary.contains("paused"));
+        assert!(summary.contains("42"));
+    }
+}
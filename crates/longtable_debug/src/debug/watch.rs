@@ -0,0 +1,212 @@
+//! Watch expressions for the debugger.
+//!
+//! A watch is a DSL expression whose value is re-evaluated and displayed
+//! while debugging. The registry only stores the source and the most
+//! recently observed value; evaluation is done by the caller.
+
+use longtable_foundation::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+// =============================================================================
+// Watch ID
+// =============================================================================
+
+/// Unique identifier for a watch expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WatchId(u64);
+
+impl WatchId {
+    /// Creates a new watch ID.
+    #[must_use]
+    pub const fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    /// Returns the raw ID value.
+    #[must_use]
+    pub const fn raw(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for WatchId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+// =============================================================================
+// Watch Expression
+// =============================================================================
+
+/// A watched expression and its last observed value.
+#[derive(Clone, Debug)]
+pub struct WatchExpression {
+    /// Unique identifier.
+    id: WatchId,
+    /// Source text of the expression.
+    source: String,
+    /// Whether this watch is active.
+    enabled: bool,
+    /// Value from the most recent evaluation.
+    last_value: Option<Value>,
+}
+
+impl WatchExpression {
+    /// Creates a new enabled watch expression.
+    #[must_use]
+    pub fn new(id: WatchId, source: String) -> Self {
+        Self {
+            id,
+            source,
+            enabled: true,
+            last_value: None,
+        }
+    }
+
+    /// Returns the watch ID.
+    #[must_use]
+    pub const fn id(&self) -> WatchId {
+        self.id
+    }
+
+    /// Returns the expression source.
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns whether this watch is enabled.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables this watch.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns the most recently observed value, if any.
+    #[must_use]
+    pub fn last_value(&self) -> Option<&Value> {
+        self.last_value.as_ref()
+    }
+
+    /// Records a newly observed value, returning the previous one.
+    pub fn set_value(&mut self, value: Value) -> Option<Value> {
+        self.last_value.replace(value)
+    }
+}
+
+// =============================================================================
+// Watch Registry
+// =============================================================================
+
+/// Registry of all watch expressions in a debug session.
+#[derive(Clone, Debug)]
+pub struct WatchRegistry {
+    /// All watches by ID (ordered for stable listing).
+    watches: BTreeMap<WatchId, WatchExpression>,
+    /// Next watch ID to assign.
+    next_id: u64,
+}
+
+impl WatchRegistry {
+    /// Creates an empty watch registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            watches: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Adds a watch expression and returns its ID.
+    pub fn add(&mut self, source: String) -> WatchId {
+        let id = WatchId::new(self.next_id);
+        self.next_id += 1;
+        self.watches.insert(id, WatchExpression::new(id, source));
+        id
+    }
+
+    /// Removes a watch, returning it if it existed.
+    pub fn remove(&mut self, id: WatchId) -> Option<WatchExpression> {
+        self.watches.remove(&id)
+    }
+
+    /// Gets a watch by ID.
+    #[must_use]
+    pub fn get(&self, id: WatchId) -> Option<&WatchExpression> {
+        self.watches.get(&id)
+    }
+
+    /// Gets a mutable reference to a watch by ID.
+    pub fn get_mut(&mut self, id: WatchId) -> Option<&mut WatchExpression> {
+        self.watches.get_mut(&id)
+    }
+
+    /// Removes all watches.
+    pub fn clear(&mut self) {
+        self.watches.clear();
+    }
+
+    /// Returns the number of watches.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.watches.len()
+    }
+
+    /// Returns true if there are no watches.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.watches.is_empty()
+    }
+
+    /// Iterates over all watches in ID order.
+    pub fn iter(&self) -> impl Iterator<Item = &WatchExpression> {
+        self.watches.values()
+    }
+
+    /// Iterates mutably over all watches in ID order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut WatchExpression> {
+        self.watches.values_mut()
+    }
+}
+
+impl Default for WatchRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_get_remove() {
+        let mut registry = WatchRegistry::new();
+        let id = registry.add("(get ?e :health)".to_string());
+
+        let watch = registry.get(id).unwrap();
+        assert_eq!(watch.source(), "(get ?e :health)");
+        assert!(watch.is_enabled());
+        assert!(watch.last_value().is_none());
+
+        assert!(registry.remove(id).is_some());
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn set_value_returns_previous() {
+        let mut registry = WatchRegistry::new();
+        let id = registry.add("x".to_string());
+        let watch = registry.get_mut(id).unwrap();
+
+        assert_eq!(watch.set_value(Value::Int(1)), None);
+        assert_eq!(watch.set_value(Value::Int(2)), Some(Value::Int(1)));
+        assert_eq!(watch.last_value(), Some(&Value::Int(2)));
+    }
+}
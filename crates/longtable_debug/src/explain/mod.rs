@@ -3,6 +3,8 @@
 //! Provides "why" queries for understanding how values were computed:
 //! - `(why entity :component)` - Single-hop explanation
 //! - `(why entity :component :depth N)` - Multi-hop causal chain
+//! - `(why entity :exists)` - What spawned the entity
+//! - `(why source :relationship target)` - Who linked or unlinked an edge
 //! - `(why entity :derived/component)` - Derived component explanation
 //! - `(explain-query query)` - Query execution explanation
 //! - `(explain-query query entity)` - Entity-specific query explanation
@@ -16,4 +18,4 @@ pub use query::{
     ClauseMatchStats, EntityMatchExplanation, MatchFailureReason, QueryExplanation,
     QueryExplanationBuilder,
 };
-pub use why::{CausalChain, CausalLink, WhyQuery, WhyResult};
+pub use why::{CausalChain, CausalLink, StructuralCause, StructuralChange, WhyQuery, WhyResult};
@@ -3,6 +3,7 @@
 //! This module provides the data types and logic for answering questions like:
 //! - "Why does this entity have this component value?"
 //! - "What rule set this value, and why did that rule fire?"
+//! - "What spawned this entity?" and "Who linked or unlinked these two?"
 //!
 //! # Example
 //!
@@ -12,9 +13,15 @@
 //!
 //! (why player :health/current :depth 3)
 //! ;; => {:chain [{rule: apply-damage, ...}, {rule: attack, ...}, ...]}
+//!
+//! (why sword :exists)
+//! ;; => spawned by :forge at tick 2
+//!
+//! (why room :contains sword)
+//! ;; => linked by :drop at tick 4, unlinked by :take at tick 6
 //! ```
 
-use longtable_engine::provenance::{ProvenanceTracker, WriteRecord};
+use longtable_engine::provenance::{LinkChange, ProvenanceTracker, WriteRecord};
 use longtable_foundation::{EntityId, KeywordId, Value};
 
 // =============================================================================
@@ -181,6 +188,48 @@ impl WhyResult {
     }
 }
 
+// =============================================================================
+// Structural Causes
+// =============================================================================
+
+/// A change to the shape of the world rather than to a component value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StructuralChange {
+    /// The entity was spawned.
+    Spawned,
+    /// The relationship was created.
+    Linked,
+    /// The relationship was removed.
+    Unlinked,
+}
+
+/// Who spawned an entity, or created or removed a relationship.
+#[derive(Clone, Debug)]
+pub struct StructuralCause {
+    /// What happened.
+    pub change: StructuralChange,
+
+    /// Which rule made the change.
+    pub rule: KeywordId,
+
+    /// Tick number when the change occurred.
+    pub tick: u64,
+
+    /// Entity binding context from the rule.
+    pub context: Vec<(String, EntityId)>,
+}
+
+impl StructuralCause {
+    fn from_record(change: StructuralChange, record: &WriteRecord) -> Self {
+        Self {
+            change,
+            rule: record.rule,
+            tick: record.tick,
+            context: record.context.clone(),
+        }
+    }
+}
+
 // =============================================================================
 // Why Query
 // =============================================================================
@@ -242,6 +291,38 @@ impl<'a> WhyQuery<'a> {
         WhyResult::Chain(chain)
     }
 
+    /// Answers "why does this entity exist?" with the rule that spawned it.
+    #[must_use]
+    pub fn why_exists(&self, entity: EntityId) -> Option<StructuralCause> {
+        self.tracker
+            .spawn_record(entity)
+            .map(|record| StructuralCause::from_record(StructuralChange::Spawned, record))
+    }
+
+    /// Answers "who linked (or unlinked) these entities?"
+    ///
+    /// Returns every recorded change to the edge, most recent first.
+    #[must_use]
+    pub fn why_linked(
+        &self,
+        source: EntityId,
+        relationship: KeywordId,
+        target: EntityId,
+    ) -> Vec<StructuralCause> {
+        self.tracker
+            .link_history(source, relationship, target)
+            .iter()
+            .rev()
+            .map(|link| {
+                let change = match link.change {
+                    LinkChange::Linked => StructuralChange::Linked,
+                    LinkChange::Unlinked => StructuralChange::Unlinked,
+                };
+                StructuralCause::from_record(change, &link.record)
+            })
+            .collect()
+    }
+
     /// Traces a causal chain by following entity references in binding contexts.
     fn trace_chain(&self, chain: &mut CausalChain, remaining_depth: usize) {
         if remaining_depth == 0 {
@@ -363,6 +444,53 @@ mod tests {
         assert_eq!(result.last_writer(), Some(apply_damage));
     }
 
+    #[test]
+    fn why_exists_and_linked() {
+        let (mut interner, _health, _damage, apply_damage) = setup();
+        let contains = interner.intern_keyword("contains");
+        let mut tracker = ProvenanceTracker::new();
+        let room = EntityId::new(1, 1);
+        let item = EntityId::new(2, 1);
+
+        tracker.record_spawn(item, apply_damage, vec![]);
+        tracker.record_link(
+            room,
+            contains,
+            item,
+            LinkChange::Linked,
+            apply_damage,
+            vec![],
+        );
+        tracker.begin_tick();
+        tracker.record_link(
+            room,
+            contains,
+            item,
+            LinkChange::Unlinked,
+            apply_damage,
+            vec![],
+        );
+
+        let query = WhyQuery::new(&tracker);
+        let spawned = query.why_exists(item).unwrap();
+        assert_eq!(spawned.change, StructuralChange::Spawned);
+        assert_eq!(spawned.rule, apply_damage);
+        assert!(query.why_exists(room).is_none());
+
+        let changes: Vec<_> = query
+            .why_linked(room, contains, item)
+            .iter()
+            .map(|c| (c.change, c.tick))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (StructuralChange::Unlinked, 1),
+                (StructuralChange::Linked, 0)
+            ]
+        );
+    }
+
     #[test]
     fn why_unknown() {
         let (_interner, health, _damage, _apply_damage) = setup();
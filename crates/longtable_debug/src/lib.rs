@@ -25,7 +25,7 @@ pub use debug::{
 pub use explain::{
     CausalChain, CausalLink, ClauseMatchStats, DerivedDependency, DerivedExplanation,
     DerivedExplanationBuilder, EntityMatchExplanation, MatchFailureReason, QueryExplanation,
-    QueryExplanationBuilder, WhyQuery, WhyResult,
+    QueryExplanationBuilder, StructuralCause, StructuralChange, WhyQuery, WhyResult,
 };
 pub use timeline::{
     Branch, BranchId, BranchRegistry, DiffGranularity, EntityDiff, HistoryBuffer, MergeResult,
@@ -33,6 +33,6 @@ pub use timeline::{
     diff_summary, diff_worlds, format_diff, merge,
 };
 pub use trace::{
-    HumanFormatter, JsonFormatter, TickPhase, TraceBuffer, TraceBufferStats, TraceEvent,
-    TraceFormatter, TraceOutput, TraceRecord, Tracer, TracerConfig,
+    ChromeTraceFormatter, HumanFormatter, JsonFormatter, TickPhase, TraceBuffer, TraceBufferStats,
+    TraceEvent, TraceFormatter, TraceOutput, TraceRecord, Tracer, TracerConfig,
 };
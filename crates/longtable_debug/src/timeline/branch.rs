@@ -182,6 +182,11 @@ impl BranchRegistry {
         }
     }
 
+    /// Iterates mutably over all branches.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Branch> {
+        self.branches.values_mut()
+    }
+
     /// Returns the main branch ID.
     #[must_use]
     pub const fn main_id(&self) -> BranchId {
@@ -165,6 +165,14 @@ impl HistoryBuffer {
         self.capacity
     }
 
+    /// Changes the capacity, evicting the oldest snapshots if over the new limit.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.snapshots.len() > capacity {
+            self.snapshots.pop_front();
+        }
+    }
+
     /// Returns the number of snapshots in the buffer.
     #[must_use]
     pub fn len(&self) -> usize {
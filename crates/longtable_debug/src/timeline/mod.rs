@@ -46,6 +46,8 @@ pub struct TimelineConfig {
     pub diff_granularity: DiffGranularity,
     /// Whether to automatically capture snapshots.
     pub auto_capture: bool,
+    /// Capture a snapshot every this many ticks (1 = every tick).
+    pub keyframe_interval: u64,
 }
 
 impl Default for TimelineConfig {
@@ -54,6 +56,7 @@ impl Default for TimelineConfig {
             history_size: 100,
             diff_granularity: DiffGranularity::Component,
             auto_capture: true,
+            keyframe_interval: 1,
         }
     }
 }
@@ -85,6 +88,13 @@ impl TimelineConfig {
         self.auto_capture = enabled;
         self
     }
+
+    /// Builder method to set the keyframe interval.
+    #[must_use]
+    pub const fn with_keyframe_interval(mut self, interval: u64) -> Self {
+        self.keyframe_interval = interval;
+        self
+    }
 }
 
 // =============================================================================
@@ -124,15 +134,9 @@ impl Timeline {
     /// Creates a new timeline with custom configuration.
     #[must_use]
     pub fn with_config(config: TimelineConfig) -> Self {
-        let branches = BranchRegistry::new();
-        let main_id = branches.main_id();
-
-        Self {
-            config,
-            branches,
-            current_branch: main_id,
-            enabled: true,
-        }
+        let mut timeline = Self::new();
+        timeline.set_config(config);
+        timeline
     }
 
     /// Returns whether the timeline is enabled.
@@ -157,6 +161,14 @@ impl Timeline {
         &self.config
     }
 
+    /// Replaces the configuration, resizing every branch's history to match.
+    pub fn set_config(&mut self, config: TimelineConfig) {
+        for branch in self.branches.iter_mut() {
+            branch.history_mut().set_capacity(config.history_size);
+        }
+        self.config = config;
+    }
+
     /// Returns the current branch ID.
     #[must_use]
     pub const fn current_branch_id(&self) -> BranchId {
@@ -197,6 +209,9 @@ impl Timeline {
         if !self.enabled || !self.config.auto_capture {
             return;
         }
+        if self.config.keyframe_interval > 1 && tick % self.config.keyframe_interval != 0 {
+            return;
+        }
 
         self.current_branch_mut()
             .push_snapshot(tick, world, summary);
@@ -248,8 +263,13 @@ impl Timeline {
     ///
     /// Returns the new branch ID, or None if creation failed.
     pub fn create_branch(&mut self, name: String, fork_tick: u64) -> Option<BranchId> {
-        self.branches
-            .create_branch(name, self.current_branch, fork_tick)
+        let id = self
+            .branches
+            .create_branch(name, self.current_branch, fork_tick)?;
+        if let Some(branch) = self.branches.get_mut(id) {
+            branch.history_mut().set_capacity(self.config.history_size);
+        }
+        Some(id)
     }
 
     /// Switches to a different branch by name.
@@ -400,6 +420,31 @@ mod tests {
         assert!(timeline.get_snapshot(4).is_none());
     }
 
+    #[test]
+    fn timeline_keyframe_interval() {
+        let mut timeline = Timeline::with_config(TimelineConfig::new().with_keyframe_interval(2));
+
+        for i in 1..=5 {
+            timeline.capture(i, make_world(i), TickSummary::success());
+        }
+
+        assert!(timeline.get_snapshot(1).is_none());
+        assert!(timeline.get_snapshot(2).is_some());
+        assert!(timeline.get_snapshot(4).is_some());
+        assert_eq!(timeline.current_branch().history().len(), 2);
+    }
+
+    #[test]
+    fn timeline_set_config_resizes_history() {
+        let mut timeline = Timeline::new();
+        for i in 1..=5 {
+            timeline.capture(i, make_world(i), TickSummary::success());
+        }
+
+        timeline.set_config(TimelineConfig::new().with_history_size(2));
+        assert_eq!(timeline.tick_range(), Some((4, 5)));
+    }
+
     #[test]
     fn timeline_rollback() {
         let mut timeline = Timeline::new();
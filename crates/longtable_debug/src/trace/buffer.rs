@@ -76,6 +76,27 @@ impl TraceBuffer {
         id
     }
 
+    /// Changes the maximum size, evicting the oldest records if over the new limit.
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        let excess = self.records.len().saturating_sub(max_size);
+        if excess == 0 {
+            return;
+        }
+        self.records.drain(..excess);
+        self.tick_index = self
+            .tick_index
+            .iter()
+            .filter_map(|&(tick, idx)| Some((tick, idx.checked_sub(excess)?)))
+            .collect();
+        // The oldest surviving tick may have started before the cut
+        if let Some(first) = self.records.front() {
+            if self.tick_index.first().map(|(t, _)| *t) != Some(first.tick) {
+                self.tick_index.insert(0, (first.tick, 0));
+            }
+        }
+    }
+
     /// Returns the number of records in the buffer.
     #[must_use]
     pub fn len(&self) -> usize {
@@ -130,10 +151,11 @@ impl TraceBuffer {
         self.records.iter().filter(|r| predicate(r)).collect()
     }
 
-    /// Returns records of a specific event type.
+    /// Returns records of a specific event type, or of a category such as
+    /// `parse` (see [`TraceEvent::matches_type`]).
     #[must_use]
     pub fn by_event_type(&self, event_type: &str) -> Vec<&TraceRecord> {
-        self.filter(|r| r.event_type() == event_type)
+        self.filter(|r| r.event.matches_type(event_type))
     }
 
     /// Returns the oldest tick number in the buffer.
@@ -259,6 +281,28 @@ mod tests {
         assert!(matches!(oldest.event, TraceEvent::TickEnd { tick: 1, .. }));
     }
 
+    #[test]
+    fn buffer_shrink() {
+        let mut buffer = TraceBuffer::new(10);
+        for tick in 1..=3 {
+            buffer.push(tick, tick * 1000, TraceEvent::TickStart { tick });
+            buffer.push(
+                tick,
+                tick * 1000 + 1,
+                TraceEvent::TickEnd {
+                    tick,
+                    success: true,
+                },
+            );
+        }
+
+        buffer.set_max_size(3);
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.oldest_tick(), Some(2));
+        assert_eq!(buffer.records_for_tick(2).len(), 1);
+        assert_eq!(buffer.records_for_tick(3).len(), 2);
+    }
+
     #[test]
     fn records_for_tick() {
         let mut buffer = TraceBuffer::new(100);
@@ -1,8 +1,10 @@
 //! Trace output formatters.
 //!
-//! Provides human-readable and JSON formatters for trace records.
+//! Provides human-readable and JSON formatters for trace records, and one
+//! for the Chrome trace event format that profiler UIs read.
 
 use longtable_foundation::Interner;
+use longtable_storage::OnDelete;
 
 use super::record::{TraceEvent, TraceRecord};
 
@@ -79,7 +81,7 @@ impl HumanFormatter {
 }
 
 impl TraceFormatter for HumanFormatter {
-    #[allow(clippy::format_push_string)]
+    #[allow(clippy::format_push_string, clippy::too_many_lines)]
     fn format(&self, record: &TraceRecord, interner: &Interner) -> String {
         use std::fmt::Write;
         let mut prefix = String::new();
@@ -130,6 +132,20 @@ impl TraceFormatter for HumanFormatter {
                 let rule_name = Self::keyword_name(*rule, interner);
                 format!("  COMPLETE :{rule_name}")
             }
+            TraceEvent::RuleError {
+                rule,
+                message,
+                file,
+                line,
+            } => {
+                let rule_name = Self::keyword_name(*rule, interner);
+                let location = match (file, line) {
+                    (Some(file), Some(line)) => format!(" at {file}:{line}"),
+                    (None, Some(line)) => format!(" at line {line}"),
+                    _ => String::new(),
+                };
+                format!("  ERROR :{rule_name}{location}: {message}")
+            }
             TraceEvent::ComponentWrite {
                 entity,
                 component,
@@ -162,6 +178,21 @@ impl TraceFormatter for HumanFormatter {
                     .unwrap_or_default();
                 format!("    DESTROY{rule_str}{entity}")
             }
+            TraceEvent::RelationshipCascade {
+                destroyed,
+                relationship,
+                source,
+                action,
+            } => format!(
+                "    CASCADE {destroyed} <-:{}- {source}: {}",
+                Self::keyword_name(*relationship, interner),
+                on_delete_name(*action)
+            ),
+            TraceEvent::SystemRun {
+                name,
+                phase,
+                effects,
+            } => format!("  SYSTEM {name} at {phase}: {effects} effects"),
             TraceEvent::ConstraintResult {
                 name,
                 passed,
@@ -180,6 +211,38 @@ impl TraceFormatter for HumanFormatter {
             TraceEvent::WatchEvaluated { watch_id, value } => {
                 format!("  WATCH #{watch_id} = {value}")
             }
+            TraceEvent::ParseTokens { input, tokens } => {
+                format!("  PARSE {input:?} -> [{}]", tokens.join(" "))
+            }
+            TraceEvent::ParseCorrection { word, correction } => {
+                format!("  CORRECT {word:?} -> {correction:?}")
+            }
+            TraceEvent::ParseCandidates { evaluated, matched } => {
+                let names: Vec<_> = matched
+                    .iter()
+                    .map(|c| format!(":{}", Self::keyword_name(*c, interner)))
+                    .collect();
+                format!(
+                    "  SYNTAX {} of {evaluated} matched [{}]",
+                    matched.len(),
+                    names.join(" ")
+                )
+            }
+            TraceEvent::NounResolution {
+                variable,
+                noun,
+                outcome,
+                entities,
+            } => {
+                let entities: Vec<_> = entities.iter().map(ToString::to_string).collect();
+                format!(
+                    "  NOUN ?{variable} {noun:?} {outcome} [{}]",
+                    entities.join(" ")
+                )
+            }
+            TraceEvent::DisambiguationAsked { question, options } => {
+                format!("  ASK {question:?} ({} options)", options.len())
+            }
             TraceEvent::Custom { name, data } => {
                 format!("  CUSTOM {name}: {data}")
             }
@@ -250,14 +313,14 @@ impl JsonFormatter {
     }
 }
 
-impl TraceFormatter for JsonFormatter {
+impl JsonFormatter {
+    /// Formats the fields particular to an event, without surrounding braces.
     #[allow(clippy::too_many_lines)]
-    fn format(&self, record: &TraceRecord, interner: &Interner) -> String {
-        let event_type = record.event_type();
+    fn event_fields(event: &TraceEvent, interner: &Interner) -> String {
         let keyword_name =
             |id: longtable_foundation::KeywordId| interner.get_keyword(id).unwrap_or("?");
 
-        let event_data = match &record.event {
+        match event {
             TraceEvent::TickStart { tick } => {
                 format!("\"tick\":{tick}")
             }
@@ -281,6 +344,23 @@ impl TraceFormatter for JsonFormatter {
             TraceEvent::RuleFiring { rule } | TraceEvent::RuleComplete { rule } => {
                 format!("\"rule\":\"{}\"", keyword_name(*rule))
             }
+            TraceEvent::RuleError {
+                rule,
+                message,
+                file,
+                line,
+            } => {
+                let file_json = file
+                    .as_ref()
+                    .map(|f| format!(",\"file\":\"{}\"", Self::escape_string(f)))
+                    .unwrap_or_default();
+                let line_json = line.map(|l| format!(",\"line\":{l}")).unwrap_or_default();
+                format!(
+                    "\"rule\":\"{}\",\"message\":\"{}\"{file_json}{line_json}",
+                    keyword_name(*rule),
+                    Self::escape_string(message)
+                )
+            }
             TraceEvent::ComponentWrite {
                 entity,
                 component,
@@ -315,6 +395,24 @@ impl TraceFormatter for JsonFormatter {
                     .unwrap_or_default();
                 format!("\"entity\":\"{entity}\"{rule_json}")
             }
+            TraceEvent::RelationshipCascade {
+                destroyed,
+                relationship,
+                source,
+                action,
+            } => format!(
+                "\"destroyed\":\"{destroyed}\",\"relationship\":\"{}\",\"source\":\"{source}\",\"action\":\"{}\"",
+                keyword_name(*relationship),
+                on_delete_name(*action)
+            ),
+            TraceEvent::SystemRun {
+                name,
+                phase,
+                effects,
+            } => format!(
+                "\"system\":\"{}\",\"phase\":\"{phase}\",\"effects\":{effects}",
+                Self::escape_string(name)
+            ),
             TraceEvent::ConstraintResult {
                 name,
                 passed,
@@ -338,6 +436,56 @@ impl TraceFormatter for JsonFormatter {
                     Self::format_value(value)
                 )
             }
+            TraceEvent::ParseTokens { input, tokens } => {
+                let tokens: Vec<_> = tokens
+                    .iter()
+                    .map(|t| format!("\"{}\"", Self::escape_string(t)))
+                    .collect();
+                format!(
+                    "\"input\":\"{}\",\"tokens\":[{}]",
+                    Self::escape_string(input),
+                    tokens.join(",")
+                )
+            }
+            TraceEvent::ParseCorrection { word, correction } => {
+                format!(
+                    "\"word\":\"{}\",\"correction\":\"{}\"",
+                    Self::escape_string(word),
+                    Self::escape_string(correction)
+                )
+            }
+            TraceEvent::ParseCandidates { evaluated, matched } => {
+                let matched: Vec<_> = matched
+                    .iter()
+                    .map(|c| format!("\"{}\"", keyword_name(*c)))
+                    .collect();
+                format!(
+                    "\"evaluated\":{evaluated},\"matched\":[{}]",
+                    matched.join(",")
+                )
+            }
+            TraceEvent::NounResolution {
+                variable,
+                noun,
+                outcome,
+                entities,
+            } => {
+                let entities: Vec<_> = entities.iter().map(|e| format!("\"{e}\"")).collect();
+                format!(
+                    "\"variable\":\"{}\",\"noun\":\"{}\",\"outcome\":\"{outcome}\",\"entities\":[{}]",
+                    Self::escape_string(variable),
+                    Self::escape_string(noun),
+                    entities.join(",")
+                )
+            }
+            TraceEvent::DisambiguationAsked { question, options } => {
+                let options: Vec<_> = options.iter().map(|e| format!("\"{e}\"")).collect();
+                format!(
+                    "\"question\":\"{}\",\"options\":[{}]",
+                    Self::escape_string(question),
+                    options.join(",")
+                )
+            }
             TraceEvent::Custom { name, data } => {
                 format!(
                     "\"name\":\"{}\",\"data\":{}",
@@ -345,7 +493,14 @@ impl TraceFormatter for JsonFormatter {
                     Self::format_value(data)
                 )
             }
-        };
+        }
+    }
+}
+
+impl TraceFormatter for JsonFormatter {
+    fn format(&self, record: &TraceRecord, interner: &Interner) -> String {
+        let event_type = record.event_type();
+        let event_data = Self::event_fields(&record.event, interner);
 
         let json = format!(
             "{{\"id\":{},\"tick\":{},\"timestamp_ns\":{},\"type\":\"{}\",{}}}",
@@ -371,6 +526,70 @@ impl TraceFormatter for JsonFormatter {
     }
 }
 
+// =============================================================================
+// Chrome Trace Formatter
+// =============================================================================
+
+/// Formats trace records in the Chrome trace event format, which
+/// `chrome://tracing`, Perfetto, and Speedscope can open.
+///
+/// Ticks and their phases become nested spans, and rules fire as slices
+/// inside them. Every other event is an instant marker carrying its fields
+/// as arguments. All events go on one thread, since a tick runs on one.
+#[derive(Clone, Debug, Default)]
+pub struct ChromeTraceFormatter;
+
+impl ChromeTraceFormatter {
+    /// Creates a new Chrome trace formatter.
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TraceFormatter for ChromeTraceFormatter {
+    fn format(&self, record: &TraceRecord, interner: &Interner) -> String {
+        let keyword_name =
+            |id: longtable_foundation::KeywordId| interner.get_keyword(id).unwrap_or("?");
+        let (name, category, kind) = match &record.event {
+            TraceEvent::TickStart { tick } => (format!("tick {tick}"), "tick", "B"),
+            TraceEvent::TickEnd { tick, .. } => (format!("tick {tick}"), "tick", "E"),
+            TraceEvent::PhaseStart { phase } => (phase.to_string(), "phase", "B"),
+            TraceEvent::PhaseEnd { phase } => (phase.to_string(), "phase", "E"),
+            TraceEvent::RuleFiring { rule } => (format!(":{}", keyword_name(*rule)), "rule", "B"),
+            TraceEvent::RuleComplete { rule } => (format!(":{}", keyword_name(*rule)), "rule", "E"),
+            event => (event.event_type().to_string(), "event", "i"),
+        };
+
+        // Chrome wants microseconds
+        let micros = record.timestamp_ns / 1000;
+        let nanos = record.timestamp_ns % 1000;
+        let scope = if kind == "i" { ",\"s\":\"t\"" } else { "" };
+        format!(
+            "{{\"name\":\"{}\",\"cat\":\"{category}\",\"ph\":\"{kind}\",\"ts\":{micros}.{nanos:03},\"pid\":1,\"tid\":1{scope},\"args\":{{{}}}}}",
+            JsonFormatter::escape_string(&name),
+            JsonFormatter::event_fields(&record.event, interner)
+        )
+    }
+
+    fn format_many(&self, records: &[&TraceRecord], interner: &Interner) -> String {
+        let items: Vec<_> = records.iter().map(|r| self.format(r, interner)).collect();
+        format!(
+            "{{\"traceEvents\":[\n{}\n],\"displayTimeUnit\":\"ms\"}}",
+            items.join(",\n")
+        )
+    }
+}
+
+/// Returns the `:on-delete` keyword name of a policy.
+fn on_delete_name(action: OnDelete) -> &'static str {
+    match action {
+        OnDelete::Remove => "remove",
+        OnDelete::Cascade => "cascade",
+        OnDelete::Nullify => "nullify",
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -450,6 +669,49 @@ mod tests {
         assert!(output.contains("75"));
     }
 
+    #[test]
+    fn human_formatter_rule_error_location() {
+        let mut interner = setup();
+        let formatter = HumanFormatter::new();
+        let rule = interner.intern_keyword("apply-damage");
+
+        let record = TraceRecord::new(
+            1,
+            5,
+            1000,
+            TraceEvent::RuleError {
+                rule,
+                message: "division by zero".to_string(),
+                file: Some("game/rules.lt".to_string()),
+                line: Some(17),
+            },
+        );
+
+        let output = formatter.format(&record, &interner);
+        assert!(output.contains("ERROR :apply-damage at game/rules.lt:17: division by zero"));
+    }
+
+    #[test]
+    fn formatters_show_system_runs() {
+        let interner = setup();
+        let record = TraceRecord::new(
+            1,
+            5,
+            1000,
+            TraceEvent::SystemRun {
+                name: "physics".to_string(),
+                phase: "after-inputs".to_string(),
+                effects: 3,
+            },
+        );
+
+        let output = HumanFormatter::new().format(&record, &interner);
+        assert!(output.contains("SYSTEM physics at after-inputs: 3 effects"));
+        let output = JsonFormatter::new().format(&record, &interner);
+        assert!(output.contains("\"type\":\"system-run\""));
+        assert!(output.contains("\"system\":\"physics\",\"phase\":\"after-inputs\",\"effects\":3"));
+    }
+
     #[test]
     fn json_formatter_basic() {
         let interner = setup();
@@ -484,4 +746,52 @@ mod tests {
         assert!(output.starts_with('['));
         assert!(output.ends_with(']'));
     }
+
+    #[test]
+    fn chrome_formatter_nests_phases_and_rules() {
+        use crate::trace::TickPhase;
+
+        let mut interner = setup();
+        let rule = interner.intern_keyword("apply-damage");
+        let events = [
+            TraceEvent::TickStart { tick: 5 },
+            TraceEvent::PhaseStart {
+                phase: TickPhase::Firing,
+            },
+            TraceEvent::RuleFiring { rule },
+            TraceEvent::EntitySpawn {
+                entity: EntityId::new(3, 0),
+                rule: Some(rule),
+            },
+            TraceEvent::RuleComplete { rule },
+            TraceEvent::PhaseEnd {
+                phase: TickPhase::Firing,
+            },
+            TraceEvent::TickEnd {
+                tick: 5,
+                success: true,
+            },
+        ];
+        let records: Vec<_> = events
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| TraceRecord::new(i as u64, 5, 1500 * i as u64, event))
+            .collect();
+        let refs: Vec<_> = records.iter().collect();
+        let output = ChromeTraceFormatter::new().format_many(&refs, &interner);
+
+        assert!(output.starts_with("{\"traceEvents\":["), "{output}");
+        let lines: Vec<_> = output.lines().collect();
+        assert!(
+            lines[1].contains("\"name\":\"tick 5\",\"cat\":\"tick\",\"ph\":\"B\",\"ts\":0.000")
+        );
+        assert!(
+            lines[2].contains("\"name\":\"firing\",\"cat\":\"phase\",\"ph\":\"B\",\"ts\":1.500")
+        );
+        assert!(lines[3].contains("\"name\":\":apply-damage\",\"cat\":\"rule\",\"ph\":\"B\""));
+        assert!(lines[4].contains("\"ph\":\"i\""), "{}", lines[4]);
+        assert!(lines[4].contains("\"args\":{\"entity\":"), "{}", lines[4]);
+        assert!(lines[7].contains("\"ph\":\"E\""));
+        assert!(lines[7].contains("\"success\":true"));
+    }
 }
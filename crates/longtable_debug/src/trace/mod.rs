@@ -11,6 +11,7 @@
 //! (trace :off)                         ;; Disable tracing
 //! (get-traces :last 10)                ;; Get recent trace records
 //! (get-traces :tick 5)                 ;; Get traces for tick 5
+//! (get-traces :type :parse)            ;; Parser decisions for player input
 //! ```
 
 pub mod buffer;
@@ -18,11 +19,11 @@ pub mod format;
 pub mod record;
 
 pub use buffer::{TraceBuffer, TraceBufferStats};
-pub use format::{HumanFormatter, JsonFormatter, TraceFormatter};
+pub use format::{ChromeTraceFormatter, HumanFormatter, JsonFormatter, TraceFormatter};
 pub use record::{TickPhase, TraceEvent, TraceRecord};
 
+use longtable_foundation::clock::Instant;
 use std::io::{self, Write};
-use std::time::Instant;
 
 use longtable_foundation::Interner;
 
@@ -31,9 +32,10 @@ use longtable_foundation::Interner;
 // =============================================================================
 
 /// Where trace output should be sent.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum TraceOutput {
     /// No output (traces still recorded in buffer).
+    #[default]
     None,
     /// Write to stderr.
     Stderr,
@@ -42,12 +44,6 @@ pub enum TraceOutput {
     Custom,
 }
 
-impl Default for TraceOutput {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 // =============================================================================
 // Tracer Configuration
 // =============================================================================
@@ -199,6 +195,12 @@ impl Tracer {
         self.config.json_format = json;
     }
 
+    /// Sets the maximum number of records kept in the buffer.
+    pub fn set_buffer_size(&mut self, size: usize) {
+        self.config.buffer_size = size;
+        self.buffer.set_max_size(size);
+    }
+
     /// Sets the trace output destination.
     pub fn set_output(&mut self, output: TraceOutput) {
         self.config.output = output;
@@ -344,6 +346,25 @@ impl Tracer {
         self.record(TraceEvent::RuleComplete { rule });
     }
 
+    /// Records a rule body failure, with the source location from the
+    /// error's context when the rule was compiled with spans.
+    #[inline]
+    pub fn rule_error(
+        &mut self,
+        rule: longtable_foundation::KeywordId,
+        error: &longtable_foundation::Error,
+    ) {
+        let context = error.context.as_ref();
+        self.record(TraceEvent::RuleError {
+            rule,
+            message: error.to_string(),
+            file: context
+                .and_then(|c| c.source.clone())
+                .filter(|source| !source.starts_with("rule :")),
+            line: context.and_then(|c| c.line),
+        });
+    }
+
     /// Records a component write event.
     #[inline]
     #[allow(clippy::too_many_arguments)]
@@ -383,6 +404,27 @@ impl Tracer {
     ) {
         self.record(TraceEvent::EntityDestroy { entity, rule });
     }
+
+    /// Records one step of a cascading destroy.
+    #[inline]
+    pub fn relationship_cascade(&mut self, step: &longtable_storage::CascadeStep) {
+        self.record(TraceEvent::RelationshipCascade {
+            destroyed: step.destroyed,
+            relationship: step.relationship,
+            source: step.source,
+            action: step.action,
+        });
+    }
+
+    /// Records a host system's run during a tick.
+    #[inline]
+    pub fn system_run(&mut self, run: &longtable_engine::SystemRun) {
+        self.record(TraceEvent::SystemRun {
+            name: run.name.clone(),
+            phase: run.phase.name().to_string(),
+            effects: run.effects,
+        });
+    }
 }
 
 impl Default for Tracer {
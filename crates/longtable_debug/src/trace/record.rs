@@ -3,6 +3,7 @@
 //! This module defines the events that can be traced during simulation execution.
 
 use longtable_foundation::{EntityId, KeywordId, Value};
+use longtable_storage::OnDelete;
 
 // =============================================================================
 // Tick Phase
@@ -88,6 +89,18 @@ pub enum TraceEvent {
         rule: KeywordId,
     },
 
+    /// A rule body failed at runtime.
+    RuleError {
+        /// The rule that failed.
+        rule: KeywordId,
+        /// The error message.
+        message: String,
+        /// Source file of the failing expression (if known).
+        file: Option<String>,
+        /// Line of the failing expression (if known).
+        line: Option<usize>,
+    },
+
     /// A component value was written.
     ComponentWrite {
         /// The entity that was written to.
@@ -118,6 +131,29 @@ pub enum TraceEvent {
         rule: Option<KeywordId>,
     },
 
+    /// Destroying an entity applied a relationship's `:on-delete` policy to
+    /// a link pointing at it.
+    RelationshipCascade {
+        /// The entity that was destroyed.
+        destroyed: EntityId,
+        /// The relationship of the link.
+        relationship: KeywordId,
+        /// The link's source.
+        source: EntityId,
+        /// The policy applied.
+        action: OnDelete,
+    },
+
+    /// A host-provided Rust system ran.
+    SystemRun {
+        /// The system's name.
+        name: String,
+        /// The tick phase it ran at (e.g. `after-inputs`).
+        phase: String,
+        /// How many effects it returned.
+        effects: usize,
+    },
+
     /// A constraint was checked.
     ConstraintResult {
         /// The constraint name.
@@ -142,6 +178,51 @@ pub enum TraceEvent {
         value: Value,
     },
 
+    /// Player input was split into words.
+    ParseTokens {
+        /// The raw input.
+        input: String,
+        /// The words and quoted strings, in order.
+        tokens: Vec<String>,
+    },
+
+    /// An unknown verb or direction in player input was corrected.
+    ParseCorrection {
+        /// The word as typed.
+        word: String,
+        /// The word it was taken to mean.
+        correction: String,
+    },
+
+    /// Syntax patterns were tried against parsed input.
+    ParseCandidates {
+        /// How many syntaxes were tried.
+        evaluated: usize,
+        /// Commands whose syntax matched, best first.
+        matched: Vec<KeywordId>,
+    },
+
+    /// A noun or pronoun in player input was resolved.
+    NounResolution {
+        /// The syntax variable being filled.
+        variable: String,
+        /// The noun as typed.
+        noun: String,
+        /// The outcome: `unique`, `ambiguous`, `multiple`, `not-found`,
+        /// `wrong-type`, `pronoun`, or `no-referent`.
+        outcome: String,
+        /// The entities it resolved to.
+        entities: Vec<EntityId>,
+    },
+
+    /// The player was asked which entity they meant.
+    DisambiguationAsked {
+        /// The question asked.
+        question: String,
+        /// The entities offered.
+        options: Vec<EntityId>,
+    },
+
     /// Custom user event.
     Custom {
         /// Event name.
@@ -163,12 +244,20 @@ impl TraceEvent {
             Self::RuleActivated { .. } => "rule-activated",
             Self::RuleFiring { .. } => "rule-firing",
             Self::RuleComplete { .. } => "rule-complete",
+            Self::RuleError { .. } => "rule-error",
             Self::ComponentWrite { .. } => "component-write",
             Self::EntitySpawn { .. } => "entity-spawn",
             Self::EntityDestroy { .. } => "entity-destroy",
+            Self::RelationshipCascade { .. } => "relationship-cascade",
+            Self::SystemRun { .. } => "system-run",
             Self::ConstraintResult { .. } => "constraint-result",
             Self::BreakpointHit { .. } => "breakpoint-hit",
             Self::WatchEvaluated { .. } => "watch-evaluated",
+            Self::ParseTokens { .. } => "parse-tokens",
+            Self::ParseCorrection { .. } => "parse-correction",
+            Self::ParseCandidates { .. } => "parse-candidates",
+            Self::NounResolution { .. } => "parse-noun",
+            Self::DisambiguationAsked { .. } => "parse-disambiguation",
             Self::Custom { .. } => "custom",
         }
     }
@@ -184,16 +273,42 @@ impl TraceEvent {
     pub fn is_rule_event(&self) -> bool {
         matches!(
             self,
-            Self::RuleActivated { .. } | Self::RuleFiring { .. } | Self::RuleComplete { .. }
+            Self::RuleActivated { .. }
+                | Self::RuleFiring { .. }
+                | Self::RuleComplete { .. }
+                | Self::RuleError { .. }
         )
     }
 
+    /// Returns true if this records a natural-language parser decision.
+    #[must_use]
+    pub fn is_parse_event(&self) -> bool {
+        matches!(
+            self,
+            Self::ParseTokens { .. }
+                | Self::ParseCorrection { .. }
+                | Self::ParseCandidates { .. }
+                | Self::NounResolution { .. }
+                | Self::DisambiguationAsked { .. }
+        )
+    }
+
+    /// Returns true if this event has the given type, or belongs to the
+    /// given category (`parse` covers every parser event).
+    #[must_use]
+    pub fn matches_type(&self, event_type: &str) -> bool {
+        self.event_type() == event_type || (event_type == "parse" && self.is_parse_event())
+    }
+
     /// Returns true if this is an entity modification event.
     #[must_use]
     pub fn is_entity_event(&self) -> bool {
         matches!(
             self,
-            Self::ComponentWrite { .. } | Self::EntitySpawn { .. } | Self::EntityDestroy { .. }
+            Self::ComponentWrite { .. }
+                | Self::EntitySpawn { .. }
+                | Self::EntityDestroy { .. }
+                | Self::RelationshipCascade { .. }
         )
     }
 }
@@ -279,6 +394,15 @@ mod tests {
             rule: None,
         };
         assert!(entity_event.is_entity_event());
+
+        let parse_event = TraceEvent::ParseCandidates {
+            evaluated: 3,
+            matched: vec![rule_id],
+        };
+        assert!(parse_event.is_parse_event());
+        assert!(parse_event.matches_type("parse"));
+        assert!(parse_event.matches_type("parse-candidates"));
+        assert!(!entity_event.matches_type("parse"));
     }
 
     #[test]
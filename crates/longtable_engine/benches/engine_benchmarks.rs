@@ -11,7 +11,7 @@
 //! - rule_engine: Rule activation finding and execution
 //! - derived_components: Derived component caching and evaluation
 //! - constraint_checking: Constraint validation performance
-//! - tick_orchestration: Full tick cycle performance
+//! - tick_orchestration: Full tick cycle performance, including debug vs play mode
 //! - bindings: Bindings data structure operations
 //! - throughput: High-level throughput measurements
 
@@ -19,8 +19,8 @@ use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group,
 
 use longtable_engine::{
     Bindings, CompiledRule, ConstraintChecker, ConstraintCompiler, DerivedCache, DerivedCompiler,
-    DerivedEvaluator, InputEvent, PatternCompiler, PatternMatcher, ProductionRuleEngine,
-    QueryCompiler, QueryExecutor, TickExecutor,
+    DerivedEvaluator, ExecutionMode, InputEvent, PatternCompiler, PatternMatcher,
+    ProductionRuleEngine, QueryCompiler, QueryExecutor, TickExecutor,
 };
 use longtable_foundation::{LtMap, Type, Value};
 use longtable_language::declaration::{
@@ -202,6 +202,9 @@ fn make_pattern(clauses: Vec<DeclClause>) -> DeclPattern {
     DeclPattern {
         clauses,
         negations: vec![],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     }
 }
 
@@ -210,7 +213,13 @@ fn make_pattern_with_negations(
     clauses: Vec<DeclClause>,
     negations: Vec<DeclClause>,
 ) -> DeclPattern {
-    DeclPattern { clauses, negations }
+    DeclPattern {
+        clauses,
+        negations,
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
+    }
 }
 
 /// Helper to create a pattern clause with default span.
@@ -234,6 +243,7 @@ fn make_query(pattern: DeclPattern, return_var: &str) -> QueryDecl {
         order_by: vec![],
         limit: None,
         return_expr: Some(Ast::Symbol(return_var.to_string(), Span::default())),
+        suppress: vec![],
         span: Span::default(),
     }
 }
@@ -329,7 +339,7 @@ fn bench_pattern_matching(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -354,7 +364,7 @@ fn bench_pattern_matching(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -380,7 +390,7 @@ fn bench_pattern_matching(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -405,7 +415,7 @@ fn bench_pattern_matching(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -440,7 +450,7 @@ fn bench_pattern_negation(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -468,7 +478,7 @@ fn bench_pattern_negation(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -493,7 +503,7 @@ fn bench_pattern_negation(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -660,7 +670,7 @@ fn bench_relationship_queries(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -722,7 +732,7 @@ fn bench_rule_engine(c: &mut Criterion) {
                 b.iter(|| {
                     let mut engine = ProductionRuleEngine::new();
                     engine.begin_tick();
-                    let activations = engine.find_activations(r, w);
+                    let activations = engine.find_activations(r, w).unwrap();
                     black_box(activations.len())
                 })
             },
@@ -761,7 +771,7 @@ fn bench_rule_engine(c: &mut Criterion) {
                 b.iter(|| {
                     let mut engine = ProductionRuleEngine::new();
                     engine.begin_tick();
-                    let activations = engine.find_activations(r, w);
+                    let activations = engine.find_activations(r, w).unwrap();
                     black_box(activations.len())
                 })
             },
@@ -799,7 +809,7 @@ fn bench_rule_engine(c: &mut Criterion) {
                 b.iter(|| {
                     let mut engine = ProductionRuleEngine::new();
                     engine.begin_tick();
-                    let activations = engine.find_activations(r, w);
+                    let activations = engine.find_activations(r, w).unwrap();
                     black_box(activations.len())
                 })
             },
@@ -955,6 +965,9 @@ fn bench_constraint_checking(c: &mut Criterion) {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         decl.on_violation = ConstraintViolation::Rollback;
 
@@ -966,7 +979,7 @@ fn bench_constraint_checking(c: &mut Criterion) {
         let world = World::new(42);
         let checker = ConstraintChecker::new();
 
-        b.iter(|| black_box(checker.check_all(&world)))
+        b.iter(|| black_box(checker.check_all(&world).unwrap()))
     });
 
     // Check single constraint at scale
@@ -982,6 +995,9 @@ fn bench_constraint_checking(c: &mut Criterion) {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         decl.on_violation = ConstraintViolation::Warn;
 
@@ -993,7 +1009,7 @@ fn bench_constraint_checking(c: &mut Criterion) {
         group.bench_with_input(
             BenchmarkId::new("single_constraint", entity_count),
             &(world, checker),
-            |b, (w, ch)| b.iter(|| black_box(ch.check_all(w))),
+            |b, (w, ch)| b.iter(|| black_box(ch.check_all(w).unwrap())),
         );
     }
 
@@ -1021,6 +1037,9 @@ fn bench_constraint_checking(c: &mut Criterion) {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             };
             decl.on_violation = ConstraintViolation::Warn;
 
@@ -1033,7 +1052,7 @@ fn bench_constraint_checking(c: &mut Criterion) {
         group.bench_with_input(
             BenchmarkId::new("5_constraints", entity_count),
             &(world, checker),
-            |b, (w, ch)| b.iter(|| black_box(ch.check_all(w))),
+            |b, (w, ch)| b.iter(|| black_box(ch.check_all(w).unwrap())),
         );
     }
 
@@ -1132,6 +1151,38 @@ fn bench_tick_orchestration(c: &mut Criterion) {
         );
     }
 
+    // Debug vs play mode: provenance bookkeeping overhead per input write
+    for input_count in [10, 100, 1_000] {
+        let mut world = create_world_with_entities(1_000);
+        let health = world.interner_mut().intern_keyword("health");
+        let current = world.interner_mut().intern_keyword("current");
+
+        let entities: Vec<_> = world.with_component(health).take(input_count).collect();
+        let inputs: Vec<_> = entities
+            .iter()
+            .map(|&entity| InputEvent::Set {
+                entity,
+                component: health,
+                value: Value::Map(LtMap::new().insert(Value::Keyword(current), Value::Int(50))),
+            })
+            .collect();
+
+        for mode in [ExecutionMode::Debug, ExecutionMode::Play] {
+            let executor = TickExecutor::new().with_mode(mode);
+            group.bench_with_input(
+                BenchmarkId::new(format!("{mode:?}_mode_inputs"), input_count),
+                &(world.clone(), inputs.clone(), executor),
+                |b, (w, inp, ex)| {
+                    b.iter(|| {
+                        let mut executor = ex.clone();
+                        let result = executor.tick(w.clone(), inp);
+                        black_box(result)
+                    })
+                },
+            );
+        }
+    }
+
     // Tick with rules (no-op execution)
     for entity_count in [100, 500, 1_000] {
         let mut world = create_world_with_entities(entity_count);
@@ -1172,6 +1223,9 @@ fn bench_tick_orchestration(c: &mut Criterion) {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         decl.on_violation = ConstraintViolation::Warn;
 
@@ -1218,6 +1272,9 @@ fn bench_tick_orchestration(c: &mut Criterion) {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         decl.on_violation = ConstraintViolation::Warn;
         let constraint = ConstraintCompiler::compile(&decl, world.interner_mut()).unwrap();
@@ -1278,7 +1335,7 @@ fn bench_throughput(c: &mut Criterion) {
         &(world.clone(), compiled),
         |b, (w, p)| {
             b.iter(|| {
-                let count = PatternMatcher::match_pattern(p, w).len();
+                let count = PatternMatcher::match_pattern(p, w).unwrap().len();
                 black_box(count)
             })
         },
@@ -1311,7 +1368,7 @@ fn bench_throughput(c: &mut Criterion) {
         b.iter(|| {
             let mut engine = ProductionRuleEngine::new();
             engine.begin_tick();
-            let activations = engine.find_activations(r, w);
+            let activations = engine.find_activations(r, w).unwrap();
             black_box(activations.len())
         })
     });
@@ -1560,7 +1617,7 @@ fn bench_pattern_edge_cases(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -1591,7 +1648,7 @@ fn bench_pattern_edge_cases(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -1612,7 +1669,7 @@ fn bench_pattern_edge_cases(c: &mut Criterion) {
         &(world.clone(), selective_compiled),
         |b, (w, p)| {
             b.iter(|| {
-                let results = PatternMatcher::match_pattern(p, w);
+                let results = PatternMatcher::match_pattern(p, w).unwrap();
                 black_box(results.len())
             })
         },
@@ -1627,7 +1684,7 @@ fn bench_pattern_edge_cases(c: &mut Criterion) {
         &(world.clone(), broad_compiled),
         |b, (w, p)| {
             b.iter(|| {
-                let results = PatternMatcher::match_pattern(p, w);
+                let results = PatternMatcher::match_pattern(p, w).unwrap();
                 black_box(results.len())
             })
         },
@@ -1642,7 +1699,7 @@ fn bench_pattern_edge_cases(c: &mut Criterion) {
         &(world, full_compiled),
         |b, (w, p)| {
             b.iter(|| {
-                let results = PatternMatcher::match_pattern(p, w);
+                let results = PatternMatcher::match_pattern(p, w).unwrap();
                 black_box(results.len())
             })
         },
@@ -1803,7 +1860,7 @@ fn bench_rule_priority(c: &mut Criterion) {
                 b.iter(|| {
                     let mut engine = ProductionRuleEngine::new();
                     engine.begin_tick();
-                    let activations = engine.find_activations(r, w);
+                    let activations = engine.find_activations(r, w).unwrap();
                     black_box(activations.len())
                 })
             },
@@ -1841,7 +1898,7 @@ fn bench_rule_priority(c: &mut Criterion) {
                 b.iter(|| {
                     let mut engine = ProductionRuleEngine::new();
                     engine.begin_tick();
-                    let activations = engine.find_activations(r, w);
+                    let activations = engine.find_activations(r, w).unwrap();
                     black_box(activations.len())
                 })
             },
@@ -1873,7 +1930,7 @@ fn bench_rule_priority(c: &mut Criterion) {
             b.iter(|| {
                 let mut engine = ProductionRuleEngine::new();
                 engine.begin_tick();
-                let activations = engine.find_activations(r, w);
+                let activations = engine.find_activations(r, w).unwrap();
                 black_box(activations.len())
             })
         },
@@ -1895,7 +1952,7 @@ fn bench_rule_priority(c: &mut Criterion) {
         b.iter(|| {
             let mut engine = ProductionRuleEngine::new();
             engine.begin_tick();
-            let activations = engine.find_activations(r, w);
+            let activations = engine.find_activations(r, w).unwrap();
             black_box(activations.len())
         })
     });
@@ -1922,8 +1979,8 @@ fn bench_rule_priority(c: &mut Criterion) {
                     engine.begin_tick();
 
                     // Find activations twice - second time should see same results
-                    let act1 = engine.find_activations(r, w);
-                    let act2 = engine.find_activations(r, w);
+                    let act1 = engine.find_activations(r, w).unwrap();
+                    let act2 = engine.find_activations(r, w).unwrap();
                     black_box((act1.len(), act2.len()))
                 })
             },
@@ -109,6 +109,9 @@ fn make_pattern(clauses: Vec<DeclClause>) -> DeclPattern {
     DeclPattern {
         clauses,
         negations: vec![],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     }
 }
 
@@ -117,7 +120,13 @@ fn make_pattern_with_negations(
     clauses: Vec<DeclClause>,
     negations: Vec<DeclClause>,
 ) -> DeclPattern {
-    DeclPattern { clauses, negations }
+    DeclPattern {
+        clauses,
+        negations,
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
+    }
 }
 
 /// Helper to create a pattern clause with default span.
@@ -141,6 +150,7 @@ fn make_query(pattern: DeclPattern, return_var: &str) -> QueryDecl {
         order_by: vec![],
         limit: None,
         return_expr: Some(Ast::Symbol(return_var.to_string(), Span::default())),
+        suppress: vec![],
         span: Span::default(),
     }
 }
@@ -167,7 +177,7 @@ fn bench_scale_pattern_matching(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -193,7 +203,7 @@ fn bench_scale_pattern_matching(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -216,7 +226,7 @@ fn bench_scale_pattern_matching(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -239,7 +249,7 @@ fn bench_scale_pattern_matching(c: &mut Criterion) {
             &(world, compiled),
             |b, (w, p)| {
                 b.iter(|| {
-                    let results = PatternMatcher::match_pattern(p, w);
+                    let results = PatternMatcher::match_pattern(p, w).unwrap();
                     black_box(results.len())
                 })
             },
@@ -275,7 +285,7 @@ fn bench_scale_rule_engine(c: &mut Criterion) {
                 b.iter(|| {
                     let mut engine = ProductionRuleEngine::new();
                     engine.begin_tick();
-                    let activations = engine.find_activations(r, w);
+                    let activations = engine.find_activations(r, w).unwrap();
                     black_box(activations.len())
                 })
             },
@@ -309,7 +319,7 @@ fn bench_scale_rule_engine(c: &mut Criterion) {
                 b.iter(|| {
                     let mut engine = ProductionRuleEngine::new();
                     engine.begin_tick();
-                    let activations = engine.find_activations(r, w);
+                    let activations = engine.find_activations(r, w).unwrap();
                     black_box(activations.len())
                 })
             },
@@ -343,7 +353,7 @@ fn bench_scale_rule_engine(c: &mut Criterion) {
                 b.iter(|| {
                     let mut engine = ProductionRuleEngine::new();
                     engine.begin_tick();
-                    let activations = engine.find_activations(r, w);
+                    let activations = engine.find_activations(r, w).unwrap();
                     black_box(activations.len())
                 })
             },
@@ -378,7 +388,7 @@ fn bench_scale_rule_engine(c: &mut Criterion) {
                 b.iter(|| {
                     let mut engine = ProductionRuleEngine::new();
                     engine.begin_tick();
-                    let activations = engine.find_activations(r, w);
+                    let activations = engine.find_activations(r, w).unwrap();
                     black_box(activations.len())
                 })
             },
@@ -597,6 +607,9 @@ fn bench_scale_tick(c: &mut Criterion) {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         decl.on_violation = ConstraintViolation::Warn;
 
@@ -640,6 +653,9 @@ fn bench_scale_tick(c: &mut Criterion) {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         decl.on_violation = ConstraintViolation::Warn;
         let constraint = ConstraintCompiler::compile(&decl, world.interner_mut()).unwrap();
@@ -686,6 +702,9 @@ fn bench_scale_constraints(c: &mut Criterion) {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         decl.on_violation = ConstraintViolation::Warn;
 
@@ -696,7 +715,7 @@ fn bench_scale_constraints(c: &mut Criterion) {
         group.bench_with_input(
             BenchmarkId::new("single_constraint", entity_count),
             &(world, checker),
-            |b, (w, ch)| b.iter(|| black_box(ch.check_all(w))),
+            |b, (w, ch)| b.iter(|| black_box(ch.check_all(w).unwrap())),
         );
     }
 
@@ -723,6 +742,9 @@ fn bench_scale_constraints(c: &mut Criterion) {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             };
             decl.on_violation = ConstraintViolation::Warn;
             ConstraintCompiler::compile(&decl, world.interner_mut()).unwrap()
@@ -734,7 +756,7 @@ fn bench_scale_constraints(c: &mut Criterion) {
         group.bench_with_input(
             BenchmarkId::new("5_constraints", entity_count),
             &(world, checker),
-            |b, (w, ch)| b.iter(|| black_box(ch.check_all(w))),
+            |b, (w, ch)| b.iter(|| black_box(ch.check_all(w).unwrap())),
         );
     }
 
@@ -3,7 +3,12 @@
 //! Constraints are invariants checked after rule execution:
 //! - Pattern-based matching to find entities to check
 //! - Expression evaluation for check conditions
-//! - Rollback or warn on violation
+//! - Rollback, warn, or score on violation
+//!
+//! Scoring constraints (`:on-violation :score`) never reject a tick. Each
+//! violation adds the constraint's `:penalty` (default 1.0) to the world
+//! score, so generators can compare candidate worlds instead of discarding
+//! them.
 
 use std::collections::HashSet;
 
@@ -37,6 +42,8 @@ pub struct CompiledConstraint {
     pub checks: Vec<CompiledExpr>,
     /// Behavior on violation
     pub on_violation: ConstraintViolation,
+    /// Penalty expression for scoring constraints (defaults to 1.0)
+    pub penalty: Option<CompiledExpr>,
 }
 
 // =============================================================================
@@ -52,8 +59,10 @@ pub struct ViolationDetails {
     pub bindings: Vec<(String, Value)>,
     /// Which check expression failed (index)
     pub failed_check_index: usize,
-    /// Whether this should cause rollback or just warn
+    /// Whether this should cause rollback, warn, or add to the score
     pub behavior: ConstraintViolation,
+    /// Penalty added to the world score (zero unless `behavior` is `Score`)
+    pub penalty: f64,
 }
 
 /// Result of constraint checking.
@@ -63,6 +72,8 @@ pub struct ConstraintResult {
     rollback: Vec<ViolationDetails>,
     /// Violations that only warn
     warn: Vec<ViolationDetails>,
+    /// Violations that add to the world score
+    scored: Vec<ViolationDetails>,
 }
 
 impl ConstraintResult {
@@ -81,7 +92,7 @@ impl ConstraintResult {
     /// Returns true if there are no violations of any kind.
     #[must_use]
     pub fn is_clean(&self) -> bool {
-        self.rollback.is_empty() && self.warn.is_empty()
+        self.rollback.is_empty() && self.warn.is_empty() && self.scored.is_empty()
     }
 
     /// Returns violations that should cause rollback.
@@ -95,6 +106,21 @@ impl ConstraintResult {
     pub fn warn_violations(&self) -> &[ViolationDetails] {
         &self.warn
     }
+
+    /// Returns violations that add to the world score.
+    #[must_use]
+    pub fn scored_violations(&self) -> &[ViolationDetails] {
+        &self.scored
+    }
+
+    /// Returns the world score: the sum of all scoring penalties.
+    ///
+    /// Lower is better; a world that satisfies every scoring constraint
+    /// scores 0.0.
+    #[must_use]
+    pub fn score(&self) -> f64 {
+        self.scored.iter().fold(0.0, |score, v| score + v.penalty)
+    }
 }
 
 // =============================================================================
@@ -118,7 +144,7 @@ impl ConstraintCompiler {
 
         // Collect all variable names from pattern for binding lookup
         let mut binding_vars = Vec::new();
-        for clause in &pattern.clauses {
+        for clause in pattern.binding_clauses() {
             if !binding_vars.contains(&clause.entity_var) {
                 binding_vars.push(clause.entity_var.clone());
             }
@@ -165,6 +191,13 @@ impl ConstraintCompiler {
             .map(|ast| compile_expression_with_interner(ast, &binding_vars, interner.clone()))
             .collect::<Result<Vec<_>>>()?;
 
+        // Compile the penalty expression
+        let penalty = decl
+            .penalty
+            .as_ref()
+            .map(|ast| compile_expression_with_interner(ast, &binding_vars, interner.clone()))
+            .transpose()?;
+
         Ok(CompiledConstraint {
             name,
             pattern,
@@ -174,6 +207,7 @@ impl ConstraintCompiler {
             guards,
             checks,
             on_violation: decl.on_violation,
+            penalty,
         })
     }
 
@@ -227,6 +261,14 @@ impl ConstraintChecker {
         self.constraints.push(constraint);
     }
 
+    /// Removes the constraint with the given name. Returns false if there
+    /// was none.
+    pub fn remove_constraint(&mut self, name: KeywordId) -> bool {
+        let before = self.constraints.len();
+        self.constraints.retain(|c| c.name != name);
+        self.constraints.len() != before
+    }
+
     /// Returns the constraints.
     #[must_use]
     pub fn constraints(&self) -> &[CompiledConstraint] {
@@ -237,14 +279,18 @@ impl ConstraintChecker {
     ///
     /// Returns `ConstraintResult::Ok` if all constraints pass,
     /// or the appropriate violation result otherwise.
-    #[must_use]
-    pub fn check_all(&self, world: &World) -> ConstraintResult {
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a constraint's pattern fails to match.
+    pub fn check_all(&self, world: &World) -> Result<ConstraintResult> {
         let mut rollback_violations = Vec::new();
         let mut warn_violations = Vec::new();
+        let mut scored_violations = Vec::new();
 
         for constraint in &self.constraints {
             // Find all matches for this constraint's pattern
-            let matches = PatternMatcher::match_pattern(&constraint.pattern, world);
+            let matches = PatternMatcher::match_pattern(&constraint.pattern, world)?;
 
             'binding_loop: for bindings in matches {
                 // Convert bindings to value vector for VM
@@ -301,6 +347,10 @@ impl ConstraintChecker {
 
                     if result != Value::Bool(true) {
                         // Check failed - record violation
+                        let penalty = match constraint.on_violation {
+                            ConstraintViolation::Score => Self::penalty(constraint, &values),
+                            _ => 0.0,
+                        };
                         let violation = ViolationDetails {
                             constraint: constraint.name,
                             bindings: constraint
@@ -311,11 +361,13 @@ impl ConstraintChecker {
                                 .collect(),
                             failed_check_index: check_index,
                             behavior: constraint.on_violation,
+                            penalty,
                         };
 
                         match constraint.on_violation {
                             ConstraintViolation::Rollback => rollback_violations.push(violation),
                             ConstraintViolation::Warn => warn_violations.push(violation),
+                            ConstraintViolation::Score => scored_violations.push(violation),
                         }
 
                         // Stop checking further checks for this binding
@@ -325,9 +377,37 @@ impl ConstraintChecker {
             }
         }
 
-        ConstraintResult {
+        Ok(ConstraintResult {
             rollback: rollback_violations,
             warn: warn_violations,
+            scored: scored_violations,
+        })
+    }
+
+    /// Computes the world score: the sum of penalties from violated scoring
+    /// constraints. Rollback and warn constraints do not contribute.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a constraint's pattern fails to match.
+    pub fn score(&self, world: &World) -> Result<f64> {
+        Ok(self.check_all(world)?.score())
+    }
+
+    /// Evaluates a scoring constraint's penalty for one violation.
+    ///
+    /// Penalties that fail to evaluate to a number count as the default 1.0.
+    fn penalty(constraint: &CompiledConstraint, values: &[Value]) -> f64 {
+        let Some(expr) = &constraint.penalty else {
+            return 1.0;
+        };
+        let mut vm = Vm::new();
+        vm.set_bindings(values.to_vec());
+        match vm.execute_bytecode(&expr.code, &expr.constants) {
+            #[allow(clippy::cast_precision_loss)]
+            Ok(Value::Int(n)) => n as f64,
+            Ok(Value::Float(f)) => f,
+            _ => 1.0,
         }
     }
 
@@ -364,7 +444,7 @@ impl ConstraintChecker {
     pub fn monitored_components(&self) -> HashSet<KeywordId> {
         self.constraints
             .iter()
-            .flat_map(|c| c.pattern.clauses.iter().map(|cl| cl.component))
+            .flat_map(|c| c.pattern.binding_clauses().map(|cl| cl.component))
             .collect()
     }
 }
@@ -396,6 +476,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         decl.on_violation = ConstraintViolation::Rollback;
 
@@ -419,6 +502,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         // Add a check expression: (>= ?hp 0)
         decl.checks.push(Ast::List(
@@ -442,7 +528,7 @@ mod tests {
         let world = World::new(42);
         let checker = ConstraintChecker::new();
 
-        let result = checker.check_all(&world);
+        let result = checker.check_all(&world).unwrap();
         assert!(result.is_ok());
     }
 
@@ -467,13 +553,16 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         let compiled = ConstraintCompiler::compile(&decl, &mut interner).unwrap();
 
         let checker = ConstraintChecker::new().with_constraints(vec![compiled]);
 
         // No entities match, so constraint trivially passes
-        let result = checker.check_all(&world);
+        let result = checker.check_all(&world).unwrap();
         assert!(result.is_ok());
     }
 
@@ -491,6 +580,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let mut decl2 = ConstraintDecl::new("c2", Span::default());
@@ -510,6 +602,9 @@ mod tests {
                 },
             ],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let c1 = ConstraintCompiler::compile(&decl1, &mut interner).unwrap();
@@ -538,6 +633,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let mut decl2 = ConstraintDecl::new("second-constraint", Span::default());
@@ -549,6 +647,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let mut decl3 = ConstraintDecl::new("third-constraint", Span::default());
@@ -560,6 +661,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let c1 = ConstraintCompiler::compile(&decl1, &mut interner).unwrap();
@@ -617,6 +721,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         // Check: (= ?s true) - the bound value must be true
         decl.checks.push(Ast::List(
@@ -633,7 +740,7 @@ mod tests {
         let checker = ConstraintChecker::new().with_constraints(vec![compiled]);
 
         // Score is true, so constraint passes
-        let result = checker.check_all(&world);
+        let result = checker.check_all(&world).unwrap();
         assert!(result.is_ok());
     }
 
@@ -669,6 +776,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         // Check: (= (get ?s :active) true)
         decl.checks.push(Ast::List(
@@ -692,7 +802,7 @@ mod tests {
         let checker = ConstraintChecker::new().with_constraints(vec![compiled]);
 
         // Active is false, but constraint requires true, so it fails with rollback
-        let result = checker.check_all(&world);
+        let result = checker.check_all(&world).unwrap();
         assert!(!result.is_ok());
         assert_eq!(result.rollback_violations().len(), 1);
         assert_eq!(result.rollback_violations()[0].failed_check_index, 0);
@@ -730,6 +840,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         // Check: (= (get ?s :valid) true)
         decl.checks.push(Ast::List(
@@ -753,7 +866,7 @@ mod tests {
         let checker = ConstraintChecker::new().with_constraints(vec![compiled]);
 
         // Constraint fails but is_ok returns true (only rollback makes is_ok false)
-        let result = checker.check_all(&world);
+        let result = checker.check_all(&world).unwrap();
         assert!(result.is_ok()); // Warn doesn't block
         assert_eq!(result.warn_violations().len(), 1);
     }
@@ -781,11 +894,11 @@ mod tests {
         world = world.register_component(schema).unwrap();
 
         // Spawn an NPC with active = false, is_player = false
-        let (world, _npc) = world.spawn(&LtMap::new()).unwrap();
+        let (world, npc) = world.spawn(&LtMap::new()).unwrap();
         let mut comp = LtMap::new();
         comp = comp.insert(Value::Keyword(active_field), Value::Bool(false));
         comp = comp.insert(Value::Keyword(is_player_field), Value::Bool(false));
-        let world = world.set(_npc, status, Value::Map(comp)).unwrap();
+        let world = world.set(npc, status, Value::Map(comp)).unwrap();
 
         // Spawn a player with active = true, is_player = true
         let (world, player_entity) = world.spawn(&LtMap::new()).unwrap();
@@ -806,6 +919,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         // Guard: only check if is_player is true
         decl.guards.push(Ast::List(
@@ -846,7 +962,7 @@ mod tests {
 
         // NPC is filtered out by guard (is_player = false),
         // player is active, so constraint passes
-        let result = checker.check_all(&world);
+        let result = checker.check_all(&world).unwrap();
         assert!(result.is_ok());
     }
 
@@ -890,6 +1006,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         // Check 1: (= (get ?c :a) true) - will pass
         decl.checks.push(Ast::List(
@@ -929,7 +1048,7 @@ mod tests {
         let checker = ConstraintChecker::new().with_constraints(vec![compiled]);
 
         // First check passes (a=true), second check fails (b=false)
-        let result = checker.check_all(&world);
+        let result = checker.check_all(&world).unwrap();
         assert!(!result.is_ok());
         assert_eq!(result.rollback_violations().len(), 1);
         assert_eq!(result.rollback_violations()[0].failed_check_index, 1); // Second check failed
@@ -974,6 +1093,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         // Let: sum = (+ (get ?p :x) (get ?p :y))
         decl.bindings.push((
@@ -1016,7 +1138,7 @@ mod tests {
         let checker = ConstraintChecker::new().with_constraints(vec![compiled]);
 
         // sum = 3 + 4 = 7 < 10, so constraint passes
-        let result = checker.check_all(&world);
+        let result = checker.check_all(&world).unwrap();
         assert!(result.is_ok());
     }
 
@@ -1052,6 +1174,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         decl1.checks.push(Ast::List(
             vec![
@@ -1073,6 +1198,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         decl2.checks.push(Ast::List(
             vec![
@@ -1089,7 +1217,7 @@ mod tests {
         let checker = ConstraintChecker::new().with_constraints(vec![c1, c2]);
 
         // Both constraints should fail
-        let result = checker.check_all(&world);
+        let result = checker.check_all(&world).unwrap();
         assert!(!result.is_ok()); // Rollback makes is_ok false
         assert_eq!(result.rollback_violations().len(), 1);
         assert_eq!(result.warn_violations().len(), 1);
@@ -1121,6 +1249,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         // No checks added
         decl.on_violation = ConstraintViolation::Rollback;
@@ -1129,7 +1260,7 @@ mod tests {
         let checker = ConstraintChecker::new().with_constraints(vec![compiled]);
 
         // No checks means always passes
-        let result = checker.check_all(&world);
+        let result = checker.check_all(&world).unwrap();
         assert!(result.is_ok());
     }
 
@@ -1175,6 +1306,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         decl.checks.push(Ast::List(
             vec![
@@ -1197,8 +1331,55 @@ mod tests {
         let checker = ConstraintChecker::new().with_constraints(vec![compiled]);
 
         // Only e2 violates, so we should have exactly 1 violation
-        let result = checker.check_all(&world);
+        let result = checker.check_all(&world).unwrap();
         assert!(!result.is_ok());
         assert_eq!(result.rollback_violations().len(), 1);
     }
+
+    #[test]
+    fn scoring_constraints_sum_penalties_without_rollback() {
+        use longtable_foundation::LtMap;
+
+        let mut world = World::new(42);
+        let room = world.interner_mut().intern_keyword("room");
+        world = world
+            .register_component(ComponentSchema::tag(room))
+            .unwrap();
+        for _ in 0..3 {
+            let (w, entity) = world.spawn(&LtMap::new()).unwrap();
+            world = w.set(entity, room, Value::Bool(true)).unwrap();
+        }
+
+        let scored = |name: &str, penalty: Option<Ast>| {
+            let mut decl = ConstraintDecl::new(name, Span::default());
+            decl.pattern = DeclPattern {
+                clauses: vec![DeclClause {
+                    entity_var: "r".to_string(),
+                    component: "room".to_string(),
+                    value: PatternValue::Variable("t".to_string()),
+                    span: Span::default(),
+                }],
+                negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
+            };
+            decl.checks.push(Ast::Bool(false, Span::default()));
+            decl.on_violation = ConstraintViolation::Score;
+            decl.penalty = penalty;
+            decl
+        };
+        let weighted = scored("weighted", Some(Ast::Float(2.5, Span::default())));
+        let unweighted = scored("unweighted", None);
+        let compiled =
+            ConstraintCompiler::compile_all(&[weighted, unweighted], world.interner_mut()).unwrap();
+        let checker = ConstraintChecker::new().with_constraints(compiled);
+
+        let result = checker.check_all(&world).unwrap();
+        assert!(result.is_ok());
+        assert!(!result.is_clean());
+        assert_eq!(result.scored_violations().len(), 6);
+        assert!((result.score() - 10.5).abs() < f64::EPSILON);
+        assert!((checker.score(&world).unwrap() - 10.5).abs() < f64::EPSILON);
+    }
 }
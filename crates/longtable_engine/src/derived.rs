@@ -6,6 +6,20 @@
 //! - Lazy evaluation with caching
 //! - Automatic invalidation when dependencies change
 //! - Cycle detection
+//!
+//! A derived depends on the components its pattern reads, and on those of
+//! any other derived its pattern reads in turn. Each cached value remembers
+//! the [`World::component_version`] of those inputs when it was computed,
+//! so it survives from tick to tick until one of them is written:
+//!
+//! ```text
+//! (derived: wounded  :where [[?self :health ?hp]] ...)      inputs: :health
+//! (derived: fragile  :where [[?self :wounded ?w]
+//!                            [?self :armor ?a]] ...)        inputs: :health :armor
+//! ```
+//!
+//! Writing `:armor` recomputes `fragile` but not `wounded`. Deriveds whose
+//! patterns read each other in a circle are rejected when compiled.
 
 use std::collections::{HashMap, HashSet};
 
@@ -60,7 +74,7 @@ impl DerivedCompiler {
 
         // Collect dependencies from pattern
         let dependencies: HashSet<KeywordId> =
-            pattern.clauses.iter().map(|c| c.component).collect();
+            pattern.binding_clauses().map(|c| c.component).collect();
 
         // Collect all binding variables
         let binding_vars: Vec<String> = decl
@@ -97,12 +111,77 @@ impl DerivedCompiler {
     /// Compile multiple derived components.
     ///
     /// # Errors
-    /// Returns an error if any compilation fails.
+    /// Returns an error if any compilation fails, or if the deriveds depend
+    /// on each other in a cycle.
     pub fn compile_all(
         decls: &[DerivedDecl],
         interner: &mut Interner,
     ) -> Result<Vec<CompiledDerived>> {
-        decls.iter().map(|d| Self::compile(d, interner)).collect()
+        let deriveds = decls
+            .iter()
+            .map(|d| Self::compile(d, interner))
+            .collect::<Result<Vec<_>>>()?;
+        Self::check_cycles(&deriveds, interner)?;
+        Ok(deriveds)
+    }
+
+    /// Checks that no derived depends, through other deriveds, on itself.
+    ///
+    /// # Errors
+    /// Returns an error naming the deriveds in the cycle.
+    pub fn check_cycles(deriveds: &[CompiledDerived], interner: &Interner) -> Result<()> {
+        let index: HashMap<KeywordId, usize> = deriveds
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (d.name, i))
+            .collect();
+        let reads = |i: usize| {
+            let mut next: Vec<usize> = deriveds[i]
+                .dependencies
+                .iter()
+                .filter_map(|c| index.get(c).copied())
+                .collect();
+            next.sort_unstable();
+            next
+        };
+
+        // Depth-first, keeping the current path; meeting a derived already
+        // on it closes a cycle
+        let mut done = vec![false; deriveds.len()];
+        for start in 0..deriveds.len() {
+            if done[start] {
+                continue;
+            }
+            let mut path = vec![start];
+            let mut pending = vec![reads(start)];
+            while let Some(next) = pending.last_mut() {
+                let Some(i) = next.pop() else {
+                    if let Some(finished) = path.pop() {
+                        done[finished] = true;
+                    }
+                    pending.pop();
+                    continue;
+                };
+                if let Some(at) = path.iter().position(|&p| p == i) {
+                    let names: Vec<String> = path[at..]
+                        .iter()
+                        .chain([&i])
+                        .map(|&p| {
+                            format!(":{}", interner.get_keyword(deriveds[p].name).unwrap_or("?"))
+                        })
+                        .collect();
+                    return Err(Error::new(ErrorKind::Cycle(format!(
+                        "derived components depend on each other in a cycle: {}",
+                        names.join(" -> ")
+                    ))));
+                }
+                if !done[i] {
+                    path.push(i);
+                    pending.push(reads(i));
+                }
+            }
+        }
+        Ok(())
     }
 }
 
@@ -117,6 +196,8 @@ struct CachedValue {
     value: Value,
     /// Version/tick when this was computed
     version: u64,
+    /// The component versions of its inputs when this was computed
+    inputs: Vec<(KeywordId, u64)>,
 }
 
 /// Cache for derived component values.
@@ -155,13 +236,46 @@ impl DerivedCache {
             .map(|c| &c.value)
     }
 
+    /// Gets a cached value if still valid and none of the inputs it was
+    /// computed from have been written in `world` since.
+    #[must_use]
+    pub fn get_current(
+        &self,
+        entity: EntityId,
+        derived: KeywordId,
+        world: &World,
+    ) -> Option<&Value> {
+        self.cache
+            .get(&(entity, derived))
+            .filter(|c| c.version == self.version)
+            .filter(|c| {
+                c.inputs
+                    .iter()
+                    .all(|(component, version)| world.component_version(*component) == *version)
+            })
+            .map(|c| &c.value)
+    }
+
     /// Stores a computed value in the cache.
     pub fn set(&mut self, entity: EntityId, derived: KeywordId, value: Value) {
+        self.set_with_inputs(entity, derived, value, Vec::new());
+    }
+
+    /// Stores a computed value in the cache along with the versions of the
+    /// components it was computed from (see [`Self::get_current`]).
+    pub fn set_with_inputs(
+        &mut self,
+        entity: EntityId,
+        derived: KeywordId,
+        value: Value,
+        inputs: Vec<(KeywordId, u64)>,
+    ) {
         self.cache.insert(
             (entity, derived),
             CachedValue {
                 value,
                 version: self.version,
+                inputs,
             },
         );
     }
@@ -171,19 +285,25 @@ impl DerivedCache {
         self.cache.clear();
     }
 
-    /// Invalidates cached values that depend on a specific component.
+    /// Invalidates cached values that depend on a specific component,
+    /// directly or through other deriveds.
     /// Returns the number of invalidated entries.
     pub fn invalidate_by_component(
         &mut self,
         component: KeywordId,
         deriveds: &[CompiledDerived],
     ) -> usize {
-        // Find which derived components depend on this component
-        let affected: HashSet<KeywordId> = deriveds
-            .iter()
-            .filter(|d| d.dependencies.contains(&component))
-            .map(|d| d.name)
-            .collect();
+        // Find which derived components depend on this component, then
+        // which depend on those
+        let mut affected: HashSet<KeywordId> = HashSet::new();
+        let mut changed = vec![component];
+        while let Some(component) = changed.pop() {
+            for d in deriveds {
+                if d.dependencies.contains(&component) && affected.insert(d.name) {
+                    changed.push(d.name);
+                }
+            }
+        }
 
         // Remove affected cached values
         let before = self.cache.len();
@@ -202,10 +322,14 @@ impl DerivedCache {
 pub struct DerivedEvaluator {
     /// Compiled derived components
     deriveds: Vec<CompiledDerived>,
+    /// The components each derived reads, directly or through other deriveds
+    inputs: HashMap<KeywordId, Vec<KeywordId>>,
     /// Value cache
     cache: DerivedCache,
     /// Max evaluation depth for cycle detection
     max_depth: usize,
+    /// Values computed, rather than found in the cache, this tick
+    recomputed: usize,
 }
 
 impl Default for DerivedEvaluator {
@@ -220,18 +344,51 @@ impl DerivedEvaluator {
     pub fn new() -> Self {
         Self {
             deriveds: Vec::new(),
+            inputs: HashMap::new(),
             cache: DerivedCache::new(),
             max_depth: 100,
+            recomputed: 0,
         }
     }
 
     /// Creates an evaluator with the given derived components.
     #[must_use]
     pub fn with_deriveds(mut self, deriveds: Vec<CompiledDerived>) -> Self {
+        self.inputs = deriveds
+            .iter()
+            .map(|d| (d.name, Self::collect_inputs(d, &deriveds)))
+            .collect();
         self.deriveds = deriveds;
+        self.cache.clear();
         self
     }
 
+    /// Returns the stored components `derived` reads, following the
+    /// dependencies of any other deriveds it reads.
+    fn collect_inputs(derived: &CompiledDerived, deriveds: &[CompiledDerived]) -> Vec<KeywordId> {
+        let mut seen: HashSet<KeywordId> = HashSet::from([derived.name]);
+        let mut inputs = Vec::new();
+        let mut pending: Vec<KeywordId> = derived.dependencies.iter().copied().collect();
+        while let Some(component) = pending.pop() {
+            if !seen.insert(component) {
+                continue;
+            }
+            match deriveds.iter().find(|d| d.name == component) {
+                Some(other) => pending.extend(other.dependencies.iter().copied()),
+                None => inputs.push(component),
+            }
+        }
+        inputs.sort_by_key(|k| k.index());
+        inputs
+    }
+
+    /// Returns the stored components `derived` depends on, directly or
+    /// through other deriveds.
+    #[must_use]
+    pub fn inputs(&self, derived: KeywordId) -> &[KeywordId] {
+        self.inputs.get(&derived).map_or(&[], Vec::as_slice)
+    }
+
     /// Sets the max evaluation depth.
     #[must_use]
     pub fn with_max_depth(mut self, max: usize) -> Self {
@@ -239,9 +396,19 @@ impl DerivedEvaluator {
         self
     }
 
-    /// Invalidates the cache (call at start of tick).
+    /// Starts a tick (call at start of tick).
+    ///
+    /// Cached values carry over from earlier ticks; each is recomputed only
+    /// once one of its inputs has been written.
     pub fn begin_tick(&mut self) {
-        self.cache.advance_version();
+        self.recomputed = 0;
+    }
+
+    /// Returns how many values were computed, rather than found in the
+    /// cache, since the tick began.
+    #[must_use]
+    pub fn recomputed(&self) -> usize {
+        self.recomputed
     }
 
     /// Gets a derived component value for an entity.
@@ -275,7 +442,7 @@ impl DerivedEvaluator {
         }
 
         // Check cache first
-        if let Some(value) = self.cache.get(entity, derived) {
+        if let Some(value) = self.cache.get_current(entity, derived, world) {
             return Ok(Some(value.clone()));
         }
 
@@ -286,7 +453,7 @@ impl DerivedEvaluator {
         };
 
         // Match the pattern to find bindings for this entity
-        let matches = PatternMatcher::match_pattern(&derived_def.pattern, world);
+        let matches = PatternMatcher::match_pattern(&derived_def.pattern, world)?;
 
         // Find a match where the for_var is bound to our entity
         let bindings = matches.into_iter().find(|b| {
@@ -302,8 +469,15 @@ impl DerivedEvaluator {
         // Full implementation would evaluate the bytecode with the bindings
         let value = Value::Nil;
 
-        // Cache the result
-        self.cache.set(entity, derived, value.clone());
+        // Cache the result, with the versions of the inputs it was read from
+        let inputs = self
+            .inputs(derived)
+            .iter()
+            .map(|&component| (component, world.component_version(component)))
+            .collect();
+        self.cache
+            .set_with_inputs(entity, derived, value.clone(), inputs);
+        self.recomputed += 1;
 
         Ok(Some(value))
     }
@@ -421,6 +595,79 @@ mod tests {
         assert!(cache.get(entity, derived_id).is_none());
     }
 
+    /// A derived for `?self` whose pattern reads each of `components`.
+    fn reading(name: &str, components: &[&str]) -> DerivedDecl {
+        let mut decl =
+            DerivedDecl::new(name, "self", Ast::Int(1, Span::default()), Span::default());
+        for component in components {
+            decl.pattern
+                .clauses
+                .push(longtable_language::declaration::PatternClause {
+                    entity_var: "self".to_string(),
+                    component: (*component).to_string(),
+                    value: longtable_language::declaration::PatternValue::Wildcard,
+                    span: Span::default(),
+                });
+        }
+        decl
+    }
+
+    #[test]
+    fn compile_all_rejects_cycles() {
+        let mut interner = Interner::new();
+        let decls = [
+            reading("wounded", &["health"]),
+            reading("fragile", &["wounded", "brittle"]),
+            reading("brittle", &["fragile"]),
+        ];
+
+        let err = DerivedCompiler::compile_all(&decls, &mut interner).unwrap_err();
+        assert!(
+            err.to_string().contains(":fragile -> :brittle -> :fragile"),
+            "{err}"
+        );
+        assert!(DerivedCompiler::compile_all(&decls[..2], &mut interner).is_ok());
+    }
+
+    #[test]
+    fn cached_values_survive_until_an_input_changes() {
+        let mut world = World::new(42);
+        let health = world.interner_mut().intern_keyword("health");
+        let armor = world.interner_mut().intern_keyword("armor");
+        let mut world = world
+            .register_component(ComponentSchema::tag(health))
+            .unwrap()
+            .register_component(ComponentSchema::tag(armor))
+            .unwrap();
+        let (w, entity) = world.spawn(&LtMap::new()).unwrap();
+        world = w.set(entity, health, Value::Bool(true)).unwrap();
+        world = world.set(entity, armor, Value::Bool(true)).unwrap();
+
+        let decls = [
+            reading("wounded", &["health"]),
+            reading("armored", &["armor"]),
+            reading("fragile", &["wounded", "armor"]),
+        ];
+        let deriveds = DerivedCompiler::compile_all(&decls, world.interner_mut()).unwrap();
+        let [wounded, armored, fragile] = [0, 1, 2].map(|i| deriveds[i].name);
+        let mut evaluator = DerivedEvaluator::new().with_deriveds(deriveds);
+        assert_eq!(evaluator.inputs(fragile), [health, armor]);
+
+        evaluator.begin_tick();
+        evaluator.get(entity, wounded, &world).unwrap();
+        evaluator.get(entity, armored, &world).unwrap();
+        assert_eq!(evaluator.recomputed(), 2);
+
+        // A new tick that writes only :armor recomputes only what reads it
+        evaluator.begin_tick();
+        world = world.set(entity, armor, Value::Bool(true)).unwrap();
+        evaluator.get(entity, wounded, &world).unwrap();
+        evaluator.get(entity, armored, &world).unwrap();
+        assert_eq!(evaluator.recomputed(), 1);
+        evaluator.get(entity, armored, &world).unwrap();
+        assert_eq!(evaluator.recomputed(), 1);
+    }
+
     #[test]
     fn evaluator_depth_limit() {
         let mut world = World::new(42);
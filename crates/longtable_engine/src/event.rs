@@ -0,0 +1,129 @@
+//! Events for Longtable.
+//!
+//! An event is a short-lived entity that records something that happened,
+//! such as a door opening. `(emit! :event/door-opened {:door ?d})` spawns an
+//! entity carrying the `:event/door-opened` component with the payload as
+//! its value, so rules can react to it with an ordinary pattern:
+//!
+//! ```clojure
+//! (rule: creak
+//!   :where [[:event/door-opened ?payload]]
+//!   :then [(say "The door creaks.")])
+//! ```
+//!
+//! Every event entity is also tagged with [`EVENT_MARKER`]. The tick executor
+//! drains (destroys) all pending events at the end of each tick, so an event
+//! is visible for the rest of the tick it was emitted in, or for the next tick
+//! if it was emitted between ticks.
+
+use longtable_foundation::{EntityId, KeywordId, LtMap, Result, Value};
+use longtable_storage::{ComponentSchema, World};
+
+/// Name of the tag component carried by every event entity.
+pub const EVENT_MARKER: &str = "longtable/event";
+
+/// Spawns an event entity of the given kind.
+///
+/// Component schemas for the marker and the event kind are registered on
+/// first use. A map payload is the event component's value as it stands;
+/// `nil` becomes an empty map, and any other value is wrapped as
+/// `{:value payload}`, like a single-field component.
+///
+/// # Errors
+/// Returns an error if `kind` names an existing component that is not an
+/// event.
+pub fn emit(world: World, kind: KeywordId, payload: Value) -> Result<(World, EntityId)> {
+    let payload = match payload {
+        Value::Map(map) => map,
+        Value::Nil => LtMap::new(),
+        other => LtMap::new().insert(Value::Keyword(KeywordId::VALUE), other),
+    };
+
+    let mut world = world;
+    let marker = world.interner_mut().intern_keyword(EVENT_MARKER);
+    if world.component_schema(marker).is_none() {
+        world = world.register_component(ComponentSchema::tag(marker))?;
+    }
+    if world.component_schema(kind).is_none() {
+        world = world.register_component(ComponentSchema::new(kind))?;
+    }
+
+    let components = LtMap::new()
+        .insert(Value::Keyword(marker), Value::Bool(true))
+        .insert(Value::Keyword(kind), Value::Map(payload));
+    world.spawn(&components)
+}
+
+/// Returns all event entities currently in the world.
+#[must_use]
+pub fn pending(world: &World) -> Vec<EntityId> {
+    world
+        .interner()
+        .lookup_keyword(EVENT_MARKER)
+        .map(|marker| world.with_component(marker).collect())
+        .unwrap_or_default()
+}
+
+/// Destroys all event entities, returning the new world and how many were drained.
+///
+/// # Errors
+/// Returns an error if an event entity cannot be destroyed.
+pub fn drain(world: World) -> Result<(World, usize)> {
+    let events = pending(&world);
+    let count = events.len();
+    let world = events
+        .into_iter()
+        .try_fold(world, |world, event| world.destroy(event))?;
+    Ok((world, count))
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emit_registers_schemas_and_spawns() {
+        let mut world = World::new(42);
+        let opened = world.interner_mut().intern_keyword("event/door-opened");
+        let door = world.interner_mut().intern_keyword("door");
+        let payload = Value::Map(LtMap::new().insert(Value::Keyword(door), Value::Int(7)));
+
+        let (world, event) = emit(world, opened, payload.clone()).unwrap();
+
+        assert_eq!(pending(&world), vec![event]);
+        assert_eq!(world.get(event, opened).unwrap(), Some(payload));
+    }
+
+    #[test]
+    fn drain_removes_only_events() {
+        let mut world = World::new(42);
+        let opened = world.interner_mut().intern_keyword("event/door-opened");
+        let (world, _) = world.spawn(&LtMap::new()).unwrap();
+        let (world, _) = emit(world, opened, Value::Nil).unwrap();
+        let (world, _) = emit(world, opened, Value::Nil).unwrap();
+        let before = world.entity_count();
+
+        let (world, drained) = drain(world).unwrap();
+
+        assert_eq!(drained, 2);
+        assert!(pending(&world).is_empty());
+        assert_eq!(world.entity_count(), before - 2);
+    }
+
+    #[test]
+    fn non_map_payloads_are_wrapped() {
+        let mut world = World::new(42);
+        let opened = world.interner_mut().intern_keyword("event/door-opened");
+        let (world, event) = emit(world, opened, Value::Int(1)).unwrap();
+        assert_eq!(
+            world.get(event, opened).unwrap(),
+            Some(Value::Map(
+                LtMap::new().insert(Value::Keyword(KeywordId::VALUE), Value::Int(1))
+            ))
+        );
+    }
+}
@@ -6,6 +6,8 @@
 //! - `QueryExecutor` - Query compilation and execution
 //! - `ConstraintChecker` - Constraint validation
 //! - `DerivedCache` - Derived component caching
+//! - `EffectMiddleware` - Effect interceptors
+//! - `SystemRegistry` - Host-provided Rust systems run each tick
 
 #![warn(missing_docs)]
 #![warn(clippy::all)]
@@ -17,11 +19,15 @@
 
 pub mod constraint;
 pub mod derived;
+pub mod event;
+pub mod middleware;
 pub mod pattern;
 pub mod provenance;
 pub mod query;
 pub mod rule;
+pub mod schedule;
 pub mod spike;
+pub mod system;
 pub mod tick;
 
 // Constraints
@@ -32,25 +38,38 @@ pub use constraint::{
 // Derived components
 pub use derived::{CompiledDerived, DerivedCache, DerivedCompiler, DerivedEvaluator};
 
+// Effect middleware
+pub use middleware::{EffectInterceptor, EffectMiddleware, Verdict};
+
 // Provenance tracking
-pub use provenance::{ProvenanceTracker, WriteRecord};
+pub use provenance::{LinkChange, LinkRecord, ProvenanceTracker, WriteRecord};
+
+// Scheduled effects
+pub use schedule::{Scheduler, Timer, TimerId};
+
+// Rust systems
+pub use system::{System, SystemAccess, SystemRegistry, SystemRun};
 
 // Tick orchestration
-pub use tick::{InputEvent, TickExecutor, TickResult};
+pub use tick::{DebugPoint, ExecutionMode, InputEvent, TickExecutor, TickPhase, TickResult};
 
 // Production pattern matching
 pub use pattern::{
-    Bindings, CompiledBinding, CompiledClause, CompiledPattern, EntityMatchResult, MatchFailure,
-    PatternCompiler, PatternMatcher,
+    Bindings, CompiledBinding, CompiledClause, CompiledDisjunction, CompiledNotJoin,
+    CompiledPattern, CompiledPredicate, EntityMatchResult, MatchFailure, PatternCompiler,
+    PatternMatcher, Proximity,
 };
 
 // Query system
-pub use query::{CompiledQuery, QueryCompiler, QueryExecutor, QueryWarning};
+pub use query::{
+    CompiledQuery, HIGH_FAN_OUT_THRESHOLD, PlanAccess, PlanStep, QueryCompiler, QueryExecutor,
+    QueryPlan, QueryWarning,
+};
 
 // Production rule engine
 pub use rule::{
-    Activation, CompiledRule, CompiledRuleBody, EffectRecord, FullCompiledRule,
-    ProductionRuleEngine, RuleCompiler,
+    Activation, AgendaEntry, CompiledRule, CompiledRuleBody, EffectRecord, FullCompiledRule,
+    ProductionRuleEngine, RuleCompiler, RuleMetrics, RuleStats,
 };
 
 // Spike code (to be replaced)
@@ -0,0 +1,233 @@
+//! Effect middleware for Longtable.
+//!
+//! Every effect produced by compiled code passes through an
+//! [`EffectMiddleware`] chain before it is applied, both in the
+//! [`TickExecutor`](crate::TickExecutor) and in the REPL. Each registered
+//! [`EffectInterceptor`] sees the effect in turn and can let it through,
+//! replace it, drop it, or veto it:
+//!
+//! ```text
+//! (destroy! e) ─▶ [order -10: audit log] ─▶ [order 0: protect :player] ─▶ apply
+//!                   logs, passes through       vetoes writes to players
+//! ```
+//!
+//! Interceptors run in ascending `order`; interceptors with the same order
+//! run in the order they were registered. A veto stops the chain and fails
+//! the evaluation or tick that produced the effect. Interceptors see the
+//! world the effects were produced against, before any of them is applied.
+
+use std::fmt;
+use std::sync::Arc;
+
+use longtable_foundation::{Error, ErrorKind, Result};
+use longtable_language::VmEffect;
+use longtable_storage::World;
+
+/// What an interceptor decided to do with an effect.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    /// Pass this effect (the original or a replacement) down the chain.
+    Apply(VmEffect),
+    /// Silently discard the effect.
+    Drop,
+    /// Reject the effect, failing whatever produced it.
+    Veto(String),
+}
+
+/// Observes, transforms, or rejects effects before they are applied.
+///
+/// Implemented for closures of the form `Fn(VmEffect, &World) -> Verdict`.
+/// Interceptors that need to record what they see should use interior
+/// mutability.
+pub trait EffectInterceptor: Send + Sync {
+    /// Decides what to do with `effect`.
+    fn intercept(&self, effect: VmEffect, world: &World) -> Verdict;
+}
+
+impl<F> EffectInterceptor for F
+where
+    F: Fn(VmEffect, &World) -> Verdict + Send + Sync,
+{
+    fn intercept(&self, effect: VmEffect, world: &World) -> Verdict {
+        self(effect, world)
+    }
+}
+
+/// A registered interceptor.
+#[derive(Clone)]
+struct Entry {
+    name: String,
+    order: i32,
+    interceptor: Arc<dyn EffectInterceptor>,
+}
+
+/// An ordered chain of effect interceptors.
+#[derive(Clone, Default)]
+pub struct EffectMiddleware {
+    /// Interceptors, sorted by `order` and then registration.
+    entries: Vec<Entry>,
+}
+
+impl fmt::Debug for EffectMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|e| (&e.name, e.order)))
+            .finish()
+    }
+}
+
+impl EffectMiddleware {
+    /// Creates an empty chain.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `interceptor` under `name`, replacing any interceptor
+    /// already registered with that name.
+    ///
+    /// Lower `order` runs earlier.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        order: i32,
+        interceptor: impl EffectInterceptor + 'static,
+    ) {
+        let name = name.into();
+        self.unregister(&name);
+        let pos = self.entries.partition_point(|e| e.order <= order);
+        self.entries.insert(
+            pos,
+            Entry {
+                name,
+                order,
+                interceptor: Arc::new(interceptor),
+            },
+        );
+    }
+
+    /// Removes the interceptor registered under `name`. Returns false if
+    /// there was none.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.name != name);
+        self.entries.len() != before
+    }
+
+    /// Returns true if no interceptors are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the registered interceptor names in the order they run.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|e| e.name.as_str())
+    }
+
+    /// Runs `effect` through the chain.
+    ///
+    /// Returns the effect to apply, or `None` if an interceptor dropped it.
+    ///
+    /// # Errors
+    /// Returns an error if an interceptor vetoes the effect.
+    pub fn process(&self, effect: VmEffect, world: &World) -> Result<Option<VmEffect>> {
+        let mut effect = effect;
+        for entry in &self.entries {
+            effect = match entry.interceptor.intercept(effect, world) {
+                Verdict::Apply(next) => next,
+                Verdict::Drop => return Ok(None),
+                Verdict::Veto(reason) => {
+                    return Err(Error::new(ErrorKind::Refused(format!(
+                        "effect vetoed by {}: {reason}",
+                        entry.name
+                    ))));
+                }
+            };
+        }
+        Ok(Some(effect))
+    }
+
+    /// Runs a batch of effects through the chain, keeping their order.
+    ///
+    /// # Errors
+    /// Returns an error if an interceptor vetoes any effect.
+    pub fn process_all(&self, effects: Vec<VmEffect>, world: &World) -> Result<Vec<VmEffect>> {
+        if self.is_empty() {
+            return Ok(effects);
+        }
+        effects
+            .into_iter()
+            .filter_map(|effect| self.process(effect, world).transpose())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::EntityId;
+    use std::sync::Mutex;
+
+    fn destroy(index: u64) -> VmEffect {
+        VmEffect::Destroy {
+            entity: EntityId::new(index, 0),
+        }
+    }
+
+    #[test]
+    fn interceptors_run_in_order() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut middleware = EffectMiddleware::new();
+        for (name, order) in [("late", 5), ("early", -5), ("also-late", 5)] {
+            let seen = Arc::clone(&seen);
+            middleware.register(name, order, move |effect, _: &World| {
+                seen.lock().unwrap().push(name);
+                Verdict::Apply(effect)
+            });
+        }
+
+        assert_eq!(
+            middleware.names().collect::<Vec<_>>(),
+            ["early", "late", "also-late"]
+        );
+        let world = World::new(0);
+        assert_eq!(
+            middleware.process(destroy(1), &world).unwrap(),
+            Some(destroy(1))
+        );
+        assert_eq!(*seen.lock().unwrap(), ["early", "late", "also-late"]);
+    }
+
+    #[test]
+    fn interceptors_transform_drop_and_veto() {
+        let mut middleware = EffectMiddleware::new();
+        middleware.register("redirect", 0, |effect, _: &World| match effect {
+            VmEffect::Destroy { entity } if entity.index == 1 => Verdict::Apply(destroy(2)),
+            VmEffect::Destroy { entity } if entity.index == 3 => Verdict::Drop,
+            VmEffect::Destroy { entity } if entity.index == 4 => {
+                Verdict::Veto("entity 4 is protected".to_string())
+            }
+            other => Verdict::Apply(other),
+        });
+
+        let world = World::new(0);
+        let applied = middleware
+            .process_all(vec![destroy(1), destroy(3), destroy(5)], &world)
+            .unwrap();
+        assert_eq!(applied, vec![destroy(2), destroy(5)]);
+
+        let err = middleware.process(destroy(4), &world).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("vetoed by redirect: entity 4 is protected")
+        );
+
+        // Re-registering a name replaces it
+        middleware.register("redirect", 0, |_, _: &World| Verdict::Drop);
+        assert_eq!(middleware.names().count(), 1);
+        assert_eq!(middleware.process(destroy(1), &world).unwrap(), None);
+        assert!(middleware.unregister("redirect"));
+        assert!(middleware.is_empty());
+    }
+}
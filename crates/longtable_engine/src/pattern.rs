@@ -6,11 +6,13 @@
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 
-use longtable_foundation::{EntityId, Interner, KeywordId, Result, Value};
-use longtable_language::Ast;
+use longtable_foundation::{EntityId, Interner, KeywordId, LtMap, Result, Value};
 use longtable_language::declaration::{
     Pattern as DeclPattern, PatternClause as DeclClause, PatternValue,
 };
+use longtable_language::{
+    Ast, CompiledProgram, Vm, WorldContext, compile_expression_with_interner,
+};
 use longtable_storage::World;
 
 // =============================================================================
@@ -39,13 +41,59 @@ pub enum CompiledBinding {
     Wildcard,
 }
 
+/// A compiled `(not-join [...] ...)`: clauses that must not all match
+/// together, joined to the rest of the pattern only on `join_vars`.
+#[derive(Clone, Debug, Default)]
+pub struct CompiledNotJoin {
+    /// Variables shared with the rest of the pattern
+    pub join_vars: Vec<String>,
+    /// Clauses matched against the world with only `join_vars` bound
+    pub clauses: Vec<CompiledClause>,
+}
+
+/// A compiled predicate clause, like `[(> ?hp 10)]`.
+#[derive(Clone, Debug)]
+pub struct CompiledPredicate {
+    /// Variables the expression reads, in binding-slot order
+    pub vars: Vec<String>,
+    /// The compiled expression
+    pub program: CompiledProgram,
+    /// Set for `(within? ?a ?b radius)`, which can narrow the entities a
+    /// clause tries to those near one already bound
+    pub proximity: Option<Proximity>,
+}
+
+/// A predicate requiring two entities to be near each other.
+#[derive(Clone, Debug)]
+pub struct Proximity {
+    /// The two entity variables
+    pub vars: [String; 2],
+    /// The furthest apart they may be
+    pub radius: f64,
+    /// The component their positions are read from
+    pub component: KeywordId,
+}
+
+/// A compiled `(or ...)`: alternative conjunctions of clauses.
+#[derive(Clone, Debug, Default)]
+pub struct CompiledDisjunction {
+    /// Each branch's clauses, matched in order
+    pub branches: Vec<Vec<CompiledClause>>,
+}
+
 /// A compiled pattern (positive clauses + negations).
 #[derive(Clone, Debug, Default)]
 pub struct CompiledPattern {
     /// Positive clauses that must match
     pub clauses: Vec<CompiledClause>,
-    /// Negated clauses (entity must NOT have these)
+    /// Negated clauses (no value of their unbound variables may match)
     pub negations: Vec<CompiledClause>,
+    /// Negated conjunctions of clauses
+    pub not_joins: Vec<CompiledNotJoin>,
+    /// Disjunctions, matched after `clauses`
+    pub disjunctions: Vec<CompiledDisjunction>,
+    /// Predicates, each checked once its variables are bound
+    pub predicates: Vec<CompiledPredicate>,
 }
 
 impl CompiledPattern {
@@ -55,11 +103,39 @@ impl CompiledPattern {
         Self::default()
     }
 
+    /// Iterates the clauses that can bind variables: the positive clauses,
+    /// then every branch of every disjunction.
+    pub fn binding_clauses(&self) -> impl Iterator<Item = &CompiledClause> {
+        self.clauses.iter().chain(
+            self.disjunctions
+                .iter()
+                .flat_map(|d| d.branches.iter().flatten()),
+        )
+    }
+
+    /// Adds every keyword the pattern matches on, as a component name or
+    /// inside a literal or predicate constant, to `live`.
+    pub fn mark_keywords(&self, live: &mut HashSet<KeywordId>) {
+        let clauses = self
+            .binding_clauses()
+            .chain(&self.negations)
+            .chain(self.not_joins.iter().flat_map(|n| &n.clauses));
+        for clause in clauses {
+            live.insert(clause.component);
+            if let CompiledBinding::Literal(value) = &clause.binding {
+                value.mark_keywords(live);
+            }
+        }
+        for predicate in &self.predicates {
+            predicate.program.mark_keywords(live);
+        }
+    }
+
     /// Returns all entity variable names referenced.
     #[must_use]
     pub fn entity_vars(&self) -> HashSet<&str> {
         let mut vars = HashSet::new();
-        for c in &self.clauses {
+        for c in self.binding_clauses() {
             vars.insert(c.entity_var.as_str());
         }
         for c in &self.negations {
@@ -97,10 +173,93 @@ impl PatternCompiler {
                 .negations
                 .push(Self::compile_clause(clause, interner)?);
         }
+        for not_join in &pattern.not_joins {
+            compiled.not_joins.push(CompiledNotJoin {
+                join_vars: not_join.join_vars.clone(),
+                clauses: not_join
+                    .clauses
+                    .iter()
+                    .map(|clause| Self::compile_clause(clause, interner))
+                    .collect::<Result<_>>()?,
+            });
+        }
+
+        // Compile disjunctions
+        for disjunction in &pattern.disjunctions {
+            let branches = disjunction
+                .branches
+                .iter()
+                .map(|branch| {
+                    branch
+                        .iter()
+                        .map(|clause| Self::compile_clause(clause, interner))
+                        .collect::<Result<Vec<_>>>()
+                })
+                .collect::<Result<_>>()?;
+            compiled.disjunctions.push(CompiledDisjunction { branches });
+        }
+
+        // Compile predicates
+        for predicate in &pattern.predicates {
+            let expr = compile_expression_with_interner(
+                &predicate.expr,
+                &predicate.vars,
+                interner.clone(),
+            )?;
+            compiled.predicates.push(CompiledPredicate {
+                vars: predicate.vars.clone(),
+                program: CompiledProgram {
+                    code: expr.code,
+                    constants: expr.constants,
+                    functions: Vec::new(),
+                },
+                proximity: Self::proximity(&predicate.expr, interner),
+            });
+        }
 
         Ok(compiled)
     }
 
+    /// Recognizes `(within? ?a ?b radius)`, optionally with `:by component`,
+    /// where the radius is a literal.
+    #[allow(clippy::cast_precision_loss)]
+    fn proximity(expr: &Ast, interner: &mut Interner) -> Option<Proximity> {
+        let Ast::List(items, _) = expr else {
+            return None;
+        };
+        let [
+            Ast::Symbol(name, _),
+            Ast::Symbol(a, _),
+            Ast::Symbol(b, _),
+            radius,
+            by @ ..,
+        ] = items.as_slice()
+        else {
+            return None;
+        };
+        if name != "within?" {
+            return None;
+        }
+        let radius = match radius {
+            Ast::Int(n, _) => *n as f64,
+            Ast::Float(x, _) => *x,
+            _ => return None,
+        };
+        let component = match by {
+            [] => "position",
+            [Ast::Keyword(option, _), Ast::Keyword(component, _)] if option == "by" => component,
+            _ => return None,
+        };
+        Some(Proximity {
+            vars: [
+                a.strip_prefix('?')?.to_string(),
+                b.strip_prefix('?')?.to_string(),
+            ],
+            radius,
+            component: interner.intern_keyword(component),
+        })
+    }
+
     fn compile_clause(clause: &DeclClause, interner: &mut Interner) -> Result<CompiledClause> {
         let component = interner.intern_keyword(&clause.component);
 
@@ -136,6 +295,23 @@ impl PatternCompiler {
                     .collect();
                 Value::Vec(values?)
             }
+            Ast::Set(elements, _) => {
+                let values: Result<_> = elements
+                    .iter()
+                    .map(|e| Self::ast_to_value(e, interner))
+                    .collect();
+                Value::Set(values?)
+            }
+            Ast::Map(entries, _) => {
+                let mut map = LtMap::new();
+                for (k, v) in entries {
+                    map = map.insert(
+                        Self::ast_to_value(k, interner)?,
+                        Self::ast_to_value(v, interner)?,
+                    );
+                }
+                Value::Map(map)
+            }
             _ => {
                 // For unsupported literals, just use Nil
                 Value::Nil
@@ -149,7 +325,7 @@ impl PatternCompiler {
 // =============================================================================
 
 /// A set of variable bindings from pattern matching.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Bindings {
     values: HashMap<String, Value>,
 }
@@ -216,6 +392,22 @@ impl Bindings {
         }
         hasher.finish()
     }
+
+    /// Compute a hash of every bound value, so bindings to the same entities
+    /// whose matched values differ produce different hashes.
+    #[must_use]
+    pub fn value_key(&self) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        let mut hasher = DefaultHasher::new();
+
+        let mut entries: Vec<_> = self.values.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, value) in entries {
+            key.hash(&mut hasher);
+            value.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
 }
 
 // =============================================================================
@@ -227,53 +419,121 @@ pub struct PatternMatcher;
 
 impl PatternMatcher {
     /// Find all binding sets that satisfy a pattern against a world.
-    #[must_use]
-    pub fn match_pattern(pattern: &CompiledPattern, world: &World) -> Vec<Bindings> {
-        if pattern.clauses.is_empty() {
-            return vec![Bindings::new()];
-        }
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the pattern's predicates fails to evaluate.
+    pub fn match_pattern(pattern: &CompiledPattern, world: &World) -> Result<Vec<Bindings>> {
+        Self::match_pattern_from(pattern, world, &Bindings::new())
+    }
 
-        // Start with first clause
-        let first = &pattern.clauses[0];
-        let mut results: Vec<Bindings> = Vec::new();
+    /// Find all binding sets that satisfy a pattern, extending `initial`.
+    ///
+    /// Variables bound in `initial` only match their bound values, so
+    /// `[?actor :location ?room]` with `?actor` bound looks up that actor's
+    /// room rather than every actor's.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the pattern's predicates fails to evaluate.
+    pub fn match_pattern_from(
+        pattern: &CompiledPattern,
+        world: &World,
+        initial: &Bindings,
+    ) -> Result<Vec<Bindings>> {
+        if pattern.clauses.is_empty()
+            && pattern.disjunctions.is_empty()
+            && pattern.predicates.is_empty()
+        {
+            return Ok(vec![initial.clone()]);
+        }
 
-        // Check if this is a relationship clause
-        if Self::is_relationship(first.component, world) {
-            // Match against relationship entities
-            let empty_bindings = Bindings::new();
-            for bindings in Self::match_relationship_clause(first, world, &empty_bindings) {
-                // Try to match remaining positive clauses
-                if let Some(positive_bindings) =
-                    Self::match_remaining(&pattern.clauses[1..], world, bindings)
+        // Check each predicate right after the clause that binds the last
+        // of its variables. Those no clause finishes binding wait until the
+        // disjunctions have matched.
+        let mut ready: Vec<Vec<&CompiledPredicate>> = vec![Vec::new(); pattern.clauses.len() + 1];
+        let mut late = Vec::new();
+        for predicate in &pattern.predicates {
+            match Self::bound_after(&pattern.clauses, &predicate.vars) {
+                Some(i) => ready[i].push(predicate),
+                None => late.push(predicate),
+            }
+        }
+        // A clause that binds one end of a proximity predicate tries only
+        // the entities near the other end, once that's bound
+        let mut near: Vec<Vec<(&Proximity, &str)>> = vec![Vec::new(); pattern.clauses.len()];
+        for (predicate, proximity) in pattern
+            .predicates
+            .iter()
+            .filter_map(|p| Some((p, p.proximity.as_ref()?)))
+        {
+            let [a, b] = &proximity.vars;
+            for (var, other) in [(a, b), (b, a)] {
+                if let Some(i) = Self::bound_after(&pattern.clauses, &predicate.vars)
+                    && i > 0
+                    && pattern.clauses[i - 1].entity_var == *var
                 {
-                    // Check negations
-                    if Self::check_negations(&pattern.negations, world, &positive_bindings) {
-                        results.push(positive_bindings);
-                    }
+                    near[i - 1].push((proximity, other));
                 }
             }
-        } else {
-            // Find entities with the first clause's component
-            for entity in world.with_component(first.component) {
-                let mut bindings = Bindings::new();
-                bindings.set(first.entity_var.clone(), Value::EntityRef(entity));
-
-                // Try to bind the value
-                if let Some(bound) = Self::try_bind_clause(first, entity, world, &bindings) {
-                    // Try to match remaining positive clauses
-                    if let Some(positive_bindings) =
-                        Self::match_remaining(&pattern.clauses[1..], world, bound)
-                    {
-                        // Check negations
-                        if Self::check_negations(&pattern.negations, world, &positive_bindings) {
-                            results.push(positive_bindings);
-                        }
+        }
+        let candidates = |remaining: usize, bindings: &Bindings| {
+            near[pattern.clauses.len() - remaining]
+                .iter()
+                .find_map(|&(proximity, other)| {
+                    let center = world.position_of(bindings.get(other)?, proximity.component)?;
+                    Some(world.entities_within(proximity.component, center, proximity.radius))
+                })
+        };
+
+        // The first predicate to fail stops the match, and its error is
+        // returned once the search unwinds
+        let mut vm = Vm::new();
+        let mut failed = None;
+        let mut holds = |predicates: &[&CompiledPredicate], bindings: &Bindings| {
+            if failed.is_some() {
+                return false;
+            }
+            for predicate in predicates {
+                match Self::predicate_holds(predicate, world, bindings, &mut vm) {
+                    Ok(true) => {}
+                    Ok(false) => return false,
+                    Err(e) => {
+                        failed = Some(e);
+                        return false;
                     }
                 }
             }
-        }
+            true
+        };
 
-        results
+        let mut results = Vec::new();
+        if holds(&ready[0], initial) {
+            let n = pattern.clauses.len();
+            Self::match_remaining(
+                &pattern.clauses,
+                world,
+                initial.clone(),
+                &mut results,
+                &mut |remaining, bindings| holds(&ready[n - remaining], bindings),
+                &candidates,
+            );
+        }
+        for disjunction in &pattern.disjunctions {
+            results = results
+                .into_iter()
+                .flat_map(|bindings| Self::match_disjunction(disjunction, world, &bindings))
+                .collect();
+        }
+        results.retain(|bindings| {
+            holds(&late, bindings)
+                && Self::check_negations(&pattern.negations, world, bindings)
+                && Self::check_not_joins(&pattern.not_joins, world, bindings)
+        });
+        match failed {
+            Some(e) => Err(e),
+            None => Ok(results),
+        }
     }
 
     /// Check if a keyword is a registered relationship type.
@@ -411,66 +671,168 @@ impl PatternMatcher {
         Some(new_bindings)
     }
 
+    /// Returns how many of `clauses` must match before all of `vars` are
+    /// bound, or `None` if they never all are.
+    fn bound_after(clauses: &[CompiledClause], vars: &[String]) -> Option<usize> {
+        let mut unbound: Vec<&String> = vars.iter().collect();
+        if unbound.is_empty() {
+            return Some(0);
+        }
+        for (i, clause) in clauses.iter().enumerate() {
+            unbound.retain(|var| {
+                **var != clause.entity_var
+                    && !matches!(&clause.binding, CompiledBinding::Variable(v) if v == *var)
+            });
+            if unbound.is_empty() {
+                return Some(i + 1);
+            }
+        }
+        None
+    }
+
+    /// Evaluates a predicate against `bindings`. Unbound variables read as
+    /// nil.
+    fn predicate_holds(
+        predicate: &CompiledPredicate,
+        world: &World,
+        bindings: &Bindings,
+        vm: &mut Vm,
+    ) -> Result<bool> {
+        let values = predicate
+            .vars
+            .iter()
+            .map(|var| bindings.get(var).cloned().unwrap_or(Value::Nil))
+            .collect();
+        vm.set_bindings(values);
+        vm.execute_with_context(&predicate.program, &WorldContext::new(world))
+            .map(|value| value.is_truthy())
+    }
+
+    /// Extends `bindings` through each of `clauses` in turn, pushing every
+    /// complete set of bindings onto `results`.
+    ///
+    /// After each clause binds, `accept` is called with the number of
+    /// clauses still to match and the bindings so far; bindings it rejects
+    /// go no further. Before a clause with an unbound entity tries every
+    /// entity with its component, `candidates` is called with the number of
+    /// clauses left including it, and may narrow the entities to try.
     fn match_remaining(
         clauses: &[CompiledClause],
         world: &World,
         bindings: Bindings,
-    ) -> Option<Bindings> {
-        if clauses.is_empty() {
-            return Some(bindings);
-        }
-
-        let clause = &clauses[0];
+        results: &mut Vec<Bindings>,
+        accept: &mut dyn FnMut(usize, &Bindings) -> bool,
+        candidates: &dyn Fn(usize, &Bindings) -> Option<Vec<EntityId>>,
+    ) {
+        let Some((clause, rest)) = clauses.split_first() else {
+            results.push(bindings);
+            return;
+        };
+        let mut descend = |bound: Bindings, results: &mut Vec<Bindings>| {
+            if accept(rest.len(), &bound) {
+                Self::match_remaining(rest, world, bound, results, accept, candidates);
+            }
+        };
 
         // Check if this is a relationship clause
         if Self::is_relationship(clause.component, world) {
             // Match against relationship entities
             for new_bindings in Self::match_relationship_clause(clause, world, &bindings) {
-                if let Some(final_bindings) =
-                    Self::match_remaining(&clauses[1..], world, new_bindings)
-                {
-                    return Some(final_bindings);
-                }
+                descend(new_bindings, results);
             }
-            return None;
+            return;
         }
 
         // Check if entity variable is already bound
         if let Some(entity) = bindings.get_entity(&clause.entity_var) {
             // Use the already-bound entity
             if let Some(new_bindings) = Self::try_bind_clause(clause, entity, world, &bindings) {
-                return Self::match_remaining(&clauses[1..], world, new_bindings);
+                descend(new_bindings, results);
             }
-            return None;
+            return;
         }
 
         // Need to find matching entities for this clause
-        for entity in world.with_component(clause.component) {
+        let entities: Box<dyn Iterator<Item = EntityId>> =
+            match candidates(clauses.len(), &bindings) {
+                Some(near) => Box::new(near.into_iter()),
+                None => Box::new(world.with_component(clause.component)),
+            };
+        for entity in entities {
             let mut new_bindings = bindings.clone();
             new_bindings.set(clause.entity_var.clone(), Value::EntityRef(entity));
 
             if let Some(bound) = Self::try_bind_clause(clause, entity, world, &new_bindings) {
-                if let Some(final_bindings) = Self::match_remaining(&clauses[1..], world, bound) {
-                    return Some(final_bindings);
-                }
+                descend(bound, results);
             }
         }
+    }
 
-        None
+    /// Extends `bindings` through each branch of a disjunction, returning the
+    /// union of what the branches match. Branches that reach the same
+    /// bindings contribute them once.
+    fn match_disjunction(
+        disjunction: &CompiledDisjunction,
+        world: &World,
+        bindings: &Bindings,
+    ) -> Vec<Bindings> {
+        let mut union: Vec<Bindings> = Vec::new();
+        for branch in &disjunction.branches {
+            let mut matches = Vec::new();
+            Self::match_remaining(
+                branch,
+                world,
+                bindings.clone(),
+                &mut matches,
+                &mut |_, _| true,
+                &|_, _| None,
+            );
+            for found in matches {
+                if !union.contains(&found) {
+                    union.push(found);
+                }
+            }
+        }
+        union
     }
 
-    /// Check that all negations are satisfied (entity does NOT have component).
+    /// Check that no negated clause matches under `bindings`.
+    ///
+    /// Variables the clause shares with `bindings` must agree; the rest may
+    /// take any value, so `(not [?e :poisoned])` with `?e` unbound means no
+    /// entity is poisoned.
     fn check_negations(negations: &[CompiledClause], world: &World, bindings: &Bindings) -> bool {
-        for clause in negations {
-            // Get the bound entity
-            if let Some(entity) = bindings.get_entity(&clause.entity_var) {
-                // Entity must NOT have this component
-                if world.has(entity, clause.component) {
-                    return false;
+        negations
+            .iter()
+            .all(|clause| !Self::any_match(std::slice::from_ref(clause), world, bindings.clone()))
+    }
+
+    /// Check that no not-join's clauses all match together, given only its
+    /// join variables from `bindings`.
+    fn check_not_joins(not_joins: &[CompiledNotJoin], world: &World, bindings: &Bindings) -> bool {
+        not_joins.iter().all(|not_join| {
+            let mut joined = Bindings::new();
+            for var in &not_join.join_vars {
+                if let Some(value) = bindings.get(var) {
+                    joined.set(var.clone(), value.clone());
                 }
             }
-        }
-        true
+            !Self::any_match(&not_join.clauses, world, joined)
+        })
+    }
+
+    /// Returns true if `clauses` match at least once starting from `bindings`.
+    fn any_match(clauses: &[CompiledClause], world: &World, bindings: Bindings) -> bool {
+        let mut matches = Vec::new();
+        Self::match_remaining(
+            clauses,
+            world,
+            bindings,
+            &mut matches,
+            &mut |_, _| true,
+            &|_, _| None,
+        );
+        !matches.is_empty()
     }
 
     /// Explain why a specific entity matched or didn't match a pattern.
@@ -579,7 +941,7 @@ impl PatternMatcher {
                 continue;
             }
 
-            if world.has(entity, clause.component) {
+            if Self::any_match(std::slice::from_ref(clause), world, bindings.clone()) {
                 return EntityMatchResult::not_matched(
                     entity,
                     pattern.clauses.len() + idx, // Negation index after positive clauses
@@ -743,6 +1105,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, &mut interner).unwrap();
@@ -767,6 +1132,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, &mut interner).unwrap();
@@ -789,6 +1157,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, &mut interner).unwrap();
@@ -812,10 +1183,13 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
-        let matches = PatternMatcher::match_pattern(&compiled, &world);
+        let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
         // Should match both entities with health
         assert_eq!(matches.len(), 2);
@@ -845,10 +1219,13 @@ mod tests {
                 value: PatternValue::Wildcard,
                 span: Span::default(),
             }],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
-        let matches = PatternMatcher::match_pattern(&compiled, &world);
+        let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
         // Should match only entity without velocity
         assert_eq!(matches.len(), 1);
@@ -875,10 +1252,13 @@ mod tests {
                 },
             ],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
-        let matches = PatternMatcher::match_pattern(&compiled, &world);
+        let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
         // Should match only entity with both components
         assert_eq!(matches.len(), 1);
@@ -995,10 +1375,13 @@ mod tests {
                 },
             ],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
-        let matches = PatternMatcher::match_pattern(&compiled, &world);
+        let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
         // Only e2 has both components
         assert_eq!(matches.len(), 1);
@@ -1085,10 +1468,13 @@ mod tests {
                 },
             ],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
-        let matches = PatternMatcher::match_pattern(&compiled, &world);
+        let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
         // Should find combinations - each faction paired with each member
         // (2 factions × 2 members = 4 combinations)
@@ -1143,10 +1529,13 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
-        let matches = PatternMatcher::match_pattern(&compiled, &world);
+        let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
         // Should find exactly 2 enemies
         assert_eq!(matches.len(), 2);
@@ -1164,10 +1553,13 @@ mod tests {
         let decl_pattern = DeclPattern {
             clauses: vec![],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, &mut Interner::new()).unwrap();
-        let matches = PatternMatcher::match_pattern(&compiled, &world);
+        let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
         // Empty pattern matches once with empty bindings
         assert_eq!(matches.len(), 1);
@@ -1220,10 +1612,13 @@ mod tests {
                 },
             ],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
-        let matches = PatternMatcher::match_pattern(&compiled, &world);
+        let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
         // Only e1 matches (has both flags with same value true)
         assert_eq!(matches.len(), 1);
@@ -1289,10 +1684,13 @@ mod tests {
                 },
             ],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
-        let matches = PatternMatcher::match_pattern(&compiled, &world);
+        let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
         // Should find the player in room1
         assert_eq!(matches.len(), 1);
@@ -1328,10 +1726,13 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
-        let matches = PatternMatcher::match_pattern(&compiled, &world);
+        let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
         // Should find the relationship
         assert_eq!(matches.len(), 1);
@@ -1370,10 +1771,13 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
-        let matches = PatternMatcher::match_pattern(&compiled, &world);
+        let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
         // Should find both relationships
         assert_eq!(matches.len(), 2);
@@ -5,6 +5,8 @@
 //! - Multi-hop "why did this value change" queries
 //! - Configurable verbosity levels (Minimal/Standard/Full)
 //! - Optional full history tracking for time travel debugging
+//! - Who spawned each entity and who created or removed each relationship
+//! - A disabled state with no per-write bookkeeping, for play mode
 
 use std::collections::HashMap;
 
@@ -119,6 +121,28 @@ pub struct WriteHistory {
     pub writes: Vec<WriteRecord>,
 }
 
+// =============================================================================
+// Link Records
+// =============================================================================
+
+/// Whether a relationship was created or removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkChange {
+    /// The relationship was created.
+    Linked,
+    /// The relationship was removed.
+    Unlinked,
+}
+
+/// Record of who created or removed a relationship and when.
+#[derive(Clone, Debug)]
+pub struct LinkRecord {
+    /// Whether the relationship was created or removed.
+    pub change: LinkChange,
+    /// Who made the change, with when and in what context.
+    pub record: WriteRecord,
+}
+
 // =============================================================================
 // Provenance Tracker
 // =============================================================================
@@ -148,6 +172,15 @@ pub struct ProvenanceTracker {
 
     /// Maximum history entries per key
     max_history_per_key: usize,
+
+    /// Whether writes are recorded at all
+    enabled: bool,
+
+    /// Who spawned each entity (always maintained)
+    spawns: HashMap<EntityId, WriteRecord>,
+
+    /// Changes to each (source, relationship, target) edge, oldest first
+    links: HashMap<(EntityId, KeywordId, EntityId), Vec<LinkRecord>>,
 }
 
 impl Default for ProvenanceTracker {
@@ -158,6 +191,9 @@ impl Default for ProvenanceTracker {
             verbosity: ProvenanceVerbosity::Minimal,
             tick: 0,
             max_history_per_key: DEFAULT_MAX_HISTORY_PER_KEY,
+            enabled: true,
+            spawns: HashMap::new(),
+            links: HashMap::new(),
         }
     }
 }
@@ -184,7 +220,33 @@ impl ProvenanceTracker {
             verbosity,
             tick: 0,
             max_history_per_key: DEFAULT_MAX_HISTORY_PER_KEY,
+            enabled: true,
+            spawns: HashMap::new(),
+            links: HashMap::new(),
+        }
+    }
+
+    /// Creates a provenance tracker that records nothing.
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// Returns whether writes are being recorded.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables recording. Disabling discards everything recorded so far.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.clear();
         }
+        self.enabled = enabled;
     }
 
     /// Returns the current verbosity level.
@@ -223,6 +285,9 @@ impl ProvenanceTracker {
 
     /// Records a write to an entity's component (minimal information).
     pub fn record_write(&mut self, entity: EntityId, component: KeywordId, rule: KeywordId) {
+        if !self.enabled {
+            return;
+        }
         let record = WriteRecord::new(rule, self.tick);
         self.insert_record(entity, component, record);
     }
@@ -235,6 +300,9 @@ impl ProvenanceTracker {
         rule: KeywordId,
         context: Vec<(String, EntityId)>,
     ) {
+        if !self.enabled {
+            return;
+        }
         let mut record = WriteRecord::new(rule, self.tick);
         record.context = context;
         self.insert_record(entity, component, record);
@@ -255,6 +323,9 @@ impl ProvenanceTracker {
         bindings: Option<Vec<(String, Value)>>,
         expr_id: Option<u32>,
     ) {
+        if !self.enabled {
+            return;
+        }
         let mut record = WriteRecord::new(rule, self.tick);
         record.context = context;
 
@@ -290,6 +361,67 @@ impl ProvenanceTracker {
         self.last_writer.insert(key, record);
     }
 
+    /// Records that `rule` spawned `entity`.
+    pub fn record_spawn(
+        &mut self,
+        entity: EntityId,
+        rule: KeywordId,
+        context: Vec<(String, EntityId)>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let mut record = WriteRecord::new(rule, self.tick);
+        record.context = context;
+        self.spawns.insert(entity, record);
+    }
+
+    /// Records that `rule` created or removed a relationship edge.
+    ///
+    /// Each edge keeps at most the configured maximum history of changes.
+    pub fn record_link(
+        &mut self,
+        source: EntityId,
+        relationship: KeywordId,
+        target: EntityId,
+        change: LinkChange,
+        rule: KeywordId,
+        context: Vec<(String, EntityId)>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let mut record = WriteRecord::new(rule, self.tick);
+        record.context = context;
+        let changes = self
+            .links
+            .entry((source, relationship, target))
+            .or_default();
+        changes.push(LinkRecord { change, record });
+        while changes.len() > self.max_history_per_key {
+            changes.remove(0);
+        }
+    }
+
+    /// Gets the record of who spawned an entity.
+    #[must_use]
+    pub fn spawn_record(&self, entity: EntityId) -> Option<&WriteRecord> {
+        self.spawns.get(&entity)
+    }
+
+    /// Gets the changes to a relationship edge, oldest first.
+    #[must_use]
+    pub fn link_history(
+        &self,
+        source: EntityId,
+        relationship: KeywordId,
+        target: EntityId,
+    ) -> &[LinkRecord] {
+        self.links
+            .get(&(source, relationship, target))
+            .map_or(&[], Vec::as_slice)
+    }
+
     /// Gets the last writer for an entity's component.
     #[must_use]
     pub fn last_writer(&self, entity: EntityId, component: KeywordId) -> Option<&WriteRecord> {
@@ -349,6 +481,8 @@ impl ProvenanceTracker {
         if let Some(history) = &mut self.history {
             history.clear();
         }
+        self.spawns.clear();
+        self.links.clear();
     }
 
     /// Clears provenance for a specific entity.
@@ -357,6 +491,9 @@ impl ProvenanceTracker {
         if let Some(history) = &mut self.history {
             history.retain(|(e, _), _| *e != entity);
         }
+        self.spawns.remove(&entity);
+        self.links
+            .retain(|(source, _, target), _| *source != entity && *target != entity);
     }
 
     /// Prunes history entries older than the specified tick.
@@ -404,6 +541,24 @@ mod tests {
         assert_eq!(writer.unwrap().rule, rule1);
     }
 
+    #[test]
+    fn disabled_tracker_records_nothing() {
+        let (_interner, health, _mana, rule1) = setup();
+        let mut tracker = ProvenanceTracker::with_verbosity(ProvenanceVerbosity::Standard);
+        let entity = EntityId::new(1, 0);
+
+        tracker.record_write(entity, health, rule1);
+        tracker.set_enabled(false);
+        assert!(tracker.last_writer(entity, health).is_none());
+
+        tracker.record_write(entity, health, rule1);
+        tracker.record_write_with_context(entity, health, rule1, vec![]);
+        assert!(tracker.last_writer(entity, health).is_none());
+        assert!(tracker.history(entity, health).is_none());
+
+        assert!(!ProvenanceTracker::disabled().is_enabled());
+    }
+
     #[test]
     fn why_query() {
         let (_interner, health, mana, rule1) = setup();
@@ -708,4 +863,38 @@ mod tests {
         // last_writer should still work
         assert!(tracker.last_writer(entity, health).is_some());
     }
+
+    #[test]
+    fn spawn_and_link_tracking() {
+        let (mut interner, _health, _mana, rule1) = setup();
+        let contains = interner.intern_keyword("contains");
+        let mut tracker = ProvenanceTracker::new();
+        let room = EntityId::new(1, 1);
+        let item = EntityId::new(2, 1);
+
+        tracker.record_spawn(item, rule1, vec![("room".to_string(), room)]);
+        tracker.record_link(room, contains, item, LinkChange::Linked, rule1, vec![]);
+        tracker.begin_tick();
+        tracker.record_link(room, contains, item, LinkChange::Unlinked, rule1, vec![]);
+
+        let spawn = tracker.spawn_record(item).unwrap();
+        assert_eq!(spawn.rule, rule1);
+        assert_eq!(spawn.context, vec![("room".to_string(), room)]);
+        assert!(tracker.spawn_record(room).is_none());
+
+        let changes: Vec<_> = tracker
+            .link_history(room, contains, item)
+            .iter()
+            .map(|l| (l.change, l.record.tick))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![(LinkChange::Linked, 0), (LinkChange::Unlinked, 1)]
+        );
+        assert!(tracker.link_history(item, contains, room).is_empty());
+
+        tracker.clear_entity(item);
+        assert!(tracker.spawn_record(item).is_none());
+        assert!(tracker.link_history(room, contains, item).is_empty());
+    }
 }
@@ -3,18 +3,22 @@
 //! This module provides:
 //! - [`CompiledQuery`] - A compiled query ready for execution
 //! - [`QueryCompiler`] - Compiles `QueryDecl` into `CompiledQuery`
+//! - [`QueryPlan`] - The join order [`QueryCompiler::plan`] chose for a query
 //! - [`QueryExecutor`] - Executes queries against a World
 //! - [`QueryWarning`] - Warnings emitted during query compilation
 
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt;
 
-use longtable_foundation::{Interner, LtVec, Result, Value};
+use longtable_foundation::{Error, ErrorKind, Interner, KeywordId, LtVec, Result, Value};
 use longtable_language::declaration::{OrderDirection, QueryDecl};
 use longtable_language::{Ast, CompiledExpr, Vm, compile_expression};
 use longtable_storage::World;
 
-use crate::pattern::{Bindings, CompiledPattern, PatternCompiler, PatternMatcher};
+use crate::pattern::{
+    Bindings, CompiledBinding, CompiledClause, CompiledPattern, PatternCompiler, PatternMatcher,
+};
 
 // =============================================================================
 // Query Warnings
@@ -35,6 +39,38 @@ pub enum QueryWarning {
         /// The entity variable used in order-by (e.g., "e" for `?e`)
         variable: String,
     },
+
+    /// A clause follows a high-fan-out relationship and nothing narrows the
+    /// targets it binds.
+    ///
+    /// Each row reaching the clause multiplies into as many rows as its
+    /// source has targets. Guards don't help: they run after the pattern
+    /// has produced every row.
+    HighFanOut {
+        /// The relationship traversed (without the leading colon)
+        relationship: String,
+        /// The target variable nothing else constrains
+        variable: String,
+        /// Most targets any one source currently has
+        max_fan_out: usize,
+    },
+}
+
+/// Fan-out at or above which [`QueryWarning::HighFanOut`] is reported.
+pub const HIGH_FAN_OUT_THRESHOLD: usize = 256;
+
+impl QueryWarning {
+    /// Every warning code, as accepted by a query's `:suppress` clause.
+    pub const CODES: &'static [&'static str] = &["unstable-order", "high-fan-out"];
+
+    /// Returns the code naming this kind of warning (e.g. `high-fan-out`).
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::EntityOrderingUnstable { .. } => "unstable-order",
+            Self::HighFanOut { .. } => "high-fan-out",
+        }
+    }
 }
 
 impl fmt::Display for QueryWarning {
@@ -47,6 +83,17 @@ impl fmt::Display for QueryWarning {
                      entity IDs may change across save/load cycles"
                 )
             }
+            Self::HighFanOut {
+                relationship,
+                variable,
+                max_fan_out,
+            } => {
+                write!(
+                    f,
+                    "`:{relationship}` has sources with up to {max_fan_out} targets, \
+                     and nothing else in the pattern constrains `?{variable}`"
+                )
+            }
         }
     }
 }
@@ -78,6 +125,95 @@ pub struct CompiledQuery {
     pub binding_vars: Vec<String>,
     /// Warnings emitted during compilation
     pub warnings: Vec<QueryWarning>,
+    /// Warning codes the query suppresses
+    pub suppress: Vec<String>,
+    /// The join order chosen by [`QueryCompiler::plan`], if it was planned
+    pub plan: Option<QueryPlan>,
+}
+
+impl CompiledQuery {
+    /// Adds warnings, dropping any whose code the query suppresses.
+    pub fn add_warnings(&mut self, warnings: impl IntoIterator<Item = QueryWarning>) {
+        let suppress = &self.suppress;
+        self.warnings.extend(
+            warnings
+                .into_iter()
+                .filter(|w| !suppress.iter().any(|code| code == w.code())),
+        );
+    }
+}
+
+// =============================================================================
+// Query Plans
+// =============================================================================
+
+/// How a planned clause finds the candidates it tries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanAccess {
+    /// Scans every entity with the component
+    Scan,
+    /// Reads the component of an entity an earlier clause bound
+    Lookup,
+    /// Scans every link of the relationship
+    RelationshipScan,
+    /// Follows the links of a source an earlier clause bound
+    Traverse,
+}
+
+impl PlanAccess {
+    /// Returns the name `(explain-plan ...)` shows for this access.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Scan => "scan",
+            Self::Lookup => "lookup",
+            Self::RelationshipScan => "relationship scan",
+            Self::Traverse => "traverse",
+        }
+    }
+}
+
+/// One clause of a planned query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlanStep {
+    /// Position of the clause in the query as written
+    pub written: usize,
+    /// How the clause finds its candidates
+    pub access: PlanAccess,
+    /// Estimated candidates the clause tries for each row reaching it
+    pub estimate: usize,
+}
+
+/// The order in which a query's clauses are matched.
+///
+/// Steps are in join order; `clauses[i]` of the planned query's pattern is
+/// matched by `steps[i]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueryPlan {
+    /// The clauses, in the order they are matched
+    pub steps: Vec<PlanStep>,
+}
+
+impl QueryPlan {
+    /// Returns the estimated rows after each step: the product of the
+    /// estimates so far.
+    #[must_use]
+    pub fn estimated_rows(&self) -> Vec<usize> {
+        self.steps
+            .iter()
+            .scan(1usize, |rows, step| {
+                *rows = rows.saturating_mul(step.estimate);
+                Some(*rows)
+            })
+            .collect()
+    }
+
+    /// Returns true if the plan matches clauses in a different order than
+    /// they were written.
+    #[must_use]
+    pub fn is_reordered(&self) -> bool {
+        self.steps.iter().enumerate().any(|(i, s)| s.written != i)
+    }
 }
 
 // =============================================================================
@@ -94,6 +230,20 @@ impl QueryCompiler {
     ///
     /// Returns an error if compilation fails.
     pub fn compile(query: &QueryDecl, interner: &mut Interner) -> Result<CompiledQuery> {
+        if let Some(code) = query
+            .suppress
+            .iter()
+            .find(|code| !QueryWarning::CODES.contains(&code.as_str()))
+        {
+            return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                "unknown query warning :{code} in :suppress"
+            )))
+            .with_hint(format!(
+                "query warnings are :{}",
+                QueryWarning::CODES.join(", :")
+            )));
+        }
+
         let mut warnings = Vec::new();
 
         // Compile pattern
@@ -117,7 +267,7 @@ impl QueryCompiler {
 
         // Collect all variable names for binding lookup
         let mut binding_vars = Vec::new();
-        for clause in &pattern.clauses {
+        for clause in pattern.binding_clauses() {
             if !binding_vars.contains(&clause.entity_var) {
                 binding_vars.push(clause.entity_var.clone());
             }
@@ -159,7 +309,7 @@ impl QueryCompiler {
             None
         };
 
-        Ok(CompiledQuery {
+        let mut compiled = CompiledQuery {
             pattern,
             bindings: compiled_bindings,
             aggregates: compiled_aggregates,
@@ -169,8 +319,162 @@ impl QueryCompiler {
             limit: query.limit,
             return_expr,
             binding_vars,
-            warnings,
-        })
+            warnings: Vec::new(),
+            suppress: query.suppress.clone(),
+            plan: None,
+        };
+        compiled.add_warnings(warnings);
+        Ok(compiled)
+    }
+
+    /// Checks a compiled query against the world's relationship fan-out.
+    ///
+    /// Reports a [`QueryWarning::HighFanOut`] for each relationship clause
+    /// whose source is already bound, whose relationship has a source with
+    /// at least `threshold` targets, and whose target variable no later
+    /// clause or negation constrains. A clause with an unbound source is a
+    /// scan of the relationship rather than a traversal, and isn't reported.
+    #[must_use]
+    pub fn fan_out_warnings(
+        query: &CompiledQuery,
+        world: &World,
+        threshold: usize,
+    ) -> Vec<QueryWarning> {
+        let clauses = &query.pattern.clauses;
+        let mut bound: Vec<&str> = Vec::new();
+        let mut warnings = Vec::new();
+        for (i, clause) in clauses.iter().enumerate() {
+            let source_bound = bound.contains(&clause.entity_var.as_str());
+            let target = match &clause.binding {
+                CompiledBinding::Variable(var) if !bound.contains(&var.as_str()) => Some(var),
+                _ => None,
+            };
+
+            if let (true, Some(var), Some(_)) = (
+                source_bound,
+                target,
+                world.relationship_schema(clause.component),
+            ) {
+                let constrained = clauses[i + 1..]
+                    .iter()
+                    .chain(&query.pattern.negations)
+                    .any(|later| Self::mentions(later, var))
+                    || query
+                        .pattern
+                        .not_joins
+                        .iter()
+                        .any(|not_join| not_join.join_vars.contains(var));
+                let max_fan_out = world.fan_out(clause.component).max;
+                if !constrained && max_fan_out >= threshold {
+                    warnings.push(QueryWarning::HighFanOut {
+                        relationship: world
+                            .interner()
+                            .get_keyword(clause.component)
+                            .unwrap_or("?")
+                            .to_string(),
+                        variable: var.clone(),
+                        max_fan_out,
+                    });
+                }
+            }
+
+            bound.push(&clause.entity_var);
+            if let CompiledBinding::Variable(var) = &clause.binding {
+                bound.push(var);
+            }
+        }
+        warnings
+    }
+
+    /// Reorders a compiled query's clauses by estimated selectivity.
+    ///
+    /// Greedily picks the next clause to match: one that shares a variable
+    /// with the clauses already chosen (so no cross product is formed while
+    /// a join is available), then the one expected to try the fewest
+    /// candidates per row, then the one written first. Estimates come from
+    /// the world's current statistics: how many entities have a component,
+    /// and how many links a relationship has per source. A clause whose
+    /// entity is already bound is a single lookup.
+    ///
+    /// Matching finds every set of bindings whatever the order, so planning
+    /// changes how long a query takes, not what it returns. The chosen order
+    /// is recorded in [`CompiledQuery::plan`].
+    pub fn plan(query: &mut CompiledQuery, world: &World) {
+        let mut fan_outs: HashMap<KeywordId, (usize, usize)> = HashMap::new();
+        for clause in &query.pattern.clauses {
+            if world.relationship_schema(clause.component).is_some() {
+                fan_outs.entry(clause.component).or_insert_with(|| {
+                    let fan_out = world.fan_out(clause.component);
+                    (fan_out.edges, fan_out.sources)
+                });
+            }
+        }
+
+        let mut remaining: Vec<(usize, CompiledClause)> =
+            std::mem::take(&mut query.pattern.clauses)
+                .into_iter()
+                .enumerate()
+                .collect();
+        let mut bound: Vec<String> = Vec::new();
+        let mut clauses = Vec::with_capacity(remaining.len());
+        let mut steps = Vec::with_capacity(remaining.len());
+
+        while let Some(next) = (0..remaining.len()).min_by_key(|&i| {
+            let (written, clause) = &remaining[i];
+            let connected = bound.is_empty() || bound.iter().any(|v| Self::mentions(clause, v));
+            let (_, estimate) = Self::estimate(clause, world, &bound, &fan_outs);
+            (!connected, estimate, *written)
+        }) {
+            let (written, clause) = remaining.remove(next);
+            let (access, estimate) = Self::estimate(&clause, world, &bound, &fan_outs);
+            bound.push(clause.entity_var.clone());
+            if let CompiledBinding::Variable(var) = &clause.binding {
+                bound.push(var.clone());
+            }
+            steps.push(PlanStep {
+                written,
+                access,
+                estimate,
+            });
+            clauses.push(clause);
+        }
+
+        query.pattern.clauses = clauses;
+        query.plan = Some(QueryPlan { steps });
+    }
+
+    /// Estimates how a clause would find its candidates once `bound`
+    /// variables are bound, and how many it would try per row.
+    fn estimate(
+        clause: &CompiledClause,
+        world: &World,
+        bound: &[String],
+        fan_outs: &HashMap<KeywordId, (usize, usize)>,
+    ) -> (PlanAccess, usize) {
+        let source_bound = bound.contains(&clause.entity_var);
+        if let Some(&(edges, sources)) = fan_outs.get(&clause.component) {
+            let target_bound = match &clause.binding {
+                CompiledBinding::Variable(var) => bound.contains(var),
+                CompiledBinding::Literal(_) => true,
+                CompiledBinding::Wildcard => false,
+            };
+            return match (source_bound, target_bound) {
+                (true, true) => (PlanAccess::Traverse, 1),
+                (true, false) => (PlanAccess::Traverse, edges.div_ceil(sources.max(1))),
+                (false, _) => (PlanAccess::RelationshipScan, edges),
+            };
+        }
+        if source_bound {
+            (PlanAccess::Lookup, 1)
+        } else {
+            (PlanAccess::Scan, world.component_count(clause.component))
+        }
+    }
+
+    /// Returns true if a clause refers to `var`.
+    fn mentions(clause: &CompiledClause, var: &str) -> bool {
+        clause.entity_var == var
+            || matches!(&clause.binding, CompiledBinding::Variable(v) if v == var)
     }
 
     /// Compile an expression with variable references resolved to binding indices.
@@ -195,7 +499,7 @@ impl QueryExecutor {
     /// Returns an error if query execution fails.
     pub fn execute(query: &CompiledQuery, world: &World) -> Result<Vec<Value>> {
         // Step 1: Pattern matching - get all binding sets
-        let all_bindings = PatternMatcher::match_pattern(&query.pattern, world);
+        let all_bindings = PatternMatcher::match_pattern(&query.pattern, world)?;
 
         if all_bindings.is_empty() {
             return Ok(Vec::new());
@@ -319,7 +623,7 @@ impl QueryExecutor {
     /// Returns an error if query execution fails.
     pub fn count(query: &CompiledQuery, world: &World) -> Result<usize> {
         // Step 1: Pattern matching - get all binding sets
-        let all_bindings = PatternMatcher::match_pattern(&query.pattern, world);
+        let all_bindings = PatternMatcher::match_pattern(&query.pattern, world)?;
 
         if all_bindings.is_empty() {
             return Ok(0);
@@ -608,6 +912,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -616,6 +923,7 @@ mod tests {
             order_by: vec![],
             limit: None,
             return_expr: Some(Ast::Symbol("e".to_string(), Span::default())),
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -643,6 +951,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -651,6 +962,7 @@ mod tests {
             order_by: vec![],
             limit: None,
             return_expr: None,
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -671,6 +983,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -679,6 +994,7 @@ mod tests {
             order_by: vec![],
             limit: None,
             return_expr: None,
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -709,6 +1025,9 @@ mod tests {
                     },
                 ],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -717,6 +1036,7 @@ mod tests {
             order_by: vec![],
             limit: None,
             return_expr: Some(Ast::Symbol("e".to_string(), Span::default())),
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -741,6 +1061,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -749,6 +1072,7 @@ mod tests {
             order_by: vec![],
             limit: Some(2),
             return_expr: Some(Ast::Symbol("e".to_string(), Span::default())),
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -772,6 +1096,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -780,6 +1107,7 @@ mod tests {
             order_by: vec![],
             limit: None,
             return_expr: Some(Ast::Symbol("e".to_string(), Span::default())),
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -805,6 +1133,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -813,6 +1144,7 @@ mod tests {
             order_by: vec![],
             limit: None,
             return_expr: Some(Ast::Symbol("hp".to_string(), Span::default())),
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -846,6 +1178,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -854,6 +1189,7 @@ mod tests {
             order_by: vec![("l".to_string(), OrderDirection::Desc)],
             limit: None,
             return_expr: Some(Ast::Symbol("l".to_string(), Span::default())),
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -889,6 +1225,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -897,6 +1236,7 @@ mod tests {
             order_by: vec![("hp".to_string(), OrderDirection::Asc)],
             limit: Some(2),
             return_expr: Some(Ast::Symbol("e".to_string(), Span::default())),
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -922,6 +1262,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -930,6 +1273,7 @@ mod tests {
             order_by: vec![],
             limit: None,
             return_expr: None,
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -953,6 +1297,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -961,6 +1308,7 @@ mod tests {
             order_by: vec![],
             limit: None,
             return_expr: None,
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -985,6 +1333,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -993,6 +1344,7 @@ mod tests {
             order_by: vec![],
             limit: None,
             return_expr: None,
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -1017,6 +1369,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -1025,6 +1380,7 @@ mod tests {
             order_by: vec![],
             limit: None,
             return_expr: Some(Ast::Symbol("e".to_string(), Span::default())),
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -1062,6 +1418,9 @@ mod tests {
                     },
                 ],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -1070,6 +1429,7 @@ mod tests {
             order_by: vec![],
             limit: None,
             return_expr: Some(Ast::Symbol("e".to_string(), Span::default())),
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -1099,6 +1459,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -1107,6 +1470,7 @@ mod tests {
             order_by: vec![("e".to_string(), OrderDirection::Asc)],
             limit: None,
             return_expr: Some(Ast::Symbol("e".to_string(), Span::default())),
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -1136,6 +1500,9 @@ mod tests {
                     span: Span::default(),
                 }],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -1144,6 +1511,7 @@ mod tests {
             order_by: vec![("hp".to_string(), OrderDirection::Asc)],
             limit: None,
             return_expr: Some(Ast::Symbol("e".to_string(), Span::default())),
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -1199,6 +1567,9 @@ mod tests {
                     },
                 ],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             aggregates: vec![],
@@ -1210,6 +1581,7 @@ mod tests {
             ],
             limit: None,
             return_expr: None,
+            suppress: vec![],
             span: Span::default(),
         };
 
@@ -1226,4 +1598,168 @@ mod tests {
             QueryWarning::EntityOrderingUnstable { variable } if variable == "e2"
         )));
     }
+
+    #[test]
+    fn suppressed_warnings_are_dropped() {
+        let mut world = setup_world();
+
+        let mut query_decl = QueryDecl::new(Span::default());
+        query_decl.pattern.clauses.push(PatternClause {
+            entity_var: "e".to_string(),
+            component: "health".to_string(),
+            value: PatternValue::Variable("hp".to_string()),
+            span: Span::default(),
+        });
+        query_decl.order_by = vec![("e".to_string(), OrderDirection::Asc)];
+        query_decl.suppress = vec!["unstable-order".to_string()];
+
+        let mut compiled = QueryCompiler::compile(&query_decl, world.interner_mut()).unwrap();
+        assert!(compiled.warnings.is_empty());
+
+        compiled.add_warnings([QueryWarning::HighFanOut {
+            relationship: "contains".to_string(),
+            variable: "item".to_string(),
+            max_fan_out: 300,
+        }]);
+        assert_eq!(compiled.warnings.len(), 1);
+        assert_eq!(compiled.warnings[0].code(), "high-fan-out");
+
+        query_decl.suppress = vec!["no-such-warning".to_string()];
+        let err = QueryCompiler::compile(&query_decl, world.interner_mut()).unwrap_err();
+        assert!(err.to_string().contains(":no-such-warning"));
+    }
+
+    #[test]
+    fn warning_when_traversing_high_fan_out_relationship() {
+        use longtable_storage::RelationshipSchema;
+
+        let mut world = setup_world();
+        let contains = world.interner_mut().intern_keyword("contains");
+        let name = world.interner_mut().intern_keyword("name");
+        world = world
+            .register_relationship(RelationshipSchema::new(contains))
+            .unwrap();
+        let chest = world.with_component(name).next().unwrap();
+        for _ in 0..3 {
+            let (w, item) = world.spawn(&LtMap::new()).unwrap();
+            world = w.link(chest, contains, item).unwrap();
+        }
+
+        let clause = |entity: &str, component: &str, value: &str| PatternClause {
+            entity_var: entity.to_string(),
+            component: component.to_string(),
+            value: PatternValue::Variable(value.to_string()),
+            span: Span::default(),
+        };
+        let query = |clauses: Vec<PatternClause>| QueryDecl {
+            pattern: Pattern {
+                clauses,
+                negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
+            },
+            bindings: vec![],
+            aggregates: vec![],
+            group_by: vec![],
+            guards: vec![],
+            order_by: vec![],
+            limit: None,
+            return_expr: Some(Ast::Symbol("item".to_string(), Span::default())),
+            suppress: vec![],
+            span: Span::default(),
+        };
+
+        // [?c :name ?n] [?c :contains ?item] - nothing narrows ?item
+        let unfiltered = QueryCompiler::compile(
+            &query(vec![
+                clause("c", "name", "n"),
+                clause("c", "contains", "item"),
+            ]),
+            world.interner_mut(),
+        )
+        .unwrap();
+        assert_eq!(
+            QueryCompiler::fan_out_warnings(&unfiltered, &world, 3),
+            vec![QueryWarning::HighFanOut {
+                relationship: "contains".to_string(),
+                variable: "item".to_string(),
+                max_fan_out: 3,
+            }]
+        );
+        assert!(QueryCompiler::fan_out_warnings(&unfiltered, &world, 4).is_empty());
+
+        // A later clause on ?item filters the traversal
+        let filtered = QueryCompiler::compile(
+            &query(vec![
+                clause("c", "name", "n"),
+                clause("c", "contains", "item"),
+                clause("item", "health", "hp"),
+            ]),
+            world.interner_mut(),
+        )
+        .unwrap();
+        assert!(QueryCompiler::fan_out_warnings(&filtered, &world, 3).is_empty());
+
+        // An unbound source is a scan, not a traversal
+        let scan = QueryCompiler::compile(
+            &query(vec![clause("c", "contains", "item")]),
+            world.interner_mut(),
+        )
+        .unwrap();
+        assert!(QueryCompiler::fan_out_warnings(&scan, &world, 3).is_empty());
+    }
+
+    #[test]
+    fn plan_matches_the_most_selective_clause_first() {
+        let mut world = setup_world();
+        let boss = world.interner_mut().intern_keyword("boss");
+        world = world
+            .register_component(ComponentSchema::new(boss))
+            .unwrap();
+        let health = world.interner_mut().intern_keyword("health");
+        let first = world.with_component(health).next().unwrap();
+        world = world.set(first, boss, Value::Map(LtMap::new())).unwrap();
+
+        let clause = |entity: &str, component: &str, value: &str| PatternClause {
+            entity_var: entity.to_string(),
+            component: component.to_string(),
+            value: PatternValue::Variable(value.to_string()),
+            span: Span::default(),
+        };
+        let mut query_decl = QueryDecl::new(Span::default());
+        query_decl.pattern.clauses = vec![
+            clause("e", "name", "n"),
+            clause("e", "health", "hp"),
+            clause("e", "boss", "b"),
+        ];
+        query_decl.return_expr = Some(Ast::Symbol("e".to_string(), Span::default()));
+
+        let mut compiled = QueryCompiler::compile(&query_decl, world.interner_mut()).unwrap();
+        let unplanned = QueryExecutor::execute(&compiled, &world).unwrap();
+        QueryCompiler::plan(&mut compiled, &world);
+
+        let plan = compiled.plan.as_ref().unwrap();
+        assert!(plan.is_reordered());
+        assert_eq!(
+            plan.steps
+                .iter()
+                .map(|s| (s.written, s.access, s.estimate))
+                .collect::<Vec<_>>(),
+            [
+                (2, PlanAccess::Scan, 1),
+                (0, PlanAccess::Lookup, 1),
+                (1, PlanAccess::Lookup, 1),
+            ]
+        );
+        assert_eq!(plan.estimated_rows(), [1, 1, 1]);
+        assert_eq!(compiled.pattern.clauses[0].component, boss);
+
+        // The order changes the work, not the answer
+        assert_eq!(
+            QueryExecutor::execute(&compiled, &world).unwrap(),
+            unplanned
+        );
+        assert_eq!(unplanned, vec![Value::EntityRef(first)]);
+    }
 }
@@ -2,16 +2,37 @@
 //!
 //! This module provides the rule engine that manages rule execution,
 //! refraction, and the run-to-quiescence loop.
+//!
+//! Refraction keeps an activation from firing over and over for the same
+//! match. Each rule declares how long that lasts with `:refraction`:
+//!
+//! - `:every-tick` (the default): once per tick for each set of matched
+//!   entities
+//! - `:on-change`: again only once a value it matched has changed, in this
+//!   tick or a later one
+//! - `:once-per-entity`: once ever for each set of matched entities
+//!
+//! The last two are remembered across ticks, so rules need no flag
+//! components to avoid refiring.
+//!
+//! Between ticks, [`ProductionRuleEngine::agenda`] lists the activations
+//! that would fire next, in firing order, each with an id that stays the
+//! same while it is pending. [`ProductionRuleEngine::cancel`] takes one off
+//! the agenda until the rules next run.
 
 pub mod compiler;
+pub mod metrics;
 
 pub use compiler::{CompiledRuleBody, FullCompiledRule, RuleCompiler};
+pub use metrics::{RuleMetrics, RuleStats};
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 
+use longtable_foundation::clock::Instant;
 use longtable_foundation::{Error, KeywordId, Result, SemanticLimit};
-use longtable_language::VmEffect;
+use longtable_language::declaration::Refraction;
+use longtable_language::{Span, VmEffect};
 use longtable_storage::World;
 
 use crate::pattern::{Bindings, CompiledPattern, PatternMatcher};
@@ -25,12 +46,24 @@ use crate::pattern::{Bindings, CompiledPattern, PatternMatcher};
 pub struct CompiledRule {
     /// Rule name (interned keyword)
     pub name: KeywordId,
+    /// Docstring
+    pub doc: Option<String>,
     /// Priority (higher fires first)
     pub salience: i32,
+    /// Rules this rule must fire before
+    pub before: Vec<KeywordId>,
+    /// Rules this rule must fire after
+    pub after: Vec<KeywordId>,
+    /// Position in the rule ordering (lower fires first), set by [`RuleCompiler::order`]
+    pub rank: usize,
+    /// Rule group this rule belongs to, if any
+    pub group: Option<KeywordId>,
     /// Compiled pattern for matching
     pub pattern: CompiledPattern,
     /// Fire only once per tick
     pub once: bool,
+    /// When the rule may fire again for bindings it already fired for
+    pub refraction: Refraction,
     /// Whether rule is enabled
     pub enabled: bool,
 }
@@ -41,13 +74,26 @@ impl CompiledRule {
     pub fn new(name: KeywordId, pattern: CompiledPattern) -> Self {
         Self {
             name,
+            doc: None,
             salience: 0,
+            before: Vec::new(),
+            after: Vec::new(),
+            rank: 0,
+            group: None,
             pattern,
             once: false,
+            refraction: Refraction::default(),
             enabled: true,
         }
     }
 
+    /// Sets the docstring.
+    #[must_use]
+    pub fn with_doc(mut self, doc: Option<String>) -> Self {
+        self.doc = doc;
+        self
+    }
+
     /// Sets the salience (priority).
     #[must_use]
     pub fn with_salience(mut self, salience: i32) -> Self {
@@ -55,26 +101,83 @@ impl CompiledRule {
         self
     }
 
+    /// Sets the rules this rule must fire before.
+    #[must_use]
+    pub fn with_before(mut self, rules: Vec<KeywordId>) -> Self {
+        self.before = rules;
+        self
+    }
+
+    /// Sets the rules this rule must fire after.
+    #[must_use]
+    pub fn with_after(mut self, rules: Vec<KeywordId>) -> Self {
+        self.after = rules;
+        self
+    }
+
+    /// Sets the rule group.
+    #[must_use]
+    pub fn with_group(mut self, group: Option<KeywordId>) -> Self {
+        self.group = group;
+        self
+    }
+
     /// Sets the once flag.
     #[must_use]
     pub fn with_once(mut self, once: bool) -> Self {
         self.once = once;
         self
     }
+
+    /// Sets the refraction policy.
+    #[must_use]
+    pub fn with_refraction(mut self, refraction: Refraction) -> Self {
+        self.refraction = refraction;
+        self
+    }
 }
 
 impl From<FullCompiledRule> for CompiledRule {
     fn from(full: FullCompiledRule) -> Self {
         Self {
             name: full.name,
+            doc: full.doc,
             salience: full.salience,
+            before: full.before,
+            after: full.after,
+            rank: full.rank,
+            group: full.group,
             pattern: full.pattern,
             once: full.once,
+            refraction: full.refraction,
             enabled: full.enabled,
         }
     }
 }
 
+/// A rule with an empty body, which only counts its activations.
+impl From<CompiledRule> for FullCompiledRule {
+    fn from(rule: CompiledRule) -> Self {
+        Self {
+            name: rule.name,
+            doc: rule.doc,
+            salience: rule.salience,
+            before: rule.before,
+            after: rule.after,
+            rank: rule.rank,
+            group: rule.group,
+            pattern: rule.pattern,
+            once: rule.once,
+            refraction: rule.refraction,
+            enabled: rule.enabled,
+            body: CompiledRuleBody::new(),
+            bindings: Vec::new(),
+            span: Span::default(),
+            file: None,
+        }
+    }
+}
+
 // =============================================================================
 // Activation
 // =============================================================================
@@ -88,6 +191,8 @@ pub struct Activation {
     pub bindings: Bindings,
     /// Rule salience
     pub salience: i32,
+    /// Rule rank from `:before`/`:after` ordering
+    pub rank: usize,
     /// Pattern specificity (number of clauses)
     pub specificity: usize,
 }
@@ -104,6 +209,15 @@ impl Activation {
     }
 }
 
+/// A pending activation listed by [`ProductionRuleEngine::agenda`].
+#[derive(Clone, Debug)]
+pub struct AgendaEntry {
+    /// Identifies the activation to [`ProductionRuleEngine::cancel`]
+    pub id: u64,
+    /// The activation
+    pub activation: Activation,
+}
+
 // =============================================================================
 // Effect Record
 // =============================================================================
@@ -128,12 +242,27 @@ pub struct ProductionRuleEngine {
     refracted: HashSet<u64>,
     /// Rules that fired with :once flag
     once_fired: HashSet<KeywordId>,
+    /// Activations of `:on-change` and `:once-per-entity` rules that have
+    /// fired, by refraction key, with the value key they fired with
+    /// (persists across ticks)
+    fired: HashMap<u64, u64>,
+    /// Activations cancelled from the agenda, by refraction key, skipped
+    /// until the rules next run to quiescence
+    cancelled: HashSet<u64>,
+    /// Ids of the activations last listed on the agenda, by refraction key
+    agenda_ids: HashMap<u64, u64>,
+    /// Last agenda id handed out
+    last_agenda_id: u64,
     /// Effect log for this tick
     effects: Vec<EffectRecord>,
     /// Number of activations this tick (for kill switch)
     activation_count: usize,
     /// Maximum activations per tick
     max_activations: usize,
+    /// Rule groups switched off (persists across ticks)
+    disabled_groups: HashSet<KeywordId>,
+    /// Per-rule match and execution costs this tick
+    metrics: RuleMetrics,
 }
 
 impl Default for ProductionRuleEngine {
@@ -149,9 +278,15 @@ impl ProductionRuleEngine {
         Self {
             refracted: HashSet::new(),
             once_fired: HashSet::new(),
+            fired: HashMap::new(),
+            cancelled: HashSet::new(),
+            agenda_ids: HashMap::new(),
+            last_agenda_id: 0,
             effects: Vec::new(),
             activation_count: 0,
             max_activations: 10_000, // Kill switch
+            disabled_groups: HashSet::new(),
+            metrics: RuleMetrics::new(),
         }
     }
 
@@ -168,11 +303,100 @@ impl ProductionRuleEngine {
         self.once_fired.clear();
         self.effects.clear();
         self.activation_count = 0;
+        self.metrics.clear();
     }
 
-    /// Find all current activations, respecting refraction.
+    /// Forgets which activations of `:on-change` and `:once-per-entity`
+    /// rules have fired, so they may all fire again.
+    pub fn reset_refraction(&mut self) {
+        self.fired.clear();
+    }
+
+    /// Enables a rule group. Returns true if it was disabled.
+    pub fn enable_group(&mut self, group: KeywordId) -> bool {
+        self.disabled_groups.remove(&group)
+    }
+
+    /// Disables a rule group so none of its rules activate. Returns true if it was enabled.
+    pub fn disable_group(&mut self, group: KeywordId) -> bool {
+        self.disabled_groups.insert(group)
+    }
+
+    /// Returns true unless the group has been disabled.
     #[must_use]
-    pub fn find_activations(&self, rules: &[CompiledRule], world: &World) -> Vec<Activation> {
+    pub fn is_group_enabled(&self, group: KeywordId) -> bool {
+        !self.disabled_groups.contains(&group)
+    }
+
+    /// Returns the currently disabled rule groups.
+    #[must_use]
+    pub fn disabled_groups(&self) -> &HashSet<KeywordId> {
+        &self.disabled_groups
+    }
+
+    /// Find all current activations, respecting refraction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a rule's pattern fails to match.
+    pub fn find_activations(
+        &self,
+        rules: &[CompiledRule],
+        world: &World,
+    ) -> Result<Vec<Activation>> {
+        self.match_rules(rules, world, None)
+    }
+
+    /// Lists the activations that would fire next, in firing order.
+    ///
+    /// An activation keeps its id across calls for as long as it stays
+    /// pending.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a rule's pattern fails to match.
+    pub fn agenda(&mut self, rules: &[CompiledRule], world: &World) -> Result<Vec<AgendaEntry>> {
+        let activations = self.find_activations(rules, world)?;
+        let mut ids = HashMap::new();
+        let entries = activations
+            .into_iter()
+            .map(|activation| {
+                let key = activation.refraction_key();
+                let id = self.agenda_ids.get(&key).copied().unwrap_or_else(|| {
+                    self.last_agenda_id += 1;
+                    self.last_agenda_id
+                });
+                ids.insert(key, id);
+                AgendaEntry { id, activation }
+            })
+            .collect();
+        self.agenda_ids = ids;
+        Ok(entries)
+    }
+
+    /// Takes the activation listed under `id` by [`Self::agenda`] off the
+    /// agenda, so it doesn't fire when the rules next run. Returns false if
+    /// no activation was listed under `id`.
+    pub fn cancel(&mut self, id: u64) -> bool {
+        let Some(key) = self
+            .agenda_ids
+            .iter()
+            .find_map(|(key, listed)| (*listed == id).then_some(*key))
+        else {
+            return false;
+        };
+        self.agenda_ids.remove(&key);
+        self.cancelled.insert(key)
+    }
+
+    /// Finds activations like [`Self::find_activations`], recording how long
+    /// each rule's pattern took to match in `metrics` if given.
+    fn match_rules(
+        &self,
+        rules: &[CompiledRule],
+        world: &World,
+        mut metrics: Option<&mut RuleMetrics>,
+    ) -> Result<Vec<Activation>> {
         let mut activations = Vec::new();
 
         for rule in rules {
@@ -181,24 +405,39 @@ impl ProductionRuleEngine {
                 continue;
             }
 
+            // Skip rules in disabled groups
+            if rule
+                .group
+                .is_some_and(|g| self.disabled_groups.contains(&g))
+            {
+                continue;
+            }
+
             // Skip :once rules that already fired
             if rule.once && self.once_fired.contains(&rule.name) {
                 continue;
             }
 
             // Find pattern matches
-            let matches = PatternMatcher::match_pattern(&rule.pattern, world);
+            let started = Instant::now();
+            let matches = PatternMatcher::match_pattern(&rule.pattern, world)?;
+            if let Some(metrics) = metrics.as_deref_mut() {
+                metrics.record_match(rule.name, started.elapsed());
+            }
 
             for bindings in matches {
                 let activation = Activation {
                     rule_name: rule.name,
                     bindings,
                     salience: rule.salience,
+                    rank: rule.rank,
                     specificity: rule.pattern.clauses.len(),
                 };
 
-                // Skip if refracted
-                if self.refracted.contains(&activation.refraction_key()) {
+                // Skip if refracted or cancelled
+                if self.is_refracted(rule.refraction, &activation)
+                    || self.cancelled.contains(&activation.refraction_key())
+                {
                     continue;
                 }
 
@@ -206,14 +445,26 @@ impl ProductionRuleEngine {
             }
         }
 
-        // Sort by salience (descending), then specificity (descending)
+        // Sort by rank (ascending), then salience and specificity (descending)
         activations.sort_by(|a, b| {
-            b.salience
-                .cmp(&a.salience)
+            a.rank
+                .cmp(&b.rank)
+                .then_with(|| b.salience.cmp(&a.salience))
                 .then_with(|| b.specificity.cmp(&a.specificity))
         });
 
-        activations
+        Ok(activations)
+    }
+
+    /// Returns true if `activation` has already fired as far as `refraction`
+    /// is concerned.
+    fn is_refracted(&self, refraction: Refraction, activation: &Activation) -> bool {
+        let key = activation.refraction_key();
+        match refraction {
+            Refraction::EveryTick => self.refracted.contains(&key),
+            Refraction::OnChange => self.fired.get(&key) == Some(&activation.bindings.value_key()),
+            Refraction::OncePerEntity => self.fired.contains_key(&key),
+        }
     }
 
     /// Fire an activation.
@@ -247,15 +498,22 @@ impl ProductionRuleEngine {
         // Record refraction
         self.refracted.insert(activation.refraction_key());
 
-        // Track :once rules
+        // Track :once rules, and refraction that outlasts the tick
         if let Some(rule) = rules.iter().find(|r| r.name == activation.rule_name) {
             if rule.once {
                 self.once_fired.insert(activation.rule_name);
             }
+            if rule.refraction != Refraction::EveryTick {
+                self.fired
+                    .insert(activation.refraction_key(), activation.bindings.value_key());
+            }
         }
 
         // Execute and collect effects
+        let started = Instant::now();
         let (effects, new_world) = execute(activation, &world)?;
+        self.metrics
+            .record_fire(activation.rule_name, started.elapsed());
 
         // Record effects
         for effect in effects {
@@ -285,7 +543,10 @@ impl ProductionRuleEngine {
         F: FnMut(&Activation, &World) -> Result<(Vec<VmEffect>, World)>,
     {
         loop {
-            let activations = self.find_activations(rules, &world);
+            let mut metrics = std::mem::take(&mut self.metrics);
+            let activations = self.match_rules(rules, &world, Some(&mut metrics));
+            self.metrics = metrics;
+            let activations = activations?;
 
             if activations.is_empty() {
                 break;
@@ -296,6 +557,7 @@ impl ProductionRuleEngine {
             world = self.fire(activation, world, rules, &mut execute)?;
         }
 
+        self.cancelled.clear();
         Ok(world)
     }
 
@@ -310,6 +572,12 @@ impl ProductionRuleEngine {
     pub fn activation_count(&self) -> usize {
         self.activation_count
     }
+
+    /// Returns what each rule has cost so far this tick.
+    #[must_use]
+    pub fn metrics(&self) -> &RuleMetrics {
+        &self.metrics
+    }
 }
 
 // =============================================================================
@@ -321,7 +589,6 @@ mod tests {
     use super::*;
     use crate::pattern::PatternCompiler;
     use longtable_foundation::{LtMap, Value};
-    use longtable_language::Span;
     use longtable_language::declaration::{Pattern as DeclPattern, PatternClause, PatternValue};
     use longtable_storage::ComponentSchema;
 
@@ -352,6 +619,100 @@ mod tests {
         (world, health, processed)
     }
 
+    #[test]
+    fn refraction_policies_decide_when_rules_refire() {
+        let (mut world, health, _processed) = setup_world_with_entities();
+        let decl_pattern = DeclPattern {
+            clauses: vec![PatternClause {
+                entity_var: "e".to_string(),
+                component: "health".to_string(),
+                value: PatternValue::Variable("hp".to_string()),
+                span: Span::default(),
+            }],
+            negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
+        };
+        let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
+        let rules: Vec<_> = Refraction::ALL
+            .into_iter()
+            .map(|refraction| {
+                let name = world.interner_mut().intern_keyword(refraction.name());
+                CompiledRule::new(name, compiled.clone()).with_refraction(refraction)
+            })
+            .collect();
+
+        // Runs a tick, counting how often each rule fires
+        let mut engine = ProductionRuleEngine::new();
+        let mut tick = |world: &World| {
+            let mut fired = [0; 3];
+            engine.begin_tick();
+            engine
+                .run_to_quiescence(&rules, world.clone(), |activation, w| {
+                    let i = rules.iter().position(|r| r.name == activation.rule_name);
+                    fired[i.unwrap()] += 1;
+                    Ok((vec![], w.clone()))
+                })
+                .unwrap();
+            fired
+        };
+
+        assert_eq!(tick(&world), [2, 2, 2]);
+        assert_eq!(tick(&world), [2, 0, 0]);
+
+        // A changed value refires :on-change for that entity only
+        let entity = world.with_component(health).next().unwrap();
+        let hp = world.interner_mut().intern_keyword("hp");
+        let wounded = LtMap::new().insert(Value::Keyword(hp), Value::Int(1));
+        let world = world.set(entity, health, Value::Map(wounded)).unwrap();
+        assert_eq!(tick(&world), [2, 1, 0]);
+        assert_eq!(tick(&world), [2, 0, 0]);
+    }
+
+    #[test]
+    fn agenda_ids_are_stable_and_cancels_last_one_run() {
+        let (mut world, _health, _processed) = setup_world_with_entities();
+        let decl_pattern = DeclPattern {
+            clauses: vec![PatternClause {
+                entity_var: "e".to_string(),
+                component: "health".to_string(),
+                value: PatternValue::Wildcard,
+                span: Span::default(),
+            }],
+            negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
+        };
+        let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
+        let rule_name = world.interner_mut().intern_keyword("test-rule");
+        let rules = vec![CompiledRule::new(rule_name, compiled)];
+
+        let mut engine = ProductionRuleEngine::new();
+        let agenda = engine.agenda(&rules, &world).unwrap();
+        let ids: Vec<u64> = agenda.iter().map(|entry| entry.id).collect();
+        assert_eq!(ids.len(), 2);
+
+        assert!(engine.cancel(ids[0]));
+        assert!(!engine.cancel(ids[0]));
+        let agenda = engine.agenda(&rules, &world).unwrap();
+        assert_eq!(agenda.len(), 1);
+        assert_eq!(agenda[0].id, ids[1]);
+
+        let mut fired = 0;
+        engine.begin_tick();
+        engine
+            .run_to_quiescence(&rules, world.clone(), |_, w| {
+                fired += 1;
+                Ok((vec![], w.clone()))
+            })
+            .unwrap();
+        assert_eq!(fired, 1);
+        engine.begin_tick();
+        assert_eq!(engine.agenda(&rules, &world).unwrap().len(), 2);
+    }
+
     #[test]
     fn find_activations_respects_refraction() {
         let (mut world, _health, _processed) = setup_world_with_entities();
@@ -365,6 +726,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
 
@@ -376,14 +740,14 @@ mod tests {
         engine.begin_tick();
 
         // First find should return 2 activations
-        let activations = engine.find_activations(&rules, &world);
+        let activations = engine.find_activations(&rules, &world).unwrap();
         assert_eq!(activations.len(), 2);
 
         // Mark one as refracted
         engine.refracted.insert(activations[0].refraction_key());
 
         // Now should return only 1
-        let activations = engine.find_activations(&rules, &world);
+        let activations = engine.find_activations(&rules, &world).unwrap();
         assert_eq!(activations.len(), 1);
     }
 
@@ -400,6 +764,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
 
@@ -414,10 +781,45 @@ mod tests {
         engine.once_fired.insert(rule_name);
 
         // Should find no activations
-        let activations = engine.find_activations(&rules, &world);
+        let activations = engine.find_activations(&rules, &world).unwrap();
         assert!(activations.is_empty());
     }
 
+    #[test]
+    fn disabled_groups_do_not_activate() {
+        let (mut world, _health, _processed) = setup_world_with_entities();
+
+        let decl_pattern = DeclPattern {
+            clauses: vec![PatternClause {
+                entity_var: "e".to_string(),
+                component: "health".to_string(),
+                value: PatternValue::Wildcard,
+                span: Span::default(),
+            }],
+            negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
+        };
+        let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
+
+        let rule_name = world.interner_mut().intern_keyword("attack");
+        let combat = world.interner_mut().intern_keyword("combat");
+        let rule = CompiledRule::new(rule_name, compiled).with_group(Some(combat));
+        let rules = vec![rule];
+
+        let mut engine = ProductionRuleEngine::new();
+        assert!(engine.disable_group(combat));
+        assert!(!engine.is_group_enabled(combat));
+
+        // Disabled groups survive the start of a new tick
+        engine.begin_tick();
+        assert!(engine.find_activations(&rules, &world).unwrap().is_empty());
+
+        assert!(engine.enable_group(combat));
+        assert_eq!(engine.find_activations(&rules, &world).unwrap().len(), 2);
+    }
+
     #[test]
     fn activation_sorting_by_salience() {
         let (mut world, _health, _processed) = setup_world_with_entities();
@@ -431,6 +833,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
 
         let compiled1 = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
@@ -447,7 +852,7 @@ mod tests {
         let mut engine = ProductionRuleEngine::new();
         engine.begin_tick();
 
-        let activations = engine.find_activations(&rule_list, &world);
+        let activations = engine.find_activations(&rule_list, &world).unwrap();
 
         // Higher salience should be first
         assert!(!activations.is_empty());
@@ -469,6 +874,7 @@ mod tests {
             rule_name: world.interner_mut().intern_keyword("test"),
             bindings: Bindings::new(),
             salience: 0,
+            rank: 0,
             specificity: 0,
         };
 
@@ -498,6 +904,9 @@ mod tests {
                 value: PatternValue::Wildcard,
                 span: Span::default(),
             }],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         let compiled = PatternCompiler::compile(&decl_pattern, world.interner_mut()).unwrap();
 
@@ -509,7 +918,7 @@ mod tests {
         engine.begin_tick();
 
         // Initially should have 2 activations (2 entities with health, none processed)
-        let initial_activations = engine.find_activations(&rules, &world);
+        let initial_activations = engine.find_activations(&rules, &world).unwrap();
         assert_eq!(initial_activations.len(), 2);
 
         // Run to quiescence with an executor that marks entities as processed
@@ -573,6 +982,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         let compiled1 = PatternCompiler::compile(&pattern1, world.interner_mut()).unwrap();
         let rule1_name = world.interner_mut().intern_keyword("rule1");
@@ -595,6 +1007,9 @@ mod tests {
                 },
             ],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         let compiled2 = PatternCompiler::compile(&pattern2, world.interner_mut()).unwrap();
         let rule2_name = world.interner_mut().intern_keyword("rule2");
@@ -609,6 +1024,9 @@ mod tests {
                 span: Span::default(),
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         let compiled3 = PatternCompiler::compile(&pattern3, world.interner_mut()).unwrap();
         let rule3_name = world.interner_mut().intern_keyword("rule3");
@@ -619,7 +1037,7 @@ mod tests {
         let mut engine = ProductionRuleEngine::new();
         engine.begin_tick();
 
-        let activations = engine.find_activations(&all_rules, &world);
+        let activations = engine.find_activations(&all_rules, &world).unwrap();
 
         // Activations for e1 should be ordered:
         // 1. rule3 (salience 20) - highest salience wins
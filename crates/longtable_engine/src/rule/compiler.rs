@@ -1,12 +1,22 @@
 //! Rule compiler - transforms declaration AST into executable rules.
 //!
 //! Compiles `RuleDecl` from the language crate into `CompiledRule` for execution.
+//! Also orders rules according to their `:before`/`:after` constraints.
+//!
+//! Compiled rules keep the spans of their guard and effect expressions, so a
+//! runtime error in a rule body reports the file, line, and rule it came from.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 
-use longtable_foundation::{Interner, KeywordId, Result};
-use longtable_language::declaration::RuleDecl;
-use longtable_language::{Ast, Bytecode, compile_expression};
+use longtable_foundation::{Error, ErrorContext, ErrorKind, Interner, KeywordId, Result, Value};
+use longtable_language::declaration::{Refraction, RuleDecl};
+use longtable_language::{
+    Ast, CompiledProgram, Span, Vm, VmEffect, WorldContext, compile_expression_with_interner,
+};
+use longtable_storage::World;
 
-use crate::pattern::{CompiledPattern, PatternCompiler};
+use crate::pattern::{Bindings, CompiledPattern, PatternCompiler};
 
 // =============================================================================
 // Compiled Rule Body
@@ -15,10 +25,23 @@ use crate::pattern::{CompiledPattern, PatternCompiler};
 /// A compiled rule body ready for execution.
 #[derive(Clone, Debug)]
 pub struct CompiledRuleBody {
-    /// Compiled effect expressions as bytecode
-    pub effects: Vec<Bytecode>,
-    /// Compiled guard expressions as bytecode
-    pub guards: Vec<Bytecode>,
+    /// Pattern variables followed by new `:let` names, in the order their
+    /// values are bound in the VM
+    pub binding_vars: Vec<String>,
+    /// Compiled `:let` expressions, evaluated in order before the guards
+    pub lets: Vec<CompiledProgram>,
+    /// Index in `binding_vars` each `:let` value is bound to
+    pub let_slots: Vec<usize>,
+    /// Source spans of the `:let` expressions
+    pub let_spans: Vec<Span>,
+    /// Compiled effect expressions
+    pub effects: Vec<CompiledProgram>,
+    /// Source spans of the effect expressions
+    pub effect_spans: Vec<Span>,
+    /// Compiled guard expressions
+    pub guards: Vec<CompiledProgram>,
+    /// Source spans of the guard expressions
+    pub guard_spans: Vec<Span>,
 }
 
 impl Default for CompiledRuleBody {
@@ -32,8 +55,14 @@ impl CompiledRuleBody {
     #[must_use]
     pub fn new() -> Self {
         Self {
+            binding_vars: Vec::new(),
+            lets: Vec::new(),
+            let_slots: Vec::new(),
+            let_spans: Vec::new(),
             effects: Vec::new(),
+            effect_spans: Vec::new(),
             guards: Vec::new(),
+            guard_spans: Vec::new(),
         }
     }
 }
@@ -47,18 +76,124 @@ impl CompiledRuleBody {
 pub struct FullCompiledRule {
     /// Rule name (interned keyword)
     pub name: KeywordId,
+    /// Docstring
+    pub doc: Option<String>,
     /// Priority (higher fires first)
     pub salience: i32,
+    /// Rules this rule must fire before
+    pub before: Vec<KeywordId>,
+    /// Rules this rule must fire after
+    pub after: Vec<KeywordId>,
+    /// Position in the rule ordering (lower fires first)
+    pub rank: usize,
+    /// Rule group this rule belongs to, if any
+    pub group: Option<KeywordId>,
     /// Compiled pattern for matching
     pub pattern: CompiledPattern,
     /// Fire only once per tick
     pub once: bool,
+    /// When the rule may fire again for bindings it already fired for
+    pub refraction: Refraction,
     /// Whether rule is enabled
     pub enabled: bool,
     /// Compiled body (guards and effects)
     pub body: CompiledRuleBody,
     /// Local bindings from :let clause (name, AST)
     pub bindings: Vec<(String, Ast)>,
+    /// Source span of the declaration
+    pub span: Span,
+    /// File the rule was loaded from, if any
+    pub file: Option<String>,
+}
+
+impl FullCompiledRule {
+    /// Records the file the rule was loaded from.
+    #[must_use]
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Adds every keyword the rule's pattern and body hold to `live`.
+    pub fn mark_keywords(&self, live: &mut HashSet<KeywordId>) {
+        self.pattern.mark_keywords(live);
+        let programs = self.body.lets.iter().chain(&self.body.guards);
+        for program in programs.chain(&self.body.effects) {
+            program.mark_keywords(live);
+        }
+    }
+
+    /// Builds an error context pointing at `span` within this rule.
+    #[must_use]
+    pub fn error_context(&self, span: Span, interner: &Interner) -> ErrorContext {
+        let name = interner.get_keyword(self.name).unwrap_or("?");
+        let (source, location) = match &self.file {
+            Some(file) => (file.clone(), format!("{file}:{}", span.line)),
+            None => (format!("rule :{name}"), format!("line {}", span.line)),
+        };
+        ErrorContext::new()
+            .with_source(source)
+            .with_position(span.line as usize, span.column as usize)
+            .with_frame(format!("rule :{name} ({location})"))
+    }
+
+    /// Runs the rule body for one activation.
+    ///
+    /// Binds the `:let` values, then returns `None` if a guard rejects the
+    /// bindings, otherwise the effects produced by the `:then` expressions.
+    ///
+    /// # Errors
+    /// Returns the VM error from the first `:let`, guard, or effect that
+    /// fails, with an [`ErrorContext`] naming the file, line, and rule it
+    /// came from.
+    pub fn execute(&self, bindings: &Bindings, world: &World) -> Result<Option<Vec<VmEffect>>> {
+        let mut values: Vec<Value> = self
+            .body
+            .binding_vars
+            .iter()
+            .map(|var| bindings.get(var).cloned().unwrap_or(Value::Nil))
+            .collect();
+        let ctx = WorldContext::new(world);
+        let mut vm = Vm::new();
+
+        let locate = |error: Error, span: Span| -> Error {
+            // The VM points at the innermost form that failed, when it can
+            let inner = error.context.as_ref().and_then(|c| {
+                let line = u32::try_from(c.line?).ok()?;
+                let column = u32::try_from(c.column?).ok()?;
+                Some(Span::new(span.start, span.end, line, column))
+            });
+            let mut context = self.error_context(inner.unwrap_or(span), world.interner());
+            // Keep the backtrace of the functions the error passed through
+            if let Some(inner) = &error.context {
+                context.stack.splice(0..0, inner.stack.iter().cloned());
+            }
+            error.with_context(context)
+        };
+
+        let lets = self.body.lets.iter().zip(&self.body.let_slots);
+        for ((program, &slot), &span) in lets.zip(&self.body.let_spans) {
+            vm.set_bindings(values.clone());
+            values[slot] = vm
+                .execute_with_context(program, &ctx)
+                .map_err(|e| locate(e, span))?;
+        }
+        vm.set_bindings(values);
+
+        for (guard, &span) in self.body.guards.iter().zip(&self.body.guard_spans) {
+            let passed = vm
+                .execute_with_context(guard, &ctx)
+                .map_err(|e| locate(e, span))?;
+            if !passed.is_truthy() {
+                return Ok(None);
+            }
+        }
+        for (effect, &span) in self.body.effects.iter().zip(&self.body.effect_spans) {
+            vm.execute_with_context(effect, &ctx)
+                .map_err(|e| locate(e, span))?;
+        }
+        Ok(Some(vm.take_effects()))
+    }
 }
 
 // =============================================================================
@@ -81,56 +216,243 @@ impl RuleCompiler {
         let pattern = PatternCompiler::compile(&decl.pattern, interner)?;
 
         // Collect all binding variables from the pattern for use in expressions
-        let binding_vars: Vec<String> = decl
+        let mut binding_vars: Vec<String> = decl
             .pattern
             .bound_variables()
             .into_iter()
             .map(String::from)
             .collect();
 
+        // Compile :let expressions; each sees the pattern variables and the
+        // names bound before it, and later expressions see its name
+        let mut lets = Vec::new();
+        let mut let_slots = Vec::new();
+        for (name, ast) in &decl.bindings {
+            lets.push(Self::compile_expr(ast, &binding_vars, interner)?);
+            let slot = binding_vars.iter().position(|v| v == name);
+            let_slots.push(slot.unwrap_or_else(|| {
+                binding_vars.push(name.clone());
+                binding_vars.len() - 1
+            }));
+        }
+
         // Compile guard expressions
         let guards = decl
             .guards
             .iter()
-            .map(|ast| Self::compile_expr(ast, &binding_vars))
+            .map(|ast| Self::compile_expr(ast, &binding_vars, interner))
             .collect::<Result<Vec<_>>>()?;
 
         // Compile effect expressions
         let effects = decl
             .effects
             .iter()
-            .map(|ast| Self::compile_expr(ast, &binding_vars))
+            .map(|ast| Self::compile_expr(ast, &binding_vars, interner))
             .collect::<Result<Vec<_>>>()?;
 
-        let body = CompiledRuleBody { effects, guards };
+        let body = CompiledRuleBody {
+            guard_spans: decl.guards.iter().map(Ast::span).collect(),
+            effect_spans: decl.effects.iter().map(Ast::span).collect(),
+            let_spans: decl.bindings.iter().map(|(_, ast)| ast.span()).collect(),
+            binding_vars,
+            lets,
+            let_slots,
+            effects,
+            guards,
+        };
+
+        let before = decl
+            .before
+            .iter()
+            .map(|r| interner.intern_keyword(r))
+            .collect();
+        let after = decl
+            .after
+            .iter()
+            .map(|r| interner.intern_keyword(r))
+            .collect();
+
+        let group = decl.group.as_deref().map(|g| interner.intern_keyword(g));
 
         Ok(FullCompiledRule {
             name,
+            doc: decl.doc.clone(),
             salience: decl.salience,
+            before,
+            after,
+            rank: 0,
+            group,
             pattern,
             once: decl.once,
+            refraction: decl.refraction,
             enabled: decl.enabled,
             body,
             bindings: decl.bindings.clone(),
+            span: decl.span,
+            file: None,
         })
     }
 
-    /// Compile a single AST expression to bytecode.
-    fn compile_expr(ast: &Ast, binding_vars: &[String]) -> Result<Bytecode> {
-        let compiled = compile_expression(ast, binding_vars)?;
-        Ok(compiled.code)
+    /// Compile a single AST expression to a standalone program.
+    ///
+    /// Keywords are interned so that world lookups in the body resolve.
+    fn compile_expr(
+        ast: &Ast,
+        binding_vars: &[String],
+        interner: &Interner,
+    ) -> Result<CompiledProgram> {
+        let compiled = compile_expression_with_interner(ast, binding_vars, interner.clone())?;
+        Ok(CompiledProgram {
+            code: compiled.code,
+            constants: compiled.constants,
+            functions: Vec::new(),
+        })
     }
 
     /// Compile multiple rule declarations.
     ///
+    /// The result is sorted into firing order (see [`RuleCompiler::order`]).
+    ///
     /// # Errors
-    /// Returns an error if any rule compilation fails.
+    /// Returns an error if any rule compilation fails or the rules'
+    /// `:before`/`:after` constraints form a cycle.
     pub fn compile_all(
         decls: &[RuleDecl],
         interner: &mut Interner,
     ) -> Result<Vec<FullCompiledRule>> {
-        decls.iter().map(|d| Self::compile(d, interner)).collect()
+        let mut rules = decls
+            .iter()
+            .map(|d| Self::compile(d, interner))
+            .collect::<Result<Vec<_>>>()?;
+        assign_ranks(&mut rules, interner)?;
+        Ok(rules)
+    }
+
+    /// Sorts rules into firing order and assigns each its `rank`.
+    ///
+    /// A rule's rank is how many rules its `:before`/`:after` constraints
+    /// make it wait for in a row, so constraints take precedence over
+    /// salience. Rules of the same rank fire by salience, highest first,
+    /// then in declaration order. Constraints naming rules that aren't in
+    /// `rules` are ignored so rules can be loaded incrementally.
+    ///
+    /// # Errors
+    /// Returns an error naming the rules involved if the constraints form a cycle.
+    pub fn order(rules: &mut [FullCompiledRule], interner: &Interner) -> Result<()> {
+        assign_ranks(rules, interner)
+    }
+}
+
+// =============================================================================
+// Rule Ordering
+// =============================================================================
+
+/// A rule as seen by the ordering pass.
+struct OrderNode<'a> {
+    name: KeywordId,
+    before: &'a [KeywordId],
+    after: &'a [KeywordId],
+}
+
+/// Ranks `rules` (see [`RuleCompiler::order`]) and sorts them by rank, then
+/// salience. The sort is stable, so declaration order breaks the last ties.
+fn assign_ranks(rules: &mut [FullCompiledRule], interner: &Interner) -> Result<()> {
+    let nodes: Vec<_> = rules
+        .iter()
+        .map(|rule| OrderNode {
+            name: rule.name,
+            before: &rule.before,
+            after: &rule.after,
+        })
+        .collect();
+    let ranks = topological_ranks(&nodes, interner)?;
+    for (rule, rank) in rules.iter_mut().zip(ranks) {
+        rule.rank = rank;
+    }
+    rules.sort_by_key(|r| (r.rank, Reverse(r.salience)));
+    Ok(())
+}
+
+/// Computes each node's rank with Kahn's algorithm: the length of the
+/// longest chain of constraints leading to it.
+fn topological_ranks(nodes: &[OrderNode<'_>], interner: &Interner) -> Result<Vec<usize>> {
+    let index: HashMap<KeywordId, usize> = nodes
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, n)| (n.name, i))
+        .collect();
+
+    // successors[i] must fire after i; predecessor counts drive the sort
+    let mut successors = vec![Vec::new(); nodes.len()];
+    let mut pending = vec![0usize; nodes.len()];
+    for (i, node) in nodes.iter().enumerate() {
+        let edges = node
+            .before
+            .iter()
+            .filter_map(|r| index.get(r).map(|&j| (i, j)))
+            .chain(
+                node.after
+                    .iter()
+                    .filter_map(|r| index.get(r).map(|&j| (j, i))),
+            );
+        for (from, to) in edges {
+            successors[from].push(to);
+            pending[to] += 1;
+        }
+    }
+
+    let mut ready: Vec<usize> = (0..nodes.len()).filter(|&i| pending[i] == 0).collect();
+    let mut depth = vec![0; nodes.len()];
+    let mut ranks = vec![usize::MAX; nodes.len()];
+    let mut ranked = 0;
+    while let Some(i) = ready.pop() {
+        ranks[i] = depth[i];
+        ranked += 1;
+        for &j in &successors[i] {
+            depth[j] = depth[j].max(depth[i] + 1);
+            pending[j] -= 1;
+            if pending[j] == 0 {
+                ready.push(j);
+            }
+        }
+    }
+
+    if ranked == nodes.len() {
+        return Ok(ranks);
+    }
+
+    // Every unranked rule still waits on an unranked predecessor, so walking
+    // predecessors from any of them must eventually revisit a rule.
+    let mut predecessor = vec![None; nodes.len()];
+    for (i, succs) in successors.iter().enumerate() {
+        for &j in succs {
+            if ranks[i] == usize::MAX && ranks[j] == usize::MAX {
+                predecessor[j] = Some(i);
+            }
+        }
+    }
+    let mut path = Vec::new();
+    let mut current = ranks.iter().position(|&r| r == usize::MAX).unwrap_or(0);
+    while !path.contains(&current) {
+        path.push(current);
+        match predecessor[current] {
+            Some(p) => current = p,
+            None => break,
+        }
     }
+    let start = path.iter().position(|&i| i == current).unwrap_or(0);
+    let mut cycle: Vec<_> = path[start..]
+        .iter()
+        .rev()
+        .map(|&i| format!(":{}", interner.get_keyword(nodes[i].name).unwrap_or("?")))
+        .collect();
+    cycle.push(cycle[0].clone());
+
+    Err(Error::new(ErrorKind::Cycle(format!(
+        "rule ordering cycle: {}",
+        cycle.join(" -> ")
+    ))))
 }
 
 // =============================================================================
@@ -160,8 +482,13 @@ mod tests {
 
         let decl = RuleDecl {
             name: "test-rule".to_string(),
+            doc: None,
             salience: 10,
+            before: vec![],
+            after: vec![],
+            group: None,
             once: false,
+            refraction: Refraction::default(),
             enabled: true,
             pattern: DeclPattern {
                 clauses: vec![make_clause(
@@ -170,6 +497,9 @@ mod tests {
                     PatternValue::Variable("hp".to_string()),
                 )],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             guards: vec![],
@@ -196,8 +526,13 @@ mod tests {
 
         let decl = RuleDecl {
             name: "guarded-rule".to_string(),
+            doc: None,
             salience: 0,
+            before: vec![],
+            after: vec![],
+            group: None,
             once: true,
+            refraction: Refraction::default(),
             enabled: true,
             pattern: DeclPattern {
                 clauses: vec![make_clause(
@@ -206,6 +541,9 @@ mod tests {
                     PatternValue::Variable("hp".to_string()),
                 )],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             guards: vec![guard_ast],
@@ -229,12 +567,20 @@ mod tests {
 
         let decl = RuleDecl {
             name: "effect-rule".to_string(),
+            doc: None,
             salience: 100,
+            before: vec![],
+            after: vec![],
+            group: None,
             once: false,
+            refraction: Refraction::default(),
             enabled: true,
             pattern: DeclPattern {
                 clauses: vec![make_clause("e", "tag", PatternValue::Wildcard)],
                 negations: vec![],
+                not_joins: vec![],
+                disjunctions: vec![],
+                predicates: vec![],
             },
             bindings: vec![],
             guards: vec![],
@@ -256,8 +602,13 @@ mod tests {
 
         let decl = RuleDecl {
             name: "binding-rule".to_string(),
+            doc: None,
             salience: 0,
+            before: vec![],
+            after: vec![],
+            group: None,
             once: false,
+            refraction: Refraction::default(),
             enabled: true,
             pattern: DeclPattern::default(),
             bindings: vec![("threshold".to_string(), binding_value)],
@@ -272,6 +623,95 @@ mod tests {
         assert_eq!(compiled.bindings[0].0, "threshold");
     }
 
+    fn ordered_decl(name: &str, salience: i32, before: &[&str], after: &[&str]) -> RuleDecl {
+        let mut decl = RuleDecl::new(name, Span::default());
+        decl.salience = salience;
+        decl.before = before.iter().map(ToString::to_string).collect();
+        decl.after = after.iter().map(ToString::to_string).collect();
+        decl
+    }
+
+    fn names(rules: &[FullCompiledRule], interner: &Interner) -> Vec<String> {
+        rules
+            .iter()
+            .map(|r| interner.get_keyword(r.name).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn order_respects_before_and_after() {
+        let mut interner = Interner::new();
+
+        let decls = vec![
+            ordered_decl("log", 0, &[], &["damage"]),
+            ordered_decl("damage", 0, &["death"], &[]),
+            ordered_decl("death", 0, &[], &[]),
+            ordered_decl("armor", 0, &["damage"], &[]),
+        ];
+
+        let compiled = RuleCompiler::compile_all(&decls, &mut interner).unwrap();
+
+        assert_eq!(
+            names(&compiled, &interner),
+            vec!["armor", "damage", "log", "death"]
+        );
+        let ranks: Vec<_> = compiled.iter().map(|r| r.rank).collect();
+        assert_eq!(ranks, vec![0, 1, 2, 2]);
+    }
+
+    #[test]
+    fn order_breaks_ties_by_salience() {
+        let mut interner = Interner::new();
+
+        let decls = vec![
+            ordered_decl("low", 0, &[], &[]),
+            ordered_decl("high", 100, &[], &[]),
+            // Constraint wins over salience, but only relative to :high
+            ordered_decl("early", -5, &["high"], &[]),
+            ordered_decl("urgent", 50, &[], &[]),
+            ordered_decl("also-low", 0, &[], &[]),
+        ];
+
+        let compiled = RuleCompiler::compile_all(&decls, &mut interner).unwrap();
+
+        // Within a rank, salience decides, then declaration order
+        assert_eq!(
+            names(&compiled, &interner),
+            vec!["urgent", "low", "also-low", "early", "high"]
+        );
+        let ranks: Vec<_> = compiled.iter().map(|r| r.rank).collect();
+        assert_eq!(ranks, vec![0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn order_ignores_unknown_rules() {
+        let mut interner = Interner::new();
+
+        let decls = vec![ordered_decl("only", 0, &["missing"], &["also-missing"])];
+
+        let compiled = RuleCompiler::compile_all(&decls, &mut interner).unwrap();
+        assert_eq!(compiled.len(), 1);
+    }
+
+    #[test]
+    fn order_detects_cycles() {
+        let mut interner = Interner::new();
+
+        let decls = vec![
+            ordered_decl("unrelated", 0, &[], &[]),
+            ordered_decl("a", 0, &["b"], &[]),
+            ordered_decl("b", 0, &["c"], &[]),
+            ordered_decl("c", 0, &["a"], &[]),
+            ordered_decl("downstream", 0, &[], &["c"]),
+        ];
+
+        let err = RuleCompiler::compile_all(&decls, &mut interner).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("cycle"), "{message}");
+        assert!(message.contains(":a") && message.contains(":b") && message.contains(":c"));
+        assert!(!message.contains("unrelated") && !message.contains("downstream"));
+    }
+
     #[test]
     fn compile_multiple_rules() {
         let mut interner = Interner::new();
@@ -286,4 +726,155 @@ mod tests {
 
         assert_eq!(compiled.len(), 3);
     }
+
+    #[test]
+    fn body_errors_carry_file_line_and_rule() {
+        let source =
+            "(rule: halve\n  :where [[?e :hp ?hp]]\n  :then [(+ ?hp 1)\n         (+ ?hp \"x\")])";
+        let ast = &parse(source).unwrap()[0];
+        let decl = longtable_language::DeclarationAnalyzer::analyze_rule(ast)
+            .unwrap()
+            .unwrap();
+
+        let mut world = World::new(42);
+        let rule = RuleCompiler::compile(&decl, world.interner_mut())
+            .unwrap()
+            .with_file("game/rules.lt");
+        assert_eq!(rule.span.line, 1);
+        assert_eq!(rule.body.effect_spans[1].line, 4);
+
+        let mut bindings = Bindings::new();
+        for var in &rule.body.binding_vars {
+            bindings.set(var.clone(), Value::Int(3));
+        }
+        let err = rule.execute(&bindings, &world).unwrap_err();
+        let ctx = err.context.unwrap();
+        assert_eq!(ctx.source.as_deref(), Some("game/rules.lt"));
+        assert_eq!(ctx.line, Some(4));
+        assert_eq!(ctx.stack, vec!["rule :halve (game/rules.lt:4)".to_string()]);
+    }
+
+    #[test]
+    fn body_errors_point_at_the_innermost_form() {
+        let source =
+            "(rule: scale\n  :where [[?e :hp ?hp]]\n  :then [(+ 1\n            (* ?hp \"x\"))])";
+        let ast = &parse(source).unwrap()[0];
+        let decl = longtable_language::DeclarationAnalyzer::analyze_rule(ast)
+            .unwrap()
+            .unwrap();
+
+        let mut world = World::new(42);
+        let rule = RuleCompiler::compile(&decl, world.interner_mut())
+            .unwrap()
+            .with_file("game/rules.lt");
+        let mut bindings = Bindings::new();
+        for var in &rule.body.binding_vars {
+            bindings.set(var.clone(), Value::Int(3));
+        }
+        let ctx = rule
+            .execute(&bindings, &world)
+            .unwrap_err()
+            .context
+            .unwrap();
+        assert_eq!((ctx.line, ctx.column), (Some(4), Some(13)));
+        assert_eq!(ctx.stack, vec!["rule :scale (game/rules.lt:4)".to_string()]);
+    }
+
+    #[test]
+    fn let_and_guards_can_peek_at_the_world() {
+        let source = "(rule: sound-alarm
+  :where [[?e :hp ?hp]]
+  :let [alarms (peek (count (with-component :alarm)))]
+  :guard [(= alarms 0)]
+  :then [(set-component! ?e :alarm true)
+         (if (peek (has? ?e :alarm)) nil (emit! :alarm-raised ?e))])";
+        let ast = &parse(source).unwrap()[0];
+        let decl = longtable_language::DeclarationAnalyzer::analyze_rule(ast)
+            .unwrap()
+            .unwrap();
+
+        let mut world = World::new(42);
+        let hp = world.interner_mut().intern_keyword("hp");
+        let alarm = world.interner_mut().intern_keyword("alarm");
+        world = world
+            .register_component(longtable_storage::ComponentSchema::tag(hp))
+            .unwrap()
+            .register_component(longtable_storage::ComponentSchema::tag(alarm))
+            .unwrap();
+        let (w, e) = world.spawn(&longtable_foundation::LtMap::new()).unwrap();
+        world = w.set(e, hp, Value::Bool(true)).unwrap();
+
+        let rule = RuleCompiler::compile(&decl, world.interner_mut()).unwrap();
+        let mut bindings = Bindings::new();
+        bindings.set("e".to_string(), Value::EntityRef(e));
+        bindings.set("hp".to_string(), Value::Bool(true));
+
+        // The peek in :then doesn't see the alarm set just before it
+        let effects = rule.execute(&bindings, &world).unwrap().unwrap();
+        assert_eq!(effects.len(), 2);
+        assert!(matches!(effects[1], VmEffect::Emit { .. }));
+
+        world = world.set(e, alarm, Value::Bool(true)).unwrap();
+        assert!(rule.execute(&bindings, &world).unwrap().is_none());
+    }
+
+    #[test]
+    fn peek_budgets_count_the_entities_read() {
+        let mut world = World::new(42);
+        let hp = world.interner_mut().intern_keyword("hp");
+        world = world
+            .register_component(longtable_storage::ComponentSchema::tag(hp))
+            .unwrap();
+        let mut entities = Vec::new();
+        for _ in 0..30 {
+            let (w, e) = world.spawn(&longtable_foundation::LtMap::new()).unwrap();
+            world = w.set(e, hp, Value::Bool(true)).unwrap();
+            entities.push(e);
+        }
+
+        let guarded = |budget: u32, world: &mut World| {
+            let source = format!(
+                "(rule: crowd :where [[?e :hp ?hp]]
+                   :guard [(peek :budget {budget} (> (count (with-component :hp)) 1))]
+                   :then [(emit! :crowded ?e)])"
+            );
+            let ast = &parse(&source).unwrap()[0];
+            let decl = longtable_language::DeclarationAnalyzer::analyze_rule(ast)
+                .unwrap()
+                .unwrap();
+            RuleCompiler::compile(&decl, world.interner_mut()).unwrap()
+        };
+        let mut bindings = Bindings::new();
+        bindings.set("e".to_string(), Value::EntityRef(entities[0]));
+        bindings.set("hp".to_string(), Value::Bool(true));
+
+        // A handful of instructions, but thirty entities read
+        let rule = guarded(20, &mut world);
+        let err = rule.execute(&bindings, &world).unwrap_err();
+        assert!(err.to_string().contains("budget of 20"), "{err}");
+        let rule = guarded(100, &mut world);
+        assert!(rule.execute(&bindings, &world).unwrap().is_some());
+    }
+
+    #[test]
+    fn peek_refuses_effects_in_rules() {
+        let source = "(rule: sneaky
+  :where [[?e :hp ?hp]]
+  :guard [(peek (destroy! ?e))]
+  :then [])";
+        let ast = &parse(source).unwrap()[0];
+        let decl = longtable_language::DeclarationAnalyzer::analyze_rule(ast)
+            .unwrap()
+            .unwrap();
+
+        let mut world = World::new(42);
+        let rule = RuleCompiler::compile(&decl, world.interner_mut()).unwrap();
+        let (w, e) = world.spawn(&longtable_foundation::LtMap::new()).unwrap();
+        world = w;
+        let mut bindings = Bindings::new();
+        bindings.set("e".to_string(), Value::EntityRef(e));
+
+        let err = rule.execute(&bindings, &world).unwrap_err();
+        assert!(err.to_string().contains("read-only"), "{err}");
+    }
 }
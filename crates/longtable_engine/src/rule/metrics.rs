@@ -0,0 +1,151 @@
+//! Per-rule performance metrics.
+//!
+//! While it runs rules to quiescence, the [`ProductionRuleEngine`] records
+//! for each rule how many activations fired, how long matching its pattern
+//! took, and how long executing its activations took. The metrics of one
+//! tick come back in [`TickResult`](crate::TickResult), and the
+//! [`TickExecutor`](crate::TickExecutor) keeps running totals, so the rules
+//! that dominate tick time can be found:
+//!
+//! ```text
+//! (rule-stats)
+//! rule               fired      match     effects      total
+//! :spread-fire         412    3.210ms     0.830ms    4.040ms
+//! :decay                90    0.120ms     0.050ms    0.170ms
+//! ```
+//!
+//! [`ProductionRuleEngine`]: super::ProductionRuleEngine
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use longtable_foundation::KeywordId;
+
+/// What one rule cost over some number of ticks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RuleStats {
+    /// Activations of the rule that fired
+    pub activations: usize,
+    /// Time spent matching the rule's pattern against the world
+    pub match_time: Duration,
+    /// Time spent running the rule's body for each activation and applying
+    /// its effects
+    pub effect_time: Duration,
+}
+
+impl RuleStats {
+    /// Returns the match and effect time together.
+    #[must_use]
+    pub fn total_time(&self) -> Duration {
+        self.match_time + self.effect_time
+    }
+
+    /// Adds another set of stats for the same rule to these.
+    pub fn add(&mut self, other: &Self) {
+        self.activations += other.activations;
+        self.match_time += other.match_time;
+        self.effect_time += other.effect_time;
+    }
+}
+
+/// Collects [`RuleStats`] for each rule.
+#[derive(Clone, Debug, Default)]
+pub struct RuleMetrics {
+    stats: HashMap<KeywordId, RuleStats>,
+}
+
+impl RuleMetrics {
+    /// Creates an empty collector.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records time spent matching `rule`'s pattern.
+    pub fn record_match(&mut self, rule: KeywordId, elapsed: Duration) {
+        self.stats.entry(rule).or_default().match_time += elapsed;
+    }
+
+    /// Records one activation of `rule` firing, and the time it took.
+    pub fn record_fire(&mut self, rule: KeywordId, elapsed: Duration) {
+        let stats = self.stats.entry(rule).or_default();
+        stats.activations += 1;
+        stats.effect_time += elapsed;
+    }
+
+    /// Returns the stats recorded for `rule`, if any.
+    #[must_use]
+    pub fn get(&self, rule: KeywordId) -> Option<&RuleStats> {
+        self.stats.get(&rule)
+    }
+
+    /// Returns true if nothing has been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.stats.is_empty()
+    }
+
+    /// Returns the number of rules with stats.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.stats.len()
+    }
+
+    /// Returns every rule's stats, costliest first.
+    #[must_use]
+    pub fn by_total_time(&self) -> Vec<(KeywordId, RuleStats)> {
+        let mut stats: Vec<_> = self.stats.iter().map(|(rule, s)| (*rule, *s)).collect();
+        stats.sort_by(|a, b| {
+            b.1.total_time()
+                .cmp(&a.1.total_time())
+                .then_with(|| b.1.activations.cmp(&a.1.activations))
+                .then_with(|| a.0.index().cmp(&b.0.index()))
+        });
+        stats
+    }
+
+    /// Adds `other`'s stats to these, rule by rule.
+    pub fn merge(&mut self, other: &Self) {
+        for (rule, stats) in &other.stats {
+            self.stats.entry(*rule).or_default().add(stats);
+        }
+    }
+
+    /// Forgets everything recorded.
+    pub fn clear(&mut self) {
+        self.stats.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::Interner;
+
+    #[test]
+    fn merges_and_orders_by_total_time() {
+        let mut interner = Interner::new();
+        let cheap = interner.intern_keyword("cheap");
+        let costly = interner.intern_keyword("costly");
+
+        let mut tick = RuleMetrics::new();
+        tick.record_match(cheap, Duration::from_micros(5));
+        tick.record_fire(cheap, Duration::from_micros(1));
+        tick.record_match(costly, Duration::from_micros(40));
+        tick.record_fire(costly, Duration::from_micros(30));
+        tick.record_fire(costly, Duration::from_micros(30));
+
+        let mut totals = RuleMetrics::new();
+        totals.merge(&tick);
+        totals.merge(&tick);
+
+        let ordered = totals.by_total_time();
+        assert_eq!(ordered[0].0, costly);
+        assert_eq!(ordered[0].1.activations, 4);
+        assert_eq!(ordered[0].1.total_time(), Duration::from_micros(200));
+        assert_eq!(
+            totals.get(cheap).unwrap().match_time,
+            Duration::from_micros(10)
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Scheduled effects (timers) for Longtable.
+//!
+//! `(schedule! :in 5 :then [(say "The bomb explodes.")])` creates a timer that
+//! runs its `:then` forms five ticks from now. Timers are owned by the
+//! [`TickExecutor`](crate::TickExecutor), which decides when they are due; the
+//! host that can call DSL functions (the REPL) takes the due timers before a
+//! tick and runs their actions during it.
+//!
+//! Timers fire in the order they fall due, and timers due on the same tick
+//! fire in the order they were scheduled.
+//!
+//! `(every 3 :then [...])` creates a recurring timer (a daemon), which is
+//! scheduled again each time it fires, and `(fuse 10 :then [...])` a one-shot
+//! one. Either may belong to an entity, given with `:on`: pausing the
+//! entity's timers stops them counting down until they are resumed.
+
+use std::collections::HashSet;
+
+use longtable_foundation::{EntityId, KeywordId, Value};
+
+/// Identifies a scheduled timer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimerId(pub u64);
+
+/// A pending timer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timer {
+    /// Timer identifier.
+    pub id: TimerId,
+    /// Tick during which the timer fires.
+    pub due_tick: u64,
+    /// Ticks between firings, for a timer that recurs.
+    pub period: Option<u64>,
+    /// Entity the timer belongs to, if any.
+    pub owner: Option<EntityId>,
+    /// Zero-argument function to call when the timer fires.
+    pub action: Value,
+}
+
+/// Holds pending timers, ordered by due tick.
+#[derive(Clone, Debug, Default)]
+pub struct Scheduler {
+    /// Pending timers, sorted by `(due_tick, id)`.
+    timers: Vec<Timer>,
+    /// Paused timers, with the ticks each had left to wait.
+    paused: Vec<(Timer, u64)>,
+    /// Next timer ID to hand out.
+    next_id: u64,
+}
+
+impl Scheduler {
+    /// Creates an empty scheduler.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a timer that fires during `due_tick`.
+    pub fn schedule(&mut self, due_tick: u64, action: Value) -> TimerId {
+        self.schedule_timer(due_tick, None, None, action)
+    }
+
+    /// Adds a timer that first fires during `due_tick`, then every `period`
+    /// ticks after if it has one, belonging to `owner`.
+    pub fn schedule_timer(
+        &mut self,
+        due_tick: u64,
+        period: Option<u64>,
+        owner: Option<EntityId>,
+        action: Value,
+    ) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        self.insert(Timer {
+            id,
+            due_tick,
+            period,
+            owner,
+            action,
+        });
+        id
+    }
+
+    fn insert(&mut self, timer: Timer) {
+        // Inserting after every timer due no later than this one keeps
+        // same-tick timers in the order they were scheduled.
+        let pos = self
+            .timers
+            .partition_point(|t| t.due_tick <= timer.due_tick);
+        self.timers.insert(pos, timer);
+    }
+
+    /// Cancels a pending or paused timer. Returns false if it was neither.
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        let before = self.timers.len() + self.paused.len();
+        self.timers.retain(|t| t.id != id);
+        self.paused.retain(|(t, _)| t.id != id);
+        self.timers.len() + self.paused.len() != before
+    }
+
+    /// Cancels every timer belonging to `owner`, paused or not. Returns how
+    /// many there were.
+    pub fn cancel_owned(&mut self, owner: EntityId) -> usize {
+        let before = self.timers.len() + self.paused.len();
+        self.timers.retain(|t| t.owner != Some(owner));
+        self.paused.retain(|(t, _)| t.owner != Some(owner));
+        before - self.timers.len() - self.paused.len()
+    }
+
+    /// Cancels every timer, paused or not, whose owner `alive` says no longer
+    /// exists. Returns how many there were.
+    pub fn cancel_orphaned(&mut self, alive: impl Fn(EntityId) -> bool) -> usize {
+        let before = self.timers.len() + self.paused.len();
+        self.timers.retain(|t| t.owner.is_none_or(&alive));
+        self.paused.retain(|(t, _)| t.owner.is_none_or(&alive));
+        before - self.timers.len() - self.paused.len()
+    }
+
+    /// Removes and returns every timer due on or before `tick`.
+    ///
+    /// Recurring timers are scheduled again, a period after they fell due
+    /// (or during the tick after `tick`, if that's later), keeping their IDs.
+    pub fn take_due(&mut self, tick: u64) -> Vec<Timer> {
+        let count = self.timers.partition_point(|t| t.due_tick <= tick);
+        let due: Vec<Timer> = self.timers.drain(..count).collect();
+        for timer in &due {
+            if let Some(period) = timer.period {
+                self.insert(Timer {
+                    due_tick: timer.due_tick.saturating_add(period).max(tick + 1),
+                    ..timer.clone()
+                });
+            }
+        }
+        due
+    }
+
+    /// Puts back timers [`Self::take_due`] returned, as though they had never
+    /// fallen due, for a tick that was abandoned. A recurring timer's next
+    /// firing is dropped in favor of the original.
+    pub fn requeue(&mut self, timers: Vec<Timer>) {
+        for timer in timers {
+            self.timers.retain(|t| t.id != timer.id);
+            self.insert(timer);
+        }
+    }
+
+    /// Pauses `owner`'s pending timers as of `tick`, so they stop counting
+    /// down. Returns how many were paused.
+    pub fn pause(&mut self, owner: EntityId, tick: u64) -> usize {
+        let (paused, pending) = std::mem::take(&mut self.timers)
+            .into_iter()
+            .partition::<Vec<_>, _>(|t| t.owner == Some(owner));
+        self.timers = pending;
+        let count = paused.len();
+        self.paused.extend(paused.into_iter().map(|t| {
+            let remaining = t.due_tick.saturating_sub(tick).max(1);
+            (t, remaining)
+        }));
+        count
+    }
+
+    /// Resumes `owner`'s paused timers as of `tick`, each with as many ticks
+    /// left to wait as it had when paused. Returns how many were resumed.
+    pub fn resume(&mut self, owner: EntityId, tick: u64) -> usize {
+        let (resumed, paused) = std::mem::take(&mut self.paused)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(t, _)| t.owner == Some(owner));
+        self.paused = paused;
+        let count = resumed.len();
+        for (timer, remaining) in resumed {
+            self.insert(Timer {
+                due_tick: tick.saturating_add(remaining),
+                ..timer
+            });
+        }
+        count
+    }
+
+    /// Returns paused timers, with the ticks each has left to wait.
+    pub fn paused(&self) -> impl Iterator<Item = (&Timer, u64)> {
+        self.paused.iter().map(|(t, remaining)| (t, *remaining))
+    }
+
+    /// Adds every keyword a pending or paused timer's action holds to `live`.
+    pub fn mark_keywords(&self, live: &mut HashSet<KeywordId>) {
+        let timers = self.timers.iter().chain(self.paused.iter().map(|(t, _)| t));
+        for timer in timers {
+            timer.action.mark_keywords(live);
+        }
+    }
+
+    /// Returns pending timers in firing order.
+    pub fn iter(&self) -> impl Iterator<Item = &Timer> {
+        self.timers.iter()
+    }
+
+    /// Returns the number of pending timers.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    /// Returns true if no timers are pending.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Cancels all pending and paused timers.
+    pub fn clear(&mut self) {
+        self.timers.clear();
+        self.paused.clear();
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timers_come_due_in_order() {
+        let mut scheduler = Scheduler::new();
+        let late = scheduler.schedule(5, Value::Int(1));
+        let early = scheduler.schedule(2, Value::Int(2));
+        let also_early = scheduler.schedule(2, Value::Int(3));
+
+        assert!(scheduler.take_due(1).is_empty());
+        let due: Vec<_> = scheduler.take_due(4).into_iter().map(|t| t.id).collect();
+        assert_eq!(due, vec![early, also_early]);
+        assert_eq!(scheduler.len(), 1);
+        assert_eq!(scheduler.take_due(10)[0].id, late);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn cancel_removes_timer() {
+        let mut scheduler = Scheduler::new();
+        let id = scheduler.schedule(3, Value::Nil);
+        assert!(scheduler.cancel(id));
+        assert!(!scheduler.cancel(id));
+        assert!(scheduler.take_due(3).is_empty());
+    }
+
+    #[test]
+    fn recurring_timers_reschedule_and_pause() {
+        let guard = EntityId::new(1, 0);
+        let mut scheduler = Scheduler::new();
+        let daemon = scheduler.schedule_timer(3, Some(3), Some(guard), Value::Int(1));
+        let fuse = scheduler.schedule_timer(4, None, Some(guard), Value::Int(2));
+
+        assert_eq!(scheduler.take_due(3)[0].id, daemon);
+        let pending: Vec<_> = scheduler.iter().map(|t| (t.id, t.due_tick)).collect();
+        assert_eq!(pending, vec![(fuse, 4), (daemon, 6)]);
+
+        // Paused at tick 3, the fuse has 1 tick left and the daemon 3
+        assert_eq!(scheduler.pause(guard, 3), 2);
+        assert!(scheduler.take_due(10).is_empty());
+        assert_eq!(scheduler.resume(guard, 10), 2);
+        let due: Vec<_> = scheduler.take_due(11).into_iter().map(|t| t.id).collect();
+        assert_eq!(due, vec![fuse]);
+        assert_eq!(scheduler.take_due(13)[0].id, daemon);
+
+        assert_eq!(scheduler.cancel_owned(guard), 1);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn cancel_orphaned_drops_paused_timers_too() {
+        let guard = EntityId::new(1, 0);
+        let ghost = EntityId::new(2, 0);
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule_timer(3, None, Some(guard), Value::Int(1));
+        scheduler.schedule_timer(3, Some(2), Some(ghost), Value::Int(2));
+        scheduler.schedule_timer(5, None, Some(ghost), Value::Int(3));
+        let unowned = scheduler.schedule(4, Value::Int(4));
+        scheduler.pause(ghost, 1);
+
+        assert_eq!(scheduler.cancel_orphaned(|owner| owner == guard), 2);
+        assert_eq!(scheduler.paused().count(), 0);
+        let pending: Vec<_> = scheduler.iter().map(|t| t.owner).collect();
+        assert_eq!(pending, vec![Some(guard), None]);
+        assert_eq!(scheduler.iter().nth(1).map(|t| t.id), Some(unowned));
+    }
+}
@@ -725,7 +725,16 @@ pub fn apply_effect(world: World, effect: &VmEffect) -> Result<World> {
         }
         // State management effects are handled at the REPL level, not here.
         // This function only handles effects that modify the World directly.
+        VmEffect::Emit { event, payload } => {
+            Ok(crate::event::emit(world, *event, payload.clone())?.0)
+        }
         VmEffect::SaveState { .. } | VmEffect::RestoreState { .. } => Ok(world),
+        VmEffect::Schedule { .. } => Err(Error::new(ErrorKind::Refused(
+            "schedule! effects must be registered with a tick executor".to_string(),
+        ))),
+        VmEffect::Command { name, .. } => Err(Error::new(ErrorKind::Refused(format!(
+            "({name}) must be run by a session"
+        )))),
     }
 }
 
@@ -883,7 +892,7 @@ mod tests {
         engine.begin_tick();
 
         // First find should return 2 activations
-        let activations = engine.find_activations(&[rule.clone()], &world);
+        let activations = engine.find_activations(std::slice::from_ref(&rule), &world);
         assert_eq!(activations.len(), 2);
 
         // Mark first activation as refracted
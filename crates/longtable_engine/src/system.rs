@@ -0,0 +1,343 @@
+//! Host-provided Rust systems for Longtable.
+//!
+//! Some per-tick logic (pathfinding, physics) is better written in Rust than
+//! as rules. A [`System`] is a Rust function the
+//! [`TickExecutor`](crate::TickExecutor) calls once per tick at a declared
+//! [`TickPhase`]. It reads the world and returns effects, which go through
+//! the same middleware, provenance, and constraint checks as any other
+//! effect in the tick.
+//!
+//! Each system declares the components and relationships it reads and
+//! writes:
+//!
+//! ```text
+//! after-inputs:  physics  reads :velocity :position  writes :position
+//!                steering reads :target              writes :velocity   ✗ conflict
+//! ```
+//!
+//! All systems of a phase see the world as it stands when they start, and
+//! their effects are applied together afterwards, in registration order. So
+//! registration rejects a system that would write something another system
+//! of the same phase reads or writes: its result would depend on which of
+//! them happened to be registered first. A system that produces an effect
+//! on a component or relationship it didn't declare as written fails the
+//! tick.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+
+use longtable_foundation::{Error, ErrorKind, KeywordId, Result, Value};
+use longtable_language::VmEffect;
+use longtable_storage::World;
+
+use crate::tick::TickPhase;
+
+/// A per-tick Rust function run by the tick executor.
+///
+/// Implemented for closures of the form `Fn(&World) -> Result<Vec<VmEffect>>`.
+/// Systems that keep state between ticks should use interior mutability.
+pub trait System: Send + Sync {
+    /// Runs the system against `world`, returning the effects to apply.
+    fn run(&self, world: &World) -> Result<Vec<VmEffect>>;
+}
+
+impl<F> System for F
+where
+    F: Fn(&World) -> Result<Vec<VmEffect>> + Send + Sync,
+{
+    fn run(&self, world: &World) -> Result<Vec<VmEffect>> {
+        self(world)
+    }
+}
+
+/// The components and relationships a system reads and writes, by name
+/// (without the leading colon).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SystemAccess {
+    reads: BTreeSet<String>,
+    writes: BTreeSet<String>,
+}
+
+impl SystemAccess {
+    /// Creates an access declaration that reads and writes nothing.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares components or relationships the system reads.
+    #[must_use]
+    pub fn reads<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.reads.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Declares components or relationships the system writes.
+    #[must_use]
+    pub fn writes<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.writes.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns true if the system declares it writes `name`.
+    #[must_use]
+    pub fn can_write(&self, name: &str) -> bool {
+        self.writes.contains(name)
+    }
+
+    /// Returns a name one of the two declarations writes and the other reads
+    /// or writes, if any.
+    #[must_use]
+    pub fn conflict_with<'a>(&'a self, other: &'a Self) -> Option<&'a str> {
+        let touches = |access: &Self, name: &String| {
+            access.reads.contains(name) || access.writes.contains(name)
+        };
+        self.writes
+            .iter()
+            .find(|name| touches(other, name))
+            .or_else(|| other.writes.iter().find(|name| self.reads.contains(*name)))
+            .map(String::as_str)
+    }
+}
+
+/// Record of a system having run during a tick.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SystemRun {
+    /// The system's name
+    pub name: String,
+    /// The phase it ran at
+    pub phase: TickPhase,
+    /// Number of effects it returned
+    pub effects: usize,
+}
+
+/// A registered system.
+#[derive(Clone)]
+struct Entry {
+    name: String,
+    phase: TickPhase,
+    access: SystemAccess,
+    system: Arc<dyn System>,
+}
+
+/// The systems registered with a tick executor.
+#[derive(Clone, Default)]
+pub struct SystemRegistry {
+    /// Systems in registration order.
+    entries: Vec<Entry>,
+}
+
+impl fmt::Debug for SystemRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|e| (&e.name, e.phase.name())))
+            .finish()
+    }
+}
+
+impl SystemRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `system` to run at `phase` under `name`, replacing any
+    /// system already registered with that name.
+    ///
+    /// # Errors
+    /// Returns an error if `phase` is [`TickPhase::AfterCommit`], when
+    /// systems could no longer change the world, or if `access` conflicts
+    /// with another system registered for the same phase.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        phase: TickPhase,
+        access: SystemAccess,
+        system: impl System + 'static,
+    ) -> Result<()> {
+        let name = name.into();
+        if phase == TickPhase::AfterCommit {
+            return Err(Error::new(ErrorKind::Refused(format!(
+                "system `{name}` cannot run at after-commit"
+            ))));
+        }
+        let conflict = self
+            .entries
+            .iter()
+            .filter(|e| e.phase == phase && e.name != name)
+            .find_map(|e| access.conflict_with(&e.access).map(|c| (&e.name, c)));
+        if let Some((other, component)) = conflict {
+            return Err(Error::new(ErrorKind::Refused(format!(
+                "system `{name}` conflicts with `{other}` at {}: one writes :{component} \
+                 and the other reads or writes it",
+                phase.name()
+            ))));
+        }
+
+        self.unregister(&name);
+        self.entries.push(Entry {
+            name,
+            phase,
+            access,
+            system: Arc::new(system),
+        });
+        Ok(())
+    }
+
+    /// Removes the system registered under `name`. Returns false if there
+    /// was none.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.name != name);
+        self.entries.len() != before
+    }
+
+    /// Returns true if no systems are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the names of the systems that run at `phase`, in order.
+    pub fn names(&self, phase: TickPhase) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .filter(move |e| e.phase == phase)
+            .map(|e| e.name.as_str())
+    }
+
+    /// Returns the access declared by the system registered under `name`.
+    #[must_use]
+    pub fn access(&self, name: &str) -> Option<&SystemAccess> {
+        self.entries
+            .iter()
+            .find(|e| e.name == name)
+            .map(|e| &e.access)
+    }
+
+    /// Runs the systems registered for `phase` against `world`.
+    ///
+    /// Returns each system's name with the effects it produced, in
+    /// registration order.
+    ///
+    /// # Errors
+    /// Returns an error if a system fails or produces an effect on a
+    /// component or relationship it didn't declare as written.
+    pub fn run(&self, phase: TickPhase, world: &World) -> Result<Vec<(&str, Vec<VmEffect>)>> {
+        self.entries
+            .iter()
+            .filter(|e| e.phase == phase)
+            .map(|entry| {
+                let effects = entry.system.run(world).map_err(|e| {
+                    Error::new(ErrorKind::Internal(format!(
+                        "system `{}` failed: {e}",
+                        entry.name
+                    )))
+                })?;
+                for effect in &effects {
+                    for written in written_names(effect) {
+                        let name = world.interner().get_keyword(written).unwrap_or("?");
+                        if !entry.access.can_write(name) {
+                            return Err(Error::new(ErrorKind::Refused(format!(
+                                "system `{}` wrote :{name} without declaring it",
+                                entry.name
+                            ))));
+                        }
+                    }
+                }
+                Ok((entry.name.as_str(), effects))
+            })
+            .collect()
+    }
+}
+
+/// Returns the components or relationships an effect writes.
+fn written_names(effect: &VmEffect) -> Vec<KeywordId> {
+    match effect {
+        VmEffect::Spawn { components, .. } => components
+            .keys()
+            .filter_map(|key| match key {
+                Value::Keyword(k) => Some(*k),
+                _ => None,
+            })
+            .collect(),
+        VmEffect::SetComponent { component, .. }
+        | VmEffect::SetField { component, .. }
+        | VmEffect::RemoveComponent { component, .. }
+        | VmEffect::VecRemove { component, .. }
+        | VmEffect::VecAdd { component, .. }
+        | VmEffect::SetRemove { component, .. }
+        | VmEffect::SetAdd { component, .. } => vec![*component],
+        VmEffect::Link { relationship, .. } | VmEffect::Unlink { relationship, .. } => {
+            vec![*relationship]
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nothing() -> impl System {
+        |_: &World| Ok(Vec::new())
+    }
+
+    #[test]
+    fn registration_rejects_conflicting_systems() {
+        let mut systems = SystemRegistry::new();
+        let physics = SystemAccess::new()
+            .reads(["velocity", "position"])
+            .writes(["position"]);
+        systems
+            .register("physics", TickPhase::AfterInputs, physics, nothing())
+            .unwrap();
+
+        // Writing what physics reads, in the same phase, conflicts
+        let steering = SystemAccess::new().reads(["target"]).writes(["velocity"]);
+        let err = systems
+            .register(
+                "steering",
+                TickPhase::AfterInputs,
+                steering.clone(),
+                nothing(),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains(":velocity"), "{err}");
+
+        // In another phase it runs on its own
+        systems
+            .register("steering", TickPhase::BeginTick, steering, nothing())
+            .unwrap();
+        // Readers don't conflict with each other
+        let render = SystemAccess::new().reads(["position"]);
+        systems
+            .register("render", TickPhase::BeginTick, render, nothing())
+            .unwrap();
+
+        assert_eq!(
+            systems.names(TickPhase::BeginTick).collect::<Vec<_>>(),
+            ["steering", "render"]
+        );
+        assert!(
+            systems
+                .register(
+                    "late",
+                    TickPhase::AfterCommit,
+                    SystemAccess::new(),
+                    nothing()
+                )
+                .is_err()
+        );
+    }
+}
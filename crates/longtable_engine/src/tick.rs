@@ -5,14 +5,33 @@
 //! 2. Runs rules to quiescence
 //! 3. Checks constraints
 //! 4. Commits changes or rolls back on constraint violation
+//! 5. Drains events (see [`crate::event`])
+//!
+//! Phase hooks can observe the world at fixed points in the tick (see
+//! [`TickPhase`]) and contribute effects of their own, as can host-provided
+//! Rust systems (see [`crate::system`]). Timers created with `schedule!` are
+//! kept by the executor (see [`crate::schedule`]).
+//!
+//! A debugger can stop a tick part way through: [`TickExecutor::tick_with_debugger`]
+//! calls it at each [`DebugPoint`] and waits for it to return before going on.
+
+use std::collections::{HashMap, HashSet};
 
-use longtable_foundation::{EntityId, KeywordId, Result, Value};
-use longtable_storage::World;
+use longtable_foundation::{EntityId, Error, ErrorKind, KeywordId, Result, Value};
+use longtable_language::VmEffect;
+use longtable_storage::{CascadeStep, World};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use crate::constraint::{ConstraintChecker, ConstraintResult};
 use crate::derived::DerivedEvaluator;
-use crate::provenance::ProvenanceTracker;
-use crate::rule::{CompiledRule, ProductionRuleEngine};
+use crate::middleware::EffectMiddleware;
+use crate::provenance::{LinkChange, ProvenanceTracker};
+use crate::rule::{
+    Activation, AgendaEntry, CompiledRule, FullCompiledRule, ProductionRuleEngine, RuleMetrics,
+};
+use crate::schedule::{Scheduler, Timer, TimerId};
+use crate::system::{System, SystemAccess, SystemRegistry, SystemRun};
 
 // =============================================================================
 // Input Event
@@ -20,6 +39,7 @@ use crate::rule::{CompiledRule, ProductionRuleEngine};
 
 /// An external input to inject at the start of a tick.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum InputEvent {
     /// Set a component value on an entity
     Set {
@@ -40,15 +60,89 @@ pub enum InputEvent {
         /// Entity to destroy
         entity: EntityId,
     },
-    /// Custom input (for game-specific events)
+    /// Custom input, emitted as an event entity (see [`crate::event`])
     Custom {
         /// Event name
         name: KeywordId,
-        /// Event payload
+        /// Event payload; a value other than a map is wrapped as
+        /// `{:value payload}`
         payload: Value,
     },
 }
 
+// =============================================================================
+// Debug Point
+// =============================================================================
+
+/// A point during a tick at which a debugger may pause it.
+#[derive(Clone, Copy, Debug)]
+pub enum DebugPoint<'a> {
+    /// The tick with this number is starting.
+    TickStart(u64),
+    /// A phase is about to run its hooks and systems.
+    Phase(TickPhase),
+    /// A rule activation is about to fire.
+    Rule(&'a Activation),
+}
+
+// =============================================================================
+// Tick Phase
+// =============================================================================
+
+/// A point during a tick at which phase hooks run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TickPhase {
+    /// Before inputs are injected.
+    BeginTick,
+    /// After inputs are injected, before rules run.
+    AfterInputs,
+    /// After rules reach quiescence, before constraints are checked.
+    BeforeConstraints,
+    /// After the tick has been committed. Hooks may not produce effects here.
+    AfterCommit,
+}
+
+impl TickPhase {
+    /// All phases, in the order they run.
+    pub const ALL: [Self; 4] = [
+        Self::BeginTick,
+        Self::AfterInputs,
+        Self::BeforeConstraints,
+        Self::AfterCommit,
+    ];
+
+    /// Returns the DSL name of this phase (e.g. `"before-constraints"`).
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::BeginTick => "begin-tick",
+            Self::AfterInputs => "after-inputs",
+            Self::BeforeConstraints => "before-constraints",
+            Self::AfterCommit => "after-commit",
+        }
+    }
+
+    /// Looks up a phase by its DSL name.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|phase| phase.name() == name)
+    }
+}
+
+// =============================================================================
+// Execution Mode
+// =============================================================================
+
+/// Whether a tick executor keeps debugging bookkeeping.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// Record provenance for every write so `why` queries work.
+    #[default]
+    Debug,
+    /// Shipping mode: no provenance is recorded, so writes carry no bookkeeping.
+    Play,
+}
+
 // =============================================================================
 // Tick Result
 // =============================================================================
@@ -64,6 +158,19 @@ pub struct TickResult {
     pub constraint_result: ConstraintResult,
     /// Whether the tick was successful (no rollback)
     pub success: bool,
+    /// Number of events drained at the end of the tick
+    pub events_drained: usize,
+    /// Session commands queued by phase hooks, as [`VmEffect::Command`]s.
+    ///
+    /// The host runs these after the tick; they are dropped if the tick
+    /// rolled back.
+    pub commands: Vec<VmEffect>,
+    /// The systems that ran, in the order they ran
+    pub systems: Vec<SystemRun>,
+    /// The `:on-delete` policies destroys applied, in the order applied
+    pub cascades: Vec<CascadeStep>,
+    /// What each rule cost this tick
+    pub rule_metrics: RuleMetrics,
 }
 
 impl TickResult {
@@ -85,6 +192,8 @@ pub struct TickExecutor {
     rule_engine: ProductionRuleEngine,
     /// Compiled rules
     rules: Vec<CompiledRule>,
+    /// Bodies of the rules added with [`Self::add_full_rule`], by name
+    bodies: HashMap<KeywordId, FullCompiledRule>,
     /// Constraint checker
     constraint_checker: ConstraintChecker,
     /// Derived component evaluator
@@ -93,6 +202,18 @@ pub struct TickExecutor {
     provenance: ProvenanceTracker,
     /// Current tick number
     tick_number: u64,
+    /// Debug or play mode
+    mode: ExecutionMode,
+    /// Pending timers
+    scheduler: Scheduler,
+    /// Interceptors that see hook effects before they are applied
+    middleware: EffectMiddleware,
+    /// Host-provided Rust systems
+    systems: SystemRegistry,
+    /// Cascade steps of the destroys applied during the current tick
+    cascades: Vec<CascadeStep>,
+    /// What each rule has cost across ticks, since the last reset
+    rule_totals: RuleMetrics,
 }
 
 impl Default for TickExecutor {
@@ -108,13 +229,34 @@ impl TickExecutor {
         Self {
             rule_engine: ProductionRuleEngine::new(),
             rules: Vec::new(),
+            bodies: HashMap::new(),
             constraint_checker: ConstraintChecker::new(),
             derived_evaluator: DerivedEvaluator::new(),
             provenance: ProvenanceTracker::new(),
             tick_number: 0,
+            mode: ExecutionMode::Debug,
+            scheduler: Scheduler::new(),
+            middleware: EffectMiddleware::new(),
+            systems: SystemRegistry::new(),
+            cascades: Vec::new(),
+            rule_totals: RuleMetrics::new(),
         }
     }
 
+    /// Sets the execution mode. Play mode disables provenance tracking.
+    #[must_use]
+    pub fn with_mode(mut self, mode: ExecutionMode) -> Self {
+        self.provenance.set_enabled(mode == ExecutionMode::Debug);
+        self.mode = mode;
+        self
+    }
+
+    /// Returns the execution mode.
+    #[must_use]
+    pub const fn mode(&self) -> ExecutionMode {
+        self.mode
+    }
+
     /// Sets the rules for this executor.
     #[must_use]
     pub fn with_rules(mut self, rules: Vec<CompiledRule>) -> Self {
@@ -122,17 +264,54 @@ impl TickExecutor {
         self
     }
 
+    /// Replaces the rules this executor fires, dropping any rule bodies.
+    pub fn set_rules(&mut self, rules: Vec<CompiledRule>) {
+        self.rules = rules;
+        self.bodies.clear();
+    }
+
     /// Adds a single rule to this executor.
     pub fn add_rule(&mut self, rule: CompiledRule) {
+        self.bodies.remove(&rule.name);
         self.rules.push(rule);
     }
 
+    /// Adds a rule along with its body, which runs each time the rule fires.
+    ///
+    /// A body that fails abandons the tick with the error, located at the
+    /// file, line, and rule it came from (see [`FullCompiledRule::execute`]).
+    pub fn add_full_rule(&mut self, rule: FullCompiledRule) {
+        self.rules.push(CompiledRule::from(rule.clone()));
+        self.bodies.insert(rule.name, rule);
+    }
+
+    /// Lists the activations this executor's rules would fire next, in
+    /// firing order (see [`ProductionRuleEngine::agenda`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a rule's pattern fails to match.
+    pub fn agenda(&mut self, world: &World) -> Result<Vec<AgendaEntry>> {
+        self.rule_engine.agenda(&self.rules, world)
+    }
+
     /// Returns the number of registered rules.
     #[must_use]
     pub fn rule_count(&self) -> usize {
         self.rules.len()
     }
 
+    /// Returns the production rule engine.
+    #[must_use]
+    pub fn rule_engine(&self) -> &ProductionRuleEngine {
+        &self.rule_engine
+    }
+
+    /// Returns the production rule engine mutably (e.g. to toggle rule groups).
+    pub fn rule_engine_mut(&mut self) -> &mut ProductionRuleEngine {
+        &mut self.rule_engine
+    }
+
     /// Sets the constraint checker.
     #[must_use]
     pub fn with_constraints(mut self, checker: ConstraintChecker) -> Self {
@@ -153,6 +332,23 @@ impl TickExecutor {
         self.tick_number
     }
 
+    /// Sets the current tick number, as when restoring a checkpoint.
+    pub fn set_tick_number(&mut self, tick: u64) {
+        self.tick_number = tick;
+    }
+
+    /// Returns what each rule has cost across the ticks run since the
+    /// executor was created or [`Self::reset_rule_metrics`] was called.
+    #[must_use]
+    pub fn rule_metrics(&self) -> &RuleMetrics {
+        &self.rule_totals
+    }
+
+    /// Starts the running per-rule totals over.
+    pub fn reset_rule_metrics(&mut self) {
+        self.rule_totals.clear();
+    }
+
     /// Returns the provenance tracker.
     #[must_use]
     pub fn provenance(&self) -> &ProvenanceTracker {
@@ -164,56 +360,460 @@ impl TickExecutor {
         &mut self.provenance
     }
 
+    /// Returns the constraint checker.
+    #[must_use]
+    pub fn constraint_checker(&self) -> &ConstraintChecker {
+        &self.constraint_checker
+    }
+
+    /// Returns mutable access to the constraint checker.
+    pub fn constraint_checker_mut(&mut self) -> &mut ConstraintChecker {
+        &mut self.constraint_checker
+    }
+
+    /// Returns the effect middleware chain.
+    #[must_use]
+    pub fn middleware(&self) -> &EffectMiddleware {
+        &self.middleware
+    }
+
+    /// Returns mutable access to the effect middleware chain, to register
+    /// or remove interceptors.
+    pub fn middleware_mut(&mut self) -> &mut EffectMiddleware {
+        &mut self.middleware
+    }
+
+    /// Returns the registered systems.
+    #[must_use]
+    pub fn systems(&self) -> &SystemRegistry {
+        &self.systems
+    }
+
+    /// Registers a Rust system to run every tick at `phase`, after that
+    /// phase's hook. See [`SystemRegistry::register`].
+    ///
+    /// # Errors
+    /// Returns an error if the system can't run at `phase` or its `access`
+    /// conflicts with another system of the same phase.
+    pub fn register_system(
+        &mut self,
+        name: impl Into<String>,
+        phase: TickPhase,
+        access: SystemAccess,
+        system: impl System + 'static,
+    ) -> Result<()> {
+        self.systems.register(name, phase, access, system)
+    }
+
+    /// Removes the system registered under `name`. Returns false if there
+    /// was none.
+    pub fn unregister_system(&mut self, name: &str) -> bool {
+        self.systems.unregister(name)
+    }
+
+    /// Adds every keyword the executor's rules and timers hold to `live`.
+    ///
+    /// This, with the session's worlds, is the mark phase for
+    /// [`Interner::sweep_generated`](longtable_foundation::Interner::sweep_generated).
+    pub fn mark_keywords(&self, live: &mut HashSet<KeywordId>) {
+        for rule in &self.rules {
+            rule.pattern.mark_keywords(live);
+        }
+        for body in self.bodies.values() {
+            body.mark_keywords(live);
+        }
+        self.scheduler.mark_keywords(live);
+    }
+
+    /// Returns the pending timers.
+    #[must_use]
+    pub fn scheduler(&self) -> &Scheduler {
+        &self.scheduler
+    }
+
+    /// Returns mutable access to the pending timers.
+    pub fn scheduler_mut(&mut self) -> &mut Scheduler {
+        &mut self.scheduler
+    }
+
+    /// Schedules `action` to fire `delay` ticks after the current tick.
+    pub fn schedule(&mut self, delay: u64, action: Value) -> TimerId {
+        self.schedule_timer(delay, false, None, action)
+    }
+
+    /// Schedules `action` to fire `delay` ticks after the current tick, and
+    /// every `delay` ticks after that if `repeat` is set, belonging to
+    /// `owner`.
+    pub fn schedule_timer(
+        &mut self,
+        delay: u64,
+        repeat: bool,
+        owner: Option<EntityId>,
+        action: Value,
+    ) -> TimerId {
+        self.scheduler.schedule_timer(
+            self.tick_number.saturating_add(delay),
+            repeat.then_some(delay),
+            owner,
+            action,
+        )
+    }
+
+    /// Pauses `owner`'s timers, so they stop counting down. Returns how many
+    /// were paused.
+    pub fn pause_timers(&mut self, owner: EntityId) -> usize {
+        self.scheduler.pause(owner, self.tick_number)
+    }
+
+    /// Resumes `owner`'s paused timers. Returns how many were resumed.
+    pub fn resume_timers(&mut self, owner: EntityId) -> usize {
+        self.scheduler.resume(owner, self.tick_number)
+    }
+
+    /// Removes and returns the timers that fire during the next tick.
+    ///
+    /// The executor cannot call DSL functions itself, so the host takes the
+    /// due timers before ticking and runs their actions from a phase hook.
+    pub fn take_due_timers(&mut self) -> Vec<Timer> {
+        self.scheduler.take_due(self.tick_number + 1)
+    }
+
     /// Execute a single tick.
     ///
     /// # Errors
     /// Returns an error if rule execution fails (e.g., kill switch triggered).
     pub fn tick(&mut self, world: World, inputs: &[InputEvent]) -> Result<TickResult> {
+        self.tick_with_hooks(world, inputs, |_, _| Ok(Vec::new()))
+    }
+
+    /// Execute a single tick, calling `hook` at each [`TickPhase`].
+    ///
+    /// The hook gets read access to the world as it stands at that phase and
+    /// returns effects to apply before the tick continues. Effects from
+    /// `BeforeConstraints` hooks are subject to constraint checking like any
+    /// rule effect.
+    ///
+    /// # Errors
+    /// Returns an error if a hook fails, if a hook returns effects other
+    /// than session commands at `AfterCommit`, or if rule execution fails.
+    pub fn tick_with_hooks<H>(
+        &mut self,
+        world: World,
+        inputs: &[InputEvent],
+        hook: H,
+    ) -> Result<TickResult>
+    where
+        H: FnMut(TickPhase, &World) -> Result<Vec<VmEffect>>,
+    {
+        self.tick_with_debugger(world, inputs, hook, |_, _| Ok(()))
+    }
+
+    /// Execute a single tick like [`Self::tick_with_hooks`], calling
+    /// `debugger` at each [`DebugPoint`] with the world as it stands there.
+    ///
+    /// The tick waits for the debugger to return, so a debugger that wants
+    /// to pause execution simply doesn't return until it is resumed.
+    ///
+    /// # Errors
+    /// Returns an error if the debugger does, abandoning the tick, or for
+    /// any of the reasons [`Self::tick_with_hooks`] fails.
+    pub fn tick_with_debugger<H, D>(
+        &mut self,
+        world: World,
+        inputs: &[InputEvent],
+        mut hook: H,
+        mut debugger: D,
+    ) -> Result<TickResult>
+    where
+        H: FnMut(TickPhase, &World) -> Result<Vec<VmEffect>>,
+        D: FnMut(DebugPoint<'_>, &World) -> Result<()>,
+    {
         // Increment tick number
         self.tick_number += 1;
+        debugger(DebugPoint::TickStart(self.tick_number), &world)?;
 
         // Save the original world for potential rollback
         let original_world = world.clone();
+        let mut commands = Vec::new();
+        let mut systems = Vec::new();
 
         // Phase 1: Begin tick (reset engine state)
         self.rule_engine.begin_tick();
         self.derived_evaluator.begin_tick();
         self.provenance.begin_tick();
+        self.cascades.clear();
+        debugger(DebugPoint::Phase(TickPhase::BeginTick), &world)?;
+        let world = self.run_hook(&mut hook, TickPhase::BeginTick, world, &mut commands)?;
+        let world = self.run_systems(TickPhase::BeginTick, world, &mut commands, &mut systems)?;
 
         // Phase 2: Inject inputs
-        let mut world = self.inject_inputs(world, inputs)?;
+        let world = self.inject_inputs(world, inputs)?;
+        debugger(DebugPoint::Phase(TickPhase::AfterInputs), &world)?;
+        let world = self.run_hook(&mut hook, TickPhase::AfterInputs, world, &mut commands)?;
+        let mut world =
+            self.run_systems(TickPhase::AfterInputs, world, &mut commands, &mut systems)?;
 
         // Phase 3: Run rules to quiescence
-        // Note: Using a simple no-op executor for now. Full rule body execution
-        // requires integration with the VM which is outside Phase 4 scope.
-        world = self
-            .rule_engine
-            .run_to_quiescence(&self.rules, world, |_activation, w| {
-                // Placeholder: actual rule execution would evaluate the rule body
-                // and return effects + modified world
-                Ok((vec![], w.clone()))
-            })?;
+        // Rules without a body only count their activations. A body's
+        // effects apply as soon as it runs, so later activations see them.
+        let mut engine = std::mem::take(&mut self.rule_engine);
+        let rules = std::mem::take(&mut self.rules);
+        let fired = engine.run_to_quiescence(&rules, world, |activation, w| {
+            debugger(DebugPoint::Rule(activation), w)?;
+            let effects = match self.bodies.get(&activation.rule_name) {
+                Some(rule) => rule.execute(&activation.bindings, w)?.unwrap_or_default(),
+                None => Vec::new(),
+            };
+            let mut world = w.clone();
+            for effect in self.middleware.process_all(effects.clone(), &world)? {
+                world =
+                    self.apply_system_effect(world, effect, activation.rule_name, &mut commands)?;
+            }
+            Ok((effects, world))
+        });
+        self.rule_engine = engine;
+        self.rules = rules;
+        world = fired?;
 
         let activations_fired = self.rule_engine.activation_count();
+        let rule_metrics = self.rule_engine.metrics().clone();
+        self.rule_totals.merge(&rule_metrics);
+        debugger(DebugPoint::Phase(TickPhase::BeforeConstraints), &world)?;
+        let world = self.run_hook(
+            &mut hook,
+            TickPhase::BeforeConstraints,
+            world,
+            &mut commands,
+        )?;
+        let world = self.run_systems(
+            TickPhase::BeforeConstraints,
+            world,
+            &mut commands,
+            &mut systems,
+        )?;
 
         // Phase 4: Check constraints
-        let constraint_result = self.constraint_checker.check_all(&world);
+        let constraint_result = self.constraint_checker.check_all(&world)?;
 
         // Phase 5: Commit or rollback
         let (final_world, success) = if constraint_result.is_ok() {
-            (world, true)
+            (world.with_score(constraint_result.score()), true)
         } else {
             (original_world, false)
         };
 
+        // Phase 6: Drain events, whether or not the tick committed
+        let (final_world, events_drained) = crate::event::drain(final_world)?;
+
+        if success {
+            // Only session commands make sense once the world is committed
+            debugger(DebugPoint::Phase(TickPhase::AfterCommit), &final_world)?;
+            let effects = hook(TickPhase::AfterCommit, &final_world)?;
+            for effect in self.middleware.process_all(effects, &final_world)? {
+                if !matches!(effect, VmEffect::Command { .. }) {
+                    return Err(Error::new(ErrorKind::Refused(
+                        "phase hooks cannot produce effects after commit".to_string(),
+                    )));
+                }
+                commands.push(effect);
+            }
+        } else {
+            commands.clear();
+            self.cascades.clear();
+        }
+
         Ok(TickResult {
             world: final_world,
             activations_fired,
             constraint_result,
             success,
+            events_drained,
+            commands,
+            systems,
+            cascades: std::mem::take(&mut self.cascades),
+            rule_metrics,
         })
     }
 
+    /// Runs the hook for a phase and applies the effects it returns.
+    ///
+    /// Effects pass through the middleware chain first. `schedule!` effects
+    /// register timers rather than touching the world, and session commands
+    /// are set aside in `commands` for the host.
+    fn run_hook<H>(
+        &mut self,
+        hook: &mut H,
+        phase: TickPhase,
+        world: World,
+        commands: &mut Vec<VmEffect>,
+    ) -> Result<World>
+    where
+        H: FnMut(TickPhase, &World) -> Result<Vec<VmEffect>>,
+    {
+        let effects = self.middleware.process_all(hook(phase, &world)?, &world)?;
+        let mut effects = effects.into_iter().peekable();
+        let mut world = world;
+        while let Some(effect) = effects.next() {
+            world = match effect {
+                VmEffect::Schedule {
+                    delay,
+                    repeat,
+                    owner,
+                    action,
+                } => {
+                    self.schedule_timer(delay, repeat, owner, action);
+                    world
+                }
+                VmEffect::Command { .. } => {
+                    commands.push(effect);
+                    world
+                }
+                // Consecutive spawns, as from spawn-many!, go in one pass
+                VmEffect::Spawn {
+                    temp_id,
+                    components,
+                } => {
+                    let mut batch = vec![(temp_id, components)];
+                    while let Some(VmEffect::Spawn {
+                        temp_id,
+                        components,
+                    }) = effects.next_if(|e| matches!(e, VmEffect::Spawn { .. }))
+                    {
+                        batch.push((temp_id, components));
+                    }
+                    world.spawn_batch_with_ids(&batch)?
+                }
+                effect => self.apply_effect(world, &effect)?,
+            };
+        }
+        Ok(world)
+    }
+
+    /// Applies an effect to the world, recording the cascade steps of a
+    /// destroy.
+    fn apply_effect(&mut self, world: World, effect: &VmEffect) -> Result<World> {
+        match effect {
+            VmEffect::Destroy { entity } => self.destroy(&world, *entity),
+            effect => crate::spike::apply_effect(world, effect),
+        }
+    }
+
+    /// Destroys an entity, applying `:on-delete` policies to the links that
+    /// point at it and recording each step.
+    fn destroy(&mut self, world: &World, entity: EntityId) -> Result<World> {
+        let (world, steps) = world.destroy_cascading(entity)?;
+        self.cascades.extend(steps);
+        Ok(world)
+    }
+
+    /// Runs the systems registered for a phase and applies their effects,
+    /// attributing writes, spawns, and links to the system that made them.
+    ///
+    /// Effects pass through the middleware chain first and are handled like
+    /// hook effects otherwise.
+    fn run_systems(
+        &mut self,
+        phase: TickPhase,
+        mut world: World,
+        commands: &mut Vec<VmEffect>,
+        runs: &mut Vec<SystemRun>,
+    ) -> Result<World> {
+        if self.systems.is_empty() {
+            return Ok(world);
+        }
+        let results: Vec<(String, Vec<VmEffect>)> = self
+            .systems
+            .run(phase, &world)?
+            .into_iter()
+            .map(|(name, effects)| (name.to_string(), effects))
+            .collect();
+        for (name, effects) in results {
+            runs.push(SystemRun {
+                name: name.clone(),
+                phase,
+                effects: effects.len(),
+            });
+            let origin = world.interner_mut().intern_keyword(&name);
+            for effect in self.middleware.process_all(effects, &world)? {
+                world = self.apply_system_effect(world, effect, origin, commands)?;
+            }
+        }
+        Ok(world)
+    }
+
+    /// Applies one system or rule body effect, recording its provenance.
+    fn apply_system_effect(
+        &mut self,
+        world: World,
+        effect: VmEffect,
+        origin: KeywordId,
+        commands: &mut Vec<VmEffect>,
+    ) -> Result<World> {
+        match &effect {
+            VmEffect::Schedule {
+                delay,
+                repeat,
+                owner,
+                action,
+            } => {
+                self.schedule_timer(*delay, *repeat, *owner, action.clone());
+                return Ok(world);
+            }
+            VmEffect::Command { .. } => {
+                commands.push(effect);
+                return Ok(world);
+            }
+            VmEffect::Spawn { temp_id, .. } => {
+                self.provenance.record_spawn(*temp_id, origin, Vec::new());
+            }
+            VmEffect::Link {
+                source,
+                relationship,
+                target,
+            }
+            | VmEffect::Unlink {
+                source,
+                relationship,
+                target,
+            } => {
+                let change = if matches!(effect, VmEffect::Link { .. }) {
+                    LinkChange::Linked
+                } else {
+                    LinkChange::Unlinked
+                };
+                self.provenance.record_link(
+                    *source,
+                    *relationship,
+                    *target,
+                    change,
+                    origin,
+                    Vec::new(),
+                );
+            }
+            VmEffect::SetComponent {
+                entity, component, ..
+            }
+            | VmEffect::SetField {
+                entity, component, ..
+            }
+            | VmEffect::RemoveComponent { entity, component }
+            | VmEffect::VecRemove {
+                entity, component, ..
+            }
+            | VmEffect::VecAdd {
+                entity, component, ..
+            }
+            | VmEffect::SetRemove {
+                entity, component, ..
+            }
+            | VmEffect::SetAdd {
+                entity, component, ..
+            } => self.provenance.record_write(*entity, *component, origin),
+            _ => {}
+        }
+        self.apply_effect(world, &effect)
+    }
+
     /// Inject input events into the world.
     fn inject_inputs(&mut self, mut world: World, inputs: &[InputEvent]) -> Result<World> {
         for input in inputs {
@@ -233,16 +833,19 @@ impl TickExecutor {
                     let (new_world, spawned_entity) =
                         world.spawn(&longtable_foundation::LtMap::new())?;
                     let mut w = new_world;
+                    // Input-driven spawns are attributed to :input
+                    let input = w.interner_mut().intern_keyword("input");
+                    self.provenance
+                        .record_spawn(spawned_entity, input, Vec::new());
                     // Set initial components
                     for (comp, val) in components {
                         w = w.set(spawned_entity, *comp, val.clone())?;
                     }
                     w
                 }
-                InputEvent::Destroy { entity } => world.destroy(*entity)?,
-                InputEvent::Custom { .. } => {
-                    // Custom events are for game logic - not handled at this level
-                    world
+                InputEvent::Destroy { entity } => self.destroy(&world, *entity)?,
+                InputEvent::Custom { name, payload } => {
+                    crate::event::emit(world, *name, payload.clone())?.0
                 }
             };
         }
@@ -262,8 +865,9 @@ mod tests {
     use longtable_language::declaration::{
         Pattern as DeclPattern, PatternClause as DeclClause, PatternValue,
     };
-    use longtable_storage::ComponentSchema;
+    use longtable_storage::{ComponentSchema, OnDelete, RelationshipSchema};
 
+    use crate::middleware::Verdict;
     use crate::pattern::PatternCompiler;
 
     #[test]
@@ -368,23 +972,434 @@ mod tests {
                 value: PatternValue::Wildcard,
                 span: Span::default(),
             }],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         };
         let compiled = PatternCompiler::compile(&pattern, world.interner_mut()).unwrap();
         let rule_name = world.interner_mut().intern_keyword("process-health");
         let rule = CompiledRule::new(rule_name, compiled);
 
         // Create executor with rules
-        // Note: Rules fire but our execute_activation is a no-op, so entities
-        // won't actually be processed. This is a placeholder test.
+        // Note: A rule added without a body only counts its activations, so
+        // entities won't actually be processed.
         let executor = TickExecutor::new().with_rules(vec![rule]);
         let mut executor = executor;
 
         let result = executor.tick(world, &[]).unwrap();
 
         assert!(result.is_ok());
-        // With our no-op executor, rules fire but entities aren't modified
-        // so we get 2 activations (one per entity)
+        // Entities aren't modified, so we get 2 activations (one per entity)
+        assert_eq!(result.activations_fired, 2);
+
+        // Each firing is counted against its rule, in the tick and in total
+        let stats = result.rule_metrics.get(rule_name).unwrap();
+        assert_eq!(stats.activations, 2);
+        let world = result.world;
+        executor.tick(world, &[]).unwrap();
+        assert_eq!(
+            executor.rule_metrics().get(rule_name).unwrap().activations,
+            4
+        );
+        executor.reset_rule_metrics();
+        assert!(executor.rule_metrics().is_empty());
+    }
+
+    #[test]
+    fn rule_bodies_are_timed_as_their_effects_apply() {
+        let source =
+            "(rule: wound :where [[?e :hp ?hp]] :then [(set-component! ?e :wounded true)])";
+        let ast = &longtable_language::parse(source).unwrap()[0];
+        let decl = longtable_language::DeclarationAnalyzer::analyze_rule(ast)
+            .unwrap()
+            .unwrap();
+
+        let mut world = World::new(42);
+        let hp = world.interner_mut().intern_keyword("hp");
+        let wounded = world.interner_mut().intern_keyword("wounded");
+        world = world.register_component(ComponentSchema::tag(hp)).unwrap();
+        world = world
+            .register_component(ComponentSchema::tag(wounded))
+            .unwrap();
+        let (w, entity) = world.spawn(&LtMap::new()).unwrap();
+        world = w.set(entity, hp, Value::Bool(true)).unwrap();
+
+        let rule = crate::rule::RuleCompiler::compile(&decl, world.interner_mut()).unwrap();
+        let rule_name = rule.name;
+        let mut executor = TickExecutor::new();
+        executor.add_full_rule(rule);
+
+        let result = executor.tick(world, &[]).unwrap();
+        assert_eq!(
+            result.world.get(entity, wounded).unwrap(),
+            Some(Value::Bool(true))
+        );
+        let stats = result.rule_metrics.get(rule_name).unwrap();
+        assert_eq!(stats.activations, 1);
+        assert!(stats.effect_time > std::time::Duration::ZERO);
+    }
+
+    #[test]
+    fn failing_rule_body_abandons_the_tick_with_its_location() {
+        let source = "(rule: halve\n  :where [[?e :hp ?hp]]\n  :then [(/ 10 (count []))])";
+        let ast = &longtable_language::parse(source).unwrap()[0];
+        let decl = longtable_language::DeclarationAnalyzer::analyze_rule(ast)
+            .unwrap()
+            .unwrap();
+
+        let mut world = World::new(42);
+        let hp = world.interner_mut().intern_keyword("hp");
+        world = world.register_component(ComponentSchema::tag(hp)).unwrap();
+        let (w, entity) = world.spawn(&LtMap::new()).unwrap();
+        world = w.set(entity, hp, Value::Bool(true)).unwrap();
+
+        let rule = crate::rule::RuleCompiler::compile(&decl, world.interner_mut())
+            .unwrap()
+            .with_file("game/rules.lt");
+        let mut executor = TickExecutor::new();
+        executor.add_full_rule(rule);
+
+        let err = executor.tick(world, &[]).unwrap_err();
+        let context = err.context.unwrap();
+        assert_eq!(context.source.as_deref(), Some("game/rules.lt"));
+        assert_eq!(context.line, Some(3));
+        assert!(
+            context
+                .stack
+                .contains(&"rule :halve (game/rules.lt:3)".to_string())
+        );
+    }
+
+    #[test]
+    fn debugger_sees_each_point_and_can_abandon_the_tick() {
+        let mut world = World::new(42);
+        let health = world.interner_mut().intern_keyword("health");
+        world = world
+            .register_component(ComponentSchema::tag(health))
+            .unwrap();
+        let (w, entity) = world.spawn(&LtMap::new()).unwrap();
+        world = w.set(entity, health, Value::Bool(true)).unwrap();
+
+        let pattern = DeclPattern {
+            clauses: vec![DeclClause {
+                entity_var: "e".to_string(),
+                component: "health".to_string(),
+                value: PatternValue::Wildcard,
+                span: Span::default(),
+            }],
+            negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
+        };
+        let compiled = PatternCompiler::compile(&pattern, world.interner_mut()).unwrap();
+        let rule_name = world.interner_mut().intern_keyword("heal");
+        let mut executor =
+            TickExecutor::new().with_rules(vec![CompiledRule::new(rule_name, compiled)]);
+
+        let mut points = Vec::new();
+        executor
+            .tick_with_debugger(
+                world.clone(),
+                &[],
+                |_, _| Ok(Vec::new()),
+                |point, _| {
+                    points.push(match point {
+                        DebugPoint::TickStart(tick) => format!("tick {tick}"),
+                        DebugPoint::Phase(phase) => phase.name().to_string(),
+                        DebugPoint::Rule(activation) => {
+                            assert_eq!(activation.rule_name, rule_name);
+                            let bound = activation.bindings.get("e");
+                            assert_eq!(bound, Some(&Value::EntityRef(entity)));
+                            "rule".to_string()
+                        }
+                    });
+                    Ok(())
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            points,
+            [
+                "tick 1",
+                "begin-tick",
+                "after-inputs",
+                "rule",
+                "before-constraints",
+                "after-commit"
+            ]
+        );
+
+        let err = executor
+            .tick_with_debugger(
+                world,
+                &[],
+                |_, _| Ok(Vec::new()),
+                |point, _| match point {
+                    DebugPoint::Rule(_) => {
+                        Err(Error::new(ErrorKind::Internal("abandoned".to_string())))
+                    }
+                    _ => Ok(()),
+                },
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("abandoned"), "{err}");
+    }
+
+    #[test]
+    fn custom_inputs_are_emitted_as_events_and_drained() {
+        let mut world = World::new(42);
+        let opened = world.interner_mut().intern_keyword("event/door-opened");
+
+        // [:event/door-opened ?payload]
+        let pattern = DeclPattern {
+            clauses: vec![DeclClause {
+                entity_var: "__global__".to_string(),
+                component: "event/door-opened".to_string(),
+                value: PatternValue::Variable("payload".to_string()),
+                span: Span::default(),
+            }],
+            negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
+        };
+        let compiled = PatternCompiler::compile(&pattern, world.interner_mut()).unwrap();
+        let rule_name = world.interner_mut().intern_keyword("creak");
+        let mut executor =
+            TickExecutor::new().with_rules(vec![CompiledRule::new(rule_name, compiled)]);
+
+        // Payloads other than maps are wrapped rather than rejected
+        let inputs = vec![
+            InputEvent::Custom {
+                name: opened,
+                payload: Value::Map(LtMap::new()),
+            },
+            InputEvent::Custom {
+                name: opened,
+                payload: Value::Int(7),
+            },
+        ];
+        let result = executor.tick(world, &inputs).unwrap();
+
         assert_eq!(result.activations_fired, 2);
+        assert_eq!(result.events_drained, 2);
+        assert!(crate::event::pending(&result.world).is_empty());
+
+        // Nothing left to match on the next tick
+        let result = executor.tick(result.world, &[]).unwrap();
+        assert_eq!(result.activations_fired, 0);
+    }
+
+    #[test]
+    fn destroys_report_their_cascade_steps() {
+        let mut world = World::new(42);
+        let part_of = world.interner_mut().intern_keyword("part-of");
+        world = world
+            .register_relationship(
+                RelationshipSchema::new(part_of).with_on_delete(OnDelete::Cascade),
+            )
+            .unwrap();
+        let (world, chest) = world.spawn(&LtMap::new()).unwrap();
+        let (world, lid) = world.spawn(&LtMap::new()).unwrap();
+        let world = world.link(lid, part_of, chest).unwrap();
+
+        // An input destroy cascades
+        let mut executor = TickExecutor::new();
+        let result = executor
+            .tick(world.clone(), &[InputEvent::Destroy { entity: chest }])
+            .unwrap();
+        assert!(!result.world.exists(lid));
+        assert_eq!(result.cascades.len(), 1);
+        assert_eq!(result.cascades[0].source, lid);
+        assert_eq!(result.cascades[0].action, OnDelete::Cascade);
+
+        // So does a hook's, and steps don't carry over between ticks
+        let result = executor
+            .tick_with_hooks(world, &[], |phase, _| {
+                Ok(if phase == TickPhase::AfterInputs {
+                    vec![VmEffect::Destroy { entity: chest }]
+                } else {
+                    Vec::new()
+                })
+            })
+            .unwrap();
+        assert_eq!(result.cascades.len(), 1);
+        assert!(
+            executor
+                .tick(result.world, &[])
+                .unwrap()
+                .cascades
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn play_mode_skips_provenance() {
+        let mut world = World::new(42);
+        let health = world.interner_mut().intern_keyword("health");
+        world = world
+            .register_component(ComponentSchema::tag(health))
+            .unwrap();
+        let (world, entity) = world.spawn(&LtMap::new()).unwrap();
+        let inputs = vec![InputEvent::Set {
+            entity,
+            component: health,
+            value: Value::Bool(true),
+        }];
+
+        let mut executor = TickExecutor::new().with_mode(ExecutionMode::Play);
+        assert_eq!(executor.mode(), ExecutionMode::Play);
+        let result = executor.tick(world, &inputs).unwrap();
+
+        assert!(result.world.has(entity, health));
+        assert!(executor.provenance().last_writer(entity, health).is_none());
+    }
+
+    #[test]
+    fn timers_fall_due_relative_to_current_tick() {
+        let mut executor = TickExecutor::new();
+        let world = World::new(42);
+        let world = executor.tick(world, &[]).unwrap().world;
+
+        // Scheduled after tick 1, so due during tick 3
+        executor.schedule(2, Value::Int(1));
+        assert!(executor.take_due_timers().is_empty());
+
+        let world = executor.tick(world, &[]).unwrap().world;
+        let due = executor.take_due_timers();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].due_tick, 3);
+        assert!(executor.scheduler().is_empty());
+
+        // Hooks can schedule timers too
+        executor
+            .tick_with_hooks(world, &[], |phase, _| {
+                Ok(if phase == TickPhase::AfterInputs {
+                    vec![VmEffect::Schedule {
+                        delay: 1,
+                        repeat: false,
+                        owner: None,
+                        action: Value::Nil,
+                    }]
+                } else {
+                    Vec::new()
+                })
+            })
+            .unwrap();
+        assert_eq!(executor.take_due_timers().len(), 1);
+    }
+
+    #[test]
+    fn tick_phase_names_round_trip() {
+        for phase in TickPhase::ALL {
+            assert_eq!(TickPhase::from_name(phase.name()), Some(phase));
+        }
+        assert_eq!(TickPhase::from_name("never"), None);
+    }
+
+    #[test]
+    fn tick_hooks_run_in_phase_order_and_apply_effects() {
+        let mut world = World::new(42);
+        let seen = world.interner_mut().intern_keyword("seen");
+        world = world
+            .register_component(ComponentSchema::tag(seen))
+            .unwrap();
+        let (world, entity) = world.spawn(&LtMap::new()).unwrap();
+
+        let mut phases = Vec::new();
+        let mut executor = TickExecutor::new();
+        let result = executor
+            .tick_with_hooks(world, &[], |phase, w| {
+                phases.push(phase);
+                if phase == TickPhase::BeforeConstraints {
+                    return Ok(vec![VmEffect::SetComponent {
+                        entity,
+                        component: seen,
+                        value: Value::Bool(true),
+                    }]);
+                }
+                if phase == TickPhase::AfterCommit {
+                    assert!(w.has(entity, seen));
+                }
+                Ok(Vec::new())
+            })
+            .unwrap();
+
+        assert!(result.is_ok());
+        assert!(result.world.has(entity, seen));
+        assert_eq!(phases, TickPhase::ALL.to_vec());
+    }
+
+    #[test]
+    fn tick_hooks_cannot_write_after_commit() {
+        let world = World::new(42);
+        let (world, entity) = world.spawn(&LtMap::new()).unwrap();
+
+        let mut executor = TickExecutor::new();
+        let result = executor.tick_with_hooks(world, &[], |phase, _| {
+            if phase == TickPhase::AfterCommit {
+                return Ok(vec![VmEffect::Destroy { entity }]);
+            }
+            Ok(Vec::new())
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn tick_hook_effects_pass_through_middleware() {
+        let world = World::new(42);
+        let (world, kept) = world.spawn(&LtMap::new()).unwrap();
+        let (world, protected) = world.spawn(&LtMap::new()).unwrap();
+
+        let mut executor = TickExecutor::new();
+        executor
+            .middleware_mut()
+            .register("protect", 0, move |effect, _: &World| match effect {
+                VmEffect::Destroy { entity } if entity == protected => Verdict::Drop,
+                other => Verdict::Apply(other),
+            });
+        let result = executor
+            .tick_with_hooks(world, &[], |phase, _| {
+                if phase != TickPhase::BeginTick {
+                    return Ok(Vec::new());
+                }
+                Ok(vec![
+                    VmEffect::Destroy { entity: kept },
+                    VmEffect::Destroy { entity: protected },
+                ])
+            })
+            .unwrap();
+
+        assert!(!result.world.exists(kept));
+        assert!(result.world.exists(protected));
+    }
+
+    #[test]
+    fn tick_hooks_queue_session_commands() {
+        let mut executor = TickExecutor::new();
+        let result = executor
+            .tick_with_hooks(World::new(42), &[], |phase, _| {
+                Ok(vec![VmEffect::Command {
+                    name: "save!".to_string(),
+                    args: vec![Value::from(phase.name())],
+                }])
+            })
+            .unwrap();
+
+        let phases: Vec<_> = result
+            .commands
+            .iter()
+            .map(|command| match command {
+                VmEffect::Command { args, .. } => args[0].clone(),
+                other => panic!("unexpected effect {other:?}"),
+            })
+            .collect();
+        let expected: Vec<_> = TickPhase::ALL
+            .iter()
+            .map(|phase| Value::from(phase.name()))
+            .collect();
+        assert_eq!(phases, expected);
     }
 
     #[test]
@@ -413,4 +1428,104 @@ mod tests {
         let who_wrote = executor.provenance().why(entity, health);
         assert!(who_wrote.is_some());
     }
+
+    #[test]
+    fn systems_run_at_their_phase_with_provenance() {
+        let mut world = World::new(42);
+        let position = world.interner_mut().intern_keyword("position");
+        let velocity = world.interner_mut().intern_keyword("velocity");
+        let x = world.interner_mut().intern_keyword("x");
+        let schema = |name| {
+            ComponentSchema::new(name).with_field(longtable_storage::FieldSchema::required(
+                x,
+                longtable_foundation::Type::Int,
+            ))
+        };
+        world = world
+            .register_component(schema(position))
+            .unwrap()
+            .register_component(schema(velocity))
+            .unwrap();
+        let at = |n| Value::Map(LtMap::new().insert(Value::Keyword(x), Value::Int(n)));
+        let (world, entity) = world.spawn(&LtMap::new()).unwrap();
+        let world = world
+            .set(entity, position, at(0))
+            .unwrap()
+            .set(entity, velocity, at(3))
+            .unwrap();
+
+        let mut executor = TickExecutor::new();
+        let access = SystemAccess::new()
+            .reads(["position", "velocity"])
+            .writes(["position"]);
+        executor
+            .register_system(
+                "physics",
+                TickPhase::AfterInputs,
+                access,
+                move |world: &World| {
+                    let mut effects = Vec::new();
+                    for e in world.with_component(velocity) {
+                        let p = world.get_field(e, position, x)?;
+                        let v = world.get_field(e, velocity, x)?;
+                        if let (Some(Value::Int(p)), Some(Value::Int(v))) = (p, v) {
+                            effects.push(VmEffect::SetField {
+                                entity: e,
+                                component: position,
+                                field: x,
+                                value: Value::Int(p + v),
+                            });
+                        }
+                    }
+                    Ok(effects)
+                },
+            )
+            .unwrap();
+
+        let result = executor.tick(world, &[]).unwrap();
+        assert!(result.is_ok());
+        assert_eq!(
+            result.world.get_field(entity, position, x).unwrap(),
+            Some(Value::Int(3))
+        );
+        assert_eq!(
+            result.systems,
+            vec![SystemRun {
+                name: "physics".to_string(),
+                phase: TickPhase::AfterInputs,
+                effects: 1,
+            }]
+        );
+        let writer = executor.provenance().why(entity, position).unwrap();
+        assert_eq!(result.world.interner().get_keyword(writer), Some("physics"));
+    }
+
+    #[test]
+    fn systems_cannot_write_undeclared_components() {
+        let mut world = World::new(42);
+        let health = world.interner_mut().intern_keyword("health");
+        world = world
+            .register_component(ComponentSchema::tag(health))
+            .unwrap();
+        let (world, entity) = world.spawn(&LtMap::new()).unwrap();
+
+        let mut executor = TickExecutor::new();
+        executor
+            .register_system(
+                "sneaky",
+                TickPhase::BeginTick,
+                SystemAccess::new().reads(["health"]),
+                move |_: &World| {
+                    Ok(vec![VmEffect::SetComponent {
+                        entity,
+                        component: health,
+                        value: Value::Bool(true),
+                    }])
+                },
+            )
+            .unwrap();
+
+        let err = executor.tick(world, &[]).unwrap_err();
+        assert!(err.to_string().contains("wrote :health"), "{err}");
+    }
 }
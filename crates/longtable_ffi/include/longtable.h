@@ -0,0 +1,53 @@
+/*
+ * C API for embedding Longtable. See crates/longtable_ffi/src/lib.rs for the
+ * full documentation of each function.
+ *
+ * Strings returned through out_* parameters are owned by the caller and must
+ * be released with longtable_string_free. Any out_* pointer may be NULL.
+ */
+
+#ifndef LONGTABLE_H
+#define LONGTABLE_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define LONGTABLE_ABI_VERSION 1
+
+typedef enum LtStatus {
+    LT_OK = 0,
+    LT_ERROR = 1,
+    LT_INVALID_ARGUMENT = 2,
+    LT_PANIC = 3,
+} LtStatus;
+
+typedef struct LtSession LtSession;
+
+uint32_t longtable_abi_version(void);
+
+LtSession *longtable_session_new(void);
+void longtable_session_free(LtSession *session);
+
+LtStatus longtable_eval(LtSession *session, const char *source, char **out_json);
+LtStatus longtable_input(LtSession *session, const char *line, char **out_text);
+LtStatus longtable_tick(LtSession *session, uint64_t *out_tick);
+LtStatus longtable_take_output(LtSession *session, char **out_text);
+
+LtStatus longtable_entity_by_name(LtSession *session, const char *name,
+                                  uint64_t *out_index, uint32_t *out_generation);
+LtStatus longtable_entity_json(LtSession *session, uint64_t index, uint32_t generation,
+                               char **out_json);
+LtStatus longtable_component_json(LtSession *session, uint64_t index, uint32_t generation,
+                                  const char *component, char **out_json);
+
+const char *longtable_last_error(const LtSession *session);
+void longtable_string_free(char *text);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LONGTABLE_H */
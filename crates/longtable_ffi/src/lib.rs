@@ -0,0 +1,498 @@
+//! C API for embedding Longtable in other engines.
+//!
+//! This crate exposes a small, stable set of `extern "C"` functions over a
+//! headless [`Repl`] so a C, C++, or C# (Unity) host can drive a world
+//! without touching the generic Rust API. The matching declarations are in
+//! `include/longtable.h`.
+//!
+//! # Conventions
+//!
+//! - A session is an opaque [`LtSession`] pointer from
+//!   [`longtable_session_new`], released with [`longtable_session_free`].
+//! - Functions return an [`LtStatus`]. On [`LtStatus::Error`] or
+//!   [`LtStatus::Panic`], [`longtable_last_error`] describes what went wrong.
+//! - Strings passed in are NUL-terminated UTF-8. Strings handed out through
+//!   `out_*` parameters are owned by the caller and must be released with
+//!   [`longtable_string_free`]. Any `out_*` pointer may be null to discard it.
+//! - Values cross the boundary as JSON, encoded as described in
+//!   [`longtable_runtime::json`].
+//! - Panics never unwind into the host; they are reported as [`LtStatus::Panic`].
+
+#![warn(missing_docs)]
+#![warn(clippy::all)]
+#![warn(clippy::pedantic)]
+// The Error type is intentionally large for rich error context
+#![allow(clippy::result_large_err)]
+
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use longtable_foundation::{EntityId, Error, Value};
+use longtable_runtime::json;
+use longtable_runtime::{HeadlessEditor, Repl};
+
+/// Version of this C API. Bumped whenever a signature or behavior changes.
+pub const ABI_VERSION: u32 = 1;
+
+/// Result of a C API call.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LtStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// Evaluation or lookup failed; see [`longtable_last_error`].
+    Error = 1,
+    /// A required pointer was null or a string was not valid UTF-8.
+    InvalidArgument = 2,
+    /// The engine panicked; the session may be inconsistent.
+    Panic = 3,
+}
+
+/// An embedded Longtable session: a world plus the interpreter driving it.
+pub struct LtSession {
+    repl: Repl<HeadlessEditor>,
+    last_error: Option<CString>,
+}
+
+impl LtSession {
+    fn new() -> longtable_foundation::Result<Self> {
+        let mut repl = Repl::with_editor(HeadlessEditor).with_captured_output();
+        repl.load_stdlib()?;
+        Ok(Self {
+            repl,
+            last_error: None,
+        })
+    }
+
+    fn set_error(&mut self, message: &str) {
+        self.last_error = Some(to_c_string(message));
+    }
+}
+
+/// Why a call failed.
+enum Failure {
+    InvalidArgument(&'static str),
+    Engine(Error),
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        Self::Engine(error)
+    }
+}
+
+/// Runs `f` against the session, catching panics and recording errors.
+unsafe fn call(
+    session: *mut LtSession,
+    f: impl FnOnce(&mut LtSession) -> Result<(), Failure>,
+) -> LtStatus {
+    // SAFETY: the caller passes a pointer from `longtable_session_new` or null.
+    let Some(session) = (unsafe { session.as_mut() }) else {
+        return LtStatus::InvalidArgument;
+    };
+    session.last_error = None;
+
+    match panic::catch_unwind(AssertUnwindSafe(|| f(session))) {
+        Ok(Ok(())) => LtStatus::Ok,
+        Ok(Err(Failure::InvalidArgument(name))) => {
+            session.set_error(&format!("invalid argument: {name}"));
+            LtStatus::InvalidArgument
+        }
+        Ok(Err(Failure::Engine(error))) => {
+            session.set_error(&error.to_string());
+            LtStatus::Error
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(ToString::to_string)
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            session.set_error(&format!("panic: {message}"));
+            LtStatus::Panic
+        }
+    }
+}
+
+/// Reads a NUL-terminated UTF-8 argument.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &'static str) -> Result<&'a str, Failure> {
+    if ptr.is_null() {
+        return Err(Failure::InvalidArgument(name));
+    }
+    // SAFETY: the caller passes a NUL-terminated string that outlives the call.
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|_| Failure::InvalidArgument(name))
+}
+
+/// Hands `value` to the caller through `out`, unless `out` is null.
+unsafe fn write_out<T>(out: *mut T, value: T) {
+    if !out.is_null() {
+        // SAFETY: the caller passes a valid, writable pointer or null.
+        unsafe { out.write(value) };
+    }
+}
+
+/// Hands an owned string to the caller through `out`, unless `out` is null.
+unsafe fn write_string(out: *mut *mut c_char, text: &str) {
+    if !out.is_null() {
+        // SAFETY: as for `write_out`.
+        unsafe { out.write(to_c_string(text).into_raw()) };
+    }
+}
+
+/// Converts to a C string, replacing interior NULs.
+fn to_c_string(text: &str) -> CString {
+    CString::new(text.replace('\0', "\u{fffd}")).unwrap_or_default()
+}
+
+fn entity_json(session: &LtSession, entity: EntityId) -> Result<String, Failure> {
+    let world = session.repl.session().world();
+    if !world.exists(entity) {
+        return Err(Error::entity_not_found(entity).into());
+    }
+    let mut object = serde_json::Map::new();
+    for &component in world.entity_components(entity) {
+        let name = world.interner().get_keyword(component).unwrap_or_default();
+        let value = world.get(entity, component)?.unwrap_or(Value::Nil);
+        object.insert(name.to_string(), json::to_json(&value, world.interner()));
+    }
+    Ok(serde_json::Value::Object(object).to_string())
+}
+
+/// Returns [`ABI_VERSION`].
+#[unsafe(no_mangle)]
+pub extern "C" fn longtable_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+/// Creates a session with the standard library loaded.
+///
+/// Returns null if the session could not be created.
+#[unsafe(no_mangle)]
+pub extern "C" fn longtable_session_new() -> *mut LtSession {
+    match panic::catch_unwind(LtSession::new) {
+        Ok(Ok(session)) => Box::into_raw(Box::new(session)),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Destroys a session. Passing null does nothing.
+///
+/// # Safety
+///
+/// `session` must be null or a pointer from [`longtable_session_new`] that
+/// has not already been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn longtable_session_free(session: *mut LtSession) {
+    if !session.is_null() {
+        // SAFETY: guaranteed by the caller.
+        drop(unsafe { Box::from_raw(session) });
+    }
+}
+
+/// Evaluates DSL source and writes the value of the last form as JSON.
+///
+/// Output printed during evaluation is kept for [`longtable_take_output`].
+///
+/// # Safety
+///
+/// `session` must be a live session, `source` a NUL-terminated string, and
+/// `out_json` null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn longtable_eval(
+    session: *mut LtSession,
+    source: *const c_char,
+    out_json: *mut *mut c_char,
+) -> LtStatus {
+    unsafe {
+        call(session, |session| {
+            let source = str_arg(source, "source")?;
+            let value = session.repl.eval(source)?;
+            let interner = session.repl.session().world().interner();
+            write_string(out_json, &json::to_json(&value, interner).to_string());
+            Ok(())
+        })
+    }
+}
+
+/// Handles a line of player input (e.g. `take lamp`) and writes the narration
+/// it produced.
+///
+/// # Safety
+///
+/// `session` must be a live session, `line` a NUL-terminated string, and
+/// `out_text` null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn longtable_input(
+    session: *mut LtSession,
+    line: *const c_char,
+    out_text: *mut *mut c_char,
+) -> LtStatus {
+    unsafe {
+        call(session, |session| {
+            let line = str_arg(line, "line")?;
+            session.repl.input(line)?;
+            write_string(out_text, &session.repl.take_output());
+            Ok(())
+        })
+    }
+}
+
+/// Runs one tick and writes the new tick number.
+///
+/// # Safety
+///
+/// `session` must be a live session and `out_tick` null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn longtable_tick(session: *mut LtSession, out_tick: *mut u64) -> LtStatus {
+    unsafe {
+        call(session, |session| {
+            session.repl.step(&[])?;
+            write_out(out_tick, session.repl.tick_number());
+            Ok(())
+        })
+    }
+}
+
+/// Writes and clears the output printed since it was last taken.
+///
+/// # Safety
+///
+/// `session` must be a live session and `out_text` null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn longtable_take_output(
+    session: *mut LtSession,
+    out_text: *mut *mut c_char,
+) -> LtStatus {
+    unsafe {
+        call(session, |session| {
+            write_string(out_text, &session.repl.take_output());
+            Ok(())
+        })
+    }
+}
+
+/// Looks up an entity declared with `(spawn: name ...)`.
+///
+/// # Safety
+///
+/// `session` must be a live session, `name` a NUL-terminated string, and the
+/// `out_*` pointers null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn longtable_entity_by_name(
+    session: *mut LtSession,
+    name: *const c_char,
+    out_index: *mut u64,
+    out_generation: *mut u32,
+) -> LtStatus {
+    unsafe {
+        call(session, |session| {
+            let name = str_arg(name, "name")?;
+            let entity = session.repl.session().get_entity(name).ok_or_else(|| {
+                Error::new(longtable_foundation::ErrorKind::NotFound(format!(
+                    "no entity named '{name}'"
+                )))
+            })?;
+            write_out(out_index, entity.index);
+            write_out(out_generation, entity.generation);
+            Ok(())
+        })
+    }
+}
+
+/// Writes all of an entity's components as a JSON object keyed by
+/// component name.
+///
+/// # Safety
+///
+/// `session` must be a live session and `out_json` null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn longtable_entity_json(
+    session: *mut LtSession,
+    index: u64,
+    generation: u32,
+    out_json: *mut *mut c_char,
+) -> LtStatus {
+    unsafe {
+        call(session, |session| {
+            let json = entity_json(session, EntityId::new(index, generation))?;
+            write_string(out_json, &json);
+            Ok(())
+        })
+    }
+}
+
+/// Writes one component of an entity as JSON (`null` if the entity lacks it).
+///
+/// `component` may be written with or without its leading colon.
+///
+/// # Safety
+///
+/// `session` must be a live session, `component` a NUL-terminated string,
+/// and `out_json` null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn longtable_component_json(
+    session: *mut LtSession,
+    index: u64,
+    generation: u32,
+    component: *const c_char,
+    out_json: *mut *mut c_char,
+) -> LtStatus {
+    unsafe {
+        call(session, |session| {
+            let name = str_arg(component, "component")?;
+            let name = name.strip_prefix(':').unwrap_or(name);
+            let world = session.repl.session().world();
+            let component = world.interner().lookup_keyword(name).ok_or_else(|| {
+                Error::new(longtable_foundation::ErrorKind::NotFound(format!(
+                    "unknown component :{name}"
+                )))
+            })?;
+            let value = world
+                .get(EntityId::new(index, generation), component)?
+                .unwrap_or(Value::Nil);
+            write_string(
+                out_json,
+                &json::to_json(&value, world.interner()).to_string(),
+            );
+            Ok(())
+        })
+    }
+}
+
+/// Returns the error from the last failed call on this session, or null.
+///
+/// The string belongs to the session and is valid until the next call on it.
+///
+/// # Safety
+///
+/// `session` must be null or a live session.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn longtable_last_error(session: *const LtSession) -> *const c_char {
+    // SAFETY: guaranteed by the caller.
+    unsafe { session.as_ref() }
+        .and_then(|session| session.last_error.as_ref())
+        .map_or(ptr::null(), |message| message.as_ptr())
+}
+
+/// Frees a string returned through an `out_*` parameter. Passing null does
+/// nothing.
+///
+/// # Safety
+///
+/// `text` must be null or a string from this library that has not already
+/// been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn longtable_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        // SAFETY: guaranteed by the caller.
+        drop(unsafe { CString::from_raw(text) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(session: *mut LtSession, source: &str) -> (LtStatus, String) {
+        let source = CString::new(source).unwrap();
+        let mut out = ptr::null_mut();
+        let status = unsafe { longtable_eval(session, source.as_ptr(), &raw mut out) };
+        (status, unsafe { take_string(out) })
+    }
+
+    unsafe fn take_string(text: *mut c_char) -> String {
+        if text.is_null() {
+            return String::new();
+        }
+        let owned = unsafe { CStr::from_ptr(text) }
+            .to_string_lossy()
+            .into_owned();
+        unsafe { longtable_string_free(text) };
+        owned
+    }
+
+    fn last_error(session: *mut LtSession) -> String {
+        let message = unsafe { longtable_last_error(session) };
+        assert!(!message.is_null());
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn eval_tick_and_query_components() {
+        let session = longtable_session_new();
+        assert!(!session.is_null());
+
+        let (status, json) = eval(
+            session,
+            "(component: health :current :int :max :int)
+             (spawn: hero :health {:current 7 :max 10})
+             (+ 1 2)",
+        );
+        assert_eq!(status, LtStatus::Ok);
+        assert_eq!(json, "3");
+
+        let hero = CString::new("hero").unwrap();
+        let (mut index, mut generation) = (0, 0);
+        let status = unsafe {
+            longtable_entity_by_name(session, hero.as_ptr(), &raw mut index, &raw mut generation)
+        };
+        assert_eq!(status, LtStatus::Ok);
+
+        let mut out = ptr::null_mut();
+        let status = unsafe { longtable_entity_json(session, index, generation, &raw mut out) };
+        assert_eq!(status, LtStatus::Ok);
+        let components: serde_json::Value =
+            serde_json::from_str(&unsafe { take_string(out) }).unwrap();
+        assert_eq!(components["health"]["current"], 7);
+
+        let health = CString::new(":health").unwrap();
+        let status = unsafe {
+            longtable_component_json(session, index, generation, health.as_ptr(), &raw mut out)
+        };
+        assert_eq!(status, LtStatus::Ok);
+        assert_eq!(unsafe { take_string(out) }, r#"{"current":7,"max":10}"#);
+
+        let mut tick = 0;
+        assert_eq!(
+            unsafe { longtable_tick(session, &raw mut tick) },
+            LtStatus::Ok
+        );
+        assert_eq!(tick, 1);
+
+        unsafe { longtable_session_free(session) };
+    }
+
+    #[test]
+    fn errors_are_reported_not_raised() {
+        let session = longtable_session_new();
+
+        let (status, json) = eval(session, "(undefined-function 1)");
+        assert_eq!(status, LtStatus::Error);
+        assert!(json.is_empty());
+        assert!(!last_error(session).is_empty());
+
+        let (status, _) = eval(session, "(println \"hi\")");
+        assert_eq!(status, LtStatus::Ok);
+        assert!(unsafe { longtable_last_error(session) }.is_null());
+        let mut out = ptr::null_mut();
+        assert_eq!(
+            unsafe { longtable_take_output(session, &raw mut out) },
+            LtStatus::Ok
+        );
+        assert_eq!(unsafe { take_string(out) }, "hi\n");
+
+        let status = unsafe { longtable_eval(session, ptr::null(), ptr::null_mut()) };
+        assert_eq!(status, LtStatus::InvalidArgument);
+        assert_eq!(last_error(session), "invalid argument: source");
+
+        let status = unsafe { longtable_tick(ptr::null_mut(), ptr::null_mut()) };
+        assert_eq!(status, LtStatus::InvalidArgument);
+
+        unsafe { longtable_session_free(session) };
+    }
+}
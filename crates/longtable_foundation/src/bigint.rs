@@ -0,0 +1,451 @@
+//! Arbitrary-precision integers.
+//!
+//! Integer arithmetic in the VM promotes to a [`BigInt`] when an `i64` would
+//! overflow, so long-running simulations keep counting instead of wrapping.
+//! Results that fit back in an `i64` are demoted again (see
+//! [`Value::integer`](crate::Value::integer)), so a number is only ever held
+//! one way. Only what the VM needs is implemented: the four operations,
+//! remainder, comparison, and decimal conversion.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+/// An integer of any size.
+///
+/// Stored as a sign and a magnitude of 32-bit limbs, least significant
+/// first, with no leading zero limbs. Zero has no limbs and is never
+/// negative.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+/// The error for a string that isn't a decimal integer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseBigIntError;
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid integer literal")
+    }
+}
+
+impl std::error::Error for ParseBigIntError {}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        Self {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    /// Returns true if this is zero.
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    /// Returns true if this is less than zero.
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns the absolute value.
+    #[must_use]
+    pub fn abs(&self) -> Self {
+        Self::from_parts(false, self.magnitude.clone())
+    }
+
+    /// Returns the value as an `i64`, if it fits.
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0u64, |acc, &limb| (acc << 32) | u64::from(limb));
+        if !self.negative {
+            i64::try_from(magnitude).ok()
+        } else if magnitude <= 1 << 63 {
+            // 2^63 itself wraps to i64::MIN, which negates to itself
+            Some((magnitude as i64).wrapping_neg())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the nearest `f64`.
+    #[must_use]
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &limb| acc * 4_294_967_296.0 + f64::from(limb));
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    /// Divides by `divisor`, rounding toward zero like `i64` division, and
+    /// returns the quotient and remainder. The remainder takes the sign of
+    /// `self`.
+    ///
+    /// Returns `None` if `divisor` is zero.
+    #[must_use]
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &divisor.magnitude);
+        Some((
+            Self::from_parts(self.negative != divisor.negative, quotient),
+            Self::from_parts(self.negative, remainder),
+        ))
+    }
+}
+
+impl From<i64> for BigInt {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from(n: i64) -> Self {
+        let magnitude = n.unsigned_abs();
+        Self::from_parts(n < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(
+                self.negative,
+                add_magnitude(&self.magnitude, &other.magnitude),
+            );
+        }
+        match compare_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_parts(
+                other.negative,
+                sub_magnitude(&other.magnitude, &self.magnitude),
+            ),
+            _ => BigInt::from_parts(
+                self.negative,
+                sub_magnitude(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_magnitude(&self.magnitude, &other.magnitude),
+        )
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Decimal digits per chunk when converting to and from text.
+const CHUNK_DIGITS: usize = 9;
+const CHUNK: u32 = 1_000_000_000;
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut chunks = Vec::new();
+        let mut rest = self.magnitude.clone();
+        while !rest.is_empty() {
+            let (quotient, chunk) = div_rem_small(&rest, CHUNK);
+            chunks.push(chunk);
+            rest = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{first}")?;
+        }
+        for chunk in chunks {
+            write!(f, "{chunk:0CHUNK_DIGITS$}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BigInt({self})")
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseBigIntError);
+        }
+        let mut magnitude = Vec::new();
+        // The first chunk takes the odd digits so the rest are whole chunks
+        let first = match digits.len() % CHUNK_DIGITS {
+            0 => CHUNK_DIGITS,
+            n => n,
+        };
+        let mut start = 0;
+        let mut end = first.min(digits.len());
+        while start < digits.len() {
+            let chunk: u32 = digits[start..end].parse().map_err(|_| ParseBigIntError)?;
+            let scale = 10u32.pow(u32::try_from(end - start).unwrap_or(0));
+            magnitude = mul_add_small(&magnitude, scale, chunk);
+            start = end;
+            end += CHUNK_DIGITS;
+        }
+        Ok(Self::from_parts(negative, magnitude))
+    }
+}
+
+fn compare_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let total = u64::from(a.get(i).copied().unwrap_or(0))
+            + u64::from(b.get(i).copied().unwrap_or(0))
+            + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+/// Subtracts `b` from `a`, which must be at least as large.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut total = i64::from(limb) - i64::from(b.get(i).copied().unwrap_or(0)) - borrow;
+        borrow = 0;
+        if total < 0 {
+            total += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    while difference.last() == Some(&0) {
+        difference.pop();
+    }
+    difference
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let total = u64::from(product[i + j]) + u64::from(x) * u64::from(y) + carry;
+            product[i + j] = total as u32;
+            carry = total >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    product
+}
+
+/// Returns `a * factor + addend`.
+#[allow(clippy::cast_possible_truncation)]
+fn mul_add_small(a: &[u32], factor: u32, addend: u32) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = u64::from(addend);
+    for &limb in a {
+        let total = u64::from(limb) * u64::from(factor) + carry;
+        result.push(total as u32);
+        carry = total >> 32;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn div_rem_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for (i, &limb) in a.iter().enumerate().rev() {
+        let current = (remainder << 32) | u64::from(limb);
+        quotient[i] = (current / u64::from(divisor)) as u32;
+        remainder = current % u64::from(divisor);
+    }
+    while quotient.last() == Some(&0) {
+        quotient.pop();
+    }
+    (quotient, remainder as u32)
+}
+
+/// Long division, one bit at a time.
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = b {
+        let (quotient, remainder) = div_rem_small(a, *divisor);
+        return (
+            quotient,
+            if remainder == 0 {
+                Vec::new()
+            } else {
+                vec![remainder]
+            },
+        );
+    }
+    if compare_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for bit in (0..a.len() * 32).rev() {
+        // remainder = remainder * 2 + the next bit of a
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for limb in &mut remainder {
+            let next = *limb >> 31;
+            *limb = (*limb << 1) | carry;
+            carry = next;
+        }
+        if carry > 0 {
+            remainder.push(carry);
+        }
+        if compare_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    while quotient.last() == Some(&0) {
+        quotient.pop();
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn round_trips_through_text_and_i64() {
+        for s in [
+            "0",
+            "-1",
+            "1000000000",
+            "-9223372036854775808",
+            "123456789012345678901234567890",
+        ] {
+            assert_eq!(big(s).to_string(), s);
+        }
+        assert_eq!(big("+007").to_string(), "7");
+        assert!("12a".parse::<BigInt>().is_err());
+        assert!("-".parse::<BigInt>().is_err());
+
+        assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(BigInt::from(i64::MAX).to_i64(), Some(i64::MAX));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(big("-9223372036854775809").to_i64(), None);
+    }
+
+    #[test]
+    fn arithmetic_matches_i128() {
+        let samples: [i128; 8] = [
+            0,
+            1,
+            -7,
+            i128::from(i64::MAX),
+            i128::from(i64::MIN),
+            i128::from(u32::MAX) + 1,
+            -123_456_789_012_345_678_901_234,
+            98_765_432_109_876_543_210,
+        ];
+        for a in samples {
+            for b in samples {
+                let (x, y) = (big(&a.to_string()), big(&b.to_string()));
+                assert_eq!((&x + &y).to_string(), (a + b).to_string());
+                assert_eq!((&x - &y).to_string(), (a - b).to_string());
+                if let Some(product) = a.checked_mul(b) {
+                    assert_eq!((&x * &y).to_string(), product.to_string());
+                }
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+                match x.div_rem(&y) {
+                    Some((q, r)) => {
+                        assert_eq!(q.to_string(), (a / b).to_string(), "{a} / {b}");
+                        assert_eq!(r.to_string(), (a % b).to_string(), "{a} % {b}");
+                    }
+                    None => assert_eq!(b, 0),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Wall-clock and monotonic time that works on every target.
+//!
+//! `std::time::Instant::now()` and `SystemTime::now()` panic on
+//! `wasm32-unknown-unknown`, where there is no clock without a host binding.
+//! Code that only uses time for diagnostics (tracing, telemetry, tick timing,
+//! transcript timestamps) goes through this module instead: on native targets
+//! it is `std::time`; on `wasm32-unknown-unknown` every instant reads as the
+//! same moment, so durations are zero and timestamps are the Unix epoch.
+
+use std::time::SystemTime;
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use std::time::Instant;
+
+/// Returns the current system time.
+///
+/// On `wasm32-unknown-unknown` this is always [`std::time::UNIX_EPOCH`].
+#[must_use]
+pub fn system_now() -> SystemTime {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    {
+        SystemTime::now()
+    }
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    {
+        std::time::UNIX_EPOCH
+    }
+}
+
+/// A monotonic instant that never advances.
+///
+/// Stands in for `std::time::Instant` on `wasm32-unknown-unknown`.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+impl Instant {
+    /// Returns the (fixed) current instant.
+    #[must_use]
+    pub fn now() -> Self {
+        Self
+    }
+
+    /// Returns the time elapsed since this instant, which is always zero.
+    #[must_use]
+    pub fn elapsed(&self) -> std::time::Duration {
+        std::time::Duration::ZERO
+    }
+
+    /// Returns the duration from `earlier` to this instant, which is always zero.
+    #[must_use]
+    pub fn duration_since(&self, _earlier: Self) -> std::time::Duration {
+        std::time::Duration::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_is_monotonic() {
+        let start = Instant::now();
+        assert!(Instant::now() >= start);
+        assert!(system_now() >= std::time::UNIX_EPOCH);
+    }
+}
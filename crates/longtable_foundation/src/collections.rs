@@ -4,7 +4,7 @@
 //! providing Longtable-specific semantics and future-proofing the API.
 
 use std::fmt;
-use std::hash::{Hash, Hasher};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::iter::FromIterator;
 
 /// Persistent vector with structural sharing.
@@ -245,13 +245,10 @@ impl<T: Clone + Eq + Hash> Eq for LtSet<T> {}
 
 impl<T: Clone + Eq + Hash> Hash for LtSet<T> {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        // Hash the length and elements in a deterministic order
+        // Equal sets can iterate in different orders, so combine the
+        // element hashes with an order-independent sum.
         self.len().hash(state);
-        // Note: This is order-dependent which is not ideal for sets,
-        // but im::HashSet doesn't guarantee order anyway
-        for item in self.iter() {
-            item.hash(state);
-        }
+        unordered_hash(self.iter()).hash(state);
     }
 }
 
@@ -355,14 +352,21 @@ impl<K: Clone + Eq + Hash, V: Clone + Eq> Eq for LtMap<K, V> {}
 
 impl<K: Clone + Eq + Hash, V: Clone + Hash> Hash for LtMap<K, V> {
     fn hash<H: Hasher>(&self, state: &mut H) {
+        // Equal maps can iterate in different orders (see LtSet)
         self.len().hash(state);
-        for (k, v) in self.iter() {
-            k.hash(state);
-            v.hash(state);
-        }
+        unordered_hash(self.iter()).hash(state);
     }
 }
 
+/// Sums the hashes of `items`, so the result does not depend on their order.
+fn unordered_hash<T: Hash>(items: impl Iterator<Item = T>) -> u64 {
+    items.fold(0, |sum, item| {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        sum.wrapping_add(hasher.finish())
+    })
+}
+
 impl<K: Clone + Eq + Hash, V: Clone> FromIterator<(K, V)> for LtMap<K, V> {
     fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
         Self(im::HashMap::from_iter(iter))
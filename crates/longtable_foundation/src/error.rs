@@ -1,8 +1,12 @@
 //! Error types for the Longtable system.
 //!
 //! Uses `thiserror` for ergonomic error definition with rich context.
+//!
+//! Every [`ErrorKind`] has a stable code (see [`ErrorKind::code`]) so tools
+//! that wrap the CLI can tell errors apart without parsing messages, and
+//! [`Error::to_json`] renders an error as one machine-readable line.
 
-use std::fmt;
+use std::fmt::{self, Write as _};
 
 use thiserror::Error;
 
@@ -17,6 +21,8 @@ pub struct Error {
     pub kind: ErrorKind,
     /// Optional context about where the error occurred.
     pub context: Option<ErrorContext>,
+    /// Suggestions for fixing the error, shown after the message.
+    pub hints: Vec<String>,
 }
 
 impl Error {
@@ -26,6 +32,7 @@ impl Error {
         Self {
             kind,
             context: None,
+            hints: Vec::new(),
         }
     }
 
@@ -36,6 +43,114 @@ impl Error {
         self
     }
 
+    /// Records where this error occurred, unless its context already says.
+    ///
+    /// Errors pass outward through nested calls and forms, so the first
+    /// (innermost) position recorded is kept.
+    #[must_use]
+    pub fn or_position(mut self, line: usize, column: usize) -> Self {
+        let context = self.context.get_or_insert_with(ErrorContext::new);
+        if context.line.is_none() {
+            context.line = Some(line);
+            context.column = Some(column);
+        }
+        self
+    }
+
+    /// Names the file or rule this error came from, unless its context
+    /// already names one.
+    #[must_use]
+    pub fn or_source(mut self, source: impl Into<String>) -> Self {
+        let context = self.context.get_or_insert_with(ErrorContext::new);
+        if context.source.is_none() {
+            context.source = Some(source.into());
+        }
+        self
+    }
+
+    /// Adds a frame to this error's backtrace, outside any frames already
+    /// recorded.
+    #[must_use]
+    pub fn with_frame(mut self, frame: impl Into<String>) -> Self {
+        self.context
+            .get_or_insert_with(ErrorContext::new)
+            .stack
+            .push(frame.into());
+        self
+    }
+
+    /// Adds a suggestion for fixing this error.
+    #[must_use]
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hints.push(hint.into());
+        self
+    }
+
+    /// Returns the stable code for this error's kind.
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    /// Renders this error as a single-line JSON object.
+    ///
+    /// ```text
+    /// {"code":"E0010","message":"parse error at 2:7: unexpected )",
+    ///  "span":{"source":"world.lt","line":2,"column":7},"hints":[],"stack":[]}
+    /// ```
+    ///
+    /// The span comes from the error's context, or from the position of a
+    /// parse error, and is `null` when neither gives a line.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\"code\":");
+        json_string(&mut out, self.code());
+        out.push_str(",\"message\":");
+        json_string(&mut out, &self.to_string());
+
+        let source = self.context.as_ref().and_then(|c| c.source.as_deref());
+        let position = match (&self.context, &self.kind) {
+            (
+                Some(ErrorContext {
+                    line: Some(line),
+                    column,
+                    ..
+                }),
+                _,
+            ) => Some((*line, column.unwrap_or(1))),
+            (_, ErrorKind::ParseError { line, column, .. }) => {
+                Some((*line as usize, *column as usize))
+            }
+            _ => None,
+        };
+        out.push_str(",\"span\":");
+        match position {
+            Some((line, column)) => {
+                out.push_str("{\"source\":");
+                match source {
+                    Some(source) => json_string(&mut out, source),
+                    None => out.push_str("null"),
+                }
+                let _ = write!(out, ",\"line\":{line},\"column\":{column}}}");
+            }
+            None => out.push_str("null"),
+        }
+
+        let stack = self.context.as_ref().map_or(&[][..], |c| &c.stack[..]);
+        for (name, items) in [("hints", &self.hints[..]), ("stack", stack)] {
+            let _ = write!(out, ",\"{name}\":[");
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                json_string(&mut out, item);
+            }
+            out.push(']');
+        }
+        out.push('}');
+        out
+    }
+
     /// Creates a type mismatch error.
     #[must_use]
     pub fn type_mismatch(expected: Type, actual: Type) -> Self {
@@ -165,6 +280,81 @@ pub enum ErrorKind {
     /// Serialization or deserialization error.
     #[error("serialization error: {0}")]
     SerializationError(String),
+
+    /// Arithmetic on a fixed-size value, such as a duration, overflowed.
+    #[error("arithmetic overflow: {0}")]
+    Overflow(String),
+
+    /// A form or function was called with arguments it can't use.
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+
+    /// A named entity, component, message, or other item doesn't exist.
+    #[error("{0}")]
+    NotFound(String),
+
+    /// Declarations depend on each other in a cycle.
+    #[error("{0}")]
+    Cycle(String),
+
+    /// The operation isn't allowed in the current mode or state.
+    #[error("{0}")]
+    Refused(String),
+
+    /// A query raised warnings while they're denied, one message each.
+    #[error("query warnings are denied: {}", .0.join("; "))]
+    DeniedWarnings(Vec<String>),
+}
+
+impl ErrorKind {
+    /// Returns the stable code for this kind of error.
+    ///
+    /// Codes are never reused or renumbered, so tools can match on them
+    /// across releases.
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::TypeMismatch { .. } => "E0001",
+            Self::EntityNotFound(_) => "E0002",
+            Self::StaleEntity(_) => "E0003",
+            Self::UndefinedSymbol(_) => "E0004",
+            Self::ArityMismatch { .. } => "E0005",
+            Self::ComponentNotFound { .. } => "E0006",
+            Self::AttributeNotFound { .. } => "E0007",
+            Self::DivisionByZero => "E0008",
+            Self::IndexOutOfBounds { .. } => "E0009",
+            Self::ParseError { .. } => "E0010",
+            Self::LimitExceeded(_) => "E0011",
+            Self::Internal(_) => "E0012",
+            Self::IoError(_) => "E0013",
+            Self::SerializationError(_) => "E0014",
+            Self::Overflow(_) => "E0015",
+            Self::InvalidArgument(_) => "E0016",
+            Self::NotFound(_) => "E0017",
+            Self::Cycle(_) => "E0018",
+            Self::Refused(_) => "E0019",
+            Self::DeniedWarnings(_) => "E0020",
+        }
+    }
+}
+
+/// Appends `s` to `out` as a JSON string literal.
+fn json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
 }
 
 /// Semantic limits (kill switches) that can be exceeded.
@@ -279,11 +469,11 @@ impl Default for ErrorContext {
 
 impl fmt::Display for ErrorContext {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(source) = &self.source {
-            write!(f, "at {source}")?;
-            if let (Some(line), Some(col)) = (self.line, self.column) {
-                write!(f, ":{line}:{col}")?;
-            }
+        match (&self.source, self.line, self.column) {
+            (Some(source), Some(line), Some(col)) => write!(f, "at {source}:{line}:{col}")?,
+            (Some(source), _, _) => write!(f, "at {source}")?,
+            (None, Some(line), Some(col)) => write!(f, "at line {line}, column {col}")?,
+            _ => {}
         }
         if !self.stack.is_empty() {
             writeln!(f)?;
@@ -323,6 +513,60 @@ mod tests {
         assert_eq!(ctx.column, Some(5));
     }
 
+    #[test]
+    fn error_to_json() {
+        let err = Error::new(ErrorKind::ParseError {
+            message: "unexpected \")\"".to_string(),
+            line: 2,
+            column: 7,
+            context: String::new(),
+        })
+        .with_hint("remove the extra paren");
+        assert_eq!(err.code(), "E0010");
+        assert_eq!(
+            err.to_json(),
+            r#"{"code":"E0010","message":"parse error at 2:7: unexpected \")\"","span":{"source":null,"line":2,"column":7},"hints":["remove the extra paren"],"stack":[]}"#
+        );
+
+        let err = Error::undefined_symbol("foo".to_string()).with_context(
+            ErrorContext::new()
+                .with_source("rules.lt")
+                .with_position(10, 5)
+                .with_frame("rule regen"),
+        );
+        assert_eq!(
+            err.to_json(),
+            r#"{"code":"E0004","message":"undefined symbol: foo","span":{"source":"rules.lt","line":10,"column":5},"hints":[],"stack":["rule regen"]}"#
+        );
+
+        let err = Error::new(ErrorKind::NotFound("no help for frob".to_string()))
+            .with_hint("(help) lists the special forms");
+        assert_eq!(
+            err.to_json(),
+            r#"{"code":"E0017","message":"no help for frob","span":null,"hints":["(help) lists the special forms"],"stack":[]}"#
+        );
+
+        let err = Error::new(ErrorKind::Internal("tab\there".to_string()));
+        assert_eq!(
+            err.to_json(),
+            r#"{"code":"E0012","message":"internal error: tab\there","span":null,"hints":[],"stack":[]}"#
+        );
+    }
+
+    #[test]
+    fn innermost_position_wins() {
+        let err = Error::undefined_symbol("foo".to_string())
+            .or_position(3, 9)
+            .or_position(1, 1);
+        assert_eq!(
+            err.context.as_ref().unwrap().to_string(),
+            "at line 3, column 9"
+        );
+
+        let err = err.or_source("rules.lt").or_source("other.lt");
+        assert_eq!(err.context.unwrap().to_string(), "at rules.lt:3:9");
+    }
+
     #[test]
     fn semantic_limit_display() {
         let limit = SemanticLimit::MaxActivations {
@@ -0,0 +1,289 @@
+//! Stable content hashing.
+//!
+//! [`Value`]'s `Hash` impl is meant for hash tables within one process: it
+//! hashes native functions by address and follows the iteration order of the
+//! underlying maps and sets. Content hashes are compared across processes
+//! and builds (replay verification, desync detection, cache keys), so they
+//! use [`StableHasher`], a fixed FNV-1a, and [`hash_value`], which hashes
+//! values by content alone:
+//!
+//! - maps and sets hash the same whatever order their entries iterate in
+//! - native functions hash by name, compiled functions by index and capture
+//!   count (captures can refer back to the function itself)
+//! - every value is prefixed by a tag byte, so `[1 2]` and `(1 2)` differ
+//!
+//! Keywords and symbols hash by interned ID, which is stable for content
+//! loaded in the same order.
+
+use std::hash::Hasher;
+
+use crate::seq::{LtSeq, SeqSource, SeqStep};
+use crate::value::{LtFn, Value};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A 64-bit FNV-1a hasher whose output never changes between builds.
+///
+/// Integers are written little-endian, so hashes also agree across platforms.
+#[derive(Clone, Copy, Debug)]
+pub struct StableHasher(u64);
+
+impl StableHasher {
+    /// Creates a hasher with the standard FNV offset basis.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u8(&mut self, n: u8) {
+        self.write(&[n]);
+    }
+
+    fn write_u16(&mut self, n: u16) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_i64(&mut self, n: i64) {
+        self.write(&n.to_le_bytes());
+    }
+}
+
+/// Feeds a value's content into `state`.
+pub fn hash_value(value: &Value, state: &mut StableHasher) {
+    match value {
+        Value::Nil => state.write_u8(0),
+        Value::Bool(b) => {
+            state.write_u8(1);
+            state.write_u8(u8::from(*b));
+        }
+        Value::Int(n) => {
+            state.write_u8(2);
+            state.write_i64(*n);
+        }
+        Value::Float(n) => {
+            state.write_u8(3);
+            state.write_u64(n.to_bits());
+        }
+        Value::String(s) => {
+            state.write_u8(4);
+            hash_str(s, state);
+        }
+        Value::Symbol(id) => {
+            state.write_u8(5);
+            state.write_u32(id.index());
+        }
+        Value::Keyword(id) => {
+            state.write_u8(6);
+            state.write_u32(id.index());
+        }
+        Value::EntityRef(id) => {
+            state.write_u8(7);
+            state.write_u64(id.index);
+            state.write_u32(id.generation);
+        }
+        Value::BigInt(n) => {
+            state.write_u8(16);
+            hash_str(&n.to_string(), state);
+        }
+        Value::Instant(ms) => {
+            state.write_u8(14);
+            state.write_i64(*ms);
+        }
+        Value::Duration(ms) => {
+            state.write_u8(15);
+            state.write_i64(*ms);
+        }
+        Value::Vec2(v) => {
+            state.write_u8(18);
+            for n in v {
+                state.write_u64(n.to_bits());
+            }
+        }
+        Value::Vec3(v) => {
+            state.write_u8(19);
+            for n in v {
+                state.write_u64(n.to_bits());
+            }
+        }
+        Value::Vec(items) | Value::List(items) => {
+            state.write_u8(if matches!(value, Value::Vec(_)) { 8 } else { 9 });
+            state.write_usize(items.len());
+            for item in items.iter() {
+                hash_value(item, state);
+            }
+        }
+        Value::Set(items) => {
+            state.write_u8(10);
+            state.write_usize(items.len());
+            state.write_u64(unordered(items.iter().map(|item| {
+                let mut entry = StableHasher::new();
+                hash_value(item, &mut entry);
+                entry.finish()
+            })));
+        }
+        Value::Map(map) => {
+            state.write_u8(11);
+            state.write_usize(map.len());
+            state.write_u64(unordered(map.iter().map(|(k, v)| {
+                let mut entry = StableHasher::new();
+                hash_value(k, &mut entry);
+                hash_value(v, &mut entry);
+                entry.finish()
+            })));
+        }
+        Value::Fn(LtFn::Native(f)) => {
+            state.write_u8(12);
+            hash_str(f.name, state);
+        }
+        Value::Fn(LtFn::Compiled(f)) => {
+            state.write_u8(13);
+            state.write_u32(f.index);
+            let captures = f
+                .captures
+                .as_ref()
+                .map_or(0, |c| c.lock().map_or(0, |c| c.len()));
+            state.write_usize(captures);
+        }
+        Value::Seq(seq) => hash_seq(seq, state),
+    }
+}
+
+/// Feeds a lazy sequence's source and steps into `state`.
+fn hash_seq(seq: &LtSeq, state: &mut StableHasher) {
+    state.write_u8(17);
+    match seq.source() {
+        SeqSource::Range { start, end, step } => {
+            state.write_u8(0);
+            state.write_i64(*start);
+            state.write_u8(u8::from(end.is_some()));
+            state.write_i64(end.unwrap_or_default());
+            state.write_i64(*step);
+        }
+        SeqSource::Iterate { f, seed } => {
+            state.write_u8(1);
+            hash_value(f, state);
+            hash_value(seed, state);
+        }
+        SeqSource::Coll(items) => {
+            state.write_u8(2);
+            state.write_usize(items.len());
+            for item in items.iter() {
+                hash_value(item, state);
+            }
+        }
+    }
+    state.write_usize(seq.steps().len());
+    for step in seq.steps() {
+        state.write_u8(match step {
+            SeqStep::Map(_) => 0,
+            SeqStep::Filter(_) => 1,
+            SeqStep::Remove(_) => 2,
+            SeqStep::TakeWhile(_) => 3,
+            SeqStep::DropWhile(_) => 4,
+            SeqStep::Take(_) => 5,
+            SeqStep::Drop(_) => 6,
+        });
+        match step {
+            SeqStep::Map(f)
+            | SeqStep::Filter(f)
+            | SeqStep::Remove(f)
+            | SeqStep::TakeWhile(f)
+            | SeqStep::DropWhile(f) => hash_value(f, state),
+            SeqStep::Take(n) | SeqStep::Drop(n) => state.write_usize(*n),
+        }
+    }
+}
+
+/// Feeds a length-prefixed string into `state`.
+pub fn hash_str(s: &str, state: &mut StableHasher) {
+    state.write_usize(s.len());
+    state.write(s.as_bytes());
+}
+
+/// Combines entry hashes so the result does not depend on their order.
+fn unordered(hashes: impl Iterator<Item = u64>) -> u64 {
+    hashes.fold(0, u64::wrapping_add)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeywordId, LtMap, LtVec};
+
+    fn stable(value: &Value) -> u64 {
+        let mut state = StableHasher::new();
+        hash_value(value, &mut state);
+        state.finish()
+    }
+
+    #[test]
+    fn fnv_matches_reference_vectors() {
+        let mut state = StableHasher::new();
+        assert_eq!(state.finish(), 0xcbf2_9ce4_8422_2325);
+        state.write(b"a");
+        assert_eq!(state.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn maps_hash_independently_of_insertion_order() {
+        let entries: Vec<(Value, Value)> = (0..64)
+            .map(|i| (Value::Keyword(KeywordId(i + 10)), Value::Int(i64::from(i))))
+            .collect();
+        let forward: LtMap<Value, Value> = entries.iter().cloned().collect();
+        let backward: LtMap<Value, Value> = entries.iter().rev().cloned().collect();
+
+        assert_eq!(
+            stable(&Value::Map(forward.clone())),
+            stable(&Value::Map(backward))
+        );
+        assert_ne!(
+            stable(&Value::Map(forward.clone())),
+            stable(&Value::Map(forward.insert(Value::Int(0), Value::Nil)))
+        );
+    }
+
+    #[test]
+    fn vectors_and_lists_differ() {
+        let items: LtVec<Value> = vec![Value::Int(1), Value::Int(2)].into_iter().collect();
+        assert_ne!(
+            stable(&Value::Vec(items.clone())),
+            stable(&Value::List(items))
+        );
+    }
+}
@@ -3,7 +3,7 @@
 //! Symbols and keywords are interned to enable fast equality comparison
 //! and reduced memory usage for repeated strings.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::Arc;
 
@@ -90,6 +90,16 @@ pub struct Interner {
     keywords: Vec<u32>,
     /// Map from keyword string to `KeywordId`.
     keyword_map: HashMap<Arc<str>, KeywordId>,
+    /// Keywords made at runtime (by `keyword` or JSON decoding) that no
+    /// source has interned since, and so may be swept when unreachable.
+    #[cfg_attr(feature = "serde", serde(default))]
+    generated: HashSet<KeywordId>,
+    /// Keyword IDs freed by a sweep, reused by the next keywords interned.
+    #[cfg_attr(feature = "serde", serde(default))]
+    free_keywords: Vec<u32>,
+    /// String slots freed by a sweep, reused by the next strings interned.
+    #[cfg_attr(feature = "serde", serde(default))]
+    free_strings: Vec<u32>,
 }
 
 impl Interner {
@@ -101,6 +111,9 @@ impl Interner {
         "value",      // KeywordId(3) = VALUE
     ];
 
+    /// String index of a keyword that has been swept.
+    const SWEPT: u32 = u32::MAX;
+
     /// Creates a new interner with reserved keywords pre-interned.
     #[must_use]
     pub fn new() -> Self {
@@ -125,9 +138,14 @@ impl Interner {
             return idx;
         }
 
-        let idx = u32::try_from(self.strings.len()).expect("too many interned strings");
         let arc: Arc<str> = s.into();
-        self.strings.push(arc.clone());
+        let idx = if let Some(idx) = self.free_strings.pop() {
+            self.strings[idx as usize] = arc.clone();
+            idx
+        } else {
+            self.strings.push(arc.clone());
+            u32::try_from(self.strings.len() - 1).expect("too many interned strings")
+        };
         self.string_to_index.insert(arc, idx);
         idx
     }
@@ -175,12 +193,19 @@ impl Interner {
     /// Panics if the number of interned keywords exceeds `u32::MAX`.
     pub fn intern_keyword(&mut self, s: &str) -> KeywordId {
         if let Some(&id) = self.keyword_map.get(s) {
+            // Source that names a generated keyword keeps it alive for good
+            self.generated.remove(&id);
             return id;
         }
 
         let string_idx = self.intern_string(s);
-        let keyword_idx = u32::try_from(self.keywords.len()).expect("too many keywords");
-        self.keywords.push(string_idx);
+        let keyword_idx = if let Some(idx) = self.free_keywords.pop() {
+            self.keywords[idx as usize] = string_idx;
+            idx
+        } else {
+            self.keywords.push(string_idx);
+            u32::try_from(self.keywords.len() - 1).expect("too many keywords")
+        };
 
         let id = KeywordId(keyword_idx);
         let arc: Arc<str> = s.into();
@@ -188,6 +213,62 @@ impl Interner {
         id
     }
 
+    /// Interns a keyword made at runtime, returning its [`KeywordId`].
+    ///
+    /// Unlike [`intern_keyword`](Self::intern_keyword), a keyword new to
+    /// the interner is marked as generated, so
+    /// [`sweep_generated`](Self::sweep_generated) can free it once nothing
+    /// refers to it. Compiled code never holds generated keywords: interning
+    /// one from source unmarks it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of interned keywords exceeds `u32::MAX`.
+    pub fn intern_generated_keyword(&mut self, s: &str) -> KeywordId {
+        if let Some(&id) = self.keyword_map.get(s) {
+            return id;
+        }
+        let id = self.intern_keyword(s);
+        self.generated.insert(id);
+        id
+    }
+
+    /// Returns the number of generated keywords that have not been swept.
+    #[must_use]
+    pub fn generated_keyword_count(&self) -> usize {
+        self.generated.len()
+    }
+
+    /// Frees the generated keywords not in `live`, returning how many were
+    /// freed.
+    ///
+    /// IDs are never renumbered, since compiled code and stored values hold
+    /// them: a freed keyword's ID stops resolving until a later keyword
+    /// reuses it, so `live` must hold every ID still referred to. Its string
+    /// is dropped unless a symbol shares it, and its slot is reused likewise.
+    /// The tables never shrink, and symbols are never freed.
+    pub fn sweep_generated(&mut self, live: &HashSet<KeywordId>) -> usize {
+        let dead: Vec<KeywordId> = self
+            .generated
+            .iter()
+            .filter(|id| !live.contains(id))
+            .copied()
+            .collect();
+        for id in &dead {
+            self.generated.remove(id);
+            let idx = std::mem::replace(&mut self.keywords[id.0 as usize], Self::SWEPT);
+            self.free_keywords.push(id.0);
+            let name = Arc::clone(&self.strings[idx as usize]);
+            self.keyword_map.remove(&name);
+            if !self.symbol_map.contains_key(&name) {
+                self.string_to_index.remove(&name);
+                self.strings[idx as usize] = Arc::from("");
+                self.free_strings.push(idx);
+            }
+        }
+        dead.len()
+    }
+
     /// Gets the string for a keyword (without the leading `:`).
     #[must_use]
     pub fn get_keyword(&self, id: KeywordId) -> Option<&str> {
@@ -225,6 +306,19 @@ impl Interner {
     pub fn keyword_count(&self) -> usize {
         self.keywords.len()
     }
+
+    /// Returns the number of interned strings, symbols and keywords
+    /// together.
+    #[must_use]
+    pub fn string_count(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns the total length in bytes of the interned strings.
+    #[must_use]
+    pub fn string_bytes(&self) -> usize {
+        self.strings.iter().map(|s| s.len()).sum()
+    }
 }
 
 #[cfg(test)]
@@ -300,6 +394,53 @@ mod tests {
         assert_eq!(value, KeywordId::VALUE);
     }
 
+    #[test]
+    fn sweep_frees_unreachable_generated_keywords() {
+        let mut interner = Interner::new();
+        let kept = interner.intern_generated_keyword("room-1");
+        let dropped = interner.intern_generated_keyword("room-2");
+        let promoted = interner.intern_generated_keyword("room-3");
+        let shared = interner.intern_generated_keyword("door");
+        interner.intern_symbol("door");
+        // Naming it in source means compiled code may hold it
+        assert_eq!(interner.intern_keyword("room-3"), promoted);
+        // Keywords interned from source are never generated
+        let health = interner.intern_keyword("health");
+        assert_eq!(interner.intern_generated_keyword("health"), health);
+        assert_eq!(interner.generated_keyword_count(), 3);
+
+        let bytes = interner.string_bytes();
+        let live = HashSet::from([kept]);
+        assert_eq!(interner.sweep_generated(&live), 2);
+        assert_eq!(interner.generated_keyword_count(), 1);
+        assert_eq!(interner.get_keyword(kept), Some("room-1"));
+        assert_eq!(interner.get_keyword(promoted), Some("room-3"));
+        assert_eq!(interner.get_keyword(dropped), None);
+        assert_eq!(interner.get_keyword(shared), None);
+        assert_eq!(interner.lookup_keyword("room-2"), None);
+        // Only the string no symbol shares is released
+        assert_eq!(interner.string_bytes(), bytes - "room-2".len());
+        assert_eq!(
+            interner
+                .lookup_symbol("door")
+                .map(|s| interner.get_symbol(s)),
+            Some(Some("door"))
+        );
+
+        // New keywords take the freed slots before the tables grow
+        let keywords = interner.keyword_count();
+        let strings = interner.string_count();
+        let again = interner.intern_generated_keyword("room-2");
+        let fresh = interner.intern_generated_keyword("room-4");
+        assert!([dropped, shared].contains(&again));
+        assert!([dropped, shared].contains(&fresh));
+        assert_eq!(interner.get_keyword(again), Some("room-2"));
+        assert_eq!(interner.get_keyword(fresh), Some("room-4"));
+        assert_eq!(interner.keyword_count(), keywords);
+        // Only room-2's string slot was freed
+        assert_eq!(interner.string_count(), strings + 1);
+    }
+
     #[test]
     fn get_symbol_string() {
         let mut interner = Interner::new();
@@ -3,27 +3,38 @@
 //! This crate provides:
 //! - [`Value`] - The core value type for all Longtable data
 //! - [`EntityId`] - Generational entity identifiers
+//! - [`BigInt`] - Arbitrary-precision integers for overflowing arithmetic
 //! - [`Type`] - Type descriptors for schema validation
 //! - [`Error`] - Rich error types with context
 //! - Persistent collections ([`LtVec`], [`LtSet`], [`LtMap`])
+//! - Lazy sequences ([`LtSeq`])
 //! - String interning ([`SymbolId`], [`KeywordId`], [`Interner`])
+//! - Stable content hashing ([`StableHasher`])
+//! - Portable time sources ([`clock`])
 
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 #![warn(clippy::pedantic)]
 
+pub mod bigint;
+pub mod clock;
 pub mod collections;
 pub mod entity;
 pub mod error;
+pub mod hash;
 pub mod intern;
+pub mod seq;
 pub mod types;
 pub mod value;
 
 // Re-export primary types at crate root for convenience
+pub use bigint::BigInt;
 pub use collections::{LtMap, LtSet, LtVec};
 pub use entity::EntityId;
 pub use error::{Error, ErrorContext, ErrorKind, SemanticLimit};
+pub use hash::StableHasher;
 pub use intern::{Interner, KeywordId, SymbolId};
+pub use seq::{LtSeq, SeqIter, SeqSource, SeqStep};
 pub use types::{Arity, Type};
 pub use value::{CompiledFn, LtFn, NativeFn, Value};
 
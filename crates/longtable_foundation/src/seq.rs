@@ -0,0 +1,380 @@
+//! Lazy sequences.
+//!
+//! An [`LtSeq`] is a source of values and the steps to apply to each, such as
+//! `(filter pred (iterate inc 0))`. Nothing is computed until the sequence
+//! is walked with a [`SeqIter`], and then only as far as the walk goes, so a
+//! rule can ask for the first few values of a sequence with no end, or of a
+//! large collection, without building the rest.
+//!
+//! Steps that call functions (`map`, `filter`, ...) hold the function as a
+//! [`Value`]; this crate can't call it, so [`SeqIter::next`] is handed a
+//! callback that does.
+
+use std::sync::Arc;
+
+use crate::collections::LtVec;
+use crate::value::Value;
+use crate::{Error, ErrorKind, Result};
+
+/// A lazy sequence.
+///
+/// Cloning is O(1). Adding a step returns a new sequence and leaves this
+/// one unchanged.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LtSeq {
+    source: Arc<SeqSource>,
+    steps: Arc<[SeqStep]>,
+}
+
+/// Where a lazy sequence's values come from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SeqSource {
+    /// `start`, `start + step`, ... stopping before `end`, if there is one.
+    Range {
+        /// First value.
+        start: i64,
+        /// Value to stop before; `None` never stops.
+        end: Option<i64>,
+        /// Difference between consecutive values (never zero).
+        step: i64,
+    },
+    /// `seed`, `(f seed)`, `(f (f seed))`, ... without end.
+    Iterate {
+        /// Function producing each value from the one before.
+        f: Value,
+        /// First value.
+        seed: Value,
+    },
+    /// The elements of a collection.
+    Coll(LtVec<Value>),
+}
+
+/// A step applied to each value of a lazy sequence, in order.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SeqStep {
+    /// Replaces each value with `(f value)`.
+    Map(Value),
+    /// Keeps values for which `(pred value)` is truthy.
+    Filter(Value),
+    /// Drops values for which `(pred value)` is truthy.
+    Remove(Value),
+    /// Ends the sequence at the first value for which `(pred value)` is falsy.
+    TakeWhile(Value),
+    /// Drops values until `(pred value)` is first falsy.
+    DropWhile(Value),
+    /// Ends the sequence after this many values.
+    Take(usize),
+    /// Drops this many values.
+    Drop(usize),
+}
+
+impl LtSeq {
+    /// Creates a sequence of `source`'s values, with no steps.
+    #[must_use]
+    pub fn new(source: SeqSource) -> Self {
+        Self {
+            source: Arc::new(source),
+            steps: Arc::from([]),
+        }
+    }
+
+    /// Returns where the sequence's values come from.
+    #[must_use]
+    pub fn source(&self) -> &SeqSource {
+        &self.source
+    }
+
+    /// Returns the steps applied to each value, in order.
+    #[must_use]
+    pub fn steps(&self) -> &[SeqStep] {
+        &self.steps
+    }
+
+    /// Returns this sequence with `step` applied after its other steps.
+    #[must_use]
+    pub fn with_step(&self, step: SeqStep) -> Self {
+        let steps: Vec<SeqStep> = self.steps.iter().cloned().chain([step]).collect();
+        Self {
+            source: self.source.clone(),
+            steps: steps.into(),
+        }
+    }
+
+    /// Returns true if walking the whole sequence is known to end: its
+    /// source ends, or a step cuts it off.
+    ///
+    /// `take-while` counts as cutting it off, although its predicate may
+    /// never fail.
+    #[must_use]
+    pub fn is_bounded(&self) -> bool {
+        let source_ends = match &*self.source {
+            SeqSource::Range { end, .. } => end.is_some(),
+            SeqSource::Iterate { .. } => false,
+            SeqSource::Coll(_) => true,
+        };
+        source_ends
+            || self
+                .steps
+                .iter()
+                .any(|step| matches!(step, SeqStep::Take(_) | SeqStep::TakeWhile(_)))
+    }
+
+    /// Returns a walk over the sequence from its start.
+    #[must_use]
+    pub fn walk(&self) -> SeqIter {
+        let source = match &*self.source {
+            SeqSource::Range { start, end, step } => SourceState::Range {
+                next: Some(*start),
+                end: *end,
+                step: *step,
+            },
+            SeqSource::Iterate { f, seed } => SourceState::Iterate {
+                f: f.clone(),
+                next: seed.clone(),
+                started: false,
+            },
+            SeqSource::Coll(items) => SourceState::Coll {
+                items: items.clone(),
+                pos: 0,
+            },
+        };
+        let counts = self
+            .steps
+            .iter()
+            .map(|step| match step {
+                SeqStep::Take(n) | SeqStep::Drop(n) => *n,
+                SeqStep::DropWhile(_) => 1,
+                _ => 0,
+            })
+            .collect();
+        SeqIter {
+            source,
+            steps: Arc::clone(&self.steps),
+            counts,
+            done: false,
+        }
+    }
+
+    /// Walks the sequence, returning up to `limit` values, or all of them.
+    ///
+    /// `call` calls a step's function with one argument.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `limit` is `None` and the sequence isn't
+    /// [bounded](Self::is_bounded), or if `call` fails.
+    pub fn realize(
+        &self,
+        limit: Option<usize>,
+        call: &mut dyn FnMut(&Value, Value) -> Result<Value>,
+    ) -> Result<LtVec<Value>> {
+        if limit.is_none() && !self.is_bounded() {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "can't realize an infinite sequence".to_string(),
+            ))
+            .with_hint("take from it first: (take 10 seq)"));
+        }
+        let mut values = LtVec::new();
+        let mut iter = self.walk();
+        while limit.is_none_or(|limit| values.len() < limit) {
+            match iter.next(call)? {
+                Some(value) => values = values.push_back(value),
+                None => break,
+            }
+        }
+        Ok(values)
+    }
+}
+
+/// A walk over an [`LtSeq`], from [`LtSeq::walk`].
+#[derive(Debug)]
+pub struct SeqIter {
+    source: SourceState,
+    steps: Arc<[SeqStep]>,
+    /// Values left to take or drop, for each `Take` and `Drop` step, and 1
+    /// for each `DropWhile` step still dropping.
+    counts: Vec<usize>,
+    done: bool,
+}
+
+#[derive(Debug)]
+enum SourceState {
+    Range {
+        next: Option<i64>,
+        end: Option<i64>,
+        step: i64,
+    },
+    Iterate {
+        f: Value,
+        next: Value,
+        started: bool,
+    },
+    Coll {
+        items: LtVec<Value>,
+        pos: usize,
+    },
+}
+
+impl SeqIter {
+    /// Returns the next value of the sequence, or `None` at its end.
+    ///
+    /// `call` calls a step's function with one argument.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `call` fails.
+    pub fn next(
+        &mut self,
+        call: &mut dyn FnMut(&Value, Value) -> Result<Value>,
+    ) -> Result<Option<Value>> {
+        'values: loop {
+            if self.done || self.counts_exhausted() {
+                self.done = true;
+                return Ok(None);
+            }
+            let Some(mut value) = self.next_source(call)? else {
+                self.done = true;
+                return Ok(None);
+            };
+            for (i, step) in self.steps.iter().enumerate() {
+                match step {
+                    SeqStep::Map(f) => value = call(f, value)?,
+                    SeqStep::Filter(pred) => {
+                        if !call(pred, value.clone())?.is_truthy() {
+                            continue 'values;
+                        }
+                    }
+                    SeqStep::Remove(pred) => {
+                        if call(pred, value.clone())?.is_truthy() {
+                            continue 'values;
+                        }
+                    }
+                    SeqStep::TakeWhile(pred) => {
+                        if !call(pred, value.clone())?.is_truthy() {
+                            self.done = true;
+                            return Ok(None);
+                        }
+                    }
+                    SeqStep::DropWhile(pred) => {
+                        if self.counts[i] == 1 && call(pred, value.clone())?.is_truthy() {
+                            continue 'values;
+                        }
+                        self.counts[i] = 0;
+                    }
+                    SeqStep::Take(_) => {
+                        if self.counts[i] == 0 {
+                            self.done = true;
+                            return Ok(None);
+                        }
+                        self.counts[i] -= 1;
+                    }
+                    SeqStep::Drop(_) => {
+                        if self.counts[i] > 0 {
+                            self.counts[i] -= 1;
+                            continue 'values;
+                        }
+                    }
+                }
+            }
+            return Ok(Some(value));
+        }
+    }
+
+    /// Returns true if a `Take` step has nothing left to take, so the
+    /// source needn't be asked for another value.
+    fn counts_exhausted(&self) -> bool {
+        self.steps
+            .iter()
+            .zip(&self.counts)
+            .any(|(step, count)| matches!(step, SeqStep::Take(_)) && *count == 0)
+    }
+
+    fn next_source(
+        &mut self,
+        call: &mut dyn FnMut(&Value, Value) -> Result<Value>,
+    ) -> Result<Option<Value>> {
+        match &mut self.source {
+            SourceState::Range { next, end, step } => {
+                let Some(value) = *next else {
+                    return Ok(None);
+                };
+                let in_range = match *end {
+                    Some(end) if *step > 0 => value < end,
+                    Some(end) => value > end,
+                    None => true,
+                };
+                if !in_range {
+                    return Ok(None);
+                }
+                *next = value.checked_add(*step);
+                Ok(Some(Value::Int(value)))
+            }
+            SourceState::Iterate { f, next, started } => {
+                if *started {
+                    *next = call(f, next.clone())?;
+                }
+                *started = true;
+                Ok(Some(next.clone()))
+            }
+            SourceState::Coll { items, pos } => {
+                let value = items.get(*pos).cloned();
+                *pos += 1;
+                Ok(value)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stands in for calling functions: each function value is an `Int` to
+    /// add, and predicates are true below 10.
+    #[allow(clippy::needless_pass_by_value)]
+    fn call(f: &Value, value: Value) -> Result<Value> {
+        match (f, &value) {
+            (Value::Int(n), Value::Int(v)) => Ok(Value::Int(v + n)),
+            (Value::Nil, Value::Int(v)) => Ok(Value::Bool(*v < 10)),
+            _ => unreachable!(),
+        }
+    }
+
+    fn ints(values: &LtVec<Value>) -> Vec<i64> {
+        values
+            .iter()
+            .map(|v| match v {
+                Value::Int(n) => *n,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn walks_only_as_far_as_needed() {
+        let naturals = LtSeq::new(SeqSource::Range {
+            start: 0,
+            end: None,
+            step: 1,
+        });
+        assert!(!naturals.is_bounded());
+        assert!(naturals.realize(None, &mut call).is_err());
+
+        let seq = naturals
+            .with_step(SeqStep::Map(Value::Int(1)))
+            .with_step(SeqStep::Drop(2))
+            .with_step(SeqStep::Take(3));
+        assert_eq!(ints(&seq.realize(None, &mut call).unwrap()), [3, 4, 5]);
+        assert_eq!(ints(&seq.realize(Some(1), &mut call).unwrap()), [3]);
+
+        let threes = LtSeq::new(SeqSource::Iterate {
+            f: Value::Int(3),
+            seed: Value::Int(0),
+        })
+        .with_step(SeqStep::TakeWhile(Value::Nil));
+        assert_eq!(
+            ints(&threes.realize(None, &mut call).unwrap()),
+            [0, 3, 6, 9]
+        );
+    }
+}
@@ -27,6 +27,14 @@ pub enum Type {
     Keyword,
     /// Entity reference type.
     EntityRef,
+    /// Point in time.
+    Instant,
+    /// Span of time.
+    Duration,
+    /// Two-dimensional numeric vector.
+    Vec2,
+    /// Three-dimensional numeric vector.
+    Vec3,
     /// Homogeneous vector type.
     Vec(Box<Type>),
     /// Homogeneous set type.
@@ -39,6 +47,8 @@ pub enum Type {
     Any,
     /// Function type (arity only, no parameter types).
     Fn(Arity),
+    /// Lazy sequence.
+    Seq,
 }
 
 /// Function arity specification.
@@ -78,6 +88,40 @@ impl Type {
         Self::Option(Box::new(inner))
     }
 
+    /// Parses a type as declarations write it, without the leading colon:
+    /// `int`, `entity-ref`, `option<string>`, and so on. Collections parse
+    /// with `any` elements.
+    ///
+    /// Returns `None` for an unknown name.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(inner) = name
+            .strip_prefix("option<")
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            return Self::from_name(inner).map(Self::option);
+        }
+        Some(match name {
+            "nil" => Self::Nil,
+            "bool" => Self::Bool,
+            "int" => Self::Int,
+            "float" => Self::Float,
+            "string" => Self::String,
+            "symbol" => Self::Symbol,
+            "keyword" => Self::Keyword,
+            "entity-ref" | "entity" => Self::EntityRef,
+            "instant" => Self::Instant,
+            "duration" => Self::Duration,
+            "vec2" => Self::Vec2,
+            "vec3" => Self::Vec3,
+            "vec" | "vector" => Self::vec(Self::Any),
+            "set" => Self::set(Self::Any),
+            "map" => Self::map(Self::Any, Self::Any),
+            "any" => Self::Any,
+            _ => return None,
+        })
+    }
+
     /// Returns true if this type is `Any`.
     #[must_use]
     pub const fn is_any(&self) -> bool {
@@ -119,7 +163,12 @@ impl Type {
             | (Self::String, Self::String)
             | (Self::Symbol, Self::Symbol)
             | (Self::Keyword, Self::Keyword)
-            | (Self::EntityRef, Self::EntityRef) => true,
+            | (Self::EntityRef, Self::EntityRef)
+            | (Self::Instant, Self::Instant)
+            | (Self::Duration, Self::Duration)
+            | (Self::Vec2, Self::Vec2)
+            | (Self::Vec3, Self::Vec3)
+            | (Self::Seq, Self::Seq) => true,
 
             // Collection types - Vec(Any), Set(Any), Map(Any,Any) indicate runtime values
             // where element types are not known statically. Accept these when expecting
@@ -197,12 +246,17 @@ impl fmt::Debug for Type {
             Self::Symbol => write!(f, "symbol"),
             Self::Keyword => write!(f, "keyword"),
             Self::EntityRef => write!(f, "entity-ref"),
+            Self::Instant => write!(f, "instant"),
+            Self::Duration => write!(f, "duration"),
+            Self::Vec2 => write!(f, "vec2"),
+            Self::Vec3 => write!(f, "vec3"),
             Self::Vec(t) => write!(f, "vec<{t:?}>"),
             Self::Set(t) => write!(f, "set<{t:?}>"),
             Self::Map(k, v) => write!(f, "map<{k:?}, {v:?}>"),
             Self::Option(t) => write!(f, "option<{t:?}>"),
             Self::Any => write!(f, "any"),
             Self::Fn(arity) => write!(f, "fn{arity:?}"),
+            Self::Seq => write!(f, "seq"),
         }
     }
 }
@@ -226,6 +280,19 @@ mod tests {
         assert_ne!(Type::vec(Type::Int), Type::vec(Type::Float));
     }
 
+    #[test]
+    fn type_from_name() {
+        assert_eq!(Type::from_name("entity-ref"), Some(Type::EntityRef));
+        assert_eq!(
+            Type::from_name("option<int>"),
+            Some(Type::option(Type::Int))
+        );
+        assert_eq!(Type::from_name("vec"), Some(Type::vec(Type::Any)));
+        assert_eq!(Type::from_name("vec3"), Some(Type::Vec3));
+        assert!(!Type::Vec3.accepts(&Type::Vec2));
+        assert_eq!(Type::from_name("integer"), None);
+    }
+
     #[test]
     fn type_display() {
         assert_eq!(format!("{}", Type::Int), "int");
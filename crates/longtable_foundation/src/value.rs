@@ -1,13 +1,16 @@
 //! Core value type for all Longtable data.
 
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::sync::{Arc, Mutex};
 
+use crate::bigint::BigInt;
 use crate::collections::{LtMap, LtSet, LtVec};
 use crate::entity::EntityId;
 use crate::intern::{KeywordId, SymbolId};
+use crate::seq::LtSeq;
 use crate::types::Type;
 
 /// Core value type for all Longtable data.
@@ -22,6 +25,9 @@ pub enum Value {
     Bool(bool),
     /// 64-bit signed integer.
     Int(i64),
+    /// Integer too large for an `Int`. Built with [`Value::integer`], so the
+    /// two never hold the same number.
+    BigInt(Arc<BigInt>),
     /// 64-bit floating point.
     Float(f64),
     /// String value.
@@ -32,6 +38,14 @@ pub enum Value {
     Keyword(KeywordId),
     /// Entity reference.
     EntityRef(EntityId),
+    /// A point in time, in milliseconds since the Unix epoch.
+    Instant(i64),
+    /// A span of time, in milliseconds.
+    Duration(i64),
+    /// Two-dimensional numeric vector, `(vec2 x y)`.
+    Vec2([f64; 2]),
+    /// Three-dimensional numeric vector, `(vec3 x y z)`.
+    Vec3([f64; 3]),
     /// Persistent vector (data).
     Vec(LtVec<Value>),
     /// List (function calls in serialized AST).
@@ -42,6 +56,8 @@ pub enum Value {
     Map(LtMap<Value, Value>),
     /// Function reference.
     Fn(LtFn),
+    /// Lazy sequence, computed as it is walked.
+    Seq(LtSeq),
 }
 
 /// Function reference.
@@ -166,16 +182,60 @@ impl Value {
         match self {
             Self::Nil => Type::Nil,
             Self::Bool(_) => Type::Bool,
-            Self::Int(_) => Type::Int,
+            Self::Int(_) | Self::BigInt(_) => Type::Int,
             Self::Float(_) => Type::Float,
             Self::String(_) => Type::String,
             Self::Symbol(_) => Type::Symbol,
             Self::Keyword(_) => Type::Keyword,
             Self::EntityRef(_) => Type::EntityRef,
+            Self::Instant(_) => Type::Instant,
+            Self::Duration(_) => Type::Duration,
+            Self::Vec2(_) => Type::Vec2,
+            Self::Vec3(_) => Type::Vec3,
             Self::Vec(_) | Self::List(_) => Type::vec(Type::Any),
             Self::Set(_) => Type::set(Type::Any),
             Self::Map(_) => Type::map(Type::Any, Type::Any),
             Self::Fn(_) => Type::Fn(crate::types::Arity::Variadic(0)),
+            Self::Seq(_) => Type::Seq,
+        }
+    }
+
+    /// Adds every keyword this value mentions, including map keys,
+    /// keywords nested in collections, and closure captures, to `live`.
+    pub fn mark_keywords(&self, live: &mut HashSet<KeywordId>) {
+        match self {
+            Self::Keyword(k) => {
+                live.insert(*k);
+            }
+            Self::Vec(items) | Self::List(items) => {
+                for item in items.iter() {
+                    item.mark_keywords(live);
+                }
+            }
+            Self::Set(items) => {
+                for item in items.iter() {
+                    item.mark_keywords(live);
+                }
+            }
+            Self::Map(entries) => {
+                for (key, value) in entries.iter() {
+                    key.mark_keywords(live);
+                    value.mark_keywords(live);
+                }
+            }
+            Self::Fn(LtFn::Compiled(CompiledFn {
+                captures: Some(captures),
+                ..
+            })) => {
+                // A recursive closure captures itself; the lock held while
+                // walking its captures stops the walk from going round again
+                if let Ok(captures) = captures.try_lock() {
+                    for capture in captures.iter() {
+                        capture.mark_keywords(live);
+                    }
+                }
+            }
+            _ => {}
         }
     }
 
@@ -211,6 +271,23 @@ impl Value {
         }
     }
 
+    /// Returns `n` as an `Int` if it fits, or as a `BigInt` otherwise.
+    #[must_use]
+    pub fn integer(n: BigInt) -> Self {
+        n.to_i64()
+            .map_or_else(|| Self::BigInt(Arc::new(n)), Self::Int)
+    }
+
+    /// Attempts to extract an integer of any size.
+    #[must_use]
+    pub fn as_bigint(&self) -> Option<BigInt> {
+        match self {
+            Self::Int(n) => Some(BigInt::from(*n)),
+            Self::BigInt(n) => Some((**n).clone()),
+            _ => None,
+        }
+    }
+
     /// Attempts to extract a float value.
     #[must_use]
     pub const fn as_float(&self) -> Option<f64> {
@@ -228,6 +305,7 @@ impl Value {
     pub fn as_number(&self) -> Option<f64> {
         match self {
             Self::Int(n) => Some(*n as f64),
+            Self::BigInt(n) => Some(n.to_f64()),
             Self::Float(n) => Some(*n),
             _ => None,
         }
@@ -269,6 +347,24 @@ impl Value {
         }
     }
 
+    /// Attempts to extract an instant, in milliseconds since the Unix epoch.
+    #[must_use]
+    pub const fn as_instant(&self) -> Option<i64> {
+        match self {
+            Self::Instant(ms) => Some(*ms),
+            _ => None,
+        }
+    }
+
+    /// Attempts to extract a duration, in milliseconds.
+    #[must_use]
+    pub const fn as_duration(&self) -> Option<i64> {
+        match self {
+            Self::Duration(ms) => Some(*ms),
+            _ => None,
+        }
+    }
+
     /// Attempts to extract a vector reference.
     #[must_use]
     pub const fn as_vec(&self) -> Option<&LtVec<Value>> {
@@ -287,6 +383,26 @@ impl Value {
         }
     }
 
+    /// Returns the components of a `Vec2` or `Vec3`.
+    #[must_use]
+    pub fn as_components(&self) -> Option<&[f64]> {
+        match self {
+            Self::Vec2(v) => Some(v),
+            Self::Vec3(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Builds a `Vec2` from two components or a `Vec3` from three.
+    #[must_use]
+    pub fn from_components(components: &[f64]) -> Option<Value> {
+        match *components {
+            [x, y] => Some(Self::Vec2([x, y])),
+            [x, y, z] => Some(Self::Vec3([x, y, z])),
+            _ => None,
+        }
+    }
+
     /// Attempts to extract a map reference.
     #[must_use]
     pub const fn as_map(&self) -> Option<&LtMap<Value, Value>> {
@@ -303,8 +419,13 @@ impl PartialEq for Value {
         match (self, other) {
             (Self::Nil, Self::Nil) => true,
             (Self::Bool(a), Self::Bool(b)) => a == b,
-            (Self::Int(a), Self::Int(b)) => a == b,
+            (Self::Int(a), Self::Int(b))
+            | (Self::Instant(a), Self::Instant(b))
+            | (Self::Duration(a), Self::Duration(b)) => a == b,
+            (Self::BigInt(a), Self::BigInt(b)) => a == b,
             (Self::Float(a), Self::Float(b)) => a.to_bits() == b.to_bits(),
+            (Self::Vec2(a), Self::Vec2(b)) => a.map(f64::to_bits) == b.map(f64::to_bits),
+            (Self::Vec3(a), Self::Vec3(b)) => a.map(f64::to_bits) == b.map(f64::to_bits),
             (Self::String(a), Self::String(b)) => a == b,
             (Self::Symbol(a), Self::Symbol(b)) => a == b,
             (Self::Keyword(a), Self::Keyword(b)) => a == b,
@@ -313,6 +434,7 @@ impl PartialEq for Value {
             (Self::Set(a), Self::Set(b)) => a == b,
             (Self::Map(a), Self::Map(b)) => a == b,
             (Self::Fn(a), Self::Fn(b)) => a == b,
+            (Self::Seq(a), Self::Seq(b)) => a == b,
             _ => false,
         }
     }
@@ -327,16 +449,21 @@ impl Hash for Value {
             Self::Nil => {}
             Self::Bool(b) => b.hash(state),
             Self::Int(n) => n.hash(state),
+            Self::BigInt(n) => n.hash(state),
             Self::Float(n) => n.to_bits().hash(state),
             Self::String(s) => s.hash(state),
             Self::Symbol(id) => id.hash(state),
             Self::Keyword(id) => id.hash(state),
             Self::EntityRef(id) => id.hash(state),
+            Self::Instant(ms) | Self::Duration(ms) => ms.hash(state),
+            Self::Vec2(v) => v.map(f64::to_bits).hash(state),
+            Self::Vec3(v) => v.map(f64::to_bits).hash(state),
             Self::Vec(v) => v.hash(state),
             Self::List(l) => l.hash(state),
             Self::Set(s) => s.hash(state),
             Self::Map(m) => m.hash(state),
             Self::Fn(f) => f.hash(state),
+            Self::Seq(s) => s.hash(state),
         }
     }
 }
@@ -352,11 +479,19 @@ impl PartialOrd for Value {
             // Cross-type numeric comparison intentionally loses precision for large i64
             (Self::Int(a), Self::Float(b)) => (*a as f64).partial_cmp(b),
             (Self::Float(a), Self::Int(b)) => a.partial_cmp(&(*b as f64)),
+            (Self::BigInt(a), Self::BigInt(b)) => a.partial_cmp(b),
+            (Self::Int(a), Self::BigInt(b)) => BigInt::from(*a).partial_cmp(b),
+            (Self::BigInt(a), Self::Int(b)) => (**a).partial_cmp(&BigInt::from(*b)),
+            (Self::BigInt(a), Self::Float(b)) => a.to_f64().partial_cmp(b),
+            (Self::Float(a), Self::BigInt(b)) => a.partial_cmp(&b.to_f64()),
             (Self::String(a), Self::String(b)) => a.partial_cmp(b),
             (Self::EntityRef(a), Self::EntityRef(b)) => match a.index.cmp(&b.index) {
                 Ordering::Equal => Some(a.generation.cmp(&b.generation)),
                 ord => Some(ord),
             },
+            (Self::Instant(a), Self::Instant(b)) | (Self::Duration(a), Self::Duration(b)) => {
+                a.partial_cmp(b)
+            }
             _ => None, // Different types or non-comparable
         }
     }
@@ -368,16 +503,22 @@ impl fmt::Debug for Value {
             Self::Nil => write!(f, "nil"),
             Self::Bool(b) => write!(f, "{b}"),
             Self::Int(n) => write!(f, "{n}"),
+            Self::BigInt(n) => write!(f, "{n:?}"),
             Self::Float(n) => write!(f, "{n}"),
             Self::String(s) => write!(f, "{s:?}"),
             Self::Symbol(id) => write!(f, "Symbol({id:?})"),
             Self::Keyword(id) => write!(f, "Keyword({id:?})"),
             Self::EntityRef(id) => write!(f, "{id:?}"),
+            Self::Instant(ms) => write!(f, "Instant({ms})"),
+            Self::Duration(ms) => write!(f, "Duration({ms})"),
+            Self::Vec2([x, y]) => write!(f, "Vec2({x}, {y})"),
+            Self::Vec3([x, y, z]) => write!(f, "Vec3({x}, {y}, {z})"),
             Self::Vec(v) => write!(f, "{v:?}"),
             Self::List(l) => write!(f, "({l:?})"),
             Self::Set(s) => write!(f, "#{s:?}"),
             Self::Map(m) => write!(f, "{m:?}"),
             Self::Fn(func) => write!(f, "{func:?}"),
+            Self::Seq(seq) => write!(f, "{seq:?}"),
         }
     }
 }
@@ -388,11 +529,16 @@ impl fmt::Display for Value {
             Self::Nil => write!(f, "nil"),
             Self::Bool(b) => write!(f, "{b}"),
             Self::Int(n) => write!(f, "{n}"),
+            Self::BigInt(n) => write!(f, "{n}"),
             Self::Float(n) => write!(f, "{n}"),
             Self::String(s) => write!(f, "{s}"),
             Self::Symbol(id) => write!(f, "Symbol({id:?})"),
             Self::Keyword(id) => write!(f, ":{id:?}"),
             Self::EntityRef(id) => write!(f, "{id}"),
+            Self::Instant(ms) => write!(f, "(instant {ms})"),
+            Self::Duration(ms) => write!(f, "(duration {ms})"),
+            Self::Vec2([x, y]) => write!(f, "(vec2 {x:?} {y:?})"),
+            Self::Vec3([x, y, z]) => write!(f, "(vec3 {x:?} {y:?} {z:?})"),
             Self::Vec(v) => {
                 write!(f, "[")?;
                 for (i, item) in v.iter().enumerate() {
@@ -434,6 +580,7 @@ impl fmt::Display for Value {
                 write!(f, "}}")
             }
             Self::Fn(func) => write!(f, "{func}"),
+            Self::Seq(_) => write!(f, "<lazy seq>"),
         }
     }
 }
@@ -798,6 +945,31 @@ mod serde_support {
                     map.serialize_entry("__entity__", &(id.index, id.generation))?;
                     map.end()
                 }
+                Value::BigInt(n) => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry("__bigint__", &n.to_string())?;
+                    map.end()
+                }
+                Value::Instant(ms) => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry("__instant__", ms)?;
+                    map.end()
+                }
+                Value::Duration(ms) => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry("__duration__", ms)?;
+                    map.end()
+                }
+                Value::Vec2(v) => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry("__vec2__", v)?;
+                    map.end()
+                }
+                Value::Vec3(v) => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry("__vec3__", v)?;
+                    map.end()
+                }
                 Value::Vec(v) => {
                     let mut seq = serializer.serialize_seq(Some(v.len()))?;
                     for item in v.iter() {
@@ -825,7 +997,7 @@ mod serde_support {
                     map.serialize_entry("__map__", &pairs)?;
                     map.end()
                 }
-                Value::Fn(_) => {
+                Value::Fn(_) | Value::Seq(_) => {
                     // Functions cannot be serialized - serialize as a marker
                     let mut map = serializer.serialize_map(Some(1))?;
                     map.serialize_entry("__fn__", &true)?;
@@ -916,6 +1088,17 @@ mod serde_support {
                         let (index, generation): (u64, u32) = map.next_value()?;
                         Ok(Value::EntityRef(EntityId::new(index, generation)))
                     }
+                    "__bigint__" => {
+                        let digits: String = map.next_value()?;
+                        digits
+                            .parse()
+                            .map(Value::integer)
+                            .map_err(de::Error::custom)
+                    }
+                    "__instant__" => Ok(Value::Instant(map.next_value()?)),
+                    "__duration__" => Ok(Value::Duration(map.next_value()?)),
+                    "__vec2__" => Ok(Value::Vec2(map.next_value()?)),
+                    "__vec3__" => Ok(Value::Vec3(map.next_value()?)),
                     "__list__" => {
                         let items: Vec<Value> = map.next_value()?;
                         Ok(Value::List(items.into_iter().collect()))
@@ -4,8 +4,12 @@
 
 use crate::span::Span;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// An AST node.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Ast {
     /// `nil`
     Nil(Span),
@@ -0,0 +1,89 @@
+//! Source comments retained for tooling.
+//!
+//! The compiler ignores comments, but the formatter, doc generator, and
+//! editor hover want them. When a [`Parser`](crate::Parser) is created with
+//! [`with_comments`](crate::Parser::with_comments), each run of comments is
+//! attached to the form that follows it and collected in a [`CommentMap`],
+//! keyed by that form's starting offset.
+//!
+//! ```
+//! use longtable_language::parse_with_comments;
+//!
+//! let (forms, comments) = parse_with_comments(";; Player health\n(component: health)").unwrap();
+//! assert_eq!(comments.leading(&forms[0])[0].body(), "Player health");
+//! ```
+
+use std::collections::HashMap;
+
+use crate::ast::Ast;
+use crate::span::Span;
+
+/// A single `;` comment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+    /// Comment text, including the leading semicolons.
+    pub text: String,
+    /// Where the comment appears in the source.
+    pub span: Span,
+}
+
+impl Comment {
+    /// Returns the comment text without leading semicolons or surrounding whitespace.
+    #[must_use]
+    pub fn body(&self) -> &str {
+        self.text.trim_start_matches(';').trim()
+    }
+}
+
+/// Comments collected while parsing, attached to the forms they precede.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommentMap {
+    /// Comments keyed by the start offset of the form that follows them.
+    leading: HashMap<usize, Vec<Comment>>,
+    /// Comments with no following form (before a closing delimiter or end of input).
+    dangling: Vec<Comment>,
+}
+
+impl CommentMap {
+    /// Creates an empty comment map.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attaches comments to the form starting at `offset`.
+    pub fn attach(&mut self, offset: usize, comments: impl IntoIterator<Item = Comment>) {
+        self.leading.entry(offset).or_default().extend(comments);
+    }
+
+    /// Records comments that are not followed by a form.
+    pub fn add_dangling(&mut self, comments: impl IntoIterator<Item = Comment>) {
+        self.dangling.extend(comments);
+    }
+
+    /// Returns the comments immediately preceding `form`.
+    #[must_use]
+    pub fn leading(&self, form: &Ast) -> &[Comment] {
+        self.leading
+            .get(&form.span().start)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns comments that were not followed by a form.
+    #[must_use]
+    pub fn dangling(&self) -> &[Comment] {
+        &self.dangling
+    }
+
+    /// Returns the total number of comments.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.leading.values().map(Vec::len).sum::<usize>() + self.dangling.len()
+    }
+
+    /// Returns true if no comments were collected.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -12,17 +12,20 @@
 #![allow(clippy::unused_self)]
 #![allow(clippy::unnecessary_wraps)]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use longtable_foundation::{Error, ErrorKind, Interner, KeywordId, LtMap, LtVec, Result, Value};
+use longtable_foundation::{
+    Error, ErrorKind, Interner, KeywordId, LtMap, LtVec, Result, Type, Value,
+};
 
 use crate::ast::Ast;
-use crate::declaration::DeclarationAnalyzer;
+use crate::declaration::{DeclarationAnalyzer, RecordDecl, Refraction, RuleDecl};
 use crate::macro_expander::MacroExpander;
 use crate::macro_registry::MacroRegistry;
 use crate::namespace::NamespaceContext;
 use crate::opcode::{Bytecode, Opcode};
 use crate::span::Span;
+use crate::vm::{DEFAULT_PEEK_BUDGET, SESSION_COMMANDS};
 
 /// Compiler state for transforming AST to bytecode.
 pub struct Compiler {
@@ -59,6 +62,18 @@ pub struct Compiler {
     /// Whether we're currently compiling an expression in tail position.
     /// When true, function calls should emit `TailCall` instead of `Call`.
     in_tail_position: bool,
+    /// Name for the next `fn` compiled, taken from the `def`, `fn:`, or
+    /// `let` binding it is the value of.
+    fn_name: Option<String>,
+    /// Records declared with `defrecord` (persist across compilations).
+    records: HashMap<String, RecordDecl>,
+    /// Locals bound to a record constructor call, with the record's name.
+    record_locals: HashMap<String, String>,
+    /// File the forms being compiled were read from, if any.
+    source: Option<String>,
+    /// Rule declarations compiled since the last [`Compiler::take_rule_decls`],
+    /// with the file each was read from.
+    rule_decls: Vec<(RuleDecl, Option<String>)>,
 }
 
 /// Key for constant deduplication.
@@ -105,6 +120,28 @@ pub struct CompiledFunction {
     /// Names of captured variables (for closures).
     /// The order corresponds to the capture index used by `LoadCapture`.
     pub captures: Vec<String>,
+    /// The name the function was defined under, if any (for backtraces).
+    pub name: Option<String>,
+    /// File the function was defined in, or `None` if entered at the REPL.
+    pub source: Option<String>,
+}
+
+impl CompiledFunction {
+    /// Describes the function for a backtrace: its name, or its parameters
+    /// if it is anonymous.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(fn [{}])", self.params.join(" ")),
+        }
+    }
+
+    /// Returns the file the function was defined in, as errors name it.
+    #[must_use]
+    pub fn source_name(&self) -> &str {
+        self.source.as_deref().unwrap_or("<repl>")
+    }
 }
 
 /// Compiled program ready for execution.
@@ -118,6 +155,15 @@ pub struct CompiledProgram {
     pub functions: Vec<CompiledFunction>,
 }
 
+impl CompiledProgram {
+    /// Adds every keyword in the constants pool to `live`.
+    pub fn mark_keywords(&self, live: &mut HashSet<KeywordId>) {
+        for constant in &self.constants {
+            constant.mark_keywords(live);
+        }
+    }
+}
+
 impl Default for Compiler {
     fn default() -> Self {
         Self::new()
@@ -143,6 +189,11 @@ impl Compiler {
             macro_registry: MacroRegistry::new(),
             interner: None,
             in_tail_position: false,
+            fn_name: None,
+            records: HashMap::new(),
+            record_locals: HashMap::new(),
+            source: None,
+            rule_decls: Vec::new(),
         };
 
         // Register built-in native functions
@@ -168,6 +219,11 @@ impl Compiler {
             macro_registry: MacroRegistry::new(),
             interner: Some(interner),
             in_tail_position: false,
+            fn_name: None,
+            records: HashMap::new(),
+            record_locals: HashMap::new(),
+            source: None,
+            rule_decls: Vec::new(),
         };
 
         // Register built-in native functions
@@ -193,6 +249,11 @@ impl Compiler {
             macro_registry: MacroRegistry::new(),
             interner: None,
             in_tail_position: false,
+            fn_name: None,
+            records: HashMap::new(),
+            record_locals: HashMap::new(),
+            source: None,
+            rule_decls: Vec::new(),
         };
 
         // Register built-in native functions
@@ -240,6 +301,11 @@ impl Compiler {
             macro_registry,
             interner: None,
             in_tail_position: false,
+            fn_name: None,
+            records: HashMap::new(),
+            record_locals: HashMap::new(),
+            source: None,
+            rule_decls: Vec::new(),
         };
 
         // Register built-in native functions
@@ -260,7 +326,35 @@ impl Compiler {
         self.next_local = 0;
         self.outer_locals = None;
         self.captures.clear();
-        // Persistent: globals, next_global, natives, macro_registry, namespace_context, functions, constants
+        self.record_locals.clear();
+        // Persistent: globals, next_global, natives, macro_registry, namespace_context, functions, constants, records
+    }
+
+    /// Builds a program that calls `callee` with no arguments.
+    ///
+    /// Used to invoke closures produced by earlier compilations (such as
+    /// `schedule!` actions), whose function indexes refer to this compiler.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the constant pool has no room for `callee`.
+    pub fn compile_call(&self, callee: Value) -> Result<CompiledProgram> {
+        let mut constants = self.constants.clone();
+        let idx = u16::try_from(constants.len()).map_err(|_| {
+            Error::new(ErrorKind::Overflow(
+                "too many constants to call a scheduled action".to_string(),
+            ))
+        })?;
+        constants.push(callee);
+
+        let mut code = Bytecode::new();
+        code.emit(Opcode::Const(idx));
+        code.emit(Opcode::Call(0));
+        Ok(CompiledProgram {
+            code,
+            constants,
+            functions: self.functions.clone(),
+        })
     }
 
     /// Returns a mutable reference to the macro registry.
@@ -285,6 +379,21 @@ impl Compiler {
         &self.namespace_context
     }
 
+    /// Sets the file the forms compiled from now on were read from, returning
+    /// the file set before.
+    pub fn set_source(&mut self, source: Option<String>) -> Option<String> {
+        std::mem::replace(&mut self.source, source)
+    }
+
+    /// Takes the rule declarations compiled since the last call, each with
+    /// the file it was read from.
+    ///
+    /// Registering a rule at runtime hands the host its declaration as data,
+    /// which has lost the source spans its body's errors are reported at.
+    pub fn take_rule_decls(&mut self) -> Vec<(RuleDecl, Option<String>)> {
+        std::mem::take(&mut self.rule_decls)
+    }
+
     /// Returns a reference to the globals map (name -> slot).
     /// This is used to sync global name bindings with the VM for late-bound lookups.
     #[must_use]
@@ -355,7 +464,7 @@ impl Compiler {
             // Misc
             "print",
             "println",
-            "say", // Alias for println (DSL output function)
+            "say", // println with markup (DSL output function)
             "type",
             // Stage S1: Critical functions
             "inc",
@@ -439,6 +548,43 @@ impl Compiler {
             "interpose",
             "zip",
             "repeat",
+            // Time
+            "instant",
+            "duration",
+            "seconds",
+            "minutes",
+            "hours",
+            "days",
+            "to-millis",
+            "to-seconds",
+            "instant?",
+            "duration?",
+            "bigint",
+            "make-record",
+            // Lazy sequences
+            "iterate",
+            "lazy",
+            "lazy?",
+            // Message catalog
+            "msg",
+            "say-msg",
+            // Text generation
+            "a-or-an",
+            "pluralize",
+            "join-and",
+            // Pathfinding
+            "find-path",
+            // Spatial
+            "entities-within",
+            "within?",
+            // Fixed-size vectors
+            "vec2",
+            "vec3",
+            "vec-x",
+            "vec-y",
+            "vec-z",
+            // Constraints
+            "world-score",
         ];
 
         for (idx, name) in natives.iter().enumerate() {
@@ -492,7 +638,7 @@ impl Compiler {
 
         // Remove the last Pop if we added one
         if !code.is_empty() && matches!(code.ops.last(), Some(Opcode::Pop)) {
-            code.ops.pop();
+            code.pop();
         }
 
         Ok(CompiledProgram {
@@ -518,7 +664,7 @@ impl Compiler {
 
         // Remove the last Pop if we added one
         if !code.is_empty() && matches!(code.ops.last(), Some(Opcode::Pop)) {
-            code.ops.pop();
+            code.pop();
         }
 
         Ok(CompiledProgram {
@@ -528,8 +674,22 @@ impl Compiler {
         })
     }
 
-    /// Compiles a single AST node.
+    /// Compiles a single AST node, mapping the instructions it emits back to
+    /// its position in the source.
     fn compile_node(&mut self, ast: &Ast, code: &mut Bytecode) -> Result<()> {
+        let span = ast.span();
+        // Synthesized forms (line 0) keep the position of the form around them
+        if span.line == 0 {
+            return self.compile_form(ast, code);
+        }
+        let outer = code.set_span(Some(span));
+        let result = self.compile_form(ast, code);
+        code.set_span(outer);
+        result
+    }
+
+    /// Compiles a single AST node.
+    fn compile_form(&mut self, ast: &Ast, code: &mut Bytecode) -> Result<()> {
         match ast {
             Ast::Nil(_) => {
                 let idx = self.add_constant(Value::Nil);
@@ -554,8 +714,9 @@ impl Compiler {
             Ast::Symbol(name, _) => {
                 self.compile_symbol(name, code);
             }
-            Ast::Keyword(name, _) => {
-                // Keywords compile to themselves as values
+            Ast::Keyword(name, span) => {
+                // Keywords compile to themselves as values, `::` ones qualified
+                let name = &self.namespace_context.resolve_keyword_at(name, *span)?;
                 let value = if let Some(ref mut interner) = self.interner {
                     // When we have an interner, properly intern the keyword
                     let keyword_id = interner.intern_keyword(name);
@@ -688,6 +849,7 @@ impl Compiler {
                 "and*" => return self.compile_and_star(args, span, code),
                 "or*" => return self.compile_or_star(args, span, code),
                 "cond*" => return self.compile_cond_star(args, span, code),
+                "match" => return self.compile_match(args, span, code),
                 "thread-first" => return self.compile_thread_first(args, span, code),
                 "thread-last" => return self.compile_thread_last(args, span, code),
                 "doto*" => return self.compile_doto_star(args, span, code),
@@ -716,13 +878,18 @@ impl Compiler {
                 "string->keyword" => return self.compile_string_to_keyword(args, span, code),
                 "targets" => return self.compile_targets(args, span, code),
                 "sources" => return self.compile_sources(args, span, code),
+                "peek" => return self.compile_peek(args, span, code),
                 // Entity construction
                 "entity-ref" => return self.compile_entity_ref(args, span, code),
                 // Entity predicates
                 "has?" => return self.compile_has_component(args, span, code),
                 // World mutation operations (! suffix follows Lisp convention)
                 "spawn!" => return self.compile_spawn(args, span, code),
+                "spawn-many!" => return self.compile_spawn_many(args, span, code),
                 "destroy!" => return self.compile_destroy(args, span, code),
+                "emit!" => return self.compile_emit(args, span, code),
+                "schedule!" => return self.compile_schedule(args, span, code),
+                "every" | "fuse" => return self.compile_timer(name, args, span, code),
                 "set-component!" => return self.compile_set_component(args, span, code),
                 "set-field!" => return self.compile_set_field(args, span, code),
                 "remove-component!" | "dissoc!" => {
@@ -738,6 +905,10 @@ impl Compiler {
                 // State management (backtracking support)
                 "save-state" => return self.compile_save_state(span, code),
                 "restore-state" => return self.compile_restore_state(args, span, code),
+                // Session commands (save!, tick!, rollback!, ...)
+                command if SESSION_COMMANDS.contains(&command) => {
+                    return self.compile_command(command, args, span, code);
+                }
                 // Declaration forms (compile to registration opcodes)
                 "component:" => return self.compile_component_decl(elements, span, code),
                 "relationship:" => return self.compile_relationship_decl(elements, span, code),
@@ -746,14 +917,22 @@ impl Compiler {
                 "preposition:" => return self.compile_preposition_decl(elements, span, code),
                 "pronoun:" => return self.compile_pronoun_decl(elements, span, code),
                 "adverb:" => return self.compile_adverb_decl(elements, span, code),
+                "message:" => return self.compile_message_decl(elements, span, code),
                 "type:" => return self.compile_type_decl(elements, span, code),
                 "scope:" => return self.compile_scope_decl(elements, span, code),
                 "command:" => return self.compile_command_decl(elements, span, code),
                 "action:" => return self.compile_action_decl(elements, span, code),
                 "rule:" => return self.compile_rule_decl(elements, span, code),
+                "rule-group:" => return self.compile_rule_group_decl(elements, span, code),
+                "defrecord" => return self.compile_defrecord(elements, span, code),
                 _ => {}
             }
 
+            // Record constructor
+            if let Some(record) = self.record_named(name).cloned() {
+                return self.compile_record_construct(&record, args, span, code);
+            }
+
             // Check for native/builtin function
             if let Some(&native_idx) = self.natives.get(name.as_str()) {
                 // Check for operators that map directly to opcodes
@@ -853,6 +1032,11 @@ impl Compiler {
             }
         }
 
+        // Keyword accessor: (:field map)
+        if let Ast::Keyword(field, field_span) = first {
+            return self.compile_keyword_access(first, field, *field_span, args, span, code);
+        }
+
         // General function call
         // Save tail position - only the call itself is in tail position, not the function or arguments
         let saved_tail = self.in_tail_position;
@@ -996,6 +1180,7 @@ impl Compiler {
         // Save current locals state for restoration
         let saved_locals = self.locals.clone();
         let saved_next = self.next_local;
+        let saved_records = self.record_locals.clone();
 
         // Phase 1: Allocate slots for ALL bindings first (letrec semantics)
         // This allows recursive references within binding values
@@ -1025,8 +1210,15 @@ impl Compiler {
         let saved_tail = self.in_tail_position;
         self.in_tail_position = false;
 
-        for (_name, value, slot) in &binding_info {
+        for (name, value, slot) in &binding_info {
+            // Remember which record the value is, for checking field accesses
+            match self.record_of(value).map(|record| record.name.clone()) {
+                Some(record) => self.record_locals.insert(name.clone(), record),
+                None => self.record_locals.remove(name),
+            };
+
             // Compile the value - this handles both regular values and closures
+            self.name_fn(name, value);
             self.compile_node(value, code)?;
 
             // Check if this created a closure with captures that need patching
@@ -1100,6 +1292,7 @@ impl Compiler {
         self.in_tail_position = saved_tail;
         self.locals = saved_locals;
         self.next_local = saved_next;
+        self.record_locals = saved_records;
 
         Ok(())
     }
@@ -1131,6 +1324,7 @@ impl Compiler {
     /// Compiles a fn expression (lambda).
     fn compile_fn(&mut self, args: &[Ast], span: Span, code: &mut Bytecode) -> Result<()> {
         // (fn [params...] body...)
+        let name = self.fn_name.take();
         if args.is_empty() {
             return Err(self.error(span, "fn requires parameters vector"));
         }
@@ -1172,6 +1366,12 @@ impl Compiler {
         self.next_local = 0;
         self.captures.clear();
 
+        // Parameters shadow locals known to hold records
+        let saved_records = self.record_locals.clone();
+        for name in &param_names {
+            self.record_locals.remove(name);
+        }
+
         // Add parameters as locals
         for name in &param_names {
             let slot = self.next_local;
@@ -1219,6 +1419,7 @@ impl Compiler {
         self.next_local = saved_next;
         self.outer_locals = saved_outer;
         self.captures = saved_captures;
+        self.record_locals = saved_records;
 
         // Create compiled function
         let func = CompiledFunction {
@@ -1227,6 +1428,8 @@ impl Compiler {
             code: fn_code,
             locals_count,
             captures: captures.clone(),
+            name,
+            source: self.source.clone(),
         };
 
         // Add to functions table
@@ -1277,6 +1480,7 @@ impl Compiler {
         };
 
         // Compile the value
+        self.name_fn(&name, &args[1]);
         self.compile_node(&args[1], code)?;
 
         // Store in local slot
@@ -1291,6 +1495,16 @@ impl Compiler {
         Ok(())
     }
 
+    /// Names the function `value` compiles to after `name`, if it is a
+    /// `(fn ...)` form.
+    fn name_fn(&mut self, name: &str, value: &Ast) {
+        if let Ast::List(items, _) = value
+            && matches!(items.first(), Some(Ast::Symbol(head, _)) if head == "fn")
+        {
+            self.fn_name = Some(name.to_string());
+        }
+    }
+
     /// Compiles a `fn:` declaration (global function/value definition).
     ///
     /// Syntax:
@@ -1329,6 +1543,7 @@ impl Compiler {
             // (fn: name [params] body...) - function definition
             Some(Ast::Vector(_, _)) => {
                 // Compile as (fn [params] body...)
+                self.fn_name = Some(name.clone());
                 self.compile_fn(rest, span, code)?;
             }
             // (fn: name "docstring" [params] body...) - function with docstring
@@ -1340,6 +1555,7 @@ impl Compiler {
                 }
                 match &fn_args[0] {
                     Ast::Vector(_, _) => {
+                        self.fn_name = Some(name.clone());
                         self.compile_fn(fn_args, span, code)?;
                     }
                     _ => {
@@ -1351,6 +1567,7 @@ impl Compiler {
             }
             // (fn: name value) - simple value definition
             Some(_) if rest.len() == 1 => {
+                self.name_fn(&name, &rest[0]);
                 self.compile_node(&rest[0], code)?;
             }
             _ => {
@@ -1540,6 +1757,313 @@ impl Compiler {
         Ok(())
     }
 
+    /// Compiles `match` - the first clause whose pattern matches the value
+    /// is evaluated, with the pattern's variables bound.
+    ///
+    /// - `(match v)` -> `nil`
+    /// - `(match v pattern expr rest...)` -> `expr` if `v` matches `pattern`,
+    ///   else `(match v rest...)`
+    ///
+    /// Patterns are tested in place against the value, so no clause is
+    /// evaluated more than once and failed tests jump straight to the next
+    /// clause.
+    fn compile_match(&mut self, args: &[Ast], span: Span, code: &mut Bytecode) -> Result<()> {
+        let Some((value, clauses)) = args.split_first() else {
+            return Err(self.error(span, "match requires a value"));
+        };
+        if clauses.len() % 2 != 0 {
+            return Err(self.error(span, "match requires pattern/expression pairs"));
+        }
+
+        let saved_locals = self.locals.clone();
+        let saved_next = self.next_local;
+        let saved_records = self.record_locals.clone();
+        let saved_tail = self.in_tail_position;
+
+        // Evaluate the value once (never in tail position)
+        self.in_tail_position = false;
+        self.compile_node(value, code)?;
+        let subject = self.next_local;
+        self.next_local += 1;
+        code.emit(Opcode::StoreLocal(subject));
+
+        let mut jump_to_ends = Vec::new();
+        for clause in clauses.chunks(2) {
+            let clause_locals = self.locals.clone();
+            let clause_next = self.next_local;
+            let clause_records = self.record_locals.clone();
+
+            // Test the pattern, binding its variables
+            self.in_tail_position = false;
+            let mut bound = HashSet::new();
+            let mut jump_to_next = Vec::new();
+            self.compile_pattern(&clause[0], subject, &mut bound, &mut jump_to_next, code)?;
+
+            // Compile expression for this clause (inherits tail position)
+            self.in_tail_position = saved_tail;
+            self.compile_node(&clause[1], code)?;
+            jump_to_ends.push(code.emit(Opcode::Jump(0)));
+
+            self.patch_jumps_here(&jump_to_next, span, code)?;
+            self.locals = clause_locals;
+            self.next_local = clause_next;
+            self.record_locals = clause_records;
+        }
+
+        // If no clause matched, result is nil
+        let idx = self.add_constant(Value::Nil);
+        code.emit(Opcode::Const(idx));
+        self.patch_jumps_here(&jump_to_ends, span, code)?;
+
+        self.in_tail_position = saved_tail;
+        self.locals = saved_locals;
+        self.next_local = saved_next;
+        self.record_locals = saved_records;
+        Ok(())
+    }
+
+    /// Compiles the test of a `match` pattern against the value in local
+    /// `subject`.
+    ///
+    /// Each failing test pushes a jump onto `fail` for the caller to patch;
+    /// `?variables` are bound as locals and recorded in `bound`, and a
+    /// variable bound twice must match an equal value.
+    fn compile_pattern(
+        &mut self,
+        pattern: &Ast,
+        subject: u16,
+        bound: &mut HashSet<String>,
+        fail: &mut Vec<usize>,
+        code: &mut Bytecode,
+    ) -> Result<()> {
+        match pattern {
+            Ast::Symbol(name, _) if name == "_" => {}
+            Ast::Symbol(name, _) if bound.contains(name) => {
+                let slot = self.locals[name];
+                code.emit(Opcode::LoadLocal(subject));
+                code.emit(Opcode::LoadLocal(slot));
+                code.emit(Opcode::Eq);
+                fail.push(code.emit(Opcode::JumpIfNot(0)));
+            }
+            Ast::Symbol(name, span) if !name.starts_with('?') => {
+                // A bare name would bind, and so match anything, silently
+                return Err(self.error(
+                    *span,
+                    &format!(
+                        "match pattern `{name}` matches anything; write `?{name}` to bind the value or `_` to ignore it"
+                    ),
+                ));
+            }
+            Ast::Symbol(name, _) => {
+                // The subject's slot is never reassigned, so it can be shared
+                self.locals.insert(name.clone(), subject);
+                self.record_locals.remove(name);
+                bound.insert(name.clone());
+            }
+            Ast::Nil(_)
+            | Ast::Bool(..)
+            | Ast::Int(..)
+            | Ast::Float(..)
+            | Ast::String(..)
+            | Ast::Keyword(..) => {
+                code.emit(Opcode::LoadLocal(subject));
+                self.compile_node(pattern, code)?;
+                code.emit(Opcode::Eq);
+                fail.push(code.emit(Opcode::JumpIfNot(0)));
+            }
+            Ast::Vector(elements, span) => {
+                self.compile_vector_pattern(elements, *span, subject, bound, fail, code)?;
+            }
+            Ast::Map(entries, _) => {
+                self.emit_native_test("map?", &[subject], code, fail);
+                for (key, value) in entries {
+                    if let (Ast::Keyword(k, _), Ast::Vector(names, _)) = (key, value) {
+                        if k == "keys" {
+                            self.compile_keys_pattern(names, subject, bound, code)?;
+                            continue;
+                        }
+                    }
+                    code.emit(Opcode::LoadLocal(subject));
+                    self.compile_node(key, code)?;
+                    let contains = self.natives["contains?"];
+                    code.emit(Opcode::CallNative(contains, 2));
+                    fail.push(code.emit(Opcode::JumpIfNot(0)));
+
+                    code.emit(Opcode::LoadLocal(subject));
+                    self.compile_node(key, code)?;
+                    let slot = self.emit_native_into_local("get", 2, code);
+                    self.compile_pattern(value, slot, bound, fail, code)?;
+                }
+            }
+            Ast::List(elements, span) => match elements.as_slice() {
+                [inner, Ast::Keyword(when, _), guard] if when == "when" => {
+                    self.compile_pattern(inner, subject, bound, fail, code)?;
+                    self.compile_node(guard, code)?;
+                    fail.push(code.emit(Opcode::JumpIfNot(0)));
+                }
+                [Ast::Keyword(or, _), alternatives @ ..]
+                    if or == "or" && !alternatives.is_empty() =>
+                {
+                    let mut jump_to_matched = Vec::new();
+                    for (i, alternative) in alternatives.iter().enumerate() {
+                        let mut alternative_bound = bound.clone();
+                        let mut jump_to_next = Vec::new();
+                        self.compile_pattern(
+                            alternative,
+                            subject,
+                            &mut alternative_bound,
+                            &mut jump_to_next,
+                            code,
+                        )?;
+                        if alternative_bound.len() != bound.len() {
+                            return Err(self.error(*span, ":or patterns can't bind variables"));
+                        }
+                        if i + 1 == alternatives.len() {
+                            fail.extend(jump_to_next);
+                        } else {
+                            jump_to_matched.push(code.emit(Opcode::Jump(0)));
+                            self.patch_jumps_here(&jump_to_next, *span, code)?;
+                        }
+                    }
+                    self.patch_jumps_here(&jump_to_matched, *span, code)?;
+                }
+                _ => {
+                    return Err(self.error(
+                        *span,
+                        "match list patterns must be (pattern :when guard) or (:or pattern ...)",
+                    ));
+                }
+            },
+            other => {
+                return Err(self.error(
+                    other.span(),
+                    &format!("unsupported match pattern: {}", other.type_name()),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Compiles a vector pattern: `[?a ?b]` matches vectors of exactly two
+    /// elements, `[?a & ?more]` vectors of at least one, with `?more`
+    /// matching the rest.
+    fn compile_vector_pattern(
+        &mut self,
+        elements: &[Ast],
+        span: Span,
+        subject: u16,
+        bound: &mut HashSet<String>,
+        fail: &mut Vec<usize>,
+        code: &mut Bytecode,
+    ) -> Result<()> {
+        let is_amp = |ast: &Ast| matches!(ast, Ast::Symbol(s, _) if s == "&");
+        let (fixed, rest) = match elements.iter().position(is_amp) {
+            Some(i) if i + 2 == elements.len() => (&elements[..i], Some(&elements[i + 1])),
+            Some(_) => {
+                return Err(self.error(
+                    span,
+                    "& in a vector pattern must be followed by one pattern",
+                ));
+            }
+            None => (elements, None),
+        };
+
+        self.emit_native_test("vector?", &[subject], code, fail);
+        code.emit(Opcode::LoadLocal(subject));
+        let count = self.natives["count"];
+        code.emit(Opcode::CallNative(count, 1));
+        let len = self.add_constant(Value::Int(i64::try_from(fixed.len()).unwrap_or(i64::MAX)));
+        code.emit(Opcode::Const(len));
+        code.emit(if rest.is_some() {
+            Opcode::Ge
+        } else {
+            Opcode::Eq
+        });
+        fail.push(code.emit(Opcode::JumpIfNot(0)));
+
+        for (i, element) in fixed.iter().enumerate() {
+            if matches!(element, Ast::Symbol(s, _) if s == "_") {
+                continue;
+            }
+            code.emit(Opcode::LoadLocal(subject));
+            let index = self.add_constant(Value::Int(i64::try_from(i).unwrap_or(i64::MAX)));
+            code.emit(Opcode::Const(index));
+            let slot = self.emit_native_into_local("nth", 2, code);
+            self.compile_pattern(element, slot, bound, fail, code)?;
+        }
+        if let Some(rest) = rest {
+            code.emit(Opcode::Const(len));
+            code.emit(Opcode::LoadLocal(subject));
+            let slot = self.emit_native_into_local("drop", 2, code);
+            self.compile_pattern(rest, slot, bound, fail, code)?;
+        }
+        Ok(())
+    }
+
+    /// Binds each name in a map pattern's `:keys [a b]` to the value under
+    /// the keyword of the same name (or nil).
+    fn compile_keys_pattern(
+        &mut self,
+        names: &[Ast],
+        subject: u16,
+        bound: &mut HashSet<String>,
+        code: &mut Bytecode,
+    ) -> Result<()> {
+        for name in names {
+            let Ast::Symbol(name, _) = name else {
+                return Err(self.error(name.span(), ":keys in a match pattern takes symbols"));
+            };
+            code.emit(Opcode::LoadLocal(subject));
+            let key = self.keyword_value(name);
+            let key = self.add_constant(key);
+            code.emit(Opcode::Const(key));
+            let slot = self.emit_native_into_local("get", 2, code);
+            self.locals.insert(name.clone(), slot);
+            self.record_locals.remove(name);
+            bound.insert(name.clone());
+        }
+        Ok(())
+    }
+
+    /// Calls native `name` on the values in `locals`, jumping to `fail` if
+    /// the result is falsy.
+    fn emit_native_test(
+        &mut self,
+        name: &str,
+        locals: &[u16],
+        code: &mut Bytecode,
+        fail: &mut Vec<usize>,
+    ) {
+        for slot in locals {
+            code.emit(Opcode::LoadLocal(*slot));
+        }
+        let native = self.natives[name];
+        code.emit(Opcode::CallNative(native, locals.len() as u8));
+        fail.push(code.emit(Opcode::JumpIfNot(0)));
+    }
+
+    /// Calls native `name` on the top `argc` stack values and stores the
+    /// result in a fresh local, returning its slot.
+    fn emit_native_into_local(&mut self, name: &str, argc: u8, code: &mut Bytecode) -> u16 {
+        let native = self.natives[name];
+        code.emit(Opcode::CallNative(native, argc));
+        let slot = self.next_local;
+        self.next_local += 1;
+        code.emit(Opcode::StoreLocal(slot));
+        slot
+    }
+
+    /// Points each of `jumps` at the next instruction to be emitted.
+    fn patch_jumps_here(&self, jumps: &[usize], span: Span, code: &mut Bytecode) -> Result<()> {
+        let target = code.len();
+        for &jump in jumps {
+            let offset = i16::try_from(target - jump - 1)
+                .map_err(|_| self.error(span, "jump offset too large"))?;
+            code.patch_jump(jump, offset);
+        }
+        Ok(())
+    }
+
     /// Compiles `thread-first` - threads value through forms as first argument.
     ///
     /// - `(thread-first x)` -> `x`
@@ -1561,12 +2085,12 @@ impl Compiler {
         }
 
         // Transform and compile: thread x through forms
-        let threaded = self.thread_first_transform(&args[0], &args[1..])?;
+        let threaded = Self::thread_first_transform(&args[0], &args[1..])?;
         self.compile_node(&threaded, code)
     }
 
     /// Recursively transforms thread-first forms.
-    fn thread_first_transform(&self, x: &Ast, forms: &[Ast]) -> Result<Ast> {
+    fn thread_first_transform(x: &Ast, forms: &[Ast]) -> Result<Ast> {
         if forms.is_empty() {
             return Ok(x.clone());
         }
@@ -1593,7 +2117,7 @@ impl Compiler {
         };
 
         // Continue threading through rest
-        self.thread_first_transform(&transformed, rest)
+        Self::thread_first_transform(&transformed, rest)
     }
 
     /// Compiles `thread-last` - threads value through forms as last argument.
@@ -1612,12 +2136,12 @@ impl Compiler {
         }
 
         // Transform and compile: thread x through forms
-        let threaded = self.thread_last_transform(&args[0], &args[1..])?;
+        let threaded = Self::thread_last_transform(&args[0], &args[1..])?;
         self.compile_node(&threaded, code)
     }
 
     /// Recursively transforms thread-last forms.
-    fn thread_last_transform(&self, x: &Ast, forms: &[Ast]) -> Result<Ast> {
+    fn thread_last_transform(x: &Ast, forms: &[Ast]) -> Result<Ast> {
         if forms.is_empty() {
             return Ok(x.clone());
         }
@@ -1644,7 +2168,7 @@ impl Compiler {
         };
 
         // Continue threading through rest
-        self.thread_last_transform(&transformed, rest)
+        Self::thread_last_transform(&transformed, rest)
     }
 
     /// Compiles `doto*` - evaluates forms with first arg, returns first arg.
@@ -2056,6 +2580,37 @@ impl Compiler {
         Ok(())
     }
 
+    /// Compiles (peek expr) or (peek :budget n expr) -> value of expr
+    ///
+    /// `expr` runs read-only against the world as it stood before this
+    /// execution's own writes. It fails if it tries to produce an effect or
+    /// runs more than `n` instructions (default [`DEFAULT_PEEK_BUDGET`]).
+    fn compile_peek(&mut self, args: &[Ast], span: Span, code: &mut Bytecode) -> Result<()> {
+        let (budget, body) = match args {
+            [body] => (DEFAULT_PEEK_BUDGET, body),
+            [Ast::Keyword(key, _), Ast::Int(n, _), body] if key == "budget" => {
+                let Ok(budget) = u32::try_from(*n) else {
+                    return Err(self.error(span, "peek :budget must be a non-negative integer"));
+                };
+                (budget, body)
+            }
+            _ => {
+                return Err(self.error(span, "peek expects (peek expr) or (peek :budget n expr)"));
+            }
+        };
+
+        // The body is never in tail position: PeekEnd has to run after it
+        let saved_tail = self.in_tail_position;
+        self.in_tail_position = false;
+        code.emit(Opcode::PeekBegin(budget));
+        let result = self.compile_node(body, code);
+        self.in_tail_position = saved_tail;
+        result?;
+        code.emit(Opcode::PeekEnd);
+
+        Ok(())
+    }
+
     /// Compiles (find-relationships rel-type-or-nil source-or-nil target-or-nil) -> [relationship-entities...]
     fn compile_find_relationships(
         &mut self,
@@ -2239,6 +2794,152 @@ impl Compiler {
         Ok(())
     }
 
+    /// Compiles (spawn-many! [components-map ...]) -> [entity ...]
+    ///
+    /// The spawns are applied to the world together, in one pass.
+    fn compile_spawn_many(&mut self, args: &[Ast], span: Span, code: &mut Bytecode) -> Result<()> {
+        if args.len() != 1 {
+            return Err(self.error(
+                span,
+                "spawn-many! requires exactly 1 argument (a collection of components-maps)",
+            ));
+        }
+
+        self.compile_node(&args[0], code)?;
+        code.emit(Opcode::SpawnMany);
+
+        Ok(())
+    }
+
+    /// Compiles (emit! :event payload?) -> nil
+    ///
+    /// Emits an event, drained at the end of the tick.
+    fn compile_emit(&mut self, args: &[Ast], span: Span, code: &mut Bytecode) -> Result<()> {
+        if args.is_empty() || args.len() > 2 {
+            return Err(self.error(span, "emit! requires 1-2 arguments (event, payload?)"));
+        }
+
+        self.compile_node(&args[0], code)?;
+        if let Some(payload) = args.get(1) {
+            self.compile_node(payload, code)?;
+        } else {
+            let idx = self.add_constant(Value::Nil);
+            code.emit(Opcode::Const(idx));
+        }
+        code.emit(Opcode::Emit);
+        // Emit returns nil
+        let idx = self.add_constant(Value::Nil);
+        code.emit(Opcode::Const(idx));
+
+        Ok(())
+    }
+
+    /// Compiles (schedule! :in ticks [:on entity] :then [forms...]) -> nil
+    ///
+    /// The `:then` forms are compiled into a zero-argument closure, so they
+    /// can refer to locals in scope, and run when the timer fires.
+    fn compile_schedule(&mut self, args: &[Ast], span: Span, code: &mut Bytecode) -> Result<()> {
+        let usage = "schedule! expects :in ticks :then [forms...]";
+        let mut delay = None;
+        let mut owner = None;
+        let mut body = None;
+        for pair in args.chunks(2) {
+            match pair {
+                [Ast::Keyword(key, _), value] if key == "in" => delay = Some(value),
+                [Ast::Keyword(key, _), value] if key == "on" => owner = Some(value),
+                [Ast::Keyword(key, _), Ast::Vector(forms, _)] if key == "then" => {
+                    body = Some(forms);
+                }
+                _ => return Err(self.error(span, usage)),
+            }
+        }
+        let (Some(delay), Some(body)) = (delay, body) else {
+            return Err(self.error(span, "schedule! requires both :in and :then"));
+        };
+        self.compile_scheduled(Opcode::Schedule, delay, owner, body, span, code)
+    }
+
+    /// Compiles (every ticks [:on entity] :then [forms...]) and
+    /// (fuse ticks [:on entity] :then [forms...]) -> nil
+    ///
+    /// `every` runs the forms every so many ticks, and `fuse` once, like
+    /// `schedule!`. With `:on`, the timer belongs to the entity, which the
+    /// forms see as `self`.
+    fn compile_timer(
+        &mut self,
+        name: &str,
+        args: &[Ast],
+        span: Span,
+        code: &mut Bytecode,
+    ) -> Result<()> {
+        let usage = format!("{name} expects ticks [:on entity] :then [forms...]");
+        let Some((delay, options)) = args.split_first() else {
+            return Err(self.error(span, &usage));
+        };
+        let mut owner = None;
+        let mut body = None;
+        for pair in options.chunks(2) {
+            match pair {
+                [Ast::Keyword(key, _), value] if key == "on" => owner = Some(value),
+                [Ast::Keyword(key, _), Ast::Vector(forms, _)] if key == "then" => {
+                    body = Some(forms);
+                }
+                _ => return Err(self.error(span, &usage)),
+            }
+        }
+        let Some(body) = body else {
+            return Err(self.error(span, &format!("{name} requires :then")));
+        };
+        let op = if name == "every" {
+            Opcode::ScheduleEvery
+        } else {
+            Opcode::Schedule
+        };
+        self.compile_scheduled(op, delay, owner, body, span, code)
+    }
+
+    /// Compiles a timer's delay, owner, and closure, then `op`.
+    fn compile_scheduled(
+        &mut self,
+        op: Opcode,
+        delay: &Ast,
+        owner: Option<&Ast>,
+        body: &[Ast],
+        span: Span,
+        code: &mut Bytecode,
+    ) -> Result<()> {
+        self.compile_node(delay, code)?;
+
+        // The owner is bound to `self` for the closure to capture
+        let saved_locals = self.locals.clone();
+        let saved_next = self.next_local;
+        if let Some(owner) = owner {
+            let slot = self.next_local;
+            self.next_local += 1;
+            self.compile_node(owner, code)?;
+            code.emit(Opcode::StoreLocal(slot));
+            code.emit(Opcode::LoadLocal(slot));
+            self.locals.insert("self".to_string(), slot);
+        } else {
+            let idx = self.add_constant(Value::Nil);
+            code.emit(Opcode::Const(idx));
+        }
+
+        let mut fn_args = vec![Ast::Vector(Vec::new(), span)];
+        fn_args.extend(body.iter().cloned());
+        let compiled = self.compile_fn(&fn_args, span, code);
+        self.locals = saved_locals;
+        self.next_local = saved_next;
+        compiled?;
+
+        code.emit(op);
+        // Scheduling returns nil
+        let idx = self.add_constant(Value::Nil);
+        code.emit(Opcode::Const(idx));
+
+        Ok(())
+    }
+
     /// Compiles (destroy! entity) -> nil
     ///
     /// Destroys an entity.
@@ -2418,6 +3119,30 @@ impl Compiler {
         Ok(())
     }
 
+    /// Compiles a session command such as (save! "path") -> nil
+    ///
+    /// The arguments are evaluated and the command is queued as an effect
+    /// for the session to run.
+    fn compile_command(
+        &mut self,
+        name: &str,
+        args: &[Ast],
+        span: Span,
+        code: &mut Bytecode,
+    ) -> Result<()> {
+        let Ok(arg_count) = u8::try_from(args.len()) else {
+            return Err(self.error(span, &format!("too many arguments to {name}")));
+        };
+        for arg in args {
+            self.compile_node(arg, code)?;
+        }
+        let name_idx = self.add_constant(Value::String(name.into()));
+        code.emit(Opcode::Command(name_idx, arg_count));
+        let idx = self.add_constant(Value::Nil);
+        code.emit(Opcode::Const(idx));
+        Ok(())
+    }
+
     /// Compiles (restore-state snapshot-id) -> nil
     ///
     /// Restores the world to a previously saved snapshot.
@@ -2551,6 +3276,189 @@ impl Compiler {
     // Declaration Compilation (to registration opcodes)
     // =========================================================================
 
+    /// Compiles a `defrecord` form, remembering the record for the
+    /// constructor calls and field accesses compiled after it.
+    fn compile_defrecord(
+        &mut self,
+        elements: &[Ast],
+        span: Span,
+        code: &mut Bytecode,
+    ) -> Result<()> {
+        let ast = Ast::List(elements.to_vec(), span);
+        let decl = DeclarationAnalyzer::analyze_record(&ast)?
+            .ok_or_else(|| self.error(span, "invalid defrecord"))?;
+        for field in &decl.fields {
+            if Type::from_name(&field.ty).is_none() {
+                return Err(self.error(
+                    field.span,
+                    &format!(
+                        "record {}: unknown type :{} for field :{}",
+                        decl.name, field.ty, field.name
+                    ),
+                ));
+            }
+        }
+        self.records.insert(decl.name.clone(), decl);
+
+        let nil_idx = self.add_constant(Value::Nil);
+        code.emit(Opcode::Const(nil_idx));
+        Ok(())
+    }
+
+    /// Returns the record declared as `name`, unless a variable shadows it.
+    fn record_named(&self, name: &str) -> Option<&RecordDecl> {
+        let shadowed = self.locals.contains_key(name)
+            || self.captures.contains_key(name)
+            || self.globals.contains_key(name)
+            || self
+                .outer_locals
+                .as_ref()
+                .is_some_and(|outer| outer.contains_key(name));
+        if shadowed {
+            None
+        } else {
+            self.records.get(name)
+        }
+    }
+
+    /// Returns the record `ast` is known to evaluate to: a constructor call,
+    /// or a local bound to one.
+    fn record_of(&self, ast: &Ast) -> Option<&RecordDecl> {
+        match ast {
+            Ast::List(elements, _) => match elements.first() {
+                Some(Ast::Symbol(name, _)) => self.record_named(name),
+                _ => None,
+            },
+            Ast::Symbol(name, _) => self
+                .record_locals
+                .get(name)
+                .and_then(|record| self.records.get(record)),
+            _ => None,
+        }
+    }
+
+    /// Compiles `(record :field value ...)` into a map.
+    ///
+    /// Unknown, repeated, and missing fields are compile errors, as are
+    /// literal values of the wrong type; other values are checked against
+    /// their field types when the map is built.
+    fn compile_record_construct(
+        &mut self,
+        record: &RecordDecl,
+        args: &[Ast],
+        span: Span,
+        code: &mut Bytecode,
+    ) -> Result<()> {
+        let name = &record.name;
+        if args.len() % 2 != 0 {
+            return Err(self.error(span, &format!("{name} expects :field value pairs")));
+        }
+        let mut given: HashMap<&str, &Ast> = HashMap::new();
+        for pair in args.chunks(2) {
+            let Ast::Keyword(field, field_span) = &pair[0] else {
+                return Err(self.error(
+                    pair[0].span(),
+                    &format!(
+                        "{name} expects :field value pairs, got {}",
+                        pair[0].type_name()
+                    ),
+                ));
+            };
+            if !record.fields.iter().any(|f| f.name == *field) {
+                return Err(self.error(*field_span, &unknown_field(record, field)));
+            }
+            if given.insert(field, &pair[1]).is_some() {
+                return Err(self.error(*field_span, &format!("{name}: :{field} given twice")));
+            }
+        }
+
+        let mut entries = Vec::with_capacity(record.fields.len());
+        let mut spec = LtVec::new();
+        for field in &record.fields {
+            let ty = Type::from_name(&field.ty).unwrap_or(Type::Any);
+            let value = match (given.get(field.name.as_str()), &field.default) {
+                (Some(value), _) => (*value).clone(),
+                (None, Some(default)) => default.clone(),
+                (None, None) if ty.is_nullable() => Ast::Nil(span),
+                (None, None) => {
+                    return Err(self.error(span, &format!("{name}: missing field :{}", field.name)));
+                }
+            };
+            if let Some(actual) = literal_type(&value) {
+                if !ty.accepts(&actual) {
+                    return Err(self.error(
+                        value.span(),
+                        &format!("{name}: field :{} expects {ty}, got {actual}", field.name),
+                    ));
+                }
+            }
+            let key = self.keyword_value(&field.name);
+            spec = spec.push_back(Value::Vec(
+                [
+                    key,
+                    Value::from(field.name.as_str()),
+                    Value::from(field.ty.as_str()),
+                ]
+                .into_iter()
+                .collect(),
+            ));
+            entries.push((Ast::Keyword(field.name.clone(), field.span), value));
+        }
+
+        // Field values are never in tail position
+        let saved_tail = self.in_tail_position;
+        self.in_tail_position = false;
+        let name_idx = self.add_constant(Value::from(name.as_str()));
+        code.emit(Opcode::Const(name_idx));
+        let spec_idx = self.add_constant(Value::Vec(spec));
+        code.emit(Opcode::Const(spec_idx));
+        self.compile_map(&entries, code)?;
+        self.in_tail_position = saved_tail;
+
+        let make_record = self.natives["make-record"];
+        code.emit(Opcode::CallNative(make_record, 3));
+        Ok(())
+    }
+
+    /// Compiles `(:field map)` straight to a lookup, checking the field
+    /// when `map` is known to be a record.
+    fn compile_keyword_access(
+        &mut self,
+        keyword: &Ast,
+        field: &str,
+        field_span: Span,
+        args: &[Ast],
+        span: Span,
+        code: &mut Bytecode,
+    ) -> Result<()> {
+        let [target] = args else {
+            return Err(self.error(span, &format!(":{field} takes exactly 1 argument")));
+        };
+        if let Some(record) = self.record_of(target) {
+            if !record.fields.iter().any(|f| f.name == field) {
+                return Err(self.error(field_span, &unknown_field(record, field)));
+            }
+        }
+
+        let saved_tail = self.in_tail_position;
+        self.in_tail_position = false;
+        self.compile_node(target, code)?;
+        self.compile_node(keyword, code)?;
+        self.in_tail_position = saved_tail;
+
+        let get = self.natives["get"];
+        code.emit(Opcode::CallNative(get, 2));
+        Ok(())
+    }
+
+    /// Returns the value a plain keyword compiles to.
+    fn keyword_value(&mut self, name: &str) -> Value {
+        match self.interner {
+            Some(ref mut interner) => Value::Keyword(interner.intern_keyword(name)),
+            None => Value::String(format!(":{name}").into()),
+        }
+    }
+
     /// Compiles a `component:` declaration.
     ///
     /// Transforms `(component: name ...)` into a data map and emits `RegisterComponent`.
@@ -2712,6 +3620,59 @@ impl Compiler {
         Ok(())
     }
 
+    /// Compiles a `message:` declaration.
+    ///
+    /// `(message: :msg/key "template" :locale :fr)` becomes a data map and
+    /// `RegisterMessage`; without `:locale` the message is in the default
+    /// locale.
+    fn compile_message_decl(
+        &mut self,
+        elements: &[Ast],
+        span: Span,
+        code: &mut Bytecode,
+    ) -> Result<()> {
+        let [
+            _,
+            Ast::Keyword(key, _),
+            Ast::String(template, _),
+            options @ ..,
+        ] = elements
+        else {
+            return Err(self.error(
+                span,
+                "message: requires a key and a template: (message: :msg/key \"text\")",
+            ));
+        };
+
+        let mut map: LtMap<Value, Value> = LtMap::new();
+        let key_key = self.intern_keyword("key");
+        let key_val = self.intern_keyword(key);
+        map = map.insert(Value::Keyword(key_key), Value::Keyword(key_val));
+        let template_key = self.intern_keyword("template");
+        map = map.insert(
+            Value::Keyword(template_key),
+            Value::String(template.as_str().into()),
+        );
+        match options {
+            [] => {}
+            [Ast::Keyword(option, _), Ast::Keyword(locale, _)] if option == "locale" => {
+                let locale_key = self.intern_keyword("locale");
+                let locale_val = self.intern_keyword(locale);
+                map = map.insert(Value::Keyword(locale_key), Value::Keyword(locale_val));
+            }
+            _ => return Err(self.error(span, "message: only takes a :locale option")),
+        }
+
+        let idx = self.add_constant(Value::Map(map));
+        code.emit(Opcode::Const(idx));
+        code.emit(Opcode::RegisterMessage);
+
+        let nil_idx = self.add_constant(Value::Nil);
+        code.emit(Opcode::Const(nil_idx));
+
+        Ok(())
+    }
+
     /// Compiles a type: declaration.
     fn compile_type_decl(
         &mut self,
@@ -2812,6 +3773,7 @@ impl Compiler {
             .ok_or_else(|| self.error(span, "invalid rule: declaration"))?;
 
         let map = self.rule_decl_to_value(&decl)?;
+        self.rule_decls.push((decl, self.source.clone()));
         let idx = self.add_constant(map);
         code.emit(Opcode::Const(idx));
         code.emit(Opcode::RegisterRule);
@@ -2824,6 +3786,32 @@ impl Compiler {
         Ok(())
     }
 
+    /// Compiles a rule-group: declaration, registering each rule in the group.
+    fn compile_rule_group_decl(
+        &mut self,
+        elements: &[Ast],
+        span: Span,
+        code: &mut Bytecode,
+    ) -> Result<()> {
+        let ast = Ast::List(elements.to_vec(), span);
+        let decl = DeclarationAnalyzer::analyze_rule_group(&ast)?
+            .ok_or_else(|| self.error(span, "invalid rule-group: declaration"))?;
+
+        for rule in decl.rules {
+            let map = self.rule_decl_to_value(&rule)?;
+            self.rule_decls.push((rule, self.source.clone()));
+            let idx = self.add_constant(map);
+            code.emit(Opcode::Const(idx));
+            code.emit(Opcode::RegisterRule);
+            code.emit(Opcode::Pop);
+        }
+
+        let nil_idx = self.add_constant(Value::Nil);
+        code.emit(Opcode::Const(nil_idx));
+
+        Ok(())
+    }
+
     // =========================================================================
     // Declaration to Value conversions
     // =========================================================================
@@ -2910,6 +3898,11 @@ impl Compiler {
             Value::String(on_delete_str.into()),
         );
 
+        if decl.acyclic {
+            let acyclic_key = self.intern_keyword("acyclic");
+            map = map.insert(Value::Keyword(acyclic_key), Value::Bool(true));
+        }
+
         Ok(Value::Map(map))
     }
 
@@ -3053,6 +4046,18 @@ impl Compiler {
         let name_val = self.intern_keyword(&decl.name);
         map = map.insert(Value::Keyword(name_key), Value::Keyword(name_val));
 
+        let extends_key = self.intern_keyword("extends");
+        let extends: LtVec<Value> = decl
+            .extends
+            .iter()
+            .map(|ext| Value::Keyword(self.intern_keyword(ext)))
+            .collect();
+        map = map.insert(Value::Keyword(extends_key), Value::Vec(extends));
+
+        let pattern_key = self.intern_keyword("pattern");
+        let pattern_val = self.pattern_to_value(&decl.pattern)?;
+        map = map.insert(Value::Keyword(pattern_key), pattern_val);
+
         Ok(Value::Map(map))
     }
 
@@ -3190,8 +4195,7 @@ impl Compiler {
 
         // :handler - Vec of serialized AST
         let handler_key = self.intern_keyword("handler");
-        let handler_val: Result<Vec<_>> =
-            decl.handler.iter().map(|h| self.ast_to_value(h)).collect();
+        let handler_val: Result<Vec<_>> = decl.handler.iter().map(Self::ast_to_value).collect();
         map = map.insert(
             Value::Keyword(handler_key),
             Value::Vec(handler_val?.into_iter().collect()),
@@ -3215,14 +4219,14 @@ impl Compiler {
         // :guard - Option<Ast>, serialize as Nil if None
         let guard_key = self.intern_keyword("guard");
         let guard_val = match &precond.guard {
-            Some(ast) => self.ast_to_value(ast)?,
+            Some(ast) => Self::ast_to_value(ast)?,
             None => Value::Nil,
         };
         map = map.insert(Value::Keyword(guard_key), guard_val);
 
         // :message - Ast for failure message
         let message_key = self.intern_keyword("message");
-        let message_val = self.ast_to_value(&precond.message)?;
+        let message_val = Self::ast_to_value(&precond.message)?;
         map = map.insert(Value::Keyword(message_key), message_val);
 
         Ok(Value::Map(map))
@@ -3239,6 +4243,12 @@ impl Compiler {
         let name_val = self.intern_keyword(&decl.name);
         map = map.insert(Value::Keyword(name_key), Value::Keyword(name_val));
 
+        // :doc (only when set)
+        if let Some(doc) = &decl.doc {
+            let doc_key = self.intern_keyword("doc");
+            map = map.insert(Value::Keyword(doc_key), Value::String(doc.as_str().into()));
+        }
+
         // :salience
         let salience_key = self.intern_keyword("salience");
         map = map.insert(
@@ -3246,10 +4256,37 @@ impl Compiler {
             Value::Int(i64::from(decl.salience)),
         );
 
+        // :before / :after - rule names as keywords
+        for (key, names) in [("before", &decl.before), ("after", &decl.after)] {
+            let key = self.intern_keyword(key);
+            let names: Vec<_> = names
+                .iter()
+                .map(|name| Value::Keyword(self.intern_keyword(name)))
+                .collect();
+            map = map.insert(Value::Keyword(key), Value::Vec(names.into_iter().collect()));
+        }
+
+        // :group (only when set)
+        if let Some(group) = &decl.group {
+            let group_key = self.intern_keyword("group");
+            let group_val = self.intern_keyword(group);
+            map = map.insert(Value::Keyword(group_key), Value::Keyword(group_val));
+        }
+
         // :once
         let once_key = self.intern_keyword("once");
         map = map.insert(Value::Keyword(once_key), Value::Bool(decl.once));
 
+        // :refraction (only when not the default)
+        if decl.refraction != Refraction::default() {
+            let refraction_key = self.intern_keyword("refraction");
+            let refraction_val = self.intern_keyword(decl.refraction.name());
+            map = map.insert(
+                Value::Keyword(refraction_key),
+                Value::Keyword(refraction_val),
+            );
+        }
+
         // :enabled
         let enabled_key = self.intern_keyword("enabled");
         map = map.insert(Value::Keyword(enabled_key), Value::Bool(decl.enabled));
@@ -3261,7 +4298,7 @@ impl Compiler {
 
         // :guards - serialize each guard AST
         let guards_key = self.intern_keyword("guards");
-        let guards_val: Result<Vec<_>> = decl.guards.iter().map(|g| self.ast_to_value(g)).collect();
+        let guards_val: Result<Vec<_>> = decl.guards.iter().map(Self::ast_to_value).collect();
         map = map.insert(
             Value::Keyword(guards_key),
             Value::Vec(guards_val?.into_iter().collect()),
@@ -3269,8 +4306,7 @@ impl Compiler {
 
         // :effects - serialize each effect AST
         let effects_key = self.intern_keyword("effects");
-        let effects_val: Result<Vec<_>> =
-            decl.effects.iter().map(|e| self.ast_to_value(e)).collect();
+        let effects_val: Result<Vec<_>> = decl.effects.iter().map(Self::ast_to_value).collect();
         map = map.insert(
             Value::Keyword(effects_key),
             Value::Vec(effects_val?.into_iter().collect()),
@@ -3283,7 +4319,7 @@ impl Compiler {
             .iter()
             .map(|(name, ast)| {
                 let name_val = Value::String(name.as_str().into());
-                let ast_val = self.ast_to_value(ast)?;
+                let ast_val = Self::ast_to_value(ast)?;
                 Ok(Value::Vec(vec![name_val, ast_val].into_iter().collect()))
             })
             .collect();
@@ -3323,6 +4359,77 @@ impl Compiler {
             Value::Vec(negations_val?.into_iter().collect()),
         );
 
+        // :not-joins - each a map with :join-vars and :clauses
+        let not_joins_key = self.intern_keyword("not-joins");
+        let join_vars_key = self.intern_keyword("join-vars");
+        let mut not_joins = Vec::new();
+        for not_join in &pattern.not_joins {
+            let join_vars = not_join
+                .join_vars
+                .iter()
+                .map(|v| Value::String(v.as_str().into()))
+                .collect();
+            let clauses: Result<Vec<_>> = not_join
+                .clauses
+                .iter()
+                .map(|c| self.pattern_clause_to_value(c))
+                .collect();
+            let not_join_map = LtMap::new()
+                .insert(Value::Keyword(join_vars_key), Value::Vec(join_vars))
+                .insert(
+                    Value::Keyword(clauses_key),
+                    Value::Vec(clauses?.into_iter().collect()),
+                );
+            not_joins.push(Value::Map(not_join_map));
+        }
+        map = map.insert(
+            Value::Keyword(not_joins_key),
+            Value::Vec(not_joins.into_iter().collect()),
+        );
+
+        // :disjunctions - each a vec of branches, each a vec of clauses
+        let disjunctions_key = self.intern_keyword("disjunctions");
+        let mut disjunctions = Vec::new();
+        for disjunction in &pattern.disjunctions {
+            let mut branches = Vec::new();
+            for branch in &disjunction.branches {
+                let clauses: Result<Vec<_>> = branch
+                    .iter()
+                    .map(|c| self.pattern_clause_to_value(c))
+                    .collect();
+                branches.push(Value::Vec(clauses?.into_iter().collect()));
+            }
+            disjunctions.push(Value::Vec(branches.into_iter().collect()));
+        }
+        map = map.insert(
+            Value::Keyword(disjunctions_key),
+            Value::Vec(disjunctions.into_iter().collect()),
+        );
+
+        // :predicates - each a map with :expr and :vars
+        let predicates_key = self.intern_keyword("predicates");
+        let expr_key = self.intern_keyword("expr");
+        let vars_key = self.intern_keyword("vars");
+        let mut predicates = Vec::new();
+        for predicate in &pattern.predicates {
+            let vars = predicate
+                .vars
+                .iter()
+                .map(|v| Value::String(v.as_str().into()))
+                .collect();
+            let predicate_map = LtMap::new()
+                .insert(
+                    Value::Keyword(expr_key),
+                    Self::ast_to_value(&predicate.expr)?,
+                )
+                .insert(Value::Keyword(vars_key), Value::Vec(vars));
+            predicates.push(Value::Map(predicate_map));
+        }
+        map = map.insert(
+            Value::Keyword(predicates_key),
+            Value::Vec(predicates.into_iter().collect()),
+        );
+
         Ok(Value::Map(map))
     }
 
@@ -3382,7 +4489,7 @@ impl Compiler {
                 map = map.insert(Value::Keyword(type_key), Value::Keyword(literal_type));
 
                 let lit_value_key = self.intern_keyword("lit-value");
-                map = map.insert(Value::Keyword(lit_value_key), self.ast_to_value(ast)?);
+                map = map.insert(Value::Keyword(lit_value_key), Self::ast_to_value(ast)?);
             }
         }
 
@@ -3410,14 +4517,14 @@ impl Compiler {
     /// Compiles a quoted expression (as data, not evaluated).
     fn compile_quoted(&mut self, ast: &Ast, code: &mut Bytecode) -> Result<()> {
         // Convert AST to runtime value
-        let value = self.ast_to_value(ast)?;
+        let value = Self::ast_to_value(ast)?;
         let idx = self.add_constant(value);
         code.emit(Opcode::Const(idx));
         Ok(())
     }
 
     /// Converts an AST node to a runtime Value.
-    fn ast_to_value(&self, ast: &Ast) -> Result<Value> {
+    fn ast_to_value(ast: &Ast) -> Result<Value> {
         Ok(match ast {
             Ast::Nil(_) => Value::Nil,
             Ast::Bool(b, _) => Value::Bool(*b),
@@ -3427,27 +4534,27 @@ impl Compiler {
             Ast::Symbol(s, _) => Value::String(format!("'{s}").into()),
             Ast::Keyword(s, _) => Value::String(format!(":{s}").into()),
             Ast::List(elements, _) => {
-                let items: Result<Vec<_>> = elements.iter().map(|e| self.ast_to_value(e)).collect();
+                let items: Result<Vec<_>> = elements.iter().map(Self::ast_to_value).collect();
                 Value::List(items?.into_iter().collect())
             }
             Ast::Vector(elements, _) => {
-                let items: Result<Vec<_>> = elements.iter().map(|e| self.ast_to_value(e)).collect();
+                let items: Result<Vec<_>> = elements.iter().map(Self::ast_to_value).collect();
                 Value::Vec(items?.into_iter().collect())
             }
             Ast::Set(elements, _) => {
-                let items: Result<Vec<_>> = elements.iter().map(|e| self.ast_to_value(e)).collect();
+                let items: Result<Vec<_>> = elements.iter().map(Self::ast_to_value).collect();
                 Value::Set(items?.into_iter().collect())
             }
             Ast::Map(entries, _) => {
                 let mut map = longtable_foundation::LtMap::new();
                 for (k, v) in entries {
-                    let key = self.ast_to_value(k)?;
-                    let val = self.ast_to_value(v)?;
+                    let key = Self::ast_to_value(k)?;
+                    let val = Self::ast_to_value(v)?;
                     map = map.insert(key, val);
                 }
                 Value::Map(map)
             }
-            Ast::Quote(inner, _) => self.ast_to_value(inner)?,
+            Ast::Quote(inner, _) | Ast::SyntaxQuote(inner, _) => Self::ast_to_value(inner)?,
             Ast::Unquote(_, span) => {
                 return Err(Error::new(ErrorKind::ParseError {
                     message: "unquote outside syntax-quote".to_string(),
@@ -3464,10 +4571,9 @@ impl Compiler {
                     context: String::new(),
                 }));
             }
-            Ast::SyntaxQuote(inner, _) => self.ast_to_value(inner)?,
             Ast::Tagged(tag, inner, _) => {
                 // Store tagged values as a vector [tag, value]
-                let inner_val = self.ast_to_value(inner)?;
+                let inner_val = Self::ast_to_value(inner)?;
                 Value::Vec(
                     vec![Value::String(format!("#{tag}").into()), inner_val]
                         .into_iter()
@@ -3550,6 +4656,36 @@ impl Compiler {
     }
 }
 
+/// The error for naming a field a record doesn't declare.
+fn unknown_field(record: &RecordDecl, field: &str) -> String {
+    let fields: Vec<String> = record
+        .fields
+        .iter()
+        .map(|f| format!(":{}", f.name))
+        .collect();
+    format!(
+        "record {} has no field :{field} (fields: {})",
+        record.name,
+        fields.join(" ")
+    )
+}
+
+/// Returns the type of a literal's value, if `ast` is a literal.
+fn literal_type(ast: &Ast) -> Option<Type> {
+    Some(match ast {
+        Ast::Nil(_) => Type::Nil,
+        Ast::Bool(..) => Type::Bool,
+        Ast::Int(..) => Type::Int,
+        Ast::Float(..) => Type::Float,
+        Ast::String(..) => Type::String,
+        Ast::Keyword(..) => Type::Keyword,
+        Ast::Vector(..) => Type::vec(Type::Any),
+        Ast::Set(..) => Type::set(Type::Any),
+        Ast::Map(..) => Type::map(Type::Any, Type::Any),
+        _ => return None,
+    })
+}
+
 /// Compiles source code to a program with stdlib macros.
 pub fn compile(source: &str) -> Result<CompiledProgram> {
     let ast = crate::parser::parse(source)?;
@@ -3883,6 +5019,33 @@ mod tests {
         assert!(has_qualified, "Should resolve alias to qualified name");
     }
 
+    #[test]
+    fn compile_auto_qualified_keyword() {
+        use crate::namespace::NamespaceContext;
+
+        let mut ns_ctx = NamespaceContext::new();
+        ns_ctx.add_alias("combat", "my.game.combat");
+
+        let mut compiler = Compiler::with_namespace_context(ns_ctx);
+        let prog = compiler
+            .compile(&crate::parse("[::combat/attack ::score]").unwrap())
+            .unwrap();
+        let keywords: Vec<_> = prog
+            .constants
+            .iter()
+            .filter_map(|c| match c {
+                Value::String(s) => Some(s.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(keywords, [":my.game.combat/attack", ":user/score"]);
+
+        let err = compiler
+            .compile(&crate::parse("::items/sword").unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown namespace alias `items`"));
+    }
+
     #[test]
     fn compile_with_referred_symbol() {
         use crate::namespace::NamespaceContext;
@@ -4065,6 +5228,74 @@ mod tests {
         assert!(prog.code.ops.iter().any(|op| matches!(op, Opcode::Spawn)));
     }
 
+    #[test]
+    fn compile_spawn_many() {
+        let prog = compile_test("(spawn-many! [{:name \"a\"} {:name \"b\"}])");
+        assert!(
+            prog.code
+                .ops
+                .iter()
+                .any(|op| matches!(op, Opcode::SpawnMany))
+        );
+        assert!(compile("(spawn-many!)").is_err());
+    }
+
+    #[test]
+    fn compile_emit() {
+        let prog = compile_test("(emit! :event/door-opened {:door 1})");
+        assert!(prog.code.ops.iter().any(|op| matches!(op, Opcode::Emit)));
+        assert!(compile("(emit!)").is_err());
+    }
+
+    #[test]
+    fn compile_schedule() {
+        let prog = compile_test("(let [x 1] (schedule! :in 5 :then [(emit! :event/tick {:x x})]))");
+        assert!(
+            prog.code
+                .ops
+                .iter()
+                .any(|op| matches!(op, Opcode::Schedule))
+        );
+        assert!(
+            prog.code
+                .ops
+                .iter()
+                .any(|op| matches!(op, Opcode::MakeClosure(..)))
+        );
+        assert!(compile("(schedule! :in 5)").is_err());
+        assert!(compile("(schedule! :in 5 :then (emit! :e))").is_err());
+    }
+
+    #[test]
+    fn compile_every_and_fuse() {
+        let prog = compile_test("(every 3 :on 7 :then [(emit! :event/wander {:npc self})])");
+        assert!(
+            prog.code
+                .ops
+                .iter()
+                .any(|op| matches!(op, Opcode::ScheduleEvery))
+        );
+        let prog = compile_test("(fuse 10 :then [(emit! :event/boom nil)])");
+        assert!(
+            prog.code
+                .ops
+                .iter()
+                .any(|op| matches!(op, Opcode::Schedule))
+        );
+        assert!(compile("(every 3)").is_err());
+        assert!(compile("(fuse :then [(emit! :e nil)])").is_err());
+    }
+
+    #[test]
+    fn compile_session_command() {
+        let prog = compile_test("(fn [n] (tick! n))");
+        let command = prog.functions[0].code.ops.iter().find_map(|op| match op {
+            Opcode::Command(name, argc) => Some((prog.constants[*name as usize].clone(), *argc)),
+            _ => None,
+        });
+        assert_eq!(command, Some((Value::from("tick!"), 1)));
+    }
+
     #[test]
     fn compile_destroy() {
         let prog = compile_test("(destroy! (entity-ref 1 0))");
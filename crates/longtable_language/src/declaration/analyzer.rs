@@ -5,15 +5,17 @@
 use crate::ast::Ast;
 use crate::namespace::{LoadDecl, NamespaceDecl, NamespaceName, RequireSpec};
 use crate::span::Span;
+use crate::visitor::{AstVisitor, walk_ast};
 use longtable_foundation::{Error, ErrorKind, Result};
 
 use super::Declaration;
 use super::types::{
     ActionDecl, AdverbDecl, Cardinality, CommandDecl, ComponentDecl, ConstraintDecl,
-    ConstraintViolation, DerivedDecl, DirectionDecl, FieldDecl, LinkDecl, NounTypeDecl,
-    OnTargetDelete, OnViolation, OrderDirection, Pattern, PatternClause, PatternValue,
-    Precondition, PrepositionDecl, PronounDecl, PronounGender, PronounNumber, QueryDecl,
-    RelationshipDecl, RuleDecl, ScopeDecl, SpawnDecl, StorageKind, SyntaxElement, VerbDecl,
+    ConstraintViolation, DerivedDecl, DirectionDecl, Disjunction, FieldDecl, LinkDecl, NotJoin,
+    NounTypeDecl, OnTargetDelete, OnViolation, OrderDirection, Pattern, PatternClause,
+    PatternPredicate, PatternValue, Precondition, PrepositionDecl, PronounDecl, PronounGender,
+    PronounNumber, QueryDecl, RecordDecl, Refraction, RelationshipDecl, RuleDecl, RuleGroupDecl,
+    ScopeDecl, SpawnDecl, StorageKind, SyntaxElement, VerbDecl,
 };
 
 /// Analyzes AST and extracts typed declarations.
@@ -36,6 +38,9 @@ impl DeclarationAnalyzer {
         if let Some(rule) = Self::analyze_rule(ast)? {
             return Ok(Some(Declaration::Rule(rule)));
         }
+        if let Some(group) = Self::analyze_rule_group(ast)? {
+            return Ok(Some(Declaration::RuleGroup(group)));
+        }
         if let Some(derived) = Self::analyze_derived(ast)? {
             return Ok(Some(Declaration::Derived(derived)));
         }
@@ -136,8 +141,14 @@ impl DeclarationAnalyzer {
 
         let mut rule = RuleDecl::new(name, span);
 
-        // Parse keyword arguments
+        // Optional docstring after the name
         let mut i = 2;
+        if let Some(Ast::String(doc, _)) = elements.get(i) {
+            rule.doc = Some(doc.clone());
+            i += 1;
+        }
+
+        // Parse keyword arguments
         while i < elements.len() {
             let key = match &elements[i] {
                 Ast::Keyword(k, _) => k.as_str(),
@@ -184,6 +195,28 @@ impl DeclarationAnalyzer {
                         };
                     }
                 }
+                "before" => {
+                    rule.before = Self::analyze_rule_names("before", value)?;
+                }
+                "after" => {
+                    rule.after = Self::analyze_rule_names("after", value)?;
+                }
+                "group" => {
+                    rule.group = match value {
+                        Ast::Symbol(g, _) | Ast::Keyword(g, _) => Some(g.clone()),
+                        other => {
+                            return Err(Error::new(ErrorKind::ParseError {
+                                message: format!(
+                                    ":group must be a group name, got {}",
+                                    other.type_name()
+                                ),
+                                line: other.span().line,
+                                column: other.span().column,
+                                context: String::new(),
+                            }));
+                        }
+                    };
+                }
                 "once" => {
                     rule.once = match value {
                         Ast::Bool(b, _) => *b,
@@ -200,6 +233,32 @@ impl DeclarationAnalyzer {
                         }
                     };
                 }
+                "refraction" => {
+                    rule.refraction = match value {
+                        Ast::Keyword(k, _) => Refraction::from_name(k).ok_or_else(|| {
+                            Error::new(ErrorKind::ParseError {
+                                message: format!(
+                                    "invalid refraction :{k} (expected :every-tick, \
+                                     :on-change, or :once-per-entity)"
+                                ),
+                                line: value.span().line,
+                                column: value.span().column,
+                                context: String::new(),
+                            })
+                        })?,
+                        other => {
+                            return Err(Error::new(ErrorKind::ParseError {
+                                message: format!(
+                                    ":refraction must be a keyword, got {}",
+                                    other.type_name()
+                                ),
+                                line: other.span().line,
+                                column: other.span().column,
+                                context: String::new(),
+                            }));
+                        }
+                    };
+                }
                 "enabled" => {
                     rule.enabled = match value {
                         Ast::Bool(b, _) => *b,
@@ -258,9 +317,19 @@ impl DeclarationAnalyzer {
         };
 
         let mut pattern = Pattern::new();
+        // Not-joins whose join variables are inferred from the positive clauses
+        let mut implicit_joins = Vec::new();
 
         for p in patterns {
             match p {
+                // Predicate clause: [(> ?hp 10)]
+                Ast::Vector(clause, span) if matches!(clause.as_slice(), [Ast::List(..)]) => {
+                    pattern.predicates.push(PatternPredicate {
+                        expr: clause[0].clone(),
+                        vars: pattern_variables(&clause[0]),
+                        span: *span,
+                    });
+                }
                 // Regular pattern clause: [?e :component ?v]
                 Ast::Vector(clause, span) => {
                     let pc = Self::analyze_pattern_clause(clause, *span)?;
@@ -273,76 +342,71 @@ impl DeclarationAnalyzer {
                     }
                     match &elements[0] {
                         Ast::Symbol(s, _) if s == "not" => {
-                            if elements.len() != 2 {
+                            let clauses = Self::analyze_negated_clauses(&elements[1..], *span)?;
+                            if let [clause] = clauses.as_slice() {
+                                pattern.negations.push(clause.clone());
+                            } else {
+                                // Joined on whichever variables the positive
+                                // clauses bind, once they are all known
+                                pattern.not_joins.push(NotJoin {
+                                    join_vars: Vec::new(),
+                                    clauses,
+                                    span: *span,
+                                });
+                                implicit_joins.push(pattern.not_joins.len() - 1);
+                            }
+                        }
+                        Ast::Symbol(s, _) if s == "not-join" => {
+                            let Some(Ast::Vector(vars, vars_span)) = elements.get(1) else {
                                 return Err(Error::new(ErrorKind::ParseError {
-                                    message: "not requires exactly one pattern".to_string(),
+                                    message: "not-join requires a vector of ?variables, then patterns: (not-join [?e] [?e :component] ...)".to_string(),
                                     line: span.line,
                                     column: span.column,
                                     context: String::new(),
                                 }));
-                            }
-                            match &elements[1] {
-                                Ast::Vector(clause, inner_span) => {
-                                    let pc = Self::analyze_pattern_clause(clause, *inner_span)?;
-                                    pattern.negations.push(pc);
-                                }
-                                Ast::List(inner_elements, _) => {
-                                    // Handle (not (exists ...)) or (not [pattern])
-                                    // For (exists ...), extract all patterns and add as negations
-                                    if let Some(Ast::Symbol(sym, _)) = inner_elements.first() {
-                                        if sym == "exists" {
-                                            // (not (exists [...] [...] ...)) - no entity exists matching all patterns
-                                            for elem in inner_elements.iter().skip(1) {
-                                                if let Ast::Vector(clause, inner_span) = elem {
-                                                    let pc = Self::analyze_pattern_clause(
-                                                        clause,
-                                                        *inner_span,
-                                                    )?;
-                                                    pattern.negations.push(pc);
-                                                }
-                                            }
-                                        }
+                            };
+                            let join_vars = vars
+                                .iter()
+                                .map(|var| match var {
+                                    Ast::Symbol(s, _) if s.starts_with('?') => {
+                                        Ok(s[1..].to_string())
                                     }
-                                }
-                                other => {
-                                    return Err(Error::new(ErrorKind::ParseError {
+                                    other => Err(Error::new(ErrorKind::ParseError {
                                         message: format!(
-                                            "not requires a pattern vector or exists form, got {}",
+                                            "not-join variables must be ?variables, got {}",
                                             other.type_name()
                                         ),
-                                        line: other.span().line,
-                                        column: other.span().column,
+                                        line: vars_span.line,
+                                        column: vars_span.column,
                                         context: String::new(),
-                                    }));
-                                }
-                            }
+                                    })),
+                                })
+                                .collect::<Result<Vec<_>>>()?;
+                            let clauses = Self::analyze_negated_clauses(&elements[2..], *span)?;
+                            pattern.not_joins.push(NotJoin {
+                                join_vars,
+                                clauses,
+                                span: *span,
+                            });
                         }
                         Ast::Symbol(s, _) if s == "or" => {
-                            // Disjunction: (or [pattern1] [pattern2] ...)
-                            // For now, add all alternative patterns to the main pattern
-                            // (this is a simplification - proper disjunction would need
-                            // runtime support to match ANY of the patterns)
-                            for elem in elements.iter().skip(1) {
-                                match elem {
-                                    Ast::Vector(clause, inner_span) => {
-                                        let pc = Self::analyze_pattern_clause(clause, *inner_span)?;
-                                        pattern.clauses.push(pc);
-                                    }
-                                    Ast::List(inner_elements, _) => {
-                                        // Nested (and ...) or other form - try to extract patterns
-                                        for inner_elem in inner_elements {
-                                            if let Ast::Vector(clause, inner_span) = inner_elem {
-                                                let pc = Self::analyze_pattern_clause(
-                                                    clause,
-                                                    *inner_span,
-                                                )?;
-                                                pattern.clauses.push(pc);
-                                            }
-                                        }
-                                    }
-                                    _ => {}
-                                }
+                            // Disjunction: (or [pattern1] (and [pattern2] [pattern3]) ...)
+                            let branches = elements[1..]
+                                .iter()
+                                .map(Self::analyze_or_branch)
+                                .collect::<Result<Vec<_>>>()?;
+                            if branches.is_empty() {
+                                return Err(Error::new(ErrorKind::ParseError {
+                                    message: "or requires at least one pattern".to_string(),
+                                    line: span.line,
+                                    column: span.column,
+                                    context: String::new(),
+                                }));
                             }
+                            pattern.disjunctions.push(Disjunction {
+                                branches,
+                                span: *span,
+                            });
                         }
                         Ast::Symbol(s, _) if s == "and" => {
                             // Conjunction: (and [pattern1] [pattern2] ...) or (and ?var [pattern] ...)
@@ -384,9 +448,120 @@ impl DeclarationAnalyzer {
             }
         }
 
+        let bound: Vec<String> = pattern
+            .bound_variables()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        for &i in &implicit_joins {
+            let not_join = &mut pattern.not_joins[i];
+            let mut join_vars: Vec<String> = Vec::new();
+            for var in not_join.clauses.iter().flat_map(PatternClause::variables) {
+                if bound.iter().any(|b| b == var) && !join_vars.iter().any(|v| v == var) {
+                    join_vars.push(var.to_string());
+                }
+            }
+            not_join.join_vars = join_vars;
+        }
+
         Ok(pattern)
     }
 
+    /// Analyzes one branch of an `(or ...)` form: a pattern vector, or an
+    /// `(and ...)` or `(exists ...)` form holding several. As in a top-level
+    /// `(and ?var ...)`, a bare variable only names what the clauses bind;
+    /// on its own it's a branch that always matches.
+    fn analyze_or_branch(branch: &Ast) -> Result<Vec<PatternClause>> {
+        let invalid = |other: &Ast| {
+            Error::new(ErrorKind::ParseError {
+                message: format!(
+                    "or branches must be pattern vectors or (and ...) forms, got {}",
+                    other.type_name()
+                ),
+                line: other.span().line,
+                column: other.span().column,
+                context: String::new(),
+            })
+        };
+        match branch {
+            Ast::Vector(clause, span) => Ok(vec![Self::analyze_pattern_clause(clause, *span)?]),
+            // (or ?weapon [...]): a variable the action supplies matches as is
+            Ast::Symbol(s, _) if s.starts_with('?') => Ok(Vec::new()),
+            Ast::List(elements, span) => {
+                let head = match elements.first() {
+                    Some(Ast::Symbol(s, _)) if s == "and" || s == "exists" => s,
+                    _ => return Err(invalid(branch)),
+                };
+                if elements.len() < 2 {
+                    return Err(Error::new(ErrorKind::ParseError {
+                        message: format!("{head} requires at least one pattern"),
+                        line: span.line,
+                        column: span.column,
+                        context: String::new(),
+                    }));
+                }
+                let mut clauses = Vec::new();
+                for element in &elements[1..] {
+                    match element {
+                        Ast::Symbol(s, _) if s.starts_with('?') => {}
+                        Ast::Vector(..) | Ast::List(..) => {
+                            clauses.extend(Self::analyze_or_branch(element)?);
+                        }
+                        other => {
+                            return Err(Error::new(ErrorKind::ParseError {
+                                message: format!(
+                                    "{head} inside or requires pattern vectors, got {}",
+                                    other.type_name()
+                                ),
+                                line: other.span().line,
+                                column: other.span().column,
+                                context: String::new(),
+                            }));
+                        }
+                    }
+                }
+                Ok(clauses)
+            }
+            other => Err(invalid(other)),
+        }
+    }
+
+    /// Analyzes the clauses of a `(not ...)` or `(not-join [...] ...)` form:
+    /// pattern vectors, or a single `(exists ...)` form holding them.
+    fn analyze_negated_clauses(elements: &[Ast], span: Span) -> Result<Vec<PatternClause>> {
+        let elements = match elements {
+            [Ast::List(inner, _)] if matches!(inner.first(), Some(Ast::Symbol(s, _)) if s == "exists") => {
+                &inner[1..]
+            }
+            _ => elements,
+        };
+        if elements.is_empty() {
+            return Err(Error::new(ErrorKind::ParseError {
+                message: "not requires at least one pattern".to_string(),
+                line: span.line,
+                column: span.column,
+                context: String::new(),
+            }));
+        }
+        elements
+            .iter()
+            .map(|element| match element {
+                Ast::Vector(clause, inner_span) => {
+                    Self::analyze_pattern_clause(clause, *inner_span)
+                }
+                other => Err(Error::new(ErrorKind::ParseError {
+                    message: format!(
+                        "not requires pattern vectors or an exists form, got {}",
+                        other.type_name()
+                    ),
+                    line: other.span().line,
+                    column: other.span().column,
+                    context: String::new(),
+                })),
+            })
+            .collect()
+    }
+
     /// Analyze a single pattern clause like [?e :component ?value].
     ///
     /// Also supports global patterns like [:component value] where the first element
@@ -531,6 +706,90 @@ impl DeclarationAnalyzer {
         Ok(result)
     }
 
+    /// Analyze a top-level form and return a rule group if it's a rule-group declaration.
+    pub fn analyze_rule_group(ast: &Ast) -> Result<Option<RuleGroupDecl>> {
+        let (elements, span) = match ast {
+            Ast::List(elements, span) if !elements.is_empty() => (elements, *span),
+            _ => return Ok(None),
+        };
+
+        // Check for (rule-group: name ...) form
+        match &elements[0] {
+            Ast::Symbol(s, _) if s == "rule-group:" => {}
+            _ => return Ok(None),
+        }
+
+        let name = match elements.get(1) {
+            Some(Ast::Symbol(s, _)) => s.clone(),
+            Some(other) => {
+                return Err(Error::new(ErrorKind::ParseError {
+                    message: format!(
+                        "rule-group name must be a symbol, got {}",
+                        other.type_name()
+                    ),
+                    line: other.span().line,
+                    column: other.span().column,
+                    context: String::new(),
+                }));
+            }
+            None => {
+                return Err(Error::new(ErrorKind::ParseError {
+                    message: "rule-group: requires a name".to_string(),
+                    line: span.line,
+                    column: span.column,
+                    context: String::new(),
+                }));
+            }
+        };
+
+        let mut rules = Vec::new();
+        for element in &elements[2..] {
+            let Some(mut rule) = Self::analyze_rule(element)? else {
+                return Err(Error::new(ErrorKind::ParseError {
+                    message: format!("rule-group {name} may only contain rule: declarations"),
+                    line: element.span().line,
+                    column: element.span().column,
+                    context: String::new(),
+                }));
+            };
+            rule.group = Some(name.clone());
+            rules.push(rule);
+        }
+
+        Ok(Some(RuleGroupDecl { name, rules, span }))
+    }
+
+    /// Analyze a :before or :after clause into a list of rule names.
+    fn analyze_rule_names(key: &str, ast: &Ast) -> Result<Vec<String>> {
+        let elements = match ast {
+            Ast::Vector(elements, _) => elements,
+            other => {
+                return Err(Error::new(ErrorKind::ParseError {
+                    message: format!(":{key} must be a vector, got {}", other.type_name()),
+                    line: other.span().line,
+                    column: other.span().column,
+                    context: String::new(),
+                }));
+            }
+        };
+
+        elements
+            .iter()
+            .map(|element| match element {
+                Ast::Symbol(name, _) | Ast::Keyword(name, _) => Ok(name.clone()),
+                other => Err(Error::new(ErrorKind::ParseError {
+                    message: format!(
+                        ":{key} entries must be rule names, got {}",
+                        other.type_name()
+                    ),
+                    line: other.span().line,
+                    column: other.span().column,
+                    context: String::new(),
+                })),
+            })
+            .collect()
+    }
+
     /// Analyze a :guard clause.
     pub(crate) fn analyze_guard_clause(ast: &Ast) -> Result<Vec<Ast>> {
         match ast {
@@ -661,7 +920,16 @@ impl DeclarationAnalyzer {
         }
 
         // Full form: (component: health :current :int :max :int :default 100)
-        let mut i = 2;
+        component.fields = Self::analyze_fields(&elements[2..], span)?;
+
+        Ok(Some(component))
+    }
+
+    /// Parses `:name :type [:default value]` field specs, as shared by
+    /// `component:` and `defrecord`.
+    fn analyze_fields(elements: &[Ast], span: Span) -> Result<Vec<FieldDecl>> {
+        let mut fields = Vec::new();
+        let mut i = 0;
         while i < elements.len() {
             // Field name
             let field_name = match &elements[i] {
@@ -727,10 +995,55 @@ impl DeclarationAnalyzer {
                 }
             }
 
-            component.fields.push(field);
+            fields.push(field);
         }
 
-        Ok(Some(component))
+        Ok(fields)
+    }
+
+    /// Analyzes a `(defrecord name :field :type ...)` form.
+    ///
+    /// Fields are declared as in `component:`, with optional `:default`s.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the form is malformed or declares a field twice.
+    pub fn analyze_record(ast: &Ast) -> Result<Option<RecordDecl>> {
+        let Ast::List(elements, span) = ast else {
+            return Ok(None);
+        };
+        match elements.first() {
+            Some(Ast::Symbol(s, _)) if s == "defrecord" => {}
+            _ => return Ok(None),
+        }
+        let name = match elements.get(1) {
+            Some(Ast::Symbol(s, _)) => s.clone(),
+            other => {
+                let at = other.map_or(*span, Ast::span);
+                return Err(Error::new(ErrorKind::ParseError {
+                    message: "defrecord requires a symbol name".to_string(),
+                    line: at.line,
+                    column: at.column,
+                    context: String::new(),
+                }));
+            }
+        };
+        let fields = Self::analyze_fields(&elements[2..], *span)?;
+        for (i, field) in fields.iter().enumerate() {
+            if fields[..i].iter().any(|f| f.name == field.name) {
+                return Err(Error::new(ErrorKind::ParseError {
+                    message: format!("record {name} declares :{} twice", field.name),
+                    line: field.span.line,
+                    column: field.span.column,
+                    context: String::new(),
+                }));
+            }
+        }
+        Ok(Some(RecordDecl {
+            name,
+            fields,
+            span: *span,
+        }))
     }
 
     /// Check if a keyword is a type name.
@@ -744,6 +1057,8 @@ impl DeclarationAnalyzer {
                 | "keyword"
                 | "symbol"
                 | "entity-ref"
+                | "instant"
+                | "duration"
                 | "map"
                 | "vec"
                 | "set"
@@ -959,6 +1274,22 @@ impl DeclarationAnalyzer {
                         }
                     };
                 }
+                "acyclic" => {
+                    rel.acyclic = match value {
+                        Ast::Bool(b, _) => *b,
+                        other => {
+                            return Err(Error::new(ErrorKind::ParseError {
+                                message: format!(
+                                    ":acyclic must be a boolean, got {}",
+                                    other.type_name()
+                                ),
+                                line: other.span().line,
+                                column: other.span().column,
+                                context: String::new(),
+                            }));
+                        }
+                    };
+                }
                 "attributes" => {
                     rel.attributes = Self::analyze_attribute_list(value)?;
                 }
@@ -1324,6 +1655,7 @@ impl DeclarationAnalyzer {
                         Ast::Keyword(k, _) => match k.as_str() {
                             "rollback" => ConstraintViolation::Rollback,
                             "warn" => ConstraintViolation::Warn,
+                            "score" => ConstraintViolation::Score,
                             other => {
                                 return Err(Error::new(ErrorKind::ParseError {
                                     message: format!("invalid on-violation :{other}"),
@@ -1346,6 +1678,9 @@ impl DeclarationAnalyzer {
                         }
                     };
                 }
+                "penalty" => {
+                    constraint.penalty = Some(value.clone());
+                }
                 other => {
                     return Err(Error::new(ErrorKind::ParseError {
                         message: format!("unknown constraint clause :{other}"),
@@ -1467,6 +1802,9 @@ impl DeclarationAnalyzer {
                 "return" => {
                     query.return_expr = Some(value.clone());
                 }
+                "suppress" => {
+                    query.suppress = Self::analyze_suppress_clause(value)?;
+                }
                 other => {
                     return Err(Error::new(ErrorKind::ParseError {
                         message: format!("unknown query clause :{other}"),
@@ -1481,6 +1819,37 @@ impl DeclarationAnalyzer {
         Ok(Some(query))
     }
 
+    /// Analyze a :suppress clause: a vector of warning keywords.
+    fn analyze_suppress_clause(ast: &Ast) -> Result<Vec<String>> {
+        let codes = match ast {
+            Ast::Vector(elements, _) => elements,
+            other => {
+                return Err(Error::new(ErrorKind::ParseError {
+                    message: format!(":suppress must be a vector, got {}", other.type_name()),
+                    line: other.span().line,
+                    column: other.span().column,
+                    context: String::new(),
+                }));
+            }
+        };
+
+        codes
+            .iter()
+            .map(|code| match code {
+                Ast::Keyword(k, _) => Ok(k.clone()),
+                other => Err(Error::new(ErrorKind::ParseError {
+                    message: format!(
+                        ":suppress entries must be keywords, got {}",
+                        other.type_name()
+                    ),
+                    line: other.span().line,
+                    column: other.span().column,
+                    context: String::new(),
+                })),
+            })
+            .collect()
+    }
+
     /// Analyze a :group-by clause.
     fn analyze_group_by_clause(ast: &Ast) -> Result<Vec<String>> {
         let vars = match ast {
@@ -1691,7 +2060,11 @@ impl DeclarationAnalyzer {
     }
 
     /// Analyze a single require spec like [game.core :as core] or [game.utils :refer [foo bar]].
-    fn analyze_require_spec(ast: &Ast) -> Result<RequireSpec> {
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the spec is malformed.
+    pub fn analyze_require_spec(ast: &Ast) -> Result<RequireSpec> {
         let elements = match ast {
             Ast::Vector(elements, _) => elements,
             other => {
@@ -3330,3 +3703,21 @@ impl DeclarationAnalyzer {
         Ok(result)
     }
 }
+
+/// Returns the `?variables` an expression mentions, without the `?`, in the
+/// order they first appear.
+fn pattern_variables(expr: &Ast) -> Vec<String> {
+    struct Variables(Vec<String>);
+    impl AstVisitor for Variables {
+        fn visit_symbol(&mut self, name: &str, _span: Span) {
+            if let Some(var) = name.strip_prefix('?') {
+                if !var.is_empty() && !self.0.iter().any(|v| v == var) {
+                    self.0.push(var.to_string());
+                }
+            }
+        }
+    }
+    let mut variables = Variables(Vec::new());
+    walk_ast(&mut variables, expr);
+    variables.0
+}
@@ -19,10 +19,11 @@ mod tests;
 // Re-export types
 pub use types::{
     ActionDecl, AdverbDecl, Cardinality, CommandDecl, ComponentDecl, ConstraintDecl,
-    ConstraintViolation, DerivedDecl, DirectionDecl, FieldDecl, LinkDecl, NounTypeDecl,
-    OnTargetDelete, OnViolation, OrderDirection, Pattern, PatternClause, PatternValue,
-    Precondition, PrepositionDecl, PronounDecl, PronounGender, PronounNumber, QueryDecl,
-    RelationshipDecl, RuleDecl, ScopeDecl, SpawnDecl, StorageKind, SyntaxElement, VerbDecl,
+    ConstraintViolation, DerivedDecl, DirectionDecl, Disjunction, FieldDecl, LinkDecl, NotJoin,
+    NounTypeDecl, OnTargetDelete, OnViolation, OrderDirection, Pattern, PatternClause,
+    PatternPredicate, PatternValue, Precondition, PrepositionDecl, PronounDecl, PronounGender,
+    PronounNumber, QueryDecl, RecordDecl, Refraction, RelationshipDecl, RuleDecl, RuleGroupDecl,
+    ScopeDecl, SpawnDecl, StorageKind, SyntaxElement, VerbDecl,
 };
 
 // Re-export analyzer
@@ -39,6 +40,8 @@ pub enum Declaration {
     Relationship(RelationshipDecl),
     /// A rule declaration.
     Rule(RuleDecl),
+    /// A rule group declaration.
+    RuleGroup(RuleGroupDecl),
     /// A derived component declaration.
     Derived(DerivedDecl),
     /// A constraint declaration.
@@ -36,6 +36,20 @@ fn analyze_simple_rule() {
     assert_eq!(rule.effects.len(), 1);
 }
 
+#[test]
+fn analyze_rule_with_docstring() {
+    let ast = parse(
+        r#"(rule: regen
+             "Heals a little every tick."
+             :where [[?e :health ?hp]]
+             :then [])"#,
+    );
+
+    let rule = DeclarationAnalyzer::analyze_rule(&ast).unwrap().unwrap();
+    assert_eq!(rule.doc.as_deref(), Some("Heals a little every tick."));
+    assert_eq!(rule.pattern.clauses.len(), 1);
+}
+
 #[test]
 fn analyze_rule_with_options() {
     let ast = parse(
@@ -58,6 +72,73 @@ fn analyze_rule_with_options() {
     }
 }
 
+#[test]
+fn analyze_rule_with_refraction() {
+    let ast = parse("(rule: greet :refraction :once-per-entity :where [[?e :npc]] :then [])");
+    let rule = DeclarationAnalyzer::analyze_rule(&ast).unwrap().unwrap();
+    assert_eq!(rule.refraction, Refraction::OncePerEntity);
+
+    let default = parse("(rule: r :where [[?e :npc]] :then [])");
+    let rule = DeclarationAnalyzer::analyze_rule(&default)
+        .unwrap()
+        .unwrap();
+    assert_eq!(rule.refraction, Refraction::EveryTick);
+
+    let bad = parse("(rule: r :refraction :sometimes :then [])");
+    let err = DeclarationAnalyzer::analyze_rule(&bad).unwrap_err();
+    assert!(err.to_string().contains(":on-change"), "{err}");
+}
+
+#[test]
+fn analyze_rule_with_ordering() {
+    let ast = parse(
+        r"(rule: apply-damage
+             :before [check-death :log-damage]
+             :after [compute-armor]
+             :where [[?e :health ?hp]]
+             :then [])",
+    );
+
+    let rule = DeclarationAnalyzer::analyze_rule(&ast).unwrap().unwrap();
+
+    assert_eq!(rule.before, vec!["check-death", "log-damage"]);
+    assert_eq!(rule.after, vec!["compute-armor"]);
+
+    let bad = parse("(rule: r :before [42] :then [])");
+    assert!(DeclarationAnalyzer::analyze_rule(&bad).is_err());
+}
+
+#[test]
+fn analyze_rule_group() {
+    let ast = parse(
+        r"(rule-group: combat
+             (rule: attack :where [[?e :health ?hp]] :then [])
+             (rule: defend :group other :then []))",
+    );
+
+    let group = DeclarationAnalyzer::analyze_rule_group(&ast)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(group.name, "combat");
+    assert_eq!(group.rules.len(), 2);
+    // The enclosing group wins over a rule's own :group
+    assert!(
+        group
+            .rules
+            .iter()
+            .all(|r| r.group.as_deref() == Some("combat"))
+    );
+
+    let rule = DeclarationAnalyzer::analyze_rule(&parse("(rule: r :group :ui :then [])"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(rule.group.as_deref(), Some("ui"));
+
+    let bad = parse("(rule-group: combat (component: health :int))");
+    assert!(DeclarationAnalyzer::analyze_rule_group(&bad).is_err());
+}
+
 #[test]
 fn analyze_rule_with_negation() {
     let ast = parse(
@@ -75,6 +156,105 @@ fn analyze_rule_with_negation() {
     assert_eq!(rule.pattern.negations[0].component, "velocity");
 }
 
+#[test]
+fn analyze_or_keeps_branches_apart() {
+    let ast = parse(
+        r"(rule: uneasy
+             :where [[?e :health ?hp]
+                     (or [?e :hungry] (and [?e :tired ?t] [?e :cold]))]
+             :then [])",
+    );
+
+    let rule = DeclarationAnalyzer::analyze_rule(&ast).unwrap().unwrap();
+
+    assert_eq!(rule.pattern.clauses.len(), 1);
+    let branches = &rule.pattern.disjunctions[0].branches;
+    assert_eq!(branches.len(), 2);
+    assert_eq!(branches[0][0].component, "hungry");
+    assert_eq!(branches[1].len(), 2);
+    assert!(rule.pattern.bound_variables().contains(&"t"));
+
+    // The shapes the adventure example's preconditions use
+    let ast = parse(
+        r"(rule: armed
+             :where [[?e :health ?hp]
+                     (or ?weapon (exists [?w :held-by ?e] [?w :damage _]))
+                     (or (and ?key [?key :opens ?e]) [?e :unlocked])]
+             :then [])",
+    );
+    let rule = DeclarationAnalyzer::analyze_rule(&ast).unwrap().unwrap();
+    let lengths = |i: usize| -> Vec<usize> {
+        rule.pattern.disjunctions[i]
+            .branches
+            .iter()
+            .map(Vec::len)
+            .collect()
+    };
+    assert_eq!((lengths(0), lengths(1)), (vec![0, 2], vec![1, 1]));
+
+    for bad in [
+        "(rule: r :where [[?e :a] (or)] :then [])",
+        "(rule: r :where [[?e :a] (or [?e :b] :c)] :then [])",
+        "(rule: r :where [[?e :a] (or (and))] :then [])",
+    ] {
+        assert!(
+            DeclarationAnalyzer::analyze_rule(&parse(bad)).is_err(),
+            "{bad}"
+        );
+    }
+}
+
+#[test]
+fn analyze_predicate_clauses() {
+    let ast = parse(
+        r"(rule: wounded
+             :where [[?e :health ?hp] [(< ?hp ?max)] [?e :max-health ?max]]
+             :then [])",
+    );
+
+    let rule = DeclarationAnalyzer::analyze_rule(&ast).unwrap().unwrap();
+
+    assert_eq!(rule.pattern.clauses.len(), 2);
+    assert_eq!(rule.pattern.predicates.len(), 1);
+    assert_eq!(rule.pattern.predicates[0].vars, ["hp", "max"]);
+}
+
+#[test]
+fn analyze_not_join() {
+    let ast = parse(
+        r"(rule: unburdened
+             :where [[?e :health ?hp]
+                     (not-join [?e] [?e :holds ?i] [?i :cursed])
+                     (not [?e :room ?r] [?r :dark true])
+                     (not (exists [?e :poisoned] [?x :healer]))]
+             :then [])",
+    );
+
+    let rule = DeclarationAnalyzer::analyze_rule(&ast).unwrap().unwrap();
+
+    assert!(rule.pattern.negations.is_empty());
+    let not_joins = &rule.pattern.not_joins;
+    assert_eq!(not_joins.len(), 3);
+    assert_eq!(not_joins[0].join_vars, ["e"]);
+    assert_eq!(not_joins[0].clauses.len(), 2);
+    // Plain `not` joins on whatever the positive clauses bind
+    assert_eq!(not_joins[1].join_vars, ["e"]);
+    assert_eq!(not_joins[2].join_vars, ["e"]);
+    assert_eq!(not_joins[2].clauses[1].component, "healer");
+
+    for bad in [
+        "(rule: r :where [[?e :a] (not)] :then [])",
+        "(rule: r :where [[?e :a] (not-join e [?e :b])] :then [])",
+        "(rule: r :where [[?e :a] (not-join [e] [?e :b])] :then [])",
+        "(rule: r :where [[?e :a] (not-join [?e])] :then [])",
+    ] {
+        assert!(
+            DeclarationAnalyzer::analyze_rule(&parse(bad)).is_err(),
+            "{bad}"
+        );
+    }
+}
+
 #[test]
 fn analyze_rule_with_let_and_guard() {
     let ast = parse(
@@ -226,6 +406,7 @@ fn analyze_simple_relationship() {
     assert_eq!(rel.cardinality, Cardinality::ManyToOne);
     assert_eq!(rel.on_target_delete, OnTargetDelete::Remove);
     assert!(rel.required);
+    assert!(!rel.acyclic);
 }
 
 #[test]
@@ -237,6 +418,7 @@ fn analyze_full_relationship() {
              :on-target-delete :cascade
              :on-violation :replace
              :required false
+             :acyclic true
              :attributes [:start-date :int :salary :int])",
     );
 
@@ -250,6 +432,7 @@ fn analyze_full_relationship() {
     assert_eq!(rel.on_target_delete, OnTargetDelete::Cascade);
     assert_eq!(rel.on_violation, OnViolation::Replace);
     assert!(!rel.required);
+    assert!(rel.acyclic);
     assert_eq!(rel.attributes.len(), 2);
     assert_eq!(rel.attributes[0].name, "start-date");
     assert_eq!(rel.attributes[1].name, "salary");
@@ -375,6 +558,24 @@ fn analyze_constraint_with_warn() {
     assert_eq!(constraint.on_violation, ConstraintViolation::Warn);
 }
 
+#[test]
+fn analyze_constraint_with_score() {
+    let ast = parse(
+        r"(constraint: rooms-connected
+             :where [[?r :room]]
+             :check [(has-exit? ?r)]
+             :on-violation :score
+             :penalty 2.5)",
+    );
+
+    let constraint = DeclarationAnalyzer::analyze_constraint(&ast)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(constraint.on_violation, ConstraintViolation::Score);
+    assert!(matches!(constraint.penalty, Some(Ast::Float(f, _)) if (f - 2.5).abs() < f64::EPSILON));
+}
+
 #[test]
 fn analyze_constraint_with_guard() {
     let ast = parse(
@@ -467,6 +668,23 @@ fn analyze_query_with_aggregates() {
     assert_eq!(query.aggregates.len(), 2);
 }
 
+#[test]
+fn analyze_query_suppress() {
+    let ast = parse(
+        r"(query
+             :where [[?e :score ?s]]
+             :order-by [[?e :asc]]
+             :suppress [:unstable-order]
+             :return ?e)",
+    );
+
+    let query = DeclarationAnalyzer::analyze_query(&ast).unwrap().unwrap();
+    assert_eq!(query.suppress, vec!["unstable-order".to_string()]);
+
+    let bad = parse("(query :where [[?e :score ?s]] :suppress [unstable-order] :return ?e)");
+    assert!(DeclarationAnalyzer::analyze_query(&bad).is_err());
+}
+
 // =========================================================================
 // Unified Analysis Tests
 // =========================================================================
@@ -805,3 +1023,21 @@ fn unified_analyze_link() {
     let decl = DeclarationAnalyzer::analyze(&ast).unwrap().unwrap();
     assert!(matches!(decl, Declaration::Link(_)));
 }
+
+// =========================================================================
+// Record Tests
+// =========================================================================
+
+#[test]
+fn analyze_record() {
+    let ast = parse(r#"(defrecord point :x :int :y :int :label :string :default "")"#);
+    let record = DeclarationAnalyzer::analyze_record(&ast).unwrap().unwrap();
+    assert_eq!(record.name, "point");
+    let fields: Vec<_> = record.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(fields, ["x", "y", "label"]);
+    assert!(record.fields[2].default.is_some());
+
+    let duplicate = parse("(defrecord point :x :int :x :float)");
+    let err = DeclarationAnalyzer::analyze_record(&duplicate).unwrap_err();
+    assert!(err.to_string().contains("declares :x twice"), "{err}");
+}
@@ -24,6 +24,18 @@ pub struct PatternClause {
     pub span: Span,
 }
 
+impl PatternClause {
+    /// Returns the variables the clause mentions: its entity variable, then
+    /// its value variable if it has one.
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        let value = match &self.value {
+            PatternValue::Variable(v) => Some(v.as_str()),
+            PatternValue::Literal(_) | PatternValue::Wildcard => None,
+        };
+        std::iter::once(self.entity_var.as_str()).chain(value)
+    }
+}
+
 /// What the value position of a pattern matches.
 #[derive(Clone, Debug, PartialEq)]
 pub enum PatternValue {
@@ -35,13 +47,63 @@ pub enum PatternValue {
     Wildcard,
 }
 
+/// A predicate clause: `[(> ?hp 10)]`.
+///
+/// Filters matches by a DSL expression over the pattern's variables. It runs
+/// as soon as matching has bound every variable it mentions, rather than
+/// after every binding has been enumerated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatternPredicate {
+    /// The expression, truthy to keep a match
+    pub expr: Ast,
+    /// The `?variables` it mentions (without the `?`), in first-use order
+    pub vars: Vec<String>,
+    /// Source span for error reporting
+    pub span: Span,
+}
+
+/// A negated conjunction of clauses.
+///
+/// Corresponds to `(not-join [?e] [?e :holds ?i] [?i :cursed])`, and to
+/// `(not ...)` forms with more than one clause. It holds when no way of
+/// matching all of `clauses` agrees with the rest of the pattern on
+/// `join_vars`; the clauses' other variables are local to the negation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NotJoin {
+    /// Variables shared with the rest of the pattern (without the `?`)
+    pub join_vars: Vec<String>,
+    /// Clauses that must not all match together
+    pub clauses: Vec<PatternClause>,
+    /// Source span for error reporting
+    pub span: Span,
+}
+
+/// A disjunction: `(or [?e :hungry] (and [?e :tired] [?e :cold]))`.
+///
+/// Holds when any branch does. Each branch is a conjunction of clauses, and
+/// each branch that matches contributes its own set of bindings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Disjunction {
+    /// The alternative conjunctions
+    pub branches: Vec<Vec<PatternClause>>,
+    /// Source span for error reporting
+    pub span: Span,
+}
+
 /// A complete pattern (conjunction of clauses and negations).
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct Pattern {
     /// Positive clauses that must match
     pub clauses: Vec<PatternClause>,
-    /// Negated patterns (entities must NOT match these)
+    /// Negated clauses: `(not [?e :component ?v])` holds when no value of
+    /// the clause's unbound variables makes it match
     pub negations: Vec<PatternClause>,
+    /// Negated conjunctions of clauses
+    pub not_joins: Vec<NotJoin>,
+    /// Disjunctions, each of which must match through one of its branches
+    pub disjunctions: Vec<Disjunction>,
+    /// Predicate clauses that filter matches
+    pub predicates: Vec<PatternPredicate>,
 }
 
 impl Pattern {
@@ -51,11 +113,16 @@ impl Pattern {
         Self::default()
     }
 
-    /// Returns all variables bound by this pattern.
+    /// Returns all variables bound by this pattern, including those bound
+    /// by only some branches of a disjunction.
     #[must_use]
     pub fn bound_variables(&self) -> Vec<&str> {
+        let branches = self
+            .disjunctions
+            .iter()
+            .flat_map(|d| d.branches.iter().flatten());
         let mut vars = Vec::new();
-        for clause in &self.clauses {
+        for clause in self.clauses.iter().chain(branches) {
             vars.push(clause.entity_var.as_str());
             if let PatternValue::Variable(v) = &clause.value {
                 vars.push(v.as_str());
@@ -69,13 +136,51 @@ impl Pattern {
 // Rule Declaration
 // =============================================================================
 
+/// When a rule may fire again for bindings it has already fired for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Refraction {
+    /// Once per tick for each set of matched entities
+    #[default]
+    EveryTick,
+    /// Again only once something it matched has changed
+    OnChange,
+    /// Once ever for each set of matched entities
+    OncePerEntity,
+}
+
+impl Refraction {
+    /// All policies.
+    pub const ALL: [Self; 3] = [Self::EveryTick, Self::OnChange, Self::OncePerEntity];
+
+    /// Returns the DSL name of this policy (e.g. `"on-change"`).
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::EveryTick => "every-tick",
+            Self::OnChange => "on-change",
+            Self::OncePerEntity => "once-per-entity",
+        }
+    }
+
+    /// Looks up a policy by its DSL name.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.name() == name)
+    }
+}
+
 /// A rule declaration extracted from AST.
 ///
 /// Corresponds to:
 /// ```clojure
 /// (rule: name
+///   "Optional docstring"
 ///   :salience n
+///   :before [other-rule ...]
+///   :after [other-rule ...]
+///   :group group-name
 ///   :once true/false
+///   :refraction :every-tick/:on-change/:once-per-entity
 ///   :where [[pattern clauses]]
 ///   :let [bindings]
 ///   :guard [conditions]
@@ -85,10 +190,20 @@ impl Pattern {
 pub struct RuleDecl {
     /// Rule name
     pub name: String,
+    /// Docstring
+    pub doc: Option<String>,
     /// Priority (higher fires first), default 0
     pub salience: i32,
+    /// Rules this rule must fire before
+    pub before: Vec<String>,
+    /// Rules this rule must fire after
+    pub after: Vec<String>,
+    /// Rule group this rule belongs to
+    pub group: Option<String>,
     /// Fire at most once per tick
     pub once: bool,
+    /// When the rule may fire again for bindings it already fired for
+    pub refraction: Refraction,
     /// Enabled flag
     pub enabled: bool,
     /// Pattern to match
@@ -108,8 +223,13 @@ impl RuleDecl {
     pub fn new(name: impl Into<String>, span: Span) -> Self {
         Self {
             name: name.into(),
+            doc: None,
             salience: 0,
+            before: Vec::new(),
+            after: Vec::new(),
+            group: None,
             once: false,
+            refraction: Refraction::default(),
             enabled: true,
             pattern: Pattern::new(),
             bindings: Vec::new(),
@@ -120,6 +240,26 @@ impl RuleDecl {
     }
 }
 
+/// A rule group declaration extracted from AST.
+///
+/// Groups let whole subsystems of rules be switched on or off at runtime.
+///
+/// Corresponds to:
+/// ```clojure
+/// (rule-group: combat
+///   (rule: attack ...)
+///   (rule: defend ...))
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleGroupDecl {
+    /// Group name
+    pub name: String,
+    /// Rules in the group, each with `group` set to this group's name
+    pub rules: Vec<RuleDecl>,
+    /// Source span
+    pub span: Span,
+}
+
 // =============================================================================
 // Component Declaration
 // =============================================================================
@@ -137,6 +277,21 @@ pub struct FieldDecl {
     pub span: Span,
 }
 
+/// A record declaration: a named set of typed fields for map values.
+///
+/// ```clojure
+/// (defrecord point :x :int :y :int :label :string :default "")
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordDecl {
+    /// Record name, which also names its constructor
+    pub name: String,
+    /// Fields, in declaration order
+    pub fields: Vec<FieldDecl>,
+    /// Source span
+    pub span: Span,
+}
+
 /// A component declaration.
 ///
 /// Corresponds to:
@@ -245,6 +400,8 @@ pub struct RelationshipDecl {
     pub on_violation: OnViolation,
     /// Whether this relationship is required
     pub required: bool,
+    /// Whether links that would form a cycle are rejected
+    pub acyclic: bool,
     /// Attributes (only for entity storage)
     pub attributes: Vec<FieldDecl>,
     /// Source span
@@ -261,6 +418,7 @@ impl RelationshipDecl {
             on_target_delete: OnTargetDelete::default(),
             on_violation: OnViolation::default(),
             required: true,
+            acyclic: false,
             attributes: Vec::new(),
             span,
         }
@@ -331,6 +489,8 @@ pub enum ConstraintViolation {
     Rollback,
     /// Log a warning and continue
     Warn,
+    /// Add the constraint's penalty to the world score and continue
+    Score,
 }
 
 /// A constraint declaration.
@@ -359,6 +519,8 @@ pub struct ConstraintDecl {
     pub checks: Vec<Ast>,
     /// Behavior on violation
     pub on_violation: ConstraintViolation,
+    /// Penalty added to the world score per violation (`:on-violation :score` only)
+    pub penalty: Option<Ast>,
     /// Source span
     pub span: Span,
 }
@@ -374,6 +536,7 @@ impl ConstraintDecl {
             guards: Vec::new(),
             checks: Vec::new(),
             on_violation: ConstraintViolation::default(),
+            penalty: None,
             span,
         }
     }
@@ -421,6 +584,8 @@ pub struct QueryDecl {
     pub limit: Option<usize>,
     /// Return expression
     pub return_expr: Option<Ast>,
+    /// Warning codes to suppress (from `:suppress [:high-fan-out]`)
+    pub suppress: Vec<String>,
     /// Source span
     pub span: Span,
 }
@@ -438,6 +603,7 @@ impl QueryDecl {
             order_by: Vec::new(),
             limit: None,
             return_expr: None,
+            suppress: Vec::new(),
             span,
         }
     }
@@ -0,0 +1,253 @@
+//! Per-file dependency graph for loading directories.
+//!
+//! When a directory without a `_.lt` entry file is loaded, each file's
+//! `(namespace ... (:require ...))` declaration says which other files must
+//! be loaded first. [`DependencyGraph`] orders the files so every namespace
+//! loads after the namespaces it requires, and names the files involved when
+//! the requires form a cycle.
+//!
+//! Files without a namespace declaration have no dependencies, and requires
+//! naming namespaces outside the graph are ignored (they may already be
+//! loaded). Files that are otherwise unordered load in path order.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use longtable_foundation::{Error, ErrorKind, Result};
+
+use crate::declaration::{Declaration, DeclarationAnalyzer};
+use crate::parser::parse;
+
+/// A file in the dependency graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileNode {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Namespace the file declares, if any.
+    pub namespace: Option<String>,
+    /// Namespaces the file requires.
+    pub requires: Vec<String>,
+}
+
+impl FileNode {
+    /// Builds a node from a file's source by reading its namespace declaration.
+    ///
+    /// Sources that fail to parse get no dependencies; the error is reported
+    /// when the file is actually loaded.
+    #[must_use]
+    pub fn from_source(path: PathBuf, source: &str) -> Self {
+        let decl = parse(source)
+            .ok()
+            .and_then(|forms| forms.into_iter().next())
+            .and_then(|form| DeclarationAnalyzer::analyze(&form).ok().flatten());
+
+        match decl {
+            Some(Declaration::Namespace(ns)) => Self {
+                path,
+                namespace: Some(ns.name.full_name()),
+                requires: ns
+                    .requires
+                    .iter()
+                    .map(|r| r.namespace().full_name())
+                    .collect(),
+            },
+            _ => Self {
+                path,
+                namespace: None,
+                requires: Vec::new(),
+            },
+        }
+    }
+
+    /// Describes the file for diagnostics, e.g. `game.combat (src/combat.lt)`.
+    fn describe(&self) -> String {
+        match &self.namespace {
+            Some(ns) => format!("{ns} ({})", self.path.display()),
+            None => self.path.display().to_string(),
+        }
+    }
+}
+
+/// Files and the `require` edges between them.
+#[derive(Clone, Debug, Default)]
+pub struct DependencyGraph {
+    /// Files in the graph.
+    files: Vec<FileNode>,
+}
+
+impl DependencyGraph {
+    /// Creates an empty graph.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, reading its dependencies from `source`.
+    pub fn add_file(&mut self, path: impl Into<PathBuf>, source: &str) {
+        self.add_node(FileNode::from_source(path.into(), source));
+    }
+
+    /// Adds a file whose dependencies are already known.
+    pub fn add_node(&mut self, node: FileNode) {
+        self.files.push(node);
+    }
+
+    /// Returns the files in the graph, in insertion order.
+    #[must_use]
+    pub fn files(&self) -> &[FileNode] {
+        &self.files
+    }
+
+    /// Returns the files in an order where each comes after the files it requires.
+    ///
+    /// # Errors
+    /// Returns an error naming the files involved if the requires form a
+    /// cycle, or if two files declare the same namespace.
+    pub fn load_order(&self) -> Result<Vec<PathBuf>> {
+        let mut by_path: Vec<usize> = (0..self.files.len()).collect();
+        by_path.sort_by(|&a, &b| self.files[a].path.cmp(&self.files[b].path));
+
+        let mut by_namespace: HashMap<&str, usize> = HashMap::new();
+        for &i in &by_path {
+            if let Some(ns) = &self.files[i].namespace {
+                if let Some(&other) = by_namespace.get(ns.as_str()) {
+                    return Err(Error::new(ErrorKind::Refused(format!(
+                        "namespace {ns} is declared by both {} and {}",
+                        self.files[other].path.display(),
+                        self.files[i].path.display()
+                    ))));
+                }
+                by_namespace.insert(ns, i);
+            }
+        }
+
+        let deps: Vec<Vec<usize>> = self
+            .files
+            .iter()
+            .map(|file| {
+                file.requires
+                    .iter()
+                    .filter_map(|r| by_namespace.get(r.as_str()).copied())
+                    .collect()
+            })
+            .collect();
+
+        let mut loaded = vec![false; self.files.len()];
+        let mut order = Vec::with_capacity(self.files.len());
+        while order.len() < self.files.len() {
+            let ready = by_path
+                .iter()
+                .copied()
+                .find(|&i| !loaded[i] && deps[i].iter().all(|&d| loaded[d]));
+            let Some(next) = ready else {
+                return Err(self.cycle_error(&deps, &loaded));
+            };
+            loaded[next] = true;
+            order.push(self.files[next].path.clone());
+        }
+        Ok(order)
+    }
+
+    /// Builds the error for files that can't be ordered, tracing one cycle.
+    fn cycle_error(&self, deps: &[Vec<usize>], loaded: &[bool]) -> Error {
+        // Every unloaded file waits on another unloaded file, so following
+        // those edges from any of them must eventually revisit a file.
+        let mut path = Vec::new();
+        let mut current = (0..self.files.len())
+            .filter(|&i| !loaded[i])
+            .min_by(|&a, &b| self.files[a].path.cmp(&self.files[b].path))
+            .unwrap_or_default();
+        while !path.contains(&current) {
+            path.push(current);
+            current = deps[current]
+                .iter()
+                .copied()
+                .find(|&d| !loaded[d])
+                .unwrap_or(current);
+        }
+
+        let start = path.iter().position(|&i| i == current).unwrap_or_default();
+        let cycle: Vec<String> = path[start..]
+            .iter()
+            .chain(std::iter::once(&current))
+            .map(|&i| self.files[i].describe())
+            .collect();
+        Error::new(ErrorKind::Cycle(format!(
+            "cyclic require: {}",
+            cycle.join(" -> ")
+        )))
+    }
+}
+
+/// Returns true if `path` looks like a Longtable source file.
+#[must_use]
+pub fn is_source_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("lt"))
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(files: &[(&str, &str)]) -> DependencyGraph {
+        let mut graph = DependencyGraph::new();
+        for (path, source) in files {
+            graph.add_file(*path, source);
+        }
+        graph
+    }
+
+    #[test]
+    fn requires_load_first() {
+        let graph = graph(&[
+            (
+                "a_combat.lt",
+                "(namespace game.combat (:require [game.core :as core]))",
+            ),
+            ("b_core.lt", "(namespace game.core)"),
+            ("c_loose.lt", "(component: loose)"),
+        ]);
+
+        let order = graph.load_order().unwrap();
+        assert_eq!(
+            order,
+            vec![
+                PathBuf::from("b_core.lt"),
+                PathBuf::from("a_combat.lt"),
+                PathBuf::from("c_loose.lt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_requires_are_ignored() {
+        let graph = graph(&[("a.lt", "(namespace game.a (:require [stdlib.math]))")]);
+        assert_eq!(graph.load_order().unwrap(), vec![PathBuf::from("a.lt")]);
+    }
+
+    #[test]
+    fn cycles_name_the_files() {
+        let graph = graph(&[
+            ("a.lt", "(namespace game.a (:require [game.b]))"),
+            ("b.lt", "(namespace game.b (:require [game.a]))"),
+            ("c.lt", "(namespace game.c)"),
+        ]);
+
+        let err = graph.load_order().unwrap_err().to_string();
+        assert!(err.contains("cyclic require: game.a (a.lt) -> game.b (b.lt) -> game.a (a.lt)"));
+    }
+
+    #[test]
+    fn duplicate_namespaces_are_rejected() {
+        let graph = graph(&[
+            ("a.lt", "(namespace game.core)"),
+            ("b.lt", "(namespace game.core)"),
+        ]);
+        assert!(graph.load_order().is_err());
+    }
+}
@@ -31,8 +31,10 @@
 #![allow(clippy::missing_errors_doc)]
 
 pub mod ast;
+pub mod comment;
 pub mod compiler;
 pub mod declaration;
+pub mod dependency;
 pub mod gensym;
 pub mod lexer;
 pub mod macro_def;
@@ -54,16 +56,19 @@ mod fuzz_tests;
 
 // Re-exports for convenience
 pub use ast::Ast;
+pub use comment::{Comment, CommentMap};
 pub use compiler::{
     CompiledExpr, CompiledFunction, CompiledProgram, Compiler, compile, compile_expr,
     compile_expression, compile_expression_with_interner,
 };
 pub use declaration::{
     ActionDecl, AdverbDecl, Cardinality, CommandDecl, ComponentDecl, Declaration,
-    DeclarationAnalyzer, DirectionDecl, FieldDecl, LinkDecl, NounTypeDecl, OnTargetDelete, Pattern,
-    PatternClause, PatternValue, PrepositionDecl, PronounDecl, PronounGender, PronounNumber,
-    RelationshipDecl, RuleDecl, ScopeDecl, SpawnDecl, StorageKind, SyntaxElement, VerbDecl,
+    DeclarationAnalyzer, DirectionDecl, Disjunction, FieldDecl, LinkDecl, NotJoin, NounTypeDecl,
+    OnTargetDelete, Pattern, PatternClause, PatternPredicate, PatternValue, PrepositionDecl,
+    PronounDecl, PronounGender, PronounNumber, Refraction, RelationshipDecl, RuleDecl, ScopeDecl,
+    SpawnDecl, StorageKind, SyntaxElement, VerbDecl,
 };
+pub use dependency::{DependencyGraph, FileNode};
 pub use gensym::GensymGenerator;
 pub use lexer::Lexer;
 pub use macro_def::{MacroDef, MacroParam};
@@ -72,8 +77,11 @@ pub use macro_registry::MacroRegistry;
 pub use module_registry::{ModuleRegistry, NamespaceInfo};
 pub use namespace::{LoadDecl, NamespaceContext, NamespaceDecl, NamespaceName, RequireSpec};
 pub use opcode::{Bytecode, Opcode};
-pub use parser::{Parser, parse, parse_one};
+pub use parser::{Parser, parse, parse_one, parse_recovering, parse_with_comments};
 pub use span::Span;
 pub use stdlib_macros::register_stdlib_macros;
 pub use token::{Token, TokenKind};
-pub use vm::{RuntimeContext, Vm, VmContext, VmEffect, WorldContext, eval};
+pub use vm::{
+    DEFAULT_PEEK_BUDGET, RichSpan, RuntimeContext, SESSION_COMMANDS, Vm, VmContext, VmEffect,
+    WorldContext, eval, plain_text,
+};
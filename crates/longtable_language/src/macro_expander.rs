@@ -6,6 +6,25 @@
 //! - Expanding macro invocations
 //! - Syntax-quote with namespace qualification
 //! - Gensym patterns for hygiene
+//! - Syntax-quoted macro templates with unquote, unquote-splicing, and
+//!   automatic renaming of the locals they bind
+//!
+//! # Templates
+//!
+//! A macro body is a template: parameters are replaced by the caller's
+//! arguments wherever they appear. A syntax-quoted body is stricter and
+//! safer. Only `~x` and `~@xs` are replaced, so a symbol that happens to
+//! share a parameter's name stays as written, and every local the template
+//! binds with `let`, `loop`, `if-let`, `when-let`, or `fn` gets a fresh
+//! gensym so it can't capture the caller's variables:
+//!
+//! ```clojure
+//! (defmacro with-doubled [v & body]
+//!   `(let [doubled (* 2 ~v)] (+ doubled ~@body)))
+//!
+//! (with-doubled 3 doubled)
+//! ;; => (let [doubled__G__7 (* 2 3)] (+ doubled__G__7 doubled))
+//! ```
 //!
 //! # Expansion Algorithm
 //!
@@ -408,9 +427,8 @@ impl<'a> MacroExpander<'a> {
                 Ok(Ast::Quote(Box::new(substituted), *s))
             }
 
-            Ast::SyntaxQuote(inner, s) => {
-                let substituted = self.substitute(inner, bindings, span)?;
-                Ok(Ast::SyntaxQuote(Box::new(substituted), *s))
+            Ast::SyntaxQuote(inner, _) => {
+                self.expand_template(inner, bindings, &HashMap::new(), span)
             }
 
             // Atoms pass through
@@ -418,6 +436,145 @@ impl<'a> MacroExpander<'a> {
         }
     }
 
+    /// Fills in a syntax-quoted template.
+    ///
+    /// Only unquoted forms see the macro's arguments; other symbols are kept
+    /// literally, except for locals the template binds, which are renamed
+    /// within the form that binds them. `renames` holds the locals of the
+    /// enclosing forms.
+    fn expand_template(
+        &mut self,
+        ast: &Ast,
+        bindings: &HashMap<String, MacroArg>,
+        renames: &HashMap<String, String>,
+        span: Span,
+    ) -> Result<Ast> {
+        match ast {
+            Ast::Symbol(name, _) if GensymGenerator::is_gensym_pattern(name) => {
+                self.substitute(ast, bindings, span)
+            }
+            Ast::Symbol(name, s) => Ok(match renames.get(name) {
+                Some(fresh) => Ast::Symbol(fresh.clone(), *s),
+                None => ast.clone(),
+            }),
+            Ast::Unquote(inner, _) | Ast::UnquoteSplice(inner, _) => {
+                self.substitute(inner, bindings, span)
+            }
+            Ast::List(elements, s) => {
+                let mut locals = Vec::new();
+                Self::form_locals(elements, &mut locals);
+                if locals.is_empty() {
+                    return Ok(Ast::List(
+                        self.expand_template_elements(elements, bindings, renames, span)?,
+                        *s,
+                    ));
+                }
+                let mut scoped = renames.clone();
+                for name in locals {
+                    let generated = self.gensym.gensym(&name);
+                    scoped.insert(name, generated);
+                }
+                Ok(Ast::List(
+                    self.expand_template_elements(elements, bindings, &scoped, span)?,
+                    *s,
+                ))
+            }
+            Ast::Vector(elements, s) => Ok(Ast::Vector(
+                self.expand_template_elements(elements, bindings, renames, span)?,
+                *s,
+            )),
+            Ast::Set(elements, s) => Ok(Ast::Set(
+                self.expand_template_elements(elements, bindings, renames, span)?,
+                *s,
+            )),
+            Ast::Map(pairs, s) => {
+                let expanded: Result<Vec<_>> = pairs
+                    .iter()
+                    .map(|(k, v)| {
+                        Ok((
+                            self.expand_template(k, bindings, renames, span)?,
+                            self.expand_template(v, bindings, renames, span)?,
+                        ))
+                    })
+                    .collect();
+                Ok(Ast::Map(expanded?, *s))
+            }
+            Ast::Quote(inner, s) => Ok(Ast::Quote(
+                Box::new(self.expand_template(inner, bindings, renames, span)?),
+                *s,
+            )),
+            _ => Ok(ast.clone()),
+        }
+    }
+
+    /// Fills in a template's elements, splicing `~@` arguments in place.
+    fn expand_template_elements(
+        &mut self,
+        elements: &[Ast],
+        bindings: &HashMap<String, MacroArg>,
+        renames: &HashMap<String, String>,
+        span: Span,
+    ) -> Result<Vec<Ast>> {
+        let mut result = Vec::new();
+        for elem in elements {
+            if let Ast::UnquoteSplice(inner, _) = elem {
+                match self.substitute(inner, bindings, span)? {
+                    Ast::List(items, _) | Ast::Vector(items, _) => result.extend(items),
+                    other => result.push(other),
+                }
+            } else {
+                result.push(self.expand_template(elem, bindings, renames, span)?);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Collects the names a template form binds as locals, if it is a `let`,
+    /// `loop`, `if-let`, `when-let`, or `fn`.
+    fn form_locals(elements: &[Ast], locals: &mut Vec<String>) {
+        match elements {
+            [Ast::Symbol(head, _), Ast::Vector(bindings, _), ..]
+                if matches!(head.as_str(), "let" | "loop" | "if-let" | "when-let") =>
+            {
+                for pattern in bindings.iter().step_by(2) {
+                    Self::binding_names(pattern, locals);
+                }
+            }
+            [Ast::Symbol(head, _), Ast::Vector(params, _), ..]
+            | [
+                Ast::Symbol(head, _),
+                Ast::Symbol(..),
+                Ast::Vector(params, _),
+                ..,
+            ] if head == "fn" => {
+                for param in params {
+                    Self::binding_names(param, locals);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Collects the symbols a binding pattern introduces.
+    fn binding_names(pattern: &Ast, locals: &mut Vec<String>) {
+        match pattern {
+            Ast::Symbol(name, _)
+                if name != "&"
+                    && name != "_"
+                    && !GensymGenerator::is_gensym_pattern(name)
+                    && !locals.contains(name) =>
+            {
+                locals.push(name.clone());
+            }
+            Ast::Vector(elements, _) => {
+                for element in elements {
+                    Self::binding_names(element, locals);
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// Substitutes in a list, handling unquote-splice.
     fn substitute_list_with_splice(
         &mut self,
@@ -697,6 +854,64 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("depth exceeded"));
     }
 
+    fn expand_with(definition: &str, call: &str) -> String {
+        let mut registry = MacroRegistry::new();
+        let mut expander = MacroExpander::new(&mut registry);
+        expander.expand_all(&parse(definition).unwrap()).unwrap();
+        let result = expander.expand_all(&parse(call).unwrap()).unwrap();
+        crate::pretty::pretty_print(&result[0])
+    }
+
+    #[test]
+    fn syntax_quote_template_substitutes_only_unquotes() {
+        let expanded = expand_with(
+            "(defmacro unless [test & body] `(if ~test nil (do ~@body)))",
+            "(unless done (print 1) (print 2))",
+        );
+        assert_eq!(expanded, "(if done nil (do (print 1) (print 2)))");
+
+        // A bare symbol sharing a parameter's name is kept as written
+        let expanded = expand_with("(defmacro pair [x] `(vector 'x ~x))", "(pair 5)");
+        assert_eq!(expanded, "(vector 'x 5)");
+    }
+
+    #[test]
+    fn syntax_quote_template_renames_its_locals() {
+        let expanded = expand_with(
+            "(defmacro with-doubled [v & body] `(let [doubled (* 2 ~v)] (+ doubled ~@body)))",
+            "(with-doubled 3 doubled)",
+        );
+        let renamed = expanded
+            .strip_prefix("(let [")
+            .and_then(|rest| rest.split(' ').next())
+            .unwrap();
+        assert!(renamed.starts_with("doubled__G__"), "{expanded}");
+        assert!(
+            expanded.ends_with(&format!("(+ {renamed} doubled))")),
+            "{expanded}"
+        );
+
+        let expanded = expand_with("(defmacro adder [n] `(fn [x] (+ x ~n)))", "(adder x)");
+        assert!(expanded.starts_with("(fn [x__G__"), "{expanded}");
+        assert!(expanded.ends_with(" x))"), "{expanded}");
+
+        // Only within the binding form: the later x is the global one
+        let expanded = expand_with("(defmacro both [] `(do (let [x 1] x) x))", "(both)");
+        assert!(expanded.starts_with("(do (let [x__G__"), "{expanded}");
+        assert!(expanded.ends_with(") x)"), "{expanded}");
+    }
+
+    #[test]
+    fn hygienic_templates_do_not_capture_caller_variables() {
+        let source = "
+            (defmacro minus-first [a b] `(let [t ~a] (- ~b t)))
+            (let [t 10] (minus-first 1 t))";
+        assert_eq!(
+            crate::vm::eval(source).unwrap(),
+            longtable_foundation::Value::Int(9)
+        );
+    }
+
     #[test]
     fn gensym_pattern_generates_unique_symbols() {
         // Helper function to check for gensym markers
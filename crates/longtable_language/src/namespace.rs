@@ -11,10 +11,19 @@
 //!             [game.utils :refer [distance clamp]]
 //!             [game.items]))
 //! ```
+//!
+//! Inside a namespace, `::attack` is shorthand for `:game.combat/attack`, and
+//! `::core/health` expands through the `core` alias to `:game.core/health`.
+//! These keywords are resolved when a form is compiled, so rule bodies can
+//! use short names while the world only ever sees fully-qualified keywords.
 
+use crate::ast::Ast;
 use crate::span::Span;
+use crate::visitor::{AstTransform, transform_ast};
+use longtable_foundation::{Error, ErrorKind, Result};
 use std::collections::HashMap;
 use std::fmt;
+use std::path::PathBuf;
 
 // =============================================================================
 // NamespaceName
@@ -56,6 +65,15 @@ impl NamespaceName {
         self.segments.last().map_or("", String::as_str)
     }
 
+    /// Returns the source file a namespace lives in, relative to a load
+    /// path (e.g. `game/combat.lt` for "game.combat").
+    #[must_use]
+    pub fn file_path(&self) -> PathBuf {
+        let mut path: PathBuf = self.segments.iter().collect();
+        path.set_extension("lt");
+        path
+    }
+
     /// Returns true if this namespace is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
@@ -270,6 +288,66 @@ impl NamespaceContext {
         self.refers.get(name).cloned()
     }
 
+    /// Resolve an auto-qualified keyword name, as written after the first `:`.
+    ///
+    /// `:attack` resolves to `game.combat/attack` in namespace `game.combat`,
+    /// and `:core/health` to `game.core/health` through the `core` alias.
+    /// Names without the extra `:` are returned unchanged. Returns `None` if
+    /// the alias is unknown or the name is empty.
+    #[must_use]
+    pub fn resolve_keyword(&self, name: &str) -> Option<String> {
+        let Some(short) = name.strip_prefix(':') else {
+            return Some(name.to_string());
+        };
+        match short.split_once('/') {
+            Some((alias, symbol)) if !alias.is_empty() && !symbol.is_empty() => {
+                self.resolve_alias(alias, symbol)
+            }
+            None if !short.is_empty() => Some(self.qualify(short)),
+            _ => None,
+        }
+    }
+
+    /// Like [`Self::resolve_keyword`], but reports an unresolvable keyword as
+    /// a parse error at `span`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keyword's alias isn't required.
+    pub fn resolve_keyword_at(&self, name: &str, span: Span) -> Result<String> {
+        self.resolve_keyword(name).ok_or_else(|| {
+            let message = match name.trim_start_matches(':').split_once('/') {
+                Some((alias, _)) => format!(
+                    "unknown namespace alias `{alias}` in :{name}; add [some.namespace :as {alias}] to a require"
+                ),
+                None => format!("invalid keyword :{name}"),
+            };
+            Error::new(ErrorKind::ParseError {
+                message,
+                line: span.line,
+                column: span.column,
+                context: String::new(),
+            })
+        })
+    }
+
+    /// Rewrites every `::` keyword in `ast` to its fully-qualified form.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for the first keyword whose alias isn't required.
+    pub fn resolve_keywords(&self, ast: &Ast) -> Result<Ast> {
+        let mut resolver = KeywordResolver {
+            context: self,
+            error: None,
+        };
+        let ast = transform_ast(&mut resolver, ast.clone());
+        match resolver.error {
+            Some(error) => Err(error),
+            None => Ok(ast),
+        }
+    }
+
     /// Returns the current namespace name as a string, or "user" if none.
     #[must_use]
     pub fn current_namespace_str(&self) -> String {
@@ -287,6 +365,24 @@ impl NamespaceContext {
     }
 }
 
+/// Rewrites `::` keywords, remembering the first one that can't be resolved.
+struct KeywordResolver<'a> {
+    context: &'a NamespaceContext,
+    error: Option<Error>,
+}
+
+impl AstTransform for KeywordResolver<'_> {
+    fn transform_keyword(&mut self, name: String, span: Span) -> Ast {
+        match self.context.resolve_keyword_at(&name, span) {
+            Ok(resolved) => Ast::Keyword(resolved, span),
+            Err(error) => {
+                self.error.get_or_insert(error);
+                Ast::Keyword(name, span)
+            }
+        }
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -391,6 +487,49 @@ mod tests {
         assert_eq!(ctx.qualify("foo"), "user/foo");
     }
 
+    #[test]
+    fn auto_qualified_keywords() {
+        let mut ctx = NamespaceContext::from_decl(&NamespaceDecl::with_name(
+            "my.game.rules",
+            Span::default(),
+        ));
+        ctx.add_alias("combat", "my.game.combat");
+
+        assert_eq!(
+            ctx.resolve_keyword(":attack").as_deref(),
+            Some("my.game.rules/attack")
+        );
+        assert_eq!(
+            ctx.resolve_keyword(":combat/attack").as_deref(),
+            Some("my.game.combat/attack")
+        );
+        assert_eq!(ctx.resolve_keyword("health").as_deref(), Some("health"));
+        assert_eq!(ctx.resolve_keyword(":items/sword"), None);
+        assert_eq!(ctx.resolve_keyword(":"), None);
+
+        let ast = crate::parse("(get ?e ::combat/attack {:base ::bonus})").unwrap();
+        let resolved = ctx.resolve_keywords(&ast[0]).unwrap();
+        assert_eq!(
+            crate::pretty::pretty_print(&resolved),
+            "(get ?e :my.game.combat/attack {:base :my.game.rules/bonus})"
+        );
+
+        let ast = crate::parse("[::items/sword]").unwrap();
+        let err = ctx.resolve_keywords(&ast[0]).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown namespace alias `items`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn namespace_file_path() {
+        assert_eq!(
+            NamespaceName::parse("my.game.combat").file_path(),
+            PathBuf::from("my/game/combat.lt")
+        );
+    }
+
     #[test]
     fn require_spec_namespace() {
         let alias = RequireSpec::Alias {
@@ -5,6 +5,8 @@
 
 #![allow(clippy::doc_markdown)]
 
+use crate::span::Span;
+
 /// A single bytecode instruction.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Opcode {
@@ -119,6 +121,8 @@ pub enum Opcode {
     // === Effects (Mutation Operations) ===
     /// Spawn entity with components map: `[components_map] -> [entity_id]`
     Spawn,
+    /// Spawn one entity per components map: `[maps] -> [entity_ids]`
+    SpawnMany,
     /// Destroy entity: `[entity] -> []`
     Destroy,
     /// Set component: `[entity, component_kw, value] -> []`
@@ -131,6 +135,17 @@ pub enum Opcode {
     Link,
     /// Remove relationship: `[source, rel_kw, target] -> []`
     Unlink,
+    /// Emit event: `[event_kw, payload] -> []`
+    Emit,
+    /// Schedule an action: `[delay, owner, action_fn] -> []`
+    Schedule,
+    /// Schedule an action to recur every `delay` ticks:
+    /// `[delay, owner, action_fn] -> []`
+    ScheduleEvery,
+    /// Queue a session command: `[args...] -> []`
+    /// Operands: (name constant index, argument count).
+    /// Requires a context that allows commands.
+    Command(u16, u8),
 
     // === Collection Field Mutations (Mergeable Effects) ===
     /// Remove value from vector field: `[entity, component_kw, field_kw, value] -> []`
@@ -154,6 +169,15 @@ pub enum Opcode {
     /// Restores the world to the snapshot identified by the ID.
     RestoreState,
 
+    // === Sandboxing ===
+    /// Open a read-only `peek` sandbox: `[] -> []`
+    /// Until the matching `PeekEnd`, reads ignore this execution's pending
+    /// writes, effects are refused, and at most the operand's number of
+    /// instructions may run.
+    PeekBegin(u32),
+    /// Close the innermost `peek` sandbox: `[value] -> [value]`
+    PeekEnd,
+
     // === Machine Configuration (RuntimeContext Operations) ===
     /// Register a component schema: `[schema_map] -> []`
     /// Schema map should contain `:name`, `:fields`, `:storage` keys.
@@ -183,6 +207,10 @@ pub enum Opcode {
     /// Data map should contain `:name` key.
     /// Requires RuntimeContext.
     RegisterAdverb,
+    /// Register a message template: `[data_map] -> []`
+    /// Data map should contain `:key`, `:template`, and optionally `:locale`.
+    /// Requires RuntimeContext.
+    RegisterMessage,
     /// Register a noun type constraint: `[data_map] -> []`
     /// Data map should contain `:name`, `:extends`, `:pattern` keys.
     /// Requires RuntimeContext.
@@ -275,27 +303,95 @@ pub enum Opcode {
     Repeatedly,
 }
 
+impl Opcode {
+    /// Returns true if the opcode changes the world or the runtime: effects,
+    /// session commands, state management, and registrations.
+    #[must_use]
+    pub fn is_effect(&self) -> bool {
+        matches!(
+            self,
+            Self::Spawn
+                | Self::SpawnMany
+                | Self::Destroy
+                | Self::SetComponent
+                | Self::SetField
+                | Self::RemoveComponent
+                | Self::Link
+                | Self::Unlink
+                | Self::Emit
+                | Self::Schedule
+                | Self::ScheduleEvery
+                | Self::Command(..)
+                | Self::VecRemove
+                | Self::VecAdd
+                | Self::SetRemove
+                | Self::SetAdd
+                | Self::SaveState
+                | Self::RestoreState
+                | Self::RegisterComponent
+                | Self::RegisterRelationship
+                | Self::RegisterVerb
+                | Self::RegisterDirection
+                | Self::RegisterPreposition
+                | Self::RegisterPronoun
+                | Self::RegisterAdverb
+                | Self::RegisterMessage
+                | Self::RegisterType
+                | Self::RegisterScope
+                | Self::RegisterCommand
+                | Self::RegisterAction
+                | Self::RegisterRule
+        )
+    }
+}
+
 /// A sequence of bytecode instructions.
+///
+/// Each instruction remembers the source position of the form it was
+/// compiled from, so runtime errors can point back at it.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Bytecode {
     /// The instructions.
     pub ops: Vec<Opcode>,
+    /// Source position of each instruction, parallel to `ops`.
+    spans: Vec<Option<Span>>,
+    /// Position given to instructions emitted from now on.
+    current_span: Option<Span>,
 }
 
 impl Bytecode {
     /// Creates an empty bytecode sequence.
     #[must_use]
     pub fn new() -> Self {
-        Self { ops: Vec::new() }
+        Self::default()
     }
 
     /// Adds an instruction and returns its index.
     pub fn emit(&mut self, op: Opcode) -> usize {
         let idx = self.ops.len();
         self.ops.push(op);
+        self.spans.push(self.current_span);
         idx
     }
 
+    /// Removes and returns the last instruction.
+    pub fn pop(&mut self) -> Option<Opcode> {
+        self.spans.pop();
+        self.ops.pop()
+    }
+
+    /// Sets the source position of the instructions emitted from now on,
+    /// returning the previous one.
+    pub fn set_span(&mut self, span: Option<Span>) -> Option<Span> {
+        std::mem::replace(&mut self.current_span, span)
+    }
+
+    /// Returns the source position of the instruction at `idx`, if known.
+    #[must_use]
+    pub fn span_at(&self, idx: usize) -> Option<Span> {
+        self.spans.get(idx).copied().flatten()
+    }
+
     /// Returns the current instruction count (next instruction index).
     #[must_use]
     pub fn len(&self) -> usize {
@@ -340,6 +436,24 @@ mod tests {
         assert_eq!(bc.len(), 2);
     }
 
+    #[test]
+    fn bytecode_tracks_spans() {
+        let mut bc = Bytecode::new();
+        bc.emit(Opcode::Const(0));
+        let form = Span::new(4, 9, 2, 3);
+        assert_eq!(bc.set_span(Some(form)), None);
+        bc.emit(Opcode::Const(1));
+        bc.emit(Opcode::Add);
+        bc.set_span(None);
+        bc.emit(Opcode::Pop);
+
+        assert_eq!(bc.span_at(0), None);
+        assert_eq!(bc.span_at(2), Some(form));
+        assert_eq!(bc.pop(), Some(Opcode::Pop));
+        assert_eq!(bc.span_at(3), None);
+        assert_eq!(bc.len(), 3);
+    }
+
     #[test]
     fn bytecode_patch_jump() {
         let mut bc = Bytecode::new();
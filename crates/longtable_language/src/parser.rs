@@ -1,10 +1,20 @@
 //! Parser for the Longtable DSL.
 //!
 //! The parser converts a stream of tokens into an abstract syntax tree.
+//!
+//! [`parse`] stops at the first error. [`parse_recovering`] keeps going: after
+//! a bad form it skips ahead to the next top-level form (an opening paren in
+//! the first column) and carries on, so every error in a file is reported at
+//! once alongside the forms that did parse.
+//!
+//! Comments are skipped unless the parser is created
+//! [`with_comments`](Parser::with_comments), in which case they are collected
+//! into a [`CommentMap`] for tooling (see [`crate::comment`]).
 
 use longtable_foundation::{Error, ErrorKind, Result};
 
 use crate::ast::Ast;
+use crate::comment::{Comment, CommentMap};
 use crate::lexer::Lexer;
 use crate::span::Span;
 use crate::token::{Token, TokenKind};
@@ -17,6 +27,13 @@ pub struct Parser<'src> {
     current: Token,
     /// Source text (for error messages).
     source: &'src str,
+    /// Whether to treat an opening paren in the first column as the start
+    /// of a new top-level form, even inside an unterminated collection.
+    recovering: bool,
+    /// Collected comments, if comment retention is enabled.
+    comments: Option<CommentMap>,
+    /// Comments seen since the last form started.
+    pending_comments: Vec<Comment>,
 }
 
 impl<'src> Parser<'src> {
@@ -29,9 +46,32 @@ impl<'src> Parser<'src> {
             lexer,
             current,
             source,
+            recovering: false,
+            comments: None,
+            pending_comments: Vec::new(),
         }
     }
 
+    /// Retains comments, attaching each run of comments to the form that follows it.
+    ///
+    /// Retrieve them with [`take_comments`](Self::take_comments) after parsing.
+    #[must_use]
+    pub fn with_comments(mut self) -> Self {
+        self.comments = Some(CommentMap::new());
+        self
+    }
+
+    /// Returns the comments collected so far, leaving an empty map behind.
+    ///
+    /// Returns an empty map if comment retention is not enabled.
+    pub fn take_comments(&mut self) -> CommentMap {
+        self.dangle_comments();
+        self.comments
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
     /// Parses a single expression from the source.
     ///
     /// # Errors
@@ -57,9 +97,60 @@ impl<'src> Parser<'src> {
         Ok(forms)
     }
 
+    /// Parses all expressions from the source, recovering from errors.
+    ///
+    /// Returns the forms that parsed along with every error encountered.
+    pub fn parse_all_recovering(&mut self) -> (Vec<Ast>, Vec<Error>) {
+        self.recovering = true;
+        let mut forms = Vec::new();
+        let mut errors = Vec::new();
+        self.skip_trivia();
+
+        while self.current.kind != TokenKind::Eof {
+            let form_start = self.current.span.start;
+            match self.parse_form() {
+                Ok(form) => forms.push(form),
+                Err(err) => {
+                    errors.push(err);
+                    self.synchronize(form_start);
+                }
+            }
+            self.skip_trivia();
+        }
+
+        (forms, errors)
+    }
+
+    /// Skips tokens until the start of the next top-level form.
+    ///
+    /// `form_start` is where the failed form began; the parser never stops
+    /// there again, so recovery always makes progress.
+    fn synchronize(&mut self, form_start: usize) {
+        while self.current.kind != TokenKind::Eof {
+            if self.at_top_level_form() && self.current.span.start != form_start {
+                return;
+            }
+            self.advance();
+        }
+    }
+
+    /// Returns true if recovering and the current token looks like the start
+    /// of a top-level form.
+    fn at_top_level_form(&self) -> bool {
+        self.recovering && self.current.kind == TokenKind::LParen && self.current.span.column == 1
+    }
+
+    /// Returns true if a collection opened at `start` cannot continue: the
+    /// input has ended, or (when recovering) a new top-level form begins.
+    fn collection_ended(&self, start: Span) -> bool {
+        self.current.kind == TokenKind::Eof
+            || (self.at_top_level_form() && self.current.span.start != start.start)
+    }
+
     /// Parses a form (expression).
     fn parse_form(&mut self) -> Result<Ast> {
         self.skip_trivia();
+        self.attach_comments();
 
         match &self.current.kind {
             TokenKind::Nil => {
@@ -142,13 +233,14 @@ impl<'src> Parser<'src> {
         self.skip_trivia();
 
         while self.current.kind != TokenKind::RParen {
-            if self.current.kind == TokenKind::Eof {
+            if self.collection_ended(start_span) {
                 return Err(self.error_at(start_span, "unterminated list"));
             }
             elements.push(self.parse_form()?);
             self.skip_trivia();
         }
 
+        self.dangle_comments();
         let end_span = self.current.span;
         self.expect(&TokenKind::RParen)?;
 
@@ -164,13 +256,14 @@ impl<'src> Parser<'src> {
         self.skip_trivia();
 
         while self.current.kind != TokenKind::RBracket {
-            if self.current.kind == TokenKind::Eof {
+            if self.collection_ended(start_span) {
                 return Err(self.error_at(start_span, "unterminated vector"));
             }
             elements.push(self.parse_form()?);
             self.skip_trivia();
         }
 
+        self.dangle_comments();
         let end_span = self.current.span;
         self.expect(&TokenKind::RBracket)?;
 
@@ -186,13 +279,14 @@ impl<'src> Parser<'src> {
         self.skip_trivia();
 
         while self.current.kind != TokenKind::RBrace {
-            if self.current.kind == TokenKind::Eof {
+            if self.collection_ended(start_span) {
                 return Err(self.error_at(start_span, "unterminated set"));
             }
             elements.push(self.parse_form()?);
             self.skip_trivia();
         }
 
+        self.dangle_comments();
         let end_span = self.current.span;
         self.expect(&TokenKind::RBrace)?;
 
@@ -208,7 +302,7 @@ impl<'src> Parser<'src> {
         self.skip_trivia();
 
         while self.current.kind != TokenKind::RBrace {
-            if self.current.kind == TokenKind::Eof {
+            if self.collection_ended(start_span) {
                 return Err(self.error_at(start_span, "unterminated map"));
             }
             let key = self.parse_form()?;
@@ -223,6 +317,7 @@ impl<'src> Parser<'src> {
             entries.push((key, value));
         }
 
+        self.dangle_comments();
         let end_span = self.current.span;
         self.expect(&TokenKind::RBrace)?;
 
@@ -334,13 +429,35 @@ impl<'src> Parser<'src> {
         self.parse_form()
     }
 
-    /// Skips comment tokens.
+    /// Skips comment tokens, holding on to them if comments are retained.
     fn skip_trivia(&mut self) {
         while self.current.kind.is_trivia() {
+            if let (TokenKind::Comment(text), Some(_)) = (&self.current.kind, &self.comments) {
+                self.pending_comments.push(Comment {
+                    text: text.clone(),
+                    span: self.current.span,
+                });
+            }
             self.advance();
         }
     }
 
+    /// Attaches pending comments to the form starting at the current token.
+    fn attach_comments(&mut self) {
+        if let Some(comments) = &mut self.comments {
+            if !self.pending_comments.is_empty() {
+                comments.attach(self.current.span.start, self.pending_comments.drain(..));
+            }
+        }
+    }
+
+    /// Records pending comments that have no following form.
+    fn dangle_comments(&mut self) {
+        if let Some(comments) = &mut self.comments {
+            comments.add_dangling(self.pending_comments.drain(..));
+        }
+    }
+
     /// Advances to the next token.
     fn advance(&mut self) {
         self.current = self.lexer.next_token();
@@ -399,6 +516,25 @@ pub fn parse(source: &str) -> Result<Vec<Ast>> {
     Parser::new(source).parse_all()
 }
 
+/// Parses source code into AST, collecting every error instead of stopping
+/// at the first.
+///
+/// Returns the forms that parsed and the errors, in source order.
+#[must_use]
+pub fn parse_recovering(source: &str) -> (Vec<Ast>, Vec<Error>) {
+    Parser::new(source).parse_all_recovering()
+}
+
+/// Parses source code into AST, retaining comments for tooling.
+///
+/// # Errors
+/// Returns an error if the source cannot be parsed.
+pub fn parse_with_comments(source: &str) -> Result<(Vec<Ast>, CommentMap)> {
+    let mut parser = Parser::new(source).with_comments();
+    let forms = parser.parse_all()?;
+    Ok((forms, parser.take_comments()))
+}
+
 /// Parses a single expression from source.
 ///
 /// # Errors
@@ -605,6 +741,67 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn parse_recovering_reports_every_error() {
+        let source =
+            "(def a 1)\n(def b ]\n(def c 3)\n(def d {:x}\n(def e 5)\n(def f (+ 1\n(def g 7)\n";
+        let (forms, errors) = parse_recovering(source);
+
+        let lines: Vec<u32> = errors
+            .iter()
+            .map(|e| match &e.kind {
+                ErrorKind::ParseError { line, .. } => *line,
+                other => panic!("unexpected error {other:?}"),
+            })
+            .collect();
+        assert_eq!(lines, vec![2, 4, 6]);
+
+        let names: Vec<&str> = forms
+            .iter()
+            .map(|f| f.as_list().unwrap()[1].as_symbol().unwrap())
+            .collect();
+        assert_eq!(names, vec!["a", "c", "e", "g"]);
+    }
+
+    #[test]
+    fn parse_recovering_skips_stray_closers() {
+        let (forms, errors) = parse_recovering(") foo ]\n(bar)");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(forms.len(), 1);
+        assert!(parse_recovering("(ok)").1.is_empty());
+    }
+
+    #[test]
+    fn parse_with_comments_attaches_to_following_form() {
+        let source = ";; Health\n;; in hit points\n(component: health)\n(rule: r\n  ;; only living things\n  :where [])\n(foo ; trailing\n)\n; end";
+        let (forms, comments) = super::parse_with_comments(source).unwrap();
+
+        let health: Vec<&str> = comments
+            .leading(&forms[0])
+            .iter()
+            .map(Comment::body)
+            .collect();
+        assert_eq!(health, vec!["Health", "in hit points"]);
+
+        let rule = forms[1].as_list().unwrap();
+        assert_eq!(comments.leading(&rule[2])[0].body(), "only living things");
+        assert!(comments.leading(&forms[1]).is_empty());
+
+        let dangling: Vec<&str> = comments.dangling().iter().map(Comment::body).collect();
+        assert_eq!(dangling, vec!["trailing", "end"]);
+        assert_eq!(comments.len(), 5);
+
+        // Comments don't change the forms themselves
+        assert_eq!(forms, parse(source).unwrap());
+    }
+
+    #[test]
+    fn comments_are_dropped_by_default() {
+        let mut parser = Parser::new("; note\n(foo)");
+        parser.parse_all().unwrap();
+        assert!(parser.take_comments().is_empty());
+    }
+
     #[test]
     fn parse_span_tracking() {
         let source = "foo bar";
@@ -12,10 +12,37 @@
 //! let source = pretty_print(&ast[0]);
 //! assert_eq!(source, "(+ 1 2)");
 //! ```
+//!
+//! # Formatting source files
+//!
+//! [`format_source`] re-emits a whole `.lt` file in canonical layout, as
+//! `longtable fmt` does. Forms that fit within [`PrettyConfig::max_width`]
+//! stay on one line. Longer lists put their first argument beside the head
+//! and indent the rest by [`PrettyConfig::indent_width`]; vectors, sets, and
+//! maps align their elements under the first. Declarations list each
+//! `:option value` pair on its own line, and `rule:`, `derived:`,
+//! `constraint:`, `action:`, and `command:` always do:
+//!
+//! ```
+//! use longtable_language::pretty::format_source;
+//!
+//! let source = "(rule: regen :where [[?e :health ?h]]   :then [])";
+//! assert_eq!(
+//!     format_source(source).unwrap(),
+//!     "(rule: regen\n  :where [[?e :health ?h]]\n  :then [])\n"
+//! );
+//! ```
+//!
+//! Comments are kept, each on its own line above the form it precedes; a
+//! comment at the end of a line moves above the next form. At most one
+//! blank line is kept between top-level forms.
 
 use std::fmt::Write;
 
 use crate::ast::Ast;
+use crate::comment::{Comment, CommentMap};
+use crate::parser::parse_with_comments;
+use longtable_foundation::Result;
 
 /// Configuration for pretty-printing.
 #[derive(Debug, Clone)]
@@ -69,6 +96,314 @@ pub fn pretty_print_all_with_config(asts: &[Ast], config: &PrettyConfig) -> Stri
         .join("\n")
 }
 
+/// Formats a source file in canonical layout, keeping its comments.
+///
+/// # Errors
+///
+/// Returns an error if the source cannot be parsed.
+pub fn format_source(source: &str) -> Result<String> {
+    format_source_with_config(source, &PrettyConfig::default())
+}
+
+/// Formats a source file in canonical layout with custom configuration.
+///
+/// # Errors
+///
+/// Returns an error if the source cannot be parsed.
+pub fn format_source_with_config(source: &str, config: &PrettyConfig) -> Result<String> {
+    let (forms, comments) = parse_with_comments(source)?;
+    let formatter = SourceFormatter {
+        config,
+        comments: &comments,
+    };
+
+    let mut output = String::new();
+    let mut dangling: Vec<&Comment> = comments.dangling().iter().collect();
+    let mut previous_end = None;
+    for form in &forms {
+        let leading = comments.leading(form);
+        if let Some(end) = previous_end {
+            let next = leading.first().map_or(form.span().start, |c| c.span.start);
+            output.push('\n');
+            if has_blank_line(&source[end..next]) {
+                output.push('\n');
+            }
+        }
+        for (i, comment) in leading.iter().enumerate() {
+            output.push_str(comment.text.trim_end());
+            output.push('\n');
+            let next = leading
+                .get(i + 1)
+                .map_or(form.span().start, |c| c.span.start);
+            if has_blank_line(&source[comment.span.start..next]) {
+                output.push('\n');
+            }
+        }
+        output.push_str(&formatter.format(form, 0));
+
+        // Comments left before a closing delimiter follow their form
+        let span = form.span();
+        let (inside, rest): (Vec<&Comment>, Vec<&Comment>) = dangling
+            .into_iter()
+            .partition(|c| c.span.start >= span.start && c.span.end <= span.end);
+        for comment in inside {
+            output.push('\n');
+            output.push_str(comment.text.trim_end());
+        }
+        dangling = rest;
+        previous_end = Some(span.end);
+    }
+
+    for comment in dangling {
+        if let Some(end) = previous_end {
+            output.push('\n');
+            if has_blank_line(&source[end..comment.span.start]) {
+                output.push('\n');
+            }
+        }
+        output.push_str(comment.text.trim_end());
+        previous_end = Some(comment.span.end);
+    }
+
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// Whether the whitespace between two forms contains an empty line.
+fn has_blank_line(between: &str) -> bool {
+    let lines: Vec<&str> = between.split('\n').collect();
+    lines.len() > 2
+        && lines[1..lines.len() - 1]
+            .iter()
+            .any(|l| l.trim().is_empty())
+}
+
+/// The column just past `text`, which started at `column`.
+fn end_column(column: usize, text: &str) -> usize {
+    match text.rsplit_once('\n') {
+        Some((_, last)) => last.chars().count(),
+        None => column + text.chars().count(),
+    }
+}
+
+/// Declarations laid out one option per line even when they would fit on one.
+const BROKEN_DECLARATIONS: &[&str] = &["rule:", "derived:", "constraint:", "action:", "command:"];
+
+/// Forms whose first argument is a vector of `name value` bindings.
+const BINDING_FORMS: &[&str] = &["let", "loop", "binding", "if-let", "when-let"];
+
+/// Lays out forms across lines for [`format_source`].
+struct SourceFormatter<'a> {
+    config: &'a PrettyConfig,
+    comments: &'a CommentMap,
+}
+
+impl SourceFormatter<'_> {
+    /// Formats `ast` as if it starts at `column`; continuation lines carry
+    /// their own indentation.
+    fn format(&self, ast: &Ast, column: usize) -> String {
+        let flat = pretty_print(ast);
+        let fits = column + flat.chars().count() <= self.config.max_width;
+        if fits && !self.contains_comments(ast) && !Self::always_breaks(ast) {
+            return flat;
+        }
+
+        match ast {
+            Ast::List(items, _) => self.format_list(items, column),
+            Ast::Vector(items, _) => self.format_sequence("[", items, "]", column),
+            Ast::Set(items, _) => self.format_sequence("#{", items, "}", column),
+            Ast::Map(entries, _) => self.format_map(entries, column),
+            Ast::Quote(inner, _) => format!("'{}", self.format(inner, column + 1)),
+            Ast::Unquote(inner, _) => format!("~{}", self.format(inner, column + 1)),
+            Ast::UnquoteSplice(inner, _) => format!("~@{}", self.format(inner, column + 2)),
+            Ast::SyntaxQuote(inner, _) => format!("`{}", self.format(inner, column + 1)),
+            Ast::Tagged(tag, inner, _) => {
+                format!("#{tag}{}", self.format(inner, column + 1 + tag.len()))
+            }
+            _ => flat,
+        }
+    }
+
+    /// Whether any form nested in `ast` has comments before it.
+    fn contains_comments(&self, ast: &Ast) -> bool {
+        let children: Vec<&Ast> = match ast {
+            Ast::List(items, _) | Ast::Vector(items, _) | Ast::Set(items, _) => {
+                items.iter().collect()
+            }
+            Ast::Map(entries, _) => entries.iter().flat_map(|(k, v)| [k, v]).collect(),
+            Ast::Quote(inner, _)
+            | Ast::Unquote(inner, _)
+            | Ast::UnquoteSplice(inner, _)
+            | Ast::SyntaxQuote(inner, _)
+            | Ast::Tagged(_, inner, _) => vec![inner.as_ref()],
+            _ => Vec::new(),
+        };
+        children
+            .into_iter()
+            .any(|child| !self.comments.leading(child).is_empty() || self.contains_comments(child))
+    }
+
+    fn always_breaks(ast: &Ast) -> bool {
+        matches!(ast, Ast::List(items, _)
+            if items.len() > 2
+                && matches!(&items[0], Ast::Symbol(head, _) if BROKEN_DECLARATIONS.contains(&head.as_str())))
+    }
+
+    /// Starts a new line at `column`, writing `item`'s comments above it.
+    fn new_line(&self, output: &mut String, item: &Ast, column: usize) {
+        output.push('\n');
+        self.push_indent(output, column);
+        for comment in self.comments.leading(item) {
+            output.push_str(comment.text.trim_end());
+            output.push('\n');
+            self.push_indent(output, column);
+        }
+    }
+
+    #[allow(clippy::unused_self)]
+    fn push_indent(&self, output: &mut String, column: usize) {
+        output.extend(std::iter::repeat_n(' ', column));
+    }
+
+    /// Elements one per line, aligned after the opening delimiter.
+    fn format_sequence(&self, open: &str, items: &[Ast], close: &str, column: usize) -> String {
+        let inner = column + open.len();
+        let mut output = open.to_string();
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 || !self.comments.leading(item).is_empty() {
+                self.new_line(&mut output, item, inner);
+            }
+            output.push_str(&self.format(item, inner));
+        }
+        output.push_str(close);
+        output
+    }
+
+    fn format_map(&self, entries: &[(Ast, Ast)], column: usize) -> String {
+        let inner = column + 1;
+        let mut output = "{".to_string();
+        for (i, (key, value)) in entries.iter().enumerate() {
+            if i > 0 || !self.comments.leading(key).is_empty() {
+                self.new_line(&mut output, key, inner);
+            }
+            let key_text = self.format(key, inner);
+            let value_column = inner + key_text.chars().count() + 1;
+            output.push_str(&key_text);
+            if self.comments.leading(value).is_empty() {
+                output.push(' ');
+                output.push_str(&self.format(value, value_column));
+            } else {
+                self.new_line(&mut output, value, inner + self.config.indent_width);
+                output.push_str(&self.format(value, inner + self.config.indent_width));
+            }
+        }
+        output.push('}');
+        output
+    }
+
+    /// A `let`-style binding vector, one `name value` pair per line.
+    fn format_bindings(&self, vector: &Ast, bindings: &[Ast], column: usize) -> String {
+        let flat = pretty_print(vector);
+        if column + flat.chars().count() <= self.config.max_width && !self.contains_comments(vector)
+        {
+            return flat;
+        }
+        let inner = column + 1;
+        let mut output = "[".to_string();
+        for (i, pair) in bindings.chunks(2).enumerate() {
+            if i > 0 || !self.comments.leading(&pair[0]).is_empty() {
+                self.new_line(&mut output, &pair[0], inner);
+            }
+            let name = self.format(&pair[0], inner);
+            output.push_str(&name);
+            if let Some(value) = pair.get(1) {
+                if self.comments.leading(value).is_empty() {
+                    output.push(' ');
+                    output.push_str(&self.format(value, end_column(inner, &name) + 1));
+                } else {
+                    self.new_line(&mut output, value, inner);
+                    output.push_str(&self.format(value, inner));
+                }
+            }
+        }
+        output.push(']');
+        output
+    }
+
+    fn format_list(&self, items: &[Ast], column: usize) -> String {
+        let Some((head @ (Ast::Symbol(..) | Ast::Keyword(..)), args)) = items.split_first() else {
+            return self.format_sequence("(", items, ")", column);
+        };
+        let head_text = pretty_print(head);
+        let body = column + self.config.indent_width;
+        let mut output = format!("({head_text}");
+
+        let is_declaration = matches!(head, Ast::Symbol(name, _) if name.ends_with(':'));
+        let mut rest = args;
+        if let Some((first, after)) = args.split_first() {
+            if self.comments.leading(first).is_empty() && head_text != "do" {
+                output.push(' ');
+                let first_column = column + 1 + head_text.chars().count() + 1;
+                let first_text = match first {
+                    Ast::Vector(bindings, _) if BINDING_FORMS.contains(&head_text.as_str()) => {
+                        self.format_bindings(first, bindings, first_column)
+                    }
+                    _ => self.format(first, first_column),
+                };
+                output.push_str(&first_text);
+                rest = after;
+            }
+        }
+
+        if is_declaration {
+            // An odd option count means a leading positional value, such as
+            // the type in `(component: tag/player :bool :default true)`
+            if rest.len() % 2 == 1 && self.comments.leading(&rest[0]).is_empty() {
+                let column = end_column(column, &output) + 1;
+                output.push(' ');
+                output.push_str(&self.format(&rest[0], column));
+                rest = &rest[1..];
+            }
+            for pair in rest.chunks(2) {
+                self.new_line(&mut output, &pair[0], body);
+                let key = self.format(&pair[0], body);
+                output.push_str(&key);
+                if let Some(value) = pair.get(1) {
+                    if self.comments.leading(value).is_empty() {
+                        output.push(' ');
+                        let value_column = body + key.chars().count() + 1;
+                        output.push_str(&match value {
+                            // Several clauses or effects get one per line
+                            Ast::Vector(clauses, _)
+                                if clauses.len() > 1
+                                    && clauses
+                                        .iter()
+                                        .all(|c| matches!(c, Ast::Vector(..) | Ast::List(..))) =>
+                            {
+                                self.format_sequence("[", clauses, "]", value_column)
+                            }
+                            _ => self.format(value, value_column),
+                        });
+                    } else {
+                        self.new_line(&mut output, value, body);
+                        output.push_str(&self.format(value, body));
+                    }
+                }
+            }
+        } else {
+            for item in rest {
+                self.new_line(&mut output, item, body);
+                output.push_str(&self.format(item, body));
+            }
+        }
+
+        output.push(')');
+        output
+    }
+}
+
 /// Pretty-printer state.
 struct PrettyPrinter {
     config: PrettyConfig,
@@ -437,6 +772,90 @@ mod tests {
         }
     }
 
+    // =========================================================================
+    // Source Formatting
+    // =========================================================================
+
+    #[test]
+    fn format_lays_out_declarations() {
+        let source = "(component: health :current :int :max :int)
+(component: tag/player :bool :default true)
+(command: look-around :syntax [:verb/look] :action look)
+(rule: heal :salience 5 :where [[?e :health ?h] [?e :tag/player true] [?e :regen/per-tick ?r]] :then [(set! ?e :health (+ ?h ?r))])";
+        let expected = "(component: health :current :int :max :int)
+(component: tag/player :bool :default true)
+(command: look-around
+  :syntax [:verb/look]
+  :action look)
+(rule: heal
+  :salience 5
+  :where [[?e :health ?h]
+          [?e :tag/player true]
+          [?e :regen/per-tick ?r]]
+  :then [(set! ?e :health (+ ?h ?r))])
+";
+        assert_eq!(format_source(source).unwrap(), expected);
+        assert_eq!(format_source(expected).unwrap(), expected);
+    }
+
+    #[test]
+    fn format_breaks_long_calls() {
+        let config = PrettyConfig {
+            max_width: 32,
+            ..PrettyConfig::default()
+        };
+        let source = "(let [damage (- attack defense) hp (get target :hp)] (when (> damage 0) (set! target :hp (- hp damage))))";
+        let expected = "(let [damage (- attack defense)
+      hp (get target :hp)]
+  (when (> damage 0)
+    (set! target
+      :hp
+      (- hp damage))))
+";
+        assert_eq!(
+            format_source_with_config(source, &config).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn format_keeps_comments_and_paragraphs() {
+        let source = ";; Health
+(component: health   :current :int)
+
+
+
+;; Regeneration
+(rule: regen
+  ;; only the living
+  :where [[?e :health ?h]] :then []) ; trailing
+(foo ; inside
+ )
+; end
+";
+        let expected = ";; Health
+(component: health :current :int)
+
+;; Regeneration
+(rule: regen
+  ;; only the living
+  :where [[?e :health ?h]]
+  :then [])
+; trailing
+(foo)
+; inside
+; end
+";
+        let formatted = format_source(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_source(&formatted).unwrap(), formatted);
+        assert_eq!(
+            parse(&formatted).unwrap().len(),
+            parse(source).unwrap().len()
+        );
+        assert!(format_source("(unclosed").is_err());
+    }
+
     // =========================================================================
     // Helper Functions
     // =========================================================================
@@ -3,10 +3,14 @@
 //! `Span` tracks the position of tokens and AST nodes in source code
 //! for error reporting and debugging.
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// A span of source text.
 ///
 /// Tracks byte offsets and line/column positions for error reporting.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Span {
     /// Byte offset where this span starts.
     pub start: usize,
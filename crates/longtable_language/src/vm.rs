@@ -26,40 +26,50 @@
 
 mod context;
 mod native;
+mod rich;
 #[cfg(test)]
 mod tests;
 
-pub use context::{ReadOnlyContext, RuntimeContext, VmContext, VmEffect, WorldContext};
+pub use context::{
+    ReadOnlyContext, RuntimeContext, SESSION_COMMANDS, VmContext, VmEffect, WorldContext,
+};
+pub use rich::{RichSpan, plain_text};
 
 use context::NoRuntimeContext;
 use native::{
-    add_values, compare_for_sort, compare_values, div_values, format_value, is_truthy, mod_values,
-    mul_values, native_abs, native_acos, native_and, native_asin, native_assoc, native_atan,
-    native_atan2, native_bool_p, native_cbrt, native_ceil, native_char_at, native_clamp,
-    native_coll_p, native_concat, native_conj, native_cons, native_contains_p, native_cos,
-    native_cosh, native_count, native_dec, native_dedupe, native_disj, native_dissoc,
-    native_distinct, native_drop, native_e, native_empty_p, native_entity_p, native_exp,
-    native_first, native_flatten, native_float_p, native_floor, native_fn_p, native_format,
-    native_get, native_inc, native_int_p, native_interleave, native_interpose, native_into,
-    native_keys, native_keyword_p, native_last, native_list_p, native_log, native_log2,
-    native_log10, native_map_p, native_max, native_merge, native_min, native_nil_p, native_nth,
-    native_number_p, native_or, native_parse_int, native_partition, native_partition_all,
-    native_pi, native_pow, native_range, native_rem, native_repeat, native_rest, native_reverse,
-    native_round, native_set, native_set_p, native_sin, native_sinh, native_some_p, native_sort,
-    native_sqrt, native_str_blank, native_str_contains, native_str_ends_with, native_str_join,
-    native_str_len, native_str_lower, native_str_replace, native_str_replace_all, native_str_split,
+    TextContext, add_values, compare_for_sort, compare_values, div_values, format_value,
+    format_with, is_truthy, mod_values, mul_values, native_a_or_an, native_abs, native_acos,
+    native_and, native_asin, native_assoc, native_atan, native_atan2, native_bigint, native_bool_p,
+    native_cbrt, native_ceil, native_char_at, native_clamp, native_coll_p, native_concat,
+    native_conj, native_cons, native_contains_p, native_cos, native_cosh, native_count,
+    native_days, native_dec, native_dedupe, native_disj, native_dissoc, native_distinct,
+    native_drop, native_duration, native_duration_p, native_e, native_empty_p,
+    native_entities_within, native_entity_p, native_exp, native_find_path, native_first,
+    native_flatten, native_float_p, native_floor, native_fn_p, native_get, native_hours,
+    native_inc, native_instant, native_instant_p, native_int_p, native_interleave,
+    native_interpose, native_into, native_iterate, native_join_and, native_keys, native_keyword_p,
+    native_last, native_lazy, native_lazy_p, native_list_p, native_log, native_log2, native_log10,
+    native_make_record, native_map_p, native_max, native_merge, native_min, native_minutes,
+    native_nil_p, native_nth, native_number_p, native_or, native_parse_int, native_partition,
+    native_partition_all, native_pi, native_pluralize, native_pow, native_range, native_rem,
+    native_repeat, native_rest, native_reverse, native_round, native_seconds, native_set,
+    native_set_p, native_sin, native_sinh, native_some_p, native_sort, native_sqrt,
+    native_str_blank, native_str_contains, native_str_ends_with, native_str_join, native_str_len,
+    native_str_lower, native_str_replace, native_str_replace_all, native_str_split,
     native_str_starts_with, native_str_substring, native_str_trim, native_str_trim_left,
     native_str_trim_right, native_str_upper, native_string_p, native_symbol_p, native_take,
-    native_tan, native_tanh, native_trunc, native_type, native_vals, native_vec, native_vec_add,
-    native_vec_angle, native_vec_cross, native_vec_distance, native_vec_dot, native_vec_length,
-    native_vec_length_sq, native_vec_lerp, native_vec_mul, native_vec_normalize, native_vec_scale,
-    native_vec_sub, native_vector_p, native_zip, neg_value, sub_values,
+    native_tan, native_tanh, native_to_millis, native_to_seconds, native_trunc, native_type,
+    native_vals, native_vec, native_vec_add, native_vec_angle, native_vec_cross,
+    native_vec_distance, native_vec_dot, native_vec_length, native_vec_length_sq, native_vec_lerp,
+    native_vec_mul, native_vec_normalize, native_vec_scale, native_vec_sub, native_vec_x,
+    native_vec_y, native_vec_z, native_vec2, native_vec3, native_vector_p, native_within_p,
+    native_zip, neg_value, sub_values,
 };
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use longtable_foundation::{
-    EntityId, Error, ErrorKind, KeywordId, LtMap, LtSet, LtVec, Result, Value,
+    EntityId, Error, ErrorKind, KeywordId, LtMap, LtSeq, LtSet, LtVec, Result, SeqStep, Type, Value,
 };
 
 use crate::compiler::CompiledProgram;
@@ -80,6 +90,27 @@ struct PendingVecOps {
     additions: Vec<Value>,
 }
 
+/// Instructions a `(peek ...)` may run when it doesn't set a `:budget`.
+pub const DEFAULT_PEEK_BUDGET: u32 = 10_000;
+
+/// An open `(peek ...)` sandbox.
+///
+/// Holds the pending writes of the surrounding execution while the sandbox
+/// reads the world without them, and counts down its instruction budget.
+struct Sandbox {
+    /// Budget the `peek` asked for, for error messages.
+    budget: u32,
+    /// Instructions left when the sandbox opened.
+    start: u32,
+    /// Instructions left now.
+    remaining: u32,
+    /// Pending writes set aside until the sandbox closes.
+    pending_fields: HashMap<FieldKey, Value>,
+    pending_components: HashMap<ComponentKey, Option<Value>>,
+    pending_vec_ops: HashMap<FieldKey, PendingVecOps>,
+    pending_spawns: HashMap<EntityId, LtMap<Value, Value>>,
+}
+
 // =============================================================================
 // Macros for reducing VM code duplication
 // =============================================================================
@@ -105,6 +136,34 @@ macro_rules! native_dispatch {
     };
 }
 
+/// Formats `(msg :key args...)`: the key's template from the message
+/// catalog, with `args` for its directives.
+fn format_message<C: VmContext>(
+    args: &[Value],
+    ctx: &C,
+    display: &dyn Fn(&Value) -> String,
+) -> Result<String> {
+    let Some(Value::Keyword(key)) = args.first() else {
+        return Err(Error::new(ErrorKind::TypeMismatch {
+            expected: Type::Keyword,
+            actual: args.first().map_or(Type::Nil, Value::value_type),
+        }));
+    };
+    let template = ctx.message(*key).ok_or_else(|| {
+        Error::new(ErrorKind::NotFound(format!(
+            "no message {} in the catalog",
+            display(&args[0])
+        )))
+    })?;
+    let format_args: Vec<Value> = std::iter::once(Value::String(template.into()))
+        .chain(args[1..].iter().cloned())
+        .collect();
+    Ok(match format_with(&format_args, display)? {
+        Value::String(text) => text.to_string(),
+        other => display(&other),
+    })
+}
+
 /// Formats a value for display, using the context to resolve keywords.
 fn format_value_with_ctx<C: VmContext>(value: &Value, ctx: &C) -> String {
     match value {
@@ -124,6 +183,12 @@ fn format_value_with_ctx<C: VmContext>(value: &Value, ctx: &C) -> String {
             .keyword_to_string(*id)
             .map_or_else(|| format!("Keyword({})", id.index()), |s| format!(":{s}")),
         Value::EntityRef(id) => format!("Entity({}, {})", id.index, id.generation),
+        Value::BigInt(_)
+        | Value::Instant(_)
+        | Value::Duration(_)
+        | Value::Vec2(_)
+        | Value::Vec3(_)
+        | Value::Seq(_) => value.to_string(),
         Value::Vec(v) => {
             let items: Vec<_> = v.iter().map(|v| format_value_with_ctx(v, ctx)).collect();
             format!("[{}]", items.join(" "))
@@ -153,6 +218,18 @@ fn format_value_with_ctx<C: VmContext>(value: &Value, ctx: &C) -> String {
     }
 }
 
+/// A call frame on the VM's explicit call stack (for TCO support).
+struct CallFrame {
+    /// The function index we were executing (None for initial/top-level code).
+    function_idx: Option<usize>,
+    /// Return address (instruction pointer after the call).
+    return_ip: usize,
+    /// Saved locals.
+    saved_locals: Vec<Value>,
+    /// Saved captures.
+    saved_captures: Vec<Value>,
+}
+
 /// Stack-based virtual machine.
 pub struct Vm {
     /// Operand stack.
@@ -169,6 +246,8 @@ pub struct Vm {
     ip: usize,
     /// Output from print statements.
     output: Vec<String>,
+    /// The same output as styled spans, with `say`'s markup.
+    rich_output: Vec<RichSpan>,
     /// Collected effects from execution.
     effects: Vec<VmEffect>,
     /// Counter for spawned entities (used for temporary IDs).
@@ -193,6 +272,8 @@ pub struct Vm {
     /// Maps temp `EntityId` to its components map, allowing queries to see
     /// spawned entities before effects are applied to the World.
     pending_spawns: HashMap<EntityId, LtMap<Value, Value>>,
+    /// Open `(peek ...)` sandboxes, innermost last.
+    sandboxes: Vec<Sandbox>,
 }
 
 impl Default for Vm {
@@ -213,6 +294,7 @@ impl Vm {
             captures: Vec::new(),
             ip: 0,
             output: Vec::new(),
+            rich_output: Vec::new(),
             effects: Vec::new(),
             spawn_counter: 0,
             pending_fields: HashMap::new(),
@@ -221,6 +303,7 @@ impl Vm {
             globals_by_name: HashMap::new(),
             effects_counts: HashMap::new(),
             pending_spawns: HashMap::new(),
+            sandboxes: Vec::new(),
         }
     }
 
@@ -229,20 +312,111 @@ impl Vm {
         self.globals_by_name.insert(name, slot);
     }
 
+    /// Adds every keyword held by a global variable to `live`.
+    pub fn mark_keywords(&self, live: &mut HashSet<KeywordId>) {
+        for value in &self.globals {
+            value.mark_keywords(live);
+        }
+    }
+
     /// Resets the VM state.
     pub fn reset(&mut self) {
+        self.abort();
+        self.spawn_counter = 0;
+    }
+
+    /// Abandons whatever the VM was running.
+    ///
+    /// Unlike [`Self::reset`], the spawn counter is kept, so temporary IDs
+    /// handed out before the abort are never handed out again.
+    pub fn abort(&mut self) {
         self.stack.clear();
         self.locals.fill(Value::Nil);
         self.bindings.clear();
         self.captures.clear();
         self.ip = 0;
         self.output.clear();
+        self.rich_output.clear();
         self.effects.clear();
-        self.spawn_counter = 0;
         self.pending_fields.clear();
         self.pending_components.clear();
         self.pending_vec_ops.clear();
         self.pending_spawns.clear();
+        self.sandboxes.clear();
+    }
+
+    /// Opens a `peek` sandbox with the given instruction budget.
+    ///
+    /// A nested sandbox gets no more than its enclosing one has left.
+    fn open_sandbox(&mut self, budget: u32) {
+        let remaining = self
+            .sandboxes
+            .last()
+            .map_or(budget, |outer| budget.min(outer.remaining));
+        self.sandboxes.push(Sandbox {
+            budget,
+            start: remaining,
+            remaining,
+            pending_fields: std::mem::take(&mut self.pending_fields),
+            pending_components: std::mem::take(&mut self.pending_components),
+            pending_vec_ops: std::mem::take(&mut self.pending_vec_ops),
+            pending_spawns: std::mem::take(&mut self.pending_spawns),
+        });
+    }
+
+    /// Closes the innermost `peek` sandbox, restoring the pending writes it
+    /// set aside and charging its instructions to the enclosing sandbox.
+    fn close_sandbox(&mut self) {
+        let Some(sandbox) = self.sandboxes.pop() else {
+            return;
+        };
+        self.pending_fields = sandbox.pending_fields;
+        self.pending_components = sandbox.pending_components;
+        self.pending_vec_ops = sandbox.pending_vec_ops;
+        self.pending_spawns = sandbox.pending_spawns;
+        if let Some(outer) = self.sandboxes.last_mut() {
+            outer.remaining = outer
+                .remaining
+                .saturating_sub(sandbox.start - sandbox.remaining);
+        }
+    }
+
+    /// Charges one instruction to the innermost `peek` sandbox, refusing
+    /// effects and instructions past its budget.
+    fn charge_sandbox(&mut self, op: &Opcode) -> Result<()> {
+        let Some(sandbox) = self.sandboxes.last_mut() else {
+            return Ok(());
+        };
+        if op.is_effect() {
+            return Err(Error::new(ErrorKind::Refused(format!(
+                "peek is read-only: {op:?} is not allowed inside (peek ...)"
+            ))));
+        }
+        if sandbox.remaining == 0 {
+            return Err(Error::new(ErrorKind::Refused(format!(
+                "peek ran past its budget of {} instructions",
+                sandbox.budget
+            ))));
+        }
+        sandbox.remaining -= 1;
+        Ok(())
+    }
+
+    /// Charges the innermost `peek` sandbox one instruction per entity a
+    /// search read, so scanning a large world costs what it reads.
+    fn charge_reads(&mut self, count: usize) -> Result<()> {
+        let Some(sandbox) = self.sandboxes.last_mut() else {
+            return Ok(());
+        };
+        let count = u32::try_from(count).unwrap_or(u32::MAX);
+        if count > sandbox.remaining {
+            return Err(Error::new(ErrorKind::Refused(format!(
+                "peek ran past its budget of {} instructions",
+                sandbox.budget
+            ))));
+        }
+        sandbox.remaining -= count;
+        Ok(())
     }
 
     /// Sets pattern bindings for rule execution.
@@ -256,9 +430,25 @@ impl Vm {
         &self.output
     }
 
+    /// Returns the output as styled spans, in order.
+    ///
+    /// Plain text from `print` and `println` appears as unstyled spans, so
+    /// the spans' text is always [`output`](Self::output) joined.
+    #[must_use]
+    pub fn rich_output(&self) -> &[RichSpan] {
+        &self.rich_output
+    }
+
     /// Clears the output buffer.
     pub fn clear_output(&mut self) {
         self.output.clear();
+        self.rich_output.clear();
+    }
+
+    /// Appends unstyled text to the output.
+    fn write_plain(&mut self, text: String) {
+        rich::push_span(&mut self.rich_output, RichSpan::plain(text.as_str()));
+        self.output.push(text);
     }
 
     /// Returns the collected effects from execution.
@@ -296,6 +486,7 @@ impl Vm {
             &program.constants,
             &program.functions,
             &mut ctx,
+            None,
         )
     }
 
@@ -314,6 +505,7 @@ impl Vm {
             &program.constants,
             &program.functions,
             &mut wrapper,
+            None,
         )
     }
 
@@ -327,40 +519,124 @@ impl Vm {
         program: &CompiledProgram,
         ctx: &mut C,
     ) -> Result<Value> {
-        self.execute_internal(&program.code, &program.constants, &program.functions, ctx)
+        self.execute_internal(
+            &program.code,
+            &program.constants,
+            &program.functions,
+            ctx,
+            None,
+        )
     }
 
     /// Executes bytecode with a constants pool (no functions available).
     pub fn execute_bytecode(&mut self, code: &Bytecode, constants: &[Value]) -> Result<Value> {
         let mut ctx = NoRuntimeContext;
-        self.execute_internal(code, constants, &[], &mut ctx)
+        self.execute_internal(code, constants, &[], &mut ctx, None)
     }
 
     /// Executes bytecode with a `RuntimeContext`.
     ///
     /// This is the unified internal execution method that handles all opcodes.
-    /// Uses an explicit call stack for tail-call optimization.
+    /// `function` is the index of the function `initial_code` belongs to,
+    /// when a native such as `map` calls back into one.
+    /// Sandboxes opened by this call and left open by an error are closed
+    /// before the error is returned.
     fn execute_internal<C: RuntimeContext>(
         &mut self,
         initial_code: &Bytecode,
         constants: &[Value],
         functions: &[crate::compiler::CompiledFunction],
         ctx: &mut C,
+        function: Option<usize>,
     ) -> Result<Value> {
-        /// A call frame on the explicit call stack (for TCO support).
-        struct CallFrame {
-            /// The function index we were executing (None for initial/top-level code).
-            function_idx: Option<usize>,
-            /// Return address (instruction pointer after the call).
-            return_ip: usize,
-            /// Saved locals.
-            saved_locals: Vec<Value>,
-            /// Saved captures.
-            saved_captures: Vec<Value>,
+        let depth = self.sandboxes.len();
+        let result = self
+            .run(initial_code, constants, functions, ctx, function)
+            .and_then(|value| match value {
+                // A program's finite sequence is shown and kept by its values
+                Value::Seq(seq) if function.is_none() && seq.is_bounded() => {
+                    self.realize_seq(&seq, None, constants, functions, ctx)
+                }
+                other => Ok(other),
+            });
+        if result.is_err() {
+            while self.sandboxes.len() > depth {
+                self.close_sandbox();
+            }
         }
+        result
+    }
 
-        let mut call_stack: Vec<CallFrame> = Vec::with_capacity(256);
-        let mut current_function_idx: Option<usize> = None;
+    /// Runs bytecode until it returns.
+    ///
+    /// An error is given the source position of the instruction that failed,
+    /// when the compiler recorded one, and a backtrace of the DSL functions
+    /// it passed through, innermost first. A position inside a function
+    /// names the file the function was defined in.
+    fn run<C: RuntimeContext>(
+        &mut self,
+        initial_code: &Bytecode,
+        constants: &[Value],
+        functions: &[crate::compiler::CompiledFunction],
+        ctx: &mut C,
+        function: Option<usize>,
+    ) -> Result<Value> {
+        let mut at = (function, 0);
+        let mut call_stack = Vec::with_capacity(256);
+        self.run_code(
+            initial_code,
+            constants,
+            functions,
+            ctx,
+            &mut at,
+            &mut call_stack,
+        )
+        .map_err(|mut error| {
+            let code =
+                |function: Option<usize>| function.map_or(initial_code, |idx| &functions[idx].code);
+            let (function, ip) = at;
+            if let Some(span) = code(function).span_at(ip) {
+                error = error.or_position(span.line as usize, span.column as usize);
+                if let Some(idx) = function {
+                    error = error.or_source(functions[idx].source_name());
+                }
+            }
+
+            // The failing function, then each caller at its call
+            let callers = call_stack
+                .iter()
+                .rev()
+                .map(|frame| (frame.function_idx, frame.return_ip.saturating_sub(1)));
+            for (function, ip) in std::iter::once((function, ip)).chain(callers) {
+                let Some(idx) = function else { continue };
+                let name = functions[idx].describe();
+                let source = functions[idx].source_name();
+                error = match code(function).span_at(ip) {
+                    Some(span) => {
+                        error.with_frame(format!("{name} ({source}:{}:{})", span.line, span.column))
+                    }
+                    None => error.with_frame(format!("{name} ({source})")),
+                };
+            }
+            error
+        })
+    }
+
+    /// Runs bytecode until it returns, keeping `at` pointed at the function
+    /// and instruction being executed.
+    ///
+    /// Uses an explicit call stack for tail-call optimization; on error,
+    /// `call_stack` is left holding the callers of the failing function.
+    fn run_code<C: RuntimeContext>(
+        &mut self,
+        initial_code: &Bytecode,
+        constants: &[Value],
+        functions: &[crate::compiler::CompiledFunction],
+        ctx: &mut C,
+        at: &mut (Option<usize>, usize),
+        call_stack: &mut Vec<CallFrame>,
+    ) -> Result<Value> {
+        let mut current_function_idx = at.0;
         self.ip = 0;
 
         loop {
@@ -391,8 +667,13 @@ impl Vm {
 
             // Clone opcode so we can modify current_function_idx
             let op = code.ops[self.ip].clone();
+            *at = (current_function_idx, self.ip);
             self.ip += 1;
 
+            if !self.sandboxes.is_empty() {
+                self.charge_sandbox(&op)?;
+            }
+
             match op {
                 Opcode::Nop => {}
 
@@ -405,6 +686,9 @@ impl Vm {
                     self.pop()?;
                 }
 
+                Opcode::PeekBegin(budget) => self.open_sandbox(budget),
+                Opcode::PeekEnd => self.close_sandbox(),
+
                 Opcode::Dup => {
                     let value = self.peek()?.clone();
                     self.push(value);
@@ -422,8 +706,14 @@ impl Vm {
                 }
 
                 // Comparison
-                Opcode::Eq => self.binary_op(|a, b| Ok(Value::Bool(a == b)))?,
-                Opcode::Ne => self.binary_op(|a, b| Ok(Value::Bool(a != b)))?,
+                Opcode::Eq => {
+                    let equal = self.pop_equal(constants, functions, ctx)?;
+                    self.push(Value::Bool(equal));
+                }
+                Opcode::Ne => {
+                    let equal = self.pop_equal(constants, functions, ctx)?;
+                    self.push(Value::Bool(!equal));
+                }
                 Opcode::Lt => self.binary_op(|a, b| compare_values(a, b, |ord| ord.is_lt()))?,
                 Opcode::Le => self.binary_op(|a, b| compare_values(a, b, |ord| ord.is_le()))?,
                 Opcode::Gt => self.binary_op(|a, b| compare_values(a, b, |ord| ord.is_gt()))?,
@@ -611,6 +901,7 @@ impl Vm {
                     // Continue main loop with new function's code
                 }
                 Opcode::CallNative(idx, arg_count) => {
+                    self.realize_native_args(idx, arg_count, constants, functions, ctx)?;
                     self.call_native(idx, arg_count, ctx)?;
                 }
                 Opcode::Return => {
@@ -828,6 +1119,7 @@ impl Vm {
                         },
                     );
 
+                    self.charge_reads(entities.len())?;
                     self.push(Value::Vec(entities));
                 }
 
@@ -854,6 +1146,7 @@ impl Vm {
                         .into_iter()
                         .map(Value::EntityRef)
                         .collect();
+                    self.charge_reads(entities.len())?;
                     self.push(Value::Vec(entities));
                 }
 
@@ -886,6 +1179,7 @@ impl Vm {
                         .into_iter()
                         .map(Value::EntityRef)
                         .collect();
+                    self.charge_reads(entities.len())?;
                     self.push(Value::Vec(entities));
                 }
 
@@ -901,6 +1195,7 @@ impl Vm {
                         .into_iter()
                         .map(Value::EntityRef)
                         .collect();
+                    self.charge_reads(entities.len())?;
                     self.push(Value::Vec(entities));
                 }
 
@@ -916,6 +1211,7 @@ impl Vm {
                         .into_iter()
                         .map(Value::EntityRef)
                         .collect();
+                    self.charge_reads(entities.len())?;
                     self.push(Value::Vec(entities));
                 }
 
@@ -926,34 +1222,34 @@ impl Vm {
                         Value::Map(m) => m,
                         _ => LtMap::new(),
                     };
+                    let temp_id = self.spawn_pending(components);
+                    self.push(Value::EntityRef(temp_id));
+                }
 
-                    // Generate a temporary entity ID with a large base offset to avoid
-                    // conflicts with real entity IDs in the World. Uses generation 1
-                    // (odd = alive) to match EntityStore conventions.
-                    self.spawn_counter += 1;
-                    let temp_id = EntityId {
-                        index: 1_000_000_000 + self.spawn_counter,
-                        generation: 1,
-                    };
-
-                    // Store in pending_spawns for read-your-writes semantics
-                    // This allows queries (with-component, get-field) to see spawned
-                    // entities before effects are applied to the World.
-                    self.pending_spawns.insert(temp_id, components.clone());
-
-                    // Also populate pending_components so has-component works
-                    for (key, value) in components.iter() {
-                        if let Value::Keyword(comp_kw) = key {
-                            self.pending_components
-                                .insert((temp_id, *comp_kw), Some(value.clone()));
+                Opcode::SpawnMany => {
+                    let batch = self.pop()?;
+                    let maps: Vec<Value> = match batch {
+                        Value::Vec(v) => v.iter().cloned().collect(),
+                        Value::List(l) => l.iter().cloned().collect(),
+                        _ => {
+                            return Err(Error::new(ErrorKind::TypeMismatch {
+                                expected: longtable_foundation::Type::Vec(Box::new(
+                                    longtable_foundation::Type::Any,
+                                )),
+                                actual: batch.value_type(),
+                            }));
                         }
+                    };
+                    let mut entities = LtVec::new();
+                    for components in maps {
+                        let components = match components {
+                            Value::Map(m) => m,
+                            _ => LtMap::new(),
+                        };
+                        entities =
+                            entities.push_back(Value::EntityRef(self.spawn_pending(components)));
                     }
-
-                    self.effects.push(VmEffect::Spawn {
-                        temp_id,
-                        components,
-                    });
-                    self.push(Value::EntityRef(temp_id));
+                    self.push(Value::Vec(entities));
                 }
 
                 Opcode::Destroy => {
@@ -962,6 +1258,64 @@ impl Vm {
                     self.effects.push(VmEffect::Destroy { entity });
                 }
 
+                Opcode::Emit => {
+                    let payload = self.pop()?;
+                    let event_val = self.pop()?;
+                    let event = extract_keyword(&event_val, ctx)?;
+                    self.effects.push(VmEffect::Emit { event, payload });
+                }
+
+                Opcode::Schedule | Opcode::ScheduleEvery => {
+                    let action = self.pop()?;
+                    let owner = match self.pop()? {
+                        Value::Nil => None,
+                        owner => Some(extract_entity(&owner)?),
+                    };
+                    let delay_val = self.pop()?;
+                    let delay = match delay_val {
+                        Value::Int(n) if n > 0 => n.unsigned_abs(),
+                        Value::Int(n) => {
+                            return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                                "schedule! delay must be at least 1 tick, got {n}"
+                            ))));
+                        }
+                        other => {
+                            return Err(Error::new(ErrorKind::TypeMismatch {
+                                expected: longtable_foundation::Type::Int,
+                                actual: other.value_type(),
+                            }));
+                        }
+                    };
+                    self.effects.push(VmEffect::Schedule {
+                        delay,
+                        repeat: matches!(op, Opcode::ScheduleEvery),
+                        owner,
+                        action,
+                    });
+                }
+
+                Opcode::Command(name_idx, arg_count) => {
+                    let name = match constants.get(name_idx as usize) {
+                        Some(Value::String(name)) => name.to_string(),
+                        _ => {
+                            return Err(Error::new(ErrorKind::Internal(
+                                "invalid session command name".to_string(),
+                            )));
+                        }
+                    };
+                    if !ctx.allows_commands() {
+                        return Err(Error::new(ErrorKind::Refused(format!(
+                            "({name}) needs a session to run in"
+                        ))));
+                    }
+                    let mut args = Vec::with_capacity(arg_count as usize);
+                    for _ in 0..arg_count {
+                        args.push(self.pop()?);
+                    }
+                    args.reverse();
+                    self.effects.push(VmEffect::Command { name, args });
+                }
+
                 Opcode::SetComponent => {
                     let value = self.pop()?;
                     let component_val = self.pop()?;
@@ -1321,7 +1675,7 @@ impl Vm {
                 // Misc
                 Opcode::Print => {
                     let value = self.pop()?;
-                    self.output.push(format_value(&value));
+                    self.write_plain(format_value(&value));
                 }
 
                 Opcode::KeywordToString => {
@@ -1367,6 +1721,12 @@ impl Vm {
                     let coll = self.pop()?;
                     let func_val = self.pop()?;
 
+                    // A lazy sequence just gains a step
+                    if let Value::Seq(seq) = &coll {
+                        self.push(Value::Seq(seq.with_step(SeqStep::Map(func_val))));
+                        continue;
+                    }
+
                     // Extract function reference
                     let func_ref = match &func_val {
                         Value::Fn(longtable_foundation::LtFn::Compiled(f)) => f.clone(),
@@ -1420,8 +1780,13 @@ impl Vm {
                         }
 
                         // Execute function
-                        let result =
-                            self.execute_internal(&func.code, constants, functions, ctx)?;
+                        let result = self.execute_internal(
+                            &func.code,
+                            constants,
+                            functions,
+                            ctx,
+                            Some(func_idx),
+                        )?;
 
                         // Restore state
                         self.ip = saved_ip;
@@ -1438,6 +1803,12 @@ impl Vm {
                     let coll = self.pop()?;
                     let func_val = self.pop()?;
 
+                    // A lazy sequence just gains a step
+                    if let Value::Seq(seq) = &coll {
+                        self.push(Value::Seq(seq.with_step(SeqStep::Filter(func_val))));
+                        continue;
+                    }
+
                     // Extract function reference
                     let func_ref = match &func_val {
                         Value::Fn(longtable_foundation::LtFn::Compiled(f)) => f.clone(),
@@ -1491,8 +1862,13 @@ impl Vm {
                         }
 
                         // Execute function
-                        let result =
-                            self.execute_internal(&func.code, constants, functions, ctx)?;
+                        let result = self.execute_internal(
+                            &func.code,
+                            constants,
+                            functions,
+                            ctx,
+                            Some(func_idx),
+                        )?;
 
                         // Restore state
                         self.ip = saved_ip;
@@ -1510,6 +1886,7 @@ impl Vm {
 
                 Opcode::Reduce => {
                     let coll = self.pop()?;
+                    let coll = self.realize_value(coll, constants, functions, ctx)?;
                     let init = self.pop()?;
                     let func_val = self.pop()?;
 
@@ -1567,8 +1944,13 @@ impl Vm {
                         }
 
                         // Execute function
-                        let result =
-                            self.execute_internal(&func.code, constants, functions, ctx)?;
+                        let result = self.execute_internal(
+                            &func.code,
+                            constants,
+                            functions,
+                            ctx,
+                            Some(func_idx),
+                        )?;
 
                         // Restore state
                         self.ip = saved_ip;
@@ -1583,6 +1965,7 @@ impl Vm {
 
                 Opcode::ReduceNoInit => {
                     let coll = self.pop()?;
+                    let coll = self.realize_value(coll, constants, functions, ctx)?;
                     let func_val = self.pop()?;
 
                     // Extract function reference
@@ -1645,8 +2028,13 @@ impl Vm {
                             }
 
                             // Execute function
-                            let result =
-                                self.execute_internal(&func.code, constants, functions, ctx)?;
+                            let result = self.execute_internal(
+                                &func.code,
+                                constants,
+                                functions,
+                                ctx,
+                                Some(func_idx),
+                            )?;
 
                             // Restore state
                             self.ip = saved_ip;
@@ -1662,6 +2050,7 @@ impl Vm {
 
                 Opcode::Every => {
                     let coll = self.pop()?;
+                    let coll = self.realize_value(coll, constants, functions, ctx)?;
                     let func_val = self.pop()?;
 
                     // Extract function reference
@@ -1717,8 +2106,13 @@ impl Vm {
                         }
 
                         // Execute function
-                        let result =
-                            self.execute_internal(&func.code, constants, functions, ctx)?;
+                        let result = self.execute_internal(
+                            &func.code,
+                            constants,
+                            functions,
+                            ctx,
+                            Some(func_idx),
+                        )?;
 
                         // Restore state
                         self.ip = saved_ip;
@@ -1736,6 +2130,7 @@ impl Vm {
 
                 Opcode::Some => {
                     let coll = self.pop()?;
+                    let coll = self.realize_value(coll, constants, functions, ctx)?;
                     let func_val = self.pop()?;
 
                     // Extract function reference
@@ -1791,8 +2186,13 @@ impl Vm {
                         }
 
                         // Execute function
-                        let result =
-                            self.execute_internal(&func.code, constants, functions, ctx)?;
+                        let result = self.execute_internal(
+                            &func.code,
+                            constants,
+                            functions,
+                            ctx,
+                            Some(func_idx),
+                        )?;
 
                         // Restore state
                         self.ip = saved_ip;
@@ -1813,6 +2213,12 @@ impl Vm {
                     let coll = self.pop()?;
                     let func_val = self.pop()?;
 
+                    // A lazy sequence just gains a step
+                    if let Value::Seq(seq) = &coll {
+                        self.push(Value::Seq(seq.with_step(SeqStep::TakeWhile(func_val))));
+                        continue;
+                    }
+
                     // Extract function reference
                     let func_ref = match &func_val {
                         Value::Fn(longtable_foundation::LtFn::Compiled(f)) => f.clone(),
@@ -1866,8 +2272,13 @@ impl Vm {
                         }
 
                         // Execute function
-                        let result =
-                            self.execute_internal(&func.code, constants, functions, ctx)?;
+                        let result = self.execute_internal(
+                            &func.code,
+                            constants,
+                            functions,
+                            ctx,
+                            Some(func_idx),
+                        )?;
 
                         // Restore state
                         self.ip = saved_ip;
@@ -1888,6 +2299,12 @@ impl Vm {
                     let coll = self.pop()?;
                     let func_val = self.pop()?;
 
+                    // A lazy sequence just gains a step
+                    if let Value::Seq(seq) = &coll {
+                        self.push(Value::Seq(seq.with_step(SeqStep::DropWhile(func_val))));
+                        continue;
+                    }
+
                     // Extract function reference
                     let func_ref = match &func_val {
                         Value::Fn(longtable_foundation::LtFn::Compiled(f)) => f.clone(),
@@ -1943,8 +2360,13 @@ impl Vm {
                             }
 
                             // Execute function
-                            let result =
-                                self.execute_internal(&func.code, constants, functions, ctx)?;
+                            let result = self.execute_internal(
+                                &func.code,
+                                constants,
+                                functions,
+                                ctx,
+                                Some(func_idx),
+                            )?;
 
                             // Restore state
                             self.ip = saved_ip;
@@ -1967,6 +2389,12 @@ impl Vm {
                     let coll = self.pop()?;
                     let func_val = self.pop()?;
 
+                    // A lazy sequence just gains a step
+                    if let Value::Seq(seq) = &coll {
+                        self.push(Value::Seq(seq.with_step(SeqStep::Remove(func_val))));
+                        continue;
+                    }
+
                     // Extract function reference
                     let func_ref = match &func_val {
                         Value::Fn(longtable_foundation::LtFn::Compiled(f)) => f.clone(),
@@ -2020,8 +2448,13 @@ impl Vm {
                         }
 
                         // Execute function
-                        let result =
-                            self.execute_internal(&func.code, constants, functions, ctx)?;
+                        let result = self.execute_internal(
+                            &func.code,
+                            constants,
+                            functions,
+                            ctx,
+                            Some(func_idx),
+                        )?;
 
                         // Restore state
                         self.ip = saved_ip;
@@ -2039,6 +2472,7 @@ impl Vm {
 
                 Opcode::GroupBy => {
                     let coll = self.pop()?;
+                    let coll = self.realize_value(coll, constants, functions, ctx)?;
                     let func_val = self.pop()?;
 
                     // Extract function reference
@@ -2094,7 +2528,13 @@ impl Vm {
                         }
 
                         // Execute function to get key
-                        let key = self.execute_internal(&func.code, constants, functions, ctx)?;
+                        let key = self.execute_internal(
+                            &func.code,
+                            constants,
+                            functions,
+                            ctx,
+                            Some(func_idx),
+                        )?;
 
                         // Restore state
                         self.ip = saved_ip;
@@ -2121,6 +2561,7 @@ impl Vm {
 
                 Opcode::SortBy => {
                     let coll = self.pop()?;
+                    let coll = self.realize_value(coll, constants, functions, ctx)?;
                     let func_val = self.pop()?;
 
                     // Extract function reference
@@ -2176,7 +2617,13 @@ impl Vm {
                         }
 
                         // Execute function to get key
-                        let key = self.execute_internal(&func.code, constants, functions, ctx)?;
+                        let key = self.execute_internal(
+                            &func.code,
+                            constants,
+                            functions,
+                            ctx,
+                            Some(func_idx),
+                        )?;
 
                         // Restore state
                         self.ip = saved_ip;
@@ -2270,8 +2717,13 @@ impl Vm {
                             }
 
                             // Execute function
-                            let result =
-                                self.execute_internal(&func.code, constants, functions, ctx)?;
+                            let result = self.execute_internal(
+                                &func.code,
+                                constants,
+                                functions,
+                                ctx,
+                                Some(func_idx),
+                            )?;
 
                             // Restore state
                             self.ip = saved_ip;
@@ -2340,8 +2792,13 @@ impl Vm {
                         }
 
                         // Execute zero-arg function
-                        let result =
-                            self.execute_internal(&func.code, constants, functions, ctx)?;
+                        let result = self.execute_internal(
+                            &func.code,
+                            constants,
+                            functions,
+                            ctx,
+                            Some(func_idx),
+                        )?;
 
                         // Restore state
                         self.ip = saved_ip;
@@ -2392,6 +2849,11 @@ impl Vm {
                     ctx.register_adverb(&data)?;
                     self.push(Value::Nil);
                 }
+                Opcode::RegisterMessage => {
+                    let data = self.pop()?;
+                    ctx.register_message(&data)?;
+                    self.push(Value::Nil);
+                }
                 Opcode::RegisterType => {
                     let data = self.pop()?;
                     ctx.register_type(&data)?;
@@ -2422,6 +2884,37 @@ impl Vm {
         // Note: loop always returns from inside, no code reaches here
     }
 
+    /// Queues a spawn effect, returning the entity's temporary ID.
+    fn spawn_pending(&mut self, components: LtMap<Value, Value>) -> EntityId {
+        // Generate a temporary entity ID with a large base offset to avoid
+        // conflicts with real entity IDs in the World. Uses generation 1
+        // (odd = alive) to match EntityStore conventions.
+        self.spawn_counter += 1;
+        let temp_id = EntityId {
+            index: 1_000_000_000 + self.spawn_counter,
+            generation: 1,
+        };
+
+        // Store in pending_spawns for read-your-writes semantics
+        // This allows queries (with-component, get-field) to see spawned
+        // entities before effects are applied to the World.
+        self.pending_spawns.insert(temp_id, components.clone());
+
+        // Also populate pending_components so has-component works
+        for (key, value) in components.iter() {
+            if let Value::Keyword(comp_kw) = key {
+                self.pending_components
+                    .insert((temp_id, *comp_kw), Some(value.clone()));
+            }
+        }
+
+        self.effects.push(VmEffect::Spawn {
+            temp_id,
+            components,
+        });
+        temp_id
+    }
+
     // Stack operations
 
     fn push(&mut self, value: Value) {
@@ -2451,6 +2944,137 @@ impl Vm {
         Ok(())
     }
 
+    /// Calls the DSL function `f` with one argument, the way `map` does.
+    fn call_fn_value<C: RuntimeContext>(
+        &mut self,
+        f: &Value,
+        arg: Value,
+        constants: &[Value],
+        functions: &[crate::compiler::CompiledFunction],
+        ctx: &mut C,
+    ) -> Result<Value> {
+        let func_ref = match f {
+            Value::Fn(longtable_foundation::LtFn::Compiled(f)) => f,
+            Value::Fn(longtable_foundation::LtFn::Native(f)) => return (f.func)(&[arg]),
+            _ => {
+                return Err(Error::new(ErrorKind::TypeMismatch {
+                    expected: longtable_foundation::Type::Fn(
+                        longtable_foundation::types::Arity::Variadic(0),
+                    ),
+                    actual: f.value_type(),
+                }));
+            }
+        };
+        let func_idx = func_ref.index as usize;
+        let func = functions.get(func_idx).ok_or_else(|| {
+            Error::new(ErrorKind::Internal(format!(
+                "function index {func_idx} out of bounds"
+            )))
+        })?;
+
+        let saved_ip = self.ip;
+        let saved_locals = std::mem::replace(&mut self.locals, vec![arg]);
+        let saved_captures = std::mem::take(&mut self.captures);
+        if let Some(caps) = &func_ref.captures {
+            self.captures.clone_from(&caps.lock().unwrap());
+        }
+        let result = self.execute_internal(&func.code, constants, functions, ctx, Some(func_idx));
+        self.ip = saved_ip;
+        self.locals = saved_locals;
+        self.captures = saved_captures;
+        result
+    }
+
+    /// Walks `seq` into a vector of up to `limit` values, or all of them.
+    fn realize_seq<C: RuntimeContext>(
+        &mut self,
+        seq: &LtSeq,
+        limit: Option<usize>,
+        constants: &[Value],
+        functions: &[crate::compiler::CompiledFunction],
+        ctx: &mut C,
+    ) -> Result<Value> {
+        seq.realize(limit, &mut |f, arg| {
+            self.call_fn_value(f, arg, constants, functions, ctx)
+        })
+        .map(Value::Vec)
+    }
+
+    /// Returns `value`, with a lazy sequence walked into a vector.
+    fn realize_value<C: RuntimeContext>(
+        &mut self,
+        value: Value,
+        constants: &[Value],
+        functions: &[crate::compiler::CompiledFunction],
+        ctx: &mut C,
+    ) -> Result<Value> {
+        match value {
+            Value::Seq(seq) => self.realize_seq(&seq, None, constants, functions, ctx),
+            other => Ok(other),
+        }
+    }
+
+    /// Pops two values and compares them, walking a finite lazy sequence
+    /// into a vector first, so `(= (take 3 (range)) [0 1 2])` holds.
+    fn pop_equal<C: RuntimeContext>(
+        &mut self,
+        constants: &[Value],
+        functions: &[crate::compiler::CompiledFunction],
+        ctx: &mut C,
+    ) -> Result<bool> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        let mut realize = |value: Value| match value {
+            Value::Seq(seq) if seq.is_bounded() => {
+                self.realize_seq(&seq, None, constants, functions, ctx)
+            }
+            other => Ok(other),
+        };
+        let a = realize(a)?;
+        let b = realize(b)?;
+        Ok(a == b)
+    }
+
+    /// Walks the lazy sequences among a native's arguments on the stack into
+    /// vectors, only as far as the native looks.
+    ///
+    /// Natives that build sequences get them as they are.
+    fn realize_native_args<C: RuntimeContext>(
+        &mut self,
+        idx: u16,
+        arg_count: u8,
+        constants: &[Value],
+        functions: &[crate::compiler::CompiledFunction],
+        ctx: &mut C,
+    ) -> Result<()> {
+        let base = self.stack.len().saturating_sub(arg_count as usize);
+        if !self.stack[base..]
+            .iter()
+            .any(|v| matches!(v, Value::Seq(_)))
+        {
+            return Ok(());
+        }
+        let limit = match idx {
+            // type, take, drop, iterate, lazy, lazy?
+            53 | 73 | 74 | 141..=143 => return Ok(()),
+            // empty?, first
+            26 | 27 => Some(1),
+            // nth
+            29 => match self.stack.get(base + 1) {
+                Some(Value::Int(n)) => usize::try_from(*n).ok().map(|n| n + 1),
+                _ => None,
+            },
+            _ => None,
+        };
+        for i in base..self.stack.len() {
+            if let Value::Seq(seq) = &self.stack[i] {
+                let seq = seq.clone();
+                self.stack[i] = self.realize_seq(&seq, limit, constants, functions, ctx)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Calls a native function.
     fn call_native<C: RuntimeContext>(&mut self, idx: u16, arg_count: u8, ctx: &C) -> Result<()> {
         // Pop arguments in reverse order
@@ -2473,21 +3097,24 @@ impl Vm {
             50 => {
                 // print
                 if let Some(v) = args.first() {
-                    self.output.push(format_val(v));
+                    self.write_plain(format_val(v));
                 }
                 Ok(Value::Nil)
             }
             51 => {
                 // println
                 if let Some(v) = args.first() {
-                    self.output.push(format!("{}\n", format_val(v)));
+                    self.write_plain(format!("{}\n", format_val(v)));
                 }
                 Ok(Value::Nil)
             }
             52 => {
-                // say (alias for println)
-                if let Some(v) = args.first() {
-                    self.output.push(format!("{}\n", format_val(v)));
+                // say - println with markup (see rich.rs)
+                let keyword_name = |id| ctx.keyword_to_string(id);
+                let spans = rich::markup(&args, &keyword_name, &format_val)?;
+                self.output.push(plain_text(&spans));
+                for span in spans {
+                    rich::push_span(&mut self.rich_output, span);
                 }
                 Ok(Value::Nil)
             }
@@ -2496,6 +3123,45 @@ impl Vm {
                 let result: String = args.iter().map(|v| format_val(v)).collect();
                 Ok(Value::String(result.into()))
             }
+            // format, with keyword resolution
+            70 => format_with(&args, &format_val),
+            // a-or-an, pluralize, join-and - read name metadata by keyword
+            146..=148 => {
+                let keyword_name = |id| ctx.keyword_to_string(id);
+                let text = TextContext {
+                    keyword_name: &keyword_name,
+                    display: &format_val,
+                };
+                match idx {
+                    146 => native_a_or_an(&args, &text),
+                    147 => native_pluralize(&args, &text),
+                    _ => native_join_and(&args, &text),
+                }
+            }
+            // find-path - search the relationship graph
+            149 => native_find_path(&args, ctx),
+            // entities-within, within? - proximity over positions
+            150 => native_entities_within(&args, ctx),
+            151 => native_within_p(&args, ctx),
+            // world-score - total penalty from the last constraint check
+            157 => {
+                if !args.is_empty() {
+                    return Err(Error::new(ErrorKind::InvalidArgument(
+                        "world-score takes no arguments".to_string(),
+                    )));
+                }
+                Ok(Value::Float(ctx.world_score()))
+            }
+            // msg, say-msg - format a message from the catalog
+            144 | 145 => {
+                let text = format_message(&args, ctx, &format_val)?;
+                if idx == 145 {
+                    self.write_plain(format!("{text}\n"));
+                    Ok(Value::Nil)
+                } else {
+                    Ok(Value::String(text.into()))
+                }
+            }
             // All other natives use the dispatch macro
             // Index matches order in compiler's register_natives()
             _ => native_dispatch!(idx, &args;
@@ -2561,7 +3227,6 @@ impl Vm {
                 67 => native_str_replace_all,
                 68 => native_str_blank,
                 69 => native_str_substring,
-                70 => native_format,
                 71 => native_char_at,
                 72 => native_parse_int,
                 // 73-81: Collection functions
@@ -2625,6 +3290,28 @@ impl Vm {
                 126 => native_interpose,
                 127 => native_zip,
                 128 => native_repeat,
+                // 129-138: Time
+                129 => native_instant,
+                130 => native_duration,
+                131 => native_seconds,
+                132 => native_minutes,
+                133 => native_hours,
+                134 => native_days,
+                135 => native_to_millis,
+                136 => native_to_seconds,
+                137 => native_instant_p,
+                138 => native_duration_p,
+                139 => native_bigint,
+                140 => native_make_record,
+                141 => native_iterate,
+                142 => native_lazy,
+                143 => native_lazy_p,
+                // 152-156: Fixed-size vectors
+                152 => native_vec2,
+                153 => native_vec3,
+                154 => native_vec_x,
+                155 => native_vec_y,
+                156 => native_vec_z,
             ),
         }?;
 
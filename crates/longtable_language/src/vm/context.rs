@@ -7,7 +7,7 @@
 //! registering schemas, vocabulary, and other machine configuration.
 
 use longtable_foundation::{EntityId, Error, ErrorKind, KeywordId, LtMap, Result, Value};
-use longtable_storage::World;
+use longtable_storage::{PathStep, Point, World, spatial};
 
 // =============================================================================
 // VmContext Trait
@@ -60,6 +60,30 @@ pub trait VmContext {
     /// Gets the source entities of relationships to a target.
     fn sources(&self, target: EntityId, rel_type: KeywordId) -> Vec<EntityId>;
 
+    /// Finds a path between entities along relationships (see
+    /// [`World::find_path`]).
+    fn find_path(
+        &self,
+        from: EntityId,
+        to: EntityId,
+        via: &[KeywordId],
+        cost: Option<KeywordId>,
+    ) -> Option<Vec<PathStep>>;
+
+    /// Reads a position from an entity's `component` (`:position` if
+    /// `None`), or from a point value like `{:x 1 :y 2}`.
+    fn position(&self, value: &Value, component: Option<KeywordId>) -> Option<Point>;
+
+    /// Finds the entities positioned within `radius` of `center` by
+    /// `component` (`:position` if `None`), nearest first (see
+    /// [`World::entities_within`]).
+    fn entities_within(
+        &self,
+        component: Option<KeywordId>,
+        center: Point,
+        radius: f64,
+    ) -> Vec<EntityId>;
+
     /// Finds relationship entities where the type starts with the given prefix.
     ///
     /// - `prefix`: String prefix to match against relationship type names (e.g., "exit/")
@@ -78,6 +102,13 @@ pub trait VmContext {
     ///
     /// Returns the keyword name without the leading colon (e.g., `KeywordId` for `:foo/bar` returns `"foo/bar"`).
     fn keyword_to_string(&self, keyword: KeywordId) -> Option<String>;
+
+    /// Returns the template of a `message:` in the current locale, falling
+    /// back to the default locale.
+    fn message(&self, key: KeywordId) -> Option<String>;
+
+    /// Returns the world score from its last constraint check.
+    fn world_score(&self) -> f64;
 }
 
 // =============================================================================
@@ -202,11 +233,24 @@ pub trait RuntimeContext: VmContext {
     /// - `:when` - vector of pattern clauses
     /// - `:then` - vector of action expressions
     /// - `:salience` - integer priority (optional, default 0)
+    /// - `:before` / `:after` - vectors of rule names to order against (optional)
     /// - `:once` - boolean for one-shot rules (optional)
     ///
     /// Returns the entity ID of the created rule entity.
     fn register_rule(&mut self, data: &Value) -> Result<EntityId>;
 
+    // =========================================================================
+    // Message Catalog
+    // =========================================================================
+
+    /// Registers a message template.
+    ///
+    /// Data map should contain:
+    /// - `:key` - keyword naming the message
+    /// - `:template` - the `format` string
+    /// - `:locale` - keyword for the locale (optional, default `:en`)
+    fn register_message(&mut self, data: &Value) -> Result<()>;
+
     // =========================================================================
     // Interner Access
     // =========================================================================
@@ -231,8 +275,50 @@ pub trait RuntimeContext: VmContext {
     /// Returns Ok(()) if the snapshot was found and restored, or an error
     /// if the snapshot ID is invalid.
     fn restore_state(&mut self, snapshot_id: u64) -> Result<()>;
+
+    // =========================================================================
+    // Session Commands
+    // =========================================================================
+
+    /// Whether [`SESSION_COMMANDS`] may be queued here.
+    ///
+    /// Commands become [`VmEffect::Command`] effects that the host session
+    /// runs once the current evaluation or tick is over, so only contexts
+    /// with a session behind them allow them.
+    fn allows_commands(&self) -> bool;
 }
 
+/// Commands that act on the session rather than the world, such as saving,
+/// ticking, or travelling in time.
+///
+/// The REPL handles them directly when typed at top level; anywhere else
+/// (inside functions, `do` blocks, phase hooks, or timers) they compile to
+/// [`VmEffect::Command`].
+pub const SESSION_COMMANDS: &[&str] = &[
+    "save!",
+    "load-world!",
+    "export-json!",
+    "import-json!",
+    "export-datoms!",
+    "import-datoms!",
+    "tick!",
+    "rollback!",
+    "goto-tick!",
+    "branch!",
+    "checkout!",
+    "merge!",
+    "undo!",
+    "redo!",
+    "enable-group!",
+    "disable-group!",
+    "telemetry-opt-in!",
+    "set-locale!",
+    "save-transcript!",
+    "input!",
+    "pause-timers!",
+    "resume-timers!",
+];
+
 // =============================================================================
 // VM Effects
 // =============================================================================
@@ -361,6 +447,26 @@ pub enum VmEffect {
         value: Value,
     },
 
+    /// Emit an event (spawned as a short-lived event entity).
+    Emit {
+        /// The event kind, e.g. `:event/door-opened`.
+        event: KeywordId,
+        /// The event payload (a map, or nil).
+        payload: Value,
+    },
+
+    /// Schedule an action to run after a number of ticks.
+    Schedule {
+        /// Number of ticks to wait (at least 1).
+        delay: u64,
+        /// Whether to run the action again every `delay` ticks after.
+        repeat: bool,
+        /// Entity the timer belongs to, for pausing and cancelling it.
+        owner: Option<EntityId>,
+        /// Zero-argument function to call when the timer fires.
+        action: Value,
+    },
+
     /// Save current world state for backtracking.
     /// The snapshot ID is generated by the VM and passed here.
     SaveState {
@@ -374,6 +480,14 @@ pub enum VmEffect {
         /// The snapshot ID to restore to.
         snapshot_id: u64,
     },
+
+    /// Run one of the [`SESSION_COMMANDS`] once execution finishes.
+    Command {
+        /// The command name, e.g. `"save!"`.
+        name: String,
+        /// The evaluated arguments.
+        args: Vec<Value>,
+    },
 }
 
 // =============================================================================
@@ -454,6 +568,33 @@ impl VmContext for WorldContext<'_> {
         self.world.sources(target, rel_type).collect()
     }
 
+    fn find_path(
+        &self,
+        from: EntityId,
+        to: EntityId,
+        via: &[KeywordId],
+        cost: Option<KeywordId>,
+    ) -> Option<Vec<PathStep>> {
+        self.world.find_path(from, to, via, cost)
+    }
+
+    fn position(&self, value: &Value, component: Option<KeywordId>) -> Option<Point> {
+        let component = self.world.position_component(component)?;
+        self.world.position_of(value, component)
+    }
+
+    fn entities_within(
+        &self,
+        component: Option<KeywordId>,
+        center: Point,
+        radius: f64,
+    ) -> Vec<EntityId> {
+        self.world
+            .position_component(component)
+            .map(|component| self.world.entities_within(component, center, radius))
+            .unwrap_or_default()
+    }
+
     fn find_relationships_by_prefix(
         &self,
         prefix: &str,
@@ -470,6 +611,15 @@ impl VmContext for WorldContext<'_> {
             .get_keyword(keyword)
             .map(|s| s.to_string())
     }
+
+    /// A bare world has no message catalog.
+    fn message(&self, _key: KeywordId) -> Option<String> {
+        None
+    }
+
+    fn world_score(&self) -> f64 {
+        self.world.score()
+    }
 }
 
 // =============================================================================
@@ -535,6 +685,33 @@ impl VmContext for NoRuntimeContext {
         Vec::new()
     }
 
+    fn find_path(
+        &self,
+        _from: EntityId,
+        _to: EntityId,
+        _via: &[KeywordId],
+        _cost: Option<KeywordId>,
+    ) -> Option<Vec<PathStep>> {
+        None
+    }
+
+    fn position(&self, value: &Value, _component: Option<KeywordId>) -> Option<Point> {
+        spatial::point(value, |kw| {
+            self.keyword_to_string(kw)
+                .as_deref()
+                .and_then(spatial::axis)
+        })
+    }
+
+    fn entities_within(
+        &self,
+        _component: Option<KeywordId>,
+        _center: Point,
+        _radius: f64,
+    ) -> Vec<EntityId> {
+        Vec::new()
+    }
+
     fn find_relationships_by_prefix(
         &self,
         _prefix: &str,
@@ -547,6 +724,14 @@ impl VmContext for NoRuntimeContext {
     fn keyword_to_string(&self, _keyword: KeywordId) -> Option<String> {
         None
     }
+
+    fn message(&self, _key: KeywordId) -> Option<String> {
+        None
+    }
+
+    fn world_score(&self) -> f64 {
+        0.0
+    }
 }
 
 impl RuntimeContext for NoRuntimeContext {
@@ -622,6 +807,12 @@ impl RuntimeContext for NoRuntimeContext {
         )))
     }
 
+    fn register_message(&mut self, _data: &Value) -> Result<()> {
+        Err(Error::new(ErrorKind::Refused(
+            "message registration not available in this context".to_string(),
+        )))
+    }
+
     fn intern_keyword(&mut self, _name: &str) -> KeywordId {
         // This should never be called in NoRuntimeContext
         panic!("intern_keyword not available in NoRuntimeContext")
@@ -638,6 +829,10 @@ impl RuntimeContext for NoRuntimeContext {
             "state restoration not available in this context".to_string(),
         )))
     }
+
+    fn allows_commands(&self) -> bool {
+        false
+    }
 }
 
 // =============================================================================
@@ -709,6 +904,29 @@ impl<C: VmContext> VmContext for ReadOnlyContext<'_, C> {
         self.inner.sources(target, rel_type)
     }
 
+    fn find_path(
+        &self,
+        from: EntityId,
+        to: EntityId,
+        via: &[KeywordId],
+        cost: Option<KeywordId>,
+    ) -> Option<Vec<PathStep>> {
+        self.inner.find_path(from, to, via, cost)
+    }
+
+    fn position(&self, value: &Value, component: Option<KeywordId>) -> Option<Point> {
+        self.inner.position(value, component)
+    }
+
+    fn entities_within(
+        &self,
+        component: Option<KeywordId>,
+        center: Point,
+        radius: f64,
+    ) -> Vec<EntityId> {
+        self.inner.entities_within(component, center, radius)
+    }
+
     fn find_relationships_by_prefix(
         &self,
         prefix: &str,
@@ -722,6 +940,14 @@ impl<C: VmContext> VmContext for ReadOnlyContext<'_, C> {
     fn keyword_to_string(&self, keyword: KeywordId) -> Option<String> {
         self.inner.keyword_to_string(keyword)
     }
+
+    fn message(&self, key: KeywordId) -> Option<String> {
+        self.inner.message(key)
+    }
+
+    fn world_score(&self) -> f64 {
+        self.inner.world_score()
+    }
 }
 
 impl<C: VmContext> RuntimeContext for ReadOnlyContext<'_, C> {
@@ -809,6 +1035,13 @@ impl<C: VmContext> RuntimeContext for ReadOnlyContext<'_, C> {
         )))
     }
 
+    fn register_message(&mut self, _data: &Value) -> Result<()> {
+        Err(Error::new(ErrorKind::Internal(
+            "registration opcodes require RuntimeContext; use execute_with_runtime_context()"
+                .to_string(),
+        )))
+    }
+
     fn intern_keyword(&mut self, _name: &str) -> KeywordId {
         panic!("intern_keyword not available in ReadOnlyContext")
     }
@@ -823,4 +1056,9 @@ impl<C: VmContext> RuntimeContext for ReadOnlyContext<'_, C> {
             "state restoration not available in ReadOnlyContext".to_string(),
         )))
     }
+
+    // Hooks and timers run read-only, in the middle of a tick
+    fn allows_commands(&self) -> bool {
+        false
+    }
 }
@@ -1,15 +1,31 @@
 //! Arithmetic and comparison helpers for the VM.
+//!
+//! Integer operations that would overflow an `i64` are redone on
+//! [`BigInt`]s, and big results that fit an `i64` again come back as `Int`s.
 
-use longtable_foundation::{Error, ErrorKind, Result, Value};
+use longtable_foundation::{BigInt, Error, ErrorKind, Result, Value};
 
 /// Adds two values.
 pub(crate) fn add_values(a: Value, b: Value) -> Result<Value> {
     match (&a, &b) {
-        (Value::Int(x), Value::Int(y)) => Ok(Value::Int(x + y)),
+        (Value::Int(x), Value::Int(y)) => Ok(promote(*x, *y, i64::checked_add, |x, y| x + y)),
+        (Value::Int(_) | Value::BigInt(_), Value::Int(_) | Value::BigInt(_)) => {
+            Ok(big_op(&a, &b, |x, y| x + y))
+        }
+        (Value::BigInt(_), Value::Float(_)) | (Value::Float(_), Value::BigInt(_)) => {
+            add_values(as_float(&a), as_float(&b))
+        }
         (Value::Float(x), Value::Float(y)) => Ok(Value::Float(x + y)),
         (Value::Int(x), Value::Float(y)) => Ok(Value::Float(*x as f64 + y)),
         (Value::Float(x), Value::Int(y)) => Ok(Value::Float(x + *y as f64)),
         (Value::String(x), Value::String(y)) => Ok(Value::String(format!("{x}{y}").into())),
+        (Value::Instant(t), Value::Duration(d)) | (Value::Duration(d), Value::Instant(t)) => {
+            checked_millis(t.checked_add(*d), "instant").map(Value::Instant)
+        }
+        (Value::Duration(x), Value::Duration(y)) => {
+            checked_millis(x.checked_add(*y), "duration").map(Value::Duration)
+        }
+        (Value::Instant(_) | Value::Duration(_), _) => Err(expected_duration(&b)),
         _ => Err(Error::new(ErrorKind::TypeMismatch {
             expected: longtable_foundation::Type::Int,
             actual: a.value_type(),
@@ -20,10 +36,23 @@ pub(crate) fn add_values(a: Value, b: Value) -> Result<Value> {
 /// Subtracts two values.
 pub(crate) fn sub_values(a: Value, b: Value) -> Result<Value> {
     match (&a, &b) {
-        (Value::Int(x), Value::Int(y)) => Ok(Value::Int(x - y)),
+        (Value::Int(x), Value::Int(y)) => Ok(promote(*x, *y, i64::checked_sub, |x, y| x - y)),
+        (Value::Int(_) | Value::BigInt(_), Value::Int(_) | Value::BigInt(_)) => {
+            Ok(big_op(&a, &b, |x, y| x - y))
+        }
+        (Value::BigInt(_), Value::Float(_)) | (Value::Float(_), Value::BigInt(_)) => {
+            sub_values(as_float(&a), as_float(&b))
+        }
         (Value::Float(x), Value::Float(y)) => Ok(Value::Float(x - y)),
         (Value::Int(x), Value::Float(y)) => Ok(Value::Float(*x as f64 - y)),
         (Value::Float(x), Value::Int(y)) => Ok(Value::Float(x - *y as f64)),
+        (Value::Instant(x), Value::Instant(y)) | (Value::Duration(x), Value::Duration(y)) => {
+            checked_millis(x.checked_sub(*y), "duration").map(Value::Duration)
+        }
+        (Value::Instant(t), Value::Duration(d)) => {
+            checked_millis(t.checked_sub(*d), "instant").map(Value::Instant)
+        }
+        (Value::Instant(_) | Value::Duration(_), _) => Err(expected_duration(&b)),
         _ => Err(Error::new(ErrorKind::TypeMismatch {
             expected: longtable_foundation::Type::Int,
             actual: a.value_type(),
@@ -34,10 +63,22 @@ pub(crate) fn sub_values(a: Value, b: Value) -> Result<Value> {
 /// Multiplies two values.
 pub(crate) fn mul_values(a: Value, b: Value) -> Result<Value> {
     match (&a, &b) {
-        (Value::Int(x), Value::Int(y)) => Ok(Value::Int(x * y)),
+        (Value::Int(x), Value::Int(y)) => Ok(promote(*x, *y, i64::checked_mul, |x, y| x * y)),
+        (Value::Int(_) | Value::BigInt(_), Value::Int(_) | Value::BigInt(_)) => {
+            Ok(big_op(&a, &b, |x, y| x * y))
+        }
+        (Value::BigInt(_), Value::Float(_)) | (Value::Float(_), Value::BigInt(_)) => {
+            mul_values(as_float(&a), as_float(&b))
+        }
         (Value::Float(x), Value::Float(y)) => Ok(Value::Float(x * y)),
         (Value::Int(x), Value::Float(y)) => Ok(Value::Float(*x as f64 * y)),
         (Value::Float(x), Value::Int(y)) => Ok(Value::Float(x * *y as f64)),
+        (Value::Duration(d), Value::Int(n)) | (Value::Int(n), Value::Duration(d)) => {
+            checked_millis(d.checked_mul(*n), "duration").map(Value::Duration)
+        }
+        (Value::Duration(d), Value::Float(n)) | (Value::Float(n), Value::Duration(d)) => {
+            round_millis(*d as f64 * n).map(Value::Duration)
+        }
         _ => Err(Error::new(ErrorKind::TypeMismatch {
             expected: longtable_foundation::Type::Int,
             actual: a.value_type(),
@@ -48,10 +89,18 @@ pub(crate) fn mul_values(a: Value, b: Value) -> Result<Value> {
 /// Divides two values.
 pub(crate) fn div_values(a: Value, b: Value) -> Result<Value> {
     match (&a, &b) {
-        (Value::Int(_) | Value::Float(_), Value::Int(0)) => {
-            Err(Error::new(ErrorKind::DivisionByZero))
+        (
+            Value::Int(_) | Value::BigInt(_) | Value::Float(_) | Value::Duration(_),
+            Value::Int(0),
+        )
+        | (Value::Duration(_), Value::Duration(0)) => Err(Error::new(ErrorKind::DivisionByZero)),
+        (Value::Int(x), Value::Int(y)) => Ok(promote(*x, *y, i64::checked_div, quotient)),
+        (Value::Int(_) | Value::BigInt(_), Value::Int(_) | Value::BigInt(_)) => {
+            Ok(big_op(&a, &b, quotient))
+        }
+        (Value::BigInt(_), Value::Float(_)) | (Value::Float(_), Value::BigInt(_)) => {
+            div_values(as_float(&a), as_float(&b))
         }
-        (Value::Int(x), Value::Int(y)) => Ok(Value::Int(x / y)),
         (Value::Float(x), Value::Float(y)) => {
             if *y == 0.0 {
                 Err(Error::new(ErrorKind::DivisionByZero))
@@ -67,6 +116,11 @@ pub(crate) fn div_values(a: Value, b: Value) -> Result<Value> {
             }
         }
         (Value::Float(x), Value::Int(y)) => Ok(Value::Float(x / *y as f64)),
+        (Value::Duration(d), Value::Int(n)) => {
+            checked_millis(d.checked_div(*n), "duration").map(Value::Duration)
+        }
+        // How many of one span fit in another, as for cooldown progress
+        (Value::Duration(x), Value::Duration(y)) => Ok(Value::Float(*x as f64 / *y as f64)),
         _ => Err(Error::new(ErrorKind::TypeMismatch {
             expected: longtable_foundation::Type::Int,
             actual: a.value_type(),
@@ -77,8 +131,12 @@ pub(crate) fn div_values(a: Value, b: Value) -> Result<Value> {
 /// Modulo of two values.
 pub(crate) fn mod_values(a: Value, b: Value) -> Result<Value> {
     match (&a, &b) {
-        (Value::Int(_), Value::Int(0)) => Err(Error::new(ErrorKind::DivisionByZero)),
-        (Value::Int(x), Value::Int(y)) => Ok(Value::Int(x % y)),
+        (Value::Int(_) | Value::BigInt(_), Value::Int(0))
+        | (Value::Duration(_), Value::Duration(0)) => Err(Error::new(ErrorKind::DivisionByZero)),
+        (Value::Int(x), Value::Int(y)) => Ok(promote(*x, *y, i64::checked_rem, remainder)),
+        (Value::Int(_) | Value::BigInt(_), Value::Int(_) | Value::BigInt(_)) => {
+            Ok(big_op(&a, &b, remainder))
+        }
         (Value::Float(x), Value::Float(y)) => {
             if *y == 0.0 {
                 Err(Error::new(ErrorKind::DivisionByZero))
@@ -86,6 +144,9 @@ pub(crate) fn mod_values(a: Value, b: Value) -> Result<Value> {
                 Ok(Value::Float(x % y))
             }
         }
+        (Value::Duration(x), Value::Duration(y)) => {
+            checked_millis(x.checked_rem(*y), "duration").map(Value::Duration)
+        }
         _ => Err(Error::new(ErrorKind::TypeMismatch {
             expected: longtable_foundation::Type::Int,
             actual: a.value_type(),
@@ -96,8 +157,12 @@ pub(crate) fn mod_values(a: Value, b: Value) -> Result<Value> {
 /// Negates a value.
 pub(crate) fn neg_value(a: Value) -> Result<Value> {
     match a {
-        Value::Int(x) => Ok(Value::Int(-x)),
+        Value::Int(x) => Ok(x
+            .checked_neg()
+            .map_or_else(|| Value::integer(-&BigInt::from(x)), Value::Int)),
+        Value::BigInt(x) => Ok(Value::integer(-&*x)),
         Value::Float(x) => Ok(Value::Float(-x)),
+        Value::Duration(x) => checked_millis(x.checked_neg(), "duration").map(Value::Duration),
         _ => Err(Error::new(ErrorKind::TypeMismatch {
             expected: longtable_foundation::Type::Int,
             actual: a.value_type(),
@@ -112,6 +177,12 @@ where
 {
     let ord = match (&a, &b) {
         (Value::Int(x), Value::Int(y)) => x.cmp(y),
+        (Value::Int(_) | Value::BigInt(_), Value::Int(_) | Value::BigInt(_)) => {
+            a.as_bigint().cmp(&b.as_bigint())
+        }
+        (Value::BigInt(_), Value::Float(_)) | (Value::Float(_), Value::BigInt(_)) => {
+            return compare_values(as_float(&a), as_float(&b), pred);
+        }
         (Value::Float(x), Value::Float(y)) => x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal),
         (Value::Int(x), Value::Float(y)) => (*x as f64)
             .partial_cmp(y)
@@ -120,6 +191,9 @@ where
             .partial_cmp(&(*y as f64))
             .unwrap_or(std::cmp::Ordering::Equal),
         (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Instant(x), Value::Instant(y)) | (Value::Duration(x), Value::Duration(y)) => {
+            x.cmp(y)
+        }
         _ => {
             return Err(Error::new(ErrorKind::TypeMismatch {
                 expected: longtable_foundation::Type::Int,
@@ -129,3 +203,69 @@ where
     };
     Ok(Value::Bool(pred(ord)))
 }
+
+/// The error for combining an instant or duration with something other than
+/// a duration.
+fn expected_duration(actual: &Value) -> Error {
+    Error::new(ErrorKind::TypeMismatch {
+        expected: longtable_foundation::Type::Duration,
+        actual: actual.value_type(),
+    })
+}
+
+/// Unwraps the result of checked arithmetic on an instant or duration,
+/// which has no big form to fall back to.
+pub(crate) fn checked_millis(millis: Option<i64>, what: &str) -> Result<i64> {
+    millis.ok_or_else(|| {
+        Error::new(ErrorKind::Overflow(format!(
+            "{what} out of range of i64 milliseconds"
+        )))
+    })
+}
+
+/// Rounds a fractional number of milliseconds, failing if it's out of range.
+pub(crate) fn round_millis(millis: f64) -> Result<i64> {
+    let rounded = millis.round();
+    // i64::MAX isn't exactly representable; 2^63 is the first float past it
+    let fits = rounded >= i64::MIN as f64 && rounded < -(i64::MIN as f64);
+    checked_millis(fits.then_some(rounded as i64), "duration")
+}
+
+/// Applies a checked `i64` operation, redoing it on big integers if it
+/// overflows.
+fn promote(
+    x: i64,
+    y: i64,
+    checked: fn(i64, i64) -> Option<i64>,
+    big: fn(&BigInt, &BigInt) -> BigInt,
+) -> Value {
+    checked(x, y).map_or_else(
+        || Value::integer(big(&BigInt::from(x), &BigInt::from(y))),
+        Value::Int,
+    )
+}
+
+/// Applies a big integer operation to two integers of either size.
+fn big_op(a: &Value, b: &Value, op: impl FnOnce(&BigInt, &BigInt) -> BigInt) -> Value {
+    let (x, y) = (
+        a.as_bigint().unwrap_or_default(),
+        b.as_bigint().unwrap_or_default(),
+    );
+    Value::integer(op(&x, &y))
+}
+
+/// Converts a number to a float, for arithmetic mixing big integers and
+/// floats.
+fn as_float(value: &Value) -> Value {
+    Value::Float(value.as_number().unwrap_or(f64::NAN))
+}
+
+/// Big integer division; callers have already rejected a zero divisor.
+fn quotient(x: &BigInt, y: &BigInt) -> BigInt {
+    x.div_rem(y).map(|(q, _)| q).unwrap_or_default()
+}
+
+/// Big integer remainder; callers have already rejected a zero divisor.
+fn remainder(x: &BigInt, y: &BigInt) -> BigInt {
+    x.div_rem(y).map(|(_, r)| r).unwrap_or_default()
+}
@@ -1,7 +1,9 @@
 //! Collection manipulation functions for the VM.
 
 use super::format_value;
-use longtable_foundation::{Error, ErrorKind, LtMap, LtSet, LtVec, Result, Value};
+use longtable_foundation::{
+    Error, ErrorKind, LtMap, LtSeq, LtSet, LtVec, Result, SeqSource, SeqStep, Value,
+};
 
 // =============================================================================
 // Basic Collection Operations
@@ -333,11 +335,19 @@ pub(crate) fn native_vals(args: &[Value]) -> Result<Value> {
 // =============================================================================
 
 /// Collection: range - generate a sequence of integers
+/// (range) - lazy 0, 1, 2, ... without end
 /// (range end) - 0 to end-1
 /// (range start end) - start to end-1
 /// (range start end step) - start to end-1 by step
 pub(crate) fn native_range(args: &[Value]) -> Result<Value> {
     let (start, end, step) = match args.len() {
+        0 => {
+            return Ok(Value::Seq(LtSeq::new(SeqSource::Range {
+                start: 0,
+                end: None,
+                step: 1,
+            })));
+        }
         1 => {
             // (range end)
             let end = match args.first() {
@@ -418,7 +428,7 @@ pub(crate) fn native_range(args: &[Value]) -> Result<Value> {
         }
         _ => {
             return Err(Error::new(ErrorKind::Internal(
-                "range requires 1, 2, or 3 arguments".to_string(),
+                "range requires 0, 1, 2, or 3 arguments".to_string(),
             )));
         }
     };
@@ -457,6 +467,9 @@ pub(crate) fn native_range(args: &[Value]) -> Result<Value> {
 /// (take n coll) -> vector of first n elements
 pub(crate) fn native_take(args: &[Value]) -> Result<Value> {
     match (args.first(), args.get(1)) {
+        (Some(Value::Int(n)), Some(Value::Seq(seq))) => Ok(Value::Seq(
+            seq.with_step(SeqStep::Take((*n).max(0) as usize)),
+        )),
         (Some(Value::Int(n)), Some(Value::Vec(v))) => {
             let n = (*n).max(0) as usize;
             let result: LtVec<Value> = v.iter().take(n).cloned().collect();
@@ -482,6 +495,9 @@ pub(crate) fn native_take(args: &[Value]) -> Result<Value> {
 /// (drop n coll) -> vector of remaining elements
 pub(crate) fn native_drop(args: &[Value]) -> Result<Value> {
     match (args.first(), args.get(1)) {
+        (Some(Value::Int(n)), Some(Value::Seq(seq))) => Ok(Value::Seq(
+            seq.with_step(SeqStep::Drop((*n).max(0) as usize)),
+        )),
         (Some(Value::Int(n)), Some(Value::Vec(v))) => {
             let n = (*n).max(0) as usize;
             let result: LtVec<Value> = v.iter().skip(n).cloned().collect();
@@ -711,6 +727,10 @@ pub(crate) fn compare_for_sort(a: &Value, b: &Value) -> std::cmp::Ordering {
         (Value::Float(x), Value::Float(y)) => x.partial_cmp(y).unwrap_or(Ordering::Equal),
         (Value::Int(x), Value::Float(y)) => (*x as f64).partial_cmp(y).unwrap_or(Ordering::Equal),
         (Value::Float(x), Value::Int(y)) => x.partial_cmp(&(*y as f64)).unwrap_or(Ordering::Equal),
+        (Value::BigInt(_), Value::Int(_) | Value::BigInt(_) | Value::Float(_))
+        | (Value::Int(_) | Value::Float(_), Value::BigInt(_)) => {
+            a.partial_cmp(b).unwrap_or(Ordering::Equal)
+        }
         (Value::String(x), Value::String(y)) => x.cmp(y),
         (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
         // For other types, compare by type name then format
@@ -1018,6 +1038,41 @@ pub(crate) fn native_repeat(args: &[Value]) -> Result<Value> {
     }
 }
 
+/// Collection: make-record - checks a record's fields against their types
+/// (make-record "point" [[:x "x" "int"]] {:x 1}) -> {:x 1}
+///
+/// Emitted by the compiler for record constructor calls; each spec entry is
+/// the field's key, name, and type name.
+pub(crate) fn native_make_record(args: &[Value]) -> Result<Value> {
+    let (Some(Value::String(name)), Some(Value::Vec(spec)), Some(Value::Map(fields))) =
+        (args.first(), args.get(1), args.get(2))
+    else {
+        return Err(Error::new(ErrorKind::InvalidArgument(
+            "make-record expects a name, a field spec, and a map".to_string(),
+        )));
+    };
+    for entry in spec {
+        let Value::Vec(entry) = entry else { continue };
+        let (Some(key), Some(Value::String(field)), Some(Value::String(ty))) =
+            (entry.get(0), entry.get(1), entry.get(2))
+        else {
+            continue;
+        };
+        let Some(expected) = longtable_foundation::Type::from_name(ty) else {
+            continue;
+        };
+        let actual = fields
+            .get(key)
+            .map_or(longtable_foundation::Type::Nil, Value::value_type);
+        if !expected.accepts(&actual) {
+            return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                "record {name}: field :{field} expects {expected}, got {actual}"
+            ))));
+        }
+    }
+    Ok(Value::Map(fields.clone()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1110,7 +1165,7 @@ mod tests {
     #[test]
     fn test_dedupe_no_consecutive() {
         let input = vec_of(&[Value::Int(1), Value::Int(2), Value::Int(3)]);
-        let result = native_dedupe(&[input.clone()]).unwrap();
+        let result = native_dedupe(std::slice::from_ref(&input)).unwrap();
         assert_eq!(result, input);
     }
 
@@ -1,6 +1,8 @@
 //! Mathematical functions for the VM.
 
-use longtable_foundation::{Error, ErrorKind, Result, Value};
+use longtable_foundation::{BigInt, Error, ErrorKind, Result, Value};
+
+use super::{add_values, mod_values, sub_values};
 
 // =============================================================================
 // Basic Math Functions
@@ -9,7 +11,7 @@ use longtable_foundation::{Error, ErrorKind, Result, Value};
 /// Math: inc - increment by 1
 pub(crate) fn native_inc(args: &[Value]) -> Result<Value> {
     match args.first() {
-        Some(Value::Int(n)) => Ok(Value::Int(n + 1)),
+        Some(n @ (Value::Int(_) | Value::BigInt(_))) => add_values(n.clone(), Value::Int(1)),
         Some(Value::Float(n)) => Ok(Value::Float(n + 1.0)),
         _ => Err(Error::new(ErrorKind::TypeMismatch {
             expected: longtable_foundation::Type::Int,
@@ -23,7 +25,7 @@ pub(crate) fn native_inc(args: &[Value]) -> Result<Value> {
 /// Math: dec - decrement by 1
 pub(crate) fn native_dec(args: &[Value]) -> Result<Value> {
     match args.first() {
-        Some(Value::Int(n)) => Ok(Value::Int(n - 1)),
+        Some(n @ (Value::Int(_) | Value::BigInt(_))) => sub_values(n.clone(), Value::Int(1)),
         Some(Value::Float(n)) => Ok(Value::Float(n - 1.0)),
         _ => Err(Error::new(ErrorKind::TypeMismatch {
             expected: longtable_foundation::Type::Int,
@@ -37,7 +39,11 @@ pub(crate) fn native_dec(args: &[Value]) -> Result<Value> {
 /// Math: abs
 pub(crate) fn native_abs(args: &[Value]) -> Result<Value> {
     match args.first() {
-        Some(Value::Int(n)) => Ok(Value::Int(n.abs())),
+        Some(Value::Int(n)) => Ok(n.checked_abs().map_or_else(
+            || Value::integer(longtable_foundation::BigInt::from(*n).abs()),
+            Value::Int,
+        )),
+        Some(Value::BigInt(n)) => Ok(Value::integer(n.abs())),
         Some(Value::Float(n)) => Ok(Value::Float(n.abs())),
         _ => Err(Error::new(ErrorKind::TypeMismatch {
             expected: longtable_foundation::Type::Int,
@@ -59,6 +65,13 @@ pub(crate) fn native_min(args: &[Value]) -> Result<Value> {
     for arg in args.iter().skip(1) {
         result = match (&result, arg) {
             (Value::Int(a), Value::Int(b)) => Value::Int(*a.min(b)),
+            (Value::Int(_) | Value::BigInt(_), Value::Int(_) | Value::BigInt(_)) => {
+                if arg.as_bigint() < result.as_bigint() {
+                    arg.clone()
+                } else {
+                    result
+                }
+            }
             (Value::Float(a), Value::Float(b)) => Value::Float(a.min(*b)),
             (Value::Int(a), Value::Float(b)) => Value::Float((*a as f64).min(*b)),
             (Value::Float(a), Value::Int(b)) => Value::Float(a.min(*b as f64)),
@@ -84,6 +97,13 @@ pub(crate) fn native_max(args: &[Value]) -> Result<Value> {
     for arg in args.iter().skip(1) {
         result = match (&result, arg) {
             (Value::Int(a), Value::Int(b)) => Value::Int(*a.max(b)),
+            (Value::Int(_) | Value::BigInt(_), Value::Int(_) | Value::BigInt(_)) => {
+                if arg.as_bigint() > result.as_bigint() {
+                    arg.clone()
+                } else {
+                    result
+                }
+            }
             (Value::Float(a), Value::Float(b)) => Value::Float(a.max(*b)),
             (Value::Int(a), Value::Float(b)) => Value::Float((*a as f64).max(*b)),
             (Value::Float(a), Value::Int(b)) => Value::Float(a.max(*b as f64)),
@@ -161,7 +181,16 @@ pub(crate) fn native_sqrt(args: &[Value]) -> Result<Value> {
 /// Math: rem - remainder (modulo preserving sign of dividend)
 pub(crate) fn native_rem(args: &[Value]) -> Result<Value> {
     match (args.first(), args.get(1)) {
-        (Some(Value::Int(a)), Some(Value::Int(b))) => Ok(Value::Int(a % b)),
+        (
+            Some(a @ (Value::Int(_) | Value::BigInt(_))),
+            Some(b @ (Value::Int(_) | Value::BigInt(_))),
+        ) => mod_values(a.clone(), b.clone()),
+        (Some(Value::BigInt(_)), Some(Value::Float(_)))
+        | (Some(Value::Float(_)), Some(Value::BigInt(_))) => {
+            let a = args[0].as_number().unwrap_or(f64::NAN);
+            let b = args[1].as_number().unwrap_or(f64::NAN);
+            Ok(Value::Float(a % b))
+        }
         (Some(Value::Float(a)), Some(Value::Float(b))) => Ok(Value::Float(a % b)),
         (Some(Value::Int(a)), Some(Value::Float(b))) => Ok(Value::Float(*a as f64 % b)),
         (Some(Value::Float(a)), Some(Value::Int(b))) => Ok(Value::Float(a % *b as f64)),
@@ -216,12 +245,24 @@ pub(crate) fn native_trunc(args: &[Value]) -> Result<Value> {
 /// Math: pow - raise to power
 pub(crate) fn native_pow(args: &[Value]) -> Result<Value> {
     match (args.first(), args.get(1)) {
+        (Some(Value::Int(base)), Some(Value::Int(exp))) if *exp < 0 => {
+            Ok(Value::Float((*base as f64).powf(*exp as f64)))
+        }
         (Some(Value::Int(base)), Some(Value::Int(exp))) => {
-            if *exp >= 0 {
-                Ok(Value::Int(base.pow(*exp as u32)))
-            } else {
-                Ok(Value::Float((*base as f64).powf(*exp as f64)))
-            }
+            let exp = pow_exponent(*exp)?;
+            Ok(base.checked_pow(exp).map_or_else(
+                || Value::integer(big_pow(&BigInt::from(*base), exp)),
+                Value::Int,
+            ))
+        }
+        (Some(Value::BigInt(base)), Some(Value::Int(exp))) if *exp >= 0 => {
+            Ok(Value::integer(big_pow(base, pow_exponent(*exp)?)))
+        }
+        (Some(Value::BigInt(base)), Some(Value::Int(exp))) => {
+            Ok(Value::Float(base.to_f64().powf(*exp as f64)))
+        }
+        (Some(Value::BigInt(base)), Some(Value::Float(exp))) => {
+            Ok(Value::Float(base.to_f64().powf(*exp)))
         }
         (Some(Value::Float(base)), Some(Value::Float(exp))) => Ok(Value::Float(base.powf(*exp))),
         (Some(Value::Int(base)), Some(Value::Float(exp))) => {
@@ -239,6 +280,38 @@ pub(crate) fn native_pow(args: &[Value]) -> Result<Value> {
     }
 }
 
+/// The largest exponent `pow` accepts for an integer result; anything bigger
+/// would build a number too large to be useful before it finished.
+const MAX_POW_EXPONENT: u32 = 1 << 16;
+
+/// Checks a non-negative integer exponent for `pow`.
+fn pow_exponent(exp: i64) -> Result<u32> {
+    u32::try_from(exp)
+        .ok()
+        .filter(|exp| *exp <= MAX_POW_EXPONENT)
+        .ok_or_else(|| {
+            Error::new(ErrorKind::Overflow(format!(
+                "pow exponent {exp} is larger than {MAX_POW_EXPONENT}"
+            )))
+        })
+}
+
+/// Raises a big integer to a power by repeated squaring.
+fn big_pow(base: &BigInt, mut exp: u32) -> BigInt {
+    let mut result = BigInt::from(1);
+    let mut square = base.clone();
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = &result * &square;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            square = &square * &square;
+        }
+    }
+    result
+}
+
 /// Math: cbrt - cube root
 pub(crate) fn native_cbrt(args: &[Value]) -> Result<Value> {
     match args.first() {
@@ -504,9 +577,10 @@ fn to_f64(v: &Value) -> Option<f64> {
     }
 }
 
-/// Helper to extract a numeric vector from a `Value::Vec`
+/// Helper to extract a numeric vector from a `Value::Vec`, `Vec2`, or `Vec3`
 fn extract_vec(v: &Value) -> Option<Vec<f64>> {
     match v {
+        Value::Vec2(_) | Value::Vec3(_) => v.as_components().map(<[f64]>::to_vec),
         Value::Vec(vec) => {
             let mut result = Vec::with_capacity(vec.len());
             for item in vec.iter() {
@@ -524,6 +598,14 @@ fn make_vec(values: &[f64]) -> Value {
     Value::Vec(vec)
 }
 
+/// Helper to create a vector of the same kind as `like`: a `Vec2` or `Vec3`
+/// stays one, and anything else gives a `Value::Vec`
+fn make_vec_like(like: &Value, values: &[f64]) -> Value {
+    like.as_components()
+        .and_then(|_| Value::from_components(values))
+        .unwrap_or_else(|| make_vec(values))
+}
+
 /// Vector: vec+ - element-wise vector addition
 /// (vec+ [1 2] [3 4]) -> [4.0 6.0]
 pub(crate) fn native_vec_add(args: &[Value]) -> Result<Value> {
@@ -551,7 +633,7 @@ pub(crate) fn native_vec_add(args: &[Value]) -> Result<Value> {
                 )));
             }
             let result: Vec<f64> = va.iter().zip(vb.iter()).map(|(x, y)| x + y).collect();
-            Ok(make_vec(&result))
+            Ok(make_vec_like(a, &result))
         }
         _ => Err(Error::new(ErrorKind::Internal(
             "vec+ requires 2 arguments".to_string(),
@@ -586,7 +668,7 @@ pub(crate) fn native_vec_sub(args: &[Value]) -> Result<Value> {
                 )));
             }
             let result: Vec<f64> = va.iter().zip(vb.iter()).map(|(x, y)| x - y).collect();
-            Ok(make_vec(&result))
+            Ok(make_vec_like(a, &result))
         }
         _ => Err(Error::new(ErrorKind::Internal(
             "vec- requires 2 arguments".to_string(),
@@ -621,7 +703,7 @@ pub(crate) fn native_vec_mul(args: &[Value]) -> Result<Value> {
                 )));
             }
             let result: Vec<f64> = va.iter().zip(vb.iter()).map(|(x, y)| x * y).collect();
-            Ok(make_vec(&result))
+            Ok(make_vec_like(a, &result))
         }
         _ => Err(Error::new(ErrorKind::Internal(
             "vec* requires 2 arguments".to_string(),
@@ -649,7 +731,7 @@ pub(crate) fn native_vec_scale(args: &[Value]) -> Result<Value> {
                 })
             })?;
             let result: Vec<f64> = vec.iter().map(|x| x * scalar).collect();
-            Ok(make_vec(&result))
+            Ok(make_vec_like(v, &result))
         }
         _ => Err(Error::new(ErrorKind::Internal(
             "vec-scale requires 2 arguments".to_string(),
@@ -723,7 +805,7 @@ pub(crate) fn native_vec_cross(args: &[Value]) -> Result<Value> {
                 va[2] * vb[0] - va[0] * vb[2],
                 va[0] * vb[1] - va[1] * vb[0],
             ];
-            Ok(make_vec(&result))
+            Ok(make_vec_like(a, &result))
         }
         _ => Err(Error::new(ErrorKind::Internal(
             "vec-cross requires 2 arguments".to_string(),
@@ -791,11 +873,11 @@ pub(crate) fn native_vec_normalize(args: &[Value]) -> Result<Value> {
             let len_sq: f64 = vec.iter().map(|x| x * x).sum();
             if len_sq == 0.0 {
                 // Return zero vector for zero-length input
-                return Ok(make_vec(&vec![0.0; vec.len()]));
+                return Ok(make_vec_like(v, &vec![0.0; vec.len()]));
             }
             let len = len_sq.sqrt();
             let result: Vec<f64> = vec.iter().map(|x| x / len).collect();
-            Ok(make_vec(&result))
+            Ok(make_vec_like(v, &result))
         }
         _ => Err(Error::new(ErrorKind::Internal(
             "vec-normalize requires 1 argument".to_string(),
@@ -879,7 +961,7 @@ pub(crate) fn native_vec_lerp(args: &[Value]) -> Result<Value> {
                 .zip(vb.iter())
                 .map(|(x, y)| x + (y - x) * t_val)
                 .collect();
-            Ok(make_vec(&result))
+            Ok(make_vec_like(a, &result))
         }
         _ => Err(Error::new(ErrorKind::Internal(
             "vec-lerp requires 3 arguments".to_string(),
@@ -929,6 +1011,109 @@ pub(crate) fn native_vec_angle(args: &[Value]) -> Result<Value> {
     }
 }
 
+/// Vector: vec2 - a two-dimensional numeric vector
+/// (vec2 3 4) -> (vec2 3.0 4.0), (vec2 [3 4]) -> (vec2 3.0 4.0)
+pub(crate) fn native_vec2(args: &[Value]) -> Result<Value> {
+    fixed_vec("vec2", 2, args)
+}
+
+/// Vector: vec3 - a three-dimensional numeric vector
+/// (vec3 1 2 3) -> (vec3 1.0 2.0 3.0), (vec3 [1 2 3]) -> (vec3 1.0 2.0 3.0)
+pub(crate) fn native_vec3(args: &[Value]) -> Result<Value> {
+    fixed_vec("vec3", 3, args)
+}
+
+/// Builds a `Vec2` or `Vec3` from `len` numbers, or from one vector of them
+fn fixed_vec(name: &str, len: usize, args: &[Value]) -> Result<Value> {
+    let components = match args {
+        [single] => extract_vec(single).ok_or_else(|| {
+            Error::new(ErrorKind::TypeMismatch {
+                expected: longtable_foundation::Type::Vec(Box::new(
+                    longtable_foundation::Type::Float,
+                )),
+                actual: single.value_type(),
+            })
+        })?,
+        _ => args
+            .iter()
+            .map(|arg| {
+                to_f64(arg).ok_or_else(|| {
+                    Error::new(ErrorKind::TypeMismatch {
+                        expected: longtable_foundation::Type::Float,
+                        actual: arg.value_type(),
+                    })
+                })
+            })
+            .collect::<Result<Vec<f64>>>()?,
+    };
+    if components.len() != len {
+        return Err(Error::new(ErrorKind::InvalidArgument(format!(
+            "{name} requires {len} numbers, got {}",
+            components.len()
+        ))));
+    }
+    Value::from_components(&components).ok_or_else(|| {
+        Error::new(ErrorKind::InvalidArgument(format!(
+            "{name}: not a vector size"
+        )))
+    })
+}
+
+/// Vector: vec-x - a vector's first component
+/// (vec-x (vec2 3 4)) -> 3.0
+pub(crate) fn native_vec_x(args: &[Value]) -> Result<Value> {
+    vec_component("vec-x", 0, args)
+}
+
+/// Vector: vec-y - a vector's second component
+/// (vec-y (vec2 3 4)) -> 4.0
+pub(crate) fn native_vec_y(args: &[Value]) -> Result<Value> {
+    vec_component("vec-y", 1, args)
+}
+
+/// Vector: vec-z - a vector's third component, 0.0 for a two-dimensional one
+/// (vec-z (vec3 1 2 3)) -> 3.0
+pub(crate) fn native_vec_z(args: &[Value]) -> Result<Value> {
+    vec_component("vec-z", 2, args)
+}
+
+fn vec_component(name: &str, axis: usize, args: &[Value]) -> Result<Value> {
+    let Some(v) = args.first() else {
+        return Err(Error::new(ErrorKind::InvalidArgument(format!(
+            "{name} requires 1 argument"
+        ))));
+    };
+    let vec = extract_vec(v).ok_or_else(|| {
+        Error::new(ErrorKind::TypeMismatch {
+            expected: longtable_foundation::Type::Vec(Box::new(longtable_foundation::Type::Float)),
+            actual: v.value_type(),
+        })
+    })?;
+    Ok(Value::Float(vec.get(axis).copied().unwrap_or(0.0)))
+}
+
+/// Math: bigint - parse an integer of any size from a string
+pub(crate) fn native_bigint(args: &[Value]) -> Result<Value> {
+    match args.first() {
+        Some(n @ (Value::Int(_) | Value::BigInt(_))) => Ok(n.clone()),
+        Some(Value::String(s)) => s
+            .trim()
+            .parse::<longtable_foundation::BigInt>()
+            .map(Value::integer)
+            .map_err(|_| {
+                Error::new(ErrorKind::InvalidArgument(format!(
+                    "bigint: invalid integer: {s}"
+                )))
+            }),
+        _ => Err(Error::new(ErrorKind::TypeMismatch {
+            expected: longtable_foundation::Type::String,
+            actual: args
+                .first()
+                .map_or(longtable_foundation::Type::Nil, |v| v.value_type()),
+        })),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -6,6 +6,10 @@
 //! - `collection`: Collection manipulation functions
 //! - `string`: String manipulation functions
 //! - `math`: Mathematical functions
+//! - `time`: Instant and duration functions
+//! - `seq`: Lazy sequence functions
+//! - `text`: Articles, plurals, and English lists
+//! - `path`: Pathfinding over relationships
 
 mod arithmetic;
 #[allow(clippy::unnecessary_wraps)]
@@ -13,12 +17,19 @@ mod arithmetic;
 mod collection;
 #[allow(clippy::unnecessary_wraps)]
 mod math;
+mod path;
 #[allow(clippy::unnecessary_wraps)]
 #[allow(clippy::match_same_arms)]
 mod predicates;
 #[allow(clippy::unnecessary_wraps)]
+mod seq;
+mod spatial;
+#[allow(clippy::unnecessary_wraps)]
 #[allow(clippy::redundant_closure_for_method_calls)]
 mod string;
+mod text;
+#[allow(clippy::unnecessary_wraps)]
+mod time;
 
 // Re-export everything for use by the VM
 #[allow(clippy::wildcard_imports)]
@@ -28,9 +39,19 @@ pub(crate) use collection::*;
 #[allow(clippy::wildcard_imports)]
 pub(crate) use math::*;
 #[allow(clippy::wildcard_imports)]
+pub(crate) use path::*;
+#[allow(clippy::wildcard_imports)]
 pub(crate) use predicates::*;
 #[allow(clippy::wildcard_imports)]
+pub(crate) use seq::*;
+#[allow(clippy::wildcard_imports)]
+pub(crate) use spatial::*;
+#[allow(clippy::wildcard_imports)]
 pub(crate) use string::*;
+#[allow(clippy::wildcard_imports)]
+pub(crate) use text::*;
+#[allow(clippy::wildcard_imports)]
+pub(crate) use time::*;
 
 use longtable_foundation::Value;
 
@@ -62,6 +83,12 @@ pub(crate) fn format_value(value: &Value) -> String {
         Value::Symbol(id) => format!("Symbol({})", id.index()),
         Value::Keyword(id) => format!("Keyword({})", id.index()),
         Value::EntityRef(id) => format!("Entity({}, {})", id.index, id.generation),
+        Value::BigInt(_)
+        | Value::Instant(_)
+        | Value::Duration(_)
+        | Value::Vec2(_)
+        | Value::Vec3(_)
+        | Value::Seq(_) => value.to_string(),
         Value::Vec(v) => {
             let items: Vec<_> = v.iter().map(format_value).collect();
             format!("[{}]", items.join(" "))
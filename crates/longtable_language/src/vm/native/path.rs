@@ -0,0 +1,95 @@
+//! Pathfinding for the VM.
+//!
+//! `(find-path from to :via rels)` finds the shortest path between two
+//! entities along relationships, and `:cost component` weighs each entity
+//! entered by the component's `:value` (see `World::find_path`). The path
+//! is a vector of `[relationship entity]` steps, or nil if there is none:
+//!
+//! ```text
+//! (find-path hall cellar :via [:exit :door/leads-to])
+//! ;; [[:exit/north door] [:door/leads-to cellar]]
+//! ```
+
+use longtable_foundation::{EntityId, Error, ErrorKind, KeywordId, LtVec, Result, Type, Value};
+
+use crate::vm::VmContext;
+
+/// Pathfinding: find-path - the steps from one entity to another
+/// (find-path from to :via :exit :cost :terrain/cost) -> [[rel entity] ...] or nil
+pub(crate) fn native_find_path(args: &[Value], ctx: &dyn VmContext) -> Result<Value> {
+    let (from, to) = match args {
+        [from, to, ..] => (entity(from)?, entity(to)?),
+        _ => return Err(usage()),
+    };
+    let mut via = Vec::new();
+    let mut cost = None;
+    for option in args[2..].chunks(2) {
+        let [Value::Keyword(name), value] = option else {
+            return Err(usage());
+        };
+        match ctx.keyword_to_string(*name).as_deref() {
+            Some("via") => via = relationships(value)?,
+            Some("cost") => {
+                cost = match value {
+                    Value::Nil => None,
+                    other => Some(keyword(other)?),
+                };
+            }
+            _ => return Err(usage()),
+        }
+    }
+    if via.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidArgument(
+            "find-path requires :via and the relationships to follow".to_string(),
+        )));
+    }
+
+    Ok(match ctx.find_path(from, to, &via, cost) {
+        Some(steps) => Value::Vec(
+            steps
+                .into_iter()
+                .map(|step| {
+                    Value::Vec(LtVec::from_iter([
+                        Value::Keyword(step.relationship),
+                        Value::EntityRef(step.entity),
+                    ]))
+                })
+                .collect(),
+        ),
+        None => Value::Nil,
+    })
+}
+
+fn usage() -> Error {
+    Error::new(ErrorKind::InvalidArgument(
+        "find-path expects from to :via rels [:cost component]".to_string(),
+    ))
+}
+
+fn entity(value: &Value) -> Result<EntityId> {
+    match value {
+        Value::EntityRef(entity) => Ok(*entity),
+        other => Err(Error::new(ErrorKind::TypeMismatch {
+            expected: Type::EntityRef,
+            actual: other.value_type(),
+        })),
+    }
+}
+
+fn keyword(value: &Value) -> Result<KeywordId> {
+    match value {
+        Value::Keyword(kw) => Ok(*kw),
+        other => Err(Error::new(ErrorKind::TypeMismatch {
+            expected: Type::Keyword,
+            actual: other.value_type(),
+        })),
+    }
+}
+
+/// Reads `:via`: a relationship keyword, or a vector of them.
+fn relationships(value: &Value) -> Result<Vec<KeywordId>> {
+    match value {
+        Value::Vec(items) | Value::List(items) => items.iter().map(keyword).collect(),
+        other => Ok(vec![keyword(other)?]),
+    }
+}
@@ -46,7 +46,10 @@ pub(crate) fn native_some_p(args: &[Value]) -> Result<Value> {
 
 /// Predicate: int?
 pub(crate) fn native_int_p(args: &[Value]) -> Result<Value> {
-    Ok(Value::Bool(matches!(args.first(), Some(Value::Int(_)))))
+    Ok(Value::Bool(matches!(
+        args.first(),
+        Some(Value::Int(_) | Value::BigInt(_))
+    )))
 }
 
 /// Predicate: float?
@@ -98,7 +101,7 @@ pub(crate) fn native_bool_p(args: &[Value]) -> Result<Value> {
 pub(crate) fn native_number_p(args: &[Value]) -> Result<Value> {
     Ok(Value::Bool(matches!(
         args.first(),
-        Some(Value::Int(_) | Value::Float(_))
+        Some(Value::Int(_) | Value::BigInt(_) | Value::Float(_))
     )))
 }
 
@@ -128,17 +131,22 @@ pub(crate) fn native_type(args: &[Value]) -> Result<Value> {
     let type_name = match args.first() {
         Some(Value::Nil) => "nil",
         Some(Value::Bool(_)) => "bool",
-        Some(Value::Int(_)) => "int",
+        Some(Value::Int(_) | Value::BigInt(_)) => "int",
         Some(Value::Float(_)) => "float",
         Some(Value::String(_)) => "string",
         Some(Value::Symbol(_)) => "symbol",
         Some(Value::Keyword(_)) => "keyword",
         Some(Value::EntityRef(_)) => "entity",
+        Some(Value::Instant(_)) => "instant",
+        Some(Value::Duration(_)) => "duration",
+        Some(Value::Vec2(_)) => "vec2",
+        Some(Value::Vec3(_)) => "vec3",
         Some(Value::Vec(_)) => "vector",
         Some(Value::List(_)) => "list",
         Some(Value::Set(_)) => "set",
         Some(Value::Map(_)) => "map",
         Some(Value::Fn(_)) => "fn",
+        Some(Value::Seq(_)) => "seq",
         None => "nil",
     };
     Ok(Value::String(format!(":{type_name}").into()))
@@ -300,7 +308,9 @@ mod tests {
 
     #[test]
     fn test_list_p_true() {
-        assert!(as_bool(&native_list_p(&[Value::Vec(empty_vec())]).unwrap()));
+        assert!(as_bool(
+            &native_list_p(&[Value::List(empty_vec())]).unwrap()
+        ));
     }
 
     #[test]
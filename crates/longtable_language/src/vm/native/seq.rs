@@ -0,0 +1,44 @@
+//! Lazy sequence functions for the VM.
+//!
+//! These only build sequences; walking one calls DSL functions, so the VM
+//! does it (see `Vm::realize_seq`).
+
+use longtable_foundation::{Error, ErrorKind, LtSeq, Result, SeqSource, Type, Value};
+
+/// Seq: iterate - the lazy sequence x, (f x), (f (f x)), ...
+/// (iterate inc 0) -> 0 1 2 ...
+pub(crate) fn native_iterate(args: &[Value]) -> Result<Value> {
+    match (args.first(), args.get(1)) {
+        (Some(f @ Value::Fn(_)), Some(seed)) => Ok(Value::Seq(LtSeq::new(SeqSource::Iterate {
+            f: f.clone(),
+            seed: seed.clone(),
+        }))),
+        (other, _) => Err(Error::new(ErrorKind::TypeMismatch {
+            expected: Type::Fn(longtable_foundation::Arity::Exact(1)),
+            actual: other.map_or(Type::Nil, Value::value_type),
+        })),
+    }
+}
+
+/// Seq: lazy - a lazy sequence of a collection's elements
+/// (lazy [1 2 3]) -> 1 2 3, computed on demand by later steps
+pub(crate) fn native_lazy(args: &[Value]) -> Result<Value> {
+    let items = match args.first() {
+        Some(Value::Seq(seq)) => return Ok(Value::Seq(seq.clone())),
+        Some(Value::Vec(v) | Value::List(v)) => v.clone(),
+        Some(Value::Set(s)) => s.iter().cloned().collect(),
+        Some(Value::Nil) | None => longtable_foundation::LtVec::new(),
+        Some(other) => {
+            return Err(Error::new(ErrorKind::TypeMismatch {
+                expected: Type::vec(Type::Any),
+                actual: other.value_type(),
+            }));
+        }
+    };
+    Ok(Value::Seq(LtSeq::new(SeqSource::Coll(items))))
+}
+
+/// Seq: lazy? - true for a lazy sequence
+pub(crate) fn native_lazy_p(args: &[Value]) -> Result<Value> {
+    Ok(Value::Bool(matches!(args.first(), Some(Value::Seq(_)))))
+}
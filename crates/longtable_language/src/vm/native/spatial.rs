@@ -0,0 +1,81 @@
+//! Proximity queries for the VM.
+//!
+//! Positions are read from `:position` unless `:by` names another
+//! component, and a point can stand in for an entity (see
+//! `World::entities_within`):
+//!
+//! ```text
+//! (entities-within player 5.0)                  ;; [goblin chest] nearest first
+//! (entities-within {:x 0 :y 0} 5.0 :by :location)
+//! (within? player goblin 5.0)                   ;; true
+//! ```
+
+use longtable_foundation::{Error, ErrorKind, KeywordId, LtVec, Result, Type, Value};
+
+use crate::vm::VmContext;
+
+/// Spatial: entities-within - the entities near an entity or point
+/// (entities-within center radius :by :position) -> [entity ...]
+pub(crate) fn native_entities_within(args: &[Value], ctx: &dyn VmContext) -> Result<Value> {
+    let [center, radius, options @ ..] = args else {
+        return Err(usage(
+            "entities-within expects center radius [:by component]",
+        ));
+    };
+    let component = by(options, ctx, "entities-within")?;
+    let radius = number(radius)?;
+    let Some(point) = ctx.position(center, component) else {
+        return Ok(Value::Vec(LtVec::new()));
+    };
+    Ok(Value::Vec(
+        ctx.entities_within(component, point, radius)
+            .into_iter()
+            .filter(|entity| center != &Value::EntityRef(*entity))
+            .map(Value::EntityRef)
+            .collect(),
+    ))
+}
+
+/// Spatial: within? - whether two entities or points are near each other
+/// (within? a b radius :by :position) -> bool
+pub(crate) fn native_within_p(args: &[Value], ctx: &dyn VmContext) -> Result<Value> {
+    let [a, b, radius, options @ ..] = args else {
+        return Err(usage("within? expects a b radius [:by component]"));
+    };
+    let component = by(options, ctx, "within?")?;
+    let radius = number(radius)?;
+    let (Some(a), Some(b)) = (ctx.position(a, component), ctx.position(b, component)) else {
+        return Ok(Value::Bool(false));
+    };
+    let distance = (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f64>().sqrt();
+    Ok(Value::Bool(distance <= radius))
+}
+
+fn usage(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidArgument(message.to_string()))
+}
+
+/// Reads the optional `:by component`.
+fn by(options: &[Value], ctx: &dyn VmContext, name: &str) -> Result<Option<KeywordId>> {
+    match options {
+        [] => Ok(None),
+        [Value::Keyword(option), Value::Keyword(component)]
+            if ctx.keyword_to_string(*option).as_deref() == Some("by") =>
+        {
+            Ok(Some(*component))
+        }
+        _ => Err(usage(&format!("{name} only takes :by component"))),
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn number(value: &Value) -> Result<f64> {
+    match value {
+        Value::Int(n) => Ok(*n as f64),
+        Value::Float(x) => Ok(*x),
+        other => Err(Error::new(ErrorKind::TypeMismatch {
+            expected: Type::Float,
+            actual: other.value_type(),
+        })),
+    }
+}
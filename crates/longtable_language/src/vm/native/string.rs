@@ -254,17 +254,123 @@ pub(crate) fn native_str_substring(args: &[Value]) -> Result<Value> {
     }
 }
 
-/// Format: format - template formatting with {} placeholders
+/// Format: format - template formatting with {} placeholders and ~ directives
 /// (format "Hello, {}!" "world") -> "Hello, world!"
-pub(crate) fn native_format(args: &[Value]) -> Result<Value> {
+/// (format "~a has ~5d hp (~,1f%)" "Ann" 42 87.25) -> "Ann has    42 hp (87.3%)"
+///
+/// `display` renders values for `{}`, `~a`, and `~s`; the VM passes one
+/// that resolves keywords to their names.
+///
+/// Directives are `~` followed by an optional width, an optional `,`
+/// precision, an optional `@`, and one of:
+/// - `a`: the value as displayed; precision truncates
+/// - `s`: the value as written, with strings quoted
+/// - `d`: an integer
+/// - `f`: a number with `precision` decimal places
+/// - `%`: a newline, and `~`: a tilde (no argument)
+///
+/// Widths pad text on the right and numbers on the left; `@` swaps. A width
+/// or precision over 4096 is an error.
+/// `{}` placeholders beyond the last argument are left as they are, but a
+/// directive without an argument is an error.
+pub(crate) fn format_with(args: &[Value], display: &dyn Fn(&Value) -> String) -> Result<Value> {
     match args.first() {
         Some(Value::String(template)) => {
-            let mut result = template.to_string();
-            for arg in args.iter().skip(1) {
-                // Replace first {} with the argument
-                if let Some(pos) = result.find("{}") {
-                    let formatted = format_value(arg);
-                    result = format!("{}{}{}", &result[..pos], formatted, &result[pos + 2..]);
+            let mut values = args.iter().skip(1);
+            let mut result = String::with_capacity(template.len());
+            let mut chars = template.chars().peekable();
+            while let Some(c) = chars.next() {
+                if c == '{' && chars.peek() == Some(&'}') {
+                    match values.next() {
+                        Some(value) => {
+                            chars.next();
+                            result.push_str(&display(value));
+                        }
+                        None => result.push(c),
+                    }
+                    continue;
+                }
+                if c != '~' {
+                    result.push(c);
+                    continue;
+                }
+
+                let width = directive_number(&mut chars)?;
+                let precision = if chars.peek() == Some(&',') {
+                    chars.next();
+                    directive_number(&mut chars)?
+                } else {
+                    None
+                };
+                let swapped = chars.peek() == Some(&'@');
+                if swapped {
+                    chars.next();
+                }
+                let directive = chars.next().ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidArgument(
+                        "format: template ends in the middle of a ~ directive".to_string(),
+                    ))
+                })?;
+                if directive == '%' {
+                    result.push('\n');
+                    continue;
+                }
+                if directive == '~' {
+                    result.push('~');
+                    continue;
+                }
+
+                let value = values.next().ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidArgument(format!(
+                        "format: no argument left for ~{directive}"
+                    )))
+                })?;
+                let (text, numeric) = match directive.to_ascii_lowercase() {
+                    'a' => {
+                        let text = display(value);
+                        match precision {
+                            Some(p) => (text.chars().take(p).collect(), false),
+                            None => (text, false),
+                        }
+                    }
+                    's' => match value {
+                        Value::String(s) => (format!("{s:?}"), false),
+                        other => (display(other), false),
+                    },
+                    'd' => match value {
+                        Value::Int(_) | Value::BigInt(_) => (value.to_string(), true),
+                        #[allow(clippy::cast_possible_truncation)]
+                        Value::Float(n) => ((n.round() as i64).to_string(), true),
+                        other => return Err(format_type_error('d', other)),
+                    },
+                    'f' => {
+                        #[allow(clippy::cast_precision_loss)]
+                        let n = match value {
+                            Value::Float(n) => *n,
+                            Value::Int(n) => *n as f64,
+                            Value::BigInt(n) => n.to_f64(),
+                            other => return Err(format_type_error('f', other)),
+                        };
+                        match precision {
+                            Some(p) => (format!("{n:.p$}"), true),
+                            None => (display(&Value::Float(n)), true),
+                        }
+                    }
+                    other => {
+                        return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                            "format: unknown directive ~{other}"
+                        ))));
+                    }
+                };
+
+                let pad = width.map_or(0, |w| w.saturating_sub(text.chars().count()));
+                let pad_left = numeric != swapped;
+                if pad_left {
+                    result.extend(std::iter::repeat_n(' ', pad));
+                }
+                result.push_str(&text);
+                if !pad_left {
+                    result.extend(std::iter::repeat_n(' ', pad));
                 }
             }
             Ok(Value::String(result.into()))
@@ -279,6 +385,37 @@ pub(crate) fn native_format(args: &[Value]) -> Result<Value> {
     }
 }
 
+/// The largest width or precision a `format` directive accepts, so a
+/// template can't ask for an enormous string.
+const MAX_DIRECTIVE_NUMBER: usize = 1 << 12;
+
+/// Reads a directive's width or precision, if there is one.
+fn directive_number(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Result<Option<usize>> {
+    let mut number: Option<usize> = None;
+    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+        chars.next();
+        number = Some(
+            number
+                .unwrap_or(0)
+                .saturating_mul(10)
+                .saturating_add(digit as usize),
+        );
+    }
+    match number {
+        Some(n) if n > MAX_DIRECTIVE_NUMBER => Err(Error::new(ErrorKind::Overflow(format!(
+            "format: width or precision {n} is larger than {MAX_DIRECTIVE_NUMBER}"
+        )))),
+        _ => Ok(number),
+    }
+}
+
+fn format_type_error(directive: char, value: &Value) -> Error {
+    Error::new(ErrorKind::InvalidArgument(format!(
+        "format: ~{directive} expects a number, got {}",
+        value.value_type()
+    )))
+}
+
 /// String: char-at - get character at index as string
 /// (char-at "hello" 0) -> "h"
 pub(crate) fn native_char_at(args: &[Value]) -> Result<Value> {
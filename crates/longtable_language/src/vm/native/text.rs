@@ -0,0 +1,265 @@
+//! Text generation functions for the VM: articles, plurals, and lists.
+//!
+//! Each takes a noun as a string, or as the value of a `:name` component,
+//! whose optional fields override the English rules used otherwise:
+//!
+//! ```text
+//! (component: name
+//!   :value :string
+//!   :article :string :default ""
+//!   :plural :string :default ""
+//!   :proper :bool :default false)
+//! (spawn: sand :name {:value "sand" :article "some" :plural "sand"})
+//! (a-or-an (get ?item :name))   ;; "some sand"
+//! ```
+//!
+//! - `:article` replaces `a`/`an`
+//! - `:plural` replaces the plural form
+//! - `:proper true` drops the article altogether, for names like "Excalibur"
+//!
+//! Empty strings count as absent, so the fields can default to `""`.
+//!
+//! Map keys are keywords, so these are called with a way to name them.
+
+use longtable_foundation::{Error, ErrorKind, KeywordId, LtMap, Result, Type, Value};
+
+/// Resolves keyword names and displays values for the text functions.
+pub(crate) struct TextContext<'a> {
+    /// Returns a keyword's name without the colon.
+    pub(crate) keyword_name: &'a dyn Fn(KeywordId) -> Option<String>,
+    /// Displays a value that isn't a noun.
+    pub(crate) display: &'a dyn Fn(&Value) -> String,
+}
+
+/// A noun and whatever its name metadata says about it.
+struct Noun {
+    name: String,
+    article: Option<String>,
+    plural: Option<String>,
+    proper: bool,
+}
+
+impl TextContext<'_> {
+    fn noun(&self, value: &Value) -> Result<Noun> {
+        match value {
+            Value::String(s) => Ok(Noun {
+                name: s.to_string(),
+                article: None,
+                plural: None,
+                proper: false,
+            }),
+            Value::Map(fields) => {
+                let name = match self.field(fields, "value") {
+                    Some(Value::String(s)) => s.to_string(),
+                    _ => return Err(noun_error(value)),
+                };
+                let text = |field| match self.field(fields, field) {
+                    Some(Value::String(s)) if !s.is_empty() => Some(s.to_string()),
+                    _ => None,
+                };
+                Ok(Noun {
+                    name,
+                    article: text("article"),
+                    plural: text("plural"),
+                    proper: self.field(fields, "proper").is_some_and(Value::is_truthy),
+                })
+            }
+            other => Err(noun_error(other)),
+        }
+    }
+
+    fn field<'m>(&self, fields: &'m LtMap<Value, Value>, name: &str) -> Option<&'m Value> {
+        fields.iter().find_map(|(key, value)| match key {
+            Value::Keyword(id) if (self.keyword_name)(*id).as_deref() == Some(name) => Some(value),
+            _ => None,
+        })
+    }
+
+    /// Shows a list item: a noun's name, or any other value as displayed.
+    fn item(&self, value: &Value) -> String {
+        self.noun(value)
+            .map_or_else(|_| (self.display)(value), |noun| noun.name)
+    }
+}
+
+fn noun_error(value: &Value) -> Error {
+    Error::new(ErrorKind::TypeMismatch {
+        expected: Type::String,
+        actual: value.value_type(),
+    })
+}
+
+/// Text: a-or-an - a noun with its indefinite article
+/// (a-or-an "apple") -> "an apple", (a-or-an "unicorn") -> "a unicorn"
+pub(crate) fn native_a_or_an(args: &[Value], text: &TextContext<'_>) -> Result<Value> {
+    let noun = text.noun(args.first().unwrap_or(&Value::Nil))?;
+    if noun.proper {
+        return Ok(Value::from(noun.name));
+    }
+    let article = noun
+        .article
+        .unwrap_or_else(|| indefinite_article(&noun.name).to_string());
+    Ok(Value::from(format!("{article} {}", noun.name)))
+}
+
+/// Text: pluralize - a noun's plural, or its singular for a count of 1
+/// (pluralize "knife") -> "knives", (pluralize "box" 1) -> "box"
+pub(crate) fn native_pluralize(args: &[Value], text: &TextContext<'_>) -> Result<Value> {
+    let noun = text.noun(args.first().unwrap_or(&Value::Nil))?;
+    match args.get(1) {
+        Some(Value::Int(1)) => return Ok(Value::from(noun.name)),
+        None | Some(Value::Int(_)) => {}
+        Some(other) => {
+            return Err(Error::new(ErrorKind::TypeMismatch {
+                expected: Type::Int,
+                actual: other.value_type(),
+            }));
+        }
+    }
+    Ok(Value::from(
+        noun.plural.unwrap_or_else(|| plural(&noun.name)),
+    ))
+}
+
+/// Text: join-and - items as an English list, with a serial comma
+/// `(join-and ["sword" "shield" "lamp"])` -> "sword, shield, and lamp"
+/// `(join-and ["tea" "coffee"] "or")` -> "tea or coffee"
+pub(crate) fn native_join_and(args: &[Value], text: &TextContext<'_>) -> Result<Value> {
+    let items: Vec<String> = match args.first() {
+        Some(Value::Vec(items) | Value::List(items)) => {
+            items.iter().map(|v| text.item(v)).collect()
+        }
+        Some(Value::Set(items)) => items.iter().map(|v| text.item(v)).collect(),
+        Some(Value::Nil) | None => Vec::new(),
+        Some(other) => {
+            return Err(Error::new(ErrorKind::TypeMismatch {
+                expected: Type::vec(Type::Any),
+                actual: other.value_type(),
+            }));
+        }
+    };
+    let conjunction = match args.get(1) {
+        Some(Value::String(s)) => s.to_string(),
+        _ => "and".to_string(),
+    };
+    let joined = match items.as_slice() {
+        [] => String::new(),
+        [only] => only.clone(),
+        [first, second] => format!("{first} {conjunction} {second}"),
+        [init @ .., last] => format!("{}, {conjunction} {last}", init.join(", ")),
+    };
+    Ok(Value::from(joined))
+}
+
+/// Returns "a" or "an" for a noun, by how its first word sounds.
+fn indefinite_article(noun: &str) -> &'static str {
+    // Silent h, and vowels sounded as consonants
+    const AN: &[&str] = &["hour", "honest", "honor", "honour", "heir"];
+    const A: &[&str] = &["uni", "use", "usu", "uti", "eu", "ewe", "one", "once"];
+    let word = noun
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if AN.iter().any(|prefix| word.starts_with(prefix)) {
+        "an"
+    } else if A.iter().any(|prefix| word.starts_with(prefix)) {
+        "a"
+    } else if word.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        "an"
+    } else {
+        "a"
+    }
+}
+
+/// Returns the plural of a noun, pluralizing its last word.
+fn plural(noun: &str) -> String {
+    let (head, word) = match noun.rfind(' ') {
+        Some(i) => noun.split_at(i + 1),
+        None => ("", noun),
+    };
+    format!("{head}{}", plural_word(word))
+}
+
+fn plural_word(word: &str) -> String {
+    const IRREGULAR: &[(&str, &str)] = &[
+        ("child", "children"),
+        ("foot", "feet"),
+        ("goose", "geese"),
+        ("man", "men"),
+        ("mouse", "mice"),
+        ("person", "people"),
+        ("tooth", "teeth"),
+        ("woman", "women"),
+        ("deer", "deer"),
+        ("fish", "fish"),
+        ("sheep", "sheep"),
+        ("knife", "knives"),
+        ("leaf", "leaves"),
+        ("life", "lives"),
+        ("loaf", "loaves"),
+        ("shelf", "shelves"),
+        ("thief", "thieves"),
+        ("wife", "wives"),
+        ("wolf", "wolves"),
+    ];
+    let lower = word.to_lowercase();
+    if let Some((_, plural)) = IRREGULAR.iter().find(|(singular, _)| *singular == lower) {
+        // Keep a capitalized word capitalized
+        let mut chars = plural.chars();
+        return match (word.chars().next(), chars.next()) {
+            (Some(first), Some(p)) if first.is_uppercase() => {
+                p.to_uppercase().chain(chars).collect()
+            }
+            _ => (*plural).to_string(),
+        };
+    }
+
+    let ends_with_consonant_y =
+        lower.ends_with('y') && !lower[..lower.len() - 1].ends_with(['a', 'e', 'i', 'o', 'u']);
+    if ends_with_consonant_y {
+        format!("{}ies", &word[..word.len() - 1])
+    } else if ["s", "x", "z", "ch", "sh"]
+        .iter()
+        .any(|suffix| lower.ends_with(suffix))
+    {
+        format!("{word}es")
+    } else {
+        format!("{word}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn articles_and_plurals_follow_english_rules() {
+        let cases = [
+            ("apple", "an"),
+            ("sword", "a"),
+            ("hour", "an"),
+            ("unicorn", "a"),
+            ("umbrella", "an"),
+            ("one-eyed cat", "a"),
+        ];
+        for (noun, article) in cases {
+            assert_eq!(indefinite_article(noun), article, "{noun}");
+        }
+
+        let cases = [
+            ("sword", "swords"),
+            ("box", "boxes"),
+            ("torch", "torches"),
+            ("ruby", "rubies"),
+            ("key", "keys"),
+            ("brass key", "brass keys"),
+            ("knife", "knives"),
+            ("Child", "Children"),
+            ("old man", "old men"),
+        ];
+        for (noun, expected) in cases {
+            assert_eq!(plural(noun), expected, "{noun}");
+        }
+    }
+}
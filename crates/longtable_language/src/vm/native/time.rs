@@ -0,0 +1,97 @@
+//! Time functions for the VM.
+//!
+//! Instants count milliseconds since the Unix epoch and durations count
+//! milliseconds; the arithmetic between them lives with the rest in
+//! `arithmetic`.
+
+use longtable_foundation::{Error, ErrorKind, Result, Type, Value};
+
+use super::arithmetic::{checked_millis, round_millis};
+
+/// Returns the first argument as a whole number of milliseconds.
+fn millis_arg(args: &[Value]) -> Result<i64> {
+    match args.first() {
+        Some(Value::Int(n)) => Ok(*n),
+        other => Err(Error::new(ErrorKind::TypeMismatch {
+            expected: Type::Int,
+            actual: other.map_or(Type::Nil, Value::value_type),
+        })),
+    }
+}
+
+/// Returns a duration of the first argument times `unit` milliseconds.
+fn span(args: &[Value], unit: i64) -> Result<Value> {
+    match args.first() {
+        Some(Value::Int(n)) => checked_millis(n.checked_mul(unit), "duration").map(Value::Duration),
+        Some(Value::Float(n)) => round_millis(n * unit as f64).map(Value::Duration),
+        other => Err(Error::new(ErrorKind::TypeMismatch {
+            expected: Type::Int,
+            actual: other.map_or(Type::Nil, Value::value_type),
+        })),
+    }
+}
+
+/// Time: instant - the instant `ms` milliseconds after the epoch
+pub(crate) fn native_instant(args: &[Value]) -> Result<Value> {
+    millis_arg(args).map(Value::Instant)
+}
+
+/// Time: duration - a duration of `ms` milliseconds
+pub(crate) fn native_duration(args: &[Value]) -> Result<Value> {
+    millis_arg(args).map(Value::Duration)
+}
+
+/// Time: seconds
+pub(crate) fn native_seconds(args: &[Value]) -> Result<Value> {
+    span(args, 1000)
+}
+
+/// Time: minutes
+pub(crate) fn native_minutes(args: &[Value]) -> Result<Value> {
+    span(args, 60 * 1000)
+}
+
+/// Time: hours
+pub(crate) fn native_hours(args: &[Value]) -> Result<Value> {
+    span(args, 60 * 60 * 1000)
+}
+
+/// Time: days
+pub(crate) fn native_days(args: &[Value]) -> Result<Value> {
+    span(args, 24 * 60 * 60 * 1000)
+}
+
+/// Time: to-millis - an instant or duration as an integer
+pub(crate) fn native_to_millis(args: &[Value]) -> Result<Value> {
+    match args.first() {
+        Some(Value::Instant(ms) | Value::Duration(ms)) => Ok(Value::Int(*ms)),
+        other => Err(Error::new(ErrorKind::TypeMismatch {
+            expected: Type::Duration,
+            actual: other.map_or(Type::Nil, Value::value_type),
+        })),
+    }
+}
+
+/// Time: to-seconds - a duration in (fractional) seconds
+pub(crate) fn native_to_seconds(args: &[Value]) -> Result<Value> {
+    match args.first() {
+        Some(Value::Duration(ms)) => Ok(Value::Float(*ms as f64 / 1000.0)),
+        other => Err(Error::new(ErrorKind::TypeMismatch {
+            expected: Type::Duration,
+            actual: other.map_or(Type::Nil, Value::value_type),
+        })),
+    }
+}
+
+/// Predicate: instant?
+pub(crate) fn native_instant_p(args: &[Value]) -> Result<Value> {
+    Ok(Value::Bool(matches!(args.first(), Some(Value::Instant(_)))))
+}
+
+/// Predicate: duration?
+pub(crate) fn native_duration_p(args: &[Value]) -> Result<Value> {
+    Ok(Value::Bool(matches!(
+        args.first(),
+        Some(Value::Duration(_))
+    )))
+}
@@ -0,0 +1,207 @@
+//! Rich text output from `say`.
+//!
+//! `println` writes plain text, which is all a terminal needs but loses what
+//! a graphical front-end would show differently: emphasis, colour, and which
+//! words name an entity the player could click. `say` takes markup vectors
+//! alongside ordinary values and records the result as [`RichSpan`]s:
+//!
+//! ```text
+//! (say "You see " [:link door "a door"] ", " [:em "slightly " [:color :red "ajar"]] ".")
+//! ```
+//!
+//! | Markup | Meaning |
+//! |--------|---------|
+//! | `[:em parts...]` | emphasis |
+//! | `[:strong parts...]` | strong emphasis |
+//! | `[:color :name parts...]` | text in the named colour |
+//! | `[:link entity parts...]` | text naming an entity |
+//!
+//! Markup nests, and anything else is displayed as `println` would display
+//! it. Every output function also writes plain spans, so the spans are a
+//! complete, ordered record of the output.
+
+use longtable_foundation::{EntityId, Error, ErrorKind, KeywordId, Result, Type, Value};
+
+/// A run of output text with one style.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RichSpan {
+    /// The text.
+    pub text: String,
+    /// Whether the text is emphasized.
+    pub emphasis: bool,
+    /// Whether the text is strongly emphasized.
+    pub strong: bool,
+    /// The colour's name, such as `"red"`.
+    pub color: Option<String>,
+    /// The entity the text names.
+    pub link: Option<EntityId>,
+}
+
+impl RichSpan {
+    /// Creates an unstyled span.
+    #[must_use]
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns true if the span has no style.
+    #[must_use]
+    pub fn is_plain(&self) -> bool {
+        !self.emphasis && !self.strong && self.color.is_none() && self.link.is_none()
+    }
+
+    fn same_style(&self, other: &Self) -> bool {
+        self.emphasis == other.emphasis
+            && self.strong == other.strong
+            && self.color == other.color
+            && self.link == other.link
+    }
+}
+
+/// Returns the text of `spans` without their styles.
+#[must_use]
+pub fn plain_text(spans: &[RichSpan]) -> String {
+    spans.iter().map(|span| span.text.as_str()).collect()
+}
+
+/// Appends `span` to `spans`, joining it to the last span if they share a
+/// style.
+pub(crate) fn push_span(spans: &mut Vec<RichSpan>, span: RichSpan) {
+    if span.text.is_empty() {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.same_style(&span) => last.text.push_str(&span.text),
+        _ => spans.push(span),
+    }
+}
+
+/// Renders `say`'s arguments as spans, ending with a newline.
+///
+/// `keyword_name` resolves markup tags and colour names; `display` shows
+/// every other value.
+///
+/// # Errors
+///
+/// Returns an error for a `:color` without a colour keyword, or a `:link`
+/// without an entity.
+pub(crate) fn markup(
+    parts: &[Value],
+    keyword_name: &dyn Fn(KeywordId) -> Option<String>,
+    display: &dyn Fn(&Value) -> String,
+) -> Result<Vec<RichSpan>> {
+    let mut spans = Vec::new();
+    let renderer = Markup {
+        keyword_name,
+        display,
+    };
+    for part in parts {
+        renderer.render(part, &RichSpan::default(), &mut spans)?;
+    }
+    push_span(&mut spans, RichSpan::plain("\n"));
+    Ok(spans)
+}
+
+struct Markup<'a> {
+    keyword_name: &'a dyn Fn(KeywordId) -> Option<String>,
+    display: &'a dyn Fn(&Value) -> String,
+}
+
+impl Markup<'_> {
+    fn render(&self, part: &Value, style: &RichSpan, spans: &mut Vec<RichSpan>) -> Result<()> {
+        let tagged = match part {
+            Value::Vec(items) => match items.first() {
+                Some(Value::Keyword(tag)) => (self.keyword_name)(*tag)
+                    .filter(|tag| matches!(tag.as_str(), "em" | "strong" | "color" | "link"))
+                    .map(|tag| (tag, items.iter().skip(1).cloned().collect::<Vec<_>>())),
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some((tag, args)) = tagged else {
+            let text = (self.display)(part);
+            push_span(
+                spans,
+                RichSpan {
+                    text,
+                    ..style.clone()
+                },
+            );
+            return Ok(());
+        };
+
+        let mut inner = style.clone();
+        let children = match tag.as_str() {
+            "em" => {
+                inner.emphasis = true;
+                &args[..]
+            }
+            "strong" => {
+                inner.strong = true;
+                &args[..]
+            }
+            "color" => {
+                let Some(Value::Keyword(color)) = args.first() else {
+                    return Err(markup_error(Type::Keyword, args.first()));
+                };
+                inner.color = (self.keyword_name)(*color);
+                &args[1..]
+            }
+            _ => {
+                let Some(Value::EntityRef(entity)) = args.first() else {
+                    return Err(markup_error(Type::EntityRef, args.first()));
+                };
+                inner.link = Some(*entity);
+                &args[1..]
+            }
+        };
+        for child in children {
+            self.render(child, &inner, spans)?;
+        }
+        Ok(())
+    }
+}
+
+fn markup_error(expected: Type, actual: Option<&Value>) -> Error {
+    Error::new(ErrorKind::TypeMismatch {
+        expected,
+        actual: actual.map_or(Type::Nil, Value::value_type),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::{Interner, LtVec};
+
+    #[test]
+    fn nests_styles_and_joins_runs() {
+        let mut interner = Interner::new();
+        let em = Value::Keyword(interner.intern_keyword("em"));
+        let color = Value::Keyword(interner.intern_keyword("color"));
+        let red = Value::Keyword(interner.intern_keyword("red"));
+        let vec = |items: Vec<Value>| Value::Vec(items.into_iter().collect::<LtVec<_>>());
+        let keyword_name = |id| interner.get_keyword(id).map(str::to_string);
+        let display = |v: &Value| v.to_string();
+
+        let parts = [
+            Value::String("a ".into()),
+            vec(vec![
+                em,
+                Value::String("b ".into()),
+                vec(vec![color, red, Value::String("c".into())]),
+            ]),
+            Value::Int(1),
+        ];
+        let spans = markup(&parts, &keyword_name, &display).unwrap();
+
+        assert_eq!(plain_text(&spans), "a b c1\n");
+        assert_eq!(spans.len(), 4);
+        assert!(spans[1].emphasis && spans[1].color.is_none());
+        assert!(spans[2].emphasis && spans[2].color.as_deref() == Some("red"));
+        assert!(spans[3].is_plain());
+    }
+}
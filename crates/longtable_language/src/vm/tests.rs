@@ -64,6 +64,284 @@ fn eval_division_by_zero() {
     assert!(result.is_err());
 }
 
+#[test]
+fn eval_integer_overflow_promotes() {
+    let big = |s: &str| Value::integer(s.parse().unwrap());
+    assert_eq!(
+        eval_test("(+ 9223372036854775807 1)"),
+        big("9223372036854775808")
+    );
+    assert_eq!(
+        eval_test("(* 9223372036854775807 9223372036854775807)"),
+        big("85070591730234615847396907784232501249")
+    );
+    assert_eq!(
+        eval_test("(dec (inc 9223372036854775807))"),
+        Value::Int(i64::MAX)
+    );
+    assert_eq!(
+        eval_test("(- (- -9223372036854775807 1))"),
+        big("9223372036854775808")
+    );
+    assert_eq!(
+        eval_test(r#"(/ (bigint "100000000000000000000") 1000000000000)"#),
+        Value::Int(100_000_000)
+    );
+    assert_eq!(
+        eval_test(r#"(< 5 (bigint "100000000000000000000"))"#),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        eval_test(r#"(int? (bigint "100000000000000000000"))"#),
+        Value::Bool(true)
+    );
+}
+
+#[test]
+fn eval_records() {
+    let point = "(defrecord point :x :int :y :int :label :string :default \"origin\")\n";
+    let eval_point = |body: &str| eval(&format!("{point}{body}"));
+
+    assert_eq!(eval_point("(:x (point :x 1 :y 2))").unwrap(), Value::Int(1));
+    assert_eq!(
+        eval_point("(let [p (point :y 2 :x 1)] (:label p))").unwrap(),
+        Value::from("origin")
+    );
+    // Non-literal values are checked when the record is built
+    let err = eval_point("(let [y \"2\"] (point :x 1 :y y))").unwrap_err();
+    assert!(err.to_string().contains("field :y expects"), "{err}");
+
+    // Mistakes the compiler can see
+    for (body, message) in [
+        ("(point :x 1 :z 2)", "has no field :z"),
+        ("(point :x 1)", "missing field :y"),
+        ("(point :x 1 :y 2.5)", "field :y expects"),
+        (
+            "(let [p (point :x 1 :y 2)] (:lable p))",
+            "has no field :lable",
+        ),
+    ] {
+        let err = eval_point(body).unwrap_err();
+        assert!(err.to_string().contains(message), "{body}: {err}");
+    }
+
+    // Keyword access works on any map
+    assert_eq!(eval_test("(:a {:a 1})"), Value::Int(1));
+    assert_eq!(eval_test("(:b {:a 1})"), Value::Nil);
+}
+
+#[test]
+fn eval_lazy_sequences() {
+    let ints = |ns: &[i64]| Value::Vec(ns.iter().map(|n| Value::Int(*n)).collect());
+
+    assert_eq!(eval_test("(vec (take 3 (range)))"), ints(&[0, 1, 2]));
+    assert_eq!(
+        eval_test("(vec (take 4 (iterate (fn [x] (* x 2)) 1)))"),
+        ints(&[1, 2, 4, 8])
+    );
+    assert_eq!(
+        eval_test("(vec (take-while (fn [x] (< x 20)) (filter (fn [x] (= 0 (mod x 7))) (range))))"),
+        ints(&[0, 7, 14])
+    );
+    assert_eq!(
+        eval_test("(first (drop-while (fn [x] (< x 100)) (map (fn [x] (* x x)) (range))))"),
+        Value::Int(100)
+    );
+    assert_eq!(
+        eval_test("(nth (remove (fn [x] (= 0 (mod x 2))) (range)) 2)"),
+        Value::Int(5)
+    );
+    assert_eq!(
+        eval_test("(reduce (fn [a b] (+ a b)) 0 (take 10 (range)))"),
+        Value::Int(45)
+    );
+    assert_eq!(eval_test("(lazy? (lazy [1 2]))"), Value::Bool(true));
+
+    // Finite sequences compare and come back by their values
+    assert_eq!(eval_test("(= (take 3 (range)) [0 1 2])"), Value::Bool(true));
+    assert_eq!(eval_test("(!= [0 1] (take 3 (range)))"), Value::Bool(true));
+    assert_eq!(
+        eval_test("(take 3 (map (fn [x] (* x x)) (range 1 10)))"),
+        ints(&[1, 4, 9])
+    );
+    assert!(matches!(eval_test("(range)"), Value::Seq(_)));
+
+    // Only the values asked for are computed
+    assert_eq!(
+        eval_test("(count (take 2 (map (fn [x] (/ 10 x)) (lazy [5 2 0]))))"),
+        Value::Int(2)
+    );
+    let err = eval("(count (range))").unwrap_err();
+    assert!(err.to_string().contains("infinite"), "{err}");
+}
+
+#[test]
+fn eval_match() {
+    let describe = r#"(fn [v]
+        (match v
+          nil "nil"
+          42 "forty-two"
+          (:or :yes :true) "truthy"
+          [] "empty"
+          [?x ?x] (str "pair of " ?x)
+          [?head & ?tail] (str ?head " then " (count ?tail))
+          {:type :point :x ?x :y ?y} (str "point at " ?x "," ?y)
+          {:keys [name age]} (str name " is " age)
+          (?n :when (> ?n 0)) "positive"
+          _ "other"))"#;
+    let cases = [
+        ("nil", "nil"),
+        ("42", "forty-two"),
+        (":true", "truthy"),
+        ("[]", "empty"),
+        ("[7 7]", "pair of 7"),
+        ("[7 8]", "7 then 1"),
+        ("[1 2 3]", "1 then 2"),
+        ("{:type :point :x 1 :y 2}", "point at 1,2"),
+        ("{:name \"Ann\" :age 30}", "Ann is 30"),
+        ("5", "positive"),
+        ("-5", "other"),
+    ];
+    for (value, expected) in cases {
+        let source = format!("(let [describe {describe}] (describe {value}))");
+        assert_eq!(eval_test(&source), Value::from(expected), "{value}");
+    }
+
+    // No matching clause gives nil
+    assert_eq!(eval_test("(match 1 2 :two)"), Value::Nil);
+    // Clauses are in tail position
+    assert_eq!(
+        eval_test("(let [f (fn [n acc] (match n 0 acc _ (f (- n 1) (+ acc 1))))] (f 10000 0))"),
+        Value::Int(10000)
+    );
+    assert!(eval("(match 1 (:or ?a 1) ?a)").is_err());
+    // A bare symbol would catch everything, so it must be ?x or _
+    let err = eval("(let [two 2] (match 1 two :two _ :other))").unwrap_err();
+    assert!(err.to_string().contains("`?two`"), "{err}");
+}
+
+#[test]
+fn eval_time_arithmetic() {
+    assert_eq!(eval_test("(seconds 1.5)"), Value::Duration(1500));
+    assert_eq!(
+        eval_test("(+ (instant 1000) (minutes 1))"),
+        Value::Instant(61_000)
+    );
+    assert_eq!(
+        eval_test("(- (instant 5000) (instant 2000))"),
+        Value::Duration(3000)
+    );
+    assert_eq!(eval_test("(* 3 (seconds 2))"), Value::Duration(6000));
+    assert_eq!(eval_test("(/ (hours 1) (minutes 30))"), Value::Float(2.0));
+    assert_eq!(eval_test("(to-millis (days 1))"), Value::Int(86_400_000));
+    assert_eq!(eval_test("(< (instant 1) (instant 2))"), Value::Bool(true));
+    assert_eq!(eval_test("(> (seconds 1) (seconds 2))"), Value::Bool(false));
+    assert_eq!(
+        eval_test("[(instant? (instant 0)) (duration? (instant 0))]"),
+        Value::Vec(
+            vec![Value::Bool(true), Value::Bool(false)]
+                .into_iter()
+                .collect()
+        )
+    );
+
+    // Instants can't be added together, or mixed with plain numbers
+    assert!(eval("(+ (instant 1) (instant 2))").is_err());
+    assert!(eval("(+ (instant 1) 2)").is_err());
+    assert!(eval("(/ (seconds 1) 0)").is_err());
+
+    // They have no big form, so overflowing one is an error
+    for source in [
+        "(+ (instant 9223372036854775807) (duration 1))",
+        "(- (instant -9223372036854775807) (seconds 1))",
+        "(* (days 1) 9223372036854775807)",
+        "(* (days 1) 1000000000000000000000.0)",
+        "(days 9223372036854775807)",
+    ] {
+        let err = eval(source).unwrap_err();
+        assert_eq!(err.kind.code(), "E0015", "{source}: {err}");
+    }
+}
+
+#[test]
+fn eval_fixed_size_vectors() {
+    assert_eq!(eval_test("(vec2 3 4)"), Value::Vec2([3.0, 4.0]));
+    assert_eq!(eval_test("(vec3 [1 2 3])"), Value::Vec3([1.0, 2.0, 3.0]));
+    assert_eq!(
+        eval_test("(vec+ (vec2 1 2) (vec2 3 4))"),
+        Value::Vec2([4.0, 6.0])
+    );
+    assert_eq!(
+        eval_test("(vec-scale (vec3 1 2 3) 2)"),
+        Value::Vec3([2.0, 4.0, 6.0])
+    );
+    assert_eq!(
+        eval_test("(vec-normalize (vec2 3 4))"),
+        Value::Vec2([0.6, 0.8])
+    );
+    assert_eq!(
+        eval_test("(vec-cross (vec3 1 0 0) (vec3 0 1 0))"),
+        Value::Vec3([0.0, 0.0, 1.0])
+    );
+    assert_eq!(
+        eval_test("(vec-dot (vec2 1 2) (vec2 3 4))"),
+        Value::Float(11.0)
+    );
+    assert_eq!(eval_test("(vec-length (vec2 3 4))"), Value::Float(5.0));
+    assert_eq!(eval_test("(vec-y (vec2 3 4))"), Value::Float(4.0));
+    assert_eq!(eval_test("(vec-z (vec2 3 4))"), Value::Float(0.0));
+    assert_eq!(
+        eval_test("(= (vec2 1 2) (vec2 1.0 2.0))"),
+        Value::Bool(true)
+    );
+    // Plain vectors stay plain
+    assert_eq!(
+        eval_test("(vec+ [1 2] (vec2 3 4))"),
+        Value::Vec(
+            vec![Value::Float(4.0), Value::Float(6.0)]
+                .into_iter()
+                .collect()
+        )
+    );
+
+    assert!(eval("(vec2 1 2 3)").is_err());
+    assert!(eval("(vec3 1 :two 3)").is_err());
+    assert!(eval("(vec+ (vec2 1 2) (vec3 1 2 3))").is_err());
+}
+
+#[test]
+fn runtime_errors_point_at_the_failing_form() {
+    let err = eval("(+ 1\n   (/ 10 0))").unwrap_err();
+    let context = err.context.expect("error should be located");
+    assert_eq!((context.line, context.column), (Some(2), Some(4)));
+
+    // Inside a function, the form in the function's body is reported
+    let err = eval("(let [f (fn [x]\n          (* x \"a\"))]\n  (f 2))").unwrap_err();
+    let context = err.context.expect("error should be located");
+    assert_eq!((context.line, context.column), (Some(2), Some(11)));
+}
+
+#[test]
+fn runtime_errors_carry_a_backtrace() {
+    let source = "(let [inner (fn [x] (/ x 0))
+      outer (fn [x]
+              (+ 1 (inner x)))]
+  (outer 5))";
+    let context = eval(source).unwrap_err().context.unwrap();
+    assert_eq!(
+        context.stack,
+        ["inner (<repl>:1:21)", "outer (<repl>:3:20)"]
+    );
+
+    // Anonymous functions are described by their parameters, and frames
+    // from functions called by natives like map are kept
+    let context = eval("(map (fn [n] (/ 1 n)) [1 0])")
+        .unwrap_err()
+        .context
+        .unwrap();
+    assert_eq!(context.stack, ["(fn [n]) (<repl>:1:14)"]);
+}
+
 #[test]
 fn eval_nested_arithmetic() {
     assert_eq!(eval_test("(+ (* 2 3) (- 10 5))"), Value::Int(11));
@@ -932,6 +1210,32 @@ fn eval_str_substring() {
     );
 }
 
+#[test]
+fn eval_text_generation() {
+    assert_eq!(eval_test(r#"(a-or-an "apple")"#), Value::from("an apple"));
+    assert_eq!(eval_test(r#"(a-or-an "hour")"#), Value::from("an hour"));
+    assert_eq!(
+        eval_test(r#"(a-or-an "unicorn")"#),
+        Value::from("a unicorn")
+    );
+    assert_eq!(eval_test(r#"(pluralize "knife")"#), Value::from("knives"));
+    assert_eq!(
+        eval_test(r#"(pluralize "torch" 3)"#),
+        Value::from("torches")
+    );
+    assert_eq!(eval_test(r#"(pluralize "torch" 1)"#), Value::from("torch"));
+    assert_eq!(
+        eval_test(r#"(join-and ["sword" "shield" "lamp"])"#),
+        Value::from("sword, shield, and lamp")
+    );
+    assert_eq!(
+        eval_test(r#"(join-and ["tea" "coffee"] "or")"#),
+        Value::from("tea or coffee")
+    );
+    assert_eq!(eval_test("(join-and [])"), Value::from(""));
+    assert!(eval("(a-or-an 3)").is_err());
+}
+
 #[test]
 fn eval_format() {
     assert_eq!(
@@ -942,6 +1246,26 @@ fn eval_format() {
         eval_test(r#"(format "{} + {} = {}" 1 2 3)"#),
         Value::String("1 + 2 = 3".into())
     );
+    assert_eq!(
+        eval_test(r#"(format "Hello ~a, you have ~a hp~%" "Ann" 12)"#),
+        Value::from("Hello Ann, you have 12 hp\n")
+    );
+    assert_eq!(
+        eval_test(r#"(format "[~6a|~6@a|~4d|~,2f|~8,1f|~s|~~]" "ab" "cd" 7 3.14159 2 "q")"#),
+        Value::from("[ab    |    cd|   7|3.14|     2.0|\"q\"|~]")
+    );
+    assert_eq!(
+        eval_test(r#"(format "~,3a..." "truncated")"#),
+        Value::from("tru...")
+    );
+    assert!(eval(r#"(format "~a and ~a" 1)"#).is_err());
+    assert!(eval(r#"(format "~d" "x")"#).is_err());
+    assert!(eval(r#"(format "~q" 1)"#).is_err());
+    assert!(matches!(
+        eval(r#"(format "~99999999999a" 1)"#).unwrap_err().kind,
+        longtable_foundation::ErrorKind::Overflow(_)
+    ));
+    assert!(eval(r#"(format "~,5000f" 1.0)"#).is_err());
 }
 
 // ============================================================================
@@ -1143,6 +1467,12 @@ fn eval_rem() {
     assert_eq!(eval_test(r"(rem 10 3)"), Value::Int(1));
     assert_eq!(eval_test(r"(rem -10 3)"), Value::Int(-1));
     assert_eq!(eval_test(r"(rem 10.5 3.0)"), Value::Float(1.5));
+    assert_eq!(
+        eval_test(r"(rem (* 9223372036854775807 10) 7)"),
+        Value::Int(0)
+    );
+    assert_eq!(eval_test(r"(rem -9223372036854775808 -1)"), Value::Int(0));
+    assert!(eval(r"(rem 10 0)").is_err());
 }
 
 #[test]
@@ -1165,6 +1495,12 @@ fn eval_pow() {
     assert_eq!(eval_test(r"(pow 2 3)"), Value::Int(8));
     assert_eq!(eval_test(r"(pow 2.0 3.0)"), Value::Float(8.0));
     assert_eq!(eval_test(r"(pow 4 0.5)"), Value::Float(2.0));
+    assert_eq!(
+        eval_test(r"(pow 2 64)").to_string(),
+        "18446744073709551616".to_string()
+    );
+    assert_eq!(eval_test(r"(pow (pow 2 64) 2)"), eval_test(r"(pow 2 128)"));
+    assert!(eval(r"(pow 2 4294967296)").is_err());
 }
 
 #[test]
@@ -1581,3 +1917,82 @@ fn eval_repeatedly_with_closure() {
         _ => panic!("Expected Vec"),
     }
 }
+
+#[test]
+fn session_commands_need_a_session() {
+    let err = eval("(let [advance (fn [] (tick!))] (advance))").unwrap_err();
+    assert!(err.to_string().contains("(tick!) needs a session"));
+}
+
+#[test]
+fn read_only_contexts_refuse_session_commands() {
+    // Phase hooks and timers run read-only, in the middle of a tick
+    let program = crate::compiler::compile("(do (save! (str \"out\" \".lt\")) 42)").unwrap();
+    let mut vm = Vm::new();
+    let err = vm
+        .execute_with_context(&program, &context::NoRuntimeContext)
+        .unwrap_err();
+    assert!(err.to_string().contains("(save!) needs a session"), "{err}");
+    assert!(vm.take_effects().is_empty());
+}
+
+#[test]
+fn spawn_many_queues_a_spawn_per_map() {
+    let program = crate::compiler::compile("(spawn-many! [{} {} {}])").unwrap();
+    let mut vm = Vm::new();
+    let result = vm
+        .execute_with_context(&program, &context::NoRuntimeContext)
+        .unwrap();
+    let Value::Vec(spawned) = result else {
+        panic!("expected a vector, got {result:?}");
+    };
+    let effects = vm.take_effects();
+    assert_eq!(spawned.len(), 3);
+    assert_eq!(effects.len(), 3);
+    for (entity, effect) in spawned.iter().zip(&effects) {
+        assert!(matches!(
+            (entity, effect),
+            (Value::EntityRef(e), VmEffect::Spawn { temp_id, .. }) if e == temp_id
+        ));
+    }
+    assert!(eval("(spawn-many! 3)").is_err());
+}
+
+#[test]
+fn abort_keeps_temporary_ids_unique() {
+    let program = crate::compiler::compile("(spawn! {})").unwrap();
+    let mut vm = Vm::new();
+    let first = vm
+        .execute_with_context(&program, &context::NoRuntimeContext)
+        .unwrap();
+    vm.abort();
+    assert!(vm.take_effects().is_empty());
+    let second = vm
+        .execute_with_context(&program, &context::NoRuntimeContext)
+        .unwrap();
+    assert_ne!(first, second);
+}
+
+#[test]
+fn eval_peek_returns_its_value() {
+    assert_eq!(eval_test("(peek (+ 1 2))"), Value::Int(3));
+    assert_eq!(eval_test("(+ 1 (peek :budget 100 (* 2 3)))"), Value::Int(7));
+}
+
+#[test]
+fn peek_refuses_effects() {
+    let err = eval("(peek (emit! :boom nil))").unwrap_err();
+    assert!(err.to_string().contains("read-only"), "{err}");
+}
+
+#[test]
+fn peek_enforces_its_budget() {
+    let source =
+        "(peek :budget 20 (reduce (fn [a b] (+ a b)) 0 (map (fn [x] (* x x)) [1 2 3 4 5 6 7 8])))";
+    let err = eval(source).unwrap_err();
+    assert!(err.to_string().contains("budget of 20"), "{err}");
+
+    let source =
+        "(peek :budget 200 (reduce (fn [a b] (+ a b)) 0 (map (fn [x] (* x x)) [1 2 3 4 5 6 7 8])))";
+    assert_eq!(eval_test(source), Value::Int(204));
+}
@@ -313,6 +313,14 @@ impl ActionBindings {
             }
         }
 
+        // Get topic from command (if present)
+        if let Ok(Some(Value::Map(map))) = world.get(command_entity, keywords.topic) {
+            if let Some(topic @ Value::Keyword(_)) = map.get(&Value::Keyword(keywords.value_field))
+            {
+                bindings.bind_value("topic", topic.clone());
+            }
+        }
+
         Some(bindings)
     }
 }
@@ -19,6 +19,10 @@ pub struct CommandEntity {
     pub actor: EntityId,
     /// Noun bindings (slot name -> entity)
     pub noun_bindings: HashMap<String, EntityId>,
+    /// Text bindings (slot name -> captured text)
+    pub text_bindings: HashMap<String, String>,
+    /// Topic binding (variable name -> topic keyword), if any
+    pub topic: Option<(String, KeywordId)>,
     /// Direction binding (variable name -> direction keyword), if any
     pub direction: Option<(String, KeywordId)>,
     /// Adverb modifier, if any
@@ -42,6 +46,8 @@ pub struct CommandKeywords {
     pub destination: KeywordId,
     /// Keyword for `:command/adverb`
     pub adverb: KeywordId,
+    /// Keyword for `:command/topic`
+    pub topic: KeywordId,
     /// Keyword for the `:value` field in component maps
     pub value_field: KeywordId,
 }
@@ -100,6 +106,11 @@ impl CommandSpawner {
             .with_field(FieldSchema::required(value_kw, Type::Keyword));
         let world = world.register_component(adverb_schema)?;
 
+        // Register topic schema (keyword value)
+        let topic_schema = ComponentSchema::new(self.keywords.topic)
+            .with_field(FieldSchema::required(value_kw, Type::Keyword));
+        let world = world.register_component(topic_schema)?;
+
         Ok(world)
     }
 
@@ -120,6 +131,7 @@ impl CommandSpawner {
     /// - `:command/instrument` - "with X" object (if any)
     /// - `:command/destination` - "to X" object (if any)
     /// - `:command/adverb` - adverb modifier (if any)
+    /// - `:command/topic` - conversation topic (if any)
     #[allow(clippy::result_large_err)]
     pub fn spawn(
         &self,
@@ -171,6 +183,14 @@ impl CommandSpawner {
             );
         }
 
+        // Optional topic
+        if let Some((_, topic)) = &cmd.topic {
+            components = components.insert(
+                Value::Keyword(self.keywords.topic),
+                self.make_component_value(Value::Keyword(*topic)),
+            );
+        }
+
         // Spawn entity with components
         world.spawn(&components)
     }
@@ -212,6 +232,7 @@ mod tests {
         let instrument_kw = world.interner_mut().intern_keyword("command/instrument");
         let destination_kw = world.interner_mut().intern_keyword("command/destination");
         let adverb_kw = world.interner_mut().intern_keyword("command/adverb");
+        let topic_kw = world.interner_mut().intern_keyword("command/topic");
         let value_kw = world.interner_mut().intern_keyword("value");
 
         let keywords = CommandKeywords {
@@ -222,6 +243,7 @@ mod tests {
             instrument: instrument_kw,
             destination: destination_kw,
             adverb: adverb_kw,
+            topic: topic_kw,
             value_field: value_kw,
         };
 
@@ -245,6 +267,8 @@ mod tests {
             action: keywords.action,
             actor,
             noun_bindings: HashMap::new(),
+            text_bindings: HashMap::new(),
+            topic: None,
             direction: None,
             adverb: None,
         };
@@ -281,6 +305,8 @@ mod tests {
             action: keywords.action,
             actor,
             noun_bindings: bindings,
+            text_bindings: HashMap::new(),
+            topic: None,
             direction: None,
             adverb: None,
         };
@@ -292,6 +318,34 @@ mod tests {
         assert!(new_world.has(entity_id, keywords.target));
     }
 
+    #[test]
+    fn test_spawn_command_with_topic() {
+        let (mut world, keywords) = setup_world_with_keywords();
+        let treasure = world.interner_mut().intern_keyword("treasure");
+        let spawner = CommandSpawner::new(keywords.clone());
+        let world = spawner.register_schemas(&world).unwrap();
+        let (world, actor) = world.spawn(&LtMap::new()).unwrap();
+
+        let cmd = CommandEntity {
+            verb: keywords.verb,
+            action: keywords.action,
+            actor,
+            noun_bindings: HashMap::new(),
+            text_bindings: HashMap::new(),
+            topic: Some(("subject".to_string(), treasure)),
+            direction: None,
+            adverb: None,
+        };
+
+        let (world, entity_id) = spawner.spawn(&cmd, &world).unwrap();
+        assert_eq!(
+            world
+                .get_field(entity_id, keywords.topic, keywords.value_field)
+                .unwrap(),
+            Some(Value::Keyword(treasure))
+        );
+    }
+
     #[test]
     fn test_spawn_multiple_commands() {
         let (world, keywords) = setup_world_with_keywords();
@@ -309,6 +363,8 @@ mod tests {
                 action: keywords.action,
                 actor,
                 noun_bindings: HashMap::new(),
+                text_bindings: HashMap::new(),
+                topic: None,
                 direction: None,
                 adverb: None,
             },
@@ -317,6 +373,8 @@ mod tests {
                 action: keywords.action,
                 actor,
                 noun_bindings: HashMap::new(),
+                text_bindings: HashMap::new(),
+                topic: None,
                 direction: None,
                 adverb: None,
             },
@@ -47,6 +47,7 @@
 //! - [`syntax`] - Syntax pattern matching
 //! - [`parser`] - Main parser pipeline orchestration
 //! - [`pronouns`] - Pronoun tracking state
+//! - [`topic`] - Conversation topics known to each NPC
 //! - [`command`] - Command entity creation
 //! - [`action`] - Action registry and execution
 //! - [`stdlib`] - Standard vocabulary for adventure games
@@ -60,11 +61,13 @@ pub mod scope;
 pub mod stdlib;
 pub mod syntax;
 pub mod tokenizer;
+pub mod topic;
 pub mod vocabulary;
 
 // Re-export main types for convenience
 pub use action::{ActionRegistry, CompiledAction};
 pub use noun_phrase::NounResolver;
-pub use parser::{NaturalLanguageParser, ParseResult};
+pub use parser::{NaturalLanguageParser, ParseResult, ParseStep};
 pub use syntax::{CompiledSyntax, CompiledSyntaxElement, SyntaxCompiler};
+pub use topic::TopicResolver;
 pub use vocabulary::VocabularyRegistry;
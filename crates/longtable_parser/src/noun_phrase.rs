@@ -45,6 +45,30 @@ impl NounPhrase {
         self.quantifier = quantifier;
         self
     }
+
+    /// Selects the nth matching entity, counting from 1.
+    #[must_use]
+    pub fn with_ordinal(mut self, ordinal: usize) -> Self {
+        self.ordinal = Some(ordinal);
+        self
+    }
+}
+
+/// Reads a phrase ending in a number ("key 2") as an ordinal over the words
+/// before it, if it has no ordinal already.
+fn trailing_number_as_ordinal(phrase: &NounPhrase) -> Option<NounPhrase> {
+    let ordinal = phrase
+        .noun
+        .parse()
+        .ok()
+        .filter(|_| phrase.ordinal.is_none())?;
+    let (noun, adjectives) = phrase.adjectives.split_last()?;
+    Some(NounPhrase {
+        adjectives: adjectives.to_vec(),
+        noun: noun.clone(),
+        quantifier: phrase.quantifier.clone(),
+        ordinal: Some(ordinal),
+    })
 }
 
 /// Quantifier for noun phrases.
@@ -56,8 +80,10 @@ pub enum Quantifier {
     Any,
     /// All matching items
     All,
-    /// All except specified entities
+    /// All except specific entities
     AllExcept(Vec<EntityId>),
+    /// All matching items except those the phrases name (all but the lamp)
+    AllBut(Vec<NounPhrase>),
 }
 
 /// Result of noun resolution.
@@ -91,6 +117,8 @@ pub struct NounResolver {
     aliases_keyword: KeywordId,
     /// Keyword for adjectives component
     adjectives_keyword: KeywordId,
+    /// Tag components whose entities a bare "all" leaves out
+    all_exclusions: Vec<KeywordId>,
 }
 
 impl NounResolver {
@@ -107,16 +135,24 @@ impl NounResolver {
             value_keyword,
             aliases_keyword,
             adjectives_keyword,
+            all_exclusions: Vec::new(),
         }
     }
 
+    /// Sets the tag components, such as `:tag/room`, whose entities a bare
+    /// "all" leaves out. They can still be named: "take all rooms".
+    #[must_use]
+    pub fn with_all_exclusions(mut self, tags: Vec<KeywordId>) -> Self {
+        self.all_exclusions = tags;
+        self
+    }
+
     /// Resolves a noun phrase to entities in scope.
     ///
-    /// Resolution order:
-    /// 1. Exact name match
-    /// 2. Alias match
-    /// 3. Adjective + noun match
-    /// 4. Partial match on name/alias
+    /// An entity matches the noun by exact name, alias, or partial name.
+    /// The matches are then [narrowed](Self::narrow) by the phrase's
+    /// adjectives, so "take the red key" picks the red key out of several
+    /// keys without asking which one.
     pub fn resolve(
         &self,
         phrase: &NounPhrase,
@@ -128,7 +164,7 @@ impl NounResolver {
         // Handle "all" quantifier
         if matches!(
             phrase.quantifier,
-            Quantifier::All | Quantifier::AllExcept(_)
+            Quantifier::All | Quantifier::AllExcept(_) | Quantifier::AllBut(_)
         ) {
             return self.resolve_all(phrase, type_constraint, scope, world, vocab);
         }
@@ -136,16 +172,26 @@ impl NounResolver {
         // Find all matching entities
         let matches = self.find_matches(phrase, type_constraint, scope, world, vocab);
 
+        // "locker 12" names locker 12 if there is one, and otherwise the
+        // twelfth locker
+        if matches.is_empty() {
+            if let Some(phrase) = trailing_number_as_ordinal(phrase) {
+                return self.resolve(&phrase, type_constraint, scope, world, vocab);
+            }
+        }
+
+        // Handle ordinal (first, second, etc.), counting in rank order
+        if let Some(ordinal) = phrase.ordinal {
+            return match ordinal.checked_sub(1).and_then(|i| matches.get(i)) {
+                Some(&entity) => NounResolution::Unique(entity),
+                None => NounResolution::NotFound,
+            };
+        }
+
         match matches.len() {
             0 => NounResolution::NotFound,
             1 => NounResolution::Unique(matches[0]),
             _ => {
-                // Handle ordinal (first, second, etc.)
-                if let Some(ordinal) = phrase.ordinal {
-                    if ordinal > 0 && ordinal <= matches.len() {
-                        return NounResolution::Unique(matches[ordinal - 1]);
-                    }
-                }
                 // Handle "any" quantifier
                 if phrase.quantifier == Quantifier::Any {
                     return NounResolution::Unique(matches[0]);
@@ -167,8 +213,15 @@ impl NounResolver {
         let mut matches = self.find_matches(phrase, type_constraint, scope, world, vocab);
 
         // Handle "all except"
-        if let Quantifier::AllExcept(exceptions) = &phrase.quantifier {
-            matches.retain(|e| !exceptions.contains(e));
+        match &phrase.quantifier {
+            Quantifier::AllExcept(exceptions) => matches.retain(|e| !exceptions.contains(e)),
+            Quantifier::AllBut(exceptions) => {
+                for exception in exceptions {
+                    let excluded = self.find_matches(exception, None, scope, world, vocab);
+                    matches.retain(|e| !excluded.contains(e));
+                }
+            }
+            _ => {}
         }
 
         if matches.is_empty() {
@@ -201,13 +254,97 @@ impl NounResolver {
             }
         }
 
+        let mut matches = self.narrow(&matches, &phrase.adjectives, world);
+        self.rank(&mut matches, world);
         matches
     }
 
+    /// Puts matching entities in a stable order: by description, then by
+    /// id. Ordinals ("the second key") count in this order, and
+    /// disambiguation questions list the entities in it.
+    pub fn rank(&self, entities: &mut [EntityId], world: &World) {
+        entities.sort_by_cached_key(|&e| {
+            (
+                self.describe(e, world).to_lowercase(),
+                e.index,
+                e.generation,
+            )
+        });
+    }
+
+    /// Keeps the candidates that every word describes.
+    ///
+    /// A word describes an entity if it is one of the entity's adjectives
+    /// (`:entity/adjectives {:value ["red" "small"]}`), or a word of its name
+    /// or aliases, so "brass" picks out a "brass key" without adjectives of
+    /// its own. Case is ignored, and with no words every candidate is kept.
+    #[must_use]
+    pub fn narrow(
+        &self,
+        candidates: &[EntityId],
+        words: &[String],
+        world: &World,
+    ) -> Vec<EntityId> {
+        if words.is_empty() {
+            return candidates.to_vec();
+        }
+        candidates
+            .iter()
+            .copied()
+            .filter(|&entity| {
+                let described_by = self.descriptive_words(entity, world);
+                words
+                    .iter()
+                    .all(|word| described_by.contains(&word.to_lowercase()))
+            })
+            .collect()
+    }
+
     /// Checks if an entity matches a noun phrase.
+    ///
+    /// An empty noun, from a bare "all", matches every entity with a name
+    /// that isn't tagged with one of the [exclusions](Self::with_all_exclusions).
+    /// A plural noun also matches a whole word of a name or alias by its
+    /// singular, so "all keys" includes the "brass key" but not the
+    /// "monkey".
     fn entity_matches(&self, entity: EntityId, phrase: &NounPhrase, world: &World) -> bool {
         let noun_lower = phrase.noun.to_lowercase();
+        if noun_lower.is_empty() {
+            return matches!(
+                world.get_field(entity, self.name_keyword, self.value_keyword),
+                Ok(Some(Value::String(_)))
+            ) && !self.all_exclusions.iter().any(
+                |&tag| matches!(world.get(entity, tag), Ok(Some(v)) if v != Value::Bool(false)),
+            );
+        }
+        self.noun_matches(entity, &noun_lower, world)
+            || singular(&noun_lower).is_some_and(|noun| self.names_word(entity, &noun, world))
+    }
+
+    /// Checks if a lowercase word is a whole word of an entity's name or
+    /// aliases.
+    fn names_word(&self, entity: EntityId, word: &str, world: &World) -> bool {
+        let mut names = Vec::new();
+        if let Ok(Some(Value::String(name))) =
+            world.get_field(entity, self.name_keyword, self.value_keyword)
+        {
+            names.push(name.to_lowercase());
+        }
+        if let Ok(Some(Value::Vec(aliases))) =
+            world.get_field(entity, self.aliases_keyword, self.value_keyword)
+        {
+            names.extend(aliases.iter().filter_map(|alias| match alias {
+                Value::String(s) => Some(s.to_lowercase()),
+                _ => None,
+            }));
+        }
+        names
+            .iter()
+            .any(|name| name.split_whitespace().any(|w| w == word))
+    }
 
+    /// Checks if an entity's name or aliases match a lowercase noun.
+    fn noun_matches(&self, entity: EntityId, noun_lower: &str, world: &World) -> bool {
         // 1. Check exact name match
         // Use get_field since components store fields in a Map structure
         // e.g., :name {:value "treasure chest"} → get_field(entity, :name, :value)
@@ -215,7 +352,7 @@ impl NounResolver {
             world.get_field(entity, self.name_keyword, self.value_keyword)
         {
             if name.to_lowercase() == noun_lower {
-                return self.adjectives_match(entity, phrase, world);
+                return true;
             }
         }
 
@@ -226,7 +363,7 @@ impl NounResolver {
             for alias in aliases.iter() {
                 if let Value::String(alias_str) = alias {
                     if alias_str.to_lowercase() == noun_lower {
-                        return self.adjectives_match(entity, phrase, world);
+                        return true;
                     }
                 }
             }
@@ -237,43 +374,39 @@ impl NounResolver {
             world.get_field(entity, self.name_keyword, self.value_keyword)
         {
             let name_lower = name.to_lowercase();
-            if name_lower.contains(&noun_lower) || noun_lower.contains(&name_lower) {
-                return self.adjectives_match(entity, phrase, world);
+            if name_lower.contains(noun_lower) || noun_lower.contains(&name_lower) {
+                return true;
             }
         }
 
         false
     }
 
-    /// Checks if the entity's adjectives match the noun phrase's adjectives.
-    fn adjectives_match(&self, entity: EntityId, phrase: &NounPhrase, world: &World) -> bool {
-        // If no adjectives required, match succeeds
-        if phrase.adjectives.is_empty() {
-            return true;
-        }
-
-        // Get entity's adjectives using get_field
-        let entity_adjectives = if let Ok(Some(Value::Vec(adjs))) =
-            world.get_field(entity, self.adjectives_keyword, self.value_keyword)
-        {
-            adjs.iter()
-                .filter_map(|v| {
-                    if let Value::String(s) = v {
-                        Some(s.to_lowercase())
-                    } else {
-                        None
-                    }
+    /// Returns the lowercase words describing an entity: its adjectives and
+    /// the words of its name and aliases.
+    fn descriptive_words(&self, entity: EntityId, world: &World) -> Vec<String> {
+        let mut words = Vec::new();
+        let strings = |component| match world.get_field(entity, component, self.value_keyword) {
+            Ok(Some(Value::String(s))) => vec![s.to_lowercase()],
+            Ok(Some(Value::Vec(items))) => items
+                .iter()
+                .filter_map(|v| match v {
+                    Value::String(s) => Some(s.to_lowercase()),
+                    _ => None,
                 })
-                .collect::<Vec<_>>()
-        } else {
-            Vec::new()
+                .collect(),
+            _ => Vec::new(),
         };
-
-        // All requested adjectives must be present
-        phrase.adjectives.iter().all(|adj| {
-            let adj_lower = adj.to_lowercase();
-            entity_adjectives.contains(&adj_lower)
-        })
+        for component in [
+            self.adjectives_keyword,
+            self.name_keyword,
+            self.aliases_keyword,
+        ] {
+            for text in strings(component) {
+                words.extend(text.split_whitespace().map(str::to_string));
+            }
+        }
+        words
     }
 
     /// Checks if an entity matches a type constraint.
@@ -332,6 +465,25 @@ impl NounResolver {
     }
 }
 
+/// Returns the singular of a regular English plural, or `None` if the word
+/// doesn't look plural.
+fn singular(word: &str) -> Option<String> {
+    if let Some(stem) = word.strip_suffix("ies") {
+        return Some(format!("{stem}y"));
+    }
+    if let Some(stem) = word.strip_suffix("es") {
+        if ["s", "x", "z", "ch", "sh"]
+            .iter()
+            .any(|end| stem.ends_with(end))
+        {
+            return Some(stem.to_string());
+        }
+    }
+    word.strip_suffix('s')
+        .filter(|stem| !stem.is_empty() && !stem.ends_with('s'))
+        .map(str::to_string)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -347,6 +499,158 @@ mod tests {
         assert!(matches!(np.quantifier, Quantifier::Specific));
     }
 
+    #[test]
+    fn test_adjectives_narrow_matches() {
+        use longtable_foundation::{LtMap, LtVec, Type};
+        use longtable_storage::schema::{ComponentSchema, FieldSchema};
+
+        let mut world = World::new(42);
+        let name = world.interner_mut().intern_keyword("entity/name");
+        let value = world.interner_mut().intern_keyword("value");
+        let aliases = world.interner_mut().intern_keyword("entity/aliases");
+        let adjectives = world.interner_mut().intern_keyword("entity/adjectives");
+        for (component, ty) in [(name, Type::String), (adjectives, Type::vec(Type::String))] {
+            world = world
+                .register_component(
+                    ComponentSchema::new(component).with_field(FieldSchema::required(value, ty)),
+                )
+                .unwrap();
+        }
+
+        let mut keys = Vec::new();
+        for (key_name, adjs) in [
+            ("key", vec!["red"]),
+            ("key", vec!["Blue"]),
+            ("brass key", vec![]),
+        ] {
+            let (next, key) = world.spawn(&LtMap::new()).unwrap();
+            let field = |v: Value| Value::Map(LtMap::new().insert(Value::Keyword(value), v));
+            let adjs: LtVec<Value> = adjs.into_iter().map(Value::from).collect();
+            world = next
+                .set(key, name, field(Value::from(key_name)))
+                .unwrap()
+                .set(key, adjectives, field(Value::Vec(adjs)))
+                .unwrap();
+            keys.push(key);
+        }
+
+        let resolver = NounResolver::new(name, value, aliases, adjectives);
+        let vocab = VocabularyRegistry::new();
+        let resolve = |phrase: NounPhrase| resolver.resolve(&phrase, None, &keys, &world, &vocab);
+
+        assert!(
+            matches!(resolve(NounPhrase::new("key")), NounResolution::Ambiguous(m) if m.len() == 3)
+        );
+        assert!(matches!(
+            resolve(NounPhrase::new("key").with_adjective("red")),
+            NounResolution::Unique(e) if e == keys[0]
+        ));
+        assert!(matches!(
+            resolve(NounPhrase::new("KEY").with_adjective("blue")),
+            NounResolution::Unique(e) if e == keys[1]
+        ));
+        // A word of the name describes the entity too
+        assert!(matches!(
+            resolve(NounPhrase::new("key").with_adjective("brass")),
+            NounResolution::Unique(e) if e == keys[2]
+        ));
+        assert!(matches!(
+            resolve(NounPhrase::new("key").with_adjective("green")),
+            NounResolution::NotFound
+        ));
+        assert_eq!(resolver.narrow(&keys, &[], &world), keys);
+
+        // Ranked by description: blue key, brass key, red key
+        assert!(matches!(
+            resolve(NounPhrase::new("key").with_ordinal(2)),
+            NounResolution::Unique(e) if e == keys[2]
+        ));
+        assert!(matches!(
+            resolve(NounPhrase::new("key").with_adjective("red").with_ordinal(1)),
+            NounResolution::Unique(e) if e == keys[0]
+        ));
+        assert!(matches!(
+            resolve(NounPhrase::new("key").with_ordinal(4)),
+            NounResolution::NotFound
+        ));
+        // A trailing number is an ordinal unless an entity is named by it
+        assert!(matches!(
+            resolve(NounPhrase::new("2").with_adjective("key")),
+            NounResolution::Unique(e) if e == keys[2]
+        ));
+        let (next, locker) = world.spawn(&LtMap::new()).unwrap();
+        let world = next
+            .set(
+                locker,
+                name,
+                Value::Map(LtMap::new().insert(Value::Keyword(value), Value::from("key 12"))),
+            )
+            .unwrap();
+        let mut scope = keys.clone();
+        scope.push(locker);
+        assert!(matches!(
+            resolver.resolve(&NounPhrase::new("12").with_adjective("key"), None, &scope, &world, &vocab),
+            NounResolution::Unique(e) if e == locker
+        ));
+    }
+
+    #[test]
+    fn test_all_quantifiers() {
+        use longtable_foundation::{LtMap, Type};
+        use longtable_storage::schema::{ComponentSchema, FieldSchema};
+
+        let mut world = World::new(42);
+        let name = world.interner_mut().intern_keyword("entity/name");
+        let value = world.interner_mut().intern_keyword("value");
+        let room = world.interner_mut().intern_keyword("tag/room");
+        world = world
+            .register_component(
+                ComponentSchema::new(name).with_field(FieldSchema::required(value, Type::String)),
+            )
+            .unwrap()
+            .register_component(ComponentSchema::tag(room))
+            .unwrap();
+
+        let mut things = Vec::new();
+        for thing in ["brass key", "iron key", "monkey", "hall"] {
+            let (next, entity) = world.spawn(&LtMap::new()).unwrap();
+            let field = Value::Map(LtMap::new().insert(Value::Keyword(value), Value::from(thing)));
+            world = next.set(entity, name, field).unwrap();
+            things.push(entity);
+        }
+        let hall = things[3];
+        world = world.set(hall, room, Value::Bool(true)).unwrap();
+
+        let resolver = NounResolver::new(name, value, name, name).with_all_exclusions(vec![room]);
+        let vocab = VocabularyRegistry::new();
+        let resolve =
+            |phrase: NounPhrase| match resolver.resolve(&phrase, None, &things, &world, &vocab) {
+                NounResolution::Multiple(mut entities) => {
+                    entities.sort_by_key(|e| e.index);
+                    entities
+                }
+                other => panic!("expected several entities, got {other:?}"),
+            };
+        let all = |noun: &str| NounPhrase::new(noun).with_quantifier(Quantifier::All);
+
+        // A plural matches whole words by its singular, so not the monkey
+        assert_eq!(resolve(all("keys")), things[..2]);
+        // A bare "all" leaves out rooms, which can still be named
+        assert_eq!(resolve(all("")), things[..3]);
+        assert!(matches!(
+            resolver.resolve(&NounPhrase::new("hall"), None, &things, &world, &vocab),
+            NounResolution::Unique(e) if e == hall
+        ));
+        assert_eq!(
+            resolve(all("").with_quantifier(Quantifier::AllExcept(vec![things[0]]))),
+            things[1..3]
+        );
+        assert_eq!(
+            resolve(all("").with_quantifier(Quantifier::AllBut(vec![NounPhrase::new("iron key")]))),
+            [things[0], things[2]]
+        );
+    }
+
     #[test]
     fn test_quantifier_variants() {
         assert!(matches!(Quantifier::Specific, Quantifier::Specific));
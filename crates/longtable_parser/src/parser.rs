@@ -4,15 +4,16 @@
 
 use std::collections::HashMap;
 
-use longtable_foundation::EntityId;
+use longtable_foundation::{EntityId, Interner, KeywordId};
 use longtable_storage::World;
 
 use crate::command::CommandEntity;
 use crate::noun_phrase::{NounPhrase, NounResolution, NounResolver, Quantifier};
 use crate::pronouns::PronounState;
 use crate::scope::{CompiledScope, ScopeEvaluator};
-use crate::syntax::{CompiledSyntax, SyntaxMatch, SyntaxMatcher};
+use crate::syntax::{CompiledSyntax, CompiledSyntaxElement, SyntaxMatch, SyntaxMatcher};
 use crate::tokenizer::{InputToken, InputTokenizer};
+use crate::topic::TopicResolver;
 use crate::vocabulary::VocabularyRegistry;
 
 /// Result of parsing player input.
@@ -21,7 +22,7 @@ pub enum ParseResult {
     /// Successfully parsed into a command
     Success(CommandEntity),
     /// Ambiguous - disambiguation needed
-    Ambiguous(DisambiguationRequest),
+    Ambiguous(Box<DisambiguationRequest>),
     /// Parse error
     Error(ParseError),
     /// Multiple commands (for "all" quantifier)
@@ -72,6 +73,65 @@ pub enum ParseError {
     },
     /// Pronoun has no referent
     NoReferent(String),
+    /// "again" with no earlier command to repeat
+    NothingToRepeat,
+    /// "oops" with no earlier unknown word to correct
+    NothingToCorrect,
+    /// No NPC in the command knows the topic
+    UnknownTopic(String),
+    /// A scope's pattern failed to match, with the error's message
+    ScopeFailed(String),
+}
+
+/// A decision the parser made, kept so odd parses can be explained.
+#[derive(Clone, Debug)]
+pub enum ParseStep {
+    /// The input was split into these words.
+    Tokenized {
+        /// The raw input
+        input: String,
+        /// The words and quoted strings, in order
+        tokens: Vec<String>,
+    },
+    /// An unknown verb or direction was taken to mean a known one.
+    Corrected {
+        /// The word as typed
+        word: String,
+        /// The word it was taken to mean
+        correction: String,
+    },
+    /// Syntax patterns were tried against the tokens.
+    SyntaxCandidates {
+        /// How many syntaxes were tried
+        evaluated: usize,
+        /// Commands whose syntax matched, best first
+        matched: Vec<KeywordId>,
+    },
+    /// A noun phrase was resolved against the entities in scope.
+    NounResolved {
+        /// The syntax variable being filled
+        variable: String,
+        /// The noun as typed
+        noun: String,
+        /// What it resolved to
+        resolution: NounResolution,
+    },
+    /// A pronoun was resolved from earlier commands.
+    PronounResolved {
+        /// The syntax variable being filled
+        variable: String,
+        /// The pronoun as typed
+        pronoun: String,
+        /// Its referent, if there is one
+        referent: Option<EntityId>,
+    },
+    /// The player was asked which of several entities they meant.
+    DisambiguationAsked {
+        /// The question asked
+        question: String,
+        /// The entities offered
+        options: Vec<EntityId>,
+    },
 }
 
 /// The main natural language parser.
@@ -82,7 +142,11 @@ pub struct NaturalLanguageParser {
     scopes: Vec<CompiledScope>,
     scope_evaluator: Option<ScopeEvaluator>,
     noun_resolver: Option<NounResolver>,
+    topic_resolver: Option<TopicResolver>,
     pronoun_state: PronounState,
+    tracing: bool,
+    steps: Vec<ParseStep>,
+    corrections: Vec<String>,
 }
 
 impl NaturalLanguageParser {
@@ -95,10 +159,22 @@ impl NaturalLanguageParser {
             scopes: Vec::new(),
             scope_evaluator: None,
             noun_resolver: None,
+            topic_resolver: None,
             pronoun_state: PronounState::new(),
+            tracing: false,
+            steps: Vec::new(),
+            corrections: Vec::new(),
         }
     }
 
+    /// Records the parser's decisions for [`Self::take_steps`] while
+    /// `tracing` is set. Off by default, so untraced parses keep nothing.
+    #[must_use]
+    pub fn with_tracing(mut self, tracing: bool) -> Self {
+        self.tracing = tracing;
+        self
+    }
+
     /// Configures the scope evaluator with the necessary keywords.
     pub fn with_scope_evaluator(mut self, evaluator: ScopeEvaluator) -> Self {
         self.scope_evaluator = Some(evaluator);
@@ -111,6 +187,12 @@ impl NaturalLanguageParser {
         self
     }
 
+    /// Configures the topic resolver with the necessary keywords.
+    pub fn with_topic_resolver(mut self, resolver: TopicResolver) -> Self {
+        self.topic_resolver = Some(resolver);
+        self
+    }
+
     /// Adds a compiled syntax pattern.
     pub fn add_syntax(&mut self, syntax: CompiledSyntax) {
         self.syntaxes.push(syntax);
@@ -122,39 +204,237 @@ impl NaturalLanguageParser {
     }
 
     /// Parses player input into a command.
+    ///
+    /// Input holding several commands ("take sword then go north") parses
+    /// to `Multiple`, with the commands in order. Parsing stops at the first
+    /// command that fails or is ambiguous, and returns that command's result.
+    ///
+    /// "again" (or "g") parses the last input that parsed successfully once
+    /// more. "oops lamp" replaces the word the last failed input couldn't
+    /// find with "lamp", and parses the result.
     pub fn parse(&mut self, input: &str, actor: EntityId, world: &World) -> ParseResult {
+        let words: Vec<String> = input.split_whitespace().map(str::to_lowercase).collect();
+        let input = match words.as_slice() {
+            [again] if again == "again" || again == "g" => match self.pronoun_state.last_input() {
+                Some(last) => last.to_string(),
+                None => return ParseResult::Error(ParseError::NothingToRepeat),
+            },
+            [oops, correction] if oops == "oops" => match self.pronoun_state.unknown_word() {
+                Some((failed, word)) => Self::correct(failed, word, correction),
+                None => return ParseResult::Error(ParseError::NothingToCorrect),
+            },
+            _ => input.to_string(),
+        };
+
+        let result = self.parse_input(&input, actor, world);
+        match &result {
+            ParseResult::Success(_) | ParseResult::Multiple(_) => {
+                self.pronoun_state.set_last_input(input);
+            }
+            ParseResult::Error(ParseError::NotFound(noun)) if noun != "all" => {
+                let word = noun.split_whitespace().last().unwrap_or(noun).to_string();
+                self.pronoun_state.set_unknown_word(input, word);
+            }
+            ParseResult::Error(ParseError::NoMatch) => {
+                if let Some(word) = self.unknown_verb(&input, world) {
+                    self.pronoun_state.set_unknown_word(input, word);
+                }
+            }
+            _ => {}
+        }
+        result
+    }
+
+    /// Returns `input` with the last occurrence of `word` replaced by
+    /// `correction`.
+    fn correct(input: &str, word: &str, correction: &str) -> String {
+        let mut words: Vec<&str> = input.split_whitespace().collect();
+        if let Some(slot) = words
+            .iter_mut()
+            .rev()
+            .find(|w| w.eq_ignore_ascii_case(word))
+        {
+            *slot = correction;
+        }
+        words.join(" ")
+    }
+
+    /// Returns the first word of `input` if it is neither a verb nor a
+    /// direction, so no syntax could have matched it.
+    fn unknown_verb(&self, input: &str, world: &World) -> Option<String> {
+        let first = input.split_whitespace().next()?.to_lowercase();
+        let known = self
+            .vocabulary
+            .lookup_word(&first, world.interner())
+            .is_some_and(|kw| {
+                self.vocabulary.lookup_verb(kw).is_some()
+                    || self.vocabulary.lookup_direction(kw).is_some()
+            });
+        (!known).then_some(first)
+    }
+
+    /// Splits input into the text of each command it holds, as typed.
+    ///
+    /// Like [`InputTokenizer::split_commands`], except that a command whose
+    /// verb takes free text ends only at "then": in "say Hello, my friend
+    /// to guard" the comma is part of what is said.
+    #[must_use]
+    pub fn split_commands(&self, input: &str, interner: &Interner) -> Vec<String> {
+        let mut commands = Vec::new();
+        let mut command: Option<(usize, usize)> = None;
+        let mut takes_text = false;
+        for (token, span) in InputTokenizer::tokenize_spanned(input) {
+            match token {
+                InputToken::Then
+                    if takes_text && !input[span.clone()].eq_ignore_ascii_case("then") => {}
+                InputToken::Then | InputToken::End => {
+                    if let Some((start, end)) = command.take() {
+                        commands.push(input[start..end].to_string());
+                    }
+                    takes_text = false;
+                }
+                InputToken::Word(_) | InputToken::QuotedString(_) => {
+                    if command.is_none() {
+                        takes_text =
+                            matches!(&token, InputToken::Word(w) if self.takes_text(w, interner));
+                    }
+                    let start = command.map_or(span.start, |(start, _)| start);
+                    command = Some((start, span.end));
+                }
+            }
+        }
+        commands
+    }
+
+    /// Returns true if `word` is a verb with a syntax that captures free
+    /// text.
+    fn takes_text(&self, word: &str, interner: &Interner) -> bool {
+        let Some(verb) = self
+            .vocabulary
+            .lookup_word(word, interner)
+            .and_then(|kw| self.vocabulary.lookup_verb(kw))
+        else {
+            return false;
+        };
+        self.syntaxes.iter().any(|syntax| {
+            syntax
+                .verb()
+                .is_some_and(|v| verb.name == v || verb.synonyms.contains(&v))
+                && syntax
+                    .elements
+                    .iter()
+                    .any(|e| matches!(e, CompiledSyntaxElement::Text { .. }))
+        })
+    }
+
+    /// Parses input that isn't a meta-command.
+    fn parse_input(&mut self, input: &str, actor: EntityId, world: &World) -> ParseResult {
+        let commands = self.split_commands(input, world.interner());
+        if commands.len() < 2 {
+            return self.parse_command(input, actor, world);
+        }
+
+        // Every command is parsed against the same world, so a command can't
+        // see what the ones before it do. Callers that run each command
+        // before parsing the next should split the input themselves.
+        let mut parsed = Vec::new();
+        for command in &commands {
+            match self.parse_command(command, actor, world) {
+                ParseResult::Success(cmd) => parsed.push(cmd),
+                ParseResult::Multiple(cmds) => parsed.extend(cmds),
+                failed => return failed,
+            }
+        }
+        ParseResult::Multiple(parsed)
+    }
+
+    /// Parses a single command.
+    fn parse_command(&mut self, input: &str, actor: EntityId, world: &World) -> ParseResult {
         // 1. Tokenize
-        let tokens = InputTokenizer::tokenize(input);
+        let (mut tokens, spans): (Vec<_>, Vec<_>) =
+            InputTokenizer::tokenize_spanned(input).into_iter().unzip();
+        if self.tracing {
+            self.steps.push(ParseStep::Tokenized {
+                input: input.to_string(),
+                tokens: tokens
+                    .iter()
+                    .filter_map(|t| match t {
+                        InputToken::Word(w) => Some(w.clone()),
+                        InputToken::QuotedString(q) => Some(format!("{q:?}")),
+                        InputToken::Then | InputToken::End => None,
+                    })
+                    .collect(),
+            });
+        }
 
         if tokens.iter().all(|t| matches!(t, InputToken::End)) {
             return ParseResult::Error(ParseError::EmptyInput);
         }
 
+        // Only the verb or direction is corrected, as nouns are matched
+        // against the entities in scope
+        if let Some(InputToken::Word(word)) = tokens.first_mut()
+            && let Some(correction) = self.vocabulary.correct_word(word, world.interner())
+        {
+            let word = std::mem::replace(word, correction.clone());
+            self.corrections.push(correction.clone());
+            if self.tracing {
+                self.steps.push(ParseStep::Corrected { word, correction });
+            }
+        }
+
         // 2. Try to match syntax patterns
         let matches =
             SyntaxMatcher::match_all(&tokens, &self.syntaxes, &self.vocabulary, world.interner());
+        if self.tracing {
+            self.steps.push(ParseStep::SyntaxCandidates {
+                evaluated: self.syntaxes.len(),
+                matched: matches.iter().map(|m| m.command).collect(),
+            });
+        }
 
         if matches.is_empty() {
             return ParseResult::Error(ParseError::NoMatch);
         }
 
         // 3. Use the best match (highest specificity/priority)
-        let syntax_match = matches.into_iter().next().unwrap();
+        let mut syntax_match = matches.into_iter().next().unwrap();
+
+        // Free text is kept as typed, with its case and punctuation; text
+        // that is all one quoted string loses its quotes
+        for (var, range) in &syntax_match.text_spans {
+            let text = match &tokens[range.clone()] {
+                [InputToken::QuotedString(quoted)] => quoted.clone(),
+                _ => input[spans[range.start].start..spans[range.end - 1].end].to_string(),
+            };
+            syntax_match.text_bindings.insert(var.clone(), text);
+        }
 
         // 4. Get visible entities for noun resolution
-        let scope = self.get_scope(actor, world);
+        let scope = match self.entities_in_scope(actor, world) {
+            Ok(scope) => scope,
+            Err(e) => return ParseResult::Error(ParseError::ScopeFailed(e.to_string())),
+        };
 
         // 5. Resolve all noun bindings
         self.resolve_nouns(input, syntax_match, actor, &scope, world)
     }
 
-    /// Gets entities in scope for the actor.
-    fn get_scope(&self, actor: EntityId, world: &World) -> Vec<EntityId> {
+    /// Gets entities in scope for the actor: those its nouns can refer to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern scope fails to match.
+    pub fn entities_in_scope(
+        &self,
+        actor: EntityId,
+        world: &World,
+    ) -> longtable_foundation::Result<Vec<EntityId>> {
         if let Some(evaluator) = &self.scope_evaluator {
             evaluator.visible_entities(actor, world, &self.scopes)
         } else {
             // Fallback: return all entities
-            world.entities().collect()
+            Ok(world.entities().collect())
         }
     }
 
@@ -176,12 +456,21 @@ impl NaturalLanguageParser {
         };
 
         let mut resolved_bindings: HashMap<String, EntityId> = HashMap::new();
-        let mut multiple_commands = Vec::new();
+        // Variables bound by "all", with their entities
+        let mut expansions: Vec<(String, Vec<EntityId>)> = Vec::new();
 
         for (var_name, noun_phrase) in &syntax_match.noun_bindings {
             // Check for pronouns
             if self.is_pronoun(&noun_phrase.noun) {
-                if let Some(entity) = self.resolve_pronoun(&noun_phrase.noun) {
+                let referent = self.resolve_pronoun(&noun_phrase.noun);
+                if self.tracing {
+                    self.steps.push(ParseStep::PronounResolved {
+                        variable: var_name.clone(),
+                        pronoun: noun_phrase.noun.clone(),
+                        referent,
+                    });
+                }
+                if let Some(entity) = referent {
                     resolved_bindings.insert(var_name.clone(), entity);
                     continue;
                 } else {
@@ -194,6 +483,13 @@ impl NaturalLanguageParser {
 
             let resolution =
                 resolver.resolve(noun_phrase, type_constraint, scope, world, &self.vocabulary);
+            if self.tracing {
+                self.steps.push(ParseStep::NounResolved {
+                    variable: var_name.clone(),
+                    noun: noun_phrase.noun.clone(),
+                    resolution: resolution.clone(),
+                });
+            }
 
             match resolution {
                 NounResolution::Unique(entity) => {
@@ -207,9 +503,16 @@ impl NaturalLanguageParser {
                         .iter()
                         .map(|&e| (resolver.describe(e, world), e))
                         .collect();
+                    let question = format!("Which {} do you mean?", noun_phrase.noun);
+                    if self.tracing {
+                        self.steps.push(ParseStep::DisambiguationAsked {
+                            question: question.clone(),
+                            options: entities,
+                        });
+                    }
 
-                    return ParseResult::Ambiguous(DisambiguationRequest {
-                        question: format!("Which {} do you mean?", noun_phrase.noun),
+                    return ParseResult::Ambiguous(Box::new(DisambiguationRequest {
+                        question,
                         options,
                         pending_parse: PendingParse {
                             input: input.to_string(),
@@ -217,7 +520,10 @@ impl NaturalLanguageParser {
                             var_name: var_name.clone(),
                             actor,
                         },
-                    });
+                    }));
+                }
+                NounResolution::NotFound if noun_phrase.noun.is_empty() => {
+                    return ParseResult::Error(ParseError::NotFound("all".to_string()));
                 }
                 NounResolution::NotFound => {
                     return ParseResult::Error(ParseError::NotFound(noun_phrase.noun.clone()));
@@ -229,46 +535,116 @@ impl NaturalLanguageParser {
                     });
                 }
                 NounResolution::Multiple(entities) => {
-                    // For "all" quantifier - create multiple commands
+                    // For "all" quantifier - one command per entity, once
+                    // every other variable is bound
                     if matches!(
                         noun_phrase.quantifier,
-                        Quantifier::All | Quantifier::AllExcept(_)
+                        Quantifier::All | Quantifier::AllExcept(_) | Quantifier::AllBut(_)
                     ) {
-                        // Update "them" pronoun first (before moving entities)
-                        self.pronoun_state.set_them(entities.clone());
-
-                        for entity in entities {
-                            let mut bindings = resolved_bindings.clone();
-                            bindings.insert(var_name.clone(), entity);
-                            multiple_commands.push(CommandEntity {
-                                verb: syntax_match.command,
-                                action: syntax_match.action,
-                                actor,
-                                noun_bindings: bindings,
-                                direction: syntax_match.direction.clone(),
-                                adverb: None,
-                            });
-                        }
+                        expansions.push((var_name.clone(), entities));
                     }
                 }
             }
         }
 
-        // Return result
-        if !multiple_commands.is_empty() {
-            return ParseResult::Multiple(multiple_commands);
+        if !expansions.is_empty() {
+            return self.expand_all(&syntax_match, actor, resolved_bindings, expansions, world);
         }
 
+        let topic = match self.resolve_topic(&syntax_match, &resolved_bindings, world) {
+            Ok(topic) => topic,
+            Err(e) => return ParseResult::Error(e),
+        };
         ParseResult::Success(CommandEntity {
             verb: syntax_match.command,
             action: syntax_match.action,
             actor,
             noun_bindings: resolved_bindings,
+            text_bindings: syntax_match.text_bindings,
+            topic,
             direction: syntax_match.direction,
             adverb: None,
         })
     }
 
+    /// Creates one command for each entity an "all" phrase matched.
+    ///
+    /// "All" never includes the actor, or an entity bound to another of the
+    /// command's variables, so "put all in bag" leaves the bag out.
+    fn expand_all(
+        &mut self,
+        syntax_match: &SyntaxMatch,
+        actor: EntityId,
+        bindings: HashMap<String, EntityId>,
+        expansions: Vec<(String, Vec<EntityId>)>,
+        world: &World,
+    ) -> ParseResult {
+        let bound: Vec<EntityId> = bindings.values().copied().collect();
+        let mut binding_sets = vec![bindings];
+        for (var_name, mut entities) in expansions {
+            entities.retain(|e| *e != actor && !bound.contains(e));
+            if entities.is_empty() {
+                return ParseResult::Error(ParseError::NotFound("all".to_string()));
+            }
+            // Update "them" pronoun
+            self.pronoun_state.set_them(entities.clone());
+
+            binding_sets = binding_sets
+                .iter()
+                .flat_map(|bindings| {
+                    entities.iter().map(|&entity| {
+                        let mut bindings = bindings.clone();
+                        bindings.insert(var_name.clone(), entity);
+                        bindings
+                    })
+                })
+                .collect();
+        }
+
+        let commands = binding_sets
+            .into_iter()
+            .map(|noun_bindings| {
+                Ok(CommandEntity {
+                    verb: syntax_match.command,
+                    action: syntax_match.action,
+                    actor,
+                    topic: self.resolve_topic(syntax_match, &noun_bindings, world)?,
+                    noun_bindings,
+                    text_bindings: syntax_match.text_bindings.clone(),
+                    direction: syntax_match.direction.clone(),
+                    adverb: None,
+                })
+            })
+            .collect::<Result<Vec<_>, ParseError>>();
+        match commands {
+            Ok(commands) => ParseResult::Multiple(commands),
+            Err(e) => ParseResult::Error(e),
+        }
+    }
+
+    /// Finds the topic a command's topic text names, asking each entity
+    /// bound to the command's other variables in turn.
+    fn resolve_topic(
+        &self,
+        syntax_match: &SyntaxMatch,
+        bindings: &HashMap<String, EntityId>,
+        world: &World,
+    ) -> Result<Option<(String, KeywordId)>, ParseError> {
+        let Some((var_name, text)) = &syntax_match.topic else {
+            return Ok(None);
+        };
+        let mut npcs: Vec<(&String, &EntityId)> = bindings.iter().collect();
+        npcs.sort_by_key(|(var_name, _)| *var_name);
+        self.topic_resolver
+            .as_ref()
+            .and_then(|resolver| {
+                npcs.iter()
+                    .find_map(|(_, npc)| resolver.resolve(**npc, text, world, world.interner()))
+            })
+            .map(|topic| Some((var_name.clone(), topic)))
+            .ok_or_else(|| ParseError::UnknownTopic(text.clone()))
+    }
+
     /// Creates a command without noun resolution (fallback).
     fn create_command_without_resolution(
         &self,
@@ -280,6 +656,8 @@ impl NaturalLanguageParser {
             action: syntax_match.action,
             actor,
             noun_bindings: HashMap::new(),
+            text_bindings: syntax_match.text_bindings,
+            topic: None,
             direction: syntax_match.direction,
             adverb: None,
         })
@@ -304,6 +682,10 @@ impl NaturalLanguageParser {
     }
 
     /// Continues parsing after disambiguation.
+    ///
+    /// The choice is a number from the list of candidates, or words that
+    /// describe one of them, such as "red" or "the red one". Failing that,
+    /// it is resolved as a noun phrase of its own.
     pub fn disambiguate(
         &mut self,
         choice: &str,
@@ -315,54 +697,62 @@ impl NaturalLanguageParser {
             None => return ParseResult::Error(ParseError::NoMatch),
         };
 
+        // The candidates the player was asked to choose between
+        let scope = match self.entities_in_scope(pending.actor, world) {
+            Ok(scope) => scope,
+            Err(e) => return ParseResult::Error(ParseError::ScopeFailed(e.to_string())),
+        };
+        let candidates = match pending.syntax_match.noun_bindings.get(&pending.var_name) {
+            Some(np) => match resolver.resolve(np, None, &scope, world, &self.vocabulary) {
+                NounResolution::Ambiguous(entities) => entities,
+                _ => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+
         // Try to parse the choice as a number (1, 2, etc.)
-        if let Ok(idx) = choice.parse::<usize>() {
-            // Find by index in the original options
-            let scope = self.get_scope(pending.actor, world);
-            let noun_phrase = pending.syntax_match.noun_bindings.get(&pending.var_name);
-
-            if let Some(np) = noun_phrase {
-                let resolution = resolver.resolve(np, None, &scope, world, &self.vocabulary);
-                if let NounResolution::Ambiguous(entities) = resolution {
-                    if idx > 0 && idx <= entities.len() {
-                        let chosen = entities[idx - 1];
-
-                        // Create the command with the chosen entity
-                        let mut bindings: HashMap<String, EntityId> = HashMap::new();
-                        bindings.insert(pending.var_name, chosen);
-
-                        self.pronoun_state.set_it(chosen);
-
-                        return ParseResult::Success(CommandEntity {
-                            verb: pending.syntax_match.command,
-                            action: pending.syntax_match.action,
-                            actor: pending.actor,
-                            noun_bindings: bindings,
-                            direction: pending.syntax_match.direction.clone(),
-                            adverb: None,
-                        });
-                    }
-                }
+        let chosen = if let Ok(idx) = choice.parse::<usize>() {
+            idx.checked_sub(1).and_then(|i| candidates.get(i).copied())
+        } else {
+            // Or as words narrowing the candidates
+            let words: Vec<String> = choice
+                .split_whitespace()
+                .map(str::to_lowercase)
+                .filter(|w| !matches!(w.as_str(), "the" | "a" | "an" | "one"))
+                .collect();
+            match resolver.narrow(&candidates, &words, world)[..] {
+                [entity] if !words.is_empty() => Some(entity),
+                _ => None,
             }
-        }
+        };
 
-        // Try to match the choice as a noun phrase
-        let new_np = NounPhrase::new(choice);
-        let scope = self.get_scope(pending.actor, world);
-        let resolution = resolver.resolve(&new_np, None, &scope, world, &self.vocabulary);
+        let chosen = chosen.map_or_else(
+            || {
+                // Try to match the choice as a noun phrase
+                let new_np = NounPhrase::new(choice);
+                resolver.resolve(&new_np, None, &scope, world, &self.vocabulary)
+            },
+            NounResolution::Unique,
+        );
 
-        match resolution {
+        match chosen {
             NounResolution::Unique(entity) => {
                 let mut bindings: HashMap<String, EntityId> = HashMap::new();
                 bindings.insert(pending.var_name, entity);
 
                 self.pronoun_state.set_it(entity);
 
+                let topic = match self.resolve_topic(&pending.syntax_match, &bindings, world) {
+                    Ok(topic) => topic,
+                    Err(e) => return ParseResult::Error(e),
+                };
                 ParseResult::Success(CommandEntity {
                     verb: pending.syntax_match.command,
                     action: pending.syntax_match.action,
                     actor: pending.actor,
                     noun_bindings: bindings,
+                    text_bindings: pending.syntax_match.text_bindings.clone(),
+                    topic,
                     direction: pending.syntax_match.direction.clone(),
                     adverb: None,
                 })
@@ -374,6 +764,18 @@ impl NaturalLanguageParser {
         }
     }
 
+    /// Returns the decisions made since the last call, oldest first, if
+    /// tracing.
+    pub fn take_steps(&mut self) -> Vec<ParseStep> {
+        std::mem::take(&mut self.steps)
+    }
+
+    /// Returns the verbs or directions misspellings were corrected to since
+    /// the last call, whether or not tracing.
+    pub fn take_corrections(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.corrections)
+    }
+
     /// Gets a reference to the vocabulary registry.
     #[must_use]
     pub fn vocabulary(&self) -> &VocabularyRegistry {
@@ -385,6 +787,12 @@ impl NaturalLanguageParser {
         &mut self.vocabulary
     }
 
+    /// Gets the noun resolver, if one is configured.
+    #[must_use]
+    pub fn noun_resolver(&self) -> Option<&NounResolver> {
+        self.noun_resolver.as_ref()
+    }
+
     /// Gets a reference to the pronoun state.
     #[must_use]
     pub fn pronoun_state(&self) -> &PronounState {
@@ -419,4 +827,135 @@ mod tests {
         let result = parser.parse("", actor, &world);
         assert!(matches!(result, ParseResult::Error(ParseError::EmptyInput)));
     }
+
+    #[test]
+    fn test_parse_records_steps() {
+        let vocab = VocabularyRegistry::new();
+        let world = World::new(42);
+        let actor = EntityId::new(1, 0);
+
+        // Nothing is kept unless tracing
+        let mut parser = NaturalLanguageParser::new(vocab.clone());
+        parser.parse("Take the LAMP!", actor, &world);
+        assert!(parser.take_steps().is_empty());
+
+        let mut parser = NaturalLanguageParser::new(vocab).with_tracing(true);
+        let result = parser.parse("Take the LAMP!", actor, &world);
+        assert!(matches!(result, ParseResult::Error(ParseError::NoMatch)));
+
+        let steps = parser.take_steps();
+        assert_eq!(steps.len(), 2);
+        let ParseStep::Tokenized { tokens, .. } = &steps[0] else {
+            panic!("expected tokens first, got {steps:?}");
+        };
+        assert_eq!(tokens, &["take", "the", "lamp"]);
+        assert!(matches!(
+            &steps[1],
+            ParseStep::SyntaxCandidates { evaluated: 0, matched } if matched.is_empty()
+        ));
+        assert!(parser.take_steps().is_empty());
+    }
+
+    #[test]
+    fn test_again_and_oops() {
+        let vocab = VocabularyRegistry::new();
+        let mut parser = NaturalLanguageParser::new(vocab).with_tracing(true);
+        let world = World::new(42);
+        let actor = EntityId::new(1, 0);
+
+        let result = parser.parse("again", actor, &world);
+        assert!(matches!(
+            result,
+            ParseResult::Error(ParseError::NothingToRepeat)
+        ));
+        let result = parser.parse("oops lamp", actor, &world);
+        assert!(matches!(
+            result,
+            ParseResult::Error(ParseError::NothingToCorrect)
+        ));
+
+        // With no verbs defined, the first word is the unknown one
+        parser.parse("tkae the lamp", actor, &world);
+        assert_eq!(
+            parser.pronoun_state().unknown_word(),
+            Some(("tkae the lamp", "tkae"))
+        );
+        parser.take_steps();
+        parser.parse("oops take", actor, &world);
+        let steps = parser.take_steps();
+        let ParseStep::Tokenized { input, .. } = &steps[0] else {
+            panic!("expected tokens first, got {steps:?}");
+        };
+        assert_eq!(input, "take the lamp");
+    }
+
+    #[test]
+    fn test_disambiguate_by_adjective() {
+        use longtable_foundation::{LtMap, LtVec, Type, Value};
+        use longtable_storage::schema::{ComponentSchema, FieldSchema};
+
+        let mut world = World::new(42);
+        let name = world.interner_mut().intern_keyword("entity/name");
+        let value = world.interner_mut().intern_keyword("value");
+        let adjectives = world.interner_mut().intern_keyword("entity/adjectives");
+        let take = world.interner_mut().intern_keyword("verb/take");
+        for (component, ty) in [(name, Type::String), (adjectives, Type::vec(Type::String))] {
+            world = world
+                .register_component(
+                    ComponentSchema::new(component).with_field(FieldSchema::required(value, ty)),
+                )
+                .unwrap();
+        }
+        let (next, actor) = world.spawn(&LtMap::new()).unwrap();
+        world = next;
+        let mut lamps = Vec::new();
+        for adj in ["brass", "oil"] {
+            let (next, lamp) = world.spawn(&LtMap::new()).unwrap();
+            let field = |v: Value| Value::Map(LtMap::new().insert(Value::Keyword(value), v));
+            let adjs: LtVec<Value> = std::iter::once(Value::from(adj)).collect();
+            world = next
+                .set(lamp, name, field(Value::from("lamp")))
+                .unwrap()
+                .set(lamp, adjectives, field(Value::Vec(adjs)))
+                .unwrap();
+            lamps.push(lamp);
+        }
+
+        let mut parser = NaturalLanguageParser::new(VocabularyRegistry::new())
+            .with_noun_resolver(NounResolver::new(name, value, adjectives, adjectives));
+        let pending = PendingParse {
+            input: "take lamp".to_string(),
+            syntax_match: SyntaxMatch {
+                command: take,
+                action: take,
+                noun_bindings: HashMap::from([("thing".to_string(), NounPhrase::new("lamp"))]),
+                type_constraints: HashMap::new(),
+                text_bindings: HashMap::new(),
+                text_spans: HashMap::new(),
+                topic: None,
+                direction: None,
+                prepositions: Vec::new(),
+                specificity: 0,
+                priority: 0,
+            },
+            var_name: "thing".to_string(),
+            actor,
+        };
+
+        for (choice, lamp) in [
+            ("oil", lamps[1]),
+            ("the brass one", lamps[0]),
+            ("2", lamps[1]),
+        ] {
+            let result = parser.disambiguate(choice, pending.clone(), &world);
+            let ParseResult::Success(cmd) = result else {
+                panic!("{choice}: expected success, got {result:?}");
+            };
+            assert_eq!(cmd.noun_bindings["thing"], lamp, "{choice}");
+        }
+        assert!(matches!(
+            parser.disambiguate("copper", pending, &world),
+            ParseResult::Error(ParseError::NotFound(_))
+        ));
+    }
 }
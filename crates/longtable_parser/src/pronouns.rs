@@ -1,6 +1,7 @@
 //! Pronoun tracking state.
 //!
-//! Tracks referents for pronouns like "it", "him", "her", "them".
+//! Tracks referents for pronouns like "it", "him", "her", "them", and the
+//! earlier input that "again" repeats and "oops" corrects.
 
 use longtable_foundation::{EntityId, KeywordId};
 
@@ -17,6 +18,10 @@ pub struct PronounState {
     them: Vec<EntityId>,
     /// Known pronoun keywords for resolution
     pronoun_keywords: Option<PronounKeywords>,
+    /// The last input that parsed to a command, for "again"
+    last_input: Option<String>,
+    /// The last input that failed on a word, and that word, for "oops"
+    unknown_word: Option<(String, String)>,
 }
 
 /// Known pronoun keywords for mapping to slots.
@@ -121,6 +126,30 @@ impl PronounState {
         &self.them
     }
 
+    /// Sets the input "again" repeats.
+    pub fn set_last_input(&mut self, input: impl Into<String>) {
+        self.last_input = Some(input.into());
+    }
+
+    /// Gets the input "again" repeats.
+    #[must_use]
+    pub fn last_input(&self) -> Option<&str> {
+        self.last_input.as_deref()
+    }
+
+    /// Sets the input that failed on `word`, for "oops" to correct.
+    pub fn set_unknown_word(&mut self, input: impl Into<String>, word: impl Into<String>) {
+        self.unknown_word = Some((input.into(), word.into()));
+    }
+
+    /// Gets the input that failed on a word, and the word.
+    #[must_use]
+    pub fn unknown_word(&self) -> Option<(&str, &str)> {
+        self.unknown_word
+            .as_ref()
+            .map(|(input, word)| (input.as_str(), word.as_str()))
+    }
+
     /// Clears all pronoun referents.
     pub fn clear(&mut self) {
         self.it = None;
@@ -128,6 +157,14 @@ impl PronounState {
         self.her = None;
         self.them.clear();
     }
+
+    /// Clears the referents and the input "again" and "oops" work from,
+    /// keeping the pronoun keywords.
+    pub fn reset(&mut self) {
+        self.clear();
+        self.last_input = None;
+        self.unknown_word = None;
+    }
 }
 
 #[cfg(test)]
@@ -143,6 +180,18 @@ mod tests {
         assert!(state.get_them().is_empty());
     }
 
+    #[test]
+    fn test_reset_forgets_earlier_input() {
+        let mut state = PronounState::new();
+        state.set_it(EntityId::new(1, 0));
+        state.set_last_input("take lamp");
+        state.set_unknown_word("take the lamb", "lamb");
+        state.reset();
+        assert!(state.get_it().is_none());
+        assert!(state.last_input().is_none());
+        assert!(state.unknown_word().is_none());
+    }
+
     #[test]
     fn test_set_and_get_it() {
         let mut state = PronounState::new();
@@ -2,12 +2,36 @@
 //!
 //! Determines which entities are visible for noun resolution based on
 //! the actor's location and game state.
+//!
+//! Besides the built-in kinds, a game can declare scopes of its own as
+//! patterns, for darkness, remote viewing, and the like:
+//!
+//! ```text
+//! (scope: magic-sight
+//!   :extends [here]
+//!   :where [[?actor :spell/scrying ?room]
+//!           [?obj :in-room ?room]])
+//! ```
+//!
+//! The pattern is matched with `?actor` bound to the actor, and every entity
+//! it binds to `?obj` is in scope.
+//!
+//! Nouns resolve against one scope: the most recently declared scope that
+//! no other scope extends. A scope can't extend itself, directly or through
+//! other scopes.
 
 use std::collections::HashSet;
 
-use longtable_foundation::{EntityId, KeywordId};
+use longtable_engine::{Bindings, CompiledPattern, PatternMatcher};
+use longtable_foundation::{EntityId, KeywordId, Result, Value};
 use longtable_storage::World;
 
+/// The pattern variable bound to the actor in a pattern scope.
+pub const ACTOR_VAR: &str = "actor";
+
+/// The pattern variable holding the entities a pattern scope brings in.
+pub const OBJECT_VAR: &str = "obj";
+
 /// A compiled scope definition.
 #[derive(Clone, Debug)]
 pub struct CompiledScope {
@@ -19,6 +43,59 @@ pub struct CompiledScope {
     pub kind: ScopeKind,
 }
 
+impl CompiledScope {
+    /// Returns the scopes this one directly includes.
+    fn includes(&self) -> impl Iterator<Item = KeywordId> + '_ {
+        let combined: &[KeywordId] = match &self.kind {
+            ScopeKind::Union(names) | ScopeKind::Pattern { extends: names, .. } => names,
+            _ => &[],
+        };
+        self.parent.into_iter().chain(combined.iter().copied())
+    }
+}
+
+/// Finds a chain of scopes through which `scope` would include itself, if
+/// it replaced any scope of the same name in `scopes`.
+///
+/// The chain starts and ends with `scope`'s name.
+#[must_use]
+pub fn inclusion_cycle(scope: &CompiledScope, scopes: &[CompiledScope]) -> Option<Vec<KeywordId>> {
+    let find = |name: KeywordId| {
+        if name == scope.name {
+            Some(scope)
+        } else {
+            scopes.iter().find(|s| s.name == name)
+        }
+    };
+
+    // Depth-first, keeping the current path; meeting the start again closes
+    // a cycle
+    let mut done = HashSet::new();
+    let mut path = vec![scope.name];
+    let mut pending: Vec<Vec<KeywordId>> = vec![scope.includes().collect()];
+    while let Some(next) = pending.last_mut() {
+        let Some(name) = next.pop() else {
+            if let Some(finished) = path.pop() {
+                done.insert(finished);
+            }
+            pending.pop();
+            continue;
+        };
+        if name == scope.name {
+            path.push(name);
+            return Some(path);
+        }
+        if done.contains(&name) || path.contains(&name) {
+            continue;
+        }
+        if let Some(included) = find(name) {
+            path.push(name);
+            pending.push(included.includes().collect());
+        }
+    }
+    None
+}
+
 /// The kind of scope, determining how entities are gathered.
 #[derive(Clone, Debug)]
 pub enum ScopeKind {
@@ -35,8 +112,14 @@ pub enum ScopeKind {
     },
     /// Combined scopes
     Union(Vec<KeywordId>),
-    /// Custom pattern-based scope (uses compiled patterns)
-    Custom,
+    /// Entities a pattern binds to `?obj`, plus those of the scopes it
+    /// extends
+    Pattern {
+        /// Scopes whose entities are included too
+        extends: Vec<KeywordId>,
+        /// The pattern, matched with `?actor` bound
+        pattern: Box<CompiledPattern>,
+    },
 }
 
 /// Evaluates entity visibility scopes.
@@ -75,57 +158,89 @@ impl ScopeEvaluator {
 
     /// Gets all entities visible to an actor given the scope definitions.
     ///
-    /// Default scopes:
-    /// - `immediate`: Room contents + inventory
-    /// - `visible`: immediate + transparent containers
-    /// - `reachable`: visible + open containers
-    #[must_use]
+    /// Only the [applicable](Self::applicable_scope) scope is evaluated,
+    /// along with the scopes it includes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern scope fails to match.
     pub fn visible_entities(
         &self,
         actor: EntityId,
         world: &World,
         scopes: &[CompiledScope],
-    ) -> Vec<EntityId> {
-        let mut visible = HashSet::new();
-
-        for scope in scopes {
-            self.evaluate_scope(actor, world, scope, scopes, &mut visible);
-        }
+    ) -> Result<Vec<EntityId>> {
+        Self::applicable_scope(scopes).map_or_else(
+            || Ok(Vec::new()),
+            |scope| self.entities_in_scope(actor, world, scope.name, scopes),
+        )
+    }
 
-        visible.into_iter().collect()
+    /// Returns the scope nouns resolve against: the last one that no other
+    /// scope includes.
+    ///
+    /// With the default scopes that is `reachable`, which includes
+    /// `visible`, which includes `immediate`.
+    #[must_use]
+    pub fn applicable_scope(scopes: &[CompiledScope]) -> Option<&CompiledScope> {
+        let included: HashSet<KeywordId> =
+            scopes.iter().flat_map(CompiledScope::includes).collect();
+        scopes
+            .iter()
+            .rev()
+            .find(|scope| !included.contains(&scope.name))
+            .or_else(|| scopes.last())
     }
 
     /// Gets entities for a specific named scope.
-    #[must_use]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern scope fails to match.
     pub fn entities_in_scope(
         &self,
         actor: EntityId,
         world: &World,
         scope_name: KeywordId,
         scopes: &[CompiledScope],
-    ) -> Vec<EntityId> {
+    ) -> Result<Vec<EntityId>> {
         let mut visible = HashSet::new();
 
         if let Some(scope) = scopes.iter().find(|s| s.name == scope_name) {
-            self.evaluate_scope(actor, world, scope, scopes, &mut visible);
+            self.evaluate_scope(
+                actor,
+                world,
+                scope,
+                scopes,
+                &mut HashSet::new(),
+                &mut visible,
+            )?;
         }
 
-        visible.into_iter().collect()
+        Ok(visible.into_iter().collect())
     }
 
     /// Evaluates a single scope definition.
+    ///
+    /// Each scope is evaluated at most once, tracked in `seen`, so a cycle
+    /// of inclusions that slipped past declaration can't recurse forever.
     fn evaluate_scope(
         &self,
         actor: EntityId,
         world: &World,
         scope: &CompiledScope,
         all_scopes: &[CompiledScope],
+        seen: &mut HashSet<KeywordId>,
         result: &mut HashSet<EntityId>,
-    ) {
+    ) -> Result<()> {
+        if !seen.insert(scope.name) {
+            return Ok(());
+        }
+
         // First, include parent scope if any
         if let Some(parent_name) = scope.parent {
             if let Some(parent) = all_scopes.iter().find(|s| s.name == parent_name) {
-                self.evaluate_scope(actor, world, parent, all_scopes, result);
+                self.evaluate_scope(actor, world, parent, all_scopes, seen, result)?;
             }
         }
 
@@ -146,14 +261,37 @@ impl ScopeEvaluator {
             ScopeKind::Union(scope_names) => {
                 for name in scope_names {
                     if let Some(sub_scope) = all_scopes.iter().find(|s| s.name == *name) {
-                        self.evaluate_scope(actor, world, sub_scope, all_scopes, result);
+                        self.evaluate_scope(actor, world, sub_scope, all_scopes, seen, result)?;
                     }
                 }
             }
-            ScopeKind::Custom => {
-                // Custom scopes need pattern evaluation - placeholder for now
+            ScopeKind::Pattern { extends, pattern } => {
+                for name in extends {
+                    if let Some(sub_scope) = all_scopes.iter().find(|s| s.name == *name) {
+                        self.evaluate_scope(actor, world, sub_scope, all_scopes, seen, result)?;
+                    }
+                }
+                Self::add_pattern_matches(actor, world, pattern, result)?;
             }
         }
+        Ok(())
+    }
+
+    /// Adds the entities a pattern binds to `?obj`, with `?actor` bound.
+    fn add_pattern_matches(
+        actor: EntityId,
+        world: &World,
+        pattern: &CompiledPattern,
+        result: &mut HashSet<EntityId>,
+    ) -> Result<()> {
+        let mut initial = Bindings::new();
+        initial.set(ACTOR_VAR.to_string(), Value::EntityRef(actor));
+        for bindings in PatternMatcher::match_pattern_from(pattern, world, &initial)? {
+            if let Some(entity) = bindings.get_entity(OBJECT_VAR) {
+                result.insert(entity);
+            }
+        }
+        Ok(())
     }
 
     /// Adds entities in the same location as the actor.
@@ -276,4 +414,24 @@ mod tests {
             }
         ));
     }
+
+    #[test]
+    fn inclusion_cycles_are_found_and_the_outermost_scope_applies() {
+        let mut world = World::new(0);
+        let [a, b, c] = ["a", "b", "c"].map(|name| world.interner_mut().intern_keyword(name));
+        let union = |name, names: Vec<KeywordId>| CompiledScope {
+            name,
+            parent: None,
+            kind: ScopeKind::Union(names),
+        };
+
+        let scopes = vec![union(a, vec![]), union(b, vec![a]), union(c, vec![b])];
+        assert!(inclusion_cycle(&scopes[2], &scopes).is_none());
+        assert_eq!(ScopeEvaluator::applicable_scope(&scopes).unwrap().name, c);
+        assert_eq!(
+            inclusion_cycle(&union(a, vec![c]), &scopes),
+            Some(vec![a, c, b, a])
+        );
+        assert_eq!(inclusion_cycle(&union(a, vec![a]), &[]), Some(vec![a, a]));
+    }
 }
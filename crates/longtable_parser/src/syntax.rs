@@ -3,10 +3,11 @@
 //! Matches token streams against command syntax patterns.
 
 use std::collections::HashMap;
+use std::ops::Range;
 
 use longtable_foundation::{Interner, KeywordId};
 
-use crate::noun_phrase::NounPhrase;
+use crate::noun_phrase::{NounPhrase, Quantifier};
 use crate::tokenizer::InputToken;
 use crate::vocabulary::VocabularyRegistry;
 
@@ -36,6 +37,18 @@ pub enum CompiledSyntaxElement {
         /// Variable name
         var: String,
     },
+    /// A free-text slot, capturing the words up to the preposition that
+    /// follows it in the pattern, or else to the end of the command
+    Text {
+        /// Variable name
+        var: String,
+    },
+    /// A conversation topic slot, capturing text like [`Self::Text`] that
+    /// is then matched against the topics of the command's NPC
+    Topic {
+        /// Variable name
+        var: String,
+    },
     /// A preposition that must appear
     Preposition(KeywordId),
 }
@@ -75,6 +88,41 @@ impl CompiledSyntax {
             .count()
     }
 
+    /// Shows the pattern as a player would type it, with slots as
+    /// variables: `put ?item in ?container:container`.
+    #[must_use]
+    pub fn describe(&self, interner: &Interner) -> String {
+        let word = |kw: KeywordId| interner.get_keyword(kw).unwrap_or("?").to_string();
+        let parts: Vec<String> = self
+            .elements
+            .iter()
+            .map(|element| match element {
+                CompiledSyntaxElement::Verb(kw) | CompiledSyntaxElement::Preposition(kw) => {
+                    word(*kw)
+                }
+                CompiledSyntaxElement::Literal(literal) => literal.clone(),
+                CompiledSyntaxElement::Noun {
+                    var,
+                    type_constraint,
+                } => match type_constraint {
+                    Some(ty) => format!("?{var}:{}", word(*ty)),
+                    None => format!("?{var}"),
+                },
+                CompiledSyntaxElement::OptionalNoun {
+                    var,
+                    type_constraint,
+                } => match type_constraint {
+                    Some(ty) => format!("[?{var}:{}]", word(*ty)),
+                    None => format!("[?{var}]"),
+                },
+                CompiledSyntaxElement::Direction { var } => format!("?{var}:direction"),
+                CompiledSyntaxElement::Text { var } => format!("?{var}:text"),
+                CompiledSyntaxElement::Topic { var } => format!("?{var}:topic"),
+            })
+            .collect();
+        parts.join(" ")
+    }
+
     /// Checks if this syntax starts with a direction slot (rather than a verb).
     #[must_use]
     pub fn starts_with_direction(&self) -> bool {
@@ -96,6 +144,15 @@ pub struct SyntaxMatch {
     pub noun_bindings: HashMap<String, NounPhrase>,
     /// Type constraints for noun bindings (variable name -> type)
     pub type_constraints: HashMap<String, KeywordId>,
+    /// Text bindings (variable name -> captured text)
+    ///
+    /// The text is rebuilt from the tokens, lowercased and without
+    /// punctuation; [`Self::text_spans`] locates it in the input as typed.
+    pub text_bindings: HashMap<String, String>,
+    /// The range of tokens each text binding was captured from
+    pub text_spans: HashMap<String, Range<usize>>,
+    /// Topic binding (variable name -> topic text), if any
+    pub topic: Option<(String, String)>,
     /// Direction binding, if any
     pub direction: Option<(String, KeywordId)>,
     /// Prepositions that appeared
@@ -180,10 +237,13 @@ impl SyntaxMatcher {
         let mut token_idx = 0;
         let mut noun_bindings = HashMap::new();
         let mut type_constraints = HashMap::new();
+        let mut text_bindings = HashMap::new();
+        let mut text_spans = HashMap::new();
+        let mut topic = None;
         let mut direction = None;
         let mut prepositions = Vec::new();
 
-        for element in &syntax.elements {
+        for (i, element) in syntax.elements.iter().enumerate() {
             match element {
                 CompiledSyntaxElement::Verb(_) => {
                     // Verb already matched at call site, consume it
@@ -260,6 +320,29 @@ impl SyntaxMatcher {
                         _ => return None,
                     }
                 }
+                CompiledSyntaxElement::Text { var } | CompiledSyntaxElement::Topic { var } => {
+                    let end = Self::text_end(
+                        tokens,
+                        token_idx,
+                        syntax.elements.get(i + 1),
+                        vocab,
+                        interner,
+                    )?;
+                    let text: Vec<&str> = tokens[token_idx..end]
+                        .iter()
+                        .filter_map(|t| match t {
+                            InputToken::Word(w) | InputToken::QuotedString(w) => Some(w.as_str()),
+                            InputToken::Then | InputToken::End => None,
+                        })
+                        .collect();
+                    if matches!(element, CompiledSyntaxElement::Topic { .. }) {
+                        topic = Some((var.clone(), text.join(" ")));
+                    } else {
+                        text_bindings.insert(var.clone(), text.join(" "));
+                        text_spans.insert(var.clone(), token_idx..end);
+                    }
+                    token_idx = end;
+                }
             }
         }
 
@@ -279,6 +362,9 @@ impl SyntaxMatcher {
             action: syntax.action,
             noun_bindings,
             type_constraints,
+            text_bindings,
+            text_spans,
+            topic,
             direction,
             prepositions,
             specificity: syntax.specificity(),
@@ -342,6 +428,9 @@ impl SyntaxMatcher {
             action: syntax.action,
             noun_bindings: HashMap::new(),
             type_constraints: HashMap::new(),
+            text_bindings: HashMap::new(),
+            text_spans: HashMap::new(),
+            topic: None,
             direction,
             prepositions: Vec::new(),
             specificity: syntax.specificity(),
@@ -349,8 +438,45 @@ impl SyntaxMatcher {
         })
     }
 
+    /// Finds where a text slot starting at `start` ends: at the last
+    /// occurrence of the preposition the pattern needs next, or else at the
+    /// end of the command. Returns `None` if the slot would be empty.
+    fn text_end(
+        tokens: &[InputToken],
+        start: usize,
+        next: Option<&CompiledSyntaxElement>,
+        vocab: &VocabularyRegistry,
+        interner: &Interner,
+    ) -> Option<usize> {
+        // Separators inside free text are part of it ("Hello, friend"); the
+        // input was already split into commands before matching
+        let command_end = tokens[start..]
+            .iter()
+            .position(|t| matches!(t, InputToken::End))
+            .map_or(tokens.len(), |i| start + i);
+        let end = match next {
+            Some(CompiledSyntaxElement::Preposition(prep_kw)) => {
+                (start + 1..command_end).rev().find(|&i| match &tokens[i] {
+                    InputToken::Word(w) => vocab
+                        .lookup_word(w, interner)
+                        .and_then(|kw| vocab.lookup_preposition(kw))
+                        .is_some_and(|prep| prep.name == *prep_kw),
+                    _ => false,
+                })?
+            }
+            _ => command_end,
+        };
+        (end > start).then_some(end)
+    }
+
     /// Collects a noun phrase from the token stream.
     ///
+    /// The last word is the noun and the words before it are adjectives. A
+    /// leading "all" (or "every", "each", "everything") quantifies the
+    /// phrase, as in "all coins", "all of the coins", or "everything except
+    /// the lamp and the sword"; a bare "all" has an empty noun, meaning
+    /// every named thing in scope.
+    ///
     /// Returns the noun phrase and number of tokens consumed.
     fn collect_noun_phrase(
         tokens: &[InputToken],
@@ -359,16 +485,8 @@ impl SyntaxMatcher {
         interner: &Interner,
     ) -> Option<(NounPhrase, usize)> {
         let mut idx = start;
-        let mut adjectives = Vec::new();
-        let mut noun = None;
-
-        // Skip articles
-        if let Some(InputToken::Word(w)) = tokens.get(idx) {
-            let lower = w.to_lowercase();
-            if lower == "the" || lower == "a" || lower == "an" {
-                idx += 1;
-            }
-        }
+        let mut words = Vec::new();
+        let mut quoted = None;
 
         // Collect words until we hit a preposition, end, or direction
         while let Some(token) = tokens.get(idx) {
@@ -387,41 +505,104 @@ impl SyntaxMatcher {
                         }
                     }
 
-                    // This is either an adjective or the noun
-                    // For now, assume last word is noun, others are adjectives
-                    if let Some(prev_noun) = noun.take() {
-                        adjectives.push(prev_noun);
-                    }
-                    noun = Some(w.clone());
+                    words.push(w.clone());
                     idx += 1;
                 }
                 InputToken::QuotedString(s) => {
                     // Quoted string is the entire noun
-                    noun = Some(s.clone());
+                    quoted = Some(s.clone());
                     idx += 1;
                     break;
                 }
-                InputToken::End => break,
+                InputToken::Then | InputToken::End => break,
             }
         }
 
-        let noun = noun?;
         let consumed = idx - start;
-
         if consumed == 0 {
             return None;
         }
 
-        Some((
-            NounPhrase {
-                adjectives,
-                noun,
-                quantifier: crate::noun_phrase::Quantifier::Specific,
-                ordinal: None,
-            },
-            consumed,
-        ))
+        let mut words = words.as_slice();
+        let quantified = matches!(
+            words.first().map(String::as_str),
+            Some("all" | "every" | "each" | "everything")
+        );
+        if !quantified {
+            return Some((Self::simple_noun_phrase(words, quoted)?, consumed));
+        }
+
+        words = &words[1..];
+        if words.first().is_some_and(|w| w == "of") {
+            words = &words[1..];
+        }
+        let (main, exceptions) = match words.iter().position(|w| w == "except" || w == "but") {
+            Some(i) => (&words[..i], &words[i + 1..]),
+            None => (words, &[][..]),
+        };
+
+        let mut phrase = Self::simple_noun_phrase(main, quoted)
+            .unwrap_or_else(|| NounPhrase::new(String::new()));
+        let exceptions: Vec<NounPhrase> = exceptions
+            .split(|w| w == "and")
+            .filter_map(|words| Self::simple_noun_phrase(words, None))
+            .collect();
+        phrase.quantifier = if exceptions.is_empty() {
+            Quantifier::All
+        } else {
+            Quantifier::AllBut(exceptions)
+        };
+        Some((phrase, consumed))
+    }
+
+    /// Builds an unquantified noun phrase: an optional article, adjectives,
+    /// and a noun, which is `quoted` if given and otherwise the last word.
+    ///
+    /// An ordinal before the adjectives ("the second key") picks one of
+    /// several matching entities. A number after the noun ("locker 12") is
+    /// left as the noun, for the resolver to read as a name or an ordinal.
+    fn simple_noun_phrase(words: &[String], quoted: Option<String>) -> Option<NounPhrase> {
+        let mut words = match words.first().map(String::as_str) {
+            Some("the" | "a" | "an") => &words[1..],
+            _ => words,
+        };
+        // A lone word is the noun, even if it's "second" or "2"
+        let mut ordinal = None;
+        if let [first, rest @ ..] = words {
+            if let Some(n) = ordinal_word(first).filter(|_| !rest.is_empty() || quoted.is_some()) {
+                ordinal = Some(n);
+                words = rest;
+            }
+        }
+        let (adjectives, noun) = match quoted {
+            Some(noun) => (words, noun),
+            None => {
+                let (noun, adjectives) = words.split_last()?;
+                (adjectives, noun.clone())
+            }
+        };
+        Some(NounPhrase {
+            adjectives: adjectives.to_vec(),
+            noun,
+            quantifier: Quantifier::Specific,
+            ordinal,
+        })
+    }
+}
+
+/// Returns the number an ordinal names: "second" or "2nd" is 2.
+fn ordinal_word(word: &str) -> Option<usize> {
+    const ORDINALS: [&str; 10] = [
+        "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth",
+        "tenth",
+    ];
+    if let Some(i) = ORDINALS.iter().position(|o| *o == word) {
+        return Some(i + 1);
     }
+    ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| word.strip_suffix(suffix))
+        .and_then(|digits| digits.parse().ok())
 }
 
 /// Compiles a syntax specification from a Value (vector) into a CompiledSyntax.
@@ -453,6 +634,8 @@ impl SyntaxCompiler {
         let vec = syntax_value.as_vec()?;
         let mut elements = Vec::new();
         let mut expect_direction_var = false;
+        let mut expect_text_var = false;
+        let mut expect_topic_var = false;
 
         for item in vec.iter() {
             match item {
@@ -462,6 +645,12 @@ impl SyntaxCompiler {
                     if name == "direction" {
                         // Next element should be a direction variable
                         expect_direction_var = true;
+                    } else if name == "text" {
+                        // Next element should be a text variable
+                        expect_text_var = true;
+                    } else if name == "topic" {
+                        // Next element should be a topic variable
+                        expect_topic_var = true;
                     } else if let Some(verb_name) = name.strip_prefix("verb/") {
                         // Verb element like :verb/go
                         let verb_kw = interner.lookup_keyword(verb_name)?;
@@ -488,15 +677,28 @@ impl SyntaxCompiler {
                                 (var_spec.to_string(), None)
                             };
 
+                        let type_name = type_constraint.and_then(|tc| interner.get_keyword(tc));
                         if expect_direction_var {
                             // This is a direction variable following :direction
                             elements.push(CompiledSyntaxElement::Direction { var: var_name });
                             expect_direction_var = false;
-                        } else if type_constraint.and_then(|tc| interner.get_keyword(tc))
-                            == Some("direction")
-                        {
+                        } else if expect_text_var {
+                            // This is a text variable following :text
+                            elements.push(CompiledSyntaxElement::Text { var: var_name });
+                            expect_text_var = false;
+                        } else if expect_topic_var {
+                            // This is a topic variable following :topic
+                            elements.push(CompiledSyntaxElement::Topic { var: var_name });
+                            expect_topic_var = false;
+                        } else if type_name == Some("direction") {
                             // Variable with :direction type constraint
                             elements.push(CompiledSyntaxElement::Direction { var: var_name });
+                        } else if var_spec.ends_with(":text") {
+                            // Variable with :text type constraint
+                            elements.push(CompiledSyntaxElement::Text { var: var_name });
+                        } else if var_spec.ends_with(":topic") {
+                            // Variable with :topic type constraint
+                            elements.push(CompiledSyntaxElement::Topic { var: var_name });
                         } else {
                             // Regular noun variable
                             elements.push(CompiledSyntaxElement::Noun {
@@ -544,6 +746,55 @@ mod tests {
         assert_eq!(syntax.specificity(), 2);
     }
 
+    #[test]
+    fn test_collect_quantified_noun_phrase() {
+        let vocab = VocabularyRegistry::new();
+        let interner = Interner::new();
+        let collect = |input: &str| {
+            let tokens = crate::tokenizer::InputTokenizer::tokenize(input);
+            SyntaxMatcher::collect_noun_phrase(&tokens, 0, &vocab, &interner)
+                .unwrap()
+                .0
+        };
+
+        let np = collect("the brass lamp");
+        assert_eq!(
+            (np.noun.as_str(), np.adjectives),
+            ("lamp", vec!["brass".to_string()])
+        );
+        assert_eq!(np.quantifier, Quantifier::Specific);
+
+        let np = collect("the second brass key");
+        assert_eq!(np.ordinal, Some(2));
+        assert_eq!(
+            (np.noun.as_str(), np.adjectives),
+            ("key", vec!["brass".to_string()])
+        );
+        let np = collect("key 3");
+        assert_eq!((np.noun.as_str(), np.ordinal), ("3", None));
+        assert_eq!(collect("1st key").ordinal, Some(1));
+        let np = collect("second");
+        assert_eq!((np.noun.as_str(), np.ordinal), ("second", None));
+
+        let np = collect("all");
+        assert_eq!((np.noun.as_str(), np.quantifier), ("", Quantifier::All));
+
+        let np = collect("all of the gold coins");
+        assert_eq!(np.noun, "coins");
+        assert_eq!(np.adjectives, ["gold"]);
+        assert_eq!(np.quantifier, Quantifier::All);
+
+        let np = collect("everything except the lamp and red key");
+        assert_eq!(np.noun, "");
+        assert_eq!(
+            np.quantifier,
+            Quantifier::AllBut(vec![
+                NounPhrase::new("lamp"),
+                NounPhrase::new("key").with_adjective("red"),
+            ])
+        );
+    }
+
     #[test]
     fn test_compiled_syntax_verb() {
         let verb_kw = KeywordId::VALUE;
@@ -556,4 +807,36 @@ mod tests {
 
         assert_eq!(syntax.verb(), Some(verb_kw));
     }
+
+    #[test]
+    fn test_describe_syntax() {
+        let mut interner = Interner::new();
+        let [put, into, container, command] =
+            ["put", "in", "container", "put-in"].map(|name| interner.intern_keyword(name));
+        let syntax = CompiledSyntax {
+            command,
+            action: put,
+            elements: vec![
+                CompiledSyntaxElement::Verb(put),
+                CompiledSyntaxElement::Noun {
+                    var: "item".to_string(),
+                    type_constraint: None,
+                },
+                CompiledSyntaxElement::Preposition(into),
+                CompiledSyntaxElement::Noun {
+                    var: "box".to_string(),
+                    type_constraint: Some(container),
+                },
+                CompiledSyntaxElement::Text {
+                    var: "note".to_string(),
+                },
+            ],
+            priority: 0,
+        };
+
+        assert_eq!(
+            syntax.describe(&interner),
+            "put ?item in ?box:container ?note:text"
+        );
+    }
 }
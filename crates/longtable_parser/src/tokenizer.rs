@@ -1,14 +1,26 @@
 //! Input tokenization.
 //!
 //! Converts raw player input into a stream of tokens.
+//!
+//! Input may hold several commands, separated by commas, semicolons,
+//! periods, or "then": "take sword, open door then go north". The
+//! separators become [`InputToken::Then`], and
+//! [`InputTokenizer::split_commands`] splits input into its commands.
+//!
+//! [`InputTokenizer::tokenize_spanned`] also gives where each token came
+//! from in the input, so free text can be recovered as it was typed.
+
+use std::ops::Range;
 
 /// A token from player input.
 #[derive(Clone, Debug, PartialEq)]
 pub enum InputToken {
     /// A lowercase word
     Word(String),
-    /// A quoted string (preserved as-is)
+    /// A quoted string, in double or single quotes (preserved as-is)
     QuotedString(String),
+    /// The end of one command and start of another
+    Then,
     /// End of input
     End,
 }
@@ -22,58 +34,151 @@ impl InputTokenizer {
     /// - Converts words to lowercase
     /// - Strips punctuation (except within quotes)
     /// - Preserves quoted strings as atomic units
+    /// - Turns command separators into one `Then` between commands
     #[must_use]
     pub fn tokenize(input: &str) -> Vec<InputToken> {
+        Self::tokenize_spanned(input)
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect()
+    }
+
+    /// Tokenizes input as [`Self::tokenize`] does, pairing each token with
+    /// the byte range of the input it came from.
+    ///
+    /// A word's range runs from its first character to its last, including
+    /// any punctuation stripped from within or after it; a quoted string's
+    /// includes its quotes; the final `End` has an empty range at the end
+    /// of the input.
+    #[must_use]
+    pub fn tokenize_spanned(input: &str) -> Vec<(InputToken, Range<usize>)> {
         let mut tokens = Vec::new();
-        let mut chars = input.chars().peekable();
+        let mut chars = input.char_indices().peekable();
         let mut current_word = String::new();
+        let mut word_span = 0..0;
 
-        while let Some(ch) = chars.next() {
+        let flush = |tokens: &mut Vec<(InputToken, Range<usize>)>,
+                     word: &mut String,
+                     span: &Range<usize>| {
+            if !word.is_empty() {
+                tokens.push((InputToken::Word(word.to_lowercase()), span.clone()));
+                word.clear();
+            }
+        };
+
+        while let Some((at, ch)) = chars.next() {
             match ch {
-                // Start of quoted string
-                '"' => {
-                    // Flush current word if any
-                    if !current_word.is_empty() {
-                        tokens.push(InputToken::Word(current_word.to_lowercase()));
-                        current_word.clear();
-                    }
-                    // Collect quoted string
-                    let mut quoted = String::new();
-                    while let Some(&c) = chars.peek() {
-                        if c == '"' {
-                            chars.next();
-                            break;
-                        }
-                        quoted.push(chars.next().unwrap());
-                    }
-                    tokens.push(InputToken::QuotedString(quoted));
+                // Start of quoted string. A single quote only opens one at
+                // the start of a word and if it is closed later, so
+                // apostrophes ("don't") are left alone.
+                '"' | '\'' if ch == '"' || current_word.is_empty() => {
+                    let Some(close) = closing_quote(input, at, ch) else {
+                        continue;
+                    };
+                    flush(&mut tokens, &mut current_word, &word_span);
+                    let quoted = input[at + 1..close].to_string();
+                    while chars.next_if(|&(i, _)| i <= close).is_some() {}
+                    let end = (close + 1).min(input.len());
+                    tokens.push((InputToken::QuotedString(quoted), at..end));
                 }
                 // Whitespace - end of word
                 ' ' | '\t' | '\n' | '\r' => {
-                    if !current_word.is_empty() {
-                        tokens.push(InputToken::Word(current_word.to_lowercase()));
-                        current_word.clear();
-                    }
+                    flush(&mut tokens, &mut current_word, &word_span);
+                }
+                // A decimal point ("3.5") is part of its number
+                '.' if current_word.ends_with(|c: char| c.is_ascii_digit())
+                    && chars.peek().is_some_and(|&(_, c)| c.is_ascii_digit()) =>
+                {
+                    current_word.push(ch);
+                    word_span.end = at + 1;
+                }
+                // Command separators
+                '.' | ',' | ';' => {
+                    flush(&mut tokens, &mut current_word, &word_span);
+                    tokens.push((InputToken::Then, at..at + 1));
                 }
                 // Punctuation to strip
-                '.' | ',' | '!' | '?' | ';' | ':' | '\'' => {
-                    // Skip punctuation (could handle contractions here)
+                '!' | '?' | ':' | '\'' => {
+                    // Skip punctuation, though a word's span takes it in
+                    if !current_word.is_empty() {
+                        word_span.end = at + 1;
+                    }
                 }
                 // Regular character
                 _ => {
+                    if current_word.is_empty() {
+                        word_span.start = at;
+                    }
                     current_word.push(ch);
+                    word_span.end = at + ch.len_utf8();
                 }
             }
         }
 
         // Flush final word
-        if !current_word.is_empty() {
-            tokens.push(InputToken::Word(current_word.to_lowercase()));
+        flush(&mut tokens, &mut current_word, &word_span);
+
+        // "then" is a separator too; keep one only between two commands
+        let mut commands: Vec<(InputToken, Range<usize>)> = Vec::with_capacity(tokens.len() + 1);
+        for (token, span) in tokens {
+            let token = match token {
+                InputToken::Word(w) if w == "then" => InputToken::Then,
+                other => other,
+            };
+            if token == InputToken::Then
+                && matches!(commands.last(), None | Some((InputToken::Then, _)))
+            {
+                continue;
+            }
+            commands.push((token, span));
+        }
+        if matches!(commands.last(), Some((InputToken::Then, _))) {
+            commands.pop();
+        }
+
+        commands.push((InputToken::End, input.len()..input.len()));
+        commands
+    }
+
+    /// Splits input into the text of each command it holds.
+    ///
+    /// "Take sword, then go north." gives `["Take sword", "go north"]`.
+    /// Each command is the input as typed, from its first word to its last.
+    #[must_use]
+    pub fn split_commands(input: &str) -> Vec<String> {
+        let mut commands = Vec::new();
+        let mut command: Option<Range<usize>> = None;
+        for (token, span) in Self::tokenize_spanned(input) {
+            match token {
+                InputToken::Then | InputToken::End => {
+                    if let Some(command) = command.take() {
+                        commands.push(input[command].to_string());
+                    }
+                }
+                InputToken::Word(_) | InputToken::QuotedString(_) => {
+                    let start = command.map_or(span.start, |command| command.start);
+                    command = Some(start..span.end);
+                }
+            }
         }
+        commands
+    }
+}
 
-        tokens.push(InputToken::End);
-        tokens
+/// Finds the quote closing the one at `open`, if any.
+///
+/// A double quote is closed by the next double quote, or else by the end of
+/// the input. A single quote is closed by the next single quote that
+/// doesn't continue a word, as the apostrophe in "don't" does.
+fn closing_quote(input: &str, open: usize, quote: char) -> Option<usize> {
+    let rest = &input[open + 1..];
+    if quote == '"' {
+        return Some(rest.find('"').map_or(input.len(), |i| open + 1 + i));
     }
+    rest.char_indices()
+        .filter(|&(_, c)| c == quote)
+        .find(|&(i, _)| !rest[i + 1..].starts_with(char::is_alphanumeric))
+        .map(|(i, _)| open + 1 + i)
 }
 
 #[cfg(test)]
@@ -119,6 +224,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_split_commands() {
+        assert_eq!(
+            InputTokenizer::split_commands("Take sword, then go north. Say \"hi, you\"."),
+            ["Take sword", "go north", "Say \"hi, you\""]
+        );
+        assert_eq!(
+            InputTokenizer::split_commands("set dial to 3.5. look"),
+            ["set dial to 3.5", "look"]
+        );
+        assert_eq!(
+            InputTokenizer::split_commands("take sword then open door; look"),
+            ["take sword", "open door", "look"]
+        );
+        assert_eq!(InputTokenizer::split_commands("look."), ["look"]);
+        assert!(InputTokenizer::split_commands(", then .").is_empty());
+
+        let tokens = InputTokenizer::tokenize("then take lamp,, then drop it.");
+        assert_eq!(
+            tokens,
+            vec![
+                InputToken::Word("take".to_string()),
+                InputToken::Word("lamp".to_string()),
+                InputToken::Then,
+                InputToken::Word("drop".to_string()),
+                InputToken::Word("it".to_string()),
+                InputToken::End,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_spanned() {
+        let input = "Say 'meet me', don't";
+        let tokens = InputTokenizer::tokenize_spanned(input);
+        let spans: Vec<&str> = tokens
+            .iter()
+            .map(|(_, span)| &input[span.clone()])
+            .collect();
+        assert_eq!(spans, ["Say", "'meet me'", ",", "don't", ""]);
+        assert_eq!(tokens[1].0, InputToken::QuotedString("meet me".to_string()));
+        assert_eq!(tokens[3].0, InputToken::Word("dont".to_string()));
+
+        // An unclosed quote runs to the end
+        let tokens = InputTokenizer::tokenize_spanned("say \"hi");
+        assert_eq!(
+            tokens[1],
+            (InputToken::QuotedString("hi".to_string()), 4..7)
+        );
+    }
+
     #[test]
     fn test_tokenize_quoted_string() {
         let tokens = InputTokenizer::tokenize("say \"Hello world\"");
@@ -0,0 +1,123 @@
+//! Conversation topics.
+//!
+//! "ask guard about treasure" names a topic rather than an entity. Each NPC
+//! lists the topics it knows in a component, as keywords:
+//!
+//! ```text
+//! (component: npc/topics :value :vec)
+//! (spawn: guard :name {:value "guard"} :npc/topics {:value [:treasure :red-key]})
+//! ```
+//!
+//! A topic matches when the words of its name (after any namespace, with
+//! hyphens as spaces) appear together in what the player typed, so "the
+//! old red key" matches `:red-key`.
+
+use longtable_foundation::{EntityId, Interner, KeywordId, Value};
+use longtable_storage::World;
+
+/// Matches topic text against the topics an NPC knows.
+#[derive(Clone, Debug)]
+pub struct TopicResolver {
+    /// Keyword for the component listing an NPC's topics
+    topics_keyword: KeywordId,
+    /// Keyword for the value field within components (typically `:value`)
+    value_keyword: KeywordId,
+}
+
+impl TopicResolver {
+    /// Creates a new topic resolver with the given keywords.
+    #[must_use]
+    pub fn new(topics_keyword: KeywordId, value_keyword: KeywordId) -> Self {
+        Self {
+            topics_keyword,
+            value_keyword,
+        }
+    }
+
+    /// Returns the topics an NPC knows.
+    #[must_use]
+    pub fn topics(&self, npc: EntityId, world: &World) -> Vec<KeywordId> {
+        match world.get_field(npc, self.topics_keyword, self.value_keyword) {
+            Ok(Some(Value::Vec(topics))) => topics
+                .iter()
+                .filter_map(|v| match v {
+                    Value::Keyword(kw) => Some(*kw),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Finds the topic of `npc`'s that `text` names.
+    ///
+    /// When several match, the one with the most words wins, then the one
+    /// listed first.
+    #[must_use]
+    pub fn resolve(
+        &self,
+        npc: EntityId,
+        text: &str,
+        world: &World,
+        interner: &Interner,
+    ) -> Option<KeywordId> {
+        let words: Vec<String> = text.split_whitespace().map(str::to_lowercase).collect();
+        let mut best: Option<(usize, KeywordId)> = None;
+        for topic in self.topics(npc, world) {
+            let Some(name) = interner.get_keyword(topic) else {
+                continue;
+            };
+            let name = name.rsplit('/').next().unwrap_or(name).to_lowercase();
+            let topic_words: Vec<&str> = name.split('-').filter(|w| !w.is_empty()).collect();
+            let named = !topic_words.is_empty()
+                && words
+                    .windows(topic_words.len())
+                    .any(|window| window.iter().zip(&topic_words).all(|(a, b)| a == b));
+            if named && best.is_none_or(|(len, _)| topic_words.len() > len) {
+                best = Some((topic_words.len(), topic));
+            }
+        }
+        best.map(|(_, topic)| topic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::{LtMap, LtVec, Type};
+    use longtable_storage::schema::{ComponentSchema, FieldSchema};
+
+    #[test]
+    fn test_resolve_topics() {
+        let mut world = World::new(42);
+        let topics_kw = world.interner_mut().intern_keyword("npc/topics");
+        let value_kw = world.interner_mut().intern_keyword("value");
+        let key = world.interner_mut().intern_keyword("key");
+        let red_key = world.interner_mut().intern_keyword("topic/red-key");
+        let treasure = world.interner_mut().intern_keyword("treasure");
+        let world = world
+            .register_component(
+                ComponentSchema::new(topics_kw)
+                    .with_field(FieldSchema::required(value_kw, Type::vec(Type::Keyword))),
+            )
+            .unwrap();
+
+        let topics: LtVec<Value> = [key, red_key, treasure]
+            .into_iter()
+            .map(Value::Keyword)
+            .collect();
+        let components = LtMap::new().insert(
+            Value::Keyword(topics_kw),
+            Value::Map(LtMap::new().insert(Value::Keyword(value_kw), Value::Vec(topics))),
+        );
+        let (world, guard) = world.spawn(&components).unwrap();
+        let resolver = TopicResolver::new(topics_kw, value_kw);
+        let resolve = |text| resolver.resolve(guard, text, &world, world.interner());
+
+        assert_eq!(resolve("the treasure"), Some(treasure));
+        assert_eq!(resolve("the old red key"), Some(red_key));
+        assert_eq!(resolve("a key"), Some(key));
+        assert_eq!(resolve("the weather"), None);
+        assert_eq!(resolve("red"), None);
+    }
+}
@@ -5,7 +5,7 @@
 
 use std::collections::{HashMap, HashSet};
 
-use longtable_foundation::KeywordId;
+use longtable_foundation::{Interner, KeywordId};
 
 /// A registered verb with its canonical name and synonyms.
 #[derive(Clone, Debug)]
@@ -107,6 +107,8 @@ pub struct VocabularyRegistry {
     pronouns: HashMap<KeywordId, Pronoun>,
     /// Adverbs (just a set of recognized words)
     adverbs: HashSet<KeywordId>,
+    /// Whether misspelled or abbreviated verbs and directions are corrected
+    fuzzy_matching: bool,
 }
 
 impl VocabularyRegistry {
@@ -137,6 +139,17 @@ impl VocabularyRegistry {
         self.verbs.insert(verb.name, verb);
     }
 
+    /// Removes a verb and its synonyms.
+    pub fn remove_verb(&mut self, name: KeywordId) {
+        self.verbs.remove(&name);
+        self.verb_synonyms.retain(|_, canonical| *canonical != name);
+    }
+
+    /// Returns all registered verbs.
+    pub fn verbs(&self) -> impl Iterator<Item = &Verb> {
+        self.verbs.values()
+    }
+
     /// Looks up a verb by word (canonical or synonym).
     #[must_use]
     pub fn lookup_verb(&self, word: KeywordId) -> Option<&Verb> {
@@ -196,6 +209,11 @@ impl VocabularyRegistry {
         self.commands.push(cmd);
     }
 
+    /// Removes every syntax registered for a command.
+    pub fn remove_command(&mut self, name: KeywordId) {
+        self.commands.retain(|cmd| cmd.name != name);
+    }
+
     /// Gets all command syntaxes that use a given verb.
     #[must_use]
     pub fn commands_for_verb(&self, _verb: KeywordId) -> Vec<&CommandSyntax> {
@@ -225,6 +243,109 @@ impl VocabularyRegistry {
     pub fn is_adverb(&self, word: KeywordId) -> bool {
         self.adverbs.contains(&word)
     }
+
+    /// Turns correction of misspelled and abbreviated words on or off.
+    pub fn set_fuzzy_matching(&mut self, enabled: bool) {
+        self.fuzzy_matching = enabled;
+    }
+
+    /// Returns true if misspelled and abbreviated words are corrected.
+    #[must_use]
+    pub fn fuzzy_matching(&self) -> bool {
+        self.fuzzy_matching
+    }
+
+    /// Returns the verb or direction an unknown word was probably meant to be.
+    ///
+    /// With fuzzy matching on, a word of three or more letters is corrected
+    /// when it is the start of only one verb or direction ("exam" for
+    /// "examine"), or else when it is one typo away from only one (a letter
+    /// added, dropped, changed, or two letters swapped). Known words and
+    /// ambiguous ones are left alone.
+    #[must_use]
+    pub fn correct_word(&self, word: &str, interner: &Interner) -> Option<String> {
+        const MIN_LEN: usize = 3;
+        if !self.fuzzy_matching || word.chars().count() < MIN_LEN {
+            return None;
+        }
+        let known = interner.lookup_keyword(word).is_some_and(|kw| {
+            self.lookup_verb(kw).is_some() || self.lookup_direction(kw).is_some()
+        });
+        if known {
+            return None;
+        }
+
+        // Every word the parser knows, with the verb or direction it names
+        let candidates: Vec<(&str, KeywordId)> = self
+            .verbs
+            .keys()
+            .map(|kw| (*kw, *kw))
+            .chain(self.verb_synonyms.iter().map(|(syn, verb)| (*syn, *verb)))
+            .chain(self.directions.keys().map(|kw| (*kw, *kw)))
+            .chain(
+                self.direction_synonyms
+                    .iter()
+                    .map(|(syn, dir)| (*syn, *dir)),
+            )
+            .filter_map(|(kw, canonical)| Some((interner.get_keyword(kw)?, canonical)))
+            .collect();
+
+        let unique = |matches: Vec<(&str, KeywordId)>| -> Option<String> {
+            let (_, canonical) = *matches.first()?;
+            if matches.iter().any(|(_, other)| *other != canonical) {
+                return None;
+            }
+            // Prefer the canonical word to a synonym of it
+            let canonical_name = interner.get_keyword(canonical);
+            matches
+                .iter()
+                .map(|(name, _)| *name)
+                .find(|name| Some(*name) == canonical_name)
+                .or_else(|| matches.iter().map(|(name, _)| *name).min())
+                .map(str::to_string)
+        };
+
+        let prefixed = candidates
+            .iter()
+            .filter(|(name, _)| name.starts_with(word))
+            .copied()
+            .collect();
+        if let Some(correction) = unique(prefixed) {
+            return Some(correction);
+        }
+        let close = candidates
+            .iter()
+            .filter(|(name, _)| within_one_edit(word, name))
+            .copied()
+            .collect();
+        unique(close)
+    }
+}
+
+/// Returns true if `a` becomes `b` by adding, dropping, or changing one
+/// letter, or by swapping two adjacent letters.
+fn within_one_edit(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (short, long) = if a.len() <= b.len() {
+        (&a, &b)
+    } else {
+        (&b, &a)
+    };
+    let prefix = short.iter().zip(long).take_while(|(x, y)| x == y).count();
+    match long.len() - short.len() {
+        0 if prefix == short.len() => false,
+        0 => {
+            let (rest_a, rest_b) = (&short[prefix + 1..], &long[prefix + 1..]);
+            rest_a == rest_b
+                || (prefix + 1 < short.len()
+                    && short[prefix] == long[prefix + 1]
+                    && short[prefix + 1] == long[prefix]
+                    && short[prefix + 2..] == long[prefix + 2..])
+        }
+        1 => short[prefix..] == long[prefix + 1..],
+        _ => false,
+    }
 }
 
 #[cfg(test)]
@@ -238,4 +359,56 @@ mod tests {
         assert!(registry.prepositions.is_empty());
         assert!(registry.directions.is_empty());
     }
+
+    #[test]
+    fn test_correct_word() {
+        let mut interner = Interner::new();
+        let mut kw = |name| interner.intern_keyword(name);
+        let (examine, x, exit, take, north, n) = (
+            kw("examine"),
+            kw("x"),
+            kw("exit"),
+            kw("take"),
+            kw("north"),
+            kw("n"),
+        );
+        let northeast = kw("northeast");
+
+        let mut registry = VocabularyRegistry::new();
+        registry.register_verb(Verb {
+            name: examine,
+            synonyms: [x].into_iter().collect(),
+        });
+        for verb in [exit, take] {
+            registry.register_verb(Verb {
+                name: verb,
+                synonyms: HashSet::new(),
+            });
+        }
+        for (dir, synonyms) in [(north, vec![n]), (northeast, vec![])] {
+            registry.register_direction(Direction {
+                name: dir,
+                synonyms: synonyms.into_iter().collect(),
+                opposite: None,
+            });
+        }
+        let correct = |registry: &VocabularyRegistry, word| registry.correct_word(word, &interner);
+
+        // Off unless asked for
+        assert_eq!(correct(&registry, "exam"), None);
+        registry.set_fuzzy_matching(true);
+
+        assert_eq!(correct(&registry, "exam").as_deref(), Some("examine"));
+        assert_eq!(correct(&registry, "exmaine").as_deref(), Some("examine"));
+        assert_eq!(correct(&registry, "tkae").as_deref(), Some("take"));
+        assert_eq!(correct(&registry, "takes").as_deref(), Some("take"));
+        assert_eq!(correct(&registry, "tale").as_deref(), Some("take"));
+        // Starts both "examine" and "exit"; "north" and "northeast"
+        assert_eq!(correct(&registry, "ex"), None);
+        assert_eq!(correct(&registry, "exi").as_deref(), Some("exit"));
+        assert_eq!(correct(&registry, "nor"), None);
+        // Already known, or nothing close
+        assert_eq!(correct(&registry, "north"), None);
+        assert_eq!(correct(&registry, "dance"), None);
+    }
 }
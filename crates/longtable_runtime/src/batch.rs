@@ -0,0 +1,155 @@
+//! Headless batch simulation.
+//!
+//! `longtable run --ticks N --script world.lt --out results.json` loads a
+//! world and runs it for a fixed number of ticks without the REPL, then
+//! writes a [`BatchReport`]: totals for the whole run plus one
+//! [`TickStats`] row per tick, as JSON. This is meant for experiments and CI
+//! jobs that would otherwise drive an interactive REPL through stdin.
+//!
+//! A tick that a constraint rolls back is counted and the run continues;
+//! a tick that fails outright (a hook or rule error) ends the run.
+
+use std::path::Path;
+use std::time::Duration;
+
+use longtable_foundation::clock::Instant;
+
+use longtable_foundation::{Error, ErrorKind, Result};
+use serde::Serialize;
+
+use crate::editor::LineEditor;
+use crate::repl::Repl;
+
+/// Statistics for a single tick.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TickStats {
+    /// Tick number.
+    pub tick: u64,
+    /// Whether the tick was committed (false if a constraint rolled it back).
+    pub committed: bool,
+    /// Rule activations fired.
+    pub activations_fired: usize,
+    /// Events drained at the end of the tick.
+    pub events_drained: usize,
+    /// Live entities after the tick.
+    pub entities: usize,
+    /// Sum of penalties from violated scoring constraints.
+    pub world_score: f64,
+    /// Number of violated scoring constraints.
+    pub soft_violations: usize,
+    /// Time spent running the tick, in microseconds.
+    pub duration_us: u64,
+}
+
+/// Summary of a batch run.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BatchReport {
+    /// Ticks run.
+    pub ticks_run: u64,
+    /// Ticks committed.
+    pub committed: u64,
+    /// Ticks rolled back by constraints.
+    pub rolled_back: u64,
+    /// Rule activations fired across all ticks.
+    pub activations_fired: usize,
+    /// Events drained across all ticks.
+    pub events_drained: usize,
+    /// Live entities before the first tick.
+    pub initial_entities: usize,
+    /// Live entities after the last tick.
+    pub final_entities: usize,
+    /// Content hash of the final world, in hex (see `World::content_hash`).
+    pub world_hash: String,
+    /// Total time spent running ticks, in microseconds.
+    pub elapsed_us: u64,
+    /// Per-tick statistics, in order.
+    pub ticks: Vec<TickStats>,
+}
+
+impl BatchReport {
+    /// Returns a one-line summary of the run.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        format!(
+            "Ran {} ticks ({} committed, {} rolled back): {} activations, {} -> {} entities, {:.3}ms",
+            self.ticks_run,
+            self.committed,
+            self.rolled_back,
+            self.activations_fired,
+            self.initial_entities,
+            self.final_entities,
+            Duration::from_micros(self.elapsed_us).as_secs_f64() * 1000.0
+        )
+    }
+
+    /// Serializes the report to pretty-printed JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            Error::new(ErrorKind::SerializationError(format!(
+                "failed to serialize batch report: {e}"
+            )))
+        })
+    }
+
+    /// Writes the report to a file as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or writing fails.
+    pub fn save_json(&self, path: &Path) -> Result<()> {
+        let json = self.to_json()?;
+        std::fs::write(path, json).map_err(|e| {
+            Error::new(ErrorKind::IoError(format!(
+                "failed to write batch report '{}': {e}",
+                path.display()
+            )))
+        })
+    }
+}
+
+/// Runs `ticks` ticks with no inputs and reports what happened.
+///
+/// # Errors
+///
+/// Returns an error if a tick fails; ticks rolled back by constraints are
+/// counted instead.
+pub fn run<E: LineEditor>(repl: &mut Repl<E>, ticks: u64) -> Result<BatchReport> {
+    let initial_entities = repl.session().world().entity_count();
+    let mut stats = Vec::new();
+    for _ in 0..ticks {
+        let started = Instant::now();
+        let result = repl.step(&[])?;
+        let duration = started.elapsed();
+
+        stats.push(TickStats {
+            tick: repl.tick_number(),
+            committed: result.success,
+            activations_fired: result.activations_fired,
+            events_drained: result.events_drained,
+            entities: repl.session().world().entity_count(),
+            world_score: result.constraint_result.score(),
+            soft_violations: result.constraint_result.scored_violations().len(),
+            duration_us: u64::try_from(duration.as_micros()).unwrap_or(u64::MAX),
+        });
+    }
+
+    let committed = stats.iter().filter(|t| t.committed).count() as u64;
+    Ok(BatchReport {
+        ticks_run: ticks,
+        committed,
+        rolled_back: ticks - committed,
+        activations_fired: stats.iter().map(|t| t.activations_fired).sum(),
+        events_drained: stats.iter().map(|t| t.events_drained).sum(),
+        initial_entities,
+        final_entities: repl.session().world().entity_count(),
+        world_hash: format!("{:016x}", repl.session().world().content_hash()),
+        elapsed_us: stats
+            .iter()
+            .fold(0, |total: u64, t| total.saturating_add(t.duration_us)),
+        ticks: stats,
+    })
+}
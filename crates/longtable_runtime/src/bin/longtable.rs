@@ -1,8 +1,12 @@
 //! Longtable CLI entry point.
 
-use longtable_runtime::Repl;
+use longtable_engine::ExecutionMode;
+use longtable_runtime::{
+    DocFormat, ErrorFormat, GoldenTranscript, Pager, PrecompiledModule, Repl, ReplayLog,
+    WarningMode,
+};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
 /// CLI configuration parsed from arguments.
@@ -11,8 +15,42 @@ struct CliConfig {
     files: Vec<PathBuf>,
     batch_mode: bool,
     run_mode: bool,
+    no_pager: bool,
+    play_mode: bool,
+    deny_warnings: bool,
+    watch: bool,
+    auto_recover: bool,
+    error_format: ErrorFormat,
+    exit_checkpoint: Option<PathBuf>,
+    features: Vec<String>,
     show_help: bool,
     show_version: bool,
+    // `longtable compile` subcommand
+    compile: bool,
+    // `longtable doc` subcommand
+    doc: Option<DocFormat>,
+    output: Option<PathBuf>,
+    // `longtable fmt` subcommand
+    fmt: bool,
+    check: bool,
+    // `longtable lint` subcommand
+    lint: bool,
+    // `longtable lsp` subcommand
+    lsp: bool,
+    // `longtable serve` subcommand
+    serve: bool,
+    port: Option<u16>,
+    // `longtable replay` subcommand
+    replay: Option<PathBuf>,
+    // `longtable run` subcommand
+    simulate: bool,
+    ticks: Option<u64>,
+    record: Option<PathBuf>,
+    // `longtable test` subcommand
+    test: bool,
+    transcripts: Vec<PathBuf>,
+    update: bool,
+    coverage: bool,
     // Debug flags
     trace_rules: bool,
     trace_vm: bool,
@@ -23,30 +61,40 @@ struct CliConfig {
 
 fn main() -> ExitCode {
     let args: Vec<String> = env::args().collect();
+    let format = parse_args(args.clone()).map_or(ErrorFormat::Human, |c| c.error_format);
 
     match run(args) {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
-            eprintln!("\x1b[31mError: {e}\x1b[0m");
+            match e.downcast_ref::<longtable_foundation::Error>() {
+                Some(e) => format.report(e),
+                None if format == ErrorFormat::Json => {
+                    let json = serde_json::json!({
+                        "code": null,
+                        "message": e.to_string(),
+                        "span": null,
+                        "hints": [],
+                        "stack": [],
+                    });
+                    eprintln!("{json}");
+                }
+                None => eprintln!("\x1b[31mError: {e}\x1b[0m"),
+            }
             ExitCode::FAILURE
         }
     }
 }
 
 /// Resolve a path argument to a file path.
-/// If the path is a directory, look for `_.lt` inside it.
-fn resolve_path(path: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+/// If the path is a directory with a `_.lt` inside it, that file is the entry
+/// point; other directories are loaded whole, in dependency order.
+fn resolve_path(path: &str) -> PathBuf {
     let path_buf = PathBuf::from(path);
-
-    if path_buf.is_dir() {
-        let entry_point = path_buf.join("_.lt");
-        if entry_point.is_file() {
-            Ok(entry_point)
-        } else {
-            Err(format!("directory '{}' has no _.lt entry point", path_buf.display()).into())
-        }
+    let entry_point = path_buf.join("_.lt");
+    if path_buf.is_dir() && entry_point.is_file() {
+        entry_point
     } else {
-        Ok(path_buf)
+        path_buf
     }
 }
 
@@ -54,16 +102,142 @@ fn parse_args(args: Vec<String>) -> Result<CliConfig, Box<dyn std::error::Error>
     let mut config = CliConfig::default();
 
     let mut i = 1;
+    match args.get(1).map(String::as_str) {
+        Some("compile") => {
+            config.compile = true;
+            i = 2;
+        }
+        Some("doc") => {
+            config.doc = Some(DocFormat::Markdown);
+            i = 2;
+        }
+        Some("fmt") => {
+            config.fmt = true;
+            i = 2;
+        }
+        Some("lint") => {
+            config.lint = true;
+            i = 2;
+        }
+        Some("lsp") => {
+            config.lsp = true;
+            i = 2;
+        }
+        Some("replay") => {
+            let log = args.get(2).ok_or("replay requires a log file")?;
+            config.replay = Some(PathBuf::from(log));
+            i = 3;
+        }
+        Some("run") => {
+            config.simulate = true;
+            i = 2;
+        }
+        Some("serve") => {
+            config.serve = true;
+            i = 2;
+        }
+        Some("test") => {
+            config.test = true;
+            i = 2;
+        }
+        _ => {}
+    }
+
     while i < args.len() {
         match args[i].as_str() {
             "-h" | "--help" => config.show_help = true,
             "-V" | "--version" => config.show_version = true,
             "-b" | "--batch" => config.batch_mode = true,
             "-r" | "--run" => config.run_mode = true,
+            "--no-pager" => config.no_pager = true,
+            "--play" => config.play_mode = true,
+            "--deny-warnings" => config.deny_warnings = true,
+            "-w" | "--watch" => config.watch = true,
+            "--auto-recover" => config.auto_recover = true,
+            "--error-format" => {
+                i += 1;
+                let name = args.get(i).ok_or("--error-format requires human or json")?;
+                config.error_format = ErrorFormat::from_name(name)
+                    .ok_or_else(|| format!("invalid --error-format value: {name}"))?;
+            }
+            "--checkpoint" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--checkpoint requires a path".into());
+                }
+                config.exit_checkpoint = Some(PathBuf::from(&args[i]));
+            }
             "--trace" => config.trace_rules = true,
             "--trace-vm" => config.trace_vm = true,
             "--trace-match" => config.trace_match = true,
             "--dump-world" => config.dump_world = true,
+            "--check" if config.fmt => config.check = true,
+            "--html" if config.doc.is_some() => config.doc = Some(DocFormat::Html),
+            "-o" | "--output" | "--out"
+                if config.doc.is_some() || config.simulate || config.compile =>
+            {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--output requires a path".into());
+                }
+                config.output = Some(PathBuf::from(&args[i]));
+            }
+            "-F" | "--feature" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--feature requires a name".into());
+                }
+                config
+                    .features
+                    .push(args[i].trim_start_matches(':').to_string());
+            }
+            "--ticks" if config.simulate => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--ticks requires a value".into());
+                }
+                config.ticks = Some(
+                    args[i]
+                        .parse()
+                        .map_err(|_| format!("invalid --ticks value: {}", args[i]))?,
+                );
+            }
+            "--port" if config.serve => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--port requires a value".into());
+                }
+                config.port = Some(
+                    args[i]
+                        .parse()
+                        .map_err(|_| format!("invalid --port value: {}", args[i]))?,
+                );
+            }
+            "--transcripts" if config.test => {
+                while args.get(i + 1).is_some_and(|arg| !arg.starts_with('-')) {
+                    i += 1;
+                    config.transcripts.push(PathBuf::from(&args[i]));
+                }
+                if config.transcripts.is_empty() {
+                    return Err("--transcripts requires at least one file".into());
+                }
+            }
+            "--update" if config.test => config.update = true,
+            "--coverage" if config.test => config.coverage = true,
+            "--script" if config.simulate => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--script requires a path".into());
+                }
+                config.files.push(resolve_path(&args[i]));
+            }
+            "--record" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--record requires a path".into());
+                }
+                config.record = Some(PathBuf::from(&args[i]));
+            }
             "--max-ticks" => {
                 i += 1;
                 if i >= args.len() {
@@ -78,7 +252,8 @@ fn parse_args(args: Vec<String>) -> Result<CliConfig, Box<dyn std::error::Error>
             arg if arg.starts_with('-') => {
                 return Err(format!("unknown option: {arg}").into());
             }
-            path => config.files.push(resolve_path(path)?),
+            path if config.fmt || config.compile => config.files.push(PathBuf::from(path)),
+            path => config.files.push(resolve_path(path)),
         }
         i += 1;
     }
@@ -117,12 +292,90 @@ fn run(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
         eprintln!();
     }
 
+    if config.fmt {
+        return format_files(&config.files, config.check);
+    }
+
+    if config.compile {
+        return compile_files(&config.files, config.output.as_deref());
+    }
+
+    if config.lsp {
+        longtable_runtime::lsp::serve(std::io::stdin().lock(), std::io::stdout().lock())?;
+        return Ok(());
+    }
+
+    if let Some(path) = &config.replay {
+        return replay(path);
+    }
+
+    if config.simulate {
+        return simulate(&config);
+    }
+
+    if config.serve {
+        return serve(&config);
+    }
+
+    if config.test {
+        return run_tests(&config);
+    }
+
     // Create REPL
     let mut repl = Repl::new()?;
+    if config.play_mode {
+        repl = repl.with_mode(ExecutionMode::Play);
+    }
+    repl = repl
+        .with_features(config.features.iter().cloned())
+        .with_hot_reload(config.watch)
+        .with_auto_recover(config.auto_recover)
+        .with_error_format(config.error_format);
+    if let Some(path) = &config.exit_checkpoint {
+        repl = repl.with_exit_checkpoint(path);
+    }
+    if config.deny_warnings {
+        repl = repl.with_warning_mode(WarningMode::Deny);
+    }
+
+    // The reference and lint report are written to stdout, so narration
+    // from the loaded files goes to stderr instead
+    let reporting = config.doc.is_some() || config.lint;
+    if reporting {
+        repl = repl.with_captured_output();
+    }
 
     // Load any specified files
-    for file in &config.files {
-        repl.eval_file(file)?;
+    let loaded = config.files.iter().try_for_each(|file| {
+        if file.is_dir() {
+            repl.load_file(&file.to_string_lossy())
+        } else {
+            repl.eval_file(file).map(drop)
+        }
+    });
+    if reporting {
+        eprint!("{}", repl.take_output());
+    }
+    loaded?;
+
+    // Generate reference documentation and exit
+    if let Some(format) = config.doc {
+        let doc = longtable_runtime::doc::generate(repl.session(), format);
+        match &config.output {
+            Some(path) => std::fs::write(path, doc)?,
+            None => print!("{doc}"),
+        }
+        return Ok(());
+    }
+
+    // Check loaded content and exit
+    if config.lint {
+        let lints = longtable_runtime::lint::lint(repl.session());
+        println!("{}", longtable_runtime::lint::format_report(&lints));
+        if lints.is_empty() {
+            return Ok(());
+        }
+        return Err(format!("{} lint issue(s)", lints.len()).into());
     }
 
     // Dump world state if requested
@@ -130,8 +383,14 @@ fn run(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
         dump_world_state(repl.session().world());
     }
 
+    if config.record.is_some() {
+        repl.start_recording(config.files.iter().map(|f| absolute(f)).collect());
+    }
+
     // If batch mode, exit now
     if config.batch_mode {
+        save_recording(&mut repl, config.record.as_deref())?;
+        repl.shutdown()?;
         return Ok(());
     }
 
@@ -146,10 +405,278 @@ fn run(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
         repl = repl.with_input_mode();
     }
 
+    if config.no_pager {
+        repl = repl.with_pager(Pager::disabled());
+    }
+
     repl.run()?;
+    save_recording(&mut repl, config.record.as_deref())
+}
+
+/// Makes a path absolute so a replay log can be run from another directory.
+fn absolute(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Writes the session's replay log to `path`, if recording.
+fn save_recording(repl: &mut Repl, path: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    if let (Some(path), Some(log)) = (path, repl.session_mut().take_replay_log()) {
+        log.save(path)?;
+        eprintln!("Recorded {} ticks to {}", log.len(), path.display());
+    }
+    Ok(())
+}
+
+/// Rewrites each file (or each `.lt` file under a directory) in canonical
+/// form. With `check`, reports files that would change instead.
+fn format_files(paths: &[PathBuf], check: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if paths.is_empty() {
+        return Err("fmt requires at least one file".into());
+    }
+    let mut files = Vec::new();
+    for path in paths {
+        collect_sources(path, &mut files)?;
+    }
+
+    let mut unformatted = 0;
+    for file in &files {
+        let source = std::fs::read_to_string(file)?;
+        let formatted = longtable_language::pretty::format_source(&source)
+            .map_err(|e| format!("{}: {e}", file.display()))?;
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{}", file.display());
+            unformatted += 1;
+        } else {
+            std::fs::write(file, formatted)?;
+            eprintln!("Formatted {}", file.display());
+        }
+    }
+
+    if unformatted > 0 {
+        return Err(format!("{unformatted} file(s) need formatting").into());
+    }
+    Ok(())
+}
+
+/// Precompiles each file (or each `.lt` file under a directory) to an `.ltc`
+/// module beside it, or to `output` when compiling a single file.
+fn compile_files(
+    paths: &[PathBuf],
+    output: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if paths.is_empty() {
+        return Err("compile requires at least one file".into());
+    }
+    let mut files = Vec::new();
+    for path in paths {
+        collect_sources(path, &mut files)?;
+    }
+    if output.is_some() && files.len() != 1 {
+        return Err("--output needs exactly one source file".into());
+    }
+
+    for file in &files {
+        let source = std::fs::read_to_string(file)?;
+        let module = PrecompiledModule::compile(&source, file)?;
+        let target = output.map_or_else(
+            || longtable_runtime::precompiled::module_path(file),
+            Path::to_path_buf,
+        );
+        module.save(&target)?;
+        eprintln!("Compiled {} -> {}", file.display(), target.display());
+    }
+    Ok(())
+}
+
+/// Collects `path` itself, or every `.lt` file beneath it, sorted.
+fn collect_sources(path: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() || entry.extension().is_some_and(|ext| ext == "lt") {
+            collect_sources(&entry, files)?;
+        }
+    }
+    Ok(())
+}
+
+/// Loads a replay log's content and re-runs its ticks, checking world hashes.
+fn replay(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let log = ReplayLog::load(path)?;
+    let mut repl = Repl::new()?.with_features(log.features.iter().cloned());
+    for file in &log.sources {
+        if file.is_dir() {
+            repl.load_file(&file.to_string_lossy())?;
+        } else {
+            repl.eval_file(file)?;
+        }
+    }
+
+    let ticks = repl.replay(&log)?;
+    println!("Replayed {ticks} ticks: every world hash matches the recording");
+    Ok(())
+}
+
+/// Loads the files into the main session and serves it over TCP.
+fn serve(config: &CliConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let mut repl =
+        longtable_runtime::server::new_session()?.with_features(config.features.iter().cloned());
+    if config.play_mode {
+        repl = repl.with_mode(ExecutionMode::Play);
+    }
+    if config.deny_warnings {
+        repl = repl.with_warning_mode(WarningMode::Deny);
+    }
+    for file in &config.files {
+        if file.is_dir() {
+            repl.load_file(&file.to_string_lossy())?;
+        } else {
+            repl.eval_file(file)?;
+        }
+    }
+
+    let port = config.port.unwrap_or(7777);
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port))?;
+    eprintln!("Serving the REPL on 127.0.0.1:{port}");
+    longtable_runtime::server::serve(listener, longtable_runtime::server::Server::new(repl))?;
     Ok(())
 }
 
+/// Loads the scripts and runs the requested number of ticks without a REPL.
+fn simulate(config: &CliConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let ticks = config.ticks.ok_or("run requires --ticks N")?;
+    // Stdout carries only the JSON report, so narration is held back and
+    // sent to stderr with the summary
+    let mut repl = Repl::new()?
+        .with_features(config.features.iter().cloned())
+        .with_captured_output();
+    if config.play_mode {
+        repl = repl.with_mode(ExecutionMode::Play);
+    }
+    if config.deny_warnings {
+        repl = repl.with_warning_mode(WarningMode::Deny);
+    }
+    repl.load_stdlib()?;
+    for file in &config.files {
+        if file.is_dir() {
+            repl.load_file(&file.to_string_lossy())?;
+        } else {
+            repl.eval_file(file)?;
+        }
+    }
+
+    let report = longtable_runtime::batch::run(&mut repl, ticks);
+    eprint!("{}", repl.take_output());
+    let report = report?;
+    match &config.output {
+        Some(path) => report.save_json(path)?,
+        None => println!("{}", report.to_json()?),
+    }
+    eprintln!("{}", report.summary());
+    Ok(())
+}
+
+/// Runs the game's declared scenarios, then replays each golden transcript
+/// against a freshly loaded game, printing a diff for every exchange that
+/// changed. With `--update`, rewrites changed transcripts instead of failing;
+/// with `--coverage`, reports the rules and syntaxes none of them reached.
+fn run_tests(config: &CliConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let mut paths = Vec::new();
+    for path in &config.transcripts {
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(path)?
+                .map(|entry| entry.map(|e| e.path()))
+                .filter(|entry| {
+                    entry
+                        .as_ref()
+                        .map_or(true, |p| p.extension().is_some_and(|ext| ext == "txt"))
+                })
+                .collect::<std::io::Result<_>>()?;
+            entries.sort();
+            paths.extend(entries);
+        } else {
+            paths.push(path.clone());
+        }
+    }
+
+    let mut repl = load_game(config)?;
+    let results = repl.run_scenarios(None);
+    if results.is_empty() && paths.is_empty() {
+        return Err("test found no scenarios; pass --transcripts FILES...".into());
+    }
+    if !results.is_empty() {
+        print!("{}", longtable_runtime::scenario::report(&results));
+    }
+    let failed_scenarios = results.iter().filter(|r| !r.passed()).count();
+
+    let mut failed = 0;
+    for path in &paths {
+        let expected = GoldenTranscript::load(path)?;
+        let mut game = load_game(config)?;
+        let run = longtable_runtime::golden::replay(&mut game, &expected);
+        repl.session_mut()
+            .coverage_mut()
+            .merge(game.session().coverage());
+        if run.passed() {
+            println!("ok      {}", path.display());
+        } else if config.update {
+            std::fs::write(path, run.actual.render())?;
+            println!("updated {}", path.display());
+        } else {
+            failed += 1;
+            println!("FAILED  {}", path.display());
+            for mismatch in &run.mismatches {
+                let input = mismatch
+                    .input
+                    .as_deref()
+                    .map_or(String::new(), |i| format!(" > {i}"));
+                println!("  {}:{}:{input}", path.display(), mismatch.line);
+                for line in mismatch.diff().lines() {
+                    println!("    {line}");
+                }
+            }
+        }
+    }
+
+    if config.coverage {
+        print!("{}", repl.coverage_report().render());
+    }
+
+    if failed > 0 {
+        return Err(format!("{failed} of {} transcript(s) failed", paths.len()).into());
+    }
+    if failed_scenarios > 0 {
+        return Err(format!("{failed_scenarios} of {} scenario(s) failed", results.len()).into());
+    }
+    Ok(())
+}
+
+/// Loads the game named by the command line into a REPL that captures its
+/// output.
+fn load_game(config: &CliConfig) -> Result<Repl, Box<dyn std::error::Error>> {
+    let mut repl = Repl::new()?
+        .with_captured_output()
+        .with_features(config.features.iter().cloned());
+    repl.load_stdlib()?;
+    for file in &config.files {
+        if file.is_dir() {
+            repl.load_file(&file.to_string_lossy())?;
+        } else {
+            repl.eval_file(file)?;
+        }
+    }
+    Ok(repl)
+}
+
 fn dump_world_state(world: &longtable_storage::World) {
     println!("\x1b[1;36m=== World State ===\x1b[0m");
     println!("Tick: {}", world.tick());
@@ -170,16 +697,69 @@ fn print_help() {
 
 \x1b[1mUSAGE:\x1b[0m
     longtable [OPTIONS] [FILES...]
+    longtable compile [-o FILE] FILES...
+    longtable doc [--html] [-o FILE] [FILES...]
+    longtable fmt [--check] FILES...
+    longtable lint [FILES...]
+    longtable lsp
+    longtable replay LOG
+    longtable serve [--port N] [FILES...]
+    longtable run --ticks N [--script FILE]... [--out FILE]
+    longtable test [FILES...] [--transcripts TRANSCRIPTS...] [--update] [--coverage]
 
 \x1b[1mARGUMENTS:\x1b[0m
     [FILES...]    Files or directories to load before starting REPL
-                  (directories must contain a _.lt entry point)
+                  (a directory loads its _.lt entry point, or else
+                  every .lt file in namespace :require order);
+                  a .lt file with an up-to-date .ltc beside it loads
+                  from the precompiled module
 
 \x1b[1mOPTIONS:\x1b[0m
     -h, --help         Print help information
     -V, --version      Print version information
     -b, --batch        Load files and exit (no REPL)
     -r, --run          Start in input mode (natural language commands)
+    --no-pager         Don't pause long output with a [MORE] prompt
+    --play             Play mode: no provenance, tracing, or history
+    --deny-warnings    Treat query warnings as errors (for CI)
+    -w, --watch        Reload loaded files that changed before each prompt
+    --auto-recover     Restore the world from before a tick that panics
+                       instead of refusing to tick until (recover!)
+    --checkpoint FILE  Save the world to FILE on exit
+    --error-format F   Print errors as human (default) text or as one json
+                       object per line, with a stable code and span
+    -F, --feature NAME Enable a content feature for (when-feature ...) forms
+                       (repeatable)
+    --record FILE      Record every tick to a replay log, written on exit
+
+\x1b[1mCOMPILE OPTIONS:\x1b[0m
+    -o, --output FILE  Write the module to FILE instead of beside the source
+                       (one source file only)
+
+\x1b[1mDOC OPTIONS:\x1b[0m
+    --html             Write HTML instead of Markdown
+    -o, --output FILE  Write the reference to FILE instead of stdout
+
+\x1b[1mFMT OPTIONS:\x1b[0m
+    --check            List files that need formatting instead of rewriting
+                       them, failing if there are any
+
+\x1b[1mRUN OPTIONS:\x1b[0m
+    --ticks N          Number of ticks to run (required)
+    --script FILE      File or directory to load (repeatable)
+    --out FILE         Write the JSON report to FILE instead of stdout
+
+\x1b[1mTEST OPTIONS:\x1b[0m
+                       Runs the game's (scenario: ...) declarations, then
+                       any transcripts
+    --transcripts T... Golden transcripts (or directories of .txt files) to
+                       replay against the loaded game, diffing the output
+    --update           Rewrite transcripts whose output changed
+    --coverage         Report rules that never fired and command syntaxes
+                       that input never matched
+
+\x1b[1mSERVE OPTIONS:\x1b[0m
+    --port N           Port to listen on at 127.0.0.1 (default 7777)
 
 \x1b[1mDEBUG OPTIONS:\x1b[0m
     --trace            Enable rule tracing output
@@ -195,8 +775,20 @@ fn print_help() {
     longtable -b test.lt             Load test.lt and exit
     longtable components.lt rules.lt Load multiple files
     longtable --trace -b sim.lt      Run with rule tracing
+    longtable doc examples/adventure Print a Markdown reference for loaded content
+    longtable --record bug.ltr world.lt  Record a session for later replay
+    longtable replay bug.ltr         Re-run a recording, checking each tick
+    longtable fmt examples/adventure Reformat every .lt file in place
+    longtable compile examples/adventure Precompile every .lt file to .ltc
+    longtable lint examples/adventure Check content for rooms without exits, etc.
+    longtable lsp                    Serve hover, completion, and diagnostics to an editor
+    longtable serve --port 7777 world.lt
+                                     Let external tools evaluate forms over TCP
+    longtable run --ticks 100 --script world.lt --out results.json
+                                     Run 100 ticks headless, writing statistics
 
 \x1b[1mREPL COMMANDS:\x1b[0m
+    (help)               List special forms; (help why) describes one
     (def name value)     Define a session variable
     (load \"path\")        Load a .lt file
     (save! \"path\")       Save world state to file
@@ -265,6 +857,128 @@ mod tests {
         assert!(config.batch_mode);
     }
 
+    #[test]
+    fn parse_no_pager() {
+        let config = parse_args(args("longtable -r --no-pager")).unwrap();
+        assert!(config.run_mode);
+        assert!(config.no_pager);
+    }
+
+    #[test]
+    fn parse_doc_subcommand() {
+        let config = parse_args(args("longtable doc --html -o ref.html world.lt")).unwrap();
+        assert_eq!(config.doc, Some(DocFormat::Html));
+        assert_eq!(config.output, Some(PathBuf::from("ref.html")));
+        assert_eq!(config.files, vec![PathBuf::from("world.lt")]);
+
+        let config = parse_args(args("longtable doc")).unwrap();
+        assert_eq!(config.doc, Some(DocFormat::Markdown));
+
+        // Doc options only apply to the subcommand
+        assert!(parse_args(args("longtable --html")).is_err());
+    }
+
+    #[test]
+    fn parse_play() {
+        let config = parse_args(args("longtable -r --play")).unwrap();
+        assert!(config.play_mode);
+    }
+
+    #[test]
+    fn parse_features() {
+        let config = parse_args(args("longtable --feature debug-content -F :steam")).unwrap();
+        assert_eq!(config.features, vec!["debug-content", "steam"]);
+        assert!(parse_args(args("longtable --feature")).is_err());
+    }
+
+    #[test]
+    fn parse_fmt_subcommand() {
+        let config = parse_args(args("longtable fmt --check examples/adventure")).unwrap();
+        assert!(config.fmt);
+        assert!(config.check);
+        assert_eq!(config.files, vec![PathBuf::from("examples/adventure")]);
+        assert!(parse_args(args("longtable --check world.lt")).is_err());
+    }
+
+    #[test]
+    fn parse_compile_subcommand() {
+        let config = parse_args(args("longtable compile world.lt -o world.ltc")).unwrap();
+        assert!(config.compile);
+        assert_eq!(config.files, vec![PathBuf::from("world.lt")]);
+        assert_eq!(config.output, Some(PathBuf::from("world.ltc")));
+        assert!(parse_args(args("longtable world.lt -o world.ltc")).is_err());
+    }
+
+    #[test]
+    fn parse_lint_subcommand() {
+        let config = parse_args(args("longtable lint world.lt")).unwrap();
+        assert!(config.lint);
+        assert_eq!(config.files, vec![PathBuf::from("world.lt")]);
+    }
+
+    #[test]
+    fn parse_replay_subcommand() {
+        let config = parse_args(args("longtable replay session.ltr")).unwrap();
+        assert_eq!(config.replay, Some(PathBuf::from("session.ltr")));
+        assert!(parse_args(args("longtable replay")).is_err());
+
+        let config = parse_args(args("longtable --record session.ltr world.lt")).unwrap();
+        assert_eq!(config.record, Some(PathBuf::from("session.ltr")));
+        assert_eq!(config.files, vec![PathBuf::from("world.lt")]);
+    }
+
+    #[test]
+    fn parse_run_subcommand() {
+        let config = parse_args(args(
+            "longtable run --ticks 100 --script world.lt --out results.json",
+        ))
+        .unwrap();
+        assert!(config.simulate);
+        assert!(!config.run_mode);
+        assert_eq!(config.ticks, Some(100));
+        assert_eq!(config.files, vec![PathBuf::from("world.lt")]);
+        assert_eq!(config.output, Some(PathBuf::from("results.json")));
+
+        assert!(parse_args(args("longtable --ticks 100")).is_err());
+        assert!(
+            parse_args(args("longtable run --ticks 1 --deny-warnings"))
+                .unwrap()
+                .deny_warnings
+        );
+        assert!(parse_args(args("longtable run --ticks many")).is_err());
+    }
+
+    #[test]
+    fn parse_serve_subcommand() {
+        let config = parse_args(args("longtable serve --port 9000 world.lt")).unwrap();
+        assert!(config.serve);
+        assert_eq!(config.port, Some(9000));
+        assert_eq!(config.files, vec![PathBuf::from("world.lt")]);
+
+        assert!(parse_args(args("longtable serve --port many")).is_err());
+        assert!(parse_args(args("longtable --port 9000")).is_err());
+    }
+
+    #[test]
+    fn parse_shutdown_options() {
+        let config = parse_args(args(
+            "longtable --auto-recover --checkpoint last.lt world.lt",
+        ))
+        .unwrap();
+        assert!(config.auto_recover);
+        assert_eq!(config.exit_checkpoint, Some(PathBuf::from("last.lt")));
+        assert_eq!(config.files, vec![PathBuf::from("world.lt")]);
+        assert!(parse_args(args("longtable --checkpoint")).is_err());
+    }
+
+    #[test]
+    fn parse_watch() {
+        let config = parse_args(args("longtable --watch game/")).unwrap();
+        assert!(config.watch);
+        assert_eq!(config.files, vec![PathBuf::from("game/")]);
+        assert!(parse_args(args("longtable -w")).unwrap().watch);
+    }
+
     #[test]
     fn parse_single_file() {
         let config = parse_args(args("longtable test.lt")).unwrap();
@@ -377,6 +1091,18 @@ mod tests {
         assert_eq!(config.files.len(), 1);
     }
 
+    #[test]
+    fn parse_error_format() {
+        let config = parse_args(args("longtable --error-format json -b")).unwrap();
+        assert_eq!(config.error_format, ErrorFormat::Json);
+        assert_eq!(
+            parse_args(args("longtable")).unwrap().error_format,
+            ErrorFormat::Human
+        );
+        assert!(parse_args(args("longtable --error-format xml")).is_err());
+        assert!(parse_args(args("longtable --error-format")).is_err());
+    }
+
     // ==================== File Execution Tests ====================
     // Note: These tests verify the run function behavior
 
@@ -413,24 +1139,18 @@ mod tests {
     #[test]
     fn resolve_path_file_passes_through() {
         // A file path should pass through unchanged
-        let result = resolve_path("test.lt").unwrap();
+        let result = resolve_path("test.lt");
         assert_eq!(result, PathBuf::from("test.lt"));
     }
 
     #[test]
-    fn resolve_path_directory_without_entry_point_fails() {
-        // A directory without _.lt should fail
+    fn resolve_path_directory_without_entry_point_passes_through() {
+        // A directory without _.lt is loaded whole, so it stays a directory
         let temp_dir = std::env::temp_dir().join("longtable_test_no_entry");
         std::fs::create_dir_all(&temp_dir).ok();
 
         let result = resolve_path(temp_dir.to_str().unwrap());
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("no _.lt entry point")
-        );
+        assert_eq!(result, temp_dir);
 
         std::fs::remove_dir_all(&temp_dir).ok();
     }
@@ -443,9 +1163,37 @@ mod tests {
         let entry_file = temp_dir.join("_.lt");
         std::fs::write(&entry_file, ";; entry point").ok();
 
-        let result = resolve_path(temp_dir.to_str().unwrap()).unwrap();
+        let result = resolve_path(temp_dir.to_str().unwrap());
         assert_eq!(result, entry_file);
 
         std::fs::remove_dir_all(&temp_dir).ok();
     }
+
+    #[test]
+    fn test_update_rewrites_transcripts_that_then_pass() {
+        let dir = std::env::temp_dir().join("longtable_test_golden_update");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let game = dir.join("game.lt");
+        let transcript = dir.join("intro.txt");
+        std::fs::write(&game, "(println \"Welcome.\")").unwrap();
+        std::fs::write(&transcript, "Hello.\n\n> (say \"hi\")\nbye\n").unwrap();
+        let command = |extra: &str| {
+            parse_args(args(&format!(
+                "longtable test {} --transcripts {} {extra}",
+                game.display(),
+                transcript.display()
+            )))
+            .unwrap()
+        };
+
+        assert!(run_tests(&command("")).is_err());
+        run_tests(&command("--update")).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&transcript).unwrap(),
+            "Welcome.\n\n> (say \"hi\")\nhi\n"
+        );
+        run_tests(&command("")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
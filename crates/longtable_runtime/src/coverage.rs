@@ -0,0 +1,167 @@
+//! Coverage of rules and command syntaxes.
+//!
+//! The session records every rule whose body ran in a committed tick and
+//! every command syntax that player input matches, so content nothing ever
+//! reaches can be found after a play-through or a test run:
+//!
+//! ```text
+//! (coverage-report)
+//! rules: 3 of 4 fired
+//!   never fired: :decay
+//! syntaxes: 5 of 7 matched
+//!   never matched: :put-in :take-from
+//! ```
+//!
+//! `longtable test game/ --coverage` prints the same report for everything
+//! its scenarios and transcripts ran. Names are kept rather than interned
+//! ids, so coverage from separately loaded games can be merged.
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+/// The rules that have fired and the syntaxes that have matched.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    rules: BTreeSet<String>,
+    syntaxes: BTreeSet<String>,
+}
+
+impl Coverage {
+    /// Creates an empty record.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that a rule fired, running its body.
+    pub fn record_rule(&mut self, name: &str) {
+        if !self.rules.contains(name) {
+            self.rules.insert(name.to_string());
+        }
+    }
+
+    /// Records that input matched a command syntax.
+    pub fn record_syntax(&mut self, command: &str) {
+        if !self.syntaxes.contains(command) {
+            self.syntaxes.insert(command.to_string());
+        }
+    }
+
+    /// Adds what `other` recorded to this.
+    pub fn merge(&mut self, other: &Self) {
+        self.rules.extend(other.rules.iter().cloned());
+        self.syntaxes.extend(other.syntaxes.iter().cloned());
+    }
+
+    /// Forgets everything recorded.
+    pub fn clear(&mut self) {
+        self.rules.clear();
+        self.syntaxes.clear();
+    }
+
+    /// Compares the record against the declared rules and command syntaxes.
+    #[must_use]
+    pub fn report<'a>(
+        &self,
+        rules: impl IntoIterator<Item = &'a str>,
+        syntaxes: impl IntoIterator<Item = &'a str>,
+    ) -> CoverageReport {
+        let rules: BTreeSet<&str> = rules.into_iter().collect();
+        let syntaxes: BTreeSet<&str> = syntaxes.into_iter().collect();
+        CoverageReport {
+            rules: rules.len(),
+            unfired_rules: missing(&rules, &self.rules),
+            syntaxes: syntaxes.len(),
+            unmatched_syntaxes: missing(&syntaxes, &self.syntaxes),
+        }
+    }
+}
+
+/// Returns the declared names that weren't recorded, in order.
+fn missing(declared: &BTreeSet<&str>, recorded: &BTreeSet<String>) -> Vec<String> {
+    declared
+        .iter()
+        .filter(|name| !recorded.contains(**name))
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// How much of a game's rules and syntaxes a run reached.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// The number of declared rules.
+    pub rules: usize,
+    /// Rules that never fired, by name.
+    pub unfired_rules: Vec<String>,
+    /// The number of declared command syntaxes.
+    pub syntaxes: usize,
+    /// Command syntaxes that input never matched, by command name.
+    pub unmatched_syntaxes: Vec<String>,
+}
+
+impl CoverageReport {
+    /// Returns true if every rule fired and every syntax matched.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.unfired_rules.is_empty() && self.unmatched_syntaxes.is_empty()
+    }
+
+    /// Formats the report, a section each for rules and syntaxes.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        let mut section = |kind: &str, verb: &str, total: usize, missed: &[String]| {
+            let _ = writeln!(text, "{kind}: {} of {total} {verb}", total - missed.len());
+            if !missed.is_empty() {
+                let names: Vec<String> = missed.iter().map(|name| format!(":{name}")).collect();
+                let _ = writeln!(text, "  never {verb}: {}", names.join(" "));
+            }
+        };
+        section("rules", "fired", self.rules, &self.unfired_rules);
+        section(
+            "syntaxes",
+            "matched",
+            self.syntaxes,
+            &self.unmatched_syntaxes,
+        );
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_what_never_ran() {
+        let mut coverage = Coverage::new();
+        coverage.record_rule("burn");
+        coverage.record_syntax("take");
+
+        let mut other = Coverage::new();
+        other.record_rule("spread");
+        other.record_rule("burn");
+        coverage.merge(&other);
+
+        let report = coverage.report(
+            ["burn", "spread", "decay"],
+            ["take", "put-in", "take-from", "take"],
+        );
+        assert_eq!(report.unfired_rules, vec!["decay"]);
+        assert_eq!(report.unmatched_syntaxes, vec!["put-in", "take-from"]);
+        assert!(!report.is_complete());
+        assert_eq!(
+            report.render(),
+            "rules: 2 of 3 fired\n  never fired: :decay\n\
+             syntaxes: 1 of 3 matched\n  never matched: :put-in :take-from\n"
+        );
+
+        coverage.clear();
+        let report = coverage.report([], []);
+        assert!(report.is_complete());
+        assert_eq!(
+            report.render(),
+            "rules: 0 of 0 fired\nsyntaxes: 0 of 0 matched\n"
+        );
+    }
+}
@@ -0,0 +1,530 @@
+//! Datom export and import.
+//!
+//! Writes a world as a flat sequence of `[entity attribute value tick]`
+//! datoms in EDN, one per line, in the shape Datalog tools expect:
+//!
+//! ```clojure
+//! [0 :db/ident :knight 3]
+//! [0 :health {:current 5} 3]
+//! [0 :home {:room #entity 1} 3]
+//! [0 :in-room #entity 1 3]
+//! [1 :room {:title "Hall"} 3]
+//! ```
+//!
+//! Entities are numbered by index. Component datoms carry the whole component
+//! value; relationship datoms carry the target as `#entity N`, as do entity
+//! references inside values. `:db/ident` names an entity, and an entity with
+//! no other datoms gets a `[N :db/entity true tick]` datom so it survives
+//! the round trip. Every datom is
+//! stamped with the world's tick. Lines are sorted by entity and attribute,
+//! and map entries by key, so exports of similar worlds diff cleanly.
+//!
+//! Importing works like [`crate::serialize::world_from_json`]: schemas come
+//! from a template world, each entity is restored at the index it was
+//! exported with, and the largest tick becomes the world's tick. Datoms don't
+//! carry generations, so restored entities start at the first generation.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::hash::BuildHasher;
+use std::path::Path;
+
+use longtable_foundation::{
+    EntityId, Error, ErrorKind, Interner, KeywordId, LtMap, LtSet, LtVec, Result, Value,
+};
+use longtable_language::{Ast, parse};
+use longtable_storage::World;
+
+use crate::serialize::{empty_world_like, invalid, relationship_field, remap_references};
+
+/// The attribute that names an entity.
+pub const IDENT: &str = "db/ident";
+
+/// The attribute that records an entity with no other datoms.
+pub const ENTITY: &str = "db/entity";
+
+/// One fact about an entity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Datom {
+    /// The entity's index.
+    pub entity: u64,
+    /// A component, a relationship, or `:db/ident`.
+    pub attribute: KeywordId,
+    /// The component value, relationship target, or name.
+    pub value: Value,
+    /// The tick the fact was recorded at.
+    pub tick: u64,
+}
+
+/// Lists a world's facts as datoms, sorted by entity and attribute name.
+///
+/// Entities named in `names` get a `:db/ident` datom; relationship entities
+/// become datoms on their source rather than entities of their own. Entities
+/// with nothing else to say get a `:db/entity` datom. `interner` should be a
+/// copy of the world's interner; the `:db/` attributes and the names are
+/// interned into it.
+pub fn world_to_datoms<S: BuildHasher>(
+    world: &World,
+    names: &HashMap<String, EntityId, S>,
+    interner: &mut Interner,
+) -> Vec<Datom> {
+    let tick = world.tick();
+    let mut datoms = Vec::new();
+    let ident = interner.intern_keyword(IDENT);
+
+    for (name, &entity) in names {
+        if world.exists(entity) {
+            let value = Value::Keyword(interner.intern_keyword(name));
+            datoms.push(Datom {
+                entity: entity.index,
+                attribute: ident,
+                value,
+                tick,
+            });
+        }
+    }
+
+    for entity in world.entities() {
+        if world.has(entity, KeywordId::REL_TYPE) {
+            continue;
+        }
+        for &component in world.entity_components(entity) {
+            if let Ok(Some(value)) = world.get(entity, component) {
+                datoms.push(Datom {
+                    entity: entity.index,
+                    attribute: component,
+                    value,
+                    tick,
+                });
+            }
+        }
+    }
+
+    for rel in world.find_relationships(None, None, None) {
+        let endpoints = (
+            relationship_field(world, rel, KeywordId::REL_TYPE),
+            relationship_field(world, rel, KeywordId::REL_SOURCE),
+            relationship_field(world, rel, KeywordId::REL_TARGET),
+        );
+        if let (
+            Some(Value::Keyword(kind)),
+            Some(Value::EntityRef(source)),
+            Some(Value::EntityRef(target)),
+        ) = endpoints
+        {
+            datoms.push(Datom {
+                entity: source.index,
+                attribute: kind,
+                value: Value::EntityRef(target),
+                tick,
+            });
+        }
+    }
+
+    let described: HashSet<u64> = datoms.iter().map(|d| d.entity).collect();
+    let entity_attribute = interner.intern_keyword(ENTITY);
+    for entity in world.entities() {
+        if !described.contains(&entity.index) && !world.has(entity, KeywordId::REL_TYPE) {
+            datoms.push(Datom {
+                entity: entity.index,
+                attribute: entity_attribute,
+                value: Value::Bool(true),
+                tick,
+            });
+        }
+    }
+
+    datoms.sort_by_cached_key(|d| {
+        let attribute = interner.get_keyword(d.attribute).unwrap_or("").to_string();
+        (d.entity, attribute, to_edn(&d.value, interner))
+    });
+    datoms
+}
+
+/// Writes a world as EDN datoms, one per line (see [`world_to_datoms`]).
+#[must_use]
+pub fn world_to_edn<S: BuildHasher>(world: &World, names: &HashMap<String, EntityId, S>) -> String {
+    let mut interner = world.interner().clone();
+    let mut out = String::from(";; longtable datoms: [entity attribute value tick]\n");
+    for datom in world_to_datoms(world, names, &mut interner) {
+        let _ = writeln!(
+            out,
+            "[{} :{} {} {}]",
+            datom.entity,
+            interner.get_keyword(datom.attribute).unwrap_or("?"),
+            to_edn(&datom.value, &interner),
+            datom.tick
+        );
+    }
+    out
+}
+
+/// Renders a value as EDN.
+///
+/// Entity references render as `#entity N`, integers beyond 64 bits as
+/// `#bigint "digits"`, instants and durations as
+/// `#instant ms` and `#duration ms`, numeric vectors as `#vec2 [x y]` and
+/// `#vec3 [x y z]`, non-finite floats as `#float "inf"` (or
+/// `"-inf"`, `"nan"`), and functions as `nil`.
+#[must_use]
+pub fn to_edn(value: &Value, interner: &Interner) -> String {
+    match value {
+        Value::Nil | Value::Fn(_) | Value::Seq(_) => "nil".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Int(n) => n.to_string(),
+        Value::BigInt(n) => format!("#bigint \"{n}\""),
+        Value::Float(f) if f.is_nan() => "#float \"nan\"".to_string(),
+        Value::Float(f) if f.is_infinite() => {
+            format!("#float \"{}\"", if *f > 0.0 { "inf" } else { "-inf" })
+        }
+        Value::Float(f) => {
+            let text = f.to_string();
+            if text.contains('.') {
+                text
+            } else {
+                text + ".0"
+            }
+        }
+        Value::String(s) => {
+            let mut out = String::from("\"");
+            for c in s.chars() {
+                match c {
+                    '"' => out.push_str("\\\""),
+                    '\\' => out.push_str("\\\\"),
+                    '\n' => out.push_str("\\n"),
+                    '\r' => out.push_str("\\r"),
+                    '\t' => out.push_str("\\t"),
+                    c => out.push(c),
+                }
+            }
+            out.push('"');
+            out
+        }
+        Value::Keyword(id) => format!(":{}", interner.get_keyword(*id).unwrap_or("?")),
+        Value::Symbol(id) => interner.get_symbol(*id).unwrap_or("?").to_string(),
+        Value::EntityRef(id) => format!("#entity {}", id.index),
+        Value::Instant(ms) => format!("#instant {ms}"),
+        Value::Duration(ms) => format!("#duration {ms}"),
+        Value::Vec2(_) | Value::Vec3(_) => {
+            let components: Vec<String> = value
+                .as_components()
+                .unwrap_or_default()
+                .iter()
+                .map(|&x| to_edn(&Value::Float(x), interner))
+                .collect();
+            format!("#{} [{}]", value.value_type(), components.join(" "))
+        }
+        Value::Vec(items) => format!("[{}]", join(items.iter(), interner, false)),
+        Value::List(items) => format!("({})", join(items.iter(), interner, false)),
+        Value::Set(items) => format!("#{{{}}}", join(items.iter(), interner, true)),
+        Value::Map(map) => {
+            let mut entries: Vec<String> = map
+                .iter()
+                .map(|(k, v)| format!("{} {}", to_edn(k, interner), to_edn(v, interner)))
+                .collect();
+            entries.sort();
+            format!("{{{}}}", entries.join(" "))
+        }
+    }
+}
+
+fn join<'a>(items: impl Iterator<Item = &'a Value>, interner: &Interner, sorted: bool) -> String {
+    let mut items: Vec<String> = items.map(|v| to_edn(v, interner)).collect();
+    if sorted {
+        items.sort();
+    }
+    items.join(" ")
+}
+
+/// Builds a world from EDN datoms, using the schemas and interner of
+/// `template`.
+///
+/// Returns the world and the entity names its `:db/ident` datoms declared.
+///
+/// # Errors
+///
+/// Returns an error if the text doesn't parse, a form isn't a
+/// `[entity attribute value tick]` vector, an attribute is neither a known
+/// component nor a known relationship, a reference names an entity with no
+/// datoms, or a value doesn't fit its schema.
+pub fn world_from_edn(text: &str, template: &World) -> Result<(World, HashMap<String, EntityId>)> {
+    let mut world = empty_world_like(template, template.seed())?;
+    let mut datoms = Vec::new();
+    for form in parse(text)? {
+        datoms.push(read_datom(&form, world.interner_mut())?);
+    }
+
+    let mut ids: HashMap<u64, EntityId> = HashMap::new();
+    for datom in &datoms {
+        if let Entry::Vacant(slot) = ids.entry(datom.entity) {
+            let entity = EntityId::new(datom.entity, 1);
+            world = world.restore_entity(entity)?;
+            slot.insert(entity);
+        }
+    }
+
+    let ident = world.interner_mut().intern_keyword(IDENT);
+    let entity_attribute = world.interner_mut().intern_keyword(ENTITY);
+    let mut names = HashMap::new();
+    let mut tick = 0;
+    for datom in datoms {
+        tick = tick.max(datom.tick);
+        let entity = ids[&datom.entity];
+        let value = remap_references(&datom.value, &mut |label| {
+            ids.get(&label.index)
+                .copied()
+                .ok_or_else(|| invalid(format!("#entity {} has no datoms of its own", label.index)))
+        })?;
+        let attribute = datom.attribute;
+        let name = world
+            .interner()
+            .get_keyword(attribute)
+            .unwrap_or("?")
+            .to_string();
+
+        if attribute == entity_attribute {
+            // The entity was restored along with the others above
+        } else if attribute == ident {
+            let Value::Keyword(id) = value else {
+                return Err(invalid(format!(":{IDENT} must be a keyword, got {value}")));
+            };
+            let ident_name = world.interner().get_keyword(id).unwrap_or("?").to_string();
+            names.insert(ident_name, entity);
+        } else if world.relationship_schema(attribute).is_some() {
+            let Value::EntityRef(target) = value else {
+                return Err(invalid(format!(
+                    "relationship :{name} needs an #entity target, got {value}"
+                )));
+            };
+            world = world.link(entity, attribute, target)?;
+        } else if world.component_schema(attribute).is_some() {
+            world = world.set(entity, attribute, value)?;
+        } else {
+            return Err(invalid(format!("unknown attribute :{name}")));
+        }
+    }
+
+    Ok((world.with_tick(tick), names))
+}
+
+/// Reads one `[entity attribute value tick]` form. Entity references in the
+/// value keep their file numbers as indexes until they are remapped.
+fn read_datom(form: &Ast, interner: &mut Interner) -> Result<Datom> {
+    let Ast::Vector(items, _) = form else {
+        return Err(invalid(format!(
+            "expected a [entity attribute value tick] datom, got {}",
+            form.type_name()
+        )));
+    };
+    let [
+        Ast::Int(entity, _),
+        Ast::Keyword(attribute, _),
+        value,
+        Ast::Int(tick, _),
+    ] = items.as_slice()
+    else {
+        return Err(invalid(
+            "datoms must be [entity attribute value tick] with integer entity and tick".to_string(),
+        ));
+    };
+    let (Ok(entity), Ok(tick)) = (u64::try_from(*entity), u64::try_from(*tick)) else {
+        return Err(invalid(
+            "datom entity and tick must not be negative".to_string(),
+        ));
+    };
+    Ok(Datom {
+        entity,
+        attribute: interner.intern_keyword(attribute),
+        value: read_value(value, interner)?,
+        tick,
+    })
+}
+
+fn read_value(ast: &Ast, interner: &mut Interner) -> Result<Value> {
+    let items = |items: &[Ast], interner: &mut Interner| -> Result<Vec<Value>> {
+        items
+            .iter()
+            .map(|item| read_value(item, interner))
+            .collect()
+    };
+    Ok(match ast {
+        Ast::Nil(_) => Value::Nil,
+        Ast::Bool(b, _) => Value::Bool(*b),
+        Ast::Int(n, _) => Value::Int(*n),
+        Ast::Float(f, _) => Value::Float(*f),
+        Ast::String(s, _) => Value::from(s.as_str()),
+        Ast::Symbol(s, _) => Value::Symbol(interner.intern_symbol(s)),
+        Ast::Keyword(k, _) => Value::Keyword(interner.intern_keyword(k)),
+        Ast::Vector(v, _) => Value::Vec(items(v, interner)?.into_iter().collect::<LtVec<_>>()),
+        Ast::List(v, _) => Value::List(items(v, interner)?.into_iter().collect::<LtVec<_>>()),
+        Ast::Set(v, _) => Value::Set(items(v, interner)?.into_iter().collect::<LtSet<_>>()),
+        Ast::Map(entries, _) => {
+            let mut map = LtMap::new();
+            for (k, v) in entries {
+                map = map.insert(read_value(k, interner)?, read_value(v, interner)?);
+            }
+            Value::Map(map)
+        }
+        Ast::Tagged(tag, inner, _) => match (tag.as_str(), inner.as_ref()) {
+            ("entity", Ast::Int(n, _)) if *n >= 0 => {
+                Value::EntityRef(EntityId::new(n.unsigned_abs(), 0))
+            }
+            ("bigint", Ast::String(digits, _)) => match digits.parse() {
+                Ok(n) => Value::integer(n),
+                Err(_) => return Err(invalid(format!("invalid #bigint \"{digits}\""))),
+            },
+            ("instant", Ast::Int(ms, _)) => Value::Instant(*ms),
+            ("duration", Ast::Int(ms, _)) => Value::Duration(*ms),
+            ("vec2" | "vec3", Ast::Vector(items, _)) => items
+                .iter()
+                .map(|item| read_value(item, interner).map(|v| v.as_number()))
+                .collect::<Result<Option<Vec<f64>>>>()?
+                .and_then(|components| Value::from_components(&components))
+                .filter(|v| v.value_type().to_string() == *tag)
+                .ok_or_else(|| invalid(format!("#{tag} expects a vector of numbers")))?,
+            ("float", Ast::String(s, _)) if s == "inf" => Value::Float(f64::INFINITY),
+            ("float", Ast::String(s, _)) if s == "-inf" => Value::Float(f64::NEG_INFINITY),
+            ("float", Ast::String(s, _)) if s == "nan" => Value::Float(f64::NAN),
+            _ => return Err(invalid(format!("unsupported tagged value #{tag}"))),
+        },
+        other => {
+            return Err(invalid(format!(
+                "unsupported form in datom value: {}",
+                other.type_name()
+            )));
+        }
+    })
+}
+
+/// Writes a world's datoms to a file (see [`world_to_edn`]).
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn save_to_file<P: AsRef<Path>, S: BuildHasher>(
+    world: &World,
+    names: &HashMap<String, EntityId, S>,
+    path: P,
+) -> Result<()> {
+    std::fs::write(path.as_ref(), world_to_edn(world, names)).map_err(|e| {
+        Error::new(ErrorKind::IoError(format!(
+            "failed to write to file '{}': {e}",
+            path.as_ref().display()
+        )))
+    })
+}
+
+/// Reads a datom file (see [`world_from_edn`]).
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or doesn't describe a world
+/// `template`'s schemas accept.
+pub fn load_from_file<P: AsRef<Path>>(
+    path: P,
+    template: &World,
+) -> Result<(World, HashMap<String, EntityId>)> {
+    let text = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+        Error::new(ErrorKind::IoError(format!(
+            "failed to open file '{}': {e}",
+            path.as_ref().display()
+        )))
+    })?;
+    world_from_edn(&text, template)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::Type;
+    use longtable_storage::schema::{ComponentSchema, FieldSchema, RelationshipSchema};
+
+    fn test_world() -> (World, HashMap<String, EntityId>) {
+        let mut world = World::new(7);
+        let health = world.interner_mut().intern_keyword("health");
+        let current = world.interner_mut().intern_keyword("current");
+        let home = world.interner_mut().intern_keyword("home");
+        let room = world.interner_mut().intern_keyword("room");
+        let contains = world.interner_mut().intern_keyword("contains");
+        world = world
+            .register_component(
+                ComponentSchema::new(health).with_field(FieldSchema::required(current, Type::Int)),
+            )
+            .unwrap()
+            .register_component(
+                ComponentSchema::new(home).with_field(FieldSchema::required(room, Type::EntityRef)),
+            )
+            .unwrap()
+            .register_relationship(RelationshipSchema::new(contains))
+            .unwrap();
+
+        let hp = LtMap::new().insert(Value::Keyword(current), Value::Int(5));
+        let (w, hall) = world
+            .spawn(&LtMap::new().insert(Value::Keyword(health), Value::Map(hp.clone())))
+            .unwrap();
+        let at_hall = LtMap::new().insert(Value::Keyword(room), Value::EntityRef(hall));
+        let (w, knight) = w
+            .spawn(
+                &LtMap::new()
+                    .insert(Value::Keyword(health), Value::Map(hp))
+                    .insert(Value::Keyword(home), Value::Map(at_hall)),
+            )
+            .unwrap();
+        let world = w.link(hall, contains, knight).unwrap().advance_tick();
+        (world, HashMap::from([("knight".to_string(), knight)]))
+    }
+
+    #[test]
+    fn exports_sorted_datoms() {
+        let (world, names) = test_world();
+        let edn = world_to_edn(&world, &names);
+        let lines: Vec<&str> = edn.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            [
+                "[0 :contains #entity 1 1]",
+                "[0 :health {:current 5} 1]",
+                "[1 :db/ident :knight 1]",
+                "[1 :health {:current 5} 1]",
+                "[1 :home {:room #entity 0} 1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn edn_roundtrip() {
+        let (world, names) = test_world();
+        let edn = world_to_edn(&world, &names);
+        let (restored, restored_names) = world_from_edn(&edn, &world).unwrap();
+        assert_eq!(restored.tick(), world.tick());
+        assert_eq!(restored_names.len(), 1);
+        for (name, entity) in &names {
+            assert_eq!(restored_names[name].index, entity.index);
+        }
+        assert_eq!(world_to_edn(&restored, &restored_names), edn);
+    }
+
+    #[test]
+    fn import_rejects_unknown_attributes_and_dangling_references() {
+        let (world, _) = test_world();
+        assert!(world_from_edn("[0 :mana 3 0]", &world).is_err());
+        assert!(world_from_edn("[0 :contains #entity 9 0]", &world).is_err());
+        assert!(world_from_edn("[0 :health]", &world).is_err());
+    }
+
+    #[test]
+    fn entities_without_components_round_trip() {
+        let (world, names) = test_world();
+        let (world, bare) = world.spawn(&LtMap::new()).unwrap();
+        let edn = world_to_edn(&world, &names);
+        assert!(
+            edn.contains(&format!("[{} :db/entity true 1]", bare.index)),
+            "{edn}"
+        );
+
+        let (restored, _) = world_from_edn(&edn, &world).unwrap();
+        assert_eq!(restored.entity_count(), world.entity_count());
+        assert!(restored.exists(EntityId::new(bare.index, 1)));
+    }
+}
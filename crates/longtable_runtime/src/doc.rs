@@ -0,0 +1,517 @@
+//! Reference documentation generated from a loaded session.
+//!
+//! `longtable doc` loads content files and then walks the live registries
+//! rather than scraping source: component schemas with their fields, types,
+//! and defaults; relationships with cardinality; rules grouped by namespace
+//! with their docstrings and patterns; and command syntaxes with the actions
+//! they invoke. The result is rendered as Markdown or HTML.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use longtable_engine::{CompiledBinding, CompiledClause};
+use longtable_foundation::{Interner, KeywordId, Value};
+use longtable_parser::CompiledSyntaxElement;
+use longtable_storage::schema::{Cardinality, OnDelete, Storage};
+
+use crate::session::Session;
+
+/// Output format for generated documentation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DocFormat {
+    /// GitHub-flavored Markdown.
+    #[default]
+    Markdown,
+    /// A standalone HTML page.
+    Html,
+}
+
+/// Label used for rules whose names have no namespace.
+const GLOBAL_NAMESPACE: &str = "(global)";
+
+// =============================================================================
+// Reference model
+// =============================================================================
+
+/// One documented item: a heading, descriptive lines, and optional code.
+struct Entry {
+    name: String,
+    doc: Option<String>,
+    details: Vec<String>,
+    code: Vec<String>,
+}
+
+/// A titled group of entries.
+struct Section {
+    title: String,
+    entries: Vec<Entry>,
+}
+
+/// Generates reference documentation for everything registered in `session`.
+#[must_use]
+pub fn generate(session: &Session, format: DocFormat) -> String {
+    let sections = collect(session);
+    match format {
+        DocFormat::Markdown => render_markdown(&sections),
+        DocFormat::Html => render_html(&sections),
+    }
+}
+
+/// Builds the reference model from the session's registries, sorted by name.
+fn collect(session: &Session) -> Vec<Section> {
+    let interner = session.world().interner();
+    let mut sections = vec![
+        component_section(session, interner),
+        relationship_section(session, interner),
+    ];
+    sections.extend(rule_sections(session, interner));
+    sections.push(command_section(session, interner));
+    sections
+}
+
+fn component_section(session: &Session, interner: &Interner) -> Section {
+    let mut components: Vec<Entry> = session
+        .world()
+        .component_schemas()
+        .map(|schema| {
+            let details = if schema.is_tag {
+                vec!["Tag component (no fields).".to_string()]
+            } else {
+                schema
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let requirement = match (&field.default, field.required) {
+                            (Some(default), _) => {
+                                format!("default `{}`", value_name(default, interner))
+                            }
+                            (None, true) => "required".to_string(),
+                            (None, false) => "optional".to_string(),
+                        };
+                        format!(
+                            "`{}` : `{}` ({requirement})",
+                            keyword_name(field.name, interner),
+                            field.ty
+                        )
+                    })
+                    .collect()
+            };
+            Entry {
+                name: keyword_name(schema.name, interner),
+                doc: None,
+                details,
+                code: Vec::new(),
+            }
+        })
+        .collect();
+    components.sort_by(|a, b| a.name.cmp(&b.name));
+    Section {
+        title: "Components".to_string(),
+        entries: components,
+    }
+}
+
+fn relationship_section(session: &Session, interner: &Interner) -> Section {
+    let mut relationships: Vec<Entry> = session
+        .world()
+        .relationship_schemas()
+        .map(|schema| {
+            let mut details = vec![
+                format!("Cardinality: `{}`", cardinality_name(schema.cardinality)),
+                format!(
+                    "On target delete: `{}`",
+                    on_delete_name(schema.on_target_delete)
+                ),
+                format!("Storage: `{}`", storage_name(schema.storage)),
+            ];
+            if schema.acyclic {
+                details.push("Acyclic: links that would form a cycle are rejected".to_string());
+            }
+            Entry {
+                name: keyword_name(schema.name, interner),
+                doc: None,
+                details,
+                code: Vec::new(),
+            }
+        })
+        .collect();
+    relationships.sort_by(|a, b| a.name.cmp(&b.name));
+    Section {
+        title: "Relationships".to_string(),
+        entries: relationships,
+    }
+}
+
+/// One section per rule namespace, in namespace order.
+fn rule_sections(session: &Session, interner: &Interner) -> Vec<Section> {
+    let mut namespaces: BTreeMap<String, Vec<Entry>> = BTreeMap::new();
+    for rule in session.compiled_rules() {
+        let name = keyword_name(rule.name, interner);
+        let namespace = name
+            .trim_start_matches(':')
+            .rsplit_once('/')
+            .map_or(GLOBAL_NAMESPACE, |(ns, _)| ns)
+            .to_string();
+
+        let mut details = Vec::new();
+        if rule.salience != 0 {
+            details.push(format!("Salience: {}", rule.salience));
+        }
+        if let Some(group) = rule.group {
+            details.push(format!("Group: `{}`", keyword_name(group, interner)));
+        }
+        if rule.once {
+            details.push("Fires at most once per tick.".to_string());
+        }
+        if !rule.enabled {
+            details.push("Disabled.".to_string());
+        }
+
+        let code = rule
+            .pattern
+            .clauses
+            .iter()
+            .map(|clause| clause_source(clause, interner))
+            .chain(
+                rule.pattern
+                    .negations
+                    .iter()
+                    .map(|clause| format!("(not {})", clause_source(clause, interner))),
+            )
+            .chain(rule.pattern.not_joins.iter().map(|not_join| {
+                let vars: Vec<String> =
+                    not_join.join_vars.iter().map(|v| format!("?{v}")).collect();
+                let clauses: Vec<String> = not_join
+                    .clauses
+                    .iter()
+                    .map(|clause| clause_source(clause, interner))
+                    .collect();
+                format!("(not-join [{}] {})", vars.join(" "), clauses.join(" "))
+            }))
+            .chain(rule.pattern.disjunctions.iter().map(|disjunction| {
+                let branches: Vec<String> = disjunction
+                    .branches
+                    .iter()
+                    .map(|branch| match branch.as_slice() {
+                        [clause] => clause_source(clause, interner),
+                        clauses => {
+                            let clauses: Vec<String> = clauses
+                                .iter()
+                                .map(|clause| clause_source(clause, interner))
+                                .collect();
+                            format!("(and {})", clauses.join(" "))
+                        }
+                    })
+                    .collect();
+                format!("(or {})", branches.join(" "))
+            }))
+            .collect();
+
+        namespaces.entry(namespace).or_default().push(Entry {
+            name,
+            doc: rule.doc.clone(),
+            details,
+            code,
+        });
+    }
+    namespaces
+        .into_iter()
+        .map(|(namespace, mut entries)| {
+            entries.sort_by(|a, b| a.name.cmp(&b.name));
+            Section {
+                title: format!("Rules: {namespace}"),
+                entries,
+            }
+        })
+        .collect()
+}
+
+fn command_section(session: &Session, interner: &Interner) -> Section {
+    let mut commands: Vec<Entry> = session
+        .compiled_syntaxes()
+        .iter()
+        .map(|syntax| {
+            let pattern: Vec<String> = syntax
+                .elements
+                .iter()
+                .map(|element| element_source(element, interner))
+                .collect();
+            let mut details = vec![format!(
+                "Action: `{}`",
+                keyword_name(syntax.action, interner)
+            )];
+            if syntax.priority != 0 {
+                details.push(format!("Priority: {}", syntax.priority));
+            }
+            Entry {
+                name: keyword_name(syntax.command, interner),
+                doc: None,
+                details,
+                code: vec![pattern.join(" ")],
+            }
+        })
+        .collect();
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    Section {
+        title: "Commands".to_string(),
+        entries: commands,
+    }
+}
+
+// =============================================================================
+// Naming helpers
+// =============================================================================
+
+fn keyword_name(kw: KeywordId, interner: &Interner) -> String {
+    interner
+        .get_keyword(kw)
+        .map_or_else(|| format!("{kw:?}"), |name| format!(":{name}"))
+}
+
+fn value_name(value: &Value, interner: &Interner) -> String {
+    match value {
+        Value::Keyword(kw) => keyword_name(*kw, interner),
+        other => other.to_string(),
+    }
+}
+
+fn clause_source(clause: &CompiledClause, interner: &Interner) -> String {
+    let value = match &clause.binding {
+        CompiledBinding::Variable(var) => format!("?{var}"),
+        CompiledBinding::Literal(value) => value_name(value, interner),
+        CompiledBinding::Wildcard => "_".to_string(),
+    };
+    let component = keyword_name(clause.component, interner);
+    // Global clauses are written without an entity: [:kw ?v]
+    if clause.entity_var == "__global__" {
+        format!("[{component} {value}]")
+    } else {
+        format!("[?{} {component} {value}]", clause.entity_var)
+    }
+}
+
+fn element_source(element: &CompiledSyntaxElement, interner: &Interner) -> String {
+    let word = |kw: KeywordId| interner.get_keyword(kw).unwrap_or("?").to_string();
+    let slot = |var: &str, ty: Option<KeywordId>| match ty {
+        Some(ty) => format!("?{var}:{}", word(ty)),
+        None => format!("?{var}"),
+    };
+    match element {
+        CompiledSyntaxElement::Verb(kw) | CompiledSyntaxElement::Preposition(kw) => word(*kw),
+        CompiledSyntaxElement::Literal(text) => text.clone(),
+        CompiledSyntaxElement::Noun {
+            var,
+            type_constraint,
+        } => slot(var, *type_constraint),
+        CompiledSyntaxElement::OptionalNoun {
+            var,
+            type_constraint,
+        } => format!("[{}]", slot(var, *type_constraint)),
+        CompiledSyntaxElement::Direction { var } => format!("?{var}:direction"),
+        CompiledSyntaxElement::Text { var } => format!("?{var}:text"),
+        CompiledSyntaxElement::Topic { var } => format!("?{var}:topic"),
+    }
+}
+
+const fn cardinality_name(cardinality: Cardinality) -> &'static str {
+    match cardinality {
+        Cardinality::OneToOne => "one-to-one",
+        Cardinality::ManyToOne => "many-to-one",
+        Cardinality::OneToMany => "one-to-many",
+        Cardinality::ManyToMany => "many-to-many",
+    }
+}
+
+const fn on_delete_name(on_delete: OnDelete) -> &'static str {
+    match on_delete {
+        OnDelete::Remove => "remove",
+        OnDelete::Cascade => "cascade",
+        OnDelete::Nullify => "nullify",
+    }
+}
+
+const fn storage_name(storage: Storage) -> &'static str {
+    match storage {
+        Storage::Field => "field",
+        Storage::Entity => "entity",
+    }
+}
+
+// =============================================================================
+// Rendering
+// =============================================================================
+
+fn render_markdown(sections: &[Section]) -> String {
+    let mut out = String::from("# Reference\n");
+    for section in sections {
+        let _ = write!(out, "\n## {}\n", section.title);
+        if section.entries.is_empty() {
+            out.push_str("\n_None._\n");
+        }
+        for entry in &section.entries {
+            let _ = write!(out, "\n### `{}`\n", entry.name);
+            if let Some(doc) = &entry.doc {
+                let _ = write!(out, "\n{doc}\n");
+            }
+            if !entry.details.is_empty() {
+                out.push('\n');
+                for detail in &entry.details {
+                    let _ = writeln!(out, "- {detail}");
+                }
+            }
+            if !entry.code.is_empty() {
+                let _ = write!(out, "\n```clojure\n{}\n```\n", entry.code.join("\n"));
+            }
+        }
+    }
+    out
+}
+
+fn render_html(sections: &[Section]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Reference</title>\n</head>\n<body>\n<h1>Reference</h1>\n",
+    );
+    for section in sections {
+        let _ = writeln!(out, "<h2>{}</h2>", escape_html(&section.title));
+        if section.entries.is_empty() {
+            out.push_str("<p><em>None.</em></p>\n");
+        }
+        for entry in &section.entries {
+            let _ = writeln!(out, "<h3><code>{}</code></h3>", escape_html(&entry.name));
+            if let Some(doc) = &entry.doc {
+                let _ = writeln!(out, "<p>{}</p>", escape_html(doc));
+            }
+            if !entry.details.is_empty() {
+                out.push_str("<ul>\n");
+                for detail in &entry.details {
+                    let _ = writeln!(out, "<li>{}</li>", inline_code_html(detail));
+                }
+                out.push_str("</ul>\n");
+            }
+            if !entry.code.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "<pre><code>{}</code></pre>",
+                    escape_html(&entry.code.join("\n"))
+                );
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escapes `text` and turns Markdown-style `code` spans into `<code>` elements.
+fn inline_code_html(text: &str) -> String {
+    escape_html(text)
+        .split('`')
+        .enumerate()
+        .map(|(i, part)| {
+            if i % 2 == 1 {
+                format!("<code>{part}</code>")
+            } else {
+                part.to_string()
+            }
+        })
+        .collect()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_engine::{CompiledPattern, CompiledRule};
+    use longtable_foundation::Type;
+    use longtable_parser::CompiledSyntax;
+    use longtable_storage::schema::{ComponentSchema, FieldSchema, RelationshipSchema};
+
+    fn session() -> Session {
+        let mut session = Session::new();
+        let world = session.world_mut();
+        let health = world.interner_mut().intern_keyword("health");
+        let current = world.interner_mut().intern_keyword("current");
+        let max = world.interner_mut().intern_keyword("max");
+        let in_room = world.interner_mut().intern_keyword("in-room");
+        let rule_name = world.interner_mut().intern_keyword("combat/regen");
+        let command = world.interner_mut().intern_keyword("take");
+        let thing = world.interner_mut().intern_keyword("thing");
+
+        let world = world
+            .register_component(
+                ComponentSchema::new(health)
+                    .with_field(FieldSchema::required(current, Type::Int))
+                    .with_field(FieldSchema::optional(max, Type::Int, Value::Int(100))),
+            )
+            .unwrap()
+            .register_relationship(
+                RelationshipSchema::new(in_room).with_cardinality(Cardinality::ManyToOne),
+            )
+            .unwrap();
+        session.set_world(world);
+
+        let pattern = CompiledPattern {
+            clauses: vec![CompiledClause {
+                entity_var: "e".to_string(),
+                component: health,
+                binding: CompiledBinding::Variable("hp".to_string()),
+            }],
+            negations: Vec::new(),
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
+        };
+        session
+            .add_compiled_rule(
+                CompiledRule::new(rule_name, pattern)
+                    .with_doc(Some("Heals a little every tick.".to_string()))
+                    .into(),
+            )
+            .unwrap();
+        session.add_compiled_syntax(CompiledSyntax {
+            command,
+            action: command,
+            elements: vec![
+                CompiledSyntaxElement::Verb(command),
+                CompiledSyntaxElement::Noun {
+                    var: "obj".to_string(),
+                    type_constraint: Some(thing),
+                },
+            ],
+            priority: 0,
+        });
+        session
+    }
+
+    #[test]
+    fn markdown_reference_covers_registries() {
+        let doc = generate(&session(), DocFormat::Markdown);
+
+        assert!(doc.contains("### `:health`"), "{doc}");
+        assert!(doc.contains("`:current` : `int` (required)"), "{doc}");
+        assert!(doc.contains("`:max` : `int` (default `100`)"), "{doc}");
+        assert!(doc.contains("Cardinality: `many-to-one`"), "{doc}");
+        assert!(doc.contains("## Rules: combat"), "{doc}");
+        assert!(doc.contains("Heals a little every tick."), "{doc}");
+        assert!(doc.contains("[?e :health ?hp]"), "{doc}");
+        assert!(doc.contains("take ?obj:thing"), "{doc}");
+    }
+
+    #[test]
+    fn html_reference_is_escaped() {
+        let doc = generate(&session(), DocFormat::Html);
+        assert!(doc.starts_with("<!DOCTYPE html>"));
+        assert!(doc.contains("<h2>Rules: combat</h2>"));
+        assert!(doc.contains("<li>Cardinality: <code>many-to-one</code></li>"));
+        assert_eq!(escape_html("<a & b>"), "&lt;a &amp; b&gt;");
+    }
+}
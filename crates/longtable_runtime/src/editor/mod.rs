@@ -0,0 +1,84 @@
+//! Line editor abstraction for the REPL.
+//!
+//! This module provides a trait-based abstraction over line editing libraries,
+//! allowing the REPL to use rustyline while remaining swappable. The
+//! rustyline editor needs a terminal and is only built with the `cli`
+//! feature; without it the REPL defaults to [`HeadlessEditor`].
+
+#[cfg(feature = "cli")]
+mod terminal;
+
+#[cfg(feature = "cli")]
+pub use terminal::RustylineEditor;
+
+use longtable_foundation::Result;
+
+/// Result of reading a line from the editor.
+#[derive(Debug)]
+pub enum ReadResult {
+    /// A line was successfully read.
+    Line(String),
+    /// User pressed Ctrl+C.
+    Interrupted,
+    /// User pressed Ctrl+D (EOF).
+    Eof,
+}
+
+/// Abstraction over line editing functionality.
+///
+/// This trait allows swapping out the underlying line editor implementation
+/// (e.g., from rustyline to reedline) without changing the REPL code.
+pub trait LineEditor {
+    /// Read a line with the given prompt.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from the terminal fails.
+    fn read_line(&mut self, prompt: &str) -> Result<ReadResult>;
+
+    /// Read a continuation line (for multi-line input).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from the terminal fails.
+    fn read_continuation(&mut self, prompt: &str) -> Result<ReadResult>;
+
+    /// Add a line to history.
+    fn add_history(&mut self, line: &str);
+
+    /// Set the session's completion candidates (special forms, declared
+    /// names), in addition to the language syntax the editor knows itself.
+    fn set_keywords(&mut self, keywords: Vec<String>);
+}
+
+/// A line editor with no input: every read reports end of input.
+///
+/// Used where there is no terminal, such as the WASM build, where input
+/// arrives through [`Repl::eval`](crate::Repl::eval) and
+/// [`Repl::input`](crate::Repl::input) instead of an interactive loop.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HeadlessEditor;
+
+impl LineEditor for HeadlessEditor {
+    fn read_line(&mut self, _prompt: &str) -> Result<ReadResult> {
+        Ok(ReadResult::Eof)
+    }
+
+    fn read_continuation(&mut self, _prompt: &str) -> Result<ReadResult> {
+        Ok(ReadResult::Eof)
+    }
+
+    fn add_history(&mut self, _line: &str) {}
+
+    fn set_keywords(&mut self, _keywords: Vec<String>) {}
+}
+
+/// The editor [`Repl::new`](crate::Repl::new) uses: rustyline with the
+/// `cli` feature, otherwise [`HeadlessEditor`].
+#[cfg(feature = "cli")]
+pub type DefaultEditor = RustylineEditor;
+
+/// The editor [`Repl::new`](crate::Repl::new) uses: rustyline with the
+/// `cli` feature, otherwise [`HeadlessEditor`].
+#[cfg(not(feature = "cli"))]
+pub type DefaultEditor = HeadlessEditor;
@@ -1,8 +1,9 @@
-//! Line editor abstraction for the REPL.
+//! Terminal line editor built on rustyline.
 //!
-//! This module provides a trait-based abstraction over line editing libraries,
-//! allowing the REPL to use rustyline while remaining swappable.
+//! Only compiled with the `cli` feature; embedders that drive the REPL
+//! programmatically (tests, the WASM build) do not need a terminal.
 
+use super::{LineEditor, ReadResult};
 use crate::highlight::LongtableHighlighter;
 use longtable_foundation::{Error, ErrorKind, Result};
 use rustyline::completion::{Completer, FilenameCompleter, Pair};
@@ -14,43 +15,6 @@ use rustyline::validate::{ValidationContext, ValidationResult, Validator};
 use rustyline::{Completer, Config, Context, Editor, Helper, Hinter, Validator as RLValidator};
 use std::borrow::Cow;
 
-/// Result of reading a line from the editor.
-#[derive(Debug)]
-pub enum ReadResult {
-    /// A line was successfully read.
-    Line(String),
-    /// User pressed Ctrl+C.
-    Interrupted,
-    /// User pressed Ctrl+D (EOF).
-    Eof,
-}
-
-/// Abstraction over line editing functionality.
-///
-/// This trait allows swapping out the underlying line editor implementation
-/// (e.g., from rustyline to reedline) without changing the REPL code.
-pub trait LineEditor {
-    /// Read a line with the given prompt.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if reading from the terminal fails.
-    fn read_line(&mut self, prompt: &str) -> Result<ReadResult>;
-
-    /// Read a continuation line (for multi-line input).
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if reading from the terminal fails.
-    fn read_continuation(&mut self, prompt: &str) -> Result<ReadResult>;
-
-    /// Add a line to history.
-    fn add_history(&mut self, line: &str);
-
-    /// Set available completions for keywords.
-    fn set_keywords(&mut self, keywords: Vec<String>);
-}
-
 /// Helper for rustyline that provides completion, hints, highlighting, and validation.
 #[derive(Helper, Completer, Hinter, RLValidator)]
 struct LongtableHelper {
@@ -90,6 +54,10 @@ impl Highlighter for LongtableHelper {
 }
 
 /// Completer for Longtable keywords and file paths.
+///
+/// Completes the language's own syntax plus whatever the REPL last passed to
+/// [`LineEditor::set_keywords`]: special forms and the names declared in the
+/// session, refreshed before every prompt.
 struct LongtableCompleter {
     file_completer: FilenameCompleter,
     keywords: Vec<String>,
@@ -103,21 +71,21 @@ impl LongtableCompleter {
         }
     }
 
+    #[allow(clippy::too_many_lines)]
     fn default_keywords() -> Vec<String> {
         vec![
-            // Special forms
-            "def".into(),
+            // Language forms (REPL special forms come from the REPL)
             "fn".into(),
             "let".into(),
             "if".into(),
             "do".into(),
             "quote".into(),
-            "load".into(),
-            "query".into(),
+            "peek".into(),
             // Declarations
             "component:".into(),
             "relationship:".into(),
             "rule:".into(),
+            "rule-group:".into(),
             "derived:".into(),
             "constraint:".into(),
             // Declaration keywords
@@ -128,14 +96,17 @@ impl LongtableCompleter {
             ":return".into(),
             ":salience".into(),
             ":once".into(),
+            ":refraction".into(),
             ":enabled".into(),
             ":for".into(),
             ":value".into(),
             ":check".into(),
             ":on-violation".into(),
+            ":penalty".into(),
             ":storage".into(),
             ":cardinality".into(),
             ":on-target-delete".into(),
+            ":acyclic".into(),
             ":attributes".into(),
             ":default".into(),
             ":aggregate".into(),
@@ -150,6 +121,8 @@ impl LongtableCompleter {
             ":keyword".into(),
             ":symbol".into(),
             ":entity-ref".into(),
+            ":instant".into(),
+            ":duration".into(),
             ":map".into(),
             ":vec".into(),
             ":set".into(),
@@ -203,16 +176,23 @@ impl LongtableCompleter {
             "type".into(),
             "print!".into(),
             "spawn!".into(),
+            "spawn-many!".into(),
             "destroy!".into(),
+            "emit!".into(),
+            "schedule!".into(),
             "set!".into(),
             "link!".into(),
             "unlink!".into(),
         ]
     }
 
-    #[allow(dead_code)]
+    /// Replaces the session-provided candidates, keeping the syntax ones.
     fn set_keywords(&mut self, keywords: Vec<String>) {
-        self.keywords = keywords;
+        let mut all = Self::default_keywords();
+        all.extend(keywords);
+        all.sort();
+        all.dedup();
+        self.keywords = all;
     }
 }
 
@@ -345,7 +325,7 @@ impl LineEditor for RustylineEditor {
 
     fn set_keywords(&mut self, keywords: Vec<String>) {
         if let Some(helper) = self.editor.helper_mut() {
-            helper.completer.keywords = keywords;
+            helper.completer.set_keywords(keywords);
         }
     }
 }
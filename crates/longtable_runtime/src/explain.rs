@@ -0,0 +1,379 @@
+//! Explanations as data.
+//!
+//! `(why ...)` and `(explain-query ...)` print their findings for people.
+//! With `:data true` they return the same findings as [`Value`] maps
+//! instead, built by the functions here, so tests and tools can inspect
+//! them without parsing text.
+//!
+//! A `why` explanation looks like:
+//!
+//! ```clojure
+//! {:entity e :component :health
+//!  :status :found            ;; or :unwritten, :unknown
+//!  :chain [{:entity e :component :health :rule :regen :tick 3
+//!           :value v :previous v :context {"e" e}} ...]
+//!  :truncated false}
+//! ```
+//!
+//! `(why e :exists)` and `(why a :rel b)` describe spawns and links:
+//!
+//! ```clojure
+//! {:entity e :exists true :status :found
+//!  :cause {:change :spawned :rule :forge :tick 2 :context {"smith" s}}}
+//!
+//! {:source a :relationship :contains :target b :linked false :status :found
+//!  :history [{:change :unlinked :rule :take :tick 6 :context {...}}
+//!            {:change :linked :rule :drop :tick 4 :context {...}}]}
+//! ```
+//!
+//! and a query explanation like:
+//!
+//! ```clojure
+//! {:clauses 2 :results 1
+//!  :clause-stats [{:index 0 :clause "[?e :health ?h]" :matches 3} ...]
+//!  :warnings [{:code :high-fan-out :message "..."}]
+//!  :entity {:entity e :matched false :failed-clause 1
+//!           :reason {:kind :missing-component :component :armor}}}
+//! ```
+
+use longtable_debug::{CausalLink, StructuralCause, StructuralChange, WhyResult};
+use longtable_engine::{
+    CompiledBinding, CompiledClause, CompiledPattern, CompiledQuery, EntityMatchResult,
+    MatchFailure, PatternMatcher, QueryWarning,
+};
+use longtable_foundation::{EntityId, Interner, KeywordId, LtMap, LtVec, Result, Value};
+use longtable_storage::World;
+
+/// Builds a map from `(key, value)` pairs, interning the keys as keywords.
+fn record(interner: &mut Interner, fields: Vec<(&str, Value)>) -> Value {
+    let map = fields.into_iter().fold(LtMap::new(), |map, (key, value)| {
+        map.insert(Value::Keyword(interner.intern_keyword(key)), value)
+    });
+    Value::Map(map)
+}
+
+fn keyword(interner: &mut Interner, name: &str) -> Value {
+    Value::Keyword(interner.intern_keyword(name))
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn int(n: usize) -> Value {
+    Value::Int(n as i64)
+}
+
+/// Describes a `why` result for `entity`'s `component`.
+#[must_use]
+pub fn why_value(
+    result: &WhyResult,
+    entity: EntityId,
+    component: KeywordId,
+    interner: &mut Interner,
+) -> Value {
+    let (status, links, truncated): (&str, &[CausalLink], bool) = match result {
+        WhyResult::Unknown => ("unknown", &[], false),
+        WhyResult::Single(None) => ("unwritten", &[], false),
+        WhyResult::Single(Some(link)) => ("found", std::slice::from_ref(link), false),
+        WhyResult::Chain(chain) => ("found", &chain.links, chain.truncated),
+    };
+    let chain: LtVec<Value> = links.iter().map(|l| link_value(l, interner)).collect();
+    let status = keyword(interner, status);
+    record(
+        interner,
+        vec![
+            ("entity", Value::EntityRef(entity)),
+            ("component", Value::Keyword(component)),
+            ("status", status),
+            ("chain", Value::Vec(chain)),
+            ("truncated", Value::Bool(truncated)),
+        ],
+    )
+}
+
+fn link_value(link: &CausalLink, interner: &mut Interner) -> Value {
+    let context = link
+        .context
+        .iter()
+        .fold(LtMap::new(), |map, (var, entity)| {
+            map.insert(Value::from(var.as_str()), Value::EntityRef(*entity))
+        });
+    let tick = Value::Int(i64::try_from(link.tick).unwrap_or(i64::MAX));
+    let mut fields = vec![
+        ("entity", Value::EntityRef(link.entity)),
+        ("component", Value::Keyword(link.component)),
+        ("rule", Value::Keyword(link.rule)),
+        ("tick", tick),
+        ("context", Value::Map(context)),
+    ];
+    if let Some(value) = &link.value {
+        fields.push(("value", value.clone()));
+    }
+    if let Some(previous) = &link.previous_value {
+        fields.push(("previous", previous.clone()));
+    }
+    record(interner, fields)
+}
+
+/// Describes what spawned `entity`, if anything was recorded.
+#[must_use]
+pub fn exists_value(
+    entity: EntityId,
+    exists: bool,
+    cause: Option<&StructuralCause>,
+    interner: &mut Interner,
+) -> Value {
+    let status = keyword(interner, if cause.is_some() { "found" } else { "unknown" });
+    let mut fields = vec![
+        ("entity", Value::EntityRef(entity)),
+        ("exists", Value::Bool(exists)),
+        ("status", status),
+    ];
+    if let Some(cause) = cause {
+        fields.push(("cause", structural_value(cause, interner)));
+    }
+    record(interner, fields)
+}
+
+/// Describes the recorded changes to a relationship edge, most recent first.
+#[must_use]
+pub fn link_history_value(
+    source: EntityId,
+    relationship: KeywordId,
+    target: EntityId,
+    linked: bool,
+    causes: &[StructuralCause],
+    interner: &mut Interner,
+) -> Value {
+    let status = keyword(
+        interner,
+        if causes.is_empty() {
+            "unknown"
+        } else {
+            "found"
+        },
+    );
+    let history: LtVec<Value> = causes
+        .iter()
+        .map(|c| structural_value(c, interner))
+        .collect();
+    record(
+        interner,
+        vec![
+            ("source", Value::EntityRef(source)),
+            ("relationship", Value::Keyword(relationship)),
+            ("target", Value::EntityRef(target)),
+            ("linked", Value::Bool(linked)),
+            ("status", status),
+            ("history", Value::Vec(history)),
+        ],
+    )
+}
+
+fn structural_value(cause: &StructuralCause, interner: &mut Interner) -> Value {
+    let change = match cause.change {
+        StructuralChange::Spawned => "spawned",
+        StructuralChange::Linked => "linked",
+        StructuralChange::Unlinked => "unlinked",
+    };
+    let change = keyword(interner, change);
+    let context = cause
+        .context
+        .iter()
+        .fold(LtMap::new(), |map, (var, entity)| {
+            map.insert(Value::from(var.as_str()), Value::EntityRef(*entity))
+        });
+    let tick = Value::Int(i64::try_from(cause.tick).unwrap_or(i64::MAX));
+    record(
+        interner,
+        vec![
+            ("change", change),
+            ("rule", Value::Keyword(cause.rule)),
+            ("tick", tick),
+            ("context", Value::Map(context)),
+        ],
+    )
+}
+
+/// How many binding sets survive each clause of a query's pattern, in order.
+///
+/// Entry `i` counts the matches of clauses `0..=i`; disjunctions and
+/// negations are applied only to the last entry, as they are during
+/// execution.
+///
+/// # Errors
+///
+/// Returns an error if one of the pattern's predicates fails to evaluate.
+pub fn clause_match_counts(pattern: &CompiledPattern, world: &World) -> Result<Vec<usize>> {
+    (1..=pattern.clauses.len())
+        .map(|n| {
+            let prefix = CompiledPattern {
+                clauses: pattern.clauses[..n].to_vec(),
+                negations: if n == pattern.clauses.len() {
+                    pattern.negations.clone()
+                } else {
+                    Vec::new()
+                },
+                not_joins: if n == pattern.clauses.len() {
+                    pattern.not_joins.clone()
+                } else {
+                    Vec::new()
+                },
+                disjunctions: if n == pattern.clauses.len() {
+                    pattern.disjunctions.clone()
+                } else {
+                    Vec::new()
+                },
+                // Predicates count once the prefix binds all their variables
+                predicates: if n == pattern.clauses.len() {
+                    pattern.predicates.clone()
+                } else {
+                    let bound: Vec<&str> = pattern.clauses[..n]
+                        .iter()
+                        .flat_map(|clause| {
+                            let value = match &clause.binding {
+                                CompiledBinding::Variable(var) => Some(var.as_str()),
+                                _ => None,
+                            };
+                            std::iter::once(clause.entity_var.as_str()).chain(value)
+                        })
+                        .collect();
+                    pattern
+                        .predicates
+                        .iter()
+                        .filter(|p| p.vars.iter().all(|var| bound.contains(&var.as_str())))
+                        .cloned()
+                        .collect()
+                },
+            };
+            Ok(PatternMatcher::match_pattern(&prefix, world)?.len())
+        })
+        .collect()
+}
+
+/// Renders a clause the way it was written, e.g. `[?e :health ?hp]`.
+#[must_use]
+pub fn clause_text(clause: &CompiledClause, interner: &Interner) -> String {
+    let component = interner.get_keyword(clause.component).unwrap_or("?");
+    let binding = match &clause.binding {
+        CompiledBinding::Variable(var) => format!("?{var}"),
+        CompiledBinding::Literal(value) => value.to_string(),
+        CompiledBinding::Wildcard => "_".to_string(),
+    };
+    format!("[?{} :{component} {binding}]", clause.entity_var)
+}
+
+/// Describes a query's execution: clause statistics, result count, warnings,
+/// and, if given, why one entity did or didn't match.
+#[must_use]
+pub fn query_value(
+    query: &CompiledQuery,
+    result_count: usize,
+    clause_counts: &[usize],
+    entity: Option<&EntityMatchResult>,
+    interner: &mut Interner,
+) -> Value {
+    let stats: LtVec<Value> = query
+        .pattern
+        .clauses
+        .iter()
+        .zip(clause_counts)
+        .enumerate()
+        .map(|(i, (clause, &matches))| {
+            let text = clause_text(clause, interner);
+            record(
+                interner,
+                vec![
+                    ("index", int(i)),
+                    ("clause", Value::from(text.as_str())),
+                    ("matches", int(matches)),
+                ],
+            )
+        })
+        .collect();
+    let warnings: LtVec<Value> = query
+        .warnings
+        .iter()
+        .map(|w| warning_value(w, interner))
+        .collect();
+
+    let mut fields = vec![
+        ("clauses", int(query.pattern.clauses.len())),
+        ("results", int(result_count)),
+        ("clause-stats", Value::Vec(stats)),
+        ("warnings", Value::Vec(warnings)),
+    ];
+    if let Some(entity) = entity {
+        fields.push(("entity", entity_match_value(entity, interner)));
+    }
+    record(interner, fields)
+}
+
+/// Describes a query warning as `{:code :message}`.
+#[must_use]
+pub fn warning_value(warning: &QueryWarning, interner: &mut Interner) -> Value {
+    let code = keyword(interner, warning.code());
+    record(
+        interner,
+        vec![
+            ("code", code),
+            ("message", Value::from(warning.to_string().as_str())),
+        ],
+    )
+}
+
+/// Describes why an entity did or didn't match a pattern.
+#[must_use]
+pub fn entity_match_value(result: &EntityMatchResult, interner: &mut Interner) -> Value {
+    let bindings = result
+        .partial_bindings
+        .iter()
+        .fold(LtMap::new(), |map, (var, value)| {
+            map.insert(Value::from(var.as_str()), value.clone())
+        });
+    let mut fields = vec![
+        ("entity", Value::EntityRef(result.entity)),
+        ("matched", Value::Bool(result.matched)),
+        ("bindings", Value::Map(bindings)),
+    ];
+    if let Some(clause) = result.failed_at_clause {
+        fields.push(("failed-clause", int(clause)));
+    }
+    if let Some(reason) = &result.failure_reason {
+        fields.push(("reason", failure_value(reason, interner)));
+    }
+    record(interner, fields)
+}
+
+fn failure_value(reason: &MatchFailure, interner: &mut Interner) -> Value {
+    let (kind, mut fields) = match reason {
+        MatchFailure::MissingComponent { component } => (
+            "missing-component",
+            vec![("component", Value::Keyword(*component))],
+        ),
+        MatchFailure::ValueMismatch { expected, actual } => (
+            "value-mismatch",
+            vec![("expected", expected.clone()), ("actual", actual.clone())],
+        ),
+        MatchFailure::UnificationFailure {
+            var,
+            expected,
+            actual,
+        } => (
+            "unification-failure",
+            vec![
+                ("variable", Value::from(var.as_str())),
+                ("expected", expected.clone()),
+                ("actual", actual.clone()),
+            ],
+        ),
+        MatchFailure::NegationMatched { component } => (
+            "negation-matched",
+            vec![("component", Value::Keyword(*component))],
+        ),
+        MatchFailure::GuardFailed { guard_index } => {
+            ("guard-failed", vec![("guard", int(*guard_index))])
+        }
+        MatchFailure::EntityNotFound => ("entity-not-found", Vec::new()),
+    };
+    fields.insert(0, ("kind", keyword(interner, kind)));
+    record(interner, fields)
+}
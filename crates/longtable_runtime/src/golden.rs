@@ -0,0 +1,358 @@
+//! Golden transcripts: recorded play sessions as regression tests.
+//!
+//! A transcript is plain text. Lines starting with `> ` are player input;
+//! the lines after each one, up to the next input, are the output it is
+//! expected to produce. Text before the first input is what loading the
+//! game prints:
+//!
+//! ```text
+//! A damp cave stretches into darkness.
+//!
+//! > take lamp
+//! Taken.
+//!
+//! > go north
+//! It is pitch dark.
+//! ```
+//!
+//! `longtable test game/ --transcripts tests/*.txt` loads the game afresh
+//! for each transcript, feeds it the recorded input, and prints a diff for
+//! every exchange whose output changed; `--update` rewrites the transcripts
+//! with the output the game gives now. Input runs as it does in input mode:
+//! `(forms)` are evaluated and anything else goes to the parser. Trailing
+//! whitespace and blank lines don't count as differences.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use longtable_foundation::{Error, ErrorKind, Result};
+
+use crate::editor::LineEditor;
+use crate::repl::Repl;
+
+/// One input and the output that follows it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Exchange {
+    /// The player's input, or `None` for the output of loading the game.
+    pub input: Option<String>,
+    /// The output, without trailing whitespace or blank lines.
+    pub output: String,
+    /// Line of the transcript the exchange starts on (1-based).
+    pub line: usize,
+}
+
+/// A recorded play session.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GoldenTranscript {
+    /// The exchanges, in order.
+    pub exchanges: Vec<Exchange>,
+}
+
+impl GoldenTranscript {
+    /// Parses a transcript's text.
+    #[must_use]
+    pub fn parse(text: &str) -> Self {
+        let mut exchanges = Vec::new();
+        let mut input = None;
+        let mut line = 1;
+        let mut output = Vec::new();
+        let mut finish = |input: Option<String>, line, lines: &mut Vec<&str>| {
+            let output = normalize(&lines.join("\n"));
+            lines.clear();
+            if input.is_some() || !output.is_empty() {
+                exchanges.push(Exchange {
+                    input,
+                    output,
+                    line,
+                });
+            }
+        };
+
+        for (number, text) in text.lines().enumerate() {
+            if let Some(next) = text.strip_prefix("> ") {
+                finish(input.take(), line, &mut output);
+                input = Some(next.trim().to_string());
+                line = number + 1;
+            } else {
+                output.push(text);
+            }
+        }
+        finish(input, line, &mut output);
+        Self { exchanges }
+    }
+
+    /// Reads and parses a transcript file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            Error::new(ErrorKind::IoError(format!(
+                "failed to read transcript '{}': {e}",
+                path.display()
+            )))
+        })?;
+        Ok(Self::parse(&text))
+    }
+
+    /// Writes the transcript as text, a blank line between exchanges.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        for (i, exchange) in self.exchanges.iter().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            if let Some(input) = &exchange.input {
+                let _ = writeln!(text, "> {input}");
+            }
+            if !exchange.output.is_empty() {
+                let _ = writeln!(text, "{}", exchange.output);
+            }
+        }
+        text
+    }
+
+    /// Returns the input exchanges, skipping the opening output.
+    fn inputs(&self) -> impl Iterator<Item = (&str, &Exchange)> {
+        self.exchanges
+            .iter()
+            .filter_map(|exchange| Some((exchange.input.as_deref()?, exchange)))
+    }
+}
+
+/// An exchange whose output changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Line of the transcript the exchange starts on.
+    pub line: usize,
+    /// The input, or `None` for the opening output.
+    pub input: Option<String>,
+    /// The recorded output.
+    pub expected: String,
+    /// The output the game gave.
+    pub actual: String,
+}
+
+impl Mismatch {
+    /// Returns the change as lines marked `-` (recorded) and `+` (actual).
+    #[must_use]
+    pub fn diff(&self) -> String {
+        let expected: Vec<&str> = self.expected.lines().collect();
+        let actual: Vec<&str> = self.actual.lines().collect();
+
+        // Longest common subsequence, so unchanged lines line up
+        let mut common = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+        for i in (0..expected.len()).rev() {
+            for j in (0..actual.len()).rev() {
+                common[i][j] = if expected[i] == actual[j] {
+                    common[i + 1][j + 1] + 1
+                } else {
+                    common[i + 1][j].max(common[i][j + 1])
+                };
+            }
+        }
+
+        let mut diff = String::new();
+        let (mut i, mut j) = (0, 0);
+        while i < expected.len() || j < actual.len() {
+            if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+                let _ = writeln!(diff, "  {}", expected[i]);
+                i += 1;
+                j += 1;
+            } else if i < expected.len()
+                && (j == actual.len() || common[i + 1][j] >= common[i][j + 1])
+            {
+                let _ = writeln!(diff, "- {}", expected[i]);
+                i += 1;
+            } else {
+                let _ = writeln!(diff, "+ {}", actual[j]);
+                j += 1;
+            }
+        }
+        diff
+    }
+}
+
+/// The result of replaying a transcript.
+#[derive(Clone, Debug, Default)]
+pub struct GoldenRun {
+    /// The session as the game plays it now, for `--update`.
+    pub actual: GoldenTranscript,
+    /// The exchanges whose output changed.
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl GoldenRun {
+    /// Returns true if every exchange matched.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Feeds a transcript's input to a loaded game and compares the output.
+///
+/// The REPL must capture its output (see [`Repl::with_captured_output`]);
+/// whatever it holds when called is taken as the opening output. An input
+/// that fails records its error in place of further output.
+pub fn replay<E: LineEditor>(repl: &mut Repl<E>, expected: &GoldenTranscript) -> GoldenRun {
+    let mut run = GoldenRun::default();
+    let mut compare = |exchange: Exchange, recorded: Option<&Exchange>| {
+        let expected = recorded.map_or("", |e| e.output.as_str());
+        if exchange.output != expected {
+            run.mismatches.push(Mismatch {
+                line: recorded.map_or(1, |e| e.line),
+                input: exchange.input.clone(),
+                expected: expected.to_string(),
+                actual: exchange.output.clone(),
+            });
+        }
+        if exchange.input.is_some() || !exchange.output.is_empty() {
+            run.actual.exchanges.push(exchange);
+        }
+    };
+
+    let opening = expected
+        .exchanges
+        .first()
+        .filter(|exchange| exchange.input.is_none());
+    let output = normalize(&repl.take_output());
+    compare(
+        Exchange {
+            input: None,
+            output,
+            line: 1,
+        },
+        opening,
+    );
+
+    for (input, recorded) in expected.inputs() {
+        let result = if input.starts_with('(') {
+            repl.eval(input).map(drop)
+        } else {
+            repl.input(input).map(drop)
+        };
+        let mut output = repl.take_output();
+        if let Err(e) = result {
+            let _ = writeln!(output, "Error: {e}");
+        }
+        compare(
+            Exchange {
+                input: Some(input.to_string()),
+                output: normalize(&output),
+                line: recorded.line,
+            },
+            Some(recorded),
+        );
+    }
+    run
+}
+
+/// Strips trailing whitespace from each line, and trailing blank lines.
+fn normalize(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let end = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |i| i + 1);
+    lines[..end].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::HeadlessEditor;
+
+    const TRANSCRIPT: &str = "Welcome.
+
+> (say \"hello\")
+hello
+
+> look
+No player entity found.
+";
+
+    #[test]
+    fn parses_and_renders_transcripts() {
+        let transcript = GoldenTranscript::parse(TRANSCRIPT);
+        let inputs: Vec<_> = transcript
+            .inputs()
+            .map(|(input, e)| (input, e.line))
+            .collect();
+        assert_eq!(inputs, vec![("(say \"hello\")", 3), ("look", 6)]);
+        assert_eq!(transcript.exchanges[0].output, "Welcome.");
+        assert_eq!(transcript.render(), TRANSCRIPT);
+
+        // Trailing whitespace and blank lines are not part of the output
+        let sloppy = GoldenTranscript::parse("> look   \nDark.  \n\n\n> look\n");
+        assert_eq!(sloppy.render(), "> look\nDark.\n\n> look\n");
+
+        // Only "> " starts an input; a quoted reply stays output
+        let quoted = GoldenTranscript::parse("> read note\n>Beware the grue.\n");
+        assert_eq!(quoted.exchanges.len(), 1);
+        assert_eq!(quoted.exchanges[0].output, ">Beware the grue.");
+    }
+
+    #[test]
+    fn replays_transcripts_against_a_game() {
+        let mut repl = Repl::with_editor(HeadlessEditor).with_captured_output();
+        repl.eval("(println \"Welcome.\")").unwrap();
+        let run = replay(&mut repl, &GoldenTranscript::parse(TRANSCRIPT));
+        assert!(run.passed(), "{:?}", run.mismatches);
+        assert_eq!(run.actual.render(), TRANSCRIPT);
+
+        let mut repl = Repl::with_editor(HeadlessEditor).with_captured_output();
+        let changed = TRANSCRIPT.replace("hello\n\n", "goodbye\n\n");
+        let run = replay(&mut repl, &GoldenTranscript::parse(&changed));
+        let lines: Vec<_> = run.mismatches.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![1, 3]);
+        assert_eq!(run.mismatches[1].diff(), "- goodbye\n+ hello\n");
+        assert_eq!(run.actual.render(), TRANSCRIPT.replace("Welcome.\n\n", ""));
+
+        let mut repl = Repl::with_editor(HeadlessEditor).with_captured_output();
+        let run = replay(
+            &mut repl,
+            &GoldenTranscript::parse("> (no-such-function)\n"),
+        );
+        assert!(run.actual.exchanges[0].output.starts_with("Error: "));
+    }
+
+    #[test]
+    fn records_narration_from_action_handlers() {
+        let mut repl = Repl::with_editor(HeadlessEditor).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(verb: look)
+(action: look :params [actor] :handler [(say "A " [:em "dusty"] " hall.")])
+(command: look :syntax [:verb/look] :action look)
+(spawn: player :tag/player true)
+"#,
+        )
+        .unwrap();
+
+        let run = replay(
+            &mut repl,
+            &GoldenTranscript::parse(
+                "> look
+A dusty hall.
+",
+            ),
+        );
+        assert!(run.passed(), "{:?}", run.mismatches);
+        assert_eq!(run.actual.exchanges[0].output, "A dusty hall.");
+    }
+
+    #[test]
+    fn diffs_line_up_unchanged_lines() {
+        let mismatch = Mismatch {
+            line: 1,
+            input: None,
+            expected: "a\nb\nc".to_string(),
+            actual: "a\nx\nc\nd".to_string(),
+        };
+        assert_eq!(mismatch.diff(), "  a\n- b\n+ x\n  c\n+ d\n");
+    }
+}
@@ -0,0 +1,996 @@
+//! Documentation for the REPL's special forms.
+//!
+//! `(help)` lists every entry in [`SPECIAL_FORMS`] grouped by [`Area`], and
+//! `(help name)` renders one entry's usage, arguments, and examples. Both are
+//! generated from the registry, so a special form documented here shows up in
+//! help without further changes. Forms that are also [`SESSION_COMMANDS`]
+//! say so, since they work inside functions and scripts too.
+
+use std::fmt::Write as _;
+
+use longtable_language::SESSION_COMMANDS;
+
+/// The part of the system a special form works with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Area {
+    /// Variables, files, and modes.
+    Session,
+    /// Entities, ticks, content, and import/export.
+    World,
+    /// Explanations, tracing, breakpoints, and checks.
+    Debug,
+    /// History, branches, and snapshots.
+    TimeTravel,
+    /// Natural language input.
+    Parser,
+}
+
+impl Area {
+    /// Every area, in the order `(help)` lists them.
+    pub const ALL: [Area; 5] = [
+        Area::Session,
+        Area::World,
+        Area::Debug,
+        Area::TimeTravel,
+        Area::Parser,
+    ];
+
+    /// The heading `(help)` prints for this area.
+    #[must_use]
+    pub const fn title(self) -> &'static str {
+        match self {
+            Area::Session => "Session",
+            Area::World => "World",
+            Area::Debug => "Debug",
+            Area::TimeTravel => "Time travel",
+            Area::Parser => "Parser",
+        }
+    }
+}
+
+/// What `(help)` knows about one special form.
+#[derive(Debug, Clone, Copy)]
+pub struct SpecialForm {
+    /// The symbol at the head of the form, e.g. `"why"`.
+    pub name: &'static str,
+    /// Where `(help)` lists the form.
+    pub area: Area,
+    /// Each accepted shape of the form.
+    pub usage: &'static [&'static str],
+    /// One line describing what the form does.
+    pub summary: &'static str,
+    /// Argument names and what they mean.
+    pub arguments: &'static [(&'static str, &'static str)],
+    /// Example invocations.
+    pub examples: &'static [&'static str],
+}
+
+/// Every special form the REPL handles.
+pub const SPECIAL_FORMS: &[SpecialForm] = &[
+    // ==================== Session ====================
+    SpecialForm {
+        name: "help",
+        area: Area::Session,
+        usage: &["(help)", "(help name)"],
+        summary: "List special forms, or describe one",
+        arguments: &[("name", "a special form, as a symbol or string")],
+        examples: &["(help why)"],
+    },
+    SpecialForm {
+        name: "def",
+        area: Area::Session,
+        usage: &["(def name value)"],
+        summary: "Define a session variable",
+        arguments: &[
+            ("name", "symbol to bind"),
+            ("value", "expression to evaluate"),
+        ],
+        examples: &["(def hero (entity-ref 1 0))"],
+    },
+    SpecialForm {
+        name: "load",
+        area: Area::Session,
+        usage: &["(load \"path\")"],
+        summary: "Load a .lt file, or every .lt file in a directory",
+        arguments: &[("path", "file or directory, relative to the current file")],
+        examples: &["(load \"rules.lt\")"],
+    },
+    SpecialForm {
+        name: "reload",
+        area: Area::Session,
+        usage: &["(reload)", "(reload \"path\")"],
+        summary: "Reload changed source files, keeping the world",
+        arguments: &[(
+            "path",
+            "file to reload even if unchanged; otherwise every changed file",
+        )],
+        examples: &["(reload)", "(reload \"rules.lt\")"],
+    },
+    SpecialForm {
+        name: "require",
+        area: Area::Session,
+        usage: &["(require [namespace :as alias] ...)"],
+        summary: "Alias namespaces so ::alias/name keywords resolve to them",
+        arguments: &[(
+            "spec",
+            "[ns :as alias], [ns :refer [names]], or [ns]; loads ns/path.lt if present",
+        )],
+        examples: &["(require [my.game.combat :as combat])"],
+    },
+    SpecialForm {
+        name: "run",
+        area: Area::Session,
+        usage: &["(run)"],
+        summary: "Enter input mode, where lines are natural language commands",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "repl",
+        area: Area::Session,
+        usage: &["(repl)"],
+        summary: "Leave input mode and return to the REPL",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "when-feature",
+        area: Area::Session,
+        usage: &["(when-feature :feature forms...)"],
+        summary: "Evaluate forms only if a content feature is enabled",
+        arguments: &[
+            ("feature", "feature keyword, enabled with --feature"),
+            ("forms", "forms to evaluate"),
+        ],
+        examples: &["(when-feature :debug-content (load \"cheats.lt\"))"],
+    },
+    SpecialForm {
+        name: "telemetry-opt-in!",
+        area: Area::Session,
+        usage: &["(telemetry-opt-in! true|false)"],
+        summary: "Record the player's telemetry choice",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "fuzzy-matching!",
+        area: Area::Session,
+        usage: &["(fuzzy-matching! true|false)"],
+        summary: "Correct misspelled and abbreviated verbs and directions",
+        arguments: &[],
+        examples: &["(fuzzy-matching! true)"],
+    },
+    SpecialForm {
+        name: "content-ids!",
+        area: Area::Session,
+        usage: &["(content-ids! true|false)"],
+        summary: "Give entities declared with spawn: IDs hashed from their names",
+        arguments: &[],
+        examples: &["(content-ids! true)"],
+    },
+    SpecialForm {
+        name: "set-locale!",
+        area: Area::Session,
+        usage: &["(set-locale! :locale)"],
+        summary: "Look up message: templates in another locale first",
+        arguments: &[],
+        examples: &["(set-locale! :fr)"],
+    },
+    // ==================== World ====================
+    SpecialForm {
+        name: "tick!",
+        area: Area::World,
+        usage: &["(tick!)"],
+        summary: "Advance the world by one tick",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "recover!",
+        area: Area::World,
+        usage: &["(recover!)", "(recover! :keep)"],
+        summary: "Let the world tick again after a tick panicked",
+        arguments: &[(
+            ":keep",
+            "keep the world as the panic left it instead of restoring it from before the tick",
+        )],
+        examples: &["(recover!)"],
+    },
+    SpecialForm {
+        name: "spawn:",
+        area: Area::World,
+        usage: &["(spawn: name :component value ...)"],
+        summary: "Spawn a named entity with components",
+        arguments: &[
+            ("name", "symbol the entity can be referred to by"),
+            (":component value", "initial component values"),
+        ],
+        examples: &["(spawn: lamp :name \"brass lamp\" :tag/portable true)"],
+    },
+    SpecialForm {
+        name: "link:",
+        area: Area::World,
+        usage: &["(link: source :relationship target)"],
+        summary: "Link two entities through a relationship",
+        arguments: &[],
+        examples: &["(link: lamp :contained-in cellar)"],
+    },
+    SpecialForm {
+        name: "constraint:",
+        area: Area::World,
+        usage: &["(constraint: name :where [...] :check [...])"],
+        summary: "Register a constraint checked after every tick",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "query",
+        area: Area::World,
+        usage: &["(query :where [...] :return expr)"],
+        summary: "Run a query against the current world",
+        arguments: &[],
+        examples: &["(query :where [[?e :health ?h]] :return ?e)"],
+    },
+    SpecialForm {
+        name: "inspect",
+        area: Area::World,
+        usage: &["(inspect entity)"],
+        summary: "Show an entity's components and relationships",
+        arguments: &[(
+            "entity",
+            "spawned entity's name, entity reference, or an index for generation 0",
+        )],
+        examples: &["(inspect hero)", "(inspect 1)"],
+    },
+    SpecialForm {
+        name: "world-hash",
+        area: Area::World,
+        usage: &["(world-hash)"],
+        summary: "Return a stable hash of the world's content",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "enable-group!",
+        area: Area::World,
+        usage: &["(enable-group! :group)"],
+        summary: "Switch a rule group on",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "disable-group!",
+        area: Area::World,
+        usage: &["(disable-group! :group)"],
+        summary: "Switch a rule group off",
+        arguments: &[],
+        examples: &["(disable-group! :combat)"],
+    },
+    SpecialForm {
+        name: "on-phase",
+        area: Area::World,
+        usage: &["(on-phase :phase (fn [ctx] ...))"],
+        summary: "Call a function at a phase of every tick",
+        arguments: &[
+            (
+                "phase",
+                ":begin-tick, :after-inputs, :before-constraints, or :after-commit",
+            ),
+            ("fn", "function of the phase context"),
+        ],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "every",
+        area: Area::World,
+        usage: &["(every ticks [:on entity] :then [forms...])"],
+        summary: "Run forms every so many ticks, as a timer an entity may own",
+        arguments: &[
+            ("ticks", "ticks between firings"),
+            ("entity", "owner, bound to self; its timers die with it"),
+        ],
+        examples: &["(every 2 :on guard :then [(println \"The guard paces.\")])"],
+    },
+    SpecialForm {
+        name: "fuse",
+        area: Area::World,
+        usage: &["(fuse ticks [:on entity] :then [forms...])"],
+        summary: "Run forms once, after so many ticks",
+        arguments: &[
+            ("ticks", "ticks to wait"),
+            ("entity", "owner, bound to self; its timers die with it"),
+        ],
+        examples: &["(fuse 3 :then [(println \"The bomb explodes.\")])"],
+    },
+    SpecialForm {
+        name: "pause-timers!",
+        area: Area::World,
+        usage: &["(pause-timers! entity)"],
+        summary: "Stop an entity's every and fuse timers counting down",
+        arguments: &[],
+        examples: &["(pause-timers! guard)"],
+    },
+    SpecialForm {
+        name: "resume-timers!",
+        area: Area::World,
+        usage: &["(resume-timers! entity)"],
+        summary: "Restart an entity's paused timers where they left off",
+        arguments: &[],
+        examples: &["(resume-timers! guard)"],
+    },
+    SpecialForm {
+        name: "spatial-index",
+        area: Area::World,
+        usage: &["(spatial-index :component :cell-size size)"],
+        summary: "Index a position component in a grid for entities-within and within?",
+        arguments: &[
+            ("component", "component holding {:x :y} or {:x :y :z}"),
+            (
+                "size",
+                "width of each grid cell, near the radius usually queried",
+            ),
+        ],
+        examples: &["(spatial-index :position :cell-size 8.0)"],
+    },
+    SpecialForm {
+        name: "before",
+        area: Area::World,
+        usage: &[
+            "(before action forms...)",
+            "(before action :on target forms...)",
+        ],
+        summary: "Run forms before an action's handler; returning :stop vetoes it",
+        arguments: &[
+            ("action", "the action to hook"),
+            (
+                "target",
+                "an entity's name, or a component marking a kind of entity",
+            ),
+            ("forms", "forms to evaluate with the action's bindings"),
+        ],
+        examples: &["(before take :on ember (println \"It's too hot to touch.\") :stop)"],
+    },
+    SpecialForm {
+        name: "after",
+        area: Area::World,
+        usage: &[
+            "(after action forms...)",
+            "(after action :on target forms...)",
+        ],
+        summary: "Run forms after an action's handler",
+        arguments: &[
+            ("action", "the action to hook"),
+            (
+                "target",
+                "an entity's name, or a component marking a kind of entity",
+            ),
+            ("forms", "forms to evaluate with the action's bindings"),
+        ],
+        examples: &["(after take :on crown (println \"You feel like royalty.\"))"],
+    },
+    SpecialForm {
+        name: "save!",
+        area: Area::World,
+        usage: &["(save! \"path\")"],
+        summary: "Save the world to a file",
+        arguments: &[],
+        examples: &["(save! \"game.ltw\")"],
+    },
+    SpecialForm {
+        name: "load-world!",
+        area: Area::World,
+        usage: &["(load-world! \"path\")"],
+        summary: "Replace the world with one saved by save!",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "export-json!",
+        area: Area::World,
+        usage: &["(export-json! \"path\")"],
+        summary: "Write entities and relationships as JSON",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "import-json!",
+        area: Area::World,
+        usage: &["(import-json! \"path\")"],
+        summary: "Replace entities with those in a JSON file",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "export-datoms!",
+        area: Area::World,
+        usage: &["(export-datoms! \"path\")"],
+        summary: "Write [entity attribute value tick] datoms as EDN",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "import-datoms!",
+        area: Area::World,
+        usage: &["(import-datoms! \"path\")"],
+        summary: "Replace entities with those in a datom file",
+        arguments: &[],
+        examples: &[],
+    },
+    // ==================== Debug ====================
+    SpecialForm {
+        name: "why",
+        area: Area::Debug,
+        usage: &[
+            "(why entity :component)",
+            "(why entity :exists)",
+            "(why source :relationship target)",
+        ],
+        summary: "Explain which rules wrote a value, spawned an entity, or linked two",
+        arguments: &[
+            (":depth N", "follow the causal chain N writes back"),
+            (
+                ":data true",
+                "return the explanation as a map instead of printing it",
+            ),
+        ],
+        examples: &[
+            "(why player :health)",
+            "(why player :health :depth 3)",
+            "(why lamp :contained-in player :data true)",
+        ],
+    },
+    SpecialForm {
+        name: "explain-query",
+        area: Area::Debug,
+        usage: &[
+            "(explain-query (query ...))",
+            "(explain-query (query ...) entity)",
+        ],
+        summary: "Show how a query matched, clause by clause",
+        arguments: &[
+            ("entity", "explain why this entity did or didn't match"),
+            (
+                ":data true",
+                "return the explanation as a map instead of printing it",
+            ),
+        ],
+        examples: &["(explain-query (query :where [[?e :health ?h]] :return ?e))"],
+    },
+    SpecialForm {
+        name: "explain-plan",
+        area: Area::Debug,
+        usage: &["(explain-plan (query ...))"],
+        summary: "Show the order a query's clauses will be matched in",
+        arguments: &[],
+        examples: &["(explain-plan (query :where [[?e :health ?h] [?e :boss _]] :return ?e))"],
+    },
+    SpecialForm {
+        name: "query-warnings",
+        area: Area::Debug,
+        usage: &["(query-warnings)", "(query-warnings :warn|:deny|:allow)"],
+        summary: "Return the last query's warnings, or set what happens to new ones",
+        arguments: &[],
+        examples: &["(query-warnings :deny)"],
+    },
+    SpecialForm {
+        name: "observability",
+        area: Area::Debug,
+        usage: &["(observability)", "(observability {:key value ...})"],
+        summary: "Show or change provenance, history, and tracing settings",
+        arguments: &[(
+            "keys",
+            ":enabled :provenance :verbosity :history-size :keyframe-interval \
+             :trace-buffer-size :provenance-history :profiling :why-depth \
+             :trace-to-stderr :json",
+        )],
+        examples: &["(observability {:history-size 50 :verbosity :full})"],
+    },
+    SpecialForm {
+        name: "trace",
+        area: Area::Debug,
+        usage: &["(trace)", "(trace :option ...)"],
+        summary: "Show or change tracing",
+        arguments: &[("option", ":on, :off, :json, :human, :clear, or :stats")],
+        examples: &["(trace :on)", "(trace :json :on)"],
+    },
+    SpecialForm {
+        name: "get-traces",
+        area: Area::Debug,
+        usage: &[
+            "(get-traces :last N)",
+            "(get-traces :tick N)",
+            "(get-traces :type TYPE)",
+            "(get-traces :all)",
+        ],
+        summary: "Print recorded trace events",
+        arguments: &[("TYPE", "an event type such as :rule-fire, or :parse")],
+        examples: &["(get-traces :last 10)", "(get-traces :type :parse)"],
+    },
+    SpecialForm {
+        name: "export-trace!",
+        area: Area::Debug,
+        usage: &["(export-trace! \"path\")"],
+        summary: "Write recorded trace events in Chrome trace format, for chrome://tracing or Perfetto",
+        arguments: &[],
+        examples: &["(export-trace! \"run.json\")"],
+    },
+    SpecialForm {
+        name: "break",
+        area: Area::Debug,
+        usage: &[
+            "(break :rule name)",
+            "(break :tick N)",
+            "(break :write [entity] :component)",
+            "(break :read [entity] :component)",
+        ],
+        summary: "Add a breakpoint, returning its id",
+        arguments: &[],
+        examples: &["(break :rule :regen)", "(break :write :health)"],
+    },
+    SpecialForm {
+        name: "unbreak",
+        area: Area::Debug,
+        usage: &["(unbreak id)"],
+        summary: "Remove a breakpoint",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "breakpoints",
+        area: Area::Debug,
+        usage: &["(breakpoints)"],
+        summary: "List breakpoints",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "watch",
+        area: Area::Debug,
+        usage: &["(watch expr)"],
+        summary: "Watch an expression, re-evaluated as each tick's rules finish, pausing the tick when it changes",
+        arguments: &[],
+        examples: &["(watch (get player :health))"],
+    },
+    SpecialForm {
+        name: "unwatch",
+        area: Area::Debug,
+        usage: &["(unwatch id)"],
+        summary: "Remove a watch",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "watches",
+        area: Area::Debug,
+        usage: &["(watches)"],
+        summary: "List watches and their current values",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "debug",
+        area: Area::Debug,
+        usage: &["(debug)"],
+        summary: "Show the debugger's status",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "continue",
+        area: Area::Debug,
+        usage: &["(continue)"],
+        summary: "Resume a tick paused at the debug> prompt",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "step-rule",
+        area: Area::Debug,
+        usage: &["(step-rule)"],
+        summary: "Step to the next rule",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "step-phase",
+        area: Area::Debug,
+        usage: &["(step-phase)"],
+        summary: "Step to the next phase",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "step-tick",
+        area: Area::Debug,
+        usage: &["(step-tick)"],
+        summary: "Step to the next tick",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "validate",
+        area: Area::Debug,
+        usage: &["(validate)"],
+        summary: "Check the world for consistency problems",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "lint-game",
+        area: Area::Debug,
+        usage: &["(lint-game)"],
+        summary: "Check loaded content for mistakes such as rooms without exits",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "scenario:",
+        area: Area::Debug,
+        usage: &["(scenario: \"name\" :setup [forms] :steps [forms] :assert [clauses])"],
+        summary: "Declare a simulation test that (run-scenarios) plays in isolation",
+        arguments: &[
+            ("setup", "forms that set up the situation"),
+            ("steps", "forms that play it forward, such as (tick!)"),
+            (
+                "assert",
+                "query clauses that must match together, or expressions that must be truthy",
+            ),
+        ],
+        examples: &[
+            "(scenario: \"goblin dies\" :steps [(input! \"attack goblin\")] :assert [[?g :health {:current 0}]])",
+        ],
+    },
+    SpecialForm {
+        name: "run-scenarios",
+        area: Area::Debug,
+        usage: &["(run-scenarios)", "(run-scenarios \"name\")"],
+        summary: "Run declared scenarios, restoring the session after each, and report diffs",
+        arguments: &[("name", "only run the scenario with this name")],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "memory",
+        area: Area::Debug,
+        usage: &["(memory)"],
+        summary: "Show entity, archetype, history, and interner memory statistics",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "gc-interner!",
+        area: Area::Debug,
+        usage: &["(gc-interner!)"],
+        summary: "Free keywords made at runtime that nothing refers to, reusing their slots",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "rule-stats",
+        area: Area::Debug,
+        usage: &["(rule-stats)", "(rule-stats :reset)"],
+        summary: "Show activations fired and match and effect time per rule, costliest first",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "coverage-report",
+        area: Area::Debug,
+        usage: &["(coverage-report)", "(coverage-report :reset)"],
+        summary: "List rules that never fired and command syntaxes input never matched",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "agenda",
+        area: Area::Debug,
+        usage: &["(agenda)"],
+        summary: "List the activations the rules would fire next, in firing order",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "cancel-activation!",
+        area: Area::Debug,
+        usage: &["(cancel-activation! id)"],
+        summary: "Take an activation listed by (agenda) off the agenda until the rules next run",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "relationship-stats",
+        area: Area::Debug,
+        usage: &["(relationship-stats)"],
+        summary: "Show fan-out and lookup statistics per relationship",
+        arguments: &[],
+        examples: &[],
+    },
+    // ==================== Time travel ====================
+    SpecialForm {
+        name: "rollback!",
+        area: Area::TimeTravel,
+        usage: &["(rollback! N)"],
+        summary: "Go back N ticks",
+        arguments: &[],
+        examples: &["(rollback! 1)"],
+    },
+    SpecialForm {
+        name: "goto-tick!",
+        area: Area::TimeTravel,
+        usage: &["(goto-tick! N)"],
+        summary: "Jump to tick N",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "branch!",
+        area: Area::TimeTravel,
+        usage: &["(branch! \"name\")"],
+        summary: "Create a branch at the current tick",
+        arguments: &[],
+        examples: &["(branch! \"experiment\")"],
+    },
+    SpecialForm {
+        name: "checkout!",
+        area: Area::TimeTravel,
+        usage: &["(checkout! \"name\")"],
+        summary: "Switch to a branch",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "branches",
+        area: Area::TimeTravel,
+        usage: &["(branches)"],
+        summary: "List branches",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "merge!",
+        area: Area::TimeTravel,
+        usage: &["(merge! \"name\")"],
+        summary: "Merge a branch into the current one",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "diff",
+        area: Area::TimeTravel,
+        usage: &["(diff N M)", "(diff :branches \"a\" \"b\")"],
+        summary: "Compare two ticks or two branches",
+        arguments: &[],
+        examples: &["(diff 3 5)"],
+    },
+    SpecialForm {
+        name: "history",
+        area: Area::TimeTravel,
+        usage: &["(history)", "(history N)"],
+        summary: "Show recent ticks",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "timeline",
+        area: Area::TimeTravel,
+        usage: &["(timeline)"],
+        summary: "Show the timeline's status",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "undo!",
+        area: Area::TimeTravel,
+        usage: &["(undo!)"],
+        summary: "Revert the last batch of effects",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "do!",
+        area: Area::TimeTravel,
+        usage: &["(do! forms...)"],
+        summary: "Evaluate forms as one block that applies in full or not at all",
+        arguments: &[(
+            "forms",
+            "forms to evaluate in order; if one fails, the session is left as it was",
+        )],
+        examples: &["(do! (spawn: chest :container {}) (link: chest :in cellar))"],
+    },
+    SpecialForm {
+        name: "redo!",
+        area: Area::TimeTravel,
+        usage: &["(redo!)"],
+        summary: "Reapply the last undone batch of effects",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "save-state",
+        area: Area::TimeTravel,
+        usage: &["(save-state)"],
+        summary: "Snapshot the world, returning the snapshot's id",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "restore-state",
+        area: Area::TimeTravel,
+        usage: &["(restore-state id)"],
+        summary: "Restore the world to a snapshot",
+        arguments: &[],
+        examples: &[],
+    },
+    // ==================== Parser ====================
+    SpecialForm {
+        name: "input!",
+        area: Area::Parser,
+        usage: &["(input! \"command text\")"],
+        summary: "Parse and run a natural language command",
+        arguments: &[],
+        examples: &["(input! \"take the lamp\")"],
+    },
+    SpecialForm {
+        name: "transcript",
+        area: Area::Parser,
+        usage: &["(transcript)"],
+        summary: "Summarize recorded input",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "save-transcript!",
+        area: Area::Parser,
+        usage: &["(save-transcript! \"path\")"],
+        summary: "Export recorded input as JSON",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "verbs",
+        area: Area::Parser,
+        usage: &["(verbs)"],
+        summary: "List the verbs the parser knows, with their synonyms",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "nouns-in-scope",
+        area: Area::Parser,
+        usage: &["(nouns-in-scope)"],
+        summary: "List the entities the player's nouns can refer to",
+        arguments: &[],
+        examples: &[],
+    },
+    SpecialForm {
+        name: "syntax-for",
+        area: Area::Parser,
+        usage: &["(syntax-for :verb word)"],
+        summary: "List the command syntaxes for a verb, in the order they're tried",
+        arguments: &[],
+        examples: &["(syntax-for :verb take)"],
+    },
+];
+
+/// Looks up a special form by name.
+#[must_use]
+pub fn find(name: &str) -> Option<&'static SpecialForm> {
+    SPECIAL_FORMS.iter().find(|form| form.name == name)
+}
+
+/// Lists every special form by area, one line each.
+#[must_use]
+pub fn overview() -> String {
+    let width = SPECIAL_FORMS
+        .iter()
+        .map(|form| form.name.len())
+        .max()
+        .unwrap_or(0);
+    let mut output = String::new();
+    for area in Area::ALL {
+        let _ = writeln!(output, "{}:", area.title());
+        for form in SPECIAL_FORMS.iter().filter(|form| form.area == area) {
+            let _ = writeln!(output, "  {:width$}  {}", form.name, form.summary);
+        }
+        output.push('\n');
+    }
+    output.push_str("Use (help name) for usage and examples.\n");
+    output
+}
+
+/// Describes one special form: usage, arguments, and examples.
+#[must_use]
+pub fn describe(form: &SpecialForm) -> String {
+    let mut output = String::new();
+    for usage in form.usage {
+        let _ = writeln!(output, "{usage}");
+    }
+    let _ = writeln!(output, "  {}", form.summary);
+    if SESSION_COMMANDS.contains(&form.name) {
+        output.push_str(
+            "  Also works inside functions and scripts, running once the surrounding\n  \
+             form's effects are applied.\n",
+        );
+    }
+    if !form.arguments.is_empty() {
+        output.push_str("\nArguments:\n");
+        let width = form
+            .arguments
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        for (name, meaning) in form.arguments {
+            let _ = writeln!(output, "  {name:width$}  {meaning}");
+        }
+    }
+    if !form.examples.is_empty() {
+        output.push_str("\nExamples:\n");
+        for example in form.examples {
+            let _ = writeln!(output, "  {example}");
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The names `try_special_form` dispatches on, read from its source.
+    fn dispatched_forms() -> Vec<&'static str> {
+        let source = include_str!("repl.rs");
+        let start = source.find("fn try_special_form").unwrap();
+        let body = &source[start..];
+        let body = &body[..body.find("\n    fn ").unwrap()];
+        body.split("s == \"")
+            .skip(1)
+            .map(|rest| &rest[..rest.find('"').unwrap()])
+            .collect()
+    }
+
+    #[test]
+    fn every_special_form_is_documented() {
+        let forms = dispatched_forms();
+        assert!(forms.contains(&"why"));
+        for name in forms {
+            assert!(find(name).is_some(), "no help entry for ({name})");
+        }
+    }
+
+    #[test]
+    fn session_commands_are_special_forms() {
+        let forms = dispatched_forms();
+        for name in SESSION_COMMANDS {
+            assert!(forms.contains(name), "({name}) is not a special form");
+        }
+        assert!(describe(find("tick!").unwrap()).contains("inside functions"));
+        assert!(!describe(find("why").unwrap()).contains("inside functions"));
+    }
+
+    #[test]
+    fn entries_are_unique_and_complete() {
+        for (i, form) in SPECIAL_FORMS.iter().enumerate() {
+            assert!(
+                SPECIAL_FORMS[..i]
+                    .iter()
+                    .all(|other| other.name != form.name),
+                "duplicate help entry for ({})",
+                form.name
+            );
+            assert!(!form.usage.is_empty(), "({}) has no usage", form.name);
+            assert!(!form.summary.is_empty(), "({}) has no summary", form.name);
+        }
+    }
+
+    #[test]
+    fn describes_forms() {
+        let overview = overview();
+        assert!(overview.contains("Time travel:\n"));
+        assert!(overview.contains("  rollback!"));
+
+        let why = describe(find("why").unwrap());
+        assert!(why.starts_with("(why entity :component)\n"));
+        assert!(why.contains("\nArguments:\n  :depth N  "));
+        assert!(why.contains("\nExamples:\n  (why player :health)\n"));
+    }
+}
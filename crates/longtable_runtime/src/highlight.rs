@@ -153,8 +153,8 @@ impl LongtableHighlighter {
                         | "match" => "\x1b[32m",
 
                         // Declarations and query - bold green
-                        "component:" | "relationship:" | "rule:" | "derived:" | "constraint:"
-                        | "query" => "\x1b[1;32m",
+                        "component:" | "relationship:" | "rule:" | "rule-group:" | "derived:"
+                        | "constraint:" | "query" => "\x1b[1;32m",
 
                         // Booleans and nil - blue
                         "true" | "false" | "nil" => "\x1b[34m",
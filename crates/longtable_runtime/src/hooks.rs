@@ -0,0 +1,125 @@
+//! Hooks that run before and after actions.
+//!
+//! An action has one handler, usually a general one from the standard
+//! library. Hooks let particular objects change what it does, without
+//! replacing it:
+//!
+//! ```text
+//! (before take :on ember (println "It's too hot to touch.") :stop)
+//! (before put-in :on :tag/sealed (println "It's sealed shut.") :stop)
+//! (after take :on crown (println "You feel like royalty."))
+//! (after go (set-field! ?actor :moves :value (inc (get-field ?actor :moves :value))))
+//! ```
+//!
+//! `:on` names an entity, or a component that marks a kind of entity, and
+//! the hook runs when the action involves one: its actor or any entity the
+//! command bound. Without `:on`, the hook runs for every use of the action.
+//! Hook forms see the action's bindings, like its handler.
+//!
+//! Before hooks run after the action's preconditions pass. One whose last
+//! form returns `:stop` vetoes the action, so neither its handler nor any
+//! later hook runs. After hooks run once the handler has. Hooks for an
+//! entity run first, then those for a component, then those for every use,
+//! each in the order they were declared.
+
+use longtable_foundation::{EntityId, KeywordId};
+use longtable_language::Ast;
+use longtable_storage::World;
+
+/// Whether a hook runs before or after its action's handler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookTiming {
+    /// Before the handler, able to stop the action.
+    Before,
+    /// After the handler.
+    After,
+}
+
+/// Which uses of an action a hook applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookTarget {
+    /// Uses involving this entity.
+    Entity(EntityId),
+    /// Uses involving an entity with this component.
+    Component(KeywordId),
+    /// Every use.
+    Any,
+}
+
+/// Forms to run before or after an action.
+#[derive(Clone, Debug)]
+pub struct ActionHook {
+    /// Before or after the handler.
+    pub timing: HookTiming,
+    /// The action hooked.
+    pub action: KeywordId,
+    /// Which uses of the action it applies to.
+    pub target: HookTarget,
+    /// The forms to evaluate.
+    pub body: Vec<Ast>,
+}
+
+impl ActionHook {
+    /// Returns true if the hook applies to a use of its action involving
+    /// `entities`.
+    #[must_use]
+    pub fn applies_to(&self, entities: &[EntityId], world: &World) -> bool {
+        match self.target {
+            HookTarget::Entity(target) => entities.contains(&target),
+            HookTarget::Component(component) => {
+                entities.iter().any(|entity| world.has(*entity, component))
+            }
+            HookTarget::Any => true,
+        }
+    }
+
+    /// Orders hooks most specific first: entity, then component, then any.
+    #[must_use]
+    pub fn specificity(&self) -> u8 {
+        match self.target {
+            HookTarget::Entity(_) => 0,
+            HookTarget::Component(_) => 1,
+            HookTarget::Any => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::{LtMap, Type, Value};
+    use longtable_storage::schema::{ComponentSchema, FieldSchema};
+
+    #[test]
+    fn applies_to_entities_and_components() {
+        let mut world = World::new(0);
+        let take = world.interner_mut().intern_keyword("take");
+        let hot = world.interner_mut().intern_keyword("tag/hot");
+        let value = world.interner_mut().intern_keyword("value");
+        let mut world = world
+            .register_component(
+                ComponentSchema::new(hot).with_field(FieldSchema::required(value, Type::Bool)),
+            )
+            .unwrap();
+        let mut spawn = |components: LtMap<Value, Value>| {
+            let (next, entity) = world.spawn(&components).unwrap();
+            world = next;
+            entity
+        };
+        let hot_field = LtMap::new().insert(Value::Keyword(value), Value::Bool(true));
+        let ember = spawn(LtMap::new().insert(Value::Keyword(hot), Value::Map(hot_field)));
+        let lamp = spawn(LtMap::new());
+
+        let hook = |target| ActionHook {
+            timing: HookTiming::Before,
+            action: take,
+            target,
+            body: Vec::new(),
+        };
+        assert!(hook(HookTarget::Entity(lamp)).applies_to(&[ember, lamp], &world));
+        assert!(!hook(HookTarget::Entity(lamp)).applies_to(&[ember], &world));
+        assert!(hook(HookTarget::Component(hot)).applies_to(&[ember], &world));
+        assert!(!hook(HookTarget::Component(hot)).applies_to(&[lamp], &world));
+        assert!(hook(HookTarget::Any).applies_to(&[], &world));
+    }
+}
@@ -0,0 +1,305 @@
+//! JSON encoding of Longtable values.
+//!
+//! Values map onto JSON as directly as possible so the output is easy to read
+//! and edit by hand:
+//!
+//! | Value | JSON |
+//! |-------|------|
+//! | `nil`, bools, ints, strings | `null`, `true`/`false`, numbers, strings |
+//! | finite floats | numbers (always written with a fraction, e.g. `1.0`) |
+//! | keywords | strings with a leading colon: `":open"` |
+//! | vectors | arrays |
+//! | maps with only keyword keys | objects keyed by the keyword name: `{"current": 2}` |
+//!
+//! Everything else uses a single-key object whose key starts with `$`:
+//! `{"$entity": [index, generation]}`, `{"$symbol": "name"}`,
+//! `{"$list": [...]}`, `{"$set": [...]}`, `{"$map": [[key, value], ...]}`,
+//! `{"$float": "inf"}` (or `"-inf"`, `"nan"`), `{"$bigint": "digits"}` for
+//! integers beyond 64 bits, `{"$instant": ms}`,
+//! `{"$duration": ms}`, `{"$vec2": [x, y]}`, `{"$vec3": [x, y, z]}`, and `{"$string": ":text"}` for
+//! strings that would otherwise read as keywords. Functions have no JSON
+//! form and encode as `null`.
+//!
+//! [`from_json`] reverses the encoding, so hand-written JSON in the same
+//! shape reads back as the values it describes.
+
+use longtable_foundation::{EntityId, Error, ErrorKind, Interner, LtMap, Result, Value};
+use serde_json::{Map, Value as Json, json};
+
+/// Encodes a value as JSON, resolving keywords and symbols through `interner`.
+#[must_use]
+pub fn to_json(value: &Value, interner: &Interner) -> Json {
+    match value {
+        Value::Nil | Value::Fn(_) | Value::Seq(_) => Json::Null,
+        Value::Bool(b) => Json::Bool(*b),
+        Value::Int(n) => json!(n),
+        Value::BigInt(n) => json!({ "$bigint": n.to_string() }),
+        Value::Float(f) => float_to_json(*f),
+        Value::String(s) if s.starts_with(':') => json!({ "$string": &**s }),
+        Value::String(s) => Json::String(s.to_string()),
+        Value::Keyword(id) => Json::String(format!(":{}", keyword_name(*id, interner))),
+        Value::Symbol(id) => {
+            let name = interner
+                .get_symbol(*id)
+                .map_or_else(|| format!("#{}", id.index()), str::to_string);
+            json!({ "$symbol": name })
+        }
+        Value::EntityRef(id) => json!({ "$entity": [id.index, id.generation] }),
+        Value::Instant(ms) => json!({ "$instant": ms }),
+        Value::Duration(ms) => json!({ "$duration": ms }),
+        Value::Vec2(v) => json!({ "$vec2": v.map(float_to_json) }),
+        Value::Vec3(v) => json!({ "$vec3": v.map(float_to_json) }),
+        Value::Vec(items) => Json::Array(items.iter().map(|v| to_json(v, interner)).collect()),
+        Value::List(items) => {
+            let items: Vec<_> = items.iter().map(|v| to_json(v, interner)).collect();
+            json!({ "$list": items })
+        }
+        Value::Set(items) => {
+            let items: Vec<_> = items.iter().map(|v| to_json(v, interner)).collect();
+            json!({ "$set": items })
+        }
+        Value::Map(map) => {
+            let mut object = Map::new();
+            for (key, value) in map.iter() {
+                let Value::Keyword(id) = key else {
+                    return map_to_pairs(map, interner);
+                };
+                let name = keyword_name(*id, interner);
+                if name.starts_with('$') {
+                    return map_to_pairs(map, interner);
+                }
+                object.insert(name, to_json(value, interner));
+            }
+            Json::Object(object)
+        }
+    }
+}
+
+/// Encodes a map whose keys can't all be object keys as `{"$map": [[k, v], ...]}`.
+fn map_to_pairs(map: &longtable_foundation::LtMap<Value, Value>, interner: &Interner) -> Json {
+    let pairs: Vec<_> = map
+        .iter()
+        .map(|(k, v)| json!([to_json(k, interner), to_json(v, interner)]))
+        .collect();
+    json!({ "$map": pairs })
+}
+
+fn float_to_json(f: f64) -> Json {
+    if f.is_nan() {
+        json!({ "$float": "nan" })
+    } else if f.is_infinite() {
+        json!({ "$float": if f > 0.0 { "inf" } else { "-inf" } })
+    } else {
+        json!(f)
+    }
+}
+
+fn keyword_name(id: longtable_foundation::KeywordId, interner: &Interner) -> String {
+    interner
+        .get_keyword(id)
+        .map_or_else(|| format!("#{}", id.index()), str::to_string)
+}
+
+/// Decodes JSON written in the shape [`to_json`] produces.
+///
+/// # Errors
+///
+/// Returns an error for numbers that fit neither `i64` nor `f64`, unknown
+/// `$` tags, and malformed tagged objects.
+pub fn from_json(json: &Json, interner: &mut Interner) -> Result<Value> {
+    match json {
+        Json::Null => Ok(Value::Nil),
+        Json::Bool(b) => Ok(Value::Bool(*b)),
+        Json::Number(n) => n
+            .as_i64()
+            .map(Value::Int)
+            .or_else(|| n.as_f64().map(Value::Float))
+            .ok_or_else(|| invalid(format!("number out of range: {n}"))),
+        Json::String(s) => Ok(match s.strip_prefix(':') {
+            Some(name) => Value::Keyword(interner.intern_generated_keyword(name)),
+            None => Value::from(s.as_str()),
+        }),
+        Json::Array(items) => Ok(Value::Vec(
+            items
+                .iter()
+                .map(|item| from_json(item, interner))
+                .collect::<Result<_>>()?,
+        )),
+        Json::Object(object) => {
+            if object.len() == 1 {
+                if let Some((tag, payload)) =
+                    object.iter().next().filter(|(k, _)| k.starts_with('$'))
+                {
+                    return tagged_from_json(tag, payload, interner);
+                }
+            }
+            let mut map = LtMap::new();
+            for (key, value) in object {
+                let key = Value::Keyword(interner.intern_keyword(key));
+                map = map.insert(key, from_json(value, interner)?);
+            }
+            Ok(Value::Map(map))
+        }
+    }
+}
+
+/// Decodes the `[index, generation]` payload of an `$entity` tag.
+///
+/// # Errors
+///
+/// Returns an error unless `json` is a pair of non-negative integers whose
+/// generation fits in 32 bits.
+pub fn entity_from_json(json: &Json) -> Result<EntityId> {
+    let pair = json.as_array().filter(|items| items.len() == 2);
+    let parts = pair.and_then(|items| Some((items[0].as_u64()?, items[1].as_u64()?)));
+    parts
+        .and_then(|(index, generation)| Some(EntityId::new(index, u32::try_from(generation).ok()?)))
+        .ok_or_else(|| {
+            invalid(format!(
+                "expected an entity as [index, generation], got {json}"
+            ))
+        })
+}
+
+fn tagged_from_json(tag: &str, payload: &Json, interner: &mut Interner) -> Result<Value> {
+    let items = |interner: &mut Interner| -> Result<Vec<Value>> {
+        payload
+            .as_array()
+            .ok_or_else(|| invalid(format!("{tag} expects an array, got {payload}")))?
+            .iter()
+            .map(|item| from_json(item, interner))
+            .collect()
+    };
+    match tag {
+        "$entity" => entity_from_json(payload).map(Value::EntityRef),
+        "$symbol" => payload
+            .as_str()
+            .map(|name| Value::Symbol(interner.intern_symbol(name)))
+            .ok_or_else(|| invalid(format!("$symbol expects a string, got {payload}"))),
+        "$string" => payload
+            .as_str()
+            .map(Value::from)
+            .ok_or_else(|| invalid(format!("$string expects a string, got {payload}"))),
+        "$float" => match payload.as_str() {
+            Some("inf") => Ok(Value::Float(f64::INFINITY)),
+            Some("-inf") => Ok(Value::Float(f64::NEG_INFINITY)),
+            Some("nan") => Ok(Value::Float(f64::NAN)),
+            _ => Err(invalid(format!(
+                "$float expects \"inf\", \"-inf\" or \"nan\", got {payload}"
+            ))),
+        },
+        "$bigint" => payload
+            .as_str()
+            .and_then(|digits| digits.parse().ok())
+            .map(Value::integer)
+            .ok_or_else(|| invalid(format!("$bigint expects a string of digits, got {payload}"))),
+        "$instant" | "$duration" => {
+            let ms = payload
+                .as_i64()
+                .ok_or_else(|| invalid(format!("{tag} expects an integer, got {payload}")))?;
+            Ok(if tag == "$instant" {
+                Value::Instant(ms)
+            } else {
+                Value::Duration(ms)
+            })
+        }
+        "$vec2" | "$vec3" => items(interner)?
+            .iter()
+            .map(Value::as_number)
+            .collect::<Option<Vec<f64>>>()
+            .and_then(|components| Value::from_components(&components))
+            .filter(|v| v.value_type().to_string() == tag[1..])
+            .ok_or_else(|| invalid(format!("{tag} expects an array of numbers, got {payload}"))),
+        "$list" => Ok(Value::List(items(interner)?.into_iter().collect())),
+        "$set" => Ok(Value::Set(items(interner)?.into_iter().collect())),
+        "$map" => {
+            let mut map = LtMap::new();
+            for pair in items(interner)? {
+                let Value::Vec(kv) = &pair else {
+                    return Err(invalid(format!(
+                        "$map expects [key, value] pairs, got {pair}"
+                    )));
+                };
+                let (Some(key), Some(value), 2) = (kv.get(0), kv.get(1), kv.len()) else {
+                    return Err(invalid(format!(
+                        "$map expects [key, value] pairs, got {pair}"
+                    )));
+                };
+                map = map.insert(key.clone(), value.clone());
+            }
+            Ok(Value::Map(map))
+        }
+        _ => Err(invalid(format!("unknown tag {tag}"))),
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::SerializationError(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::LtVec;
+
+    #[test]
+    fn encodes_values_readably() {
+        let mut interner = Interner::new();
+        let open = interner.intern_keyword("open");
+        let current = interner.intern_keyword("current");
+
+        let map = LtMap::new().insert(Value::Keyword(current), Value::Int(2));
+        assert_eq!(
+            to_json(&Value::Map(map), &interner),
+            json!({ "current": 2 })
+        );
+
+        let items: LtVec<Value> = vec![
+            Value::Keyword(open),
+            Value::from(":not-a-keyword"),
+            Value::EntityRef(EntityId::new(3, 1)),
+            Value::Float(f64::INFINITY),
+            Value::Nil,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            to_json(&Value::Vec(items), &interner),
+            json!([
+                ":open",
+                { "$string": ":not-a-keyword" },
+                { "$entity": [3, 1] },
+                { "$float": "inf" },
+                null
+            ])
+        );
+
+        let mixed = LtMap::new().insert(Value::Int(1), Value::Bool(true));
+        assert_eq!(
+            to_json(&Value::Map(mixed), &interner),
+            json!({ "$map": [[1, true]] })
+        );
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        let mut interner = Interner::new();
+        let json = json!({
+            "health": { "current": 3 },
+            "tags": { "$set": [":brave"] },
+            "home": { "$entity": [2, 1] },
+            "motto": { "$string": ":ni" },
+            "scores": { "$map": [[1, 2.5]] },
+            "born": { "$instant": 1000 },
+            "gold": { "$bigint": "18446744073709551616" },
+            "cooldown": { "$duration": 1500 },
+            "heading": { "$vec2": [0.5, -1.0] }
+        });
+        let value = from_json(&json, &mut interner).unwrap();
+        assert_eq!(to_json(&value, &interner), json);
+
+        assert!(from_json(&json!({ "$bogus": 1 }), &mut interner).is_err());
+        assert!(from_json(&json!({ "$entity": [1] }), &mut interner).is_err());
+        assert!(from_json(&json!({ "$vec3": [1, 2] }), &mut interner).is_err());
+    }
+}
@@ -4,6 +4,12 @@
 //! - [`Repl`] - Interactive read-eval-print loop
 //! - CLI argument parsing and execution
 //! - World serialization and deserialization
+//! - Precompiled `.ltc` modules that load without parsing
+//! - Hot reload of changed source files
+//! - A REPL server that lets external tools evaluate forms over TCP
+//! - Coverage of which rules fired and which command syntaxes matched
+//! - ANSI and JSON renderers for `say`'s rich text
+//! - JavaScript bindings for browser embedding (the `wasm` feature)
 //!
 //! # Example
 //!
@@ -20,13 +26,52 @@
 // The Error type is intentionally large for rich error context
 #![allow(clippy::result_large_err)]
 
+pub mod batch;
+pub mod coverage;
+pub mod datoms;
+pub mod doc;
 mod editor;
+pub mod explain;
+pub mod golden;
+pub mod help;
+#[cfg(feature = "cli")]
 mod highlight;
+pub mod hooks;
+pub mod json;
+pub mod lint;
+pub mod lsp;
+pub mod messages;
+mod pager;
+pub mod precompiled;
+pub mod reload;
 mod repl;
+pub mod replay;
+pub mod rich;
+pub mod scenario;
 pub mod serialize;
+pub mod server;
 mod session;
+pub mod telemetry;
+pub mod transcript;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-pub use editor::{LineEditor, RustylineEditor};
-pub use repl::Repl;
+pub use batch::{BatchReport, TickStats};
+pub use coverage::{Coverage, CoverageReport};
+pub use doc::DocFormat;
+#[cfg(feature = "cli")]
+pub use editor::RustylineEditor;
+pub use editor::{DefaultEditor, HeadlessEditor, LineEditor};
+pub use golden::{Exchange, GoldenRun, GoldenTranscript, Mismatch};
+pub use hooks::{ActionHook, HookTarget, HookTiming};
+pub use lint::{Lint, LintKind, SourceSite};
+pub use pager::Pager;
+pub use precompiled::PrecompiledModule;
+pub use reload::FileWatcher;
+pub use repl::{ErrorFormat, Repl};
+pub use replay::{ReplayFrame, ReplayLog};
+pub use scenario::{Scenario, ScenarioFailure, ScenarioResult};
 pub use serialize::{from_bytes, load_from_file, save_to_file, to_bytes};
-pub use session::{Session, SessionContext};
+pub use session::{Poisoned, Session, SessionCheckpoint, SessionContext, WarningMode};
+pub use telemetry::{Telemetry, TelemetryEvent, TelemetrySink};
+pub use transcript::{InputOutcome, Transcript, TranscriptEntry};
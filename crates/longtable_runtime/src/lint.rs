@@ -0,0 +1,349 @@
+//! Adventure-specific content lints.
+//!
+//! The compiler checks that content is well-formed Longtable; these lints
+//! check that it makes a playable game. `(lint-game)` and `longtable lint`
+//! walk the loaded session looking for:
+//!
+//! - rooms (`:tag/room`) with no `:exit/*` relationships leading out
+//! - exits whose destination is missing or is not a room
+//! - takeable items (`:tag/takeable`) that are neither `:in-room` anywhere
+//!   nor `:contained-in` anything
+//! - commands whose `:action` has no `action:` declaration
+//! - nouns (entity `:name`s and `:aliases`) that are also verb words, which
+//!   makes the parser read the noun as a command
+//!
+//! Each lint points at the declaration it concerns when the session knows
+//! where that was (see [`Session::declaration_site`]).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use longtable_foundation::{EntityId, Interner, KeywordId, Value};
+use longtable_language::Span;
+use longtable_storage::World;
+
+use crate::session::Session;
+
+/// Where a top-level declaration appeared in source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceSite {
+    /// File the declaration was loaded from, or `None` if entered at the REPL.
+    pub file: Option<PathBuf>,
+    /// Span of the declaration form.
+    pub span: Span,
+}
+
+impl fmt::Display for SourceSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(
+                f,
+                "{}:{}:{}",
+                file.display(),
+                self.span.line,
+                self.span.column
+            ),
+            None => write!(f, "<repl>:{}:{}", self.span.line, self.span.column),
+        }
+    }
+}
+
+/// The kind of problem a lint reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LintKind {
+    /// A room with no exits.
+    RoomWithoutExits,
+    /// An exit leading to something that is not a room.
+    DanglingExit,
+    /// A takeable item that is not anywhere.
+    ItemWithoutLocation,
+    /// A command invoking an undeclared action.
+    UnknownAction,
+    /// A noun that is also a verb word.
+    NounVerbCollision,
+}
+
+impl LintKind {
+    /// Returns the lint's name, as shown in reports.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::RoomWithoutExits => "room-without-exits",
+            Self::DanglingExit => "dangling-exit",
+            Self::ItemWithoutLocation => "item-without-location",
+            Self::UnknownAction => "unknown-action",
+            Self::NounVerbCollision => "noun-verb-collision",
+        }
+    }
+}
+
+/// A single content problem.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    /// What kind of problem this is.
+    pub kind: LintKind,
+    /// Description naming the entities or declarations involved.
+    pub message: String,
+    /// Declaration the problem concerns, if its source is known.
+    pub site: Option<SourceSite>,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(site) = &self.site {
+            write!(f, "{site}: ")?;
+        }
+        write!(f, "[{}] {}", self.kind.name(), self.message)
+    }
+}
+
+/// Checks the content loaded into `session`, returning lints in source order.
+///
+/// Lints without a known source come last, ordered by kind and message.
+#[must_use]
+pub fn lint(session: &Session) -> Vec<Lint> {
+    let linter = Linter::new(session);
+    let mut lints = Vec::new();
+    linter.check_rooms(&mut lints);
+    linter.check_items(&mut lints);
+    linter.check_commands(&mut lints);
+    linter.check_nouns(&mut lints);
+
+    lints.sort_by(|a, b| {
+        let key = |lint: &Lint| {
+            lint.site
+                .as_ref()
+                .map(|site| (site.file.clone(), site.span.start))
+        };
+        match (key(a), key(b)) {
+            (Some(a_key), Some(b_key)) => a_key.cmp(&b_key),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => (a.kind, &a.message).cmp(&(b.kind, &b.message)),
+        }
+    });
+    lints
+}
+
+/// Formats lints as a report, in the style of `(validate)`.
+#[must_use]
+pub fn format_report(lints: &[Lint]) -> String {
+    if lints.is_empty() {
+        return "Lint: no issues found".to_string();
+    }
+    let mut out = format!("Lint: {} issue(s)", lints.len());
+    for lint in lints {
+        out.push_str("\n  ");
+        out.push_str(&lint.to_string());
+    }
+    out
+}
+
+// =============================================================================
+// Checks
+// =============================================================================
+
+/// Lookups shared by the individual checks.
+struct Linter<'a> {
+    session: &'a Session,
+    world: &'a World,
+    interner: &'a Interner,
+    /// Declared entity names, by entity.
+    names: HashMap<EntityId, &'a str>,
+}
+
+impl<'a> Linter<'a> {
+    fn new(session: &'a Session) -> Self {
+        let names = session
+            .entity_names()
+            .iter()
+            .map(|(name, &entity)| (entity, name.as_str()))
+            .collect();
+        Self {
+            session,
+            world: session.world(),
+            interner: session.world().interner(),
+            names,
+        }
+    }
+
+    fn keyword(&self, name: &str) -> Option<KeywordId> {
+        self.interner.lookup_keyword(name)
+    }
+
+    fn keyword_name(&self, keyword: KeywordId) -> &str {
+        self.interner.get_keyword(keyword).unwrap_or("?")
+    }
+
+    /// Names an entity by its `spawn:` name, falling back to its ID.
+    fn describe(&self, entity: EntityId) -> String {
+        self.names
+            .get(&entity)
+            .map_or_else(|| entity.to_string(), |name| (*name).to_string())
+    }
+
+    /// Returns where an entity was spawned, if it was spawned by name.
+    fn spawn_site(&self, entity: EntityId) -> Option<SourceSite> {
+        let name = self.names.get(&entity)?;
+        self.session.declaration_site("spawn:", name).cloned()
+    }
+
+    /// Reads the `:value` field of a component.
+    fn field(&self, entity: EntityId, component: KeywordId) -> Option<Value> {
+        self.world
+            .get_field(entity, component, KeywordId::VALUE)
+            .ok()
+            .flatten()
+    }
+
+    /// Returns entities carrying a tag component, in ID order.
+    fn tagged(&self, tag: &str) -> Vec<EntityId> {
+        let mut entities: Vec<EntityId> = self
+            .keyword(tag)
+            .map(|tag| self.world.with_component(tag).collect())
+            .unwrap_or_default();
+        entities.sort_by_key(|e| (e.index, e.generation));
+        entities
+    }
+
+    fn check_rooms(&self, lints: &mut Vec<Lint>) {
+        let room_tag = self.keyword("tag/room");
+        for room in self.tagged("tag/room") {
+            let exits = self
+                .world
+                .find_relationships_by_prefix("exit/", Some(room), None);
+            if exits.is_empty() {
+                lints.push(Lint {
+                    kind: LintKind::RoomWithoutExits,
+                    message: format!("room {} has no exits", self.describe(room)),
+                    site: self.spawn_site(room),
+                });
+            }
+
+            for exit in exits {
+                let direction = match self.field(exit, KeywordId::REL_TYPE) {
+                    Some(Value::Keyword(kw)) => self.keyword_name(kw).to_string(),
+                    _ => "exit".to_string(),
+                };
+                let target = match self.field(exit, KeywordId::REL_TARGET) {
+                    Some(Value::EntityRef(target)) => Some(target),
+                    _ => None,
+                };
+                let problem = match target {
+                    Some(target) if !self.world.exists(target) => {
+                        format!("leads to {target}, which no longer exists")
+                    }
+                    Some(target) if !room_tag.is_some_and(|tag| self.world.has(target, tag)) => {
+                        format!("leads to {}, which is not a room", self.describe(target))
+                    }
+                    Some(_) => continue,
+                    None => "has no destination".to_string(),
+                };
+
+                let site = target
+                    .and_then(|target| {
+                        let link = format!(
+                            "{} :{direction} {}",
+                            self.names.get(&room)?,
+                            self.names.get(&target)?
+                        );
+                        self.session.declaration_site("link:", &link).cloned()
+                    })
+                    .or_else(|| self.spawn_site(room));
+                lints.push(Lint {
+                    kind: LintKind::DanglingExit,
+                    message: format!("exit :{direction} from {} {problem}", self.describe(room)),
+                    site,
+                });
+            }
+        }
+    }
+
+    fn check_items(&self, lints: &mut Vec<Lint>) {
+        let locations: Vec<KeywordId> = ["in-room", "contained-in"]
+            .into_iter()
+            .filter_map(|name| self.keyword(name))
+            .collect();
+        for item in self.tagged("tag/takeable") {
+            if locations
+                .iter()
+                .any(|&rel| self.world.has_outgoing(item, rel))
+            {
+                continue;
+            }
+            lints.push(Lint {
+                kind: LintKind::ItemWithoutLocation,
+                message: format!(
+                    "takeable item {} is not :in-room or :contained-in anything",
+                    self.describe(item)
+                ),
+                site: self.spawn_site(item),
+            });
+        }
+    }
+
+    fn check_commands(&self, lints: &mut Vec<Lint>) {
+        for syntax in self.session.compiled_syntaxes() {
+            if self.session.get_action_decl(syntax.action).is_some() {
+                continue;
+            }
+            let command = self.keyword_name(syntax.command);
+            lints.push(Lint {
+                kind: LintKind::UnknownAction,
+                message: format!(
+                    "command {command} invokes :{}, which has no action: declaration",
+                    self.keyword_name(syntax.action)
+                ),
+                site: self.session.declaration_site("command:", command).cloned(),
+            });
+        }
+    }
+
+    fn check_nouns(&self, lints: &mut Vec<Lint>) {
+        let mut verbs: HashMap<String, &str> = HashMap::new();
+        for verb in self.session.vocabulary_registry().verbs() {
+            let canonical = self.keyword_name(verb.name);
+            for word in std::iter::once(&verb.name).chain(&verb.synonyms) {
+                verbs.insert(self.keyword_name(*word).to_lowercase(), canonical);
+            }
+        }
+        if verbs.is_empty() {
+            return;
+        }
+
+        let (Some(name_kw), aliases_kw) = (self.keyword("name"), self.keyword("aliases")) else {
+            return;
+        };
+        let mut entities: Vec<EntityId> = self.world.with_component(name_kw).collect();
+        entities.sort_by_key(|e| (e.index, e.generation));
+        for entity in entities {
+            let mut nouns = Vec::new();
+            if let Some(Value::String(name)) = self.field(entity, name_kw) {
+                nouns.push(name.to_string());
+            }
+            if let Some(Value::Vec(aliases)) = aliases_kw.and_then(|kw| self.field(entity, kw)) {
+                nouns.extend(aliases.iter().filter_map(|alias| match alias {
+                    Value::String(alias) => Some(alias.to_string()),
+                    _ => None,
+                }));
+            }
+
+            for noun in nouns {
+                let collision = noun
+                    .split_whitespace()
+                    .find_map(|word| verbs.get(&word.to_lowercase()).map(|verb| (word, verb)));
+                if let Some((word, verb)) = collision {
+                    lints.push(Lint {
+                        kind: LintKind::NounVerbCollision,
+                        message: format!(
+                            "{} is called \"{noun}\", but \"{word}\" is also a word for the verb {verb}",
+                            self.describe(entity)
+                        ),
+                        site: self.spawn_site(entity),
+                    });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,742 @@
+//! A language server for `.lt` files.
+//!
+//! `longtable lsp` speaks the Language Server Protocol over stdin/stdout so
+//! editors can offer, while you type:
+//!
+//! - **diagnostics**: syntax errors and malformed declarations, as reported
+//!   by the parser and [`DeclarationAnalyzer`]
+//! - **hover**: the declaration behind a component, relationship, rule,
+//!   action, or named entity
+//! - **go-to-definition** for the same names
+//! - **completion** of declared component and relationship keywords after
+//!   `:`, and of declared names elsewhere
+//!
+//! Declarations are collected from every open document, plus the `.lt`
+//! files under the workspace root when the client names one. Documents are
+//! synchronized whole on each change.
+//!
+//! [`LanguageServer`] holds the state and answers requests; [`serve`] runs
+//! it over a reader and writer with the protocol's `Content-Length` framing.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use longtable_foundation::{Error, ErrorKind};
+use longtable_language::declaration::Declaration;
+use longtable_language::{Ast, DeclarationAnalyzer, Span, parse_recovering};
+use serde_json::{Value as Json, json};
+
+/// What a declared name refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DefinitionKind {
+    /// A `component:` or `derived:` declaration.
+    Component,
+    /// A `relationship:` declaration.
+    Relationship,
+    /// A `rule:` declaration.
+    Rule,
+    /// A `constraint:` declaration.
+    Constraint,
+    /// An `action:` declaration.
+    Action,
+    /// A `command:` declaration.
+    Command,
+    /// A `verb:` declaration.
+    Verb,
+    /// A `spawn:` declaration.
+    Entity,
+}
+
+impl DefinitionKind {
+    /// Returns the declaration form that introduces this kind of name.
+    #[must_use]
+    pub const fn form(self) -> &'static str {
+        match self {
+            Self::Component => "component:",
+            Self::Relationship => "relationship:",
+            Self::Rule => "rule:",
+            Self::Constraint => "constraint:",
+            Self::Action => "action:",
+            Self::Command => "command:",
+            Self::Verb => "verb:",
+            Self::Entity => "spawn:",
+        }
+    }
+
+    /// Whether the name is written as a keyword (`:health`) where it is used.
+    #[must_use]
+    pub const fn is_keyword(self) -> bool {
+        matches!(self, Self::Component | Self::Relationship)
+    }
+
+    /// The LSP `CompletionItemKind` for this kind of name.
+    const fn completion_kind(self) -> u32 {
+        match self {
+            Self::Component => 22,              // Struct
+            Self::Relationship => 18,           // Reference
+            Self::Rule | Self::Constraint => 3, // Function
+            Self::Action | Self::Command => 2,  // Method
+            Self::Verb => 14,                   // Keyword
+            Self::Entity => 6,                  // Variable
+        }
+    }
+}
+
+/// A declared name and where it was declared.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Definition {
+    /// What the name refers to.
+    pub kind: DefinitionKind,
+    /// The name as declared, without a leading colon.
+    pub name: String,
+    /// The document declaring it.
+    pub uri: String,
+    /// The span of the declaring form.
+    pub span: Span,
+    /// The declaring form's source text.
+    pub source: String,
+}
+
+/// A zero-based line and UTF-16 column, as LSP counts them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    /// Zero-based line.
+    pub line: u32,
+    /// Zero-based column, in UTF-16 code units.
+    pub character: u32,
+}
+
+/// A problem found in a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Where the problem starts.
+    pub start: Position,
+    /// Where the problem ends.
+    pub end: Position,
+    /// What is wrong.
+    pub message: String,
+}
+
+/// An open or indexed document.
+#[derive(Clone, Debug, Default)]
+struct Document {
+    text: String,
+    definitions: Vec<Definition>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Document {
+    fn analyze(uri: &str, text: String) -> Self {
+        let (forms, errors) = parse_recovering(&text);
+        let mut diagnostics: Vec<Diagnostic> = errors
+            .iter()
+            .map(|e| error_diagnostic(&text, e, None))
+            .collect();
+        let mut definitions = Vec::new();
+
+        for form in &forms {
+            match DeclarationAnalyzer::analyze(form) {
+                Ok(Some(decl)) => {
+                    if let Some((kind, name)) = declared_name(&decl) {
+                        let span = form.span();
+                        definitions.push(Definition {
+                            kind,
+                            name,
+                            uri: uri.to_string(),
+                            span,
+                            source: text.get(span.start..span.end).unwrap_or("").to_string(),
+                        });
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => diagnostics.push(error_diagnostic(&text, &e, Some(form))),
+            }
+        }
+
+        Self {
+            text,
+            definitions,
+            diagnostics,
+        }
+    }
+}
+
+/// The kind and name a declaration introduces, if it introduces one.
+fn declared_name(decl: &Declaration) -> Option<(DefinitionKind, String)> {
+    let (kind, name) = match decl {
+        Declaration::Component(d) => (DefinitionKind::Component, &d.name),
+        Declaration::Derived(d) => (DefinitionKind::Component, &d.name),
+        Declaration::Relationship(d) => (DefinitionKind::Relationship, &d.name),
+        Declaration::Rule(d) => (DefinitionKind::Rule, &d.name),
+        Declaration::Constraint(d) => (DefinitionKind::Constraint, &d.name),
+        Declaration::Action(d) => (DefinitionKind::Action, &d.name),
+        Declaration::Command(d) => (DefinitionKind::Command, &d.name),
+        Declaration::Verb(d) => (DefinitionKind::Verb, &d.name),
+        Declaration::Spawn(d) => (DefinitionKind::Entity, &d.name),
+        _ => return None,
+    };
+    Some((kind, name.clone()))
+}
+
+/// Places an error at its reported line and column, or else over `form`.
+fn error_diagnostic(text: &str, error: &Error, form: Option<&Ast>) -> Diagnostic {
+    let form_range = form.map(|f| {
+        let span = f.span();
+        (position_of(text, span.start), position_of(text, span.end))
+    });
+    let (message, start) = match &error.kind {
+        ErrorKind::ParseError {
+            message,
+            line,
+            column,
+            ..
+        } => {
+            let line = line.saturating_sub(1);
+            let start = text
+                .lines()
+                .nth(line as usize)
+                .map_or(Position::default(), |l| {
+                    let column = l
+                        .chars()
+                        .take(column.saturating_sub(1) as usize)
+                        .map(char::len_utf16)
+                        .sum::<usize>();
+                    Position {
+                        line,
+                        character: u32::try_from(column).unwrap_or(u32::MAX),
+                    }
+                });
+            (message.clone(), start)
+        }
+        other => (
+            other.to_string(),
+            form_range.map_or(Position::default(), |(start, _)| start),
+        ),
+    };
+    let end = form_range
+        .map(|(_, end)| end)
+        .filter(|end| (end.line, end.character) > (start.line, start.character))
+        .unwrap_or(Position {
+            line: start.line,
+            character: start.character + 1,
+        });
+    Diagnostic {
+        start,
+        end,
+        message,
+    }
+}
+
+/// Converts a byte offset in `text` to a [`Position`].
+#[must_use]
+pub fn position_of(text: &str, offset: usize) -> Position {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+    Position {
+        line: u32::try_from(before.matches('\n').count()).unwrap_or(u32::MAX),
+        character: u32::try_from(character).unwrap_or(u32::MAX),
+    }
+}
+
+/// Converts a [`Position`] to a byte offset in `text`, clamping to the end
+/// of its line.
+#[must_use]
+pub fn offset_of(text: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let mut units = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if c == '\n' || units >= position.character as usize {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || "-_/?!*+<>=.:'".contains(c)
+}
+
+/// The byte range of the word around `offset`.
+fn word_at(text: &str, offset: usize) -> (usize, usize) {
+    let start = text[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word_char(c))
+        .last()
+        .map_or(offset, |(i, _)| i);
+    let end = text[offset..]
+        .char_indices()
+        .find(|&(_, c)| !is_word_char(c))
+        .map_or(text.len(), |(i, _)| offset + i);
+    (start, end)
+}
+
+/// Longtable's language server state.
+#[derive(Debug, Default)]
+pub struct LanguageServer {
+    documents: HashMap<String, Document>,
+    shutdown: bool,
+}
+
+impl LanguageServer {
+    /// Creates a server with no documents.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens or replaces a document, returning its diagnostics.
+    pub fn update(&mut self, uri: &str, text: String) -> &[Diagnostic] {
+        let document = Document::analyze(uri, text);
+        &self
+            .documents
+            .entry(uri.to_string())
+            .insert_entry(document)
+            .into_mut()
+            .diagnostics
+    }
+
+    /// Indexes every `.lt` file under `root` so their declarations can be
+    /// found before they are opened.
+    ///
+    /// Symlinked directories aren't followed, so a link back up the tree
+    /// can't make the walk loop.
+    pub fn index_directory(&mut self, root: &Path) {
+        let Ok(entries) = std::fs::read_dir(root) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_type().is_ok_and(|ty| ty.is_dir()) {
+                self.index_directory(&path);
+            } else if path.extension().is_some_and(|ext| ext == "lt") {
+                let uri = path_to_uri(&path);
+                if !self.documents.contains_key(&uri) {
+                    if let Ok(text) = std::fs::read_to_string(&path) {
+                        self.update(&uri, text);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the diagnostics for a document.
+    #[must_use]
+    pub fn diagnostics(&self, uri: &str) -> &[Diagnostic] {
+        self.documents
+            .get(uri)
+            .map_or(&[], |doc| doc.diagnostics.as_slice())
+    }
+
+    fn definitions(&self) -> impl Iterator<Item = &Definition> {
+        self.documents.values().flat_map(|doc| &doc.definitions)
+    }
+
+    /// Finds the declaration of the name at `position`.
+    #[must_use]
+    pub fn definition(&self, uri: &str, position: Position) -> Option<&Definition> {
+        let text = &self.documents.get(uri)?.text;
+        let (start, end) = word_at(text, offset_of(text, position));
+        let word = &text[start..end];
+        let (name, keyword) = match word.strip_prefix(':') {
+            Some(name) => (name.strip_prefix("rel/").unwrap_or(name), true),
+            None => (word, false),
+        };
+        if name.is_empty() {
+            return None;
+        }
+        let mut matches = self.definitions().filter(|d| d.name == name);
+        if keyword {
+            matches.find(|d| d.kind.is_keyword())
+        } else {
+            matches.min_by_key(|d| d.kind.is_keyword())
+        }
+    }
+
+    /// Describes the declaration of the name at `position`, as Markdown.
+    #[must_use]
+    pub fn hover(&self, uri: &str, position: Position) -> Option<String> {
+        let definition = self.definition(uri, position)?;
+        let source: Vec<&str> = definition.source.lines().take(12).collect();
+        let more = if definition.source.lines().count() > source.len() {
+            "\n..."
+        } else {
+            ""
+        };
+        Some(format!("```clojure\n{}{more}\n```", source.join("\n")))
+    }
+
+    /// Suggests declared names for the word being typed at `position`.
+    ///
+    /// After a `:`, these are component and relationship keywords; elsewhere,
+    /// every other declared name.
+    #[must_use]
+    pub fn completions(&self, uri: &str, position: Position) -> Vec<&Definition> {
+        let Some(doc) = self.documents.get(uri) else {
+            return Vec::new();
+        };
+        let offset = offset_of(&doc.text, position);
+        let (start, _) = word_at(&doc.text, offset);
+        let prefix = &doc.text[start..offset];
+        let (prefix, keyword) = match prefix.strip_prefix(':') {
+            Some(rest) => (rest, true),
+            None => (prefix, false),
+        };
+
+        let mut items: Vec<&Definition> = self
+            .definitions()
+            .filter(|d| d.kind.is_keyword() == keyword && d.name.starts_with(prefix))
+            .collect();
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        items.dedup_by(|a, b| a.name == b.name && a.kind == b.kind);
+        items
+    }
+
+    /// Handles one JSON-RPC message, returning the messages to send back.
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        let id = message.get("id").cloned();
+        let method = message.get("method").and_then(Json::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Json::Null);
+        let uri = params
+            .pointer("/textDocument/uri")
+            .and_then(Json::as_str)
+            .unwrap_or("")
+            .to_string();
+
+        let result = match method {
+            "initialize" => self.initialize(&params),
+            "shutdown" => {
+                self.shutdown = true;
+                Json::Null
+            }
+            "textDocument/didOpen" => {
+                let text = params
+                    .pointer("/textDocument/text")
+                    .and_then(Json::as_str)
+                    .unwrap_or("");
+                self.update(&uri, text.to_string());
+                return vec![self.publish_diagnostics(&uri)];
+            }
+            "textDocument/didChange" => {
+                let Some(text) = params
+                    .pointer("/contentChanges")
+                    .and_then(Json::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Json::as_str)
+                else {
+                    return Vec::new();
+                };
+                self.update(&uri, text.to_string());
+                return vec![self.publish_diagnostics(&uri)];
+            }
+            "textDocument/hover" => {
+                let position = position_param(&params);
+                self.hover(&uri, position).map_or(
+                    Json::Null,
+                    |text| json!({ "contents": { "kind": "markdown", "value": text } }),
+                )
+            }
+            "textDocument/definition" => {
+                let position = position_param(&params);
+                self.definition(&uri, position).map_or(Json::Null, |d| {
+                    let text = &self.documents[&d.uri].text;
+                    json!({
+                        "uri": d.uri,
+                        "range": range_json(
+                            position_of(text, d.span.start),
+                            position_of(text, d.span.end),
+                        )
+                    })
+                })
+            }
+            "textDocument/completion" => self.completion_json(&uri, position_param(&params)),
+            // didClose keeps the document's declarations for other files.
+            _ => match id {
+                Some(_) if !method.is_empty() && !method.starts_with("$/") => {
+                    return vec![json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": -32601, "message": format!("method not found: {method}") }
+                    })];
+                }
+                _ => return Vec::new(),
+            },
+        };
+
+        match id {
+            Some(id) => vec![json!({ "jsonrpc": "2.0", "id": id, "result": result })],
+            None => Vec::new(),
+        }
+    }
+
+    fn initialize(&mut self, params: &Json) -> Json {
+        let root = params
+            .get("rootUri")
+            .and_then(Json::as_str)
+            .and_then(uri_to_path)
+            .or_else(|| {
+                params
+                    .get("rootPath")
+                    .and_then(Json::as_str)
+                    .map(PathBuf::from)
+            });
+        if let Some(root) = root {
+            self.index_directory(&root);
+        }
+        json!({
+            "capabilities": {
+                "textDocumentSync": 1,
+                "hoverProvider": true,
+                "definitionProvider": true,
+                "completionProvider": { "triggerCharacters": [":"] }
+            },
+            "serverInfo": { "name": "longtable", "version": env!("CARGO_PKG_VERSION") }
+        })
+    }
+
+    fn completion_json(&self, uri: &str, position: Position) -> Json {
+        let items: Vec<Json> = self
+            .completions(uri, position)
+            .into_iter()
+            .map(|d| {
+                let label = if d.kind.is_keyword() {
+                    format!(":{}", d.name)
+                } else {
+                    d.name.clone()
+                };
+                json!({
+                    "label": label,
+                    "kind": d.kind.completion_kind(),
+                    "detail": format!("({} {})", d.kind.form(), d.name),
+                    "filterText": label,
+                })
+            })
+            .collect();
+        json!(items)
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Json {
+        let diagnostics: Vec<Json> = self
+            .diagnostics(uri)
+            .iter()
+            .map(|d| {
+                json!({
+                    "range": range_json(d.start, d.end),
+                    "severity": 1,
+                    "source": "longtable",
+                    "message": d.message,
+                })
+            })
+            .collect();
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics }
+        })
+    }
+}
+
+fn position_param(params: &Json) -> Position {
+    let field = |name: &str| {
+        params
+            .pointer(&format!("/position/{name}"))
+            .and_then(Json::as_u64)
+            .and_then(|n| u32::try_from(n).ok())
+            .unwrap_or(0)
+    };
+    Position {
+        line: field("line"),
+        character: field("character"),
+    }
+}
+
+fn range_json(start: Position, end: Position) -> Json {
+    json!({
+        "start": { "line": start.line, "character": start.character },
+        "end": { "line": end.line, "character": end.character }
+    })
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    uri.strip_prefix("file://")
+        .map(|path| PathBuf::from(path.replace("%20", " ")))
+}
+
+fn path_to_uri(path: &Path) -> String {
+    format!("file://{}", path.display().to_string().replace(' ', "%20"))
+}
+
+/// Runs a [`LanguageServer`] over `input` and `output` until the client
+/// sends `exit` or closes the stream.
+///
+/// # Errors
+///
+/// Returns an error if reading or writing fails, or a message is not
+/// framed or encoded as the protocol requires.
+pub fn serve<R: BufRead, W: Write>(mut input: R, mut output: W) -> io::Result<()> {
+    let mut server = LanguageServer::new();
+    while let Some(message) = read_message(&mut input)? {
+        if message.get("method").and_then(Json::as_str) == Some("exit") {
+            break;
+        }
+        for reply in server.handle(&message) {
+            write_message(&mut output, &reply)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Json>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let length = length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URI: &str = "file:///game/world.lt";
+    const SOURCE: &str = "(component: health :current :int :max :int)
+(relationship: contains)
+(rule: regen
+  :where [[?e :health ?h]]
+  :then [])
+(spawn: hero :health {:current 5 :max 10})
+";
+
+    fn at(line: u32, character: u32) -> Position {
+        Position { line, character }
+    }
+
+    #[test]
+    fn hover_and_definition_find_declarations() {
+        let mut server = LanguageServer::new();
+        assert!(server.update(URI, SOURCE.to_string()).is_empty());
+
+        // `:health` inside the rule's pattern
+        let definition = server.definition(URI, at(3, 14)).unwrap();
+        assert_eq!(definition.kind, DefinitionKind::Component);
+        assert_eq!(position_of(SOURCE, definition.span.start), at(0, 0));
+
+        let hover = server.hover(URI, at(3, 14)).unwrap();
+        assert!(hover.contains("(component: health :current :int :max :int)"));
+
+        assert_eq!(
+            server.definition(URI, at(5, 9)).unwrap().kind,
+            DefinitionKind::Entity
+        );
+        assert!(server.definition(URI, at(4, 3)).is_none());
+    }
+
+    #[test]
+    fn completes_keywords_from_schemas() {
+        let mut server = LanguageServer::new();
+        server.update(URI, format!("{SOURCE}(get hero :he"));
+        let names: Vec<&str> = server
+            .completions(URI, at(6, 13))
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect();
+        assert_eq!(names, ["health"]);
+
+        server.update(URI, format!("{SOURCE}(re"));
+        let names: Vec<&str> = server
+            .completions(URI, at(6, 3))
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect();
+        assert_eq!(names, ["regen"]);
+    }
+
+    #[test]
+    fn reports_syntax_and_declaration_errors() {
+        let mut server = LanguageServer::new();
+        let diagnostics = server.update(URI, "(component: health :current)\n(+ 1".to_string());
+        assert_eq!(diagnostics.len(), 2, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].start.line, 1);
+        assert_eq!(diagnostics[1].start, at(0, 0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn indexing_skips_symlinked_directories() {
+        let root = std::env::temp_dir().join("longtable_test_lsp_index");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("rooms")).unwrap();
+        std::fs::write(root.join("rooms/hall.lt"), SOURCE).unwrap();
+        std::os::unix::fs::symlink(&root, root.join("rooms/up")).unwrap();
+
+        let mut server = LanguageServer::new();
+        server.index_directory(&root);
+        let _ = std::fs::remove_dir_all(&root);
+        assert_eq!(server.documents.len(), 1);
+        assert_eq!(
+            server.definitions().filter(|d| d.name == "health").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn serves_framed_json_rpc() {
+        let messages = [
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen",
+                    "params": { "textDocument": { "uri": URI, "text": SOURCE } } }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/definition",
+                    "params": { "textDocument": { "uri": URI },
+                                "position": { "line": 3, "character": 14 } } }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ];
+        let mut input = Vec::new();
+        for message in &messages {
+            write_message(&mut input, message).unwrap();
+        }
+        let mut output = Vec::new();
+        serve(input.as_slice(), &mut output).unwrap();
+
+        let mut replies = Vec::new();
+        let mut reader = output.as_slice();
+        while let Some(reply) = read_message(&mut reader).unwrap() {
+            replies.push(reply);
+        }
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0]["result"]["capabilities"]["hoverProvider"], true);
+        assert_eq!(replies[1]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(replies[2]["result"]["range"]["start"]["line"], 0);
+    }
+}
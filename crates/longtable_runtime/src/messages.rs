@@ -0,0 +1,122 @@
+//! Message catalogs for translatable text.
+//!
+//! Action handlers that print English literals can't be translated. Instead
+//! they name a message, and the text comes from the catalog of the current
+//! locale:
+//!
+//! ```text
+//! (message: :msg/take-success "You take the ~a.")
+//! (message: :msg/take-success "Vous prenez ~a." :locale :fr)
+//!
+//! (say-msg :msg/take-success "lamp")   ;; You take the lamp.
+//! (set-locale! :fr)
+//! (say-msg :msg/take-success "lamp")   ;; Vous prenez lamp.
+//! ```
+//!
+//! A message declared without a `:locale` belongs to the default locale,
+//! [`DEFAULT_LOCALE`]. A message missing from the current locale falls back
+//! to the default locale's text, so a partial translation still shows every
+//! message. Each locale's messages usually live in a file of their own,
+//! loaded with `(load "messages/fr.lt")` whenever the game needs them.
+//!
+//! Templates are `format` strings, so `~a`, `{}`, and the other directives
+//! take the message's arguments.
+
+use std::collections::HashMap;
+
+use longtable_foundation::KeywordId;
+
+/// The locale of messages declared without one.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Message templates for each locale, and which locale is in use.
+#[derive(Clone, Debug)]
+pub struct MessageCatalog {
+    /// Templates by locale, then message key.
+    catalogs: HashMap<String, HashMap<KeywordId, String>>,
+    /// The locale messages are looked up in first.
+    locale: String,
+}
+
+impl Default for MessageCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageCatalog {
+    /// Creates an empty catalog using the default locale.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            catalogs: HashMap::new(),
+            locale: DEFAULT_LOCALE.to_string(),
+        }
+    }
+
+    /// Defines `key`'s template in `locale`, replacing any earlier one.
+    pub fn define(&mut self, locale: &str, key: KeywordId, template: impl Into<String>) {
+        self.catalogs
+            .entry(locale.to_string())
+            .or_default()
+            .insert(key, template.into());
+    }
+
+    /// Returns the locale messages are looked up in first.
+    #[must_use]
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Switches the locale messages are looked up in first.
+    ///
+    /// The locale needn't have any messages yet; until it does, every
+    /// lookup falls back to the default locale.
+    pub fn set_locale(&mut self, locale: impl Into<String>) {
+        self.locale = locale.into();
+    }
+
+    /// Returns the locales with messages, sorted.
+    #[must_use]
+    pub fn locales(&self) -> Vec<&str> {
+        let mut locales: Vec<&str> = self.catalogs.keys().map(String::as_str).collect();
+        locales.sort_unstable();
+        locales
+    }
+
+    /// Returns `key`'s template in the current locale, or else in the
+    /// default locale.
+    #[must_use]
+    pub fn lookup(&self, key: KeywordId) -> Option<&str> {
+        [self.locale.as_str(), DEFAULT_LOCALE]
+            .into_iter()
+            .find_map(|locale| self.catalogs.get(locale)?.get(&key))
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::Interner;
+
+    #[test]
+    fn falls_back_to_the_default_locale() {
+        let mut interner = Interner::new();
+        let take = interner.intern_keyword("msg/take");
+        let drop = interner.intern_keyword("msg/drop");
+        let missing = interner.intern_keyword("msg/missing");
+
+        let mut catalog = MessageCatalog::new();
+        catalog.define(DEFAULT_LOCALE, take, "You take ~a.");
+        catalog.define(DEFAULT_LOCALE, drop, "You drop ~a.");
+        catalog.define("fr", take, "Vous prenez ~a.");
+
+        assert_eq!(catalog.lookup(take), Some("You take ~a."));
+        catalog.set_locale("fr");
+        assert_eq!(catalog.lookup(take), Some("Vous prenez ~a."));
+        assert_eq!(catalog.lookup(drop), Some("You drop ~a."));
+        assert_eq!(catalog.lookup(missing), None);
+        assert_eq!(catalog.locales(), ["en", "fr"]);
+    }
+}
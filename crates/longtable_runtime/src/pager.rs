@@ -0,0 +1,212 @@
+//! Output pagination for game mode.
+//!
+//! Long room descriptions and inventories can scroll off-screen before the
+//! player has a chance to read them. The [`Pager`] counts lines written since
+//! the last command and pauses with a `[MORE]` prompt when a page fills up.
+
+use std::io::{self, IsTerminal, Write};
+
+/// Default page height when the terminal size is unknown.
+const DEFAULT_PAGE_HEIGHT: usize = 24;
+
+/// Line-counting pager that pauses output between pages.
+#[derive(Clone, Debug)]
+pub struct Pager {
+    /// Lines per page, or `None` to disable pagination.
+    page_height: Option<usize>,
+    /// Prompt shown when a page fills up.
+    prompt: String,
+    /// Complete lines written since the last pause or reset.
+    lines_written: usize,
+    /// Set when the reader declined to see more; output is dropped until reset.
+    suppressed: bool,
+}
+
+impl Pager {
+    /// Creates a pager that pauses every `page_height` lines.
+    ///
+    /// One line of each page is reserved for the prompt itself.
+    #[must_use]
+    pub fn new(page_height: usize) -> Self {
+        Self {
+            page_height: Some(page_height.max(2)),
+            prompt: "[MORE]".to_string(),
+            lines_written: 0,
+            suppressed: false,
+        }
+    }
+
+    /// Creates a pager that never pauses.
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self {
+            page_height: None,
+            ..Self::new(DEFAULT_PAGE_HEIGHT)
+        }
+    }
+
+    /// Creates a pager suited to standard output.
+    ///
+    /// Pagination is disabled when stdout is not a terminal (pipes, files,
+    /// test harnesses). Otherwise the page height is taken from the `LINES`
+    /// environment variable, falling back to 24.
+    #[must_use]
+    pub fn for_stdout() -> Self {
+        if !io::stdout().is_terminal() {
+            return Self::disabled();
+        }
+        let height = std::env::var("LINES")
+            .ok()
+            .and_then(|lines| lines.trim().parse().ok())
+            .unwrap_or(DEFAULT_PAGE_HEIGHT);
+        Self::new(height)
+    }
+
+    /// Sets the prompt shown between pages.
+    #[must_use]
+    pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Returns the page height, or `None` if pagination is disabled.
+    #[must_use]
+    pub const fn page_height(&self) -> Option<usize> {
+        self.page_height
+    }
+
+    /// Returns true if this pager will ever pause.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.page_height.is_some()
+    }
+
+    /// Starts a fresh page.
+    ///
+    /// Call this whenever the user has just entered input, since the screen
+    /// has scrolled to show their command.
+    pub fn reset(&mut self) {
+        self.lines_written = 0;
+        self.suppressed = false;
+    }
+
+    /// Writes `text` to `out`, pausing between pages.
+    ///
+    /// When a page fills up, `out` is flushed and `more` is called with the
+    /// prompt. If it returns false, the rest of the output is discarded until
+    /// the next [`reset`](Self::reset).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `out` fails.
+    pub fn write<W: Write>(
+        &mut self,
+        text: &str,
+        out: &mut W,
+        mut more: impl FnMut(&str) -> bool,
+    ) -> io::Result<()> {
+        let Some(height) = self.page_height else {
+            return out.write_all(text.as_bytes());
+        };
+
+        for line in text.split_inclusive('\n') {
+            if self.suppressed {
+                return Ok(());
+            }
+
+            if self.lines_written >= height - 1 {
+                out.flush()?;
+                if !more(&self.prompt) {
+                    self.suppressed = true;
+                    return Ok(());
+                }
+                self.lines_written = 0;
+            }
+
+            out.write_all(line.as_bytes())?;
+            if line.ends_with('\n') {
+                self.lines_written += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Pager {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(n: usize) -> String {
+        (1..=n)
+            .map(|i| format!("line {i}\n"))
+            .collect::<Vec<_>>()
+            .concat()
+    }
+
+    #[test]
+    fn disabled_pager_never_pauses() {
+        let mut pager = Pager::disabled();
+        let mut out = Vec::new();
+        pager
+            .write(&lines(100), &mut out, |_| panic!("should not pause"))
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), lines(100));
+    }
+
+    #[test]
+    fn pauses_when_page_is_full() {
+        let mut pager = Pager::new(4);
+        let mut out = Vec::new();
+        let mut prompts = Vec::new();
+        pager
+            .write(&lines(7), &mut out, |prompt| {
+                prompts.push(prompt.to_string());
+                true
+            })
+            .unwrap();
+
+        // 3 lines per page (one reserved for the prompt)
+        assert_eq!(prompts, vec!["[MORE]", "[MORE]"]);
+        assert_eq!(String::from_utf8(out).unwrap(), lines(7));
+    }
+
+    #[test]
+    fn declining_discards_remaining_output() {
+        let mut pager = Pager::new(3);
+        let mut out = Vec::new();
+        pager.write(&lines(10), &mut out, |_| false).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), lines(2));
+
+        // Further output is dropped until reset
+        let mut out = Vec::new();
+        pager.write("more\n", &mut out, |_| true).unwrap();
+        assert!(out.is_empty());
+
+        pager.reset();
+        pager.write("more\n", &mut out, |_| true).unwrap();
+        assert_eq!(out, b"more\n");
+    }
+
+    #[test]
+    fn line_count_carries_across_writes() {
+        let mut pager = Pager::new(3);
+        let mut out = Vec::new();
+        let mut pauses = 0;
+        for _ in 0..4 {
+            pager
+                .write("x\n", &mut out, |_| {
+                    pauses += 1;
+                    true
+                })
+                .unwrap();
+        }
+        assert_eq!(pauses, 1);
+    }
+}
@@ -0,0 +1,296 @@
+//! Precompiled modules (`.ltc`).
+//!
+//! `longtable compile world.lt` parses a source file once and writes its
+//! forms to `world.ltc`, so loading it later skips the parser. Loading
+//! `world.lt` picks up a `world.ltc` beside it automatically as long as the
+//! module was built by the same version of Longtable from the source as it
+//! is now, judged by the file's size and modification time, so a fresh
+//! module is used without reading the source at all. A stale or unreadable
+//! module is ignored and the source is parsed as usual. An `.ltc` file can
+//! also be loaded directly, without its source.
+//!
+//! Modules hold parsed forms rather than bytecode: compiled programs refer to
+//! global slots and interned keyword IDs, which depend on everything loaded
+//! into the session before them, so forms are still compiled as they load.
+//!
+//! A module is the bytes `LTC` followed by a `MessagePack` record:
+//!
+//! ```text
+//! {version: 2, compiler: "0.1.0", source: "world.lt",
+//!  source_stamp: {len: 812, modified: 1760521996000000000}, forms: [...]}
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use longtable_foundation::{Error, ErrorKind, Result};
+use longtable_language::{Ast, parse_recovering};
+use serde::{Deserialize, Serialize};
+
+/// Version of the module format written by [`PrecompiledModule::to_bytes`].
+pub const MODULE_FORMAT_VERSION: u32 = 2;
+
+/// File extension for precompiled modules.
+pub const MODULE_EXTENSION: &str = "ltc";
+
+/// Leading bytes of every module.
+const MAGIC: &[u8] = b"LTC";
+
+/// A source file's parsed forms, ready to load without parsing.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PrecompiledModule {
+    /// Module format version.
+    pub version: u32,
+    /// Version of Longtable that wrote the module.
+    pub compiler: String,
+    /// Name of the source file, for messages.
+    pub source: String,
+    /// Size and modification time of the source file it was built from,
+    /// if that file was on disk.
+    pub source_stamp: Option<SourceStamp>,
+    /// Top-level forms, in source order.
+    pub forms: Vec<Ast>,
+}
+
+impl PrecompiledModule {
+    /// Parses `source`, read from `path`, into a module stamped with the
+    /// file's current size and modification time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing every syntax error in the source.
+    pub fn compile(source: &str, path: &Path) -> Result<Self> {
+        Ok(Self {
+            version: MODULE_FORMAT_VERSION,
+            compiler: env!("CARGO_PKG_VERSION").to_string(),
+            source: path
+                .file_name()
+                .map_or_else(String::new, |name| name.to_string_lossy().into_owned()),
+            source_stamp: SourceStamp::of(path),
+            forms: parse_module(source, path)?,
+        })
+    }
+
+    /// Returns true if this module was built by this version of Longtable
+    /// from the file at `source_path` as it is now.
+    #[must_use]
+    pub fn is_fresh_for(&self, source_path: &Path) -> bool {
+        self.compiler == env!("CARGO_PKG_VERSION")
+            && self.source_stamp.is_some()
+            && self.source_stamp == SourceStamp::of(source_path)
+    }
+
+    /// Serializes the module.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = MAGIC.to_vec();
+        rmp_serde::encode::write_named(&mut bytes, self)
+            .map_err(|e| Error::new(ErrorKind::SerializationError(e.to_string())))?;
+        Ok(bytes)
+    }
+
+    /// Deserializes a module written by [`Self::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not a module, or were written by a
+    /// different version of Longtable.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let body = bytes.strip_prefix(MAGIC).ok_or_else(|| {
+            Error::new(ErrorKind::SerializationError(
+                "not a precompiled module".to_string(),
+            ))
+        })?;
+        let module: Self = rmp_serde::from_slice(body)
+            .map_err(|e| Error::new(ErrorKind::SerializationError(e.to_string())))?;
+        if module.version != MODULE_FORMAT_VERSION || module.compiler != env!("CARGO_PKG_VERSION") {
+            return Err(Error::new(ErrorKind::SerializationError(format!(
+                "{} was precompiled by longtable {} (module format {}); recompile it",
+                module.source, module.compiler, module.version
+            ))));
+        }
+        Ok(module)
+    }
+
+    /// Writes the module to a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or writing fails.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path.as_ref(), self.to_bytes()?).map_err(|e| {
+            Error::new(ErrorKind::IoError(format!(
+                "failed to write module '{}': {e}",
+                path.as_ref().display()
+            )))
+        })
+    }
+
+    /// Reads a module from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a current module.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let bytes = std::fs::read(path.as_ref()).map_err(|e| {
+            Error::new(ErrorKind::IoError(format!(
+                "failed to read module '{}': {e}",
+                path.as_ref().display()
+            )))
+        })?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Returns the module beside `source_path` if it is fresh for the file.
+    #[must_use]
+    pub fn cached_for(source_path: &Path) -> Option<Self> {
+        Self::load(module_path(source_path))
+            .ok()
+            .filter(|module| module.is_fresh_for(source_path))
+    }
+}
+
+/// What a module remembers of its source file to tell whether it changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceStamp {
+    /// Size of the file in bytes.
+    pub len: u64,
+    /// Modification time, in nanoseconds since the Unix epoch.
+    pub modified: u64,
+}
+
+impl SourceStamp {
+    /// Reads the stamp of the file at `path`, or `None` if the file or its
+    /// modification time can't be read.
+    #[must_use]
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            len: metadata.len(),
+            modified: u64::try_from(modified.as_nanos()).ok()?,
+        })
+    }
+}
+
+/// Returns the path a source file's module is written to: the same path with
+/// an `.ltc` extension.
+#[must_use]
+pub fn module_path(source_path: &Path) -> PathBuf {
+    source_path.with_extension(MODULE_EXTENSION)
+}
+
+/// Returns true if `path` names a precompiled module.
+#[must_use]
+pub fn is_module_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(MODULE_EXTENSION))
+}
+
+/// Parses a file's source, reporting every syntax error in it at once.
+///
+/// # Errors
+///
+/// Returns the syntax error, or a summary of all of them if there are several.
+pub fn parse_module(source: &str, path: &Path) -> Result<Vec<Ast>> {
+    let (forms, mut errors) = parse_recovering(source);
+    if errors.len() == 1 {
+        return Err(errors.remove(0));
+    }
+    if let Some(first) = errors.first() {
+        let report = errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n  ");
+        // Point at the first error, like a single one would
+        let (line, column, context) = match &first.kind {
+            ErrorKind::ParseError {
+                line,
+                column,
+                context,
+                ..
+            } => (*line, *column, context.clone()),
+            _ => (0, 0, String::new()),
+        };
+        return Err(Error::new(ErrorKind::ParseError {
+            message: format!(
+                "{} parse errors in {}:\n  {report}",
+                errors.len(),
+                path.display()
+            ),
+            line,
+            column,
+            context,
+        }));
+    }
+    Ok(forms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "(component: health :current :int)\n(def x `(a ~b))\n";
+
+    #[test]
+    fn module_round_trips() {
+        let module = PrecompiledModule::compile(SOURCE, Path::new("game/world.lt")).unwrap();
+        assert_eq!(module.source, "world.lt");
+        assert_eq!(module.forms.len(), 2);
+
+        let bytes = module.to_bytes().unwrap();
+        assert!(bytes.starts_with(b"LTC"));
+        assert_eq!(PrecompiledModule::from_bytes(&bytes).unwrap(), module);
+    }
+
+    #[test]
+    fn modules_know_their_source() {
+        let path = std::env::temp_dir().join("longtable_test_module_stamp.lt");
+        std::fs::write(&path, SOURCE).unwrap();
+        let module = PrecompiledModule::compile(SOURCE, &path).unwrap();
+        let fresh = module.is_fresh_for(&path);
+        std::fs::write(&path, "(component: health :current :float)").unwrap();
+        let stale = module.is_fresh_for(&path);
+        std::fs::remove_file(&path).ok();
+
+        assert!(fresh);
+        assert!(!stale);
+        // A module built from source that isn't on disk is never fresh
+        let module = PrecompiledModule::compile(SOURCE, Path::new("no/such/world.lt")).unwrap();
+        assert!(!module.is_fresh_for(Path::new("no/such/world.lt")));
+    }
+
+    #[test]
+    fn several_parse_errors_are_one_parse_error() {
+        let err = parse_module("(def b ]\n(def d {:x}\n", Path::new("world.lt")).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::ParseError { .. }), "{err}");
+        assert!(
+            err.to_string().contains("2 parse errors in world.lt"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_foreign_and_outdated_modules() {
+        assert!(PrecompiledModule::from_bytes(b"not a module").is_err());
+
+        let mut module = PrecompiledModule::compile(SOURCE, Path::new("world.lt")).unwrap();
+        module.compiler = "0.0.0-old".to_string();
+        let err = PrecompiledModule::from_bytes(&module.to_bytes().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("recompile it"), "{err}");
+    }
+
+    #[test]
+    fn module_paths() {
+        assert_eq!(
+            module_path(Path::new("game/world.lt")),
+            PathBuf::from("game/world.ltc")
+        );
+        assert!(is_module_file(Path::new("world.ltc")));
+        assert!(!is_module_file(Path::new("world.lt")));
+    }
+}
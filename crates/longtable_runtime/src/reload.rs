@@ -0,0 +1,109 @@
+//! Hot reload of `.lt` source files.
+//!
+//! The REPL remembers every file it loads in a [`FileWatcher`]. With hot
+//! reload on (`longtable --watch`), it checks the watched files before each
+//! prompt and reloads the ones that changed; `(reload)` does the same on
+//! demand. Reloading a file swaps its definitions in place of the old ones
+//! while the world keeps running:
+//!
+//! - rules, commands, and constraints the file used to declare are removed
+//!   first, so edited ones are replaced and deleted ones stop firing;
+//! - components and relationships it declares again get their new schemas,
+//!   keeping the data already stored under them;
+//! - verbs, actions, and functions are simply declared again;
+//! - `spawn:` and `link:` declarations that were already applied are
+//!   skipped, so entities keep their state, while new ones are applied.
+//!
+//! A reload either applies in full or not at all: a file that fails to parse
+//! or evaluate leaves the session as it was.
+//!
+//! Files are polled by modification time rather than watched with OS
+//! notifications, so there is no background thread to coordinate with.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Tracks loaded source files and notices when they change.
+#[derive(Clone, Debug, Default)]
+pub struct FileWatcher {
+    /// Watched files and their modification times when last seen.
+    files: BTreeMap<PathBuf, Option<SystemTime>>,
+}
+
+impl FileWatcher {
+    /// Creates a watcher with no files.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts watching `path`, or marks it as up to date if it is already
+    /// watched.
+    pub fn watch(&mut self, path: &Path) {
+        self.files.insert(path.to_path_buf(), modified(path));
+    }
+
+    /// Stops watching `path`. Returns false if it wasn't watched.
+    pub fn unwatch(&mut self, path: &Path) -> bool {
+        self.files.remove(path).is_some()
+    }
+
+    /// Returns the watched files, in path order.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+
+    /// Returns the watched files that changed since they were last seen,
+    /// in path order, and marks them as seen.
+    ///
+    /// A file that can't be read (for instance, mid-save) counts as
+    /// unchanged until it can be.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, seen) in &mut self.files {
+            let Some(current) = modified(path) else {
+                continue;
+            };
+            if *seen != Some(current) {
+                *seen = Some(current);
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+/// Returns a file's modification time, if it can be read.
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn notices_changed_files() {
+        let dir = std::env::temp_dir().join("longtable_test_file_watcher");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("rules.lt");
+        fs::write(&path, "(def x 1)").unwrap();
+
+        let mut watcher = FileWatcher::new();
+        watcher.watch(&path);
+        assert!(watcher.changed().is_empty());
+
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(watcher.changed(), vec![path.clone()]);
+        assert!(watcher.changed().is_empty());
+
+        assert!(watcher.unwatch(&path));
+        assert_eq!(watcher.files().count(), 0);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
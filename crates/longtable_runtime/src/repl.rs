@@ -1,27 +1,102 @@
 //! The main REPL implementation.
 
-use crate::editor::{LineEditor, ReadResult, RustylineEditor};
+use crate::coverage::CoverageReport;
+use crate::datoms;
+use crate::editor::{DefaultEditor, LineEditor, ReadResult};
+use crate::explain;
+use crate::hooks::{ActionHook, HookTarget, HookTiming};
+use crate::lint::{self, SourceSite};
+use crate::pager::Pager;
+use crate::precompiled::{self, PrecompiledModule};
+use crate::reload::FileWatcher;
+use crate::replay::ReplayLog;
+use crate::rich;
+use crate::scenario::{self, Scenario, ScenarioFailure, ScenarioResult};
 use crate::serialize;
-use crate::session::{Session, SessionContext};
+use crate::session::{self, Poisoned, Session, SessionContext, WarningMode};
+use crate::telemetry::{ParseFailureClass, TelemetryEvent};
+use crate::transcript::{InputOutcome, TranscriptEntry};
 
 /// Embedded core stdlib functions.
 const STDLIB_CORE: &str = include_str!("../../longtable_stdlib/stdlib/core.lt");
+/// Embedded default handlers for the standard adventure actions, loaded by
+/// `(require [stdlib.actions])`.
+const STDLIB_ACTIONS: &str = include_str!("../../longtable_stdlib/stdlib/actions.lt");
+
+/// Returns the source of a stdlib module games can require, by namespace.
+fn stdlib_module(namespace: &str) -> Option<&'static str> {
+    match namespace {
+        "stdlib.actions" => Some(STDLIB_ACTIONS),
+        _ => None,
+    }
+}
+use longtable_debug::{DebugSession, ObservabilityConfig, TickPhase as TracePhase, Tracer};
+use longtable_engine::provenance::{LinkChange, ProvenanceVerbosity};
 use longtable_engine::{
-    Bindings, InputEvent, PatternCompiler, PatternMatcher, QueryCompiler, QueryExecutor,
-    TickExecutor,
+    Bindings, ConstraintCompiler, DebugPoint, EffectMiddleware, ExecutionMode,
+    HIGH_FAN_OUT_THRESHOLD, InputEvent, PatternCompiler, PatternMatcher, PlanAccess, QueryCompiler,
+    QueryExecutor, QueryWarning, System, SystemAccess, TickExecutor, TickPhase,
 };
-use longtable_foundation::{EntityId, Error, ErrorKind, KeywordId, Result, Value};
+use longtable_foundation::clock::{self, Instant};
+use longtable_foundation::{EntityId, Error, ErrorKind, Interner, KeywordId, LtMap, Result, Value};
 use longtable_language::{
-    Ast, Compiler, Declaration, DeclarationAnalyzer, NamespaceContext, NamespaceInfo, Vm, parse,
+    Ast, CompiledProgram, Compiler, Declaration, DeclarationAnalyzer, DependencyGraph,
+    NamespaceContext, NamespaceDecl, NamespaceInfo, RichSpan, SESSION_COMMANDS, Span, Vm, VmEffect,
+    WorldContext, compile_expression_with_interner, dependency::is_source_file, parse, plain_text,
 };
-use longtable_parser::NounResolver;
-use longtable_parser::parser::{NaturalLanguageParser, ParseError, ParseResult};
+use longtable_parser::command::CommandEntity;
+use longtable_parser::parser::{NaturalLanguageParser, ParseError, ParseResult, ParseStep};
+use longtable_parser::{NounResolver, TopicResolver};
+use longtable_storage::{World, count_lookups};
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write as _;
 use std::fs;
-use std::io::{self, Write};
-use std::path::Path;
+use std::io::{self, IsTerminal, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+/// How errors are reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Colored messages with their context and hints, for people.
+    #[default]
+    Human,
+    /// One JSON object per error (see [`Error::to_json`]), for tools.
+    Json,
+}
+
+impl ErrorFormat {
+    /// Parses a format name: `human` or `json`.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(Self::Human),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Writes `error` to stderr in this format.
+    pub fn report(self, error: &Error) {
+        match self {
+            Self::Human => {
+                eprintln!("\x1b[31mError: {error}\x1b[0m");
+                if let Some(context) = &error.context {
+                    eprintln!("\x1b[31m  {}\x1b[0m", context.to_string().trim_end());
+                }
+                for hint in &error.hints {
+                    eprintln!("  hint: {hint}");
+                }
+            }
+            Self::Json => eprintln!("{}", error.to_json()),
+        }
+    }
+}
 
 /// The interactive REPL.
-pub struct Repl<E: LineEditor = RustylineEditor> {
+#[allow(clippy::struct_excessive_bools)]
+pub struct Repl<E: LineEditor = DefaultEditor> {
     /// The line editor for input.
     editor: E,
 
@@ -51,17 +126,62 @@ pub struct Repl<E: LineEditor = RustylineEditor> {
 
     /// Prompt to use in input mode.
     input_mode_prompt: String,
+
+    /// Pager for narration output in input mode.
+    pager: Pager,
+
+    /// Narration collected by [`Repl::take_output`] instead of written to stdout.
+    captured: Option<Vec<RichSpan>>,
+
+    /// The action whose handlers are running, with its entity bindings, so
+    /// spawns and links can be attributed to it. `None` means the REPL itself.
+    effect_origin: Option<(KeywordId, Vec<(String, EntityId)>)>,
+
+    /// Source files loaded so far, for reloading when they change.
+    watcher: FileWatcher,
+
+    /// Whether changed files are reloaded before each prompt.
+    hot_reload: bool,
+
+    /// Whether a tick that panics is rolled back at once instead of
+    /// poisoning the session.
+    auto_recover: bool,
+
+    /// Whether a tick is running, so commands it queues can't start another.
+    ticking: bool,
+
+    /// The session rule revision the tick executor's rules were copied at.
+    synced_rules: Option<u64>,
+
+    /// Where [`Repl::shutdown`] saves the world, if anywhere.
+    exit_checkpoint: Option<PathBuf>,
+
+    /// How errors are printed.
+    error_format: ErrorFormat,
 }
 
-impl Repl<RustylineEditor> {
+#[cfg(feature = "cli")]
+impl Repl<DefaultEditor> {
     /// Creates a new REPL with the default rustyline editor.
     ///
     /// # Errors
     ///
     /// Returns an error if the editor fails to initialize.
     pub fn new() -> Result<Self> {
-        let editor = RustylineEditor::new()?;
-        Ok(Self::with_editor(editor))
+        let editor = DefaultEditor::new()?;
+        Ok(Self::with_editor(editor).with_pager(Pager::for_stdout()))
+    }
+}
+
+#[cfg(not(feature = "cli"))]
+impl Repl<DefaultEditor> {
+    /// Creates a new REPL with no terminal, capturing its output.
+    ///
+    /// # Errors
+    ///
+    /// Never fails; the signature matches the `cli` build.
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_editor(DefaultEditor::default()).with_captured_output())
     }
 }
 
@@ -79,6 +199,16 @@ impl<E: LineEditor> Repl<E> {
             continuation_prompt: ".. ".to_string(),
             input_mode: false,
             input_mode_prompt: "> ".to_string(),
+            pager: Pager::disabled(),
+            captured: None,
+            effect_origin: None,
+            watcher: FileWatcher::new(),
+            hot_reload: false,
+            auto_recover: false,
+            ticking: false,
+            synced_rules: None,
+            exit_checkpoint: None,
+            error_format: ErrorFormat::Human,
         }
     }
 
@@ -103,6 +233,107 @@ impl<E: LineEditor> Repl<E> {
         self
     }
 
+    /// Sets the pager used for output in input mode.
+    ///
+    /// Use [`Pager::disabled`] for non-interactive sinks.
+    #[must_use]
+    pub fn with_pager(mut self, pager: Pager) -> Self {
+        self.pager = pager;
+        self
+    }
+
+    /// Collects narration and player-facing messages for [`Repl::take_output`]
+    /// instead of writing them to stdout.
+    ///
+    /// For embedders with no terminal, such as a browser player.
+    #[must_use]
+    pub fn with_captured_output(mut self) -> Self {
+        self.captured = Some(Vec::new());
+        self
+    }
+
+    /// Returns the output captured since the last call and clears it.
+    ///
+    /// Always empty unless the REPL was built [`with_captured_output`](Self::with_captured_output).
+    pub fn take_output(&mut self) -> String {
+        plain_text(&self.take_rich_output())
+    }
+
+    /// Returns the output captured since the last call as styled spans, and
+    /// clears it.
+    ///
+    /// Like [`take_output`](Self::take_output), but keeps the markup `say`
+    /// was given, for front-ends that render it themselves.
+    pub fn take_rich_output(&mut self) -> Vec<RichSpan> {
+        self.captured
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Sets the execution mode.
+    ///
+    /// [`ExecutionMode::Play`] switches off provenance, tracing, and history
+    /// capture so shipped games don't pay for debugging support.
+    #[must_use]
+    pub fn with_mode(mut self, mode: ExecutionMode) -> Self {
+        self.tick_executor = std::mem::take(&mut self.tick_executor).with_mode(mode);
+        if mode == ExecutionMode::Play {
+            self.apply_observability(ObservabilityConfig::play());
+        }
+        self
+    }
+
+    /// Enables content features for `(when-feature ...)` forms.
+    #[must_use]
+    pub fn with_features<I, S>(mut self, features: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for feature in features {
+            self.session.enable_feature(feature);
+        }
+        self
+    }
+
+    /// Reloads changed source files before each prompt (see
+    /// [`crate::reload`]).
+    #[must_use]
+    pub const fn with_hot_reload(mut self, enabled: bool) -> Self {
+        self.hot_reload = enabled;
+        self
+    }
+
+    /// Restores the world from before a tick that panics, instead of
+    /// poisoning the session until [`Repl::recover`] is called.
+    #[must_use]
+    pub const fn with_auto_recover(mut self, enabled: bool) -> Self {
+        self.auto_recover = enabled;
+        self
+    }
+
+    /// Saves the world to `path` when the REPL [shuts down](Self::shutdown).
+    #[must_use]
+    pub fn with_exit_checkpoint(mut self, path: impl Into<PathBuf>) -> Self {
+        self.exit_checkpoint = Some(path.into());
+        self
+    }
+
+    /// Sets how errors are printed.
+    #[must_use]
+    pub const fn with_error_format(mut self, format: ErrorFormat) -> Self {
+        self.error_format = format;
+        self
+    }
+
+    /// Sets what happens to query warnings.
+    #[must_use]
+    pub fn with_warning_mode(mut self, mode: WarningMode) -> Self {
+        self.session.set_warning_mode(mode);
+        self
+    }
+
     /// Sets the primary prompt.
     #[must_use]
     pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
@@ -121,6 +352,268 @@ impl<E: LineEditor> Repl<E> {
         &mut self.session
     }
 
+    /// Returns the number of ticks run so far.
+    #[must_use]
+    pub fn tick_number(&self) -> u64 {
+        self.tick_executor.tick_number()
+    }
+
+    /// Returns the effect middleware, to register interceptors that see
+    /// every effect before it is applied, whether it comes from the REPL or
+    /// from a tick.
+    pub fn middleware_mut(&mut self) -> &mut EffectMiddleware {
+        self.tick_executor.middleware_mut()
+    }
+
+    /// Registers a Rust system to run every tick at `phase`. See
+    /// [`TickExecutor::register_system`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the system can't run at `phase` or its `access`
+    /// conflicts with another system of the same phase.
+    pub fn register_system(
+        &mut self,
+        name: impl Into<String>,
+        phase: TickPhase,
+        access: SystemAccess,
+        system: impl System + 'static,
+    ) -> Result<()> {
+        self.tick_executor
+            .register_system(name, phase, access, system)
+    }
+
+    /// Runs one tick without printing a summary, committing the new world
+    /// if no constraint rolled it back.
+    ///
+    /// A panic during the tick, say in a native function or a host system,
+    /// is caught. The session is then [poisoned](Session::poisoned) and
+    /// won't tick again until [recovered](Self::recover), or, with
+    /// [`with_auto_recover`](Self::with_auto_recover), the world from before
+    /// the tick is restored at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a phase hook, timer, or rule fails, if the tick
+    /// panics, if the session is poisoned, or if a tick is already running.
+    pub fn step(&mut self, inputs: &[InputEvent]) -> Result<longtable_engine::TickResult> {
+        if let Some(poisoned) = self.session.poisoned() {
+            return Err(poisoned_error(poisoned));
+        }
+        if self.ticking {
+            return Err(Error::new(ErrorKind::Refused(
+                "(tick!) can't run while a tick is in progress".to_string(),
+            )));
+        }
+
+        let checkpoint = self.session.world().clone();
+        let tick = self.tick_executor.tick_number() + 1;
+        self.ticking = true;
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| self.step_unguarded(inputs)));
+        self.ticking = false;
+        match outcome {
+            Ok(result) => result,
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(ToString::to_string)
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                // Whatever the VM was running was cut short
+                self.vm.abort();
+                let poisoned = Poisoned {
+                    message,
+                    tick,
+                    checkpoint,
+                };
+                let error = poisoned_error(&poisoned);
+                self.session.poison(poisoned);
+                if self.auto_recover {
+                    self.recover(false)?;
+                    return Err(Error::new(ErrorKind::Refused(format!(
+                        "tick {tick} panicked; restored the world from before it"
+                    ))));
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Runs one tick for [`Self::step`], which catches any panic.
+    fn step_unguarded(&mut self, inputs: &[InputEvent]) -> Result<longtable_engine::TickResult> {
+        let started = Instant::now();
+        let mut result = self.run_tick(inputs)?;
+        self.session.telemetry_mut().record(TelemetryEvent::Tick {
+            duration: started.elapsed().into(),
+        });
+        if result.success {
+            let interner = result.world.interner();
+            for (rule, stats) in result.rule_metrics.by_total_time() {
+                if let (true, Some(name)) = (stats.activations > 0, interner.get_keyword(rule)) {
+                    self.session.coverage_mut().record_rule(name);
+                }
+            }
+            self.session.set_world(result.world.clone());
+            let tracer = self.session.tracer_mut();
+            tracer.set_tick(result.world.tick());
+            for step in &result.cascades {
+                tracer.relationship_cascade(step);
+            }
+            for run in &result.systems {
+                tracer.system_run(run);
+            }
+        }
+        self.run_commands(std::mem::take(&mut result.commands))?;
+        Ok(result)
+    }
+
+    /// Evaluates the enabled watch expressions against `world` and records
+    /// their values in the debug session, returning a report of the watches
+    /// that changed or failed.
+    ///
+    /// Watches only read the world: any effects they produce are dropped.
+    /// A watch whose value differs from the one it had at the previous tick
+    /// pauses the debug session. A watch that fails to evaluate is reported
+    /// without failing the tick.
+    fn evaluate_watches(
+        compiler: &mut Compiler,
+        vm: &mut Vm,
+        interner: &mut Interner,
+        debug: &mut DebugSession,
+        world: &World,
+    ) -> String {
+        use longtable_debug::PauseReason;
+
+        let watches: Vec<_> = debug
+            .watches()
+            .iter()
+            .filter(|w| w.is_enabled())
+            .map(|w| (w.id(), w.source().to_string()))
+            .collect();
+
+        let mut report = String::new();
+        for (id, source) in watches {
+            let value = match Self::evaluate_watch(compiler, vm, interner, &source, world) {
+                Ok(value) => value,
+                Err(e) => {
+                    let _ = writeln!(report, "watch {id} {source} failed: {e}");
+                    continue;
+                }
+            };
+            let Some(watch) = debug.watches_mut().get_mut(id) else {
+                continue;
+            };
+            match watch.set_value(value.clone()) {
+                Some(previous) if previous != value => {
+                    let _ = writeln!(
+                        report,
+                        "watch {id} {source}: {} -> {}",
+                        format_value_with(&previous, interner),
+                        format_value_with(&value, interner)
+                    );
+                    debug.pause(PauseReason::WatchChanged(id));
+                }
+                _ => {}
+            }
+        }
+        report
+    }
+
+    /// Evaluates one watch expression read-only against `world`.
+    fn evaluate_watch(
+        compiler: &mut Compiler,
+        vm: &mut Vm,
+        interner: &mut Interner,
+        source: &str,
+        world: &World,
+    ) -> Result<Value> {
+        let form = longtable_language::parse_one(source)?;
+
+        compiler.prepare_for_compilation();
+        compiler.set_interner(interner.clone());
+        let program = compiler.compile(std::slice::from_ref(&form));
+        if let Some(updated) = compiler.take_interner() {
+            *interner = updated;
+        }
+        for (name, &slot) in compiler.globals() {
+            vm.register_global(name.clone(), slot);
+        }
+
+        let result = vm.execute_with_context(&program?, &WorldContext::new(world));
+        vm.take_effects();
+        vm.clear_output();
+        result
+    }
+
+    /// Clears a [poisoned](Session::poisoned) session so it can tick again.
+    ///
+    /// Restores the world and tick number from before the tick that
+    /// panicked, or with `keep`, carries on with the world as the panic
+    /// left it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session isn't poisoned.
+    pub fn recover(&mut self, keep: bool) -> Result<()> {
+        let poisoned = self.session.clear_poison().ok_or_else(|| {
+            Error::new(ErrorKind::Refused(
+                "nothing to recover: no tick has panicked".to_string(),
+            ))
+        })?;
+        if !keep {
+            self.rewind_world(poisoned.checkpoint);
+            self.tick_executor.set_tick_number(poisoned.tick - 1);
+        }
+        Ok(())
+    }
+
+    /// Replaces the world with an earlier or loaded one.
+    ///
+    /// The rule engine's record of which `:on-change` and `:once-per-entity`
+    /// activations have fired describes the world being replaced, so it is
+    /// forgotten, as are the pronouns and the input "again" repeats.
+    fn rewind_world(&mut self, world: World) {
+        self.session.set_world(world);
+        self.session.reset_pronoun_state();
+        self.forget_refraction();
+    }
+
+    /// Forgets which refracted rule activations have fired, after the
+    /// session's world was swapped for another.
+    fn forget_refraction(&mut self) {
+        self.tick_executor.rule_engine_mut().reset_refraction();
+    }
+
+    /// Shuts the session down cleanly.
+    ///
+    /// Saves the world to the [exit checkpoint](Self::with_exit_checkpoint),
+    /// if one is set, sends pending telemetry and closes its sink, and
+    /// flushes trace and narration output. A poisoned session saves the
+    /// world from before the tick that panicked. [`Self::run`] calls this
+    /// on exit; hosts driving the REPL themselves should call it when they
+    /// are done. Calling it again does no harm.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checkpoint can't be written. The telemetry
+    /// and output are still flushed.
+    pub fn shutdown(&mut self) -> Result<()> {
+        let saved = match &self.exit_checkpoint {
+            Some(path) => {
+                let world = self
+                    .session
+                    .poisoned()
+                    .map_or(self.session.world(), |p| &p.checkpoint);
+                serialize::save_to_file(world, self.session.resolve_path(&path.to_string_lossy()))
+            }
+            None => Ok(()),
+        };
+        self.session.telemetry_mut().close();
+        let _ = io::stdout().flush();
+        let _ = io::stderr().flush();
+        saved
+    }
+
     /// Loads the standard library functions into the REPL session.
     ///
     /// This is called automatically by `run()`, but can be called manually
@@ -161,13 +654,53 @@ impl<E: LineEditor> Repl<E> {
         }
 
         println!("\nGoodbye!");
-        Ok(())
+        self.shutdown()
+    }
+
+    /// Returns the names tab completion offers from the session: REPL
+    /// special forms, declared components and relationships (as keywords),
+    /// compiled rules, named entities, and variables.
+    #[must_use]
+    pub fn completions(&self) -> Vec<String> {
+        let world = self.session.world();
+        let interner = world.interner();
+        let keyword = |id| interner.get_keyword(id).map(|name| format!(":{name}"));
+
+        let mut names: Vec<String> = crate::help::SPECIAL_FORMS
+            .iter()
+            .map(|form| form.name.to_string())
+            .collect();
+        names.extend(world.component_schemas().filter_map(|s| keyword(s.name)));
+        names.extend(world.relationship_schemas().filter_map(|s| keyword(s.name)));
+        names.extend(
+            self.session
+                .compiled_rules()
+                .iter()
+                .filter_map(|rule| interner.get_keyword(rule.name).map(String::from)),
+        );
+        names.extend(self.session.entity_names().keys().cloned());
+        names.extend(self.session.variables().keys().cloned());
+        names.sort();
+        names.dedup();
+        names
     }
 
     /// Executes one read-eval-print iteration.
     ///
     /// Returns `Ok(true)` to continue, `Ok(false)` to exit.
     fn read_eval_print(&mut self) -> Result<bool> {
+        if self.hot_reload {
+            for (path, result) in self.reload_changed_files() {
+                match result {
+                    Ok(()) => println!("Reloaded {}", path.display()),
+                    Err(e) => {
+                        eprintln!("\x1b[31mReload of {} failed:\x1b[0m", path.display());
+                        self.print_error(&e);
+                    }
+                }
+            }
+        }
+
         // Read input
         let Some(input) = self.read_input()? else {
             return Ok(false); // EOF
@@ -182,6 +715,9 @@ impl<E: LineEditor> Repl<E> {
         // Add to history
         self.editor.add_history(&input);
 
+        // The screen has scrolled to show the command, so start a fresh page
+        self.pager.reset();
+
         // In input mode, dispatch to natural language handler unless it's an S-expression
         if self.input_mode {
             // S-expressions (starting with '(') are still evaluated normally
@@ -232,6 +768,7 @@ impl<E: LineEditor> Repl<E> {
     fn read_input(&mut self) -> Result<Option<String>> {
         let mut input = String::new();
         let mut first_line = true;
+        self.editor.set_keywords(self.completions());
 
         loop {
             let prompt = if first_line {
@@ -315,15 +852,88 @@ impl<E: LineEditor> Repl<E> {
         let forms = parse(input)?;
 
         // Evaluate all forms
+        self.eval_top_level(&forms, None)
+    }
+
+    /// Handles a line of natural language input, as if typed in input mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matched action fails.
+    pub fn input(&mut self, line: &str) -> Result<Value> {
+        Ok(self.dispatch_input(line)?.unwrap_or(Value::Nil))
+    }
+
+    /// Evaluates top-level forms, remembering where named declarations came from.
+    fn eval_top_level(&mut self, forms: &[Ast], file: Option<&Path>) -> Result<Value> {
+        let outer = self
+            .compiler
+            .set_source(file.map(|file| file.display().to_string()));
+        let result = self.eval_forms(forms, file);
+        self.compiler.set_source(outer);
+        result
+    }
+
+    /// Evaluates top-level forms read from `file`, in order.
+    fn eval_forms(&mut self, forms: &[Ast], file: Option<&Path>) -> Result<Value> {
         let mut result = Value::Nil;
         for form in forms {
-            result = self.eval_form(&form)?;
+            self.record_declaration_site(form, file);
+            let (evaluated, lookups) = count_lookups(|| self.eval_form(form));
+            self.session.record_relationship_lookups(&lookups);
+            result = evaluated.map_err(|error| {
+                let span = form.span();
+                let error = error.or_position(span.line as usize, span.column as usize);
+                match file {
+                    Some(file) => error.or_source(file.display().to_string()),
+                    None => error,
+                }
+            })?;
         }
         Ok(result)
     }
 
+    /// Records the site of a `(form: name ...)` declaration so lints can point at it.
+    fn record_declaration_site(&mut self, form: &Ast, file: Option<&Path>) {
+        let Some((head, name)) = Self::declaration_key(form) else {
+            return;
+        };
+        let site = SourceSite {
+            file: file.map(Path::to_path_buf),
+            span: form.span(),
+        };
+        self.session.record_declaration_site(head, name, site);
+    }
+
+    /// Returns the form and name of a `(form: name ...)` declaration.
+    ///
+    /// `link:` declarations are named `source :relationship target`.
+    fn declaration_key(form: &Ast) -> Option<(&str, String)> {
+        let Ast::List(list, _) = form else {
+            return None;
+        };
+        let [Ast::Symbol(head, _), Ast::Symbol(name, _), rest @ ..] = list.as_slice() else {
+            return None;
+        };
+        if !head.ends_with(':') {
+            return None;
+        }
+
+        let name = match (head.as_str(), rest) {
+            ("link:", [Ast::Keyword(relationship, _), Ast::Symbol(target, _), ..]) => {
+                format!("{name} :{relationship} {target}")
+            }
+            _ => name.clone(),
+        };
+        Some((head, name))
+    }
+
     /// Evaluates a single form.
     fn eval_form(&mut self, form: &longtable_language::Ast) -> Result<Value> {
+        // Qualify `::name` and `::alias/name` keywords for the current namespace
+        let resolved = self.session.namespace_context().resolve_keywords(form)?;
+        let form = &resolved;
+
         // Check for special REPL forms
         if let Some(result) = self.try_special_form(form)? {
             return Ok(result);
@@ -338,12 +948,13 @@ impl<E: LineEditor> Repl<E> {
         let interner = self.session.world().interner().clone();
         self.compiler.set_interner(interner);
 
-        let program = self.compiler.compile(&[form.clone()])?;
+        let program = self.compiler.compile(std::slice::from_ref(form))?;
 
         // Sync interner back to session (compiler may have added keywords)
         if let Some(interner) = self.compiler.take_interner() {
             self.session.world_mut().set_interner(interner);
         }
+        self.session.declare_rules(self.compiler.take_rule_decls());
 
         // Sync compiler's globals map to VM for late-bound lookups (forward references)
         for (name, &slot) in self.compiler.globals() {
@@ -352,20 +963,65 @@ impl<E: LineEditor> Repl<E> {
 
         // Execute with full RuntimeContext for registration opcode support
         let mut ctx = SessionContext::new(&mut self.session);
-        let result = self.vm.execute_with_runtime_context(&program, &mut ctx)?;
+        let result = match self.vm.execute_with_runtime_context(&program, &mut ctx) {
+            Ok(result) => result,
+            Err(e) => {
+                // A form that fails applies none of its effects
+                self.vm.clear_effects();
+                return Err(e);
+            }
+        };
 
         // Apply any effects produced by VM execution (Link, Unlink, SetComponent, etc.)
-        self.apply_vm_effects()?;
+        let commands = self.apply_vm_effects()?;
 
         // Print any output from print/println/say calls
-        for line in self.vm.output() {
-            print!("{line}");
-        }
+        let output = self.vm.rich_output().to_vec();
         self.vm.clear_output();
+        self.write_spans(output);
+
+        // Session commands queued by the form run once its effects are in
+        self.run_commands(commands)?;
 
         Ok(result)
     }
 
+    /// Writes narration output, paginating it in input mode.
+    fn write_output(&mut self, text: &str) {
+        self.write_spans(vec![RichSpan::plain(text)]);
+    }
+
+    /// Writes styled narration output, as ANSI escapes on a terminal.
+    fn write_spans(&mut self, spans: Vec<RichSpan>) {
+        if spans.iter().all(|span| span.text.is_empty()) {
+            return;
+        }
+        if let Some(captured) = &mut self.captured {
+            captured.extend(spans);
+            return;
+        }
+
+        let mut stdout = io::stdout();
+        let text = if stdout.is_terminal() {
+            rich::to_ansi(&spans)
+        } else {
+            plain_text(&spans)
+        };
+        if !self.input_mode {
+            let _ = stdout.write_all(text.as_bytes());
+            return;
+        }
+
+        // Any input other than "q" continues to the next page
+        let editor = &mut self.editor;
+        let _ = self.pager.write(&text, &mut stdout, |prompt| {
+            matches!(
+                editor.read_line(prompt),
+                Ok(ReadResult::Line(line)) if !line.trim().eq_ignore_ascii_case("q")
+            )
+        });
+    }
+
     /// Applies VM effects to the world.
     ///
     /// Effects like `Link`, `Unlink`, `SetComponent`, etc. are collected during VM execution
@@ -374,13 +1030,42 @@ impl<E: LineEditor> Repl<E> {
     /// Mergeable effects (`VecRemove`, `VecAdd`, `SetRemove`, `SetAdd`) on the same (entity, component, field)
     /// are grouped and merged before application. This ensures that multiple operations on the same
     /// field within a single expression all take effect.
+    ///
+    /// The effects are applied as one [`World::transaction`]: if any of them
+    /// fails, none are, and nothing is recorded for them.
+    ///
+    /// Effects first pass through the tick executor's effect middleware, so
+    /// interceptors see REPL and tick effects alike. Session commands
+    /// (`VmEffect::Command`) are not applied here; they are returned for
+    /// [`Self::run_commands`].
     #[allow(clippy::too_many_lines, clippy::items_after_statements)]
-    fn apply_vm_effects(&mut self) -> Result<()> {
+    fn apply_vm_effects(&mut self) -> Result<Vec<VmEffect>> {
         use longtable_foundation::{KeywordId, LtSet, Type};
-        use longtable_language::VmEffect;
+        use longtable_storage::CascadeStep;
         use std::collections::HashMap;
 
         let effects = self.vm.take_effects();
+        let (commands, effects): (Vec<_>, Vec<_>) = self
+            .tick_executor
+            .middleware()
+            .process_all(effects, self.session.world())?
+            .into_iter()
+            .partition(|effect| matches!(effect, VmEffect::Command { .. }));
+        if effects.is_empty() {
+            return Ok(commands);
+        }
+
+        // Spawns and links are attributed to the running action, or to :repl
+        let (origin, context) = match &self.effect_origin {
+            Some((action, context)) => (*action, context.clone()),
+            None => (
+                self.session
+                    .world_mut()
+                    .interner_mut()
+                    .intern_keyword("repl"),
+                Vec::new(),
+            ),
+        };
 
         // Group mergeable effects by (entity, component, field)
         // Each entry contains (values_to_remove, values_to_add)
@@ -388,226 +1073,267 @@ impl<E: LineEditor> Repl<E> {
         let mut vec_field_ops: HashMap<FieldKey, (Vec<Value>, Vec<Value>)> = HashMap::new();
         let mut set_field_ops: HashMap<FieldKey, (Vec<Value>, Vec<Value>)> = HashMap::new();
 
-        // Mapping from temporary spawn IDs to actual entity IDs.
-        // With spawn_with_id, temp IDs become real IDs, so this map stays empty.
-        // Kept for future flexibility if spawn semantics change.
-        let temp_to_real_id: HashMap<EntityId, EntityId> = HashMap::new();
-
-        // Helper to translate entity IDs (temp -> real)
-        let translate_id = |id: EntityId, map: &HashMap<EntityId, EntityId>| -> EntityId {
-            *map.get(&id).unwrap_or(&id)
-        };
+        // Provenance, traces, and timers, recorded once the transaction commits
+        let mut links: Vec<(EntityId, KeywordId, EntityId, LinkChange)> = Vec::new();
+        let mut spawned: Vec<EntityId> = Vec::new();
+        let mut destroyed: Vec<(EntityId, Vec<CascadeStep>)> = Vec::new();
+        let mut scheduled = Vec::new();
+
+        let world = self.session.world().transaction(|tx| {
+            let mut effects = effects.into_iter().peekable();
+            while let Some(effect) = effects.next() {
+                match effect {
+                    // Non-mergeable effects: apply immediately
+                    VmEffect::Link {
+                        source,
+                        relationship,
+                        target,
+                    } => {
+                        tx.link(source, relationship, target);
+                        links.push((source, relationship, target, LinkChange::Linked));
+                    }
+                    VmEffect::Unlink {
+                        source,
+                        relationship,
+                        target,
+                    } => {
+                        tx.unlink(source, relationship, target);
+                        links.push((source, relationship, target, LinkChange::Unlinked));
+                    }
+                    VmEffect::SetComponent {
+                        entity,
+                        component,
+                        value,
+                    } => {
+                        tx.set(entity, component, value);
+                    }
+                    VmEffect::SetField {
+                        entity,
+                        component,
+                        field,
+                        value,
+                    } => {
+                        tx.set_field(entity, component, field, value);
+                    }
+                    VmEffect::Spawn {
+                        temp_id,
+                        components,
+                    } => {
+                        // Spawn with the temp_id as the entity's permanent ID. This ensures
+                        // EntityRefs returned from spawn! remain valid after effects are
+                        // applied. Consecutive spawns, as from spawn-many!, go in one pass.
+                        let mut batch = vec![(temp_id, components)];
+                        while let Some(VmEffect::Spawn {
+                            temp_id,
+                            components,
+                        }) = effects.next_if(|e| matches!(e, VmEffect::Spawn { .. }))
+                        {
+                            batch.push((temp_id, components));
+                        }
+                        tx.apply(|world| world.spawn_batch_with_ids(&batch));
+                        spawned.extend(batch.into_iter().map(|(temp_id, _)| temp_id));
+                    }
+                    VmEffect::Destroy { entity } => {
+                        let mut steps = Vec::new();
+                        tx.apply(|world| {
+                            let (world, cascade) = world.destroy_cascading(entity)?;
+                            steps = cascade;
+                            Ok(world)
+                        });
+                        destroyed.push((entity, steps));
+                    }
+                    VmEffect::RemoveComponent { entity, component } => {
+                        tx.remove_component(entity, component);
+                    }
 
-        for effect in effects {
-            match effect {
-                // Non-mergeable effects: apply immediately
-                VmEffect::Link {
-                    source,
-                    relationship,
-                    target,
-                } => {
-                    let real_source = translate_id(source, &temp_to_real_id);
-                    let real_target = translate_id(target, &temp_to_real_id);
-                    let new_world =
-                        self.session
-                            .world()
-                            .link(real_source, relationship, real_target)?;
-                    *self.session.world_mut() = new_world;
-                }
-                VmEffect::Unlink {
-                    source,
-                    relationship,
-                    target,
-                } => {
-                    let real_source = translate_id(source, &temp_to_real_id);
-                    let real_target = translate_id(target, &temp_to_real_id);
-                    let new_world =
-                        self.session
-                            .world()
-                            .unlink(real_source, relationship, real_target)?;
-                    *self.session.world_mut() = new_world;
-                }
-                VmEffect::SetComponent {
-                    entity,
-                    component,
-                    value,
-                } => {
-                    let real_entity = translate_id(entity, &temp_to_real_id);
-                    let new_world = self.session.world().set(real_entity, component, value)?;
-                    *self.session.world_mut() = new_world;
-                }
-                VmEffect::SetField {
-                    entity,
-                    component,
-                    field,
-                    value,
-                } => {
-                    let real_entity = translate_id(entity, &temp_to_real_id);
-                    let new_world =
-                        self.session
-                            .world()
-                            .set_field(real_entity, component, field, value)?;
-                    *self.session.world_mut() = new_world;
-                }
-                VmEffect::Spawn {
-                    temp_id,
-                    components,
-                } => {
-                    // Use spawn_with_id to create the entity with the temp_id as its
-                    // permanent ID. This ensures EntityRefs returned from spawn! remain
-                    // valid after effects are applied.
-                    let (new_world, _) =
-                        self.session.world().spawn_with_id(temp_id, &components)?;
-                    *self.session.world_mut() = new_world;
-                }
-                VmEffect::Destroy { entity } => {
-                    let real_entity = translate_id(entity, &temp_to_real_id);
-                    let new_world = self.session.world().destroy(real_entity)?;
-                    *self.session.world_mut() = new_world;
-                }
-                VmEffect::RemoveComponent { entity, component } => {
-                    let real_entity = translate_id(entity, &temp_to_real_id);
-                    let new_world = self
-                        .session
-                        .world()
-                        .remove_component(real_entity, component)?;
-                    *self.session.world_mut() = new_world;
-                }
+                    // Mergeable effects: collect for later merging
+                    VmEffect::VecRemove {
+                        entity,
+                        component,
+                        field,
+                        value,
+                    } => {
+                        let key = (entity, component, field);
+                        let entry = vec_field_ops.entry(key).or_insert_with(|| (vec![], vec![]));
+                        entry.0.push(value);
+                    }
+                    VmEffect::VecAdd {
+                        entity,
+                        component,
+                        field,
+                        value,
+                    } => {
+                        let key = (entity, component, field);
+                        let entry = vec_field_ops.entry(key).or_insert_with(|| (vec![], vec![]));
+                        entry.1.push(value);
+                    }
+                    VmEffect::SetRemove {
+                        entity,
+                        component,
+                        field,
+                        value,
+                    } => {
+                        let key = (entity, component, field);
+                        let entry = set_field_ops.entry(key).or_insert_with(|| (vec![], vec![]));
+                        entry.0.push(value);
+                    }
+                    VmEffect::SetAdd {
+                        entity,
+                        component,
+                        field,
+                        value,
+                    } => {
+                        let key = (entity, component, field);
+                        let entry = set_field_ops.entry(key).or_insert_with(|| (vec![], vec![]));
+                        entry.1.push(value);
+                    }
 
-                // Mergeable effects: collect for later merging
-                // Translate entity IDs from temp to real for all mergeable effects
-                VmEffect::VecRemove {
-                    entity,
-                    component,
-                    field,
-                    value,
-                } => {
-                    let real_entity = translate_id(entity, &temp_to_real_id);
-                    let key = (real_entity, component, field);
-                    let entry = vec_field_ops.entry(key).or_insert_with(|| (vec![], vec![]));
-                    entry.0.push(value);
-                }
-                VmEffect::VecAdd {
-                    entity,
-                    component,
-                    field,
-                    value,
-                } => {
-                    let real_entity = translate_id(entity, &temp_to_real_id);
-                    let key = (real_entity, component, field);
-                    let entry = vec_field_ops.entry(key).or_insert_with(|| (vec![], vec![]));
-                    entry.1.push(value);
-                }
-                VmEffect::SetRemove {
-                    entity,
-                    component,
-                    field,
-                    value,
-                } => {
-                    let real_entity = translate_id(entity, &temp_to_real_id);
-                    let key = (real_entity, component, field);
-                    let entry = set_field_ops.entry(key).or_insert_with(|| (vec![], vec![]));
-                    entry.0.push(value);
-                }
-                VmEffect::SetAdd {
-                    entity,
-                    component,
-                    field,
-                    value,
-                } => {
-                    let real_entity = translate_id(entity, &temp_to_real_id);
-                    let key = (real_entity, component, field);
-                    let entry = set_field_ops.entry(key).or_insert_with(|| (vec![], vec![]));
-                    entry.1.push(value);
-                }
+                    VmEffect::Emit { event, payload } => {
+                        tx.apply(|world| {
+                            longtable_engine::event::emit(world.clone(), event, payload)
+                                .map(|(world, _)| world)
+                        });
+                    }
 
-                // State management effects are now handled directly through RuntimeContext
-                // during VM execution, not deferred as effects. These match arms are kept
-                // for completeness but should not be reached.
-                VmEffect::SaveState { .. } | VmEffect::RestoreState { .. } => {
-                    // No-op: these are handled immediately during VM execution
+                    VmEffect::Schedule {
+                        delay,
+                        repeat,
+                        owner,
+                        action,
+                    } => scheduled.push((delay, repeat, owner, action)),
+
+                    // State management effects are now handled directly through RuntimeContext
+                    // during VM execution, not deferred as effects. These match arms are kept
+                    // for completeness but should not be reached.
+                    VmEffect::SaveState { .. } | VmEffect::RestoreState { .. } => {
+                        // No-op: these are handled immediately during VM execution
+                    }
+                    VmEffect::Command { .. } => unreachable!("commands are partitioned out above"),
                 }
             }
-        }
 
-        // Apply merged vector field operations
-        for ((entity, component, field), (to_remove, to_add)) in vec_field_ops {
-            // Get current field value
-            let current = self.session.world().get_field(entity, component, field)?;
+            // Apply merged vector field operations
+            for ((entity, component, field), (to_remove, to_add)) in vec_field_ops {
+                // Get current field value
+                let current = match tx.world().get_field(entity, component, field) {
+                    Ok(current) => current,
+                    Err(e) => return tx.abort(e),
+                };
 
-            // Extract current vector elements
-            let mut elements: Vec<Value> = match current {
-                Some(Value::Vec(v)) => v.iter().cloned().collect(),
-                Some(Value::Nil) | None => vec![],
-                Some(other) => {
-                    return Err(Error::new(ErrorKind::TypeMismatch {
-                        expected: Type::vec(Type::Any),
-                        actual: other.value_type(),
-                    }));
-                }
-            };
+                // Extract current vector elements
+                let mut elements: Vec<Value> = match current {
+                    Some(Value::Vec(v)) => v.iter().cloned().collect(),
+                    Some(Value::Nil) | None => vec![],
+                    Some(other) => {
+                        return tx.abort(Error::new(ErrorKind::TypeMismatch {
+                            expected: Type::vec(Type::Any),
+                            actual: other.value_type(),
+                        }));
+                    }
+                };
 
-            // Remove values (preserve order, remove first occurrence of each)
-            for val in &to_remove {
-                if let Some(pos) = elements.iter().position(|e| e == val) {
-                    elements.remove(pos);
+                // Remove values (preserve order, remove first occurrence of each)
+                for val in &to_remove {
+                    if let Some(pos) = elements.iter().position(|e| e == val) {
+                        elements.remove(pos);
+                    }
                 }
-            }
 
-            // Add values
-            for val in to_add {
-                elements.push(val);
+                // Add values
+                for val in to_add {
+                    elements.push(val);
+                }
+
+                // Apply the merged change
+                let new_vec = Value::Vec(elements.into_iter().collect());
+                tx.set_field(entity, component, field, new_vec);
             }
 
-            // Convert back to Value::Vec
-            let new_vec = Value::Vec(elements.into_iter().collect());
+            // Apply merged set field operations
+            for ((entity, component, field), (to_remove, to_add)) in set_field_ops {
+                // Get current field value
+                let current = match tx.world().get_field(entity, component, field) {
+                    Ok(current) => current,
+                    Err(e) => return tx.abort(e),
+                };
 
-            // Apply the merged change
-            let new_world = self
-                .session
-                .world()
-                .set_field(entity, component, field, new_vec)?;
-            *self.session.world_mut() = new_world;
-        }
+                // Extract current set elements
+                let mut elements: LtSet<Value> = match current {
+                    Some(Value::Set(s)) => s,
+                    Some(Value::Nil) | None => LtSet::new(),
+                    Some(other) => {
+                        return tx.abort(Error::new(ErrorKind::TypeMismatch {
+                            expected: Type::set(Type::Any),
+                            actual: other.value_type(),
+                        }));
+                    }
+                };
 
-        // Apply merged set field operations
-        for ((entity, component, field), (to_remove, to_add)) in set_field_ops {
-            // Get current field value
-            let current = self.session.world().get_field(entity, component, field)?;
+                // Remove values
+                for val in to_remove {
+                    elements = elements.remove(&val);
+                }
 
-            // Extract current set elements
-            let mut elements: LtSet<Value> = match current {
-                Some(Value::Set(s)) => s,
-                Some(Value::Nil) | None => LtSet::new(),
-                Some(other) => {
-                    return Err(Error::new(ErrorKind::TypeMismatch {
-                        expected: Type::set(Type::Any),
-                        actual: other.value_type(),
-                    }));
+                // Add values
+                for val in to_add {
+                    elements = elements.insert(val);
                 }
-            };
 
-            // Remove values
-            for val in to_remove {
-                elements = elements.remove(&val);
+                // Apply the merged change
+                tx.set_field(entity, component, field, Value::Set(elements));
             }
+        })?;
 
-            // Add values
-            for val in to_add {
-                elements = elements.insert(val);
+        self.session.record_undo_point();
+        *self.session.world_mut() = world;
+
+        let provenance = self.tick_executor.provenance_mut();
+        for (source, relationship, target, change) in links {
+            provenance.record_link(
+                source,
+                relationship,
+                target,
+                change,
+                origin,
+                context.clone(),
+            );
+        }
+        for entity in spawned {
+            provenance.record_spawn(entity, origin, context.clone());
+        }
+        for (delay, repeat, owner, action) in scheduled {
+            self.tick_executor
+                .schedule_timer(delay, repeat, owner, action);
+        }
+        let tracer = self.session.tracer_mut();
+        for (entity, steps) in &destroyed {
+            tracer.entity_destroy(*entity, Some(origin));
+            for step in steps {
+                tracer.relationship_cascade(step);
             }
+        }
 
-            // Convert back to Value::Set
-            let new_set = Value::Set(elements);
+        Ok(commands)
+    }
 
-            // Apply the merged change
-            let new_world = self
-                .session
-                .world()
-                .set_field(entity, component, field, new_set)?;
-            *self.session.world_mut() = new_world;
+    /// Runs session commands queued by compiled code, in order.
+    ///
+    /// Each `(name args...)` is handled exactly as if it had been typed at
+    /// the top level, with its arguments already evaluated.
+    fn run_commands(&mut self, commands: Vec<VmEffect>) -> Result<()> {
+        let span = Span::default();
+        for command in commands {
+            let VmEffect::Command { name, args } = command else {
+                continue;
+            };
+            let form = Ast::List(
+                std::iter::once(Ast::Symbol(name, span))
+                    .chain(args.iter().map(|arg| self.value_to_ast(arg, span)))
+                    .collect(),
+                span,
+            );
+            self.try_special_form(&form)?;
         }
-
         Ok(())
     }
 
@@ -624,6 +1350,9 @@ impl<E: LineEditor> Repl<E> {
         match &list[0] {
             // NOTE: (say) is replaced by (println) native function
 
+            // (help) or (help name) - list special forms or describe one
+            Ast::Symbol(s, _) if s == "help" => self.handle_help(&list[1..]),
+
             // (def name value) - define a variable in the session
             Ast::Symbol(s, _) if s == "def" => {
                 if list.len() != 3 {
@@ -659,7 +1388,9 @@ impl<E: LineEditor> Repl<E> {
                 }
 
                 self.input_mode = true;
-                println!("Entering input mode. S-expressions still work. Use (repl) to exit.");
+                self.write_output(
+                    "Entering input mode. S-expressions still work. Use (repl) to exit.\n",
+                );
                 Ok(Some(Value::Nil))
             }
 
@@ -672,7 +1403,7 @@ impl<E: LineEditor> Repl<E> {
                 }
 
                 self.input_mode = false;
-                println!("Returning to REPL mode.");
+                self.write_output("Returning to REPL mode.\n");
                 Ok(Some(Value::Nil))
             }
 
@@ -698,6 +1429,32 @@ impl<E: LineEditor> Repl<E> {
                 Ok(Some(Value::Nil))
             }
 
+            // (reload) or (reload "path") - reload changed files, or one file
+            Ast::Symbol(s, _) if s == "reload" => match &list[1..] {
+                [] => {
+                    let mut reloaded = Vec::new();
+                    for (path, result) in self.reload_changed_files() {
+                        result?;
+                        reloaded.push(Value::String(path.display().to_string().into()));
+                    }
+                    Ok(Some(Value::Vec(reloaded.into_iter().collect())))
+                }
+                [Ast::String(path, _)] => {
+                    let path = self.session.resolve_path(path);
+                    self.reload_file(&path)?;
+                    Ok(Some(Value::Nil))
+                }
+                _ => Err(Error::new(ErrorKind::InvalidArgument(
+                    "reload takes an optional path: (reload) or (reload \"rules.lt\")".to_string(),
+                ))),
+            },
+
+            // (require [ns :as alias] ...) - alias namespaces for ::alias/name keywords
+            Ast::Symbol(s, _) if s == "require" => {
+                self.handle_require(&list[1..])?;
+                Ok(Some(Value::Nil))
+            }
+
             // (save! "path") - save world state to file
             Ast::Symbol(s, _) if s == "save!" => {
                 if list.len() != 2 {
@@ -718,7 +1475,7 @@ impl<E: LineEditor> Repl<E> {
 
                 let resolved = self.session.resolve_path(&path);
                 serialize::save_to_file(self.session.world(), &resolved)?;
-                println!("World saved to: {}", resolved.display());
+                self.write_output(&format!("World saved to: {}\n", resolved.display()));
                 Ok(Some(Value::Nil))
             }
 
@@ -745,16 +1502,31 @@ impl<E: LineEditor> Repl<E> {
                 let world = serialize::load_from_file(&resolved)?;
                 let entity_count = world.entity_count();
                 let tick = world.tick();
-                self.session.set_world(world);
-                println!(
-                    "World loaded from: {} ({} entities, tick {})",
+                self.rewind_world(world);
+                self.write_output(&format!(
+                    "World loaded from: {} ({} entities, tick {})\n",
                     resolved.display(),
                     entity_count,
                     tick
-                );
+                ));
                 Ok(Some(Value::Nil))
             }
 
+            // (export-json! "path") - write entities and relationships as JSON
+            Ast::Symbol(s, _) if s == "export-json!" => self.handle_export_json(&list[1..]),
+
+            // (import-json! "path") - replace entities with those in a JSON file
+            Ast::Symbol(s, _) if s == "import-json!" => self.handle_import_json(&list[1..]),
+
+            // (export-datoms! "path") - write [entity attribute value tick] datoms as EDN
+            Ast::Symbol(s, _) if s == "export-datoms!" => self.handle_export_datoms(&list[1..]),
+
+            // (import-datoms! "path") - replace entities with those in a datom file
+            Ast::Symbol(s, _) if s == "import-datoms!" => self.handle_import_datoms(&list[1..]),
+
+            // (export-trace! "path") - write the trace buffer in Chrome trace format
+            Ast::Symbol(s, _) if s == "export-trace!" => self.handle_export_trace(&list[1..]),
+
             // (tick!) or (tick! [events]) - advance world by one tick
             Ast::Symbol(s, _) if s == "tick!" => {
                 let inputs: Vec<InputEvent> = if list.len() > 1 {
@@ -765,74 +1537,91 @@ impl<E: LineEditor> Repl<E> {
                     Vec::new()
                 };
 
-                let world = self.session.world().clone();
-                let result = self.tick_executor.tick(world, &inputs)?;
+                let started = Instant::now();
+                let result = self.step(&inputs)?;
+                let elapsed = started.elapsed();
+                let tick = self.tick_executor.tick_number();
+                if self.session.observability().profiling {
+                    self.write_output(&format!("Tick {tick} took {elapsed:?}\n"));
+                }
 
                 if result.success {
-                    self.session.set_world(result.world);
-                    println!(
-                        "Tick {}: {} activations fired",
-                        self.tick_executor.tick_number(),
+                    self.write_output(&format!(
+                        "Tick {tick}: {} activations fired\n",
                         result.activations_fired
-                    );
+                    ));
+                    let scored = result.constraint_result.scored_violations();
+                    if !scored.is_empty() {
+                        self.write_output(&format!(
+                            "  world score: {} ({} soft violations)\n",
+                            result.constraint_result.score(),
+                            scored.len()
+                        ));
+                    }
                 } else {
-                    println!(
-                        "Tick {} rolled back: {:?}",
-                        self.tick_executor.tick_number(),
+                    self.write_output(&format!(
+                        "Tick {tick} rolled back: {:?}\n",
                         result.constraint_result
-                    );
+                    ));
                 }
 
                 Ok(Some(Value::Nil))
             }
 
-            // (inspect entity) - show entity details
-            Ast::Symbol(s, _) if s == "inspect" => {
-                if list.len() != 2 {
-                    return Err(Error::new(ErrorKind::Internal(
-                        "inspect requires exactly 1 argument: (inspect entity)".to_string(),
-                    )));
-                }
+            // (recover!) or (recover! :keep) - clear a poisoned session
+            Ast::Symbol(s, _) if s == "recover!" => self.handle_recover(&list[1..]),
 
-                // Evaluate the argument to get entity id
-                let entity_val = self.eval_form(&list[1])?;
-                let entity_id = match entity_val {
-                    Value::EntityRef(id) => id,
-                    Value::Int(idx) if idx >= 0 => {
-                        // Allow using integer as entity index (for convenience)
-                        // Use generation 0 as default for convenience lookup
-                        #[allow(clippy::cast_sign_loss)]
-                        EntityId::new(idx as u64, 0)
-                    }
-                    other => {
-                        return Err(Error::new(ErrorKind::Internal(format!(
-                            "inspect argument must be an entity, got {:?}",
-                            other.value_type()
-                        ))));
-                    }
-                };
+            // (telemetry-opt-in! true|false) - record the player's telemetry choice
+            Ast::Symbol(s, _) if s == "telemetry-opt-in!" => {
+                self.handle_telemetry_opt_in(&list[1..])
+            }
 
-                let world = self.session.world();
+            // (fuzzy-matching! true|false) - correct misspelled verbs and directions
+            Ast::Symbol(s, _) if s == "fuzzy-matching!" => self.handle_fuzzy_matching(&list[1..]),
+            // (content-ids! true|false) - give spawn: declarations content-addressed IDs
+            Ast::Symbol(s, _) if s == "content-ids!" => self.handle_content_ids(&list[1..]),
 
-                // Check if entity exists
-                if !world.exists(entity_id) {
-                    println!("Entity {entity_id} does not exist or is dead");
-                    return Ok(Some(Value::Nil));
-                }
+            // (set-locale! :fr) - look messages up in another locale first
+            Ast::Symbol(s, _) if s == "set-locale!" => self.handle_set_locale(&list[1..]),
+
+            // (enable-group! :group) / (disable-group! :group) - switch a rule group on or off
+            Ast::Symbol(s, _) if s == "enable-group!" || s == "disable-group!" => {
+                self.handle_set_group_enabled(s == "enable-group!", &list[1..])
+            }
 
-                // Get entity info
-                println!("Entity {entity_id}:");
-                println!("  index: {}", entity_id.index);
-                println!("  generation: {}", entity_id.generation);
-                println!("  status: alive");
+            // (on-phase :phase (fn [ctx] ...)) - call a function at a tick phase
+            Ast::Symbol(s, _) if s == "on-phase" => self.handle_on_phase(&list[1..]),
 
-                // Note: Without a schema iterator, we can't easily list all components
-                // This is a basic implementation - could be enhanced with schema listing
-                println!("  (use (get entity :component) to query specific components)");
+            // (every 3 :on guard :then [...]) - let a timer belong to a spawned entity by name
+            Ast::Symbol(s, _) if s == "every" || s == "fuse" => self.handle_named_timer_owner(list),
 
-                Ok(Some(Value::Nil))
+            // (pause-timers! entity) / (resume-timers! entity) - stop and restart an entity's timers
+            Ast::Symbol(s, _) if s == "pause-timers!" || s == "resume-timers!" => {
+                self.handle_pause_timers(s == "pause-timers!", &list[1..])
             }
 
+            // (spatial-index :position :cell-size 8.0) - index a component by position
+            Ast::Symbol(s, _) if s == "spatial-index" => self.handle_spatial_index(&list[1..]),
+
+            // (before action :on target forms...) / (after action ...) - hook an action
+            Ast::Symbol(s, _) if s == "before" || s == "after" => {
+                let timing = if s == "before" {
+                    HookTiming::Before
+                } else {
+                    HookTiming::After
+                };
+                self.handle_action_hook(timing, &list[1..])
+            }
+
+            // (when-feature :feature forms...) - evaluate forms only if the feature is enabled
+            Ast::Symbol(s, _) if s == "when-feature" => self.handle_when_feature(&list[1..]),
+
+            // (do! forms...) - evaluate forms as one all-or-nothing block
+            Ast::Symbol(s, _) if s == "do!" => self.handle_do_block(&list[1..]),
+
+            // (inspect entity) - show entity details
+            Ast::Symbol(s, _) if s == "inspect" => self.handle_inspect(&list[1..]),
+
             // NOTE: component:, relationship:, rule: are now handled by compiler opcodes
 
             // (spawn: name :component value ...) - spawn an entity with components
@@ -846,6 +1635,30 @@ impl<E: LineEditor> Repl<E> {
                 }
             }
 
+            // (constraint: name :where [...] :check [...]) - register a constraint
+            Ast::Symbol(s, _) if s == "constraint:" => {
+                if let Some(Declaration::Constraint(constraint_decl)) =
+                    DeclarationAnalyzer::analyze(form)?
+                {
+                    self.execute_constraint(&constraint_decl)
+                } else {
+                    Err(Error::new(ErrorKind::Internal(
+                        "invalid constraint: form".to_string(),
+                    )))
+                }
+            }
+
+            // (world-hash) - stable hash of the world's content
+            Ast::Symbol(s, _) if s == "world-hash" => {
+                if list.len() != 1 {
+                    return Err(Error::new(ErrorKind::InvalidArgument(
+                        "world-hash takes no arguments".to_string(),
+                    )));
+                }
+                #[allow(clippy::cast_possible_wrap)]
+                Ok(Some(Value::Int(self.session.world().content_hash() as i64)))
+            }
+
             // (link: source :relationship target) - create a relationship between entities
             Ast::Symbol(s, _) if s == "link:" => {
                 if let Some(Declaration::Link(link_decl)) = DeclarationAnalyzer::analyze(form)? {
@@ -874,6 +1687,12 @@ impl<E: LineEditor> Repl<E> {
             // (explain-query (query ...)) or (explain-query (query ...) entity)
             Ast::Symbol(s, _) if s == "explain-query" => self.handle_explain_query(&list[1..]),
 
+            // (explain-plan (query ...))
+            Ast::Symbol(s, _) if s == "explain-plan" => self.handle_explain_plan(&list[1..]),
+
+            // (observability) or (observability {:history-size 50 ...}) - show/reconfigure
+            Ast::Symbol(s, _) if s == "observability" => self.handle_observability(&list[1..]),
+
             // (trace :on) or (trace :off) or (trace :json :on) etc.
             Ast::Symbol(s, _) if s == "trace" => self.handle_trace(&list[1..]),
 
@@ -942,6 +1761,53 @@ impl<E: LineEditor> Repl<E> {
             // (timeline) - show timeline status
             Ast::Symbol(s, _) if s == "timeline" => self.handle_timeline(),
 
+            // (undo!) - revert the last effect batch
+            Ast::Symbol(s, _) if s == "undo!" => self.handle_undo(),
+
+            // (redo!) - reapply the last undone effect batch
+            Ast::Symbol(s, _) if s == "redo!" => self.handle_redo(),
+
+            // (validate) - check world consistency
+            Ast::Symbol(s, _) if s == "validate" => self.handle_validate(),
+
+            // (lint-game) - check loaded content for adventure-specific mistakes
+            Ast::Symbol(s, _) if s == "lint-game" => self.handle_lint_game(),
+
+            // (scenario: "name" :setup [...] :steps [...] :assert [...]) - declare a test
+            Ast::Symbol(s, _) if s == "scenario:" => {
+                self.session.add_scenario(Scenario::parse(&list[1..])?);
+                Ok(Some(Value::Nil))
+            }
+
+            // (run-scenarios) or (run-scenarios "name") - run declared scenarios
+            Ast::Symbol(s, _) if s == "run-scenarios" => self.handle_run_scenarios(&list[1..]),
+
+            // (memory) - what the world and its history hold in memory
+            Ast::Symbol(s, _) if s == "memory" => self.handle_memory(),
+
+            // (gc-interner!) - free generated keywords nothing refers to
+            Ast::Symbol(s, _) if s == "gc-interner!" => self.handle_gc_interner(),
+
+            // (rule-stats) or (rule-stats :reset) - what each rule has cost
+            Ast::Symbol(s, _) if s == "rule-stats" => self.handle_rule_stats(&list[1..]),
+
+            // (coverage-report) or (coverage-report :reset) - what never ran
+            Ast::Symbol(s, _) if s == "coverage-report" => self.handle_coverage_report(&list[1..]),
+
+            // (agenda) - activations that would fire next
+            Ast::Symbol(s, _) if s == "agenda" => self.handle_agenda(&list[1..]),
+
+            // (cancel-activation! id) - take an activation off the agenda
+            Ast::Symbol(s, _) if s == "cancel-activation!" => {
+                self.handle_cancel_activation(&list[1..])
+            }
+
+            // (relationship-stats) - fan-out and lookup statistics per relationship
+            Ast::Symbol(s, _) if s == "relationship-stats" => self.handle_relationship_stats(),
+
+            // (query-warnings) or (query-warnings :warn|:deny|:allow)
+            Ast::Symbol(s, _) if s == "query-warnings" => self.handle_query_warnings(&list[1..]),
+
             // ==================== Backtracking Support ====================
 
             // (save-state) - save current world state, returns snapshot ID
@@ -955,6 +1821,21 @@ impl<E: LineEditor> Repl<E> {
             // (input! "command text") - parse and execute natural language command
             Ast::Symbol(s, _) if s == "input!" => self.handle_input(&list[1..]),
 
+            // (transcript) - summarize recorded input
+            Ast::Symbol(s, _) if s == "transcript" => self.handle_transcript(),
+
+            // (save-transcript! "path") - export recorded input as JSON
+            Ast::Symbol(s, _) if s == "save-transcript!" => self.handle_save_transcript(&list[1..]),
+
+            // (verbs) - the verbs the parser knows
+            Ast::Symbol(s, _) if s == "verbs" => self.handle_verbs(&list[1..]),
+
+            // (nouns-in-scope) - the entities the player's nouns can refer to
+            Ast::Symbol(s, _) if s == "nouns-in-scope" => self.handle_nouns_in_scope(&list[1..]),
+
+            // (syntax-for :verb take) - the command syntaxes for a verb
+            Ast::Symbol(s, _) if s == "syntax-for" => self.handle_syntax_for(&list[1..]),
+
             // NOTE: (entity-ref) is now a compiler form
             // NOTE: Parser vocabulary declarations (verb:, direction:, preposition:, etc.)
             //       are now handled by compiler opcodes
@@ -970,13 +1851,8 @@ impl<E: LineEditor> Repl<E> {
         &mut self,
         query_decl: &longtable_language::declaration::QueryDecl,
     ) -> Result<Option<Value>> {
-        // Compile the query
-        let compiled = QueryCompiler::compile(query_decl, self.session.world_mut().interner_mut())?;
-
-        // Print any warnings
-        for warning in &compiled.warnings {
-            eprintln!("Warning: {warning}");
-        }
+        let compiled = self.compile_query(query_decl)?;
+        self.report_query_warnings(&compiled.warnings)?;
 
         // Execute the query
         let results = QueryExecutor::execute(&compiled, self.session.world())?;
@@ -985,6 +1861,260 @@ impl<E: LineEditor> Repl<E> {
         Ok(Some(Value::Vec(results.into_iter().collect())))
     }
 
+    /// Compiles a query, adding any warnings that depend on the current world.
+    fn compile_query(
+        &mut self,
+        query_decl: &longtable_language::declaration::QueryDecl,
+    ) -> Result<longtable_engine::CompiledQuery> {
+        let mut compiled =
+            QueryCompiler::compile(query_decl, self.session.world_mut().interner_mut())?;
+        QueryCompiler::plan(&mut compiled, self.session.world());
+        let fan_out = QueryCompiler::fan_out_warnings(
+            &compiled,
+            self.session.world(),
+            HIGH_FAN_OUT_THRESHOLD,
+        );
+        compiled.add_warnings(fan_out);
+        Ok(compiled)
+    }
+
+    /// Records a query's warnings and prints or rejects them per the warning mode.
+    fn report_query_warnings(&mut self, warnings: &[QueryWarning]) -> Result<()> {
+        self.session.set_query_warnings(warnings.to_vec());
+        match self.session.warning_mode() {
+            WarningMode::Warn => {
+                for warning in warnings {
+                    eprintln!("Warning: {warning} [:{}]", warning.code());
+                }
+                Ok(())
+            }
+            WarningMode::Deny if !warnings.is_empty() => {
+                let messages: Vec<_> = warnings
+                    .iter()
+                    .map(|w| format!("{w} [:{}]", w.code()))
+                    .collect();
+                Err(Error::new(ErrorKind::DeniedWarnings(messages))
+                    .with_hint("(query-warnings :warn) reports them without failing"))
+            }
+            WarningMode::Deny | WarningMode::Allow => Ok(()),
+        }
+    }
+
+    /// Handles the (recover!) and (recover! :keep) forms.
+    fn handle_recover(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let keep = match args {
+            [] => false,
+            [Ast::Keyword(k, _)] if k == "keep" => true,
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidArgument(
+                    "recover! takes an optional :keep: (recover!) or (recover! :keep)".to_string(),
+                )));
+            }
+        };
+        let tick = self.session.poisoned().map(|p| p.tick);
+        self.recover(keep)?;
+        if let Some(tick) = tick {
+            if keep {
+                self.write_output(&format!("Keeping the world as tick {tick} left it\n"));
+            } else {
+                self.write_output(&format!("Restored the world from before tick {tick}\n"));
+            }
+        }
+        Ok(Some(Value::Nil))
+    }
+
+    /// Handles the (inspect entity) form.
+    ///
+    /// Prints every component with its value, then the entity's outgoing
+    /// and incoming relationships, naming entities where they have names.
+    fn handle_inspect(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [arg] = args else {
+            return Err(Error::new(ErrorKind::Internal(
+                "inspect requires exactly 1 argument: (inspect entity)".to_string(),
+            )));
+        };
+
+        // A spawned entity's name, or an expression giving the entity
+        let named = match arg {
+            Ast::Symbol(name, _) => self.session.get_entity(name),
+            _ => None,
+        };
+        let value = match named {
+            Some(id) => Value::EntityRef(id),
+            None => self.eval_form(arg)?,
+        };
+        let entity_id = match value {
+            Value::EntityRef(id) => id,
+            Value::Int(idx) if idx >= 0 => {
+                // Allow using integer as entity index (for convenience)
+                // Use generation 0 as default for convenience lookup
+                #[allow(clippy::cast_sign_loss)]
+                EntityId::new(idx as u64, 0)
+            }
+            other => {
+                return Err(Error::new(ErrorKind::Internal(format!(
+                    "inspect argument must be an entity, got {:?}",
+                    other.value_type()
+                ))));
+            }
+        };
+
+        let world = self.session.world();
+        if !world.exists(entity_id) {
+            self.write_output(&format!("Entity {entity_id} does not exist or is dead\n"));
+            return Ok(Some(Value::Nil));
+        }
+
+        let mut components: Vec<(String, String)> = world
+            .components_of(entity_id)
+            .map(|(component, value)| {
+                (
+                    self.format_value_inner(&Value::Keyword(component)),
+                    self.format_value_inner(&value),
+                )
+            })
+            .collect();
+        components.sort();
+        let mut relationships: Vec<String> = world
+            .outgoing(entity_id)
+            .into_iter()
+            .map(|(rel, target)| (rel, "->", target))
+            .chain(
+                world
+                    .incoming(entity_id)
+                    .into_iter()
+                    .map(|(rel, source)| (rel, "<-", source)),
+            )
+            .map(|(rel, arrow, other)| {
+                format!(
+                    "{} {arrow} {}",
+                    self.format_value_inner(&Value::Keyword(rel)),
+                    self.entity_label(other)
+                )
+            })
+            .collect();
+        relationships.sort();
+
+        let mut text = format!("{}:\n", self.entity_label(entity_id));
+        if components.is_empty() {
+            text.push_str("  (no components)\n");
+        }
+        for (component, value) in components {
+            let _ = writeln!(text, "  {component} {value}");
+        }
+        if !relationships.is_empty() {
+            text.push_str("  relationships:\n");
+            for relationship in relationships {
+                let _ = writeln!(text, "    {relationship}");
+            }
+        }
+        self.write_output(&text);
+        Ok(Some(Value::Nil))
+    }
+
+    /// Describes an entity by its `spawn:` name, if it has one, and its id.
+    fn entity_label(&self, entity: EntityId) -> String {
+        let id = format!("Entity({}, {})", entity.index, entity.generation);
+        let mut names: Vec<&String> = self
+            .session
+            .entity_names()
+            .iter()
+            .filter(|&(_, &e)| e == entity)
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+        match names.first() {
+            Some(name) => format!("{name} {id}"),
+            None => id,
+        }
+    }
+
+    /// Handles the (help) and (help name) forms.
+    ///
+    /// Prints the special forms by area, or one form's usage, arguments, and
+    /// examples, from the [`crate::help`] registry.
+    fn handle_help(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let text = match args {
+            [] => crate::help::overview(),
+            [Ast::Symbol(name, _) | Ast::String(name, _)] => {
+                let form = crate::help::find(name).ok_or_else(|| {
+                    Error::new(ErrorKind::NotFound(format!("no help for {name}")))
+                        .with_hint("(help) lists the special forms")
+                })?;
+                crate::help::describe(form)
+            }
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidArgument(
+                    "help takes an optional form name: (help why)".to_string(),
+                )));
+            }
+        };
+        self.write_output(&text);
+        Ok(Some(Value::Nil))
+    }
+
+    /// Handles the (query-warnings) form.
+    ///
+    /// With no arguments, returns the most recent query's warnings as maps of
+    /// `:code` and `:message`. With `:warn`, `:deny`, or `:allow`, sets what
+    /// happens to future warnings.
+    fn handle_query_warnings(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        match args {
+            [] => {
+                let code_kw = self
+                    .session
+                    .world_mut()
+                    .interner_mut()
+                    .intern_keyword("code");
+                let message_kw = self
+                    .session
+                    .world_mut()
+                    .interner_mut()
+                    .intern_keyword("message");
+                let warnings: Vec<_> = self
+                    .session
+                    .query_warnings()
+                    .to_vec()
+                    .into_iter()
+                    .map(|warning| {
+                        let code = self
+                            .session
+                            .world_mut()
+                            .interner_mut()
+                            .intern_keyword(warning.code());
+                        let map = LtMap::new()
+                            .insert(Value::Keyword(code_kw), Value::Keyword(code))
+                            .insert(
+                                Value::Keyword(message_kw),
+                                Value::from(warning.to_string().as_str()),
+                            );
+                        Value::Map(map)
+                    })
+                    .collect();
+                Ok(Some(Value::Vec(warnings.into_iter().collect())))
+            }
+            [Ast::Keyword(mode, _)] => {
+                let mode_value = match mode.as_str() {
+                    "warn" => WarningMode::Warn,
+                    "deny" => WarningMode::Deny,
+                    "allow" => WarningMode::Allow,
+                    other => {
+                        return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                            "query-warnings mode must be :warn, :deny, or :allow, got :{other}"
+                        ))));
+                    }
+                };
+                self.session.set_warning_mode(mode_value);
+                Ok(Some(Value::Keyword(
+                    self.session.world_mut().interner_mut().intern_keyword(mode),
+                )))
+            }
+            _ => Err(Error::new(ErrorKind::InvalidArgument(
+                "usage: (query-warnings) or (query-warnings :warn|:deny|:allow)".to_string(),
+            ))),
+        }
+    }
+
     /// Executes a spawn: declaration.
     ///
     /// Creates an entity with the specified components and registers it by name.
@@ -1011,17 +2141,65 @@ impl<E: LineEditor> Repl<E> {
             components = components.insert(Value::Keyword(comp_kw), comp_value);
         }
 
-        // Spawn the entity
-        let (new_world, entity_id) = self.session.world().spawn(&components)?;
+        // A name that already has a live entity updates it in place
+        let existing = self
+            .session
+            .get_entity(&spawn_decl.name)
+            .filter(|&entity| self.session.world().exists(entity));
+        if let Some(entity_id) = existing {
+            let mut world = self.session.world().clone();
+            for (component, value) in components.iter() {
+                if let Value::Keyword(component) = component {
+                    world = world.set(entity_id, *component, value.clone())?;
+                }
+            }
+            self.session.record_undo_point();
+            self.session.set_world(world);
+            return Ok(Some(Value::EntityRef(entity_id)));
+        }
+
+        let world = self.session.world();
+        let (new_world, entity_id) = if world.entity_policy().content_ids {
+            world.spawn_content(&spawn_decl.name, &components)?
+        } else {
+            world.spawn(&components)?
+        };
+        self.session.record_undo_point();
         self.session.set_world(new_world);
 
         // Register the entity by name
         self.session
             .register_entity(spawn_decl.name.clone(), entity_id);
 
+        let repl = self
+            .session
+            .world_mut()
+            .interner_mut()
+            .intern_keyword("repl");
+        self.tick_executor
+            .provenance_mut()
+            .record_spawn(entity_id, repl, Vec::new());
+
         Ok(Some(Value::EntityRef(entity_id)))
     }
 
+    /// Executes a constraint: declaration.
+    ///
+    /// Compiles the constraint and adds it to the checker run at the end of
+    /// every tick.
+    fn execute_constraint(
+        &mut self,
+        constraint_decl: &longtable_language::declaration::ConstraintDecl,
+    ) -> Result<Option<Value>> {
+        let compiled =
+            ConstraintCompiler::compile(constraint_decl, self.session.world_mut().interner_mut())?;
+        let name = compiled.name;
+        self.tick_executor
+            .constraint_checker_mut()
+            .add_constraint(compiled);
+        Ok(Some(Value::Keyword(name)))
+    }
+
     /// Executes a link: declaration.
     ///
     /// Creates a relationship between two entities by name.
@@ -1057,8 +2235,23 @@ impl<E: LineEditor> Repl<E> {
             .session
             .world()
             .create_relationship(rel_kw, source_id, target_id)?;
+        self.session.record_undo_point();
         self.session.set_world(new_world);
 
+        let repl = self
+            .session
+            .world_mut()
+            .interner_mut()
+            .intern_keyword("repl");
+        self.tick_executor.provenance_mut().record_link(
+            source_id,
+            rel_kw,
+            target_id,
+            LinkChange::Linked,
+            repl,
+            Vec::new(),
+        );
+
         Ok(Some(Value::Nil))
     }
 
@@ -1066,73 +2259,63 @@ impl<E: LineEditor> Repl<E> {
     //       execute_adverb(), execute_noun_type(), execute_scope(), execute_command(),
     //       execute_rule(), execute_action() removed - now handled by compiler opcodes
 
-    /// Handles the (why entity :component) or (why entity :component :depth N) form.
+    /// Handles the (why entity :component) form, with optional `:depth N`
+    /// and `:data true`.
     ///
-    /// Returns information about why an entity has a particular component value,
-    /// tracing back through the provenance chain.
+    /// Prints why an entity has a particular component value, tracing back
+    /// through the provenance chain. `(why entity :exists)` explains what
+    /// spawned the entity, and `(why source :relationship target)` who linked
+    /// or unlinked the two. With `:data true`, returns the explanation as a map
+    /// instead (see [`crate::explain`]).
     fn handle_why(&mut self, args: &[longtable_language::Ast]) -> Result<Option<Value>> {
         use longtable_debug::WhyQuery;
         use longtable_language::Ast;
 
-        if args.len() < 2 || args.len() > 4 {
-            return Err(Error::new(ErrorKind::Internal(
-                "why requires 2-4 arguments: (why entity :component) or (why entity :component :depth N)".to_string(),
-            )));
-        }
-
-        // Parse entity - support named entities from session
-        let entity = if let Ast::Symbol(name, _) = &args[0] {
-            // Try to resolve as a named entity
-            self.session
-                .get_entity(name)
-                .ok_or_else(|| Error::new(ErrorKind::Internal(format!("unknown entity: {name}"))))?
-        } else {
-            let entity_val = self.eval_form(&args[0])?;
-            match entity_val {
-                Value::EntityRef(id) => id,
-                Value::Int(idx) if idx >= 0 =>
-                {
-                    #[allow(clippy::cast_sign_loss)]
-                    EntityId::new(idx as u64, 0)
-                }
-                other => {
-                    return Err(Error::new(ErrorKind::Internal(format!(
-                        "why entity must be an entity reference, got {:?}",
-                        other.value_type()
-                    ))));
-                }
-            }
+        let usage = || {
+            Error::new(ErrorKind::InvalidArgument(
+                "why requires an entity and component: (why entity :component [:depth N] [:data true]), \
+                 (why entity :exists) or (why source :relationship target)"
+                    .to_string(),
+            ))
         };
+        let [subject, Ast::Keyword(name, _), rest @ ..] = args else {
+            return Err(usage());
+        };
+        let entity = self.resolve_why_entity(subject)?;
 
-        // Parse component
-        let component = match &args[1] {
-            Ast::Keyword(name, _) => self.session.world_mut().interner_mut().intern_keyword(name),
-            other => {
+        if name == "exists" {
+            let (_, as_data) = Self::parse_why_options(rest)?;
+            return Ok(Some(self.explain_existence(entity, as_data)));
+        }
+        if let Some((target, options)) = rest
+            .split_first()
+            .filter(|(t, _)| !matches!(t, Ast::Keyword(..)))
+        {
+            let (_, as_data) = Self::parse_why_options(options)?;
+            let target = self.resolve_why_entity(target)?;
+            let name = name.strip_prefix("rel/").unwrap_or(name);
+            let relationship = self.session.world_mut().interner_mut().intern_keyword(name);
+            if self
+                .session
+                .world()
+                .relationship_schema(relationship)
+                .is_none()
+            {
                 return Err(Error::new(ErrorKind::Internal(format!(
-                    "why component must be a keyword, got {}",
-                    other.type_name()
+                    "why: :{name} is not a relationship"
                 ))));
             }
-        };
+            return Ok(Some(self.explain_link(
+                entity,
+                relationship,
+                target,
+                as_data,
+            )));
+        }
 
-        // Parse optional :depth N
-        let depth = if args.len() >= 4 {
-            match (&args[2], &args[3]) {
-                (Ast::Keyword(k, _), Ast::Int(n, _)) if k == "depth" => {
-                    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-                    {
-                        *n as usize
-                    }
-                }
-                _ => {
-                    return Err(Error::new(ErrorKind::Internal(
-                        "expected :depth N after component".to_string(),
-                    )));
-                }
-            }
-        } else {
-            1
-        };
+        let component = self.session.world_mut().interner_mut().intern_keyword(name);
+        let (depth, as_data) = Self::parse_why_options(rest)?;
+        let depth = depth.unwrap_or(self.session.observability().why_depth);
 
         // Perform the why query
         let tracker = self.tick_executor.provenance();
@@ -1143,21 +2326,180 @@ impl<E: LineEditor> Repl<E> {
 
         let result = query.why_depth(entity, component, depth, current_value);
 
-        // Format the result
-        self.format_why_result(&result, entity, component);
+        if as_data {
+            let interner = self.session.world_mut().interner_mut();
+            return Ok(Some(explain::why_value(
+                &result, entity, component, interner,
+            )));
+        }
+
+        let text = self.format_why_result(&result, entity, component);
+        self.write_output(&text);
 
         Ok(Some(Value::Nil))
     }
 
-    /// Formats and prints a `WhyResult`.
-    fn format_why_result(
-        &self,
+    /// Resolves the entity argument of `why`: a session name or an expression.
+    fn resolve_why_entity(&mut self, ast: &longtable_language::Ast) -> Result<EntityId> {
+        if let Ast::Symbol(name, _) = ast {
+            return self
+                .session
+                .get_entity(name)
+                .ok_or_else(|| Error::new(ErrorKind::Internal(format!("unknown entity: {name}"))));
+        }
+        match self.eval_form(ast)? {
+            Value::EntityRef(id) => Ok(id),
+            Value::Int(idx) if idx >= 0 =>
+            {
+                #[allow(clippy::cast_sign_loss)]
+                Ok(EntityId::new(idx as u64, 0))
+            }
+            other => Err(Error::new(ErrorKind::Internal(format!(
+                "why entity must be an entity reference, got {:?}",
+                other.value_type()
+            )))),
+        }
+    }
+
+    /// Parses the trailing `:depth N` and `:data true` options of `why`.
+    fn parse_why_options(options: &[longtable_language::Ast]) -> Result<(Option<usize>, bool)> {
+        let mut depth = None;
+        let mut as_data = false;
+        if options.len() % 2 != 0 {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "expected :depth N or :data true after component".to_string(),
+            )));
+        }
+        for option in options.chunks(2) {
+            match (&option[0], &option[1]) {
+                (Ast::Keyword(k, _), Ast::Int(n, _)) if k == "depth" => {
+                    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+                    {
+                        depth = Some(*n as usize);
+                    }
+                }
+                (Ast::Keyword(k, _), Ast::Bool(b, _)) if k == "data" => as_data = *b,
+                _ => {
+                    return Err(Error::new(ErrorKind::InvalidArgument(
+                        "expected :depth N or :data true after component".to_string(),
+                    )));
+                }
+            }
+        }
+        Ok((depth, as_data))
+    }
+
+    /// Explains what spawned `entity`, printing it or returning it as data.
+    fn explain_existence(&mut self, entity: EntityId, as_data: bool) -> Value {
+        let cause =
+            longtable_debug::WhyQuery::new(self.tick_executor.provenance()).why_exists(entity);
+        let exists = self.session.world().exists(entity);
+        if as_data {
+            let interner = self.session.world_mut().interner_mut();
+            return explain::exists_value(entity, exists, cause.as_ref(), interner);
+        }
+
+        let state = if exists { "exists" } else { "no longer exists" };
+        let mut out = String::new();
+        match cause {
+            None => {
+                let _ = writeln!(out, "No spawn recorded for {entity} ({state})");
+            }
+            Some(cause) => {
+                let _ = writeln!(out, "Why does {entity} exist? ({state})");
+                self.write_structural_cause(&mut out, &cause, "  ");
+            }
+        }
+        self.write_output(&out);
+        Value::Nil
+    }
+
+    /// Explains who linked or unlinked an edge, printing it or returning it
+    /// as data.
+    fn explain_link(
+        &mut self,
+        source: EntityId,
+        relationship: KeywordId,
+        target: EntityId,
+        as_data: bool,
+    ) -> Value {
+        let causes = longtable_debug::WhyQuery::new(self.tick_executor.provenance()).why_linked(
+            source,
+            relationship,
+            target,
+        );
+        let linked = self
+            .session
+            .world()
+            .targets(source, relationship)
+            .any(|t| t == target);
+        if as_data {
+            let interner = self.session.world_mut().interner_mut();
+            return explain::link_history_value(
+                source,
+                relationship,
+                target,
+                linked,
+                &causes,
+                interner,
+            );
+        }
+
+        let name = self
+            .session
+            .world()
+            .interner()
+            .get_keyword(relationship)
+            .unwrap_or("?");
+        let state = if linked { "linked" } else { "not linked" };
+        let mut out = String::new();
+        if causes.is_empty() {
+            let _ = writeln!(
+                out,
+                "No link or unlink recorded for {source} :{name} {target} ({state})"
+            );
+        } else {
+            let _ = writeln!(out, "Why {source} :{name} {target}? (currently {state})");
+            for (i, cause) in causes.iter().enumerate() {
+                self.write_structural_cause(&mut out, cause, &format!("  [{i}] "));
+            }
+        }
+        self.write_output(&out);
+        Value::Nil
+    }
+
+    /// Writes one spawn, link, or unlink record to `out`.
+    fn write_structural_cause(
+        &self,
+        out: &mut String,
+        cause: &longtable_debug::StructuralCause,
+        prefix: &str,
+    ) {
+        use longtable_debug::StructuralChange;
+
+        let interner = self.session.world().interner();
+        let rule = interner.get_keyword(cause.rule).unwrap_or("?");
+        let change = match cause.change {
+            StructuralChange::Spawned => "Spawned",
+            StructuralChange::Linked => "Linked",
+            StructuralChange::Unlinked => "Unlinked",
+        };
+        let _ = writeln!(out, "{prefix}{change} by :{rule} at tick {}", cause.tick);
+        for (var, eid) in &cause.context {
+            let _ = writeln!(out, "{prefix}  {var} = {eid}");
+        }
+    }
+
+    /// Formats a `WhyResult` for printing.
+    fn format_why_result(
+        &self,
         result: &longtable_debug::WhyResult,
         entity: EntityId,
         component: longtable_foundation::KeywordId,
-    ) {
+    ) -> String {
         use longtable_debug::WhyResult;
 
+        let mut out = String::new();
         let component_name = self
             .session
             .world()
@@ -1167,10 +2509,13 @@ impl<E: LineEditor> Repl<E> {
 
         match result {
             WhyResult::Unknown => {
-                println!("No provenance information for {entity} :{component_name}");
+                let _ = writeln!(
+                    out,
+                    "No provenance information for {entity} :{component_name}"
+                );
             }
             WhyResult::Single(None) => {
-                println!("No write recorded for {entity} :{component_name}");
+                let _ = writeln!(out, "No write recorded for {entity} :{component_name}");
             }
             WhyResult::Single(Some(link)) => {
                 let rule_name = self
@@ -1179,24 +2524,24 @@ impl<E: LineEditor> Repl<E> {
                     .interner()
                     .get_keyword(link.rule)
                     .unwrap_or("?");
-                println!("Why {entity} :{component_name}?");
-                println!("  Rule: :{rule_name}");
-                println!("  Tick: {}", link.tick);
+                let _ = writeln!(out, "Why {entity} :{component_name}?");
+                let _ = writeln!(out, "  Rule: :{rule_name}");
+                let _ = writeln!(out, "  Tick: {}", link.tick);
                 if let Some(ref value) = link.value {
-                    println!("  Value: {value}");
+                    let _ = writeln!(out, "  Value: {value}");
                 }
                 if let Some(ref prev) = link.previous_value {
-                    println!("  Previous: {prev}");
+                    let _ = writeln!(out, "  Previous: {prev}");
                 }
                 if !link.context.is_empty() {
-                    println!("  Context:");
+                    out.push_str("  Context:\n");
                     for (var, eid) in &link.context {
-                        println!("    {var} = {eid}");
+                        let _ = writeln!(out, "    {var} = {eid}");
                     }
                 }
             }
             WhyResult::Chain(chain) => {
-                println!("Why {entity} :{component_name}? (causal chain)");
+                let _ = writeln!(out, "Why {entity} :{component_name}? (causal chain)");
                 for (i, link) in chain.links.iter().enumerate() {
                     let rule_name = self
                         .session
@@ -1210,25 +2555,32 @@ impl<E: LineEditor> Repl<E> {
                         .interner()
                         .get_keyword(link.component)
                         .unwrap_or("?");
-                    println!("  [{i}] {} :{comp_name}", link.entity);
-                    println!("      Rule: :{rule_name}, Tick: {}", link.tick);
+                    let _ = writeln!(out, "  [{i}] {} :{comp_name}", link.entity);
+                    let _ = writeln!(out, "      Rule: :{rule_name}, Tick: {}", link.tick);
                     if let Some(ref value) = link.value {
-                        println!("      Value: {value}");
+                        let _ = writeln!(out, "      Value: {value}");
                     }
                 }
                 if chain.truncated {
-                    println!("  ... (chain truncated at depth limit)");
+                    out.push_str("  ... (chain truncated at depth limit)\n");
                 }
             }
         }
+        out
     }
 
     /// Handles the (explain-query (query ...)) form.
     ///
-    /// Shows how a query was executed through its pipeline of clauses.
+    /// Shows how a query was executed through its pipeline of clauses. With a
+    /// trailing `:data true`, returns the explanation as a map instead (see
+    /// [`crate::explain::query_value`]).
     fn handle_explain_query(&mut self, args: &[longtable_language::Ast]) -> Result<Option<Value>> {
         use longtable_language::Ast;
 
+        let (args, as_data) = match args {
+            [rest @ .., Ast::Keyword(k, _), Ast::Bool(b, _)] if k == "data" => (rest, *b),
+            _ => (args, false),
+        };
         if args.is_empty() || args.len() > 2 {
             return Err(Error::new(ErrorKind::Internal(
                 "explain-query requires 1-2 arguments: (explain-query (query ...)) or (explain-query (query ...) entity)".to_string(),
@@ -1271,46 +2623,144 @@ impl<E: LineEditor> Repl<E> {
         };
 
         // Compile the query
-        let compiled =
-            QueryCompiler::compile(&query_decl, self.session.world_mut().interner_mut())?;
+        let compiled = self.compile_query(&query_decl)?;
+        self.session.set_query_warnings(compiled.warnings.clone());
 
         // Execute the query (needed for statistics)
         let results = QueryExecutor::execute(&compiled, self.session.world())?;
+        let clause_counts = explain::clause_match_counts(&compiled.pattern, self.session.world())?;
+
+        if as_data {
+            let entity_match = target_entity.map(|entity| {
+                PatternMatcher::explain_entity(&compiled.pattern, entity, self.session.world())
+            });
+            let interner = self.session.world_mut().interner_mut();
+            return Ok(Some(explain::query_value(
+                &compiled,
+                results.len(),
+                &clause_counts,
+                entity_match.as_ref(),
+                interner,
+            )));
+        }
 
         // Print explanation
-        println!("Query Explanation:");
-        println!("  Clauses: {}", compiled.pattern.clauses.len());
-        println!("  Results: {}", results.len());
+        let mut out = String::new();
+        out.push_str("Query Explanation:\n");
+        let _ = writeln!(out, "  Clauses: {}", compiled.pattern.clauses.len());
+        let interner = self.session.world().interner();
+        for (clause, matches) in compiled.pattern.clauses.iter().zip(&clause_counts) {
+            let _ = writeln!(
+                out,
+                "    {}: {matches} match(es)",
+                explain::clause_text(clause, interner)
+            );
+        }
+        let _ = writeln!(out, "  Results: {}", results.len());
+        if compiled.warnings.is_empty() {
+            out.push_str("  Warnings: none\n");
+        } else {
+            out.push_str("  Warnings:\n");
+            for warning in &compiled.warnings {
+                let _ = writeln!(out, "    [:{}] {warning}", warning.code());
+            }
+        }
 
         if let Some(entity) = target_entity {
-            self.print_entity_match_explanation(entity, &compiled);
+            self.write_entity_match_explanation(&mut out, entity, &compiled);
         }
+        self.write_output(&out);
 
         Ok(Some(Value::Nil))
     }
 
-    /// Prints entity-specific match explanation.
-    fn print_entity_match_explanation(
+    /// Handles the (explain-plan (query ...)) form.
+    ///
+    /// Shows the order the planner chose for the query's clauses, how each
+    /// finds its candidates, and the rows it expects after each, without
+    /// running the query. Returns the clauses in join order.
+    fn handle_explain_plan(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [query_form] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "explain-plan requires 1 argument: (explain-plan (query ...))".to_string(),
+            )));
+        };
+        let Some(Declaration::Query(query_decl)) = DeclarationAnalyzer::analyze(query_form)? else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "explain-plan argument must be a query form".to_string(),
+            )));
+        };
+
+        let compiled = self.compile_query(&query_decl)?;
+        let plan = compiled.plan.unwrap_or_default();
+        let interner = self.session.world().interner();
+        let texts: Vec<String> = compiled
+            .pattern
+            .clauses
+            .iter()
+            .map(|clause| explain::clause_text(clause, interner))
+            .collect();
+        let width = texts.iter().map(String::len).max().unwrap_or(0);
+
+        let mut text = String::from("Join order:\n");
+        for (i, ((clause, step), rows)) in texts
+            .iter()
+            .zip(&plan.steps)
+            .zip(plan.estimated_rows())
+            .enumerate()
+        {
+            let access = match step.access {
+                PlanAccess::Lookup => step.access.name().to_string(),
+                PlanAccess::Traverse => {
+                    format!("{} (~{} per source)", step.access.name(), step.estimate)
+                }
+                PlanAccess::Scan | PlanAccess::RelationshipScan => {
+                    format!("{} ({})", step.access.name(), step.estimate)
+                }
+            };
+            let _ = write!(
+                text,
+                "  {}. {clause:<width$}  {access:<24} ~{rows} rows",
+                i + 1
+            );
+            if step.written != i {
+                let _ = write!(text, "  (written {})", step.written + 1);
+            }
+            text.push('\n');
+        }
+        if !plan.is_reordered() {
+            text.push_str("  (as written)\n");
+        }
+        self.write_output(&text);
+
+        Ok(Some(Value::Vec(
+            texts.into_iter().map(|t| Value::String(t.into())).collect(),
+        )))
+    }
+
+    /// Writes entity-specific match explanation to `out`.
+    fn write_entity_match_explanation(
         &self,
+        out: &mut String,
         entity: EntityId,
         compiled: &longtable_engine::CompiledQuery,
     ) {
         use longtable_engine::PatternMatcher;
 
-        println!("\n  Entity {entity} match analysis:");
+        let _ = writeln!(out, "\n  Entity {entity} match analysis:");
 
         let result =
             PatternMatcher::explain_entity(&compiled.pattern, entity, self.session.world());
 
         if result.matched {
-            println!("    Status: MATCHED");
+            out.push_str("    Status: MATCHED\n");
             for (var, val) in result.partial_bindings.iter() {
-                println!("    ?{var} = {val}");
+                let _ = writeln!(out, "    ?{var} = {val}");
             }
         } else {
-            println!("    Status: NOT MATCHED");
+            out.push_str("    Status: NOT MATCHED\n");
             if let Some(clause_idx) = result.failed_at_clause {
-                println!("    Failed at clause: {clause_idx}");
+                let _ = writeln!(out, "    Failed at clause: {clause_idx}");
                 if clause_idx < compiled.pattern.clauses.len() {
                     let clause = &compiled.pattern.clauses[clause_idx];
                     let comp_name = self
@@ -1319,17 +2769,21 @@ impl<E: LineEditor> Repl<E> {
                         .interner()
                         .get_keyword(clause.component)
                         .unwrap_or("?");
-                    println!("      [?{} :{comp_name} ...]", clause.entity_var);
+                    let _ = writeln!(out, "      [?{} :{comp_name} ...]", clause.entity_var);
                 }
             }
             if let Some(ref reason) = result.failure_reason {
-                self.print_match_failure_reason(reason);
+                self.write_match_failure_reason(out, reason);
             }
         }
     }
 
-    /// Prints match failure reason.
-    fn print_match_failure_reason(&self, reason: &longtable_engine::MatchFailure) {
+    /// Writes match failure reason to `out`.
+    fn write_match_failure_reason(
+        &self,
+        out: &mut String,
+        reason: &longtable_engine::MatchFailure,
+    ) {
         use longtable_engine::MatchFailure;
 
         match reason {
@@ -1340,21 +2794,21 @@ impl<E: LineEditor> Repl<E> {
                     .interner()
                     .get_keyword(*component)
                     .unwrap_or("?");
-                println!("    Reason: Entity missing component :{comp_name}");
+                let _ = writeln!(out, "    Reason: Entity missing component :{comp_name}");
             }
             MatchFailure::ValueMismatch { expected, actual } => {
-                println!("    Reason: Value mismatch");
-                println!("      Expected: {expected}");
-                println!("      Actual: {actual}");
+                out.push_str("    Reason: Value mismatch\n");
+                let _ = writeln!(out, "      Expected: {expected}");
+                let _ = writeln!(out, "      Actual: {actual}");
             }
             MatchFailure::UnificationFailure {
                 var,
                 expected,
                 actual,
             } => {
-                println!("    Reason: Unification failure for ?{var}");
-                println!("      Previously bound to: {expected}");
-                println!("      New value: {actual}");
+                let _ = writeln!(out, "    Reason: Unification failure for ?{var}");
+                let _ = writeln!(out, "      Previously bound to: {expected}");
+                let _ = writeln!(out, "      New value: {actual}");
             }
             MatchFailure::NegationMatched { component } => {
                 let comp_name = self
@@ -1363,13 +2817,13 @@ impl<E: LineEditor> Repl<E> {
                     .interner()
                     .get_keyword(*component)
                     .unwrap_or("?");
-                println!("    Reason: Entity has negated component :{comp_name}");
+                let _ = writeln!(out, "    Reason: Entity has negated component :{comp_name}");
             }
             MatchFailure::GuardFailed { guard_index } => {
-                println!("    Reason: Guard {guard_index} returned false");
+                let _ = writeln!(out, "    Reason: Guard {guard_index} returned false");
             }
             MatchFailure::EntityNotFound => {
-                println!("    Reason: Entity does not exist");
+                out.push_str("    Reason: Entity does not exist\n");
             }
         }
     }
@@ -1378,13 +2832,12 @@ impl<E: LineEditor> Repl<E> {
     ///
     /// Enables or disables tracing.
     fn handle_trace(&mut self, args: &[longtable_language::Ast]) -> Result<Option<Value>> {
-        use longtable_debug::TraceOutput;
         use longtable_language::Ast;
 
         if args.is_empty() {
             // Show current status
             let enabled = self.session.tracer().is_enabled();
-            println!("Trace: {}", if enabled { "on" } else { "off" });
+            self.write_output(&format!("Trace: {}\n", if enabled { "on" } else { "off" }));
             return Ok(Some(Value::Bool(enabled)));
         }
 
@@ -1393,38 +2846,42 @@ impl<E: LineEditor> Repl<E> {
         while i < args.len() {
             match &args[i] {
                 Ast::Keyword(k, _) if k == "on" => {
-                    self.session.tracer_mut().enable();
-                    self.session.tracer_mut().set_output(TraceOutput::Stderr);
-                    println!("Trace enabled");
+                    let config = self.session.observability().clone();
+                    self.set_observability(config.with_enabled(true).with_trace_to_stderr(true))?;
+                    self.write_output("Trace enabled\n");
                 }
                 Ast::Keyword(k, _) if k == "off" => {
-                    self.session.tracer_mut().disable();
-                    self.session.tracer_mut().set_output(TraceOutput::None);
-                    println!("Trace disabled");
+                    let config = self.session.observability().clone();
+                    self.set_observability(config.with_enabled(false))?;
+                    self.write_output("Trace disabled\n");
                 }
                 Ast::Keyword(k, _) if k == "json" => {
-                    self.session.tracer_mut().set_json_format(true);
-                    println!("Trace output format: JSON");
+                    let config = self.session.observability().clone();
+                    self.set_observability(config.with_json_output(true))?;
+                    self.write_output("Trace output format: JSON\n");
                 }
                 Ast::Keyword(k, _) if k == "human" => {
-                    self.session.tracer_mut().set_json_format(false);
-                    println!("Trace output format: human-readable");
+                    let config = self.session.observability().clone();
+                    self.set_observability(config.with_json_output(false))?;
+                    self.write_output("Trace output format: human-readable\n");
                 }
                 Ast::Keyword(k, _) if k == "clear" => {
                     self.session.tracer_mut().clear();
-                    println!("Trace buffer cleared");
+                    self.write_output("Trace buffer cleared\n");
                 }
                 Ast::Keyword(k, _) if k == "stats" => {
                     let stats = self.session.tracer().stats();
-                    println!("Trace statistics:");
-                    println!("  Records: {}/{}", stats.record_count, stats.max_size);
+                    let mut out = String::new();
+                    out.push_str("Trace statistics:\n");
+                    let _ = writeln!(out, "  Records: {}/{}", stats.record_count, stats.max_size);
                     if let (Some(oldest), Some(newest)) = (stats.oldest_tick, stats.newest_tick) {
-                        println!("  Ticks: {oldest} - {newest}");
+                        let _ = writeln!(out, "  Ticks: {oldest} - {newest}");
                     }
-                    println!("  Event types:");
+                    out.push_str("  Event types:\n");
                     for (event_type, count) in &stats.event_counts {
-                        println!("    {event_type}: {count}");
+                        let _ = writeln!(out, "    {event_type}: {count}");
                     }
+                    self.write_output(&out);
                 }
                 other => {
                     return Err(Error::new(ErrorKind::Internal(format!(
@@ -1438,6 +2895,126 @@ impl<E: LineEditor> Repl<E> {
         Ok(Some(Value::Nil))
     }
 
+    /// Applies observability settings, refusing any that would turn on
+    /// something play mode keeps off.
+    fn set_observability(&mut self, config: ObservabilityConfig) -> Result<()> {
+        if self.tick_executor.mode() == ExecutionMode::Play && !config.is_play() {
+            return Err(Error::new(ErrorKind::Refused(
+                "play mode keeps tracing, provenance, history, and profiling off".to_string(),
+            ))
+            .with_hint("restart without --play to turn them on"));
+        }
+        self.apply_observability(config);
+        Ok(())
+    }
+
+    /// Applies observability settings to the session and the tick executor.
+    fn apply_observability(&mut self, config: ObservabilityConfig) {
+        config.apply_to_provenance(self.tick_executor.provenance_mut());
+        self.session.set_observability(config);
+    }
+
+    /// Handles the (observability) and (observability {...}) forms.
+    ///
+    /// With a map, updates the listed settings and applies them to every
+    /// debug subsystem. Always prints the resulting configuration.
+    fn handle_observability(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        match args {
+            [] => {}
+            [Ast::Map(entries, _)] => {
+                let mut config = self.session.observability().clone();
+                for (key, value) in entries {
+                    let Ast::Keyword(key, _) = key else {
+                        return Err(Error::new(ErrorKind::InvalidArgument(
+                            "observability settings must be keyed by keywords".to_string(),
+                        )));
+                    };
+                    Self::apply_observability_setting(&mut config, key, value)?;
+                }
+                self.set_observability(config)?;
+            }
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidArgument(
+                    "observability takes an optional map: (observability {:history-size 50})"
+                        .to_string(),
+                )));
+            }
+        }
+
+        let mut out = String::new();
+        let config = self.session.observability();
+        let _ = writeln!(
+            out,
+            "Observability: {}",
+            if config.enabled { "on" } else { "off" }
+        );
+        let _ = writeln!(out, "  :provenance         {}", config.provenance);
+        let _ = writeln!(out, "  :verbosity          {:?}", config.verbosity);
+        let _ = writeln!(out, "  :history-size       {}", config.history_size);
+        let _ = writeln!(out, "  :keyframe-interval  {}", config.keyframe_interval);
+        let _ = writeln!(out, "  :trace-buffer-size  {}", config.trace_buffer_size);
+        let _ = writeln!(out, "  :provenance-history {}", config.provenance_history);
+        let _ = writeln!(out, "  :profiling          {}", config.profiling);
+        let _ = writeln!(out, "  :why-depth          {}", config.why_depth);
+        let _ = writeln!(out, "  :trace-to-stderr    {}", config.trace_to_stderr);
+        let _ = writeln!(out, "  :json               {}", config.json_output);
+        self.write_output(&out);
+        Ok(Some(Value::Nil))
+    }
+
+    /// Updates one field of an observability config from a `(observability {...})` entry.
+    fn apply_observability_setting(
+        config: &mut ObservabilityConfig,
+        key: &str,
+        value: &Ast,
+    ) -> Result<()> {
+        let expected = |kind: &str| {
+            Error::new(ErrorKind::InvalidArgument(format!(
+                "observability :{key} expects {kind}"
+            )))
+        };
+        let int = || match value {
+            Ast::Int(n, _) => usize::try_from(*n).map_err(|_| expected("a non-negative integer")),
+            _ => Err(expected("a non-negative integer")),
+        };
+        let flag = || match value {
+            Ast::Bool(b, _) => Ok(*b),
+            _ => Err(expected("a boolean")),
+        };
+
+        match key {
+            "enabled" => config.enabled = flag()?,
+            "provenance" => config.provenance = flag()?,
+            "verbosity" => {
+                config.verbosity = match value {
+                    Ast::Keyword(v, _) if v == "minimal" => ProvenanceVerbosity::Minimal,
+                    Ast::Keyword(v, _) if v == "standard" => ProvenanceVerbosity::Standard,
+                    Ast::Keyword(v, _) if v == "full" => ProvenanceVerbosity::Full,
+                    _ => return Err(expected(":minimal, :standard, or :full")),
+                };
+            }
+            "history-size" => config.history_size = int()?,
+            "keyframe-interval" => config.keyframe_interval = (int()? as u64).max(1),
+            "trace-buffer-size" => config.trace_buffer_size = int()?,
+            "provenance-history" => config.provenance_history = int()?,
+            "profiling" => config.profiling = flag()?,
+            "why-depth" => config.why_depth = int()?,
+            "trace-to-stderr" => config.trace_to_stderr = flag()?,
+            "json" => config.json_output = flag()?,
+            other => {
+                return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                    "unknown observability setting :{other}"
+                )))
+                .with_hint(
+                    "settings are :enabled, :provenance, :verbosity, :history-size, \
+                     :keyframe-interval, :trace-buffer-size, :provenance-history, \
+                     :profiling, :why-depth, :trace-to-stderr, and :json",
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Handles the (get-traces :last N) form.
     ///
     /// Retrieves and displays trace records.
@@ -1524,16 +3101,17 @@ impl<E: LineEditor> Repl<E> {
             TraceQuery::Type(ref t) => buffer.by_event_type(t),
         };
 
-        if records.is_empty() {
-            println!("No traces found");
+        let count = records.len();
+        let output = if records.is_empty() {
+            "No traces found\n".to_string()
         } else {
             let interner = self.session.world().interner();
             let tracer = self.session.tracer();
-            let output = tracer.format_records(&records, interner);
-            println!("{output}");
-        }
+            format!("{}\n", tracer.format_records(&records, interner))
+        };
+        self.write_output(&output);
 
-        Ok(Some(Value::Int(records.len() as i64)))
+        Ok(Some(Value::Int(count as i64)))
     }
 
     /// Handles the (break ...) form.
@@ -1678,7 +3256,7 @@ impl<E: LineEditor> Repl<E> {
             }
         };
 
-        println!("Breakpoint {id} added");
+        self.write_output(&format!("Breakpoint {id} added\n"));
         #[allow(clippy::cast_possible_wrap)]
         Ok(Some(Value::Int(id.raw() as i64)))
     }
@@ -1709,35 +3287,38 @@ impl<E: LineEditor> Repl<E> {
             .remove(bp_id)
             .is_some()
         {
-            println!("Breakpoint {bp_id} removed");
+            self.write_output(&format!("Breakpoint {bp_id} removed\n"));
             Ok(Some(Value::Bool(true)))
         } else {
-            println!("Breakpoint {bp_id} not found");
+            self.write_output(&format!("Breakpoint {bp_id} not found\n"));
             Ok(Some(Value::Bool(false)))
         }
     }
 
     /// Handles the (breakpoints) form.
     #[allow(clippy::unnecessary_wraps)]
-    fn handle_breakpoints(&self) -> Result<Option<Value>> {
+    fn handle_breakpoints(&mut self) -> Result<Option<Value>> {
         let registry = self.session.debug_session().breakpoints();
+        let count = registry.len();
+        let mut out = String::new();
 
         if registry.is_empty() {
-            println!("No breakpoints");
+            out.push_str("No breakpoints\n");
         } else {
-            println!("Breakpoints:");
+            out.push_str("Breakpoints:\n");
             for bp in registry.iter() {
                 let status = if bp.is_enabled() {
                     "enabled"
                 } else {
                     "disabled"
                 };
-                println!("  {} - {} ({})", bp.id(), bp.description(), status);
+                let _ = writeln!(out, "  {} - {} ({})", bp.id(), bp.description(), status);
             }
         }
+        self.write_output(&out);
 
         #[allow(clippy::cast_possible_wrap)]
-        Ok(Some(Value::Int(registry.len() as i64)))
+        Ok(Some(Value::Int(count as i64)))
     }
 
     /// Handles the (watch expr) form.
@@ -1754,7 +3335,7 @@ impl<E: LineEditor> Repl<E> {
         let source = pretty_print(&args[0]);
         let id = self.session.debug_session_mut().watches_mut().add(source);
 
-        println!("Watch {id} added");
+        self.write_output(&format!("Watch {id} added\n"));
         #[allow(clippy::cast_possible_wrap)]
         Ok(Some(Value::Int(id.raw() as i64)))
     }
@@ -1785,23 +3366,25 @@ impl<E: LineEditor> Repl<E> {
             .remove(watch_id)
             .is_some()
         {
-            println!("Watch {watch_id} removed");
+            self.write_output(&format!("Watch {watch_id} removed\n"));
             Ok(Some(Value::Bool(true)))
         } else {
-            println!("Watch {watch_id} not found");
+            self.write_output(&format!("Watch {watch_id} not found\n"));
             Ok(Some(Value::Bool(false)))
         }
     }
 
     /// Handles the (watches) form.
     #[allow(clippy::unnecessary_wraps)]
-    fn handle_watches(&self) -> Result<Option<Value>> {
+    fn handle_watches(&mut self) -> Result<Option<Value>> {
         let registry = self.session.debug_session().watches();
+        let count = registry.len();
+        let mut out = String::new();
 
         if registry.is_empty() {
-            println!("No watches");
+            out.push_str("No watches\n");
         } else {
-            println!("Watches:");
+            out.push_str("Watches:\n");
             for watch in registry.iter() {
                 let status = if watch.is_enabled() {
                     "enabled"
@@ -1810,8 +3393,11 @@ impl<E: LineEditor> Repl<E> {
                 };
                 let value_str = watch
                     .last_value()
-                    .map_or("(not evaluated)".to_string(), |v| format!("{v}"));
-                println!(
+                    .map_or("(not evaluated)".to_string(), |v| {
+                        format_value_with(v, self.session.world().interner())
+                    });
+                let _ = writeln!(
+                    out,
                     "  {} - {} = {} ({})",
                     watch.id(),
                     watch.source(),
@@ -1820,16 +3406,17 @@ impl<E: LineEditor> Repl<E> {
                 );
             }
         }
+        self.write_output(&out);
 
         #[allow(clippy::cast_possible_wrap)]
-        Ok(Some(Value::Int(registry.len() as i64)))
+        Ok(Some(Value::Int(count as i64)))
     }
 
     /// Handles the (debug) form.
     #[allow(clippy::unnecessary_wraps)]
-    fn handle_debug(&self) -> Result<Option<Value>> {
-        let session = self.session.debug_session();
-        println!("{}", session.status_summary());
+    fn handle_debug(&mut self) -> Result<Option<Value>> {
+        let summary = self.session.debug_session().status_summary();
+        self.write_output(&format!("{summary}\n"));
         Ok(Some(Value::Nil))
     }
 
@@ -1837,7 +3424,7 @@ impl<E: LineEditor> Repl<E> {
     #[allow(clippy::unnecessary_wraps)]
     fn handle_continue(&mut self) -> Result<Option<Value>> {
         self.session.debug_session_mut().resume();
-        println!("Resumed execution");
+        self.write_output("Resumed execution\n");
         Ok(Some(Value::Nil))
     }
 
@@ -1845,7 +3432,7 @@ impl<E: LineEditor> Repl<E> {
     #[allow(clippy::unnecessary_wraps)]
     fn handle_step_rule(&mut self) -> Result<Option<Value>> {
         self.session.debug_session_mut().step_rule();
-        println!("Stepping to next rule");
+        self.write_output("Stepping to next rule\n");
         Ok(Some(Value::Nil))
     }
 
@@ -1853,7 +3440,7 @@ impl<E: LineEditor> Repl<E> {
     #[allow(clippy::unnecessary_wraps)]
     fn handle_step_phase(&mut self) -> Result<Option<Value>> {
         self.session.debug_session_mut().step_phase();
-        println!("Stepping to next phase");
+        self.write_output("Stepping to next phase\n");
         Ok(Some(Value::Nil))
     }
 
@@ -1861,7 +3448,7 @@ impl<E: LineEditor> Repl<E> {
     #[allow(clippy::unnecessary_wraps)]
     fn handle_step_tick(&mut self) -> Result<Option<Value>> {
         self.session.debug_session_mut().step_tick();
-        println!("Stepping to next tick");
+        self.write_output("Stepping to next tick\n");
         Ok(Some(Value::Nil))
     }
 
@@ -1895,8 +3482,8 @@ impl<E: LineEditor> Repl<E> {
 
         let world = snapshot.world().clone();
         let tick = snapshot.tick();
-        self.session.set_world(world);
-        println!("Rolled back to tick {tick}");
+        self.rewind_world(world);
+        self.write_output(&format!("Rolled back to tick {tick}\n"));
 
         #[allow(clippy::cast_possible_wrap)]
         Ok(Some(Value::Int(tick as i64)))
@@ -1929,8 +3516,8 @@ impl<E: LineEditor> Repl<E> {
         };
 
         let world = snapshot.world().clone();
-        self.session.set_world(world);
-        println!("Jumped to tick {target_tick}");
+        self.rewind_world(world);
+        self.write_output(&format!("Jumped to tick {target_tick}\n"));
 
         #[allow(clippy::cast_possible_wrap)]
         Ok(Some(Value::Int(target_tick as i64)))
@@ -1969,7 +3556,9 @@ impl<E: LineEditor> Repl<E> {
             ))));
         };
 
-        println!("Created branch '{name}' at tick {current_tick} (id: {branch_id})");
+        self.write_output(&format!(
+            "Created branch '{name}' at tick {current_tick} (id: {branch_id})\n"
+        ));
         Ok(Some(Value::Nil))
     }
 
@@ -2004,10 +3593,10 @@ impl<E: LineEditor> Repl<E> {
         // Restore world from branch tip if available
         if let Some(snapshot) = self.session.timeline().latest_snapshot() {
             let world = snapshot.world().clone();
-            self.session.set_world(world);
+            self.rewind_world(world);
         }
 
-        println!("Switched to branch '{name}'");
+        self.write_output(&format!("Switched to branch '{name}'\n"));
         Ok(Some(Value::Nil))
     }
 
@@ -2015,16 +3604,18 @@ impl<E: LineEditor> Repl<E> {
     ///
     /// Lists all branches.
     #[allow(clippy::unnecessary_wraps)]
-    fn handle_branches(&self) -> Result<Option<Value>> {
+    fn handle_branches(&mut self) -> Result<Option<Value>> {
         let timeline = self.session.timeline();
         let current = timeline.current_branch().name();
         let names = timeline.branch_names();
 
-        println!("Branches:");
+        let mut out = String::new();
+        out.push_str("Branches:\n");
         for name in names {
             let marker = if name == current { " *" } else { "" };
-            println!("  {name}{marker}");
+            let _ = writeln!(out, "  {name}{marker}");
         }
+        self.write_output(&out);
 
         Ok(Some(Value::Nil))
     }
@@ -2075,7 +3666,7 @@ impl<E: LineEditor> Repl<E> {
         if result.is_success() {
             if let Some(world) = result.into_world() {
                 self.session.set_world(world);
-                println!("Merged branch '{name}' into current branch");
+                self.write_output(&format!("Merged branch '{name}' into current branch\n"));
             }
         } else if result.is_failed() {
             return Err(Error::new(ErrorKind::Internal(format!(
@@ -2135,7 +3726,9 @@ impl<E: LineEditor> Repl<E> {
                 };
 
                 let output = format_diff(&diff, self.session.world().interner(), 20);
-                println!("Diff between branches '{name1}' and '{name2}':\n{output}");
+                self.write_output(&format!(
+                    "Diff between branches '{name1}' and '{name2}':\n{output}\n"
+                ));
 
                 return Ok(Some(Value::Nil));
             }
@@ -2172,7 +3765,7 @@ impl<E: LineEditor> Repl<E> {
         };
 
         let output = format_diff(&diff, self.session.world().interner(), 20);
-        println!("Diff between tick {t1} and {t2}:\n{output}");
+        self.write_output(&format!("Diff between tick {t1} and {t2}:\n{output}\n"));
 
         Ok(Some(Value::Nil))
     }
@@ -2198,18 +3791,21 @@ impl<E: LineEditor> Repl<E> {
         };
 
         let history = self.session.timeline().recent_history(count);
+        let shown = history.len();
+        let mut out = String::new();
 
         if history.is_empty() {
-            println!("No history available");
+            out.push_str("No history available\n");
         } else {
-            println!("Recent history ({} ticks):", history.len());
+            let _ = writeln!(out, "Recent history ({} ticks):", history.len());
             for (tick, summary) in &history {
-                println!("  Tick {tick}: {summary}");
+                let _ = writeln!(out, "  Tick {tick}: {summary}");
             }
         }
+        self.write_output(&out);
 
         #[allow(clippy::cast_possible_wrap)]
-        Ok(Some(Value::Int(history.len() as i64)))
+        Ok(Some(Value::Int(shown as i64)))
     }
 
     /// Handles the (input "command") form.
@@ -2245,1196 +3841,6615 @@ impl<E: LineEditor> Repl<E> {
         self.dispatch_input(input_str)
     }
 
-    /// Dispatches natural language input to the appropriate action.
-    fn dispatch_input(&mut self, input: &str) -> Result<Option<Value>> {
-        // Get the player entity as the actor
-        let Some(actor) = self.session.get_entity("player") else {
-            println!("No player entity found.");
-            return Ok(Some(Value::Nil));
+    /// Handles the (transcript) form.
+    ///
+    /// Summarizes recorded natural language input and returns the entry count.
+    #[allow(clippy::unnecessary_wraps)]
+    fn handle_transcript(&mut self) -> Result<Option<Value>> {
+        let transcript = self.session.transcript();
+        let count = transcript.len();
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "Transcript: {} inputs ({} ok, {} ambiguous, {} errors)",
+            transcript.len(),
+            transcript.count(InputOutcome::Success),
+            transcript.count(InputOutcome::Ambiguous),
+            transcript.count(InputOutcome::Error)
+        );
+        for entry in transcript.entries().iter().rev().take(10).rev() {
+            let detail = entry
+                .error
+                .as_deref()
+                .or(entry.syntax.as_deref())
+                .or(entry.action.as_deref())
+                .unwrap_or("");
+            let _ = writeln!(
+                out,
+                "  [tick {}] {:?} -> {:?} {} ({}us)",
+                entry.tick, entry.input, entry.outcome, detail, entry.duration_us
+            );
+        }
+        self.write_output(&out);
+
+        #[allow(clippy::cast_possible_wrap)]
+        Ok(Some(Value::Int(count as i64)))
+    }
+
+    /// Handles the (save-transcript! "path") form.
+    fn handle_save_transcript(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let path = match args.first() {
+            Some(Ast::String(p, _)) => p.clone(),
+            Some(other) => {
+                return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                    "save-transcript! path must be a string, got {}",
+                    other.type_name()
+                ))));
+            }
+            None => {
+                return Err(Error::new(ErrorKind::InvalidArgument(
+                    "save-transcript! requires a path: (save-transcript! \"path\")".to_string(),
+                )));
+            }
         };
 
-        // Try to parse with NaturalLanguageParser
-        let parse_result = {
-            // Build parser with current vocabulary and syntaxes
-            let vocab = self.session.vocabulary_registry().clone();
-            let mut parser = NaturalLanguageParser::new(vocab);
-
-            // Add all compiled syntaxes
-            for syntax in self.session.compiled_syntaxes() {
-                parser.add_syntax(syntax.clone());
-            }
-
-            // Configure noun resolver with appropriate keywords
-            let name_kw = self.session.world().interner().lookup_keyword("name");
-            let value_kw = self.session.world().interner().lookup_keyword("value");
-            let aliases_kw = self.session.world().interner().lookup_keyword("aliases");
-            let adjectives_kw = self.session.world().interner().lookup_keyword("adjectives");
-
-            if let (Some(name_kw), Some(value_kw)) = (name_kw, value_kw) {
-                let resolver = NounResolver::new(
-                    name_kw,
-                    value_kw,
-                    aliases_kw.unwrap_or(name_kw),    // fallback
-                    adjectives_kw.unwrap_or(name_kw), // fallback
-                );
-                parser = parser.with_noun_resolver(resolver);
+        let resolved = self.session.resolve_path(&path);
+        self.session.transcript().save_json(&resolved)?;
+        self.write_output(&format!("Transcript saved to: {}\n", resolved.display()));
+        Ok(Some(Value::Nil))
+    }
+
+    /// Handles the (verbs) form.
+    ///
+    /// Lists the verbs the parser knows, with their synonyms, and returns
+    /// their names.
+    fn handle_verbs(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        if !args.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "verbs takes no arguments".to_string(),
+            )));
+        }
+
+        let interner = self.session.world().interner();
+        let name = |kw: KeywordId| interner.get_keyword(kw).unwrap_or("?").to_string();
+        let mut verbs: Vec<(String, KeywordId, Vec<String>)> = self
+            .session
+            .vocabulary_registry()
+            .verbs()
+            .map(|verb| {
+                let mut synonyms: Vec<String> = verb.synonyms.iter().map(|s| name(*s)).collect();
+                synonyms.sort();
+                (name(verb.name), verb.name, synonyms)
+            })
+            .collect();
+        verbs.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut report = String::new();
+        if verbs.is_empty() {
+            report.push_str("No verbs\n");
+        }
+        for (verb, _, synonyms) in &verbs {
+            if synonyms.is_empty() {
+                let _ = writeln!(report, ":{verb}");
+            } else {
+                let _ = writeln!(report, ":{verb} ({})", synonyms.join(", "));
             }
+        }
+        self.write_output(&report);
+        Ok(Some(Value::Vec(
+            verbs
+                .into_iter()
+                .map(|(_, verb, _)| Value::Keyword(verb))
+                .collect(),
+        )))
+    }
 
-            // Parse the input
-            parser.parse(input, actor, self.session.world())
+    /// Handles the (nouns-in-scope) form.
+    ///
+    /// Lists the entities the player's nouns can refer to right now, as the
+    /// parser would describe them, and returns them, in the order the parser
+    /// ranks them.
+    fn handle_nouns_in_scope(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        if !args.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "nouns-in-scope takes no arguments".to_string(),
+            )));
+        }
+        let Some(actor) = self.session.get_entity("player") else {
+            self.write_output("No player entity found.\n");
+            return Ok(Some(Value::Nil));
         };
 
-        match parse_result {
-            ParseResult::Success(cmd) => {
-                self.execute_parsed_command(cmd.action, actor, cmd.direction, cmd.noun_bindings)
-            }
-            ParseResult::Multiple(cmds) => {
-                // Execute each command in sequence
-                for cmd in cmds {
-                    self.execute_parsed_command(
-                        cmd.action,
-                        actor,
-                        cmd.direction,
-                        cmd.noun_bindings,
-                    )?;
+        let parser = self.input_parser();
+        let world = self.session.world();
+        let mut entities = parser.entities_in_scope(actor, world)?;
+        match parser.noun_resolver() {
+            Some(resolver) => resolver.rank(&mut entities, world),
+            None => entities.sort_by_key(|e| (e.index, e.generation)),
+        }
+        let mut report = String::new();
+        if entities.is_empty() {
+            report.push_str("Nothing in scope\n");
+        }
+        for &entity in &entities {
+            match parser.noun_resolver() {
+                Some(resolver) => {
+                    let _ = writeln!(report, "{entity} {}", resolver.describe(entity, world));
                 }
-                Ok(Some(Value::Nil))
-            }
-            ParseResult::Ambiguous(disamb) => {
-                println!("{}", disamb.question);
-                for (i, (desc, _)) in disamb.options.iter().enumerate() {
-                    println!("  {}. {}", i + 1, desc);
+                None => {
+                    let _ = writeln!(report, "{entity}");
                 }
-                // TODO: Store pending parse state for disambiguation
-                Ok(Some(Value::Nil))
-            }
-            ParseResult::Error(err) => {
-                // Fall back to simple verb lookup for backwards compatibility
-                self.dispatch_input_simple(input, actor).or_else(|_| {
-                    match err {
-                        ParseError::EmptyInput => println!("What?"),
-                        ParseError::NoMatch => {
-                            let verb = input.split_whitespace().next().unwrap_or(input);
-                            println!("I don't understand '{verb}'.");
-                        }
-                        ParseError::UnknownWord(word) => {
-                            println!("I don't know the word '{word}'.");
-                        }
-                        ParseError::NotFound(noun) => {
-                            println!("I don't see any '{noun}' here.");
-                        }
-                        ParseError::WrongType { noun, expected } => {
-                            println!("You can't do that with the {noun} ({expected} expected).");
-                        }
-                        ParseError::NoReferent(pronoun) => {
-                            println!("I don't know what '{pronoun}' refers to.");
-                        }
-                    }
-                    Ok(Some(Value::Nil))
-                })
             }
         }
+        self.write_output(&report);
+        Ok(Some(Value::Vec(
+            entities.into_iter().map(Value::EntityRef).collect(),
+        )))
     }
 
-    /// Executes a parsed command.
-    fn execute_parsed_command(
-        &mut self,
-        action: KeywordId,
-        actor: EntityId,
-        direction: Option<(String, KeywordId)>,
-        noun_bindings: std::collections::HashMap<String, EntityId>,
-    ) -> Result<Option<Value>> {
-        // Get the full action declaration
-        let Some(action_decl) = self.session.get_action_decl(action).cloned() else {
-            let action_name = self
-                .session
-                .world()
-                .interner()
-                .get_keyword(action)
-                .unwrap_or("unknown");
-            println!("Action '{action_name}' has no declaration.");
-            return Ok(Some(Value::Nil));
+    /// Handles the (syntax-for :verb word) form.
+    ///
+    /// Lists the command syntaxes starting with a verb, in the order the
+    /// parser tries them, and returns the commands' names.
+    fn handle_syntax_for(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let word = match args {
+            [
+                Ast::Keyword(kind, _),
+                Ast::Symbol(word, _) | Ast::Keyword(word, _),
+            ] if kind == "verb" => word,
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidArgument(
+                    "syntax-for requires a verb: (syntax-for :verb take)".to_string(),
+                )));
+            }
         };
 
-        // Build bindings
-        let mut bindings = Bindings::new();
+        let interner = self.session.world().interner();
+        let vocabulary = self.session.vocabulary_registry();
+        let Some(verb) = interner
+            .lookup_keyword(word)
+            .and_then(|kw| vocabulary.lookup_verb(kw))
+        else {
+            self.write_output(&format!("'{word}' isn't a verb.\n"));
+            return Ok(Some(Value::Vec(std::iter::empty().collect())));
+        };
 
-        // Bind actor
-        if action_decl.params.contains(&"actor".to_string()) {
-            bindings.set("actor".to_string(), Value::EntityRef(actor));
+        let mut syntaxes: Vec<_> = self
+            .session
+            .compiled_syntaxes()
+            .iter()
+            .filter(|syntax| syntax.verb() == Some(verb.name))
+            .collect();
+        syntaxes.sort_by(|a, b| {
+            b.specificity()
+                .cmp(&a.specificity())
+                .then_with(|| b.priority.cmp(&a.priority))
+        });
+
+        let name = |kw: KeywordId| interner.get_keyword(kw).unwrap_or("?");
+        let mut report = String::new();
+        if syntaxes.is_empty() {
+            let _ = writeln!(report, "No syntax for :{}", name(verb.name));
         }
-
-        // Bind direction if present
-        // Note: We use "direction" as the binding name since that's what the `go` action expects
-        // TODO: Properly use the :bindings mapping from command declarations
-        if let Some((_var_name, dir_kw)) = direction {
-            bindings.set("direction".to_string(), Value::Keyword(dir_kw));
+        for syntax in &syntaxes {
+            let _ = writeln!(
+                report,
+                ":{:<20} {} -> :{}",
+                name(syntax.command),
+                syntax.describe(interner),
+                name(syntax.action)
+            );
         }
+        let commands = syntaxes
+            .iter()
+            .map(|syntax| Value::Keyword(syntax.command))
+            .collect();
+        self.write_output(&report);
+        Ok(Some(Value::Vec(commands)))
+    }
 
-        // Bind all noun bindings (e.g., target, item, container, etc.)
-        for (var_name, entity_id) in noun_bindings {
-            bindings.set(var_name, Value::EntityRef(entity_id));
-        }
+    /// Handles the (export-json! "path") form.
+    fn handle_export_json(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [Ast::String(path, _)] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "export-json! requires a path string: (export-json! \"path\")".to_string(),
+            )));
+        };
 
-        // Evaluate preconditions
-        if !action_decl.preconditions.is_empty() {
-            match self.evaluate_preconditions(&action_decl, &bindings) {
-                Ok(Some(new_bindings)) => bindings = new_bindings,
-                Ok(None) => return Ok(Some(Value::Nil)),
-                Err(e) => return Err(e),
-            }
-        }
+        let resolved = self.session.resolve_path(path);
+        serialize::save_json_to_file(self.session.world(), self.session.entity_names(), &resolved)?;
+        self.write_output(&format!("World exported to: {}\n", resolved.display()));
+        Ok(Some(Value::Nil))
+    }
 
-        // Execute handlers
-        for handler in &action_decl.handler {
-            self.execute_action_handler(handler, &bindings)?;
-        }
+    /// Handles the (import-json! "path") form.
+    ///
+    /// The file replaces every entity and named entity; schemas, rules, and
+    /// everything else loaded into the session stay as they are.
+    fn handle_import_json(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [Ast::String(path, _)] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "import-json! requires a path string: (import-json! \"path\")".to_string(),
+            )));
+        };
 
+        let resolved = self.session.resolve_path(path);
+        let (world, names) = serialize::load_json_from_file(&resolved, self.session.world())?;
+        let entity_count = world.entity_count();
+        let tick = world.tick();
+        self.session.record_undo_point();
+        self.session.set_world(world);
+        self.session.set_entity_names(names);
+        self.write_output(&format!(
+            "World imported from: {} ({entity_count} entities, tick {tick})\n",
+            resolved.display()
+        ));
         Ok(Some(Value::Nil))
     }
 
-    /// Simple input dispatch - fallback for when parser fails.
-    fn dispatch_input_simple(&mut self, input: &str, actor: EntityId) -> Result<Option<Value>> {
-        let tokens: Vec<&str> = input.split_whitespace().collect();
+    /// Handles the (export-datoms! "path") form.
+    fn handle_export_datoms(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [Ast::String(path, _)] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "export-datoms! requires a path string: (export-datoms! \"path\")".to_string(),
+            )));
+        };
 
-        if tokens.is_empty() {
-            return Err(Error::new(ErrorKind::Internal("Empty input".to_string())));
-        }
+        let resolved = self.session.resolve_path(path);
+        datoms::save_to_file(self.session.world(), self.session.entity_names(), &resolved)?;
+        self.write_output(&format!("Datoms exported to: {}\n", resolved.display()));
+        Ok(Some(Value::Nil))
+    }
 
-        let verb = tokens[0].to_lowercase();
-        let action_name_kw = self
-            .session
-            .world_mut()
-            .interner_mut()
-            .intern_keyword(&verb);
+    /// Handles the (export-trace! "path") form, which writes the trace buffer
+    /// in the Chrome trace event format for `chrome://tracing` or Perfetto.
+    fn handle_export_trace(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        use longtable_debug::{ChromeTraceFormatter, TraceFormatter};
 
-        // Check if we have an action registered with this name
-        if self.session.action_registry().get(action_name_kw).is_none() {
-            return Err(Error::new(ErrorKind::Internal(format!(
-                "Unknown action: {verb}"
-            ))));
-        }
+        let [Ast::String(path, _)] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "export-trace! requires a path string: (export-trace! \"path\")".to_string(),
+            )));
+        };
 
-        let Some(action_decl) = self.session.get_action_decl(action_name_kw).cloned() else {
-            return Err(Error::new(ErrorKind::Internal(format!(
-                "Action '{verb}' has no declaration."
-            ))));
+        let records: Vec<_> = self.session.tracer().buffer().iter().collect();
+        let count = records.len();
+        let json =
+            ChromeTraceFormatter::new().format_many(&records, self.session.world().interner());
+        let resolved = self.session.resolve_path(path);
+        std::fs::write(&resolved, json).map_err(|e| {
+            Error::new(ErrorKind::IoError(format!(
+                "cannot write {}: {e}",
+                resolved.display()
+            )))
+        })?;
+        self.write_output(&format!(
+            "Trace exported to: {} ({count} records)\n",
+            resolved.display()
+        ));
+        #[allow(clippy::cast_possible_wrap)]
+        Ok(Some(Value::Int(count as i64)))
+    }
+
+    /// Handles the (import-datoms! "path") form, which replaces entities like
+    /// (import-json! ...).
+    fn handle_import_datoms(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [Ast::String(path, _)] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "import-datoms! requires a path string: (import-datoms! \"path\")".to_string(),
+            )));
         };
 
-        let mut bindings = Bindings::new();
+        let resolved = self.session.resolve_path(path);
+        let (world, names) = datoms::load_from_file(&resolved, self.session.world())?;
+        let entity_count = world.entity_count();
+        let tick = world.tick();
+        self.session.record_undo_point();
+        self.session.set_world(world);
+        self.session.set_entity_names(names);
+        self.write_output(&format!(
+            "Datoms imported from: {} ({entity_count} entities, tick {tick})\n",
+            resolved.display()
+        ));
+        Ok(Some(Value::Nil))
+    }
 
-        if action_decl.params.contains(&"actor".to_string()) {
-            bindings.set("actor".to_string(), Value::EntityRef(actor));
-        }
+    /// Handles the (telemetry-opt-in! bool) form.
+    fn handle_telemetry_opt_in(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [Ast::Bool(opted_in, _)] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "telemetry-opt-in! requires a boolean: (telemetry-opt-in! true)".to_string(),
+            )));
+        };
 
-        // Bind direction if present
-        if action_decl.params.contains(&"direction".to_string()) && tokens.len() > 1 {
-            let direction_str = tokens[1].to_lowercase();
-            let direction_kw = self
-                .session
-                .world_mut()
-                .interner_mut()
-                .intern_keyword(&direction_str);
-            bindings.set("direction".to_string(), Value::Keyword(direction_kw));
-        }
+        self.session.telemetry_mut().set_opted_in(*opted_in);
+        Ok(Some(Value::Bool(self.session.telemetry().is_enabled())))
+    }
 
-        if !action_decl.preconditions.is_empty() {
-            match self.evaluate_preconditions(&action_decl, &bindings) {
-                Ok(Some(new_bindings)) => bindings = new_bindings,
-                Ok(None) => return Ok(Some(Value::Nil)),
-                Err(e) => return Err(e),
-            }
-        }
+    /// Handles the (fuzzy-matching! true|false) form.
+    fn handle_fuzzy_matching(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [Ast::Bool(enabled, _)] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "fuzzy-matching! requires a boolean: (fuzzy-matching! true)".to_string(),
+            )));
+        };
 
-        for handler in &action_decl.handler {
-            self.execute_action_handler(handler, &bindings)?;
-        }
+        self.session
+            .vocabulary_registry_mut()
+            .set_fuzzy_matching(*enabled);
+        Ok(Some(Value::Nil))
+    }
+
+    /// Handles the (content-ids! true|false) form.
+    fn handle_content_ids(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [Ast::Bool(enabled, _)] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "content-ids! requires a boolean: (content-ids! true)".to_string(),
+            )));
+        };
 
+        let world = self.session.world();
+        let policy = world.entity_policy().with_content_ids(*enabled);
+        let world = world.with_entity_policy(policy);
+        self.session.set_world(world);
         Ok(Some(Value::Nil))
     }
 
-    /// Evaluates action preconditions and returns bindings if they all pass.
-    fn evaluate_preconditions(
-        &mut self,
-        action_decl: &longtable_language::ActionDecl,
-        initial_bindings: &Bindings,
-    ) -> Result<Option<Bindings>> {
-        // Evaluate all preconditions and accumulate bindings
-        let mut result = initial_bindings.clone();
+    /// Handles the (set-locale! :locale) form.
+    fn handle_set_locale(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [Ast::Keyword(locale, _)] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "set-locale! requires a locale keyword: (set-locale! :fr)".to_string(),
+            )));
+        };
 
-        for precondition in &action_decl.preconditions {
-            // Compile the pattern
-            let compiled = PatternCompiler::compile(
-                &precondition.pattern,
-                self.session.world_mut().interner_mut(),
-            )?;
+        self.session.messages_mut().set_locale(locale.as_str());
+        Ok(Some(Value::Nil))
+    }
 
-            // Try to match against the world
-            let all_matches = PatternMatcher::match_pattern(&compiled, self.session.world());
+    /// Handles the (enable-group! :group) and (disable-group! :group) forms.
+    fn handle_set_group_enabled(&mut self, enabled: bool, args: &[Ast]) -> Result<Option<Value>> {
+        let form = if enabled {
+            "enable-group!"
+        } else {
+            "disable-group!"
+        };
+        let [Ast::Keyword(name, _)] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                "{form} requires a group keyword: ({form} :combat)"
+            ))));
+        };
 
-            // Filter matches to those compatible with our initial bindings
-            let compatible_matches: Vec<_> = all_matches
-                .into_iter()
-                .filter(|m| {
-                    // Check that all variables in initial_bindings match this result
-                    for (var, expected_val) in result.iter() {
-                        if let Some(actual_val) = m.get(var) {
-                            if actual_val != expected_val {
-                                return false;
-                            }
-                        }
-                    }
-                    true
-                })
-                .collect();
+        let group = self.session.world_mut().interner_mut().intern_keyword(name);
+        let engine = self.tick_executor.rule_engine_mut();
+        if enabled {
+            engine.enable_group(group);
+        } else {
+            engine.disable_group(group);
+        }
+        Ok(Some(Value::Nil))
+    }
 
-            if compatible_matches.is_empty() {
-                // No match - precondition failed
-                // TODO: Print the failure message from precondition.message
-                println!("You can't do that.");
-                return Ok(None);
+    /// Handles the (on-phase :phase fn) form.
+    fn handle_on_phase(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [Ast::Keyword(name, _), hook] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "on-phase requires a phase and a function: (on-phase :before-constraints (fn [ctx] ...))"
+                    .to_string(),
+            )));
+        };
+        let phase = TickPhase::from_name(name).ok_or_else(|| {
+            let names: Vec<_> = TickPhase::ALL.iter().map(|p| p.name()).collect();
+            Error::new(ErrorKind::InvalidArgument(format!(
+                "unknown tick phase :{name}"
+            )))
+            .with_hint(format!("phases are :{}", names.join(", :")))
+        })?;
+
+        self.session.add_phase_hook(phase, hook.clone());
+        Ok(Some(Value::Nil))
+    }
+
+    /// Handles `:on name` in a top-level timer form, where the name is a
+    /// spawned entity's, by evaluating the form with the entity in its place.
+    /// Other timer forms are left to the compiler.
+    fn handle_named_timer_owner(&mut self, list: &[Ast]) -> Result<Option<Value>> {
+        let owner = list
+            .windows(2)
+            .enumerate()
+            .find_map(|(i, pair)| match pair {
+                [Ast::Keyword(on, _), Ast::Symbol(name, span)] if on == "on" => self
+                    .session
+                    .get_entity(name)
+                    .map(|entity| (i + 1, entity, *span)),
+                _ => None,
+            });
+        let Some((at, entity, span)) = owner else {
+            return Ok(None);
+        };
+        let mut form = list.to_vec();
+        form[at] = self.value_to_ast(&Value::EntityRef(entity), span);
+        self.eval_form(&Ast::List(form, span)).map(Some)
+    }
+
+    /// Handles the (pause-timers! entity) and (resume-timers! entity) forms.
+    fn handle_pause_timers(&mut self, pause: bool, args: &[Ast]) -> Result<Option<Value>> {
+        let form = if pause {
+            "pause-timers!"
+        } else {
+            "resume-timers!"
+        };
+        let [arg] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                "{form} requires exactly 1 argument: ({form} entity)"
+            ))));
+        };
+        // A spawned entity's name, or an expression giving the entity
+        let named = match arg {
+            Ast::Symbol(name, _) => self.session.get_entity(name),
+            _ => None,
+        };
+        let value = match named {
+            Some(id) => Value::EntityRef(id),
+            None => self.eval_form(arg)?,
+        };
+        let Value::EntityRef(entity) = value else {
+            return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                "{form} argument must be an entity"
+            ))));
+        };
+        let count = if pause {
+            self.tick_executor.pause_timers(entity)
+        } else {
+            self.tick_executor.resume_timers(entity)
+        };
+        Ok(Some(Value::Int(i64::try_from(count).unwrap_or(i64::MAX))))
+    }
+
+    /// Handles the (spatial-index :component :cell-size size) form.
+    ///
+    /// Indexes the component's positions in a grid, so `entities-within` and
+    /// `within?` patterns only visit the cells near the point they ask about.
+    fn handle_spatial_index(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [Ast::Keyword(component, _), Ast::Keyword(option, _), size] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "spatial-index expects (spatial-index :component :cell-size size)".to_string(),
+            )));
+        };
+        if option != "cell-size" {
+            return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                "spatial-index: unknown option :{option}, expected :cell-size"
+            ))));
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let cell_size = match self.eval_form(size)? {
+            Value::Int(n) => n as f64,
+            Value::Float(x) => x,
+            other => {
+                return Err(Error::new(ErrorKind::Internal(format!(
+                    "spatial-index: :cell-size must be a number, got {}",
+                    self.display_value(&other)
+                ))));
             }
+        };
+        let component = self
+            .session
+            .world_mut()
+            .interner_mut()
+            .intern_keyword(component);
+        let world = self
+            .session
+            .world()
+            .with_spatial_index(component, cell_size)?;
+        self.session.set_world(world);
+        Ok(Some(Value::Nil))
+    }
 
-            // Use the first compatible match and merge with accumulated bindings
-            for (var, value) in compatible_matches[0].iter() {
-                result.set(var.clone(), value.clone());
+    /// Handles the (before action ...) and (after action ...) forms.
+    ///
+    /// `:on` takes an entity's name or a component keyword; the forms that
+    /// follow are the hook's body.
+    fn handle_action_hook(&mut self, timing: HookTiming, args: &[Ast]) -> Result<Option<Value>> {
+        let form = match timing {
+            HookTiming::Before => "before",
+            HookTiming::After => "after",
+        };
+        let usage = || {
+            Error::new(ErrorKind::InvalidArgument(format!(
+                "{form} requires an action and forms: ({form} take :on lamp forms...)"
+            )))
+        };
+        let Some((Ast::Symbol(action, _) | Ast::Keyword(action, _), rest)) = args.split_first()
+        else {
+            return Err(usage());
+        };
+        let (target, body) = match rest {
+            [Ast::Keyword(on, _), Ast::Symbol(name, _), body @ ..] if on == "on" => {
+                let entity = self.session.get_entity(name).ok_or_else(|| {
+                    Error::new(ErrorKind::NotFound(format!(
+                        "{form} :on names unknown entity '{name}'"
+                    )))
+                })?;
+                (HookTarget::Entity(entity), body)
+            }
+            [Ast::Keyword(on, _), Ast::Keyword(component, _), body @ ..] if on == "on" => {
+                let component = self
+                    .session
+                    .world_mut()
+                    .interner_mut()
+                    .intern_keyword(component);
+                (HookTarget::Component(component), body)
             }
+            [Ast::Keyword(on, _), ..] if on == "on" => return Err(usage()),
+            body => (HookTarget::Any, body),
+        };
+        if body.is_empty() {
+            return Err(usage());
         }
 
-        Ok(Some(result))
+        let action = self
+            .session
+            .world_mut()
+            .interner_mut()
+            .intern_keyword(action);
+        self.session.add_action_hook(ActionHook {
+            timing,
+            action,
+            target,
+            body: body.to_vec(),
+        });
+        Ok(Some(Value::Nil))
     }
 
-    /// Executes a single action handler expression with variable bindings.
-    fn execute_action_handler(&mut self, handler: &Ast, bindings: &Bindings) -> Result<Value> {
-        // Convert Vector to List for evaluation (handlers may be deserialized as vectors)
-        let handler = match handler {
-            Ast::Vector(elements, span) => Ast::List(elements.clone(), *span),
-            other => other.clone(),
+    /// Handles the (when-feature condition forms...) form.
+    ///
+    /// The condition is a feature keyword, or `(not c)`, `(and c...)`, or
+    /// `(or c...)` over conditions. Forms are evaluated in order when it holds,
+    /// returning the last value; otherwise they are skipped and nil returned.
+    fn handle_when_feature(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let Some((condition, body)) = args.split_first() else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "when-feature requires a feature: (when-feature :debug-content forms...)"
+                    .to_string(),
+            )));
         };
-
-        // Check for special forms like (say "...")
-        if let Ast::List(ref elements, _) = handler {
-            if let Some(Ast::Symbol(name, _)) = elements.first() {
-                if name == "say" {
-                    // Handle (say expr) specially
-                    if let Some(msg_ast) = elements.get(1) {
-                        let msg = self.eval_with_bindings(msg_ast, bindings)?;
-                        if let Value::String(s) = msg {
-                            println!("{s}");
-                        } else {
-                            println!("{msg}");
-                        }
-                        return Ok(Value::Nil);
-                    }
-                }
-            }
+        if !self.feature_condition(condition)? {
+            return Ok(Some(Value::Nil));
         }
 
-        // Fall back to general evaluation with bindings
-        self.eval_with_bindings(&handler, bindings)
+        let mut result = Value::Nil;
+        for form in body {
+            result = self.eval_form(form)?;
+        }
+        Ok(Some(result))
     }
 
-    /// Evaluates an AST with variable bindings.
-    fn eval_with_bindings(&mut self, ast: &Ast, bindings: &Bindings) -> Result<Value> {
-        // If it's a variable reference (symbol starting with ?), look it up
-        if let Ast::Symbol(name, _) = ast {
-            if let Some(stripped) = name.strip_prefix('?') {
-                if let Some(value) = bindings.get(stripped) {
-                    return Ok(value.clone());
-                }
-                // Variable not bound - return as error or nil
-                return Err(Error::new(ErrorKind::Internal(format!(
-                    "unbound variable: {name}"
-                ))));
+    /// Handles the (do! forms...) form.
+    ///
+    /// Evaluates the forms in order, each seeing the effects of those before
+    /// it, and returns the last value. If one fails, the world, definitions,
+    /// constraints, and provenance go back to how they were before the block,
+    /// so it applies in full or not at all. A block that succeeds is a single
+    /// step for `(undo!)`.
+    fn handle_do_block(&mut self, body: &[Ast]) -> Result<Option<Value>> {
+        let checkpoint = self.session.checkpoint();
+        let constraints = self.tick_executor.constraint_checker().clone();
+        let provenance = self.tick_executor.provenance().clone();
+        let undo_depth = self.session.undo_depth();
+
+        let result = body
+            .iter()
+            .try_fold(Value::Nil, |_, form| self.eval_form(form));
+        match result {
+            Ok(value) => {
+                self.session.truncate_undo(undo_depth + 1);
+                Ok(Some(value))
+            }
+            Err(e) => {
+                self.session.restore(checkpoint);
+                self.session.truncate_undo(undo_depth);
+                *self.tick_executor.constraint_checker_mut() = constraints;
+                *self.tick_executor.provenance_mut() = provenance;
+                Err(e)
             }
         }
+    }
 
-        // For function calls, substitute variables in arguments
-        // Handle both List and Vector (vectors from deserialization should be treated as lists)
-        let (Ast::List(elements, span) | Ast::Vector(elements, span)) = ast else {
-            return self.eval_form(ast);
+    /// Handles the (run-scenarios) and (run-scenarios "name") forms.
+    ///
+    /// Prints a report and returns whether every scenario passed.
+    fn handle_run_scenarios(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let only = match args {
+            [] => None,
+            [Ast::String(name, _)] => Some(name.as_str()),
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidArgument(
+                    "run-scenarios takes an optional scenario name".to_string(),
+                )));
+            }
         };
+        let results = self.run_scenarios(only);
+        if let (Some(name), true) = (only, results.is_empty()) {
+            return Err(Error::new(ErrorKind::NotFound(format!(
+                "no scenario named \"{name}\""
+            ))));
+        }
+        self.write_output(&scenario::report(&results));
+        Ok(Some(Value::Bool(
+            results.iter().all(ScenarioResult::passed),
+        )))
+    }
 
-        if !elements.is_empty() {
-            // Substitute variables in each element
-            let substituted: Vec<Ast> = elements
-                .iter()
-                .map(|elem| self.substitute_variables(elem, bindings))
-                .collect();
+    /// Runs the declared scenarios, or only the one named `only`.
+    ///
+    /// Each runs against the session as it stands and is undone afterwards,
+    /// output included.
+    pub fn run_scenarios(&mut self, only: Option<&str>) -> Vec<ScenarioResult> {
+        let scenarios: Vec<Scenario> = self
+            .session
+            .scenarios()
+            .iter()
+            .filter(|s| only.is_none_or(|name| s.name == name))
+            .cloned()
+            .collect();
+        scenarios
+            .iter()
+            .map(|scenario| self.run_scenario(scenario))
+            .collect()
+    }
 
-            // Evaluate the substituted form as a list (function call)
-            let new_ast = Ast::List(substituted, *span);
-            return self.eval_form(&new_ast);
+    /// Runs one scenario's setup and steps, then checks its assertions.
+    fn run_scenario(&mut self, scenario: &Scenario) -> ScenarioResult {
+        let checkpoint = self.session.checkpoint();
+        let constraints = self.tick_executor.constraint_checker().clone();
+        let provenance = self.tick_executor.provenance().clone();
+        let scheduler = self.tick_executor.scheduler().clone();
+        let tick = self.tick_executor.tick_number();
+        let undo_depth = self.session.undo_depth();
+        let captured = self.captured.replace(Vec::new());
+
+        let mut failures = Vec::new();
+        let ran = scenario
+            .setup
+            .iter()
+            .chain(&scenario.steps)
+            .try_for_each(|form| {
+                self.eval_form(form).map(drop).map_err(|e| ScenarioFailure {
+                    form: longtable_language::pretty::pretty_print(form),
+                    detail: error_detail(&e),
+                })
+            });
+        if let Err(failure) = ran {
+            failures.push(failure);
+        } else {
+            let (clauses, expressions): (Vec<&Ast>, Vec<&Ast>) = scenario
+                .assertions
+                .iter()
+                .partition(|assertion| matches!(assertion, Ast::Vector(..)));
+            let clauses: Vec<Ast> = clauses.into_iter().cloned().collect();
+            if !clauses.is_empty() {
+                failures.extend(self.check_scenario_clauses(&clauses));
+            }
+            for expression in expressions {
+                let detail = match self.eval_form(expression) {
+                    Ok(value) if value.is_truthy() => continue,
+                    Ok(value) => format!("- true\n+ {}\n", self.display_value(&value)),
+                    Err(e) => error_detail(&e),
+                };
+                failures.push(ScenarioFailure {
+                    form: longtable_language::pretty::pretty_print(expression),
+                    detail,
+                });
+            }
         }
 
-        // Fall back to normal evaluation
-        self.eval_form(ast)
+        let output = std::mem::replace(&mut self.captured, captured)
+            .map(|spans| plain_text(&spans))
+            .unwrap_or_default();
+        self.session.restore(checkpoint);
+        self.session.truncate_undo(undo_depth);
+        *self.tick_executor.constraint_checker_mut() = constraints;
+        *self.tick_executor.provenance_mut() = provenance;
+        *self.tick_executor.scheduler_mut() = scheduler;
+        self.tick_executor.set_tick_number(tick);
+        ScenarioResult {
+            name: scenario.name.clone(),
+            failures,
+            output,
+        }
     }
 
-    /// Substitutes bound variables in an AST.
-    fn substitute_variables(&self, ast: &Ast, bindings: &Bindings) -> Ast {
-        match ast {
-            Ast::Symbol(name, span) => {
-                if let Some(stripped) = name.strip_prefix('?') {
-                    if let Some(value) = bindings.get(stripped) {
-                        // Convert Value back to AST
-                        return self.value_to_ast(value, *span);
+    /// Checks that query clauses match together, explaining the first one
+    /// that leaves nothing matching.
+    fn check_scenario_clauses(&mut self, clauses: &[Ast]) -> Option<ScenarioFailure> {
+        let pretty = longtable_language::pretty::pretty_print;
+        let mut expanded = Vec::new();
+        for clause in clauses {
+            let before = expanded.len();
+            let detail = match self.expand_field_clause(clause, before) {
+                Ok(group) => {
+                    expanded.extend(group);
+                    match self.query_clauses(&expanded, None) {
+                        Ok(results) if !results.is_empty() => continue,
+                        Ok(_) => self.explain_failed_clause(&expanded[..before], clause),
+                        Err(e) => error_detail(&e),
                     }
                 }
-                ast.clone()
-            }
-            Ast::List(elements, span) => {
-                let substituted: Vec<Ast> = elements
-                    .iter()
-                    .map(|elem| self.substitute_variables(elem, bindings))
-                    .collect();
-                Ast::List(substituted, *span)
-            }
-            // Vectors - substitute but keep as vectors (needed for let bindings, etc.)
-            Ast::Vector(elements, span) => {
-                let substituted: Vec<Ast> = elements
-                    .iter()
-                    .map(|elem| self.substitute_variables(elem, bindings))
-                    .collect();
-                Ast::Vector(substituted, *span)
-            }
-            // Other AST types pass through unchanged
-            _ => ast.clone(),
+                Err(e) => error_detail(&e),
+            };
+            return Some(ScenarioFailure {
+                form: pretty(clause),
+                detail,
+            });
         }
+        None
     }
 
-    /// Converts a Value back to an AST for substitution.
-    #[allow(clippy::cast_possible_wrap)]
-    fn value_to_ast(&self, value: &Value, span: longtable_language::Span) -> Ast {
-        match value {
-            Value::Bool(b) => Ast::Bool(*b, span),
-            Value::Int(n) => Ast::Int(*n, span),
-            Value::Float(f) => Ast::Float(*f, span),
-            Value::String(s) => Ast::String(s.to_string(), span),
-            Value::Keyword(kw) => {
-                // Look up the keyword name
-                let name = self
-                    .session
-                    .world()
-                    .interner()
-                    .get_keyword(*kw)
-                    .unwrap_or("unknown");
-                Ast::Keyword(name.to_string(), span)
-            }
-            Value::EntityRef(id) => {
-                // Represent as a special form the evaluator can understand
-                Ast::List(
-                    vec![
-                        Ast::Symbol("entity-ref".to_string(), span),
-                        Ast::Int(id.index as i64, span),
-                        Ast::Int(i64::from(id.generation), span),
-                    ],
-                    span,
-                )
-            }
-            // Nil and collections fall through to Nil
-            _ => Ast::Nil(span),
+    /// Splits a clause on one field of a component, like
+    /// `[?g :health/current 0]`, into its entity, component, field, and
+    /// value. Returns `None` for any other clause, including one on a
+    /// component whose own name has a slash.
+    fn field_clause<'a>(&self, clause: &'a Ast) -> Option<(&'a Ast, &'a str, &'a str, &'a Ast)> {
+        let Ast::Vector(parts, _) = clause else {
+            return None;
+        };
+        let [entity, Ast::Keyword(attribute, _), value] = parts.as_slice() else {
+            return None;
+        };
+        let world = self.session.world();
+        let interner = world.interner();
+        let is_component = |name: &str| {
+            interner
+                .lookup_keyword(name)
+                .and_then(|id| world.component_schema(id))
+        };
+        if is_component(attribute).is_some() {
+            return None;
         }
+        let (component, field) = attribute.rsplit_once('/')?;
+        let field_id = interner.lookup_keyword(field)?;
+        is_component(component)?
+            .fields
+            .iter()
+            .any(|schema| schema.name == field_id)
+            .then_some((entity, component, field, value))
     }
 
-    /// Handles the (timeline) form.
-    ///
-    /// Shows timeline status.
-    #[allow(clippy::unnecessary_wraps)]
-    fn handle_timeline(&self) -> Result<Option<Value>> {
-        let status = self.session.timeline().status();
-        println!("{status}");
-        Ok(Some(Value::Nil))
+    /// Rewrites a field clause (see [`Self::field_clause`]) as a clause
+    /// binding the component and a test of the field, so
+    /// `[?g :health/current 0]` becomes `[?g :health ?field-N]
+    /// [(= (get ?field-N :current) 0)]`. Other clauses are returned as they
+    /// are.
+    fn expand_field_clause(&self, clause: &Ast, n: usize) -> Result<Vec<Ast>> {
+        let Some((entity, component, field, value)) = self.field_clause(clause) else {
+            return Ok(vec![clause.clone()]);
+        };
+        if matches!(value, Ast::Symbol(name, _) if name.starts_with('?')) {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "a field clause needs a literal value".to_string(),
+            ))
+            .with_hint(format!(
+                "bind the component instead: [?e :{component} ?c] [(= (get ?c :{field}) ...)]"
+            )));
+        }
+        let span = clause.span();
+        let binding = Ast::Symbol(format!("?field-{n}"), span);
+        let get = Ast::List(
+            vec![
+                Ast::Symbol("get".to_string(), span),
+                binding.clone(),
+                Ast::Keyword(field.to_string(), span),
+            ],
+            span,
+        );
+        let test = Ast::List(
+            vec![Ast::Symbol("=".to_string(), span), get, value.clone()],
+            span,
+        );
+        Ok(vec![
+            Ast::Vector(
+                vec![
+                    entity.clone(),
+                    Ast::Keyword(component.to_string(), span),
+                    binding,
+                ],
+                span,
+            ),
+            Ast::Vector(vec![test], span),
+        ])
     }
 
-    // ==================== Backtracking Support Handlers ====================
-
-    /// Handles the (save-state) form.
-    ///
-    /// Saves the current world state and returns a snapshot ID.
-    /// This is used by the Sudoku solver for backtracking.
-    #[allow(clippy::unnecessary_wraps)]
-    fn handle_save_state(&mut self) -> Result<Option<Value>> {
-        let id = self.session.save_state();
-        #[allow(clippy::cast_possible_wrap)]
-        Ok(Some(Value::Int(id as i64)))
+    /// Describes a clause that matched nothing as a diff against the values
+    /// the world holds in its place: `[?g :health {:current 0}]` becomes
+    /// `- [?g :health {:current 0}]` and `+ [?g :health {:current 3}]`.
+    fn explain_failed_clause(&mut self, before: &[Ast], clause: &Ast) -> String {
+        let pretty = longtable_language::pretty::pretty_print;
+        let mut detail = format!("- {}\n", pretty(clause));
+        let found = match clause {
+            Ast::Vector(parts, span) => match parts.as_slice() {
+                [entity, attribute @ Ast::Keyword(..), value]
+                    if !matches!(value, Ast::Symbol(..)) =>
+                {
+                    let actual = Ast::Symbol("?actual".to_string(), *span);
+                    // A field clause looks up the component and reports the field
+                    let (queried, field) = match self.field_clause(clause) {
+                        Some((_, component, field, _)) => (
+                            Ast::Keyword(component.to_string(), *span),
+                            self.session.world().interner().lookup_keyword(field),
+                        ),
+                        None => (attribute.clone(), None),
+                    };
+                    let mut clauses = before.to_vec();
+                    clauses.push(Ast::Vector(
+                        vec![entity.clone(), queried, actual.clone()],
+                        *span,
+                    ));
+                    let values = self
+                        .query_clauses(&clauses, Some(actual))
+                        .unwrap_or_default();
+                    let mut found: Vec<String> = values
+                        .iter()
+                        .map(|value| match (field, value) {
+                            (Some(field), Value::Map(fields)) => fields
+                                .get(&Value::Keyword(field))
+                                .cloned()
+                                .unwrap_or(Value::Nil),
+                            _ => value.clone(),
+                        })
+                        .map(|value| {
+                            let value =
+                                session::value_to_ast(&value, self.session.world().interner());
+                            let parts = vec![entity.clone(), attribute.clone(), value];
+                            pretty(&Ast::Vector(parts, *span))
+                        })
+                        .collect();
+                    found.sort();
+                    found.dedup();
+                    found
+                }
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+        if found.is_empty() {
+            detail.push_str("+ nothing matches\n");
+        }
+        for clause in found {
+            let _ = writeln!(detail, "+ {clause}");
+        }
+        detail
     }
 
-    /// Handles the (restore-state ID) form.
-    ///
-    /// Restores the world to a previously saved snapshot.
-    fn handle_restore_state(&mut self, args: &[Ast]) -> Result<Option<Value>> {
-        if args.is_empty() {
-            return Err(Error::new(ErrorKind::Internal(
-                "restore-state requires a snapshot ID: (restore-state ID)".to_string(),
-            )));
+    /// Runs a query over `clauses`, returning `returning` for each match (or
+    /// the matches' bindings).
+    fn query_clauses(&mut self, clauses: &[Ast], returning: Option<Ast>) -> Result<Vec<Value>> {
+        let span = Span::default();
+        let mut query = vec![
+            Ast::Symbol("query".to_string(), span),
+            Ast::Keyword("where".to_string(), span),
+            Ast::Vector(clauses.to_vec(), span),
+        ];
+        if let Some(returning) = returning {
+            query.push(Ast::Keyword("return".to_string(), span));
+            query.push(returning);
         }
-
-        let id_val = self.eval_form(&args[0])?;
-        let Value::Int(id) = id_val else {
-            return Err(Error::new(ErrorKind::Internal(
-                "restore-state requires an integer snapshot ID".to_string(),
+        let query = self
+            .session
+            .namespace_context()
+            .resolve_keywords(&Ast::List(query, span))?;
+        let Some(Declaration::Query(query_decl)) = DeclarationAnalyzer::analyze(&query)? else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "invalid query clauses".to_string(),
             )));
         };
+        let compiled = self.compile_query(&query_decl)?;
+        QueryExecutor::execute(&compiled, self.session.world())
+    }
 
-        #[allow(clippy::cast_sign_loss)]
-        let id = id as u64;
-
-        self.session.restore_state(id)?;
-        Ok(Some(Value::Nil))
+    /// Evaluates a `when-feature` condition against the session's features.
+    fn feature_condition(&self, condition: &Ast) -> Result<bool> {
+        match condition {
+            Ast::Keyword(name, _) => Ok(self.session.has_feature(name)),
+            Ast::List(elements, _) => match elements.split_first() {
+                Some((Ast::Symbol(op, _), [operand])) if op == "not" => {
+                    Ok(!self.feature_condition(operand)?)
+                }
+                Some((Ast::Symbol(op, _), operands)) if op == "and" => {
+                    for operand in operands {
+                        if !self.feature_condition(operand)? {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                }
+                Some((Ast::Symbol(op, _), operands)) if op == "or" => {
+                    for operand in operands {
+                        if self.feature_condition(operand)? {
+                            return Ok(true);
+                        }
+                    }
+                    Ok(false)
+                }
+                _ => Err(Error::new(ErrorKind::InvalidArgument(
+                    "when-feature condition must be :feature, (not c), (and c...), or (or c...)"
+                        .to_string(),
+                ))),
+            },
+            other => Err(Error::new(ErrorKind::InvalidArgument(format!(
+                "when-feature condition must be a keyword, got {}",
+                other.type_name()
+            )))),
+        }
     }
 
-    /// Loads and evaluates a file.
-    ///
-    /// If the path is a directory containing a `_.lt` file, that file is loaded.
-    /// Uses cycle detection to prevent recursive loading.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the file cannot be read, contains cyclic loads, or evaluation fails.
-    pub fn load_file(&mut self, path: &str) -> Result<()> {
-        // Resolve path relative to current load path
-        let resolved = self.session.resolve_path(path);
+    /// Advances the session world by one tick, recording it to the replay log
+    /// if one is active (see [`Self::run_hooked_tick`]).
+    fn run_tick(&mut self, inputs: &[InputEvent]) -> Result<longtable_engine::TickResult> {
+        let before = self
+            .session
+            .replay_log()
+            .map(|_| self.session.world().clone());
+        let result = self.run_hooked_tick(inputs)?;
+
+        let tick = self.tick_executor.tick_number();
+        if let (Some(before), Some(log)) = (before, self.session.replay_log_mut()) {
+            let after = if result.success {
+                &result.world
+            } else {
+                &before
+            };
+            log.record(tick, inputs, &before, after);
+        }
+        Ok(result)
+    }
 
-        // Try with .lt extension if not specified
-        let has_lt_ext = Path::new(path)
-            .extension()
-            .is_some_and(|ext| ext.eq_ignore_ascii_case("lt"));
+    /// Gives the tick executor the rules the session has declared, bodies
+    /// and all, so it fires exactly those.
+    fn sync_rules(&mut self) {
+        let revision = self.session.rule_revision();
+        if self.synced_rules != Some(revision) {
+            self.tick_executor.set_rules(Vec::new());
+            for rule in self.session.compiled_rules() {
+                self.tick_executor.add_full_rule(rule.clone());
+            }
+            self.synced_rules = Some(revision);
+        }
+    }
 
-        let file_path = if resolved.is_dir() {
-            // If it's a directory, look for _.lt inside
-            let entry_file = resolved.join("_.lt");
-            if entry_file.exists() {
-                entry_file
-            } else {
-                return Err(Error::new(ErrorKind::Internal(format!(
-                    "directory '{path}' does not contain _.lt entry file"
-                ))));
+    /// Advances the session world by one tick, running any phase hooks and
+    /// firing any timers that fall due.
+    ///
+    /// Each hook is called as `(hook {:tick N :phase :name})` with read-only
+    /// access to the world at that phase; effects it produces are handed back
+    /// to the tick executor. Due timers fire after inputs are injected, before
+    /// the after-inputs hooks. Watches are evaluated once the rules have run
+    /// (see [`Self::evaluate_watches`]), and one that changed opens the debug
+    /// prompt before the tick commits.
+    #[allow(clippy::too_many_lines)]
+    fn run_hooked_tick(&mut self, inputs: &[InputEvent]) -> Result<longtable_engine::TickResult> {
+        self.sync_rules();
+        let world = self.session.world().clone();
+        let hooks = self.session.phase_hooks().to_vec();
+        // Timers die with the entities they belong to, paused or not
+        self.tick_executor
+            .scheduler_mut()
+            .cancel_orphaned(|owner| world.exists(owner));
+        let mut timers = self.tick_executor.take_due_timers();
+        let watching = self
+            .session
+            .debug_session()
+            .watches()
+            .iter()
+            .any(longtable_debug::WatchExpression::is_enabled);
+        let debugging = watching || self.session.debug_session().is_active();
+        let tracing = self.session.tracer().is_enabled();
+        if hooks.is_empty() && timers.is_empty() && !debugging && !tracing {
+            return self.tick_executor.tick(world, inputs);
+        }
+        // A tick that doesn't commit leaves the watches as they were
+        let watches = watching.then(|| self.session.debug_session().watches().clone());
+
+        let tick = self.tick_executor.tick_number() + 1;
+        let taken = timers.clone();
+        // Hooks, timers, and forms typed at the debug prompt share these
+        let engine = RefCell::new((&mut self.compiler, &mut self.vm, world.interner().clone()));
+        let mut output = String::new();
+        let editor = &mut self.editor;
+        let captured = &mut self.captured;
+        let (debug, tracer) = self.session.debugger_mut();
+        let mut spans = TickSpans::default();
+
+        let debugger = |point: DebugPoint<'_>, world: &World| {
+            if tracing {
+                spans.enter(tracer, point);
             }
-        } else if resolved.exists() {
-            resolved
-        } else if !has_lt_ext {
-            let with_ext = self.session.resolve_path(&format!("{path}.lt"));
-            if with_ext.exists() {
-                with_ext
+            let mut pause = debugging && debug.on_debug_point(point, world.interner());
+            // Watches see the world the rules leave, so a change pauses the
+            // tick before it commits
+            if watching && matches!(point, DebugPoint::Phase(TickPhase::BeforeConstraints)) {
+                let (compiler, vm, interner) = &mut *engine.borrow_mut();
+                let report = Self::evaluate_watches(compiler, vm, interner, debug, world);
+                Self::emit(captured, &report);
+                pause |= debug.is_paused();
+            }
+            if pause {
+                let mut evaluate = |source: &str| {
+                    let (_, vm, interner) = &mut *engine.borrow_mut();
+                    Self::debug_eval(vm, interner, source, point, world)
+                };
+                Self::debug_prompt(editor, captured, debug, point, world, &mut evaluate)
             } else {
-                return Err(Error::new(ErrorKind::Internal(format!(
-                    "file not found: {path}"
-                ))));
+                Ok(())
             }
-        } else {
-            return Err(Error::new(ErrorKind::Internal(format!(
-                "file not found: {path}"
-            ))));
         };
+        let result = self.tick_executor.tick_with_debugger(
+            world,
+            inputs,
+            |phase, world| {
+                let (compiler, vm, interner) = &mut *engine.borrow_mut();
+                let mut effects = Vec::new();
+                if phase == TickPhase::AfterInputs {
+                    for timer in timers.drain(..) {
+                        let program = compiler.compile_call(timer.action)?;
+                        vm.execute_with_context(&program, &WorldContext::new(world))?;
+                        effects.extend(vm.take_effects());
+                        output.extend(vm.output().iter().map(String::as_str));
+                        vm.clear_output();
+                    }
+                }
+                for (_, hook) in hooks.iter().filter(|(p, _)| *p == phase) {
+                    let call = Self::phase_hook_call(hook, tick, phase);
+
+                    compiler.prepare_for_compilation();
+                    compiler.set_interner(interner.clone());
+                    let program = compiler.compile(std::slice::from_ref(&call));
+                    if let Some(updated) = compiler.take_interner() {
+                        *interner = updated;
+                    }
+                    for (name, &slot) in compiler.globals() {
+                        vm.register_global(name.clone(), slot);
+                    }
 
-        // Canonicalize for consistent cycle detection
-        let canonical = file_path
-            .canonicalize()
-            .unwrap_or_else(|_| file_path.clone());
-
-        // Check if already loaded (skip re-loading)
-        if self.session.module_registry().has_file(&canonical) {
-            return Ok(());
+                    vm.execute_with_context(&program?, &WorldContext::new(world))?;
+                    effects.extend(vm.take_effects());
+                    output.extend(vm.output().iter().map(String::as_str));
+                    vm.clear_output();
+                }
+                Ok(effects)
+            },
+            debugger,
+        );
+
+        // Keep keywords interned while compiling hooks
+        let (_, _, interner) = engine.into_inner();
+        self.session.world_mut().set_interner(interner.clone());
+        // An abandoned tick prints nothing
+        if result.is_ok() {
+            self.write_output(&output);
         }
-
-        // Begin loading (cycle detection)
-        self.session
-            .module_registry_mut()
-            .begin_loading(canonical.clone())?;
-
-        // Read file
-        let source = fs::read_to_string(&file_path).map_err(|e| {
-            // Clean up loading state on error
-            self.session
-                .module_registry_mut()
-                .finish_loading(&canonical);
-            Error::new(ErrorKind::Internal(format!(
-                "failed to read {}: {e}",
-                file_path.display()
-            )))
-        })?;
-
-        // Save and update load path
-        let old_path = self.session.load_path().clone();
-        if let Some(parent) = file_path.parent() {
-            self.session.set_load_path(parent.to_path_buf());
+        if tracing {
+            let tracer = self.session.tracer_mut();
+            if let Err(error) = &result {
+                spans.fail(tracer, error);
+            }
+            let success = result.as_ref().is_ok_and(|r| r.success);
+            spans.finish(tracer, tick, success);
         }
 
-        // Evaluate with file context
-        let result = self.eval_with_file_context(&source, &canonical);
+        // An abandoned or rolled-back tick fired none of its timers
+        if !result.as_ref().is_ok_and(|r| r.success) {
+            self.tick_executor.scheduler_mut().requeue(taken);
+            if let Some(watches) = watches {
+                *self.session.debug_session_mut().watches_mut() = watches;
+            }
+        }
+        let mut result = result?;
+        result.world.set_interner(interner);
+        Ok(result)
+    }
 
-        // Restore load path
-        self.session.set_load_path(old_path);
+    /// Holds a tick paused at `point` until the debug session is resumed.
+    ///
+    /// Shows where the tick stopped, with the bindings of the rule about to
+    /// fire, then reads debugger commands: `(continue)` and the `(step-…)`
+    /// forms resume the tick, `(abort)` abandons it, and `(where)` and
+    /// `(debug)` describe the pause. Any other form is evaluated against the
+    /// paused world (see [`Self::debug_eval`]), except session commands such
+    /// as `(tick!)` or `(save!)`, which wait until the tick has finished. End
+    /// of input continues.
+    fn debug_prompt(
+        editor: &mut E,
+        captured: &mut Option<Vec<RichSpan>>,
+        debug: &mut DebugSession,
+        point: DebugPoint<'_>,
+        world: &World,
+        evaluate: &mut dyn FnMut(&str) -> Result<String>,
+    ) -> Result<()> {
+        let mut say = |text: &str| Self::emit(captured, text);
+
+        let interner = world.interner();
+        let mut location = match debug.pause_reason() {
+            Some(reason) => format!("Paused ({reason}) at tick {}", debug.current_tick()),
+            None => format!("Paused at tick {}", debug.current_tick()),
+        };
+        match point {
+            DebugPoint::TickStart(_) => location.push_str(", before it starts\n"),
+            DebugPoint::Phase(phase) => {
+                let _ = writeln!(location, ", phase {}", phase.name());
+            }
+            DebugPoint::Rule(activation) => {
+                let rule = interner.get_keyword(activation.rule_name).unwrap_or("?");
+                let _ = writeln!(location, ", rule {rule}");
+                let mut bindings: Vec<_> = activation.bindings.iter().collect();
+                bindings.sort_by(|a, b| a.0.cmp(b.0));
+                for (var, value) in bindings {
+                    let _ = writeln!(
+                        location,
+                        "  ?{var} = {}",
+                        format_value_with(value, interner)
+                    );
+                }
+            }
+        }
+        say(&location);
 
-        // Finish loading (remove from loading stack)
-        self.session
-            .module_registry_mut()
-            .finish_loading(&canonical);
+        loop {
+            let line = match editor.read_line("debug> ")? {
+                ReadResult::Line(line) => line,
+                ReadResult::Interrupted | ReadResult::Eof => {
+                    debug.resume();
+                    return Ok(());
+                }
+            };
+            match line.trim() {
+                "" => {}
+                "(continue)" => {
+                    debug.resume();
+                    return Ok(());
+                }
+                "(step-rule)" => {
+                    debug.step_rule();
+                    return Ok(());
+                }
+                "(step-phase)" => {
+                    debug.step_phase();
+                    return Ok(());
+                }
+                "(step-tick)" => {
+                    debug.step_tick();
+                    return Ok(());
+                }
+                "(abort)" => {
+                    debug.resume();
+                    return Err(Error::new(ErrorKind::Refused(format!(
+                        "tick {} abandoned from the debugger",
+                        debug.current_tick()
+                    ))));
+                }
+                "(where)" => say(&location),
+                "(debug)" => say(&format!("{}\n", debug.status_summary())),
+                form if form.starts_with('(') => match evaluate(form) {
+                    Ok(text) => say(&text),
+                    Err(e) => say(&format!("Error: {e}\n")),
+                },
+                _ => say(
+                    "The tick is paused: (continue), (step-rule), (step-phase), (step-tick), \
+                     (where), (debug), (abort), or a form to evaluate (not a session \
+                     command like (tick!))\n",
+                ),
+            }
+        }
+    }
 
-        result.map(|_| ())
+    /// Writes `text` to the captured output, or straight to stdout, for code
+    /// running inside a tick that can't reach [`Self::write_output`].
+    fn emit(captured: &mut Option<Vec<RichSpan>>, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if let Some(captured) = captured {
+            captured.push(RichSpan::plain(text));
+        } else {
+            print!("{text}");
+            let _ = io::stdout().flush();
+        }
     }
 
-    /// Evaluates source code within a file context.
+    /// Evaluates a form typed at the debug prompt against the paused world,
+    /// with the bindings of the rule about to fire (`?e`) in scope.
     ///
-    /// Handles namespace declarations and registers the file in the module registry.
-    fn eval_with_file_context(
-        &mut self,
+    /// The world is read-only here: effects the form produces are dropped.
+    /// Returns what it printed followed by its value.
+    fn debug_eval(
+        vm: &mut Vm,
+        interner: &Interner,
         source: &str,
-        file_path: &std::path::Path,
-    ) -> Result<Value> {
-        // Parse
-        let forms = parse(source)?;
+        point: DebugPoint<'_>,
+        world: &World,
+    ) -> Result<String> {
+        let [form] = parse(source)?.try_into().map_err(|_| {
+            Error::new(ErrorKind::InvalidArgument(
+                "the debug prompt evaluates one form at a time".to_string(),
+            ))
+        })?;
+        if let Ast::List(items, _) = &form
+            && let Some(Ast::Symbol(name, _)) = items.first()
+            && SESSION_COMMANDS.contains(&name.as_str())
+        {
+            return Err(Error::new(ErrorKind::Refused(format!(
+                "({name}) can't run while a tick is paused; (continue) or (abort) it first"
+            ))));
+        }
+        let (names, values): (Vec<String>, Vec<Value>) = match point {
+            DebugPoint::Rule(activation) => activation
+                .bindings
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .unzip(),
+            _ => (Vec::new(), Vec::new()),
+        };
+        let expr = compile_expression_with_interner(&form, &names, interner.clone())?;
+        let program = CompiledProgram {
+            code: expr.code,
+            constants: expr.constants,
+            functions: Vec::new(),
+        };
 
-        // Check for namespace declaration at the beginning
-        if let Some(first_form) = forms.first() {
-            if let Some(Declaration::Namespace(ns_decl)) = DeclarationAnalyzer::analyze(first_form)?
-            {
-                // Build namespace context from declaration
-                let ns_context = NamespaceContext::from_decl(&ns_decl);
+        vm.set_bindings(values);
+        let value = vm.execute_with_context(&program, &WorldContext::new(world));
+        vm.clear_effects();
+        let mut text = vm.output().concat();
+        vm.clear_output();
+        let _ = writeln!(text, "{}", format_value_with(&value?, interner));
+        Ok(text)
+    }
 
-                // Register the namespace
-                let ns_info = NamespaceInfo::new(ns_decl, file_path.to_path_buf());
-                self.session
-                    .module_registry_mut()
-                    .register_namespace(ns_info);
+    /// Builds the `(hook {:tick N :phase :name})` call for a phase hook.
+    #[allow(clippy::cast_possible_wrap)]
+    fn phase_hook_call(hook: &Ast, tick: u64, phase: TickPhase) -> Ast {
+        let span = Span::default();
+        let info = Ast::Map(
+            vec![
+                (
+                    Ast::Keyword("tick".to_string(), span),
+                    Ast::Int(tick as i64, span),
+                ),
+                (
+                    Ast::Keyword("phase".to_string(), span),
+                    Ast::Keyword(phase.name().to_string(), span),
+                ),
+            ],
+            span,
+        );
+        Ast::List(vec![hook.clone(), info], span)
+    }
 
-                // Set as current namespace context for compilation
-                self.session.set_namespace_context(ns_context);
-            }
+    /// Dispatches natural language input to the appropriate action.
+    ///
+    /// Records the input, its timing, and how it was parsed in the session transcript.
+    ///
+    /// Input holding several commands ("take lamp, then go north") runs them
+    /// in order, each parsed after the one before has run, and stops at the
+    /// first that fails or asks which entity was meant. The commands after
+    /// one that asks wait for the answer, and run once it's given. Each
+    /// command is a transcript entry of its own.
+    fn dispatch_input(&mut self, input: &str) -> Result<Option<Value>> {
+        // One parser for every command, so "it" can refer to an earlier one
+        let mut parser = self.input_parser();
+        let mut commands: VecDeque<String> = parser
+            .split_commands(input, self.session.world().interner())
+            .into();
+        if commands.len() < 2 {
+            commands = VecDeque::from([input.to_string()]);
         }
 
-        // Evaluate each form
-        let mut result = Value::Nil;
-        for form in forms {
-            result = self.eval_form(&form)?;
-        }
+        let mut result = Ok(Some(Value::Nil));
+        while let Some(command) = commands.pop_front() {
+            let command = command.as_str();
+            let timer = Instant::now();
+            let mut entry = TranscriptEntry::new(
+                command,
+                clock::system_now(),
+                self.session.world().tick(),
+                InputOutcome::Error,
+            );
+
+            result = self.dispatch_input_recorded(command, &mut parser, &mut entry, &mut commands);
+            if let Err(e) = &result {
+                entry.error.get_or_insert_with(|| e.to_string());
+            }
 
-        Ok(result)
+            let entry = entry.with_duration(timer.elapsed());
+            let succeeded = result.is_ok() && entry.outcome == InputOutcome::Success;
+            if entry.outcome == InputOutcome::Ambiguous {
+                self.session.queue_commands(commands.drain(..).collect());
+            }
+            self.session.transcript_mut().record(entry);
+            if !succeeded {
+                break;
+            }
+        }
+        self.session
+            .set_pronoun_state(parser.pronoun_state().clone());
+        result
     }
 
-    /// Evaluates a file without changing the load path (for CLI batch mode).
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the file cannot be read or evaluated.
-    pub fn eval_file(&mut self, path: &Path) -> Result<Value> {
-        let source = fs::read_to_string(path).map_err(|e| {
-            Error::new(ErrorKind::Internal(format!(
-                "failed to read {}: {e}",
-                path.display()
-            )))
-        })?;
+    /// Builds a parser for player input from the session's vocabulary,
+    /// command syntaxes, and scopes.
+    fn input_parser(&self) -> NaturalLanguageParser {
+        let vocab = self.session.vocabulary_registry().clone();
+        let mut parser =
+            NaturalLanguageParser::new(vocab).with_tracing(self.session.tracer().is_enabled());
+        *parser.pronoun_state_mut() = self.session.pronoun_state().clone();
+
+        // Add all compiled syntaxes
+        for syntax in self.session.compiled_syntaxes() {
+            parser.add_syntax(syntax.clone());
+        }
 
-        // Set load path to file's directory
-        if let Some(parent) = path.parent() {
-            self.session.set_load_path(parent.to_path_buf());
+        // Configure noun resolver with appropriate keywords
+        let name_kw = self.session.world().interner().lookup_keyword("name");
+        let value_kw = self.session.world().interner().lookup_keyword("value");
+        let aliases_kw = self.session.world().interner().lookup_keyword("aliases");
+        let adjectives_kw = ["entity/adjectives", "adjectives"]
+            .into_iter()
+            .find_map(|name| self.session.world().interner().lookup_keyword(name));
+
+        if let (Some(name_kw), Some(value_kw)) = (name_kw, value_kw) {
+            let resolver = NounResolver::new(
+                name_kw,
+                value_kw,
+                aliases_kw.unwrap_or(name_kw),    // fallback
+                adjectives_kw.unwrap_or(name_kw), // fallback
+            )
+            .with_all_exclusions(
+                ["tag/room", "tag/scenery"]
+                    .into_iter()
+                    .filter_map(|tag| self.session.world().interner().lookup_keyword(tag))
+                    .collect(),
+            );
+            parser = parser.with_noun_resolver(resolver);
+        }
+        let topics_kw = self.session.world().interner().lookup_keyword("npc/topics");
+        if let (Some(topics_kw), Some(value_kw)) = (topics_kw, value_kw) {
+            parser = parser.with_topic_resolver(TopicResolver::new(topics_kw, value_kw));
         }
 
-        self.eval(&source)
+        // Declared scopes limit what nouns can refer to; without any, every
+        // entity is in scope
+        if !self.session.scopes().is_empty() {
+            parser = parser.with_scope_evaluator(self.session.scope_evaluator().clone());
+            for scope in self.session.scopes() {
+                parser.add_scope(scope.clone());
+            }
+        }
+        parser
     }
 
-    /// Formats a value for display, resolving keywords via the world's interner.
-    fn format_value(&self, value: &Value) -> String {
-        let formatted = self.format_value_inner(value);
-        format!("\x1b[1m{formatted}\x1b[0m")
-    }
+    /// Records the parser's decisions in the session's tracer, at the tick
+    /// the input is about to run in.
+    fn trace_parse_steps(&mut self, steps: Vec<ParseStep>) {
+        use longtable_debug::TraceEvent;
+        use longtable_parser::noun_phrase::NounResolution;
 
-    /// Recursively formats a value, resolving keywords to their string names.
-    fn format_value_inner(&self, value: &Value) -> String {
-        match value {
-            Value::Nil => "nil".to_string(),
-            Value::Bool(b) => b.to_string(),
-            Value::Int(n) => n.to_string(),
-            Value::Float(n) => {
-                if n.fract() == 0.0 {
-                    format!("{n}.0")
-                } else {
-                    n.to_string()
+        let tick = self.session.world().tick();
+        let tracer = self.session.tracer_mut();
+        if !tracer.is_enabled() {
+            return;
+        }
+        tracer.set_tick(tick);
+        for step in steps {
+            tracer.record(match step {
+                ParseStep::Tokenized { input, tokens } => TraceEvent::ParseTokens { input, tokens },
+                ParseStep::Corrected { word, correction } => {
+                    TraceEvent::ParseCorrection { word, correction }
+                }
+                ParseStep::SyntaxCandidates { evaluated, matched } => {
+                    TraceEvent::ParseCandidates { evaluated, matched }
+                }
+                ParseStep::NounResolved {
+                    variable,
+                    noun,
+                    resolution,
+                } => {
+                    let (outcome, entities) = match resolution {
+                        NounResolution::Unique(e) => ("unique", vec![e]),
+                        NounResolution::Ambiguous(es) => ("ambiguous", es),
+                        NounResolution::Multiple(es) => ("multiple", es),
+                        NounResolution::NotFound => ("not-found", Vec::new()),
+                        NounResolution::WrongType { found, .. } => ("wrong-type", vec![found]),
+                    };
+                    TraceEvent::NounResolution {
+                        variable,
+                        noun,
+                        outcome: outcome.to_string(),
+                        entities,
+                    }
+                }
+                ParseStep::PronounResolved {
+                    variable,
+                    pronoun,
+                    referent,
+                } => TraceEvent::NounResolution {
+                    variable,
+                    noun: pronoun,
+                    outcome: if referent.is_some() {
+                        "pronoun"
+                    } else {
+                        "no-referent"
+                    }
+                    .to_string(),
+                    entities: referent.into_iter().collect(),
+                },
+                ParseStep::DisambiguationAsked { question, options } => {
+                    TraceEvent::DisambiguationAsked { question, options }
+                }
+            });
+        }
+    }
+
+    /// Parses and executes natural language input, filling in `entry` as it goes.
+    #[allow(clippy::too_many_lines)]
+    fn dispatch_input_recorded(
+        &mut self,
+        input: &str,
+        parser: &mut NaturalLanguageParser,
+        entry: &mut TranscriptEntry,
+        rest: &mut VecDeque<String>,
+    ) -> Result<Option<Value>> {
+        // Get the player entity as the actor
+        let Some(actor) = self.session.get_entity("player") else {
+            self.write_output("No player entity found.\n");
+            entry.error = Some("no player entity".to_string());
+            return Ok(Some(Value::Nil));
+        };
+
+        // Try to parse with NaturalLanguageParser
+        let parse_result = {
+            // An answer to "Which do you mean?" finishes the pending command,
+            // and the commands typed after it follow; anything else is parsed
+            // as a new one
+            let queued = self.session.take_queued_commands();
+            let answer = self
+                .session
+                .take_pending_parse()
+                .map(|pending| parser.disambiguate(input, pending, self.session.world()))
+                .filter(|result| !matches!(result, ParseResult::Error(_)));
+            if answer.is_some() {
+                for command in queued.into_iter().rev() {
+                    rest.push_front(command);
+                }
+            }
+            let result = answer.unwrap_or_else(|| parser.parse(input, actor, self.session.world()));
+            for correction in parser.take_corrections() {
+                self.write_output(&format!("(I assume you mean: {correction})\n"));
+            }
+            self.trace_parse_steps(parser.take_steps());
+            result
+        };
+
+        match parse_result {
+            ParseResult::Success(cmd) => {
+                self.record_match(entry, InputOutcome::Success, cmd.verb, Some(cmd.action));
+                self.execute_parsed_command(actor, cmd)
+            }
+            ParseResult::Multiple(cmds) => {
+                if let Some(first) = cmds.first() {
+                    self.record_match(entry, InputOutcome::Success, first.verb, Some(first.action));
+                }
+
+                // Execute each command in sequence
+                for cmd in cmds {
+                    self.execute_parsed_command(actor, cmd)?;
+                }
+                Ok(Some(Value::Nil))
+            }
+            ParseResult::Ambiguous(disamb) => {
+                let syntax_match = &disamb.pending_parse.syntax_match;
+                self.record_match(
+                    entry,
+                    InputOutcome::Ambiguous,
+                    syntax_match.command,
+                    Some(syntax_match.action),
+                );
+
+                self.write_output(&format!("{}\n", disamb.question));
+                for (i, (desc, _)) in disamb.options.iter().enumerate() {
+                    self.write_output(&format!("  {}. {}\n", i + 1, desc));
+                }
+                self.session.set_pending_parse(disamb.pending_parse);
+                Ok(Some(Value::Nil))
+            }
+            ParseResult::Error(err) => {
+                // Fall back to simple verb lookup for backwards compatibility
+                if let Ok(value) = self.dispatch_input_simple(input, actor) {
+                    entry.outcome = InputOutcome::Success;
+                    if let Some(verb) = input.split_whitespace().next() {
+                        entry.action = Some(verb.to_lowercase());
+                        self.session
+                            .telemetry_mut()
+                            .record(TelemetryEvent::Command {
+                                category: verb.to_lowercase(),
+                            });
+                    }
+                    return Ok(value);
                 }
+
+                self.session
+                    .telemetry_mut()
+                    .record(TelemetryEvent::ParseFailure {
+                        class: ParseFailureClass::from(&err),
+                    });
+
+                let message = match err {
+                    ParseError::EmptyInput => "What?".to_string(),
+                    ParseError::NoMatch => {
+                        let verb = input.split_whitespace().next().unwrap_or(input);
+                        format!("I don't understand '{verb}'.")
+                    }
+                    ParseError::UnknownWord(word) => {
+                        format!("I don't know the word '{word}'.")
+                    }
+                    ParseError::NotFound(noun) => format!("I don't see any '{noun}' here."),
+                    ParseError::WrongType { noun, expected } => {
+                        format!("You can't do that with the {noun} ({expected} expected).")
+                    }
+                    ParseError::NoReferent(pronoun) => {
+                        format!("I don't know what '{pronoun}' refers to.")
+                    }
+                    ParseError::NothingToRepeat => "There's nothing to repeat.".to_string(),
+                    ParseError::NothingToCorrect => "There's nothing to correct.".to_string(),
+                    ParseError::UnknownTopic(topic) => {
+                        format!("There's nothing to say about '{topic}'.")
+                    }
+                    ParseError::ScopeFailed(message) => format!("Scope error: {message}"),
+                };
+                self.write_output(&format!("{message}\n"));
+                entry.error = Some(message);
+                Ok(Some(Value::Nil))
             }
-            Value::String(s) => format!("\"{s}\""),
-            Value::Symbol(id) => self
+        }
+    }
+
+    /// Records which syntax and action a parse matched, in the transcript and telemetry.
+    fn record_match(
+        &mut self,
+        entry: &mut TranscriptEntry,
+        outcome: InputOutcome,
+        syntax: KeywordId,
+        action: Option<KeywordId>,
+    ) {
+        let interner = self.session.world().interner();
+        entry.outcome = outcome;
+        entry.syntax = interner.get_keyword(syntax).map(str::to_string);
+        entry.action = action
+            .and_then(|a| interner.get_keyword(a))
+            .map(str::to_string);
+        if let Some(command) = &entry.syntax {
+            self.session.coverage_mut().record_syntax(command);
+        }
+
+        let event = match (outcome, &entry.action) {
+            (InputOutcome::Ambiguous, _) => TelemetryEvent::ParseFailure {
+                class: ParseFailureClass::Ambiguous,
+            },
+            (_, Some(action)) => TelemetryEvent::Command {
+                category: action.clone(),
+            },
+            (_, None) => return,
+        };
+        self.session.telemetry_mut().record(event);
+    }
+
+    /// Executes a parsed command.
+    fn execute_parsed_command(
+        &mut self,
+        actor: EntityId,
+        cmd: CommandEntity,
+    ) -> Result<Option<Value>> {
+        let action = cmd.action;
+
+        // Get the full action declaration
+        let Some(action_decl) = self.session.get_action_decl(action).cloned() else {
+            let action_name = self
                 .session
                 .world()
                 .interner()
-                .get_symbol(*id)
-                .map_or_else(|| format!("Symbol({})", id.index()), str::to_string),
-            Value::Keyword(id) => self
+                .get_keyword(action)
+                .unwrap_or("unknown");
+            self.write_output(&format!("Action '{action_name}' has no declaration.\n"));
+            return Ok(Some(Value::Nil));
+        };
+
+        // Build bindings
+        let mut bindings = Bindings::new();
+
+        // Bind actor
+        if action_decl.params.contains(&"actor".to_string()) {
+            bindings.set("actor".to_string(), Value::EntityRef(actor));
+        }
+
+        // Bind direction if present
+        // Note: We use "direction" as the binding name since that's what the `go` action expects
+        // TODO: Properly use the :bindings mapping from command declarations
+        if let Some((_var_name, dir_kw)) = cmd.direction {
+            bindings.set("direction".to_string(), Value::Keyword(dir_kw));
+        }
+
+        // Bind all noun bindings (e.g., target, item, container, etc.)
+        for (var_name, entity_id) in cmd.noun_bindings {
+            bindings.set(var_name, Value::EntityRef(entity_id));
+        }
+
+        // Bind captured text (e.g., the message of `say`)
+        for (var_name, text) in cmd.text_bindings {
+            bindings.set(var_name, Value::from(text));
+        }
+
+        // Bind the conversation topic (e.g., :treasure in "ask guard about treasure")
+        if let Some((var_name, topic)) = cmd.topic {
+            bindings.set(var_name, Value::Keyword(topic));
+        }
+
+        // Evaluate preconditions
+        if !action_decl.preconditions.is_empty() {
+            match self.evaluate_preconditions(&action_decl, &bindings) {
+                Ok(Some(new_bindings)) => bindings = new_bindings,
+                Ok(None) => return Ok(Some(Value::Nil)),
+                Err(e) => return Err(e),
+            }
+        }
+
+        self.run_action_handlers(action, &action_decl, &bindings)?;
+
+        Ok(Some(Value::Nil))
+    }
+
+    /// Simple input dispatch - fallback for when parser fails.
+    fn dispatch_input_simple(&mut self, input: &str, actor: EntityId) -> Result<Option<Value>> {
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+
+        if tokens.is_empty() {
+            return Err(Error::new(ErrorKind::Internal("Empty input".to_string())));
+        }
+
+        let verb = tokens[0].to_lowercase();
+        let action_name_kw = self
+            .session
+            .world_mut()
+            .interner_mut()
+            .intern_keyword(&verb);
+
+        // Check if we have an action registered with this name
+        if self.session.action_registry().get(action_name_kw).is_none() {
+            return Err(Error::new(ErrorKind::Internal(format!(
+                "Unknown action: {verb}"
+            ))));
+        }
+
+        let Some(action_decl) = self.session.get_action_decl(action_name_kw).cloned() else {
+            return Err(Error::new(ErrorKind::Internal(format!(
+                "Action '{verb}' has no declaration."
+            ))));
+        };
+
+        let mut bindings = Bindings::new();
+
+        if action_decl.params.contains(&"actor".to_string()) {
+            bindings.set("actor".to_string(), Value::EntityRef(actor));
+        }
+
+        // Bind direction if present
+        if action_decl.params.contains(&"direction".to_string()) && tokens.len() > 1 {
+            let direction_str = tokens[1].to_lowercase();
+            let direction_kw = self
                 .session
-                .world()
-                .interner()
-                .get_keyword(*id)
-                .map_or_else(|| format!("Keyword({})", id.index()), |s| format!(":{s}")),
-            Value::EntityRef(id) => format!("Entity({}, {})", id.index, id.generation),
-            Value::Vec(v) => {
-                let items: Vec<_> = v.iter().map(|v| self.format_value_inner(v)).collect();
-                format!("[{}]", items.join(" "))
-            }
-            Value::List(l) => {
-                let items: Vec<_> = l.iter().map(|v| self.format_value_inner(v)).collect();
-                format!("({})", items.join(" "))
-            }
-            Value::Set(s) => {
-                let items: Vec<_> = s.iter().map(|v| self.format_value_inner(v)).collect();
-                format!("#{{{}}}", items.join(" "))
-            }
-            Value::Map(m) => {
-                let pairs: Vec<_> = m
-                    .iter()
-                    .map(|(k, v)| {
-                        format!(
-                            "{} {}",
-                            self.format_value_inner(k),
-                            self.format_value_inner(v)
-                        )
-                    })
-                    .collect();
-                format!("{{{}}}", pairs.join(" "))
+                .world_mut()
+                .interner_mut()
+                .intern_keyword(&direction_str);
+            bindings.set("direction".to_string(), Value::Keyword(direction_kw));
+        }
+
+        if !action_decl.preconditions.is_empty() {
+            match self.evaluate_preconditions(&action_decl, &bindings) {
+                Ok(Some(new_bindings)) => bindings = new_bindings,
+                Ok(None) => return Ok(Some(Value::Nil)),
+                Err(e) => return Err(e),
             }
-            Value::Fn(_) => "<fn>".to_string(),
         }
+
+        self.run_action_handlers(action_name_kw, &action_decl, &bindings)?;
+
+        Ok(Some(Value::Nil))
     }
 
-    /// Prints an error to stderr.
-    #[allow(clippy::unused_self)]
-    fn print_error(&self, error: &Error) {
-        eprintln!("\x1b[31mError: {error}\x1b[0m");
+    /// Evaluates action preconditions and returns bindings if they all pass.
+    fn evaluate_preconditions(
+        &mut self,
+        action_decl: &longtable_language::ActionDecl,
+        initial_bindings: &Bindings,
+    ) -> Result<Option<Bindings>> {
+        // Evaluate all preconditions and accumulate bindings
+        let mut result = initial_bindings.clone();
+
+        for precondition in &action_decl.preconditions {
+            // Compile the pattern
+            let compiled = PatternCompiler::compile(
+                &precondition.pattern,
+                self.session.world_mut().interner_mut(),
+            )?;
+
+            // Try to match against the world
+            let all_matches = PatternMatcher::match_pattern(&compiled, self.session.world())?;
+
+            // Filter matches to those compatible with our initial bindings
+            let compatible_matches: Vec<_> = all_matches
+                .into_iter()
+                .filter(|m| {
+                    // Check that all variables in initial_bindings match this result
+                    for (var, expected_val) in result.iter() {
+                        if let Some(actual_val) = m.get(var) {
+                            if actual_val != expected_val {
+                                return false;
+                            }
+                        }
+                    }
+                    true
+                })
+                .collect();
+
+            if compatible_matches.is_empty() {
+                // No match - precondition failed
+                // TODO: Print the failure message from precondition.message
+                self.write_output("You can't do that.\n");
+                return Ok(None);
+            }
+
+            // Use the first compatible match and merge with accumulated bindings
+            for (var, value) in compatible_matches[0].iter() {
+                result.set(var.clone(), value.clone());
+            }
+        }
+
+        Ok(Some(result))
     }
 
-    /// Prints the welcome banner.
-    #[allow(clippy::unused_self)]
-    fn print_banner(&self) {
-        println!("\x1b[1;36m");
-        println!("  _                        _        _     _      ");
-        println!(" | |    ___  _ __   __ _ _| |_ __ _| |__ | | ___ ");
-        println!(" | |   / _ \\| '_ \\ / _` |_   _/ _` | '_ \\| |/ _ \\");
-        println!(" | |__| (_) | | | | (_| | | || (_| | |_) | |  __/");
-        println!(" |_____\\___/|_| |_|\\__, | |_| \\__,_|_.__/|_|\\___|");
-        println!("                   |___/                         ");
-        println!("\x1b[0m");
-        println!("Welcome to Longtable REPL v{}", env!("CARGO_PKG_VERSION"));
-        println!("Type expressions to evaluate. Use Ctrl+D to exit.\n");
+    /// Runs an action's handlers between its before and after hooks,
+    /// attributing their spawns and links to it.
+    fn run_action_handlers(
+        &mut self,
+        action: KeywordId,
+        action_decl: &longtable_language::ActionDecl,
+        bindings: &Bindings,
+    ) -> Result<()> {
+        let context: Vec<(String, EntityId)> = bindings
+            .iter()
+            .filter_map(|(var, value)| match value {
+                Value::EntityRef(entity) => Some((var.clone(), *entity)),
+                _ => None,
+            })
+            .collect();
+        let entities: Vec<EntityId> = context.iter().map(|(_, entity)| *entity).collect();
+        self.effect_origin = Some((action, context));
+        let result = self
+            .run_action_hooks(HookTiming::Before, action, &entities, bindings)
+            .and_then(|proceed| {
+                if !proceed {
+                    return Ok(());
+                }
+                action_decl.handler.iter().try_for_each(|handler| {
+                    self.execute_action_handler(handler, bindings).map(drop)
+                })?;
+                self.run_action_hooks(HookTiming::After, action, &entities, bindings)
+                    .map(drop)
+            });
+        self.effect_origin = None;
+        result
+    }
+
+    /// Runs the hooks for a use of `action` involving `entities`, most
+    /// specific first. Returns false if a before hook stopped the action.
+    fn run_action_hooks(
+        &mut self,
+        timing: HookTiming,
+        action: KeywordId,
+        entities: &[EntityId],
+        bindings: &Bindings,
+    ) -> Result<bool> {
+        let world = self.session.world();
+        let mut hooks: Vec<ActionHook> = self
+            .session
+            .action_hooks()
+            .iter()
+            .filter(|hook| {
+                hook.timing == timing && hook.action == action && hook.applies_to(entities, world)
+            })
+            .cloned()
+            .collect();
+        hooks.sort_by_key(ActionHook::specificity);
+
+        let stop = self
+            .session
+            .world_mut()
+            .interner_mut()
+            .intern_keyword("stop");
+        for hook in hooks {
+            let mut last = Value::Nil;
+            for form in &hook.body {
+                last = self.execute_action_handler(form, bindings)?;
+            }
+            if timing == HookTiming::Before && last == Value::Keyword(stop) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Executes a single action handler expression with variable bindings.
+    fn execute_action_handler(&mut self, handler: &Ast, bindings: &Bindings) -> Result<Value> {
+        // Convert Vector to List for evaluation (handlers may be deserialized as vectors)
+        let handler = match handler {
+            Ast::Vector(elements, span) => Ast::List(elements.clone(), *span),
+            other => other.clone(),
+        };
+
+        // (say ...) compiles like any other form, so its narration is
+        // styled and captured with the rest of the output
+        self.eval_with_bindings(&handler, bindings)
+    }
+
+    /// Evaluates an AST with variable bindings.
+    fn eval_with_bindings(&mut self, ast: &Ast, bindings: &Bindings) -> Result<Value> {
+        // If it's a variable reference (symbol starting with ?), look it up
+        if let Ast::Symbol(name, _) = ast {
+            if let Some(stripped) = name.strip_prefix('?') {
+                if let Some(value) = bindings.get(stripped) {
+                    return Ok(value.clone());
+                }
+                // Variable not bound - return as error or nil
+                return Err(Error::new(ErrorKind::Internal(format!(
+                    "unbound variable: {name}"
+                ))));
+            }
+        }
+
+        // For function calls, substitute variables in arguments
+        // Handle both List and Vector (vectors from deserialization should be treated as lists)
+        let (Ast::List(elements, span) | Ast::Vector(elements, span)) = ast else {
+            return self.eval_form(ast);
+        };
+
+        if !elements.is_empty() {
+            // Substitute variables in each element
+            let substituted: Vec<Ast> = elements
+                .iter()
+                .map(|elem| self.substitute_variables(elem, bindings))
+                .collect();
+
+            // Evaluate the substituted form as a list (function call)
+            let new_ast = Ast::List(substituted, *span);
+            return self.eval_form(&new_ast);
+        }
+
+        // Fall back to normal evaluation
+        self.eval_form(ast)
+    }
+
+    /// Substitutes bound variables in an AST.
+    fn substitute_variables(&self, ast: &Ast, bindings: &Bindings) -> Ast {
+        match ast {
+            Ast::Symbol(name, span) => {
+                if let Some(stripped) = name.strip_prefix('?') {
+                    if let Some(value) = bindings.get(stripped) {
+                        // Convert Value back to AST
+                        return self.value_to_ast(value, *span);
+                    }
+                }
+                ast.clone()
+            }
+            Ast::List(elements, span) => {
+                let substituted: Vec<Ast> = elements
+                    .iter()
+                    .map(|elem| self.substitute_variables(elem, bindings))
+                    .collect();
+                Ast::List(substituted, *span)
+            }
+            // Vectors - substitute but keep as vectors (needed for let bindings, etc.)
+            Ast::Vector(elements, span) => {
+                let substituted: Vec<Ast> = elements
+                    .iter()
+                    .map(|elem| self.substitute_variables(elem, bindings))
+                    .collect();
+                Ast::Vector(substituted, *span)
+            }
+            // Other AST types pass through unchanged
+            _ => ast.clone(),
+        }
+    }
+
+    /// Converts a Value back to an AST for substitution.
+    #[allow(clippy::cast_possible_wrap)]
+    fn value_to_ast(&self, value: &Value, span: longtable_language::Span) -> Ast {
+        match value {
+            Value::Bool(b) => Ast::Bool(*b, span),
+            Value::Int(n) => Ast::Int(*n, span),
+            Value::Float(f) => Ast::Float(*f, span),
+            Value::String(s) => Ast::String(s.to_string(), span),
+            Value::Keyword(kw) => {
+                // Look up the keyword name
+                let name = self
+                    .session
+                    .world()
+                    .interner()
+                    .get_keyword(*kw)
+                    .unwrap_or("unknown");
+                Ast::Keyword(name.to_string(), span)
+            }
+            Value::EntityRef(id) => {
+                // Represent as a special form the evaluator can understand
+                Ast::List(
+                    vec![
+                        Ast::Symbol("entity-ref".to_string(), span),
+                        Ast::Int(id.index as i64, span),
+                        Ast::Int(i64::from(id.generation), span),
+                    ],
+                    span,
+                )
+            }
+            // Nil and collections fall through to Nil
+            _ => Ast::Nil(span),
+        }
+    }
+
+    /// Handles the (timeline) form.
+    ///
+    /// Shows timeline status.
+    #[allow(clippy::unnecessary_wraps)]
+    fn handle_timeline(&mut self) -> Result<Option<Value>> {
+        let status = self.session.timeline().status();
+        self.write_output(&format!("{status}\n"));
+        Ok(Some(Value::Nil))
+    }
+
+    /// Handles the (undo!) form.
+    ///
+    /// Reverts the world to before the last effect batch (spawn, link, set, ...).
+    fn handle_undo(&mut self) -> Result<Option<Value>> {
+        if !self.session.undo() {
+            return Err(Error::new(ErrorKind::Refused(
+                "nothing to undo".to_string(),
+            )));
+        }
+        self.forget_refraction();
+        self.write_output(&format!(
+            "Undone ({} more undo, {} redo available)\n",
+            self.session.undo_depth(),
+            self.session.redo_depth()
+        ));
+        Ok(Some(Value::Nil))
+    }
+
+    /// Handles the (redo!) form.
+    ///
+    /// Reapplies the most recently undone effect batch.
+    fn handle_redo(&mut self) -> Result<Option<Value>> {
+        if !self.session.redo() {
+            return Err(Error::new(ErrorKind::Refused(
+                "nothing to redo".to_string(),
+            )));
+        }
+        self.forget_refraction();
+        self.write_output(&format!(
+            "Redone ({} more redo available)\n",
+            self.session.redo_depth()
+        ));
+        Ok(Some(Value::Nil))
+    }
+
+    /// Handles the (validate) form.
+    ///
+    /// Checks the whole world against component schemas, relationship
+    /// cardinalities, and entity references, and returns the issues as
+    /// `{:kind :schema-violation :entity e :message "..."}` maps.
+    #[allow(clippy::unnecessary_wraps)]
+    fn handle_validate(&mut self) -> Result<Option<Value>> {
+        let world = self.session.world();
+        let report = world.validate();
+        let text = format!("{}\n", report.format(world.interner()));
+        self.write_output(&text);
+
+        let world = self.session.world();
+        let issues: Vec<_> = report
+            .issues
+            .iter()
+            .map(|issue| (issue, issue.describe(world.interner())))
+            .collect();
+        let interner = self.session.world_mut().interner_mut();
+        let [kind, entity, message] =
+            ["kind", "entity", "message"].map(|k| Value::Keyword(interner.intern_keyword(k)));
+        let issues = issues
+            .into_iter()
+            .map(|(issue, description)| {
+                let map = LtMap::new()
+                    .insert(
+                        kind.clone(),
+                        Value::Keyword(interner.intern_keyword(issue.kind_name())),
+                    )
+                    .insert(entity.clone(), Value::EntityRef(issue.entity()))
+                    .insert(message.clone(), Value::String(description.into()));
+                Value::Map(map)
+            })
+            .collect();
+        Ok(Some(Value::Vec(issues)))
+    }
+
+    /// Handles the (lint-game) form.
+    ///
+    /// Prints content lints (see [`crate::lint`]) and returns how many there were.
+    #[allow(clippy::unnecessary_wraps)]
+    fn handle_lint_game(&mut self) -> Result<Option<Value>> {
+        let lints = lint::lint(&self.session);
+        self.write_output(&format!("{}\n", lint::format_report(&lints)));
+
+        #[allow(clippy::cast_possible_wrap)]
+        Ok(Some(Value::Int(lints.len() as i64)))
+    }
+
+    /// Handles the (memory) form.
+    ///
+    /// Prints what the world and the history it retains hold, along with
+    /// the worlds the session keeps for undo and `save-state`, and returns
+    /// how many earlier worlds the current one retains.
+    #[allow(clippy::unnecessary_wraps)]
+    fn handle_memory(&mut self) -> Result<Option<Value>> {
+        let world = self.session.world();
+        let stats = world.memory_stats();
+        let mut text = stats.describe(world.interner()).join("\n");
+        let _ = write!(
+            text,
+            "\nsession: {} undo, {} redo, {} saved states\n",
+            self.session.undo_depth(),
+            self.session.redo_depth(),
+            self.session.saved_state_count()
+        );
+        self.write_output(&text);
+
+        #[allow(clippy::cast_possible_wrap)]
+        Ok(Some(Value::Int(stats.history as i64)))
+    }
+
+    /// Handles the (gc-interner!) form.
+    ///
+    /// Frees the keywords made at runtime (by `string->keyword` or JSON
+    /// decoding) that nothing in the session refers to any longer, prints
+    /// how many were freed, and returns the count. Their IDs and string
+    /// slots are reused by the keywords interned next, but the interner's
+    /// tables never shrink, and symbols are never freed.
+    #[allow(clippy::unnecessary_wraps)]
+    fn handle_gc_interner(&mut self) -> Result<Option<Value>> {
+        let before = self.session.world().interner().string_bytes();
+        let mut live = HashSet::new();
+        self.tick_executor.mark_keywords(&mut live);
+        self.vm.mark_keywords(&mut live);
+        let freed = self.session.sweep_keywords(live);
+        let released = before - self.session.world().interner().string_bytes();
+        self.write_output(&format!(
+            "freed {freed} generated keywords ({released} bytes)\n"
+        ));
+
+        #[allow(clippy::cast_possible_wrap)]
+        Ok(Some(Value::Int(freed as i64)))
+    }
+
+    /// Handles the (rule-stats) form.
+    ///
+    /// Prints how many activations each rule has fired and how long its
+    /// matching and effects took across the ticks run so far, costliest
+    /// first, and returns how many rules are listed. `(rule-stats :reset)`
+    /// starts the totals over.
+    fn handle_rule_stats(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        match args {
+            [] => {}
+            [Ast::Keyword(k, _)] if k == "reset" => {
+                self.tick_executor.reset_rule_metrics();
+                return Ok(Some(Value::Nil));
+            }
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidArgument(
+                    "rule-stats takes no arguments, or :reset".to_string(),
+                )));
+            }
+        }
+
+        let stats = self.tick_executor.rule_metrics().by_total_time();
+        let mut report = String::new();
+        if stats.is_empty() {
+            report.push_str("No rules have run\n");
+        } else {
+            let interner = self.session.world().interner();
+            let ms = |d: std::time::Duration| format!("{:.3}ms", d.as_secs_f64() * 1000.0);
+            let _ = writeln!(
+                report,
+                "{:<24} {:>8} {:>11} {:>11} {:>11}",
+                "rule", "fired", "match", "effects", "total"
+            );
+            for (rule, stats) in &stats {
+                let name = format!(":{}", interner.get_keyword(*rule).unwrap_or("?"));
+                let _ = writeln!(
+                    report,
+                    "{name:<24} {:>8} {:>11} {:>11} {:>11}",
+                    stats.activations,
+                    ms(stats.match_time),
+                    ms(stats.effect_time),
+                    ms(stats.total_time())
+                );
+            }
+        }
+        self.write_output(&report);
+
+        #[allow(clippy::cast_possible_wrap)]
+        Ok(Some(Value::Int(stats.len() as i64)))
+    }
+
+    /// Handles the (coverage-report) form.
+    ///
+    /// Prints how many of the declared rules have fired and how many command
+    /// syntaxes input has matched, naming the ones that haven't, and returns
+    /// them as `{:rules [...] :syntaxes [...]}`. `(coverage-report :reset)`
+    /// starts the record over.
+    fn handle_coverage_report(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        match args {
+            [] => {}
+            [Ast::Keyword(k, _)] if k == "reset" => {
+                self.session.coverage_mut().clear();
+                return Ok(Some(Value::Nil));
+            }
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidArgument(
+                    "coverage-report takes no arguments, or :reset".to_string(),
+                )));
+            }
+        }
+
+        let report = self.coverage_report();
+        self.write_output(&report.render());
+        let interner = self.session.world_mut().interner_mut();
+        let mut names = |names: &[String]| {
+            Value::Vec(
+                names
+                    .iter()
+                    .map(|name| Value::Keyword(interner.intern_keyword(name)))
+                    .collect(),
+            )
+        };
+        let rules = names(&report.unfired_rules);
+        let syntaxes = names(&report.unmatched_syntaxes);
+        let map = LtMap::new()
+            .insert(Value::Keyword(interner.intern_keyword("rules")), rules)
+            .insert(
+                Value::Keyword(interner.intern_keyword("syntaxes")),
+                syntaxes,
+            );
+        Ok(Some(Value::Map(map)))
+    }
+
+    /// Returns which declared rules have fired and which command syntaxes
+    /// input has matched since the session started (see [`crate::coverage`]).
+    #[must_use]
+    pub fn coverage_report(&self) -> CoverageReport {
+        let interner = self.session.world().interner();
+        let rules = self
+            .session
+            .compiled_rules()
+            .iter()
+            .filter_map(|rule| interner.get_keyword(rule.name));
+        let syntaxes = self
+            .session
+            .compiled_syntaxes()
+            .iter()
+            .filter_map(|syntax| interner.get_keyword(syntax.command));
+        self.session.coverage().report(rules, syntaxes)
+    }
+
+    /// Handles the (agenda) form.
+    ///
+    /// Lists the activations the rules would fire next, in firing order, with
+    /// their ids for `(cancel-activation! id)`. Returns them as maps of
+    /// `:id`, `:rule`, `:salience`, and `:bindings`.
+    fn handle_agenda(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        if !args.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "agenda takes no arguments".to_string(),
+            )));
+        }
+
+        self.sync_rules();
+        let world = self.session.world().clone();
+        let agenda = self.tick_executor.agenda(&world)?;
+        let interner = world.interner();
+        let mut report = String::new();
+        if agenda.is_empty() {
+            report.push_str("No pending activations\n");
+        }
+        let mut rows = Vec::new();
+        for entry in &agenda {
+            let rule = interner
+                .get_keyword(entry.activation.rule_name)
+                .unwrap_or("?");
+            let mut bindings: Vec<_> = entry.activation.bindings.iter().collect();
+            bindings.sort_by(|a, b| a.0.cmp(b.0));
+            let shown: Vec<String> = bindings
+                .iter()
+                .map(|(var, value)| format!("?{var}={}", format_value_with(value, interner)))
+                .collect();
+            let _ = writeln!(
+                report,
+                "#{:<4} :{rule:<24} salience {:<4} {}",
+                entry.id,
+                entry.activation.salience,
+                shown.join(" ")
+            );
+            rows.push((entry, bindings));
+        }
+        self.write_output(&report);
+
+        let interner = self.session.world_mut().interner_mut();
+        let [id, rule, salience, bindings_key] = ["id", "rule", "salience", "bindings"]
+            .map(|k| Value::Keyword(interner.intern_keyword(k)));
+        let entries = rows
+            .into_iter()
+            .map(|(entry, bindings)| {
+                let bindings = bindings
+                    .into_iter()
+                    .fold(LtMap::new(), |map, (var, value)| {
+                        map.insert(Value::Keyword(interner.intern_keyword(var)), value.clone())
+                    });
+                #[allow(clippy::cast_possible_wrap)]
+                let map = LtMap::new()
+                    .insert(id.clone(), Value::Int(entry.id as i64))
+                    .insert(rule.clone(), Value::Keyword(entry.activation.rule_name))
+                    .insert(
+                        salience.clone(),
+                        Value::Int(i64::from(entry.activation.salience)),
+                    )
+                    .insert(bindings_key.clone(), Value::Map(bindings));
+                Value::Map(map)
+            })
+            .collect();
+        Ok(Some(Value::Vec(entries)))
+    }
+
+    /// Handles the (cancel-activation! id) form.
+    ///
+    /// Takes an activation listed by `(agenda)` off the agenda, so it doesn't
+    /// fire when the rules next run.
+    fn handle_cancel_activation(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        let [Ast::Int(id, _)] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "cancel-activation! requires an activation id from (agenda)".to_string(),
+            )));
+        };
+        let cancelled =
+            u64::try_from(*id).is_ok_and(|id| self.tick_executor.rule_engine_mut().cancel(id));
+        if !cancelled {
+            return Err(
+                Error::new(ErrorKind::NotFound(format!("no pending activation #{id}")))
+                    .with_hint("(agenda) lists the pending activations"),
+            );
+        }
+        Ok(Some(Value::Nil))
+    }
+
+    /// Handles the (relationship-stats) form.
+    ///
+    /// Prints fan-out and lookup statistics for each relationship type and
+    /// returns how many relationship types there are. Lookups are those made
+    /// by the forms evaluated in the session so far.
+    #[allow(clippy::unnecessary_wraps)]
+    fn handle_relationship_stats(&mut self) -> Result<Option<Value>> {
+        let world = self.session.world();
+        let stats = world.relationship_stats(self.session.relationship_lookups());
+        let mut out = String::new();
+        if stats.is_empty() {
+            out.push_str("No relationships declared\n");
+        }
+        for relationship in &stats {
+            let _ = writeln!(out, "{}", relationship.describe(world.interner()));
+        }
+        self.write_output(&out);
+
+        #[allow(clippy::cast_possible_wrap)]
+        Ok(Some(Value::Int(stats.len() as i64)))
+    }
+
+    // ==================== Backtracking Support Handlers ====================
+
+    /// Handles the (save-state) form.
+    ///
+    /// Saves the current world state and returns a snapshot ID.
+    /// This is used by the Sudoku solver for backtracking.
+    #[allow(clippy::unnecessary_wraps)]
+    fn handle_save_state(&mut self) -> Result<Option<Value>> {
+        let id = self.session.save_state();
+        #[allow(clippy::cast_possible_wrap)]
+        Ok(Some(Value::Int(id as i64)))
+    }
+
+    /// Handles the (restore-state ID) form.
+    ///
+    /// Restores the world to a previously saved snapshot.
+    fn handle_restore_state(&mut self, args: &[Ast]) -> Result<Option<Value>> {
+        if args.is_empty() {
+            return Err(Error::new(ErrorKind::Internal(
+                "restore-state requires a snapshot ID: (restore-state ID)".to_string(),
+            )));
+        }
+
+        let id_val = self.eval_form(&args[0])?;
+        let Value::Int(id) = id_val else {
+            return Err(Error::new(ErrorKind::Internal(
+                "restore-state requires an integer snapshot ID".to_string(),
+            )));
+        };
+
+        #[allow(clippy::cast_sign_loss)]
+        let id = id as u64;
+
+        self.session.restore_state(id)?;
+        self.forget_refraction();
+        Ok(Some(Value::Nil))
+    }
+
+    /// Starts recording every tick to a replay log, beginning from the current world.
+    ///
+    /// `sources` are the content files that were loaded, so a replay can load
+    /// them again. The log is available from [`Session::replay_log`].
+    pub fn start_recording(&mut self, sources: Vec<PathBuf>) {
+        let mut features: Vec<String> = self.session.features().iter().cloned().collect();
+        features.sort();
+        let log = ReplayLog::new(
+            self.session.world().clone(),
+            self.tick_executor.tick_number(),
+        )
+        .with_sources(sources)
+        .with_features(features);
+        self.session.start_replay(log);
+    }
+
+    /// Re-runs the ticks in a replay log, checking the world hash after each.
+    ///
+    /// The log's content files should already be loaded. Returns the number
+    /// of ticks replayed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log began at a different tick than the session
+    /// is at, if a tick fails, or if a tick leaves a different world than it
+    /// did when recorded.
+    pub fn replay(&mut self, log: &ReplayLog) -> Result<usize> {
+        if self.tick_executor.tick_number() != log.base_tick {
+            return Err(Error::new(ErrorKind::Refused(format!(
+                "replay log begins at tick {}, but the session is at tick {}",
+                log.base_tick,
+                self.tick_executor.tick_number()
+            ))));
+        }
+
+        self.session.set_world(log.base.clone());
+        for frame in &log.frames {
+            if let Some(world) = &frame.rebase {
+                self.session.set_world(world.clone());
+            }
+            self.step(&frame.inputs)?;
+            ReplayLog::verify(frame, self.session.world())?;
+        }
+        Ok(log.len())
+    }
+
+    /// Loads and evaluates a file.
+    ///
+    /// If the path is a directory containing a `_.lt` file, that file is loaded.
+    /// Any other directory has all of its `.lt` files loaded in dependency
+    /// order (see [`Self::load_directory`]).
+    /// Uses cycle detection to prevent recursive loading.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, contains cyclic loads, or evaluation fails.
+    pub fn load_file(&mut self, path: &str) -> Result<()> {
+        // Resolve path relative to current load path
+        let resolved = self.session.resolve_path(path);
+
+        // Try with .lt extension if not specified
+        let has_lt_ext = Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("lt"));
+
+        let file_path = if resolved.is_dir() {
+            // If it's a directory, look for _.lt inside
+            let entry_file = resolved.join("_.lt");
+            if entry_file.exists() {
+                entry_file
+            } else {
+                return self.load_directory(&resolved);
+            }
+        } else if resolved.exists() {
+            resolved
+        } else if !has_lt_ext {
+            let with_ext = self.session.resolve_path(&format!("{path}.lt"));
+            if with_ext.exists() {
+                with_ext
+            } else {
+                return Err(Error::new(ErrorKind::Internal(format!(
+                    "file not found: {path}"
+                ))));
+            }
+        } else {
+            return Err(Error::new(ErrorKind::Internal(format!(
+                "file not found: {path}"
+            ))));
+        };
+        self.load_resolved(&file_path)
+    }
+
+    /// Loads and evaluates a file whose path has already been resolved
+    /// against the load path.
+    fn load_resolved(&mut self, file_path: &Path) -> Result<()> {
+        // Canonicalize for consistent cycle detection
+        let canonical = file_path
+            .canonicalize()
+            .unwrap_or_else(|_| file_path.to_path_buf());
+
+        // Check if already loaded (skip re-loading)
+        if self.session.module_registry().has_file(&canonical) {
+            return Ok(());
+        }
+
+        // Begin loading (cycle detection)
+        self.session
+            .module_registry_mut()
+            .begin_loading(canonical.clone())?;
+        self.watcher.watch(&canonical);
+
+        // Read the file's forms, from its precompiled module if it has one
+        let forms = Self::read_forms(file_path).inspect_err(|_| {
+            // Clean up loading state on error
+            self.session
+                .module_registry_mut()
+                .finish_loading(&canonical);
+        })?;
+
+        // Save and update load path
+        let old_path = self.session.load_path().clone();
+        if let Some(parent) = file_path.parent() {
+            self.session.set_load_path(parent.to_path_buf());
+        }
+
+        // Evaluate with file context
+        let result = self.eval_with_file_context(&forms, &canonical);
+
+        // Restore load path
+        self.session.set_load_path(old_path);
+
+        // Finish loading (remove from loading stack)
+        self.session
+            .module_registry_mut()
+            .finish_loading(&canonical);
+
+        result.map(|_| ())
+    }
+
+    /// Loads every `.lt` file under a directory that has no `_.lt` entry file.
+    ///
+    /// Files are ordered by the `:require`s in their namespace declarations,
+    /// so each file loads after the namespaces it depends on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory has no source files, the requires
+    /// form a cycle, or any file fails to load.
+    fn load_directory(&mut self, dir: &Path) -> Result<()> {
+        let mut files = Vec::new();
+        Self::collect_source_files(dir, &mut files)?;
+        if files.is_empty() {
+            return Err(Error::new(ErrorKind::Internal(format!(
+                "directory '{}' contains no .lt files",
+                dir.display()
+            ))));
+        }
+
+        let mut graph = DependencyGraph::new();
+        for file in files {
+            let source = fs::read_to_string(&file).map_err(|e| {
+                Error::new(ErrorKind::Internal(format!(
+                    "failed to read {}: {e}",
+                    file.display()
+                )))
+            })?;
+            graph.add_file(file, &source);
+        }
+
+        for file in graph.load_order()? {
+            self.load_resolved(&file)?;
+        }
+        Ok(())
+    }
+
+    /// Collects `.lt` files under `dir`, recursing into subdirectories and
+    /// skipping hidden entries.
+    fn collect_source_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) -> Result<()> {
+        let entries = fs::read_dir(dir).map_err(|e| {
+            Error::new(ErrorKind::Internal(format!(
+                "failed to read {}: {e}",
+                dir.display()
+            )))
+        })?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                Self::collect_source_files(&path, files)?;
+            } else if is_source_file(&path) {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the top-level forms of a source file or precompiled module.
+    ///
+    /// A source file with a fresh `.ltc` module beside it is loaded from the
+    /// module instead of being parsed (see [`crate::precompiled`]).
+    fn read_forms(file_path: &Path) -> Result<Vec<Ast>> {
+        if precompiled::is_module_file(file_path) {
+            return Ok(PrecompiledModule::load(file_path)?.forms);
+        }
+        if let Some(module) = PrecompiledModule::cached_for(file_path) {
+            return Ok(module.forms);
+        }
+        let source = fs::read_to_string(file_path).map_err(|e| {
+            Error::new(ErrorKind::Internal(format!(
+                "failed to read {}: {e}",
+                file_path.display()
+            )))
+        })?;
+        precompiled::parse_module(&source, file_path)
+            .map_err(|e| e.or_source(file_path.display().to_string()))
+    }
+
+    /// Handles `(require [ns :as alias] ...)`.
+    ///
+    /// A namespace that isn't loaded yet is loaded from its file under the
+    /// load path (`my.game.combat` from `my/game/combat.lt`) if there is one.
+    /// Each spec is then added to the session's namespace context, so later
+    /// forms can write `::alias/name` keywords.
+    fn handle_require(&mut self, specs: &[Ast]) -> Result<()> {
+        if specs.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidArgument(
+                "require needs at least one spec: (require [my.game.combat :as combat])"
+                    .to_string(),
+            )));
+        }
+        for spec in specs {
+            let spec = DeclarationAnalyzer::analyze_require_spec(spec)?;
+            let namespace = spec.namespace();
+            let name = namespace.full_name();
+            let file = namespace.file_path().to_string_lossy().into_owned();
+            let loaded = self.session.module_registry().has_namespace(&name);
+            if let Some(source) = stdlib_module(&name) {
+                if !loaded {
+                    self.eval(source)?;
+                    let decl = NamespaceDecl::with_name(&name, Span::default());
+                    self.session
+                        .module_registry_mut()
+                        .register_namespace(NamespaceInfo::new(decl, namespace.file_path()));
+                }
+            } else if !loaded && self.session.resolve_path(&file).exists() {
+                // Loading the file switches to its namespace; come back after
+                let context = self.session.namespace_context().clone();
+                let loaded = self.load_file(&file);
+                self.session.set_namespace_context(context);
+                loaded?;
+            }
+            self.session.namespace_context_mut().add_require(&spec);
+        }
+        Ok(())
+    }
+
+    /// Evaluates a file's forms within a file context.
+    ///
+    /// Handles namespace declarations and registers the file in the module registry.
+    fn eval_with_file_context(&mut self, forms: &[Ast], file_path: &Path) -> Result<Value> {
+        let body = self.enter_file_namespace(forms, file_path)?;
+
+        // Evaluate each form
+        self.eval_top_level(body, Some(file_path))
+    }
+
+    /// Enters the namespace a file declares in its first form, if any, and
+    /// returns the forms after the declaration.
+    ///
+    /// The declaration is consumed here rather than evaluated, since
+    /// `namespace` isn't something the compiler can run.
+    fn enter_file_namespace<'a>(
+        &mut self,
+        forms: &'a [Ast],
+        file_path: &Path,
+    ) -> Result<&'a [Ast]> {
+        let Some(first_form) = forms.first() else {
+            return Ok(forms);
+        };
+        let Some(Declaration::Namespace(ns_decl)) = DeclarationAnalyzer::analyze(first_form)?
+        else {
+            return Ok(forms);
+        };
+
+        // Build namespace context from declaration
+        let ns_context = NamespaceContext::from_decl(&ns_decl);
+
+        // Register the namespace
+        let ns_info = NamespaceInfo::new(ns_decl, file_path.to_path_buf());
+        self.session
+            .module_registry_mut()
+            .register_namespace(ns_info);
+
+        // Set as current namespace context for compilation
+        self.session.set_namespace_context(ns_context);
+        Ok(&forms[1..])
+    }
+
+    /// Evaluates a file without changing the load path (for CLI batch mode).
+    ///
+    /// `path` may be a precompiled `.ltc` module, and a source file with a
+    /// fresh module beside it is loaded from the module.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or evaluated.
+    pub fn eval_file(&mut self, path: &Path) -> Result<Value> {
+        let forms = Self::read_forms(path)?;
+        self.watcher.watch(path);
+
+        // Set load path to file's directory
+        if let Some(parent) = path.parent() {
+            self.session.set_load_path(parent.to_path_buf());
+        }
+
+        self.eval_top_level(&forms, Some(path))
+    }
+
+    /// Returns the source files loaded so far, which hot reload watches.
+    pub fn watched_files(&self) -> impl Iterator<Item = &Path> {
+        self.watcher.files()
+    }
+
+    /// Reloads every loaded file that changed since it was loaded.
+    ///
+    /// Returns each changed file with the outcome of reloading it; a file
+    /// that fails to reload is left as it was.
+    pub fn reload_changed_files(&mut self) -> Vec<(PathBuf, Result<()>)> {
+        self.watcher
+            .changed()
+            .into_iter()
+            .map(|path| {
+                let result = self.reload_file(&path);
+                (path, result)
+            })
+            .collect()
+    }
+
+    /// Reloads a source file, swapping its definitions for the new ones
+    /// while keeping the world (see [`crate::reload`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the session unchanged, if the file can't be
+    /// read, parsed, or evaluated.
+    pub fn reload_file(&mut self, path: &Path) -> Result<()> {
+        // Use the path the file was loaded under, so its declarations match
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let file = self
+            .watcher
+            .files()
+            .find(|watched| watched.canonicalize().ok().as_deref() == Some(&canonical))
+            .map_or(canonical, Path::to_path_buf);
+        let forms = Self::read_forms(&file)?;
+
+        let checkpoint = self.session.checkpoint();
+        let constraints = self.tick_executor.constraint_checker().clone();
+        let load_path = self.session.load_path().clone();
+        let namespace_context = self.session.namespace_context().clone();
+
+        let result = self.swap_definitions(&file, &forms);
+
+        self.session.set_load_path(load_path);
+        if result.is_err() {
+            self.session.restore(checkpoint);
+            *self.tick_executor.constraint_checker_mut() = constraints;
+        }
+        self.session.set_namespace_context(namespace_context);
+        self.watcher.watch(&file);
+        result
+    }
+
+    /// Replaces the definitions `file` made with those in `forms`.
+    fn swap_definitions(&mut self, file: &Path, forms: &[Ast]) -> Result<()> {
+        // Retract the rules, commands, verbs, and constraints the file declared, so
+        // edited ones are replaced and deleted ones disappear
+        let previous: Vec<_> = self
+            .session
+            .declarations_from(file)
+            .into_iter()
+            .filter_map(|(form, name)| {
+                let keyword = self.session.world().interner().lookup_keyword(&name)?;
+                Some((form, keyword))
+            })
+            .collect();
+        for (form, keyword) in previous {
+            match form.as_str() {
+                "rule:" => {
+                    self.session.remove_compiled_rule(keyword);
+                }
+                "command:" => self.session.remove_command(keyword),
+                "verb:" => self.session.vocabulary_registry_mut().remove_verb(keyword),
+                "constraint:" => {
+                    self.tick_executor
+                        .constraint_checker_mut()
+                        .remove_constraint(keyword);
+                }
+                _ => {}
+            }
+        }
+
+        // Skip world state that's already there; free schemas declared again
+        let mut pending = Vec::with_capacity(forms.len());
+        for form in forms {
+            match Self::declaration_key(form) {
+                Some(("spawn:", name)) if self.session.get_entity(&name).is_some() => {}
+                Some(("link:", name))
+                    if self
+                        .session
+                        .declaration_site("link:", &name)
+                        .is_some_and(|site| site.file.as_deref() == Some(file)) => {}
+                Some((head @ ("component:" | "relationship:"), name)) => {
+                    let world = self.session.world();
+                    if let Some(keyword) = world.interner().lookup_keyword(&name) {
+                        let world = if head == "component:" {
+                            world.unregister_component(keyword)
+                        } else {
+                            world.unregister_relationship(keyword)
+                        };
+                        self.session.set_world(world);
+                    }
+                    pending.push(form.clone());
+                }
+                _ => pending.push(form.clone()),
+            }
+        }
+
+        if let Some(parent) = file.parent() {
+            self.session.set_load_path(parent.to_path_buf());
+        }
+        self.eval_with_file_context(&pending, file).map(|_| ())
+    }
+
+    /// Formats a value as the REPL would print it, without terminal styling.
+    #[must_use]
+    pub fn display_value(&self, value: &Value) -> String {
+        self.format_value_inner(value)
+    }
+
+    /// Formats a value for display, resolving keywords via the world's interner.
+    fn format_value(&self, value: &Value) -> String {
+        let formatted = self.format_value_inner(value);
+        format!("\x1b[1m{formatted}\x1b[0m")
+    }
+
+    /// Recursively formats a value, resolving keywords to their string names.
+    fn format_value_inner(&self, value: &Value) -> String {
+        format_value_with(value, self.session.world().interner())
+    }
+
+    /// Prints an error to stderr.
+    fn print_error(&self, error: &Error) {
+        self.error_format.report(error);
+    }
+
+    /// Prints the welcome banner.
+    #[allow(clippy::unused_self)]
+    fn print_banner(&self) {
+        println!("\x1b[1;36m");
+        println!("  _                        _        _     _      ");
+        println!(" | |    ___  _ __   __ _ _| |_ __ _| |__ | | ___ ");
+        println!(" | |   / _ \\| '_ \\ / _` |_   _/ _` | '_ \\| |/ _ \\");
+        println!(" | |__| (_) | | | | (_| | | || (_| | |_) | |  __/");
+        println!(" |_____\\___/|_| |_|\\__, | |_| \\__,_|_.__/|_|\\___|");
+        println!("                   |___/                         ");
+        println!("\x1b[0m");
+        println!("Welcome to Longtable REPL v{}", env!("CARGO_PKG_VERSION"));
+        println!("Type expressions to evaluate. Use Ctrl+D to exit.\n");
+
+        // Flush to ensure banner appears
+        let _ = io::stdout().flush();
+    }
+}
+
+/// The trace spans a tick has open, so each can be closed when the next
+/// [`DebugPoint`] arrives.
+///
+/// Engine phases map onto trace phases: hooks and input injection at the
+/// start of a tick are `input`, rules fire during `firing`, and the hooks
+/// and checks before commit are `constraints`.
+#[derive(Default)]
+struct TickSpans {
+    phase: Option<TracePhase>,
+    rule: Option<KeywordId>,
+}
+
+impl TickSpans {
+    /// Closes the spans `point` ends and opens the one it begins.
+    fn enter(&mut self, tracer: &mut Tracer, point: DebugPoint<'_>) {
+        if let Some(rule) = self.rule.take() {
+            tracer.rule_complete(rule);
+        }
+        match point {
+            DebugPoint::TickStart(tick) => tracer.tick_start(tick),
+            DebugPoint::Phase(phase) => {
+                if let Some(open) = self.phase.take() {
+                    tracer.phase_end(open);
+                }
+                self.phase = match phase {
+                    TickPhase::BeginTick => Some(TracePhase::Input),
+                    TickPhase::AfterInputs => Some(TracePhase::Firing),
+                    TickPhase::BeforeConstraints => Some(TracePhase::Constraints),
+                    TickPhase::AfterCommit => None,
+                };
+                if let Some(phase) = self.phase {
+                    tracer.phase_start(phase);
+                }
+            }
+            DebugPoint::Rule(activation) => {
+                tracer.rule_firing(activation.rule_name);
+                self.rule = Some(activation.rule_name);
+            }
+        }
+    }
+
+    /// Records `error` against the rule that was firing when the tick
+    /// failed, in place of its completion. Only a failing rule body locates
+    /// its error; an abort from the debugger or the activation limit leaves
+    /// the rule to complete as usual.
+    fn fail(&mut self, tracer: &mut Tracer, error: &Error) {
+        if error.context.is_some() {
+            if let Some(rule) = self.rule.take() {
+                tracer.rule_error(rule, error);
+            }
+        }
+    }
+
+    /// Closes whatever is still open and ends the tick.
+    fn finish(&mut self, tracer: &mut Tracer, tick: u64, success: bool) {
+        if let Some(rule) = self.rule.take() {
+            tracer.rule_complete(rule);
+        }
+        if let Some(phase) = self.phase.take() {
+            tracer.phase_end(phase);
+        }
+        tracer.tick_end(tick, success);
+    }
+}
+
+/// Formats a value as the REPL prints it, resolving names via `interner`.
+fn format_value_with(value: &Value, interner: &Interner) -> String {
+    match value {
+        Value::Nil => "nil".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Int(n) => n.to_string(),
+        Value::Float(n) => {
+            if n.fract() == 0.0 {
+                format!("{n}.0")
+            } else {
+                n.to_string()
+            }
+        }
+        Value::String(s) => format!("\"{s}\""),
+        Value::Symbol(id) => interner
+            .get_symbol(*id)
+            .map_or_else(|| format!("Symbol({})", id.index()), str::to_string),
+        Value::Keyword(id) => interner
+            .get_keyword(*id)
+            .map_or_else(|| format!("Keyword({})", id.index()), |s| format!(":{s}")),
+        Value::EntityRef(id) => format!("Entity({}, {})", id.index, id.generation),
+        Value::BigInt(_)
+        | Value::Instant(_)
+        | Value::Duration(_)
+        | Value::Vec2(_)
+        | Value::Vec3(_)
+        | Value::Seq(_) => value.to_string(),
+        Value::Vec(v) => {
+            let items: Vec<_> = v.iter().map(|v| format_value_with(v, interner)).collect();
+            format!("[{}]", items.join(" "))
+        }
+        Value::List(l) => {
+            let items: Vec<_> = l.iter().map(|v| format_value_with(v, interner)).collect();
+            format!("({})", items.join(" "))
+        }
+        Value::Set(s) => {
+            let items: Vec<_> = s.iter().map(|v| format_value_with(v, interner)).collect();
+            format!("#{{{}}}", items.join(" "))
+        }
+        Value::Map(m) => {
+            let pairs: Vec<_> = m
+                .iter()
+                .map(|(k, v)| {
+                    format!(
+                        "{} {}",
+                        format_value_with(k, interner),
+                        format_value_with(v, interner)
+                    )
+                })
+                .collect();
+            format!("{{{}}}", pairs.join(" "))
+        }
+        Value::Fn(_) => "<fn>".to_string(),
+    }
+}
+
+/// Renders an error for a scenario failure, followed by its hints.
+fn error_detail(error: &Error) -> String {
+    let mut detail = error.to_string();
+    for hint in &error.hints {
+        let _ = write!(detail, "\nhint: {hint}");
+    }
+    detail
+}
+
+/// The error a poisoned session gives when asked to tick.
+fn poisoned_error(poisoned: &Poisoned) -> Error {
+    Error::new(ErrorKind::Refused(format!(
+        "tick {} panicked ({}), so the world may be inconsistent",
+        poisoned.tick, poisoned.message
+    )))
+    .with_hint("(recover!) restores the world from before that tick")
+    .with_hint("(recover! :keep) keeps it as it is")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A simple mock editor for testing.
+    struct MockEditor {
+        inputs: Vec<String>,
+        index: usize,
+    }
+
+    impl MockEditor {
+        fn new(inputs: Vec<&str>) -> Self {
+            Self {
+                inputs: inputs.into_iter().map(String::from).collect(),
+                index: 0,
+            }
+        }
+    }
+
+    impl LineEditor for MockEditor {
+        fn read_line(&mut self, _prompt: &str) -> Result<ReadResult> {
+            if self.index < self.inputs.len() {
+                let line = self.inputs[self.index].clone();
+                self.index += 1;
+                Ok(ReadResult::Line(line))
+            } else {
+                Ok(ReadResult::Eof)
+            }
+        }
+
+        fn read_continuation(&mut self, prompt: &str) -> Result<ReadResult> {
+            self.read_line(prompt)
+        }
+
+        fn add_history(&mut self, _line: &str) {}
+
+        fn set_keywords(&mut self, _keywords: Vec<String>) {}
+    }
+
+    #[test]
+    fn eval_simple_expression() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        let result = repl.eval("(+ 1 2)").unwrap();
+        assert_eq!(result, Value::Int(3));
+    }
+
+    #[test]
+    fn eval_def_creates_variable() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        repl.eval("(def x 42)").unwrap();
+        assert_eq!(repl.session.get_variable("x"), Some(&Value::Int(42)));
+
+        // Note: Using session variables in subsequent expressions requires
+        // VM global variable support, which is not yet implemented.
+        // For now, we just verify the variable is stored in the session.
+    }
+
+    #[test]
+    fn eval_def_with_expression() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        repl.eval("(def sum (+ 10 20))").unwrap();
+        assert_eq!(repl.session.get_variable("sum"), Some(&Value::Int(30)));
+    }
+
+    #[test]
+    fn is_complete_balanced() {
+        let editor = MockEditor::new(vec![]);
+        let repl = Repl::with_editor(editor);
+
+        assert!(repl.is_complete("(+ 1 2)"));
+        assert!(repl.is_complete("[1 2 3]"));
+        assert!(repl.is_complete("{:a 1}"));
+        assert!(repl.is_complete("42"));
+        assert!(repl.is_complete(""));
+    }
+
+    #[test]
+    fn is_complete_unbalanced() {
+        let editor = MockEditor::new(vec![]);
+        let repl = Repl::with_editor(editor);
+
+        assert!(!repl.is_complete("(+ 1"));
+        assert!(!repl.is_complete("[1 2"));
+        assert!(!repl.is_complete("{:a"));
+        assert!(!repl.is_complete("\"hello"));
+    }
+
+    #[test]
+    fn is_complete_nested() {
+        let editor = MockEditor::new(vec![]);
+        let repl = Repl::with_editor(editor);
+
+        assert!(repl.is_complete("(if (> x 0) (+ x 1) (- x 1))"));
+        assert!(!repl.is_complete("(if (> x 0) (+ x 1)"));
+    }
+
+    #[test]
+    fn is_complete_string_with_brackets() {
+        let editor = MockEditor::new(vec![]);
+        let repl = Repl::with_editor(editor);
+
+        // Brackets inside strings should be ignored
+        assert!(repl.is_complete("\"hello (world\""));
+        assert!(repl.is_complete("(str \"[test]\")"));
+    }
+
+    // ==================== Error Recovery Tests ====================
+
+    #[test]
+    fn error_recovery_syntax_error() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Syntax errors should not crash the REPL
+        let result = repl.eval("(+ 1 2");
+        assert!(result.is_err());
+
+        // REPL should still be usable after error
+        let result = repl.eval("(+ 1 2)");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn error_recovery_undefined_function() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Calling undefined function should return error
+        let result = repl.eval("(undefined-fn 1 2)");
+        assert!(result.is_err());
+
+        // REPL should still be usable
+        let result = repl.eval("42");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn error_recovery_division_by_zero() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Division by zero should return error
+        let result = repl.eval("(/ 1 0)");
+        assert!(result.is_err());
+
+        // REPL should still be usable
+        let result = repl.eval("(/ 10 2)");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn error_recovery_type_error() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Type error should return error
+        let result = repl.eval(r#"(+ "hello" 1)"#);
+        assert!(result.is_err());
+
+        // REPL should still be usable
+        let result = repl.eval("(+ 1 2)");
+        assert!(result.is_ok());
+    }
+
+    // ==================== Interactive Scenario Tests ====================
+
+    #[test]
+    fn interactive_arithmetic_chain() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Complex expression evaluation
+        let result = repl.eval("(+ (* 2 3) (- 10 5))").unwrap();
+        assert_eq!(result, Value::Int(11));
+    }
+
+    #[test]
+    fn interactive_let_expression() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Use let to bind a variable within a single expression
+        let result = repl.eval("(let [x 10] (+ x 5))").unwrap();
+        assert_eq!(result, Value::Int(15));
+
+        // Nested let bindings
+        let result = repl.eval("(let [x 10 y 20] (+ x y))").unwrap();
+        assert_eq!(result, Value::Int(30));
+    }
+
+    #[test]
+    fn interactive_collection_operations() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Create and manipulate collections
+        let result = repl.eval("(first [1 2 3])").unwrap();
+        assert_eq!(result, Value::Int(1));
+
+        let result = repl.eval("(count [1 2 3 4 5])").unwrap();
+        assert_eq!(result, Value::Int(5));
+    }
+
+    #[test]
+    fn interactive_nested_collections() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Nested vector creation
+        let result = repl.eval("[[1 2] [3 4]]").unwrap();
+        // Just verify it's a vector
+        assert!(matches!(result, Value::Vec(_)));
+
+        // Map creation
+        let result = repl.eval("{:a 1 :b 2}").unwrap();
+        assert!(matches!(result, Value::Map(_)));
+    }
+
+    #[test]
+    fn interactive_string_operations() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // String concatenation
+        let result = repl.eval(r#"(str "hello" " " "world")"#).unwrap();
+        assert_eq!(result, Value::String("hello world".into()));
+
+        // String functions
+        let result = repl.eval(r#"(str/upper "hello")"#).unwrap();
+        assert_eq!(result, Value::String("HELLO".into()));
+    }
+
+    #[test]
+    fn interactive_higher_order_functions() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Map with lambda - returns vector
+        let result = repl.eval("(map (fn [x] (* x 2)) [1 2 3])").unwrap();
+        assert!(matches!(result, Value::Vec(_)));
+
+        // Reduce with lambda (native functions can't be passed directly)
+        let result = repl
+            .eval("(reduce (fn [acc x] (+ acc x)) 0 [1 2 3 4 5])")
+            .unwrap();
+        assert_eq!(result, Value::Int(15));
+    }
+
+    #[test]
+    fn interactive_math_functions() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Basic math
+        let result = repl.eval("(abs -5)").unwrap();
+        assert_eq!(result, Value::Int(5));
+
+        // Min/max
+        let result = repl.eval("(max 1 5 3)").unwrap();
+        assert_eq!(result, Value::Int(5));
+
+        let result = repl.eval("(min 1 5 3)").unwrap();
+        assert_eq!(result, Value::Int(1));
+    }
+
+    // ==================== Spawn and Link Tests ====================
+
+    #[test]
+    fn spawn_creates_entity() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // First define the component schema (tag component with :bool :default true)
+        repl.eval("(component: tag/player :bool :default true)")
+            .unwrap();
+
+        // Spawn an entity
+        repl.eval("(spawn: player :tag/player true)").unwrap();
+
+        // Verify entity was created
+        assert_eq!(repl.session.world().entity_count(), 1);
+
+        // Verify entity is registered by name
+        let entity_id = repl.session.get_entity("player");
+        assert!(entity_id.is_some());
+    }
+
+    #[test]
+    fn spawn_with_component_map() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // First define the component schema with optional fields with defaults
+        repl.eval("(component: health :current :int :default 0 :max :int :default 100)")
+            .unwrap();
+
+        // Spawn an entity with a map component value
+        repl.eval("(spawn: player :health {:current 100 :max 100})")
+            .unwrap();
+
+        // Verify entity was created
+        assert_eq!(repl.session.world().entity_count(), 1);
+
+        // Verify entity is registered by name
+        let entity_id = repl.session.get_entity("player");
+        assert!(entity_id.is_some());
+    }
+
+    #[test]
+    fn link_creates_relationship() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Define components and relationship (tag components)
+        repl.eval("(component: tag/player :bool :default true)")
+            .unwrap();
+        repl.eval("(component: tag/room :bool :default true)")
+            .unwrap();
+        repl.eval("(relationship: in-room :cardinality :many-to-one)")
+            .unwrap();
+
+        // Spawn two entities
+        repl.eval("(spawn: player :tag/player true)").unwrap();
+        repl.eval("(spawn: room :tag/room true)").unwrap();
+
+        // Link them
+        repl.eval("(link: player :in-room room)").unwrap();
+
+        // Verify entities exist (player, room, and the relationship entity from dual-write)
+        assert_eq!(repl.session.world().entity_count(), 3);
+    }
+
+    #[test]
+    fn link_unknown_source_fails() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Define components and relationship
+        repl.eval("(component: tag/room :bool :default true)")
+            .unwrap();
+        repl.eval("(relationship: in-room :cardinality :many-to-one)")
+            .unwrap();
+
+        // Spawn only target
+        repl.eval("(spawn: room :tag/room true)").unwrap();
+
+        // Try to link with unknown source
+        let result = repl.eval("(link: player :in-room room)");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("unknown entity"));
+    }
+
+    #[test]
+    fn link_unknown_target_fails() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Define components and relationship
+        repl.eval("(component: tag/player :bool :default true)")
+            .unwrap();
+        repl.eval("(relationship: in-room :cardinality :many-to-one)")
+            .unwrap();
+
+        // Spawn only source
+        repl.eval("(spawn: player :tag/player true)").unwrap();
+
+        // Try to link with unknown target
+        let result = repl.eval("(link: player :in-room room)");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("unknown entity"));
+    }
+
+    #[test]
+    fn component_defines_schema() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Define a component with fields
+        repl.eval("(component: health :current :int :max :int :default 100)")
+            .unwrap();
+
+        // Should not error - component is registered
+    }
+
+    #[test]
+    fn relationship_defines_schema() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Define a relationship
+        repl.eval("(relationship: in-room :cardinality :many-to-one :on-target-delete :remove)")
+            .unwrap();
+
+        // Should not error - relationship is registered
+    }
+
+    #[test]
+    fn acyclic_relationship_rejects_containment_cycles() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: tag/container :bool :default true)
+             (relationship: contained-in :cardinality :many-to-one :acyclic true)
+             (spawn: bag :tag/container true)
+             (spawn: chest :tag/container true)
+             (link: bag :contained-in chest)",
+        )
+        .unwrap();
+
+        let err = repl.eval("(link: chest :contained-in bag)").unwrap_err();
+        assert!(err.to_string().contains("relationship cycle"));
+    }
+
+    #[test]
+    fn spawn_many_spawns_in_one_pass() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval("(component: position :x :float :y :float)")
+            .unwrap();
+        let before = repl.session().world().entity_count();
+
+        let spawned = repl
+            .eval("(spawn-many! (map (fn [i] {:position {:x (* i 1.0) :y 0.0}}) (range 1000)))")
+            .unwrap();
+        let Value::Vec(spawned) = spawned else {
+            panic!("expected a vector, got {spawned:?}");
+        };
+        assert_eq!(spawned.len(), 1000);
+        let world = repl.session().world();
+        assert_eq!(world.entity_count(), before + 1000);
+        let Some(Value::EntityRef(last)) = spawned.iter().last() else {
+            panic!("expected entities");
+        };
+        let position = world.interner().lookup_keyword("position").unwrap();
+        let x = world.interner().lookup_keyword("x").unwrap();
+        assert_eq!(
+            world.get_field(*last, position, x).unwrap(),
+            Some(Value::Float(999.0))
+        );
+        // The batch is a single undo step
+        assert_eq!(world.previous().unwrap().entity_count(), before);
+    }
+
+    #[test]
+    fn memory_reports_history_and_archetypes() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            "(component: glow :level :int)
+             (spawn: lamp :glow {:level 3})
+             (spawn: torch :glow {:level 5})",
+        )
+        .unwrap();
+        repl.take_output();
+
+        let history = repl.eval("(memory)").unwrap();
+        let output = repl.take_output();
+        assert!(matches!(history, Value::Int(n) if n > 0), "{history:?}");
+        assert!(
+            output.starts_with("2 entities, 2 component values"),
+            "{output}"
+        );
+        assert!(output.contains("      2  :glow"), "{output}");
+        assert!(output.contains("saved states"), "{output}");
+    }
+
+    #[test]
+    fn gc_interner_frees_unreachable_generated_keywords() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            "(component: tag :name :keyword)
+             (spawn! {:tag {:name (string->keyword \"kept\")}})
+             (string->keyword \"dropped\")
+             (string->keyword \"tag\")",
+        )
+        .unwrap();
+        // A timer's closure holds what it captured until it fires
+        repl.eval("(let [k (string->keyword \"timed\")] (fuse 5 :then [(println k)]))")
+            .unwrap();
+        repl.take_output();
+
+        let freed = repl.eval("(gc-interner!)").unwrap();
+        assert_eq!(freed, Value::Int(1));
+        assert_eq!(repl.take_output(), "freed 1 generated keywords (7 bytes)\n");
+        let interner = repl.session().world().interner();
+        assert!(interner.lookup_keyword("kept").is_some());
+        assert!(interner.lookup_keyword("tag").is_some());
+        assert!(interner.lookup_keyword("timed").is_some());
+        assert!(interner.lookup_keyword("dropped").is_none());
+        assert_eq!(repl.eval("(gc-interner!)").unwrap(), Value::Int(0));
+    }
+
+    #[test]
+    fn watches_pause_the_tick_that_changes_them() {
+        use longtable_debug::{DebugState, WatchId};
+
+        let editor = MockEditor::new(vec!["(where)", "(abort)"]);
+        let mut repl = Repl::with_editor(editor).with_captured_output();
+        repl.eval("(component: glow :level :int) (spawn: lamp :glow {:level 3})")
+            .unwrap();
+        let lamp = repl.session().get_entity("lamp").unwrap();
+        let lamp = format!("(entity-ref {} {})", lamp.index, lamp.generation);
+        repl.eval(&format!("(watch (get-component {lamp} :glow))"))
+            .unwrap();
+        assert_eq!(repl.take_output(), "Watch #1 added\n");
+
+        // The first tick records the value without reporting it
+        repl.step(&[]).unwrap();
+        assert_eq!(repl.take_output(), "");
+        let watched = |repl: &Repl<MockEditor>| {
+            let watches = repl.session().debug_session().watches();
+            watches.get(WatchId::new(1)).unwrap().last_value().cloned()
+        };
+        let level = repl
+            .session()
+            .world()
+            .interner()
+            .lookup_keyword("level")
+            .unwrap();
+        let level = |n| Value::Map(LtMap::new().insert(Value::Keyword(level), Value::Int(n)));
+        assert_eq!(watched(&repl), Some(level(3)));
+        assert!(!repl.session().debug_session().is_paused());
+
+        // A rule changes it, so the tick pauses before it commits
+        repl.eval(
+            "(rule: brighten :where [[?e :glow ?g] [(< (get ?g :level) 5)]]
+               :then [(set-component! ?e :glow {:level 5})])",
+        )
+        .unwrap();
+        assert!(repl.step(&[]).is_err());
+        let paused = "Paused (watch #1 changed) at tick 2, phase before-constraints\n";
+        assert_eq!(
+            repl.take_output(),
+            format!(
+                "watch #1 (get-component {lamp} :glow): {{:level 3}} -> {{:level 5}}\n\
+                 {paused}{paused}"
+            )
+        );
+        assert_eq!(repl.session().debug_session().state(), &DebugState::Running);
+
+        // The abandoned tick leaves the watch as it was
+        assert_eq!(watched(&repl), Some(level(3)));
+        repl.eval("(watches)").unwrap();
+        assert_eq!(
+            repl.take_output(),
+            format!("Watches:\n  #1 - (get-component {lamp} :glow) = {{:level 3}} (enabled)\n")
+        );
+    }
+
+    #[test]
+    fn breakpoints_pause_ticks_at_a_debug_prompt() {
+        use longtable_debug::DebugState;
+
+        let editor = MockEditor::new(vec!["(where)", "(step-phase)", "(continue)", "(abort)"]);
+        let mut repl = Repl::with_editor(editor).with_captured_output();
+        repl.eval("(break :tick 2)").unwrap();
+        assert_eq!(repl.take_output(), "Breakpoint #1 added\n");
+
+        repl.step(&[]).unwrap();
+        assert_eq!(repl.take_output(), "");
+
+        // Paused before tick 2, stepped to its first phase, then continued
+        repl.step(&[]).unwrap();
+        let output = repl.take_output();
+        let paused = "Paused (breakpoint #1) at tick 2, before it starts\n";
+        assert_eq!(
+            output,
+            format!("{paused}{paused}Paused (step complete) at tick 2, phase begin-tick\n")
+        );
+        assert_eq!(repl.session().debug_session().state(), &DebugState::Running);
+
+        // Stepping again pauses at the next tick, which can be abandoned
+        repl.eval("(step-tick)").unwrap();
+        let err = repl.step(&[]).unwrap_err();
+        assert!(err.to_string().contains("tick 3 abandoned"), "{err}");
+        assert!(!repl.session().debug_session().is_paused());
+    }
+
+    #[test]
+    fn rule_breakpoints_pause_with_bindings_and_evaluate_forms() {
+        let editor = MockEditor::new(vec!["(get-field ?e :glow :level)", "(+ 1 2)", "(abort)"]);
+        let mut repl = Repl::with_editor(editor).with_captured_output();
+        repl.eval(
+            "(component: glow :level :int)
+             (rule: dim :where [[?e :glow ?g]] :then [])
+             (spawn: lamp :glow {:level 3})
+             (schedule! :in 1 :then [(println \"ding\")])
+             (break :rule :dim)",
+        )
+        .unwrap();
+        repl.take_output();
+
+        let err = repl.step(&[]).unwrap_err();
+        assert!(err.to_string().contains("tick 1 abandoned"), "{err}");
+        let output = repl.take_output();
+        assert!(
+            output.starts_with("Paused (breakpoint #1) at tick 1, rule dim\n"),
+            "{output}"
+        );
+        assert!(output.contains("  ?e = Entity("), "{output}");
+        assert!(output.ends_with("  ?g = {:level 3}\n3\n3\n"), "{output}");
+
+        // The abandoned tick's timer is still due
+        repl.eval("(unbreak 1)").unwrap();
+        repl.step(&[]).unwrap();
+        assert!(repl.take_output().contains("ding\n"));
+    }
+
+    #[test]
+    fn debug_prompt_refuses_session_commands_until_the_tick_ends() {
+        let editor = MockEditor::new(vec!["(tick!)", "(continue)"]);
+        let mut repl = Repl::with_editor(editor).with_captured_output();
+        repl.eval("(break :tick 1)").unwrap();
+        repl.take_output();
+
+        repl.step(&[]).unwrap();
+        let output = repl.take_output();
+        assert!(
+            output.contains(
+                "Error: (tick!) can't run while a tick is paused; (continue) or (abort) it first\n"
+            ),
+            "{output}"
+        );
+        assert_eq!(repl.tick_executor.tick_number(), 1);
+    }
+
+    #[test]
+    fn ticks_export_as_nested_chrome_trace_spans() {
+        let path = std::env::temp_dir().join("longtable_test_export_trace.json");
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval("(trace :on)").unwrap();
+        repl.step(&[]).unwrap();
+        let exported = repl
+            .eval(&format!("(export-trace! \"{}\")", path.display()))
+            .unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+        let spans: Vec<_> = json["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                format!(
+                    "{} {}",
+                    e["ph"].as_str().unwrap(),
+                    e["name"].as_str().unwrap()
+                )
+            })
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                "B tick 1",
+                "B input",
+                "E input",
+                "B firing",
+                "E firing",
+                "B constraints",
+                "E constraints",
+                "E tick 1"
+            ]
+        );
+        assert_eq!(exported, Value::Int(8));
+    }
+
+    #[test]
+    fn rules_loaded_from_files_run_their_bodies() {
+        let dir = std::env::temp_dir().join("longtable_test_rule_bodies");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("rules.lt");
+        fs::write(
+            &path,
+            "(component: health :current :int)
+             (component: dead :bool :default true)
+             (rule: bleed :where [[?e :health ?h] [(> (get ?h :current) 0)]]
+               :then [(set-component! ?e :health {:current (dec (get ?h :current))})])
+             (rule: die :where [[?e :health ?h] [(= (get ?h :current) 0)]]
+               :then [(set-component! ?e :dead true)])
+             (spawn: troll :health {:current 1})",
+        )
+        .unwrap();
+
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.load_file(path.to_str().unwrap()).unwrap();
+        let result = repl.step(&[]).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        // The second rule sees the first one's write in the same tick
+        assert_eq!(result.activations_fired, 2);
+        let troll = repl.session().get_entity("troll").unwrap();
+        let world = repl.session().world();
+        let [health, current, dead] =
+            ["health", "current", "dead"].map(|k| world.interner().lookup_keyword(k).unwrap());
+        assert_eq!(
+            world.get_field(troll, health, current).unwrap(),
+            Some(Value::Int(0))
+        );
+        assert_eq!(world.get(troll, dead).unwrap(), Some(Value::Bool(true)));
+    }
+
+    #[test]
+    fn failing_rule_bodies_are_traced_with_their_location() {
+        use longtable_debug::TraceEvent;
+
+        let dir = std::env::temp_dir().join("longtable_test_failing_rule_bodies");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("rules.lt");
+        fs::write(
+            &path,
+            "(rule: halve\n  :where [[?e :hp ?hp]]\n  :then [(/ 10 (count []))])",
+        )
+        .unwrap();
+
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval("(component: hp :bool :default true) (spawn: troll :hp true) (trace :on)")
+            .unwrap();
+        repl.load_file(path.to_str().unwrap()).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(repl.step(&[]).is_err());
+        let errors: Vec<_> = repl
+            .session()
+            .tracer()
+            .buffer()
+            .iter()
+            .filter_map(|r| match &r.event {
+                TraceEvent::RuleError { file, line, .. } => Some((file.clone(), *line)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors, [(Some(path.display().to_string()), Some(3))]);
+    }
+
+    #[test]
+    fn agenda_lists_and_cancels_pending_activations() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            "(component: glow :level :int)
+             (rule: dim :salience 5 :where [[?e :glow ?g]] :then [])
+             (rule: flicker :where [[?e :glow ?g]] :then [])
+             (spawn: lamp :glow {:level 3})",
+        )
+        .unwrap();
+
+        let Value::Vec(agenda) = repl.eval("(agenda)").unwrap() else {
+            panic!("agenda didn't return a vector");
+        };
+        assert_eq!(agenda.len(), 2);
+        let output = repl.take_output();
+        let first = output.lines().next().unwrap();
+        assert!(
+            first.starts_with("#1") && first.contains(":dim"),
+            "{output}"
+        );
+        assert!(
+            first.contains("salience 5") && first.contains("?e="),
+            "{output}"
+        );
+
+        // Cancelling keeps the remaining activation's id
+        repl.eval("(cancel-activation! 2)").unwrap();
+        let Value::Vec(agenda) = repl.eval("(agenda)").unwrap() else {
+            panic!("agenda didn't return a vector");
+        };
+        assert_eq!(agenda.len(), 1);
+        assert!(repl.take_output().starts_with("#1"));
+        assert!(repl.eval("(cancel-activation! 2)").is_err());
+
+        // The cancelled activation skips one run of the rules
+        let interner = repl.session().world().interner();
+        let [dim, flicker] = ["dim", "flicker"].map(|k| interner.lookup_keyword(k).unwrap());
+        let result = repl.step(&[]).unwrap();
+        assert_eq!(result.rule_metrics.get(dim).unwrap().activations, 1);
+        assert_eq!(
+            result
+                .rule_metrics
+                .get(flicker)
+                .map_or(0, |stats| stats.activations),
+            0
+        );
+        let result = repl.step(&[]).unwrap();
+        assert_eq!(result.rule_metrics.get(flicker).unwrap().activations, 1);
+    }
+
+    #[test]
+    fn rule_stats_total_each_rules_cost() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            "(component: glow :level :int)
+             (rule: dim :where [[?e :glow ?g]] :then [])
+             (spawn: lamp :glow {:level 3})
+             (spawn: torch :glow {:level 5})",
+        )
+        .unwrap();
+        assert_eq!(repl.eval("(rule-stats)").unwrap(), Value::Int(0));
+        assert_eq!(repl.take_output(), "No rules have run\n");
+
+        let result = repl.step(&[]).unwrap();
+        let dim = repl
+            .session()
+            .world()
+            .interner()
+            .lookup_keyword("dim")
+            .unwrap();
+        assert_eq!(result.rule_metrics.get(dim).unwrap().activations, 2);
+        repl.step(&[]).unwrap();
+
+        assert_eq!(repl.eval("(rule-stats)").unwrap(), Value::Int(1));
+        let output = repl.take_output();
+        let row = output.lines().nth(1).unwrap();
+        assert!(row.starts_with(":dim "), "{output}");
+        assert_eq!(row.split_whitespace().nth(1), Some("4"), "{output}");
+
+        repl.eval("(rule-stats :reset)").unwrap();
+        assert_eq!(repl.eval("(rule-stats)").unwrap(), Value::Int(0));
+    }
+
+    #[test]
+    fn coverage_counts_only_rules_whose_bodies_ran() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: glow :level :int)
+             (rule: dim :where [[?e :glow ?g] [(> (get ?g :level) 0)]]
+               :then [(set-component! ?e :glow {:level 0})])
+             (spawn: lamp :glow {:level 3})",
+        )
+        .unwrap();
+        repl.eval("(tick!)").unwrap();
+        assert!(repl.coverage_report().unfired_rules.is_empty());
+
+        // A body that fails rolls its tick back, so its rule never fired
+        repl.eval("(rule: flicker :where [[?e :glow ?g]] :then [(/ 1 (get ?g :level))])")
+            .unwrap();
+        assert!(repl.eval("(tick!)").is_err());
+        assert_eq!(repl.coverage_report().unfired_rules, vec!["flicker"]);
+    }
+
+    #[test]
+    fn coverage_report_names_rules_and_syntaxes_that_never_ran() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            "(component: glow :level :int)
+             (component: smoke :density :int)
+             (rule: dim :where [[?e :glow ?g]] :then [])
+             (rule: clear-smoke :where [[?e :smoke ?s]] :then [])
+             (verb: look)
+             (verb: light)
+             (action: look :params [actor] :handler [])
+             (action: light :params [actor] :handler [])
+             (command: look-around :syntax [:verb/look] :action look)
+             (command: light-it :syntax [:verb/light] :action light)
+             (spawn: player :glow {:level 3})",
+        )
+        .unwrap();
+        repl.eval("(tick!)").unwrap();
+        repl.input("look").unwrap();
+        repl.take_output();
+
+        let report = repl.coverage_report();
+        assert_eq!(report.unfired_rules, vec!["clear-smoke"]);
+        assert_eq!(report.unmatched_syntaxes, vec!["light-it"]);
+        let Value::Map(missing) = repl.eval("(coverage-report)").unwrap() else {
+            panic!("coverage-report returns a map")
+        };
+        let interner = repl.session().world().interner();
+        let kw = |name| Value::Keyword(interner.lookup_keyword(name).unwrap());
+        assert_eq!(
+            missing.get(&kw("rules")),
+            Some(&Value::Vec(std::iter::once(kw("clear-smoke")).collect()))
+        );
+        assert_eq!(
+            missing.get(&kw("syntaxes")),
+            Some(&Value::Vec(std::iter::once(kw("light-it")).collect()))
+        );
+        assert_eq!(
+            repl.take_output(),
+            "rules: 1 of 2 fired\n  never fired: :clear-smoke\n\
+             syntaxes: 1 of 2 matched\n  never matched: :light-it\n"
+        );
+
+        repl.eval("(coverage-report :reset)").unwrap();
+        assert_eq!(repl.coverage_report().unfired_rules.len(), 2);
+    }
+
+    #[test]
+    fn destroy_cascades_and_traces_each_step() {
+        use longtable_debug::TraceEvent;
+
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(relationship: part-of :cardinality :many-to-one :on-target-delete :cascade)
+             (spawn: chest)
+             (spawn: lid)
+             (link: lid :part-of chest)
+             (trace :on)",
+        )
+        .unwrap();
+        let chest = repl.session().get_entity("chest").unwrap();
+        let lid = repl.session().get_entity("lid").unwrap();
+        repl.eval(&format!(
+            "(destroy! (entity-ref {} {}))",
+            chest.index, chest.generation
+        ))
+        .unwrap();
+
+        // The lid went with the chest
+        assert!(!repl.session().world().exists(lid));
+        let cascades: Vec<_> = repl
+            .session()
+            .tracer()
+            .buffer()
+            .iter()
+            .filter(|r| matches!(r.event, TraceEvent::RelationshipCascade { .. }))
+            .collect();
+        assert_eq!(cascades.len(), 1);
+    }
+
+    // ==================== Explain System Tests ====================
+
+    #[test]
+    fn why_unknown_entity() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // why with unknown entity name should error
+        let result = repl.eval("(why player :health)");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("unknown entity"));
+    }
+
+    #[test]
+    fn why_requires_component() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // why with no component should error
+        let result = repl.eval("(why 0)");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn why_with_entity_ref() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Define component
+        repl.eval("(component: tag/player :bool :default true)")
+            .unwrap();
+
+        // Spawn entity
+        repl.eval("(spawn: player :tag/player true)").unwrap();
+
+        // why with entity by name should work (even if no provenance recorded)
+        let result = repl.eval("(why player :tag/player)");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn why_with_depth() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Define component
+        repl.eval("(component: tag/player :bool :default true)")
+            .unwrap();
+
+        // Spawn entity
+        repl.eval("(spawn: player :tag/player true)").unwrap();
+
+        // why with depth should work
+        let result = repl.eval("(why player :tag/player :depth 3)");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn explain_query_basic() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Define component
+        repl.eval("(component: tag/player :bool :default true)")
+            .unwrap();
+
+        // Spawn entity
+        repl.eval("(spawn: player :tag/player true)").unwrap();
+
+        // explain-query should work
+        let result = repl.eval("(explain-query (query :where [[?e :tag/player]] :return ?e))");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn explain_query_with_entity() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor);
+
+        // Define component
+        repl.eval("(component: tag/player :bool :default true)")
+            .unwrap();
+
+        // Spawn entity
+        repl.eval("(spawn: player :tag/player true)").unwrap();
+
+        // explain-query with entity should work
+        let result =
+            repl.eval("(explain-query (query :where [[?e :tag/player]] :return ?e) player)");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn json_export_and_import_roundtrip() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: health :current :int)
+             (component: home :room :entity)
+             (relationship: in-room :cardinality :many-to-one)
+             (spawn: hall :health {:current 1})
+             (spawn: knight :health {:current 5})
+             (link: knight :in-room hall)",
+        )
+        .unwrap();
+        let hall = repl.session().get_entity("hall").unwrap();
+        let knight = repl.session().get_entity("knight").unwrap();
+        repl.eval(&format!(
+            "(set-component! (entity-ref {} {}) :home {{:room (entity-ref {} {})}})",
+            knight.index, knight.generation, hall.index, hall.generation
+        ))
+        .unwrap();
+
+        let path = std::env::temp_dir().join("longtable_repl_export.json");
+        let path_str = path.display().to_string().replace('\\', "/");
+        repl.eval(&format!("(export-json! \"{path_str}\")"))
+            .unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("\"name\": \"knight\""), "{text}");
+
+        let edited = text.replace("\"current\": 5", "\"current\": 9");
+        std::fs::write(&path, edited).unwrap();
+        repl.eval(&format!("(import-json! \"{path_str}\")"))
+            .unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let world = repl.session().world();
+        let knight = repl.session().get_entity("knight").unwrap();
+        let hall = repl.session().get_entity("hall").unwrap();
+        let interner = world.interner();
+        let health = interner.lookup_keyword("health").unwrap();
+        let home = interner.lookup_keyword("home").unwrap();
+        let room = interner.lookup_keyword("room").unwrap();
+        let in_room = interner.lookup_keyword("in-room").unwrap();
+        assert_eq!(
+            repl.format_value_inner(&world.get(knight, health).unwrap().unwrap()),
+            "{:current 9}"
+        );
+        assert_eq!(
+            world.get_field(knight, home, room).unwrap(),
+            Some(Value::EntityRef(hall))
+        );
+        assert_eq!(world.targets(knight, in_room).collect::<Vec<_>>(), [hall]);
+    }
+
+    #[test]
+    fn why_explains_spawns_and_links() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            "(component: tag/player :bool :default true)
+             (component: glow :level :int)
+             (relationship: carries)
+             (verb: take)
+             (action: take-lamp :params [actor]
+               :handler [(link! ?actor :carries (entity-ref 1 1))])
+             (command: take-it :syntax [:verb/take] :action take-lamp)
+             (spawn: player :tag/player true)
+             (spawn: lamp :glow {:level 3})",
+        )
+        .unwrap();
+        let player = repl.session().get_entity("player").unwrap();
+        let lamp = repl.session().get_entity("lamp").unwrap();
+        assert_eq!((lamp.index, lamp.generation), (1, 1));
+
+        let data = repl.eval("(why lamp :exists :data true)").unwrap();
+        let text = repl.format_value_inner(&data);
+        assert!(text.contains(":rule :repl"), "{text}");
+        assert!(text.contains(":change :spawned"), "{text}");
+
+        repl.input("take").unwrap();
+        let data = repl
+            .eval("(why player :rel/carries lamp :data true)")
+            .unwrap();
+        let text = repl.format_value_inner(&data);
+        assert!(text.contains(":linked true"), "{text}");
+        assert!(text.contains(":rule :take-lamp"), "{text}");
+        assert!(text.contains("\"actor\""), "{text}");
+
+        repl.eval(&format!(
+            "(unlink! (entity-ref {} {}) :carries (entity-ref 1 1))",
+            player.index, player.generation
+        ))
+        .unwrap();
+        let data = repl.eval("(why player :carries lamp :data true)").unwrap();
+        let text = repl.format_value_inner(&data);
+        assert!(text.contains(":linked false"), "{text}");
+        let Value::Map(map) = &data else {
+            panic!("expected a map, got {text}");
+        };
+        let history = repl
+            .session()
+            .world()
+            .interner()
+            .lookup_keyword("history")
+            .unwrap();
+        let Some(Value::Vec(history)) = map.get(&Value::Keyword(history)) else {
+            panic!("expected a history, got {text}");
+        };
+        let changes: Vec<String> = history.iter().map(|c| repl.format_value_inner(c)).collect();
+        assert_eq!(changes.len(), 2, "{text}");
+        assert!(changes[0].contains(":change :unlinked") && changes[0].contains(":rule :repl"));
+        assert!(changes[1].contains(":change :linked") && changes[1].contains(":rule :take-lamp"));
+
+        assert!(repl.eval("(why player :health lamp)").is_err());
+    }
+
+    #[test]
+    fn spawned_declarations_get_content_addressed_ids() {
+        const CONTENT: &str = "(component: glow :level :int)
+                               (spawn: lamp :glow {:level 3})";
+
+        let mut first = Repl::with_editor(MockEditor::new(vec![]));
+        first.eval("(content-ids! true)").unwrap();
+        first.eval(CONTENT).unwrap();
+        let lamp = first.session().get_entity("lamp").unwrap();
+        assert_eq!(lamp.index, longtable_storage::content_index("lamp"));
+
+        // Unrelated spawns before loading don't change the ID
+        let mut second = Repl::with_editor(MockEditor::new(vec![]));
+        second.eval("(content-ids! true)").unwrap();
+        second.eval("(spawn: torch)").unwrap();
+        second.eval(CONTENT).unwrap();
+        assert_eq!(second.session().get_entity("lamp"), Some(lamp));
+
+        // Declaring the name again updates the live entity
+        let again = second.eval("(spawn: lamp :glow {:level 5})").unwrap();
+        assert_eq!(again, Value::EntityRef(lamp));
+        assert_eq!(second.session().world().entity_count(), 2);
+        let world = second.session().world();
+        let glow = world.interner().lookup_keyword("glow").unwrap();
+        let Ok(Some(Value::Map(value))) = world.get(lamp, glow) else {
+            panic!("lamp has no glow");
+        };
+        let level = world.interner().lookup_keyword("level").unwrap();
+        assert_eq!(value.get(&Value::Keyword(level)), Some(&Value::Int(5)));
+    }
+
+    #[test]
+    fn completions_come_from_the_session() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        assert!(!repl.completions().contains(&":glow".to_string()));
+
+        repl.eval(
+            "(component: glow :level :int)
+             (relationship: lit-by)
+             (rule: dim :where [[?e :glow ?g]] :then [])
+             (spawn: lamp :glow {:level 3})
+             (def brightness 7)",
+        )
+        .unwrap();
+
+        let completions = repl.completions();
+        for name in ["inspect", ":glow", ":lit-by", "dim", "lamp", "brightness"] {
+            assert!(completions.contains(&name.to_string()), "missing {name}");
+        }
+    }
+
+    #[test]
+    fn explain_query_returns_data() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: health :current :int)
+             (component: armor :value :int)
+             (spawn: knight :health {:current 5} :armor {:value 2})
+             (spawn: peasant :health {:current 3})",
+        )
+        .unwrap();
+
+        let data = repl
+            .eval(
+                "(explain-query (query :where [[?e :health ?h] [?e :armor ?a]] :return ?e)
+                                peasant :data true)",
+            )
+            .unwrap();
+        let text = repl.format_value_inner(&data);
+        assert!(text.contains(":results 1"), "{text}");
+        assert!(text.contains("\"[?e :health ?h]\""), "{text}");
+        // The planner matches the rarer :armor first
+        assert!(text.contains("\"[?e :armor ?a]\""), "{text}");
+        assert!(
+            text.contains(":matches 1") && !text.contains(":matches 2"),
+            "{text}"
+        );
+        assert!(text.contains(":matched false"), "{text}");
+        assert!(text.contains(":kind :missing-component"), "{text}");
+        assert!(text.contains(":component :armor"), "{text}");
+    }
+
+    #[test]
+    fn explain_plan_shows_the_chosen_join_order() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            "(component: health :current :int)
+             (component: crown :jewels :int)
+             (relationship: in-room)
+             (spawn: hall)
+             (spawn: king :health {:current 5} :crown {:jewels 3})
+             (spawn: guard :health {:current 4})
+             (spawn: cook :health {:current 2})
+             (link: king :in-room hall)
+             (link: guard :in-room hall)",
+        )
+        .unwrap();
+        repl.take_output();
+
+        let query = "(query :where [[?e :health ?h] [?e :in-room ?r] [?e :crown ?c]] :return ?e)";
+        let order = repl.eval(&format!("(explain-plan {query})")).unwrap();
+        assert_eq!(
+            repl.format_value_inner(&order),
+            r#"["[?e :crown ?c]" "[?e :health ?h]" "[?e :in-room ?r]"]"#
+        );
+        let out = repl.take_output();
+        assert!(out.contains("1. [?e :crown ?c]"), "{out}");
+        assert!(out.contains("scan (1)"), "{out}");
+        assert!(out.contains("(written 3)"), "{out}");
+        assert!(out.contains("traverse (~1 per source)"), "{out}");
+
+        // Reordering doesn't change what the query finds
+        let king = repl.session().get_entity("king").unwrap();
+        assert_eq!(
+            repl.eval(query).unwrap(),
+            Value::Vec(std::iter::once(Value::EntityRef(king)).collect())
+        );
+    }
+
+    #[test]
+    fn queries_find_entities_without_something() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: health :current :int)
+             (component: armor :value :int)
+             (component: cursed :bool :default true)
+             (relationship: holds)
+             (spawn: knight :health {:current 5} :armor {:value 2})
+             (spawn: peasant :health {:current 3})
+             (spawn: thief :health {:current 4})
+             (spawn: dagger :cursed true)
+             (link: thief :holds dagger)",
+        )
+        .unwrap();
+        let mut names = |query: &str| -> Vec<String> {
+            let Value::Vec(found) = repl.eval(query).unwrap() else {
+                panic!("{query} didn't return a vector");
+            };
+            let mut names: Vec<String> = ["knight", "peasant", "thief"]
+                .into_iter()
+                .filter(|name| {
+                    let entity = repl.session().get_entity(name).unwrap();
+                    found.iter().any(|v| v == &Value::EntityRef(entity))
+                })
+                .map(str::to_string)
+                .collect();
+            names.sort();
+            names
+        };
 
-        // Flush to ensure banner appears
-        let _ = io::stdout().flush();
+        assert_eq!(
+            names("(query :where [[?e :health ?h] (not [?e :armor])] :return ?e)"),
+            ["peasant", "thief"]
+        );
+        assert_eq!(
+            names(
+                "(query :where [[?e :health ?h] (not-join [?e] [?e :holds ?i] [?i :cursed])]
+                        :return ?e)"
+            ),
+            ["knight", "peasant"]
+        );
+        // An unbound negated variable ranges over every entity
+        assert_eq!(
+            names("(query :where [[?e :health ?h] (not [?x :cursed])] :return ?e)"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            names("(query :where [[?e :health ?h] (not [?x :armor 7])] :return ?e)").len(),
+            3
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn predicate_clauses_filter_matches() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: health :current :int)
+             (component: armor :value :int)
+             (spawn: knight :health {:current 9} :armor {:value 4})
+             (spawn: squire :health {:current 2} :armor {:value 1})
+             (spawn: page :health {:current 6} :armor {:value 8})
+             (rule: sturdy
+               :where [[?e :health ?h] [(> (get ?h :current) 3)] [?e :armor ?a] [(< (get ?a :value) 5)]]
+               :then [])",
+        )
+        .unwrap();
+
+        let Value::Vec(found) = repl
+            .eval(
+                "(query :where [[?e :health ?h] [(> (get ?h :current) 3)] [?e :armor ?a] [(< (get ?a :value) 5)]]
+                        :return ?e)",
+            )
+            .unwrap()
+        else {
+            panic!("query didn't return a vector");
+        };
+        let knight = Value::EntityRef(repl.session().get_entity("knight").unwrap());
+        assert_eq!(found.iter().collect::<Vec<_>>(), [&knight]);
+
+        // Rules keep their predicates through registration
+        let rule = &repl.session().compiled_rules()[0];
+        assert_eq!(rule.pattern.predicates.len(), 2);
+        let activations = longtable_engine::ProductionRuleEngine::new()
+            .find_activations(&[rule.clone().into()], repl.session().world())
+            .unwrap();
+        assert_eq!(activations.len(), 1);
 
-    /// A simple mock editor for testing.
-    struct MockEditor {
-        inputs: Vec<String>,
-        index: usize,
+        // A predicate that fails to evaluate fails the query
+        let err = repl
+            .eval("(query :where [[?e :health ?h] [(> (/ (get ?h :current) 0) 1)]] :return ?e)")
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::DivisionByZero), "{err}");
     }
 
-    impl MockEditor {
-        fn new(inputs: Vec<&str>) -> Self {
-            Self {
-                inputs: inputs.into_iter().map(String::from).collect(),
-                index: 0,
-            }
-        }
+    #[test]
+    fn rules_keep_their_refraction() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: npc :bool :default true)
+             (rule: greet :refraction :once-per-entity :where [[?e :npc]] :then [])",
+        )
+        .unwrap();
+
+        let rule = &repl.session().compiled_rules()[0];
+        assert_eq!(
+            rule.refraction,
+            longtable_language::declaration::Refraction::OncePerEntity
+        );
     }
 
-    impl LineEditor for MockEditor {
-        fn read_line(&mut self, _prompt: &str) -> Result<ReadResult> {
-            if self.index < self.inputs.len() {
-                let line = self.inputs[self.index].clone();
-                self.index += 1;
-                Ok(ReadResult::Line(line))
-            } else {
-                Ok(ReadResult::Eof)
-            }
-        }
+    #[test]
+    fn restoring_a_world_forgets_which_activations_fired() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: npc :bool :default true)
+             (rule: greet :refraction :once-per-entity :where [[?e :npc]] :then [])
+             (spawn: guard :npc true)",
+        )
+        .unwrap();
+        let saved = repl.eval("(save-state)").unwrap();
+        repl.sync_rules();
+
+        assert_eq!(repl.step(&[]).unwrap().activations_fired, 1);
+        assert_eq!(repl.step(&[]).unwrap().activations_fired, 0);
+
+        let Value::Int(saved) = saved else {
+            panic!("expected a snapshot id, got {saved:?}");
+        };
+        repl.eval(&format!("(restore-state {saved})")).unwrap();
+        assert_eq!(repl.step(&[]).unwrap().activations_fired, 1);
+    }
 
-        fn read_continuation(&mut self, prompt: &str) -> Result<ReadResult> {
-            self.read_line(prompt)
+    #[test]
+    fn or_matches_the_union_of_its_branches() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: health :current :int)
+             (component: hungry :bool :default true)
+             (component: tired :bool :default true)
+             (component: cold :bool :default true)
+             (spawn: baker :health {:current 5} :hungry true)
+             (spawn: miner :health {:current 4} :tired true :cold true)
+             (spawn: smith :health {:current 3} :tired true)
+             (spawn: cook :health {:current 2} :hungry true :tired true :cold true)
+             (rule: comfort
+               :where [[?e :health ?h] (or [?e :hungry] (and [?e :tired] [?e :cold]))]
+               :then [])",
+        )
+        .unwrap();
+
+        let Value::Vec(found) = repl
+            .eval(
+                "(query :where [[?e :health ?h] (or [?e :hungry] (and [?e :tired] [?e :cold]))]
+                        :return ?e)",
+            )
+            .unwrap()
+        else {
+            panic!("query didn't return a vector");
+        };
+        let entity = |name| Value::EntityRef(repl.session().get_entity(name).unwrap());
+        // The cook matches both branches but is found once; the smith neither
+        assert_eq!(found.len(), 3);
+        for name in ["baker", "miner", "cook"] {
+            assert!(found.iter().any(|v| v == &entity(name)), "{name}");
         }
 
-        fn add_history(&mut self, _line: &str) {}
+        // Rules keep the disjunction through registration
+        let rule = &repl.session().compiled_rules()[0];
+        assert_eq!(rule.pattern.disjunctions.len(), 1);
+        let activations = longtable_engine::ProductionRuleEngine::new()
+            .find_activations(&[rule.clone().into()], repl.session().world())
+            .unwrap();
+        assert_eq!(activations.len(), 3);
+    }
 
-        fn set_keywords(&mut self, _keywords: Vec<String>) {}
+    #[test]
+    fn why_returns_data() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: counter :value :int)
+             (spawn: clock :counter {:value 0})",
+        )
+        .unwrap();
+        let clock = repl.session().get_entity("clock").unwrap();
+        let value = repl.eval("{:value 1}").unwrap();
+        let counter = repl
+            .session()
+            .world()
+            .interner()
+            .lookup_keyword("counter")
+            .unwrap();
+        repl.step(&[InputEvent::Set {
+            entity: clock,
+            component: counter,
+            value,
+        }])
+        .unwrap();
+
+        let data = repl.eval("(why clock :counter :data true)").unwrap();
+        let text = repl.format_value_inner(&data);
+        assert!(text.contains(":status :found"), "{text}");
+        assert!(text.contains(":tick 1"), "{text}");
+        assert!(text.contains(":value {:value 1}"), "{text}");
+
+        let data = repl
+            .eval("(why clock :health :depth 2 :data true)")
+            .unwrap();
+        let text = repl.format_value_inner(&data);
+        assert!(!text.contains(":status :found"), "{text}");
+        assert!(repl.eval("(why clock :counter :data)").is_err());
     }
 
     #[test]
-    fn eval_simple_expression() {
+    fn explain_query_invalid_form() {
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
 
-        let result = repl.eval("(+ 1 2)").unwrap();
-        assert_eq!(result, Value::Int(3));
+        // explain-query with non-query should error
+        let result = repl.eval("(explain-query 42)");
+        assert!(result.is_err());
     }
 
+    // ==================== Time Travel Tests ====================
+
     #[test]
-    fn eval_def_creates_variable() {
+    fn timeline_shows_status() {
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
 
-        repl.eval("(def x 42)").unwrap();
-        assert_eq!(repl.session.get_variable("x"), Some(&Value::Int(42)));
-
-        // Note: Using session variables in subsequent expressions requires
-        // VM global variable support, which is not yet implemented.
-        // For now, we just verify the variable is stored in the session.
+        // timeline command should work
+        let result = repl.eval("(timeline)");
+        assert!(result.is_ok());
     }
 
     #[test]
-    fn eval_def_with_expression() {
+    fn rules_are_ordered_by_before_and_after() {
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
 
-        repl.eval("(def sum (+ 10 20))").unwrap();
-        assert_eq!(repl.session.get_variable("sum"), Some(&Value::Int(30)));
+        repl.eval("(component: health :current :int)").unwrap();
+        repl.eval("(rule: log :after [damage] :where [[?e :health ?hp]] :then [])")
+            .unwrap();
+        repl.eval("(rule: damage :salience -10 :where [[?e :health ?hp]] :then [])")
+            .unwrap();
+        repl.eval("(rule: armor :before [damage] :where [[?e :health ?hp]] :then [])")
+            .unwrap();
+
+        let world = repl.session().world();
+        let names: Vec<_> = repl
+            .session()
+            .compiled_rules()
+            .iter()
+            .map(|r| world.interner().get_keyword(r.name).unwrap().to_string())
+            .collect();
+        assert_eq!(names, vec!["armor", "damage", "log"]);
+
+        // A cycle is rejected and the existing rules are kept
+        assert!(
+            repl.eval("(rule: cyclic :before [armor] :after [log] :then [])")
+                .is_err()
+        );
+        assert_eq!(repl.session().compiled_rule_count(), 3);
     }
 
     #[test]
-    fn is_complete_balanced() {
+    fn rule_groups_can_be_toggled() {
         let editor = MockEditor::new(vec![]);
-        let repl = Repl::with_editor(editor);
+        let mut repl = Repl::with_editor(editor);
 
-        assert!(repl.is_complete("(+ 1 2)"));
-        assert!(repl.is_complete("[1 2 3]"));
-        assert!(repl.is_complete("{:a 1}"));
-        assert!(repl.is_complete("42"));
-        assert!(repl.is_complete(""));
+        repl.eval("(component: health :current :int)").unwrap();
+        repl.eval(
+            "(rule-group: combat
+               (rule: attack :where [[?e :health ?hp]] :then [])
+               (rule: defend :where [[?e :health ?hp]] :then []))",
+        )
+        .unwrap();
+
+        let world = repl.session().world();
+        let groups: Vec<_> = repl
+            .session()
+            .compiled_rules()
+            .iter()
+            .map(|r| {
+                world
+                    .interner()
+                    .get_keyword(r.group.unwrap())
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(groups, vec!["combat", "combat"]);
+
+        let combat = repl
+            .session()
+            .world()
+            .interner()
+            .lookup_keyword("combat")
+            .unwrap();
+        repl.eval("(disable-group! :combat)").unwrap();
+        assert!(!repl.tick_executor.rule_engine().is_group_enabled(combat));
+        repl.eval("(enable-group! :combat)").unwrap();
+        assert!(repl.tick_executor.rule_engine().is_group_enabled(combat));
+
+        assert!(repl.eval("(disable-group! combat)").is_err());
     }
 
     #[test]
-    fn is_complete_unbalanced() {
+    fn validate_reports_no_issues_for_clean_world() {
         let editor = MockEditor::new(vec![]);
-        let repl = Repl::with_editor(editor);
+        let mut repl = Repl::with_editor(editor);
 
-        assert!(!repl.is_complete("(+ 1"));
-        assert!(!repl.is_complete("[1 2"));
-        assert!(!repl.is_complete("{:a"));
-        assert!(!repl.is_complete("\"hello"));
+        repl.eval("(component: health :current :int)").unwrap();
+        repl.eval("(spawn: player :health {:current 10})").unwrap();
+
+        let result = repl.eval("(validate)").unwrap();
+        assert_eq!(result, Value::Vec(longtable_foundation::LtVec::new()));
+
+        repl.eval("(component: owner :value :entity)").unwrap();
+        repl.eval("(spawn: goblin)").unwrap();
+        let player = repl.session().get_entity("player").unwrap();
+        let goblin = repl.session().get_entity("goblin").unwrap();
+        let player = format!("(entity-ref {} {})", player.index, player.generation);
+        let goblin_ref = format!("(entity-ref {} {})", goblin.index, goblin.generation);
+        repl.eval(&format!(
+            "(set-component! {goblin_ref} :owner {{:value {player}}})"
+        ))
+        .unwrap();
+        repl.eval(&format!("(destroy! {player})")).unwrap();
+
+        let Value::Vec(issues) = repl.eval("(validate)").unwrap() else {
+            panic!("expected a vector of issues");
+        };
+        assert_eq!(issues.len(), 1);
+        let Value::Map(issue) = issues.get(0).unwrap() else {
+            panic!("expected an issue map");
+        };
+        let interner = repl.session().world().interner();
+        let key = |name: &str| Value::Keyword(interner.lookup_keyword(name).unwrap());
+        assert_eq!(issue.get(&key("kind")), Some(&key("dangling-reference")));
+        assert_eq!(issue.get(&key("entity")), Some(&Value::EntityRef(goblin)));
+        let Some(Value::String(message)) = issue.get(&key("message")) else {
+            panic!("expected a message");
+        };
+        assert!(message.contains("refers to missing entity"), "{message}");
     }
 
     #[test]
-    fn is_complete_nested() {
+    fn undo_and_redo_spawn() {
         let editor = MockEditor::new(vec![]);
-        let repl = Repl::with_editor(editor);
+        let mut repl = Repl::with_editor(editor);
 
-        assert!(repl.is_complete("(if (> x 0) (+ x 1) (- x 1))"));
-        assert!(!repl.is_complete("(if (> x 0) (+ x 1)"));
+        repl.eval("(component: health :current :int)").unwrap();
+        let before = repl.session().world().entity_count();
+        repl.eval("(spawn: player :health {:current 10})").unwrap();
+        assert_eq!(repl.session().world().entity_count(), before + 1);
+
+        let player = repl.session().get_entity("player");
+        assert!(player.is_some());
+
+        repl.eval("(undo!)").unwrap();
+        assert_eq!(repl.session().world().entity_count(), before);
+        assert!(repl.session().get_entity("player").is_none());
+
+        repl.eval("(redo!)").unwrap();
+        assert_eq!(repl.session().world().entity_count(), before + 1);
+        assert_eq!(repl.session().get_entity("player"), player);
+
+        // Nothing left to redo
+        assert!(repl.eval("(redo!)").is_err());
     }
 
     #[test]
-    fn is_complete_string_with_brackets() {
-        let editor = MockEditor::new(vec![]);
-        let repl = Repl::with_editor(editor);
-
-        // Brackets inside strings should be ignored
-        assert!(repl.is_complete("\"hello (world\""));
-        assert!(repl.is_complete("(str \"[test]\")"));
+    fn failed_effects_and_do_blocks_change_nothing() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval("(component: health :current :int)").unwrap();
+        repl.eval("(spawn: player :health {:current 10})").unwrap();
+        let player = repl.session().get_entity("player").unwrap();
+        let player = format!("(entity-ref {} {})", player.index, player.generation);
+        let before = repl.session().world().entity_count();
+        let undo_depth = repl.session().undo_depth();
+
+        // The spawn would succeed, but the write to an unknown component
+        // fails, so neither applies
+        assert!(
+            repl.eval("(let [e (spawn! {:health {:current 1}})] (set-component! e :mana 3))")
+                .is_err()
+        );
+        assert_eq!(repl.session().world().entity_count(), before);
+        assert_eq!(repl.session().undo_depth(), undo_depth);
+
+        // A do! block's forms see each other's effects, and it undoes as one step
+        repl.eval(&format!(
+            "(do! (spawn: goblin :health {{:current 3}})
+                  (set-component! {player} :health {{:current 2}}))"
+        ))
+        .unwrap();
+        assert_eq!(repl.session().world().entity_count(), before + 1);
+        assert_eq!(repl.session().undo_depth(), undo_depth + 1);
+        repl.eval("(undo!)").unwrap();
+        assert_eq!(repl.session().world().entity_count(), before);
+
+        // A later form failing takes back the earlier ones
+        assert!(
+            repl.eval(&format!(
+                "(do! (spawn: orc :health {{:current 5}}) (set-component! {player} :mana 1))"
+            ))
+            .is_err()
+        );
+        assert_eq!(repl.session().world().entity_count(), before);
+        assert_eq!(repl.session().undo_depth(), undo_depth);
+        assert!(repl.session().get_entity("orc").is_none());
     }
 
-    // ==================== Error Recovery Tests ====================
+    #[test]
+    fn effect_middleware_sees_repl_and_tick_effects() {
+        use longtable_engine::Verdict;
+        use std::sync::{Arc, Mutex};
+
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval("(component: health :current :int)").unwrap();
+        repl.eval("(on-phase :after-inputs (fn [ctx] (spawn! {:health {:current 1}})))")
+            .unwrap();
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&log);
+        repl.middleware_mut()
+            .register("log", -1, move |effect: VmEffect, _: &World| {
+                seen.lock().unwrap().push(format!("{effect:?}"));
+                Verdict::Apply(effect)
+            });
+        repl.middleware_mut()
+            .register("guard", 0, |effect, _: &World| match effect {
+                VmEffect::Emit { .. } => Verdict::Drop,
+                VmEffect::Spawn { .. } => Verdict::Veto("no spawning".to_string()),
+                other => Verdict::Apply(other),
+            });
+
+        let before = repl.session().world().entity_count();
+        repl.eval("(emit! :ping {})").unwrap();
+        assert_eq!(repl.session().world().entity_count(), before);
+
+        let err = repl.eval("(tick!)").unwrap_err();
+        assert!(
+            err.to_string().contains("vetoed by guard: no spawning"),
+            "{err}"
+        );
+        assert_eq!(repl.session().world().entity_count(), before);
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 2);
+        assert!(log[0].starts_with("Emit"));
+        assert!(log[1].starts_with("Spawn"));
+    }
 
     #[test]
-    fn error_recovery_syntax_error() {
+    fn phase_hooks_run_during_tick() {
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
 
-        // Syntax errors should not crash the REPL
-        let result = repl.eval("(+ 1 2");
-        assert!(result.is_err());
+        repl.eval("(component: health :current :int)").unwrap();
+        repl.eval(
+            "(on-phase :before-constraints (fn [ctx] (spawn! {:health {:current (get ctx :tick)}})))",
+        )
+        .unwrap();
+        let before = repl.session().world().entity_count();
 
-        // REPL should still be usable after error
-        let result = repl.eval("(+ 1 2)");
-        assert!(result.is_ok());
+        repl.eval("(tick!)").unwrap();
+        assert_eq!(repl.session().world().entity_count(), before + 1);
     }
 
     #[test]
-    fn error_recovery_undefined_function() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
+    fn session_commands_run_from_compiled_code_but_not_hooks() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
 
-        // Calling undefined function should return error
-        let result = repl.eval("(undefined-fn 1 2)");
-        assert!(result.is_err());
+        let advance = "((fn [n] (tick!) n) 7)";
+        assert_eq!(repl.eval(advance).unwrap(), Value::Int(7));
+        repl.eval("(do (tick!) :again)").unwrap();
+        assert_eq!(repl.tick_executor.tick_number(), 2);
 
-        // REPL should still be usable
-        let result = repl.eval("42");
-        assert!(result.is_ok());
+        // A hook that ticks would tick forever
+        repl.eval("(on-phase :after-commit (fn [ctx] (tick!)))")
+            .unwrap();
+        let err = repl.eval("(tick!)").unwrap_err();
+        assert!(err.to_string().contains("(tick!) needs a session"), "{err}");
+        assert!(!repl.ticking);
+
+        repl.ticking = true;
+        let err = repl.step(&[]).unwrap_err();
+        assert!(err.to_string().contains("tick is in progress"), "{err}");
     }
 
     #[test]
-    fn error_recovery_division_by_zero() {
+    fn reload_swaps_definitions_and_keeps_the_world() {
+        let dir = std::env::temp_dir().join("longtable_test_hot_reload");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("counter.lt");
+        let source = |step: i64, extra: &str| {
+            format!(
+                "(component: counter :value :int)
+                 (spawn: clock :counter {{:value 0}})
+                 (def step {step})
+                 (rule: count :where [[?e :counter/value ?v]]
+                   :then [(set! ?e :counter/value (+ ?v step))])
+                 {extra}"
+            )
+        };
+        fs::write(&path, source(1, "(verb: take :synonyms [get])")).unwrap();
+
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.load_file(path.to_str().unwrap()).unwrap();
+        let verb = |repl: &Repl<MockEditor>, word: &str| {
+            let word = repl.session().world().interner().lookup_keyword(word)?;
+            repl.session()
+                .vocabulary_registry()
+                .lookup_verb(word)
+                .cloned()
+        };
+        assert!(verb(&repl, "get").is_some());
+        assert_eq!(repl.watched_files().count(), 1);
+        let clock = repl.session().get_entity("clock").unwrap();
+        repl.eval(&format!(
+            "(set-component! (entity-ref {} {}) :counter {{:value 5}})",
+            clock.index, clock.generation
+        ))
+        .unwrap();
+        let value = |repl: &Repl<MockEditor>| {
+            let world = repl.session().world();
+            let counter = world.interner().lookup_keyword("counter").unwrap();
+            let field = world.interner().lookup_keyword("value").unwrap();
+            world.get_field(clock, counter, field).unwrap().unwrap()
+        };
+        assert_eq!(value(&repl), Value::Int(5));
+
+        // Edited definitions replace the old ones; the clock keeps its state
+        fs::write(
+            &path,
+            source(10, "(rule: noop :where [[?e :counter/value ?v]] :then [])"),
+        )
+        .unwrap();
+        repl.reload_file(&path).unwrap();
+        assert_eq!(repl.session().compiled_rule_count(), 2);
+        assert!(verb(&repl, "take").is_none() && verb(&repl, "get").is_none());
+        assert_eq!(repl.session().get_variable("step"), Some(&Value::Int(10)));
+        assert_eq!(repl.session().get_entity("clock"), Some(clock));
+        assert_eq!(value(&repl), Value::Int(5));
+
+        // Deleted rules go away, and a broken file changes nothing
+        fs::write(&path, source(10, "")).unwrap();
+        repl.reload_file(&path).unwrap();
+        assert_eq!(repl.session().compiled_rule_count(), 1);
+        fs::write(
+            &path,
+            source(
+                20,
+                "(rule: broken :where [[?e :counter/value ?v]] :then [])\n(unknown-fn)",
+            ),
+        )
+        .unwrap();
+        let failed = repl.reload_file(&path);
+        fs::remove_dir_all(&dir).ok();
+        assert!(failed.is_err());
+        assert_eq!(repl.session().compiled_rule_count(), 1);
+        assert_eq!(repl.session().get_variable("step"), Some(&Value::Int(10)));
+    }
+
+    #[test]
+    fn constraints_declared_in_files_score_and_reload() {
+        let dir = std::env::temp_dir().join("longtable_test_constraint_reload");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("world.lt");
+        let source = |extra: &str| {
+            format!(
+                "(component: health :current :int)
+                 (spawn: weak :health {{:current 2}})
+                 {extra}"
+            )
+        };
+        fs::write(
+            &path,
+            source(
+                "(constraint: healthy :where [[?e :health ?h]]
+                   :check [(>= (get ?h :current) 5)]
+                   :on-violation :score :penalty 10)",
+            ),
+        )
+        .unwrap();
+
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.load_file(path.to_str().unwrap()).unwrap();
+        repl.eval("(tick!)").unwrap();
+        assert_eq!(repl.eval("(world-score)").unwrap(), Value::Float(10.0));
+
+        // Removing the declaration retracts the constraint
+        fs::write(&path, source("")).unwrap();
+        repl.reload_file(&path).unwrap();
+        fs::remove_dir_all(&dir).ok();
+        repl.eval("(tick!)").unwrap();
+        assert_eq!(repl.eval("(world-score)").unwrap(), Value::Float(0.0));
+    }
+
+    #[test]
+    fn format_shows_keywords_by_name() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        assert_eq!(
+            repl.eval(r#"(format "~a takes the ~8a!" :hero :sword)"#)
+                .unwrap(),
+            Value::from(":hero takes the :sword  !")
+        );
+    }
+
+    #[test]
+    fn require_aliases_namespaced_keywords() {
+        let dir = std::env::temp_dir().join("longtable_test_require_as");
+        fs::create_dir_all(dir.join("my/game")).unwrap();
+        fs::write(
+            dir.join("my/game/combat.lt"),
+            "(namespace my.game.combat)\n(def default-stance ::defend)\n",
+        )
+        .unwrap();
+
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.session_mut().set_load_path(dir.clone());
+        let required = repl.eval("(require [my.game.combat :as combat] [my.game.items :as items])");
+        fs::remove_dir_all(&dir).ok();
+        required.unwrap();
+        assert!(
+            repl.session()
+                .module_registry()
+                .has_namespace("my.game.combat")
+        );
+
+        // The file's own `::` keywords resolved to its namespace
+        let defend = repl
+            .session()
+            .world()
+            .interner()
+            .lookup_keyword("my.game.combat/defend")
+            .unwrap();
+        assert_eq!(
+            repl.session().get_variable("default-stance"),
+            Some(&Value::Keyword(defend))
+        );
+        // Aliases work with or without a loaded file, and `::` alone means user/
+        assert_eq!(
+            repl.eval(
+                "[(= ::combat/attack :my.game.combat/attack)
+                  (= ::items/sword :my.game.items/sword)
+                  (= ::score :user/score)]"
+            )
+            .unwrap(),
+            Value::Vec(vec![Value::Bool(true); 3].into_iter().collect())
+        );
+
+        let err = repl.eval("::spells/fireball").unwrap_err();
+        assert!(
+            err.to_string().contains("unknown namespace alias `spells`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn loads_precompiled_modules() {
+        let dir = std::env::temp_dir().join("longtable_test_precompiled");
+        fs::create_dir_all(&dir).unwrap();
+        let source_path = dir.join("world.lt");
+        let source = "(component: health :current :int)\n(spawn: hero :health {:current 5})\n";
+        fs::write(&source_path, source).unwrap();
+
+        // A fresh module beside the source is used instead of parsing it
+        let mut module = PrecompiledModule::compile(source, &source_path).unwrap();
+        module
+            .forms
+            .push(parse("(spawn: villain :health {:current 9})").unwrap()[0].clone());
+        module.save(precompiled::module_path(&source_path)).unwrap();
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        let before = repl.session().world().entity_count();
+        repl.load_file(source_path.to_str().unwrap()).unwrap();
+        assert_eq!(repl.session().world().entity_count(), before + 2);
+
+        // Once the source changes, the stale module is ignored
+        fs::write(&source_path, format!("{source}; edited\n")).unwrap();
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval_file(&source_path).unwrap();
+        assert_eq!(repl.session().world().entity_count(), before + 1);
+
+        // Modules also load on their own
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        let loaded = repl.eval_file(&precompiled::module_path(&source_path));
+        fs::remove_dir_all(&dir).ok();
+        loaded.unwrap();
+        assert_eq!(repl.session().world().entity_count(), before + 2);
+    }
+
+    #[test]
+    fn load_reports_every_parse_error() {
+        let path = std::env::temp_dir().join("longtable_test_parse_errors.lt");
+        fs::write(&path, "(def a 1)\n(def b ]\n(def c 3)\n(def d {:x}\n").unwrap();
+
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
+        let err = repl.load_file(path.to_str().unwrap()).unwrap_err();
+        fs::remove_file(&path).ok();
+
+        let message = err.to_string();
+        // It's still a parse error, pointing at the first one
+        assert!(
+            matches!(err.kind, ErrorKind::ParseError { line: 2, .. }),
+            "{message}"
+        );
+        assert_eq!(err.code(), "E0010");
+        assert!(message.contains("2 parse errors"), "{message}");
+        assert!(message.contains("at 2:"), "{message}");
+        assert!(message.contains("at 4:"), "{message}");
+    }
 
-        // Division by zero should return error
-        let result = repl.eval("(/ 1 0)");
-        assert!(result.is_err());
+    #[test]
+    fn errors_in_functions_name_the_defining_file() {
+        let dir = std::env::temp_dir();
+        let defs = dir.join("longtable_test_defining_file_defs.lt");
+        let caller = dir.join("longtable_test_defining_file_caller.lt");
+        fs::write(&defs, "(fn: halve [x]\n  (/ x 0))\n").unwrap();
+        fs::write(&caller, "(def result\n  (halve 8))\n").unwrap();
+
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.load_file(defs.to_str().unwrap()).unwrap();
+        let loaded = repl.load_file(caller.to_str().unwrap()).unwrap_err();
+        let entered = repl.eval("(halve 2)").unwrap_err();
+        fs::remove_file(&defs).ok();
+        fs::remove_file(&caller).ok();
+
+        let defs = defs.display().to_string();
+        for err in [loaded, entered] {
+            let context = err.context.as_ref().expect("error has context");
+            assert_eq!(context.source.as_deref(), Some(defs.as_str()), "{err}");
+            assert_eq!((context.line, context.column), (Some(2), Some(3)), "{err}");
+            assert_eq!(context.stack, vec![format!("halve ({defs}:2:3)")]);
+        }
+    }
 
-        // REPL should still be usable
-        let result = repl.eval("(/ 10 2)");
-        assert!(result.is_ok());
+    #[test]
+    fn files_declaring_a_namespace_load() {
+        let path = std::env::temp_dir().join("longtable_test_namespace_decl.lt");
+        fs::write(&path, "(namespace game.core)\n(spawn: beacon)\n").unwrap();
+
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        let result = repl.load_file(path.to_str().unwrap());
+        fs::remove_file(&path).ok();
+
+        result.unwrap();
+        assert!(repl.session().module_registry().has_namespace("game.core"));
+        assert!(repl.session().get_entity("beacon").is_some());
     }
 
     #[test]
-    fn error_recovery_type_error() {
+    fn load_directory_orders_files_by_require() {
+        let dir = std::env::temp_dir().join("longtable_test_load_order");
+        fs::create_dir_all(&dir).unwrap();
+        // Alphabetically a_combat.lt comes first, but it needs game.core's component
+        fs::write(
+            dir.join("a_combat.lt"),
+            "(namespace game.combat (:require [game.core]))\n(spawn: hero :health {:current 5})\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("b_core.lt"),
+            "(namespace game.core)\n(component: health :current :int)\n",
+        )
+        .unwrap();
+
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
+        // The directory is found on the load path; its files aren't looked
+        // up there again
+        repl.session.set_load_path(std::env::temp_dir());
+        let result = repl.load_file("longtable_test_load_order");
+        fs::remove_dir_all(&dir).ok();
+
+        result.unwrap();
+        assert!(repl.session().get_entity("hero").is_some());
+    }
 
-        // Type error should return error
-        let result = repl.eval(r#"(+ "hello" 1)"#);
-        assert!(result.is_err());
+    #[test]
+    fn replay_reproduces_recorded_ticks() {
+        let source = "(component: counter :value :int)";
+        let record = |edit: &str| {
+            let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+            repl.eval(source).unwrap();
+            repl.start_recording(Vec::new());
+            repl.eval("(tick!)").unwrap();
+            repl.eval(edit).unwrap();
+            repl.eval("(tick!)").unwrap();
+            repl.session_mut().take_replay_log().unwrap()
+        };
+        let log = record("(spawn: counter-1 :counter {:value 1})");
+        assert_eq!(log.len(), 2);
+        assert!(log.frames[0].rebase.is_none());
+        assert!(log.frames[1].rebase.is_some());
+
+        let mut replay = Repl::with_editor(MockEditor::new(vec![]));
+        replay.eval(source).unwrap();
+        assert_eq!(replay.replay(&log).unwrap(), 2);
+
+        let mut tampered = log.clone();
+        tampered.frames[1].hash ^= 1;
+        let mut replay = Repl::with_editor(MockEditor::new(vec![]));
+        replay.eval(source).unwrap();
+        let err = replay.replay(&tampered).unwrap_err();
+        assert!(err.to_string().contains("replay diverged at tick 2"));
+    }
 
-        // REPL should still be usable
-        let result = repl.eval("(+ 1 2)");
-        assert!(result.is_ok());
+    #[test]
+    fn scenarios_see_the_effects_of_rules() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"(component: health :current :int)
+               (component: tag/dead :bool :default true)
+               (rule: die :where [[?e :health ?h] [(<= (get ?h :current) 0)]]
+                 :then [(set-component! ?e :tag/dead true)])
+               (scenario: "rule marks zero-health dead"
+                 :setup [(spawn: goblin :health {:current 0})]
+                 :steps [(tick!)]
+                 :assert [[?g :tag/dead true] [?g :health/current 0]])
+               (scenario: "nothing dies without a tick"
+                 :setup [(spawn: goblin :health {:current 0})]
+                 :assert [[?g :tag/dead true]])"#,
+        )
+        .unwrap();
+
+        let results = repl.run_scenarios(None);
+        assert!(results[0].passed(), "{:?}", results[0].failures);
+        assert!(!results[1].passed());
+        assert!(repl.session().get_entity("goblin").is_none());
     }
 
-    // ==================== Interactive Scenario Tests ====================
+    #[test]
+    fn scenarios_run_in_isolation_and_report_diffs() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval("(component: health :current :int) (spawn: goblin :health {:current 5})")
+            .unwrap();
+        let goblin = repl.session().get_entity("goblin").unwrap();
+        let goblin = format!("(entity-ref {} {})", goblin.index, goblin.generation);
+        let hit = format!(
+            "(set-field! {goblin} :health :current (dec (get-field {goblin} :health :current)))"
+        );
+        repl.eval(&format!(
+            r#"(scenario: "goblin dies"
+                 :setup [(spawn: goblin :health {{:current 2}})]
+                 :steps [{hit} (tick!) {hit}]
+                 :assert [[?g :health/current 0]])
+               (scenario: "goblin survives"
+                 :setup [(set-field! {goblin} :health :current 3)]
+                 :steps [{hit} (tick!) {hit} (println "still standing")]
+                 :assert [[?g :health {{:current 0}}] (= 1 2)])"#
+        ))
+        .unwrap();
+        let tick = repl.tick_number();
+
+        let results = repl.run_scenarios(None);
+        assert!(results[0].passed(), "{:?}", results[0].failures);
+        let failures: Vec<_> = results[1]
+            .failures
+            .iter()
+            .map(|f| (f.form.as_str(), f.detail.as_str()))
+            .collect();
+        assert_eq!(
+            failures,
+            vec![
+                (
+                    "[?g :health {:current 0}]",
+                    "- [?g :health {:current 0}]\n+ [?g :health {:current 1}]\n"
+                ),
+                ("(= 1 2)", "- true\n+ false\n"),
+            ]
+        );
+        assert_eq!(
+            results[1].output,
+            "Tick 1: 0 activations fired\nstill standing\n"
+        );
+
+        // Each scenario is undone afterwards
+        assert_eq!(
+            repl.eval(&format!("(get-field {goblin} :health :current)"))
+                .unwrap(),
+            Value::Int(5)
+        );
+        assert_eq!(repl.tick_number(), tick);
+        assert_eq!(repl.take_output(), "");
+
+        assert_eq!(
+            repl.eval(r#"(run-scenarios "goblin dies")"#).unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(repl.eval("(run-scenarios)").unwrap(), Value::Bool(false));
+        let report = repl.take_output();
+        assert!(report.contains("FAILED  goblin survives"), "{report}");
+        assert!(report.ends_with("2 scenario(s), 1 failed\n"), "{report}");
+        assert!(repl.eval(r#"(run-scenarios "goblin flees")"#).is_err());
+
+        // Field clauses are explained with the field's actual value
+        repl.eval(
+            r#"(scenario: "goblin is wounded" :assert [[?g :health/current 3]])
+               (scenario: "goblin is bound" :assert [[?g :health/current ?hp]])"#,
+        )
+        .unwrap();
+        let results = repl.run_scenarios(Some("goblin is wounded"));
+        assert_eq!(
+            results[0].failures[0].detail,
+            "- [?g :health/current 3]\n+ [?g :health/current 5]\n"
+        );
+        let results = repl.run_scenarios(Some("goblin is bound"));
+        let detail = &results[0].failures[0].detail;
+        assert!(detail.contains("[?e :health ?c]"), "{detail}");
+    }
 
     #[test]
-    fn interactive_arithmetic_chain() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
+    fn captured_output_collects_narration_and_player_messages() {
+        let mut repl = Repl::with_editor(crate::editor::HeadlessEditor).with_captured_output();
+        repl.eval("(println \"You are in a maze.\")").unwrap();
+        assert_eq!(repl.take_output(), "You are in a maze.\n");
+        assert_eq!(repl.take_output(), "");
+
+        assert_eq!(repl.input("look").unwrap(), Value::Nil);
+        assert_eq!(repl.take_output(), "No player entity found.\n");
+    }
 
-        // Complex expression evaluation
-        let result = repl.eval("(+ (* 2 3) (- 10 5))").unwrap();
-        assert_eq!(result, Value::Int(11));
+    #[test]
+    fn messages_follow_the_locale_with_fallback() {
+        let mut repl = Repl::with_editor(crate::editor::HeadlessEditor).with_captured_output();
+        repl.eval(
+            r#"(message: :msg/take "You take the ~a.")
+               (message: :msg/drop "You drop the ~a.")
+               (message: :msg/take "Vous prenez ~a." :locale :fr)"#,
+        )
+        .unwrap();
+
+        repl.eval(r#"(say-msg :msg/take "lamp")"#).unwrap();
+        assert_eq!(repl.take_output(), "You take the lamp.\n");
+
+        repl.eval("(set-locale! :fr)").unwrap();
+        repl.eval(r#"(say-msg :msg/take "lamp")"#).unwrap();
+        repl.eval(r#"(say-msg :msg/drop "lamp")"#).unwrap();
+        assert_eq!(
+            repl.take_output(),
+            "Vous prenez lamp.\nYou drop the lamp.\n"
+        );
+
+        assert_eq!(
+            repl.eval(r#"(msg :msg/drop "key")"#).unwrap(),
+            Value::from("You drop the key.")
+        );
+        assert!(repl.eval("(msg :msg/missing)").is_err());
+        assert!(repl.eval("(message: :msg/take)").is_err());
     }
 
     #[test]
-    fn interactive_let_expression() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
+    fn text_helpers_read_name_metadata() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        let mut text = |body: &str| {
+            repl.eval(&format!(
+                r#"(let [sand {{:value "sand" :article "some" :plural "sand"}}
+                         sting {{:value "Sting" :proper true}}]
+                     {body})"#
+            ))
+            .unwrap()
+        };
 
-        // Use let to bind a variable within a single expression
-        let result = repl.eval("(let [x 10] (+ x 5))").unwrap();
-        assert_eq!(result, Value::Int(15));
+        assert_eq!(text("(a-or-an sand)"), Value::from("some sand"));
+        assert_eq!(text("(pluralize sand 2)"), Value::from("sand"));
+        assert_eq!(text("(a-or-an sting)"), Value::from("Sting"));
+        assert_eq!(
+            text(r#"(join-and [sting (a-or-an "orb")])"#),
+            Value::from("Sting and an orb")
+        );
+    }
 
-        // Nested let bindings
-        let result = repl.eval("(let [x 10 y 20] (+ x y))").unwrap();
-        assert_eq!(result, Value::Int(30));
+    #[test]
+    fn inspect_lists_components_and_relationships() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            "(component: health :current :int)
+             (component: tag/room :bool :default true)
+             (relationship: in-room :cardinality :many-to-one)
+             (spawn: player :health {:current 5})
+             (spawn: cave :tag/room true)
+             (link: player :in-room cave)",
+        )
+        .unwrap();
+
+        let player = repl.session().get_entity("player").unwrap();
+        let cave = repl.session().get_entity("cave").unwrap();
+        repl.eval("(inspect player)").unwrap();
+        assert_eq!(
+            repl.take_output(),
+            format!(
+                "player Entity({}, {}):\n  :health {{:current 5}}\n  relationships:\n    \
+                 :in-room -> cave Entity({}, {})\n",
+                player.index, player.generation, cave.index, cave.generation
+            )
+        );
+
+        repl.eval("(inspect cave)").unwrap();
+        let output = repl.take_output();
+        assert!(output.contains("  :tag/room true\n"), "{output}");
+        assert!(output.contains(":in-room <- player"), "{output}");
     }
 
     #[test]
-    fn interactive_collection_operations() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
+    fn system_runs_are_traced() {
+        use longtable_debug::TraceEvent;
 
-        // Create and manipulate collections
-        let result = repl.eval("(first [1 2 3])").unwrap();
-        assert_eq!(result, Value::Int(1));
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        let idle = |_: &longtable_storage::World| -> Result<Vec<VmEffect>> { Ok(Vec::new()) };
+        repl.register_system("idle", TickPhase::AfterInputs, SystemAccess::new(), idle)
+            .unwrap();
+        repl.eval("(trace :on)").unwrap();
+        repl.step(&[]).unwrap();
+
+        let runs: Vec<_> = repl
+            .session()
+            .tracer()
+            .buffer()
+            .iter()
+            .filter_map(|r| match &r.event {
+                TraceEvent::SystemRun { name, phase, .. } => Some((name.clone(), phase.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(runs, [("idle".to_string(), "after-inputs".to_string())]);
+    }
+
+    #[test]
+    fn a_panicking_tick_poisons_the_session() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.step(&[]).unwrap();
+        let before = repl.session().world().content_hash();
+        let flaky = |_: &longtable_storage::World| -> Result<Vec<VmEffect>> { panic!("boom") };
+        repl.register_system("flaky", TickPhase::BeginTick, SystemAccess::new(), flaky)
+            .unwrap();
+
+        let err = repl.step(&[]).unwrap_err();
+        assert!(err.to_string().contains("tick 2 panicked (boom)"), "{err}");
+        assert_eq!(repl.session().poisoned().unwrap().tick, 2);
+        // It won't tick on a world that may be inconsistent
+        let err = repl.step(&[]).unwrap_err();
+        assert_eq!(err.code(), "E0019");
+        assert!(err.hints.iter().any(|h| h.contains("(recover!)")), "{err}");
+
+        repl.eval("(recover!)").unwrap();
+        assert!(repl.session().poisoned().is_none());
+        assert_eq!(repl.tick_number(), 1);
+        assert_eq!(repl.session().world().content_hash(), before);
+        assert!(repl.eval("(recover!)").is_err());
+
+        // With auto-recovery the session is never left poisoned
+        let mut repl = repl.with_auto_recover(true);
+        let err = repl.step(&[]).unwrap_err();
+        assert!(err.to_string().contains("restored"), "{err}");
+        assert!(repl.session().poisoned().is_none());
+        assert_eq!(repl.tick_number(), 1);
+    }
+
+    #[test]
+    fn shutdown_saves_the_exit_checkpoint() {
+        let path = std::env::temp_dir().join("longtable_test_exit_checkpoint.lt");
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_exit_checkpoint(&path);
+        repl.eval(
+            "(component: health :current :int)
+             (spawn: hero :health {:current 3})",
+        )
+        .unwrap();
+        repl.shutdown().unwrap();
+
+        let world = serialize::load_from_file(&path).unwrap();
+        assert_eq!(world.entity_count(), repl.session().world().entity_count());
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn batch_run_reports_each_tick() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: health :current :int)
+             (spawn: weak :health {:current 2})
+             (constraint: healthy
+               :where [[?e :health ?h]]
+               :check [(>= (get ?h :current) 5)]
+               :on-violation :score
+               :penalty (- 5 (get ?h :current)))",
+        )
+        .unwrap();
+
+        let report = crate::batch::run(&mut repl, 3).unwrap();
+        assert_eq!(report.ticks_run, 3);
+        assert_eq!(report.committed, 3);
+        assert_eq!(report.rolled_back, 0);
+        assert_eq!(report.final_entities, report.initial_entities);
+        assert_eq!(
+            report.ticks.iter().map(|t| t.tick).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!((report.ticks[0].world_score - 3.0).abs() < f64::EPSILON);
+        assert_eq!(report.ticks[0].soft_violations, 1);
+        assert_eq!(
+            report.world_hash,
+            format!("{:016x}", repl.session().world().content_hash())
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["ticks"][2]["committed"], true);
+    }
+
+    #[test]
+    fn query_warnings_can_be_inspected_suppressed_and_denied() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: score :value :int)
+             (spawn: a :score {:value 1})",
+        )
+        .unwrap();
+        let by_entity = "(query :where [[?e :score ?s]] :order-by [[?e :asc]] :return ?e)";
+
+        repl.eval("(query-warnings :allow)").unwrap();
+        repl.eval(by_entity).unwrap();
+        let Value::Vec(warnings) = repl.eval("(query-warnings)").unwrap() else {
+            panic!("expected a vector of warnings");
+        };
+        assert_eq!(warnings.len(), 1);
+        let code = repl
+            .session()
+            .world()
+            .interner()
+            .lookup_keyword("code")
+            .unwrap();
+        let Value::Map(warning) = warnings.get(0).unwrap() else {
+            panic!("expected a warning map");
+        };
+        assert_eq!(
+            repl.format_value_inner(warning.get(&Value::Keyword(code)).unwrap()),
+            ":unstable-order"
+        );
+
+        repl.eval("(query-warnings :deny)").unwrap();
+        let err = repl.eval(by_entity).unwrap_err();
+        assert!(matches!(&err.kind, ErrorKind::DeniedWarnings(messages) if messages.len() == 1));
+        assert_eq!(err.code(), "E0020");
+        assert!(err.to_string().contains("[:unstable-order]"));
+
+        let suppressed = "(query :where [[?e :score ?s]] :order-by [[?e :asc]]
+                                  :suppress [:unstable-order] :return ?e)";
+        assert!(repl.eval(suppressed).is_ok());
+        assert_eq!(
+            repl.eval("(query-warnings)").unwrap(),
+            Value::Vec(longtable_foundation::LtVec::new())
+        );
+
+        assert!(repl.eval("(query-warnings :loud)").is_err());
+    }
+
+    #[test]
+    fn relationship_stats_counts_edges() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            "(component: tag/room :bool :default true)
+             (relationship: in-room :cardinality :many-to-one)
+             (spawn: hall :tag/room true)
+             (spawn: lamp :tag/room true)
+             (link: lamp :in-room hall)",
+        )
+        .unwrap();
+
+        assert_eq!(repl.eval("(relationship-stats)").unwrap(), Value::Int(1));
+        let in_room = repl
+            .session()
+            .world()
+            .interner()
+            .lookup_keyword("in-room")
+            .unwrap();
+        assert_eq!(repl.session().world().fan_out(in_room).edges, 1);
+
+        // Lookups made by evaluated forms count for the session, not the world
+        let before = repl.session().relationship_lookups().get(in_room);
+        repl.eval("(query :where [[?e :in-room ?room]] :return ?e)")
+            .unwrap();
+        let after = repl.session().relationship_lookups().get(in_room);
+        assert_eq!(after.lookups - before.lookups, 1);
+        assert_eq!(after.matched - before.matched, 1);
+    }
+
+    #[test]
+    fn lint_game_reports_adventure_pitfalls() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval(
+            r#"
+(component: tag/room :bool :default true)
+(component: tag/takeable :bool :default true)
+(component: name :value :string)
+(relationship: exit/north :cardinality :one-to-one)
+(relationship: in-room :cardinality :many-to-one)
+(verb: light :synonyms [ignite])
+(action: look :params [actor] :handler [])
+(command: look-around :syntax [:verb/look] :action look)
+(command: light-it :syntax [:verb/light] :action light)
+(spawn: hall :tag/room true :name {:value "Hall"})
+(spawn: cellar :tag/room true :name {:value "Cellar"})
+(spawn: torch :tag/takeable true :name {:value "light"})
+(spawn: lamp :tag/takeable true :name {:value "lamp"})
+(link: hall :exit/north cellar)
+(link: cellar :exit/north lamp)
+(link: lamp :in-room hall)
+"#,
+        )
+        .unwrap();
+
+        let lints = lint::lint(repl.session());
+        let report: Vec<String> = lints.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            report,
+            vec![
+                "<repl>:10:1: [unknown-action] command light-it invokes :light, which has no action: declaration",
+                "<repl>:13:1: [item-without-location] takeable item torch is not :in-room or :contained-in anything",
+                "<repl>:13:1: [noun-verb-collision] torch is called \"light\", but \"light\" is also a word for the verb light",
+                "<repl>:16:1: [dangling-exit] exit :exit/north from cellar leads to lamp, which is not a room",
+            ]
+        );
+        assert_eq!(repl.eval("(lint-game)").unwrap(), Value::Int(4));
+    }
+
+    #[test]
+    fn help_lists_and_describes_special_forms() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval("(help)").unwrap();
+        let overview = repl.take_output();
+        assert!(overview.starts_with("Session:\n"));
+        assert!(overview.contains("\nTime travel:\n"));
+        assert!(overview.contains("  why "));
+
+        repl.eval("(help why)").unwrap();
+        let why = repl.take_output();
+        assert!(why.starts_with("(why entity :component)\n"));
+        assert!(why.contains(":data true"));
+
+        let err = repl.eval("(help frobnicate)").unwrap_err();
+        assert_eq!(err.code(), "E0017");
+        assert_eq!(
+            err.hints,
+            vec!["(help) lists the special forms".to_string()]
+        );
+        assert_eq!(repl.eval("(help 1 2)").unwrap_err().code(), "E0016");
+    }
+
+    #[test]
+    fn runs_compound_commands_until_one_fails() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(verb: take :synonyms [get])
+(verb: drop)
+(action: take :params [actor thing] :handler [(println (str "Taken " ?thing))])
+(action: drop :params [actor thing] :handler [(println (str "Dropped " ?thing))])
+(command: take-thing :syntax [:verb/take ?thing] :action take)
+(command: drop-thing :syntax [:verb/drop ?thing] :action drop)
+(spawn: player :tag/player true)
+(spawn: lamp :name {:value "lamp"})
+(spawn: sword :name {:value "sword"})
+"#,
+        )
+        .unwrap();
+
+        let show = |name: &str| {
+            let entity = repl.session().get_entity(name).unwrap();
+            format!("Entity({}, {})", entity.index, entity.generation)
+        };
+        let (lamp, sword) = (show("lamp"), show("sword"));
+        repl.input("get lamp, then drop it. Take sword then take bogus; drop lamp")
+            .unwrap();
+        assert_eq!(
+            repl.take_output(),
+            format!("Taken {lamp}\nDropped {lamp}\nTaken {sword}\nI don't see any 'bogus' here.\n")
+        );
+
+        let inputs: Vec<&str> = repl
+            .session()
+            .transcript()
+            .entries()
+            .iter()
+            .map(|entry| entry.input.as_str())
+            .collect();
+        assert_eq!(inputs, ["get lamp", "drop it", "Take sword", "take bogus"]);
+    }
 
-        let result = repl.eval("(count [1 2 3 4 5])").unwrap();
-        assert_eq!(result, Value::Int(5));
+    #[test]
+    fn commands_after_a_question_wait_for_the_answer() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(verb: take)
+(verb: drop)
+(action: take :params [actor thing] :handler [(println (str "Taken " ?thing))])
+(action: drop :params [actor thing] :handler [(println (str "Dropped " ?thing))])
+(command: take-thing :syntax [:verb/take ?thing] :action take)
+(command: drop-thing :syntax [:verb/drop ?thing] :action drop)
+(spawn: player :tag/player true)
+(spawn: lamp :name {:value "lamp"})
+(spawn: gold :name {:value "gold coin"})
+(spawn: silver :name {:value "silver coin"})
+"#,
+        )
+        .unwrap();
+        let show = |name: &str| {
+            let entity = repl.session().get_entity(name).unwrap();
+            format!("Entity({}, {})", entity.index, entity.generation)
+        };
+        let (lamp, gold) = (show("lamp"), show("gold"));
+
+        repl.input("take coin, then drop lamp").unwrap();
+        let question = repl.take_output();
+        assert!(!question.contains("Dropped"), "{question}");
+
+        repl.input("gold").unwrap();
+        assert_eq!(
+            repl.take_output(),
+            format!("Taken {gold}\nDropped {lamp}\n")
+        );
+
+        // Typing something else abandons the question and what followed it
+        repl.input("take coin. drop lamp").unwrap();
+        repl.take_output();
+        repl.input("drop gold").unwrap();
+        assert_eq!(repl.take_output(), format!("Dropped {gold}\n"));
     }
 
     #[test]
-    fn interactive_nested_collections() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
+    fn all_expands_to_one_command_per_target() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: tag/room :bool :default true)
+(component: name :value :string)
+(verb: take)
+(verb: put)
+(preposition: in)
+(action: take :params [actor thing] :handler [(println (str "Taken " ?thing))])
+(action: put-in :params [actor thing bag] :handler [(println (str "Put " ?thing " in " ?bag))])
+(command: take-thing :syntax [:verb/take ?thing] :action take)
+(command: put-in :syntax [:verb/put ?thing :prep/in ?bag] :action put-in)
+(spawn: player :tag/player true :name {:value "you"})
+(spawn: lamp :name {:value "lamp"})
+(spawn: gold :name {:value "gold coin"})
+(spawn: silver :name {:value "silver coin"})
+(spawn: sack :name {:value "sack"})
+(spawn: hall :tag/room true :name {:value "hall"})
+"#,
+        )
+        .unwrap();
+        let show = |name: &str| {
+            let entity = repl.session().get_entity(name).unwrap();
+            format!("Entity({}, {})", entity.index, entity.generation)
+        };
+        let [lamp, gold, silver, sack] = ["lamp", "gold", "silver", "sack"].map(show);
+        let lines = |repl: &mut Repl<MockEditor>| {
+            let mut lines: Vec<String> = repl.take_output().lines().map(str::to_string).collect();
+            lines.sort();
+            lines
+        };
 
-        // Nested vector creation
-        let result = repl.eval("[[1 2] [3 4]]").unwrap();
-        // Just verify it's a vector
-        assert!(matches!(result, Value::Vec(_)));
+        repl.input("take all").unwrap();
+        let mut expected: Vec<String> = [&lamp, &gold, &silver, &sack]
+            .map(|e| format!("Taken {e}"))
+            .to_vec();
+        expected.sort();
+        assert_eq!(lines(&mut repl), expected);
 
-        // Map creation
-        let result = repl.eval("{:a 1 :b 2}").unwrap();
-        assert!(matches!(result, Value::Map(_)));
+        repl.input("take everything except the lamp and sack")
+            .unwrap();
+        let mut expected = vec![format!("Taken {gold}"), format!("Taken {silver}")];
+        expected.sort();
+        assert_eq!(lines(&mut repl), expected);
+
+        repl.input("put all of the coins in the sack").unwrap();
+        let mut expected = vec![
+            format!("Put {gold} in {sack}"),
+            format!("Put {silver} in {sack}"),
+        ];
+        expected.sort();
+        assert_eq!(lines(&mut repl), expected);
+
+        repl.input("put all in sack").unwrap();
+        assert_eq!(lines(&mut repl).len(), 3, "leaves out the sack itself");
     }
 
     #[test]
-    fn interactive_string_operations() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
+    fn text_slots_capture_free_text() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(verb: say)
+(verb: write)
+(preposition: to)
+(preposition: on)
+(action: say-to :params [actor msg who] :handler [(println (str "Said " ?msg " to " ?who))])
+(action: write-on :params [actor msg thing] :handler [(println (str "Wrote " ?msg))])
+(command: say-to :syntax [:verb/say :text ?msg :prep/to ?who] :action say-to)
+(command: write-on :syntax [:verb/write ?msg:text :prep/on ?thing] :action write-on)
+(spawn: player :tag/player true :name {:value "you"})
+(spawn: guard :name {:value "guard"})
+(spawn: note :name {:value "note"})
+"#,
+        )
+        .unwrap();
+        let guard = repl.session().get_entity("guard").unwrap();
+        let guard = format!("Entity({}, {})", guard.index, guard.generation);
+
+        repl.input("say hello there to guard").unwrap();
+        assert_eq!(repl.take_output(), format!("Said hello there to {guard}\n"));
+
+        repl.input("say go to the gate to the guard").unwrap();
+        assert_eq!(
+            repl.take_output(),
+            format!("Said go to the gate to {guard}\n"),
+            "the text runs to the last preposition"
+        );
+
+        repl.input(r#"write "Meet me at dawn, by the well" on note"#)
+            .unwrap();
+        assert_eq!(repl.take_output(), "Wrote Meet me at dawn, by the well\n");
+
+        // Text keeps its case and punctuation, and separators inside it
+        // don't split the command
+        repl.input("say Hello, my friend to guard").unwrap();
+        assert_eq!(
+            repl.take_output(),
+            format!("Said Hello, my friend to {guard}\n")
+        );
+        repl.input("write 'meet me at dawn' on note").unwrap();
+        assert_eq!(repl.take_output(), "Wrote meet me at dawn\n");
+        repl.input("say Don't go! to guard then write 'ok' on note")
+            .unwrap();
+        assert_eq!(
+            repl.take_output(),
+            format!("Said Don't go! to {guard}\nWrote ok\n")
+        );
+    }
 
-        // String concatenation
-        let result = repl.eval(r#"(str "hello" " " "world")"#).unwrap();
-        assert_eq!(result, Value::String("hello world".into()));
+    #[test]
+    fn ask_and_tell_match_topics_of_the_npc() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(component: npc/topics :value :vec)
+(verb: ask)
+(verb: tell)
+(preposition: about)
+(action: ask :params [actor npc subject] :handler [(println (str "Asked about " ?subject))])
+(action: tell :params [actor npc subject] :handler [(println (str "Told about " ?subject))])
+(command: ask-about :syntax [:verb/ask ?npc :prep/about :topic ?subject] :action ask)
+(command: tell-about :syntax [:verb/tell ?npc :prep/about ?subject:topic] :action tell)
+(spawn: player :tag/player true :name {:value "you"})
+(spawn: guard :name {:value "guard"} :npc/topics {:value [:treasure :topic/red-key]})
+"#,
+        )
+        .unwrap();
+
+        repl.input("ask guard about the treasure").unwrap();
+        assert_eq!(repl.take_output(), "Asked about :treasure\n");
+
+        repl.input("tell the guard about the old red key").unwrap();
+        assert_eq!(repl.take_output(), "Told about :topic/red-key\n");
+
+        repl.input("ask guard about the weather").unwrap();
+        assert_eq!(
+            repl.take_output(),
+            "There's nothing to say about 'the weather'.\n"
+        );
+    }
 
-        // String functions
-        let result = repl.eval(r#"(str/upper "hello")"#).unwrap();
-        assert_eq!(result, Value::String("HELLO".into()));
+    #[test]
+    fn declared_scopes_limit_what_nouns_refer_to() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(component: place :value :keyword)
+(component: scrying :value :keyword)
+(verb: take)
+(action: take :params [actor thing] :handler [(println (str "Taken " ?thing))])
+(command: take-thing :syntax [:verb/take ?thing] :action take)
+(scope: here
+  :where [[?actor :place ?p]
+          [?obj :place ?p]])
+(scope: sight
+  :extends [here]
+  :where [[?actor :scrying ?p]
+          [?obj :place ?p]])
+(spawn: player :tag/player true :name {:value "you"} :place {:value :hall} :scrying {:value :vault})
+(spawn: lamp :name {:value "lamp"} :place {:value :hall})
+(spawn: gem :name {:value "gem"} :place {:value :vault})
+(spawn: coin :name {:value "coin"} :place {:value :cellar})
+"#,
+        )
+        .unwrap();
+
+        for noun in ["lamp", "gem"] {
+            let entity = repl.session().get_entity(noun).unwrap();
+            repl.input(&format!("take {noun}")).unwrap();
+            assert_eq!(
+                repl.take_output(),
+                format!("Taken Entity({}, {})\n", entity.index, entity.generation),
+                "{noun} is in scope"
+            );
+        }
+        repl.input("take coin").unwrap();
+        assert_eq!(repl.take_output(), "I don't see any 'coin' here.\n");
+
+        let err = repl
+            .eval("(scope: here :extends [sight] :where [[?actor :place ?obj]])")
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("scopes extend each other in a cycle: :here -> :sight -> :here"),
+            "{err}"
+        );
     }
 
     #[test]
-    fn interactive_higher_order_functions() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
+    fn again_repeats_and_oops_corrects() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(verb: take)
+(action: take :params [actor thing] :handler [(println (str "Taken " ?thing))])
+(command: take-thing :syntax [:verb/take ?thing] :action take)
+(spawn: player :tag/player true :name {:value "you"})
+(spawn: lamp :name {:value "lamp"})
+"#,
+        )
+        .unwrap();
+        let lamp = repl.session().get_entity("lamp").unwrap();
+        let taken = format!("Taken Entity({}, {})\n", lamp.index, lamp.generation);
+
+        repl.input("again").unwrap();
+        assert_eq!(repl.take_output(), "There's nothing to repeat.\n");
+
+        repl.input("take lamp").unwrap();
+        repl.input("g").unwrap();
+        assert_eq!(repl.take_output(), taken.repeat(2));
+
+        repl.input("take the lamb").unwrap();
+        assert_eq!(repl.take_output(), "I don't see any 'lamb' here.\n");
+        repl.input("oops lamp").unwrap();
+        assert_eq!(repl.take_output(), taken);
+
+        repl.input("tkae lamp").unwrap();
+        repl.take_output();
+        repl.input("oops take").unwrap();
+        assert_eq!(repl.take_output(), taken);
+    }
 
-        // Map with lambda - returns vector
-        let result = repl.eval("(map (fn [x] (* x 2)) [1 2 3])").unwrap();
-        assert!(matches!(result, Value::Vec(_)));
+    #[test]
+    fn undo_and_load_restore_the_parser_state() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(verb: take)
+(action: take :params [actor thing] :handler [(println (str "Taken " ?thing))])
+(command: take-thing :syntax [:verb/take ?thing] :action take)
+(spawn: player :tag/player true :name {:value "you"})
+(spawn: lamp :name {:value "lamp"})
+"#,
+        )
+        .unwrap();
+        let lamp = repl.session().get_entity("lamp").unwrap();
+        let path = std::env::temp_dir().join("longtable_test_pronoun_reset.lt");
+        repl.eval(&format!("(save! {:?})", path.display().to_string()))
+            .unwrap();
 
-        // Reduce with lambda (native functions can't be passed directly)
-        let result = repl
-            .eval("(reduce (fn [acc x] (+ acc x)) 0 [1 2 3 4 5])")
+        repl.input("take lamp").unwrap();
+        repl.eval("(spawn: rock :name {:value \"rock\"})").unwrap();
+        repl.input("take rock").unwrap();
+        repl.eval("(undo!)").unwrap();
+        repl.take_output();
+        // "again" repeats the command from before the undone spawn
+        repl.input("again").unwrap();
+        assert_eq!(
+            repl.take_output(),
+            format!("Taken Entity({}, {})\n", lamp.index, lamp.generation)
+        );
+        assert_eq!(repl.session().pronoun_state().get_it(), Some(lamp));
+
+        repl.eval(&format!("(load-world! {:?})", path.display().to_string()))
             .unwrap();
-        assert_eq!(result, Value::Int(15));
+        repl.take_output();
+        let _ = std::fs::remove_file(&path);
+        assert!(repl.session().pronoun_state().get_it().is_none());
+        repl.input("again").unwrap();
+        assert_eq!(repl.take_output(), "There's nothing to repeat.\n");
     }
 
     #[test]
-    fn interactive_math_functions() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
-
-        // Basic math
-        let result = repl.eval("(abs -5)").unwrap();
-        assert_eq!(result, Value::Int(5));
-
-        // Min/max
-        let result = repl.eval("(max 1 5 3)").unwrap();
-        assert_eq!(result, Value::Int(5));
+    fn introspection_shows_what_the_parser_knows() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(verb: take :synonyms [get grab])
+(verb: look)
+(preposition: in)
+(action: take :params [actor thing] :handler [])
+(action: put :params [actor thing box] :handler [])
+(command: take-thing :syntax [:verb/take ?thing] :action take)
+(command: take-from :syntax [:verb/take ?thing :prep/in ?box] :action put)
+(spawn: player :tag/player true :name {:value "you"})
+(spawn: lamp :name {:value "lamp"})
+(spawn: anvil :name {:value "anvil"})
+"#,
+        )
+        .unwrap();
+
+        let Value::Vec(verbs) = repl.eval("(verbs)").unwrap() else {
+            panic!("verbs didn't return a vector");
+        };
+        assert_eq!(verbs.len(), 2);
+        assert_eq!(repl.take_output(), ":look\n:take (get, grab)\n");
 
-        let result = repl.eval("(min 1 5 3)").unwrap();
-        assert_eq!(result, Value::Int(1));
+        let Value::Vec(commands) = repl.eval("(syntax-for :verb grab)").unwrap() else {
+            panic!("syntax-for didn't return a vector");
+        };
+        assert_eq!(commands.len(), 2);
+        let output = repl.take_output();
+        let lines: Vec<_> = output.lines().collect();
+        assert!(
+            lines[0].starts_with(":take-from") && lines[0].ends_with("take ?thing in ?box -> :put"),
+            "{output}"
+        );
+        assert!(lines[1].ends_with("take ?thing -> :take"), "{output}");
+        repl.eval("(syntax-for :verb dance)").unwrap();
+        assert_eq!(repl.take_output(), "'dance' isn't a verb.\n");
+
+        let lamp = repl.session().get_entity("lamp").unwrap();
+        let Value::Vec(nouns) = repl.eval("(nouns-in-scope)").unwrap() else {
+            panic!("nouns-in-scope didn't return a vector");
+        };
+        assert!(nouns.iter().any(|v| *v == Value::EntityRef(lamp)));
+        let output = repl.take_output();
+        assert!(output.contains(&format!("{lamp} lamp\n")));
+        // Listed by description, not in spawn order
+        let anvil = output.find(" anvil\n").unwrap();
+        assert!(anvil < output.find(" lamp\n").unwrap(), "{output}");
     }
 
-    // ==================== Spawn and Link Tests ====================
-
     #[test]
-    fn spawn_creates_entity() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
-
-        // First define the component schema (tag component with :bool :default true)
-        repl.eval("(component: tag/player :bool :default true)")
-            .unwrap();
+    #[allow(clippy::too_many_lines)]
+    fn standard_actions_work_the_standard_components() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.load_stdlib().unwrap();
+        let drop = repl
+            .session
+            .world_mut()
+            .interner_mut()
+            .intern_keyword("drop");
+        assert!(repl.session().get_action_decl(drop).is_none());
+        repl.eval(
+            r#"
+(require [stdlib.actions])
+(require [stdlib.actions])
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(component: description :value :string)
+(component: tag/takeable :bool :default true)
+(component: tag/container :bool :default true)
+(component: tag/openable :bool :default true)
+(component: tag/open :bool :default true)
+(component: tag/locked :bool :default true)
+(component: tag/wearable :bool :default true)
+(component: tag/worn :bool :default true)
+(component: tag/door :bool :default true)
+(component: lock :key-id :keyword)
+(component: key :key-id :keyword)
+(relationship: in-room :cardinality :many-to-one)
+(relationship: contained-in :cardinality :many-to-one)
+(relationship: exit/north :cardinality :one-to-one)
+(relationship: exit/south :cardinality :one-to-one)
+(relationship: door/leads-to :cardinality :many-to-many)
+(verb: take)
+(verb: drop)
+(verb: put)
+(verb: open)
+(verb: close)
+(verb: lock)
+(verb: unlock)
+(verb: wear)
+(verb: go)
+(verb: inventory :synonyms [i])
+(preposition: in)
+(preposition: with)
+(direction: north :synonyms [n])
+(direction: south :synonyms [s])
+(command: take :syntax [:verb/take ?obj] :action take)
+(command: drop :syntax [:verb/drop ?obj] :action drop)
+(command: put-in :syntax [:verb/put ?obj :prep/in ?dest] :action put-in)
+(command: open :syntax [:verb/open ?obj] :action open)
+(command: close :syntax [:verb/close ?obj] :action close)
+(command: lock :syntax [:verb/lock ?obj :prep/with ?key] :action lock)
+(command: unlock :syntax [:verb/unlock ?obj :prep/with ?key] :action unlock)
+(command: wear :syntax [:verb/wear ?obj] :action wear)
+(command: go :syntax [:direction ?dir] :action go)
+(command: inventory :syntax [:verb/inventory] :action show-inventory)
+(spawn: hall :name {:value "Hall"} :description {:value "A long hall."})
+(spawn: cellar :name {:value "Cellar"} :description {:value "A damp cellar."})
+(spawn: door :name {:value "door"} :tag/door true :tag/openable true :tag/locked true :lock {:key-id :iron})
+(spawn: player :tag/player true :name {:value "you"})
+(spawn: key :name {:value "key"} :tag/takeable true :key {:key-id :iron})
+(spawn: hat :name {:value "hat"} :tag/takeable true :tag/wearable true)
+(spawn: box :name {:value "box"} :tag/container true :tag/openable true)
+(link: player :in-room hall)
+(link: key :in-room hall)
+(link: hat :in-room hall)
+(link: box :in-room hall)
+(link: hall :exit/north door)
+(link: cellar :exit/south door)
+(link: door :door/leads-to hall)
+(link: door :door/leads-to cellar)
+"#,
+        )
+        .unwrap();
+        repl.take_output();
+        let turns = [
+            ("take key", "Taken.\n"),
+            ("take key", "You already have the key.\n"),
+            ("take box", "You can't take the box.\n"),
+            ("i", "You are carrying a key.\n"),
+            ("n", "The door is locked.\n"),
+            ("unlock door with key", "Unlocked.\n"),
+            ("n", "The door is closed.\n"),
+            ("open door", "Opened.\n"),
+            ("n", "\nCellar\nA damp cellar.\nYou can go south.\n"),
+            ("s", "\nHall\nA long hall.\nYou can go north.\n"),
+            ("put key in box", "The box is closed.\n"),
+            ("open box", "Opened.\n"),
+            ("put key in box", "You put the key in the box.\n"),
+            ("take hat", "Taken.\n"),
+            ("wear hat", "You put on the hat.\n"),
+            ("i", "You are carrying a hat (worn).\n"),
+            ("drop hat", "Dropped.\n"),
+            ("i", "You are empty-handed.\n"),
+        ];
+        for (input, expected) in turns {
+            repl.input(input).unwrap();
+            assert_eq!(repl.take_output(), expected, "> {input}");
+        }
 
-        // Spawn an entity
-        repl.eval("(spawn: player :tag/player true)").unwrap();
+        // An actor outside any room has nowhere to drop things
+        repl.input("take hat").unwrap();
+        let entity = |repl: &Repl<MockEditor>, name| {
+            let e = repl.session().get_entity(name).unwrap();
+            format!("(entity-ref {} {})", e.index, e.generation)
+        };
+        repl.eval(&format!(
+            "(unlink! {} :in-room {})",
+            entity(&repl, "player"),
+            entity(&repl, "hall")
+        ))
+        .unwrap();
+        repl.take_output();
+        repl.input("drop hat").unwrap();
+        assert_eq!(repl.take_output(), "There's nowhere to put the hat down.\n");
+        repl.eval("(link: player :in-room hall)").unwrap();
+        repl.input("drop hat").unwrap();
+        repl.take_output();
+
+        // Games override actions and reword messages
+        repl.eval(
+            r#"
+(message: :msg/taken "Got it.")
+(action: drop :params [actor obj] :handler [(println "It's stuck to your hand.")])
+"#,
+        )
+        .unwrap();
+        repl.input("take hat").unwrap();
+        repl.input("drop hat").unwrap();
+        assert_eq!(repl.take_output(), "Got it.\nIt's stuck to your hand.\n");
+    }
 
-        // Verify entity was created
-        assert_eq!(repl.session.world().entity_count(), 1);
+    #[test]
+    fn before_and_after_hooks_veto_and_augment_actions() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.load_stdlib().unwrap();
+        repl.eval(
+            r#"
+(require [stdlib.actions])
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(component: tag/takeable :bool :default true)
+(component: tag/cursed :bool :default true)
+(relationship: in-room :cardinality :many-to-one)
+(relationship: contained-in :cardinality :many-to-one)
+(verb: take)
+(command: take :syntax [:verb/take ?obj] :action take)
+(spawn: hall :name {:value "Hall"})
+(spawn: player :tag/player true :name {:value "you"})
+(spawn: ember :name {:value "ember"} :tag/takeable true)
+(spawn: crown :name {:value "crown"} :tag/takeable true :tag/cursed true)
+(spawn: ring :name {:value "ring"} :tag/takeable true :tag/cursed true)
+(link: player :in-room hall)
+(link: ember :in-room hall)
+(link: crown :in-room hall)
+(link: ring :in-room hall)
+(after take (println "The wind howls."))
+(after take :on :tag/cursed (println "You feel uneasy."))
+(after take :on crown (println "You feel like royalty."))
+(before take :on ember (println "It's too hot to touch.") :stop)
+(before take :on ember (println "Never shown."))
+"#,
+        )
+        .unwrap();
+        repl.take_output();
+
+        repl.input("take ember").unwrap();
+        assert_eq!(repl.take_output(), "It's too hot to touch.\n");
+        repl.input("take crown").unwrap();
+        assert_eq!(
+            repl.take_output(),
+            "Taken.\nYou feel like royalty.\nYou feel uneasy.\nThe wind howls.\n"
+        );
+        repl.input("take ring").unwrap();
+        assert_eq!(
+            repl.take_output(),
+            "Taken.\nYou feel uneasy.\nThe wind howls.\n"
+        );
+
+        let err = repl.eval("(before take :on unicorn :stop)").unwrap_err();
+        assert!(err.to_string().contains("unknown entity 'unicorn'"));
+    }
 
-        // Verify entity is registered by name
-        let entity_id = repl.session.get_entity("player");
-        assert!(entity_id.is_some());
+    #[test]
+    fn fuzzy_matching_corrects_verbs() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(verb: examine :synonyms [x])
+(action: examine :params [actor thing] :handler [(println (str "Examined " ?thing))])
+(command: examine-thing :syntax [:verb/examine ?thing] :action examine)
+(spawn: player :tag/player true :name {:value "you"})
+(spawn: lamp :name {:value "lamp"})
+"#,
+        )
+        .unwrap();
+        let lamp = repl.session().get_entity("lamp").unwrap();
+        let examined = format!("Examined Entity({}, {})\n", lamp.index, lamp.generation);
+
+        repl.input("exam lamp").unwrap();
+        assert!(!repl.take_output().contains("Examined"));
+
+        repl.eval("(fuzzy-matching! true)").unwrap();
+        repl.input("exam lamp").unwrap();
+        assert_eq!(
+            repl.take_output(),
+            format!("(I assume you mean: examine)\n{examined}")
+        );
+        repl.input("exmaine lamp").unwrap();
+        assert_eq!(
+            repl.take_output(),
+            format!("(I assume you mean: examine)\n{examined}")
+        );
+        repl.input("x lamp").unwrap();
+        assert_eq!(repl.take_output(), examined);
     }
 
     #[test]
-    fn spawn_with_component_map() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
+    fn answers_to_disambiguation_finish_the_command() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(component: entity/adjectives :value :vec)
+(verb: take :synonyms [get])
+(action: take :params [actor thing] :handler [(println (str "Taken: " ?thing))])
+(command: take-thing :syntax [:verb/take ?thing] :action take)
+(spawn: player :tag/player true)
+(spawn: brass :name {:value "lamp"} :entity/adjectives {:value ["brass"]})
+(spawn: oil :name {:value "lamp"} :entity/adjectives {:value ["oil"]})
+"#,
+        )
+        .unwrap();
+
+        let taken = |name: &str| {
+            let entity = repl.session().get_entity(name).unwrap();
+            format!("Taken: Entity({}, {})\n", entity.index, entity.generation)
+        };
+        let (brass, oil) = (taken("brass"), taken("oil"));
 
-        // First define the component schema with optional fields with defaults
-        repl.eval("(component: health :current :int :default 0 :max :int :default 100)")
-            .unwrap();
+        repl.input("get the lamp").unwrap();
+        assert!(repl.take_output().contains("Which lamp do you mean?"));
+        repl.input("the oil one").unwrap();
+        assert_eq!(repl.take_output(), oil);
 
-        // Spawn an entity with a map component value
-        repl.eval("(spawn: player :health {:current 100 :max 100})")
-            .unwrap();
+        repl.input("get lamp").unwrap();
+        let question = repl.take_output();
+        let number = if question.contains("1. brass lamp") {
+            "1"
+        } else {
+            "2"
+        };
+        repl.input(number).unwrap();
+        assert_eq!(repl.take_output(), brass);
+
+        // Only the next input answers the question
+        repl.input("get lamp").unwrap();
+        repl.take_output();
+        repl.input("get the oil lamp").unwrap();
+        assert_eq!(repl.take_output(), oil);
+        repl.input("2").unwrap();
+        assert!(!repl.take_output().contains("Taken"));
+    }
 
-        // Verify entity was created
-        assert_eq!(repl.session.world().entity_count(), 1);
+    #[test]
+    fn traces_parser_decisions() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: tag/player :bool :default true)
+(component: name :value :string)
+(verb: take :synonyms [get])
+(action: take :params [actor thing] :handler [])
+(command: take-thing :syntax [:verb/take ?thing] :action take)
+(spawn: player :tag/player true)
+(spawn: brass :name {:value "lamp"})
+(spawn: oil :name {:value "lamp"})
+(trace :on)
+"#,
+        )
+        .unwrap();
+
+        repl.input("get the lamp").unwrap();
+        assert!(repl.take_output().contains("Which lamp do you mean?"));
+
+        let tracer = repl.session().tracer();
+        let types: Vec<&str> = tracer
+            .buffer()
+            .by_event_type("parse")
+            .iter()
+            .map(|r| r.event_type())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                "parse-tokens",
+                "parse-candidates",
+                "parse-noun",
+                "parse-disambiguation"
+            ]
+        );
+        let noun = tracer.buffer().by_event_type("parse-noun");
+        let output = tracer.format_records(&noun, repl.session().world().interner());
+        assert!(
+            output.contains("NOUN ?thing \"lamp\" ambiguous"),
+            "{output}"
+        );
+
+        assert_eq!(
+            repl.eval("(get-traces :type :parse)").unwrap(),
+            Value::Int(4)
+        );
+    }
 
-        // Verify entity is registered by name
-        let entity_id = repl.session.get_entity("player");
-        assert!(entity_id.is_some());
+    #[test]
+    fn world_hash_tracks_content() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval("(component: hp :value :int)").unwrap();
+        let empty = repl.eval("(world-hash)").unwrap();
+        assert_eq!(repl.eval("(world-hash)").unwrap(), empty);
+
+        repl.eval("(spawn: a :hp {:value 1})").unwrap();
+        assert_ne!(repl.eval("(world-hash)").unwrap(), empty);
+        assert!(repl.eval("(world-hash 1)").is_err());
     }
 
     #[test]
-    fn link_creates_relationship() {
+    fn when_feature_gates_content() {
         let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
+        let mut repl = Repl::with_editor(editor).with_features(["debug-content"]);
 
-        // Define components and relationship (tag components)
-        repl.eval("(component: tag/player :bool :default true)")
+        repl.eval("(when-feature :debug-content (def cheats true))")
             .unwrap();
-        repl.eval("(component: tag/room :bool :default true)")
+        repl.eval("(when-feature :steam (def achievements true))")
             .unwrap();
-        repl.eval("(relationship: in-room :cardinality :many-to-one)")
+        repl.eval("(when-feature (and :debug-content (not :steam)) (def dev-build true))")
             .unwrap();
 
-        // Spawn two entities
-        repl.eval("(spawn: player :tag/player true)").unwrap();
-        repl.eval("(spawn: room :tag/room true)").unwrap();
-
-        // Link them
-        repl.eval("(link: player :in-room room)").unwrap();
-
-        // Verify entities exist (player, room, and the relationship entity from dual-write)
-        assert_eq!(repl.session.world().entity_count(), 3);
+        let session = repl.session();
+        assert_eq!(session.get_variable("cheats"), Some(&Value::Bool(true)));
+        assert_eq!(session.get_variable("achievements"), None);
+        assert_eq!(session.get_variable("dev-build"), Some(&Value::Bool(true)));
+        assert!(repl.eval("(when-feature debug-content (def x 1))").is_err());
     }
 
     #[test]
-    fn link_unknown_source_fails() {
+    fn scheduled_effects_fire_after_delay() {
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
 
-        // Define components and relationship
-        repl.eval("(component: tag/room :bool :default true)")
-            .unwrap();
-        repl.eval("(relationship: in-room :cardinality :many-to-one)")
+        repl.eval("(component: health :current :int)").unwrap();
+        repl.eval("(let [hp 7] (schedule! :in 2 :then [(spawn! {:health {:current hp}})]))")
             .unwrap();
+        assert_eq!(repl.tick_executor.scheduler().len(), 1);
+        let before = repl.session().world().entity_count();
 
-        // Spawn only target
-        repl.eval("(spawn: room :tag/room true)").unwrap();
+        repl.eval("(tick!)").unwrap();
+        assert_eq!(repl.session().world().entity_count(), before);
 
-        // Try to link with unknown source
-        let result = repl.eval("(link: player :in-room room)");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("unknown entity"));
+        repl.eval("(tick!)").unwrap();
+        assert_eq!(repl.session().world().entity_count(), before + 1);
+        assert!(repl.tick_executor.scheduler().is_empty());
     }
 
     #[test]
-    fn link_unknown_target_fails() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
-
-        // Define components and relationship
-        repl.eval("(component: tag/player :bool :default true)")
+    fn rolled_back_ticks_requeue_their_timers() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![]));
+        repl.eval("(component: health :current :int)").unwrap();
+        repl.eval("(constraint: alive :where [[?e :health ?h]] :check [(> (get ?h :current) 0)])")
             .unwrap();
-        repl.eval("(relationship: in-room :cardinality :many-to-one)")
+        repl.eval("(schedule! :in 1 :then [(spawn! {:health {:current 0}})])")
             .unwrap();
+        let before = repl.session().world().entity_count();
 
-        // Spawn only source
-        repl.eval("(spawn: player :tag/player true)").unwrap();
-
-        // Try to link with unknown target
-        let result = repl.eval("(link: player :in-room room)");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("unknown entity"));
+        // The spawn breaks the constraint, so the tick rolls back and the
+        // timer is still waiting
+        repl.eval("(tick!)").unwrap();
+        assert_eq!(repl.session().world().entity_count(), before);
+        assert_eq!(repl.tick_executor.scheduler().len(), 1);
     }
 
     #[test]
-    fn component_defines_schema() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
+    fn find_path_walks_exits_and_doors() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: name :value :string)
+(relationship: exit/north :cardinality :one-to-one)
+(relationship: exit/east :cardinality :one-to-one)
+(relationship: door/leads-to :cardinality :many-to-many)
+(spawn: hall :name {:value "hall"})
+(spawn: door :name {:value "door"})
+(spawn: cellar :name {:value "cellar"})
+(spawn: attic :name {:value "attic"})
+(link: hall :exit/north door)
+(link: door :door/leads-to hall)
+(link: door :door/leads-to cellar)
+(link: cellar :exit/east attic)
+"#,
+        )
+        .unwrap();
+        let entity = |repl: &Repl<MockEditor>, name| {
+            let id = repl.session().get_entity(name).unwrap();
+            format!("(entity-ref {} {})", id.index, id.generation)
+        };
+        let hall = entity(&repl, "hall");
+        let attic = entity(&repl, "attic");
 
-        // Define a component with fields
-        repl.eval("(component: health :current :int :max :int :default 100)")
+        let path = repl
+            .eval(&format!(
+                "(map (fn [step] (get-field (nth step 1) :name :value)) (find-path {hall} {attic} :via [:exit :door/leads-to]))"
+            ))
             .unwrap();
-
-        // Should not error - component is registered
+        assert_eq!(path.to_string(), "[door cellar attic]");
+        let first = repl
+            .eval(&format!(
+                "(first (first (find-path {hall} {attic} :via [:exit :door/leads-to])))"
+            ))
+            .unwrap();
+        assert_eq!(repl.display_value(&first), ":exit/north");
+        assert_eq!(
+            repl.eval(&format!("(find-path {hall} {attic} :via :exit)"))
+                .unwrap(),
+            Value::Nil
+        );
+        assert!(repl.eval(&format!("(find-path {hall} {attic})")).is_err());
     }
 
     #[test]
-    fn relationship_defines_schema() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
+    fn proximity_queries_use_the_spatial_index() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: name :value :string)
+(component: position :x :float :y :float)
+(spawn: camp :name {:value "camp"} :position {:x 0.0 :y 0.0})
+(spawn: well :name {:value "well"} :position {:x 3.0 :y 4.0})
+(spawn: tree :name {:value "tree"} :position {:x -1.0 :y 0.0})
+(spawn: tower :name {:value "tower"} :position {:x 40.0 :y 0.0})
+"#,
+        )
+        .unwrap();
+        let id = repl.session().get_entity("camp").unwrap();
+        let camp = format!("(entity-ref {} {})", id.index, id.generation);
+        let near =
+            format!("(map (fn [e] (get-field e :name :value)) (entities-within {camp} 5.0))");
+        // Every entity is near itself; camp is near tree and well
+        let pairs = |repl: &mut Repl<MockEditor>| {
+            let found = repl
+                .eval("(query :where [[?a :position _] [?b :position _] [(within? ?a ?b 5)]] :return [?a ?b])")
+                .unwrap();
+            let Value::Vec(found) = found else {
+                panic!("query returns a vector");
+            };
+            let mut found: Vec<String> = found.iter().map(|pair| format!("{pair:?}")).collect();
+            found.sort();
+            found
+        };
 
-        // Define a relationship
-        repl.eval("(relationship: in-room :cardinality :many-to-one :on-target-delete :remove)")
-            .unwrap();
+        let scanned = repl.eval(&near).unwrap();
+        assert_eq!(scanned.to_string(), "[tree well]");
+        let scanned_pairs = pairs(&mut repl);
+        assert_eq!(scanned_pairs.len(), 8);
 
-        // Should not error - relationship is registered
+        repl.eval("(spatial-index :position :cell-size 2.0)")
+            .unwrap();
+        assert_eq!(repl.eval(&near).unwrap(), scanned);
+        assert_eq!(pairs(&mut repl), scanned_pairs);
+        assert_eq!(
+            repl.eval("(count (entities-within {:x 40 :y 1} 2))")
+                .unwrap(),
+            Value::Int(1)
+        );
+        assert_eq!(
+            repl.eval(&format!("(within? {camp} {{:x 3 :y 4}} 5)"))
+                .unwrap(),
+            Value::Bool(true)
+        );
+        assert!(repl.eval("(spatial-index :position :cell-size 0)").is_err());
     }
 
-    // ==================== Explain System Tests ====================
-
     #[test]
-    fn why_unknown_entity() {
-        let editor = MockEditor::new(vec![]);
-        let mut repl = Repl::with_editor(editor);
-
-        // why with unknown entity name should error
-        let result = repl.eval("(why player :health)");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("unknown entity"));
+    fn daemons_and_fuses_follow_ticks() {
+        let mut repl = Repl::with_editor(MockEditor::new(vec![])).with_captured_output();
+        repl.eval(
+            r#"
+(component: name :value :string)
+(spawn: guard :name {:value "guard"})
+(every 2 :on guard :then [(println (str (get-field self :name :value) " paces."))])
+(fuse 3 :then [(println "The bomb explodes.")])
+"#,
+        )
+        .unwrap();
+        // What the timers printed, without (tick!)'s own report
+        let tick = |repl: &mut Repl<MockEditor>| {
+            repl.eval("(tick!)").unwrap();
+            let output = repl.take_output();
+            output
+                .split_inclusive('\n')
+                .filter(|line| !line.starts_with("Tick "))
+                .collect::<String>()
+        };
+        let ticks: Vec<String> = (0..4).map(|_| tick(&mut repl)).collect();
+        assert_eq!(
+            ticks,
+            [
+                "",
+                "guard paces.\n",
+                "The bomb explodes.\n",
+                "guard paces.\n"
+            ]
+        );
+
+        // Paused after tick 4, the daemon has 2 ticks left whenever it resumes
+        assert_eq!(repl.eval("(pause-timers! guard)").unwrap(), Value::Int(1));
+        let ticks: Vec<String> = (0..3).map(|_| tick(&mut repl)).collect();
+        assert_eq!(ticks, ["", "", ""]);
+        assert_eq!(repl.eval("(resume-timers! guard)").unwrap(), Value::Int(1));
+        assert_eq!(tick(&mut repl), "");
+        assert_eq!(tick(&mut repl), "guard paces.\n");
+
+        // Timers stop when the entity they belong to is destroyed, even paused
+        assert_eq!(repl.eval("(pause-timers! guard)").unwrap(), Value::Int(1));
+        let guard = repl.session().get_entity("guard").unwrap();
+        repl.eval(&format!(
+            "(destroy! (entity-ref {} {}))",
+            guard.index, guard.generation
+        ))
+        .unwrap();
+        let ticks: Vec<String> = (0..2).map(|_| tick(&mut repl)).collect();
+        assert_eq!(ticks, ["", ""]);
+        assert!(repl.tick_executor.scheduler().is_empty());
+        assert_eq!(repl.tick_executor.scheduler().paused().count(), 0);
     }
 
     #[test]
-    fn why_requires_component() {
+    fn scoring_constraints_feed_world_score() {
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
 
-        // why with no component should error
-        let result = repl.eval("(why 0)");
-        assert!(result.is_err());
+        repl.eval("(component: health :current :int)").unwrap();
+        repl.eval("(spawn: weak :health {:current 2})").unwrap();
+        repl.eval("(spawn: strong :health {:current 10})").unwrap();
+        repl.eval(
+            "(constraint: healthy
+               :where [[?e :health ?h]]
+               :check [(>= (get ?h :current) 5)]
+               :on-violation :score
+               :penalty (- 5 (get ?h :current)))",
+        )
+        .unwrap();
+        // The score is the last tick's, so nothing has been scored yet
+        assert_eq!(repl.eval("(world-score)").unwrap(), Value::Float(0.0));
+
+        // Soft violations never roll the tick back
+        repl.eval("(tick!)").unwrap();
+        assert_eq!(repl.tick_executor.tick_number(), 1);
+        assert_eq!(repl.eval("(world-score)").unwrap(), Value::Float(3.0));
+
+        // Queries and rules see it too
+        let scored = repl
+            .eval("(query :where [[?e :health ?h] [(> (world-score) 1.0)]] :return ?e)")
+            .unwrap();
+        assert!(
+            matches!(scored, Value::Vec(ref v) if v.len() == 2),
+            "{scored:?}"
+        );
     }
 
     #[test]
-    fn why_with_entity_ref() {
+    fn emitted_events_are_drained_by_tick() {
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
 
-        // Define component
-        repl.eval("(component: tag/player :bool :default true)")
-            .unwrap();
+        repl.eval("(emit! :event/door-opened {:door 1})").unwrap();
+        let events = longtable_engine::event::pending(repl.session().world());
+        assert_eq!(events.len(), 1);
 
-        // Spawn entity
-        repl.eval("(spawn: player :tag/player true)").unwrap();
+        let opened = repl
+            .session()
+            .world()
+            .interner()
+            .lookup_keyword("event/door-opened")
+            .unwrap();
+        assert!(repl.session().world().has(events[0], opened));
 
-        // why with entity by name should work (even if no provenance recorded)
-        let result = repl.eval("(why player :tag/player)");
-        assert!(result.is_ok());
+        repl.eval("(tick!)").unwrap();
+        assert!(longtable_engine::event::pending(repl.session().world()).is_empty());
     }
 
     #[test]
-    fn why_with_depth() {
+    fn telemetry_records_ticks_after_opt_in() {
+        use crate::telemetry::{TelemetryEvent, TelemetrySink};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct Sink(Rc<RefCell<Vec<TelemetryEvent>>>);
+        impl TelemetrySink for Sink {
+            fn send(&mut self, events: &[TelemetryEvent]) {
+                self.0.borrow_mut().extend_from_slice(events);
+            }
+        }
+
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
+        let received = Rc::new(RefCell::new(Vec::new()));
+        repl.session_mut()
+            .telemetry_mut()
+            .set_sink(Box::new(Sink(received.clone())));
+
+        repl.eval("(tick!)").unwrap();
+        assert_eq!(repl.session().telemetry().pending_len(), 0);
+
+        assert_eq!(
+            repl.eval("(telemetry-opt-in! true)").unwrap(),
+            Value::Bool(true)
+        );
+        repl.eval("(tick!)").unwrap();
+        repl.session_mut().telemetry_mut().flush();
+        assert!(matches!(
+            received.borrow().as_slice(),
+            [TelemetryEvent::Tick { .. }]
+        ));
+    }
 
-        // Define component
-        repl.eval("(component: tag/player :bool :default true)")
-            .unwrap();
+    #[test]
+    fn play_mode_disables_debug_bookkeeping() {
+        let editor = MockEditor::new(vec![]);
+        let repl = Repl::with_editor(editor).with_mode(ExecutionMode::Play);
 
-        // Spawn entity
-        repl.eval("(spawn: player :tag/player true)").unwrap();
+        assert_eq!(repl.tick_executor.mode(), ExecutionMode::Play);
+        assert!(!repl.tick_executor.provenance().is_enabled());
+        assert!(!repl.session().tracer().is_enabled());
+        assert!(!repl.session().timeline().is_enabled());
+        assert!(!repl.session().observability().provenance);
+    }
 
-        // why with depth should work
-        let result = repl.eval("(why player :tag/player :depth 3)");
-        assert!(result.is_ok());
+    #[test]
+    fn play_mode_refuses_to_turn_observability_back_on() {
+        let editor = MockEditor::new(vec![]);
+        let mut repl = Repl::with_editor(editor).with_mode(ExecutionMode::Play);
+
+        assert!(repl.eval("(trace :on)").is_err());
+        assert!(repl.eval("(observability {:provenance true})").is_err());
+        assert!(repl.eval("(observability {:history-size 50})").is_err());
+        assert!(!repl.session().tracer().is_enabled());
+        assert!(!repl.session().timeline().is_enabled());
+        assert!(!repl.tick_executor.provenance().is_enabled());
+
+        // Settings that change only how traces would look are fine
+        repl.eval("(trace :json)").unwrap();
+        repl.eval("(observability {:verbosity :full})").unwrap();
+        assert!(!repl.tick_executor.provenance().is_enabled());
     }
 
     #[test]
-    fn explain_query_basic() {
+    fn observability_reconfigures_subsystems() {
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
 
-        // Define component
-        repl.eval("(component: tag/player :bool :default true)")
-            .unwrap();
-
-        // Spawn entity
-        repl.eval("(spawn: player :tag/player true)").unwrap();
-
-        // explain-query should work
-        let result = repl.eval("(explain-query (query :where [[?e :tag/player]] :return ?e))");
-        assert!(result.is_ok());
+        repl.eval(
+            "(observability {:history-size 5 :keyframe-interval 2 :trace-buffer-size 64 :verbosity :full})",
+        )
+        .unwrap();
+
+        let session = repl.session();
+        assert_eq!(session.observability().history_size, 5);
+        assert_eq!(session.timeline().config().keyframe_interval, 2);
+        assert_eq!(session.tracer().stats().max_size, 64);
+        assert_eq!(
+            repl.tick_executor.provenance().verbosity(),
+            ProvenanceVerbosity::Full
+        );
+
+        // (trace :on) goes through the same config
+        repl.eval("(trace :on)").unwrap();
+        assert!(repl.session().observability().enabled);
+        assert!(repl.session().tracer().is_enabled());
+
+        assert!(repl.eval("(observability {:bogus 1})").is_err());
+        assert!(repl.eval("(observability {:history-size -1})").is_err());
     }
 
     #[test]
-    fn explain_query_with_entity() {
+    fn on_phase_rejects_unknown_phase() {
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
 
-        // Define component
-        repl.eval("(component: tag/player :bool :default true)")
-            .unwrap();
-
-        // Spawn entity
-        repl.eval("(spawn: player :tag/player true)").unwrap();
-
-        // explain-query with entity should work
-        let result =
-            repl.eval("(explain-query (query :where [[?e :tag/player]] :return ?e) player)");
-        assert!(result.is_ok());
+        assert!(repl.eval("(on-phase :whenever (fn [ctx] nil))").is_err());
+        assert!(repl.session().phase_hooks().is_empty());
     }
 
     #[test]
-    fn explain_query_invalid_form() {
+    fn undo_fails_with_empty_stack() {
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
 
-        // explain-query with non-query should error
-        let result = repl.eval("(explain-query 42)");
-        assert!(result.is_err());
+        assert!(repl.eval("(undo!)").is_err());
     }
 
-    // ==================== Time Travel Tests ====================
-
     #[test]
-    fn timeline_shows_status() {
+    fn input_is_recorded_in_transcript() {
         let editor = MockEditor::new(vec![]);
         let mut repl = Repl::with_editor(editor);
 
-        // timeline command should work
-        let result = repl.eval("(timeline)");
-        assert!(result.is_ok());
+        repl.eval("(component: tag/player :bool :default true)")
+            .unwrap();
+        repl.eval("(spawn: player :tag/player true)").unwrap();
+        repl.eval(r#"(input! "xyzzy")"#).unwrap();
+
+        let entries = repl.session().transcript().entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].input, "xyzzy");
+        assert_eq!(entries[0].outcome, InputOutcome::Error);
+        assert!(entries[0].error.is_some());
+
+        let result = repl.eval("(transcript)").unwrap();
+        assert_eq!(result, Value::Int(1));
     }
 
     #[test]
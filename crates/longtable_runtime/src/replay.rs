@@ -0,0 +1,241 @@
+//! Replay logs for reproducing sessions deterministically.
+//!
+//! A [`ReplayLog`] records every tick a session runs: the tick number, the
+//! [`InputEvent`]s injected, and the world's content hash once the tick is
+//! done. Together with a snapshot of the world when recording began and the
+//! content files that were loaded, that is enough for
+//! `longtable replay session.ltr` to re-run a user's session and check that
+//! every tick produces the same world.
+//!
+//! Changes made between ticks (REPL edits, actions run in input mode) are not
+//! input events, so when the world before a tick differs from the world the
+//! previous tick left behind, that frame carries a snapshot to resume from.
+//!
+//! Logs are stored as `MessagePack`, like saved worlds (see [`crate::serialize`]).
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use longtable_engine::InputEvent;
+use longtable_foundation::{Error, ErrorKind, KeywordId, Result};
+use longtable_storage::World;
+use serde::{Deserialize, Serialize};
+
+/// One recorded tick.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    /// Tick number after the tick ran.
+    pub tick: u64,
+    /// Inputs injected at the start of the tick.
+    pub inputs: Vec<InputEvent>,
+    /// World to run the tick against, if it was changed since the previous tick.
+    pub rebase: Option<World>,
+    /// Content hash of the world after the tick.
+    pub hash: u64,
+}
+
+/// A recorded session: a base snapshot plus every tick run after it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayLog {
+    /// Content files loaded before recording began, in load order.
+    pub sources: Vec<PathBuf>,
+    /// Content features that were enabled.
+    pub features: Vec<String>,
+    /// Tick number when recording began.
+    pub base_tick: u64,
+    /// World when recording began.
+    pub base: World,
+    /// Recorded ticks, in order.
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl ReplayLog {
+    /// Adds every keyword the recorded worlds and input events hold to `live`.
+    pub fn mark_keywords(&self, live: &mut HashSet<KeywordId>) {
+        self.base.mark_keywords(live);
+        for frame in &self.frames {
+            if let Some(world) = &frame.rebase {
+                world.mark_keywords(live);
+            }
+            for input in &frame.inputs {
+                match input {
+                    InputEvent::Set {
+                        component, value, ..
+                    } => {
+                        live.insert(*component);
+                        value.mark_keywords(live);
+                    }
+                    InputEvent::Spawn { components } => {
+                        for (component, value) in components {
+                            live.insert(*component);
+                            value.mark_keywords(live);
+                        }
+                    }
+                    InputEvent::Destroy { .. } => {}
+                    InputEvent::Custom { name, payload } => {
+                        live.insert(*name);
+                        payload.mark_keywords(live);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Starts a log from the world as it stands at tick `base_tick`.
+    #[must_use]
+    pub fn new(base: World, base_tick: u64) -> Self {
+        Self {
+            sources: Vec::new(),
+            features: Vec::new(),
+            base_tick,
+            base,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Sets the content files to load before replaying.
+    #[must_use]
+    pub fn with_sources(mut self, sources: Vec<PathBuf>) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    /// Sets the content features to enable before replaying.
+    #[must_use]
+    pub fn with_features(mut self, features: Vec<String>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Records a tick that ran against `before` and left the world as `after`.
+    pub fn record(&mut self, tick: u64, inputs: &[InputEvent], before: &World, after: &World) {
+        let expected = self
+            .frames
+            .last()
+            .map_or_else(|| self.base.content_hash(), |frame| frame.hash);
+        let rebase = (before.content_hash() != expected).then(|| before.clone());
+        self.frames.push(ReplayFrame {
+            tick,
+            inputs: inputs.to_vec(),
+            rebase,
+            hash: after.content_hash(),
+        });
+    }
+
+    /// Checks a replayed world against the recorded hash for `frame`.
+    ///
+    /// # Errors
+    /// Returns an error naming the tick if the hashes differ.
+    pub fn verify(frame: &ReplayFrame, world: &World) -> Result<()> {
+        let actual = world.content_hash();
+        if actual == frame.hash {
+            Ok(())
+        } else {
+            Err(Error::new(ErrorKind::Refused(format!(
+                "replay diverged at tick {}: expected world hash {:016x}, got {actual:016x}",
+                frame.tick, frame.hash
+            ))))
+        }
+    }
+
+    /// Returns the number of recorded ticks.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns true if no ticks were recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Writes the log to a file.
+    ///
+    /// # Errors
+    /// Returns an error if serialization fails or the file cannot be written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let bytes = rmp_serde::to_vec_named(self)
+            .map_err(|e| Error::new(ErrorKind::SerializationError(e.to_string())))?;
+        std::fs::write(path.as_ref(), bytes).map_err(|e| {
+            Error::new(ErrorKind::IoError(format!(
+                "failed to write replay log '{}': {e}",
+                path.as_ref().display()
+            )))
+        })
+    }
+
+    /// Reads a log from a file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a replay log.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let bytes = std::fs::read(path.as_ref()).map_err(|e| {
+            Error::new(ErrorKind::IoError(format!(
+                "failed to read replay log '{}': {e}",
+                path.as_ref().display()
+            )))
+        })?;
+        rmp_serde::from_slice(&bytes)
+            .map_err(|e| Error::new(ErrorKind::SerializationError(e.to_string())))
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::{LtMap, Value};
+
+    fn spawn(world: &World) -> World {
+        world.spawn(&LtMap::new()).unwrap().0
+    }
+
+    #[test]
+    fn record_rebases_only_after_outside_changes() {
+        let base = World::new(7);
+        let mut log = ReplayLog::new(base.clone(), 0);
+
+        let first = base.advance_tick();
+        log.record(1, &[], &base, &first);
+        let edited = spawn(&first);
+        let second = edited.advance_tick();
+        log.record(2, &[], &edited, &second);
+
+        assert!(log.frames[0].rebase.is_none());
+        assert!(log.frames[1].rebase.is_some());
+        assert!(ReplayLog::verify(&log.frames[1], &second).is_ok());
+        let err = ReplayLog::verify(&log.frames[1], &first).unwrap_err();
+        assert!(err.to_string().contains("replay diverged at tick 2"));
+    }
+
+    #[test]
+    fn roundtrips_through_file() {
+        let base = World::new(7);
+        let mut log = ReplayLog::new(base.clone(), 0).with_features(vec!["debug".into()]);
+        let mut world = base.clone();
+        let component = world.interner_mut().intern_keyword("input/go");
+        let inputs = [InputEvent::Custom {
+            name: component,
+            payload: Value::Nil,
+        }];
+        log.record(1, &inputs, &base, &world.advance_tick());
+
+        let path =
+            std::env::temp_dir().join(format!("longtable-replay-{}.ltr", std::process::id()));
+        log.save(&path).unwrap();
+        let loaded = ReplayLog::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.features, vec!["debug".to_string()]);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.frames[0].hash, log.frames[0].hash);
+        assert!(matches!(
+            loaded.frames[0].inputs[0],
+            InputEvent::Custom { .. }
+        ));
+    }
+}
@@ -0,0 +1,136 @@
+//! Renderers for rich text output.
+//!
+//! `say` records its output as [`RichSpan`]s (see the language crate). The
+//! REPL shows them in a terminal with [`to_ansi`]; GUI clients get them from
+//! the REPL server as [`to_json`]:
+//!
+//! ```text
+//! [{"text": "You see "}, {"text": "a door", "entity": [4, 0]}, {"text": ".\n"}]
+//! ```
+//!
+//! Each object has the span's `text`, plus `em`, `strong`, `color`, and
+//! `entity` when the span has that style.
+
+use std::fmt::Write as _;
+
+use longtable_language::RichSpan;
+use serde_json::{Map, Value as Json, json};
+
+/// Renders spans for a terminal, with ANSI escapes for their styles.
+///
+/// Links are underlined. Colours other than the eight basic ANSI colours
+/// are left out.
+#[must_use]
+pub fn to_ansi(spans: &[RichSpan]) -> String {
+    let mut out = String::new();
+    for span in spans {
+        let mut codes = Vec::new();
+        if span.strong {
+            codes.push(1);
+        }
+        if span.emphasis {
+            codes.push(3);
+        }
+        if span.link.is_some() {
+            codes.push(4);
+        }
+        if let Some(color) = span.color.as_deref().and_then(ansi_color) {
+            codes.push(color);
+        }
+        if codes.is_empty() {
+            out.push_str(&span.text);
+            continue;
+        }
+        let codes: Vec<String> = codes.iter().map(u8::to_string).collect();
+        let _ = write!(out, "\x1b[{}m{}\x1b[0m", codes.join(";"), span.text);
+    }
+    out
+}
+
+/// Returns the ANSI foreground code for a colour name.
+fn ansi_color(name: &str) -> Option<u8> {
+    let code = match name {
+        "black" => 30,
+        "red" => 31,
+        "green" => 32,
+        "yellow" => 33,
+        "blue" => 34,
+        "magenta" => 35,
+        "cyan" => 36,
+        "white" => 37,
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// Encodes spans as a JSON array of objects, one per span.
+#[must_use]
+pub fn to_json(spans: &[RichSpan]) -> Json {
+    let spans = spans
+        .iter()
+        .map(|span| {
+            let mut object = Map::new();
+            object.insert("text".to_string(), json!(span.text));
+            if span.emphasis {
+                object.insert("em".to_string(), json!(true));
+            }
+            if span.strong {
+                object.insert("strong".to_string(), json!(true));
+            }
+            if let Some(color) = &span.color {
+                object.insert("color".to_string(), json!(color));
+            }
+            if let Some(entity) = span.link {
+                object.insert(
+                    "entity".to_string(),
+                    json!([entity.index, entity.generation]),
+                );
+            }
+            Json::Object(object)
+        })
+        .collect();
+    Json::Array(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_foundation::EntityId;
+
+    fn spans() -> Vec<RichSpan> {
+        vec![
+            RichSpan::plain("You see "),
+            RichSpan {
+                text: "a door".to_string(),
+                link: Some(EntityId::new(4, 0)),
+                ..RichSpan::default()
+            },
+            RichSpan {
+                text: " ajar".to_string(),
+                emphasis: true,
+                color: Some("red".to_string()),
+                ..RichSpan::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn renders_ansi_escapes() {
+        assert_eq!(
+            to_ansi(&spans()),
+            "You see \x1b[4ma door\x1b[0m\x1b[3;31m ajar\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn renders_json_objects() {
+        assert_eq!(
+            to_json(&spans()),
+            json!([
+                {"text": "You see "},
+                {"text": "a door", "entity": [4, 0]},
+                {"text": " ajar", "em": true, "color": "red"},
+            ])
+        );
+    }
+}
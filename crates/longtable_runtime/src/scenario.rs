@@ -0,0 +1,180 @@
+//! Scenarios: simulation tests written in the DSL.
+//!
+//! A scenario sets up a situation, plays it forward, and checks the world
+//! that results:
+//!
+//! ```text
+//! (scenario: "goblin dies"
+//!   :setup  [(spawn: goblin :health {:current 1} :tag/enemy true)]
+//!   :steps  [(input! "attack goblin") (tick!)]
+//!   :assert [[?g :tag/enemy true] [?g :health/current 0]])
+//! (run-scenarios)
+//! ```
+//!
+//! Setup and steps are forms evaluated in order against the loaded game.
+//! The assertion vectors are query clauses that must match together (the
+//! same `:where` a query takes), plus field clauses like
+//! `[?g :health/current 0]` that test one field of a component against a
+//! literal value; an assertion written as a list is an expression that must
+//! be truthy. `(run-scenarios)` runs each scenario in
+//! turn and restores the session afterwards, so scenarios don't see each
+//! other's changes, and `longtable test game/` runs them from the command
+//! line. A failed clause is reported as a diff between the clause and the
+//! values the world holds instead.
+
+use std::fmt::Write as _;
+
+use longtable_foundation::{Error, ErrorKind, Result};
+use longtable_language::Ast;
+
+/// A declared scenario.
+#[derive(Clone, Debug)]
+pub struct Scenario {
+    /// The scenario's name.
+    pub name: String,
+    /// Forms that set up the situation.
+    pub setup: Vec<Ast>,
+    /// Forms that play it forward, such as `(input! ...)` and `(tick!)`.
+    pub steps: Vec<Ast>,
+    /// Query clauses (vectors) and expressions (lists) to check.
+    pub assertions: Vec<Ast>,
+}
+
+impl Scenario {
+    /// Reads a scenario from the arguments of a `scenario:` form.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is missing or an option is unknown or
+    /// not a vector.
+    pub fn parse(args: &[Ast]) -> Result<Self> {
+        let usage = |message: &str| {
+            Error::new(ErrorKind::InvalidArgument(format!(
+                "scenario: {message}: (scenario: \"name\" :setup [...] :steps [...] :assert [...])"
+            )))
+        };
+        let Some((Ast::String(name, _), mut options)) = args.split_first() else {
+            return Err(usage("requires a name"));
+        };
+
+        let mut scenario = Self {
+            name: name.clone(),
+            setup: Vec::new(),
+            steps: Vec::new(),
+            assertions: Vec::new(),
+        };
+        while let [Ast::Keyword(option, _), Ast::Vector(forms, _), rest @ ..] = options {
+            let target = match option.as_str() {
+                "setup" => &mut scenario.setup,
+                "steps" => &mut scenario.steps,
+                "assert" => &mut scenario.assertions,
+                _ => return Err(usage(&format!("unknown option :{option}"))),
+            };
+            target.extend(forms.iter().cloned());
+            options = rest;
+        }
+        if !options.is_empty() {
+            return Err(usage("expects :setup, :steps, and :assert vectors"));
+        }
+        if scenario.assertions.is_empty() {
+            return Err(usage("requires at least one :assert"));
+        }
+        Ok(scenario)
+    }
+}
+
+/// Why a scenario failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScenarioFailure {
+    /// The assertion that failed, or the setup or step form that errored.
+    pub form: String,
+    /// The expected and actual values as `-` and `+` lines, or the error.
+    pub detail: String,
+}
+
+/// The outcome of running one scenario.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScenarioResult {
+    /// The scenario's name.
+    pub name: String,
+    /// Everything that went wrong; empty if it passed.
+    pub failures: Vec<ScenarioFailure>,
+    /// The output the scenario printed.
+    pub output: String,
+}
+
+impl ScenarioResult {
+    /// Returns true if every assertion held.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Formats results as a report: a line per scenario, the failures beneath
+/// each one that failed, and a count.
+#[must_use]
+pub fn report(results: &[ScenarioResult]) -> String {
+    let mut text = String::new();
+    for result in results {
+        if result.passed() {
+            let _ = writeln!(text, "ok      {}", result.name);
+            continue;
+        }
+        let _ = writeln!(text, "FAILED  {}", result.name);
+        for failure in &result.failures {
+            let _ = writeln!(text, "  {}", failure.form);
+            for line in failure.detail.lines() {
+                let _ = writeln!(text, "    {line}");
+            }
+        }
+        if !result.output.is_empty() {
+            let _ = writeln!(text, "  output:");
+            for line in result.output.lines() {
+                let _ = writeln!(text, "    {line}");
+            }
+        }
+    }
+    let failed = results.iter().filter(|r| !r.passed()).count();
+    let _ = writeln!(text, "{} scenario(s), {failed} failed", results.len());
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use longtable_language::parse;
+
+    fn scenario(source: &str) -> Result<Scenario> {
+        let form = parse(source).unwrap().remove(0);
+        let Ast::List(elements, _) = form else {
+            panic!("not a list")
+        };
+        Scenario::parse(&elements[1..])
+    }
+
+    #[test]
+    fn parses_scenario_declarations() {
+        let parsed = scenario(
+            r#"(scenario: "goblin dies"
+                 :setup [(spawn: goblin :health {:current 1})]
+                 :steps [(input! "attack goblin") (tick!)]
+                 :assert [[?g :health {:current 0}]])"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.name, "goblin dies");
+        assert_eq!(
+            (
+                parsed.setup.len(),
+                parsed.steps.len(),
+                parsed.assertions.len()
+            ),
+            (1, 2, 1)
+        );
+
+        assert!(scenario("(scenario: :assert [[?g :health]])").is_err());
+        assert!(scenario(r#"(scenario: "x" :setup [])"#).is_err());
+        assert!(scenario(r#"(scenario: "x" :expect [] :assert [[?g :health]])"#).is_err());
+        assert!(scenario(r#"(scenario: "x" :assert [[?g :health]] :steps)"#).is_err());
+    }
+}
@@ -1,14 +1,47 @@
-//! World serialization and deserialization using `MessagePack`.
+//! World serialization and deserialization.
 //!
 //! This module provides functions for saving and loading world state
-//! to/from files using the `MessagePack` binary format.
-
+//! to/from files using the `MessagePack` binary format, and for exporting
+//! and importing entity data as human-editable JSON.
+//!
+//! # JSON format
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "tick": 3,
+//!   "seed": 42,
+//!   "entities": [
+//!     { "id": [0, 1], "name": "knight", "components": { "health": { "current": 5 } } },
+//!     { "id": [1, 1], "components": { "room": { "title": "Hall" } } }
+//!   ],
+//!   "relationships": [
+//!     { "type": ":in-room", "source": [0, 1], "target": [1, 1] }
+//!   ]
+//! }
+//! ```
+//!
+//! Component values use the encoding in [`crate::json`]. Schemas are not part
+//! of the file: importing takes them from a template world, usually the one
+//! the session's DSL files declared, and rejects components or relationships
+//! it doesn't know. Importing restores every entity at the id it was saved
+//! with, so `{"$entity": [...]}` references, relationship endpoints, and ids
+//! held outside the world stay valid.
+
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
+use std::hash::BuildHasher;
 use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::Path;
 
-use longtable_foundation::{Error, ErrorKind, Result};
+use longtable_foundation::{EntityId, Error, ErrorKind, KeywordId, Result, Value};
 use longtable_storage::World;
+use serde_json::{Value as Json, json};
+
+use crate::json;
+
+/// Version of the JSON world format written by [`world_to_json`].
+pub const JSON_FORMAT_VERSION: u64 = 1;
 
 /// Serializes a world to bytes using `MessagePack` format.
 ///
@@ -94,6 +127,319 @@ pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<World> {
     from_bytes(&bytes)
 }
 
+/// Exports a world's entities, components, and relationships as JSON.
+///
+/// Entities named in `names` carry a `"name"`; when several names refer to
+/// the same entity the alphabetically first is used.
+#[must_use]
+pub fn world_to_json<S: BuildHasher>(world: &World, names: &HashMap<String, EntityId, S>) -> Json {
+    let mut entity_names: HashMap<EntityId, &str> = HashMap::new();
+    for (name, &entity) in names {
+        let slot = entity_names.entry(entity).or_insert(name);
+        if name.as_str() < *slot {
+            *slot = name;
+        }
+    }
+
+    let interner = world.interner();
+    let mut entities = Vec::new();
+    for entity in world.entities() {
+        if world.has(entity, KeywordId::REL_TYPE) {
+            continue;
+        }
+        let mut components = serde_json::Map::new();
+        for &component in world.entity_components(entity) {
+            if let Ok(Some(value)) = world.get(entity, component) {
+                let name = interner.get_keyword(component).unwrap_or("?").to_string();
+                components.insert(name, json::to_json(&value, interner));
+            }
+        }
+        let mut record = json!({
+            "id": [entity.index, entity.generation],
+            "components": components,
+        });
+        if let Some(name) = entity_names.get(&entity) {
+            record["name"] = json!(name);
+        }
+        entities.push(record);
+    }
+
+    let relationships: Vec<Json> = world
+        .find_relationships(None, None, None)
+        .into_iter()
+        .filter_map(|rel| {
+            let kind = relationship_field(world, rel, KeywordId::REL_TYPE)?;
+            let Value::EntityRef(source) = relationship_field(world, rel, KeywordId::REL_SOURCE)?
+            else {
+                return None;
+            };
+            let Value::EntityRef(target) = relationship_field(world, rel, KeywordId::REL_TARGET)?
+            else {
+                return None;
+            };
+            Some(json!({
+                "type": json::to_json(&kind, interner),
+                "source": [source.index, source.generation],
+                "target": [target.index, target.generation],
+            }))
+        })
+        .collect();
+
+    json!({
+        "version": JSON_FORMAT_VERSION,
+        "tick": world.tick(),
+        "seed": world.seed(),
+        "entities": entities,
+        "relationships": relationships,
+    })
+}
+
+/// Reads the `:value` field of one of a relationship entity's components.
+pub(crate) fn relationship_field(
+    world: &World,
+    rel: EntityId,
+    component: KeywordId,
+) -> Option<Value> {
+    match world.get(rel, component).ok()?? {
+        Value::Map(map) => map.get(&Value::Keyword(KeywordId::VALUE)).cloned(),
+        _ => None,
+    }
+}
+
+/// Builds a world from JSON written by [`world_to_json`] (or by hand in the
+/// same shape), using the schemas and interner of `template`.
+///
+/// Returns the world and the entity names the file declared.
+///
+/// # Errors
+///
+/// Returns an error if the JSON doesn't follow the format, names an unknown
+/// component or relationship, refers to an entity the file doesn't define,
+/// or has values that don't fit their schemas.
+pub fn world_from_json(
+    input: &Json,
+    template: &World,
+) -> Result<(World, HashMap<String, EntityId>)> {
+    if let Some(version) = input.get("version") {
+        if version.as_u64() != Some(JSON_FORMAT_VERSION) {
+            return Err(invalid(format!(
+                "unsupported world format version {version}, expected {JSON_FORMAT_VERSION}"
+            )));
+        }
+    }
+    let seed = match input.get("seed") {
+        Some(seed) => seed
+            .as_u64()
+            .ok_or_else(|| invalid(format!("seed must be a non-negative integer, got {seed}")))?,
+        None => template.seed(),
+    };
+    let tick = match input.get("tick") {
+        Some(tick) => tick
+            .as_u64()
+            .ok_or_else(|| invalid(format!("tick must be a non-negative integer, got {tick}")))?,
+        None => 0,
+    };
+
+    let mut world = empty_world_like(template, seed)?;
+    let entities = array_field(input, "entities")?;
+    let mut ids = HashSet::new();
+    let mut names = HashMap::new();
+    for record in entities {
+        let entity = json::entity_from_json(record.get("id").unwrap_or(&Json::Null))?;
+        if !ids.insert(entity) {
+            return Err(invalid(format!("entity {entity} is defined twice")));
+        }
+        world = world.restore_entity(entity)?;
+        match record.get("name") {
+            Some(Json::String(name)) => {
+                names.insert(name.clone(), entity);
+            }
+            Some(other) => {
+                return Err(invalid(format!(
+                    "entity name must be a string, got {other}"
+                )));
+            }
+            None => {}
+        }
+    }
+
+    for record in entities {
+        let entity = json::entity_from_json(&record["id"])?;
+        let Some(components) = record.get("components") else {
+            continue;
+        };
+        let components = components
+            .as_object()
+            .ok_or_else(|| invalid(format!("components must be an object, got {components}")))?;
+        for (name, value) in components {
+            let component = world.interner_mut().intern_keyword(name);
+            if world.component_schema(component).is_none() {
+                return Err(invalid(format!("unknown component :{name}")));
+            }
+            let value = json::from_json(value, world.interner_mut())?;
+            check_references(&value, &ids)?;
+            world = world.set(entity, component, value)?;
+        }
+    }
+
+    for record in array_field(input, "relationships")? {
+        let kind = match record.get("type") {
+            Some(Json::String(kind)) => kind.strip_prefix(':').unwrap_or(kind),
+            other => {
+                return Err(invalid(format!(
+                    "relationship type must be a keyword string, got {}",
+                    other.unwrap_or(&Json::Null)
+                )));
+            }
+        };
+        let relationship = world.interner_mut().intern_keyword(kind);
+        if world.relationship_schema(relationship).is_none() {
+            return Err(invalid(format!("unknown relationship :{kind}")));
+        }
+        let endpoint = |field: &str| -> Result<EntityId> {
+            let file_id = json::entity_from_json(record.get(field).unwrap_or(&Json::Null))?;
+            ids.get(&file_id).copied().ok_or_else(|| {
+                invalid(format!(
+                    "relationship {field} {file_id} is not defined in the file"
+                ))
+            })
+        };
+        world = world.link(endpoint("source")?, relationship, endpoint("target")?)?;
+    }
+
+    Ok((world.with_tick(tick), names))
+}
+
+/// Creates an empty world with `template`'s interner and component and
+/// relationship schemas.
+pub(crate) fn empty_world_like(template: &World, seed: u64) -> Result<World> {
+    let mut world = World::new(seed);
+    world.set_interner(template.interner().clone());
+    for schema in template.component_schemas() {
+        if ![
+            KeywordId::REL_TYPE,
+            KeywordId::REL_SOURCE,
+            KeywordId::REL_TARGET,
+        ]
+        .contains(&schema.name)
+        {
+            world = world.register_component(schema.clone())?;
+        }
+    }
+    for schema in template.relationship_schemas() {
+        world = world.register_relationship(schema.clone())?;
+    }
+    Ok(world)
+}
+
+/// Returns `input[field]` as an array, treating a missing field as empty.
+fn array_field<'a>(input: &'a Json, field: &str) -> Result<&'a [Json]> {
+    match input.get(field) {
+        None => Ok(&[]),
+        Some(Json::Array(items)) => Ok(items),
+        Some(other) => Err(invalid(format!("{field} must be an array, got {other}"))),
+    }
+}
+
+/// Checks that every entity reference in `value` is an entity the file
+/// defines.
+fn check_references(value: &Value, ids: &HashSet<EntityId>) -> Result<()> {
+    remap_references(value, &mut |id| {
+        if ids.contains(&id) {
+            Ok(id)
+        } else {
+            Err(invalid(format!(
+                "reference to entity {id} not defined in the file"
+            )))
+        }
+    })
+    .map(drop)
+}
+
+/// Rebuilds `value` with every entity reference in it, including those
+/// inside collections, replaced by what `remap` returns for it.
+pub(crate) fn remap_references(
+    value: &Value,
+    remap: &mut dyn FnMut(EntityId) -> Result<EntityId>,
+) -> Result<Value> {
+    Ok(match value {
+        Value::EntityRef(id) => Value::EntityRef(remap(*id)?),
+        Value::Vec(items) => Value::Vec(
+            items
+                .iter()
+                .map(|v| remap_references(v, remap))
+                .collect::<Result<_>>()?,
+        ),
+        Value::List(items) => Value::List(
+            items
+                .iter()
+                .map(|v| remap_references(v, remap))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Set(items) => Value::Set(
+            items
+                .iter()
+                .map(|v| remap_references(v, remap))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Map(map) => Value::Map(
+            map.iter()
+                .map(|(k, v)| Ok((remap_references(k, remap)?, remap_references(v, remap)?)))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+pub(crate) fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::SerializationError(message))
+}
+
+/// Writes a world as pretty-printed JSON (see [`world_to_json`]).
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn save_json_to_file<P: AsRef<Path>, S: BuildHasher>(
+    world: &World,
+    names: &HashMap<String, EntityId, S>,
+    path: P,
+) -> Result<()> {
+    let text = serde_json::to_string_pretty(&world_to_json(world, names))
+        .map_err(|e| Error::new(ErrorKind::SerializationError(e.to_string())))?;
+    std::fs::write(path.as_ref(), text + "\n").map_err(|e| {
+        Error::new(ErrorKind::IoError(format!(
+            "failed to write to file '{}': {e}",
+            path.as_ref().display()
+        )))
+    })
+}
+
+/// Reads a JSON world file (see [`world_from_json`]).
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, isn't valid JSON, or doesn't
+/// describe a world `template`'s schemas accept.
+pub fn load_json_from_file<P: AsRef<Path>>(
+    path: P,
+    template: &World,
+) -> Result<(World, HashMap<String, EntityId>)> {
+    let text = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+        Error::new(ErrorKind::IoError(format!(
+            "failed to open file '{}': {e}",
+            path.as_ref().display()
+        )))
+    })?;
+    let input: Json = serde_json::from_str(&text).map_err(|e| {
+        invalid(format!(
+            "invalid JSON in '{}': {e}",
+            path.as_ref().display()
+        ))
+    })?;
+    world_from_json(&input, template)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -181,6 +527,18 @@ mod tests {
         let _ = std::fs::remove_file(&temp_path);
     }
 
+    #[test]
+    fn loading_restamps_component_versions() {
+        let world = create_test_world();
+        let health = world.interner().lookup_keyword("health").unwrap();
+        let before = world.component_version(health);
+        assert_ne!(before, 0);
+
+        // A cache that saw the old stamp mustn't mistake the loaded data for it
+        let restored = from_bytes(&to_bytes(&world).unwrap()).unwrap();
+        assert!(restored.component_version(health) > before);
+    }
+
     #[test]
     fn entities_preserved() {
         let world = create_test_world();
@@ -202,6 +560,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn entity_policy_preserved() {
+        use longtable_storage::{EntityPolicy, GenerationOverflow};
+
+        let policy = EntityPolicy::append_only().with_overflow(GenerationOverflow::Wrap);
+        let world = create_test_world().with_entity_policy(policy);
+        let restored = from_bytes(&to_bytes(&world).unwrap()).unwrap();
+        assert_eq!(restored.entity_policy(), policy);
+        assert_eq!(restored.entity_stats(), world.entity_stats());
+    }
+
     #[test]
     fn history_not_serialized() {
         let world = create_test_world();
@@ -351,4 +720,44 @@ mod tests {
         // Verify history is NOT preserved (by design)
         assert!(restored.previous().is_none());
     }
+
+    #[test]
+    fn json_roundtrip_preserves_entities_and_relationships() {
+        let world = create_test_world();
+        let player = world
+            .entities()
+            .find(|&e| world.entity_components(e).len() == 1 && !world.has(e, KeywordId::REL_TYPE))
+            .unwrap();
+        let names = HashMap::from([("player".to_string(), player)]);
+
+        let exported = world_to_json(&world, &names);
+        assert_eq!(exported["entities"].as_array().unwrap().len(), 2);
+        assert_eq!(exported["relationships"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            exported["entities"][0]["components"]["health"],
+            json!({ "current": 75, "max": 100 })
+        );
+
+        let (restored, names) = world_from_json(&exported, &world).unwrap();
+        assert_eq!(restored.tick(), world.tick());
+        assert_eq!(restored.entity_count(), world.entity_count());
+        assert_eq!(names["player"], player);
+        let contains = restored.interner().lookup_keyword("contains").unwrap();
+        let room: Vec<_> = restored.sources(player, contains).collect();
+        assert_eq!(room.len(), 1);
+        assert_eq!(world_to_json(&restored, &names), exported);
+    }
+
+    #[test]
+    fn json_import_rejects_unknown_components_and_entities() {
+        let world = create_test_world();
+        let unknown = json!({ "entities": [{ "id": [0, 1], "components": { "mana": 3 } }] });
+        assert!(world_from_json(&unknown, &world).is_err());
+
+        let dangling = json!({
+            "entities": [{ "id": [0, 1] }],
+            "relationships": [{ "type": ":contains", "source": [0, 1], "target": [5, 1] }]
+        });
+        assert!(world_from_json(&dangling, &world).is_err());
+    }
 }
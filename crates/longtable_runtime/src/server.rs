@@ -0,0 +1,402 @@
+//! A REPL server for external tools.
+//!
+//! `longtable serve --port 7777` lets editors, test harnesses, and inspector
+//! UIs evaluate forms against running sessions over TCP. Each request is a
+//! JSON object on one line, and each gets exactly one JSON line back:
+//!
+//! ```text
+//! → {"id": 1, "op": "eval", "code": "(+ 1 2)"}
+//! ← {"id": 1, "value": "3", "output": "", "rich": []}
+//! → {"id": 2, "op": "eval", "session": "scratch", "code": "(tick!)"}
+//! ← {"id": 2, "error": "no session named scratch"}
+//! ```
+//!
+//! The `op`s are:
+//!
+//! - `eval`: evaluates `code` as if typed at the prompt, answering with the
+//!   printed `value` and any narration in `output`, or with an `error`;
+//!   `rich` has the narration again as styled spans (see [`crate::rich`])
+//! - `input`: handles `code` as a line of natural language input
+//! - `complete`: lists the names the session would complete, filtered by
+//!   the `code` prefix
+//! - `new-session`: starts a fresh session named `session` (or a generated
+//!   name), with the standard library loaded
+//! - `close-session`: ends the session named `session`
+//! - `sessions`: lists the open sessions
+//!
+//! Requests without a `session` use `main`, which is where `longtable serve`
+//! loads the files it is given. The `id` is echoed back untouched. Sessions
+//! are shared by every connection, so one tool can inspect what another is
+//! driving.
+//!
+//! Sessions live on the thread that calls [`serve`]; connections are read on
+//! threads of their own and hand their requests over one at a time, so
+//! evaluations never interleave. The server listens on whatever address its
+//! listener was bound to — the CLI binds `127.0.0.1` — and has no
+//! authentication, so it should not be exposed beyond the local machine.
+//! WebSocket framing is not supported; a browser UI needs a small bridge.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+
+use longtable_foundation::Result;
+use longtable_language::plain_text;
+use serde_json::{Value as Json, json};
+
+use crate::editor::HeadlessEditor;
+use crate::repl::Repl;
+use crate::rich;
+
+/// The session requests use when they don't name one.
+pub const MAIN_SESSION: &str = "main";
+
+/// A REPL session served to external tools.
+pub type ServedRepl = Repl<HeadlessEditor>;
+
+/// The named sessions of a server, and the requests they answer.
+pub struct Server {
+    sessions: BTreeMap<String, ServedRepl>,
+    /// Counter for generated session names.
+    next_session: u64,
+}
+
+impl std::fmt::Debug for Server {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Server")
+            .field("sessions", &self.sessions.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Creates a session with no terminal, capturing its output.
+///
+/// # Errors
+///
+/// Returns an error if the standard library fails to load.
+pub fn new_session() -> Result<ServedRepl> {
+    let mut repl = Repl::with_editor(HeadlessEditor).with_captured_output();
+    repl.load_stdlib()?;
+    Ok(repl)
+}
+
+impl Server {
+    /// Creates a server whose `main` session is `main`.
+    ///
+    /// The session should capture its output (see
+    /// [`Repl::with_captured_output`]) so narration reaches the client
+    /// rather than the server's stdout.
+    #[must_use]
+    pub fn new(main: ServedRepl) -> Self {
+        let mut sessions = BTreeMap::new();
+        sessions.insert(MAIN_SESSION.to_string(), main);
+        Self {
+            sessions,
+            next_session: 1,
+        }
+    }
+
+    /// Returns the names of the open sessions, in order.
+    pub fn sessions(&self) -> impl Iterator<Item = &str> {
+        self.sessions.keys().map(String::as_str)
+    }
+
+    /// Returns the session named `name`.
+    #[must_use]
+    pub fn session(&self, name: &str) -> Option<&ServedRepl> {
+        self.sessions.get(name)
+    }
+
+    /// Answers one request line with one response.
+    pub fn handle_line(&mut self, line: &str) -> Json {
+        match serde_json::from_str::<Json>(line) {
+            Ok(request) if request.is_object() => self.handle(&request),
+            Ok(_) => json!({ "id": null, "error": "request must be a JSON object" }),
+            Err(e) => json!({ "id": null, "error": format!("invalid JSON: {e}") }),
+        }
+    }
+
+    /// Answers one request.
+    pub fn handle(&mut self, request: &Json) -> Json {
+        let id = request.get("id").cloned().unwrap_or(Json::Null);
+        let op = request.get("op").and_then(Json::as_str).unwrap_or("eval");
+        let code = request.get("code").and_then(Json::as_str).unwrap_or("");
+        let name = request.get("session").and_then(Json::as_str);
+
+        let mut response = match self.dispatch(op, code, name) {
+            Ok(Json::Object(fields)) => fields,
+            Ok(other) => std::iter::once(("value".to_string(), other)).collect(),
+            Err(message) => std::iter::once(("error".to_string(), json!(message))).collect(),
+        };
+        response.insert("id".to_string(), id);
+        Json::Object(response)
+    }
+
+    fn dispatch(
+        &mut self,
+        op: &str,
+        code: &str,
+        name: Option<&str>,
+    ) -> std::result::Result<Json, String> {
+        match op {
+            "eval" | "input" => {
+                let repl = self.session_mut(name)?;
+                let result = if op == "eval" {
+                    repl.eval(code)
+                } else {
+                    repl.input(code)
+                };
+                let spans = repl.take_rich_output();
+                let output = plain_text(&spans);
+                let rich = rich::to_json(&spans);
+                match result {
+                    Ok(value) => Ok(json!({
+                        "value": repl.display_value(&value),
+                        "output": output,
+                        "rich": rich,
+                    })),
+                    Err(e) => Ok(json!({ "error": e.to_string(), "output": output, "rich": rich })),
+                }
+            }
+            "complete" => {
+                let repl = self.session_mut(name)?;
+                let names: Vec<String> = repl
+                    .completions()
+                    .into_iter()
+                    .filter(|n| n.starts_with(code))
+                    .collect();
+                Ok(json!({ "completions": names }))
+            }
+            "new-session" => {
+                let name = match name {
+                    Some(name) if self.sessions.contains_key(name) => {
+                        return Err(format!("a session named {name} is already open"));
+                    }
+                    Some(name) => name.to_string(),
+                    None => self.generate_name(),
+                };
+                let repl = new_session().map_err(|e| e.to_string())?;
+                self.sessions.insert(name.clone(), repl);
+                Ok(json!({ "session": name }))
+            }
+            "close-session" => {
+                let name = name.ok_or("close-session requires a session")?;
+                self.sessions
+                    .remove(name)
+                    .map(|_| json!({ "session": name }))
+                    .ok_or_else(|| format!("no session named {name}"))
+            }
+            "sessions" => Ok(json!({ "sessions": self.sessions().collect::<Vec<_>>() })),
+            _ => Err(format!("unknown op: {op}")),
+        }
+    }
+
+    fn session_mut(&mut self, name: Option<&str>) -> std::result::Result<&mut ServedRepl, String> {
+        let name = name.unwrap_or(MAIN_SESSION);
+        self.sessions
+            .get_mut(name)
+            .ok_or_else(|| format!("no session named {name}"))
+    }
+
+    fn generate_name(&mut self) -> String {
+        loop {
+            let name = format!("session-{}", self.next_session);
+            self.next_session += 1;
+            if !self.sessions.contains_key(&name) {
+                return name;
+            }
+        }
+    }
+}
+
+/// Answers the requests on one connection until it closes.
+///
+/// # Errors
+///
+/// Returns an error if reading or writing fails.
+pub fn serve_connection<R: BufRead, W: Write>(
+    server: &mut Server,
+    input: R,
+    mut output: W,
+) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(output, "{}", server.handle_line(&line))?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// A request read from a connection, with the channel for its response.
+type Request = (String, mpsc::Sender<Json>);
+
+/// Serves `server`'s sessions to every connection `listener` accepts.
+///
+/// Runs until the listener fails.
+///
+/// # Errors
+///
+/// Returns an error if accepting connections fails.
+pub fn serve(listener: TcpListener, mut server: Server) -> io::Result<()> {
+    let (requests, incoming) = mpsc::channel::<Request>();
+    let acceptor = thread::spawn(move || -> io::Result<()> {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let requests = requests.clone();
+            thread::spawn(move || {
+                // A connection that drops mid-request only affects itself.
+                let _ = forward(&stream, &requests);
+            });
+        }
+        Ok(())
+    });
+
+    for (line, reply) in incoming {
+        let _ = reply.send(server.handle_line(&line));
+    }
+    acceptor
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("acceptor thread panicked")))
+}
+
+/// Hands each request on `stream` to the serving thread and writes back its
+/// response.
+fn forward(stream: &TcpStream, requests: &mpsc::Sender<Request>) -> io::Result<()> {
+    let mut output = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (reply, response) = mpsc::channel();
+        if requests.send((line, reply)).is_err() {
+            break;
+        }
+        let Ok(response) = response.recv() else {
+            break;
+        };
+        writeln!(output, "{response}")?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> Server {
+        Server::new(new_session().unwrap())
+    }
+
+    fn request(server: &mut Server, line: &str) -> Json {
+        server.handle_line(line)
+    }
+
+    #[test]
+    fn evaluates_in_named_sessions() {
+        let mut server = server();
+        let reply = request(
+            &mut server,
+            r#"{"id": 1, "op": "eval", "code": "(def x 40)"}"#,
+        );
+        assert_eq!(reply["id"], 1);
+        assert!(reply.get("error").is_none(), "{reply}");
+
+        let reply = request(&mut server, r#"{"id": 2, "code": "(+ 40 2)"}"#);
+        assert_eq!(reply["value"], "42");
+
+        // A new session starts from scratch
+        let reply = request(
+            &mut server,
+            r#"{"id": 3, "op": "new-session", "session": "scratch"}"#,
+        );
+        assert_eq!(reply["session"], "scratch");
+        let reply = request(
+            &mut server,
+            r#"{"op": "complete", "session": "scratch", "code": "x"}"#,
+        );
+        assert_eq!(reply["completions"], json!([]));
+        let reply = request(&mut server, r#"{"op": "complete", "code": "x"}"#);
+        assert_eq!(reply["completions"], json!(["x"]));
+
+        let reply = request(&mut server, r#"{"op": "sessions"}"#);
+        assert_eq!(reply["sessions"], json!(["main", "scratch"]));
+
+        request(
+            &mut server,
+            r#"{"op": "close-session", "session": "scratch"}"#,
+        );
+        let reply = request(&mut server, r#"{"session": "scratch", "code": "1"}"#);
+        assert_eq!(reply["error"], "no session named scratch");
+    }
+
+    #[test]
+    fn returns_narration_as_plain_and_rich_text() {
+        let mut server = server();
+        let reply = request(
+            &mut server,
+            r#"{"code": "(say \"It is \" [:em \"very \" [:color :red \"dark\"]] \".\")"}"#,
+        );
+        assert_eq!(reply["output"], "It is very dark.\n", "{reply}");
+        assert_eq!(
+            reply["rich"],
+            json!([
+                {"text": "It is "},
+                {"text": "very ", "em": true},
+                {"text": "dark", "em": true, "color": "red"},
+                {"text": ".\n"},
+            ])
+        );
+
+        let reply = request(&mut server, r#"{"code": "(say [:link 1 \"x\"])"}"#);
+        assert!(reply["error"].is_string(), "{reply}");
+    }
+
+    #[test]
+    fn returns_the_output_of_special_forms() {
+        let mut server = server();
+        let reply = request(&mut server, r#"{"code": "(breakpoints)"}"#);
+        assert_eq!(reply["output"], "No breakpoints\n", "{reply}");
+
+        let reply = request(&mut server, r#"{"code": "(observability)"}"#);
+        let output = reply["output"].as_str().unwrap();
+        assert!(output.starts_with("Observability: "), "{reply}");
+    }
+
+    #[test]
+    fn reports_bad_requests_and_completes() {
+        let mut server = server();
+        assert!(request(&mut server, "(+ 1 2)")["error"].is_string());
+        assert_eq!(
+            request(&mut server, r#"{"id": "a", "op": "dance"}"#),
+            json!({ "id": "a", "error": "unknown op: dance" })
+        );
+
+        let reply = request(&mut server, r#"{"op": "complete", "code": "tic"}"#);
+        let names = reply["completions"].as_array().unwrap();
+        assert!(names.iter().any(|n| n == "tick!"), "{reply}");
+        assert!(names.iter().all(|n| n.as_str().unwrap().starts_with("tic")));
+    }
+
+    #[test]
+    fn serves_a_connection_line_by_line() {
+        let mut server = server();
+        let input = "{\"id\": 1, \"code\": \"(def y 2)\"}\n\n{\"id\": 2, \"code\": \"(* 2 3)\"}\n";
+        let mut output = Vec::new();
+        serve_connection(&mut server, input.as_bytes(), &mut output).unwrap();
+
+        let replies: Vec<Json> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[1]["value"], "6");
+    }
+}
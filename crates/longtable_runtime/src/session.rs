@@ -5,27 +5,54 @@
 //! This module also provides [`SessionContext`], which implements the
 //! [`RuntimeContext`] trait for VM execution with full runtime access.
 
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 
-use longtable_debug::{DebugSession, Timeline, Tracer};
-use longtable_engine::PatternCompiler;
-use longtable_engine::rule::CompiledRule;
+use longtable_debug::{DebugSession, ObservabilityConfig, TickSnapshot, Timeline, Tracer};
+use longtable_engine::rule::{FullCompiledRule, RuleCompiler};
+use longtable_engine::{PatternCompiler, QueryWarning, TickPhase};
 use longtable_foundation::{EntityId, Error, ErrorKind, Interner, KeywordId, Result, Type, Value};
-use longtable_language::Span;
-use longtable_language::declaration::{Pattern, PatternClause, PatternValue, Precondition};
+use longtable_language::declaration::{
+    Disjunction, NotJoin, Pattern, PatternClause, PatternPredicate, PatternValue, Precondition,
+    RuleDecl,
+};
 use longtable_language::{ActionDecl, ModuleRegistry, NamespaceContext, RuntimeContext, VmContext};
-use longtable_parser::scope::CompiledScope;
+use longtable_language::{Ast, Span};
+use longtable_parser::parser::PendingParse;
+use longtable_parser::pronouns::PronounState;
+use longtable_parser::scope::{CompiledScope, ScopeEvaluator, ScopeKind, inclusion_cycle};
+use longtable_parser::stdlib::{StdlibKeywords, create_scope_evaluator};
 use longtable_parser::vocabulary::{
     CommandSyntax, Direction, NounType, Preposition, Pronoun, PronounGender, PronounNumber, Verb,
 };
 use longtable_parser::{
     ActionRegistry, CompiledAction, CompiledSyntax, SyntaxCompiler, VocabularyRegistry,
 };
-use longtable_storage::World;
 use longtable_storage::schema::{
     Cardinality, ComponentSchema, FieldSchema, OnDelete, RelationshipSchema,
 };
+use longtable_storage::{LookupLog, PathStep, Point, World};
+
+use crate::coverage::Coverage;
+use crate::hooks::ActionHook;
+use crate::lint::SourceSite;
+use crate::messages::{DEFAULT_LOCALE, MessageCatalog};
+use crate::replay::ReplayLog;
+use crate::scenario::Scenario;
+use crate::telemetry::Telemetry;
+use crate::transcript::Transcript;
+
+/// What the REPL does with query warnings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WarningMode {
+    /// Print warnings to stderr and continue.
+    #[default]
+    Warn,
+    /// Fail the query, for CI runs that should stay warning-free.
+    Deny,
+    /// Keep warnings for `(query-warnings)` without printing them.
+    Allow,
+}
 
 /// Session state for an interactive REPL session.
 #[allow(clippy::struct_field_names)]
@@ -40,6 +67,10 @@ pub struct Session {
     /// Maps symbolic names (e.g., "player", "cave-entrance") to `EntityId`s.
     entity_names: HashMap<String, EntityId>,
 
+    /// Where named top-level declarations were loaded from, keyed by
+    /// declaration form (e.g. `spawn:`) and name.
+    declaration_sites: HashMap<(String, String), SourceSite>,
+
     /// Current load path for relative file resolution.
     load_path: PathBuf,
 
@@ -52,6 +83,9 @@ pub struct Session {
     /// Current namespace context for symbol resolution.
     namespace_context: NamespaceContext,
 
+    /// Settings shared by the tracer, timeline, and provenance tracker.
+    observability: ObservabilityConfig,
+
     /// Tracer for observability.
     tracer: Tracer,
 
@@ -67,16 +101,35 @@ pub struct Session {
     /// Action registry for command execution.
     action_registry: ActionRegistry,
 
+    /// Message templates from `message:` declarations.
+    messages: MessageCatalog,
+
     /// Compiled scopes for noun resolution.
     scopes: Vec<CompiledScope>,
 
+    /// Evaluates the scopes, with its keywords interned when the session
+    /// was created.
+    scope_evaluator: ScopeEvaluator,
+
     /// Full action declarations keyed by action name.
     /// Includes params, preconditions, and handlers.
     action_decls: HashMap<KeywordId, ActionDecl>,
 
     /// Compiled rules for tick execution.
     /// Rules are compiled when registered via `register_rule`.
-    compiled_rules: Vec<CompiledRule>,
+    compiled_rules: Vec<FullCompiledRule>,
+
+    /// Rule declarations handed over by the compiler, by name, with the file
+    /// each was loaded from. `register_rule` compiles a rule's body from its
+    /// declaration, since the data it is registered with has no source spans.
+    rule_decls: HashMap<String, (RuleDecl, Option<String>)>,
+
+    /// Counts changes to `compiled_rules`, so the tick executor can tell
+    /// when its copy is out of date.
+    rule_revision: u64,
+
+    /// Relationship lookups made by the forms evaluated so far.
+    relationship_lookups: LookupLog,
 
     /// Compiled command syntaxes for natural language parsing.
     compiled_syntaxes: Vec<CompiledSyntax>,
@@ -87,56 +140,200 @@ pub struct Session {
 
     /// Counter for generating unique snapshot IDs.
     next_snapshot_id: u64,
+
+    /// States before each recent effect batch, most recent last.
+    undo_stack: VecDeque<UndoPoint>,
+
+    /// States undone by `undo`, most recently undone last.
+    redo_stack: Vec<UndoPoint>,
+
+    /// A command waiting for the player to say which entity they meant.
+    pending_parse: Option<PendingParse>,
+
+    /// Commands typed after the one waiting on the player's choice, run
+    /// once it's made.
+    queued_commands: Vec<String>,
+
+    /// Pronoun referents, and the inputs "again" and "oops" work on.
+    pronoun_state: PronounState,
+
+    /// Recorded natural language input for analytics.
+    transcript: Transcript,
+
+    /// Replay log of every tick, while recording.
+    replay: Option<ReplayLog>,
+
+    /// Opt-in anonymized telemetry for shipped games.
+    telemetry: Telemetry,
+
+    /// Rules that have fired and syntaxes that have matched.
+    coverage: Coverage,
+
+    /// DSL functions to call at tick phases, in registration order.
+    phase_hooks: Vec<(TickPhase, Ast)>,
+
+    /// Forms to run before and after actions, in declaration order.
+    action_hooks: Vec<ActionHook>,
+
+    /// Declared scenarios, in declaration order.
+    scenarios: Vec<Scenario>,
+
+    /// Content features enabled for `when-feature` forms (e.g. `debug-content`).
+    features: HashSet<String>,
+
+    /// What to do with query warnings.
+    warning_mode: WarningMode,
+
+    /// Warnings from the most recent query.
+    query_warnings: Vec<QueryWarning>,
+
+    /// Set when a tick panicked, leaving a world that may be inconsistent.
+    poisoned: Option<Poisoned>,
+}
+
+/// A panic that interrupted a tick, leaving a world that may be
+/// inconsistent.
+///
+/// See [`Session::poisoned`].
+#[derive(Clone, Debug)]
+pub struct Poisoned {
+    /// The panic message.
+    pub message: String,
+    /// The tick that panicked.
+    pub tick: u64,
+    /// The world from before the tick, the last one known to be good.
+    pub checkpoint: World,
+}
+
+/// The state `undo` and `redo` move between: the world, the names
+/// `spawn:` gave its entities, and what the parser's pronouns refer to.
+struct UndoPoint {
+    world: World,
+    entity_names: HashMap<String, EntityId>,
+    pronoun_state: PronounState,
+}
+
+/// Everything a reload can change, saved so a failed reload can be undone.
+///
+/// See [`Session::checkpoint`].
+#[derive(Clone)]
+pub struct SessionCheckpoint {
+    world: World,
+    variables: HashMap<String, Value>,
+    entity_names: HashMap<String, EntityId>,
+    declaration_sites: HashMap<(String, String), SourceSite>,
+    namespace_context: NamespaceContext,
+    vocabulary_registry: VocabularyRegistry,
+    action_registry: ActionRegistry,
+    messages: MessageCatalog,
+    scopes: Vec<CompiledScope>,
+    action_decls: HashMap<KeywordId, ActionDecl>,
+    compiled_rules: Vec<FullCompiledRule>,
+    compiled_syntaxes: Vec<CompiledSyntax>,
+    phase_hooks: Vec<(TickPhase, Ast)>,
+    action_hooks: Vec<ActionHook>,
 }
 
+/// Maximum number of effect batches that can be undone.
+const MAX_UNDO_DEPTH: usize = 100;
+
 impl Session {
     /// Creates a new session with an empty world.
     #[must_use]
     pub fn new() -> Self {
+        let mut world = World::new(0);
+        let scope_evaluator = create_scope_evaluator(&StdlibKeywords::intern(&mut world));
         Self {
-            world: World::new(0),
+            world,
             variables: HashMap::new(),
             entity_names: HashMap::new(),
+            declaration_sites: HashMap::new(),
             load_path: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
             auto_commit: true,
             module_registry: ModuleRegistry::new(),
             namespace_context: NamespaceContext::new(),
+            observability: ObservabilityConfig::default(),
             tracer: Tracer::disabled(),
             debug_session: DebugSession::new(),
             timeline: Timeline::new(),
             vocabulary_registry: VocabularyRegistry::default(),
             action_registry: ActionRegistry::new(),
+            messages: MessageCatalog::new(),
             scopes: Vec::new(),
+            scope_evaluator,
             action_decls: HashMap::new(),
             compiled_rules: Vec::new(),
+            rule_decls: HashMap::new(),
+            rule_revision: 0,
+            relationship_lookups: LookupLog::default(),
             compiled_syntaxes: Vec::new(),
             state_snapshots: HashMap::new(),
             next_snapshot_id: 0,
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            pending_parse: None,
+            queued_commands: Vec::new(),
+            pronoun_state: PronounState::new(),
+            transcript: Transcript::new(),
+            replay: None,
+            telemetry: Telemetry::new(),
+            coverage: Coverage::new(),
+            phase_hooks: Vec::new(),
+            action_hooks: Vec::new(),
+            scenarios: Vec::new(),
+            features: HashSet::new(),
+            warning_mode: WarningMode::default(),
+            query_warnings: Vec::new(),
+            poisoned: None,
         }
     }
 
     /// Creates a new session with the given world.
     #[must_use]
-    pub fn with_world(world: World) -> Self {
+    pub fn with_world(mut world: World) -> Self {
+        let scope_evaluator = create_scope_evaluator(&StdlibKeywords::intern(&mut world));
         Self {
             world,
             variables: HashMap::new(),
             entity_names: HashMap::new(),
+            declaration_sites: HashMap::new(),
             load_path: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
             auto_commit: true,
             module_registry: ModuleRegistry::new(),
             namespace_context: NamespaceContext::new(),
+            observability: ObservabilityConfig::default(),
             tracer: Tracer::disabled(),
             debug_session: DebugSession::new(),
             timeline: Timeline::new(),
             vocabulary_registry: VocabularyRegistry::default(),
             action_registry: ActionRegistry::new(),
+            messages: MessageCatalog::new(),
             scopes: Vec::new(),
+            scope_evaluator,
             action_decls: HashMap::new(),
             compiled_rules: Vec::new(),
+            rule_decls: HashMap::new(),
+            rule_revision: 0,
+            relationship_lookups: LookupLog::default(),
             compiled_syntaxes: Vec::new(),
             state_snapshots: HashMap::new(),
             next_snapshot_id: 0,
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            pending_parse: None,
+            queued_commands: Vec::new(),
+            pronoun_state: PronounState::new(),
+            transcript: Transcript::new(),
+            replay: None,
+            telemetry: Telemetry::new(),
+            coverage: Coverage::new(),
+            phase_hooks: Vec::new(),
+            action_hooks: Vec::new(),
+            scenarios: Vec::new(),
+            features: HashSet::new(),
+            warning_mode: WarningMode::default(),
+            query_warnings: Vec::new(),
+            poisoned: None,
         }
     }
 
@@ -182,6 +379,350 @@ impl Session {
         }
     }
 
+    /// Records the current world as an undo point.
+    ///
+    /// Call this before applying a batch of effects. Recording a new undo
+    /// point discards anything that could have been redone.
+    pub fn record_undo_point(&mut self) {
+        if self.undo_stack.len() == MAX_UNDO_DEPTH {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(UndoPoint {
+            world: self.world.clone(),
+            entity_names: self.entity_names.clone(),
+            pronoun_state: self.pronoun_state.clone(),
+        });
+        self.redo_stack.clear();
+    }
+
+    /// Reverts the world to before the last recorded effect batch.
+    ///
+    /// Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(previous) = self.undo_stack.pop_back() else {
+            return false;
+        };
+        let current = self.swap_undo_point(previous);
+        self.redo_stack.push(current);
+        true
+    }
+
+    /// Reapplies the most recently undone effect batch.
+    ///
+    /// Returns false if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(next) = self.redo_stack.pop() else {
+            return false;
+        };
+        let current = self.swap_undo_point(next);
+        self.undo_stack.push_back(current);
+        true
+    }
+
+    /// Moves the session to `point`, returning the state it left.
+    fn swap_undo_point(&mut self, point: UndoPoint) -> UndoPoint {
+        UndoPoint {
+            world: self.swap_world_keeping_interner(point.world),
+            entity_names: std::mem::replace(&mut self.entity_names, point.entity_names),
+            pronoun_state: std::mem::replace(&mut self.pronoun_state, point.pronoun_state),
+        }
+    }
+
+    /// Drops the undo points recorded since the stack was `depth` deep.
+    pub fn truncate_undo(&mut self, depth: usize) {
+        self.undo_stack.truncate(depth);
+    }
+
+    /// Returns the number of effect batches that can be undone.
+    #[must_use]
+    pub fn undo_depth(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// Returns the number of effect batches that can be redone.
+    #[must_use]
+    pub fn redo_depth(&self) -> usize {
+        self.redo_stack.len()
+    }
+
+    /// Frees the generated keywords nothing in the session refers to,
+    /// returning how many were freed.
+    ///
+    /// `live` holds the keywords marked outside the session, by the tick
+    /// executor's rules and timers and by the VM's globals. To those this
+    /// adds the keywords held by the current world, the undo and redo
+    /// stacks, saved states, and timeline snapshots (with the history each
+    /// retains), the replay log, compiled rules, and session variables, then
+    /// sweeps the current world's interner. Retained worlds keep their own
+    /// copy of the interner, so the freed strings are released once they
+    /// are dropped.
+    pub fn sweep_keywords(&mut self, mut live: HashSet<KeywordId>) -> usize {
+        let mut seen = HashSet::new();
+        let retained = std::iter::once(&self.world)
+            .chain(self.undo_stack.iter().map(|point| &point.world))
+            .chain(self.redo_stack.iter().map(|point| &point.world))
+            .chain(self.state_snapshots.values())
+            .chain(
+                self.timeline
+                    .branches()
+                    .iter()
+                    .flat_map(|branch| branch.history().iter())
+                    .map(TickSnapshot::world),
+            );
+        for world in retained {
+            let mut world = Some(world);
+            // Histories share their tails, so each world is marked once
+            while let Some(current) = world.filter(|w| seen.insert(std::ptr::from_ref(*w))) {
+                current.mark_keywords(&mut live);
+                world = current.previous();
+            }
+        }
+        if let Some(replay) = &self.replay {
+            replay.mark_keywords(&mut live);
+        }
+        for rule in &self.compiled_rules {
+            rule.mark_keywords(&mut live);
+        }
+        for value in self.variables.values() {
+            value.mark_keywords(&mut live);
+        }
+        self.world.interner_mut().sweep_generated(&live)
+    }
+
+    /// Returns the number of world states kept by `save-state`.
+    #[must_use]
+    pub fn saved_state_count(&self) -> usize {
+        self.state_snapshots.len()
+    }
+
+    /// Replaces the world, returning the old one.
+    ///
+    /// The interner only ever grows, and compiled code may hold keyword IDs
+    /// interned after `world` was captured, so the current interner is kept.
+    fn swap_world_keeping_interner(&mut self, mut world: World) -> World {
+        world.set_interner(self.world.interner().clone());
+        std::mem::replace(&mut self.world, world)
+    }
+
+    /// Records where a named declaration appeared, e.g. `("spawn:", "lantern")`.
+    pub fn record_declaration_site(&mut self, form: &str, name: String, site: SourceSite) {
+        self.declaration_sites
+            .insert((form.to_string(), name), site);
+    }
+
+    /// Returns the `(form, name)` of every declaration loaded from `file`.
+    #[must_use]
+    pub fn declarations_from(&self, file: &Path) -> Vec<(String, String)> {
+        let mut declarations: Vec<_> = self
+            .declaration_sites
+            .iter()
+            .filter(|(_, site)| site.file.as_deref() == Some(file))
+            .map(|(key, _)| key.clone())
+            .collect();
+        declarations.sort();
+        declarations
+    }
+
+    /// Saves the world and every definition, for [`Self::restore`].
+    #[must_use]
+    pub fn checkpoint(&self) -> SessionCheckpoint {
+        SessionCheckpoint {
+            world: self.world.clone(),
+            variables: self.variables.clone(),
+            entity_names: self.entity_names.clone(),
+            declaration_sites: self.declaration_sites.clone(),
+            namespace_context: self.namespace_context.clone(),
+            vocabulary_registry: self.vocabulary_registry.clone(),
+            action_registry: self.action_registry.clone(),
+            messages: self.messages.clone(),
+            scopes: self.scopes.clone(),
+            action_decls: self.action_decls.clone(),
+            compiled_rules: self.compiled_rules.clone(),
+            compiled_syntaxes: self.compiled_syntaxes.clone(),
+            phase_hooks: self.phase_hooks.clone(),
+            action_hooks: self.action_hooks.clone(),
+        }
+    }
+
+    /// Puts back everything saved by [`Self::checkpoint`].
+    ///
+    /// Undo history, snapshots, and debugging state are left alone.
+    pub fn restore(&mut self, checkpoint: SessionCheckpoint) {
+        self.world = checkpoint.world;
+        self.variables = checkpoint.variables;
+        self.entity_names = checkpoint.entity_names;
+        self.declaration_sites = checkpoint.declaration_sites;
+        self.namespace_context = checkpoint.namespace_context;
+        self.vocabulary_registry = checkpoint.vocabulary_registry;
+        self.action_registry = checkpoint.action_registry;
+        self.messages = checkpoint.messages;
+        self.scopes = checkpoint.scopes;
+        self.action_decls = checkpoint.action_decls;
+        self.compiled_rules = checkpoint.compiled_rules;
+        self.rule_revision += 1;
+        self.compiled_syntaxes = checkpoint.compiled_syntaxes;
+        self.phase_hooks = checkpoint.phase_hooks;
+        self.action_hooks = checkpoint.action_hooks;
+    }
+
+    /// Returns where a named declaration appeared, if it was loaded from source.
+    #[must_use]
+    pub fn declaration_site(&self, form: &str, name: &str) -> Option<&SourceSite> {
+        self.declaration_sites
+            .get(&(form.to_string(), name.to_string()))
+    }
+
+    /// Returns the input transcript.
+    #[must_use]
+    pub const fn transcript(&self) -> &Transcript {
+        &self.transcript
+    }
+
+    /// Returns a mutable reference to the input transcript.
+    pub fn transcript_mut(&mut self) -> &mut Transcript {
+        &mut self.transcript
+    }
+
+    /// Returns the replay log, if recording.
+    #[must_use]
+    pub const fn replay_log(&self) -> Option<&ReplayLog> {
+        self.replay.as_ref()
+    }
+
+    /// Returns a mutable reference to the replay log, if recording.
+    pub fn replay_log_mut(&mut self) -> Option<&mut ReplayLog> {
+        self.replay.as_mut()
+    }
+
+    /// Starts recording ticks into `log`, replacing any log in progress.
+    pub fn start_replay(&mut self, log: ReplayLog) {
+        self.replay = Some(log);
+    }
+
+    /// Stops recording and returns the log.
+    pub fn take_replay_log(&mut self) -> Option<ReplayLog> {
+        self.replay.take()
+    }
+
+    /// Returns the telemetry collector.
+    #[must_use]
+    pub const fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
+
+    /// Returns a mutable reference to the telemetry collector.
+    ///
+    /// Hosts use this to install a sink and record the player's opt-in choice.
+    pub fn telemetry_mut(&mut self) -> &mut Telemetry {
+        &mut self.telemetry
+    }
+
+    /// Returns the rules that have fired and syntaxes that have matched.
+    #[must_use]
+    pub const fn coverage(&self) -> &Coverage {
+        &self.coverage
+    }
+
+    /// Returns a mutable reference to the coverage record.
+    pub fn coverage_mut(&mut self) -> &mut Coverage {
+        &mut self.coverage
+    }
+
+    /// Returns the panic that interrupted a tick, if the world may be
+    /// inconsistent since.
+    ///
+    /// The REPL refuses to tick a poisoned session until it is recovered
+    /// (see [`Repl::recover`](crate::Repl::recover)).
+    #[must_use]
+    pub const fn poisoned(&self) -> Option<&Poisoned> {
+        self.poisoned.as_ref()
+    }
+
+    /// Marks the world as possibly inconsistent after a panic.
+    pub fn poison(&mut self, poisoned: Poisoned) {
+        self.poisoned = Some(poisoned);
+    }
+
+    /// Clears the poisoned mark, returning it.
+    pub fn clear_poison(&mut self) -> Option<Poisoned> {
+        self.poisoned.take()
+    }
+
+    /// Returns what the REPL does with query warnings.
+    #[must_use]
+    pub const fn warning_mode(&self) -> WarningMode {
+        self.warning_mode
+    }
+
+    /// Sets what the REPL does with query warnings.
+    pub fn set_warning_mode(&mut self, mode: WarningMode) {
+        self.warning_mode = mode;
+    }
+
+    /// Returns the warnings raised by the most recent query.
+    #[must_use]
+    pub fn query_warnings(&self) -> &[QueryWarning] {
+        &self.query_warnings
+    }
+
+    /// Records the warnings raised by the most recent query.
+    pub fn set_query_warnings(&mut self, warnings: Vec<QueryWarning>) {
+        self.query_warnings = warnings;
+    }
+
+    /// Registers a hook function to call at a tick phase.
+    pub fn add_phase_hook(&mut self, phase: TickPhase, hook: Ast) {
+        self.phase_hooks.push((phase, hook));
+    }
+
+    /// Returns all registered phase hooks.
+    #[must_use]
+    pub fn phase_hooks(&self) -> &[(TickPhase, Ast)] {
+        &self.phase_hooks
+    }
+
+    /// Registers a hook to run before or after an action.
+    pub fn add_action_hook(&mut self, hook: ActionHook) {
+        self.action_hooks.push(hook);
+    }
+
+    /// Returns all registered action hooks, in declaration order.
+    #[must_use]
+    pub fn action_hooks(&self) -> &[ActionHook] {
+        &self.action_hooks
+    }
+
+    /// Declares a scenario, replacing any with the same name.
+    pub fn add_scenario(&mut self, scenario: Scenario) {
+        match self.scenarios.iter_mut().find(|s| s.name == scenario.name) {
+            Some(existing) => *existing = scenario,
+            None => self.scenarios.push(scenario),
+        }
+    }
+
+    /// Returns the declared scenarios, in declaration order.
+    #[must_use]
+    pub fn scenarios(&self) -> &[Scenario] {
+        &self.scenarios
+    }
+
+    /// Enables a content feature, so `(when-feature :name ...)` forms load.
+    pub fn enable_feature(&mut self, name: impl Into<String>) {
+        self.features.insert(name.into());
+    }
+
+    /// Returns true if a content feature is enabled.
+    #[must_use]
+    pub fn has_feature(&self, name: &str) -> bool {
+        self.features.contains(name)
+    }
+
+    /// Returns the enabled content features.
+    #[must_use]
+    pub fn features(&self) -> &HashSet<String> {
+        &self.features
+    }
+
     /// Gets a session variable by name.
     #[must_use]
     pub fn get_variable(&self, name: &str) -> Option<&Value> {
@@ -210,6 +751,11 @@ impl Session {
         self.entity_names.insert(name, entity);
     }
 
+    /// Replaces all named entities, e.g. after importing a world.
+    pub fn set_entity_names(&mut self, names: HashMap<String, EntityId>) {
+        self.entity_names = names;
+    }
+
     /// Returns all named entities.
     #[must_use]
     pub fn entity_names(&self) -> &HashMap<String, EntityId> {
@@ -276,6 +822,23 @@ impl Session {
         }
     }
 
+    /// Returns the observability settings.
+    #[must_use]
+    pub const fn observability(&self) -> &ObservabilityConfig {
+        &self.observability
+    }
+
+    /// Replaces the observability settings and applies them to the tracer
+    /// and timeline.
+    ///
+    /// The provenance tracker belongs to the tick executor, so callers that
+    /// own one should also apply the config to it.
+    pub fn set_observability(&mut self, config: ObservabilityConfig) {
+        config.apply_to_tracer(&mut self.tracer);
+        config.apply_to_timeline(&mut self.timeline);
+        self.observability = config;
+    }
+
     /// Returns a reference to the tracer.
     #[must_use]
     pub fn tracer(&self) -> &Tracer {
@@ -298,6 +861,12 @@ impl Session {
         &mut self.debug_session
     }
 
+    /// Returns the debug session and the tracer together, for a tick that
+    /// reports to both as it runs.
+    pub fn debugger_mut(&mut self) -> (&mut DebugSession, &mut Tracer) {
+        (&mut self.debug_session, &mut self.tracer)
+    }
+
     /// Returns a reference to the timeline.
     #[must_use]
     pub fn timeline(&self) -> &Timeline {
@@ -320,6 +889,56 @@ impl Session {
         &mut self.vocabulary_registry
     }
 
+    /// Saves a command that matched more than one entity, so the player's
+    /// next input can say which one they meant.
+    pub fn set_pending_parse(&mut self, pending: PendingParse) {
+        self.pending_parse = Some(pending);
+    }
+
+    /// Removes and returns the command waiting on the player's choice.
+    pub fn take_pending_parse(&mut self) -> Option<PendingParse> {
+        self.pending_parse.take()
+    }
+
+    /// Saves the commands typed after one that asked which entity was
+    /// meant, to run once the player answers.
+    pub fn queue_commands(&mut self, commands: Vec<String>) {
+        self.queued_commands = commands;
+    }
+
+    /// Removes and returns the commands waiting on the player's answer.
+    pub fn take_queued_commands(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.queued_commands)
+    }
+
+    /// Returns the parser state kept between inputs.
+    #[must_use]
+    pub fn pronoun_state(&self) -> &PronounState {
+        &self.pronoun_state
+    }
+
+    /// Saves the parser state to carry over to the next input.
+    pub fn set_pronoun_state(&mut self, state: PronounState) {
+        self.pronoun_state = state;
+    }
+
+    /// Forgets the parser state, whose pronouns and earlier input describe
+    /// a world that was replaced.
+    pub fn reset_pronoun_state(&mut self) {
+        self.pronoun_state.reset();
+    }
+
+    /// Returns the message catalog.
+    #[must_use]
+    pub fn messages(&self) -> &MessageCatalog {
+        &self.messages
+    }
+
+    /// Returns a mutable reference to the message catalog.
+    pub fn messages_mut(&mut self) -> &mut MessageCatalog {
+        &mut self.messages
+    }
+
     /// Returns a reference to the action registry.
     #[must_use]
     pub fn action_registry(&self) -> &ActionRegistry {
@@ -337,9 +956,37 @@ impl Session {
         &self.scopes
     }
 
-    /// Adds a compiled scope.
-    pub fn add_scope(&mut self, scope: CompiledScope) {
+    /// Returns the evaluator for the compiled scopes.
+    #[must_use]
+    pub const fn scope_evaluator(&self) -> &ScopeEvaluator {
+        &self.scope_evaluator
+    }
+
+    /// Adds a compiled scope, replacing any earlier one of the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error (and leaves the scopes unchanged) if the scope would
+    /// include itself, directly or through other scopes.
+    pub fn add_scope(&mut self, scope: CompiledScope) -> Result<()> {
+        if let Some(cycle) = inclusion_cycle(&scope, &self.scopes) {
+            let names: Vec<String> = cycle
+                .iter()
+                .map(|&name| {
+                    format!(
+                        ":{}",
+                        self.world.interner().get_keyword(name).unwrap_or("?")
+                    )
+                })
+                .collect();
+            return Err(Error::new(ErrorKind::Cycle(format!(
+                "scopes extend each other in a cycle: {}",
+                names.join(" -> ")
+            ))));
+        }
+        self.scopes.retain(|s| s.name != scope.name);
         self.scopes.push(scope);
+        Ok(())
     }
 
     /// Registers a full action declaration.
@@ -355,13 +1002,57 @@ impl Session {
 
     /// Returns a reference to the compiled rules.
     #[must_use]
-    pub fn compiled_rules(&self) -> &[CompiledRule] {
+    pub fn compiled_rules(&self) -> &[FullCompiledRule] {
         &self.compiled_rules
     }
 
-    /// Adds a compiled rule.
-    pub fn add_compiled_rule(&mut self, rule: CompiledRule) {
-        self.compiled_rules.push(rule);
+    /// Keeps rule declarations from the compiler, each with the file it was
+    /// loaded from, until their rules are registered.
+    pub fn declare_rules(&mut self, decls: Vec<(RuleDecl, Option<String>)>) {
+        for (decl, file) in decls {
+            self.rule_decls.insert(decl.name.clone(), (decl, file));
+        }
+    }
+
+    /// Adds a compiled rule and re-sorts all rules into firing order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error (and leaves the rules unchanged) if the new rule's
+    /// `:before`/`:after` constraints would create a cycle.
+    pub fn add_compiled_rule(&mut self, rule: FullCompiledRule) -> Result<()> {
+        let mut rules = self.compiled_rules.clone();
+        rules.push(rule);
+        RuleCompiler::order(&mut rules, self.world.interner())?;
+        self.compiled_rules = rules;
+        self.rule_revision += 1;
+        Ok(())
+    }
+
+    /// Removes the compiled rule with the given name. Returns false if there
+    /// was none.
+    pub fn remove_compiled_rule(&mut self, name: KeywordId) -> bool {
+        let before = self.compiled_rules.len();
+        self.compiled_rules.retain(|rule| rule.name != name);
+        self.rule_revision += 1;
+        self.compiled_rules.len() != before
+    }
+
+    /// Returns a number that changes whenever the compiled rules do.
+    #[must_use]
+    pub const fn rule_revision(&self) -> u64 {
+        self.rule_revision
+    }
+
+    /// Returns the relationship lookups made by the forms evaluated so far.
+    #[must_use]
+    pub fn relationship_lookups(&self) -> &LookupLog {
+        &self.relationship_lookups
+    }
+
+    /// Adds lookups to those made by the session.
+    pub fn record_relationship_lookups(&mut self, lookups: &LookupLog) {
+        self.relationship_lookups.merge(lookups);
     }
 
     /// Returns the number of compiled rules.
@@ -380,6 +1071,13 @@ impl Session {
     pub fn add_compiled_syntax(&mut self, syntax: CompiledSyntax) {
         self.compiled_syntaxes.push(syntax);
     }
+
+    /// Removes a command's syntaxes from both the parser and the vocabulary.
+    pub fn remove_command(&mut self, name: KeywordId) {
+        self.compiled_syntaxes
+            .retain(|syntax| syntax.command != name);
+        self.vocabulary_registry.remove_command(name);
+    }
 }
 
 impl Default for Session {
@@ -496,6 +1194,34 @@ impl VmContext for SessionContext<'_> {
         self.session.world.sources(target, rel_type).collect()
     }
 
+    fn find_path(
+        &self,
+        from: EntityId,
+        to: EntityId,
+        via: &[KeywordId],
+        cost: Option<KeywordId>,
+    ) -> Option<Vec<PathStep>> {
+        self.session.world.find_path(from, to, via, cost)
+    }
+
+    fn position(&self, value: &Value, component: Option<KeywordId>) -> Option<Point> {
+        let world = &self.session.world;
+        world.position_of(value, world.position_component(component)?)
+    }
+
+    fn entities_within(
+        &self,
+        component: Option<KeywordId>,
+        center: Point,
+        radius: f64,
+    ) -> Vec<EntityId> {
+        let world = &self.session.world;
+        world
+            .position_component(component)
+            .map(|component| world.entities_within(component, center, radius))
+            .unwrap_or_default()
+    }
+
     fn find_relationships_by_prefix(
         &self,
         prefix: &str,
@@ -512,6 +1238,14 @@ impl VmContext for SessionContext<'_> {
             .get_keyword(keyword)
             .map(ToString::to_string)
     }
+
+    fn message(&self, key: KeywordId) -> Option<String> {
+        self.session.messages.lookup(key).map(str::to_string)
+    }
+
+    fn world_score(&self) -> f64 {
+        self.session.world.score()
+    }
 }
 
 // =============================================================================
@@ -580,11 +1314,18 @@ impl RuntimeContext for SessionContext<'_> {
     }
 
     fn register_scope(&mut self, data: &Value) -> Result<()> {
-        // Scopes require compilation - for now, store the raw data
-        // Full implementation will compile the scope resolver
-        let _name = extract_keyword_field(data, "name", self.interner())?;
-        // TODO: Compile scope and add to session.scopes
-        Ok(())
+        let name = extract_keyword_field(data, "name", self.interner())?;
+        let extends = extract_keyword_vec(data, "extends", self.interner());
+        let pattern = parse_pattern_from_value(data, self.interner())?;
+        let pattern = PatternCompiler::compile(&pattern, self.session.world_mut().interner_mut())?;
+        self.session.add_scope(CompiledScope {
+            name,
+            parent: None,
+            kind: ScopeKind::Pattern {
+                extends,
+                pattern: Box::new(pattern),
+            },
+        })
     }
 
     fn register_command(&mut self, data: &Value) -> Result<()> {
@@ -644,32 +1385,38 @@ impl RuntimeContext for SessionContext<'_> {
         Ok(())
     }
 
+    fn register_message(&mut self, data: &Value) -> Result<()> {
+        let key = extract_keyword_field(data, "key", self.interner())?;
+        let template =
+            extract_string_field(data, "template", self.interner()).ok_or_else(|| {
+                Error::new(ErrorKind::InvalidArgument(
+                    "message: requires a template string".to_string(),
+                ))
+            })?;
+        let locale = extract_optional_keyword_field(data, "locale", self.interner())
+            .and_then(|locale| self.interner().get_keyword(locale))
+            .unwrap_or(DEFAULT_LOCALE)
+            .to_string();
+        self.session.messages.define(&locale, key, template);
+        Ok(())
+    }
+
     fn register_rule(&mut self, data: &Value) -> Result<EntityId> {
-        // Parse the rule data from Value map
+        // The compiler handed over the declaration, spans and all
         let name = extract_keyword_field(data, "name", self.interner())?;
-        #[allow(clippy::cast_possible_truncation)]
-        let salience = extract_int_field(data, "salience", self.interner()).unwrap_or(0) as i32;
-        let once = extract_bool_field(data, "once", self.interner()).unwrap_or(false);
-        let enabled = extract_bool_field(data, "enabled", self.interner()).unwrap_or(true);
-
-        // Parse the pattern
-        let pattern = parse_pattern_from_value(data, self.interner())?;
+        let name = self.interner().get_keyword(name).unwrap_or_default();
+        let (decl, file) = self.session.rule_decls.get(name).cloned().ok_or_else(|| {
+            Error::new(ErrorKind::Internal(format!(
+                "rule :{name} was registered without its declaration"
+            )))
+        })?;
 
-        // Compile the pattern
-        let compiled_pattern =
-            PatternCompiler::compile(&pattern, self.session.world_mut().interner_mut())?;
-
-        // Create the compiled rule
-        let rule = CompiledRule {
-            name,
-            salience,
-            pattern: compiled_pattern,
-            once,
-            enabled,
-        };
+        // Compile the pattern and body
+        let mut rule = RuleCompiler::compile(&decl, self.session.world_mut().interner_mut())?;
+        rule.file = file;
 
         // Store the compiled rule in the session
-        self.session.add_compiled_rule(rule);
+        self.session.add_compiled_rule(rule)?;
 
         // Create a placeholder entity ID for the rule
         // In the future, rules could be first-class entities
@@ -678,7 +1425,7 @@ impl RuntimeContext for SessionContext<'_> {
     }
 
     fn intern_keyword(&mut self, name: &str) -> KeywordId {
-        self.interner_mut().intern_keyword(name)
+        self.interner_mut().intern_generated_keyword(name)
     }
 
     fn save_state(&mut self) -> u64 {
@@ -688,6 +1435,9 @@ impl RuntimeContext for SessionContext<'_> {
     fn restore_state(&mut self, snapshot_id: u64) -> Result<()> {
         self.session.restore_state(snapshot_id)
     }
+    fn allows_commands(&self) -> bool {
+        true
+    }
 }
 
 // =============================================================================
@@ -838,14 +1588,20 @@ fn extract_value_field(value: &Value, field_name: &str, interner: &Interner) ->
 ///
 /// This is the inverse of `ast_to_value` in the compiler.
 /// Note: Some information is lost in the round-trip (e.g., symbols become prefixed strings).
-fn value_to_ast(value: &Value, interner: &Interner) -> longtable_language::Ast {
-    use longtable_language::Ast;
+pub(crate) fn value_to_ast(value: &Value, interner: &Interner) -> longtable_language::Ast {
     let span = Span::default();
 
     match value {
         Value::Nil => Ast::Nil(span),
         Value::Bool(b) => Ast::Bool(*b, span),
         Value::Int(n) => Ast::Int(*n, span),
+        Value::BigInt(n) => Ast::List(
+            vec![
+                Ast::Symbol("bigint".to_string(), span),
+                Ast::String(n.to_string(), span),
+            ],
+            span,
+        ),
         Value::Float(f) => Ast::Float(*f, span),
         Value::String(s) => {
             let s_str = s.to_string();
@@ -900,8 +1656,33 @@ fn value_to_ast(value: &Value, interner: &Interner) -> longtable_language::Ast {
                 span,
             )
         }
-        Value::Fn(_) => {
-            // Functions can't be serialized back to AST
+        Value::Instant(ms) | Value::Duration(ms) => {
+            // Represent as the call that constructs it
+            let constructor = if matches!(value, Value::Instant(_)) {
+                "instant"
+            } else {
+                "duration"
+            };
+            Ast::List(
+                vec![
+                    Ast::Symbol(constructor.to_string(), span),
+                    Ast::Int(*ms, span),
+                ],
+                span,
+            )
+        }
+        Value::Vec2(_) | Value::Vec3(_) => {
+            let components = value.as_components().unwrap_or_default();
+            let constructor = Ast::Symbol(value.value_type().to_string(), span);
+            Ast::List(
+                std::iter::once(constructor)
+                    .chain(components.iter().map(|&x| Ast::Float(x, span)))
+                    .collect(),
+                span,
+            )
+        }
+        Value::Fn(_) | Value::Seq(_) => {
+            // Functions (and sequences of their results) can't be serialized back to AST
             Ast::Nil(span)
         }
     }
@@ -994,6 +1775,9 @@ fn parse_pattern_from_pattern_value(pattern_val: &Value, interner: &Interner) ->
 
     let mut clauses = Vec::new();
     let mut negations = Vec::new();
+    let mut not_joins = Vec::new();
+    let mut disjunctions = Vec::new();
+    let mut predicates = Vec::new();
 
     for (k, v) in map.iter() {
         if let Value::Keyword(kw) = k {
@@ -1013,13 +1797,22 @@ fn parse_pattern_from_pattern_value(pattern_val: &Value, interner: &Interner) ->
                             }
                         }
                     }
+                    "not-joins" => not_joins = parse_not_joins(v, interner)?,
+                    "disjunctions" => disjunctions = parse_disjunctions(v, interner)?,
+                    "predicates" => predicates = parse_predicates(v, interner)?,
                     _ => {}
                 }
             }
         }
     }
 
-    Ok(Pattern { clauses, negations })
+    Ok(Pattern {
+        clauses,
+        negations,
+        not_joins,
+        disjunctions,
+        predicates,
+    })
 }
 
 /// Parses handler AST expressions from a Value map.
@@ -1081,6 +1874,10 @@ fn parse_field_schema(value: &Value, interner: &Interner) -> Result<FieldSchema>
         "string" => Type::String,
         "keyword" => Type::Keyword,
         "entity" => Type::EntityRef,
+        "instant" => Type::Instant,
+        "duration" => Type::Duration,
+        "vec2" => Type::Vec2,
+        "vec3" => Type::Vec3,
         "vec" | "vector" => Type::vec(Type::Any),
         "map" => Type::map(Type::Any, Type::Any),
         "set" => Type::set(Type::Any),
@@ -1123,6 +1920,10 @@ fn parse_relationship_schema(value: &Value, interner: &Interner) -> Result<Relat
         schema = schema.with_on_delete(on_delete);
     }
 
+    if extract_bool_field(value, "acyclic", interner) == Some(true) {
+        schema = schema.with_acyclic(true);
+    }
+
     Ok(schema)
 }
 
@@ -1237,8 +2038,115 @@ fn parse_pattern_from_value(data: &Value, interner: &Interner) -> Result<Pattern
     } else {
         parse_single_clauses(&negations_val, interner)?
     };
+    let not_joins = match extract_value_field(&pattern_val, "not-joins", interner) {
+        Some(val) => parse_not_joins(&val, interner)?,
+        None => Vec::new(),
+    };
+    let disjunctions = match extract_value_field(&pattern_val, "disjunctions", interner) {
+        Some(val) => parse_disjunctions(&val, interner)?,
+        None => Vec::new(),
+    };
+    let predicates = match extract_value_field(&pattern_val, "predicates", interner) {
+        Some(val) => parse_predicates(&val, interner)?,
+        None => Vec::new(),
+    };
+
+    Ok(Pattern {
+        clauses,
+        negations,
+        not_joins,
+        disjunctions,
+        predicates,
+    })
+}
 
-    Ok(Pattern { clauses, negations })
+/// Parses a pattern's `:predicates`, each a map with `:expr` and `:vars`.
+fn parse_predicates(val: &Value, interner: &Interner) -> Result<Vec<PatternPredicate>> {
+    let malformed = |what: &str| {
+        Error::new(ErrorKind::InvalidArgument(format!(
+            "malformed pattern predicate: {what}"
+        )))
+    };
+    let vec = val
+        .as_vec()
+        .ok_or_else(|| malformed("expected a vec of predicates"))?;
+    vec.iter()
+        .map(|predicate| {
+            let expr = extract_value_field(predicate, "expr", interner)
+                .ok_or_else(|| malformed("missing :expr"))?;
+            let vars = match extract_value_field(predicate, "vars", interner) {
+                Some(vars) => vars
+                    .as_vec()
+                    .ok_or_else(|| malformed(":vars must be a vec"))?
+                    .iter()
+                    .map(|v| {
+                        v.as_str()
+                            .map(str::to_string)
+                            .ok_or_else(|| malformed(":vars must hold strings"))
+                    })
+                    .collect::<Result<_>>()?,
+                None => Vec::new(),
+            };
+            Ok(PatternPredicate {
+                expr: value_to_ast(&expr, interner),
+                vars,
+                span: Span::default(),
+            })
+        })
+        .collect()
+}
+
+/// Parses a pattern's `:disjunctions`, each a vector of branches of clauses.
+fn parse_disjunctions(val: &Value, interner: &Interner) -> Result<Vec<Disjunction>> {
+    let Some(vec) = val.as_vec() else {
+        return Ok(Vec::new());
+    };
+    vec.iter()
+        .map(|disjunction| {
+            let branches = disjunction.as_vec().ok_or_else(|| {
+                Error::new(ErrorKind::InvalidArgument(
+                    "expected vec for disjunction".to_string(),
+                ))
+            })?;
+            Ok(Disjunction {
+                branches: branches
+                    .iter()
+                    .map(|branch| parse_single_clauses(branch, interner))
+                    .collect::<Result<_>>()?,
+                span: Span::default(),
+            })
+        })
+        .collect()
+}
+
+/// Parses a pattern's `:not-joins`, each a map with `:join-vars` and
+/// `:clauses`.
+fn parse_not_joins(val: &Value, interner: &Interner) -> Result<Vec<NotJoin>> {
+    let Some(vec) = val.as_vec() else {
+        return Ok(Vec::new());
+    };
+    vec.iter()
+        .map(|not_join| {
+            let join_vars = extract_value_field(not_join, "join-vars", interner)
+                .and_then(|v| v.as_vec().cloned())
+                .map(|vars| {
+                    vars.iter()
+                        .filter_map(|v| v.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let clauses = extract_value_field(not_join, "clauses", interner).ok_or_else(|| {
+                Error::new(ErrorKind::InvalidArgument(
+                    "not-join missing clauses".to_string(),
+                ))
+            })?;
+            Ok(NotJoin {
+                join_vars,
+                clauses: parse_single_clauses(&clauses, interner)?,
+                span: Span::default(),
+            })
+        })
+        .collect()
 }
 
 /// Parses a `Vec<PatternClause>` from a `Value::Vec`.
@@ -0,0 +1,297 @@
+//! Opt-in telemetry for shipped games.
+//!
+//! Hosts that want to learn how players get on with their content can plug in
+//! a [`TelemetrySink`]. Nothing is collected until the session has explicitly
+//! opted in, and events never carry raw player input: only the category of
+//! command that ran, the class of a parse failure, or a bucketed tick
+//! duration. Events are delivered to the sink in batches.
+
+use std::fmt;
+use std::time::Duration;
+
+use longtable_parser::parser::ParseError;
+use serde::Serialize;
+
+/// Default number of events buffered before a batch is sent.
+const DEFAULT_BATCH_SIZE: usize = 32;
+
+/// Why natural language input could not be turned into a single command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParseFailureClass {
+    /// Nothing was entered.
+    EmptyInput,
+    /// A word isn't in the vocabulary.
+    UnknownWord,
+    /// No command syntax matched.
+    NoMatch,
+    /// A noun didn't resolve to anything in scope.
+    NotFound,
+    /// A noun resolved to the wrong kind of thing.
+    WrongType,
+    /// A pronoun had nothing to refer to.
+    NoReferent,
+    /// A noun matched more than one entity.
+    Ambiguous,
+    /// "again" or "oops" had no earlier input to work on.
+    NoEarlierInput,
+    /// No NPC in the command knows the topic.
+    UnknownTopic,
+    /// A scope's pattern failed to match.
+    ScopeFailed,
+}
+
+impl From<&ParseError> for ParseFailureClass {
+    fn from(err: &ParseError) -> Self {
+        match err {
+            ParseError::EmptyInput => Self::EmptyInput,
+            ParseError::UnknownWord(_) => Self::UnknownWord,
+            ParseError::NoMatch => Self::NoMatch,
+            ParseError::NotFound(_) => Self::NotFound,
+            ParseError::WrongType { .. } => Self::WrongType,
+            ParseError::NoReferent(_) => Self::NoReferent,
+            ParseError::NothingToRepeat | ParseError::NothingToCorrect => Self::NoEarlierInput,
+            ParseError::UnknownTopic(_) => Self::UnknownTopic,
+            ParseError::ScopeFailed(_) => Self::ScopeFailed,
+        }
+    }
+}
+
+/// Coarse bucket for a tick's wall-clock duration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DurationBucket {
+    /// Under 1ms.
+    Under1Ms,
+    /// 1ms up to 10ms.
+    Under10Ms,
+    /// 10ms up to 100ms.
+    Under100Ms,
+    /// 100ms up to 1s.
+    Under1S,
+    /// 1s or longer.
+    Over1S,
+}
+
+impl From<Duration> for DurationBucket {
+    fn from(duration: Duration) -> Self {
+        match duration.as_millis() {
+            0 => Self::Under1Ms,
+            1..10 => Self::Under10Ms,
+            10..100 => Self::Under100Ms,
+            100..1000 => Self::Under1S,
+            _ => Self::Over1S,
+        }
+    }
+}
+
+/// An anonymized telemetry event.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum TelemetryEvent {
+    /// A command was parsed and run. `category` is the author-defined action name.
+    Command {
+        /// Action name.
+        category: String,
+    },
+    /// Input could not be parsed into a command.
+    ParseFailure {
+        /// What went wrong.
+        class: ParseFailureClass,
+    },
+    /// A tick completed.
+    Tick {
+        /// How long it took.
+        duration: DurationBucket,
+    },
+}
+
+/// Receives batches of telemetry events. Implemented by the host application.
+pub trait TelemetrySink {
+    /// Delivers a batch of events, oldest first.
+    fn send(&mut self, events: &[TelemetryEvent]);
+}
+
+/// Buffers telemetry events and forwards them to a sink once opted in.
+pub struct Telemetry {
+    /// Whether the player has agreed to telemetry.
+    opted_in: bool,
+    /// Where batches are sent.
+    sink: Option<Box<dyn TelemetrySink>>,
+    /// Events waiting for the next batch.
+    pending: Vec<TelemetryEvent>,
+    /// Number of events per batch.
+    batch_size: usize,
+}
+
+impl Telemetry {
+    /// Creates telemetry with no sink that has not been opted into.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            opted_in: false,
+            sink: None,
+            pending: Vec::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Sets the number of events buffered before a batch is sent.
+    #[must_use]
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Installs the sink that receives batches, flushing anything pending
+    /// to the previous sink first.
+    pub fn set_sink(&mut self, sink: Box<dyn TelemetrySink>) {
+        self.flush();
+        self.sink = Some(sink);
+    }
+
+    /// Opts in or out. Opting out discards any events not yet sent.
+    pub fn set_opted_in(&mut self, opted_in: bool) {
+        self.opted_in = opted_in;
+        if !opted_in {
+            self.pending.clear();
+        }
+    }
+
+    /// Returns true if the session has opted in.
+    #[must_use]
+    pub const fn is_opted_in(&self) -> bool {
+        self.opted_in
+    }
+
+    /// Returns true if events are being collected (opted in with a sink installed).
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.opted_in && self.sink.is_some()
+    }
+
+    /// Returns the number of events waiting for the next batch.
+    #[must_use]
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Records an event. Does nothing unless telemetry is enabled.
+    pub fn record(&mut self, event: TelemetryEvent) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.pending.push(event);
+        if self.pending.len() >= self.batch_size {
+            self.flush();
+        }
+    }
+
+    /// Sends any pending events to the sink now.
+    pub fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        if let Some(sink) = &mut self.sink {
+            sink.send(&self.pending);
+        }
+        self.pending.clear();
+    }
+
+    /// Sends any pending events and drops the sink, as at shutdown.
+    pub fn close(&mut self) {
+        self.flush();
+        self.sink = None;
+    }
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Telemetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Telemetry")
+            .field("opted_in", &self.opted_in)
+            .field("has_sink", &self.sink.is_some())
+            .field("pending", &self.pending.len())
+            .field("batch_size", &self.batch_size)
+            .finish()
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct Collect(Rc<RefCell<Vec<Vec<TelemetryEvent>>>>);
+
+    impl TelemetrySink for Collect {
+        fn send(&mut self, events: &[TelemetryEvent]) {
+            self.0.borrow_mut().push(events.to_vec());
+        }
+    }
+
+    fn tick() -> TelemetryEvent {
+        TelemetryEvent::Tick {
+            duration: DurationBucket::Under1Ms,
+        }
+    }
+
+    #[test]
+    fn nothing_is_recorded_without_opt_in() {
+        let sink = Collect::default();
+        let mut telemetry = Telemetry::new().with_batch_size(1);
+        telemetry.set_sink(Box::new(sink.clone()));
+
+        telemetry.record(tick());
+        assert_eq!(telemetry.pending_len(), 0);
+        assert!(sink.0.borrow().is_empty());
+    }
+
+    #[test]
+    fn events_are_batched() {
+        let sink = Collect::default();
+        let mut telemetry = Telemetry::new().with_batch_size(2);
+        telemetry.set_sink(Box::new(sink.clone()));
+        telemetry.set_opted_in(true);
+
+        telemetry.record(tick());
+        assert!(sink.0.borrow().is_empty());
+        telemetry.record(tick());
+        assert_eq!(sink.0.borrow().len(), 1);
+        assert_eq!(sink.0.borrow()[0].len(), 2);
+
+        // Remaining events go out when telemetry is dropped
+        telemetry.record(tick());
+        drop(telemetry);
+        assert_eq!(sink.0.borrow().len(), 2);
+    }
+
+    #[test]
+    fn durations_are_bucketed() {
+        assert_eq!(
+            DurationBucket::from(Duration::from_micros(300)),
+            DurationBucket::Under1Ms
+        );
+        assert_eq!(
+            DurationBucket::from(Duration::from_millis(42)),
+            DurationBucket::Under100Ms
+        );
+        assert_eq!(
+            DurationBucket::from(Duration::from_secs(3)),
+            DurationBucket::Over1S
+        );
+    }
+}
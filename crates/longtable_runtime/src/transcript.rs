@@ -0,0 +1,214 @@
+//! Transcript of natural language input for play-test analytics.
+//!
+//! Every command the player types in input mode is recorded along with
+//! when it was entered, how long it took to handle, and how the parser
+//! dealt with it. The log can be exported as JSON so authors can find the
+//! places where players struggle (frequent parse errors, ambiguity, etc.).
+
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use longtable_foundation::{Error, ErrorKind, Result};
+use serde::Serialize;
+
+/// How the parser handled a line of input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputOutcome {
+    /// Parsed into one or more commands.
+    Success,
+    /// Matched more than one entity; the player was asked to disambiguate.
+    Ambiguous,
+    /// Could not be parsed.
+    Error,
+}
+
+/// A single recorded line of input.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TranscriptEntry {
+    /// The raw input text.
+    pub input: String,
+    /// Wall-clock time the input was entered, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    /// Time spent parsing and executing the input, in microseconds.
+    pub duration_us: u64,
+    /// World tick when the input was entered.
+    pub tick: u64,
+    /// How the parser handled the input.
+    pub outcome: InputOutcome,
+    /// Name of the command syntax that matched, if any.
+    pub syntax: Option<String>,
+    /// Name of the action that was invoked, if any.
+    pub action: Option<String>,
+    /// Parse or execution error message, if any.
+    pub error: Option<String>,
+}
+
+impl TranscriptEntry {
+    /// Creates an entry for input entered at `started`, with no match details.
+    #[must_use]
+    pub fn new(
+        input: impl Into<String>,
+        started: SystemTime,
+        tick: u64,
+        outcome: InputOutcome,
+    ) -> Self {
+        let timestamp_ms = started
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+        Self {
+            input: input.into(),
+            timestamp_ms,
+            duration_us: 0,
+            tick,
+            outcome,
+            syntax: None,
+            action: None,
+            error: None,
+        }
+    }
+
+    /// Sets how long the input took to handle.
+    #[must_use]
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration_us = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        self
+    }
+
+    /// Sets the matched syntax name.
+    #[must_use]
+    pub fn with_syntax(mut self, syntax: impl Into<String>) -> Self {
+        self.syntax = Some(syntax.into());
+        self
+    }
+
+    /// Sets the invoked action name.
+    #[must_use]
+    pub fn with_action(mut self, action: impl Into<String>) -> Self {
+        self.action = Some(action.into());
+        self
+    }
+
+    /// Sets the error message.
+    #[must_use]
+    pub fn with_error(mut self, error: impl Into<String>) -> Self {
+        self.error = Some(error.into());
+        self
+    }
+}
+
+/// Ordered log of recorded input.
+#[derive(Clone, Debug, Default)]
+pub struct Transcript {
+    entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    /// Creates an empty transcript.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an entry.
+    pub fn record(&mut self, entry: TranscriptEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Returns all entries in the order they were recorded.
+    #[must_use]
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if nothing has been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the number of entries with the given outcome.
+    #[must_use]
+    pub fn count(&self, outcome: InputOutcome) -> usize {
+        self.entries.iter().filter(|e| e.outcome == outcome).count()
+    }
+
+    /// Serializes the transcript as a pretty-printed JSON array.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(&self.entries).map_err(|e| {
+            Error::new(ErrorKind::SerializationError(format!(
+                "failed to serialize transcript: {e}"
+            )))
+        })
+    }
+
+    /// Writes the transcript to a file as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or writing fails.
+    pub fn save_json(&self, path: &Path) -> Result<()> {
+        let json = self.to_json()?;
+        std::fs::write(path, json).map_err(|e| {
+            Error::new(ErrorKind::IoError(format!(
+                "failed to write transcript '{}': {e}",
+                path.display()
+            )))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_count() {
+        let mut transcript = Transcript::new();
+        let now = SystemTime::now();
+        transcript.record(TranscriptEntry::new("look", now, 0, InputOutcome::Success));
+        transcript.record(TranscriptEntry::new("xyzzy", now, 0, InputOutcome::Error));
+        transcript.record(TranscriptEntry::new("take", now, 1, InputOutcome::Error));
+
+        assert_eq!(transcript.len(), 3);
+        assert_eq!(transcript.count(InputOutcome::Success), 1);
+        assert_eq!(transcript.count(InputOutcome::Error), 2);
+        assert_eq!(transcript.count(InputOutcome::Ambiguous), 0);
+    }
+
+    #[test]
+    fn json_export() {
+        let mut transcript = Transcript::new();
+        transcript.record(
+            TranscriptEntry::new("take lamp", UNIX_EPOCH, 3, InputOutcome::Success)
+                .with_duration(Duration::from_micros(250))
+                .with_syntax("take")
+                .with_action("take"),
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&transcript.to_json().unwrap()).unwrap();
+        let entry = &json[0];
+        assert_eq!(entry["input"], "take lamp");
+        assert_eq!(entry["timestamp_ms"], 0);
+        assert_eq!(entry["duration_us"], 250);
+        assert_eq!(entry["tick"], 3);
+        assert_eq!(entry["outcome"], "success");
+        assert_eq!(entry["syntax"], "take");
+        assert!(entry["error"].is_null());
+    }
+}
@@ -0,0 +1,99 @@
+//! JavaScript bindings for running Longtable in a browser.
+//!
+//! Build the runtime for `wasm32-unknown-unknown` without the terminal
+//! front end and with these bindings:
+//!
+//! ```text
+//! wasm-pack build crates/longtable_runtime --target web -- --no-default-features --features wasm
+//! ```
+//!
+//! An [`Engine`] wraps a headless [`Repl`] that captures its output, so a
+//! page can load a game with [`Engine::eval`], feed it player commands with
+//! [`Engine::input`], and show whatever the game printed. There is no file
+//! system in the browser: `load`, `save!` and friends return errors, so
+//! games are loaded by passing their source text to `eval`.
+
+use wasm_bindgen::prelude::{JsError, wasm_bindgen};
+
+use crate::{HeadlessEditor, Repl};
+
+/// A Longtable world and interpreter for JavaScript.
+#[wasm_bindgen]
+pub struct Engine {
+    repl: Repl<HeadlessEditor>,
+}
+
+#[wasm_bindgen]
+impl Engine {
+    /// Creates an engine with the standard library loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message if the standard library fails to load.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Result<Engine, JsError> {
+        let mut repl = Repl::with_editor(HeadlessEditor).with_captured_output();
+        repl.load_stdlib().map_err(to_js)?;
+        Ok(Self { repl })
+    }
+
+    /// Evaluates DSL source and returns what it printed followed by its value
+    /// (omitted when `nil`).
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message if parsing or evaluation fails.
+    pub fn eval(&mut self, source: &str) -> Result<String, JsError> {
+        let value = self.repl.eval(source).map_err(to_js)?;
+        let mut output = self.repl.take_output();
+        if !value.is_nil() {
+            output.push_str(&self.repl.display_value(&value));
+        }
+        Ok(output)
+    }
+
+    /// Handles a player command such as `take lamp` and returns the narration.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message if the matched action fails.
+    pub fn input(&mut self, line: &str) -> Result<String, JsError> {
+        self.repl.input(line).map_err(to_js)?;
+        Ok(self.repl.take_output())
+    }
+
+    /// Runs one tick and returns the new tick number.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message if a hook or rule fails.
+    pub fn tick(&mut self) -> Result<u64, JsError> {
+        self.repl.step(&[]).map_err(to_js)?;
+        Ok(self.repl.tick_number())
+    }
+
+    /// Returns the number of ticks run so far.
+    #[wasm_bindgen(getter, js_name = tickNumber)]
+    #[must_use]
+    pub fn tick_number(&self) -> u64 {
+        self.repl.tick_number()
+    }
+
+    /// Returns the world's content hash in hex, for save-state comparison.
+    #[wasm_bindgen(getter, js_name = worldHash)]
+    #[must_use]
+    pub fn world_hash(&self) -> String {
+        format!("{:016x}", self.repl.session().world().content_hash())
+    }
+
+    /// Returns and clears any output not yet returned by `eval` or `input`.
+    #[wasm_bindgen(js_name = takeOutput)]
+    pub fn take_output(&mut self) -> String {
+        self.repl.take_output()
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn to_js(error: longtable_foundation::Error) -> JsError {
+    JsError::new(&error.to_string())
+}
@@ -373,10 +373,13 @@ fn test_relationship_query() {
             },
         ],
         negations: vec![],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     };
 
     let compiled = PatternCompiler::compile(&pattern, world.interner_mut()).unwrap();
-    let matches = PatternMatcher::match_pattern(&compiled, &world);
+    let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
     // Should find the player in the cave entrance
     assert_eq!(matches.len(), 1);
@@ -422,10 +425,13 @@ fn test_find_all_items_in_rooms() {
             },
         ],
         negations: vec![],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     };
 
     let compiled = PatternCompiler::compile(&pattern, world.interner_mut()).unwrap();
-    let matches = PatternMatcher::match_pattern(&compiled, &world);
+    let matches = PatternMatcher::match_pattern(&compiled, &world).unwrap();
 
     // Should find items in rooms (the test world has brass-lantern in cave entrance, sword in main hall)
     assert!(
@@ -434,3 +440,14 @@ fn test_find_all_items_in_rooms() {
         matches.len()
     );
 }
+
+#[test]
+fn test_example_loads() {
+    use longtable_runtime::{HeadlessEditor, Repl};
+
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/adventure");
+    let mut repl = Repl::with_editor(HeadlessEditor).with_captured_output();
+    repl.load_stdlib().unwrap();
+    repl.load_file(dir).unwrap();
+    assert!(repl.session().get_entity("player").is_some());
+}
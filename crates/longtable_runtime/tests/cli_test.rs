@@ -0,0 +1,56 @@
+//! Integration tests for the `longtable` binary's subcommands.
+//!
+//! These run the built binary against the adventure example and check
+//! that stdout carries only the requested output, not the game's narration.
+
+#![cfg(feature = "cli")]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Path to the adventure example shipped with the repository.
+fn adventure() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../examples/adventure")
+}
+
+/// Runs the binary with `args` and returns its stdout, failing on a non-zero exit.
+fn longtable(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_longtable"))
+        .args(args)
+        .output()
+        .expect("failed to run longtable");
+    assert!(
+        output.status.success(),
+        "longtable {args:?} failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("stdout is UTF-8")
+}
+
+#[test]
+fn run_prints_only_the_json_report() {
+    let script = adventure();
+    let stdout = longtable(&["run", "--ticks", "2", "--script", script.to_str().unwrap()]);
+
+    let report: serde_json::Value = serde_json::from_str(&stdout).unwrap_or_else(|e| {
+        panic!("stdout is not JSON ({e}):\n{stdout}");
+    });
+    assert_eq!(report["ticks_run"], 2);
+}
+
+#[test]
+fn doc_prints_only_the_reference() {
+    let script = adventure();
+    let stdout = longtable(&["doc", script.to_str().unwrap()]);
+
+    assert!(stdout.starts_with("# Reference"), "{stdout}");
+    assert!(!stdout.contains("THE DARK CAVE"), "{stdout}");
+}
+
+#[test]
+fn lint_prints_only_the_report() {
+    let script = adventure();
+    let stdout = longtable(&["lint", script.to_str().unwrap()]);
+
+    assert_eq!(stdout.trim_end(), "Lint: no issues found");
+}
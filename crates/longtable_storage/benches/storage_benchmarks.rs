@@ -789,11 +789,11 @@ fn bench_relationship_advanced(c: &mut Criterion) {
                 let entities: Vec<_> = (1..=fan_out as u64).map(|i| EntityId::new(i, 1)).collect();
 
                 // Half point to hub, half hub points to
-                for i in 0..fan_out / 2 {
-                    store.link(entities[i], connected, hub).unwrap();
+                for &e in &entities[..fan_out / 2] {
+                    store.link(e, connected, hub).unwrap();
                 }
-                for i in fan_out / 2..fan_out {
-                    store.link(hub, connected, entities[i]).unwrap();
+                for &e in &entities[fan_out / 2..] {
+                    store.link(hub, connected, e).unwrap();
                 }
 
                 // Benchmark looking up both directions
@@ -4,6 +4,7 @@
 //! Archetypes track which components each entity has for efficient querying.
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use longtable_foundation::{EntityId, Error, ErrorKind, KeywordId, LtMap, Result, Type, Value};
 
@@ -11,6 +12,7 @@ use longtable_foundation::{EntityId, Error, ErrorKind, KeywordId, LtMap, Result,
 use serde::{Deserialize, Serialize};
 
 use crate::schema::{ComponentSchema, FieldSchema};
+use crate::spatial::SpatialGrid;
 
 /// Represents a set of component types an entity has.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
@@ -95,8 +97,23 @@ pub struct ComponentStore {
     data: HashMap<KeywordId, HashMap<EntityId, Value>>,
     /// Archetype for each entity.
     archetypes: HashMap<EntityId, Archetype>,
+    /// Stamp of the last write to each component (not serialized; stamps
+    /// are fresh for each process, so loading restamps every component).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    versions: HashMap<KeywordId, u64>,
+    /// Cell size of each component indexed by position.
+    #[cfg_attr(feature = "serde", serde(default))]
+    spatial: HashMap<KeywordId, f64>,
+    /// Position grid of each component indexed by position (not serialized;
+    /// rebuilt on load).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    grids: HashMap<KeywordId, SpatialGrid>,
 }
 
+/// Source of write stamps. Shared by every store, so two worlds forked from
+/// the same one never stamp different writes alike.
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(1);
+
 impl ComponentStore {
     /// Creates a new empty component store.
     #[must_use]
@@ -122,12 +139,23 @@ impl ComponentStore {
         Ok(())
     }
 
+    /// Removes the schema for a component type, keeping any data stored
+    /// under it. Returns the removed schema.
+    pub fn unregister_schema(&mut self, component: KeywordId) -> Option<ComponentSchema> {
+        self.schemas.remove(&component)
+    }
+
     /// Gets the schema for a component type.
     #[must_use]
     pub fn schema(&self, component: KeywordId) -> Option<&ComponentSchema> {
         self.schemas.get(&component)
     }
 
+    /// Iterates over all registered component schemas (in no particular order).
+    pub fn schemas(&self) -> impl Iterator<Item = &ComponentSchema> {
+        self.schemas.values()
+    }
+
     /// Sets a component on an entity.
     ///
     /// The value should be a map with field values for non-tag components,
@@ -151,6 +179,8 @@ impl ComponentStore {
             .entry(component)
             .or_default()
             .insert(entity, value);
+        self.touch(component);
+        self.place(entity, component);
 
         // Update archetype
         let archetype = self.archetypes.entry(entity).or_default();
@@ -206,6 +236,8 @@ impl ComponentStore {
             let new_map = map.insert(Value::Keyword(field), value);
             *comp_value = Value::Map(new_map);
         }
+        self.touch(component);
+        self.place(entity, component);
 
         // Update archetype
         let archetype = self.archetypes.entry(entity).or_default();
@@ -251,6 +283,8 @@ impl ComponentStore {
         let value = self.data.get_mut(&component)?.remove(&entity);
 
         if value.is_some() {
+            self.touch(component);
+            self.place(entity, component);
             if let Some(archetype) = self.archetypes.get_mut(&entity) {
                 *archetype = archetype.without_component(component);
             }
@@ -266,7 +300,14 @@ impl ComponentStore {
         for comp_data in self.data.values_mut() {
             comp_data.remove(&entity);
         }
-        self.archetypes.remove(&entity);
+        if let Some(archetype) = self.archetypes.remove(&entity) {
+            for component in archetype.components() {
+                self.touch(*component);
+            }
+        }
+        for grid in self.grids.values_mut() {
+            grid.place(entity, None);
+        }
     }
 
     /// Gets the archetype for an entity.
@@ -275,6 +316,28 @@ impl ComponentStore {
         self.archetypes.get(&entity)
     }
 
+    /// Counts the entities of each archetype.
+    #[must_use]
+    pub fn archetype_counts(&self) -> HashMap<&Archetype, usize> {
+        let mut counts = HashMap::new();
+        for archetype in self.archetypes.values() {
+            *counts.entry(archetype).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Returns the number of component values stored.
+    #[must_use]
+    pub fn value_count(&self) -> usize {
+        self.data.values().map(HashMap::len).sum()
+    }
+
+    /// Returns the number of entities with `component`.
+    #[must_use]
+    pub fn count(&self, component: KeywordId) -> usize {
+        self.data.get(&component).map_or(0, HashMap::len)
+    }
+
     /// Iterates entities with a specific component.
     pub fn with_component(&self, component: KeywordId) -> impl Iterator<Item = EntityId> + '_ {
         self.data
@@ -294,6 +357,16 @@ impl ComponentStore {
             .map(|(id, _)| *id)
     }
 
+    /// Returns a stamp that changes whenever `component` is set, updated, or
+    /// removed on any entity; 0 if it never has been.
+    ///
+    /// Stamps only ever grow, across all stores, so a stamp seen earlier
+    /// still matching means nothing has written the component since.
+    #[must_use]
+    pub fn version(&self, component: KeywordId) -> u64 {
+        self.versions.get(&component).copied().unwrap_or(0)
+    }
+
     /// Returns all component data in sorted order for deterministic hashing.
     ///
     /// Returns an iterator of (component, entity, value) tuples sorted by
@@ -313,8 +386,73 @@ impl ComponentStore {
         all_data.into_iter()
     }
 
+    /// Indexes a component by position, in grid cells `cell_size` across.
+    /// `axes` gives the axis each map key names.
+    pub(crate) fn index_spatially(
+        &mut self,
+        component: KeywordId,
+        cell_size: f64,
+        axes: HashMap<KeywordId, usize>,
+    ) {
+        self.spatial.insert(component, cell_size);
+        let mut grid = SpatialGrid::new(cell_size, axes);
+        for (entity, value) in self.data.get(&component).into_iter().flatten() {
+            grid.place(*entity, Some(value));
+        }
+        self.grids.insert(component, grid);
+    }
+
+    /// Rebuilds the grid of every component indexed by position, as after
+    /// loading a store.
+    #[cfg(feature = "serde")]
+    pub(crate) fn reindex_spatially(&mut self, axes: &HashMap<KeywordId, usize>) {
+        let indexed: Vec<(KeywordId, f64)> = self.spatial.iter().map(|(k, s)| (*k, *s)).collect();
+        for (component, cell_size) in indexed {
+            self.index_spatially(component, cell_size, axes.clone());
+        }
+    }
+
+    /// Gives every component a fresh stamp, as after loading a store, so no
+    /// stamp seen before the load matches.
+    #[cfg(feature = "serde")]
+    pub(crate) fn restamp(&mut self) {
+        let components: Vec<KeywordId> = self
+            .schemas
+            .keys()
+            .chain(self.data.keys())
+            .copied()
+            .collect();
+        for component in components {
+            self.touch(component);
+        }
+    }
+
+    /// Returns the cell size a component is indexed by, if it is.
+    #[must_use]
+    pub fn spatial_cell_size(&self, component: KeywordId) -> Option<f64> {
+        self.spatial.get(&component).copied()
+    }
+
+    /// Returns a component's position grid, if it's indexed by position.
+    pub(crate) fn grid(&self, component: KeywordId) -> Option<&SpatialGrid> {
+        self.grids.get(&component)
+    }
+
     // --- Private helpers ---
 
+    /// Moves an entity within a component's position grid, if it has one.
+    fn place(&mut self, entity: EntityId, component: KeywordId) {
+        if let Some(grid) = self.grids.get_mut(&component) {
+            let value = self.data.get(&component).and_then(|data| data.get(&entity));
+            grid.place(entity, value);
+        }
+    }
+
+    fn touch(&mut self, component: KeywordId) {
+        self.versions
+            .insert(component, NEXT_VERSION.fetch_add(1, Ordering::Relaxed));
+    }
+
     fn validate_component_value(schema: &ComponentSchema, value: &Value) -> Result<()> {
         if schema.is_tag {
             // Tag components accept true or a map
@@ -598,8 +736,10 @@ mod tests {
         assert!(store.has(entity, health));
         assert!(store.has(entity, position));
 
+        let before = store.version(health);
         store.remove_entity(entity);
 
+        assert!(store.version(health) > before);
         assert!(!store.has(entity, health));
         assert!(!store.has(entity, position));
         assert!(store.archetype(entity).is_none());
@@ -2,20 +2,147 @@
 //!
 //! The `EntityStore` manages entity allocation and tracks generations
 //! to detect stale references to destroyed entities.
+//!
+//! How destroyed indices come back is an [`EntityPolicy`]:
+//!
+//! - [`Recycling::FreeList`] (the default) reuses destroyed indices, keeping
+//!   the store compact. [`Recycling::AppendOnly`] never reuses an index, so
+//!   an index names one entity for the life of the world.
+//! - A slot's generation grows every time it is reused. Once it reaches
+//!   [`RETIRED_GENERATION`], [`GenerationOverflow::Retire`] (the default)
+//!   takes the slot out of service for good, while
+//!   [`GenerationOverflow::Wrap`] starts it over at generation 0, at the
+//!   risk of a very old reference matching a new entity.
+//!
+//! Entities declared by name in source (`spawn:`) get content-addressed
+//! indices instead: [`content_index`] hashes the name into the upper range
+//! of indices, at or above [`CONTENT_INDEX_BASE`], so loading the same
+//! content into a fresh world always yields the same [`EntityId`]s no matter
+//! what else was spawned first. Content slots are kept apart from the dense
+//! slots and are never handed out by [`EntityStore::spawn`].
 
 // Allow u64 to usize casts - we target 64-bit systems
 #![allow(clippy::cast_possible_truncation)]
 
-use longtable_foundation::{EntityId, Error, Result};
+use std::collections::BTreeMap;
+use std::hash::Hasher;
+
+use longtable_foundation::{EntityId, Error, ErrorKind, Result, StableHasher};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// Generation a slot is left at once it can no longer be reused safely.
+///
+/// It is even, so the slot reads as free, and spawning into it again would
+/// leave no generation to mark the next destruction.
+pub const RETIRED_GENERATION: u32 = u32::MAX - 1;
+
+/// First index used for content-addressed entities.
+///
+/// Dense allocation would need four quintillion spawns to get here.
+pub const CONTENT_INDEX_BASE: u64 = 1 << 62;
+
+/// Returns the content-addressed index for an entity declared as `name`.
+///
+/// The index is a stable hash of the name, so it is the same in every
+/// world, process, and build.
+#[must_use]
+pub fn content_index(name: &str) -> u64 {
+    let mut hasher = StableHasher::new();
+    hasher.write(name.as_bytes());
+    CONTENT_INDEX_BASE | (hasher.finish() & (CONTENT_INDEX_BASE - 1))
+}
+
+/// Returns true if `index` is in the content-addressed range.
+#[must_use]
+pub const fn is_content_index(index: u64) -> bool {
+    index >= CONTENT_INDEX_BASE
+}
+
+/// Whether destroyed entity indices are reused.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Recycling {
+    /// Reuse destroyed indices, most recently destroyed first.
+    #[default]
+    FreeList,
+    /// Always allocate a new index.
+    AppendOnly,
+}
+
+/// What happens to a slot whose generation runs out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GenerationOverflow {
+    /// Stop reusing the slot, so no stale reference can ever match it.
+    #[default]
+    Retire,
+    /// Start the slot over at generation 0.
+    Wrap,
+}
+
+/// How an [`EntityStore`] recycles entity indices.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EntityPolicy {
+    /// Whether destroyed indices are reused.
+    pub recycling: Recycling,
+    /// What happens when a slot's generation runs out.
+    pub overflow: GenerationOverflow,
+    /// Whether entities declared by name in source get content-addressed
+    /// IDs (see [`content_index`]) rather than the next dense index.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub content_ids: bool,
+}
+
+impl EntityPolicy {
+    /// A policy that never reuses an index.
+    #[must_use]
+    pub const fn append_only() -> Self {
+        Self {
+            recycling: Recycling::AppendOnly,
+            overflow: GenerationOverflow::Retire,
+            content_ids: false,
+        }
+    }
+
+    /// Sets whether named declarations get content-addressed IDs.
+    #[must_use]
+    pub const fn with_content_ids(mut self, content_ids: bool) -> Self {
+        self.content_ids = content_ids;
+        self
+    }
+
+    /// Sets the generation overflow behavior.
+    #[must_use]
+    pub const fn with_overflow(mut self, overflow: GenerationOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+}
+
+/// Counts of entity slots, from [`EntityStore::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EntityStats {
+    /// Live entities.
+    pub live: usize,
+    /// Allocated slots with no live entity, including retired ones.
+    pub dead: usize,
+    /// Dead slots waiting to be reused.
+    pub reusable: usize,
+    /// Slots whose generation ran out and will never be reused.
+    pub retired: usize,
+    /// Highest densely allocated index, if any.
+    pub highest_index: Option<u64>,
+}
+
 /// Manages entity lifecycle and generation tracking.
 ///
 /// Entities are allocated from a free list when available, otherwise
-/// new indices are allocated. When an entity is destroyed, its index
-/// is added to the free list and its generation is incremented.
+/// new indices are allocated. When an entity is destroyed, its generation
+/// is incremented and, unless the [`EntityPolicy`] says otherwise, its
+/// index is added to the free list.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EntityStore {
@@ -26,6 +153,13 @@ pub struct EntityStore {
     free_list: Vec<u64>,
     /// Count of live entities.
     live_count: usize,
+    /// How indices are recycled.
+    #[cfg_attr(feature = "serde", serde(default))]
+    policy: EntityPolicy,
+    /// Generations of content-addressed slots, and of slots spawned by ID
+    /// beyond the dense range, by index.
+    #[cfg_attr(feature = "serde", serde(default))]
+    content: BTreeMap<u64, u32>,
 }
 
 impl Default for EntityStore {
@@ -42,9 +176,37 @@ impl EntityStore {
             generations: Vec::new(),
             free_list: Vec::new(),
             live_count: 0,
+            policy: EntityPolicy::default(),
+            content: BTreeMap::new(),
         }
     }
 
+    /// Returns the recycling policy.
+    #[must_use]
+    pub const fn policy(&self) -> EntityPolicy {
+        self.policy
+    }
+
+    /// Changes the recycling policy.
+    ///
+    /// Switching to [`Recycling::AppendOnly`] forgets the free list;
+    /// switching back to [`Recycling::FreeList`] makes every dead slot that
+    /// isn't retired reusable again.
+    pub fn set_policy(&mut self, policy: EntityPolicy) {
+        if policy.recycling != self.policy.recycling {
+            self.free_list = match policy.recycling {
+                Recycling::AppendOnly => Vec::new(),
+                Recycling::FreeList => (0..self.generations.len() as u64)
+                    .filter(|&index| {
+                        let generation = self.generations[index as usize];
+                        generation % 2 == 0 && generation != RETIRED_GENERATION
+                    })
+                    .collect(),
+            };
+        }
+        self.policy = policy;
+    }
+
     /// Spawns a new entity, returns its ID.
     ///
     /// Reuses indices from the free list when available.
@@ -73,24 +235,36 @@ impl EntityStore {
     pub fn destroy(&mut self, id: EntityId) -> Result<()> {
         self.validate(id)?;
 
+        if self.has_sparse_slot(id.index) {
+            // Sparse slots are only ever respawned by name or by ID
+            self.content.insert(id.index, id.generation + 1);
+            self.live_count -= 1;
+            return Ok(());
+        }
+
         let idx = id.index as usize;
         // Increment generation (was odd/alive, now even/free)
         self.generations[idx] += 1;
-        self.free_list.push(id.index);
         self.live_count -= 1;
 
+        if self.generations[idx] == RETIRED_GENERATION {
+            match self.policy.overflow {
+                GenerationOverflow::Retire => return Ok(()),
+                GenerationOverflow::Wrap => self.generations[idx] = 0,
+            }
+        }
+        if self.policy.recycling == Recycling::FreeList {
+            self.free_list.push(id.index);
+        }
+
         Ok(())
     }
 
     /// Checks if an entity exists and is not stale.
     #[must_use]
     pub fn exists(&self, id: EntityId) -> bool {
-        let idx = id.index as usize;
-        if idx >= self.generations.len() {
-            return false;
-        }
         // Entity is alive if generation matches and is odd
-        self.generations[idx] == id.generation && id.generation % 2 == 1
+        self.generation(id.index) == Some(id.generation) && id.generation % 2 == 1
     }
 
     /// Validates that an entity is live.
@@ -98,13 +272,9 @@ impl EntityStore {
     /// Returns `Ok(())` if the entity exists.
     /// Returns `Err` with context if the entity is stale or never existed.
     pub fn validate(&self, id: EntityId) -> Result<()> {
-        let idx = id.index as usize;
-
-        if idx >= self.generations.len() {
+        let Some(current_gen) = self.generation(id.index) else {
             return Err(Error::entity_not_found(id));
-        }
-
-        let current_gen = self.generations[idx];
+        };
 
         if current_gen != id.generation {
             // Generation mismatch - entity was destroyed and possibly reused
@@ -138,6 +308,12 @@ impl EntityStore {
             .enumerate()
             .filter(|(_, generation)| *generation % 2 == 1) // Odd generation = alive
             .map(|(idx, generation)| EntityId::new(idx as u64, *generation))
+            .chain(
+                self.content
+                    .iter()
+                    .filter(|(_, generation)| *generation % 2 == 1)
+                    .map(|(&index, &generation)| EntityId::new(index, generation)),
+            )
     }
 
     /// Returns the current generation for an index, if it exists.
@@ -145,7 +321,17 @@ impl EntityStore {
     /// This is useful for debugging and testing.
     #[must_use]
     pub fn generation(&self, index: u64) -> Option<u32> {
-        self.generations.get(index as usize).copied()
+        if self.has_sparse_slot(index) {
+            self.content.get(&index).copied()
+        } else {
+            self.generations.get(index as usize).copied()
+        }
+    }
+
+    /// Returns true if `index` is kept in the sparse slots rather than the
+    /// dense generations array.
+    fn has_sparse_slot(&self, index: u64) -> bool {
+        is_content_index(index) || index >= self.generations.len() as u64
     }
 
     /// Returns the generations slice for content hashing.
@@ -156,20 +342,69 @@ impl EntityStore {
         &self.generations
     }
 
+    /// Returns the content-addressed slots and their generations, in index
+    /// order.
+    pub fn content_slots(&self) -> impl Iterator<Item = (u64, u32)> + '_ {
+        self.content
+            .iter()
+            .map(|(&index, &generation)| (index, generation))
+    }
+
+    /// Spawns the content-addressed entity for `name`.
+    ///
+    /// The entity gets [`content_index`]`(name)` as its index. If an entity
+    /// by that name was spawned and destroyed before, it comes back with the
+    /// next generation, so old references to it stay stale.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entity by that name (or one whose name hashes
+    /// to the same index) is already alive.
+    pub fn spawn_content(&mut self, name: &str) -> Result<EntityId> {
+        let index = content_index(name);
+        let generation = match self.content.get(&index) {
+            Some(&current) if current % 2 == 1 => {
+                return Err(Error::new(ErrorKind::Refused(format!(
+                    "entity `{name}` already exists as {}",
+                    EntityId::new(index, current)
+                ))));
+            }
+            Some(&current) => current + 1,
+            None => 1,
+        };
+        self.content.insert(index, generation);
+        self.live_count += 1;
+        Ok(EntityId::new(index, generation))
+    }
+
     /// Spawns an entity with a specific ID.
     ///
     /// This is used when the entity ID was pre-determined (e.g., during VM execution
-    /// with read-your-writes semantics). The generations array is extended if needed.
+    /// with read-your-writes semantics). An index just past the dense range
+    /// extends it; one further out, like the VM's temporary IDs, gets a
+    /// sparse slot, so it doesn't allocate every index below it.
     ///
     /// # Panics
     ///
     /// Panics if the index is already occupied by a live entity with a different generation.
     pub fn spawn_with_id(&mut self, id: EntityId) -> EntityId {
+        if self.has_sparse_slot(id.index) && id.index != self.generations.len() as u64 {
+            let current_gen = self.content.get(&id.index).copied().unwrap_or(0);
+            assert!(
+                current_gen % 2 == 0,
+                "spawn_with_id: index {} already occupied with generation {}",
+                id.index,
+                current_gen
+            );
+            self.content.insert(id.index, id.generation);
+            self.live_count += 1;
+            return id;
+        }
+
         let idx = id.index as usize;
 
-        // Ensure the generations array is large enough
-        while self.generations.len() <= idx {
-            // Fill with 0 (even = dead/free)
+        // Extend the generations array to the new index
+        if self.generations.len() == idx {
             self.generations.push(0);
         }
 
@@ -189,6 +424,62 @@ impl EntityStore {
         id
     }
 
+    /// Brings back an entity at exactly `id`, as when loading a saved world.
+    ///
+    /// Unlike [`spawn_with_id`](Self::spawn_with_id), a dense index past the
+    /// end extends the dense range, leaving the slots in between free.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `id` has a free (even) generation, or its index
+    /// is already alive.
+    pub fn restore(&mut self, id: EntityId) -> Result<EntityId> {
+        if id.generation % 2 == 0 {
+            return Err(Error::new(ErrorKind::Refused(format!(
+                "can't restore {id}: generation {} marks a free slot",
+                id.generation
+            ))));
+        }
+        if let Some(current) = self.generation(id.index).filter(|g| g % 2 == 1) {
+            return Err(Error::new(ErrorKind::Refused(format!(
+                "can't restore {id}: {} is alive",
+                EntityId::new(id.index, current)
+            ))));
+        }
+        if is_content_index(id.index) {
+            self.content.insert(id.index, id.generation);
+        } else {
+            let idx = id.index as usize;
+            while self.generations.len() <= idx {
+                if self.policy.recycling == Recycling::FreeList {
+                    self.free_list.push(self.generations.len() as u64);
+                }
+                self.generations.push(0);
+            }
+            self.free_list.retain(|&index| index != id.index);
+            self.generations[idx] = id.generation;
+        }
+        self.live_count += 1;
+        Ok(id)
+    }
+
+    /// Counts live, dead, reusable, and retired slots.
+    #[must_use]
+    pub fn stats(&self) -> EntityStats {
+        EntityStats {
+            live: self.live_count,
+            dead: self.generations.len() + self.content.len() - self.live_count,
+            reusable: self.free_list.len(),
+            retired: self
+                .generations
+                .iter()
+                .chain(self.content.values())
+                .filter(|&&generation| generation == RETIRED_GENERATION)
+                .count(),
+            highest_index: self.generations.len().checked_sub(1).map(|i| i as u64),
+        }
+    }
+
     /// Returns the current highest allocated index.
     ///
     /// Returns 0 if no entities have been allocated.
@@ -367,6 +658,119 @@ mod tests {
         let result = store.validate(fake);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn append_only_never_reuses_indices() {
+        let mut store = EntityStore::new();
+        store.set_policy(EntityPolicy::append_only());
+
+        let e1 = store.spawn();
+        store.destroy(e1).unwrap();
+        let e2 = store.spawn();
+        assert_eq!(e2.index, 1);
+
+        let stats = store.stats();
+        assert_eq!(stats.live, 1);
+        assert_eq!(stats.dead, 1);
+        assert_eq!(stats.reusable, 0);
+        assert_eq!(stats.highest_index, Some(1));
+
+        // Going back to a free list makes the dead slot reusable
+        store.set_policy(EntityPolicy::default());
+        assert_eq!(store.stats().reusable, 1);
+        assert_eq!(store.spawn(), EntityId::new(0, 3));
+    }
+
+    #[test]
+    fn exhausted_generations_retire_or_wrap() {
+        let mut store = EntityStore::new();
+        let e = store.spawn();
+        store.generations[0] = RETIRED_GENERATION - 1;
+        store
+            .destroy(EntityId::new(0, RETIRED_GENERATION - 1))
+            .unwrap();
+        assert!(!store.exists(e));
+
+        // Retired: the slot is never handed out again
+        let stats = store.stats();
+        assert_eq!((stats.retired, stats.reusable, stats.dead), (1, 0, 1));
+        assert_eq!(store.spawn().index, 1);
+
+        // Wrapped: the slot starts over
+        let mut store = EntityStore::new();
+        store.set_policy(EntityPolicy::default().with_overflow(GenerationOverflow::Wrap));
+        store.spawn();
+        store.generations[0] = RETIRED_GENERATION - 1;
+        store
+            .destroy(EntityId::new(0, RETIRED_GENERATION - 1))
+            .unwrap();
+        assert_eq!(store.stats().retired, 0);
+        assert_eq!(store.spawn(), EntityId::new(0, 1));
+    }
+
+    #[test]
+    fn content_entities_have_stable_ids() {
+        let mut store = EntityStore::new();
+        let hero = store.spawn_content("hero").unwrap();
+        assert_eq!(hero, EntityId::new(content_index("hero"), 1));
+        assert!(is_content_index(hero.index));
+        assert!(store.spawn_content("hero").is_err());
+
+        // Dense spawns don't disturb content IDs, and vice versa
+        let mut other = EntityStore::new();
+        let first = other.spawn();
+        assert_eq!(other.spawn_content("hero").unwrap(), hero);
+        assert_eq!(other.spawn(), EntityId::new(first.index + 1, 1));
+        assert_eq!(other.len(), 3);
+        assert_eq!(other.iter().last(), Some(hero));
+
+        // Respawning after destruction moves to the next generation
+        store.destroy(hero).unwrap();
+        assert!(!store.exists(hero));
+        assert_eq!(store.stats().dead, 1);
+        let again = store.spawn_content("hero").unwrap();
+        assert_eq!(again, EntityId::new(hero.index, 3));
+        assert!(store.validate(hero).is_err());
+        assert_eq!(store.content_slots().collect::<Vec<_>>(), [(hero.index, 3)]);
+    }
+
+    #[test]
+    fn spawn_with_distant_id_stays_sparse() {
+        let mut store = EntityStore::new();
+        let near = store.spawn_with_id(EntityId::new(0, 1));
+        let far = store.spawn_with_id(EntityId::new(1_000_000_000, 1));
+        assert_eq!(store.generations().len(), 1);
+        assert!(store.exists(near) && store.exists(far));
+        assert_eq!(store.iter().collect::<Vec<_>>(), [near, far]);
+
+        store.destroy(far).unwrap();
+        assert!(!store.exists(far));
+        assert_eq!(store.spawn(), EntityId::new(1, 1));
+    }
+
+    #[test]
+    fn restore_keeps_saved_ids() {
+        let mut store = EntityStore::new();
+        let lamp = store.restore(EntityId::new(2, 3)).unwrap();
+        let hero = store
+            .restore(EntityId::new(content_index("hero"), 1))
+            .unwrap();
+        assert_eq!(store.iter().collect::<Vec<_>>(), [lamp, hero]);
+        assert!(store.restore(lamp).is_err());
+        assert!(store.restore(EntityId::new(4, 2)).is_err());
+
+        // The slots skipped over are free for new spawns
+        assert_eq!(store.stats().reusable, 2);
+        let mut spawned = [store.spawn().index, store.spawn().index];
+        spawned.sort_unstable();
+        assert_eq!(spawned, [0, 1]);
+        assert_eq!(store.spawn(), EntityId::new(3, 1));
+    }
+
+    #[test]
+    fn stats_of_empty_store() {
+        assert_eq!(EntityStore::new().stats(), EntityStats::default());
+    }
 }
 
 #[cfg(test)]
@@ -5,6 +5,8 @@
 //! - [`ComponentStore`] - Archetype-based component storage with schema validation
 //! - [`RelationshipStore`] - Bidirectional relationship indices for O(1) traversal
 //! - [`World`] - Immutable world state with structural sharing via persistent data structures
+//! - [`define_components!`] - Typed Rust structs for reading and writing components
+//! - `testing` - Proptest strategies for arbitrary worlds (with the `test-support` feature)
 //!
 //! All storage types are designed for immutable use - mutation methods return new instances
 //! that share structure with the original via `Arc` and the `im` crate.
@@ -19,15 +21,34 @@
 
 pub mod component;
 pub mod entity;
+pub mod memory;
+pub mod path;
 pub mod relationship;
 pub mod schema;
+pub mod spatial;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
+pub mod transaction;
+pub mod typed;
+pub mod validation;
 pub mod world;
 
 // Re-export primary types at crate root
 pub use component::{Archetype, ComponentStore};
-pub use entity::EntityStore;
-pub use relationship::RelationshipStore;
+pub use entity::{
+    CONTENT_INDEX_BASE, EntityPolicy, EntityStats, EntityStore, GenerationOverflow,
+    RETIRED_GENERATION, Recycling, content_index, is_content_index,
+};
+pub use memory::{ArchetypeCount, MemoryStats, StoreSharing};
+pub use path::PathStep;
+pub use relationship::{
+    FanOut, LookupCounts, LookupLog, RelationshipStats, RelationshipStore, count_lookups,
+};
 pub use schema::{
     Cardinality, ComponentSchema, FieldSchema, OnDelete, OnViolation, RelationshipSchema, Storage,
 };
-pub use world::World;
+pub use spatial::Point;
+pub use transaction::Transaction;
+pub use typed::{FieldValue, TypedComponent};
+pub use validation::{ValidationIssue, ValidationReport};
+pub use world::{CascadeStep, World};
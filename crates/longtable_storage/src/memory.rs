@@ -0,0 +1,104 @@
+//! Memory statistics for worlds.
+//!
+//! Every change to a [`World`](crate::World) returns a new world that keeps
+//! the old one as its `previous`, so a world retains its whole history. A
+//! change copies the store it touches and shares the others with the world
+//! before it, so what that history costs depends on how many distinct copies
+//! of each store it holds. [`MemoryStats`] reports those counts alongside
+//! what the world itself holds.
+//!
+//! The figures are counts, not bytes: component values are persistent maps
+//! that share structure between copies, so bytes per copy would overstate
+//! what is actually allocated.
+
+use longtable_foundation::{Interner, KeywordId};
+
+/// How many entities have one combination of components.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchetypeCount {
+    /// The components, sorted by keyword.
+    pub components: Vec<KeywordId>,
+    /// Live entities with exactly those components.
+    pub entities: usize,
+}
+
+/// How one store is shared across a world and its history.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StoreSharing {
+    /// Distinct copies of the store.
+    pub copies: usize,
+    /// Worlds that share a copy with a newer world instead of holding their
+    /// own.
+    pub shared: usize,
+}
+
+/// Memory statistics for a world and the history it retains.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// Live entities.
+    pub entities: usize,
+    /// Live entities by archetype, most common first.
+    pub archetypes: Vec<ArchetypeCount>,
+    /// Component values stored in this world.
+    pub component_values: usize,
+    /// Earlier worlds retained through `previous`.
+    pub history: usize,
+    /// Sharing of the entity store.
+    pub entity_store: StoreSharing,
+    /// Sharing of the component store.
+    pub component_store: StoreSharing,
+    /// Sharing of the relationship store.
+    pub relationship_store: StoreSharing,
+    /// Sharing of the interner.
+    pub interner_store: StoreSharing,
+    /// Component values across every distinct copy of the component store.
+    pub retained_component_values: usize,
+    /// Interned strings (symbols and keywords).
+    pub interned_strings: usize,
+    /// Total length of the interned strings, in bytes.
+    pub interned_bytes: usize,
+}
+
+impl MemoryStats {
+    /// Formats the statistics as lines of text.
+    #[must_use]
+    pub fn describe(&self, interner: &Interner) -> Vec<String> {
+        let sharing = |name: &str, store: StoreSharing| {
+            format!("  {name}: {} copies, {} shared", store.copies, store.shared)
+        };
+        let mut lines = vec![
+            format!(
+                "{} entities, {} component values, {} archetypes",
+                self.entities,
+                self.component_values,
+                self.archetypes.len()
+            ),
+            format!(
+                "history: {} worlds retaining {} component values",
+                self.history, self.retained_component_values
+            ),
+            sharing("entities", self.entity_store),
+            sharing("components", self.component_store),
+            sharing("relationships", self.relationship_store),
+            sharing("interner", self.interner_store),
+            format!(
+                "interner: {} strings, {} bytes",
+                self.interned_strings, self.interned_bytes
+            ),
+        ];
+        for archetype in &self.archetypes {
+            let names: Vec<String> = archetype
+                .components
+                .iter()
+                .map(|&k| format!(":{}", interner.get_keyword(k).unwrap_or("?")))
+                .collect();
+            let names = if names.is_empty() {
+                "(no components)".to_string()
+            } else {
+                names.join(" ")
+            };
+            lines.push(format!("  {:>6}  {names}", archetype.entities));
+        }
+        lines
+    }
+}
@@ -0,0 +1,253 @@
+//! Shortest paths over relationships.
+//!
+//! [`World::find_path`] treats entities as nodes and relationships as edges,
+//! for questions like "which way to the cellar?":
+//!
+//! ```text
+//! (find-path hall cellar :via :exit)                  ;; [[:exit/north door] ...]
+//! (find-path hall cellar :via [:exit :door/leads-to])
+//! (find-path camp peak :via :trail :cost :terrain/cost)
+//! ```
+//!
+//! `:via` names the relationships to follow. A name without a namespace also
+//! covers the relationships in its namespace, so `:exit` follows
+//! `:exit/north`, `:exit/up`, and the rest. Without a cost, the path with the
+//! fewest steps wins; with one, the cheapest, where entering an entity costs
+//! the `:value` of its cost component, or 1 if it has none.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+
+use longtable_foundation::{EntityId, KeywordId, Value};
+
+use crate::world::World;
+
+/// One step of a path: the relationship followed, and the entity reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PathStep {
+    /// The relationship followed.
+    pub relationship: KeywordId,
+    /// The entity reached.
+    pub entity: EntityId,
+}
+
+/// A path's cost so far, ordered for the search frontier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Cost(f64);
+
+impl Eq for Cost {}
+
+impl PartialOrd for Cost {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cost {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl World {
+    /// Finds a path from `from` to `to` along the relationships `via` names.
+    ///
+    /// Returns the steps after `from`, so a path from an entity to itself is
+    /// empty, or `None` if `to` can't be reached. Ties between equally good
+    /// paths are broken the same way every time.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn find_path(
+        &self,
+        from: EntityId,
+        to: EntityId,
+        via: &[KeywordId],
+        cost: Option<KeywordId>,
+    ) -> Option<Vec<PathStep>> {
+        let edges = self.path_edges(via);
+        let step_cost = |entity: EntityId| match cost.and_then(|cost| {
+            self.get_field(entity, cost, KeywordId::VALUE)
+                .ok()
+                .flatten()
+        }) {
+            Some(Value::Int(n)) => n.max(0) as f64,
+            Some(Value::Float(x)) => x.max(0.0),
+            _ => 1.0,
+        };
+
+        // Uniform-cost search; with every step costing 1, this is a
+        // breadth-first search
+        let mut best: HashMap<EntityId, f64> = HashMap::from([(from, 0.0)]);
+        let mut came_from: HashMap<EntityId, (EntityId, PathStep)> = HashMap::new();
+        // Entities queued, indexed by the order they were queued in
+        let mut queued = vec![from];
+        let mut frontier = BinaryHeap::from([(Reverse(Cost(0.0)), Reverse(0_usize))]);
+        while let Some((Reverse(Cost(spent)), Reverse(order))) = frontier.pop() {
+            let entity = queued[order];
+            if entity == to {
+                break;
+            }
+            if best.get(&entity).is_some_and(|&b| spent > b) {
+                continue;
+            }
+            for &(relationship, next) in edges.get(&entity).into_iter().flatten() {
+                let total = spent + step_cost(next);
+                if best.get(&next).is_some_and(|&b| total >= b) {
+                    continue;
+                }
+                best.insert(next, total);
+                came_from.insert(
+                    next,
+                    (
+                        entity,
+                        PathStep {
+                            relationship,
+                            entity: next,
+                        },
+                    ),
+                );
+                frontier.push((Reverse(Cost(total)), Reverse(queued.len())));
+                queued.push(next);
+            }
+        }
+
+        if !best.contains_key(&to) {
+            return None;
+        }
+        let mut steps = Vec::new();
+        let mut at = to;
+        while at != from {
+            let (previous, step) = came_from[&at];
+            steps.push(step);
+            at = previous;
+        }
+        steps.reverse();
+        Some(steps)
+    }
+
+    /// Returns the edges out of each entity along the relationships `via`
+    /// names, in the order they were linked.
+    fn path_edges(&self, via: &[KeywordId]) -> HashMap<EntityId, Vec<(KeywordId, EntityId)>> {
+        let interner = self.interner();
+        let names: Vec<&str> = via
+            .iter()
+            .filter_map(|&kw| interner.get_keyword(kw))
+            .collect();
+        let follows = |relationship: KeywordId| {
+            via.contains(&relationship)
+                || interner.get_keyword(relationship).is_some_and(|name| {
+                    names.iter().any(|via| {
+                        !via.contains('/')
+                            && name
+                                .strip_prefix(via)
+                                .is_some_and(|rest| rest.starts_with('/'))
+                    })
+                })
+        };
+        let field = |rel: EntityId, component: KeywordId| {
+            self.get_field(rel, component, KeywordId::VALUE)
+                .ok()
+                .flatten()
+        };
+
+        let mut relationships = self.find_relationships(None, None, None);
+        relationships.sort_unstable_by_key(|rel| (rel.index, rel.generation));
+        let mut edges: HashMap<EntityId, Vec<(KeywordId, EntityId)>> = HashMap::new();
+        for rel in relationships {
+            let (
+                Some(Value::Keyword(relationship)),
+                Some(Value::EntityRef(source)),
+                Some(Value::EntityRef(target)),
+            ) = (
+                field(rel, KeywordId::REL_TYPE),
+                field(rel, KeywordId::REL_SOURCE),
+                field(rel, KeywordId::REL_TARGET),
+            )
+            else {
+                continue;
+            };
+            if follows(relationship) {
+                edges
+                    .entry(source)
+                    .or_default()
+                    .push((relationship, target));
+            }
+        }
+        edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{ComponentSchema, FieldSchema};
+    use longtable_foundation::{LtMap, Type};
+
+    #[test]
+    fn finds_shortest_and_cheapest_paths() {
+        let mut world = World::new(42);
+        let north = world.interner_mut().intern_keyword("exit/north");
+        let east = world.interner_mut().intern_keyword("exit/east");
+        let exit = world.interner_mut().intern_keyword("exit");
+        let exits_seen = world.interner_mut().intern_keyword("exits/seen");
+        let terrain = world.interner_mut().intern_keyword("terrain");
+        let mut world = world
+            .register_component(
+                ComponentSchema::new(terrain)
+                    .with_field(FieldSchema::required(KeywordId::VALUE, Type::Int)),
+            )
+            .unwrap();
+
+        // gate -north-> swamp -east-> keep, and gate -east-> road -east-> field -north-> keep
+        let mut spawn = |components: LtMap<Value, Value>| {
+            let (next, entity) = world.spawn(&components).unwrap();
+            world = next;
+            entity
+        };
+        let cost = |n| {
+            LtMap::new().insert(
+                Value::Keyword(terrain),
+                Value::Map(LtMap::new().insert(Value::Keyword(KeywordId::VALUE), Value::Int(n))),
+            )
+        };
+        let gate = spawn(LtMap::new());
+        let swamp = spawn(cost(10));
+        let keep = spawn(LtMap::new());
+        let road = spawn(LtMap::new());
+        let field = spawn(LtMap::new());
+        let nowhere = spawn(LtMap::new());
+        for (source, relationship, target) in [
+            (gate, north, swamp),
+            (swamp, east, keep),
+            (gate, east, road),
+            (road, east, field),
+            (field, north, keep),
+            (keep, exits_seen, nowhere),
+        ] {
+            world = world
+                .spawn_relationship(relationship, source, target)
+                .unwrap()
+                .0;
+        }
+
+        let step = |relationship, entity| PathStep {
+            relationship,
+            entity,
+        };
+        assert_eq!(
+            world.find_path(gate, keep, &[exit], None),
+            Some(vec![step(north, swamp), step(east, keep)])
+        );
+        assert_eq!(
+            world.find_path(gate, keep, &[exit], Some(terrain)),
+            Some(vec![step(east, road), step(east, field), step(north, keep)])
+        );
+        assert_eq!(
+            world.find_path(gate, keep, &[east], None),
+            None,
+            "only exit/east is followed"
+        );
+        assert_eq!(world.find_path(gate, gate, &[exit], None), Some(vec![]));
+        assert_eq!(world.find_path(gate, nowhere, &[exit], None), None);
+    }
+}
@@ -9,9 +9,11 @@
 //! indices are legacy and kept only for deserialization compatibility with
 //! older saved worlds. New code should use `World::find_relationships()`.
 
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
 
-use longtable_foundation::{EntityId, Error, ErrorKind, KeywordId, Result};
+use longtable_foundation::{EntityId, Error, ErrorKind, Interner, KeywordId, Result};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -68,12 +70,23 @@ impl RelationshipStore {
         Ok(())
     }
 
+    /// Removes the schema for a relationship type, keeping any existing
+    /// links. Returns the removed schema.
+    pub fn unregister_schema(&mut self, relationship: KeywordId) -> Option<RelationshipSchema> {
+        self.schemas.remove(&relationship)
+    }
+
     /// Gets the schema for a relationship type.
     #[must_use]
     pub fn schema(&self, relationship: KeywordId) -> Option<&RelationshipSchema> {
         self.schemas.get(&relationship)
     }
 
+    /// Iterates over all registered relationship schemas (in no particular order).
+    pub fn schemas(&self) -> impl Iterator<Item = &RelationshipSchema> {
+        self.schemas.values()
+    }
+
     /// Creates a relationship edge.
     ///
     /// Linking an existing edge is idempotent (no-op).
@@ -83,6 +96,7 @@ impl RelationshipStore {
     /// Returns an error if:
     /// - The relationship is not registered
     /// - Cardinality would be violated and `on_violation` is `Error`
+    /// - The relationship is acyclic and the edge would close a cycle
     #[allow(clippy::too_many_lines)]
     pub fn link(
         &mut self,
@@ -97,12 +111,26 @@ impl RelationshipStore {
         })?;
         let cardinality = schema.cardinality;
         let on_violation = schema.on_violation;
+        let acyclic = schema.acyclic;
 
         // Check for existing edge (idempotent)
         if self.has_edge(source, relationship, target) {
             return Ok(());
         }
 
+        if acyclic {
+            if let Some(path) =
+                cycle_path(source, target, |e| self.targets(e, relationship).collect())
+            {
+                return Err(cycle_error(
+                    &format!("{relationship:?}"),
+                    source,
+                    target,
+                    &path,
+                ));
+            }
+        }
+
         // Check cardinality constraints
         match cardinality {
             Cardinality::OneToOne => {
@@ -368,6 +396,256 @@ impl RelationshipStore {
     }
 }
 
+/// Finds the path by which `target` already reaches `source`, following
+/// `targets_of`, which linking `source -> target` would turn into a cycle.
+///
+/// Only the entities reachable from `target` are visited, so the cost of
+/// checking a link is bounded by the size of the hierarchy below it rather
+/// than the world. The path runs from `target` to `source` inclusive.
+pub(crate) fn cycle_path(
+    source: EntityId,
+    target: EntityId,
+    targets_of: impl Fn(EntityId) -> Vec<EntityId>,
+) -> Option<Vec<EntityId>> {
+    let mut parents: HashMap<EntityId, EntityId> = HashMap::new();
+    let mut visited: HashSet<EntityId> = HashSet::from([target]);
+    let mut frontier = vec![target];
+    while let Some(entity) = frontier.pop() {
+        if entity == source {
+            let mut path = vec![source];
+            let mut current = source;
+            while let Some(&parent) = parents.get(&current) {
+                path.push(parent);
+                current = parent;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in targets_of(entity) {
+            if visited.insert(next) {
+                parents.insert(next, entity);
+                frontier.push(next);
+            }
+        }
+    }
+    None
+}
+
+// =============================================================================
+// Statistics
+// =============================================================================
+
+/// Lookups of one relationship type.
+///
+/// Relationship edges have no index yet, so every lookup visits every edge
+/// in the world; `visited` against `matched` shows what an index would save.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LookupCounts {
+    /// Number of lookups.
+    pub lookups: u64,
+    /// Lookups that named a source or target entity.
+    pub narrowed: u64,
+    /// Edges visited, over all lookups.
+    pub visited: u64,
+    /// Edges found, over all lookups.
+    pub matched: u64,
+}
+
+impl LookupCounts {
+    /// Adds another set of counts to these.
+    pub fn add(&mut self, other: &Self) {
+        self.lookups += other.lookups;
+        self.narrowed += other.narrowed;
+        self.visited += other.visited;
+        self.matched += other.matched;
+    }
+
+    /// Returns the fraction of visited edges that were found, if any were
+    /// visited.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn selectivity(&self) -> Option<f64> {
+        (self.visited > 0).then(|| self.matched as f64 / self.visited as f64)
+    }
+}
+
+/// Relationship lookups by type, as collected by [`count_lookups`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LookupLog {
+    counts: HashMap<KeywordId, LookupCounts>,
+}
+
+impl LookupLog {
+    /// Returns the lookups recorded for `relationship`.
+    #[must_use]
+    pub fn get(&self, relationship: KeywordId) -> LookupCounts {
+        self.counts.get(&relationship).copied().unwrap_or_default()
+    }
+
+    /// Adds `counts` to those of `relationship`.
+    pub fn record(&mut self, relationship: KeywordId, counts: &LookupCounts) {
+        self.counts.entry(relationship).or_default().add(counts);
+    }
+
+    /// Adds every count in `other` to this log.
+    pub fn merge(&mut self, other: &Self) {
+        for (&relationship, counts) in &other.counts {
+            self.record(relationship, counts);
+        }
+    }
+
+    /// Returns true if nothing was recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Forgets everything recorded.
+    pub fn clear(&mut self) {
+        self.counts.clear();
+    }
+}
+
+thread_local! {
+    /// The log of the innermost [`count_lookups`] running on this thread.
+    static LOOKUPS: RefCell<Option<LookupLog>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` and returns the relationship lookups it made on this thread.
+///
+/// Lookups are only counted while a caller is collecting them, so worlds
+/// stay free of bookkeeping. Nested calls each see their own lookups, which
+/// are also counted by the calls around them.
+pub fn count_lookups<T>(f: impl FnOnce() -> T) -> (T, LookupLog) {
+    let outer = LOOKUPS.with(|lookups| lookups.replace(Some(LookupLog::default())));
+    let result = f();
+    let log = LOOKUPS
+        .with(|lookups| lookups.replace(outer))
+        .unwrap_or_default();
+    LOOKUPS.with(|lookups| {
+        if let Some(outer) = lookups.borrow_mut().as_mut() {
+            outer.merge(&log);
+        }
+    });
+    (result, log)
+}
+
+/// Records a lookup of `relationship` with whichever [`count_lookups`] is
+/// collecting on this thread, if any.
+pub(crate) fn record_lookup(relationship: KeywordId, counts: &LookupCounts) {
+    LOOKUPS.with(|lookups| {
+        if let Some(log) = lookups.borrow_mut().as_mut() {
+            log.record(relationship, counts);
+        }
+    });
+}
+
+/// Upper bounds of the fan-out histogram buckets: 1, 2-4, 5-16, 17-64, 65+.
+pub const FAN_OUT_BUCKETS: [usize; 5] = [1, 4, 16, 64, usize::MAX];
+
+/// How many targets each source of a relationship type has.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FanOut {
+    /// Total edges.
+    pub edges: usize,
+    /// Distinct sources with at least one edge.
+    pub sources: usize,
+    /// Most targets any one source has.
+    pub max: usize,
+    /// Number of sources per bucket of [`FAN_OUT_BUCKETS`].
+    pub histogram: [usize; FAN_OUT_BUCKETS.len()],
+}
+
+impl FanOut {
+    /// Builds the distribution from each source's number of targets.
+    pub fn from_counts(counts: impl IntoIterator<Item = usize>) -> Self {
+        let mut fan_out = Self::default();
+        for count in counts {
+            fan_out.edges += count;
+            fan_out.sources += 1;
+            fan_out.max = fan_out.max.max(count);
+            let bucket = FAN_OUT_BUCKETS
+                .iter()
+                .position(|&bound| count <= bound)
+                .unwrap_or(FAN_OUT_BUCKETS.len() - 1);
+            fan_out.histogram[bucket] += 1;
+        }
+        fan_out
+    }
+
+    /// Returns the mean number of targets per source.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mean(&self) -> f64 {
+        if self.sources == 0 {
+            0.0
+        } else {
+            self.edges as f64 / self.sources as f64
+        }
+    }
+}
+
+/// Lookup and fan-out statistics for one relationship type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelationshipStats {
+    /// The relationship type.
+    pub relationship: KeywordId,
+    /// Lookups recorded by the caller (see [`count_lookups`]).
+    pub lookups: LookupCounts,
+    /// Current fan-out distribution.
+    pub fan_out: FanOut,
+}
+
+impl RelationshipStats {
+    /// Formats the statistics as a single line.
+    #[must_use]
+    pub fn describe(&self, interner: &Interner) -> String {
+        let name = interner.get_keyword(self.relationship).unwrap_or("?");
+        let mut out = format!(
+            ":{name}: {} edges from {} sources (mean {:.1}, max {})",
+            self.fan_out.edges,
+            self.fan_out.sources,
+            self.fan_out.mean(),
+            self.fan_out.max
+        );
+        let labels = ["1", "2-4", "5-16", "17-64", "65+"];
+        let buckets: Vec<String> = labels
+            .iter()
+            .zip(self.fan_out.histogram)
+            .filter(|(_, count)| *count > 0)
+            .map(|(label, count)| format!("{label}:{count}"))
+            .collect();
+        if !buckets.is_empty() {
+            let _ = write!(out, " [{}]", buckets.join(" "));
+        }
+        let lookups = &self.lookups;
+        if lookups.lookups == 0 {
+            out.push_str("; no lookups");
+        } else {
+            let _ = write!(
+                out,
+                "; {} lookups ({} by endpoint) visited {} edges to find {}",
+                lookups.lookups, lookups.narrowed, lookups.visited, lookups.matched
+            );
+        }
+        out
+    }
+}
+
+/// Builds the error for a link rejected by an acyclic relationship.
+pub(crate) fn cycle_error(
+    relationship: &str,
+    source: EntityId,
+    target: EntityId,
+    path: &[EntityId],
+) -> Error {
+    let path: Vec<String> = path.iter().map(ToString::to_string).collect();
+    Error::new(ErrorKind::Cycle(format!(
+        "relationship cycle: linking {source} {relationship} {target} would put {source} inside itself ({})",
+        path.join(" -> ")
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -578,4 +856,66 @@ mod tests {
 
         assert!(victims.contains(&child));
     }
+
+    #[test]
+    fn acyclic_relationship_rejects_cycles() {
+        let (mut store, mut interner) = setup();
+        let contained_in = interner.intern_keyword("contained-in");
+
+        store
+            .register_schema(RelationshipSchema::new(contained_in).with_acyclic(true))
+            .unwrap();
+
+        let bag = EntityId::new(0, 1);
+        let box_ = EntityId::new(1, 1);
+        let chest = EntityId::new(2, 1);
+
+        store.link(bag, contained_in, box_).unwrap();
+        store.link(box_, contained_in, chest).unwrap();
+
+        let err = store.link(chest, contained_in, bag).unwrap_err();
+        assert!(err.to_string().contains("relationship cycle"));
+        assert!(store.link(bag, contained_in, bag).is_err());
+        assert!(!store.has_edge(chest, contained_in, bag));
+
+        // Siblings are not cycles
+        store
+            .link(chest, contained_in, EntityId::new(3, 1))
+            .unwrap();
+    }
+
+    #[test]
+    fn lookups_are_counted_only_while_collecting() {
+        let (_, mut interner) = setup();
+        let contains = interner.intern_keyword("contains");
+        let lookup = LookupCounts {
+            lookups: 1,
+            narrowed: 1,
+            visited: 4,
+            matched: 1,
+        };
+
+        record_lookup(contains, &lookup);
+        let ((), outer) = count_lookups(|| {
+            record_lookup(contains, &lookup);
+            let ((), inner) = count_lookups(|| record_lookup(contains, &lookup));
+            assert_eq!(inner.get(contains), lookup);
+        });
+
+        let counts = outer.get(contains);
+        assert_eq!(counts.lookups, 2);
+        assert_eq!(counts.visited, 8);
+        assert!((counts.selectivity().unwrap() - 0.25).abs() < 1e-9);
+        assert!(LookupCounts::default().selectivity().is_none());
+    }
+
+    #[test]
+    fn fan_out_buckets_sources() {
+        let fan_out = FanOut::from_counts([1, 1, 3, 20, 100]);
+        assert_eq!(fan_out.edges, 125);
+        assert_eq!(fan_out.sources, 5);
+        assert_eq!(fan_out.max, 100);
+        assert_eq!(fan_out.histogram, [2, 1, 0, 1, 1]);
+        assert!((fan_out.mean() - 25.0).abs() < f64::EPSILON);
+    }
 }
@@ -119,6 +119,10 @@ pub struct RelationshipSchema {
     pub on_violation: OnViolation,
     /// Attributes on the relationship edge (only for Entity storage).
     pub attributes: Vec<FieldSchema>,
+    /// If true, links that would close a cycle (an entity inside itself,
+    /// directly or transitively) are rejected.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub acyclic: bool,
 }
 
 impl RelationshipSchema {
@@ -132,6 +136,7 @@ impl RelationshipSchema {
             on_target_delete: OnDelete::Remove,
             on_violation: OnViolation::Error,
             attributes: Vec::new(),
+            acyclic: false,
         }
     }
 
@@ -163,6 +168,13 @@ impl RelationshipSchema {
         self
     }
 
+    /// Sets whether links that would form a cycle are rejected.
+    #[must_use]
+    pub fn with_acyclic(mut self, acyclic: bool) -> Self {
+        self.acyclic = acyclic;
+        self
+    }
+
     /// Adds an attribute to the relationship.
     #[must_use]
     pub fn with_attribute(mut self, attr: FieldSchema) -> Self {
@@ -203,7 +215,7 @@ pub enum OnDelete {
     Remove,
     /// Delete the source entity as well (cascade).
     Cascade,
-    /// Set the relationship to nil (only for Field storage).
+    /// Keep the link with a nil target, so the source can tell it lost it.
     Nullify,
 }
 
@@ -276,6 +288,8 @@ mod tests {
         assert_eq!(schema.storage, Storage::Field);
         assert_eq!(schema.cardinality, Cardinality::OneToMany);
         assert_eq!(schema.on_target_delete, OnDelete::Cascade);
+        assert!(!schema.acyclic);
+        assert!(schema.with_acyclic(true).acyclic);
     }
 
     #[test]
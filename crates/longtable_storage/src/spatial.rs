@@ -0,0 +1,334 @@
+//! Spatial indexing for proximity queries.
+//!
+//! A position is a component holding `{:x :y}` or `{:x :y :z}`, a `vec2` or
+//! `vec3`, or a vector of two or three numbers; a missing `z` is 0.
+//! [`World::entities_within`] finds the entities whose position lies within
+//! a radius of a point:
+//!
+//! ```text
+//! (spatial-index :position :cell-size 8.0)
+//! (entities-within player 5.0)                        ;; nearest first
+//! :where [[?a :position _] [?b :position _] [(within? ?a ?b 5.0)]]
+//! ```
+//!
+//! Without an index, a query checks every position. [`World::with_spatial_index`]
+//! buckets positions into a grid of cells `cell_size` across, so a query only
+//! visits the cells its radius reaches. The grid lives in the component store
+//! and moves an entity between cells as its position is written.
+
+use std::collections::HashMap;
+
+use longtable_foundation::{EntityId, Error, ErrorKind, Interner, KeywordId, Result, Value};
+
+use crate::world::World;
+
+/// A point in space.
+pub type Point = [f64; 3];
+
+/// A grid cell's coordinates.
+type Cell = [i64; 3];
+
+/// The positions of one component, bucketed by cell.
+#[derive(Clone, Debug)]
+pub(crate) struct SpatialGrid {
+    /// Width of each cell
+    cell_size: f64,
+    /// The axis each map key names
+    axes: HashMap<KeywordId, usize>,
+    /// Entities and their positions, by cell
+    cells: HashMap<Cell, HashMap<EntityId, Point>>,
+    /// The cell each entity is in
+    placed: HashMap<EntityId, Cell>,
+}
+
+impl SpatialGrid {
+    pub(crate) fn new(cell_size: f64, axes: HashMap<KeywordId, usize>) -> Self {
+        Self {
+            cell_size,
+            axes,
+            cells: HashMap::new(),
+            placed: HashMap::new(),
+        }
+    }
+
+    /// Moves an entity to the position `value` holds, or out of the grid if
+    /// it holds none.
+    pub(crate) fn place(&mut self, entity: EntityId, value: Option<&Value>) {
+        if let Some(cell) = self.placed.remove(&entity)
+            && let Some(occupants) = self.cells.get_mut(&cell)
+        {
+            occupants.remove(&entity);
+            if occupants.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+        let axes = &self.axes;
+        if let Some(point) = value.and_then(|value| point(value, |kw| axes.get(&kw).copied())) {
+            let cell = cell_of(point, self.cell_size);
+            self.cells.entry(cell).or_default().insert(entity, point);
+            self.placed.insert(entity, cell);
+        }
+    }
+
+    /// Calls `visit` with every position in the cells a sphere overlaps.
+    fn visit_near(&self, center: Point, radius: f64, mut visit: impl FnMut(EntityId, Point)) {
+        let low = cell_of(center.map(|c| c - radius), self.cell_size);
+        let high = cell_of(center.map(|c| c + radius), self.cell_size);
+        let span: i128 = (0..3)
+            .map(|axis| i128::from(high[axis]) - i128::from(low[axis]) + 1)
+            .product();
+
+        // A radius spanning more cells than are occupied visits those instead
+        if span > i128::try_from(self.cells.len()).unwrap_or(i128::MAX) {
+            for (&e, &p) in self.cells.values().flatten() {
+                visit(e, p);
+            }
+            return;
+        }
+        for x in low[0]..=high[0] {
+            for y in low[1]..=high[1] {
+                for z in low[2]..=high[2] {
+                    for (&e, &p) in self.cells.get(&[x, y, z]).into_iter().flatten() {
+                        visit(e, p);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn cell_of(point: Point, cell_size: f64) -> Cell {
+    point.map(|c| (c / cell_size).floor() as i64)
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f64>().sqrt()
+}
+
+/// Returns the axis (0 to 2) named `x`, `y`, or `z`.
+#[must_use]
+pub fn axis(name: &str) -> Option<usize> {
+    match name {
+        "x" => Some(0),
+        "y" => Some(1),
+        "z" => Some(2),
+        _ => None,
+    }
+}
+
+/// Returns the keywords naming each axis that `interner` has interned.
+pub(crate) fn axes(interner: &Interner) -> HashMap<KeywordId, usize> {
+    ["x", "y", "z"]
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, name)| Some((interner.lookup_keyword(name)?, i)))
+        .collect()
+}
+
+/// Reads a position: a map with `:x` and `:y` (and `:z`), a `vec2` or
+/// `vec3`, or a vector of two or three numbers. `axis_of` names the axis a
+/// map key stands for.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn point(value: &Value, axis_of: impl Fn(KeywordId) -> Option<usize>) -> Option<Point> {
+    let number = |value: &Value| match value {
+        Value::Int(n) => Some(*n as f64),
+        Value::Float(x) => Some(*x),
+        _ => None,
+    };
+    let mut point = [0.0; 3];
+    match value {
+        Value::Map(map) => {
+            let mut seen = [false; 3];
+            for (key, value) in map.iter() {
+                if let Value::Keyword(kw) = key
+                    && let Some(axis) = axis_of(*kw)
+                {
+                    point[axis] = number(value)?;
+                    seen[axis] = true;
+                }
+            }
+            (seen[0] && seen[1]).then_some(point)
+        }
+        Value::Vec2([x, y]) => Some([*x, *y, 0.0]),
+        Value::Vec3(v) => Some(*v),
+        Value::Vec(items) | Value::List(items) if (2..=3).contains(&items.len()) => {
+            for (axis, item) in items.iter().enumerate() {
+                point[axis] = number(item)?;
+            }
+            Some(point)
+        }
+        _ => None,
+    }
+}
+
+impl World {
+    /// Indexes `component` by position, in grid cells `cell_size` across.
+    ///
+    /// Pick a cell size near the radius you usually query with. Returns a
+    /// new World with the index.
+    pub fn with_spatial_index(&self, component: KeywordId, cell_size: f64) -> Result<World> {
+        if !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(Error::new(ErrorKind::InvalidArgument(format!(
+                "spatial index cell size must be positive, got {cell_size}"
+            ))));
+        }
+        // Positions written later may name axes that aren't interned yet
+        let mut world = self.clone();
+        for name in ["x", "y", "z"] {
+            world.interner_mut().intern_keyword(name);
+        }
+        let mut components = world.component_store().clone();
+        components.index_spatially(component, cell_size, axes(world.interner()));
+        Ok(world.with_component_store(components))
+    }
+
+    /// Returns `component`, or `:position` if it's `None` and interned.
+    #[must_use]
+    pub fn position_component(&self, component: Option<KeywordId>) -> Option<KeywordId> {
+        component.or_else(|| self.interner().lookup_keyword("position"))
+    }
+
+    /// Reads a position from an entity's `component`, or from a point value
+    /// (see [`point`]).
+    #[must_use]
+    pub fn position_of(&self, value: &Value, component: KeywordId) -> Option<Point> {
+        match value {
+            Value::EntityRef(entity) => self.position(*entity, component),
+            other => self.point(other),
+        }
+    }
+
+    /// Returns an entity's position, read from `component`.
+    #[must_use]
+    pub fn position(&self, entity: EntityId, component: KeywordId) -> Option<Point> {
+        let value = self.get(entity, component).ok().flatten()?;
+        self.point(&value)
+    }
+
+    /// Reads a position value (see [`point`]).
+    #[must_use]
+    pub fn point(&self, value: &Value) -> Option<Point> {
+        let interner = self.interner();
+        point(value, |kw| interner.get_keyword(kw).and_then(axis))
+    }
+
+    /// Returns the entities whose `component` position lies within `radius`
+    /// of `center`, nearest first.
+    ///
+    /// Ties are broken the same way every time. Uses the component's grid
+    /// if it has one (see [`World::with_spatial_index`]), and checks every
+    /// position otherwise.
+    #[must_use]
+    pub fn entities_within(
+        &self,
+        component: KeywordId,
+        center: Point,
+        radius: f64,
+    ) -> Vec<EntityId> {
+        let mut found: Vec<(f64, EntityId)> = Vec::new();
+        let mut visit = |entity, point| {
+            let d = distance(center, point);
+            if d <= radius {
+                found.push((d, entity));
+            }
+        };
+        match self.component_store().grid(component) {
+            Some(grid) => grid.visit_near(center, radius, visit),
+            None => {
+                for (entity, point) in self.positions(component) {
+                    visit(entity, point);
+                }
+            }
+        }
+        found.sort_unstable_by(|(a, x), (b, y)| {
+            a.total_cmp(b)
+                .then((x.index, x.generation).cmp(&(y.index, y.generation)))
+        });
+        found.into_iter().map(|(_, entity)| entity).collect()
+    }
+
+    /// Iterates the entities with a readable `component` position.
+    fn positions(&self, component: KeywordId) -> impl Iterator<Item = (EntityId, Point)> + '_ {
+        self.with_component(component)
+            .filter_map(move |entity| Some((entity, self.position(entity, component)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{ComponentSchema, FieldSchema};
+    use longtable_foundation::{LtMap, Type};
+
+    #[test]
+    fn finds_entities_within_a_radius() {
+        let mut world = World::new(42);
+        let position = world.interner_mut().intern_keyword("position");
+        let x = world.interner_mut().intern_keyword("x");
+        let y = world.interner_mut().intern_keyword("y");
+        let mut world = world
+            .register_component(
+                ComponentSchema::new(position)
+                    .with_field(FieldSchema::required(x, Type::Float))
+                    .with_field(FieldSchema::required(y, Type::Float)),
+            )
+            .unwrap();
+        let at = |px: f64, py: f64| {
+            LtMap::new().insert(
+                Value::Keyword(position),
+                Value::Map(
+                    LtMap::new()
+                        .insert(Value::Keyword(x), Value::Float(px))
+                        .insert(Value::Keyword(y), Value::Float(py)),
+                ),
+            )
+        };
+        let spawn = |world: &mut World, px, py| {
+            let (next, entity) = world.spawn(&at(px, py)).unwrap();
+            *world = next;
+            entity
+        };
+        let origin = spawn(&mut world, 0.0, 0.0);
+        let near = spawn(&mut world, 3.0, 4.0);
+        let far = spawn(&mut world, 30.0, -40.0);
+        let across = spawn(&mut world, -2.0, 0.0);
+
+        let scanned = world.entities_within(position, [0.0; 3], 5.0);
+        assert_eq!(scanned, vec![origin, across, near]);
+
+        let indexed = world.with_spatial_index(position, 2.0).unwrap();
+        assert_eq!(indexed.entities_within(position, [0.0; 3], 5.0), scanned);
+        assert_eq!(
+            indexed.entities_within(position, [0.0; 3], 1000.0),
+            vec![origin, across, near, far]
+        );
+
+        // Writing the component moves the entity within the grid
+        let moved = indexed
+            .set(
+                far,
+                position,
+                at(1.0, 0.0).get(&Value::Keyword(position)).unwrap().clone(),
+            )
+            .unwrap();
+        assert_eq!(
+            moved.entities_within(position, [0.0; 3], 1.5),
+            vec![origin, far]
+        );
+        assert_eq!(
+            indexed.entities_within(position, [0.0; 3], 1.5),
+            vec![origin]
+        );
+
+        // Destroying an entity takes it out of the grid
+        let destroyed = moved.destroy(far).unwrap();
+        assert_eq!(
+            destroyed.entities_within(position, [0.0; 3], 1.5),
+            vec![origin]
+        );
+
+        assert!(world.with_spatial_index(position, 0.0).is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! Arbitrary worlds for property tests.
+//!
+//! [`worlds`] turns the schemas registered on a template world into a
+//! proptest strategy. Each generated world has up to `max_entities` new
+//! entities, each carrying a random subset of the registered components with
+//! fields drawn from their declared types, linked by the registered
+//! relationships wherever cardinality and acyclicity allow:
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn saves_round_trip(world in worlds(&template, 20)) {
+//!         prop_assert_eq!(load(&save(&world)).content_hash(), world.content_hash());
+//!     }
+//! }
+//! ```
+//!
+//! Fields of function or sequence type can't be generated: optional ones are
+//! left out, and components that require one are never attached. Available
+//! with the `test-support` feature.
+
+use longtable_foundation::{EntityId, KeywordId, LtMap, SymbolId, Type, Value};
+use proptest::prelude::{BoxedStrategy, Just, Strategy, any, prop_oneof};
+use proptest::sample::select;
+
+use crate::schema::ComponentSchema;
+use crate::world::World;
+
+/// Components the world reserves for relationship entities.
+const RESERVED: [KeywordId; 3] = [
+    KeywordId::REL_TYPE,
+    KeywordId::REL_SOURCE,
+    KeywordId::REL_TARGET,
+];
+
+/// Values to draw keywords, symbols, and entity references from.
+struct Pool {
+    keywords: Vec<KeywordId>,
+    symbols: Vec<SymbolId>,
+    entities: Vec<EntityId>,
+}
+
+/// Generates worlds populated from `template`'s registered schemas.
+///
+/// Generated worlds pass [`World::validate`]. Links a cardinality or cycle
+/// check rejects are skipped, so they may have fewer edges than attempted.
+///
+/// # Panics
+///
+/// Panics while generating if a drawn value doesn't fit its schema, which
+/// would be a bug here.
+pub fn worlds(template: &World, max_entities: usize) -> BoxedStrategy<World> {
+    let template = template.clone();
+    (0..=max_entities)
+        .prop_flat_map(move |count| {
+            let mut world = template.clone();
+            let interner = world.interner_mut();
+            let names = ["alpha", "beta", "gamma"];
+            let keywords = names.map(|n| interner.intern_keyword(n)).to_vec();
+            let symbols = names.map(|n| interner.intern_symbol(n)).to_vec();
+            let (world, entities) = world
+                .spawn_batch(&vec![LtMap::new(); count])
+                .expect("spawning bare entities cannot fail");
+            let pool = Pool {
+                keywords,
+                symbols,
+                entities,
+            };
+
+            let components: Vec<_> = world
+                .component_schemas()
+                .filter(|schema| !RESERVED.contains(&schema.name))
+                .filter_map(|schema| {
+                    let name = schema.name;
+                    let value = component(schema, &pool)?;
+                    Some(proptest::option::of(value.prop_map(move |v| (name, v))).boxed())
+                })
+                .collect();
+
+            let rel_types: Vec<_> = world.relationship_schemas().map(|s| s.name).collect();
+            let links = if rel_types.is_empty() || count == 0 {
+                Just(Vec::new()).boxed()
+            } else {
+                let entity = select(pool.entities.clone());
+                proptest::collection::vec(
+                    (select(rel_types), entity.clone(), entity),
+                    0..=count * 2,
+                )
+                .boxed()
+            };
+
+            let entities = pool.entities;
+            (vec![components; count], links)
+                .prop_map(move |(components, links)| populate(&world, &entities, components, links))
+        })
+        .boxed()
+}
+
+/// Writes the generated components, then makes each link that's allowed.
+fn populate(
+    world: &World,
+    entities: &[EntityId],
+    components: Vec<Vec<Option<(KeywordId, Value)>>>,
+    links: Vec<(KeywordId, EntityId, EntityId)>,
+) -> World {
+    let writes: Vec<_> = entities
+        .iter()
+        .zip(components)
+        .flat_map(|(&entity, values)| {
+            values
+                .into_iter()
+                .flatten()
+                .map(move |(name, value)| (entity, name, value))
+        })
+        .collect();
+    let mut world = world
+        .set_batch(&writes)
+        .expect("generated components match their schemas");
+    for (rel_type, source, target) in links {
+        if let Ok((next, _)) = world.create_relationship(rel_type, source, target) {
+            world = next;
+        }
+    }
+    world
+}
+
+/// Generates a component's value, or `None` if a required field can't be.
+fn component(schema: &ComponentSchema, pool: &Pool) -> Option<BoxedStrategy<Value>> {
+    if schema.is_tag {
+        return Some(Just(Value::Bool(true)).boxed());
+    }
+    let mut fields = Vec::new();
+    for field in &schema.fields {
+        let name = field.name;
+        match value(&field.ty, pool) {
+            Some(value) if field.required => {
+                fields.push(value.prop_map(move |v| Some((name, v))).boxed());
+            }
+            Some(value) => fields.push(
+                proptest::option::of(value)
+                    .prop_map(move |v| v.map(|v| (name, v)))
+                    .boxed(),
+            ),
+            None if field.required => return None,
+            None => {}
+        }
+    }
+    Some(
+        fields
+            .prop_map(|fields| {
+                Value::Map(
+                    fields
+                        .into_iter()
+                        .flatten()
+                        .map(|(name, value)| (Value::Keyword(name), value))
+                        .collect(),
+                )
+            })
+            .boxed(),
+    )
+}
+
+/// Generates values `ty` accepts, or `None` if there are none to draw.
+fn value(ty: &Type, pool: &Pool) -> Option<BoxedStrategy<Value>> {
+    let float = || -1.0e6..1.0e6f64;
+    Some(match ty {
+        Type::Nil => Just(Value::Nil).boxed(),
+        Type::Bool => any::<bool>().prop_map(Value::Bool).boxed(),
+        Type::Int => any::<i64>().prop_map(Value::Int).boxed(),
+        Type::Float => float().prop_map(Value::Float).boxed(),
+        Type::String => "[a-z ]{0,12}".prop_map(|s| Value::from(s.as_str())).boxed(),
+        Type::Symbol => select(pool.symbols.clone()).prop_map(Value::Symbol).boxed(),
+        Type::Keyword => select(pool.keywords.clone())
+            .prop_map(Value::Keyword)
+            .boxed(),
+        Type::EntityRef if pool.entities.is_empty() => return None,
+        Type::EntityRef => select(pool.entities.clone())
+            .prop_map(Value::EntityRef)
+            .boxed(),
+        Type::Instant => any::<i64>().prop_map(Value::Instant).boxed(),
+        Type::Duration => (0..i64::MAX).prop_map(Value::Duration).boxed(),
+        Type::Vec2 => (float(), float())
+            .prop_map(|(x, y)| Value::Vec2([x, y]))
+            .boxed(),
+        Type::Vec3 => (float(), float(), float())
+            .prop_map(|(x, y, z)| Value::Vec3([x, y, z]))
+            .boxed(),
+        Type::Vec(element) => proptest::collection::vec(value(element, pool)?, 0..4)
+            .prop_map(|items| Value::Vec(items.into_iter().collect()))
+            .boxed(),
+        Type::Set(element) => proptest::collection::vec(value(element, pool)?, 0..4)
+            .prop_map(|items| Value::Set(items.into_iter().collect()))
+            .boxed(),
+        Type::Map(key, val) => {
+            proptest::collection::vec((value(key, pool)?, value(val, pool)?), 0..4)
+                .prop_map(|entries| Value::Map(entries.into_iter().collect()))
+                .boxed()
+        }
+        // A nil stands in when there's nothing of the inner type to draw
+        Type::Option(inner) => match value(inner, pool) {
+            Some(inner) => proptest::option::of(inner)
+                .prop_map(|v| v.unwrap_or(Value::Nil))
+                .boxed(),
+            None => Just(Value::Nil).boxed(),
+        },
+        Type::Any => prop_oneof![
+            Just(Value::Nil),
+            any::<bool>().prop_map(Value::Bool),
+            any::<i64>().prop_map(Value::Int),
+            "[a-z]{0,8}".prop_map(|s| Value::from(s.as_str())),
+        ]
+        .boxed(),
+        Type::Fn(_) | Type::Seq => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{Cardinality, FieldSchema, OnViolation, RelationshipSchema};
+    use longtable_foundation::Arity;
+    use proptest::prelude::*;
+
+    fn template() -> World {
+        let mut world = World::new(7);
+        let mut kw = |name: &str| world.interner_mut().intern_keyword(name);
+        let (health, current, max) = (kw("health"), kw("current"), kw("max"));
+        let (tags, owner, heading) = (kw("tags"), kw("owner"), kw("heading"));
+        let (script, player) = (kw("script"), kw("player"));
+        let (spouse, in_room, contains) = (kw("spouse"), kw("in-room"), kw("contains"));
+        world
+            .register_component(
+                ComponentSchema::new(health)
+                    .with_field(FieldSchema::required(current, Type::Int))
+                    .with_field(FieldSchema::optional(max, Type::Float, Value::Float(10.0)))
+                    .with_field(FieldSchema::optional_nil(tags, Type::set(Type::Keyword)))
+                    .with_field(FieldSchema::optional_nil(
+                        owner,
+                        Type::option(Type::EntityRef),
+                    ))
+                    .with_field(FieldSchema::optional_nil(heading, Type::Vec2)),
+            )
+            .and_then(|w| {
+                w.register_component(
+                    ComponentSchema::new(script)
+                        .with_field(FieldSchema::required(script, Type::Fn(Arity::Exact(0)))),
+                )
+            })
+            .and_then(|w| w.register_component(ComponentSchema::tag(player)))
+            .and_then(|w| {
+                w.register_relationship(
+                    RelationshipSchema::new(spouse).with_cardinality(Cardinality::OneToOne),
+                )
+            })
+            .and_then(|w| {
+                w.register_relationship(
+                    RelationshipSchema::new(in_room)
+                        .with_cardinality(Cardinality::ManyToOne)
+                        .with_on_violation(OnViolation::Replace),
+                )
+            })
+            .and_then(|w| {
+                w.register_relationship(RelationshipSchema::new(contains).with_acyclic(true))
+            })
+            .unwrap()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn generated_worlds_are_valid(world in worlds(&template(), 12)) {
+            let report = world.validate();
+            prop_assert!(report.is_valid(), "{}", report.format(world.interner()));
+
+            let script = world.interner().lookup_keyword("script").unwrap();
+            prop_assert_eq!(world.component_count(script), 0);
+        }
+    }
+}
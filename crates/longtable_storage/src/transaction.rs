@@ -0,0 +1,300 @@
+//! Atomic multi-step world edits.
+//!
+//! Each [`World`] operation returns a new world, so a sequence of edits that
+//! fails halfway leaves the caller holding whichever intermediate world it
+//! last assigned. [`World::transaction`] runs the edits against a working
+//! copy instead and hands back a new world only if all of them succeed:
+//!
+//! ```text
+//! let world = world.transaction(|tx| {
+//!     tx.set(door, open, Value::Bool(true));
+//!     tx.link(player, in_room, hallway);
+//! })?;
+//! ```
+//!
+//! Each edit is checked as it is made, as usual; once one fails, the rest
+//! are skipped. At commit the entities the transaction touched are checked
+//! once more (see [`World::validate_entities`]), which catches what no
+//! single edit could, such as a component left referring to an entity
+//! destroyed later in the same transaction. The committed world's
+//! [`previous`](World::previous) is the world the transaction started from,
+//! so the edits count as one step of history.
+
+use std::collections::HashSet;
+
+use longtable_foundation::{EntityId, Error, ErrorKind, KeywordId, LtMap, Result, Value};
+
+use crate::world::World;
+
+/// The working state of a [`World::transaction`].
+#[derive(Debug)]
+pub struct Transaction {
+    /// The world with every edit so far applied.
+    world: World,
+    /// The first edit that failed, if any.
+    error: Option<Error>,
+    /// Entities written by the edits, checked again at commit.
+    touched: HashSet<EntityId>,
+}
+
+impl Transaction {
+    fn new(world: World) -> Self {
+        Self {
+            world,
+            error: None,
+            touched: HashSet::new(),
+        }
+    }
+
+    /// Returns the world with every edit so far applied.
+    #[must_use]
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Returns the error that will abort the transaction, if an edit failed.
+    #[must_use]
+    pub fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    /// Aborts the transaction with `error`, unless it has already failed.
+    pub fn abort(&mut self, error: Error) {
+        self.error.get_or_insert(error);
+    }
+
+    /// Applies an arbitrary edit to the working world.
+    ///
+    /// Does nothing once the transaction has failed.
+    pub fn apply(&mut self, edit: impl FnOnce(&World) -> Result<World>) -> &mut Self {
+        if self.error.is_none() {
+            match edit(&self.world) {
+                Ok(world) => self.world = world,
+                Err(e) => self.error = Some(e),
+            }
+        }
+        self
+    }
+
+    /// Sets a component on an entity.
+    pub fn set(&mut self, entity: EntityId, component: KeywordId, value: Value) -> &mut Self {
+        self.touched.insert(entity);
+        self.apply(|world| world.set(entity, component, value))
+    }
+
+    /// Sets one field of a component.
+    pub fn set_field(
+        &mut self,
+        entity: EntityId,
+        component: KeywordId,
+        field: KeywordId,
+        value: Value,
+    ) -> &mut Self {
+        self.touched.insert(entity);
+        self.apply(|world| world.set_field(entity, component, field, value))
+    }
+
+    /// Removes a component from an entity.
+    pub fn remove_component(&mut self, entity: EntityId, component: KeywordId) -> &mut Self {
+        self.touched.insert(entity);
+        self.apply(|world| world.remove_component(entity, component))
+    }
+
+    /// Links `source` to `target`.
+    pub fn link(
+        &mut self,
+        source: EntityId,
+        relationship: KeywordId,
+        target: EntityId,
+    ) -> &mut Self {
+        self.touched.extend([source, target]);
+        self.apply(|world| world.link(source, relationship, target))
+    }
+
+    /// Unlinks `source` from `target`.
+    pub fn unlink(
+        &mut self,
+        source: EntityId,
+        relationship: KeywordId,
+        target: EntityId,
+    ) -> &mut Self {
+        self.touched.extend([source, target]);
+        self.apply(|world| world.unlink(source, relationship, target))
+    }
+
+    /// Destroys an entity, applying its links' delete policies.
+    pub fn destroy(&mut self, entity: EntityId) -> &mut Self {
+        self.apply(|world| world.destroy(entity))
+    }
+
+    /// Spawns an entity with `components`, returning its ID.
+    ///
+    /// Returns `None`, spawning nothing, once the transaction has failed.
+    pub fn spawn(&mut self, components: &LtMap<Value, Value>) -> Option<EntityId> {
+        let mut spawned = None;
+        self.apply(|world| {
+            let (world, id) = world.spawn(components)?;
+            spawned = Some(id);
+            Ok(world)
+        });
+        self.touched.extend(spawned);
+        spawned
+    }
+
+    /// Checks the touched entities and returns the working world.
+    fn commit(self, start: &World) -> Result<World> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if !self.touched.is_empty() {
+            let report = self.world.validate_entities(self.touched.iter().copied());
+            let issues: Vec<_> = report
+                .issues
+                .iter()
+                .map(|issue| issue.describe(self.world.interner()))
+                .collect();
+            if !issues.is_empty() {
+                return Err(Error::new(ErrorKind::Refused(format!(
+                    "transaction would leave the world inconsistent: {}",
+                    issues.join("; ")
+                ))));
+            }
+        }
+        Ok(self.world.descended_from(start))
+    }
+}
+
+impl World {
+    /// Runs `edits` against a copy of this world and returns the result if
+    /// every edit succeeded.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first edit's error, or an error describing what the
+    /// edits left inconsistent. This world is unchanged either way.
+    pub fn transaction(&self, edits: impl FnOnce(&mut Transaction)) -> Result<World> {
+        let mut tx = Transaction::new(self.clone());
+        edits(&mut tx);
+        tx.commit(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{Cardinality, ComponentSchema, FieldSchema, RelationshipSchema};
+    use longtable_foundation::Type;
+
+    struct Fixture {
+        world: World,
+        hp: KeywordId,
+        value: KeywordId,
+        holds: KeywordId,
+    }
+
+    fn fixture() -> Fixture {
+        let mut world = World::new(0);
+        let hp = world.interner_mut().intern_keyword("hp");
+        let value = world.interner_mut().intern_keyword("value");
+        let holds = world.interner_mut().intern_keyword("holds");
+        let world = world
+            .register_component(
+                ComponentSchema::new(hp).with_field(FieldSchema::required(value, Type::Int)),
+            )
+            .unwrap()
+            .register_relationship(
+                RelationshipSchema::new(holds).with_cardinality(Cardinality::ManyToOne),
+            )
+            .unwrap();
+        Fixture {
+            world,
+            hp,
+            value,
+            holds,
+        }
+    }
+
+    fn hp(value: KeywordId, n: i64) -> Value {
+        Value::Map(LtMap::new().insert(Value::Keyword(value), Value::Int(n)))
+    }
+
+    #[test]
+    fn commits_every_edit_as_one_step() {
+        let f = fixture();
+        let (start, hero) = f.world.spawn(&LtMap::new()).unwrap();
+        let (start, sword) = start.spawn(&LtMap::new()).unwrap();
+
+        let mut spawned = None;
+        let world = start
+            .transaction(|tx| {
+                tx.set(hero, f.hp, hp(f.value, 10))
+                    .link(hero, f.holds, sword)
+                    .set_field(hero, f.hp, f.value, Value::Int(7));
+                spawned = tx.spawn(&LtMap::new());
+            })
+            .unwrap();
+
+        assert_eq!(
+            world.get_field(hero, f.hp, f.value).unwrap(),
+            Some(Value::Int(7))
+        );
+        assert_eq!(world.targets(hero, f.holds).count(), 1);
+        assert!(world.exists(spawned.unwrap()));
+        assert_eq!(
+            world.previous().unwrap().content_hash(),
+            start.content_hash()
+        );
+    }
+
+    #[test]
+    fn a_failed_edit_changes_nothing() {
+        let f = fixture();
+        let (start, hero) = f.world.spawn(&LtMap::new()).unwrap();
+        let (start, sword) = start.spawn(&LtMap::new()).unwrap();
+        let (start, axe) = start.spawn(&LtMap::new()).unwrap();
+
+        let mut spawned = Some(hero);
+        let err = start
+            .transaction(|tx| {
+                tx.set(hero, f.hp, hp(f.value, 10))
+                    .link(hero, f.holds, sword)
+                    // Wrong type: fails, and the rest is skipped
+                    .set(axe, f.hp, Value::Int(3))
+                    .link(axe, f.holds, sword);
+                spawned = tx.spawn(&LtMap::new());
+                assert!(tx.error().is_some());
+            })
+            .unwrap_err();
+
+        assert!(matches!(err.kind, ErrorKind::TypeMismatch { .. }), "{err}");
+        assert_eq!(spawned, None);
+        assert!(!start.has(hero, f.hp));
+        assert_eq!(start.targets(hero, f.holds).count(), 0);
+    }
+
+    #[test]
+    fn commit_checks_the_touched_entities() {
+        let mut f = fixture();
+        let target = f.world.interner_mut().intern_keyword("target");
+        let aims = f.world.interner_mut().intern_keyword("aims");
+        let world = f
+            .world
+            .register_component(
+                ComponentSchema::new(aims)
+                    .with_field(FieldSchema::required(target, Type::EntityRef)),
+            )
+            .unwrap();
+        let (world, archer) = world.spawn(&LtMap::new()).unwrap();
+        let (world, dummy) = world.spawn(&LtMap::new()).unwrap();
+        let aim = Value::Map(LtMap::new().insert(Value::Keyword(target), Value::EntityRef(dummy)));
+
+        // Each edit is fine on its own, but together they leave a dangling reference
+        let err = world
+            .transaction(|tx| {
+                tx.set(archer, aims, aim).destroy(dummy);
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("inconsistent"), "{err}");
+        assert!(world.exists(dummy));
+    }
+}
@@ -0,0 +1,489 @@
+//! Typed Rust access to components.
+//!
+//! Component values are maps from field keywords to [`Value`]s, which suits
+//! the DSL but leaves Rust code looking up keywords and matching on `Value`
+//! for every read and write. [`define_components!`] generates a plain struct
+//! per component that converts to and from those maps:
+//!
+//! ```text
+//! define_components! {
+//!     /// Hit points.
+//!     pub struct Health = "health" {
+//!         current: i64,
+//!         max_hp: i64,
+//!     }
+//! }
+//!
+//! let world = Health::register(&world)?;
+//! let world = Health { current: 10, max_hp: 10 }.set(&world, hero)?;
+//! let world = Health::update(&world, hero, |h| h.current -= 3)?;
+//! assert_eq!(Health::get(&world, hero)?.unwrap().current, 7);
+//! ```
+//!
+//! Field names become keywords with underscores turned into dashes, so
+//! `max_hp` is `:max-hp`. Fields can be any [`FieldValue`]: the scalar
+//! types, [`EntityId`], [`KeywordId`], `Vec`s of those, a raw [`Value`], or
+//! an `Option` of any of them for a field that may be nil.
+
+use longtable_foundation::{EntityId, Error, ErrorKind, Interner, KeywordId, LtMap, Type};
+
+pub use longtable_foundation::{Result, Value};
+
+use crate::schema::{ComponentSchema, FieldSchema};
+use crate::world::World;
+
+/// A Rust type that can be stored in a component field.
+pub trait FieldValue: Sized {
+    /// True if the field may be nil, as for `Option`.
+    const OPTIONAL: bool = false;
+
+    /// Returns the type the field is declared with.
+    fn field_type() -> Type;
+
+    /// Converts a stored value, or returns `None` if it has the wrong type.
+    fn from_field(value: &Value) -> Option<Self>;
+
+    /// Converts to a value for storing.
+    fn into_field(self) -> Value;
+}
+
+macro_rules! field_value {
+    ($ty:ty, $field_type:expr, $variant:ident) => {
+        impl FieldValue for $ty {
+            fn field_type() -> Type {
+                $field_type
+            }
+
+            fn from_field(value: &Value) -> Option<Self> {
+                match value {
+                    Value::$variant(v) => Some(v.clone()),
+                    _ => None,
+                }
+            }
+
+            fn into_field(self) -> Value {
+                Value::$variant(self)
+            }
+        }
+    };
+}
+
+field_value!(bool, Type::Bool, Bool);
+field_value!(i64, Type::Int, Int);
+field_value!(f64, Type::Float, Float);
+field_value!(EntityId, Type::EntityRef, EntityRef);
+field_value!(KeywordId, Type::Keyword, Keyword);
+
+impl FieldValue for String {
+    fn field_type() -> Type {
+        Type::String
+    }
+
+    fn from_field(value: &Value) -> Option<Self> {
+        value.as_str().map(str::to_string)
+    }
+
+    fn into_field(self) -> Value {
+        Value::String(self.into())
+    }
+}
+
+impl FieldValue for Value {
+    fn field_type() -> Type {
+        Type::Any
+    }
+
+    fn from_field(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+
+    fn into_field(self) -> Value {
+        self
+    }
+}
+
+impl<T: FieldValue> FieldValue for Option<T> {
+    const OPTIONAL: bool = true;
+
+    fn field_type() -> Type {
+        Type::option(T::field_type())
+    }
+
+    fn from_field(value: &Value) -> Option<Self> {
+        match value {
+            Value::Nil => Some(None),
+            value => T::from_field(value).map(Some),
+        }
+    }
+
+    fn into_field(self) -> Value {
+        self.map_or(Value::Nil, T::into_field)
+    }
+}
+
+impl<T: FieldValue> FieldValue for Vec<T> {
+    fn field_type() -> Type {
+        Type::vec(T::field_type())
+    }
+
+    fn from_field(value: &Value) -> Option<Self> {
+        match value {
+            Value::Vec(items) => items.iter().map(T::from_field).collect(),
+            _ => None,
+        }
+    }
+
+    fn into_field(self) -> Value {
+        Value::Vec(self.into_iter().map(T::into_field).collect())
+    }
+}
+
+/// Returns the keyword name of a Rust field name.
+fn field_name(field: &str) -> String {
+    field.replace('_', "-")
+}
+
+/// Reads the fields of a component value, for [`TypedComponent::read`].
+#[derive(Debug)]
+pub struct FieldReader<'a> {
+    component: &'static str,
+    fields: &'a LtMap<Value, Value>,
+    interner: &'a Interner,
+}
+
+impl FieldReader<'_> {
+    /// Reads the field declared as `field` in Rust.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required field is missing or has the wrong type.
+    pub fn read<T: FieldValue>(&self, field: &str) -> Result<T> {
+        let name = field_name(field);
+        let value = self
+            .interner
+            .lookup_keyword(&name)
+            .and_then(|k| self.fields.get(&Value::Keyword(k)))
+            .unwrap_or(&Value::Nil);
+        if value.is_nil() && !T::OPTIONAL {
+            return Err(Error::new(ErrorKind::AttributeNotFound {
+                component: self.component.to_string(),
+                attribute: name,
+            }));
+        }
+        T::from_field(value)
+            .ok_or_else(|| Error::type_mismatch(T::field_type(), value.value_type()))
+    }
+}
+
+/// Writes the fields of a component value, for [`TypedComponent::write`].
+#[derive(Debug)]
+pub struct FieldWriter<'a> {
+    fields: LtMap<Value, Value>,
+    interner: &'a Interner,
+}
+
+impl FieldWriter<'_> {
+    /// Writes the field declared as `field` in Rust.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the field's keyword was never interned, so no
+    /// schema can declare it.
+    pub fn write<T: FieldValue>(&mut self, field: &str, value: T) -> Result<()> {
+        let name = field_name(field);
+        let key = self
+            .interner
+            .lookup_keyword(&name)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound(format!("unknown field :{name}"))))?;
+        self.fields = self.fields.insert(Value::Keyword(key), value.into_field());
+        Ok(())
+    }
+}
+
+/// Declares the fields of a component's schema, for
+/// [`TypedComponent::declare`].
+#[derive(Debug)]
+pub struct FieldDeclarer<'a> {
+    schema: ComponentSchema,
+    interner: &'a mut Interner,
+}
+
+impl FieldDeclarer<'_> {
+    /// Declares the field named `field` in Rust, as required unless its type
+    /// is an `Option`.
+    pub fn declare<T: FieldValue>(&mut self, field: &str) {
+        let name = self.interner.intern_keyword(&field_name(field));
+        let field = if T::OPTIONAL {
+            FieldSchema::optional_nil(name, T::field_type())
+        } else {
+            FieldSchema::required(name, T::field_type())
+        };
+        self.schema.fields.push(field);
+    }
+}
+
+/// A Rust struct mirroring a component, generated by
+/// [`define_components!`].
+pub trait TypedComponent: Sized {
+    /// The component's name, without the leading colon.
+    const NAME: &'static str;
+
+    /// Builds the struct from a reader over the component's fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field is missing or has the wrong type.
+    fn read(fields: &FieldReader<'_>) -> Result<Self>;
+
+    /// Writes the struct's fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field's keyword is unknown.
+    fn write(&self, fields: &mut FieldWriter<'_>) -> Result<()>;
+
+    /// Declares the struct's fields in the component's schema.
+    fn declare(fields: &mut FieldDeclarer<'_>);
+
+    /// Returns the component's keyword in `world`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the component was never registered.
+    fn keyword(world: &World) -> Result<KeywordId> {
+        world
+            .interner()
+            .lookup_keyword(Self::NAME)
+            .filter(|&k| world.component_schema(k).is_some())
+            .ok_or_else(|| {
+                Error::new(ErrorKind::NotFound(format!(
+                    "unknown component :{}",
+                    Self::NAME
+                )))
+            })
+    }
+
+    /// Returns the schema for the component, interning its keywords.
+    fn schema(interner: &mut Interner) -> ComponentSchema {
+        let name = interner.intern_keyword(Self::NAME);
+        let mut fields = FieldDeclarer {
+            schema: ComponentSchema::new(name),
+            interner,
+        };
+        Self::declare(&mut fields);
+        fields.schema
+    }
+
+    /// Registers the component's schema with `world`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the schema can't be registered.
+    fn register(world: &World) -> Result<World> {
+        let mut world = world.clone();
+        let schema = Self::schema(world.interner_mut());
+        world.register_component(schema)
+    }
+
+    /// Converts a stored component value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a map, or a field is missing or
+    /// has the wrong type.
+    fn from_value(value: &Value, interner: &Interner) -> Result<Self> {
+        let Value::Map(fields) = value else {
+            return Err(Error::type_mismatch(
+                Type::map(Type::Keyword, Type::Any),
+                value.value_type(),
+            ));
+        };
+        Self::read(&FieldReader {
+            component: Self::NAME,
+            fields,
+            interner,
+        })
+    }
+
+    /// Converts to a component value for storing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field's keyword is unknown.
+    fn to_value(&self, interner: &Interner) -> Result<Value> {
+        let mut fields = FieldWriter {
+            fields: LtMap::new(),
+            interner,
+        };
+        self.write(&mut fields)?;
+        Ok(Value::Map(fields.fields))
+    }
+
+    /// Returns the component on `entity`, if it has it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the component is unknown or the entity doesn't
+    /// exist.
+    fn get(world: &World, entity: EntityId) -> Result<Option<Self>> {
+        world
+            .get(entity, Self::keyword(world)?)?
+            .map(|value| Self::from_value(&value, world.interner()))
+            .transpose()
+    }
+
+    /// Sets the component on `entity`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the component is unknown, the entity doesn't
+    /// exist, or the value doesn't match the schema.
+    fn set(&self, world: &World, entity: EntityId) -> Result<World> {
+        let value = self.to_value(world.interner())?;
+        world.set(entity, Self::keyword(world)?, value)
+    }
+
+    /// Changes the component on `entity` with `change`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entity doesn't have the component, or as for
+    /// [`Self::set`].
+    fn update(world: &World, entity: EntityId, change: impl FnOnce(&mut Self)) -> Result<World> {
+        let mut component = Self::get(world, entity)?.ok_or_else(|| {
+            Error::new(ErrorKind::ComponentNotFound {
+                entity,
+                component: Self::NAME.to_string(),
+            })
+        })?;
+        change(&mut component);
+        component.set(world, entity)
+    }
+}
+
+/// Defines Rust structs mirroring components, implementing
+/// [`TypedComponent`](crate::typed::TypedComponent) for each.
+///
+/// Each struct names the component it mirrors; see the
+/// [module docs](crate::typed) for an example.
+#[macro_export]
+macro_rules! define_components {
+    ($(
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident = $component:literal {
+            $(
+                $(#[$field_meta:meta])*
+                $field:ident : $ty:ty
+            ),* $(,)?
+        }
+    )*) => {$(
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq)]
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                pub $field: $ty,
+            )*
+        }
+
+        impl $crate::typed::TypedComponent for $name {
+            const NAME: &'static str = $component;
+
+            fn read(fields: &$crate::typed::FieldReader<'_>) -> $crate::typed::Result<Self> {
+                Ok(Self {
+                    $($field: fields.read::<$ty>(stringify!($field))?,)*
+                })
+            }
+
+            fn write(
+                &self,
+                fields: &mut $crate::typed::FieldWriter<'_>,
+            ) -> $crate::typed::Result<()> {
+                $(fields.write(stringify!($field), self.$field.clone())?;)*
+                Ok(())
+            }
+
+            fn declare(fields: &mut $crate::typed::FieldDeclarer<'_>) {
+                $(fields.declare::<$ty>(stringify!($field));)*
+            }
+        }
+    )*};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    define_components! {
+        /// Hit points.
+        struct Health = "health" {
+            current: i64,
+            max_hp: i64,
+        }
+
+        /// Where an entity is headed, if anywhere.
+        struct Travel = "travel" {
+            destination: Option<EntityId>,
+            route: Vec<KeywordId>,
+            note: String,
+        }
+    }
+
+    #[test]
+    fn reads_and_writes_typed_components() {
+        let world = Health::register(&World::new(0)).unwrap();
+        let world = Travel::register(&world).unwrap();
+        let (world, hero) = world.spawn(&LtMap::new()).unwrap();
+
+        let world = Health {
+            current: 10,
+            max_hp: 12,
+        }
+        .set(&world, hero)
+        .unwrap();
+        let world = Health::update(&world, hero, |h| h.current -= 3).unwrap();
+        assert_eq!(
+            Health::get(&world, hero).unwrap(),
+            Some(Health {
+                current: 7,
+                max_hp: 12
+            })
+        );
+
+        // The DSL sees ordinary fields, with dashes for underscores
+        let max_hp = world.interner().lookup_keyword("max-hp").unwrap();
+        let health = Health::keyword(&world).unwrap();
+        assert_eq!(
+            world.get_field(hero, health, max_hp).unwrap(),
+            Some(Value::Int(12))
+        );
+
+        let mut world = world;
+        let north = world.interner_mut().intern_keyword("north");
+        let travel = Travel {
+            destination: None,
+            route: vec![north, north],
+            note: "slowly".to_string(),
+        };
+        let world = travel.set(&world, hero).unwrap();
+        assert_eq!(Travel::get(&world, hero).unwrap(), Some(travel));
+    }
+
+    #[test]
+    fn reports_unknown_components_and_bad_values() {
+        let world = World::new(0);
+        let (world, hero) = world.spawn(&LtMap::new()).unwrap();
+        let err = Health::get(&world, hero).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown component :health"),
+            "{err}"
+        );
+
+        let mut world = Health::register(&world).unwrap();
+        let current = world.interner_mut().intern_keyword("current");
+        let partial = Value::Map(LtMap::new().insert(Value::Keyword(current), Value::Int(1)));
+        let err = Health::from_value(&partial, world.interner()).unwrap_err();
+        assert!(err.to_string().contains("max-hp"), "{err}");
+        assert!(Health::update(&world, hero, |_| {}).is_err());
+    }
+}
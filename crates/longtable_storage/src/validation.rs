@@ -0,0 +1,614 @@
+//! Whole-world consistency checking.
+//!
+//! Storage operations validate their inputs as they go, but bugs in engine
+//! code (or hand-edited save files) can still leave a world in a state that
+//! only surfaces much later as a confusing error. [`World::validate`] walks
+//! the entire world and reports every inconsistency it finds at once.
+//!
+//! [`World::validate`]: crate::World::validate
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use longtable_foundation::{EntityId, Interner, KeywordId, Value};
+
+use crate::schema::{Cardinality, ComponentSchema};
+use crate::world::World;
+
+// =============================================================================
+// Validation Issues
+// =============================================================================
+
+/// A single inconsistency found while validating a world.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationIssue {
+    /// An entity has a component with no registered schema.
+    UnknownComponent {
+        /// The entity carrying the component.
+        entity: EntityId,
+        /// The unregistered component.
+        component: KeywordId,
+    },
+    /// A component value does not conform to its schema.
+    SchemaViolation {
+        /// The entity carrying the component.
+        entity: EntityId,
+        /// The offending component.
+        component: KeywordId,
+        /// The offending field, if the problem is with a single field.
+        field: Option<KeywordId>,
+        /// What was wrong with the value.
+        message: String,
+    },
+    /// A component value refers to an entity that no longer exists.
+    DanglingReference {
+        /// The entity carrying the reference.
+        entity: EntityId,
+        /// The component containing the reference.
+        component: KeywordId,
+        /// The referenced (missing) entity.
+        target: EntityId,
+    },
+    /// A relationship entity is of a type with no registered schema.
+    UnknownRelationship {
+        /// The relationship entity.
+        relationship: EntityId,
+        /// The unregistered relationship type.
+        rel_type: KeywordId,
+    },
+    /// A relationship entity is missing its type, source, or target.
+    MalformedRelationship {
+        /// The relationship entity.
+        relationship: EntityId,
+    },
+    /// An entity has more edges of a relationship type than its cardinality allows.
+    CardinalityViolation {
+        /// The relationship type.
+        rel_type: KeywordId,
+        /// The declared cardinality.
+        cardinality: Cardinality,
+        /// The over-connected entity.
+        entity: EntityId,
+        /// True if the excess edges are outgoing (entity is the source).
+        outgoing: bool,
+        /// Number of edges found.
+        count: usize,
+    },
+}
+
+impl ValidationIssue {
+    /// Returns the entity this issue is attached to.
+    #[must_use]
+    pub fn entity(&self) -> EntityId {
+        match self {
+            Self::UnknownComponent { entity, .. }
+            | Self::SchemaViolation { entity, .. }
+            | Self::DanglingReference { entity, .. }
+            | Self::CardinalityViolation { entity, .. } => *entity,
+            Self::UnknownRelationship { relationship, .. }
+            | Self::MalformedRelationship { relationship } => *relationship,
+        }
+    }
+
+    /// Returns a short kebab-case name for this kind of issue.
+    #[must_use]
+    pub const fn kind_name(&self) -> &'static str {
+        match self {
+            Self::UnknownComponent { .. } => "unknown-component",
+            Self::SchemaViolation { .. } => "schema-violation",
+            Self::DanglingReference { .. } => "dangling-reference",
+            Self::UnknownRelationship { .. } => "unknown-relationship",
+            Self::MalformedRelationship { .. } => "malformed-relationship",
+            Self::CardinalityViolation { .. } => "cardinality-violation",
+        }
+    }
+
+    /// Returns a human-readable description, resolving keyword names.
+    #[must_use]
+    pub fn describe(&self, interner: &Interner) -> String {
+        let kw = |k: KeywordId| {
+            interner
+                .get_keyword(k)
+                .map_or_else(|| format!("{k:?}"), |name| format!(":{name}"))
+        };
+
+        match self {
+            Self::UnknownComponent { entity, component } => {
+                format!("{entity}: component {} has no schema", kw(*component))
+            }
+            Self::SchemaViolation {
+                entity,
+                component,
+                field,
+                message,
+            } => match field {
+                Some(field) => format!(
+                    "{entity}: {} field {}: {message}",
+                    kw(*component),
+                    kw(*field)
+                ),
+                None => format!("{entity}: {}: {message}", kw(*component)),
+            },
+            Self::DanglingReference {
+                entity,
+                component,
+                target,
+            } => format!(
+                "{entity}: {} refers to missing entity {target}",
+                kw(*component)
+            ),
+            Self::UnknownRelationship {
+                relationship,
+                rel_type,
+            } => format!(
+                "{relationship}: relationship type {} has no schema",
+                kw(*rel_type)
+            ),
+            Self::MalformedRelationship { relationship } => {
+                format!("{relationship}: relationship is missing its type, source, or target")
+            }
+            Self::CardinalityViolation {
+                rel_type,
+                cardinality,
+                entity,
+                outgoing,
+                count,
+            } => {
+                let direction = if *outgoing { "outgoing" } else { "incoming" };
+                format!(
+                    "{entity}: {count} {direction} {} edges violate {cardinality:?}",
+                    kw(*rel_type)
+                )
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Validation Report
+// =============================================================================
+
+/// The result of validating a world.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidationReport {
+    /// Every issue found, in entity order.
+    pub issues: Vec<ValidationIssue>,
+    /// Number of live entities checked.
+    pub entities_checked: usize,
+    /// Number of relationship entities checked.
+    pub relationships_checked: usize,
+}
+
+impl ValidationReport {
+    /// Returns true if no issues were found.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns the number of issues found.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.issues.len()
+    }
+
+    /// Returns true if no issues were found.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Formats the report for display, resolving keyword names.
+    #[must_use]
+    pub fn format(&self, interner: &Interner) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "Checked {} entities ({} relationships): ",
+            self.entities_checked, self.relationships_checked
+        );
+        if self.issues.is_empty() {
+            out.push_str("no issues found");
+            return out;
+        }
+        let _ = write!(out, "{} issue(s)", self.issues.len());
+        for issue in &self.issues {
+            let _ = write!(
+                out,
+                "\n  [{}] {}",
+                issue.kind_name(),
+                issue.describe(interner)
+            );
+        }
+        out
+    }
+}
+
+// =============================================================================
+// Validation
+// =============================================================================
+
+/// Validates every entity, component, and relationship in a world.
+pub(crate) fn validate_world(world: &World) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    let mut entities: Vec<EntityId> = world.entities().collect();
+    entities.sort_by_key(|e| (e.index, e.generation));
+
+    // Edge counts per (type, endpoint), for cardinality checks
+    let mut outgoing: HashMap<(KeywordId, EntityId), usize> = HashMap::new();
+    let mut incoming: HashMap<(KeywordId, EntityId), usize> = HashMap::new();
+
+    for &entity in &entities {
+        if let Some((rel_type, source, Value::EntityRef(target))) =
+            check_entity(world, &mut report, entity)
+        {
+            *outgoing.entry((rel_type, source)).or_default() += 1;
+            *incoming.entry((rel_type, target)).or_default() += 1;
+        }
+    }
+
+    check_cardinality(world, &mut report, outgoing, true);
+    check_cardinality(world, &mut report, incoming, false);
+
+    report
+}
+
+/// Validates only the given entities, skipping any that no longer exist.
+///
+/// Each entity's components are checked against their schemas and for
+/// dangling references, and each relationship entity for its type, source,
+/// and target. Cardinality, which [`World::link`] already enforces edge by
+/// edge, isn't rechecked, so the cost is in the entities given rather than
+/// the size of the world.
+pub(crate) fn validate_entities(
+    world: &World,
+    entities: impl IntoIterator<Item = EntityId>,
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    let mut entities: Vec<EntityId> = entities
+        .into_iter()
+        .filter(|&entity| world.exists(entity))
+        .collect();
+    entities.sort_by_key(|e| (e.index, e.generation));
+    entities.dedup();
+
+    for entity in entities {
+        check_entity(world, &mut report, entity);
+    }
+    report
+}
+
+/// Checks one entity's components and, for a relationship entity, its
+/// type, source, and target.
+///
+/// Returns a well-formed relationship's type, source, and target (which is
+/// nil once nullified), for the cardinality checks.
+fn check_entity(
+    world: &World,
+    report: &mut ValidationReport,
+    entity: EntityId,
+) -> Option<(KeywordId, EntityId, Value)> {
+    report.entities_checked += 1;
+
+    for &component in world.entity_components(entity) {
+        let Ok(Some(value)) = world.get(entity, component) else {
+            continue;
+        };
+
+        match world.component_schema(component) {
+            Some(schema) => check_component(report, entity, schema, &value),
+            None => report
+                .issues
+                .push(ValidationIssue::UnknownComponent { entity, component }),
+        }
+
+        let mut refs = Vec::new();
+        collect_entity_refs(&value, &mut refs);
+        for target in refs {
+            if !world.exists(target) {
+                report.issues.push(ValidationIssue::DanglingReference {
+                    entity,
+                    component,
+                    target,
+                });
+            }
+        }
+    }
+
+    if !world.has(entity, KeywordId::REL_TYPE) {
+        return None;
+    }
+    report.relationships_checked += 1;
+
+    let rel_type = relationship_field(world, entity, KeywordId::REL_TYPE);
+    let source = relationship_field(world, entity, KeywordId::REL_SOURCE);
+    let target = relationship_field(world, entity, KeywordId::REL_TARGET);
+
+    // A nullified link keeps its source but has a nil target
+    let (
+        Some(Value::Keyword(rel_type)),
+        Some(Value::EntityRef(source)),
+        Some(target @ (Value::EntityRef(_) | Value::Nil)),
+    ) = (rel_type, source, target)
+    else {
+        report.issues.push(ValidationIssue::MalformedRelationship {
+            relationship: entity,
+        });
+        return None;
+    };
+
+    if world.relationship_schema(rel_type).is_none() {
+        report.issues.push(ValidationIssue::UnknownRelationship {
+            relationship: entity,
+            rel_type,
+        });
+        return None;
+    }
+
+    Some((rel_type, source, target))
+}
+
+/// Checks a single component value against its schema.
+fn check_component(
+    report: &mut ValidationReport,
+    entity: EntityId,
+    schema: &ComponentSchema,
+    value: &Value,
+) {
+    let component = schema.name;
+
+    if schema.is_tag {
+        if !matches!(value, Value::Bool(true) | Value::Map(_)) {
+            report.issues.push(ValidationIssue::SchemaViolation {
+                entity,
+                component,
+                field: None,
+                message: format!("tag value must be true, got {}", value.value_type()),
+            });
+        }
+        return;
+    }
+
+    let Value::Map(map) = value else {
+        report.issues.push(ValidationIssue::SchemaViolation {
+            entity,
+            component,
+            field: None,
+            message: format!("expected a map, got {}", value.value_type()),
+        });
+        return;
+    };
+
+    for field in &schema.fields {
+        match map.get(&Value::Keyword(field.name)) {
+            None => {
+                if field.required {
+                    report.issues.push(ValidationIssue::SchemaViolation {
+                        entity,
+                        component,
+                        field: Some(field.name),
+                        message: "required field is missing".to_string(),
+                    });
+                }
+            }
+            // Optional fields may be explicitly nil
+            Some(Value::Nil) if !field.required => {}
+            Some(field_value) => {
+                let actual = field_value.value_type();
+                if !field.ty.accepts(&actual) {
+                    report.issues.push(ValidationIssue::SchemaViolation {
+                        entity,
+                        component,
+                        field: Some(field.name),
+                        message: format!("expected {}, got {actual}", field.ty),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Reports any (type, endpoint) pair with more edges than the cardinality allows.
+fn check_cardinality(
+    world: &World,
+    report: &mut ValidationReport,
+    counts: HashMap<(KeywordId, EntityId), usize>,
+    outgoing: bool,
+) {
+    let mut violations: Vec<_> = counts
+        .into_iter()
+        .filter(|&(_, count)| count > 1)
+        .filter_map(|((rel_type, entity), count)| {
+            let cardinality = world.relationship_schema(rel_type)?.cardinality;
+            let limited = if outgoing {
+                matches!(cardinality, Cardinality::OneToOne | Cardinality::ManyToOne)
+            } else {
+                matches!(cardinality, Cardinality::OneToOne | Cardinality::OneToMany)
+            };
+            limited.then_some(ValidationIssue::CardinalityViolation {
+                rel_type,
+                cardinality,
+                entity,
+                outgoing,
+                count,
+            })
+        })
+        .collect();
+
+    violations.sort_by_key(|issue| {
+        let e = issue.entity();
+        (e.index, e.generation)
+    });
+    report.issues.extend(violations);
+}
+
+/// Reads the `:value` field of one of a relationship entity's reserved components.
+fn relationship_field(world: &World, entity: EntityId, component: KeywordId) -> Option<Value> {
+    world
+        .get_field(entity, component, KeywordId::VALUE)
+        .ok()
+        .flatten()
+}
+
+/// Collects every entity reference nested anywhere inside a value.
+fn collect_entity_refs(value: &Value, out: &mut Vec<EntityId>) {
+    match value {
+        Value::EntityRef(id) => out.push(*id),
+        Value::Vec(items) => items.iter().for_each(|v| collect_entity_refs(v, out)),
+        Value::List(items) => items.iter().for_each(|v| collect_entity_refs(v, out)),
+        Value::Set(items) => items.iter().for_each(|v| collect_entity_refs(v, out)),
+        Value::Map(map) => {
+            for (k, v) in map.iter() {
+                collect_entity_refs(k, out);
+                collect_entity_refs(v, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{FieldSchema, RelationshipSchema};
+    use longtable_foundation::{LtMap, Type};
+
+    fn map_of(key: KeywordId, value: Value) -> Value {
+        Value::Map(LtMap::new().insert(Value::Keyword(key), value))
+    }
+
+    #[test]
+    fn empty_world_is_valid() {
+        let report = World::new(0).validate();
+        assert!(report.is_valid());
+        assert_eq!(report.entities_checked, 0);
+    }
+
+    #[test]
+    fn linked_world_is_valid() {
+        let mut world = World::new(0);
+        let contains = world.interner_mut().intern_keyword("contains");
+        let world = world
+            .register_relationship(RelationshipSchema::new(contains))
+            .unwrap();
+        let (world, a) = world.spawn(&LtMap::new()).unwrap();
+        let (world, b) = world.spawn(&LtMap::new()).unwrap();
+        let world = world.link(a, contains, b).unwrap();
+
+        let report = world.validate();
+        assert!(report.is_valid(), "{report:?}");
+        assert_eq!(report.entities_checked, 3);
+        assert_eq!(report.relationships_checked, 1);
+    }
+
+    #[test]
+    fn detects_field_type_mismatch() {
+        let mut world = World::new(0);
+        let health = world.interner_mut().intern_keyword("health");
+        let current = world.interner_mut().intern_keyword("current");
+        let world = world
+            .register_component(
+                ComponentSchema::new(health).with_field(FieldSchema::required(current, Type::Int)),
+            )
+            .unwrap();
+        let (world, e) = world.spawn(&LtMap::new()).unwrap();
+        let world = world
+            .set(e, health, map_of(current, Value::String("lots".into())))
+            .unwrap();
+
+        let report = world.validate();
+        assert_eq!(report.len(), 1);
+        assert!(matches!(
+            &report.issues[0],
+            ValidationIssue::SchemaViolation { entity, field: Some(f), .. }
+                if *entity == e && *f == current
+        ));
+    }
+
+    #[test]
+    fn detects_dangling_reference() {
+        let mut world = World::new(0);
+        let owner = world.interner_mut().intern_keyword("owner");
+        let value = world.interner_mut().intern_keyword("value");
+        let world = world
+            .register_component(
+                ComponentSchema::new(owner)
+                    .with_field(FieldSchema::required(value, Type::EntityRef)),
+            )
+            .unwrap();
+        let (world, item) = world.spawn(&LtMap::new()).unwrap();
+        let (world, player) = world.spawn(&LtMap::new()).unwrap();
+        let world = world
+            .set(item, owner, map_of(value, Value::EntityRef(player)))
+            .unwrap();
+        let world = world.destroy(player).unwrap();
+
+        let report = world.validate();
+        assert_eq!(
+            report.issues,
+            vec![ValidationIssue::DanglingReference {
+                entity: item,
+                component: owner,
+                target: player,
+            }]
+        );
+
+        // Only the entities asked about are checked
+        let (world, other) = world.spawn(&LtMap::new()).unwrap();
+        let report = world.validate_entities([other, player]);
+        assert!(report.is_valid(), "{report:?}");
+        assert_eq!(report.entities_checked, 1);
+        assert_eq!(world.validate_entities([item]).len(), 1);
+    }
+
+    #[test]
+    fn detects_cardinality_violation() {
+        let mut world = World::new(0);
+        let in_room = world.interner_mut().intern_keyword("in-room");
+        let world = world
+            .register_relationship(
+                RelationshipSchema::new(in_room).with_cardinality(Cardinality::ManyToOne),
+            )
+            .unwrap();
+        let (world, player) = world.spawn(&LtMap::new()).unwrap();
+        let (world, hall) = world.spawn(&LtMap::new()).unwrap();
+        let (world, kitchen) = world.spawn(&LtMap::new()).unwrap();
+
+        // Bypass create_relationship so cardinality is not enforced
+        let (world, _) = world.spawn_relationship(in_room, player, hall).unwrap();
+        let (world, _) = world.spawn_relationship(in_room, player, kitchen).unwrap();
+
+        let report = world.validate();
+        assert_eq!(
+            report.issues,
+            vec![ValidationIssue::CardinalityViolation {
+                rel_type: in_room,
+                cardinality: Cardinality::ManyToOne,
+                entity: player,
+                outgoing: true,
+                count: 2,
+            }]
+        );
+        assert!(report.format(world.interner()).contains(":in-room"));
+    }
+
+    #[test]
+    fn detects_unknown_relationship_type() {
+        let mut world = World::new(0);
+        let mystery = world.interner_mut().intern_keyword("mystery");
+        let (world, a) = world.spawn(&LtMap::new()).unwrap();
+        let (world, b) = world.spawn(&LtMap::new()).unwrap();
+        let (world, rel) = world.spawn_relationship(mystery, a, b).unwrap();
+
+        let report = world.validate();
+        assert_eq!(
+            report.issues,
+            vec![ValidationIssue::UnknownRelationship {
+                relationship: rel,
+                rel_type: mystery,
+            }]
+        );
+    }
+}
@@ -3,14 +3,20 @@
 //! The `World` is the unified interface to all storage systems.
 //! It uses persistent data structures for O(1) cloning and structural sharing.
 
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use longtable_foundation::{EntityId, Error, ErrorKind, Interner, KeywordId, LtMap, Result, Value};
 
 use crate::component::ComponentStore;
-use crate::entity::EntityStore;
-use crate::relationship::RelationshipStore;
+use crate::entity::{EntityPolicy, EntityStats, EntityStore};
+use crate::memory::{ArchetypeCount, MemoryStats, StoreSharing};
+use crate::relationship::{
+    FanOut, LookupCounts, LookupLog, RelationshipStats, RelationshipStore, cycle_error, cycle_path,
+    record_lookup,
+};
 use crate::schema::{ComponentSchema, OnDelete, RelationshipSchema};
+use crate::validation::ValidationReport;
 
 #[cfg(feature = "serde")]
 mod serde_support {
@@ -116,13 +122,15 @@ mod serde_support {
                     }
 
                     let entities = entities.ok_or_else(|| de::Error::missing_field("entities"))?;
-                    let components =
+                    let mut components: crate::component::ComponentStore =
                         components.ok_or_else(|| de::Error::missing_field("components"))?;
                     let relationships =
                         relationships.ok_or_else(|| de::Error::missing_field("relationships"))?;
                     let interner = interner.ok_or_else(|| de::Error::missing_field("interner"))?;
                     let tick = tick.ok_or_else(|| de::Error::missing_field("tick"))?;
                     let seed = seed.ok_or_else(|| de::Error::missing_field("seed"))?;
+                    components.reindex_spatially(&crate::spatial::axes(&interner));
+                    components.restamp();
 
                     Ok(World {
                         entities: Arc::new(entities),
@@ -131,6 +139,7 @@ mod serde_support {
                         interner: Arc::new(interner),
                         tick,
                         seed,
+                        score: 0.0,     // Recomputed by the next tick's constraint check
                         previous: None, // History is not serialized
                     })
                 }
@@ -149,6 +158,41 @@ mod serde_support {
     }
 }
 
+/// What a relationship's `on_target_delete` policy did when the target of
+/// one of its links was destroyed.
+///
+/// See [`World::destroy_cascading`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CascadeStep {
+    /// The entity that was destroyed.
+    pub destroyed: EntityId,
+    /// The relationship of the link that pointed at it.
+    pub relationship: KeywordId,
+    /// The link's source.
+    pub source: EntityId,
+    /// The policy applied to the link.
+    pub action: OnDelete,
+}
+
+/// A world's relationship entities, by source and by target.
+#[derive(Default)]
+struct LinkIndex {
+    from: HashMap<EntityId, Vec<EntityId>>,
+    to: HashMap<EntityId, Vec<EntityId>>,
+}
+
+impl LinkIndex {
+    /// Returns the links from `entity`.
+    fn from(&self, entity: EntityId) -> &[EntityId] {
+        self.from.get(&entity).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the links to `entity`.
+    fn to(&self, entity: EntityId) -> &[EntityId] {
+        self.to.get(&entity).map_or(&[], Vec::as_slice)
+    }
+}
+
 /// Immutable snapshot of simulation state.
 ///
 /// Clone is O(1) due to structural sharing via `Arc`.
@@ -167,6 +211,9 @@ pub struct World {
     tick: u64,
     /// Random seed for determinism.
     seed: u64,
+    /// Total penalty of the scoring constraints this world was last checked
+    /// against.
+    score: f64,
     /// Previous world state (for history/undo).
     previous: Option<Arc<World>>,
 }
@@ -203,9 +250,10 @@ impl World {
             .register_schema(rel_source_schema)
             .expect("failed to register :rel/source schema");
 
-        // :rel/target - stores the target entity
-        let rel_target_schema = ComponentSchema::new(KeywordId::REL_TARGET)
-            .with_field(FieldSchema::required(KeywordId::VALUE, Type::EntityRef));
+        // :rel/target - stores the target entity, or nil once nullified
+        let rel_target_schema = ComponentSchema::new(KeywordId::REL_TARGET).with_field(
+            FieldSchema::required(KeywordId::VALUE, Type::option(Type::EntityRef)),
+        );
         components
             .register_schema(rel_target_schema)
             .expect("failed to register :rel/target schema");
@@ -217,6 +265,7 @@ impl World {
             interner: Arc::new(Interner::new()),
             tick: 0,
             seed,
+            score: 0.0,
             previous: None,
         }
     }
@@ -233,18 +282,154 @@ impl World {
         self.seed
     }
 
+    /// Returns the world score: the total penalty from soft constraints
+    /// violated when the tick that produced this world checked them, or
+    /// `0.0` for a world no tick has checked.
+    ///
+    /// The score is derived, so it is neither saved nor hashed.
+    #[must_use]
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    /// Returns this world with its score set.
+    #[must_use]
+    pub fn with_score(&self, score: f64) -> World {
+        World {
+            score,
+            ..self.clone()
+        }
+    }
+
+    /// Returns this world with a different entity recycling policy.
+    ///
+    /// The policy is saved with the world. See [`EntityPolicy`].
+    #[must_use]
+    pub fn with_entity_policy(&self, policy: EntityPolicy) -> World {
+        let mut entities = (*self.entities).clone();
+        entities.set_policy(policy);
+        World {
+            entities: Arc::new(entities),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the entity recycling policy.
+    #[must_use]
+    pub fn entity_policy(&self) -> EntityPolicy {
+        self.entities.policy()
+    }
+
+    /// Counts live, dead, reusable, and retired entity slots.
+    #[must_use]
+    pub fn entity_stats(&self) -> EntityStats {
+        self.entities.stats()
+    }
+
     /// Returns the number of live entities.
     #[must_use]
     pub fn entity_count(&self) -> usize {
         self.entities.len()
     }
 
+    /// Adds every keyword this world refers to, as component names or
+    /// inside component values, to `live`.
+    ///
+    /// This is the mark phase for [`Interner::sweep_generated`]. It does not
+    /// walk the `previous` chain; callers that retain history mark each
+    /// world in it.
+    pub fn mark_keywords(&self, live: &mut HashSet<KeywordId>) {
+        for entity in self.entities() {
+            for (component, value) in self.components_of(entity) {
+                live.insert(component);
+                value.mark_keywords(live);
+            }
+        }
+    }
+
+    /// Reports what this world and the history it retains hold in memory.
+    ///
+    /// Walks the whole `previous` chain, so it takes time proportional to
+    /// the history's length.
+    #[must_use]
+    pub fn memory_stats(&self) -> MemoryStats {
+        let mut worlds = vec![self];
+        while let Some(previous) = worlds[worlds.len() - 1].previous() {
+            worlds.push(previous);
+        }
+
+        let (component_store, component_copies) = store_sharing(&worlds, |w| &w.components);
+        let mut archetypes: Vec<ArchetypeCount> = self
+            .components
+            .archetype_counts()
+            .into_iter()
+            .map(|(archetype, entities)| ArchetypeCount {
+                components: archetype.components().to_vec(),
+                entities,
+            })
+            .collect();
+        let with_components: usize = archetypes.iter().map(|a| a.entities).sum();
+        let bare = self.entity_count().saturating_sub(with_components);
+        if bare > 0 {
+            archetypes.push(ArchetypeCount {
+                components: Vec::new(),
+                entities: bare,
+            });
+        }
+        archetypes.sort_by(|a, b| {
+            b.entities.cmp(&a.entities).then_with(|| {
+                let index =
+                    |c: &ArchetypeCount| c.components.iter().map(|k| k.index()).collect::<Vec<_>>();
+                index(a).cmp(&index(b))
+            })
+        });
+
+        MemoryStats {
+            entities: self.entity_count(),
+            archetypes,
+            component_values: self.components.value_count(),
+            history: worlds.len() - 1,
+            entity_store: store_sharing(&worlds, |w| &w.entities).0,
+            component_store,
+            relationship_store: store_sharing(&worlds, |w| &w.relationships).0,
+            interner_store: store_sharing(&worlds, |w| &w.interner).0,
+            retained_component_values: component_copies
+                .iter()
+                .map(|store| store.value_count())
+                .sum(),
+            interned_strings: self.interner.string_count(),
+            interned_bytes: self.interner.string_bytes(),
+        }
+    }
+
     /// Returns a reference to the previous world state, if any.
     #[must_use]
     pub fn previous(&self) -> Option<&World> {
         self.previous.as_ref().map(Arc::as_ref)
     }
 
+    /// Returns this world with `previous` as the state it came from, so a
+    /// batch of edits counts as a single step of history.
+    pub(crate) fn descended_from(self, previous: &World) -> World {
+        World {
+            previous: Some(Arc::new(previous.clone())),
+            ..self
+        }
+    }
+
+    /// Returns the component store.
+    pub(crate) fn component_store(&self) -> &ComponentStore {
+        &self.components
+    }
+
+    /// Returns this world with its components stored in `components`.
+    pub(crate) fn with_component_store(&self, components: ComponentStore) -> World {
+        World {
+            components: Arc::new(components),
+            ..self.clone()
+        }
+    }
+
     /// Returns a reference to the interner.
     #[must_use]
     pub fn interner(&self) -> &Interner {
@@ -291,6 +476,33 @@ impl World {
         })
     }
 
+    /// Removes a component schema so it can be registered again.
+    ///
+    /// Values already stored under the component are kept. Returns a new
+    /// World without the schema.
+    #[must_use]
+    pub fn unregister_component(&self, name: KeywordId) -> World {
+        let mut new_components = (*self.components).clone();
+        new_components.unregister_schema(name);
+        World {
+            components: Arc::new(new_components),
+            ..self.clone()
+        }
+    }
+
+    /// Removes a relationship schema so it can be registered again.
+    ///
+    /// Existing links are kept. Returns a new World without the schema.
+    #[must_use]
+    pub fn unregister_relationship(&self, name: KeywordId) -> World {
+        let mut new_relationships = (*self.relationships).clone();
+        new_relationships.unregister_schema(name);
+        World {
+            relationships: Arc::new(new_relationships),
+            ..self.clone()
+        }
+    }
+
     /// Gets a component schema by name.
     #[must_use]
     pub fn component_schema(&self, name: KeywordId) -> Option<&ComponentSchema> {
@@ -303,6 +515,16 @@ impl World {
         self.relationships.schema(name)
     }
 
+    /// Iterates over all registered component schemas (in no particular order).
+    pub fn component_schemas(&self) -> impl Iterator<Item = &ComponentSchema> {
+        self.components.schemas()
+    }
+
+    /// Iterates over all registered relationship schemas (in no particular order).
+    pub fn relationship_schemas(&self) -> impl Iterator<Item = &RelationshipSchema> {
+        self.relationships.schemas()
+    }
+
     // --- Entity Operations ---
 
     /// Spawns a new entity with optional initial components.
@@ -367,6 +589,112 @@ impl World {
         Ok((new_world, id))
     }
 
+    /// Brings back an entity with no components at exactly `id`, as when
+    /// loading a saved world.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `id` is already alive or names a free slot.
+    pub fn restore_entity(&self, id: EntityId) -> Result<World> {
+        let mut new_entities = (*self.entities).clone();
+        new_entities.restore(id)?;
+        Ok(World {
+            entities: Arc::new(new_entities),
+            previous: Some(Arc::new(self.clone())),
+            ..self.clone()
+        })
+    }
+
+    /// Spawns many entities in one pass, one per components map.
+    ///
+    /// Unlike calling [`World::spawn`] for each, the stores are copied once
+    /// for the whole batch rather than once per entity. Returns the new
+    /// World and the spawned entity IDs, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a component cannot be set.
+    pub fn spawn_batch(&self, batch: &[LtMap<Value, Value>]) -> Result<(World, Vec<EntityId>)> {
+        let mut new_entities = (*self.entities).clone();
+        let mut new_components = (*self.components).clone();
+
+        let mut ids = Vec::with_capacity(batch.len());
+        for components in batch {
+            let id = new_entities.spawn();
+            set_initial_components(&mut new_components, id, components)?;
+            ids.push(id);
+        }
+
+        let new_world = World {
+            entities: Arc::new(new_entities),
+            components: Arc::new(new_components),
+            previous: Some(Arc::new(self.clone())),
+            ..self.clone()
+        };
+
+        Ok((new_world, ids))
+    }
+
+    /// Spawns many entities with pre-determined IDs in one pass, as
+    /// [`World::spawn_with_id`] does each.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a component cannot be set.
+    pub fn spawn_batch_with_ids(&self, batch: &[(EntityId, LtMap<Value, Value>)]) -> Result<World> {
+        let mut new_entities = (*self.entities).clone();
+        let mut new_components = (*self.components).clone();
+
+        for (id, components) in batch {
+            new_entities.spawn_with_id(*id);
+            set_initial_components(&mut new_components, *id, components)?;
+        }
+
+        Ok(World {
+            entities: Arc::new(new_entities),
+            components: Arc::new(new_components),
+            previous: Some(Arc::new(self.clone())),
+            ..self.clone()
+        })
+    }
+
+    /// Spawns the content-addressed entity declared as `name`.
+    ///
+    /// The entity's ID depends only on `name` (see
+    /// [`content_index`](crate::content_index)), so the same declaration
+    /// gets the same ID in every fresh world.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entity by that name is already alive, or a
+    /// component cannot be set.
+    pub fn spawn_content(
+        &self,
+        name: &str,
+        components: &LtMap<Value, Value>,
+    ) -> Result<(World, EntityId)> {
+        let mut new_entities = (*self.entities).clone();
+        let id = new_entities.spawn_content(name)?;
+
+        let mut new_components = (*self.components).clone();
+
+        // Set initial components
+        for (key, value) in components.iter() {
+            if let Value::Keyword(comp_name) = key {
+                new_components.set(id, *comp_name, value.clone())?;
+            }
+        }
+
+        let new_world = World {
+            entities: Arc::new(new_entities),
+            components: Arc::new(new_components),
+            previous: Some(Arc::new(self.clone())),
+            ..self.clone()
+        };
+
+        Ok((new_world, id))
+    }
+
     /// Spawns a relationship entity linking source to target.
     ///
     /// This creates a new entity with `:rel/type`, `:rel/source`, and `:rel/target`
@@ -426,6 +754,10 @@ impl World {
     /// 3. Handles violations according to schema settings (Error or Replace)
     /// 4. Creates the relationship entity
     ///
+    /// For acyclic relationships, only the entities the target already reaches
+    /// are walked, so containment hierarchies stay cycle-free without a
+    /// whole-world check each tick.
+    ///
     /// # Cardinality Rules
     ///
     /// - `OneToOne`: Source can have at most one target, target can have at most one source
@@ -439,6 +771,7 @@ impl World {
     /// - Source or target entities don't exist
     /// - Relationship type is not registered
     /// - Cardinality would be violated and `on_violation` is `Error`
+    /// - The relationship is acyclic and the link would close a cycle
     pub fn create_relationship(
         &self,
         rel_type: KeywordId,
@@ -456,6 +789,7 @@ impl World {
 
         let cardinality = schema.cardinality;
         let on_violation = schema.on_violation;
+        let acyclic = schema.acyclic;
 
         // Check for existing identical relationship (idempotent)
         let existing = self.find_relationships(Some(rel_type), Some(source), Some(target));
@@ -464,14 +798,27 @@ impl World {
             return Ok((self.clone(), existing[0]));
         }
 
-        // Collect relationship entities to remove before creating new one
-        let mut to_remove: Vec<EntityId> = Vec::new();
+        // Acyclic relationships (containment) reject an entity inside itself
+        if acyclic {
+            if let Some(path) = cycle_path(source, target, |e| self.targets(e, rel_type).collect())
+            {
+                let name = self.interner.get_keyword(rel_type).unwrap_or("?");
+                return Err(cycle_error(&format!(":{name}"), source, target, &path));
+            }
+        }
+
+        // Collect relationship entities to remove before creating new one,
+        // starting with the source's nullified links, which the new one
+        // takes the place of
+        let (mut to_remove, live_from_source): (Vec<EntityId>, Vec<EntityId>) = self
+            .find_relationships(Some(rel_type), Some(source), None)
+            .into_iter()
+            .partition(|&rel| self.get_relationship_target(rel).is_none());
 
         match cardinality {
             Cardinality::OneToOne => {
                 // Source can have at most one target
-                let existing_from_source =
-                    self.find_relationships(Some(rel_type), Some(source), None);
+                let existing_from_source = live_from_source.clone();
                 if !existing_from_source.is_empty() {
                     match on_violation {
                         OnViolation::Error => {
@@ -503,8 +850,7 @@ impl World {
             }
             Cardinality::ManyToOne => {
                 // Source can have at most one target
-                let existing_from_source =
-                    self.find_relationships(Some(rel_type), Some(source), None);
+                let existing_from_source = live_from_source.clone();
                 if !existing_from_source.is_empty() {
                     match on_violation {
                         OnViolation::Error => {
@@ -559,6 +905,9 @@ impl World {
     ///
     /// Returns entity IDs of matching relationship entities.
     ///
+    /// Lookups of a single type are counted for any caller collecting them
+    /// (see [`count_lookups`](crate::relationship::count_lookups)).
+    ///
     /// Note: This is currently O(n) over all entities. Will be optimized
     /// with indexes in Phase 5.6.
     #[must_use]
@@ -569,9 +918,12 @@ impl World {
         target: Option<EntityId>,
     ) -> Vec<EntityId> {
         // Find all entities that have :rel/type component (i.e., are relationship entities)
-        self.components
+        let mut visited = 0;
+        let found: Vec<EntityId> = self
+            .components
             .with_component(KeywordId::REL_TYPE)
             .filter(|&entity| {
+                visited += 1;
                 // Check rel_type filter
                 if let Some(expected_type) = rel_type {
                     if let Some(Value::Map(map)) = self.components.get(entity, KeywordId::REL_TYPE)
@@ -630,7 +982,20 @@ impl World {
 
                 true
             })
-            .collect()
+            .collect();
+
+        if let Some(rel_type) = rel_type {
+            record_lookup(
+                rel_type,
+                &LookupCounts {
+                    lookups: 1,
+                    narrowed: u64::from(source.is_some() || target.is_some()),
+                    visited,
+                    matched: found.len() as u64,
+                },
+            );
+        }
+        found
     }
 
     /// Finds relationship entities where the type name starts with the given prefix.
@@ -679,9 +1044,9 @@ impl World {
     /// Will be optimized with indexes in Phase 5.6.
     #[must_use]
     pub fn has_outgoing(&self, source: EntityId, rel_type: KeywordId) -> bool {
-        !self
-            .find_relationships(Some(rel_type), Some(source), None)
-            .is_empty()
+        self.find_relationships(Some(rel_type), Some(source), None)
+            .into_iter()
+            .any(|rel| self.get_relationship_target(rel).is_some())
     }
 
     /// Checks if an entity has an incoming relationship of the given type.
@@ -695,6 +1060,63 @@ impl World {
             .is_empty()
     }
 
+    /// Returns the fan-out distribution of one relationship type.
+    #[must_use]
+    pub fn fan_out(&self, rel_type: KeywordId) -> FanOut {
+        self.fan_outs(Some(rel_type))
+            .remove(&rel_type)
+            .unwrap_or_default()
+    }
+
+    /// Returns fan-out statistics for every registered relationship type,
+    /// with the lookups in `lookups`, ordered by name.
+    #[must_use]
+    pub fn relationship_stats(&self, lookups: &LookupLog) -> Vec<RelationshipStats> {
+        let mut fan_outs = self.fan_outs(None);
+        let mut stats: Vec<RelationshipStats> = self
+            .relationships
+            .schemas()
+            .map(|schema| RelationshipStats {
+                relationship: schema.name,
+                lookups: lookups.get(schema.name),
+                fan_out: fan_outs.remove(&schema.name).unwrap_or_default(),
+            })
+            .collect();
+        stats.sort_by(|a, b| {
+            self.interner
+                .get_keyword(a.relationship)
+                .cmp(&self.interner.get_keyword(b.relationship))
+        });
+        stats
+    }
+
+    /// Computes fan-out distributions in one pass over relationship entities,
+    /// for one relationship type or all of them.
+    fn fan_outs(&self, only: Option<KeywordId>) -> HashMap<KeywordId, FanOut> {
+        let mut counts: HashMap<KeywordId, HashMap<EntityId, usize>> = HashMap::new();
+        for rel_entity in self.components.with_component(KeywordId::REL_TYPE) {
+            if let (Some(rel_type), Some(source)) = (
+                self.get_relationship_type(rel_entity),
+                self.get_relationship_source(rel_entity),
+            ) {
+                if only.is_some_and(|only| only != rel_type)
+                    || self.get_relationship_target(rel_entity).is_none()
+                {
+                    continue;
+                }
+                *counts
+                    .entry(rel_type)
+                    .or_default()
+                    .entry(source)
+                    .or_default() += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(rel_type, sources)| (rel_type, FanOut::from_counts(sources.into_values())))
+            .collect()
+    }
+
     /// Gets the relationship type from a relationship entity.
     fn get_relationship_type(&self, rel_entity: EntityId) -> Option<KeywordId> {
         if let Some(Value::Map(map)) = self.components.get(rel_entity, KeywordId::REL_TYPE) {
@@ -715,77 +1137,184 @@ impl World {
         None
     }
 
+    /// Gets the target entity from a relationship entity, or `None` if the
+    /// link was nullified.
+    fn get_relationship_target(&self, rel_entity: EntityId) -> Option<EntityId> {
+        if let Some(Value::Map(map)) = self.components.get(rel_entity, KeywordId::REL_TARGET) {
+            if let Some(Value::EntityRef(id)) = map.get(&Value::Keyword(KeywordId::VALUE)) {
+                return Some(*id);
+            }
+        }
+        None
+    }
+
     /// Destroys an entity and all its components/relationships.
     ///
-    /// Returns a new World with the entity removed.
+    /// Returns a new World with the entity removed. Links to the entity are
+    /// handled by their relationship's `on_target_delete` policy (see
+    /// [`World::destroy_cascading`]).
     pub fn destroy(&self, entity: EntityId) -> Result<World> {
-        self.entities.validate(entity)?;
+        self.destroy_cascading(entity).map(|(world, _)| world)
+    }
+
+    /// Destroys an entity, applying each relationship's `on_target_delete`
+    /// policy to the links that point at it, and reports what each policy
+    /// did.
+    ///
+    /// Links from the entity are removed. A link to it is
+    /// - removed, for [`OnDelete::Remove`];
+    /// - removed along with its source, which is destroyed in turn, for
+    ///   [`OnDelete::Cascade`];
+    /// - kept with a nil target, so its source (and any attributes) survive
+    ///   to notice the loss, for [`OnDelete::Nullify`].
+    ///
+    /// The steps are in the order they happened, cascades included.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entity doesn't exist.
+    pub fn destroy_cascading(&self, entity: EntityId) -> Result<(World, Vec<CascadeStep>)> {
+        self.destroy_batch_cascading(&[entity])
+    }
+
+    /// Destroys many entities in one pass, as [`World::destroy`] does each.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the entities doesn't exist.
+    pub fn destroy_batch(&self, entities: &[EntityId]) -> Result<World> {
+        self.destroy_batch_cascading(entities)
+            .map(|(world, _)| world)
+    }
+
+    /// Destroys many entities in one pass, as [`World::destroy_cascading`]
+    /// does each, and reports the steps of every cascade.
+    ///
+    /// An entity already destroyed by an earlier one's cascade is skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the entities doesn't exist.
+    pub fn destroy_batch_cascading(
+        &self,
+        entities: &[EntityId],
+    ) -> Result<(World, Vec<CascadeStep>)> {
+        for &entity in entities {
+            self.entities.validate(entity)?;
+        }
 
+        let links = self.link_index();
         let mut new_entities = (*self.entities).clone();
         let mut new_components = (*self.components).clone();
+        let mut steps = Vec::new();
+        for &entity in entities {
+            if new_entities.exists(entity) {
+                self.destroy_into(
+                    entity,
+                    &links,
+                    &mut new_entities,
+                    &mut new_components,
+                    &mut steps,
+                )?;
+            }
+        }
 
-        // Find relationship entities where this entity is source OR target
-        let rel_entities_as_source = self.find_relationships(None, Some(entity), None);
-        let rel_entities_as_target = self.find_relationships(None, None, Some(entity));
+        let world = World {
+            entities: Arc::new(new_entities),
+            components: Arc::new(new_components),
+            previous: Some(Arc::new(self.clone())),
+            ..self.clone()
+        };
+        Ok((world, steps))
+    }
 
-        // Collect cascade victims: entities that should be deleted because of OnDelete::Cascade
-        // When entity E is deleted, find relationships where E is TARGET with Cascade policy
-        let mut cascade_victims = Vec::new();
-        for rel_entity in &rel_entities_as_target {
-            if let Some(rel_type) = self.get_relationship_type(*rel_entity) {
-                if let Some(schema) = self.relationships.schema(rel_type) {
-                    if schema.on_target_delete == OnDelete::Cascade {
-                        if let Some(source) = self.get_relationship_source(*rel_entity) {
-                            if !cascade_victims.contains(&source) {
-                                cascade_victims.push(source);
-                            }
-                        }
-                    }
-                }
+    /// Indexes the relationship entities by source and by target.
+    fn link_index(&self) -> LinkIndex {
+        let mut index = LinkIndex::default();
+        for link in self.components.with_component(KeywordId::REL_TYPE) {
+            if let Some(source) = self.get_relationship_source(link) {
+                index.from.entry(source).or_default().push(link);
+            }
+            if let Some(target) = self.get_relationship_target(link) {
+                index.to.entry(target).or_default().push(link);
             }
         }
+        index
+    }
 
-        // Collect all relationship entities to destroy (deduplicated)
-        let mut rel_entities_to_destroy: Vec<EntityId> = rel_entities_as_source;
-        for rel in rel_entities_as_target {
-            if !rel_entities_to_destroy.contains(&rel) {
-                rel_entities_to_destroy.push(rel);
+    /// Destroys an entity in the given stores, appending the policy applied
+    /// to each link to it, then destroys its cascade victims in turn.
+    ///
+    /// `links` indexes this world's links; the stores say which of them are
+    /// still alive.
+    fn destroy_into(
+        &self,
+        entity: EntityId,
+        links: &LinkIndex,
+        entities: &mut EntityStore,
+        components: &mut ComponentStore,
+        steps: &mut Vec<CascadeStep>,
+    ) -> Result<()> {
+        let mut cascade_victims = Vec::new();
+        let remove_link =
+            |link: EntityId, entities: &mut EntityStore, components: &mut ComponentStore| {
+                components.remove_entity(link);
+                let _ = entities.destroy(link);
+            };
+
+        // Links to it follow their relationship's policy
+        for &link in links.to(entity) {
+            if !entities.exists(link) {
+                continue;
+            }
+            let (Some(rel_type), Some(source)) = (
+                self.get_relationship_type(link),
+                self.get_relationship_source(link),
+            ) else {
+                continue;
+            };
+            let action = self
+                .relationships
+                .schema(rel_type)
+                .map_or(OnDelete::Remove, |schema| schema.on_target_delete);
+            steps.push(CascadeStep {
+                destroyed: entity,
+                relationship: rel_type,
+                source,
+                action,
+            });
+            match action {
+                OnDelete::Remove => remove_link(link, entities, components),
+                OnDelete::Cascade => {
+                    remove_link(link, entities, components);
+                    if source != entity && !cascade_victims.contains(&source) {
+                        cascade_victims.push(source);
+                    }
+                }
+                OnDelete::Nullify => {
+                    let nil_target =
+                        LtMap::new().insert(Value::Keyword(KeywordId::VALUE), Value::Nil);
+                    components.set(link, KeywordId::REL_TARGET, Value::Map(nil_target))?;
+                }
             }
         }
 
-        // Destroy relationship entities
-        for rel_entity in rel_entities_to_destroy {
-            if new_entities.exists(rel_entity) {
-                new_components.remove_entity(rel_entity);
-                let _ = new_entities.destroy(rel_entity);
+        // Links from the entity go with it, nullified ones included
+        for &link in links.from(entity) {
+            if entities.exists(link) {
+                remove_link(link, entities, components);
             }
         }
 
-        // Remove all components
-        new_components.remove_entity(entity);
-
-        // Destroy the entity
-        new_entities.destroy(entity)?;
-
-        // Process cascade deletions recursively
-        // Note: We build new World first, then call destroy on cascade victims
-        let mut world = World {
-            entities: Arc::new(new_entities),
-            components: Arc::new(new_components),
-            relationships: Arc::clone(&self.relationships),
-            previous: Some(Arc::new(self.clone())),
-            interner: Arc::clone(&self.interner),
-            tick: self.tick,
-            seed: self.seed,
-        };
+        components.remove_entity(entity);
+        entities.destroy(entity)?;
 
         for victim in cascade_victims {
-            if world.exists(victim) {
-                world = world.destroy(victim)?;
+            if entities.exists(victim) {
+                self.destroy_into(victim, links, entities, components, steps)?;
             }
         }
-
-        Ok(world)
+        Ok(())
     }
 
     /// Checks if an entity exists.
@@ -834,6 +1363,29 @@ impl World {
         })
     }
 
+    /// Sets many components in one pass, in order.
+    ///
+    /// Like [`World::spawn_batch`], the component store is copied once for
+    /// the whole batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entity doesn't exist or a value doesn't match
+    /// its component's schema. Nothing is set in that case.
+    pub fn set_batch(&self, writes: &[(EntityId, KeywordId, Value)]) -> Result<World> {
+        let mut new_components = (*self.components).clone();
+        for (entity, component, value) in writes {
+            self.entities.validate(*entity)?;
+            new_components.set(*entity, *component, value.clone())?;
+        }
+
+        Ok(World {
+            components: Arc::new(new_components),
+            previous: Some(Arc::new(self.clone())),
+            ..self.clone()
+        })
+    }
+
     /// Sets a specific field in a component.
     ///
     /// Returns a new World with the field updated.
@@ -889,6 +1441,75 @@ impl World {
             .map_or(&[], |arch| arch.components())
     }
 
+    /// Iterates an entity's components with their values.
+    ///
+    /// Yields nothing if the entity doesn't exist.
+    pub fn components_of(&self, entity: EntityId) -> impl Iterator<Item = (KeywordId, Value)> + '_ {
+        self.entity_components(entity)
+            .iter()
+            .filter_map(move |&component| {
+                self.components
+                    .get(entity, component)
+                    .map(|value| (component, value.clone()))
+            })
+    }
+
+    /// Returns the relationships from `entity`, as `(relationship, target)`
+    /// pairs.
+    #[must_use]
+    pub fn outgoing(&self, entity: EntityId) -> Vec<(KeywordId, EntityId)> {
+        self.find_relationships(None, Some(entity), None)
+            .into_iter()
+            .filter_map(|rel| {
+                Some((
+                    self.get_relationship_type(rel)?,
+                    self.get_relationship_target(rel)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the relationships to `entity`, as `(relationship, source)`
+    /// pairs.
+    #[must_use]
+    pub fn incoming(&self, entity: EntityId) -> Vec<(KeywordId, EntityId)> {
+        self.find_relationships(None, None, Some(entity))
+            .into_iter()
+            .filter_map(|rel| {
+                Some((
+                    self.get_relationship_type(rel)?,
+                    self.get_relationship_source(rel)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the number of entities with `component`.
+    #[must_use]
+    pub fn component_count(&self, component: KeywordId) -> usize {
+        self.components.count(component)
+    }
+
+    /// Returns a stamp that changes whenever `component` is written on any
+    /// entity (see [`ComponentStore::version`]). For a relationship, it
+    /// changes whenever a link of any type is made or broken.
+    #[must_use]
+    pub fn component_version(&self, component: KeywordId) -> u64 {
+        if self.relationship_schema(component).is_some() {
+            [
+                KeywordId::REL_TYPE,
+                KeywordId::REL_SOURCE,
+                KeywordId::REL_TARGET,
+            ]
+            .into_iter()
+            .map(|reserved| self.components.version(reserved))
+            .max()
+            .unwrap_or(0)
+        } else {
+            self.components.version(component)
+        }
+    }
+
     /// Iterates entities with a specific component.
     pub fn with_component(&self, component: KeywordId) -> impl Iterator<Item = EntityId> + '_ {
         self.components.with_component(component)
@@ -1019,6 +1640,19 @@ impl World {
 
     // --- Tick Operations ---
 
+    /// Returns this world relabelled at `tick`, without history.
+    ///
+    /// Used when restoring state captured outside the engine, such as an
+    /// imported fixture.
+    #[must_use]
+    pub fn with_tick(&self, tick: u64) -> World {
+        World {
+            tick,
+            previous: None,
+            ..self.clone()
+        }
+    }
+
     /// Advances to the next tick.
     ///
     /// Returns a new World with incremented tick and history link.
@@ -1031,34 +1665,74 @@ impl World {
         }
     }
 
-    /// Creates a content hash for memoization.
+    /// Creates a stable hash of the world's content.
     ///
-    /// Two worlds with identical content will have the same hash.
-    /// This includes tick, seed, entity generations, and all component data
-    /// in a deterministic order.
+    /// Two worlds with identical content have the same hash, whichever
+    /// process or build computed it and however their persistent structures
+    /// happen to be shared. This covers tick, seed, entity generations, and
+    /// all component data (relationships are entities, so they are included)
+    /// in a deterministic order. See [`longtable_foundation::hash`].
     #[must_use]
     pub fn content_hash(&self) -> u64 {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+        use longtable_foundation::StableHasher;
+        use longtable_foundation::hash::hash_value;
+        use std::hash::Hasher;
 
-        let mut hasher = DefaultHasher::new();
+        let mut hasher = StableHasher::new();
 
         // Hash basic fields
-        self.tick.hash(&mut hasher);
-        self.seed.hash(&mut hasher);
+        hasher.write_u64(self.tick);
+        hasher.write_u64(self.seed);
 
         // Hash entity generations (deterministically ordered by index)
-        self.entities.generations().hash(&mut hasher);
+        let generations = self.entities.generations();
+        hasher.write_usize(generations.len());
+        for &generation in generations {
+            hasher.write_u32(generation);
+        }
+
+        // Content-addressed slots are sparse; worlds without any hash as before
+        for (index, generation) in self.entities.content_slots() {
+            hasher.write_u64(index);
+            hasher.write_u32(generation);
+        }
 
         // Hash all component data in sorted order
         for (component, entity, value) in self.components.sorted_data() {
-            component.hash(&mut hasher);
-            entity.hash(&mut hasher);
-            value.hash(&mut hasher);
+            hasher.write_u32(component.index());
+            hasher.write_u64(entity.index);
+            hasher.write_u32(entity.generation);
+            hash_value(value, &mut hasher);
         }
 
         hasher.finish()
     }
+
+    // --- Validation ---
+
+    /// Checks the whole world for internal consistency.
+    ///
+    /// Verifies every component value against its registered schema, every
+    /// relationship against its cardinality, and every entity reference
+    /// (including relationship endpoints) against the set of live entities.
+    /// All issues are collected rather than stopping at the first one.
+    #[must_use]
+    pub fn validate(&self) -> ValidationReport {
+        crate::validation::validate_world(self)
+    }
+
+    /// Checks just the given entities for internal consistency.
+    ///
+    /// Runs the same checks as [`World::validate`] on each entity that still
+    /// exists, apart from relationship cardinality, which linking already
+    /// enforces.
+    #[must_use]
+    pub fn validate_entities(
+        &self,
+        entities: impl IntoIterator<Item = EntityId>,
+    ) -> ValidationReport {
+        crate::validation::validate_entities(self, entities)
+    }
 }
 
 impl Default for World {
@@ -1067,11 +1741,47 @@ impl Default for World {
     }
 }
 
+/// Counts the distinct copies of one store across `worlds`, returning the
+/// sharing and the copies themselves.
+fn store_sharing<'a, T>(
+    worlds: &[&'a World],
+    store: impl Fn(&'a World) -> &'a Arc<T>,
+) -> (StoreSharing, Vec<&'a T>) {
+    let mut seen = HashSet::new();
+    let mut copies = Vec::new();
+    for world in worlds {
+        let copy = store(world);
+        if seen.insert(Arc::as_ptr(copy)) {
+            copies.push(copy.as_ref());
+        }
+    }
+    let sharing = StoreSharing {
+        copies: copies.len(),
+        shared: worlds.len() - copies.len(),
+    };
+    (sharing, copies)
+}
+
+/// Sets a spawned entity's initial components from a map of component
+/// names to values.
+fn set_initial_components(
+    store: &mut ComponentStore,
+    entity: EntityId,
+    components: &LtMap<Value, Value>,
+) -> Result<()> {
+    for (key, value) in components.iter() {
+        if let Value::Keyword(comp_name) = key {
+            store.set(entity, *comp_name, value.clone())?;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::schema::{
-        Cardinality, ComponentSchema, FieldSchema, OnViolation, RelationshipSchema,
+        Cardinality, ComponentSchema, FieldSchema, OnDelete, OnViolation, RelationshipSchema,
     };
     use longtable_foundation::Type;
 
@@ -1120,6 +1830,96 @@ mod tests {
         assert_eq!(value, Some(Value::Int(100)));
     }
 
+    #[test]
+    fn batch_operations_apply_in_one_step() {
+        let mut world = setup_world();
+        let health = world.interner_mut().intern_keyword("health");
+        let current = world.interner_mut().intern_keyword("current");
+        let part_of = world.interner_mut().intern_keyword("part-of");
+        let hp = |n| Value::Map(LtMap::new().insert(Value::Keyword(current), Value::Int(n)));
+        world = world
+            .register_component(
+                ComponentSchema::new(health).with_field(FieldSchema::required(current, Type::Int)),
+            )
+            .unwrap();
+        world = world
+            .register_relationship(
+                RelationshipSchema::new(part_of).with_on_delete(OnDelete::Cascade),
+            )
+            .unwrap();
+
+        let batch: Vec<_> = (0..100)
+            .map(|i| LtMap::new().insert(Value::Keyword(health), hp(i)))
+            .collect();
+        let (world, ids) = world.spawn_batch(&batch).unwrap();
+        assert_eq!(ids.len(), 100);
+        assert_eq!(world.entity_count(), 100);
+        assert_eq!(world.get(ids[7], health).unwrap(), Some(hp(7)));
+        // The whole batch is one step back
+        assert_eq!(world.previous().unwrap().entity_count(), 0);
+
+        let writes: Vec<_> = ids.iter().map(|&id| (id, health, hp(0))).collect();
+        let world = world.set_batch(&writes).unwrap();
+        assert!(
+            ids.iter()
+                .all(|&id| world.get(id, health).unwrap() == Some(hp(0)))
+        );
+        let stale = world.destroy(ids[0]).unwrap();
+        assert!(stale.set_batch(&writes).is_err());
+
+        // Destroying a batch cascades, skipping what already went
+        let world = world.link(ids[1], part_of, ids[0]).unwrap();
+        let (world, steps) = world
+            .destroy_batch_cascading(&[ids[0], ids[1], ids[2]])
+            .unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(world.entity_count(), 97);
+        assert!(
+            world
+                .find_relationships(Some(part_of), None, None)
+                .is_empty()
+        );
+        assert!(world.destroy_batch(&[ids[3], ids[0]]).is_err());
+    }
+
+    #[test]
+    fn memory_stats_count_history_and_sharing() {
+        let mut world = setup_world();
+        let health = world.interner_mut().intern_keyword("health");
+        world = world
+            .register_component(ComponentSchema::tag(health))
+            .unwrap();
+        let (world, first) = world.spawn(&LtMap::new()).unwrap();
+        let (world, _) = world.spawn(&LtMap::new()).unwrap();
+        let world = world.set(first, health, Value::Bool(true)).unwrap();
+        let world = world.advance_tick();
+
+        let stats = world.memory_stats();
+        assert_eq!(stats.entities, 2);
+        assert_eq!(stats.component_values, 1);
+        assert_eq!(stats.history, 4);
+        // Ticking shares every store with the world before it
+        assert_eq!(stats.entity_store.copies + stats.entity_store.shared, 5);
+        assert!(stats.entity_store.shared >= 1);
+        assert_eq!(stats.interner_store.copies, 1);
+        assert_eq!(stats.retained_component_values, 1);
+        assert_eq!(
+            stats.archetypes,
+            vec![
+                ArchetypeCount {
+                    components: Vec::new(),
+                    entities: 1
+                },
+                ArchetypeCount {
+                    components: vec![health],
+                    entities: 1
+                },
+            ]
+        );
+        assert!(stats.interned_strings >= 1 && stats.interned_bytes >= "health".len());
+        assert!(stats.describe(world.interner())[0].starts_with("2 entities"));
+    }
+
     #[test]
     fn destroy_removes_entity() {
         let world = setup_world();
@@ -1169,6 +1969,28 @@ mod tests {
 
         let sources: Vec<_> = world.sources(item, contains).collect();
         assert_eq!(sources, vec![room]);
+
+        assert_eq!(world.outgoing(room), vec![(contains, item)]);
+        assert_eq!(world.incoming(item), vec![(contains, room)]);
+        assert!(world.outgoing(item).is_empty());
+    }
+
+    #[test]
+    fn components_of_lists_values() {
+        let mut world = setup_world();
+        let health = world.interner_mut().intern_keyword("health");
+        let current = world.interner_mut().intern_keyword("current");
+        let schema =
+            ComponentSchema::new(health).with_field(FieldSchema::required(current, Type::Int));
+        world = world.register_component(schema).unwrap();
+
+        let data = LtMap::new().insert(Value::Keyword(current), Value::Int(7));
+        let components = LtMap::new().insert(Value::Keyword(health), Value::Map(data.clone()));
+        let (world, entity) = world.spawn(&components).unwrap();
+
+        let listed: Vec<_> = world.components_of(entity).collect();
+        assert_eq!(listed, vec![(health, Value::Map(data))]);
+        assert_eq!(world.components_of(EntityId::new(99, 0)).count(), 0);
     }
 
     #[test]
@@ -1388,6 +2210,83 @@ mod tests {
 
     // --- Cardinality Enforcement Tests ---
 
+    #[test]
+    fn create_relationship_acyclic_rejects_containment_cycles() {
+        let mut world = setup_world();
+
+        let contained_in = world.interner_mut().intern_keyword("contained-in");
+        world = world
+            .register_relationship(
+                RelationshipSchema::new(contained_in)
+                    .with_cardinality(Cardinality::ManyToOne)
+                    .with_on_violation(OnViolation::Replace)
+                    .with_acyclic(true),
+            )
+            .unwrap();
+
+        let (world, bag) = world.spawn(&LtMap::new()).unwrap();
+        let (world, pouch) = world.spawn(&LtMap::new()).unwrap();
+        let (world, coin) = world.spawn(&LtMap::new()).unwrap();
+
+        let world = world.link(coin, contained_in, pouch).unwrap();
+        let world = world.link(pouch, contained_in, bag).unwrap();
+
+        let err = world.link(bag, contained_in, coin).unwrap_err().to_string();
+        assert!(err.contains(":contained-in"));
+        assert!(err.contains("inside itself"));
+        assert!(world.link(bag, contained_in, bag).is_err());
+
+        // Moving the pouch out of the bag is fine
+        let (world, table) = world.spawn(&LtMap::new()).unwrap();
+        assert!(world.link(pouch, contained_in, table).is_ok());
+    }
+
+    #[test]
+    fn relationship_stats_report_fan_out_and_lookups() {
+        let mut world = setup_world();
+
+        let contains = world.interner_mut().intern_keyword("contains");
+        world = world
+            .register_relationship(RelationshipSchema::new(contains))
+            .unwrap();
+
+        let (mut world, chest) = world.spawn(&LtMap::new()).unwrap();
+        let (w, bag) = world.spawn(&LtMap::new()).unwrap();
+        world = w;
+        for owner in [chest, chest, chest, bag] {
+            let (w, item) = world.spawn(&LtMap::new()).unwrap();
+            world = w.link(owner, contains, item).unwrap();
+        }
+
+        let ((), lookups) = crate::relationship::count_lookups(|| {
+            let _ = world.targets(chest, contains).count();
+            let _ = world.find_relationships(Some(contains), None, None);
+        });
+
+        let stats = world.relationship_stats(&lookups);
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].fan_out.edges, 4);
+        assert_eq!(stats[0].fan_out.sources, 2);
+        assert_eq!(stats[0].fan_out.max, 3);
+        assert_eq!(stats[0].fan_out.histogram, [1, 1, 0, 0, 0]);
+        // Each lookup visits all four edges, with or without a source
+        assert_eq!(
+            stats[0].lookups,
+            LookupCounts {
+                lookups: 2,
+                narrowed: 1,
+                visited: 8,
+                matched: 7,
+            }
+        );
+        assert_eq!(world.fan_out(contains), stats[0].fan_out);
+        assert!(
+            stats[0]
+                .describe(world.interner())
+                .starts_with(":contains: 4 edges from 2 sources (mean 2.0, max 3)")
+        );
+    }
+
     #[test]
     fn create_relationship_one_to_one_errors_on_duplicate_source() {
         let mut world = setup_world();
@@ -1751,4 +2650,107 @@ mod tests {
         assert!(!world.exists(rel2));
         assert_eq!(world.entity_count(), 2); // only room and item remain
     }
+
+    #[test]
+    fn destroy_applies_on_target_delete_policies() {
+        let mut world = setup_world();
+        let relationship = |world: &mut World, name: &str, on_delete| {
+            let id = world.interner_mut().intern_keyword(name);
+            *world = world
+                .register_relationship(
+                    RelationshipSchema::new(id)
+                        .with_cardinality(Cardinality::ManyToOne)
+                        .with_on_delete(on_delete),
+                )
+                .unwrap();
+            id
+        };
+        let in_room = relationship(&mut world, "in-room", OnDelete::Remove);
+        let part_of = relationship(&mut world, "part-of", OnDelete::Cascade);
+        let owned_by = relationship(&mut world, "owned-by", OnDelete::Nullify);
+
+        let (world, room) = world.spawn(&LtMap::new()).unwrap();
+        let (world, chest) = world.spawn(&LtMap::new()).unwrap();
+        let (world, lid) = world.spawn(&LtMap::new()).unwrap();
+        let (world, hinge) = world.spawn(&LtMap::new()).unwrap();
+        let (world, owner) = world.spawn(&LtMap::new()).unwrap();
+        let world = world.link(chest, in_room, room).unwrap();
+        let world = world.link(lid, part_of, chest).unwrap();
+        let world = world.link(hinge, part_of, lid).unwrap();
+        let (world, ownership) = world.create_relationship(owned_by, chest, owner).unwrap();
+        let world = world.link(lid, owned_by, owner).unwrap();
+
+        // Destroying the room only unlinks the chest
+        let (after, steps) = world.destroy_cascading(room).unwrap();
+        assert!(after.exists(chest));
+        assert_eq!(after.targets(chest, in_room).count(), 0);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].action, OnDelete::Remove);
+
+        // Destroying the chest takes its parts with it, recursively
+        let (after, steps) = world.destroy_cascading(chest).unwrap();
+        assert!(!after.exists(lid) && !after.exists(hinge));
+        let cascaded: Vec<_> = steps
+            .iter()
+            .filter(|s| s.action == OnDelete::Cascade)
+            .map(|s| (s.destroyed, s.source))
+            .collect();
+        assert_eq!(cascaded, vec![(chest, lid), (lid, hinge)]);
+        // No relationship entities are left behind
+        assert_eq!(after.find_relationships(None, None, None).len(), 0);
+        assert!(after.validate().is_valid());
+
+        // Destroying the owner keeps a nil link on what it owned
+        let (after, steps) = world.destroy_cascading(owner).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert!(after.exists(ownership));
+        assert_eq!(after.outgoing(chest), vec![(in_room, room)]);
+        assert!(!after.has_outgoing(chest, owned_by));
+        let report = after.validate();
+        assert!(report.is_valid(), "{report:?}");
+
+        // A new link takes the nullified one's place, and the source's
+        // destruction takes it away
+        let (after, new_owner) = after.spawn(&LtMap::new()).unwrap();
+        let relinked = after.link(chest, owned_by, new_owner).unwrap();
+        assert!(!relinked.exists(ownership));
+        let after = after.destroy(chest).unwrap();
+        assert!(!after.exists(ownership));
+    }
+
+    #[test]
+    fn content_hash_ignores_construction_history() {
+        let build = |reverse: bool| {
+            let mut world = World::new(42);
+            let health = world.interner_mut().intern_keyword("health");
+            let fields: Vec<KeywordId> = (0..16)
+                .map(|i| world.interner_mut().intern_keyword(&format!("field-{i}")))
+                .collect();
+            world = world
+                .register_component(ComponentSchema::new(health))
+                .unwrap();
+
+            let mut entries: Vec<(Value, Value)> = fields
+                .iter()
+                .zip(0..)
+                .map(|(&field, i)| (Value::Keyword(field), Value::Int(i)))
+                .collect();
+            if reverse {
+                entries.reverse();
+            }
+            let value = Value::Map(entries.into_iter().collect());
+            let (world, _) = world
+                .spawn(&LtMap::new().insert(Value::Keyword(health), value))
+                .unwrap();
+            world
+        };
+
+        let forward = build(false);
+        let backward = build(true);
+        assert_eq!(forward.content_hash(), backward.content_hash());
+        assert_ne!(
+            forward.content_hash(),
+            forward.advance_tick().content_hash()
+        );
+    }
 }
@@ -30,6 +30,9 @@ fn make_constraint_decl(
             span: Span::default(),
         }],
         negations: vec![],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     };
     decl.on_violation = violation;
     decl
@@ -132,6 +135,9 @@ fn compile_constraint_with_multiple_clauses() {
             },
         ],
         negations: vec![],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     };
     decl.on_violation = ConstraintViolation::Rollback;
 
@@ -164,7 +170,7 @@ fn check_all_empty_world() {
     let world = World::new(42);
     let checker = ConstraintChecker::new();
 
-    let result = checker.check_all(&world);
+    let result = checker.check_all(&world).unwrap();
     assert!(result.is_ok());
 }
 
@@ -180,7 +186,7 @@ fn check_all_no_constraints() {
     let _world = world.set(entity, health_kw, Value::Bool(true)).unwrap();
 
     let checker = ConstraintChecker::new();
-    let result = checker.check_all(&_world);
+    let result = checker.check_all(&_world).unwrap();
 
     // No constraints = always passes
     assert!(result.is_ok());
@@ -201,7 +207,7 @@ fn check_all_with_constraints_no_matches() {
     let compiled = ConstraintCompiler::compile(&decl, &mut interner).unwrap();
 
     let checker = ConstraintChecker::new().with_constraints(vec![compiled]);
-    let result = checker.check_all(&world);
+    let result = checker.check_all(&world).unwrap();
 
     // No matches = passes (constraint only applies to matching entities)
     assert!(result.is_ok());
@@ -20,6 +20,9 @@ fn create_component_pattern(
             binding: CompiledBinding::Wildcard,
         }],
         negations: vec![],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     }
 }
 
@@ -39,7 +42,7 @@ fn pattern_matches_entity_with_component() {
     let world = world.set(player, player_kw, Value::Bool(true)).unwrap();
 
     let pattern = create_component_pattern("?e", player_kw);
-    let results = PatternMatcher::match_pattern(&pattern, &world);
+    let results = PatternMatcher::match_pattern(&pattern, &world).unwrap();
 
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].get_entity("?e"), Some(player));
@@ -61,7 +64,7 @@ fn pattern_matches_multiple_entities() {
     let world = world.set(e3, enemy_kw, Value::Bool(true)).unwrap();
 
     let pattern = create_component_pattern("?e", enemy_kw);
-    let results = PatternMatcher::match_pattern(&pattern, &world);
+    let results = PatternMatcher::match_pattern(&pattern, &world).unwrap();
 
     assert_eq!(results.len(), 3);
 }
@@ -85,7 +88,7 @@ fn pattern_no_match_when_component_missing() {
 
     // Pattern looks for enemy tag
     let pattern = create_component_pattern("?e", enemy_kw);
-    let results = PatternMatcher::match_pattern(&pattern, &world);
+    let results = PatternMatcher::match_pattern(&pattern, &world).unwrap();
 
     assert_eq!(results.len(), 0);
 }
@@ -131,9 +134,12 @@ fn pattern_with_multiple_components() {
             },
         ],
         negations: vec![],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     };
 
-    let results = PatternMatcher::match_pattern(&pattern, &world);
+    let results = PatternMatcher::match_pattern(&pattern, &world).unwrap();
 
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].get_entity("?e"), Some(e1));
@@ -161,9 +167,12 @@ fn pattern_binds_value() {
             binding: CompiledBinding::Variable("?active".to_string()),
         }],
         negations: vec![],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     };
 
-    let results = PatternMatcher::match_pattern(&pattern, &world);
+    let results = PatternMatcher::match_pattern(&pattern, &world).unwrap();
 
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].get("?active"), Some(&Value::Bool(true)));
@@ -196,9 +205,12 @@ fn pattern_literal_matching() {
             binding: CompiledBinding::Literal(Value::Bool(true)),
         }],
         negations: vec![],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     };
 
-    let results = PatternMatcher::match_pattern(&pattern, &world);
+    let results = PatternMatcher::match_pattern(&pattern, &world).unwrap();
 
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].get_entity("?e"), Some(e1));
@@ -242,9 +254,12 @@ fn pattern_negation() {
             component: dead_kw,
             binding: CompiledBinding::Wildcard,
         }],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     };
 
-    let results = PatternMatcher::match_pattern(&pattern, &world);
+    let results = PatternMatcher::match_pattern(&pattern, &world).unwrap();
 
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].get_entity("?e"), Some(alive));
@@ -296,7 +311,7 @@ fn pattern_on_empty_world() {
         .unwrap();
 
     let pattern = create_component_pattern("?e", player_kw);
-    let results = PatternMatcher::match_pattern(&pattern, &world);
+    let results = PatternMatcher::match_pattern(&pattern, &world).unwrap();
 
     assert_eq!(results.len(), 0);
 }
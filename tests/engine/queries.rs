@@ -17,6 +17,9 @@ fn simple_query(entity_var: &str, component: longtable_foundation::KeywordId) ->
                 binding: CompiledBinding::Wildcard,
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         },
         bindings: vec![],
         aggregates: vec![],
@@ -27,6 +30,8 @@ fn simple_query(entity_var: &str, component: longtable_foundation::KeywordId) ->
         return_expr: None,
         binding_vars: vec![entity_var.to_string()],
         warnings: vec![],
+        suppress: vec![],
+        plan: None,
     }
 }
 
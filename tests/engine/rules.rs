@@ -21,6 +21,9 @@ fn simple_rule(
                 binding: CompiledBinding::Wildcard,
             }],
             negations: vec![],
+            not_joins: vec![],
+            disjunctions: vec![],
+            predicates: vec![],
         },
     )
 }
@@ -46,7 +49,7 @@ fn find_activations_single_rule() {
 
     let mut engine = ProductionRuleEngine::new();
     engine.begin_tick();
-    let activations = engine.find_activations(&rules, &world);
+    let activations = engine.find_activations(&rules, &world).unwrap();
 
     assert_eq!(activations.len(), 1);
     assert_eq!(activations[0].rule_name, rule_kw);
@@ -72,7 +75,7 @@ fn find_activations_multiple_matches() {
 
     let mut engine = ProductionRuleEngine::new();
     engine.begin_tick();
-    let activations = engine.find_activations(&rules, &world);
+    let activations = engine.find_activations(&rules, &world).unwrap();
 
     assert_eq!(activations.len(), 2);
 }
@@ -99,7 +102,7 @@ fn find_activations_no_match() {
 
     let mut engine = ProductionRuleEngine::new();
     engine.begin_tick();
-    let activations = engine.find_activations(&rules, &world);
+    let activations = engine.find_activations(&rules, &world).unwrap();
 
     assert_eq!(activations.len(), 0);
 }
@@ -129,7 +132,7 @@ fn activations_sorted_by_salience() {
 
     let mut engine = ProductionRuleEngine::new();
     engine.begin_tick();
-    let activations = engine.find_activations(&rules, &world);
+    let activations = engine.find_activations(&rules, &world).unwrap();
 
     assert_eq!(activations.len(), 2);
     // High salience should be first
@@ -163,7 +166,7 @@ fn once_rule_prevents_refire() {
     engine.begin_tick();
 
     // First call finds all matching entities
-    let activations = engine.find_activations(&rules, &world);
+    let activations = engine.find_activations(&rules, &world).unwrap();
     assert_eq!(activations.len(), 2);
 
     // Fire one activation - this marks the rule as "once_fired"
@@ -172,7 +175,7 @@ fn once_rule_prevents_refire() {
     });
 
     // After firing, once rule should not return any more activations
-    let activations_after = engine.find_activations(&rules, &world);
+    let activations_after = engine.find_activations(&rules, &world).unwrap();
     assert_eq!(activations_after.len(), 0);
 }
 
@@ -199,12 +202,12 @@ fn begin_tick_resets_state() {
 
     // First tick
     engine.begin_tick();
-    let act1 = engine.find_activations(&rules, &world);
+    let act1 = engine.find_activations(&rules, &world).unwrap();
     assert_eq!(act1.len(), 1);
 
     // Second tick - should find activations again after reset
     engine.begin_tick();
-    let act2 = engine.find_activations(&rules, &world);
+    let act2 = engine.find_activations(&rules, &world).unwrap();
     assert_eq!(act2.len(), 1);
 }
 
@@ -227,6 +230,7 @@ fn max_activations_kill_switch() {
         rule_name: test_kw,
         bindings: Bindings::new(),
         salience: 0,
+        rank: 0,
         specificity: 0,
     };
 
@@ -597,7 +597,7 @@ fn movement_rule_marks_command_processed() {
     engine.begin_tick();
 
     let (result_world, _) = engine
-        .run_to_quiescence(&[move_rule.clone()], world)
+        .run_to_quiescence(std::slice::from_ref(&move_rule), world)
         .unwrap();
 
     // Command should now be processed
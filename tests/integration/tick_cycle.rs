@@ -192,6 +192,9 @@ fn tick_with_rules_fires_activations() {
             binding: CompiledBinding::Wildcard,
         }],
         negations: vec![],
+        not_joins: vec![],
+        disjunctions: vec![],
+        predicates: vec![],
     };
 
     let rule = CompiledRule::new(rule_kw, pattern);
@@ -233,22 +236,32 @@ fn tick_tracks_input_provenance() {
 }
 
 // =============================================================================
-// Custom Events (pass-through)
+// Custom Events
 // =============================================================================
 
 #[test]
-fn tick_custom_events_pass_through() {
+fn tick_custom_events_are_drained() {
     let mut world = World::new(42);
     let event_kw = world.interner_mut().intern_keyword("events/test");
-
-    // Custom events don't modify world state at this level
-    let inputs = vec![InputEvent::Custom {
-        name: event_kw,
-        payload: Value::Int(42),
-    }];
+    let entity_count = world.entity_count();
+
+    // Custom inputs become event entities for the duration of the tick,
+    // whatever their payload
+    let inputs = vec![
+        InputEvent::Custom {
+            name: event_kw,
+            payload: Value::Map(LtMap::new()),
+        },
+        InputEvent::Custom {
+            name: event_kw,
+            payload: Value::Int(42),
+        },
+    ];
 
     let mut executor = TickExecutor::new();
     let result = executor.tick(world, &inputs).unwrap();
 
     assert!(result.is_ok());
+    assert_eq!(result.events_drained, 2);
+    assert_eq!(result.world.entity_count(), entity_count);
 }
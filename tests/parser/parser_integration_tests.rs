@@ -180,7 +180,10 @@ fn scope_kind_variants_exist() {
         require_transparent: false,
     };
     let _union = ScopeKind::Union(vec![]);
-    let _custom = ScopeKind::Custom;
+    let _pattern = ScopeKind::Pattern {
+        extends: vec![],
+        pattern: Box::default(),
+    };
 }
 
 #[test]